@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use sctp::association::Association;
+use sctp::congestion::CongestionControlAlgorithm;
+use sctp::stream::StreamScheduler;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use util::conn::conn_bridge::*;
+use util::conn::*;
+
+use super::*;
+use crate::data_channel::Config as DataChannelConfig;
+use crate::error::Result;
+
+fn association_config(
+    net_conn: Arc<dyn Conn + Send + Sync>,
+    name: &str,
+) -> sctp::association::Config {
+    sctp::association::Config {
+        net_conn,
+        max_receive_buffer_size: 0,
+        max_message_size: 0,
+        name: name.to_owned(),
+        stream_scheduler: StreamScheduler::default(),
+        congestion_control: CongestionControlAlgorithm::default(),
+        sack_delay: Duration::ZERO,
+        packets_per_sack: 0,
+        immediate_sack: false,
+        zero_checksum: false,
+        max_num_outbound_streams: 0,
+        max_num_inbound_streams: 0,
+        heartbeat_interval: Duration::ZERO,
+        path_max_retransmits: 0,
+        association_max_retransmits: 0,
+        ecn: false,
+        max_send_buffer_size: 0,
+    }
+}
+
+fn start_ticker(br: &Arc<Bridge>) -> JoinHandle<()> {
+    let br = Arc::clone(br);
+    tokio::spawn(async move {
+        loop {
+            br.tick().await;
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_broadcast() -> Result<()> {
+    let (br, ca, cb) = Bridge::new(0, None, None);
+    let ticker = start_ticker(&br);
+    let (a0, a1) = tokio::try_join!(
+        Association::client(association_config(Arc::new(ca), "client")),
+        Association::server(association_config(Arc::new(cb), "server")),
+    )?;
+    let (a0, a1) = (Arc::new(a0), Arc::new(a1));
+
+    let mut senders = vec![];
+    let mut receivers: Vec<DataChannel> = vec![];
+    for id in 1..=3 {
+        let cfg = DataChannelConfig {
+            label: format!("subscriber {id}"),
+            ..Default::default()
+        };
+        senders.push(Arc::new(DataChannel::dial(&a0, id, cfg).await?));
+        let dc = DataChannel::accept(&a1, DataChannelConfig::default(), &receivers).await?;
+        receivers.push(dc);
+    }
+    ticker.abort();
+    let _ = ticker.await;
+
+    // Nothing is acknowledged while the bridge isn't ticked, so the second subscriber stays
+    // backpressured after the first message.
+    senders[1].set_send_buffer_watermarks(1, 0);
+    senders[2].close().await?;
+
+    let first = Bytes::from_static(b"tick 1");
+    let deliveries = broadcast(&senders, &first, Config::default()).await;
+    assert_eq!(
+        deliveries,
+        vec![
+            Delivery::Queued { buffered_amount: 6 },
+            Delivery::Queued { buffered_amount: 6 },
+            Delivery::Failed(Error::Sctp(sctp::Error::ErrStreamClosed)),
+        ]
+    );
+
+    let second = Bytes::from_static(b"tick 2");
+    let deliveries = broadcast(&senders[..2], &second, Config::default()).await;
+    assert_eq!(
+        deliveries,
+        vec![
+            Delivery::Queued {
+                buffered_amount: 12
+            },
+            Delivery::Backpressured { buffered_amount: 6 },
+        ]
+    );
+    assert!(deliveries[0].is_queued());
+    assert!(!deliveries[1].is_queued());
+
+    let ticker = start_ticker(&br);
+    let mut buf = vec![0u8; 64];
+    for expected in [&first, &second] {
+        let n = receivers[0].read(&mut buf).await?;
+        assert_eq!(&buf[..n], &expected[..]);
+    }
+    let n = receivers[1].read(&mut buf).await?;
+    assert_eq!(&buf[..n], &first[..]);
+
+    a0.close().await?;
+    a1.close().await?;
+    ticker.abort();
+    Ok(())
+}
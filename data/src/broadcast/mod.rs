@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod broadcast_test;
+
+use std::borrow::Borrow;
+
+use bytes::Bytes;
+use sctp::stream::WriteOptions;
+
+use crate::data_channel::DataChannel;
+use crate::error::Error;
+
+/// Config is used to configure a [`broadcast`].
+#[derive(Default, Debug, Copy, Clone)]
+pub struct Config {
+    /// Whether the message is sent as a string.
+    pub is_string: bool,
+    /// How the message is sent, see [`DataChannel::write_data_channel_with_options`].
+    pub options: WriteOptions,
+}
+
+/// Delivery reports what became of a broadcast message for one of the data channels.
+#[derive(Debug, PartialEq)]
+pub enum Delivery {
+    /// The message was queued for sending, and buffered_amount bytes are buffered now.
+    Queued { buffered_amount: usize },
+    /// The message was dropped, as the send buffer of the data channel is full, see
+    /// [`DataChannel::is_writable`].
+    Backpressured { buffered_amount: usize },
+    /// Writing the message failed, e.g. as the data channel is closed.
+    Failed(Error),
+}
+
+impl Delivery {
+    /// is_queued returns true if the message was queued for sending.
+    pub fn is_queued(&self) -> bool {
+        matches!(self, Delivery::Queued { .. })
+    }
+}
+
+/// broadcast queues data for sending on all of data_channels, without copying it for each of
+/// them, and returns the delivery for each data channel in the same order. Data channels
+/// whose send buffer is full don't get the message, so a single slow recipient doesn't make
+/// the buffered data of all grow.
+pub async fn broadcast<T>(data_channels: &[T], data: &Bytes, config: Config) -> Vec<Delivery>
+where
+    T: Borrow<DataChannel>,
+{
+    let mut deliveries = Vec::with_capacity(data_channels.len());
+    for data_channel in data_channels {
+        let data_channel = data_channel.borrow();
+        match data_channel.is_writable() {
+            Ok(true) => {}
+            Ok(false) => {
+                deliveries.push(Delivery::Backpressured {
+                    buffered_amount: data_channel.buffered_amount(),
+                });
+                continue;
+            }
+            Err(err) => {
+                deliveries.push(Delivery::Failed(err));
+                continue;
+            }
+        }
+
+        // The chunks queued for each data channel are slices of data, not copies.
+        let delivery = match data_channel
+            .write_data_channel_with_options(data, config.is_string, config.options)
+            .await
+        {
+            Ok(_) => Delivery::Queued {
+                buffered_amount: data_channel.buffered_amount(),
+            },
+            // The send buffer of the association is full.
+            Err(Error::Sctp(sctp::Error::ErrSendBufferFull)) => Delivery::Backpressured {
+                buffered_amount: data_channel.buffered_amount(),
+            },
+            Err(err) => Delivery::Failed(err),
+        };
+        deliveries.push(delivery);
+    }
+    deliveries
+}
@@ -1,3 +1,4 @@
+use sctp::congestion::CongestionControlAlgorithm;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::sync::{broadcast, mpsc};
 use tokio::time::Duration;
@@ -35,6 +36,19 @@ async fn create_new_association_pair(
             max_receive_buffer_size: 0,
             max_message_size: 0,
             name: "client".to_owned(),
+            stream_scheduler: StreamScheduler::default(),
+            congestion_control: CongestionControlAlgorithm::default(),
+            sack_delay: Duration::ZERO,
+            packets_per_sack: 0,
+            immediate_sack: false,
+            zero_checksum: false,
+            max_num_outbound_streams: 0,
+            max_num_inbound_streams: 0,
+            heartbeat_interval: Duration::ZERO,
+            path_max_retransmits: 0,
+            association_max_retransmits: 0,
+            ecn: false,
+            max_send_buffer_size: 0,
         })
         .await;
 
@@ -51,6 +65,19 @@ async fn create_new_association_pair(
             max_receive_buffer_size: 0,
             max_message_size: 0,
             name: "server".to_owned(),
+            stream_scheduler: StreamScheduler::default(),
+            congestion_control: CongestionControlAlgorithm::default(),
+            sack_delay: Duration::ZERO,
+            packets_per_sack: 0,
+            immediate_sack: false,
+            zero_checksum: false,
+            max_num_outbound_streams: 0,
+            max_num_inbound_streams: 0,
+            heartbeat_interval: Duration::ZERO,
+            path_max_retransmits: 0,
+            association_max_retransmits: 0,
+            ecn: false,
+            max_send_buffer_size: 0,
         })
         .await;
 
@@ -412,8 +439,181 @@ async fn test_data_channel_channel_type_partial_reliable_timed_unordered() -> Re
     pr_ordered_unordered_test(ChannelType::PartialReliableTimedUnordered, false).await
 }
 
+#[cfg(not(target_os = "windows"))] // this times out in CI on windows.
+#[tokio::test]
+async fn test_data_channel_partial_reliable_message() -> Result<()> {
+    let mut sbuf = vec![0u8; 1000];
+    let mut rbuf = vec![0u8; 2000];
+
+    let (br, ca, cb) = Bridge::new(0, None, None);
+
+    let (a0, a1) = create_new_association_pair(&br, Arc::new(ca), Arc::new(cb)).await?;
+
+    let cfg = Config {
+        channel_type: ChannelType::Reliable,
+        label: "data".to_string(),
+        ..Default::default()
+    };
+
+    let dc0 = DataChannel::dial(&a0, 100, cfg.clone()).await?;
+    bridge_process_at_least_one(&br).await;
+
+    let existing_data_channels: Vec<DataChannel> = Vec::new();
+    let dc1 = DataChannel::accept(&a1, Config::default(), &existing_data_channels).await?;
+    bridge_process_at_least_one(&br).await;
+
+    dc0.commit_reliability_params();
+    dc1.commit_reliability_params();
+
+    // Only the first message may be abandoned, the channel stays reliable.
+    sbuf[0..4].copy_from_slice(&1u32.to_be_bytes());
+    let n = dc0
+        .write_data_channel_with_reliability(
+            &Bytes::from(sbuf.clone()),
+            true,
+            ReliabilityType::Rexmit,
+            0,
+        )
+        .await?;
+    assert_eq!(sbuf.len(), n, "data length should match");
+
+    sbuf[0..4].copy_from_slice(&2u32.to_be_bytes());
+    let n = dc0
+        .write_data_channel(&Bytes::from(sbuf.clone()), true)
+        .await?;
+    assert_eq!(sbuf.len(), n, "data length should match");
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    br.drop_offset(0, 0, 1).await; // drop the first packet on the wire
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    bridge_process_at_least_one(&br).await;
+
+    let (n, is_string) = dc1.read_data_channel(&mut rbuf[..]).await?;
+    assert!(is_string, "should return isString being true");
+    assert_eq!(sbuf.len(), n, "data length should match");
+    assert_eq!(
+        2,
+        u32::from_be_bytes([rbuf[0], rbuf[1], rbuf[2], rbuf[3]]),
+        "data should match"
+    );
+
+    dc0.close().await?;
+    dc1.close().await?;
+    bridge_process_at_least_one(&br).await;
+
+    close_association_pair(&br, a0, a1).await;
+
+    Ok(())
+}
+
 //TODO: remove this conditional test
 #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+#[tokio::test]
+async fn test_data_channel_unordered_message() -> Result<()> {
+    let mut sbuf = vec![0u8; 1000];
+    let mut rbuf = vec![0u8; 2000];
+
+    let (br, ca, cb) = Bridge::new(0, None, None);
+
+    let (a0, a1) = create_new_association_pair(&br, Arc::new(ca), Arc::new(cb)).await?;
+
+    let cfg = Config {
+        channel_type: ChannelType::Reliable,
+        label: "data".to_string(),
+        ..Default::default()
+    };
+
+    let dc0 = DataChannel::dial(&a0, 100, cfg.clone()).await?;
+    bridge_process_at_least_one(&br).await;
+
+    let existing_data_channels: Vec<DataChannel> = Vec::new();
+    let dc1 = DataChannel::accept(&a1, Config::default(), &existing_data_channels).await?;
+    bridge_process_at_least_one(&br).await;
+
+    dc0.commit_reliability_params();
+    dc1.commit_reliability_params();
+
+    // Only the second message is unordered, the channel stays ordered.
+    sbuf[0..4].copy_from_slice(&1u32.to_be_bytes());
+    let n = dc0
+        .write_data_channel(&Bytes::from(sbuf.clone()), true)
+        .await?;
+    assert_eq!(sbuf.len(), n, "data length should match");
+
+    sbuf[0..4].copy_from_slice(&2u32.to_be_bytes());
+    let options = WriteOptions {
+        unordered: true,
+        ..Default::default()
+    };
+    let n = dc0
+        .write_data_channel_with_options(&Bytes::from(sbuf.clone()), true, options)
+        .await?;
+    assert_eq!(sbuf.len(), n, "data length should match");
+
+    // The unordered message overtakes the ordered one.
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    bridge_process_at_least_one(&br).await;
+
+    for expected in [2u32, 1] {
+        let (n, is_string) = dc1.read_data_channel(&mut rbuf[..]).await?;
+        assert!(is_string, "should return isString being true");
+        assert_eq!(sbuf.len(), n, "data length should match");
+        assert_eq!(
+            expected,
+            u32::from_be_bytes([rbuf[0], rbuf[1], rbuf[2], rbuf[3]]),
+            "data should match"
+        );
+    }
+
+    dc0.close().await?;
+    dc1.close().await?;
+    bridge_process_at_least_one(&br).await;
+
+    close_association_pair(&br, a0, a1).await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_data_channel_unordered_message_before_ack() -> Result<()> {
+    let mut rbuf = vec![0u8; 2000];
+
+    let (br, ca, cb) = Bridge::new(0, None, None);
+
+    let (a0, a1) = create_new_association_pair(&br, Arc::new(ca), Arc::new(cb)).await?;
+
+    let cfg = Config {
+        channel_type: ChannelType::Reliable,
+        label: "data".to_string(),
+        ..Default::default()
+    };
+
+    // The message must not overtake the DATA_CHANNEL_OPEN message.
+    let dc0 = DataChannel::dial(&a0, 100, cfg.clone()).await?;
+    let options = WriteOptions {
+        unordered: true,
+        ..Default::default()
+    };
+    dc0.write_data_channel_with_options(&Bytes::from_static(b"early"), false, options)
+        .await?;
+    bridge_process_at_least_one(&br).await;
+
+    let existing_data_channels: Vec<DataChannel> = Vec::new();
+    let dc1 = DataChannel::accept(&a1, Config::default(), &existing_data_channels).await?;
+    bridge_process_at_least_one(&br).await;
+
+    let n = dc1.read(&mut rbuf[..]).await?;
+    assert_eq!(&rbuf[..n], b"early", "data should match");
+
+    dc0.close().await?;
+    dc1.close().await?;
+    bridge_process_at_least_one(&br).await;
+
+    close_association_pair(&br, a0, a1).await;
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_data_channel_buffered_amount() -> Result<()> {
     let sbuf = vec![0u8; 1000];
@@ -668,3 +868,234 @@ async fn test_poll_data_channel() -> Result<()> {
 
     Ok(())
 }
+
+async fn create_poll_data_channel_pair(
+    br: &Arc<Bridge>,
+    a0: &Arc<Association>,
+    a1: &Arc<Association>,
+) -> Result<(PollDataChannel, PollDataChannel)> {
+    let cfg = Config {
+        channel_type: ChannelType::Reliable,
+        label: "data".to_string(),
+        ..Default::default()
+    };
+
+    let dc0 = Arc::new(DataChannel::dial(a0, 100, cfg).await?);
+    bridge_process_at_least_one(br).await;
+
+    let existing_data_channels: Vec<DataChannel> = Vec::new();
+    let dc1 = Arc::new(DataChannel::accept(a1, Config::default(), &existing_data_channels).await?);
+    bridge_process_at_least_one(br).await;
+
+    Ok((PollDataChannel::new(dc0), PollDataChannel::new(dc1)))
+}
+
+#[tokio::test]
+async fn test_poll_data_channel_message_per_frame() -> Result<()> {
+    let mut rbuf = vec![0u8; 1500];
+
+    let (br, ca, cb) = Bridge::new(0, None, None);
+    let (a0, a1) = create_new_association_pair(&br, Arc::new(ca), Arc::new(cb)).await?;
+    let (mut poll_dc0, mut poll_dc1) = create_poll_data_channel_pair(&br, &a0, &a1).await?;
+    poll_dc0.set_framing(Framing::MessagePerFrame);
+    poll_dc1.set_framing(Framing::MessagePerFrame);
+
+    for m in [&b"hello"[..], &b""[..], &b"world!"[..]] {
+        poll_dc0
+            .write_all(m)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+        poll_dc0
+            .flush()
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+    }
+    bridge_process_at_least_one(&br).await;
+
+    let n = poll_dc1
+        .read(&mut rbuf[..])
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+    assert_eq!(&rbuf[..n], b"hello", "message boundaries should be kept");
+
+    let err = poll_dc1
+        .read(&mut rbuf[..3])
+        .await
+        .expect_err("a message should not be split");
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+    let n = poll_dc1
+        .read(&mut rbuf[..])
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+    assert_eq!(
+        &rbuf[..n],
+        b"world!",
+        "the message should be kept after an error"
+    );
+    assert!(!poll_dc1.last_read_is_string());
+
+    poll_dc0.into_inner().close().await?;
+    poll_dc1.into_inner().close().await?;
+    bridge_process_at_least_one(&br).await;
+
+    close_association_pair(&br, a0, a1).await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_poll_data_channel_length_prefixed() -> Result<()> {
+    let (br, ca, cb) = Bridge::new(0, None, None);
+    let (a0, a1) = create_new_association_pair(&br, Arc::new(ca), Arc::new(cb)).await?;
+    let (mut poll_dc0, mut poll_dc1) = create_poll_data_channel_pair(&br, &a0, &a1).await?;
+    poll_dc0.set_framing(Framing::LengthPrefixed);
+    poll_dc0.set_write_text(true);
+    poll_dc0.set_max_frame_len(16);
+    poll_dc1.set_framing(Framing::LengthPrefixed);
+
+    // Two frames, written across their boundaries.
+    let mut stream = vec![];
+    for m in [&b"first frame"[..], &b"second"[..]] {
+        stream.extend_from_slice(&(m.len() as u32).to_be_bytes());
+        stream.extend_from_slice(m);
+    }
+    for part in [&stream[..6], &stream[6..17], &stream[17..]] {
+        poll_dc0
+            .write_all(part)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+    }
+    poll_dc0
+        .flush()
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+    bridge_process_at_least_one(&br).await;
+
+    let dc1 = poll_dc1.clone_inner();
+    assert_eq!(dc1.messages_received(), 0);
+
+    let mut rbuf = vec![0u8; stream.len()];
+    poll_dc1
+        .read_exact(&mut rbuf)
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+    assert_eq!(rbuf, stream, "frames should be reassembled");
+    assert!(
+        poll_dc1.last_read_is_string(),
+        "frames should be sent as text"
+    );
+    assert_eq!(
+        dc1.messages_received(),
+        2,
+        "each frame should be one message"
+    );
+
+    let mut frame = 17u32.to_be_bytes().to_vec();
+    frame.resize(4 + 17, b'a');
+    let err = poll_dc0
+        .write(&frame)
+        .await
+        .expect_err("a frame longer than the maximum should fail");
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let mut frame = 2u32.to_be_bytes().to_vec();
+    frame.extend_from_slice(&[0xff, 0xfe]);
+    let err = poll_dc0
+        .write(&frame)
+        .await
+        .expect_err("a text frame should be valid UTF-8");
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    poll_dc0.into_inner().close().await?;
+    dc1.close().await?;
+    bridge_process_at_least_one(&br).await;
+
+    close_association_pair(&br, a0, a1).await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_data_channel_accept_with_filter() -> Result<()> {
+    let (br, ca, cb) = Bridge::new(0, None, None);
+    let (a0, a1) = create_new_association_pair(&br, Arc::new(ca), Arc::new(cb)).await?;
+
+    let label_cfg = |label: &str| Config {
+        label: label.to_string(),
+        protocol: "proto".to_string(),
+        ..Default::default()
+    };
+    let existing_data_channels: Vec<DataChannel> = Vec::new();
+    let filter = |cfg: &Config| cfg.label != "rejected";
+
+    let dc0 = DataChannel::dial(&a0, 100, label_cfg("rejected")).await?;
+    bridge_process_at_least_one(&br).await;
+
+    let result =
+        DataChannel::accept_with_filter(&a1, Config::default(), &existing_data_channels, filter)
+            .await;
+    assert_eq!(
+        result.err(),
+        Some(Error::ErrDataChannelRejected {
+            label: "rejected".to_string(),
+            protocol: "proto".to_string(),
+        })
+    );
+    dc0.close().await?;
+    bridge_process_at_least_one(&br).await;
+
+    let dc0 = DataChannel::dial(&a0, 102, label_cfg("accepted")).await?;
+    bridge_process_at_least_one(&br).await;
+
+    let dc1 =
+        DataChannel::accept_with_filter(&a1, Config::default(), &existing_data_channels, filter)
+            .await?;
+    bridge_process_at_least_one(&br).await;
+    assert_eq!(dc1.config.label, "accepted");
+    assert_eq!(dc1.config.protocol, "proto");
+
+    dc0.close().await?;
+    dc1.close().await?;
+    bridge_process_at_least_one(&br).await;
+
+    close_association_pair(&br, a0, a1).await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_data_channel_accept_malformed_open() -> Result<()> {
+    let (br, ca, cb) = Bridge::new(0, None, None);
+    let (a0, a1) = create_new_association_pair(&br, Arc::new(ca), Arc::new(cb)).await?;
+
+    let s0 = a0
+        .open_stream(100, PayloadProtocolIdentifier::Binary)
+        .await?;
+    let raw = Bytes::from_static(&[
+        0x03, // message type
+        0x11, // channel type
+        0x00, 0x00, // priority
+        0x00, 0x00, 0x00, 0x00, // reliability parameter
+        0x00, 0x00, // label length
+        0x00, 0x00, // protocol length
+    ]);
+    s0.write_sctp(&raw, PayloadProtocolIdentifier::Dcep).await?;
+    bridge_process_at_least_one(&br).await;
+
+    let existing_data_channels: Vec<DataChannel> = Vec::new();
+    let result = DataChannel::accept(&a1, Config::default(), &existing_data_channels).await;
+    assert_eq!(
+        result.err(),
+        Some(Error::ErrMalformedDataChannelOpen(Box::new(
+            Error::InvalidChannelType(0x11)
+        )))
+    );
+
+    s0.shutdown(Shutdown::Both).await?;
+    bridge_process_at_least_one(&br).await;
+
+    close_association_pair(&br, a0, a1).await;
+
+    Ok(())
+}
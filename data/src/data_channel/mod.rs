@@ -10,8 +10,8 @@ use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::{fmt, io};
 
-use bytes::{Buf, Bytes};
-use portable_atomic::AtomicUsize;
+use bytes::{Buf, Bytes, BytesMut};
+use portable_atomic::{AtomicBool, AtomicUsize};
 use sctp::association::Association;
 use sctp::chunk::chunk_payload_data::PayloadProtocolIdentifier;
 use sctp::stream::*;
@@ -47,10 +47,15 @@ pub struct DataChannel {
     messages_received: Arc<AtomicUsize>,
     bytes_sent: Arc<AtomicUsize>,
     bytes_received: Arc<AtomicUsize>,
+
+    // Until the DATA_CHANNEL_ACK is sent or received, messages must be sent ordered, see
+    // RFC 8832 sec 6. A negotiated channel has no such handshake.
+    acked: Arc<AtomicBool>,
 }
 
 impl DataChannel {
     pub fn new(stream: Arc<Stream>, config: Config) -> Self {
+        stream.set_priority(config.priority);
         Self {
             config,
             stream,
@@ -79,6 +84,22 @@ impl DataChannel {
     ) -> Result<Self>
     where
         T: Borrow<Self>,
+    {
+        Self::accept_with_filter(association, config, existing_channels, |_| true).await
+    }
+
+    /// AcceptWithFilter is accept, but rejects an incoming data channel for which filter
+    /// returns false given its configuration, see [`DataChannel::server_with_filter`]. Data
+    /// channels already in existing_channels are not filtered.
+    pub async fn accept_with_filter<T, F>(
+        association: &Arc<Association>,
+        config: Config,
+        existing_channels: &[T],
+        filter: F,
+    ) -> Result<Self>
+    where
+        T: Borrow<Self>,
+        F: FnOnce(&Config) -> bool,
     {
         let stream = association
             .accept_stream()
@@ -96,30 +117,51 @@ impl DataChannel {
 
         stream.set_default_payload_type(PayloadProtocolIdentifier::Binary);
 
-        Self::server(stream, config).await
+        Self::server_with_filter(stream, config, filter).await
     }
 
     /// Client opens a data channel over an SCTP stream
     pub async fn client(stream: Arc<Stream>, config: Config) -> Result<Self> {
         if !config.negotiated {
-            let msg = Message::DataChannelOpen(DataChannelOpen {
+            let dco = DataChannelOpen {
                 channel_type: config.channel_type,
                 priority: config.priority,
                 reliability_parameter: config.reliability_parameter,
                 label: config.label.bytes().collect(),
                 protocol: config.protocol.bytes().collect(),
-            })
-            .marshal()?;
+            };
+            dco.validate()?;
+            let msg = Message::DataChannelOpen(dco).marshal()?;
 
             stream
                 .write_sctp(&msg, PayloadProtocolIdentifier::Dcep)
                 .await?;
         }
-        Ok(DataChannel::new(stream, config))
+        let data_channel = DataChannel::new(stream, config);
+        if data_channel.config.negotiated {
+            data_channel.acked.store(true, Ordering::SeqCst);
+        }
+        Ok(data_channel)
     }
 
     /// Server accepts a data channel over an SCTP stream
-    pub async fn server(stream: Arc<Stream>, mut config: Config) -> Result<Self> {
+    pub async fn server(stream: Arc<Stream>, config: Config) -> Result<Self> {
+        Self::server_with_filter(stream, config, |_| true).await
+    }
+
+    /// ServerWithFilter is server, but calls filter with the configuration requested by the
+    /// DATA_CHANNEL_OPEN message before acknowledging it. If filter returns false, the stream
+    /// is reset and [`Error::ErrDataChannelRejected`] returned. A DATA_CHANNEL_OPEN message
+    /// which can't be parsed also resets the stream, and is returned as
+    /// [`Error::ErrMalformedDataChannelOpen`] with the cause.
+    pub async fn server_with_filter<F>(
+        stream: Arc<Stream>,
+        mut config: Config,
+        filter: F,
+    ) -> Result<Self>
+    where
+        F: FnOnce(&Config) -> bool,
+    {
         let mut buf = vec![0u8; RECEIVE_MTU];
 
         let (n, ppi) = stream.read_sctp(&mut buf).await?;
@@ -129,18 +171,41 @@ impl DataChannel {
         }
 
         let mut read_buf = &buf[..n];
-        let msg = Message::unmarshal(&mut read_buf)?;
-
-        if let Message::DataChannelOpen(dco) = msg {
-            config.channel_type = dco.channel_type;
-            config.priority = dco.priority;
-            config.reliability_parameter = dco.reliability_parameter;
-            config.label = String::from_utf8(dco.label)?;
-            config.protocol = String::from_utf8(dco.protocol)?;
-        } else {
-            return Err(Error::InvalidMessageType(msg.message_type() as u8));
+        let dco = match Message::unmarshal(&mut read_buf) {
+            Ok(Message::DataChannelOpen(dco)) => dco,
+            Ok(msg) => return Err(Error::InvalidMessageType(msg.message_type() as u8)),
+            Err(err) => {
+                stream.shutdown(Shutdown::Both).await?;
+                return Err(Error::ErrMalformedDataChannelOpen(Box::new(
+                    Error::from_util(err),
+                )));
+            }
         };
 
+        let parsed = String::from_utf8(dco.label)
+            .and_then(|label| String::from_utf8(dco.protocol).map(|protocol| (label, protocol)));
+        match parsed {
+            Ok((label, protocol)) => {
+                config.channel_type = dco.channel_type;
+                config.priority = dco.priority;
+                config.reliability_parameter = dco.reliability_parameter;
+                config.label = label;
+                config.protocol = protocol;
+            }
+            Err(err) => {
+                stream.shutdown(Shutdown::Both).await?;
+                return Err(Error::ErrMalformedDataChannelOpen(Box::new(err.into())));
+            }
+        }
+
+        if !filter(&config) {
+            stream.shutdown(Shutdown::Both).await?;
+            return Err(Error::ErrDataChannelRejected {
+                label: config.label,
+                protocol: config.protocol,
+            });
+        }
+
         let data_channel = DataChannel::new(stream, config);
 
         data_channel.write_data_channel_ack().await?;
@@ -161,12 +226,19 @@ impl DataChannel {
     ///
     /// See [`sctp::stream::Stream::read_sctp`].
     pub async fn read_data_channel(&self, buf: &mut [u8]) -> Result<(usize, bool)> {
+        Ok(self.read_message(buf).await?.unwrap_or((0, false)))
+    }
+
+    /// read_message is read_data_channel, but returns `None` once the incoming stream was reset
+    /// or the reading half was shutdown, which an empty message can't be told apart from
+    /// otherwise.
+    pub(crate) async fn read_message(&self, buf: &mut [u8]) -> Result<Option<(usize, bool)>> {
         loop {
             //TODO: add handling of cancel read_data_channel
             let (mut n, ppi) = match self.stream.read_sctp(buf).await {
                 Ok((0, PayloadProtocolIdentifier::Unknown)) => {
                     // The incoming stream was reset or the reading half was shutdown
-                    return Ok((0, false));
+                    return Ok(None);
                 }
                 Ok((n, ppi)) => (n, ppi),
                 Err(err) => {
@@ -204,7 +276,7 @@ impl DataChannel {
             self.messages_received.fetch_add(1, Ordering::SeqCst);
             self.bytes_received.fetch_add(n, Ordering::SeqCst);
 
-            return Ok((n, is_string));
+            return Ok(Some((n, is_string)));
         }
     }
 
@@ -233,6 +305,12 @@ impl DataChannel {
         self.stream.stream_identifier()
     }
 
+    /// RetransmittedChunks returns the number of DATA chunks of the stream that
+    /// had to be sent again.
+    pub fn retransmitted_chunks(&self) -> u64 {
+        self.stream.retransmitted_chunks()
+    }
+
     async fn handle_dcep<B>(&self, data: &mut B) -> Result<()>
     where
         B: Buf,
@@ -262,6 +340,49 @@ impl DataChannel {
 
     /// WriteDataChannel writes len(p) bytes from p
     pub async fn write_data_channel(&self, data: &Bytes, is_string: bool) -> Result<usize> {
+        self.write_message(data, is_string, WriteOptions::default())
+            .await
+    }
+
+    /// WriteDataChannelWithReliability writes len(p) bytes from p, retransmitting them
+    /// according to rel_type and rel_val instead of the reliability of the channel.
+    pub async fn write_data_channel_with_reliability(
+        &self,
+        data: &Bytes,
+        is_string: bool,
+        rel_type: ReliabilityType,
+        rel_val: u32,
+    ) -> Result<usize> {
+        let options = WriteOptions {
+            reliability: Some((rel_type, rel_val)),
+            ..Default::default()
+        };
+        self.write_message(data, is_string, options).await
+    }
+
+    /// WriteDataChannelWithOptions writes len(p) bytes from p, sending them as set by options
+    /// instead of by the parameters of the channel, e.g. unordered on an ordered channel. As
+    /// the DATA_CHANNEL_OPEN message must not be overtaken, messages are sent ordered until
+    /// the channel is acknowledged.
+    pub async fn write_data_channel_with_options(
+        &self,
+        data: &Bytes,
+        is_string: bool,
+        options: WriteOptions,
+    ) -> Result<usize> {
+        self.write_message(data, is_string, options).await
+    }
+
+    async fn write_message(
+        &self,
+        data: &Bytes,
+        is_string: bool,
+        mut options: WriteOptions,
+    ) -> Result<usize> {
+        if options.unordered && !self.acked.load(Ordering::SeqCst) {
+            options.unordered = false;
+        }
+
         let data_len = data.len();
 
         // https://tools.ietf.org/html/draft-ietf-rtcweb-data-channel-12#section-6.6
@@ -280,12 +401,11 @@ impl DataChannel {
 
         let n = if data_len == 0 {
             let _ = self
-                .stream
-                .write_sctp(&Bytes::from_static(&[0]), ppi)
+                .write_sctp(&Bytes::from_static(&[0]), ppi, options)
                 .await?;
             0
         } else {
-            let n = self.stream.write_sctp(data, ppi).await?;
+            let n = self.write_sctp(data, ppi, options).await?;
             self.bytes_sent.fetch_add(n, Ordering::SeqCst);
             n
         };
@@ -294,6 +414,18 @@ impl DataChannel {
         Ok(n)
     }
 
+    async fn write_sctp(
+        &self,
+        data: &Bytes,
+        ppi: PayloadProtocolIdentifier,
+        options: WriteOptions,
+    ) -> Result<usize> {
+        Ok(self
+            .stream
+            .write_sctp_with_options(data, ppi, options)
+            .await?)
+    }
+
     async fn write_data_channel_ack(&self) -> Result<usize> {
         let ack = Message::DataChannelAck(DataChannelAck {}).marshal()?;
         Ok(self
@@ -342,6 +474,27 @@ impl DataChannel {
         self.stream.on_buffered_amount_low(f)
     }
 
+    /// Writable waits until the send buffer has room for more data.
+    ///
+    /// See [`sctp::stream::Stream::writable`].
+    pub async fn writable(&self) -> Result<()> {
+        Ok(self.stream.writable().await?)
+    }
+
+    /// IsWritable returns true if the send buffer has room for more data right now.
+    ///
+    /// See [`sctp::stream::Stream::is_writable`].
+    pub fn is_writable(&self) -> Result<bool> {
+        Ok(self.stream.is_writable()?)
+    }
+
+    /// SetSendBufferWatermarks sets the high and low watermarks of the send buffer.
+    ///
+    /// See [`sctp::stream::Stream::set_send_buffer_watermarks`].
+    pub fn set_send_buffer_watermarks(&self, high: usize, low: usize) {
+        self.stream.set_send_buffer_watermarks(high, low)
+    }
+
     fn commit_reliability_params(&self) {
         let (unordered, reliability_type) = match self.config.channel_type {
             ChannelType::Reliable => (false, ReliabilityType::Reliable),
@@ -357,18 +510,59 @@ impl DataChannel {
             reliability_type,
             self.config.reliability_parameter,
         );
+        self.acked.store(true, Ordering::SeqCst);
     }
 }
 
-/// Default capacity of the temporary read buffer used by [`PollStream`].
+/// Default capacity of the temporary read buffer used by [`PollDataChannel`].
 const DEFAULT_READ_BUF_SIZE: usize = 8192;
 
-/// State of the read `Future` in [`PollStream`].
+/// Size of the length prefix of a frame in [`Framing::LengthPrefixed`].
+const LENGTH_PREFIX_SIZE: usize = 4;
+
+/// Default maximum payload length of a frame in [`Framing::LengthPrefixed`], matching the
+/// default maximum message size of SCTP.
+const DEFAULT_MAX_FRAME_LEN: usize = 65536;
+
+/// Framing selects how [`PollDataChannel`] maps the messages of a data channel onto the bytes
+/// read and written through it.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Framing {
+    /// Messages are read as a stream of bytes, a read may return part of a message. Every
+    /// write is sent as a message.
+    #[default]
+    RawStream,
+    /// Every read returns exactly one message and every write is sent as exactly one message,
+    /// so message boundaries are kept. Empty messages are skipped. A read into a buffer too
+    /// small for the next message fails with `io::ErrorKind::InvalidInput`, and leaves the
+    /// message to be read with a larger one.
+    MessagePerFrame,
+    /// Every message is a frame of the byte stream, prefixed by its length as a 4 byte
+    /// big-endian integer, like `tokio_util::codec::LengthDelimitedCodec` expects by default.
+    /// Written bytes are buffered until a frame is complete, which is then sent as one message.
+    LengthPrefixed,
+}
+
+impl fmt::Display for Framing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match *self {
+            Framing::RawStream => "RawStream",
+            Framing::MessagePerFrame => "MessagePerFrame",
+            Framing::LengthPrefixed => "LengthPrefixed",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// A message read by [`PollDataChannel`], `None` at the end of the channel.
+type ReadMessage = Option<(Vec<u8>, bool)>;
+
+/// State of the read `Future` in [`PollDataChannel`].
 enum ReadFut {
     /// Nothing in progress.
     Idle,
     /// Reading data from the underlying stream.
-    Reading(Pin<Box<dyn Future<Output = Result<Vec<u8>>> + Send>>),
+    Reading(Pin<Box<dyn Future<Output = Result<ReadMessage>> + Send>>),
     /// Finished reading, but there's unread data in the temporary buffer.
     RemainingData(Vec<u8>),
 }
@@ -379,7 +573,9 @@ impl ReadFut {
     /// # Panics
     ///
     /// Panics if `ReadFut` variant is not `Reading`.
-    fn get_reading_mut(&mut self) -> &mut Pin<Box<dyn Future<Output = Result<Vec<u8>>> + Send>> {
+    fn get_reading_mut(
+        &mut self,
+    ) -> &mut Pin<Box<dyn Future<Output = Result<ReadMessage>> + Send>> {
         match self {
             ReadFut::Reading(ref mut fut) => fut,
             _ => panic!("expected ReadFut to be Reading"),
@@ -388,7 +584,7 @@ impl ReadFut {
 }
 
 /// A wrapper around around [`DataChannel`], which implements [`AsyncRead`] and
-/// [`AsyncWrite`].
+/// [`AsyncWrite`], framing messages as set with [`PollDataChannel::set_framing`].
 ///
 /// Both `poll_read` and `poll_write` calls allocate temporary buffers, which results in an
 /// additional overhead.
@@ -400,6 +596,12 @@ pub struct PollDataChannel {
     shutdown_fut: Option<Pin<Box<dyn Future<Output = Result<()>> + Send>>>,
 
     read_buf_cap: usize,
+    framing: Framing,
+    max_frame_len: usize,
+    /// Bytes of a frame written in part, in [`Framing::LengthPrefixed`].
+    write_frame: BytesMut,
+    write_text: bool,
+    last_read_is_string: bool,
 }
 
 impl PollDataChannel {
@@ -422,6 +624,11 @@ impl PollDataChannel {
             write_fut: None,
             shutdown_fut: None,
             read_buf_cap: DEFAULT_READ_BUF_SIZE,
+            framing: Framing::default(),
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+            write_frame: BytesMut::new(),
+            write_text: false,
+            last_read_is_string: false,
         }
     }
 
@@ -476,6 +683,100 @@ impl PollDataChannel {
     pub fn set_read_buf_capacity(&mut self, capacity: usize) {
         self.read_buf_cap = capacity
     }
+
+    /// Framing returns how messages are mapped onto the bytes read and written.
+    pub fn framing(&self) -> Framing {
+        self.framing
+    }
+
+    /// Set how messages are mapped onto the bytes read and written (default:
+    /// [`Framing::RawStream`]).
+    pub fn set_framing(&mut self, framing: Framing) {
+        self.framing = framing
+    }
+
+    /// Set the maximum payload length of a written frame in [`Framing::LengthPrefixed`]
+    /// (default: 65536). Longer frames fail with `io::ErrorKind::InvalidData`.
+    pub fn set_max_frame_len(&mut self, max_frame_len: usize) {
+        self.max_frame_len = max_frame_len
+    }
+
+    /// Set whether messages are written as text rather than binary data (default: false).
+    /// Unless the framing is [`Framing::RawStream`], where a write may end within a character,
+    /// a message that is not valid UTF-8 fails with `io::ErrorKind::InvalidData`.
+    pub fn set_write_text(&mut self, text: bool) {
+        self.write_text = text
+    }
+
+    /// LastReadIsString returns whether the message read last was sent as text.
+    pub fn last_read_is_string(&self) -> bool {
+        self.last_read_is_string
+    }
+
+    /// frame_messages turns written bytes into the messages to send.
+    fn frame_messages(&mut self, buf: &[u8]) -> io::Result<Vec<Bytes>> {
+        let messages = match self.framing {
+            Framing::RawStream | Framing::MessagePerFrame => vec![Bytes::copy_from_slice(buf)],
+            Framing::LengthPrefixed => {
+                self.write_frame.extend_from_slice(buf);
+
+                let mut messages = vec![];
+                while self.write_frame.len() >= LENGTH_PREFIX_SIZE {
+                    let mut prefix = &self.write_frame[..LENGTH_PREFIX_SIZE];
+                    let len = prefix.get_u32() as usize;
+                    if len > self.max_frame_len {
+                        self.write_frame.clear();
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("frame of {len} bytes is longer than {}", self.max_frame_len),
+                        ));
+                    }
+                    if self.write_frame.len() < LENGTH_PREFIX_SIZE + len {
+                        break;
+                    }
+
+                    self.write_frame.advance(LENGTH_PREFIX_SIZE);
+                    messages.push(self.write_frame.split_to(len).freeze());
+                }
+                messages
+            }
+        };
+
+        if self.write_text && self.framing != Framing::RawStream {
+            if let Some(m) = messages.iter().find(|m| std::str::from_utf8(m).is_err()) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("text message of {} bytes is not valid UTF-8", m.len()),
+                ));
+            }
+        }
+
+        Ok(messages)
+    }
+
+    /// put_message copies as much of a message into buf as the framing allows, keeping the
+    /// rest to be read next.
+    fn put_message(&mut self, buf: &mut ReadBuf<'_>, mut data: Vec<u8>) -> io::Result<()> {
+        let remaining = buf.remaining();
+        if self.framing == Framing::MessagePerFrame && data.len() > remaining {
+            let len = data.len();
+            self.read_fut = ReadFut::RemainingData(data);
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("message of {len} bytes does not fit into {remaining} bytes"),
+            ));
+        }
+
+        let len = std::cmp::min(data.len(), remaining);
+        buf.put_slice(&data[..len]);
+        if data.len() > remaining {
+            data.drain(..len);
+            self.read_fut = ReadFut::RemainingData(data);
+        } else {
+            self.read_fut = ReadFut::Idle;
+        }
+        Ok(())
+    }
 }
 
 impl AsyncRead for PollDataChannel {
@@ -494,26 +795,26 @@ impl AsyncRead for PollDataChannel {
                 // be shorter than the lifetime of `read_fut`.
                 let data_channel = self.data_channel.clone();
                 let mut temp_buf = vec![0; self.read_buf_cap];
+                // An empty message has no bytes to be read, unless it has a length prefix.
+                let skip_empty = self.framing != Framing::LengthPrefixed;
                 self.read_fut = ReadFut::Reading(Box::pin(async move {
-                    data_channel.read(temp_buf.as_mut_slice()).await.map(|n| {
-                        temp_buf.truncate(n);
-                        temp_buf
-                    })
+                    loop {
+                        match data_channel.read_message(temp_buf.as_mut_slice()).await? {
+                            Some((0, _)) if skip_empty => {}
+                            Some((n, is_string)) => {
+                                temp_buf.truncate(n);
+                                return Ok(Some((temp_buf, is_string)));
+                            }
+                            None => return Ok(None),
+                        }
+                    }
                 }));
                 self.read_fut.get_reading_mut()
             }
             ReadFut::Reading(ref mut fut) => fut,
             ReadFut::RemainingData(ref mut data) => {
-                let remaining = buf.remaining();
-                let len = std::cmp::min(data.len(), remaining);
-                buf.put_slice(&data[..len]);
-                if data.len() > remaining {
-                    // ReadFut remains to be RemainingData
-                    data.drain(..len);
-                } else {
-                    self.read_fut = ReadFut::Idle;
-                }
-                return Poll::Ready(Ok(()));
+                let data = std::mem::take(data);
+                return Poll::Ready(self.put_message(buf, data));
             }
         };
 
@@ -524,7 +825,7 @@ impl AsyncRead for PollDataChannel {
                 // since there's no way to setup a waker.
                 Poll::Ready(Err(Error::Sctp(sctp::Error::ErrTryAgain))) => {}
                 // EOF has been reached => don't touch buf and just return Ok
-                Poll::Ready(Err(Error::Sctp(sctp::Error::ErrEof))) => {
+                Poll::Ready(Err(Error::Sctp(sctp::Error::ErrEof))) | Poll::Ready(Ok(None)) => {
                     self.read_fut = ReadFut::Idle;
                     return Poll::Ready(Ok(()));
                 }
@@ -532,17 +833,13 @@ impl AsyncRead for PollDataChannel {
                     self.read_fut = ReadFut::Idle;
                     return Poll::Ready(Err(e.into()));
                 }
-                Poll::Ready(Ok(mut temp_buf)) => {
-                    let remaining = buf.remaining();
-                    let len = std::cmp::min(temp_buf.len(), remaining);
-                    buf.put_slice(&temp_buf[..len]);
-                    if temp_buf.len() > remaining {
-                        temp_buf.drain(..len);
-                        self.read_fut = ReadFut::RemainingData(temp_buf);
-                    } else {
-                        self.read_fut = ReadFut::Idle;
+                Poll::Ready(Ok(Some((mut temp_buf, is_string)))) => {
+                    self.last_read_is_string = is_string;
+                    if self.framing == Framing::LengthPrefixed {
+                        let prefix = (temp_buf.len() as u32).to_be_bytes();
+                        temp_buf.splice(..0, prefix);
                     }
-                    return Poll::Ready(Ok(()));
+                    return Poll::Ready(self.put_message(buf, temp_buf));
                 }
             }
         }
@@ -559,52 +856,47 @@ impl AsyncWrite for PollDataChannel {
             return Poll::Ready(Ok(0));
         }
 
+        // The previous write has been reported as done, its data only has to be queued yet.
         if let Some(fut) = self.write_fut.as_mut() {
             match fut.as_mut().poll(cx) {
-                Poll::Pending => Poll::Pending,
-                Poll::Ready(Err(e)) => {
-                    let data_channel = self.data_channel.clone();
-                    let bytes = Bytes::copy_from_slice(buf);
-                    self.write_fut =
-                        Some(Box::pin(async move { data_channel.write(&bytes).await }));
-                    Poll::Ready(Err(e.into()))
-                }
-                // Given the data is buffered, it's okay to ignore the number of written bytes.
-                //
-                // TODO: In the long term, `data_channel.write` should be made sync. Then we could
-                // remove the whole `if` condition and just call `data_channel.write`.
-                Poll::Ready(Ok(_)) => {
-                    let data_channel = self.data_channel.clone();
-                    let bytes = Bytes::copy_from_slice(buf);
-                    self.write_fut =
-                        Some(Box::pin(async move { data_channel.write(&bytes).await }));
-                    Poll::Ready(Ok(buf.len()))
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(result) => {
+                    self.write_fut = None;
+                    if let Err(e) = result {
+                        return Poll::Ready(Err(e.into()));
+                    }
                 }
             }
-        } else {
-            let data_channel = self.data_channel.clone();
-            let bytes = Bytes::copy_from_slice(buf);
-            let fut = self
-                .write_fut
-                .insert(Box::pin(async move { data_channel.write(&bytes).await }));
+        }
 
-            match fut.as_mut().poll(cx) {
-                // If it's the first time we're polling the future, `Poll::Pending` can't be
-                // returned because that would mean the `PollDataChannel` is not ready for writing.
-                // And this is not true since we've just created a future, which is going to write
-                // the buf to the underlying stream.
-                //
-                // It's okay to return `Poll::Ready` if the data is buffered (this is what the
-                // buffered writer and `File` do).
-                Poll::Pending => Poll::Ready(Ok(buf.len())),
-                Poll::Ready(Err(e)) => {
-                    self.write_fut = None;
-                    Poll::Ready(Err(e.into()))
-                }
-                Poll::Ready(Ok(n)) => {
-                    self.write_fut = None;
-                    Poll::Ready(Ok(n))
-                }
+        let messages = match self.frame_messages(buf) {
+            Ok(messages) if messages.is_empty() => return Poll::Ready(Ok(buf.len())),
+            Ok(messages) => messages,
+            Err(e) => return Poll::Ready(Err(e)),
+        };
+
+        let data_channel = self.data_channel.clone();
+        let is_string = self.write_text;
+        let fut = self.write_fut.insert(Box::pin(async move {
+            let mut n = 0;
+            for m in &messages {
+                n += data_channel.write_data_channel(m, is_string).await?;
+            }
+            Ok(n)
+        }));
+
+        match fut.as_mut().poll(cx) {
+            // If it's the first time we're polling the future, `Poll::Pending` can't be
+            // returned because that would mean the `PollDataChannel` is not ready for writing.
+            // And this is not true since we've just created a future, which is going to write
+            // the buf to the underlying stream.
+            //
+            // It's okay to return `Poll::Ready` if the data is buffered (this is what the
+            // buffered writer and `File` do).
+            Poll::Pending => Poll::Ready(Ok(buf.len())),
+            Poll::Ready(result) => {
+                self.write_fut = None;
+                Poll::Ready(result.map(|_| buf.len()).map_err(Into::into))
             }
         }
     }
@@ -632,6 +924,14 @@ impl AsyncWrite for PollDataChannel {
             Poll::Ready(_) => {}
         }
 
+        if !self.write_frame.is_empty() {
+            log::warn!(
+                "dropping {} bytes of an incomplete frame on shutdown",
+                self.write_frame.len()
+            );
+            self.write_frame.clear();
+        }
+
         let fut = match self.shutdown_fut.as_mut() {
             Some(fut) => fut,
             None => {
@@ -662,7 +962,12 @@ impl AsyncWrite for PollDataChannel {
 
 impl Clone for PollDataChannel {
     fn clone(&self) -> PollDataChannel {
-        PollDataChannel::new(self.clone_inner())
+        let mut clone = PollDataChannel::new(self.clone_inner());
+        clone.read_buf_cap = self.read_buf_cap;
+        clone.framing = self.framing;
+        clone.max_frame_len = self.max_frame_len;
+        clone.write_text = self.write_text;
+        clone
     }
 }
 
@@ -671,6 +976,9 @@ impl fmt::Debug for PollDataChannel {
         f.debug_struct("PollDataChannel")
             .field("data_channel", &self.data_channel)
             .field("read_buf_cap", &self.read_buf_cap)
+            .field("framing", &self.framing)
+            .field("max_frame_len", &self.max_frame_len)
+            .field("write_text", &self.write_text)
             .finish()
     }
 }
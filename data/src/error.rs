@@ -20,6 +20,26 @@ pub enum Error {
     InvalidPayloadProtocolIdentifier(u8),
     #[error("Stream closed")]
     ErrStreamClosed,
+    #[error("DataChannel label of {0} bytes is longer than 65535 bytes")]
+    ErrLabelTooLong(usize),
+    #[error("DataChannel protocol of {0} bytes is longer than 65535 bytes")]
+    ErrProtocolTooLong(usize),
+    #[error("malformed DATA_CHANNEL_OPEN: {0}")]
+    ErrMalformedDataChannelOpen(Box<Error>),
+    #[error("DataChannel {label:?} with protocol {protocol:?} was rejected")]
+    ErrDataChannelRejected { label: String, protocol: String },
+    #[error("Unknown FlowFrameType {0}")]
+    InvalidFlowFrameType(u8),
+    #[error("Flow closed")]
+    ErrFlowClosed,
+    #[error("Flow message of {len} bytes is larger than {max} bytes")]
+    ErrFlowMessageTooLarge { len: usize, max: usize },
+    #[error("Unknown FileMessageType {0}")]
+    InvalidFileMessageType(u8),
+    #[error("unexpected file transfer message of type {0}")]
+    ErrUnexpectedFileMessage(u8),
+    #[error("file transfer failed, chunk {0} couldn't be verified after all retries")]
+    ErrFileTransferRetriesExceeded(u64),
 
     #[error("{0}")]
     Util(#[from] util::Error),
@@ -27,25 +47,47 @@ pub enum Error {
     Sctp(#[from] sctp::Error),
     #[error("utf-8 error: {0}")]
     Utf8(#[from] FromUtf8Error),
+    #[error("io error: {0}")]
+    Io(String),
 
     #[allow(non_camel_case_types)]
     #[error("{0}")]
     new(String),
 }
 
+impl Error {
+    /// from_util recovers an [`Error`] wrapped into a [`util::Error`], like the ones returned
+    /// by unmarshaling.
+    pub(crate) fn from_util(err: util::Error) -> Self {
+        match err {
+            util::Error::Std(std_err) if std_err.0.is::<Error>() => match std_err.0.downcast() {
+                Ok(e) => *e,
+                Err(e) => Error::new(e.to_string()),
+            },
+            err => Error::Util(err),
+        }
+    }
+}
+
 impl From<Error> for util::Error {
     fn from(e: Error) -> Self {
         util::Error::from_std(e)
     }
 }
 
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e.to_string())
+    }
+}
+
 impl From<Error> for io::Error {
     fn from(error: Error) -> Self {
         match error {
             e @ Error::Sctp(sctp::Error::ErrEof) => {
                 io::Error::new(io::ErrorKind::UnexpectedEof, e.to_string())
             }
-            e @ Error::ErrStreamClosed => {
+            e @ (Error::ErrStreamClosed | Error::ErrFlowClosed) => {
                 io::Error::new(io::ErrorKind::ConnectionAborted, e.to_string())
             }
             e => io::Error::new(io::ErrorKind::Other, e.to_string()),
@@ -0,0 +1,240 @@
+use bytes::{Buf, BufMut, Bytes};
+use util::marshal::*;
+
+use crate::error::Error;
+
+type Result<T> = std::result::Result<T, util::Error>;
+
+const FILE_MESSAGE_TYPE_OFFER: u8 = 0x00;
+const FILE_MESSAGE_TYPE_ACCEPT: u8 = 0x01;
+const FILE_MESSAGE_TYPE_CHUNK: u8 = 0x02;
+const FILE_MESSAGE_TYPE_DONE: u8 = 0x03;
+const FILE_MESSAGE_TYPE_RETRY: u8 = 0x04;
+const FILE_MESSAGE_TYPE_COMPLETE: u8 = 0x05;
+
+const FILE_MESSAGE_TYPE_LEN: usize = 1;
+const FILE_OFFER_HEADER_LEN: usize = 12;
+const FILE_INDEX_LEN: usize = 8;
+pub(crate) const FILE_HASH_LEN: usize = 32;
+pub(crate) const FILE_CHUNK_HEADER_LEN: usize =
+    FILE_MESSAGE_TYPE_LEN + FILE_INDEX_LEN + FILE_HASH_LEN;
+
+/// A message of a file transfer, see [`super::FileSender`].
+///
+/// # Memory layout
+///
+/// Every message starts with its type, followed by its fields in network byte order:
+///
+/// ```plain
+/// Offer (0x00):    | Type | Size (64) | Chunk Size (32) | Name (UTF-8) ...           |
+/// Accept (0x01):   | Type | Next Chunk (64) |
+/// Chunk (0x02):    | Type | Index (64) | SHA-256 of Data (256) | Data ...            |
+/// Done (0x03):     | Type |
+/// Retry (0x04):    | Type | Index (64) |
+/// Complete (0x05): | Type |
+/// ```
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub(crate) enum FileMessage {
+    /// The sender offers a file of size bytes, sent in chunks of chunk_size bytes.
+    Offer {
+        size: u64,
+        chunk_size: u32,
+        name: String,
+    },
+    /// The receiver accepts the offer and asks for all chunks from next_chunk on.
+    Accept { next_chunk: u64 },
+    /// A chunk of the file.
+    Chunk {
+        index: u64,
+        hash: [u8; FILE_HASH_LEN],
+        data: Bytes,
+    },
+    /// The sender sent all chunks.
+    Done,
+    /// The receiver asks for all chunks from index on again, as that one failed to verify.
+    Retry { index: u64 },
+    /// The receiver has verified and stored the whole file.
+    Complete,
+}
+
+impl FileMessage {
+    pub(crate) fn message_type(&self) -> u8 {
+        match self {
+            FileMessage::Offer { .. } => FILE_MESSAGE_TYPE_OFFER,
+            FileMessage::Accept { .. } => FILE_MESSAGE_TYPE_ACCEPT,
+            FileMessage::Chunk { .. } => FILE_MESSAGE_TYPE_CHUNK,
+            FileMessage::Done => FILE_MESSAGE_TYPE_DONE,
+            FileMessage::Retry { .. } => FILE_MESSAGE_TYPE_RETRY,
+            FileMessage::Complete => FILE_MESSAGE_TYPE_COMPLETE,
+        }
+    }
+}
+
+impl MarshalSize for FileMessage {
+    fn marshal_size(&self) -> usize {
+        FILE_MESSAGE_TYPE_LEN
+            + match self {
+                FileMessage::Offer { name, .. } => FILE_OFFER_HEADER_LEN + name.len(),
+                FileMessage::Accept { .. } | FileMessage::Retry { .. } => FILE_INDEX_LEN,
+                FileMessage::Chunk { data, .. } => FILE_INDEX_LEN + FILE_HASH_LEN + data.len(),
+                FileMessage::Done | FileMessage::Complete => 0,
+            }
+    }
+}
+
+impl Marshal for FileMessage {
+    fn marshal_to(&self, mut buf: &mut [u8]) -> Result<usize> {
+        let required_len = self.marshal_size();
+        if buf.remaining_mut() < required_len {
+            return Err(Error::UnexpectedEndOfBuffer {
+                expected: required_len,
+                actual: buf.remaining_mut(),
+            }
+            .into());
+        }
+
+        buf.put_u8(self.message_type());
+        match self {
+            FileMessage::Offer {
+                size,
+                chunk_size,
+                name,
+            } => {
+                buf.put_u64(*size);
+                buf.put_u32(*chunk_size);
+                buf.put_slice(name.as_bytes());
+            }
+            FileMessage::Accept { next_chunk: index } | FileMessage::Retry { index } => {
+                buf.put_u64(*index);
+            }
+            FileMessage::Chunk { index, hash, data } => {
+                buf.put_u64(*index);
+                buf.put_slice(hash);
+                buf.put_slice(data);
+            }
+            FileMessage::Done | FileMessage::Complete => {}
+        }
+
+        Ok(required_len)
+    }
+}
+
+impl Unmarshal for FileMessage {
+    fn unmarshal<B>(buf: &mut B) -> Result<Self>
+    where
+        Self: Sized,
+        B: Buf,
+    {
+        if buf.remaining() < FILE_MESSAGE_TYPE_LEN {
+            return Err(Error::UnexpectedEndOfBuffer {
+                expected: FILE_MESSAGE_TYPE_LEN,
+                actual: buf.remaining(),
+            }
+            .into());
+        }
+
+        let message_type = buf.get_u8();
+        let required_len = match message_type {
+            FILE_MESSAGE_TYPE_OFFER => FILE_OFFER_HEADER_LEN,
+            FILE_MESSAGE_TYPE_ACCEPT | FILE_MESSAGE_TYPE_RETRY => FILE_INDEX_LEN,
+            FILE_MESSAGE_TYPE_CHUNK => FILE_INDEX_LEN + FILE_HASH_LEN,
+            FILE_MESSAGE_TYPE_DONE | FILE_MESSAGE_TYPE_COMPLETE => 0,
+            _ => return Err(Error::InvalidFileMessageType(message_type).into()),
+        };
+        if buf.remaining() < required_len {
+            return Err(Error::UnexpectedEndOfBuffer {
+                expected: required_len,
+                actual: buf.remaining(),
+            }
+            .into());
+        }
+
+        Ok(match message_type {
+            FILE_MESSAGE_TYPE_OFFER => {
+                let size = buf.get_u64();
+                let chunk_size = buf.get_u32();
+                let name = String::from_utf8(buf.copy_to_bytes(buf.remaining()).to_vec())
+                    .map_err(Error::Utf8)?;
+                FileMessage::Offer {
+                    size,
+                    chunk_size,
+                    name,
+                }
+            }
+            FILE_MESSAGE_TYPE_ACCEPT => FileMessage::Accept {
+                next_chunk: buf.get_u64(),
+            },
+            FILE_MESSAGE_TYPE_RETRY => FileMessage::Retry {
+                index: buf.get_u64(),
+            },
+            FILE_MESSAGE_TYPE_CHUNK => {
+                let index = buf.get_u64();
+                let mut hash = [0u8; FILE_HASH_LEN];
+                buf.copy_to_slice(&mut hash);
+                FileMessage::Chunk {
+                    index,
+                    hash,
+                    data: buf.copy_to_bytes(buf.remaining()),
+                }
+            }
+            FILE_MESSAGE_TYPE_DONE => FileMessage::Done,
+            _ => FileMessage::Complete,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_message_round_trip() -> Result<()> {
+        let messages = [
+            FileMessage::Offer {
+                size: 1 << 40,
+                chunk_size: 16384,
+                name: "photo.jpg".to_owned(),
+            },
+            FileMessage::Accept { next_chunk: 3 },
+            FileMessage::Chunk {
+                index: 3,
+                hash: [0xab; FILE_HASH_LEN],
+                data: Bytes::from_static(b"chunk"),
+            },
+            FileMessage::Done,
+            FileMessage::Retry { index: 7 },
+            FileMessage::Complete,
+        ];
+
+        for message in messages {
+            let mut raw = message.marshal()?;
+            assert_eq!(raw.len(), message.marshal_size());
+            assert_eq!(FileMessage::unmarshal(&mut raw)?, message);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_message_marshal() -> Result<()> {
+        let raw = FileMessage::Retry { index: 0x0102 }.marshal()?;
+        assert_eq!(&raw[..], &[0x04, 0, 0, 0, 0, 0, 0, 0x01, 0x02]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_message_unmarshal_invalid() {
+        let mut raw = Bytes::from_static(&[0x02, 0x00, 0x00]);
+        let err = FileMessage::unmarshal(&mut raw).expect_err("chunk should be too short");
+        assert_eq!(
+            Error::UnexpectedEndOfBuffer {
+                expected: FILE_INDEX_LEN + FILE_HASH_LEN,
+                actual: 2
+            },
+            err
+        );
+
+        let mut raw = Bytes::from_static(&[0x10]);
+        let err = FileMessage::unmarshal(&mut raw).expect_err("type should be unknown");
+        assert_eq!(Error::InvalidFileMessageType(0x10), err);
+    }
+}
@@ -0,0 +1,279 @@
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+
+use sctp::association::Association;
+use sctp::congestion::CongestionControlAlgorithm;
+use sctp::stream::StreamScheduler;
+use tokio::task::JoinHandle;
+use util::conn::conn_bridge::*;
+use util::conn::*;
+
+use super::*;
+use crate::data_channel::Config as DataChannelConfig;
+
+fn association_config(
+    net_conn: Arc<dyn Conn + Send + Sync>,
+    name: &str,
+) -> sctp::association::Config {
+    sctp::association::Config {
+        net_conn,
+        max_receive_buffer_size: 0,
+        max_message_size: 0,
+        name: name.to_owned(),
+        stream_scheduler: StreamScheduler::default(),
+        congestion_control: CongestionControlAlgorithm::default(),
+        sack_delay: Duration::ZERO,
+        packets_per_sack: 0,
+        immediate_sack: false,
+        zero_checksum: false,
+        max_num_outbound_streams: 0,
+        max_num_inbound_streams: 0,
+        heartbeat_interval: Duration::ZERO,
+        path_max_retransmits: 0,
+        association_max_retransmits: 0,
+        ecn: false,
+        max_send_buffer_size: 0,
+    }
+}
+
+/// create_data_channel_pair connects two data channels over a bridge which is ticked in the
+/// background until the returned task is aborted.
+async fn create_data_channel_pair() -> Result<(
+    Arc<DataChannel>,
+    Arc<DataChannel>,
+    Arc<Association>,
+    Arc<Association>,
+    JoinHandle<()>,
+)> {
+    let (br, ca, cb) = Bridge::new(0, None, None);
+    let ticker = tokio::spawn(async move {
+        loop {
+            br.tick().await;
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    });
+
+    let (a0, a1) = tokio::try_join!(
+        Association::client(association_config(Arc::new(ca), "client")),
+        Association::server(association_config(Arc::new(cb), "server")),
+    )?;
+    let (a0, a1) = (Arc::new(a0), Arc::new(a1));
+
+    let cfg = DataChannelConfig {
+        label: "files".to_owned(),
+        ..Default::default()
+    };
+    let dc0 = DataChannel::dial(&a0, 1, cfg).await?;
+    let existing_data_channels: Vec<DataChannel> = Vec::new();
+    let dc1 =
+        DataChannel::accept(&a1, DataChannelConfig::default(), &existing_data_channels).await?;
+
+    Ok((Arc::new(dc0), Arc::new(dc1), a0, a1, ticker))
+}
+
+fn file_content(size: usize) -> Vec<u8> {
+    (0..size).map(|i| (i * 7 % 251) as u8).collect()
+}
+
+#[tokio::test]
+async fn test_file_transfer() -> Result<()> {
+    let (dc0, dc1, a0, a1, ticker) = create_data_channel_pair().await?;
+    let content = file_content(100_000);
+
+    let sent = Arc::new(Mutex::new(vec![]));
+    let mut sender = FileSender::new(Config::default());
+    let sent2 = Arc::clone(&sent);
+    sender.on_progress(Box::new(move |progress| {
+        sent2.lock().unwrap().push(progress)
+    }));
+    let sender_content = content.clone();
+    let sender_dc = Arc::clone(&dc0);
+    let sending = tokio::spawn(async move {
+        sender
+            .send(&sender_dc, "file.bin", Cursor::new(sender_content))
+            .await
+    });
+
+    let mut receiver = FileReceiver::new();
+    let offer = receiver.recv_offer(&dc1).await?;
+    assert_eq!(
+        offer,
+        FileOffer {
+            name: "file.bin".to_owned(),
+            size: 100_000,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    );
+    let mut received = Cursor::new(vec![]);
+    receiver.receive(&dc1, &offer, &mut received, 0).await?;
+    sending.await.unwrap()?;
+
+    assert_eq!(received.into_inner(), content);
+    let sent = sent.lock().unwrap().clone();
+    assert_eq!(sent.len(), 7, "progress should be reported for every chunk");
+    assert_eq!(
+        sent.last(),
+        Some(&Progress {
+            transferred: 100_000,
+            total: 100_000
+        })
+    );
+
+    a0.close().await?;
+    a1.close().await?;
+    ticker.abort();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_file_transfer_resume() -> Result<()> {
+    let (dc0, dc1, a0, a1, ticker) = create_data_channel_pair().await?;
+    let content = file_content(100_000);
+    let config = Config {
+        chunk_size: 10_000,
+        ..Default::default()
+    };
+
+    let mut sender = FileSender::new(config);
+    let sender_content = content.clone();
+    let sender_dc = Arc::clone(&dc0);
+    let sending = tokio::spawn(async move {
+        sender
+            .send(&sender_dc, "file.bin", Cursor::new(sender_content))
+            .await
+    });
+
+    // An earlier transfer got 45000 bytes, but only 4 whole chunks are kept.
+    let mut partial = content[..45_000].to_vec();
+    partial[44_999] ^= 0xff;
+    let received = Arc::new(Mutex::new(vec![]));
+    let mut receiver = FileReceiver::new();
+    let received2 = Arc::clone(&received);
+    receiver.on_progress(Box::new(move |progress| {
+        received2.lock().unwrap().push(progress.transferred)
+    }));
+    let offer = receiver.recv_offer(&dc1).await?;
+    let mut file = Cursor::new(partial);
+    receiver.receive(&dc1, &offer, &mut file, 45_000).await?;
+    sending.await.unwrap()?;
+
+    assert_eq!(file.into_inner(), content);
+    assert_eq!(
+        *received.lock().unwrap(),
+        (5..=10).map(|i| i * 10_000).collect::<Vec<u64>>(),
+        "only the missing chunks should be sent"
+    );
+
+    a0.close().await?;
+    a1.close().await?;
+    ticker.abort();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_file_transfer_retry_corrupted_chunk() -> Result<()> {
+    let (dc0, dc1, a0, a1, ticker) = create_data_channel_pair().await?;
+
+    let receiving = tokio::spawn(async move {
+        let mut receiver = FileReceiver::new();
+        let offer = receiver.recv_offer(&dc1).await?;
+        let mut file = Cursor::new(vec![]);
+        receiver.receive(&dc1, &offer, &mut file, 0).await?;
+        Result::<Vec<u8>>::Ok(file.into_inner())
+    });
+
+    let mut buf = vec![0u8; 1024];
+    let chunks = [Bytes::from_static(b"0123"), Bytes::from_static(b"45")];
+    write_file_message(
+        &dc0,
+        FileMessage::Offer {
+            size: 6,
+            chunk_size: 4,
+            name: "file.bin".to_owned(),
+        },
+    )
+    .await?;
+    assert_eq!(
+        read_file_message(&dc0, &mut buf).await?,
+        FileMessage::Accept { next_chunk: 0 }
+    );
+
+    // The first chunk is fine, the second one was corrupted.
+    for (index, data) in chunks.iter().enumerate() {
+        let mut hash: [u8; FILE_HASH_LEN] = Sha256::digest(data).into();
+        hash[0] ^= index as u8;
+        write_file_message(
+            &dc0,
+            FileMessage::Chunk {
+                index: index as u64,
+                hash,
+                data: data.clone(),
+            },
+        )
+        .await?;
+    }
+    write_file_message(&dc0, FileMessage::Done).await?;
+    assert_eq!(
+        read_file_message(&dc0, &mut buf).await?,
+        FileMessage::Retry { index: 1 }
+    );
+
+    write_file_message(
+        &dc0,
+        FileMessage::Chunk {
+            index: 1,
+            hash: Sha256::digest(&chunks[1]).into(),
+            data: chunks[1].clone(),
+        },
+    )
+    .await?;
+    write_file_message(&dc0, FileMessage::Done).await?;
+    assert_eq!(
+        read_file_message(&dc0, &mut buf).await?,
+        FileMessage::Complete
+    );
+    assert_eq!(receiving.await.unwrap()?, b"012345");
+
+    a0.close().await?;
+    a1.close().await?;
+    ticker.abort();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_file_transfer_rate_limit() -> Result<()> {
+    let (dc0, dc1, a0, a1, ticker) = create_data_channel_pair().await?;
+    let content = file_content(40_000);
+
+    let mut sender = FileSender::new(Config {
+        chunk_size: 10_000,
+        rate_limit: 100_000,
+        ..Default::default()
+    });
+    let sender_dc = Arc::clone(&dc0);
+    let start = Instant::now();
+    let sending = tokio::spawn(async move {
+        sender
+            .send(&sender_dc, "file.bin", Cursor::new(content))
+            .await
+    });
+
+    let mut receiver = FileReceiver::new();
+    let offer = receiver.recv_offer(&dc1).await?;
+    receiver
+        .receive(&dc1, &offer, Cursor::new(vec![]), 0)
+        .await?;
+    sending.await.unwrap()?;
+
+    // The first chunk goes out right away, each of the other three 100ms later.
+    assert!(
+        start.elapsed() >= Duration::from_millis(300),
+        "sending should be limited by the rate, took {:?}",
+        start.elapsed()
+    );
+
+    a0.close().await?;
+    a1.close().await?;
+    ticker.abort();
+    Ok(())
+}
@@ -0,0 +1,352 @@
+#[cfg(test)]
+mod file_transfer_test;
+
+mod file_message;
+
+use std::fmt;
+use std::io::SeekFrom;
+
+use bytes::Bytes;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+use tokio::time::{Duration, Instant};
+use util::marshal::*;
+
+use crate::data_channel::DataChannel;
+use crate::error::{Error, Result};
+use file_message::*;
+
+/// Size of a chunk if [`Config::chunk_size`] is zero.
+pub const DEFAULT_CHUNK_SIZE: u32 = 16 * 1024;
+
+/// Number of times chunks are sent again if [`Config::max_retries`] is zero.
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// Config is used to configure a [`FileSender`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Config {
+    /// Number of bytes of a chunk, [`DEFAULT_CHUNK_SIZE`] if zero. A chunk plus a header of
+    /// 41 bytes has to fit into the maximum message size of the data channel.
+    pub chunk_size: u32,
+    /// Maximum number of bytes sent per second, no limit if zero.
+    pub rate_limit: u64,
+    /// Number of times the receiver may ask for chunks again, after one failed to verify,
+    /// before the transfer fails. [`DEFAULT_MAX_RETRIES`] if zero.
+    pub max_retries: u32,
+}
+
+/// Progress of a file transfer, passed to [`OnProgressFn`] after every chunk. Bytes skipped
+/// when resuming are counted as transferred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub transferred: u64,
+    pub total: u64,
+}
+
+pub type OnProgressFn = Box<dyn FnMut(Progress) + Send + Sync>;
+
+/// FileOffer describes a file a [`FileSender`] offers, see [`FileReceiver::recv_offer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileOffer {
+    pub name: String,
+    pub size: u64,
+    pub chunk_size: u32,
+}
+
+/// FileSender sends a file over a data channel to a [`FileReceiver`], in chunks which the
+/// receiver verifies against their SHA-256 hash. The receiver may resume an interrupted
+/// transfer with the chunks it doesn't have yet, and asks for all chunks from the first one
+/// failing to verify again.
+///
+/// A transfer reads all messages of the data channel, so it should be used for nothing else
+/// at the same time, and needs a reliable ordered one.
+pub struct FileSender {
+    config: Config,
+    on_progress: Option<OnProgressFn>,
+}
+
+/// FileReceiver receives a file sent by a [`FileSender`], see there.
+#[derive(Default)]
+pub struct FileReceiver {
+    on_progress: Option<OnProgressFn>,
+}
+
+impl FileSender {
+    pub fn new(config: Config) -> Self {
+        FileSender {
+            config,
+            on_progress: None,
+        }
+    }
+
+    /// on_progress sets an event handler which is invoked after every chunk sent.
+    pub fn on_progress(&mut self, f: OnProgressFn) {
+        self.on_progress = Some(f);
+    }
+
+    /// send offers the file read from reader under name, and sends the chunks the receiver
+    /// asks for. It returns once the receiver has verified all of them.
+    pub async fn send<R>(
+        &mut self,
+        data_channel: &DataChannel,
+        name: &str,
+        mut reader: R,
+    ) -> Result<()>
+    where
+        R: AsyncRead + AsyncSeek + Unpin,
+    {
+        let size = reader.seek(SeekFrom::End(0)).await?;
+        let chunk_size = match self.config.chunk_size {
+            0 => DEFAULT_CHUNK_SIZE,
+            chunk_size => chunk_size,
+        };
+        let max_retries = match self.config.max_retries {
+            0 => DEFAULT_MAX_RETRIES,
+            max_retries => max_retries,
+        };
+        let chunks = size.div_ceil(chunk_size as u64);
+
+        write_file_message(
+            data_channel,
+            FileMessage::Offer {
+                size,
+                chunk_size,
+                name: name.to_owned(),
+            },
+        )
+        .await?;
+
+        let mut buf = vec![0u8; FILE_CHUNK_HEADER_LEN + chunk_size as usize];
+        let mut next_chunk = match read_file_message(data_channel, &mut buf).await? {
+            FileMessage::Accept { next_chunk } => next_chunk.min(chunks),
+            message => return Err(Error::ErrUnexpectedFileMessage(message.message_type())),
+        };
+
+        let mut retries = 0;
+        loop {
+            self.send_chunks(
+                data_channel,
+                &mut reader,
+                &mut buf,
+                size,
+                chunk_size,
+                next_chunk,
+            )
+            .await?;
+            write_file_message(data_channel, FileMessage::Done).await?;
+
+            match read_file_message(data_channel, &mut buf).await? {
+                FileMessage::Complete => return Ok(()),
+                FileMessage::Retry { index } => {
+                    retries += 1;
+                    if retries > max_retries {
+                        return Err(Error::ErrFileTransferRetriesExceeded(index));
+                    }
+                    log::debug!("receiver of {} asked for chunks from {} again", name, index);
+                    next_chunk = index.min(chunks);
+                }
+                message => return Err(Error::ErrUnexpectedFileMessage(message.message_type())),
+            }
+        }
+    }
+
+    async fn send_chunks<R>(
+        &mut self,
+        data_channel: &DataChannel,
+        reader: &mut R,
+        buf: &mut [u8],
+        size: u64,
+        chunk_size: u32,
+        first_chunk: u64,
+    ) -> Result<()>
+    where
+        R: AsyncRead + AsyncSeek + Unpin,
+    {
+        let mut offset = first_chunk * chunk_size as u64;
+        reader.seek(SeekFrom::Start(offset)).await?;
+
+        let mut rate_limiter = RateLimiter::new(self.config.rate_limit);
+        let mut index = first_chunk;
+        while offset < size {
+            let len = std::cmp::min(chunk_size as u64, size - offset) as usize;
+            let data = &mut buf[..len];
+            reader.read_exact(data).await?;
+
+            rate_limiter.wait(len).await;
+            // Keep the data buffered for sending bounded, however large the file.
+            data_channel.writable().await?;
+            write_file_message(
+                data_channel,
+                FileMessage::Chunk {
+                    index,
+                    hash: Sha256::digest(&data[..]).into(),
+                    data: Bytes::copy_from_slice(data),
+                },
+            )
+            .await?;
+
+            index += 1;
+            offset += len as u64;
+            if let Some(f) = &mut self.on_progress {
+                f(Progress {
+                    transferred: offset,
+                    total: size,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for FileSender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileSender")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl FileReceiver {
+    pub fn new() -> Self {
+        FileReceiver::default()
+    }
+
+    /// on_progress sets an event handler which is invoked after every chunk received.
+    pub fn on_progress(&mut self, f: OnProgressFn) {
+        self.on_progress = Some(f);
+    }
+
+    /// recv_offer waits for the offer of a file, to be received with [`FileReceiver::receive`].
+    pub async fn recv_offer(&mut self, data_channel: &DataChannel) -> Result<FileOffer> {
+        let mut buf = vec![0u8; u16::MAX as usize];
+        match read_file_message(data_channel, &mut buf).await? {
+            FileMessage::Offer {
+                size,
+                chunk_size,
+                name,
+            } if chunk_size != 0 => Ok(FileOffer {
+                name,
+                size,
+                chunk_size,
+            }),
+            message => Err(Error::ErrUnexpectedFileMessage(message.message_type())),
+        }
+    }
+
+    /// receive accepts offer and writes the file to writer. If the first resume_from bytes of
+    /// an earlier transfer are in writer already, only the chunks from there on are sent again.
+    /// It returns once all chunks have been verified and written.
+    pub async fn receive<W>(
+        &mut self,
+        data_channel: &DataChannel,
+        offer: &FileOffer,
+        mut writer: W,
+        resume_from: u64,
+    ) -> Result<()>
+    where
+        W: AsyncWrite + AsyncSeek + Unpin,
+    {
+        let chunk_size = offer.chunk_size as u64;
+        let chunks = offer.size.div_ceil(chunk_size);
+        // Only whole chunks can be verified, so a partial last one is received again.
+        let mut next_chunk = std::cmp::min(resume_from, offer.size) / chunk_size;
+        writer
+            .seek(SeekFrom::Start(next_chunk * chunk_size))
+            .await?;
+        write_file_message(data_channel, FileMessage::Accept { next_chunk }).await?;
+
+        let mut buf = vec![0u8; FILE_CHUNK_HEADER_LEN + offer.chunk_size as usize];
+        let mut failed = false;
+        loop {
+            match read_file_message(data_channel, &mut buf).await? {
+                FileMessage::Chunk { index, hash, data } => {
+                    if failed {
+                        continue;
+                    }
+
+                    let offset = next_chunk * chunk_size;
+                    let len = std::cmp::min(chunk_size, offer.size.saturating_sub(offset));
+                    if index != next_chunk
+                        || data.len() as u64 != len
+                        || <[u8; FILE_HASH_LEN]>::from(Sha256::digest(&data)) != hash
+                    {
+                        log::warn!("chunk {} of {} failed to verify", next_chunk, offer.name);
+                        failed = true;
+                        continue;
+                    }
+
+                    writer.write_all(&data).await?;
+                    next_chunk += 1;
+                    if let Some(f) = &mut self.on_progress {
+                        f(Progress {
+                            transferred: offset + len,
+                            total: offer.size,
+                        });
+                    }
+                }
+                FileMessage::Done => {
+                    if failed || next_chunk < chunks {
+                        // Nothing was written from failed chunks on, so writer is at the
+                        // right position already.
+                        failed = false;
+                        write_file_message(data_channel, FileMessage::Retry { index: next_chunk })
+                            .await?;
+                        continue;
+                    }
+
+                    writer.flush().await?;
+                    return write_file_message(data_channel, FileMessage::Complete).await;
+                }
+                message => return Err(Error::ErrUnexpectedFileMessage(message.message_type())),
+            }
+        }
+    }
+}
+
+impl fmt::Debug for FileReceiver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileReceiver").finish()
+    }
+}
+
+/// RateLimiter delays sending so that no more than rate bytes are sent per second on average.
+struct RateLimiter {
+    rate: u64,
+    start: Instant,
+    sent: u64,
+}
+
+impl RateLimiter {
+    fn new(rate: u64) -> Self {
+        RateLimiter {
+            rate,
+            start: Instant::now(),
+            sent: 0,
+        }
+    }
+
+    async fn wait(&mut self, len: usize) {
+        if self.rate == 0 {
+            return;
+        }
+
+        // The first bytes go out right away, the ones after them once their time has come.
+        let due = Duration::from_secs_f64(self.sent as f64 / self.rate as f64);
+        self.sent += len as u64;
+        tokio::time::sleep_until(self.start + due).await;
+    }
+}
+
+async fn write_file_message(data_channel: &DataChannel, message: FileMessage) -> Result<()> {
+    let raw = message.marshal()?;
+    data_channel.write(&raw).await?;
+    Ok(())
+}
+
+async fn read_file_message(data_channel: &DataChannel, buf: &mut [u8]) -> Result<FileMessage> {
+    match data_channel.read_message(buf).await? {
+        Some((n, _)) => FileMessage::unmarshal(&mut &buf[..n]).map_err(Error::from_util),
+        None => Err(Error::ErrStreamClosed),
+    }
+}
@@ -0,0 +1,242 @@
+use bytes::{Buf, BufMut, Bytes};
+use util::marshal::*;
+
+use crate::error::Error;
+
+type Result<T> = std::result::Result<T, util::Error>;
+
+const FLOW_FRAME_TYPE_DATA: u8 = 0x00;
+const FLOW_FRAME_TYPE_CREDIT: u8 = 0x01;
+const FLOW_FRAME_TYPE_CLOSE: u8 = 0x02;
+
+const FLOW_FRAME_FLAG_OPENED_BY_SENDER: u8 = 0x01;
+
+pub(crate) const FLOW_FRAME_HEADER_LEN: usize = 6;
+const FLOW_FRAME_CREDIT_LEN: usize = 4;
+
+/// FlowId identifies a flow of a [`super::FlowMux`]. Both sides open flows independently, so
+/// an identifier is only unique together with the side which opened the flow.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub(crate) struct FlowId {
+    pub(crate) id: u32,
+    pub(crate) local: bool,
+}
+
+/// A frame sent over the data channel of a [`super::FlowMux`], one per message.
+///
+/// # Memory layout
+///
+/// ```plain
+///  0                   1                   2                   3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |   Frame Type  |     Flags     |            Flow ID            |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |    Flow ID (continued)        |                               |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+                               |
+/// |            Payload (DATA) or Credit (CREDIT, 32 bits)         |
+/// |                                                               |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// The flag 0x01 is set if the sender of the frame opened the flow.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub(crate) enum FlowFrame {
+    /// A message of the flow.
+    Data { flow: FlowId, payload: Bytes },
+    /// More bytes the receiver of the frame may send on the flow.
+    Credit { flow: FlowId, credit: u32 },
+    /// The sender of the frame won't send on the flow anymore.
+    Close { flow: FlowId },
+}
+
+impl FlowFrame {
+    pub(crate) fn flow(&self) -> FlowId {
+        match self {
+            FlowFrame::Data { flow, .. }
+            | FlowFrame::Credit { flow, .. }
+            | FlowFrame::Close { flow } => *flow,
+        }
+    }
+}
+
+impl MarshalSize for FlowFrame {
+    fn marshal_size(&self) -> usize {
+        FLOW_FRAME_HEADER_LEN
+            + match self {
+                FlowFrame::Data { payload, .. } => payload.len(),
+                FlowFrame::Credit { .. } => FLOW_FRAME_CREDIT_LEN,
+                FlowFrame::Close { .. } => 0,
+            }
+    }
+}
+
+impl Marshal for FlowFrame {
+    fn marshal_to(&self, mut buf: &mut [u8]) -> Result<usize> {
+        let required_len = self.marshal_size();
+        if buf.remaining_mut() < required_len {
+            return Err(Error::UnexpectedEndOfBuffer {
+                expected: required_len,
+                actual: buf.remaining_mut(),
+            }
+            .into());
+        }
+
+        let frame_type = match self {
+            FlowFrame::Data { .. } => FLOW_FRAME_TYPE_DATA,
+            FlowFrame::Credit { .. } => FLOW_FRAME_TYPE_CREDIT,
+            FlowFrame::Close { .. } => FLOW_FRAME_TYPE_CLOSE,
+        };
+        let flow = self.flow();
+
+        buf.put_u8(frame_type);
+        // The flag tells the receiver whether the flow is its own or not.
+        buf.put_u8(if flow.local {
+            FLOW_FRAME_FLAG_OPENED_BY_SENDER
+        } else {
+            0
+        });
+        buf.put_u32(flow.id);
+        match self {
+            FlowFrame::Data { payload, .. } => buf.put_slice(payload),
+            FlowFrame::Credit { credit, .. } => buf.put_u32(*credit),
+            FlowFrame::Close { .. } => {}
+        }
+
+        Ok(required_len)
+    }
+}
+
+impl Unmarshal for FlowFrame {
+    fn unmarshal<B>(buf: &mut B) -> Result<Self>
+    where
+        Self: Sized,
+        B: Buf,
+    {
+        if buf.remaining() < FLOW_FRAME_HEADER_LEN {
+            return Err(Error::UnexpectedEndOfBuffer {
+                expected: FLOW_FRAME_HEADER_LEN,
+                actual: buf.remaining(),
+            }
+            .into());
+        }
+
+        let frame_type = buf.get_u8();
+        let flags = buf.get_u8();
+        let flow = FlowId {
+            id: buf.get_u32(),
+            // A flow opened by the sender is a remote one for the receiver.
+            local: flags & FLOW_FRAME_FLAG_OPENED_BY_SENDER == 0,
+        };
+
+        match frame_type {
+            FLOW_FRAME_TYPE_DATA => Ok(FlowFrame::Data {
+                flow,
+                payload: buf.copy_to_bytes(buf.remaining()),
+            }),
+            FLOW_FRAME_TYPE_CREDIT => {
+                if buf.remaining() < FLOW_FRAME_CREDIT_LEN {
+                    return Err(Error::UnexpectedEndOfBuffer {
+                        expected: FLOW_FRAME_CREDIT_LEN,
+                        actual: buf.remaining(),
+                    }
+                    .into());
+                }
+                Ok(FlowFrame::Credit {
+                    flow,
+                    credit: buf.get_u32(),
+                })
+            }
+            FLOW_FRAME_TYPE_CLOSE => Ok(FlowFrame::Close { flow }),
+            _ => Err(Error::InvalidFlowFrameType(frame_type).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flow_frame_round_trip() -> Result<()> {
+        let local = FlowId { id: 7, local: true };
+        let remote = FlowId {
+            id: 7,
+            local: false,
+        };
+        let frames = [
+            FlowFrame::Data {
+                flow: local,
+                payload: Bytes::from_static(b"hello"),
+            },
+            FlowFrame::Credit {
+                flow: remote,
+                credit: 65536,
+            },
+            FlowFrame::Close { flow: local },
+        ];
+
+        for frame in frames {
+            let mut raw = frame.marshal()?;
+            let parsed = FlowFrame::unmarshal(&mut raw)?;
+
+            // The receiver sees the flow from the other side.
+            let flow = frame.flow();
+            let flow = FlowId {
+                local: !flow.local,
+                ..flow
+            };
+            let expected = match frame {
+                FlowFrame::Data { payload, .. } => FlowFrame::Data { flow, payload },
+                FlowFrame::Credit { credit, .. } => FlowFrame::Credit { flow, credit },
+                FlowFrame::Close { .. } => FlowFrame::Close { flow },
+            };
+            assert_eq!(parsed, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_flow_frame_marshal() -> Result<()> {
+        let frame = FlowFrame::Credit {
+            flow: FlowId {
+                id: 0x01020304,
+                local: true,
+            },
+            credit: 0x00010000,
+        };
+        let raw = frame.marshal()?;
+        assert_eq!(
+            &raw[..],
+            &[0x01, 0x01, 0x01, 0x02, 0x03, 0x04, 0x00, 0x01, 0x00, 0x00]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_flow_frame_unmarshal_invalid() {
+        let mut raw = Bytes::from_static(&[0x00, 0x00, 0x00]);
+        let err = FlowFrame::unmarshal(&mut raw).expect_err("header should be too short");
+        assert_eq!(
+            Error::UnexpectedEndOfBuffer {
+                expected: FLOW_FRAME_HEADER_LEN,
+                actual: 3
+            },
+            err
+        );
+
+        let mut raw = Bytes::from_static(&[0x01, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00]);
+        let err = FlowFrame::unmarshal(&mut raw).expect_err("credit should be too short");
+        assert_eq!(
+            Error::UnexpectedEndOfBuffer {
+                expected: FLOW_FRAME_CREDIT_LEN,
+                actual: 1
+            },
+            err
+        );
+
+        let mut raw = Bytes::from_static(&[0x09, 0x00, 0x00, 0x00, 0x00, 0x01]);
+        let err = FlowFrame::unmarshal(&mut raw).expect_err("type should be unknown");
+        assert_eq!(Error::InvalidFlowFrameType(0x09), err);
+    }
+}
@@ -0,0 +1,172 @@
+use sctp::association::Association;
+use sctp::congestion::CongestionControlAlgorithm;
+use sctp::stream::StreamScheduler;
+use tokio::task::JoinHandle;
+use tokio::time::{timeout, Duration};
+use util::conn::conn_bridge::*;
+use util::conn::*;
+
+use super::*;
+use crate::data_channel::Config as DataChannelConfig;
+
+fn association_config(
+    net_conn: Arc<dyn Conn + Send + Sync>,
+    name: &str,
+) -> sctp::association::Config {
+    sctp::association::Config {
+        net_conn,
+        max_receive_buffer_size: 0,
+        max_message_size: 0,
+        name: name.to_owned(),
+        stream_scheduler: StreamScheduler::default(),
+        congestion_control: CongestionControlAlgorithm::default(),
+        sack_delay: Duration::ZERO,
+        packets_per_sack: 0,
+        immediate_sack: false,
+        zero_checksum: false,
+        max_num_outbound_streams: 0,
+        max_num_inbound_streams: 0,
+        heartbeat_interval: Duration::ZERO,
+        path_max_retransmits: 0,
+        association_max_retransmits: 0,
+        ecn: false,
+        max_send_buffer_size: 0,
+    }
+}
+
+/// create_flow_mux_pair connects two FlowMuxes over a bridge which is ticked in the
+/// background until the returned task is aborted.
+async fn create_flow_mux_pair(
+    config: Config,
+) -> Result<(
+    FlowMux,
+    FlowMux,
+    Arc<Association>,
+    Arc<Association>,
+    JoinHandle<()>,
+)> {
+    let (br, ca, cb) = Bridge::new(0, None, None);
+    let ticker = tokio::spawn(async move {
+        loop {
+            br.tick().await;
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    });
+
+    let (a0, a1) = tokio::try_join!(
+        Association::client(association_config(Arc::new(ca), "client")),
+        Association::server(association_config(Arc::new(cb), "server")),
+    )?;
+    let (a0, a1) = (Arc::new(a0), Arc::new(a1));
+
+    let cfg = DataChannelConfig {
+        label: "flows".to_owned(),
+        ..Default::default()
+    };
+    let dc0 = DataChannel::dial(&a0, 1, cfg).await?;
+    let existing_data_channels: Vec<DataChannel> = Vec::new();
+    let dc1 =
+        DataChannel::accept(&a1, DataChannelConfig::default(), &existing_data_channels).await?;
+
+    Ok((
+        FlowMux::new(Arc::new(dc0), config),
+        FlowMux::new(Arc::new(dc1), config),
+        a0,
+        a1,
+        ticker,
+    ))
+}
+
+#[tokio::test]
+async fn test_flow_request_response() -> Result<()> {
+    let (mux0, mux1, a0, a1, ticker) = create_flow_mux_pair(Config::default()).await?;
+
+    let requests = [mux0.open_flow().await?, mux0.open_flow().await?];
+    for (i, flow) in requests.iter().enumerate() {
+        flow.send(Bytes::from(format!("request {i}"))).await?;
+        flow.close().await?;
+    }
+
+    for _ in 0..requests.len() {
+        let flow = mux1.accept_flow().await.expect("a flow should be accepted");
+        assert!(!flow.is_local());
+        let request = flow.recv().await.expect("a request should be received");
+        assert_eq!(request, Bytes::from(format!("request {}", flow.id())));
+        assert_eq!(flow.recv().await, None, "the peer closed the flow");
+
+        flow.send(Bytes::from(format!("response {}", flow.id())))
+            .await?;
+        flow.close().await?;
+    }
+
+    for (i, flow) in requests.iter().enumerate() {
+        assert!(flow.is_local());
+        assert_eq!(
+            flow.recv().await,
+            Some(Bytes::from(format!("response {i}")))
+        );
+        assert_eq!(flow.recv().await, None);
+        assert_eq!(
+            flow.send(Bytes::from_static(b"late")).await,
+            Err(Error::ErrFlowClosed)
+        );
+    }
+    assert!(
+        mux0.inner.flows.lock().is_empty(),
+        "closed flows should be removed"
+    );
+
+    a0.close().await?;
+    a1.close().await?;
+    ticker.abort();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_flow_backpressure() -> Result<()> {
+    let (mux0, mux1, a0, a1, ticker) = create_flow_mux_pair(Config {
+        window: INITIAL_CREDIT,
+        max_message_size: 30000,
+    })
+    .await?;
+
+    let slow = mux0.open_flow().await?;
+    let message = Bytes::from(vec![0u8; 30000]);
+    slow.send(message.clone()).await?;
+    slow.send(message.clone()).await?;
+    assert_eq!(slow.credit(), INITIAL_CREDIT as u64 - 60000);
+    assert!(
+        timeout(Duration::from_millis(100), slow.send(message.clone()))
+            .await
+            .is_err(),
+        "a send beyond the credit should wait"
+    );
+
+    // Another flow isn't held back by the slow one.
+    let fast = mux0.open_flow().await?;
+    fast.send(message.clone()).await?;
+
+    let remote_slow = mux1.accept_flow().await.expect("a flow should be accepted");
+    let remote_fast = mux1.accept_flow().await.expect("a flow should be accepted");
+    assert_eq!(remote_fast.recv().await, Some(message.clone()));
+
+    assert_eq!(remote_slow.recv().await, Some(message.clone()));
+    assert_eq!(remote_slow.recv().await, Some(message.clone()));
+    timeout(Duration::from_secs(5), slow.send(message.clone()))
+        .await
+        .expect("the consumed credit should be granted again")?;
+    assert_eq!(remote_slow.recv().await, Some(message.clone()));
+
+    assert_eq!(
+        slow.send(Bytes::from(vec![0u8; 30001])).await,
+        Err(Error::ErrFlowMessageTooLarge {
+            len: 30001,
+            max: 30000
+        })
+    );
+
+    a0.close().await?;
+    a1.close().await?;
+    ticker.abort();
+    Ok(())
+}
@@ -0,0 +1,435 @@
+#[cfg(test)]
+mod flow_test;
+
+mod flow_frame;
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use portable_atomic::{AtomicBool, AtomicU32};
+use std::sync::atomic::Ordering;
+use tokio::sync::{mpsc, Mutex, Notify};
+use util::marshal::*;
+use util::sync::Mutex as SyncMutex;
+
+use crate::data_channel::DataChannel;
+use crate::error::{Error, Result};
+use flow_frame::*;
+
+/// Number of bytes a side may send on a new flow before the receiver granted any credit.
+pub const INITIAL_CREDIT: u32 = 65536;
+
+/// Receive window of a flow if [`Config::window`] is zero.
+pub const DEFAULT_WINDOW: u32 = 256 * 1024;
+
+/// Largest message of a flow if [`Config::max_message_size`] is zero, so that a frame fits
+/// into the default maximum message size of an SCTP association.
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 65536 - FLOW_FRAME_HEADER_LEN;
+
+/// Config is used to configure a [`FlowMux`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Config {
+    /// How many bytes the peer may have in flight on each flow, not yet consumed by
+    /// [`Flow::recv`]. [`DEFAULT_WINDOW`] if zero, and at least [`INITIAL_CREDIT`].
+    pub window: u32,
+    /// Largest message [`Flow::send`] accepts, [`DEFAULT_MAX_MESSAGE_SIZE`] if zero, and at most
+    /// [`INITIAL_CREDIT`]. Both sides should use the same value, as a larger frame fails the
+    /// data channel of the receiver.
+    pub max_message_size: usize,
+}
+
+/// FlowMux carries any number of flows over a single data channel. Each flow is a sequence
+/// of messages in both directions with its own credit-based flow control: a side only sends
+/// as many bytes as the receiver granted, and the receiver grants them again once they are
+/// consumed. A slow flow thus holds back its own sender without filling the SCTP buffer of
+/// the data channel for all others.
+///
+/// The FlowMux reads all messages of the data channel itself, so it must not be read from
+/// anywhere else. Messages are ordered within a flow only if the data channel is ordered.
+pub struct FlowMux {
+    inner: Arc<FlowMuxInner>,
+    accept_rx: Mutex<mpsc::UnboundedReceiver<Flow>>,
+}
+
+struct FlowMuxInner {
+    data_channel: Arc<DataChannel>,
+    window: u32,
+    max_message_size: usize,
+    next_id: AtomicU32,
+    flows: SyncMutex<HashMap<FlowId, Arc<FlowState>>>,
+    closed: AtomicBool,
+}
+
+struct FlowState {
+    id: FlowId,
+    send: SyncMutex<SendState>,
+    recv: SyncMutex<RecvState>,
+    /// Notified whenever credit is granted or a half of the flow is closed.
+    notify: Notify,
+}
+
+struct SendState {
+    /// Number of bytes the peer still accepts.
+    credit: u64,
+    closed: bool,
+}
+
+struct RecvState {
+    queue: VecDeque<Bytes>,
+    /// Number of bytes the peer may still send.
+    remaining: u64,
+    /// Number of bytes consumed since credit was last granted.
+    consumed: u64,
+    closed: bool,
+}
+
+/// Flow is a flow of a [`FlowMux`], see there.
+pub struct Flow {
+    state: Arc<FlowState>,
+    mux: Arc<FlowMuxInner>,
+}
+
+impl FlowMux {
+    /// new starts to carry flows over data_channel.
+    pub fn new(data_channel: Arc<DataChannel>, config: Config) -> Self {
+        let window = match config.window {
+            0 => DEFAULT_WINDOW,
+            window => window.max(INITIAL_CREDIT),
+        };
+        let max_message_size = match config.max_message_size {
+            0 => DEFAULT_MAX_MESSAGE_SIZE,
+            max_message_size => max_message_size.min(INITIAL_CREDIT as usize),
+        };
+
+        let inner = Arc::new(FlowMuxInner {
+            data_channel,
+            window,
+            max_message_size,
+            next_id: AtomicU32::new(0),
+            flows: SyncMutex::new(HashMap::new()),
+            closed: AtomicBool::new(false),
+        });
+
+        let (accept_tx, accept_rx) = mpsc::unbounded_channel();
+        tokio::spawn(FlowMuxInner::read_loop(Arc::clone(&inner), accept_tx));
+
+        FlowMux {
+            inner,
+            accept_rx: Mutex::new(accept_rx),
+        }
+    }
+
+    /// open_flow opens a new flow. The peer accepts it with its first frame.
+    pub async fn open_flow(&self) -> Result<Flow> {
+        if self.inner.closed.load(Ordering::SeqCst) {
+            return Err(Error::ErrFlowClosed);
+        }
+
+        let id = FlowId {
+            id: self.inner.next_id.fetch_add(1, Ordering::SeqCst),
+            local: true,
+        };
+        let state = self.inner.add_flow(id);
+        self.inner.grant_initial_window(id).await?;
+
+        Ok(Flow {
+            state,
+            mux: Arc::clone(&self.inner),
+        })
+    }
+
+    /// accept_flow waits for the next flow opened by the peer. It returns `None` once the
+    /// data channel is closed.
+    pub async fn accept_flow(&self) -> Option<Flow> {
+        self.accept_rx.lock().await.recv().await
+    }
+
+    /// window returns the receive window of each flow.
+    pub fn window(&self) -> u32 {
+        self.inner.window
+    }
+
+    /// max_message_size returns the largest message a flow sends.
+    pub fn max_message_size(&self) -> usize {
+        self.inner.max_message_size
+    }
+}
+
+impl fmt::Debug for FlowMux {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlowMux")
+            .field("window", &self.inner.window)
+            .field("max_message_size", &self.inner.max_message_size)
+            .field("flows", &self.inner.flows.lock().len())
+            .finish()
+    }
+}
+
+impl FlowMuxInner {
+    fn add_flow(&self, id: FlowId) -> Arc<FlowState> {
+        let state = Arc::new(FlowState {
+            id,
+            send: SyncMutex::new(SendState {
+                credit: INITIAL_CREDIT as u64,
+                closed: false,
+            }),
+            recv: SyncMutex::new(RecvState {
+                queue: VecDeque::new(),
+                remaining: self.window as u64,
+                consumed: 0,
+                closed: false,
+            }),
+            notify: Notify::new(),
+        });
+        self.flows.lock().insert(id, Arc::clone(&state));
+        state
+    }
+
+    /// grant_initial_window lets the peer use all of the window of a new flow, beyond the
+    /// initial credit.
+    async fn grant_initial_window(&self, flow: FlowId) -> Result<()> {
+        let credit = self.window - INITIAL_CREDIT;
+        if credit == 0 {
+            return Ok(());
+        }
+        self.write_frame(FlowFrame::Credit { flow, credit }).await
+    }
+
+    async fn write_frame(&self, frame: FlowFrame) -> Result<()> {
+        let raw = frame.marshal()?;
+        self.data_channel.write(&raw).await?;
+        Ok(())
+    }
+
+    fn remove_if_closed(&self, state: &FlowState) {
+        if state.send.lock().closed && state.recv.lock().closed {
+            self.flows.lock().remove(&state.id);
+        }
+    }
+
+    async fn read_loop(inner: Arc<FlowMuxInner>, accept_tx: mpsc::UnboundedSender<Flow>) {
+        let mut buf = vec![0u8; inner.max_message_size + FLOW_FRAME_HEADER_LEN];
+        loop {
+            let n = match inner.data_channel.read_message(&mut buf).await {
+                Ok(Some((n, _))) => n,
+                Ok(None) => break,
+                Err(err) => {
+                    log::debug!("FlowMux failed to read: {}", err);
+                    break;
+                }
+            };
+
+            let frame = match FlowFrame::unmarshal(&mut &buf[..n]) {
+                Ok(frame) => frame,
+                Err(err) => {
+                    log::warn!("FlowMux discarded a frame: {}", err);
+                    continue;
+                }
+            };
+
+            inner.handle_frame(frame, &accept_tx).await;
+        }
+
+        inner.closed.store(true, Ordering::SeqCst);
+        let flows: Vec<Arc<FlowState>> = inner.flows.lock().drain().map(|(_, s)| s).collect();
+        for state in flows {
+            state.send.lock().closed = true;
+            state.recv.lock().closed = true;
+            state.notify.notify_waiters();
+        }
+    }
+
+    async fn handle_frame(
+        self: &Arc<Self>,
+        frame: FlowFrame,
+        accept_tx: &mpsc::UnboundedSender<Flow>,
+    ) {
+        let id = frame.flow();
+        let existing = self.flows.lock().get(&id).cloned();
+        let state = match existing {
+            Some(state) => state,
+            None if id.local => {
+                log::debug!("FlowMux discarded a frame for closed flow {}", id.id);
+                return;
+            }
+            None => {
+                let state = self.add_flow(id);
+                let _ = accept_tx.send(Flow {
+                    state: Arc::clone(&state),
+                    mux: Arc::clone(self),
+                });
+                if let Err(err) = self.grant_initial_window(id).await {
+                    log::debug!(
+                        "FlowMux failed to grant the window of flow {}: {}",
+                        id.id,
+                        err
+                    );
+                }
+                state
+            }
+        };
+
+        match frame {
+            FlowFrame::Data { payload, .. } => {
+                let mut recv = state.recv.lock();
+                if recv.closed {
+                    return;
+                }
+                if payload.len() as u64 > recv.remaining {
+                    log::warn!(
+                        "FlowMux closed flow {} whose peer sent {} bytes beyond its credit",
+                        id.id,
+                        payload.len() as u64 - recv.remaining
+                    );
+                    recv.closed = true;
+                    recv.queue.clear();
+                } else {
+                    recv.remaining -= payload.len() as u64;
+                    recv.queue.push_back(payload);
+                }
+            }
+            FlowFrame::Credit { credit, .. } => {
+                let mut send = state.send.lock();
+                send.credit = send.credit.saturating_add(credit as u64);
+            }
+            FlowFrame::Close { .. } => {
+                state.recv.lock().closed = true;
+            }
+        }
+        state.notify.notify_waiters();
+        self.remove_if_closed(&state);
+    }
+}
+
+impl Flow {
+    /// id returns the identifier of the flow, unique among the flows opened by the same side.
+    pub fn id(&self) -> u32 {
+        self.state.id.id
+    }
+
+    /// is_local returns true if the flow was opened by this side.
+    pub fn is_local(&self) -> bool {
+        self.state.id.local
+    }
+
+    /// credit returns how many more bytes may be sent before the peer grants more.
+    pub fn credit(&self) -> u64 {
+        self.state.send.lock().credit
+    }
+
+    /// send sends a message on the flow, waiting as long as the peer didn't grant enough
+    /// credit for it.
+    pub async fn send(&self, data: Bytes) -> Result<()> {
+        if data.len() > self.mux.max_message_size {
+            return Err(Error::ErrFlowMessageTooLarge {
+                len: data.len(),
+                max: self.mux.max_message_size,
+            });
+        }
+
+        loop {
+            let notified = self.state.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            {
+                let mut send = self.state.send.lock();
+                if send.closed {
+                    return Err(Error::ErrFlowClosed);
+                }
+                if send.credit >= data.len() as u64 {
+                    send.credit -= data.len() as u64;
+                    break;
+                }
+            }
+            notified.await;
+        }
+
+        self.mux
+            .write_frame(FlowFrame::Data {
+                flow: self.state.id,
+                payload: data,
+            })
+            .await
+    }
+
+    /// recv waits for the next message of the flow. It returns `None` once the peer closed
+    /// the flow and all its messages were received.
+    pub async fn recv(&self) -> Option<Bytes> {
+        let (data, credit) = loop {
+            let notified = self.state.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            {
+                let mut recv = self.state.recv.lock();
+                if let Some(data) = recv.queue.pop_front() {
+                    recv.consumed += data.len() as u64;
+                    // Granting credit in halves of the window saves frames, but all of it is
+                    // granted once nothing is left, so a sender can't wait for good.
+                    let credit = if !recv.closed
+                        && (recv.consumed >= self.mux.window as u64 / 2
+                            || (recv.queue.is_empty() && recv.consumed != 0))
+                    {
+                        let credit = recv.consumed;
+                        recv.consumed = 0;
+                        recv.remaining += credit;
+                        credit
+                    } else {
+                        0
+                    };
+                    break (data, credit);
+                }
+                if recv.closed {
+                    return None;
+                }
+            }
+            notified.await;
+        };
+
+        if credit != 0 {
+            let frame = FlowFrame::Credit {
+                flow: self.state.id,
+                credit: credit as u32,
+            };
+            if let Err(err) = self.mux.write_frame(frame).await {
+                log::debug!("Flow {} failed to grant credit: {}", self.state.id.id, err);
+            }
+        }
+        Some(data)
+    }
+
+    /// close tells the peer that no more messages are sent on the flow. Messages of the peer
+    /// can still be received until it closes the flow as well.
+    pub async fn close(&self) -> Result<()> {
+        {
+            let mut send = self.state.send.lock();
+            if send.closed {
+                return Ok(());
+            }
+            send.closed = true;
+        }
+        self.state.notify.notify_waiters();
+
+        let result = self
+            .mux
+            .write_frame(FlowFrame::Close {
+                flow: self.state.id,
+            })
+            .await;
+        self.mux.remove_if_closed(&self.state);
+        result
+    }
+}
+
+impl fmt::Debug for Flow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Flow")
+            .field("id", &self.state.id.id)
+            .field("local", &self.state.id.local)
+            .field("credit", &self.credit())
+            .finish()
+    }
+}
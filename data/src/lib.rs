@@ -1,8 +1,11 @@
 #![warn(rust_2018_idioms)]
 #![allow(dead_code)]
 
+pub mod broadcast;
 pub mod data_channel;
 mod error;
+pub mod file_transfer;
+pub mod flow;
 pub mod message;
 
 pub use error::Error;
@@ -160,8 +160,23 @@ impl MarshalSize for DataChannelOpen {
     }
 }
 
+impl DataChannelOpen {
+    /// validate checks that the label and protocol fit into their 16 bit length fields.
+    pub fn validate(&self) -> std::result::Result<(), Error> {
+        if self.label.len() > u16::MAX as usize {
+            return Err(Error::ErrLabelTooLong(self.label.len()));
+        }
+        if self.protocol.len() > u16::MAX as usize {
+            return Err(Error::ErrProtocolTooLong(self.protocol.len()));
+        }
+        Ok(())
+    }
+}
+
 impl Marshal for DataChannelOpen {
     fn marshal_to(&self, mut buf: &mut [u8]) -> Result<usize> {
+        self.validate()?;
+
         let required_len = self.marshal_size();
         if buf.remaining_mut() < required_len {
             return Err(Error::UnexpectedEndOfBuffer {
@@ -438,4 +453,31 @@ mod tests {
         assert_eq!(&bytes[..], &MARSHALED_BYTES);
         Ok(())
     }
+
+    #[test]
+    fn test_channel_open_marshal_too_long() -> Result<()> {
+        let mut channel_open = DataChannelOpen {
+            channel_type: ChannelType::Reliable,
+            priority: 0,
+            reliability_parameter: 0,
+            label: vec![b'a'; u16::MAX as usize + 1],
+            protocol: vec![],
+        };
+
+        let err = channel_open
+            .marshal()
+            .expect_err("label should be too long");
+        assert_eq!(Error::ErrLabelTooLong(u16::MAX as usize + 1), err);
+
+        channel_open.label = vec![b'a'; u16::MAX as usize];
+        channel_open.protocol = vec![b'b'; u16::MAX as usize + 1];
+        let err = channel_open
+            .marshal()
+            .expect_err("protocol should be too long");
+        assert_eq!(Error::ErrProtocolTooLong(u16::MAX as usize + 1), err);
+
+        channel_open.protocol = vec![b'b'; u16::MAX as usize];
+        assert!(channel_open.marshal().is_ok());
+        Ok(())
+    }
 }
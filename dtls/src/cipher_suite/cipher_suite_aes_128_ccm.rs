@@ -82,7 +82,7 @@ impl CipherSuite for CipherSuiteAes128Ccm {
                 &keys.client_write_iv,
                 &keys.server_write_key,
                 &keys.server_write_iv,
-            ));
+            )?);
         } else {
             self.ccm = Some(CryptoCcm::new(
                 &self.crypto_ccm_tag_len,
@@ -90,7 +90,7 @@ impl CipherSuite for CipherSuiteAes128Ccm {
                 &keys.server_write_iv,
                 &keys.client_write_key,
                 &keys.client_write_iv,
-            ));
+            )?);
         }
 
         Ok(())
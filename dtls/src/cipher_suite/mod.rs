@@ -21,6 +21,7 @@ use cipher_suite_tls_psk_with_aes_128_gcm_sha256::*;
 use super::client_certificate_type::*;
 use super::error::*;
 use super::record_layer::record_layer_header::*;
+use super::signature_hash_algorithm::HashAlgorithm;
 
 // CipherSuiteID is an ID for our supported CipherSuites
 // Supported Cipher Suites
@@ -112,6 +113,12 @@ impl CipherSuiteHash {
             CipherSuiteHash::Sha256 => 32,
         }
     }
+
+    pub(crate) fn hash_algorithm(&self) -> HashAlgorithm {
+        match *self {
+            CipherSuiteHash::Sha256 => HashAlgorithm::Sha256,
+        }
+    }
 }
 
 pub trait CipherSuite {
@@ -5,8 +5,12 @@ use tokio::time::Duration;
 use crate::cipher_suite::*;
 use crate::crypto::*;
 use crate::error::*;
+use crate::extension::extension_max_fragment_length::MaxFragmentLength;
+use crate::extension::extension_record_size_limit::*;
+use crate::extension::extension_supported_ekt_ciphers::EktCipher;
 use crate::extension::extension_use_srtp::SrtpProtectionProfile;
-use crate::handshaker::VerifyPeerCertificateFn;
+use crate::handshake_observer::HandshakeObserver;
+use crate::handshaker::{VerifyOcspResponseFn, VerifyPeerCertificateFn};
 use crate::signature_hash_algorithm::SignatureScheme;
 
 /// Config is used to configure a DTLS client or server.
@@ -30,6 +34,12 @@ pub struct Config {
     /// Servers will assert that clients send one of these profiles and will respond as needed
     pub srtp_protection_profiles: Vec<SrtpProtectionProfile>,
 
+    /// ekt_ciphers are the supported Encrypted Key Transport ciphers (RFC 8870)
+    /// Clients will send this via supported_ekt_ciphers and accept the server's selection
+    /// Servers will select the first client cipher they also support, or omit the extension
+    /// If empty, EKT is not negotiated
+    pub ekt_ciphers: Vec<EktCipher>,
+
     /// client_auth determines the server's policy for
     /// TLS Client Authentication. The default is NoClientCert.
     pub client_auth: ClientAuthType,
@@ -40,8 +50,22 @@ pub struct Config {
 
     /// flight_interval controls how often we send outbound handshake messages
     /// defaults to time.Second
+    /// It is the initial retransmission timeout of every flight.
     pub flight_interval: Duration,
 
+    /// max_flight_interval is the upper bound of the retransmission timeout.
+    /// Each time a flight is retransmitted the timeout is doubled, up to this
+    /// value, as described in RFC 6347 Section 4.2.4.1. The timeout is reset
+    /// to flight_interval once the handshake progresses to the next flight.
+    /// If it is not greater than flight_interval, the timeout stays constant
+    /// (default).
+    pub max_flight_interval: Duration,
+
+    /// flight_interval_jitter adds a random delay between zero and this value to
+    /// every retransmission timeout, so that many peers started at the same
+    /// time do not retransmit in lockstep. (default is no jitter)
+    pub flight_interval_jitter: Duration,
+
     /// psk sets the pre-shared key used by this DTLS connection
     /// If psk is non-nil only psk cipher_suites will be used
     pub psk: Option<PskCallback>,
@@ -75,6 +99,14 @@ pub struct Config {
     /// be considered but the verifiedChains will always be nil.
     pub verify_peer_certificate: Option<VerifyPeerCertificateFn>,
 
+    /// verify_ocsp_response, if not nil, makes a client request OCSP stapling
+    /// via the status_request extension (RFC 6066). It is called after
+    /// verify_peer_certificate with the certificates provided by the server and
+    /// the DER encoded OCSP response stapled to them, which is empty if the
+    /// server did not staple one. If it returns a non-nil error, the handshake
+    /// is aborted and that error results.
+    pub verify_ocsp_response: Option<VerifyOcspResponseFn>,
+
     /// roots_cas defines the set of root certificate authorities
     /// that one peer uses when verifying the other peer's certificates.
     /// If RootCAs is nil, TLS uses the host's root CA set.
@@ -100,6 +132,45 @@ pub struct Config {
     /// Packet with sequence number older than this value compared to the latest
     /// accepted packet will be discarded. (default is 64)
     pub replay_protection_window: usize,
+
+    /// insecure_skip_hello_verify disables the cookie exchange on a server, so
+    /// ServerHello is sent in response to the first ClientHello without a
+    /// HelloVerifyRequest round trip.
+    /// This saves one round trip, but the server no longer verifies that the
+    /// client owns its source address, which exposes it to address spoofing
+    /// and amplification attacks. It should only be used when the transport
+    /// already provides that guarantee, e.g. behind ICE connectivity checks.
+    pub insecure_skip_hello_verify: bool,
+
+    /// supported_protocols is the list of application protocols offered via the
+    /// ALPN extension, in order of preference.
+    /// Clients send this list in the ClientHello. Servers select the first entry
+    /// of their own list that the client also offered, and abort the handshake
+    /// with a no_application_protocol alert if there is no overlap.
+    /// If empty, ALPN is not negotiated.
+    pub supported_protocols: Vec<String>,
+
+    /// record_size_limit is the largest record plaintext this endpoint is willing
+    /// to receive, advertised via the record_size_limit extension (RFC 8449).
+    /// The limit advertised by the peer is honored when sending application data
+    /// regardless of this value. Must be at least 64, 0 disables the extension.
+    pub record_size_limit: u16,
+
+    /// max_fragment_length is requested by clients via the legacy
+    /// max_fragment_length extension (RFC 6066) when set. Servers always accept
+    /// the client's request unless record_size_limit was offered as well.
+    pub max_fragment_length: Option<MaxFragmentLength>,
+
+    /// ocsp_response is the DER encoded OCSP response a server staples to its
+    /// certificate when the client requests it via the status_request extension.
+    /// The application is responsible for keeping it fresh. If empty, no
+    /// response is stapled.
+    pub ocsp_response: Vec<u8>,
+
+    /// handshake_observer, if not nil, receives structured events for every
+    /// step of the handshake: flights sent and received, the selected cipher
+    /// suite, negotiated extensions and alerts.
+    pub handshake_observer: Option<Arc<dyn HandshakeObserver>>,
 }
 
 impl Default for Config {
@@ -109,20 +180,30 @@ impl Default for Config {
             cipher_suites: vec![],
             signature_schemes: vec![],
             srtp_protection_profiles: vec![],
+            ekt_ciphers: vec![],
             client_auth: ClientAuthType::default(),
             extended_master_secret: ExtendedMasterSecretType::default(),
             flight_interval: Duration::default(),
+            max_flight_interval: Duration::default(),
+            flight_interval_jitter: Duration::default(),
             psk: None,
             psk_identity_hint: None,
             insecure_skip_verify: false,
             insecure_hashes: false,
             insecure_verification: false,
             verify_peer_certificate: None,
+            verify_ocsp_response: None,
             roots_cas: rustls::RootCertStore::empty(),
             client_cas: rustls::RootCertStore::empty(),
             server_name: String::default(),
             mtu: 0,
             replay_protection_window: 0,
+            insecure_skip_hello_verify: false,
+            supported_protocols: vec![],
+            record_size_limit: 0,
+            max_fragment_length: None,
+            ocsp_response: vec![],
+            handshake_observer: None,
         }
     }
 }
@@ -135,7 +216,7 @@ pub(crate) type PskCallback = Arc<dyn (Fn(&[u8]) -> Result<Vec<u8>>) + Send + Sy
 
 // ClientAuthType declares the policy the server will follow for
 // TLS Client Authentication.
-#[derive(Default, Copy, Clone, PartialEq, Eq)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ClientAuthType {
     #[default]
     NoClientCert = 0,
@@ -180,6 +261,14 @@ pub(crate) fn validate_config(is_client: bool, config: &Config) -> Result<()> {
         }
     }
 
+    if config.record_size_limit != 0 && config.record_size_limit < RECORD_SIZE_LIMIT_MIN {
+        return Err(Error::ErrInvalidRecordSizeLimit);
+    }
+
+    if config.max_fragment_length == Some(MaxFragmentLength::Unsupported) {
+        return Err(Error::ErrInvalidMaxFragmentLength);
+    }
+
     parse_cipher_suites(
         &config.cipher_suites,
         config.psk.is_none(),
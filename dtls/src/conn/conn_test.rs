@@ -1,22 +1,29 @@
+use std::io::{BufReader, BufWriter};
+use std::sync::atomic::Ordering;
 use std::time::SystemTime;
 
 use rand::Rng;
 use rustls::pki_types::CertificateDer;
 use util::conn::conn_pipe::*;
-use util::KeyingMaterialExporter;
+use util::replay_detector::{ReplayDetector, SlidingWindowDetector};
+use util::{KeyingMaterialExporter, KeyingMaterialExporterError};
 
 use super::*;
 use crate::cipher_suite::cipher_suite_aes_128_gcm_sha256::*;
 use crate::cipher_suite::*;
 use crate::compression_methods::*;
+use crate::content::*;
 use crate::crypto::*;
 use crate::curve::*;
 use crate::error::*;
+use crate::extension::extension_max_fragment_length::*;
+use crate::extension::extension_supported_ekt_ciphers::*;
 use crate::extension::extension_supported_elliptic_curves::*;
 use crate::extension::extension_supported_point_formats::*;
 use crate::extension::extension_supported_signature_algorithms::*;
 use crate::extension::renegotiation_info::ExtensionRenegotiationInfo;
 use crate::extension::*;
+use crate::handshake::handshake_cache::*;
 use crate::handshake::handshake_message_certificate::*;
 use crate::handshake::handshake_message_client_hello::*;
 use crate::handshake::handshake_message_hello_verify_request::*;
@@ -24,6 +31,9 @@ use crate::handshake::handshake_message_server_hello::*;
 use crate::handshake::handshake_message_server_hello_done::*;
 use crate::handshake::handshake_message_server_key_exchange::*;
 use crate::handshake::handshake_random::*;
+use crate::handshake::*;
+use crate::record_layer::record_layer_header::*;
+use crate::record_layer::*;
 use crate::signature_hash_algorithm::*;
 
 const ERR_TEST_PSK_INVALID_IDENTITY: &str = "TestPSK: Server got invalid identity";
@@ -187,7 +197,8 @@ async fn test_sequence_number_overflow_on_application_data() -> Result<()> {
     let (ca, cb) = build_pipe().await?;
 
     {
-        let mut lsn = ca.state.local_sequence_number.lock().await;
+        let driver = ca.driver.lock();
+        let mut lsn = driver.state.local_sequence_number.lock();
         lsn[1] = MAX_SEQUENCE_NUMBER;
     }
 
@@ -251,7 +262,8 @@ async fn test_sequence_number_overflow_on_handshake() -> Result<()> {
     let (ca, cb) = build_pipe().await?;
 
     {
-        let mut lsn = ca.state.local_sequence_number.lock().await;
+        let driver = ca.driver.lock();
+        let mut lsn = driver.state.local_sequence_number.lock();
         lsn[0] = MAX_SEQUENCE_NUMBER + 1;
     }
 
@@ -394,50 +406,29 @@ async fn test_export_keying_material() -> Result<()> {
     let expected_server_key = vec![0x61, 0x09, 0x9d, 0x7d, 0xcb, 0x08, 0x52, 0x2c, 0xe7, 0x7b];
     let expected_client_key = vec![0x87, 0xf0, 0x40, 0x02, 0xf6, 0x1c, 0xf1, 0xfe, 0x8c, 0x77];
 
-    let (_decrypted_tx, decrypted_rx) = mpsc::channel(1);
-    let (_handshake_tx, handshake_rx) = mpsc::channel(1);
-    let (packet_tx, _packet_rx) = mpsc::channel(1);
-    let (handle_queue_tx, _handle_queue_rx) = mpsc::channel(1);
     let (ca, _cb) = pipe();
 
-    let mut c = DTLSConn {
-        conn: Arc::new(ca),
-        state: State {
-            local_random: HandshakeRandom {
-                gmt_unix_time: SystemTime::UNIX_EPOCH
-                    .checked_add(Duration::new(500, 0))
-                    .unwrap(),
-                ..Default::default()
-            },
-            remote_random: HandshakeRandom {
-                gmt_unix_time: SystemTime::UNIX_EPOCH
-                    .checked_add(Duration::new(1000, 0))
-                    .unwrap(),
-                ..Default::default()
-            },
-            local_sequence_number: Arc::new(Mutex::new(vec![0, 0])),
-            cipher_suite: Arc::new(Mutex::new(Some(Box::new(CipherSuiteAes128GcmSha256::new(
-                false,
-            ))))),
+    let mut driver = DTLSDriver::new(Config::default(), true)?;
+    driver.state = State {
+        local_random: HandshakeRandom {
+            gmt_unix_time: SystemTime::UNIX_EPOCH
+                .checked_add(Duration::new(500, 0))
+                .unwrap(),
+            ..Default::default()
+        },
+        remote_random: HandshakeRandom {
+            gmt_unix_time: SystemTime::UNIX_EPOCH
+                .checked_add(Duration::new(1000, 0))
+                .unwrap(),
             ..Default::default()
         },
-        cache: HandshakeCache::new(),
-        decrypted_rx: Mutex::new(decrypted_rx),
-        handshake_completed_successfully: Arc::new(AtomicBool::new(false)),
-        connection_closed_by_user: false,
-        closed: AtomicBool::new(false),
-        current_flight: Box::new(Flight0 {}) as Box<dyn Flight + Send + Sync>,
-        flights: None,
-        cfg: HandshakeConfig::default(),
-        retransmit: false,
-        handshake_rx,
-
-        packet_tx: Arc::new(packet_tx),
-        handle_queue_tx,
-        handshake_done_tx: None,
-
-        reader_close_tx: Mutex::new(None),
+        local_sequence_number: Arc::new(util::sync::Mutex::new(vec![0, 0])),
+        cipher_suite: Arc::new(util::sync::Mutex::new(Some(Box::new(
+            CipherSuiteAes128GcmSha256::new(false),
+        )))),
+        ..Default::default()
     };
+    let c = DTLSConn::from_driver(Arc::new(ca), driver);
 
     c.set_local_epoch(0);
     let state = c.connection_state().await;
@@ -490,7 +481,7 @@ async fn test_export_keying_material() -> Result<()> {
         &expected_server_key, &keying_material,
     );
 
-    c.state.is_client = true;
+    c.driver.lock().state.is_client = true;
     let state = c.connection_state().await;
     let keying_material = state.export_keying_material(export_label, &[], 10).await?;
     assert_eq!(
@@ -499,6 +490,45 @@ async fn test_export_keying_material() -> Result<()> {
         &expected_client_key, &keying_material,
     );
 
+    let keying_material = c.export_keying_material(export_label, None, 10).await?;
+    assert_eq!(
+        &keying_material, &expected_client_key,
+        "DTLSConn export without context: expected ({:?}) actual ({:?})",
+        &expected_client_key, &keying_material,
+    );
+
+    let empty_context = c
+        .export_keying_material(export_label, Some(&[]), 10)
+        .await?;
+    let context = c
+        .export_keying_material(export_label, Some(&[0x01, 0x02]), 10)
+        .await?;
+    assert_ne!(
+        empty_context, keying_material,
+        "empty context must differ from no context"
+    );
+    assert_ne!(context, empty_context, "context must alter keying material");
+    assert_eq!(
+        context,
+        c.export_keying_material(export_label, Some(&[0x01, 0x02]), 10)
+            .await?,
+        "export with context must be deterministic"
+    );
+
+    let result = c
+        .export_keying_material(export_label, Some(&vec![0u8; 1 << 16]), 10)
+        .await;
+    assert_eq!(result, Err(Error::ErrContextTooLong));
+
+    c.set_local_epoch(0);
+    let result = c.export_keying_material(export_label, None, 10).await;
+    assert_eq!(
+        result,
+        Err(Error::KeyingMaterial(
+            KeyingMaterialExporterError::HandshakeInProgress
+        ))
+    );
+
     Ok(())
 }
 
@@ -1292,6 +1322,529 @@ async fn test_extended_master_secret() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_skip_hello_verify() -> Result<()> {
+    for (name, insecure_skip_hello_verify) in
+        [("Cookie exchange", false), ("Skip cookie exchange", true)]
+    {
+        let (client_res_tx, mut client_res_rx) = mpsc::channel(1);
+        let (ca, cb) = pipe();
+        tokio::spawn(async move {
+            let result = create_test_client(Arc::new(ca), Config::default(), true).await;
+            let _ = client_res_tx.send(result).await;
+        });
+
+        let config = Config {
+            insecure_skip_hello_verify,
+            ..Default::default()
+        };
+        let server = create_test_server(Arc::new(cb), config, true).await;
+        assert!(server.is_ok(), "{name} expected server, but got err");
+
+        let server = server?;
+        let has_hello_verify_request = !server
+            .driver
+            .lock()
+            .cache
+            .pull(&[HandshakeCachePullRule {
+                typ: HandshakeType::HelloVerifyRequest,
+                epoch: 0,
+                is_client: false,
+                optional: false,
+            }])
+            .is_empty();
+        assert_eq!(
+            has_hello_verify_request, !insecure_skip_hello_verify,
+            "{name} HelloVerifyRequest sent mismatch"
+        );
+
+        let client_result = client_res_rx.recv().await;
+        assert!(
+            matches!(client_result, Some(Ok(_))),
+            "{name} expected client, but got err"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_next_retransmit_interval() {
+    let tests = vec![
+        ("No backoff", 1000, 0, 1000),
+        ("Max below initial", 1000, 500, 1000),
+        ("Double", 1000, 60000, 2000),
+        ("Capped", 40000, 60000, 60000),
+        ("At max", 60000, 60000, 60000),
+    ];
+
+    for (name, current, max_interval, expected) in tests {
+        let next = next_retransmit_interval(
+            Duration::from_millis(current),
+            Duration::from_millis(max_interval),
+        );
+        assert_eq!(next, Duration::from_millis(expected), "{name}");
+    }
+}
+
+#[cfg(feature = "pq-hybrid")]
+#[tokio::test]
+async fn test_pq_hybrid_key_exchange() -> Result<()> {
+    use crate::curve::named_curve::NamedCurve;
+
+    let (ca, cb) = pipe();
+    let (client, server) = pipe_conn(Arc::new(ca), Arc::new(cb)).await?;
+
+    for conn in [&client, &server] {
+        let curve = conn
+            .driver
+            .lock()
+            .state
+            .local_keypair
+            .as_ref()
+            .map(|k| k.curve);
+        assert_eq!(curve, Some(NamedCurve::X25519MlKem768));
+    }
+
+    client.write(b"hybrid", None).await?;
+    let mut buf = vec![0u8; 16];
+    let n = server.read(&mut buf, None).await?;
+    assert_eq!(&buf[..n], b"hybrid");
+
+    client.close().await?;
+    server.close().await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_record_size_limit() -> Result<()> {
+    #[allow(clippy::type_complexity)]
+    let tests: Vec<(
+        &str,
+        u16,
+        Option<MaxFragmentLength>,
+        u16,
+        Option<u16>,
+        Option<u16>,
+    )> = vec![
+        ("No limit", 0, None, 0, None, None),
+        ("Client limit only", 1024, None, 0, Some(16384), Some(1024)),
+        ("Both limits", 512, None, 256, Some(256), Some(512)),
+        (
+            "Max fragment length",
+            0,
+            Some(MaxFragmentLength::Pow2_9),
+            0,
+            Some(512),
+            Some(512),
+        ),
+        (
+            "Record size limit takes precedence",
+            1024,
+            Some(MaxFragmentLength::Pow2_9),
+            0,
+            Some(16384),
+            Some(1024),
+        ),
+    ];
+
+    for (
+        name,
+        client_record_size_limit,
+        client_max_fragment_length,
+        server_record_size_limit,
+        expected_client_limit,
+        expected_server_limit,
+    ) in tests
+    {
+        let (client_res_tx, mut client_res_rx) = mpsc::channel(1);
+        let (ca, cb) = pipe();
+        let client_cfg = Config {
+            record_size_limit: client_record_size_limit,
+            max_fragment_length: client_max_fragment_length,
+            ..Default::default()
+        };
+        tokio::spawn(async move {
+            let result = create_test_client(Arc::new(ca), client_cfg, true).await;
+            let _ = client_res_tx.send(result).await;
+        });
+
+        let server_cfg = Config {
+            record_size_limit: server_record_size_limit,
+            ..Default::default()
+        };
+        let server = create_test_server(Arc::new(cb), server_cfg, true).await?;
+        let client = client_res_rx.recv().await.unwrap()?;
+
+        assert_eq!(
+            client.record_size_limit(),
+            expected_client_limit,
+            "{name}: unexpected client side limit"
+        );
+        assert_eq!(
+            server.record_size_limit(),
+            expected_server_limit,
+            "{name}: unexpected server side limit"
+        );
+
+        // Application data is split according to the peer's limit
+        let data = vec![0xab; 2000];
+        client.write(&data, None).await?;
+        let mut received = 0;
+        let mut buf = vec![0u8; 4096];
+        while received < data.len() {
+            let n = server.read(&mut buf, None).await?;
+            if let Some(limit) = expected_client_limit {
+                assert!(n <= limit as usize, "{name}: record exceeds limit");
+            }
+            received += n;
+        }
+        assert_eq!(received, data.len(), "{name}: data lost");
+
+        client.close().await?;
+        server.close().await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_write_vectored() -> Result<()> {
+    let (client_res_tx, mut client_res_rx) = mpsc::channel(1);
+    let (ca, cb) = pipe();
+    let client_cfg = Config {
+        record_size_limit: 1024,
+        ..Default::default()
+    };
+    tokio::spawn(async move {
+        let result = create_test_client(Arc::new(ca), client_cfg, true).await;
+        let _ = client_res_tx.send(result).await;
+    });
+
+    let server_cfg = Config {
+        record_size_limit: 256,
+        ..Default::default()
+    };
+    let server = create_test_server(Arc::new(cb), server_cfg, true).await?;
+    let client = client_res_rx.recv().await.unwrap()?;
+
+    // The slices are gathered into records of at most the peer's limit.
+    let data: Vec<u8> = (0..700).map(|i| i as u8).collect();
+    let bufs = [
+        IoSlice::new(&data[..100]),
+        IoSlice::new(&data[100..100]),
+        IoSlice::new(&data[100..650]),
+        IoSlice::new(&data[650..]),
+    ];
+    assert_eq!(client.write_vectored(&bufs, None).await?, data.len());
+
+    let mut received = vec![];
+    let mut buf = vec![0u8; 4096];
+    while received.len() < data.len() {
+        let n = server.read(&mut buf, None).await?;
+        assert!(n <= 256, "record exceeds limit");
+        received.extend_from_slice(&buf[..n]);
+    }
+    assert_eq!(received, data);
+
+    client.close().await?;
+    server.close().await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ocsp_stapling() -> Result<()> {
+    let ocsp_response = vec![0x30, 0x03, 0x0a, 0x01, 0x00];
+
+    let tests = vec![
+        (
+            "Stapled",
+            true,
+            ocsp_response.clone(),
+            Some(ocsp_response.clone()),
+        ),
+        ("Server has no response", true, vec![], Some(vec![])),
+        (
+            "Client does not request",
+            false,
+            ocsp_response.clone(),
+            None,
+        ),
+    ];
+
+    for (name, client_requests, server_ocsp_response, expected_response) in tests {
+        let stapled = Arc::new(std::sync::Mutex::new(None));
+        let stapled2 = Arc::clone(&stapled);
+        let verify_ocsp_response: Option<VerifyOcspResponseFn> = if client_requests {
+            Some(Arc::new(move |certs: &[Vec<u8>], response: &[u8]| {
+                assert!(!certs.is_empty());
+                *stapled2.lock().unwrap() = Some(response.to_vec());
+                Ok(())
+            }))
+        } else {
+            None
+        };
+
+        let (client_res_tx, mut client_res_rx) = mpsc::channel(1);
+        let (ca, cb) = pipe();
+        tokio::spawn(async move {
+            let result = create_test_client(
+                Arc::new(ca),
+                Config {
+                    verify_ocsp_response,
+                    ..Default::default()
+                },
+                true,
+            )
+            .await;
+            let _ = client_res_tx.send(result).await;
+        });
+
+        let server = create_test_server(
+            Arc::new(cb),
+            Config {
+                ocsp_response: server_ocsp_response,
+                ..Default::default()
+            },
+            true,
+        )
+        .await?;
+        let client = client_res_rx.recv().await.unwrap()?;
+
+        assert_eq!(
+            *stapled.lock().unwrap(),
+            expected_response,
+            "{name}: unexpected stapled response"
+        );
+        assert_eq!(
+            client.driver.lock().state.ocsp_stapling,
+            expected_response.as_ref().is_some_and(|r| !r.is_empty()),
+            "{name}: unexpected stapling state"
+        );
+
+        client.close().await?;
+        server.close().await?;
+    }
+
+    // Rejecting the stapled response aborts the handshake
+    let (ca, cb) = pipe();
+    tokio::spawn(async move {
+        let _ = create_test_server(
+            Arc::new(cb),
+            Config {
+                ocsp_response,
+                ..Default::default()
+            },
+            true,
+        )
+        .await;
+    });
+
+    let result = create_test_client(
+        Arc::new(ca),
+        Config {
+            verify_ocsp_response: Some(Arc::new(|_, _| Err(Error::Other("revoked".to_owned())))),
+            ..Default::default()
+        },
+        true,
+    )
+    .await;
+    assert!(result.is_err(), "expected the handshake to fail");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ekt_cipher_negotiation() -> Result<()> {
+    let tests = vec![
+        ("No EKT in use", vec![], vec![], EktCipher::Unsupported),
+        (
+            "EKT both ends",
+            vec![EktCipher::AesKw128],
+            vec![EktCipher::AesKw128],
+            EktCipher::AesKw128,
+        ),
+        (
+            "EKT client chooses",
+            vec![EktCipher::AesKw256, EktCipher::AesKw128],
+            vec![EktCipher::AesKw128, EktCipher::AesKw256],
+            EktCipher::AesKw256,
+        ),
+        (
+            "EKT client only",
+            vec![EktCipher::AesKw128],
+            vec![],
+            EktCipher::Unsupported,
+        ),
+        (
+            "EKT server only",
+            vec![],
+            vec![EktCipher::AesKw128],
+            EktCipher::Unsupported,
+        ),
+        (
+            "EKT no overlap",
+            vec![EktCipher::AesKw128],
+            vec![EktCipher::AesKw256],
+            EktCipher::Unsupported,
+        ),
+    ];
+
+    for (name, client_ekt, server_ekt, expected_cipher) in tests {
+        let (client_res_tx, mut client_res_rx) = mpsc::channel(1);
+        let (ca, cb) = pipe();
+        tokio::spawn(async move {
+            let conf = Config {
+                ekt_ciphers: client_ekt,
+                ..Default::default()
+            };
+
+            let result = create_test_client(Arc::new(ca), conf, true).await;
+            let _ = client_res_tx.send(result).await;
+        });
+
+        let config = Config {
+            ekt_ciphers: server_ekt,
+            ..Default::default()
+        };
+
+        let server = create_test_server(Arc::new(cb), config, true)
+            .await
+            .unwrap_or_else(|err| panic!("{name} expected server, but got {err}"));
+        assert_eq!(
+            server.selected_ekt_cipher(),
+            expected_cipher,
+            "{name} Server EKT cipher mismatch"
+        );
+
+        let client = client_res_rx
+            .recv()
+            .await
+            .unwrap_or_else(|| panic!("{name} expected client, but got none"))
+            .unwrap_or_else(|err| panic!("{name} expected client, but got {err}"));
+        assert_eq!(
+            client.selected_ekt_cipher(),
+            expected_cipher,
+            "{name} Client EKT cipher mismatch"
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_alpn() -> Result<()> {
+    #[allow(clippy::type_complexity)]
+    let tests: Vec<(
+        &str,
+        Vec<&str>,
+        Vec<&str>,
+        Option<&str>,
+        Option<Error>,
+        Option<Error>,
+    )> = vec![
+        ("No ALPN in use", vec![], vec![], None, None, None),
+        (
+            "ALPN both ends",
+            vec!["webrtc", "c-webrtc"],
+            vec!["webrtc"],
+            Some("webrtc"),
+            None,
+            None,
+        ),
+        (
+            "ALPN server preference",
+            vec!["webrtc", "c-webrtc"],
+            vec!["c-webrtc", "webrtc"],
+            Some("c-webrtc"),
+            None,
+            None,
+        ),
+        ("ALPN client only", vec!["webrtc"], vec![], None, None, None),
+        ("ALPN server only", vec![], vec!["webrtc"], None, None, None),
+        (
+            "ALPN no overlap",
+            vec!["webrtc"],
+            vec!["c-webrtc"],
+            None,
+            Some(Error::ErrAlertFatalOrClose),
+            Some(Error::ErrAlpnNoAppProtocol),
+        ),
+    ];
+
+    for (
+        name,
+        client_protocols,
+        server_protocols,
+        expected_protocol,
+        expected_client_err,
+        expected_server_err,
+    ) in tests
+    {
+        let (client_res_tx, mut client_res_rx) = mpsc::channel(1);
+        let (ca, cb) = pipe();
+        let client_cfg = Config {
+            supported_protocols: client_protocols.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        };
+        tokio::spawn(async move {
+            let result = create_test_client(Arc::new(ca), client_cfg, true).await;
+            let _ = client_res_tx.send(result).await;
+        });
+
+        let server_cfg = Config {
+            supported_protocols: server_protocols.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        };
+        let result = create_test_server(Arc::new(cb), server_cfg, true).await;
+        let client_result = client_res_rx.recv().await;
+        assert!(client_result.is_some(), "{name}, expected client conn");
+        let res = client_result.unwrap();
+
+        if let Some(client_err) = expected_client_err {
+            if let Err(err) = res {
+                assert_eq!(
+                    err.to_string(),
+                    client_err.to_string(),
+                    "{name} Client error expected: \"{client_err}\" but got \"{err}\"",
+                );
+            } else {
+                panic!("{name} expected err, but got ok");
+            }
+        } else {
+            let client = res.unwrap_or_else(|err| panic!("{name} expected ok, but got {err}"));
+            assert_eq!(
+                client.negotiated_application_protocol(),
+                expected_protocol,
+                "{name} Client negotiated protocol mismatch"
+            );
+        }
+
+        if let Some(server_err) = expected_server_err {
+            if let Err(err) = result {
+                assert_eq!(
+                    err.to_string(),
+                    server_err.to_string(),
+                    "{name} Server error expected: \"{server_err}\" but got \"{err}\"",
+                );
+            } else {
+                panic!("{name} expected err, but got ok");
+            }
+        } else {
+            let server = result.unwrap_or_else(|err| panic!("{name} expected ok, but got {err}"));
+            assert_eq!(
+                server.negotiated_application_protocol(),
+                expected_protocol,
+                "{name} Server negotiated protocol mismatch"
+            );
+        }
+    }
+
+    Ok(())
+}
+
 fn fn_not_expected_chain(_cert: &[Vec<u8>], chain: &[CertificateDer<'static>]) -> Result<()> {
     if !chain.is_empty() {
         return Err(Error::Other(ERR_NOT_EXPECTED_CHAIN.to_owned()));
@@ -1613,7 +2166,8 @@ async fn test_cipher_suite_configuration() -> Result<()> {
                 assert!(result.is_ok(), "{name} expected ok, but got error");
                 let client = result.unwrap();
                 if let Some(want_cs) = want_selected_cipher_suite {
-                    let cipher_suite = client.state.cipher_suite.lock().await;
+                    let driver = client.driver.lock();
+                    let cipher_suite = driver.state.cipher_suite.lock();
                     assert!(cipher_suite.is_some(), "{name} expected some, but got none");
                     if let Some(cs) = &*cipher_suite {
                         assert_eq!(cs.id(), want_cs,
@@ -2456,3 +3010,143 @@ async fn test_renegotiation_info() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_snapshot_restore() -> Result<()> {
+    let (client, server) = build_pipe().await?;
+
+    // Exchange some data so that sequence numbers move past the handshake
+    let mut buf = vec![0u8; 64];
+    for _ in 0..3 {
+        client.write(b"before", None).await?;
+        let n = server.read(&mut buf, None).await?;
+        assert_eq!(&buf[..n], b"before");
+    }
+
+    let client_snapshot = client.snapshot().await?;
+    let server_snapshot = server.snapshot().await?;
+
+    // The format version leads the snapshot and newer versions are rejected
+    assert_eq!(server_snapshot[0], 2);
+    let mut newer_snapshot = server_snapshot.clone();
+    newer_snapshot[0] = 3;
+    assert_eq!(
+        State::default().unmarshal_binary(&newer_snapshot).await,
+        Err(Error::ErrUnsupportedStateVersion(3))
+    );
+
+    let (ca, cb) = pipe();
+    let restored_client = DTLSConn::restore(
+        Arc::new(ca),
+        Config {
+            insecure_skip_verify: true,
+            ..Default::default()
+        },
+        true,
+        &client_snapshot,
+    )
+    .await?;
+    let restored_server = DTLSConn::restore(
+        Arc::new(cb),
+        Config {
+            certificates: vec![Certificate::generate_self_signed(vec![
+                "localhost".to_owned()
+            ])?],
+            ..Default::default()
+        },
+        false,
+        &server_snapshot,
+    )
+    .await?;
+
+    assert_eq!(
+        restored_client.selected_srtpprotection_profile(),
+        SrtpProtectionProfile::Srtp_Aes128_Cm_Hmac_Sha1_80
+    );
+
+    let client_state = client.connection_state().await;
+    let restored_state = restored_client.connection_state().await;
+    assert_eq!(client_state.master_secret, restored_state.master_secret);
+    assert_eq!(
+        client_state.local_epoch.load(Ordering::SeqCst),
+        restored_state.local_epoch.load(Ordering::SeqCst)
+    );
+    // The whole replay window is restored, not only the highest sequence number
+    let epoch = server
+        .driver
+        .lock()
+        .state
+        .remote_epoch
+        .load(Ordering::SeqCst) as usize;
+    let window = server.driver.lock().state.replay_detector.lock()[epoch].window();
+    let restored_window =
+        restored_server.driver.lock().state.replay_detector.lock()[epoch].window();
+    assert_eq!(restored_window, window);
+    let mut restored_detector =
+        SlidingWindowDetector::from_window(&restored_window, MAX_SEQUENCE_NUMBER);
+    assert!(!restored_detector.check(window.latest_seq - 1));
+    assert!(restored_detector.check(window.latest_seq + 1));
+
+    restored_client.write(b"after client", None).await?;
+    let n = restored_server.read(&mut buf, None).await?;
+    assert_eq!(&buf[..n], b"after client");
+
+    restored_server.write(b"after server", None).await?;
+    let n = restored_client.read(&mut buf, None).await?;
+    assert_eq!(&buf[..n], b"after server");
+
+    restored_client.close().await?;
+    restored_server.close().await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unmarshal_unversioned_state() -> Result<()> {
+    // marshal_binary of a client before the format was versioned
+    let mut snapshot = vec![];
+    snapshot.extend_from_slice(&1u16.to_le_bytes()); // local_epoch
+    snapshot.extend_from_slice(&1u16.to_le_bytes()); // remote_epoch
+    snapshot.extend_from_slice(&[0x11; HANDSHAKE_RANDOM_LENGTH]); // local_random
+    snapshot.extend_from_slice(&[0x22; HANDSHAKE_RANDOM_LENGTH]); // remote_random
+    snapshot.extend_from_slice(
+        &(CipherSuiteId::Tls_Ecdhe_Ecdsa_With_Aes_128_Gcm_Sha256 as u16).to_le_bytes(),
+    );
+    snapshot.extend_from_slice(&48u64.to_le_bytes()); // master_secret
+    snapshot.extend_from_slice(&[0x33; 48]);
+    snapshot.extend_from_slice(&7u64.to_le_bytes()); // sequence_number
+    snapshot.extend_from_slice(&1u16.to_le_bytes()); // srtp_protection_profile
+    snapshot.extend_from_slice(&1u64.to_le_bytes()); // peer_certificates
+    snapshot.extend_from_slice(&3u64.to_le_bytes());
+    snapshot.extend_from_slice(b"der");
+    snapshot.extend_from_slice(&4u64.to_le_bytes()); // identity_hint
+    snapshot.extend_from_slice(b"hint");
+    snapshot.push(1); // is_client
+
+    let mut state = State::default();
+    state.unmarshal_binary(&snapshot).await?;
+
+    assert_eq!(state.local_epoch.load(Ordering::SeqCst), 1);
+    assert_eq!(state.remote_epoch.load(Ordering::SeqCst), 1);
+    assert_eq!(state.local_sequence_number.lock()[1], 7);
+    assert_eq!(state.master_secret, vec![0x33; 48]);
+    assert_eq!(
+        state.srtp_protection_profile,
+        SrtpProtectionProfile::Srtp_Aes128_Cm_Hmac_Sha1_80
+    );
+    assert_eq!(state.ekt_cipher, EktCipher::Unsupported);
+    assert_eq!(state.peer_certificates, vec![b"der".to_vec()]);
+    assert_eq!(state.identity_hint, b"hint".to_vec());
+    assert!(state.is_client);
+    assert!(state.replay_detector.lock().is_empty());
+    assert!(state
+        .cipher_suite
+        .lock()
+        .as_ref()
+        .is_some_and(|cipher_suite| cipher_suite.is_initialized()));
+
+    // It is written back in the current format
+    assert_eq!(state.marshal_binary().await?[0], 2);
+
+    Ok(())
+}
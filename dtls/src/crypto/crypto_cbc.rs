@@ -8,25 +8,25 @@
 
 // https://github.com/RustCrypto/block-ciphers
 
-use aes::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use cbc::cipher::block_padding::RawPadding;
 use p256::elliptic_curve::subtle::ConstantTimeEq;
 use rand::Rng;
 use std::io::Cursor;
 use std::ops::Not;
+use std::sync::Arc;
 
 use super::padding::DtlsPadding;
+use super::provider::{self, BlockCipher};
 use crate::content::*;
 use crate::error::*;
 use crate::prf::*;
 use crate::record_layer::record_layer_header::*;
-type Aes256CbcEnc = cbc::Encryptor<aes::Aes256>;
-type Aes256CbcDec = cbc::Decryptor<aes::Aes256>;
 
 // State needed to handle encrypted input/output
 #[derive(Clone)]
 pub struct CryptoCbc {
-    local_key: Vec<u8>,
-    remote_key: Vec<u8>,
+    local_cbc: Arc<dyn BlockCipher>,
+    remote_cbc: Arc<dyn BlockCipher>,
     write_mac: Vec<u8>,
     read_mac: Vec<u8>,
 }
@@ -42,10 +42,10 @@ impl CryptoCbc {
         remote_mac: &[u8],
     ) -> Result<Self> {
         Ok(CryptoCbc {
-            local_key: local_key.to_vec(),
+            local_cbc: provider::get_default().aes_cbc(local_key)?,
             write_mac: local_mac.to_vec(),
 
-            remote_key: remote_key.to_vec(),
+            remote_cbc: provider::get_default().aes_cbc(remote_key)?,
             read_mac: remote_mac.to_vec(),
         })
     }
@@ -70,8 +70,14 @@ impl CryptoCbc {
         let mut iv: Vec<u8> = vec![0; Self::BLOCK_SIZE];
         rand::thread_rng().fill(iv.as_mut_slice());
 
-        let write_cbc = Aes256CbcEnc::new_from_slices(&self.local_key, &iv)?;
-        let encrypted = write_cbc.encrypt_padded_vec_mut::<DtlsPadding>(&payload);
+        // Pad up to the next block, a full one being added to aligned payloads
+        let pos = payload.len() % Self::BLOCK_SIZE;
+        payload.resize(payload.len() - pos + Self::BLOCK_SIZE, 0);
+        let last_block = payload.len() - Self::BLOCK_SIZE;
+        DtlsPadding::raw_pad(&mut payload[last_block..], pos);
+
+        self.local_cbc.encrypt(&iv, &mut payload)?;
+        let encrypted = payload;
 
         // Prepend unencrypte header with encrypted payload
         let mut r = vec![];
@@ -95,15 +101,17 @@ impl CryptoCbc {
         }
 
         let body = &r[RECORD_LAYER_HEADER_SIZE..];
+        if body.len() < 2 * Self::BLOCK_SIZE || !body.len().is_multiple_of(Self::BLOCK_SIZE) {
+            return Err(Error::ErrInvalidPacketLength);
+        }
         let iv = &body[0..Self::BLOCK_SIZE];
-        let body = &body[Self::BLOCK_SIZE..];
-        //TODO: add body.len() check
+        let mut body = body[Self::BLOCK_SIZE..].to_vec();
 
-        let read_cbc = Aes256CbcDec::new_from_slices(&self.remote_key, iv)?;
-
-        let decrypted = read_cbc
-            .decrypt_padded_vec_mut::<DtlsPadding>(body)
-            .map_err(|_| Error::ErrInvalidPacketLength)?;
+        self.remote_cbc.decrypt(iv, &mut body)?;
+        let decrypted = DtlsPadding::raw_unpad(&body).map_err(|_| Error::ErrInvalidPacketLength)?;
+        if decrypted.len() < Self::MAC_SIZE {
+            return Err(Error::ErrInvalidPacketLength);
+        }
 
         let recv_mac = &decrypted[decrypted.len() - Self::MAC_SIZE..];
         let decrypted = &decrypted[0..decrypted.len() - Self::MAC_SIZE];
@@ -10,14 +10,9 @@
 
 use std::io::Cursor;
 
-use aes::Aes128;
-use ccm::aead::generic_array::GenericArray;
-use ccm::aead::AeadInPlace;
-use ccm::consts::{U12, U16, U8};
-use ccm::Ccm;
-use ccm::KeyInit;
 use rand::Rng;
 
+use super::provider::{self, Aead};
 use super::*;
 use crate::content::*;
 use crate::error::*;
@@ -27,50 +22,20 @@ const CRYPTO_CCM_8_TAG_LENGTH: usize = 8;
 const CRYPTO_CCM_TAG_LENGTH: usize = 16;
 const CRYPTO_CCM_NONCE_LENGTH: usize = 12;
 
-type AesCcm8 = Ccm<Aes128, U8, U12>;
-type AesCcm = Ccm<Aes128, U16, U12>;
-
 #[derive(Clone)]
 pub enum CryptoCcmTagLen {
     CryptoCcm8TagLength,
     CryptoCcmTagLength,
 }
 
-enum CryptoCcmType {
-    CryptoCcm8(AesCcm8),
-    CryptoCcm(AesCcm),
-}
-
 // State needed to handle encrypted input/output
+#[derive(Clone)]
 pub struct CryptoCcm {
-    local_ccm: CryptoCcmType,
-    remote_ccm: CryptoCcmType,
+    tag_len: usize,
+    local_ccm: Arc<dyn Aead>,
+    remote_ccm: Arc<dyn Aead>,
     local_write_iv: Vec<u8>,
     remote_write_iv: Vec<u8>,
-    // used by clone()
-    local_write_key: Vec<u8>,
-    remote_write_key: Vec<u8>,
-}
-
-impl Clone for CryptoCcm {
-    fn clone(&self) -> Self {
-        match self.local_ccm {
-            CryptoCcmType::CryptoCcm(_) => Self::new(
-                &CryptoCcmTagLen::CryptoCcmTagLength,
-                &self.local_write_key,
-                &self.local_write_iv,
-                &self.remote_write_key,
-                &self.remote_write_iv,
-            ),
-            CryptoCcmType::CryptoCcm8(_) => Self::new(
-                &CryptoCcmTagLen::CryptoCcm8TagLength,
-                &self.local_write_key,
-                &self.local_write_iv,
-                &self.remote_write_key,
-                &self.remote_write_iv,
-            ),
-        }
-    }
 }
 
 impl CryptoCcm {
@@ -80,27 +45,19 @@ impl CryptoCcm {
         local_write_iv: &[u8],
         remote_key: &[u8],
         remote_write_iv: &[u8],
-    ) -> Self {
-        let key = GenericArray::from_slice(local_key);
-        let local_ccm = match tag_len {
-            CryptoCcmTagLen::CryptoCcmTagLength => CryptoCcmType::CryptoCcm(AesCcm::new(key)),
-            CryptoCcmTagLen::CryptoCcm8TagLength => CryptoCcmType::CryptoCcm8(AesCcm8::new(key)),
-        };
-
-        let key = GenericArray::from_slice(remote_key);
-        let remote_ccm = match tag_len {
-            CryptoCcmTagLen::CryptoCcmTagLength => CryptoCcmType::CryptoCcm(AesCcm::new(key)),
-            CryptoCcmTagLen::CryptoCcm8TagLength => CryptoCcmType::CryptoCcm8(AesCcm8::new(key)),
+    ) -> Result<Self> {
+        let tag_len = match tag_len {
+            CryptoCcmTagLen::CryptoCcmTagLength => CRYPTO_CCM_TAG_LENGTH,
+            CryptoCcmTagLen::CryptoCcm8TagLength => CRYPTO_CCM_8_TAG_LENGTH,
         };
 
-        CryptoCcm {
-            local_ccm,
-            local_write_key: local_key.to_vec(),
+        Ok(CryptoCcm {
+            tag_len,
+            local_ccm: provider::get_default().aes_ccm(local_key, tag_len)?,
             local_write_iv: local_write_iv.to_vec(),
-            remote_ccm,
-            remote_write_key: remote_key.to_vec(),
+            remote_ccm: provider::get_default().aes_ccm(remote_key, tag_len)?,
             remote_write_iv: remote_write_iv.to_vec(),
-        }
+        })
     }
 
     pub fn encrypt(&self, pkt_rlh: &RecordLayerHeader, raw: &[u8]) -> Result<Vec<u8>> {
@@ -110,23 +67,14 @@ impl CryptoCcm {
         let mut nonce = vec![0u8; CRYPTO_CCM_NONCE_LENGTH];
         nonce[..4].copy_from_slice(&self.local_write_iv[..4]);
         rand::thread_rng().fill(&mut nonce[4..]);
-        let nonce = GenericArray::from_slice(&nonce);
 
         let additional_data = generate_aead_additional_data(pkt_rlh, payload.len());
 
         let mut buffer: Vec<u8> = Vec::new();
         buffer.extend_from_slice(payload);
 
-        match &self.local_ccm {
-            CryptoCcmType::CryptoCcm(ccm) => {
-                ccm.encrypt_in_place(nonce, &additional_data, &mut buffer)
-                    .map_err(|e| Error::Other(e.to_string()))?;
-            }
-            CryptoCcmType::CryptoCcm8(ccm8) => {
-                ccm8.encrypt_in_place(nonce, &additional_data, &mut buffer)
-                    .map_err(|e| Error::Other(e.to_string()))?;
-            }
-        }
+        self.local_ccm
+            .encrypt(&nonce, &additional_data, &mut buffer)?;
 
         let mut r = Vec::with_capacity(raw.len() + nonce.len() + buffer.len());
 
@@ -157,27 +105,15 @@ impl CryptoCcm {
         let mut nonce = vec![];
         nonce.extend_from_slice(&self.remote_write_iv[..4]);
         nonce.extend_from_slice(&r[RECORD_LAYER_HEADER_SIZE..RECORD_LAYER_HEADER_SIZE + 8]);
-        let nonce = GenericArray::from_slice(&nonce);
 
         let out = &r[RECORD_LAYER_HEADER_SIZE + 8..];
 
         let mut buffer: Vec<u8> = Vec::new();
         buffer.extend_from_slice(out);
 
-        match &self.remote_ccm {
-            CryptoCcmType::CryptoCcm(ccm) => {
-                let additional_data =
-                    generate_aead_additional_data(&h, out.len() - CRYPTO_CCM_TAG_LENGTH);
-                ccm.decrypt_in_place(nonce, &additional_data, &mut buffer)
-                    .map_err(|e| Error::Other(e.to_string()))?;
-            }
-            CryptoCcmType::CryptoCcm8(ccm8) => {
-                let additional_data =
-                    generate_aead_additional_data(&h, out.len() - CRYPTO_CCM_8_TAG_LENGTH);
-                ccm8.decrypt_in_place(nonce, &additional_data, &mut buffer)
-                    .map_err(|e| Error::Other(e.to_string()))?;
-            }
-        }
+        let additional_data = generate_aead_additional_data(&h, out.len() - self.tag_len);
+        self.remote_ccm
+            .decrypt(&nonce, &additional_data, &mut buffer)?;
 
         let mut d = Vec::with_capacity(RECORD_LAYER_HEADER_SIZE + buffer.len());
         d.extend_from_slice(&r[..RECORD_LAYER_HEADER_SIZE]);
@@ -8,11 +8,9 @@
 
 use std::io::Cursor;
 
-use aes_gcm::aead::generic_array::GenericArray;
-use aes_gcm::aead::AeadInPlace;
-use aes_gcm::{Aes128Gcm, KeyInit};
 use rand::Rng;
 
+use super::provider::{self, Aead};
 use super::*;
 use crate::content::*;
 use crate::error::*;
@@ -24,8 +22,8 @@ const CRYPTO_GCM_NONCE_LENGTH: usize = 12;
 // State needed to handle encrypted input/output
 #[derive(Clone)]
 pub struct CryptoGcm {
-    local_gcm: Aes128Gcm,
-    remote_gcm: Aes128Gcm,
+    local_gcm: Arc<dyn Aead>,
+    remote_gcm: Arc<dyn Aead>,
     local_write_iv: Vec<u8>,
     remote_write_iv: Vec<u8>,
 }
@@ -36,19 +34,16 @@ impl CryptoGcm {
         local_write_iv: &[u8],
         remote_key: &[u8],
         remote_write_iv: &[u8],
-    ) -> Self {
-        let key = GenericArray::from_slice(local_key);
-        let local_gcm = Aes128Gcm::new(key);
+    ) -> Result<Self> {
+        let local_gcm = provider::get_default().aes_gcm(local_key)?;
+        let remote_gcm = provider::get_default().aes_gcm(remote_key)?;
 
-        let key = GenericArray::from_slice(remote_key);
-        let remote_gcm = Aes128Gcm::new(key);
-
-        CryptoGcm {
+        Ok(CryptoGcm {
             local_gcm,
             local_write_iv: local_write_iv.to_vec(),
             remote_gcm,
             remote_write_iv: remote_write_iv.to_vec(),
-        }
+        })
     }
 
     pub fn encrypt(&self, pkt_rlh: &RecordLayerHeader, raw: &[u8]) -> Result<Vec<u8>> {
@@ -58,7 +53,6 @@ impl CryptoGcm {
         let mut nonce = vec![0u8; CRYPTO_GCM_NONCE_LENGTH];
         nonce[..4].copy_from_slice(&self.local_write_iv[..4]);
         rand::thread_rng().fill(&mut nonce[4..]);
-        let nonce = GenericArray::from_slice(&nonce);
 
         let additional_data = generate_aead_additional_data(pkt_rlh, payload.len());
 
@@ -66,8 +60,7 @@ impl CryptoGcm {
         buffer.extend_from_slice(payload);
 
         self.local_gcm
-            .encrypt_in_place(nonce, &additional_data, &mut buffer)
-            .map_err(|e| Error::Other(e.to_string()))?;
+            .encrypt(&nonce, &additional_data, &mut buffer)?;
 
         let mut r = Vec::with_capacity(raw.len() + nonce.len() + buffer.len());
         r.extend_from_slice(raw);
@@ -97,7 +90,6 @@ impl CryptoGcm {
         let mut nonce = vec![];
         nonce.extend_from_slice(&self.remote_write_iv[..4]);
         nonce.extend_from_slice(&r[RECORD_LAYER_HEADER_SIZE..RECORD_LAYER_HEADER_SIZE + 8]);
-        let nonce = GenericArray::from_slice(&nonce);
 
         let out = &r[RECORD_LAYER_HEADER_SIZE + 8..];
 
@@ -107,8 +99,7 @@ impl CryptoGcm {
         buffer.extend_from_slice(out);
 
         self.remote_gcm
-            .decrypt_in_place(nonce, &additional_data, &mut buffer)
-            .map_err(|e| Error::Other(e.to_string()))?;
+            .decrypt(&nonce, &additional_data, &mut buffer)?;
 
         let mut d = Vec::with_capacity(RECORD_LAYER_HEADER_SIZE + buffer.len());
         d.extend_from_slice(&r[..RECORD_LAYER_HEADER_SIZE]);
@@ -5,6 +5,7 @@ pub mod crypto_cbc;
 pub mod crypto_ccm;
 pub mod crypto_gcm;
 pub mod padding;
+pub mod provider;
 
 use std::convert::TryFrom;
 use std::sync::Arc;
@@ -23,7 +24,7 @@ use ring::signature::{EcdsaKeyPair, Ed25519KeyPair};
 use crate::curve::named_curve::*;
 use crate::error::*;
 use crate::record_layer::record_layer_header::*;
-use crate::signature_hash_algorithm::{HashAlgorithm, SignatureAlgorithm, SignatureHashAlgorithm};
+use crate::signature_hash_algorithm::SignatureHashAlgorithm;
 
 /// A X.509 certificate(s) used to authenticate a DTLS connection.
 #[derive(Clone, PartialEq, Debug)]
@@ -124,10 +125,14 @@ pub(crate) fn value_key_message(
     public_key: &[u8],
     named_curve: NamedCurve,
 ) -> Vec<u8> {
-    let mut server_ecdh_params = vec![0u8; 4];
+    let mut server_ecdh_params = vec![0u8; 3];
     server_ecdh_params[0] = 3; // named curve
     server_ecdh_params[1..3].copy_from_slice(&(named_curve as u16).to_be_bytes());
-    server_ecdh_params[3] = public_key.len() as u8;
+    if named_curve.is_hybrid() {
+        server_ecdh_params.extend_from_slice(&(public_key.len() as u16).to_be_bytes());
+    } else {
+        server_ecdh_params.push(public_key.len() as u8);
+    }
 
     let mut plaintext = vec![];
     plaintext.extend_from_slice(client_random);
@@ -265,31 +270,7 @@ pub(crate) fn generate_key_signature(
     private_key: &CryptoPrivateKey, /*, hash_algorithm: HashAlgorithm*/
 ) -> Result<Vec<u8>> {
     let msg = value_key_message(client_random, server_random, public_key, named_curve);
-    let signature = match &private_key.kind {
-        CryptoPrivateKeyKind::Ed25519(kp) => kp.sign(&msg).as_ref().to_vec(),
-        CryptoPrivateKeyKind::Ecdsa256(kp) => {
-            let system_random = SystemRandom::new();
-            kp.sign(&system_random, &msg)
-                .map_err(|e| Error::Other(e.to_string()))?
-                .as_ref()
-                .to_vec()
-        }
-        CryptoPrivateKeyKind::Rsa256(kp) => {
-            let system_random = SystemRandom::new();
-            let mut signature = vec![0; kp.public().modulus_len()];
-            kp.sign(
-                &ring::signature::RSA_PKCS1_SHA256,
-                &system_random,
-                &msg,
-                &mut signature,
-            )
-            .map_err(|e| Error::Other(e.to_string()))?;
-
-            signature
-        }
-    };
-
-    Ok(signature)
+    provider::get_default().sign(private_key, &msg)
 }
 
 // add OID_ED25519 which is not defined in x509_parser
@@ -310,53 +291,13 @@ fn verify_signature(
     let (_, certificate) = x509_parser::parse_x509_certificate(&raw_certificates[0])
         .map_err(|e| Error::Other(e.to_string()))?;
 
-    let verify_alg: &dyn ring::signature::VerificationAlgorithm = match hash_algorithm.signature {
-        SignatureAlgorithm::Ed25519 => &ring::signature::ED25519,
-        SignatureAlgorithm::Ecdsa if hash_algorithm.hash == HashAlgorithm::Sha256 => {
-            &ring::signature::ECDSA_P256_SHA256_ASN1
-        }
-        SignatureAlgorithm::Ecdsa if hash_algorithm.hash == HashAlgorithm::Sha384 => {
-            &ring::signature::ECDSA_P384_SHA384_ASN1
-        }
-        SignatureAlgorithm::Rsa if hash_algorithm.hash == HashAlgorithm::Sha1 => {
-            &ring::signature::RSA_PKCS1_1024_8192_SHA1_FOR_LEGACY_USE_ONLY
-        }
-        SignatureAlgorithm::Rsa if (hash_algorithm.hash == HashAlgorithm::Sha256) => {
-            if remote_key_signature.len() < 256 && insecure_verification {
-                &ring::signature::RSA_PKCS1_1024_8192_SHA256_FOR_LEGACY_USE_ONLY
-            } else {
-                &ring::signature::RSA_PKCS1_2048_8192_SHA256
-            }
-        }
-        SignatureAlgorithm::Rsa if hash_algorithm.hash == HashAlgorithm::Sha384 => {
-            &ring::signature::RSA_PKCS1_2048_8192_SHA384
-        }
-        SignatureAlgorithm::Rsa if hash_algorithm.hash == HashAlgorithm::Sha512 => {
-            if remote_key_signature.len() < 256 && insecure_verification {
-                &ring::signature::RSA_PKCS1_1024_8192_SHA512_FOR_LEGACY_USE_ONLY
-            } else {
-                &ring::signature::RSA_PKCS1_2048_8192_SHA512
-            }
-        }
-        _ => return Err(Error::ErrKeySignatureVerifyUnimplemented),
-    };
-
-    log::trace!("Picked an algorithm {:?}", verify_alg);
-
-    let public_key = ring::signature::UnparsedPublicKey::new(
-        verify_alg,
-        certificate
-            .tbs_certificate
-            .subject_pki
-            .subject_public_key
-            .data,
-    );
-
-    public_key
-        .verify(message, remote_key_signature)
-        .map_err(|e| Error::Other(e.to_string()))?;
-
-    Ok(())
+    provider::get_default().verify(
+        hash_algorithm,
+        certificate.tbs_certificate.subject_pki.raw,
+        message,
+        remote_key_signature,
+        insecure_verification,
+    )
 }
 
 pub(crate) fn verify_key_signature(
@@ -387,31 +328,7 @@ pub(crate) fn generate_certificate_verify(
     handshake_bodies: &[u8],
     private_key: &CryptoPrivateKey, /*, hashAlgorithm hashAlgorithm*/
 ) -> Result<Vec<u8>> {
-    let signature = match &private_key.kind {
-        CryptoPrivateKeyKind::Ed25519(kp) => kp.sign(handshake_bodies).as_ref().to_vec(),
-        CryptoPrivateKeyKind::Ecdsa256(kp) => {
-            let system_random = SystemRandom::new();
-            kp.sign(&system_random, handshake_bodies)
-                .map_err(|e| Error::Other(e.to_string()))?
-                .as_ref()
-                .to_vec()
-        }
-        CryptoPrivateKeyKind::Rsa256(kp) => {
-            let system_random = SystemRandom::new();
-            let mut signature = vec![0; kp.public().modulus_len()];
-            kp.sign(
-                &ring::signature::RSA_PKCS1_SHA256,
-                &system_random,
-                handshake_bodies,
-                &mut signature,
-            )
-            .map_err(|e| Error::Other(e.to_string()))?;
-
-            signature
-        }
-    };
-
-    Ok(signature)
+    provider::get_default().sign(private_key, handshake_bodies)
 }
 
 pub(crate) fn verify_certificate_verify(
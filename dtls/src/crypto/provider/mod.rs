@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod provider_test;
+
+#[cfg(feature = "openssl")]
+pub mod openssl;
+#[cfg(feature = "ring")]
+pub mod ring;
+pub mod rust_crypto;
+
+use std::sync::{Arc, OnceLock};
+
+use super::CryptoPrivateKey;
+use crate::curve::named_curve::NamedCurve;
+use crate::error::*;
+use crate::signature_hash_algorithm::{HashAlgorithm, SignatureHashAlgorithm};
+
+/// CryptoProvider supplies the primitives of the cipher suites, of the key
+/// exchange and of the handshake signatures, so that they can be backed by a
+/// certified module without forking this crate.
+///
+/// The provider is process wide, see `install_default`.
+pub trait CryptoProvider: Send + Sync {
+    /// aes_gcm returns AES-GCM keyed with key, with 16 bytes tags.
+    fn aes_gcm(&self, key: &[u8]) -> Result<Arc<dyn Aead>>;
+
+    /// aes_ccm returns AES-CCM keyed with key, with tag_len (8 or 16) bytes tags.
+    fn aes_ccm(&self, key: &[u8], tag_len: usize) -> Result<Arc<dyn Aead>>;
+
+    /// aes_cbc returns AES-CBC keyed with key, without padding.
+    fn aes_cbc(&self, key: &[u8]) -> Result<Arc<dyn BlockCipher>>;
+
+    /// hmac returns the HMAC of the concatenation of data keyed with key.
+    fn hmac(&self, hash: HashAlgorithm, key: &[u8], data: &[&[u8]]) -> Result<Vec<u8>>;
+
+    /// hash returns the digest of data.
+    fn hash(&self, hash: HashAlgorithm, data: &[u8]) -> Result<Vec<u8>>;
+
+    /// key_exchange generates an ephemeral ECDHE keypair on curve, one of
+    /// P-256, P-384 or X25519.
+    fn key_exchange(&self, curve: NamedCurve) -> Result<Box<dyn KeyExchange>>;
+
+    /// sign signs message with private_key, RSA keys with PKCS#1 v1.5 and SHA-256.
+    fn sign(&self, private_key: &CryptoPrivateKey, message: &[u8]) -> Result<Vec<u8>>;
+
+    /// verify verifies the signature of message by public_key, the DER encoded
+    /// SubjectPublicKeyInfo of the peer's certificate. Unless insecure_verification
+    /// is set RSA keys shorter than 2048 bits are rejected.
+    fn verify(
+        &self,
+        hash_algorithm: &SignatureHashAlgorithm,
+        public_key: &[u8],
+        message: &[u8],
+        signature: &[u8],
+        insecure_verification: bool,
+    ) -> Result<()>;
+}
+
+/// Aead is an AEAD cipher.
+pub trait Aead: Send + Sync {
+    /// encrypt encrypts buffer in place and appends the tag.
+    fn encrypt(&self, nonce: &[u8], additional_data: &[u8], buffer: &mut Vec<u8>) -> Result<()>;
+
+    /// decrypt verifies and strips the tag, and decrypts buffer in place.
+    fn decrypt(&self, nonce: &[u8], additional_data: &[u8], buffer: &mut Vec<u8>) -> Result<()>;
+}
+
+/// BlockCipher is a block cipher in CBC mode, the data is a multiple of the block size.
+pub trait BlockCipher: Send + Sync {
+    fn encrypt(&self, iv: &[u8], data: &mut [u8]) -> Result<()>;
+
+    fn decrypt(&self, iv: &[u8], data: &mut [u8]) -> Result<()>;
+}
+
+/// KeyExchange is the private half of an ephemeral ECDHE keypair.
+pub trait KeyExchange: Send + Sync {
+    fn curve(&self) -> NamedCurve;
+
+    /// public_key returns the encoded public key, sent in the key exchange messages.
+    fn public_key(&self) -> Result<Vec<u8>>;
+
+    /// diffie_hellman returns the shared secret with the encoded peer_public_key.
+    fn diffie_hellman(&self, peer_public_key: &[u8]) -> Result<Vec<u8>>;
+}
+
+static DEFAULT_PROVIDER: OnceLock<Arc<dyn CryptoProvider>> = OnceLock::new();
+
+/// install_default sets the provider of the connections of this process. It must be
+/// called before the first connection is made, the provider is handed back otherwise.
+pub fn install_default(
+    provider: Arc<dyn CryptoProvider>,
+) -> std::result::Result<(), Arc<dyn CryptoProvider>> {
+    DEFAULT_PROVIDER.set(provider)
+}
+
+/// get_default returns the installed provider. Unless one was installed it's
+/// `rust_crypto::RustCryptoProvider`, `ring::RingProvider` with the `ring` feature,
+/// or `openssl::OpensslProvider` with the `openssl` feature.
+pub fn get_default() -> &'static Arc<dyn CryptoProvider> {
+    DEFAULT_PROVIDER.get_or_init(|| {
+        #[cfg(feature = "openssl")]
+        let provider = Arc::new(self::openssl::OpensslProvider);
+        #[cfg(all(feature = "ring", not(feature = "openssl")))]
+        let provider = Arc::new(self::ring::RingProvider);
+        #[cfg(not(any(feature = "ring", feature = "openssl")))]
+        let provider = Arc::new(self::rust_crypto::RustCryptoProvider);
+
+        provider
+    })
+}
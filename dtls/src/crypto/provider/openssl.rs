@@ -0,0 +1,377 @@
+use ::openssl::bn::BigNumContext;
+use ::openssl::cipher::{Cipher as CipherAlgorithm, CipherRef};
+use ::openssl::cipher_ctx::CipherCtx;
+use ::openssl::derive::Deriver;
+use ::openssl::ec::{EcGroup, EcKey, EcPoint, PointConversionForm};
+use ::openssl::error::ErrorStack;
+use ::openssl::hash::{hash, MessageDigest};
+use ::openssl::nid::Nid;
+use ::openssl::pkey::{Id, PKey, Private};
+use ::openssl::sign::{Signer, Verifier};
+use ::openssl::symm::{Cipher, Crypter, Mode};
+
+use super::*;
+use crate::crypto::CryptoPrivateKeyKind;
+use crate::signature_hash_algorithm::SignatureAlgorithm;
+
+/// OpensslProvider implements all the primitives with OpenSSL, which allows using
+/// a FIPS validated provider when OpenSSL is configured accordingly.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OpensslProvider;
+
+impl CryptoProvider for OpensslProvider {
+    fn aes_gcm(&self, key: &[u8]) -> Result<Arc<dyn Aead>> {
+        let cipher = match key.len() {
+            16 => CipherAlgorithm::aes_128_gcm(),
+            32 => CipherAlgorithm::aes_256_gcm(),
+            _ => return Err(Error::Aes(aes::cipher::InvalidLength)),
+        };
+
+        Ok(Arc::new(AesAead {
+            cipher,
+            key: key.to_vec(),
+            tag_len: 16,
+        }))
+    }
+
+    fn aes_ccm(&self, key: &[u8], tag_len: usize) -> Result<Arc<dyn Aead>> {
+        if tag_len != 8 && tag_len != 16 {
+            return Err(Error::Other(format!(
+                "unsupported CCM tag length {tag_len}"
+            )));
+        }
+        let cipher = match key.len() {
+            16 => CipherAlgorithm::aes_128_ccm(),
+            32 => CipherAlgorithm::aes_256_ccm(),
+            _ => return Err(Error::Aes(aes::cipher::InvalidLength)),
+        };
+
+        Ok(Arc::new(AesAead {
+            cipher,
+            key: key.to_vec(),
+            tag_len,
+        }))
+    }
+
+    fn aes_cbc(&self, key: &[u8]) -> Result<Arc<dyn BlockCipher>> {
+        let cipher = match key.len() {
+            16 => Cipher::aes_128_cbc(),
+            32 => Cipher::aes_256_cbc(),
+            _ => return Err(Error::Aes(aes::cipher::InvalidLength)),
+        };
+
+        Ok(Arc::new(AesCbc {
+            cipher,
+            key: key.to_vec(),
+        }))
+    }
+
+    fn hmac(&self, hash: HashAlgorithm, key: &[u8], data: &[&[u8]]) -> Result<Vec<u8>> {
+        // OpenSSL 3 rejects empty keys, HMAC pads the key with zeros to the block size
+        // so a single zero byte is the same key
+        let key = PKey::hmac(if key.is_empty() { &[0] } else { key }).map_err(other)?;
+        let mut signer = Signer::new(message_digest(hash)?, &key).map_err(other)?;
+        for d in data {
+            signer.update(d).map_err(other)?;
+        }
+        signer.sign_to_vec().map_err(other)
+    }
+
+    fn hash(&self, hash_algorithm: HashAlgorithm, data: &[u8]) -> Result<Vec<u8>> {
+        Ok(hash(message_digest(hash_algorithm)?, data)
+            .map_err(other)?
+            .to_vec())
+    }
+
+    fn key_exchange(&self, curve: NamedCurve) -> Result<Box<dyn KeyExchange>> {
+        let key = match curve {
+            NamedCurve::P256 => ec_key(Nid::X9_62_PRIME256V1)?,
+            NamedCurve::P384 => ec_key(Nid::SECP384R1)?,
+            NamedCurve::X25519 => PKey::generate_x25519().map_err(other)?,
+            _ => return Err(Error::ErrInvalidNamedCurve),
+        };
+
+        Ok(Box::new(EcdhKey { curve, key }))
+    }
+
+    fn sign(&self, private_key: &CryptoPrivateKey, message: &[u8]) -> Result<Vec<u8>> {
+        let mut signer = match private_key.kind {
+            CryptoPrivateKeyKind::Ed25519(_) => {
+                let seed = ed25519_seed(&private_key.serialized_der)?;
+                let key = PKey::private_key_from_raw_bytes(&seed, Id::ED25519).map_err(other)?;
+                Signer::new_without_digest(&key)
+            }
+            CryptoPrivateKeyKind::Ecdsa256(_) | CryptoPrivateKeyKind::Rsa256(_) => {
+                let key = PKey::private_key_from_der(&private_key.serialized_der).map_err(other)?;
+                Signer::new(MessageDigest::sha256(), &key)
+            }
+        }
+        .map_err(other)?;
+
+        signer.sign_oneshot_to_vec(message).map_err(other)
+    }
+
+    fn verify(
+        &self,
+        hash_algorithm: &SignatureHashAlgorithm,
+        public_key: &[u8],
+        message: &[u8],
+        signature: &[u8],
+        insecure_verification: bool,
+    ) -> Result<()> {
+        let key = PKey::public_key_from_der(public_key).map_err(other)?;
+
+        let (digest, key_matches) = match (hash_algorithm.signature, hash_algorithm.hash) {
+            (SignatureAlgorithm::Ed25519, _) => (None, key.id() == Id::ED25519),
+            (SignatureAlgorithm::Ecdsa, HashAlgorithm::Sha256) => (
+                Some(MessageDigest::sha256()),
+                ec_curve(&key) == Some(Nid::X9_62_PRIME256V1),
+            ),
+            (SignatureAlgorithm::Ecdsa, HashAlgorithm::Sha384) => (
+                Some(MessageDigest::sha384()),
+                ec_curve(&key) == Some(Nid::SECP384R1),
+            ),
+            (
+                SignatureAlgorithm::Rsa,
+                hash @ (HashAlgorithm::Sha1
+                | HashAlgorithm::Sha256
+                | HashAlgorithm::Sha384
+                | HashAlgorithm::Sha512),
+            ) => {
+                // Same key sizes as the ring algorithms of the default provider
+                let legacy = hash == HashAlgorithm::Sha1
+                    || (hash != HashAlgorithm::Sha384
+                        && signature.len() < 256
+                        && insecure_verification);
+                let min_bits = if legacy { 1024 } else { 2048 };
+                (
+                    Some(message_digest(hash)?),
+                    key.id() == Id::RSA && (min_bits..=8192).contains(&key.bits()),
+                )
+            }
+            _ => return Err(Error::ErrKeySignatureVerifyUnimplemented),
+        };
+        if !key_matches {
+            return Err(Error::Other(
+                "public key doesn't match the signature algorithm".to_owned(),
+            ));
+        }
+
+        let mut verifier = match digest {
+            Some(digest) => Verifier::new(digest, &key),
+            None => Verifier::new_without_digest(&key),
+        }
+        .map_err(other)?;
+
+        match verifier.verify_oneshot(signature, message) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Error::Other("signature verification failed".to_owned())),
+            Err(err) => Err(other(err)),
+        }
+    }
+}
+
+fn other(err: ErrorStack) -> Error {
+    Error::Other(err.to_string())
+}
+
+// ed25519_seed returns the private key of an Ed25519 PKCS#8 document, as the
+// v2 documents of ring, which carry the public key, aren't parsed by OpenSSL
+fn ed25519_seed(pkcs8: &[u8]) -> Result<Vec<u8>> {
+    let seed = der_parser::parse_der(pkcs8).ok().and_then(|(_, info)| {
+        let private_key = info.as_sequence().ok()?.get(2)?.as_slice().ok()?;
+        let (_, seed) = der_parser::parse_der(private_key).ok()?;
+        Some(seed.as_slice().ok()?.to_vec())
+    });
+
+    seed.ok_or_else(|| Error::Other("invalid Ed25519 PKCS#8 document".to_owned()))
+}
+
+fn message_digest(hash: HashAlgorithm) -> Result<MessageDigest> {
+    match hash {
+        HashAlgorithm::Sha1 => Ok(MessageDigest::sha1()),
+        HashAlgorithm::Sha256 => Ok(MessageDigest::sha256()),
+        HashAlgorithm::Sha384 => Ok(MessageDigest::sha384()),
+        HashAlgorithm::Sha512 => Ok(MessageDigest::sha512()),
+        _ => Err(Error::ErrInvalidHashAlgorithm),
+    }
+}
+
+fn ec_key(nid: Nid) -> Result<PKey<Private>> {
+    let group = EcGroup::from_curve_name(nid).map_err(other)?;
+    let key = EcKey::generate(&group).map_err(other)?;
+    PKey::from_ec_key(key).map_err(other)
+}
+
+fn ec_curve<T: ::openssl::pkey::HasPublic>(key: &PKey<T>) -> Option<Nid> {
+    key.ec_key().ok()?.group().curve_name()
+}
+
+struct AesAead {
+    cipher: &'static CipherRef,
+    key: Vec<u8>,
+    tag_len: usize,
+}
+
+impl AesAead {
+    // The nonce and tag lengths of CCM must be set before the key and nonce, which
+    // `symm::encrypt_aead` doesn't do.
+    fn init(&self, mode: Mode, nonce: &[u8], tag: Option<&[u8]>) -> Result<CipherCtx> {
+        let mut ctx = CipherCtx::new().map_err(other)?;
+        match mode {
+            Mode::Encrypt => ctx.encrypt_init(Some(self.cipher), None, None),
+            Mode::Decrypt => ctx.decrypt_init(Some(self.cipher), None, None),
+        }
+        .map_err(other)?;
+        ctx.set_iv_length(nonce.len()).map_err(other)?;
+        match tag {
+            Some(tag) => ctx.set_tag(tag).map_err(other)?,
+            None if self.is_ccm() => ctx.set_tag_length(self.tag_len).map_err(other)?,
+            None => {}
+        }
+        match mode {
+            Mode::Encrypt => ctx.encrypt_init(None, Some(&self.key), Some(nonce)),
+            Mode::Decrypt => ctx.decrypt_init(None, Some(&self.key), Some(nonce)),
+        }
+        .map_err(other)?;
+
+        Ok(ctx)
+    }
+
+    fn is_ccm(&self) -> bool {
+        self.cipher.nid() == Nid::AES_128_CCM || self.cipher.nid() == Nid::AES_256_CCM
+    }
+}
+
+impl Aead for AesAead {
+    fn encrypt(&self, nonce: &[u8], additional_data: &[u8], buffer: &mut Vec<u8>) -> Result<()> {
+        let mut ctx = self.init(Mode::Encrypt, nonce, None)?;
+        if self.is_ccm() {
+            ctx.set_data_len(buffer.len()).map_err(other)?;
+        }
+        ctx.cipher_update(additional_data, None).map_err(other)?;
+
+        let mut out = vec![];
+        ctx.cipher_update_vec(buffer, &mut out).map_err(other)?;
+        ctx.cipher_final_vec(&mut out).map_err(other)?;
+
+        let mut tag = vec![0u8; self.tag_len];
+        ctx.tag(&mut tag).map_err(other)?;
+
+        *buffer = out;
+        buffer.extend_from_slice(&tag);
+
+        Ok(())
+    }
+
+    fn decrypt(&self, nonce: &[u8], additional_data: &[u8], buffer: &mut Vec<u8>) -> Result<()> {
+        if buffer.len() < self.tag_len {
+            return Err(Error::ErrInvalidMac);
+        }
+
+        let (ciphertext, tag) = buffer.split_at(buffer.len() - self.tag_len);
+        let mut ctx = self.init(Mode::Decrypt, nonce, Some(tag))?;
+        if self.is_ccm() {
+            ctx.set_data_len(ciphertext.len()).map_err(other)?;
+        }
+        ctx.cipher_update(additional_data, None).map_err(other)?;
+
+        // CCM verifies the tag in the update, GCM in the final
+        let mut out = vec![];
+        ctx.cipher_update_vec(ciphertext, &mut out).map_err(other)?;
+        if !self.is_ccm() {
+            ctx.cipher_final_vec(&mut out).map_err(other)?;
+        }
+
+        *buffer = out;
+
+        Ok(())
+    }
+}
+
+struct AesCbc {
+    cipher: Cipher,
+    key: Vec<u8>,
+}
+
+impl AesCbc {
+    fn apply(&self, mode: Mode, iv: &[u8], data: &mut [u8]) -> Result<()> {
+        if !data.len().is_multiple_of(self.cipher.block_size()) {
+            return Err(Error::ErrInvalidPacketLength);
+        }
+
+        let mut crypter = Crypter::new(self.cipher, mode, &self.key, Some(iv)).map_err(other)?;
+        crypter.pad(false);
+
+        let mut out = vec![0u8; data.len() + self.cipher.block_size()];
+        let mut n = crypter.update(data, &mut out).map_err(other)?;
+        n += crypter.finalize(&mut out[n..]).map_err(other)?;
+        data.copy_from_slice(&out[..n]);
+
+        Ok(())
+    }
+}
+
+impl BlockCipher for AesCbc {
+    fn encrypt(&self, iv: &[u8], data: &mut [u8]) -> Result<()> {
+        self.apply(Mode::Encrypt, iv, data)
+    }
+
+    fn decrypt(&self, iv: &[u8], data: &mut [u8]) -> Result<()> {
+        self.apply(Mode::Decrypt, iv, data)
+    }
+}
+
+struct EcdhKey {
+    curve: NamedCurve,
+    key: PKey<Private>,
+}
+
+impl EcdhKey {
+    fn peer_key(&self, peer_public_key: &[u8]) -> Result<PKey<::openssl::pkey::Public>> {
+        match self.curve {
+            NamedCurve::X25519 => {
+                if peer_public_key.len() != 32 {
+                    return Err(Error::Other("Public key is not 32 len".into()));
+                }
+                PKey::public_key_from_raw_bytes(peer_public_key, Id::X25519).map_err(other)
+            }
+            _ => {
+                let ec_key = self.key.ec_key().map_err(other)?;
+                let group = ec_key.group();
+                let mut ctx = BigNumContext::new().map_err(other)?;
+                let point = EcPoint::from_bytes(group, peer_public_key, &mut ctx).map_err(other)?;
+                let peer = EcKey::from_public_key(group, &point).map_err(other)?;
+                peer.check_key().map_err(other)?;
+                PKey::from_ec_key(peer).map_err(other)
+            }
+        }
+    }
+}
+
+impl KeyExchange for EcdhKey {
+    fn curve(&self) -> NamedCurve {
+        self.curve
+    }
+
+    fn public_key(&self) -> Result<Vec<u8>> {
+        match self.curve {
+            NamedCurve::X25519 => self.key.raw_public_key().map_err(other),
+            _ => {
+                let ec_key = self.key.ec_key().map_err(other)?;
+                let mut ctx = BigNumContext::new().map_err(other)?;
+                ec_key
+                    .public_key()
+                    .to_bytes(ec_key.group(), PointConversionForm::UNCOMPRESSED, &mut ctx)
+                    .map_err(other)
+            }
+        }
+    }
+
+    fn diffie_hellman(&self, peer_public_key: &[u8]) -> Result<Vec<u8>> {
+        let peer = self.peer_key(peer_public_key)?;
+        let mut deriver = Deriver::new(&self.key).map_err(other)?;
+        deriver.set_peer(&peer).map_err(other)?;
+        deriver.derive_to_vec().map_err(other)
+    }
+}
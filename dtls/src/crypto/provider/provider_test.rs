@@ -0,0 +1,246 @@
+use super::rust_crypto::RustCryptoProvider;
+use super::*;
+use crate::crypto::Certificate;
+use crate::signature_hash_algorithm::SignatureAlgorithm;
+
+fn providers() -> Vec<Box<dyn CryptoProvider>> {
+    vec![
+        Box::new(RustCryptoProvider),
+        #[cfg(feature = "ring")]
+        Box::new(super::ring::RingProvider),
+        #[cfg(feature = "openssl")]
+        Box::new(super::openssl::OpensslProvider),
+    ]
+}
+
+#[test]
+fn test_aes_gcm() -> Result<()> {
+    // Test Case 2 of the GCM specification
+    let expected = vec![
+        0x03, 0x88, 0xda, 0xce, 0x60, 0xb6, 0xa3, 0x92, 0xf3, 0x28, 0xc2, 0xb9, 0x71, 0xb2, 0xfe,
+        0x78, 0xab, 0x6e, 0x47, 0xd4, 0x2c, 0xec, 0x13, 0xbd, 0xf5, 0x3a, 0x67, 0xb2, 0x12, 0x57,
+        0xbd, 0xdf,
+    ];
+
+    for provider in providers() {
+        let gcm = provider.aes_gcm(&[0u8; 16])?;
+
+        let mut buffer = vec![0u8; 16];
+        gcm.encrypt(&[0u8; 12], &[], &mut buffer)?;
+        assert_eq!(buffer, expected);
+
+        let mut tampered = buffer.clone();
+        tampered[0] ^= 1;
+        assert!(gcm.decrypt(&[0u8; 12], &[], &mut tampered).is_err());
+
+        gcm.decrypt(&[0u8; 12], &[], &mut buffer)?;
+        assert_eq!(buffer, vec![0u8; 16]);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_aes_ccm() -> Result<()> {
+    let key = [0x18u8; 16];
+    let nonce = [0x0eu8; 12];
+    let additional_data = b"header";
+    let plaintext = b"some application data".to_vec();
+
+    let mut ciphertexts = vec![];
+    for provider in providers() {
+        for tag_len in [8, 16] {
+            let ccm = provider.aes_ccm(&key, tag_len)?;
+
+            let mut buffer = plaintext.clone();
+            ccm.encrypt(&nonce, additional_data, &mut buffer)?;
+            assert_eq!(buffer.len(), plaintext.len() + tag_len);
+            ciphertexts.push(buffer.clone());
+
+            assert!(ccm.decrypt(&nonce, b"other", &mut buffer.clone()).is_err());
+
+            ccm.decrypt(&nonce, additional_data, &mut buffer)?;
+            assert_eq!(buffer, plaintext);
+        }
+
+        assert!(provider.aes_ccm(&key, 12).is_err());
+    }
+
+    // The providers agree
+    for (i, ciphertext) in ciphertexts.iter().enumerate().skip(2) {
+        assert_eq!(ciphertext, &ciphertexts[i % 2]);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_aes_cbc() -> Result<()> {
+    // CBC-AES256.Encrypt from NIST SP 800-38A, F.2.5
+    let key = [
+        0x60, 0x3d, 0xeb, 0x10, 0x15, 0xca, 0x71, 0xbe, 0x2b, 0x73, 0xae, 0xf0, 0x85, 0x7d, 0x77,
+        0x81, 0x1f, 0x35, 0x2c, 0x07, 0x3b, 0x61, 0x08, 0xd7, 0x2d, 0x98, 0x10, 0xa3, 0x09, 0x14,
+        0xdf, 0xf4,
+    ];
+    let iv = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+        0x0f,
+    ];
+    let plaintext = [
+        0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17,
+        0x2a, 0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf,
+        0x8e, 0x51,
+    ];
+    let expected = [
+        0xf5, 0x8c, 0x4c, 0x04, 0xd6, 0xe5, 0xf1, 0xba, 0x77, 0x9e, 0xab, 0xfb, 0x5f, 0x7b, 0xfb,
+        0xd6, 0x9c, 0xfc, 0x4e, 0x96, 0x7e, 0xdb, 0x80, 0x8d, 0x67, 0x9f, 0x77, 0x7b, 0xc6, 0x70,
+        0x2c, 0x7d,
+    ];
+
+    for provider in providers() {
+        let cbc = provider.aes_cbc(&key)?;
+
+        let mut data = plaintext;
+        cbc.encrypt(&iv, &mut data)?;
+        assert_eq!(data, expected);
+
+        cbc.decrypt(&iv, &mut data)?;
+        assert_eq!(data, plaintext);
+
+        assert!(cbc.encrypt(&iv, &mut [0u8; 15]).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_hmac_and_hash() -> Result<()> {
+    // Test Case 2 from https://tools.ietf.org/html/rfc2202 and https://tools.ietf.org/html/rfc4231
+    let expected_sha1 = vec![
+        0xef, 0xfc, 0xdf, 0x6a, 0xe5, 0xeb, 0x2f, 0xa2, 0xd2, 0x74, 0x16, 0xd5, 0xf1, 0x84, 0xdf,
+        0x9c, 0x25, 0x9a, 0x7c, 0x79,
+    ];
+    let expected_sha256 = vec![
+        0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08, 0x95, 0x75,
+        0xc7, 0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec, 0x58, 0xb9, 0x64, 0xec,
+        0x38, 0x43,
+    ];
+    // SHA-256 of "abc" from FIPS 180-2
+    let expected_digest = vec![
+        0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae, 0x22,
+        0x23, 0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61, 0xf2, 0x00,
+        0x15, 0xad,
+    ];
+
+    for provider in providers() {
+        assert_eq!(
+            provider.hmac(
+                HashAlgorithm::Sha1,
+                b"Jefe",
+                &[b"what do ya want for nothing?"]
+            )?,
+            expected_sha1
+        );
+        assert_eq!(
+            provider.hmac(
+                HashAlgorithm::Sha256,
+                b"Jefe",
+                &[b"what do ya ", b"want for nothing?"]
+            )?,
+            expected_sha256
+        );
+        assert_eq!(
+            provider.hash(HashAlgorithm::Sha256, b"abc")?,
+            expected_digest
+        );
+
+        assert_eq!(
+            provider.hash(HashAlgorithm::Md5, b"abc"),
+            Err(Error::ErrInvalidHashAlgorithm)
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_key_exchange() -> Result<()> {
+    for curve in [NamedCurve::P256, NamedCurve::P384, NamedCurve::X25519] {
+        let keys = providers()
+            .iter()
+            .map(|provider| provider.key_exchange(curve))
+            .collect::<Result<Vec<_>>>()?;
+        let peer = RustCryptoProvider.key_exchange(curve)?;
+
+        for key in keys {
+            assert_eq!(key.curve(), curve);
+            assert_eq!(
+                key.diffie_hellman(&peer.public_key()?)?,
+                peer.diffie_hellman(&key.public_key()?)?
+            );
+        }
+    }
+
+    for provider in providers() {
+        assert!(provider.key_exchange(NamedCurve::Unsupported).is_err());
+
+        let key = provider.key_exchange(NamedCurve::X25519)?;
+        assert!(key.diffie_hellman(&[0u8; 31]).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_sign_and_verify() -> Result<()> {
+    let message = b"handshake bodies";
+
+    for (alg, hash_algorithm) in [
+        (
+            &rcgen::PKCS_ECDSA_P256_SHA256,
+            SignatureHashAlgorithm {
+                hash: HashAlgorithm::Sha256,
+                signature: SignatureAlgorithm::Ecdsa,
+            },
+        ),
+        (
+            &rcgen::PKCS_ED25519,
+            SignatureHashAlgorithm {
+                hash: HashAlgorithm::Ed25519,
+                signature: SignatureAlgorithm::Ed25519,
+            },
+        ),
+    ] {
+        let certificate =
+            Certificate::generate_self_signed_with_alg(vec!["webrtc.rs".to_owned()], alg)?;
+        let (_, x509) = x509_parser::parse_x509_certificate(certificate.certificate[0].as_ref())
+            .map_err(|e| Error::Other(e.to_string()))?;
+        let public_key = x509.tbs_certificate.subject_pki.raw;
+
+        // Each provider verifies the signatures of every other
+        for signer in providers() {
+            let signature = signer.sign(&certificate.private_key, message)?;
+
+            for verifier in providers() {
+                verifier.verify(&hash_algorithm, public_key, message, &signature, false)?;
+                assert!(verifier
+                    .verify(&hash_algorithm, public_key, b"tampered", &signature, false)
+                    .is_err());
+            }
+        }
+
+        // The key must match the signature algorithm
+        let mismatched = SignatureHashAlgorithm {
+            hash: HashAlgorithm::Sha384,
+            signature: SignatureAlgorithm::Ecdsa,
+        };
+        for verifier in providers() {
+            let signature = verifier.sign(&certificate.private_key, message)?;
+            assert!(verifier
+                .verify(&mismatched, public_key, message, &signature, false)
+                .is_err());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use ::ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_128_GCM, AES_256_GCM};
+use ::ring::agreement::{EphemeralPrivateKey, UnparsedPublicKey, ECDH_P256, ECDH_P384, X25519};
+use ::ring::digest;
+use ::ring::error::Unspecified;
+use ::ring::hmac;
+use ::ring::rand::SystemRandom;
+use util::sync::Mutex;
+
+use super::rust_crypto::RustCryptoProvider;
+use super::*;
+
+/// RingProvider implements AES-GCM, the HMACs, the hashes, the key exchange and the
+/// signatures with ring. ring has neither AES-CCM nor AES-CBC, those are left to
+/// `RustCryptoProvider`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RingProvider;
+
+impl CryptoProvider for RingProvider {
+    fn aes_gcm(&self, key: &[u8]) -> Result<Arc<dyn Aead>> {
+        let algorithm = match key.len() {
+            16 => &AES_128_GCM,
+            32 => &AES_256_GCM,
+            _ => return Err(Error::Aes(aes::cipher::InvalidLength)),
+        };
+
+        Ok(Arc::new(AesGcm(LessSafeKey::new(
+            UnboundKey::new(algorithm, key).map_err(other)?,
+        ))))
+    }
+
+    fn aes_ccm(&self, key: &[u8], tag_len: usize) -> Result<Arc<dyn Aead>> {
+        RustCryptoProvider.aes_ccm(key, tag_len)
+    }
+
+    fn aes_cbc(&self, key: &[u8]) -> Result<Arc<dyn BlockCipher>> {
+        RustCryptoProvider.aes_cbc(key)
+    }
+
+    fn hmac(&self, hash: HashAlgorithm, key: &[u8], data: &[&[u8]]) -> Result<Vec<u8>> {
+        let algorithm = match hash {
+            HashAlgorithm::Sha1 => hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY,
+            HashAlgorithm::Sha256 => hmac::HMAC_SHA256,
+            HashAlgorithm::Sha384 => hmac::HMAC_SHA384,
+            HashAlgorithm::Sha512 => hmac::HMAC_SHA512,
+            _ => return Err(Error::ErrInvalidHashAlgorithm),
+        };
+
+        let mut ctx = hmac::Context::with_key(&hmac::Key::new(algorithm, key));
+        for d in data {
+            ctx.update(d);
+        }
+        Ok(ctx.sign().as_ref().to_vec())
+    }
+
+    fn hash(&self, hash: HashAlgorithm, data: &[u8]) -> Result<Vec<u8>> {
+        let algorithm = match hash {
+            HashAlgorithm::Sha1 => &digest::SHA1_FOR_LEGACY_USE_ONLY,
+            HashAlgorithm::Sha256 => &digest::SHA256,
+            HashAlgorithm::Sha384 => &digest::SHA384,
+            HashAlgorithm::Sha512 => &digest::SHA512,
+            _ => return Err(Error::ErrInvalidHashAlgorithm),
+        };
+
+        Ok(digest::digest(algorithm, data).as_ref().to_vec())
+    }
+
+    fn key_exchange(&self, curve: NamedCurve) -> Result<Box<dyn KeyExchange>> {
+        let algorithm = match curve {
+            NamedCurve::P256 => &ECDH_P256,
+            NamedCurve::P384 => &ECDH_P384,
+            NamedCurve::X25519 => &X25519,
+            _ => return Err(Error::ErrInvalidNamedCurve),
+        };
+
+        let private_key =
+            EphemeralPrivateKey::generate(algorithm, &SystemRandom::new()).map_err(other)?;
+        let public_key = private_key.compute_public_key().map_err(other)?;
+
+        Ok(Box::new(EcdhKey {
+            curve,
+            public_key: public_key.as_ref().to_vec(),
+            private_key: Mutex::new(Some(private_key)),
+        }))
+    }
+
+    fn sign(&self, private_key: &CryptoPrivateKey, message: &[u8]) -> Result<Vec<u8>> {
+        // The handshake keys are ring keys already
+        RustCryptoProvider.sign(private_key, message)
+    }
+
+    fn verify(
+        &self,
+        hash_algorithm: &SignatureHashAlgorithm,
+        public_key: &[u8],
+        message: &[u8],
+        signature: &[u8],
+        insecure_verification: bool,
+    ) -> Result<()> {
+        RustCryptoProvider.verify(
+            hash_algorithm,
+            public_key,
+            message,
+            signature,
+            insecure_verification,
+        )
+    }
+}
+
+fn other(err: Unspecified) -> Error {
+    Error::Other(err.to_string())
+}
+
+struct AesGcm(LessSafeKey);
+
+impl Aead for AesGcm {
+    fn encrypt(&self, nonce: &[u8], additional_data: &[u8], buffer: &mut Vec<u8>) -> Result<()> {
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(other)?;
+        self.0
+            .seal_in_place_append_tag(nonce, Aad::from(additional_data), buffer)
+            .map_err(other)
+    }
+
+    fn decrypt(&self, nonce: &[u8], additional_data: &[u8], buffer: &mut Vec<u8>) -> Result<()> {
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(other)?;
+        let len = self
+            .0
+            .open_in_place(nonce, Aad::from(additional_data), buffer)
+            .map_err(other)?
+            .len();
+        buffer.truncate(len);
+        Ok(())
+    }
+}
+
+/// EcdhKey holds the private key until the one agreement ring allows with it.
+struct EcdhKey {
+    curve: NamedCurve,
+    public_key: Vec<u8>,
+    private_key: Mutex<Option<EphemeralPrivateKey>>,
+}
+
+impl KeyExchange for EcdhKey {
+    fn curve(&self) -> NamedCurve {
+        self.curve
+    }
+
+    fn public_key(&self) -> Result<Vec<u8>> {
+        Ok(self.public_key.clone())
+    }
+
+    fn diffie_hellman(&self, peer_public_key: &[u8]) -> Result<Vec<u8>> {
+        let private_key = self
+            .private_key
+            .lock()
+            .take()
+            .ok_or_else(|| Error::Other("the key was already agreed on".to_owned()))?;
+        let peer_public_key = UnparsedPublicKey::new(private_key.algorithm(), peer_public_key);
+
+        ::ring::agreement::agree_ephemeral(private_key, &peer_public_key, |secret| secret.to_vec())
+            .map_err(other)
+    }
+}
@@ -0,0 +1,294 @@
+use std::convert::TryInto;
+
+use ::ring::rand::SystemRandom;
+use aes::cipher::block_padding::NoPadding;
+use aes::cipher::{BlockDecryptMut, BlockEncryptMut, InvalidLength, KeyIvInit};
+use aes::{Aes128, Aes256};
+use aes_gcm::aead::generic_array::GenericArray;
+use aes_gcm::aead::AeadInPlace;
+use aes_gcm::{Aes128Gcm, KeyInit};
+use ccm::consts::{U12, U16, U8};
+use ccm::Ccm;
+use hmac::Hmac;
+use rand_core::OsRng;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use x509_parser::prelude::FromDer;
+use x509_parser::x509::SubjectPublicKeyInfo;
+
+use super::*;
+use crate::crypto::CryptoPrivateKeyKind;
+use crate::signature_hash_algorithm::SignatureAlgorithm;
+
+/// RustCryptoProvider implements the ciphers, the HMACs and the key exchange with
+/// the RustCrypto crates. Signatures are made and verified with ring, RustCrypto
+/// being used for neither RSA nor Ed25519 here.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RustCryptoProvider;
+
+impl CryptoProvider for RustCryptoProvider {
+    fn aes_gcm(&self, key: &[u8]) -> Result<Arc<dyn Aead>> {
+        Ok(Arc::new(AesGcm(Aes128Gcm::new_from_slice(key)?)))
+    }
+
+    fn aes_ccm(&self, key: &[u8], tag_len: usize) -> Result<Arc<dyn Aead>> {
+        match tag_len {
+            8 => Ok(Arc::new(AesCcm::Ccm8(AesCcm8::new_from_slice(key)?))),
+            16 => Ok(Arc::new(AesCcm::Ccm(AesCcm16::new_from_slice(key)?))),
+            _ => Err(Error::Other(format!(
+                "unsupported CCM tag length {tag_len}"
+            ))),
+        }
+    }
+
+    fn aes_cbc(&self, key: &[u8]) -> Result<Arc<dyn BlockCipher>> {
+        match key.len() {
+            16 | 32 => Ok(Arc::new(AesCbc(key.to_vec()))),
+            _ => Err(Error::Aes(InvalidLength)),
+        }
+    }
+
+    fn hmac(&self, hash: HashAlgorithm, key: &[u8], data: &[&[u8]]) -> Result<Vec<u8>> {
+        match hash {
+            HashAlgorithm::Sha1 => hmac::<Hmac<Sha1>>(key, data),
+            HashAlgorithm::Sha256 => hmac::<Hmac<Sha256>>(key, data),
+            HashAlgorithm::Sha384 => hmac::<Hmac<Sha384>>(key, data),
+            HashAlgorithm::Sha512 => hmac::<Hmac<Sha512>>(key, data),
+            _ => Err(Error::ErrInvalidHashAlgorithm),
+        }
+    }
+
+    fn hash(&self, hash: HashAlgorithm, data: &[u8]) -> Result<Vec<u8>> {
+        match hash {
+            HashAlgorithm::Sha1 => Ok(Sha1::digest(data).to_vec()),
+            HashAlgorithm::Sha256 => Ok(Sha256::digest(data).to_vec()),
+            HashAlgorithm::Sha384 => Ok(Sha384::digest(data).to_vec()),
+            HashAlgorithm::Sha512 => Ok(Sha512::digest(data).to_vec()),
+            _ => Err(Error::ErrInvalidHashAlgorithm),
+        }
+    }
+
+    fn key_exchange(&self, curve: NamedCurve) -> Result<Box<dyn KeyExchange>> {
+        match curve {
+            NamedCurve::P256 => Ok(Box::new(EcdhSecret::P256(
+                p256::ecdh::EphemeralSecret::random(&mut OsRng),
+            ))),
+            NamedCurve::P384 => Ok(Box::new(EcdhSecret::P384(
+                p384::ecdh::EphemeralSecret::random(&mut OsRng),
+            ))),
+            NamedCurve::X25519 => Ok(Box::new(EcdhSecret::X25519(
+                x25519_dalek::StaticSecret::random_from_rng(OsRng),
+            ))),
+            _ => Err(Error::ErrInvalidNamedCurve),
+        }
+    }
+
+    fn sign(&self, private_key: &CryptoPrivateKey, message: &[u8]) -> Result<Vec<u8>> {
+        let signature = match &private_key.kind {
+            CryptoPrivateKeyKind::Ed25519(kp) => kp.sign(message).as_ref().to_vec(),
+            CryptoPrivateKeyKind::Ecdsa256(kp) => {
+                let system_random = SystemRandom::new();
+                kp.sign(&system_random, message)
+                    .map_err(|e| Error::Other(e.to_string()))?
+                    .as_ref()
+                    .to_vec()
+            }
+            CryptoPrivateKeyKind::Rsa256(kp) => {
+                let system_random = SystemRandom::new();
+                let mut signature = vec![0; kp.public().modulus_len()];
+                kp.sign(
+                    &::ring::signature::RSA_PKCS1_SHA256,
+                    &system_random,
+                    message,
+                    &mut signature,
+                )
+                .map_err(|e| Error::Other(e.to_string()))?;
+
+                signature
+            }
+        };
+
+        Ok(signature)
+    }
+
+    fn verify(
+        &self,
+        hash_algorithm: &SignatureHashAlgorithm,
+        public_key: &[u8],
+        message: &[u8],
+        signature: &[u8],
+        insecure_verification: bool,
+    ) -> Result<()> {
+        let verify_alg: &dyn ::ring::signature::VerificationAlgorithm =
+            match hash_algorithm.signature {
+                SignatureAlgorithm::Ed25519 => &::ring::signature::ED25519,
+                SignatureAlgorithm::Ecdsa if hash_algorithm.hash == HashAlgorithm::Sha256 => {
+                    &::ring::signature::ECDSA_P256_SHA256_ASN1
+                }
+                SignatureAlgorithm::Ecdsa if hash_algorithm.hash == HashAlgorithm::Sha384 => {
+                    &::ring::signature::ECDSA_P384_SHA384_ASN1
+                }
+                SignatureAlgorithm::Rsa if hash_algorithm.hash == HashAlgorithm::Sha1 => {
+                    &::ring::signature::RSA_PKCS1_1024_8192_SHA1_FOR_LEGACY_USE_ONLY
+                }
+                SignatureAlgorithm::Rsa if (hash_algorithm.hash == HashAlgorithm::Sha256) => {
+                    if signature.len() < 256 && insecure_verification {
+                        &::ring::signature::RSA_PKCS1_1024_8192_SHA256_FOR_LEGACY_USE_ONLY
+                    } else {
+                        &::ring::signature::RSA_PKCS1_2048_8192_SHA256
+                    }
+                }
+                SignatureAlgorithm::Rsa if hash_algorithm.hash == HashAlgorithm::Sha384 => {
+                    &::ring::signature::RSA_PKCS1_2048_8192_SHA384
+                }
+                SignatureAlgorithm::Rsa if hash_algorithm.hash == HashAlgorithm::Sha512 => {
+                    if signature.len() < 256 && insecure_verification {
+                        &::ring::signature::RSA_PKCS1_1024_8192_SHA512_FOR_LEGACY_USE_ONLY
+                    } else {
+                        &::ring::signature::RSA_PKCS1_2048_8192_SHA512
+                    }
+                }
+                _ => return Err(Error::ErrKeySignatureVerifyUnimplemented),
+            };
+
+        log::trace!("Picked an algorithm {:?}", verify_alg);
+
+        let (_, spki) =
+            SubjectPublicKeyInfo::from_der(public_key).map_err(|e| Error::Other(e.to_string()))?;
+        ::ring::signature::UnparsedPublicKey::new(verify_alg, spki.subject_public_key.data)
+            .verify(message, signature)
+            .map_err(|e| Error::Other(e.to_string()))
+    }
+}
+
+fn hmac<M: hmac::Mac + KeyInit>(key: &[u8], data: &[&[u8]]) -> Result<Vec<u8>> {
+    let mut mac = <M as hmac::Mac>::new_from_slice(key).map_err(|e| Error::Other(e.to_string()))?;
+    for d in data {
+        mac.update(d);
+    }
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+struct AesGcm(Aes128Gcm);
+
+impl Aead for AesGcm {
+    fn encrypt(&self, nonce: &[u8], additional_data: &[u8], buffer: &mut Vec<u8>) -> Result<()> {
+        self.0
+            .encrypt_in_place(GenericArray::from_slice(nonce), additional_data, buffer)
+            .map_err(|e| Error::Other(e.to_string()))
+    }
+
+    fn decrypt(&self, nonce: &[u8], additional_data: &[u8], buffer: &mut Vec<u8>) -> Result<()> {
+        self.0
+            .decrypt_in_place(GenericArray::from_slice(nonce), additional_data, buffer)
+            .map_err(|e| Error::Other(e.to_string()))
+    }
+}
+
+type AesCcm8 = Ccm<Aes128, U8, U12>;
+type AesCcm16 = Ccm<Aes128, U16, U12>;
+
+enum AesCcm {
+    Ccm8(AesCcm8),
+    Ccm(AesCcm16),
+}
+
+impl Aead for AesCcm {
+    fn encrypt(&self, nonce: &[u8], additional_data: &[u8], buffer: &mut Vec<u8>) -> Result<()> {
+        let nonce = GenericArray::from_slice(nonce);
+        match self {
+            AesCcm::Ccm8(ccm) => ccm.encrypt_in_place(nonce, additional_data, buffer),
+            AesCcm::Ccm(ccm) => ccm.encrypt_in_place(nonce, additional_data, buffer),
+        }
+        .map_err(|e| Error::Other(e.to_string()))
+    }
+
+    fn decrypt(&self, nonce: &[u8], additional_data: &[u8], buffer: &mut Vec<u8>) -> Result<()> {
+        let nonce = GenericArray::from_slice(nonce);
+        match self {
+            AesCcm::Ccm8(ccm) => ccm.decrypt_in_place(nonce, additional_data, buffer),
+            AesCcm::Ccm(ccm) => ccm.decrypt_in_place(nonce, additional_data, buffer),
+        }
+        .map_err(|e| Error::Other(e.to_string()))
+    }
+}
+
+struct AesCbc(Vec<u8>);
+
+impl BlockCipher for AesCbc {
+    fn encrypt(&self, iv: &[u8], data: &mut [u8]) -> Result<()> {
+        let len = data.len();
+        match self.0.len() {
+            16 => cbc::Encryptor::<Aes128>::new_from_slices(&self.0, iv)?
+                .encrypt_padded_mut::<NoPadding>(data, len)
+                .map(|_| ()),
+            _ => cbc::Encryptor::<Aes256>::new_from_slices(&self.0, iv)?
+                .encrypt_padded_mut::<NoPadding>(data, len)
+                .map(|_| ()),
+        }
+        .map_err(|_| Error::ErrInvalidPacketLength)
+    }
+
+    fn decrypt(&self, iv: &[u8], data: &mut [u8]) -> Result<()> {
+        match self.0.len() {
+            16 => cbc::Decryptor::<Aes128>::new_from_slices(&self.0, iv)?
+                .decrypt_padded_mut::<NoPadding>(data)
+                .map(|_| ()),
+            _ => cbc::Decryptor::<Aes256>::new_from_slices(&self.0, iv)?
+                .decrypt_padded_mut::<NoPadding>(data)
+                .map(|_| ()),
+        }
+        .map_err(|_| Error::ErrInvalidPacketLength)
+    }
+}
+
+pub(crate) enum EcdhSecret {
+    P256(p256::ecdh::EphemeralSecret),
+    P384(p384::ecdh::EphemeralSecret),
+    X25519(x25519_dalek::StaticSecret),
+}
+
+impl KeyExchange for EcdhSecret {
+    fn curve(&self) -> NamedCurve {
+        match self {
+            EcdhSecret::P256(_) => NamedCurve::P256,
+            EcdhSecret::P384(_) => NamedCurve::P384,
+            EcdhSecret::X25519(_) => NamedCurve::X25519,
+        }
+    }
+
+    fn public_key(&self) -> Result<Vec<u8>> {
+        let public_key = match self {
+            EcdhSecret::P256(secret) => p256::EncodedPoint::from(secret.public_key())
+                .as_bytes()
+                .to_vec(),
+            EcdhSecret::P384(secret) => p384::EncodedPoint::from(secret.public_key())
+                .as_bytes()
+                .to_vec(),
+            EcdhSecret::X25519(secret) => x25519_dalek::PublicKey::from(secret).as_bytes().to_vec(),
+        };
+        Ok(public_key)
+    }
+
+    fn diffie_hellman(&self, peer_public_key: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            EcdhSecret::P256(secret) => {
+                let pub_key = p256::EncodedPoint::from_bytes(peer_public_key)?;
+                let public = p256::PublicKey::from_sec1_bytes(pub_key.as_ref())?;
+                Ok(secret.diffie_hellman(&public).raw_secret_bytes().to_vec())
+            }
+            EcdhSecret::P384(secret) => {
+                let pub_key = p384::EncodedPoint::from_bytes(peer_public_key)?;
+                let public = p384::PublicKey::from_sec1_bytes(pub_key.as_ref())?;
+                Ok(secret.diffie_hellman(&public).raw_secret_bytes().to_vec())
+            }
+            EcdhSecret::X25519(secret) => {
+                let pub_key: [u8; 32] = peer_public_key
+                    .try_into()
+                    .map_err(|_| Error::Other("Public key is not 32 len".into()))?;
+                let public = x25519_dalek::PublicKey::from(pub_key);
+                Ok(secret.diffie_hellman(&public).as_bytes().to_vec())
+            }
+        }
+    }
+}
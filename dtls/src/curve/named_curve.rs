@@ -1,5 +1,11 @@
+#[cfg(feature = "pq-hybrid")]
+use ml_kem::kem::{Decapsulate, Encapsulate};
+#[cfg(feature = "pq-hybrid")]
+use ml_kem::{EncodedSizeUser, KemCore, MlKem768};
+#[cfg(feature = "pq-hybrid")]
 use rand_core::OsRng; // requires 'getrandom' feature
 
+use crate::crypto::provider::{self, KeyExchange};
 use crate::error::*;
 
 // https://www.iana.org/assignments/tls-parameters/tls-parameters.xml#tls-parameters-8
@@ -10,6 +16,10 @@ pub enum NamedCurve {
     P256 = 0x0017,
     P384 = 0x0018,
     X25519 = 0x001d,
+    // Experimental hybrid of X25519 and ML-KEM-768
+    // https://datatracker.ietf.org/doc/draft-kwiatkowski-tls-ecdhe-mlkem/
+    #[cfg(feature = "pq-hybrid")]
+    X25519MlKem768 = 0x11ec,
 }
 
 impl From<u16> for NamedCurve {
@@ -18,17 +28,36 @@ impl From<u16> for NamedCurve {
             0x0017 => NamedCurve::P256,
             0x0018 => NamedCurve::P384,
             0x001d => NamedCurve::X25519,
+            #[cfg(feature = "pq-hybrid")]
+            0x11ec => NamedCurve::X25519MlKem768,
             _ => NamedCurve::Unsupported,
         }
     }
 }
 
 pub(crate) enum NamedCurvePrivateKey {
-    EphemeralSecretP256(p256::ecdh::EphemeralSecret),
-    EphemeralSecretP384(p384::ecdh::EphemeralSecret),
-    StaticSecretX25519(x25519_dalek::StaticSecret),
+    // ECDHE key of the crypto provider
+    Ecdhe(Box<dyn KeyExchange>),
+    // The hybrid group is always implemented with RustCrypto
+    // Server side of the hybrid group: X25519 secret and ML-KEM decapsulation key
+    #[cfg(feature = "pq-hybrid")]
+    X25519MlKem768Decapsulation(
+        x25519_dalek::StaticSecret,
+        Box<<MlKem768 as KemCore>::DecapsulationKey>,
+    ),
+    // Client side of the hybrid group: X25519 secret and the encapsulated ML-KEM shared secret
+    #[cfg(feature = "pq-hybrid")]
+    X25519MlKem768Encapsulation(x25519_dalek::StaticSecret, Vec<u8>),
 }
 
+// Sizes of the ML-KEM-768 encapsulation key and ciphertext
+#[cfg(feature = "pq-hybrid")]
+pub(crate) const MLKEM768_ENCAPSULATION_KEY_SIZE: usize = 1184;
+#[cfg(feature = "pq-hybrid")]
+pub(crate) const MLKEM768_CIPHERTEXT_SIZE: usize = 1088;
+#[cfg(feature = "pq-hybrid")]
+pub(crate) const X25519_PUBLIC_KEY_SIZE: usize = 32;
+
 pub struct NamedCurveKeypair {
     pub(crate) curve: NamedCurve,
     pub(crate) public_key: Vec<u8>,
@@ -36,39 +65,106 @@ pub struct NamedCurveKeypair {
 }
 
 fn elliptic_curve_keypair(curve: NamedCurve) -> Result<NamedCurveKeypair> {
-    let (public_key, private_key) = match curve {
-        NamedCurve::P256 => {
-            let secret_key = p256::ecdh::EphemeralSecret::random(&mut OsRng);
-            let public_key = p256::EncodedPoint::from(secret_key.public_key());
-            (
-                public_key.as_bytes().to_vec(),
-                NamedCurvePrivateKey::EphemeralSecretP256(secret_key),
-            )
-        }
-        NamedCurve::P384 => {
-            let secret_key = p384::ecdh::EphemeralSecret::random(&mut OsRng);
-            let public_key = p384::EncodedPoint::from(secret_key.public_key());
+    let key_exchange = provider::get_default().key_exchange(curve)?;
+
+    Ok(NamedCurveKeypair {
+        curve,
+        public_key: key_exchange.public_key()?,
+        private_key: NamedCurvePrivateKey::Ecdhe(key_exchange),
+    })
+}
+
+// The server sends the ML-KEM encapsulation key followed by its X25519 share
+#[cfg(feature = "pq-hybrid")]
+fn hybrid_server_keypair() -> Result<NamedCurveKeypair> {
+    let (dk, ek) = MlKem768::generate(&mut OsRng);
+    let secret_key = x25519_dalek::StaticSecret::random_from_rng(OsRng);
+
+    let mut public_key = ek.as_bytes().to_vec();
+    public_key.extend_from_slice(x25519_dalek::PublicKey::from(&secret_key).as_bytes());
+
+    Ok(NamedCurveKeypair {
+        curve: NamedCurve::X25519MlKem768,
+        public_key,
+        private_key: NamedCurvePrivateKey::X25519MlKem768Decapsulation(secret_key, Box::new(dk)),
+    })
+}
+
+// The client answers with the ML-KEM ciphertext followed by its X25519 share
+#[cfg(feature = "pq-hybrid")]
+fn hybrid_client_keypair(peer_public_key: &[u8]) -> Result<NamedCurveKeypair> {
+    if peer_public_key.len() != MLKEM768_ENCAPSULATION_KEY_SIZE + X25519_PUBLIC_KEY_SIZE {
+        return Err(Error::ErrInvalidNamedCurve);
+    }
+
+    let encoded = ml_kem::Encoded::<<MlKem768 as KemCore>::EncapsulationKey>::try_from(
+        &peer_public_key[..MLKEM768_ENCAPSULATION_KEY_SIZE],
+    )
+    .map_err(|_| Error::ErrInvalidNamedCurve)?;
+    let ek = <MlKem768 as KemCore>::EncapsulationKey::from_bytes(&encoded);
+    let (ct, shared_key) = ek
+        .encapsulate(&mut OsRng)
+        .map_err(|_| Error::Other("ML-KEM encapsulation failed".into()))?;
+    let secret_key = x25519_dalek::StaticSecret::random_from_rng(OsRng);
+
+    let mut public_key = ct.to_vec();
+    public_key.extend_from_slice(x25519_dalek::PublicKey::from(&secret_key).as_bytes());
+
+    Ok(NamedCurveKeypair {
+        curve: NamedCurve::X25519MlKem768,
+        public_key,
+        private_key: NamedCurvePrivateKey::X25519MlKem768Encapsulation(
+            secret_key,
+            shared_key.to_vec(),
+        ),
+    })
+}
+
+// Combines the ML-KEM and X25519 shared secrets, in that order
+#[cfg(feature = "pq-hybrid")]
+pub(crate) fn hybrid_pre_master_secret(
+    public_key: &[u8],
+    private_key: &NamedCurvePrivateKey,
+) -> Result<Vec<u8>> {
+    let (mut pre_master_secret, secret_key, peer_share) = match private_key {
+        NamedCurvePrivateKey::X25519MlKem768Decapsulation(secret_key, dk) => {
+            if public_key.len() != MLKEM768_CIPHERTEXT_SIZE + X25519_PUBLIC_KEY_SIZE {
+                return Err(Error::ErrInvalidNamedCurve);
+            }
+            let ct =
+                ml_kem::Ciphertext::<MlKem768>::try_from(&public_key[..MLKEM768_CIPHERTEXT_SIZE])
+                    .map_err(|_| Error::ErrInvalidNamedCurve)?;
+            let shared_key = dk
+                .decapsulate(&ct)
+                .map_err(|_| Error::Other("ML-KEM decapsulation failed".into()))?;
+
             (
-                public_key.as_bytes().to_vec(),
-                NamedCurvePrivateKey::EphemeralSecretP384(secret_key),
+                shared_key.to_vec(),
+                secret_key,
+                &public_key[MLKEM768_CIPHERTEXT_SIZE..],
             )
         }
-        NamedCurve::X25519 => {
-            let secret_key = x25519_dalek::StaticSecret::random_from_rng(OsRng);
-            let public_key = x25519_dalek::PublicKey::from(&secret_key);
+        NamedCurvePrivateKey::X25519MlKem768Encapsulation(secret_key, shared_key) => {
+            if public_key.len() != MLKEM768_ENCAPSULATION_KEY_SIZE + X25519_PUBLIC_KEY_SIZE {
+                return Err(Error::ErrInvalidNamedCurve);
+            }
+
             (
-                public_key.as_bytes().to_vec(),
-                NamedCurvePrivateKey::StaticSecretX25519(secret_key),
+                shared_key.clone(),
+                secret_key,
+                &public_key[MLKEM768_ENCAPSULATION_KEY_SIZE..],
             )
         }
-        _ => return Err(Error::ErrInvalidNamedCurve),
+        _ => return Err(Error::ErrNamedCurveAndPrivateKeyMismatch),
     };
 
-    Ok(NamedCurveKeypair {
-        curve,
-        public_key,
-        private_key,
-    })
+    let peer_share: [u8; X25519_PUBLIC_KEY_SIZE] = peer_share
+        .try_into()
+        .map_err(|_| Error::ErrInvalidNamedCurve)?;
+    let public = x25519_dalek::PublicKey::from(peer_share);
+    pre_master_secret.extend_from_slice(secret_key.diffie_hellman(&public).as_bytes());
+
+    Ok(pre_master_secret)
 }
 
 impl NamedCurve {
@@ -77,7 +173,47 @@ impl NamedCurve {
             NamedCurve::X25519 => elliptic_curve_keypair(NamedCurve::X25519),
             NamedCurve::P256 => elliptic_curve_keypair(NamedCurve::P256),
             NamedCurve::P384 => elliptic_curve_keypair(NamedCurve::P384),
+            #[cfg(feature = "pq-hybrid")]
+            NamedCurve::X25519MlKem768 => hybrid_server_keypair(),
             _ => Err(Error::ErrInvalidNamedCurve),
         }
     }
+
+    // generate_client_keypair generates the keypair the client sends in
+    // ClientKeyExchange, which depends on the server's public key for KEM based groups
+    pub(crate) fn generate_client_keypair(
+        &self,
+        peer_public_key: &[u8],
+    ) -> Result<NamedCurveKeypair> {
+        match *self {
+            #[cfg(feature = "pq-hybrid")]
+            NamedCurve::X25519MlKem768 => hybrid_client_keypair(peer_public_key),
+            _ => {
+                let _ = peer_public_key;
+                self.generate_keypair()
+            }
+        }
+    }
+
+    // is_hybrid reports whether the public key exceeds the one byte length
+    // prefix of ECPoint and thus needs a two byte prefix on the wire
+    pub(crate) fn is_hybrid(&self) -> bool {
+        #[cfg(feature = "pq-hybrid")]
+        if *self == NamedCurve::X25519MlKem768 {
+            return true;
+        }
+
+        false
+    }
+}
+
+// default_named_curves returns the groups offered in the ClientHello, in order of preference
+pub(crate) fn default_named_curves() -> Vec<NamedCurve> {
+    vec![
+        #[cfg(feature = "pq-hybrid")]
+        NamedCurve::X25519MlKem768,
+        NamedCurve::P256,
+        NamedCurve::X25519,
+        NamedCurve::P384,
+    ]
 }
@@ -0,0 +1,1062 @@
+#[cfg(test)]
+mod driver_test;
+
+use std::collections::VecDeque;
+use std::io::{BufReader, BufWriter, IoSlice};
+use std::net::SocketAddr;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use log::*;
+use util::replay_detector::*;
+
+use crate::alert::*;
+use crate::application_data::*;
+use crate::cipher_suite::*;
+use crate::config::*;
+use crate::conn::*;
+use crate::content::*;
+use crate::error::*;
+use crate::extension::extension_supported_ekt_ciphers::*;
+use crate::extension::extension_use_srtp::*;
+use crate::flight::flight0::*;
+use crate::flight::flight1::*;
+use crate::flight::flight5::*;
+use crate::flight::flight6::*;
+use crate::flight::*;
+use crate::fragment_buffer::*;
+use crate::handshake::handshake_cache::*;
+use crate::handshake::handshake_header::HandshakeHeader;
+use crate::handshake::*;
+use crate::handshake_observer::*;
+use crate::handshaker::*;
+use crate::record_layer::record_layer_header::*;
+use crate::record_layer::*;
+use crate::signature_hash_algorithm::parse_signature_schemes;
+use crate::state::*;
+
+/// DTLSDriver is the sans-IO state machine of a DTLS connection: it runs the
+/// handshake and protects the records, but never touches a socket or a clock.
+///
+/// Datagrams received from the peer are fed in with `handle_read`, and the
+/// datagrams to send are taken out with `poll_transmit`. The retransmission
+/// timer is driven by calling `handle_timeout` once `poll_timeout` has passed.
+/// This allows running DTLS inside a custom event loop, over a socket shared
+/// with other protocols, or deterministically in tests where every datagram
+/// and every timeout is under control. `DTLSConn` runs one over a `Conn`.
+pub struct DTLSDriver {
+    pub(crate) state: State,
+    pub(crate) cache: HandshakeCache, // caching of handshake messages for verifyData generation
+    pub(crate) cfg: HandshakeConfig,
+    maximum_transmission_unit: usize,
+    replay_protection_window: usize,
+
+    pub(crate) handshake_state: HandshakeState,
+    handshake_completed_successfully: bool,
+    pub(crate) current_flight: Box<dyn Flight + Send + Sync>,
+    pub(crate) flights: Option<Vec<Packet>>,
+    pub(crate) retransmit: bool,
+    pub(crate) current_retransmit_interval: Duration,
+    pub(crate) flight_transmissions: usize,
+    pub(crate) retransmit_deadline: Option<Instant>,
+
+    fragment_buffer: FragmentBuffer,
+    encrypted_packets: Vec<Vec<u8>>, // records of the next epoch, handled once it can be decrypted
+
+    transmits: VecDeque<Vec<u8>>,
+    application_data: VecDeque<Vec<u8>>,
+    closed: bool,
+}
+
+impl DTLSDriver {
+    /// new creates the state machine of a connection as client or server. The
+    /// handshake starts with `start`.
+    pub fn new(config: Config, is_client: bool) -> Result<Self> {
+        DTLSDriver::with_state(config, is_client, None, None)
+    }
+
+    /// restore re-creates the state machine of an established connection from a
+    /// `DTLSConn::snapshot`, skipping the handshake.
+    pub fn restore(config: Config, is_client: bool, snapshot: &[u8]) -> Result<Self> {
+        let mut state = State::default();
+        state.unmarshal_state(snapshot)?;
+
+        DTLSDriver::with_state(config, is_client, Some(state), None)
+    }
+
+    pub(crate) fn with_state(
+        mut config: Config,
+        is_client: bool,
+        initial_state: Option<State>,
+        remote_addr: Option<SocketAddr>,
+    ) -> Result<Self> {
+        validate_config(is_client, &config)?;
+
+        let local_cipher_suites: Vec<CipherSuiteId> = parse_cipher_suites(
+            &config.cipher_suites,
+            config.psk.is_none(),
+            config.psk.is_some(),
+        )?
+        .iter()
+        .map(|cs| cs.id())
+        .collect();
+
+        let sigs: Vec<u16> = config.signature_schemes.iter().map(|x| *x as u16).collect();
+        let local_signature_schemes = parse_signature_schemes(&sigs, config.insecure_hashes)?;
+
+        let retransmit_interval = if config.flight_interval != Duration::from_secs(0) {
+            config.flight_interval
+        } else {
+            INITIAL_TICKER_INTERVAL
+        };
+
+        let maximum_transmission_unit = if config.mtu == 0 {
+            DEFAULT_MTU
+        } else {
+            config.mtu
+        };
+
+        let replay_protection_window = if config.replay_protection_window == 0 {
+            DEFAULT_REPLAY_PROTECTION_WINDOW
+        } else {
+            config.replay_protection_window
+        };
+
+        let mut server_name = config.server_name.clone();
+
+        // Use host from conn address when server_name is not provided
+        if is_client && server_name.is_empty() {
+            if let Some(remote_addr) = remote_addr {
+                server_name = remote_addr.ip().to_string();
+            } else {
+                warn!("conn.remote_addr is empty, please set explicitly server_name in Config! Use default \"localhost\" as server_name now");
+                "localhost".clone_into(&mut server_name);
+            }
+        }
+
+        let cfg = HandshakeConfig {
+            local_psk_callback: config.psk.take(),
+            local_psk_identity_hint: config.psk_identity_hint.take(),
+            local_cipher_suites,
+            local_signature_schemes,
+            extended_master_secret: config.extended_master_secret,
+            local_srtp_protection_profiles: config.srtp_protection_profiles.clone(),
+            local_ekt_ciphers: config.ekt_ciphers.clone(),
+            supported_protocols: config.supported_protocols.clone(),
+            local_record_size_limit: config.record_size_limit,
+            local_max_fragment_length: config.max_fragment_length,
+            local_ocsp_response: std::mem::take(&mut config.ocsp_response),
+            server_name,
+            client_auth: config.client_auth,
+            local_certificates: config.certificates.clone(),
+            insecure_skip_verify: config.insecure_skip_verify,
+            insecure_verification: config.insecure_verification,
+            verify_peer_certificate: config.verify_peer_certificate.take(),
+            verify_ocsp_response: config.verify_ocsp_response.take(),
+            client_cert_verifier: if config.client_auth as u8
+                >= ClientAuthType::VerifyClientCertIfGiven as u8
+            {
+                Some(
+                    rustls::server::WebPkiClientVerifier::builder(Arc::new(config.client_cas))
+                        .allow_unauthenticated()
+                        .build()
+                        .unwrap_or(
+                            rustls::server::WebPkiClientVerifier::builder(Arc::new(
+                                gen_self_signed_root_cert(),
+                            ))
+                            .allow_unauthenticated()
+                            .build()
+                            .unwrap(),
+                        ),
+                )
+            } else {
+                None
+            },
+            server_cert_verifier: rustls::client::WebPkiServerVerifier::builder(Arc::new(
+                config.roots_cas,
+            ))
+            .build()
+            .unwrap_or(
+                rustls::client::WebPkiServerVerifier::builder(
+                    Arc::new(gen_self_signed_root_cert()),
+                )
+                .build()
+                .unwrap(),
+            ),
+            retransmit_interval,
+            max_retransmit_interval: config.max_flight_interval,
+            retransmit_jitter: config.flight_interval_jitter,
+            insecure_skip_hello_verify: config.insecure_skip_hello_verify,
+            initial_epoch: 0,
+            handshake_observer: config.handshake_observer.take(),
+            ..Default::default()
+        };
+
+        let (state, flight, handshake_state) = if let Some(state) = initial_state {
+            let flight = if is_client {
+                Box::new(Flight5 {}) as Box<dyn Flight + Send + Sync>
+            } else {
+                Box::new(Flight6 {}) as Box<dyn Flight + Send + Sync>
+            };
+
+            (state, flight, HandshakeState::Finished)
+        } else {
+            let flight = if is_client {
+                Box::new(Flight1 {}) as Box<dyn Flight + Send + Sync>
+            } else {
+                Box::new(Flight0 {}) as Box<dyn Flight + Send + Sync>
+            };
+
+            (
+                State {
+                    is_client,
+                    ..Default::default()
+                },
+                flight,
+                HandshakeState::Preparing,
+            )
+        };
+
+        Ok(DTLSDriver {
+            state,
+            cache: HandshakeCache::new(),
+            cfg,
+            maximum_transmission_unit,
+            replay_protection_window,
+
+            handshake_state,
+            handshake_completed_successfully: false,
+            current_flight: flight,
+            flights: None,
+            retransmit: false,
+            current_retransmit_interval: retransmit_interval,
+            flight_transmissions: 0,
+            retransmit_deadline: None,
+
+            fragment_buffer: FragmentBuffer::new(),
+            encrypted_packets: vec![],
+
+            transmits: VecDeque::new(),
+            application_data: VecDeque::new(),
+            closed: false,
+        })
+    }
+
+    /// start starts the handshake, the client sends its ClientHello.
+    pub fn start(&mut self, now: Instant) -> Result<()> {
+        self.step_handshake(now, false)
+    }
+
+    /// handle_read handles a datagram received from the peer.
+    ///
+    /// Returns `ErrAlertFatalOrClose` once the peer closed the connection or
+    /// sent a fatal alert, the datagrams to answer it are still to be sent.
+    pub fn handle_read(&mut self, now: Instant, datagram: &[u8]) -> Result<()> {
+        if self.closed {
+            return Err(Error::ErrConnClosed);
+        }
+
+        let pkts = unpack_datagram(datagram)?;
+        let mut has_handshake = false;
+        for pkt in pkts {
+            let (hs, alert, err) = self.handle_incoming_packet(pkt, true);
+            self.handle_alert(alert, err)?;
+
+            if hs {
+                has_handshake = true
+            }
+        }
+
+        if has_handshake {
+            loop {
+                self.step_handshake(now, true)?;
+
+                // The records of the next epoch can be decrypted once the cipher
+                // suite is initialized, the Finished of the peer among them
+                if !self.handle_queued_packets()? {
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// poll_transmit returns the next datagram to send to the peer, if any.
+    pub fn poll_transmit(&mut self) -> Option<Vec<u8>> {
+        self.transmits.pop_front()
+    }
+
+    /// poll_timeout returns when handle_timeout should be called next, if the
+    /// retransmission timer is running.
+    pub fn poll_timeout(&self) -> Option<Instant> {
+        self.retransmit_deadline
+    }
+
+    /// handle_timeout retransmits the last flight if the retransmission timer
+    /// expired by now.
+    pub fn handle_timeout(&mut self, now: Instant) -> Result<()> {
+        match self.retransmit_deadline {
+            Some(deadline) if deadline <= now => self.retransmit_deadline = None,
+            _ => return Ok(()),
+        }
+
+        trace!(
+            "[handshake:{}] {} retransmit_timer",
+            srv_cli_str(self.state.is_client),
+            self.current_flight.to_string()
+        );
+        match self.handshake_state {
+            HandshakeState::Waiting => {
+                if !self.retransmit {
+                    self.set_retransmit_timer(now);
+                    return Ok(());
+                }
+                self.current_retransmit_interval = next_retransmit_interval(
+                    self.current_retransmit_interval,
+                    self.cfg.max_retransmit_interval,
+                );
+            }
+            HandshakeState::Finished => {}
+            _ => return Ok(()),
+        }
+        self.handshake_state = HandshakeState::Sending;
+
+        self.step_handshake(now, false)
+    }
+
+    /// poll_application_data returns the next application data received from
+    /// the peer, if any.
+    pub fn poll_application_data(&mut self) -> Option<Vec<u8>> {
+        self.application_data.pop_front()
+    }
+
+    /// write writes p as application data.
+    pub fn write(&mut self, p: &[u8]) -> Result<usize> {
+        self.write_vectored(&[IoSlice::new(p)])
+    }
+
+    /// write_vectored writes the concatenation of bufs as application data,
+    /// gathering them straight into the application data records.
+    pub fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> Result<usize> {
+        if self.closed {
+            return Err(Error::ErrConnClosed);
+        }
+
+        if !self.handshake_completed_successfully {
+            return Err(Error::ErrHandshakeInProgress);
+        }
+
+        // Honor the record size limit negotiated with the peer by splitting
+        // the data across several records
+        let len: usize = bufs.iter().map(|b| b.len()).sum();
+        let record_size_limit = self.state.remote_record_size_limit as usize;
+        let record_len = if record_size_limit != 0 {
+            std::cmp::min(record_size_limit, len)
+        } else {
+            len
+        };
+
+        let mut fragments = vec![];
+        let mut data = Vec::with_capacity(record_len);
+        for b in bufs {
+            let mut b: &[u8] = b;
+            while !b.is_empty() {
+                let n = std::cmp::min(record_len - data.len(), b.len());
+                data.extend_from_slice(&b[..n]);
+                b = &b[n..];
+                if data.len() == record_len {
+                    fragments.push(std::mem::replace(&mut data, Vec::with_capacity(record_len)));
+                }
+            }
+        }
+        if !data.is_empty() || fragments.is_empty() {
+            fragments.push(data);
+        }
+
+        let pkts = fragments
+            .into_iter()
+            .map(|data| Packet {
+                record: RecordLayer::new(
+                    PROTOCOL_VERSION1_2,
+                    self.get_local_epoch(),
+                    Content::ApplicationData(ApplicationData { data }),
+                ),
+                should_encrypt: true,
+                reset_local_sequence_number: false,
+            })
+            .collect();
+        self.write_packets(pkts)?;
+
+        Ok(len)
+    }
+
+    /// close sends a close_notify to the peer, the connection can't be used
+    /// anymore.
+    pub fn close(&mut self) -> Result<()> {
+        if !self.closed {
+            self.closed = true;
+
+            self.notify(AlertLevel::Warning, AlertDescription::CloseNotify)?;
+        }
+
+        Ok(())
+    }
+
+    /// is_handshake_complete returns whether the handshake completed successfully.
+    pub fn is_handshake_complete(&self) -> bool {
+        self.handshake_completed_successfully
+    }
+
+    /// is_handshake_failed returns whether the handshake failed, the error was
+    /// returned by the call that failed it.
+    pub fn is_handshake_failed(&self) -> bool {
+        self.handshake_state == HandshakeState::Errored
+    }
+
+    /// is_closed returns whether the connection was closed with `close`.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// connection_state returns basic DTLS details about the connection.
+    pub fn connection_state(&self) -> State {
+        self.state.clone()
+    }
+
+    /// snapshot serializes the keys, epochs and sequence numbers of an established
+    /// connection, see `DTLSConn::snapshot`.
+    pub fn snapshot(&self) -> Result<Vec<u8>> {
+        if !self.handshake_completed_successfully {
+            return Err(Error::ErrHandshakeInProgress);
+        }
+
+        self.state.marshal_state()
+    }
+
+    /// selected_srtpprotection_profile returns the selected SRTPProtectionProfile
+    pub fn selected_srtpprotection_profile(&self) -> SrtpProtectionProfile {
+        self.state.srtp_protection_profile
+    }
+
+    /// selected_ekt_cipher returns the EKT cipher negotiated via supported_ekt_ciphers,
+    /// or EktCipher::Unsupported if EKT is not in use
+    pub fn selected_ekt_cipher(&self) -> EktCipher {
+        self.state.ekt_cipher
+    }
+
+    /// export_keying_material returns length bytes of keying material derived
+    /// from the master secret, see `DTLSConn::export_keying_material`.
+    pub fn export_keying_material(
+        &self,
+        label: &str,
+        context: Option<&[u8]>,
+        length: usize,
+    ) -> Result<Vec<u8>> {
+        if let Some(context) = context {
+            if context.len() > u16::MAX as usize {
+                return Err(Error::ErrContextTooLong);
+            }
+        }
+
+        Ok(self
+            .state
+            .export_keying_material_with_context(label, context, length)?)
+    }
+
+    /// record_size_limit returns the largest record plaintext the peer accepts,
+    /// as negotiated via record_size_limit or max_fragment_length. None if the
+    /// peer did not send a limit.
+    pub fn record_size_limit(&self) -> Option<u16> {
+        if self.state.remote_record_size_limit == 0 {
+            None
+        } else {
+            Some(self.state.remote_record_size_limit)
+        }
+    }
+
+    /// negotiated_application_protocol returns the protocol selected via ALPN,
+    /// or None if ALPN was not negotiated
+    pub fn negotiated_application_protocol(&self) -> Option<&str> {
+        if self.state.negotiated_protocol.is_empty() {
+            None
+        } else {
+            Some(&self.state.negotiated_protocol)
+        }
+    }
+
+    // step_handshake runs the handshake until it waits for the peer or the
+    // timer, parsing the handshake messages received if received is set
+    fn step_handshake(&mut self, now: Instant, received: bool) -> Result<()> {
+        let mut result = self.handshake(now);
+        if received && result.is_ok() {
+            result = match self.handshake_state {
+                HandshakeState::Waiting => self.wait(),
+                HandshakeState::Finished => self.finish(now),
+                _ => Ok(()),
+            }
+            .and_then(|_| self.handshake(now));
+        }
+
+        if let Err(err) = &result {
+            self.fail_handshake(err);
+        }
+        result
+    }
+
+    fn fail_handshake(&mut self, err: &Error) {
+        if self.handshake_state == HandshakeState::Errored {
+            return;
+        }
+        self.handshake_state = HandshakeState::Errored;
+        self.retransmit_deadline = None;
+        if !self.handshake_completed_successfully {
+            self.cfg.observe(
+                self.state.is_client,
+                HandshakeEvent::Failed(err.to_string()),
+            );
+        }
+    }
+
+    pub(crate) fn notify(&mut self, level: AlertLevel, desc: AlertDescription) -> Result<()> {
+        self.write_packets(vec![Packet {
+            record: RecordLayer::new(
+                PROTOCOL_VERSION1_2,
+                self.get_local_epoch(),
+                Content::Alert(Alert {
+                    alert_level: level,
+                    alert_description: desc,
+                }),
+            ),
+            should_encrypt: self.handshake_completed_successfully,
+            reset_local_sequence_number: false,
+        }])
+    }
+
+    // handle_alert answers the alert raised by a received record, and returns
+    // ErrAlertFatalOrClose if it ends the connection
+    fn handle_alert(&mut self, alert: Option<Alert>, mut err: Option<Error>) -> Result<()> {
+        if let Some(alert) = alert {
+            if let Err(alert_err) = self.notify(alert.alert_level, alert.alert_description) {
+                if err.is_none() {
+                    err = Some(alert_err);
+                }
+            }
+
+            if alert.alert_level == AlertLevel::Fatal
+                || alert.alert_description == AlertDescription::CloseNotify
+            {
+                self.fail_handshake_in_progress(&Error::ErrAlertFatalOrClose);
+                return Err(Error::ErrAlertFatalOrClose);
+            }
+        }
+
+        if let Some(err) = err {
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    fn fail_handshake_in_progress(&mut self, err: &Error) {
+        if !self.handshake_completed_successfully {
+            self.fail_handshake(err);
+        }
+    }
+
+    pub(crate) fn write_packets(&mut self, mut pkts: Vec<Packet>) -> Result<()> {
+        if let Some(observer) = &self.cfg.handshake_observer {
+            for p in &pkts {
+                if let Content::Alert(a) = &p.record.content {
+                    observer.on_event(self.state.is_client, &HandshakeEvent::AlertSent(*a));
+                }
+            }
+        }
+
+        let mut raw_packets = vec![];
+        for p in &mut pkts {
+            if let Content::Handshake(h) = &p.record.content {
+                let mut handshake_raw = vec![];
+                {
+                    let mut writer = BufWriter::<&mut Vec<u8>>::new(handshake_raw.as_mut());
+                    p.record.marshal(&mut writer)?;
+                }
+                trace!(
+                    "Send [handshake:{}] -> {} (epoch: {}, seq: {})",
+                    srv_cli_str(self.state.is_client),
+                    h.handshake_header.handshake_type.to_string(),
+                    p.record.record_layer_header.epoch,
+                    h.handshake_header.message_sequence
+                );
+                self.cache.push(
+                    handshake_raw[RECORD_LAYER_HEADER_SIZE..].to_vec(),
+                    p.record.record_layer_header.epoch,
+                    h.handshake_header.message_sequence,
+                    h.handshake_header.handshake_type,
+                    self.state.is_client,
+                );
+
+                let raw_handshake_packets = self.process_handshake_packet(p, h)?;
+                raw_packets.extend_from_slice(&raw_handshake_packets);
+            } else {
+                let raw_packet = self.process_packet(p)?;
+                raw_packets.push(raw_packet);
+            }
+        }
+
+        if !raw_packets.is_empty() {
+            self.transmits.extend(compact_raw_packets(
+                &raw_packets,
+                self.maximum_transmission_unit,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn process_packet(&self, p: &mut Packet) -> Result<Vec<u8>> {
+        let epoch = p.record.record_layer_header.epoch as usize;
+        let seq = {
+            let mut lsn = self.state.local_sequence_number.lock();
+            while lsn.len() <= epoch {
+                lsn.push(0);
+            }
+
+            lsn[epoch] += 1;
+            lsn[epoch] - 1
+        };
+
+        if seq > MAX_SEQUENCE_NUMBER {
+            // RFC 6347 Section 4.1.0
+            // The implementation must either abandon an association or rehandshake
+            // prior to allowing the sequence number to wrap.
+            return Err(Error::ErrSequenceNumberOverflow);
+        }
+        p.record.record_layer_header.sequence_number = seq;
+
+        let mut raw_packet = vec![];
+        {
+            let mut writer = BufWriter::<&mut Vec<u8>>::new(raw_packet.as_mut());
+            p.record.marshal(&mut writer)?;
+        }
+
+        if p.should_encrypt {
+            let cipher_suite = self.state.cipher_suite.lock();
+            if let Some(cipher_suite) = &*cipher_suite {
+                raw_packet = cipher_suite.encrypt(&p.record.record_layer_header, &raw_packet)?;
+            }
+        }
+
+        Ok(raw_packet)
+    }
+
+    fn process_handshake_packet(&self, p: &Packet, h: &Handshake) -> Result<Vec<Vec<u8>>> {
+        let mut raw_packets = vec![];
+
+        let handshake_fragments = fragment_handshake(self.maximum_transmission_unit, h)?;
+
+        let epoch = p.record.record_layer_header.epoch as usize;
+
+        let mut lsn = self.state.local_sequence_number.lock();
+        while lsn.len() <= epoch {
+            lsn.push(0);
+        }
+
+        for handshake_fragment in &handshake_fragments {
+            let seq = {
+                lsn[epoch] += 1;
+                lsn[epoch] - 1
+            };
+            if seq > MAX_SEQUENCE_NUMBER {
+                return Err(Error::ErrSequenceNumberOverflow);
+            }
+
+            let record_layer_header = RecordLayerHeader {
+                protocol_version: p.record.record_layer_header.protocol_version,
+                content_type: p.record.record_layer_header.content_type,
+                content_len: handshake_fragment.len() as u16,
+                epoch: p.record.record_layer_header.epoch,
+                sequence_number: seq,
+            };
+
+            let mut record_layer_header_bytes = vec![];
+            {
+                let mut writer = BufWriter::<&mut Vec<u8>>::new(record_layer_header_bytes.as_mut());
+                record_layer_header.marshal(&mut writer)?;
+            }
+
+            let mut raw_packet = vec![];
+            raw_packet.extend_from_slice(&record_layer_header_bytes);
+            raw_packet.extend_from_slice(handshake_fragment);
+            if p.should_encrypt {
+                let cipher_suite = self.state.cipher_suite.lock();
+                if let Some(cipher_suite) = &*cipher_suite {
+                    raw_packet = cipher_suite.encrypt(&record_layer_header, &raw_packet)?;
+                }
+            }
+
+            raw_packets.push(raw_packet);
+        }
+
+        Ok(raw_packets)
+    }
+
+    // handle_queued_packets handles the records queued until the cipher suite was
+    // initialized, and returns whether handshake messages were among them
+    fn handle_queued_packets(&mut self) -> Result<bool> {
+        if self.encrypted_packets.is_empty() || !self.is_cipher_suite_initialized() {
+            return Ok(false);
+        }
+
+        let mut has_handshake = false;
+        let pkts: Vec<Vec<u8>> = self.encrypted_packets.drain(..).collect();
+        for p in pkts {
+            let (hs, alert, err) = self.handle_incoming_packet(p, false); // don't re-enqueue
+            self.handle_alert(alert, err)?;
+
+            if hs {
+                has_handshake = true;
+            }
+        }
+
+        Ok(has_handshake)
+    }
+
+    fn is_cipher_suite_initialized(&self) -> bool {
+        match &*self.state.cipher_suite.lock() {
+            Some(cipher_suite) => cipher_suite.is_initialized(),
+            None => false,
+        }
+    }
+
+    fn handle_incoming_packet(
+        &mut self,
+        mut pkt: Vec<u8>,
+        enqueue: bool,
+    ) -> (bool, Option<Alert>, Option<Error>) {
+        let is_client = self.state.is_client;
+        let mut reader = BufReader::new(pkt.as_slice());
+        let h = match RecordLayerHeader::unmarshal(&mut reader) {
+            Ok(h) => h,
+            Err(err) => {
+                // Decode error must be silently discarded
+                // [RFC6347 Section-4.1.2.7]
+                debug!(
+                    "{}: discarded broken packet: {}",
+                    srv_cli_str(is_client),
+                    err
+                );
+                return (false, None, None);
+            }
+        };
+
+        // Validate epoch
+        let epoch = self.state.remote_epoch.load(Ordering::SeqCst);
+        if h.epoch > epoch {
+            if h.epoch > epoch + 1 {
+                debug!(
+                    "{}: discarded future packet (epoch: {}, seq: {})",
+                    srv_cli_str(is_client),
+                    h.epoch,
+                    h.sequence_number,
+                );
+                return (false, None, None);
+            }
+            if enqueue {
+                debug!(
+                    "{}: received packet of next epoch, queuing packet",
+                    srv_cli_str(is_client)
+                );
+                self.encrypted_packets.push(pkt);
+            }
+            return (false, None, None);
+        }
+
+        // Anti-replay protection
+        let ok = {
+            let mut replay_detector = self.state.replay_detector.lock();
+            while replay_detector.len() <= h.epoch as usize {
+                replay_detector.push(SlidingWindowDetector::new(
+                    self.replay_protection_window,
+                    MAX_SEQUENCE_NUMBER,
+                ));
+            }
+
+            replay_detector[h.epoch as usize].check(h.sequence_number)
+        };
+        if !ok {
+            debug!(
+                "{}: discarded duplicated packet (epoch: {}, seq: {})",
+                srv_cli_str(is_client),
+                h.epoch,
+                h.sequence_number,
+            );
+            return (false, None, None);
+        }
+
+        // Decrypt
+        if h.epoch != 0 {
+            if !self.is_cipher_suite_initialized() {
+                if enqueue {
+                    debug!(
+                        "{}: handshake not finished, queuing packet",
+                        srv_cli_str(is_client)
+                    );
+                    self.encrypted_packets.push(pkt);
+                }
+                return (false, None, None);
+            }
+
+            let cipher_suite = self.state.cipher_suite.lock();
+            if let Some(cipher_suite) = &*cipher_suite {
+                pkt = match cipher_suite.decrypt(&pkt) {
+                    Ok(pkt) => pkt,
+                    Err(err) => {
+                        debug!("{}: decrypt failed: {}", srv_cli_str(is_client), err);
+
+                        // If we get an error for PSK we need to return an error.
+                        if cipher_suite.is_psk() {
+                            return (
+                                false,
+                                Some(Alert {
+                                    alert_level: AlertLevel::Fatal,
+                                    alert_description: AlertDescription::UnknownPskIdentity,
+                                }),
+                                None,
+                            );
+                        } else {
+                            return (false, None, None);
+                        }
+                    }
+                };
+            }
+        }
+
+        let is_handshake = match self.fragment_buffer.push(&pkt) {
+            Ok(is_handshake) => is_handshake,
+            Err(err) => {
+                // Decode error must be silently discarded
+                // [RFC6347 Section-4.1.2.7]
+                debug!("{}: defragment failed: {}", srv_cli_str(is_client), err);
+                return (false, None, None);
+            }
+        };
+        if is_handshake {
+            self.state.replay_detector.lock()[h.epoch as usize].accept();
+            while let Ok((out, epoch)) = self.fragment_buffer.pop() {
+                let mut reader = BufReader::new(out.as_slice());
+                let raw_handshake = match Handshake::unmarshal(&mut reader) {
+                    Ok(rh) => {
+                        trace!(
+                            "Recv [handshake:{}] -> {} (epoch: {}, seq: {})",
+                            srv_cli_str(is_client),
+                            rh.handshake_header.handshake_type.to_string(),
+                            h.epoch,
+                            rh.handshake_header.message_sequence
+                        );
+                        rh
+                    }
+                    Err(err) => {
+                        debug!(
+                            "{}: handshake parse failed: {}",
+                            srv_cli_str(is_client),
+                            err
+                        );
+                        continue;
+                    }
+                };
+
+                self.cache.push(
+                    out,
+                    epoch,
+                    raw_handshake.handshake_header.message_sequence,
+                    raw_handshake.handshake_header.handshake_type,
+                    !is_client,
+                );
+            }
+
+            return (true, None, None);
+        }
+
+        let mut reader = BufReader::new(pkt.as_slice());
+        let r = match RecordLayer::unmarshal(&mut reader) {
+            Ok(r) => r,
+            Err(err) => {
+                return (
+                    false,
+                    Some(Alert {
+                        alert_level: AlertLevel::Fatal,
+                        alert_description: AlertDescription::DecodeError,
+                    }),
+                    Some(err),
+                );
+            }
+        };
+
+        match r.content {
+            Content::Alert(mut a) => {
+                trace!("{}: <- {}", srv_cli_str(is_client), a.to_string());
+                self.cfg
+                    .observe(is_client, HandshakeEvent::AlertReceived(a));
+                if a.alert_description == AlertDescription::CloseNotify {
+                    // Respond with a close_notify [RFC5246 Section 7.2.1]
+                    a = Alert {
+                        alert_level: AlertLevel::Warning,
+                        alert_description: AlertDescription::CloseNotify,
+                    };
+                }
+                self.state.replay_detector.lock()[h.epoch as usize].accept();
+                return (
+                    false,
+                    Some(a),
+                    Some(Error::Other(format!("Error of Alert {a}"))),
+                );
+            }
+            Content::ChangeCipherSpec(_) => {
+                if !self.is_cipher_suite_initialized() {
+                    if enqueue {
+                        debug!(
+                            "{}: CipherSuite not initialized, queuing packet",
+                            srv_cli_str(is_client)
+                        );
+                        self.encrypted_packets.push(pkt);
+                    }
+                    return (false, None, None);
+                }
+
+                let new_remote_epoch = h.epoch + 1;
+                trace!(
+                    "{}: <- ChangeCipherSpec (epoch: {})",
+                    srv_cli_str(is_client),
+                    new_remote_epoch
+                );
+
+                if epoch + 1 == new_remote_epoch {
+                    self.state
+                        .remote_epoch
+                        .store(new_remote_epoch, Ordering::SeqCst);
+                    self.state.replay_detector.lock()[h.epoch as usize].accept();
+                }
+            }
+            Content::ApplicationData(a) => {
+                if h.epoch == 0 {
+                    return (
+                        false,
+                        Some(Alert {
+                            alert_level: AlertLevel::Fatal,
+                            alert_description: AlertDescription::UnexpectedMessage,
+                        }),
+                        Some(Error::ErrApplicationDataEpochZero),
+                    );
+                }
+
+                self.state.replay_detector.lock()[h.epoch as usize].accept();
+
+                self.application_data.push_back(a.data);
+            }
+            _ => {
+                return (
+                    false,
+                    Some(Alert {
+                        alert_level: AlertLevel::Fatal,
+                        alert_description: AlertDescription::UnexpectedMessage,
+                    }),
+                    Some(Error::ErrUnhandledContextType),
+                );
+            }
+        };
+
+        (false, None, None)
+    }
+
+    pub(crate) fn set_local_epoch(&mut self, epoch: u16) {
+        self.state.local_epoch.store(epoch, Ordering::SeqCst);
+    }
+
+    pub(crate) fn get_local_epoch(&self) -> u16 {
+        self.state.local_epoch.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn set_handshake_completed_successfully(&mut self) {
+        self.handshake_completed_successfully = true;
+    }
+}
+
+fn fragment_handshake(maximum_transmission_unit: usize, h: &Handshake) -> Result<Vec<Vec<u8>>> {
+    let mut content = vec![];
+    {
+        let mut writer = BufWriter::<&mut Vec<u8>>::new(content.as_mut());
+        h.handshake_message.marshal(&mut writer)?;
+    }
+
+    let mut fragmented_handshakes = vec![];
+
+    let mut content_fragments = split_bytes(&content, maximum_transmission_unit);
+    if content_fragments.is_empty() {
+        content_fragments = vec![vec![]];
+    }
+
+    let mut offset = 0;
+    for content_fragment in &content_fragments {
+        let content_fragment_len = content_fragment.len();
+
+        let handshake_header_fragment = HandshakeHeader {
+            handshake_type: h.handshake_header.handshake_type,
+            length: h.handshake_header.length,
+            message_sequence: h.handshake_header.message_sequence,
+            fragment_offset: offset as u32,
+            fragment_length: content_fragment_len as u32,
+        };
+
+        offset += content_fragment_len;
+
+        let mut handshake_header_fragment_raw = vec![];
+        {
+            let mut writer = BufWriter::<&mut Vec<u8>>::new(handshake_header_fragment_raw.as_mut());
+            handshake_header_fragment.marshal(&mut writer)?;
+        }
+
+        let mut fragmented_handshake = vec![];
+        fragmented_handshake.extend_from_slice(&handshake_header_fragment_raw);
+        fragmented_handshake.extend_from_slice(content_fragment);
+
+        fragmented_handshakes.push(fragmented_handshake);
+    }
+
+    Ok(fragmented_handshakes)
+}
+
+fn compact_raw_packets(raw_packets: &[Vec<u8>], maximum_transmission_unit: usize) -> Vec<Vec<u8>> {
+    let mut combined_raw_packets = vec![];
+    let mut current_combined_raw_packet = vec![];
+
+    for raw_packet in raw_packets {
+        if !current_combined_raw_packet.is_empty()
+            && current_combined_raw_packet.len() + raw_packet.len() >= maximum_transmission_unit
+        {
+            combined_raw_packets.push(current_combined_raw_packet);
+            current_combined_raw_packet = vec![];
+        }
+        current_combined_raw_packet.extend_from_slice(raw_packet);
+    }
+
+    combined_raw_packets.push(current_combined_raw_packet);
+
+    combined_raw_packets
+}
+
+fn split_bytes(bytes: &[u8], split_len: usize) -> Vec<Vec<u8>> {
+    let mut splits = vec![];
+    let num_bytes = bytes.len();
+    for i in (0..num_bytes).step_by(split_len) {
+        let mut j = i + split_len;
+        if j > num_bytes {
+            j = num_bytes;
+        }
+
+        splits.push(bytes[i..j].to_vec());
+    }
+
+    splits
+}
@@ -0,0 +1,168 @@
+use super::*;
+use crate::crypto::Certificate;
+
+fn test_config() -> Result<Config> {
+    Ok(Config {
+        certificates: vec![Certificate::generate_self_signed(vec![
+            "localhost".to_owned()
+        ])?],
+        insecure_skip_verify: true,
+        flight_interval: Duration::from_millis(100),
+        ..Default::default()
+    })
+}
+
+// forward moves the datagrams sent by one driver to the other, skipping the
+// ones for which drop returns true, and returns how many were sent
+fn forward(
+    from: &mut DTLSDriver,
+    to: &mut DTLSDriver,
+    now: Instant,
+    drop: &mut impl FnMut() -> bool,
+) -> Result<usize> {
+    let mut sent = 0;
+    while let Some(datagram) = from.poll_transmit() {
+        sent += 1;
+        if drop() {
+            continue;
+        }
+        to.handle_read(now, &datagram)?;
+    }
+    Ok(sent)
+}
+
+// handshake_pair runs the handshake between the drivers, firing their timers
+// whenever no datagram is in flight, and returns when it completed
+fn handshake_pair(
+    client: &mut DTLSDriver,
+    server: &mut DTLSDriver,
+    mut drop_client: impl FnMut() -> bool,
+    mut drop_server: impl FnMut() -> bool,
+) -> Result<Instant> {
+    let mut now = Instant::now();
+    client.start(now)?;
+    server.start(now)?;
+
+    for _ in 0..100 {
+        let sent = forward(client, server, now, &mut drop_client)?
+            + forward(server, client, now, &mut drop_server)?;
+        if client.is_handshake_complete() && server.is_handshake_complete() {
+            return Ok(now);
+        }
+        if sent != 0 {
+            continue;
+        }
+
+        let timeout = match (client.poll_timeout(), server.poll_timeout()) {
+            (Some(a), Some(b)) => std::cmp::min(a, b),
+            (Some(t), None) | (None, Some(t)) => t,
+            (None, None) => panic!("handshake stalled without a timer"),
+        };
+        assert!(timeout >= now);
+        now = timeout;
+        client.handle_timeout(now)?;
+        server.handle_timeout(now)?;
+    }
+
+    panic!("handshake didn't complete");
+}
+
+#[test]
+fn test_driver_handshake() -> Result<()> {
+    let mut client = DTLSDriver::new(test_config()?, true)?;
+    let mut server = DTLSDriver::new(test_config()?, false)?;
+
+    let now = handshake_pair(&mut client, &mut server, || false, || false)?;
+    assert_eq!(client.poll_timeout(), None);
+
+    client.write(b"ping")?;
+    forward(&mut client, &mut server, now, &mut || false)?;
+    assert_eq!(server.poll_application_data(), Some(b"ping".to_vec()));
+    assert_eq!(server.poll_application_data(), None);
+
+    server.write(b"pong")?;
+    forward(&mut server, &mut client, now, &mut || false)?;
+    assert_eq!(client.poll_application_data(), Some(b"pong".to_vec()));
+
+    // The close_notify closes the peer
+    client.close()?;
+    assert!(client.is_closed());
+    let datagram = client
+        .poll_transmit()
+        .expect("client should send a close_notify");
+    assert_eq!(
+        server.handle_read(now, &datagram),
+        Err(Error::ErrAlertFatalOrClose)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_driver_handshake_with_lost_flights() -> Result<()> {
+    let mut client = DTLSDriver::new(test_config()?, true)?;
+    let mut server = DTLSDriver::new(test_config()?, false)?;
+
+    // Lose the first ClientHello and the first HelloVerifyRequest
+    let (mut client_sent, mut server_sent) = (0, 0);
+    let now = handshake_pair(
+        &mut client,
+        &mut server,
+        || {
+            client_sent += 1;
+            client_sent == 1
+        },
+        || {
+            server_sent += 1;
+            server_sent == 1
+        },
+    )?;
+    assert!(client_sent > 1);
+    assert!(server_sent > 1);
+
+    client.write(b"ping")?;
+    forward(&mut client, &mut server, now, &mut || false)?;
+    assert_eq!(server.poll_application_data(), Some(b"ping".to_vec()));
+
+    Ok(())
+}
+
+#[test]
+fn test_driver_poll_transmit() -> Result<()> {
+    let mut client = DTLSDriver::new(test_config()?, true)?;
+    assert_eq!(client.poll_transmit(), None);
+    assert_eq!(client.poll_timeout(), None);
+
+    // The first datagram is a handshake record carrying the ClientHello, the
+    // retransmission timer runs until it's answered
+    let now = Instant::now();
+    client.start(now)?;
+    let datagram = client
+        .poll_transmit()
+        .expect("client should send a ClientHello");
+    assert_eq!(datagram[0], ContentType::Handshake as u8);
+    let timeout = client.poll_timeout().expect("client should arm its timer");
+    assert!(timeout > now);
+
+    // It's sent again once the timer expired
+    client.handle_timeout(now)?;
+    assert_eq!(client.poll_transmit(), None);
+    client.handle_timeout(timeout)?;
+    assert!(client.poll_transmit().is_some());
+    assert!(
+        client
+            .poll_timeout()
+            .expect("client should re-arm its timer")
+            > timeout
+    );
+
+    assert_eq!(client.write(b"ping"), Err(Error::ErrHandshakeInProgress));
+
+    client.close()?;
+    assert_eq!(
+        client.handle_read(now, &datagram),
+        Err(Error::ErrConnClosed)
+    );
+
+    Ok(())
+}
@@ -18,10 +18,14 @@ pub enum Error {
     ErrBufferTooSmall,
     #[error("context is not supported for export_keying_material")]
     ErrContextUnsupported,
+    #[error("context for export_keying_material must be shorter than 2^16 bytes")]
+    ErrContextTooLong,
     #[error("packet is too short")]
     ErrDtlspacketInvalidLength,
     #[error("handshake is in progress")]
     ErrHandshakeInProgress,
+    #[error("unsupported serialized state version {0}")]
+    ErrUnsupportedStateVersion(u8),
     #[error("invalid content type")]
     ErrInvalidContentType,
     #[error("invalid mac")]
@@ -142,6 +146,24 @@ pub enum Error {
     ErrEmptyFragment,
     #[error("Alert is Fatal or Close Notify")]
     ErrAlertFatalOrClose,
+    #[error("server responded with EKT cipher we do not support")]
+    ErrClientNoMatchingEktCipher,
+    #[error("EKT cipher selection must contain exactly one cipher")]
+    ErrInvalidEktCipherSelection,
+    #[error("ALPN extension is malformed")]
+    ErrAlpnInvalidFormat,
+    #[error("client and server do not support any shared application protocol")]
+    ErrAlpnNoAppProtocol,
+    #[error("server selected an application protocol the client did not offer")]
+    ErrAlpnUnexpectedProtocol,
+    #[error("record_size_limit must be at least 64")]
+    ErrInvalidRecordSizeLimit,
+    #[error("max_fragment_length is malformed or differs from the requested value")]
+    ErrInvalidMaxFragmentLength,
+    #[error("certificate status type is not supported")]
+    ErrInvalidCertificateStatusType,
+    #[error("server stapled a certificate status that was not requested")]
+    ErrUnexpectedCertificateStatus,
 
     #[error(
         "Fragment buffer overflow. New size {new_size} is greater than specified max {max_size}"
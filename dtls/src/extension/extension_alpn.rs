@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod extension_alpn_test;
+
+use super::*;
+
+/// ## Specifications
+///
+/// * [RFC 7301 §3.1]
+///
+/// [RFC 7301 §3.1]: https://tools.ietf.org/html/rfc7301#section-3.1
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtensionAlpn {
+    pub(crate) protocol_name_list: Vec<String>,
+}
+
+impl ExtensionAlpn {
+    pub fn extension_value(&self) -> ExtensionValue {
+        ExtensionValue::Alpn
+    }
+
+    pub fn size(&self) -> usize {
+        let mut len = 2 + 2;
+        for proto in &self.protocol_name_list {
+            len += 1 + proto.len();
+        }
+        len
+    }
+
+    pub fn marshal<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let mut protocol_name_list_len = 0;
+        for proto in &self.protocol_name_list {
+            if proto.is_empty() || proto.len() > 255 {
+                return Err(Error::ErrAlpnInvalidFormat);
+            }
+            protocol_name_list_len += 1 + proto.len();
+        }
+
+        writer.write_u16::<BigEndian>(2 + protocol_name_list_len as u16)?;
+        writer.write_u16::<BigEndian>(protocol_name_list_len as u16)?;
+        for proto in &self.protocol_name_list {
+            writer.write_u8(proto.len() as u8)?;
+            writer.write_all(proto.as_bytes())?;
+        }
+
+        Ok(writer.flush()?)
+    }
+
+    pub fn unmarshal<R: Read>(reader: &mut R) -> Result<Self> {
+        let _ = reader.read_u16::<BigEndian>()? as usize;
+
+        let protocol_name_list_len = reader.read_u16::<BigEndian>()? as usize;
+        let mut buf = vec![0u8; protocol_name_list_len];
+        reader.read_exact(&mut buf)?;
+
+        let mut protocol_name_list = vec![];
+        let mut offset = 0;
+        while offset < buf.len() {
+            let proto_len = buf[offset] as usize;
+            offset += 1;
+            if proto_len == 0 || offset + proto_len > buf.len() {
+                return Err(Error::ErrAlpnInvalidFormat);
+            }
+
+            let proto = String::from_utf8(buf[offset..offset + proto_len].to_vec())?;
+            protocol_name_list.push(proto);
+            offset += proto_len;
+        }
+
+        Ok(ExtensionAlpn { protocol_name_list })
+    }
+}
+
+/// alpn_protocol_selection picks the first protocol in the server's preference
+/// order that is also offered by the client.
+///
+/// An empty string is returned when the server has no application protocols
+/// configured, in which case ALPN is simply not negotiated.
+pub(crate) fn alpn_protocol_selection(
+    supported_protocols: &[String],
+    peer_supported_protocols: &[String],
+) -> Result<String> {
+    if supported_protocols.is_empty() {
+        return Ok(String::new());
+    }
+
+    for s in supported_protocols {
+        if peer_supported_protocols.contains(s) {
+            return Ok(s.clone());
+        }
+    }
+
+    Err(Error::ErrAlpnNoAppProtocol)
+}
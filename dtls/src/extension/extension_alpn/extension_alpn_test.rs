@@ -0,0 +1,88 @@
+use std::io::{BufReader, BufWriter};
+
+use super::*;
+
+#[test]
+fn test_extension_alpn() -> Result<()> {
+    let raw_alpn = vec![
+        0x00, 0x0e, 0x00, 0x0c, 0x06, 0x77, 0x65, 0x62, 0x72, 0x74, 0x63, 0x04, 0x63, 0x2d, 0x77,
+        0x70,
+    ];
+    let parsed_alpn = ExtensionAlpn {
+        protocol_name_list: vec!["webrtc".to_owned(), "c-wp".to_owned()],
+    };
+
+    let mut raw = vec![];
+    {
+        let mut writer = BufWriter::<&mut Vec<u8>>::new(raw.as_mut());
+        parsed_alpn.marshal(&mut writer)?;
+    }
+
+    assert_eq!(
+        raw, raw_alpn,
+        "extensionALPN marshal: got {raw:?}, want {raw_alpn:?}"
+    );
+    assert_eq!(parsed_alpn.size(), raw_alpn.len());
+
+    let mut reader = BufReader::new(raw.as_slice());
+    let new_alpn = ExtensionAlpn::unmarshal(&mut reader)?;
+
+    assert_eq!(
+        new_alpn, parsed_alpn,
+        "extensionALPN unmarshal: got {new_alpn:?}, want {parsed_alpn:?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_extension_alpn_invalid() -> Result<()> {
+    // Protocol length runs past the end of the list
+    let raw_alpn = vec![0x00, 0x04, 0x00, 0x02, 0x05, 0x77];
+    let mut reader = BufReader::new(raw_alpn.as_slice());
+    let result = ExtensionAlpn::unmarshal(&mut reader);
+    assert_eq!(result, Err(Error::ErrAlpnInvalidFormat));
+
+    let empty_proto = ExtensionAlpn {
+        protocol_name_list: vec![String::new()],
+    };
+    let mut raw = vec![];
+    let result = empty_proto.marshal(&mut raw);
+    assert_eq!(result, Err(Error::ErrAlpnInvalidFormat));
+
+    Ok(())
+}
+
+#[test]
+fn test_alpn_protocol_selection() -> Result<()> {
+    #[allow(clippy::type_complexity)]
+    let tests: Vec<(&str, Vec<&str>, Vec<&str>, Result<String>)> = vec![
+        (
+            "no server protocols",
+            vec![],
+            vec!["webrtc"],
+            Ok(String::new()),
+        ),
+        (
+            "server preference wins",
+            vec!["c-webrtc", "webrtc"],
+            vec!["webrtc", "c-webrtc"],
+            Ok("c-webrtc".to_owned()),
+        ),
+        (
+            "no overlap",
+            vec!["webrtc"],
+            vec!["c-webrtc"],
+            Err(Error::ErrAlpnNoAppProtocol),
+        ),
+    ];
+
+    for (name, supported, peer_supported, expected) in tests {
+        let supported: Vec<String> = supported.iter().map(|s| s.to_string()).collect();
+        let peer_supported: Vec<String> = peer_supported.iter().map(|s| s.to_string()).collect();
+        let result = alpn_protocol_selection(&supported, &peer_supported);
+        assert_eq!(result, expected, "{name}");
+    }
+
+    Ok(())
+}
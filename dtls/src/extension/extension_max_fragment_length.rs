@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod extension_max_fragment_length_test;
+
+use super::*;
+
+// MaxFragmentLength is the legacy way of negotiating smaller records
+// https://tools.ietf.org/html/rfc6066#section-4
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MaxFragmentLength {
+    Pow2_9 = 1,
+    Pow2_10 = 2,
+    Pow2_11 = 3,
+    Pow2_12 = 4,
+    Unsupported,
+}
+
+impl From<u8> for MaxFragmentLength {
+    fn from(val: u8) -> Self {
+        match val {
+            1 => MaxFragmentLength::Pow2_9,
+            2 => MaxFragmentLength::Pow2_10,
+            3 => MaxFragmentLength::Pow2_11,
+            4 => MaxFragmentLength::Pow2_12,
+            _ => MaxFragmentLength::Unsupported,
+        }
+    }
+}
+
+impl MaxFragmentLength {
+    /// length returns the maximum plaintext fragment size in bytes, 0 if unsupported.
+    pub fn length(&self) -> u16 {
+        match *self {
+            MaxFragmentLength::Pow2_9 => 1 << 9,
+            MaxFragmentLength::Pow2_10 => 1 << 10,
+            MaxFragmentLength::Pow2_11 => 1 << 11,
+            MaxFragmentLength::Pow2_12 => 1 << 12,
+            MaxFragmentLength::Unsupported => 0,
+        }
+    }
+}
+
+/// ## Specifications
+///
+/// * [RFC 6066]
+///
+/// [RFC 6066]: https://tools.ietf.org/html/rfc6066#section-4
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtensionMaxFragmentLength {
+    pub(crate) max_fragment_length: MaxFragmentLength,
+}
+
+impl ExtensionMaxFragmentLength {
+    pub fn extension_value(&self) -> ExtensionValue {
+        ExtensionValue::MaxFragmentLength
+    }
+
+    pub fn size(&self) -> usize {
+        2 + 1
+    }
+
+    pub fn marshal<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u16::<BigEndian>(1)?;
+        writer.write_u8(self.max_fragment_length as u8)?;
+
+        Ok(writer.flush()?)
+    }
+
+    pub fn unmarshal<R: Read>(reader: &mut R) -> Result<Self> {
+        let length = reader.read_u16::<BigEndian>()?;
+        if length != 1 {
+            return Err(Error::ErrInvalidMaxFragmentLength);
+        }
+
+        let max_fragment_length: MaxFragmentLength = reader.read_u8()?.into();
+        if max_fragment_length == MaxFragmentLength::Unsupported {
+            return Err(Error::ErrInvalidMaxFragmentLength);
+        }
+
+        Ok(ExtensionMaxFragmentLength {
+            max_fragment_length,
+        })
+    }
+}
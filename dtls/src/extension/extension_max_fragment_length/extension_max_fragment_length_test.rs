@@ -0,0 +1,44 @@
+use std::io::{BufReader, BufWriter};
+
+use super::*;
+
+#[test]
+fn test_extension_max_fragment_length() -> Result<()> {
+    let raw_max_fragment_length = vec![0x00, 0x01, 0x02];
+    let parsed_max_fragment_length = ExtensionMaxFragmentLength {
+        max_fragment_length: MaxFragmentLength::Pow2_10,
+    };
+
+    let mut raw = vec![];
+    {
+        let mut writer = BufWriter::<&mut Vec<u8>>::new(raw.as_mut());
+        parsed_max_fragment_length.marshal(&mut writer)?;
+    }
+
+    assert_eq!(
+        raw, raw_max_fragment_length,
+        "extensionMaxFragmentLength marshal: got {raw:?}, want {raw_max_fragment_length:?}"
+    );
+    assert_eq!(
+        parsed_max_fragment_length.size(),
+        raw_max_fragment_length.len()
+    );
+
+    let mut reader = BufReader::new(raw.as_slice());
+    let new_max_fragment_length = ExtensionMaxFragmentLength::unmarshal(&mut reader)?;
+
+    assert_eq!(
+        new_max_fragment_length, parsed_max_fragment_length,
+        "extensionMaxFragmentLength unmarshal: got {new_max_fragment_length:?}, want {parsed_max_fragment_length:?}"
+    );
+    assert_eq!(new_max_fragment_length.max_fragment_length.length(), 1024);
+
+    let raw = vec![0x00, 0x01, 0x05];
+    let mut reader = BufReader::new(raw.as_slice());
+    assert_eq!(
+        ExtensionMaxFragmentLength::unmarshal(&mut reader),
+        Err(Error::ErrInvalidMaxFragmentLength)
+    );
+
+    Ok(())
+}
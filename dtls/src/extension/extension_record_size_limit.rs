@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod extension_record_size_limit_test;
+
+use super::*;
+
+// Smallest record size limit an endpoint may advertise
+pub(crate) const RECORD_SIZE_LIMIT_MIN: u16 = 64;
+
+// Largest plaintext record size allowed by (D)TLS 1.2
+pub(crate) const RECORD_SIZE_LIMIT_MAX: u16 = 1 << 14;
+
+/// RecordSizeLimit advertises the largest record plaintext the sender is
+/// willing to receive.
+///
+/// ## Specifications
+///
+/// * [RFC 8449]
+///
+/// [RFC 8449]: https://tools.ietf.org/html/rfc8449
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtensionRecordSizeLimit {
+    pub(crate) record_size_limit: u16,
+}
+
+impl ExtensionRecordSizeLimit {
+    pub fn extension_value(&self) -> ExtensionValue {
+        ExtensionValue::RecordSizeLimit
+    }
+
+    pub fn size(&self) -> usize {
+        2 + 2
+    }
+
+    pub fn marshal<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u16::<BigEndian>(2)?;
+        writer.write_u16::<BigEndian>(self.record_size_limit)?;
+
+        Ok(writer.flush()?)
+    }
+
+    pub fn unmarshal<R: Read>(reader: &mut R) -> Result<Self> {
+        let length = reader.read_u16::<BigEndian>()?;
+        if length != 2 {
+            return Err(Error::ErrInvalidRecordSizeLimit);
+        }
+
+        // Values lower than 64 are a protocol error
+        let record_size_limit = reader.read_u16::<BigEndian>()?;
+        if record_size_limit < RECORD_SIZE_LIMIT_MIN {
+            return Err(Error::ErrInvalidRecordSizeLimit);
+        }
+
+        Ok(ExtensionRecordSizeLimit { record_size_limit })
+    }
+}
@@ -0,0 +1,51 @@
+use std::io::{BufReader, BufWriter};
+
+use super::*;
+
+#[test]
+fn test_extension_record_size_limit() -> Result<()> {
+    let raw_record_size_limit = vec![0x00, 0x02, 0x04, 0x00];
+    let parsed_record_size_limit = ExtensionRecordSizeLimit {
+        record_size_limit: 1024,
+    };
+
+    let mut raw = vec![];
+    {
+        let mut writer = BufWriter::<&mut Vec<u8>>::new(raw.as_mut());
+        parsed_record_size_limit.marshal(&mut writer)?;
+    }
+
+    assert_eq!(
+        raw, raw_record_size_limit,
+        "extensionRecordSizeLimit marshal: got {raw:?}, want {raw_record_size_limit:?}"
+    );
+    assert_eq!(parsed_record_size_limit.size(), raw_record_size_limit.len());
+
+    let mut reader = BufReader::new(raw.as_slice());
+    let new_record_size_limit = ExtensionRecordSizeLimit::unmarshal(&mut reader)?;
+
+    assert_eq!(
+        new_record_size_limit, parsed_record_size_limit,
+        "extensionRecordSizeLimit unmarshal: got {new_record_size_limit:?}, want {parsed_record_size_limit:?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_extension_record_size_limit_invalid() {
+    let tests = vec![
+        ("Too small", vec![0x00, 0x02, 0x00, 0x3f]),
+        ("Bad length", vec![0x00, 0x03, 0x04, 0x00, 0x00]),
+    ];
+
+    for (name, raw) in tests {
+        let mut reader = BufReader::new(raw.as_slice());
+        let result = ExtensionRecordSizeLimit::unmarshal(&mut reader);
+        assert_eq!(
+            result,
+            Err(Error::ErrInvalidRecordSizeLimit),
+            "{name}: expected ErrInvalidRecordSizeLimit"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod extension_status_request_test;
+
+use super::*;
+
+// CertificateStatusType identifies the kind of certificate status information
+/// ## Specifications
+///
+/// * [RFC 6066 §8]
+///
+/// [RFC 6066 §8]: https://tools.ietf.org/html/rfc6066#section-8
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CertificateStatusType {
+    Ocsp = 1,
+    Unsupported,
+}
+
+impl From<u8> for CertificateStatusType {
+    fn from(val: u8) -> Self {
+        match val {
+            1 => CertificateStatusType::Ocsp,
+            _ => CertificateStatusType::Unsupported,
+        }
+    }
+}
+
+/// The status_request extension asks the server to staple certificate status
+/// information to its Certificate. A ClientHello carries the request, the
+/// ServerHello of a server that staples carries an empty extension.
+///
+/// Responder ids and request extensions are opaque to this implementation.
+///
+/// ## Specifications
+///
+/// * [RFC 6066 §8]
+///
+/// [RFC 6066 §8]: https://tools.ietf.org/html/rfc6066#section-8
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtensionStatusRequest {
+    pub(crate) status_type: CertificateStatusType,
+    pub(crate) responder_id_list: Vec<u8>,
+    pub(crate) request_extensions: Vec<u8>,
+    pub(crate) is_server_response: bool,
+}
+
+impl ExtensionStatusRequest {
+    pub fn extension_value(&self) -> ExtensionValue {
+        ExtensionValue::StatusRequest
+    }
+
+    pub fn size(&self) -> usize {
+        if self.is_server_response {
+            2
+        } else {
+            2 + 1 + 2 + self.responder_id_list.len() + 2 + self.request_extensions.len()
+        }
+    }
+
+    pub fn marshal<W: Write>(&self, writer: &mut W) -> Result<()> {
+        if self.is_server_response {
+            writer.write_u16::<BigEndian>(0)?;
+        } else {
+            writer.write_u16::<BigEndian>((self.size() - 2) as u16)?;
+            writer.write_u8(self.status_type as u8)?;
+            writer.write_u16::<BigEndian>(self.responder_id_list.len() as u16)?;
+            writer.write_all(&self.responder_id_list)?;
+            writer.write_u16::<BigEndian>(self.request_extensions.len() as u16)?;
+            writer.write_all(&self.request_extensions)?;
+        }
+
+        Ok(writer.flush()?)
+    }
+
+    pub fn unmarshal<R: Read>(reader: &mut R) -> Result<Self> {
+        let extension_len = reader.read_u16::<BigEndian>()? as usize;
+
+        // A ServerHello acknowledges the request with empty extension data
+        if extension_len == 0 {
+            return Ok(ExtensionStatusRequest {
+                status_type: CertificateStatusType::Ocsp,
+                responder_id_list: vec![],
+                request_extensions: vec![],
+                is_server_response: true,
+            });
+        }
+
+        let status_type: CertificateStatusType = reader.read_u8()?.into();
+        if status_type != CertificateStatusType::Ocsp {
+            // Unknown request formats are skipped, the server then doesn't staple
+            let mut rest = vec![0u8; extension_len - 1];
+            reader.read_exact(&mut rest)?;
+            return Ok(ExtensionStatusRequest {
+                status_type,
+                responder_id_list: vec![],
+                request_extensions: vec![],
+                is_server_response: false,
+            });
+        }
+
+        let responder_id_list_len = reader.read_u16::<BigEndian>()? as usize;
+        let mut responder_id_list = vec![0u8; responder_id_list_len];
+        reader.read_exact(&mut responder_id_list)?;
+
+        let request_extensions_len = reader.read_u16::<BigEndian>()? as usize;
+        let mut request_extensions = vec![0u8; request_extensions_len];
+        reader.read_exact(&mut request_extensions)?;
+
+        if 1 + 2 + responder_id_list_len + 2 + request_extensions_len != extension_len {
+            return Err(Error::ErrLengthMismatch);
+        }
+
+        Ok(ExtensionStatusRequest {
+            status_type,
+            responder_id_list,
+            request_extensions,
+            is_server_response: false,
+        })
+    }
+}
@@ -0,0 +1,69 @@
+use std::io::{BufReader, BufWriter};
+
+use super::*;
+
+#[test]
+fn test_extension_status_request() -> Result<()> {
+    let tests = vec![
+        (
+            vec![0x00, 0x05, 0x01, 0x00, 0x00, 0x00, 0x00],
+            ExtensionStatusRequest {
+                status_type: CertificateStatusType::Ocsp,
+                responder_id_list: vec![],
+                request_extensions: vec![],
+                is_server_response: false,
+            },
+        ),
+        (
+            vec![0x00, 0x08, 0x01, 0x00, 0x02, 0xab, 0xcd, 0x00, 0x01, 0xef],
+            ExtensionStatusRequest {
+                status_type: CertificateStatusType::Ocsp,
+                responder_id_list: vec![0xab, 0xcd],
+                request_extensions: vec![0xef],
+                is_server_response: false,
+            },
+        ),
+        (
+            vec![0x00, 0x00],
+            ExtensionStatusRequest {
+                status_type: CertificateStatusType::Ocsp,
+                responder_id_list: vec![],
+                request_extensions: vec![],
+                is_server_response: true,
+            },
+        ),
+    ];
+
+    for (raw_status_request, parsed_status_request) in tests {
+        let mut raw = vec![];
+        {
+            let mut writer = BufWriter::<&mut Vec<u8>>::new(raw.as_mut());
+            parsed_status_request.marshal(&mut writer)?;
+        }
+
+        assert_eq!(
+            raw, raw_status_request,
+            "extensionStatusRequest marshal: got {raw:?}, want {raw_status_request:?}"
+        );
+        assert_eq!(parsed_status_request.size(), raw_status_request.len());
+
+        let mut reader = BufReader::new(raw.as_slice());
+        let new_status_request = ExtensionStatusRequest::unmarshal(&mut reader)?;
+
+        assert_eq!(
+            new_status_request, parsed_status_request,
+            "extensionStatusRequest unmarshal: got {new_status_request:?}, want {parsed_status_request:?}"
+        );
+    }
+
+    // Unknown status types are skipped
+    let raw = vec![0x00, 0x03, 0x02, 0x00, 0x00];
+    let mut reader = BufReader::new(raw.as_slice());
+    let status_request = ExtensionStatusRequest::unmarshal(&mut reader)?;
+    assert_eq!(
+        status_request.status_type,
+        CertificateStatusType::Unsupported
+    );
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod extension_supported_ekt_ciphers_test;
+
+use super::*;
+
+// EktCipher defines the key wrap algorithm used to protect EKT fields in SRTP
+/// ## Specifications
+///
+/// * [RFC 8870 §5.2.2]
+///
+/// [RFC 8870 §5.2.2]: https://tools.ietf.org/html/rfc8870#section-5.2.2
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EktCipher {
+    AesKw128 = 1,
+    AesKw256 = 2,
+    Unsupported,
+}
+
+impl From<u8> for EktCipher {
+    fn from(val: u8) -> Self {
+        match val {
+            1 => EktCipher::AesKw128,
+            2 => EktCipher::AesKw256,
+            _ => EktCipher::Unsupported,
+        }
+    }
+}
+
+/// The supported_ekt_ciphers extension carries the list of offered ciphers
+/// in a ClientHello and the single selected cipher in a ServerHello.
+///
+/// ## Specifications
+///
+/// * [RFC 8870 §5.2.2]
+///
+/// [RFC 8870 §5.2.2]: https://tools.ietf.org/html/rfc8870#section-5.2.2
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtensionSupportedEktCiphers {
+    pub(crate) ekt_ciphers: Vec<EktCipher>,
+    pub(crate) is_server_selection: bool,
+}
+
+impl ExtensionSupportedEktCiphers {
+    pub fn extension_value(&self) -> ExtensionValue {
+        ExtensionValue::SupportedEktCiphers
+    }
+
+    pub fn size(&self) -> usize {
+        if self.is_server_selection {
+            2 + 1
+        } else {
+            2 + 1 + self.ekt_ciphers.len()
+        }
+    }
+
+    pub fn marshal<W: Write>(&self, writer: &mut W) -> Result<()> {
+        if self.is_server_selection {
+            if self.ekt_ciphers.len() != 1 {
+                return Err(Error::ErrInvalidEktCipherSelection);
+            }
+            writer.write_u16::<BigEndian>(1)?;
+            writer.write_u8(self.ekt_ciphers[0] as u8)?;
+        } else {
+            writer.write_u16::<BigEndian>(1 + self.ekt_ciphers.len() as u16)?;
+            writer.write_u8(self.ekt_ciphers.len() as u8)?;
+            for v in &self.ekt_ciphers {
+                writer.write_u8(*v as u8)?;
+            }
+        }
+
+        Ok(writer.flush()?)
+    }
+
+    pub fn unmarshal<R: Read>(reader: &mut R) -> Result<Self> {
+        let extension_len = reader.read_u16::<BigEndian>()? as usize;
+
+        // A ServerHello carries exactly one selected cipher and no list length
+        if extension_len == 1 {
+            let ekt_cipher = reader.read_u8()?.into();
+            return Ok(ExtensionSupportedEktCiphers {
+                ekt_ciphers: vec![ekt_cipher],
+                is_server_selection: true,
+            });
+        }
+
+        let cipher_count = reader.read_u8()? as usize;
+        let mut ekt_ciphers = vec![];
+        for _ in 0..cipher_count {
+            let ekt_cipher = reader.read_u8()?.into();
+            ekt_ciphers.push(ekt_cipher);
+        }
+
+        Ok(ExtensionSupportedEktCiphers {
+            ekt_ciphers,
+            is_server_selection: false,
+        })
+    }
+}
@@ -0,0 +1,47 @@
+use std::io::{BufReader, BufWriter};
+
+use super::*;
+
+#[test]
+fn test_extension_supported_ekt_ciphers() -> Result<()> {
+    let tests = vec![
+        (
+            vec![0x00, 0x03, 0x02, 0x01, 0x02],
+            ExtensionSupportedEktCiphers {
+                ekt_ciphers: vec![EktCipher::AesKw128, EktCipher::AesKw256],
+                is_server_selection: false,
+            },
+        ),
+        (
+            vec![0x00, 0x01, 0x02],
+            ExtensionSupportedEktCiphers {
+                ekt_ciphers: vec![EktCipher::AesKw256],
+                is_server_selection: true,
+            },
+        ),
+    ];
+
+    for (raw_ekt_ciphers, parsed_ekt_ciphers) in tests {
+        let mut raw = vec![];
+        {
+            let mut writer = BufWriter::<&mut Vec<u8>>::new(raw.as_mut());
+            parsed_ekt_ciphers.marshal(&mut writer)?;
+        }
+
+        assert_eq!(
+            raw, raw_ekt_ciphers,
+            "extensionSupportedEktCiphers marshal: got {raw:?}, want {raw_ekt_ciphers:?}"
+        );
+        assert_eq!(parsed_ekt_ciphers.size(), raw_ekt_ciphers.len());
+
+        let mut reader = BufReader::new(raw.as_slice());
+        let new_ekt_ciphers = ExtensionSupportedEktCiphers::unmarshal(&mut reader)?;
+
+        assert_eq!(
+            new_ekt_ciphers, parsed_ekt_ciphers,
+            "extensionSupportedEktCiphers unmarshal: got {new_ekt_ciphers:?}, want {parsed_ekt_ciphers:?}"
+        );
+    }
+
+    Ok(())
+}
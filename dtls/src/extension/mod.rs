@@ -1,4 +1,9 @@
+pub mod extension_alpn;
+pub mod extension_max_fragment_length;
+pub mod extension_record_size_limit;
 pub mod extension_server_name;
+pub mod extension_status_request;
+pub mod extension_supported_ekt_ciphers;
 pub mod extension_supported_elliptic_curves;
 pub mod extension_supported_point_formats;
 pub mod extension_supported_signature_algorithms;
@@ -9,7 +14,12 @@ pub mod renegotiation_info;
 use std::io::{Read, Write};
 
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use extension_alpn::*;
+use extension_max_fragment_length::*;
+use extension_record_size_limit::*;
 use extension_server_name::*;
+use extension_status_request::*;
+use extension_supported_ekt_ciphers::*;
 use extension_supported_elliptic_curves::*;
 use extension_supported_point_formats::*;
 use extension_supported_signature_algorithms::*;
@@ -23,11 +33,16 @@ use crate::extension::renegotiation_info::ExtensionRenegotiationInfo;
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ExtensionValue {
     ServerName = 0,
+    MaxFragmentLength = 1,
+    StatusRequest = 5,
     SupportedEllipticCurves = 10,
     SupportedPointFormats = 11,
     SupportedSignatureAlgorithms = 13,
     UseSrtp = 14,
+    Alpn = 16,
     UseExtendedMasterSecret = 23,
+    RecordSizeLimit = 28,
+    SupportedEktCiphers = 39,
     RenegotiationInfo = 65281,
     Unsupported,
 }
@@ -36,11 +51,16 @@ impl From<u16> for ExtensionValue {
     fn from(val: u16) -> Self {
         match val {
             0 => ExtensionValue::ServerName,
+            1 => ExtensionValue::MaxFragmentLength,
+            5 => ExtensionValue::StatusRequest,
             10 => ExtensionValue::SupportedEllipticCurves,
             11 => ExtensionValue::SupportedPointFormats,
             13 => ExtensionValue::SupportedSignatureAlgorithms,
             14 => ExtensionValue::UseSrtp,
+            16 => ExtensionValue::Alpn,
             23 => ExtensionValue::UseExtendedMasterSecret,
+            28 => ExtensionValue::RecordSizeLimit,
+            39 => ExtensionValue::SupportedEktCiphers,
             65281 => ExtensionValue::RenegotiationInfo,
             _ => ExtensionValue::Unsupported,
         }
@@ -50,11 +70,16 @@ impl From<u16> for ExtensionValue {
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub enum Extension {
     ServerName(ExtensionServerName),
+    MaxFragmentLength(ExtensionMaxFragmentLength),
+    StatusRequest(ExtensionStatusRequest),
     SupportedEllipticCurves(ExtensionSupportedEllipticCurves),
     SupportedPointFormats(ExtensionSupportedPointFormats),
     SupportedSignatureAlgorithms(ExtensionSupportedSignatureAlgorithms),
     UseSrtp(ExtensionUseSrtp),
+    Alpn(ExtensionAlpn),
     UseExtendedMasterSecret(ExtensionUseExtendedMasterSecret),
+    RecordSizeLimit(ExtensionRecordSizeLimit),
+    SupportedEktCiphers(ExtensionSupportedEktCiphers),
     RenegotiationInfo(ExtensionRenegotiationInfo),
 }
 
@@ -62,11 +87,16 @@ impl Extension {
     pub fn extension_value(&self) -> ExtensionValue {
         match self {
             Extension::ServerName(ext) => ext.extension_value(),
+            Extension::MaxFragmentLength(ext) => ext.extension_value(),
+            Extension::StatusRequest(ext) => ext.extension_value(),
             Extension::SupportedEllipticCurves(ext) => ext.extension_value(),
             Extension::SupportedPointFormats(ext) => ext.extension_value(),
             Extension::SupportedSignatureAlgorithms(ext) => ext.extension_value(),
             Extension::UseSrtp(ext) => ext.extension_value(),
+            Extension::Alpn(ext) => ext.extension_value(),
             Extension::UseExtendedMasterSecret(ext) => ext.extension_value(),
+            Extension::RecordSizeLimit(ext) => ext.extension_value(),
+            Extension::SupportedEktCiphers(ext) => ext.extension_value(),
             Extension::RenegotiationInfo(ext) => ext.extension_value(),
         }
     }
@@ -76,11 +106,16 @@ impl Extension {
 
         len += match self {
             Extension::ServerName(ext) => ext.size(),
+            Extension::MaxFragmentLength(ext) => ext.size(),
+            Extension::StatusRequest(ext) => ext.size(),
             Extension::SupportedEllipticCurves(ext) => ext.size(),
             Extension::SupportedPointFormats(ext) => ext.size(),
             Extension::SupportedSignatureAlgorithms(ext) => ext.size(),
             Extension::UseSrtp(ext) => ext.size(),
+            Extension::Alpn(ext) => ext.size(),
             Extension::UseExtendedMasterSecret(ext) => ext.size(),
+            Extension::RecordSizeLimit(ext) => ext.size(),
+            Extension::SupportedEktCiphers(ext) => ext.size(),
             Extension::RenegotiationInfo(ext) => ext.size(),
         };
 
@@ -91,11 +126,16 @@ impl Extension {
         writer.write_u16::<BigEndian>(self.extension_value() as u16)?;
         match self {
             Extension::ServerName(ext) => ext.marshal(writer),
+            Extension::MaxFragmentLength(ext) => ext.marshal(writer),
+            Extension::StatusRequest(ext) => ext.marshal(writer),
             Extension::SupportedEllipticCurves(ext) => ext.marshal(writer),
             Extension::SupportedPointFormats(ext) => ext.marshal(writer),
             Extension::SupportedSignatureAlgorithms(ext) => ext.marshal(writer),
             Extension::UseSrtp(ext) => ext.marshal(writer),
+            Extension::Alpn(ext) => ext.marshal(writer),
             Extension::UseExtendedMasterSecret(ext) => ext.marshal(writer),
+            Extension::RecordSizeLimit(ext) => ext.marshal(writer),
+            Extension::SupportedEktCiphers(ext) => ext.marshal(writer),
             Extension::RenegotiationInfo(ext) => ext.marshal(writer),
         }
     }
@@ -106,6 +146,12 @@ impl Extension {
             ExtensionValue::ServerName => Ok(Extension::ServerName(
                 ExtensionServerName::unmarshal(reader)?,
             )),
+            ExtensionValue::MaxFragmentLength => Ok(Extension::MaxFragmentLength(
+                ExtensionMaxFragmentLength::unmarshal(reader)?,
+            )),
+            ExtensionValue::StatusRequest => Ok(Extension::StatusRequest(
+                ExtensionStatusRequest::unmarshal(reader)?,
+            )),
             ExtensionValue::SupportedEllipticCurves => Ok(Extension::SupportedEllipticCurves(
                 ExtensionSupportedEllipticCurves::unmarshal(reader)?,
             )),
@@ -118,9 +164,16 @@ impl Extension {
                 ))
             }
             ExtensionValue::UseSrtp => Ok(Extension::UseSrtp(ExtensionUseSrtp::unmarshal(reader)?)),
+            ExtensionValue::Alpn => Ok(Extension::Alpn(ExtensionAlpn::unmarshal(reader)?)),
             ExtensionValue::UseExtendedMasterSecret => Ok(Extension::UseExtendedMasterSecret(
                 ExtensionUseExtendedMasterSecret::unmarshal(reader)?,
             )),
+            ExtensionValue::RecordSizeLimit => Ok(Extension::RecordSizeLimit(
+                ExtensionRecordSizeLimit::unmarshal(reader)?,
+            )),
+            ExtensionValue::SupportedEktCiphers => Ok(Extension::SupportedEktCiphers(
+                ExtensionSupportedEktCiphers::unmarshal(reader)?,
+            )),
             ExtensionValue::RenegotiationInfo => Ok(Extension::RenegotiationInfo(
                 ExtensionRenegotiationInfo::unmarshal(reader)?,
             )),
@@ -1,14 +1,19 @@
 use std::fmt;
 use std::sync::atomic::Ordering;
 
-use async_trait::async_trait;
 use rand::Rng;
 
 use super::flight2::*;
+use super::flight4::*;
 use super::*;
 use crate::config::*;
 use crate::conn::*;
+use crate::curve::named_curve::*;
 use crate::error::Error;
+use crate::extension::extension_alpn::*;
+use crate::extension::extension_max_fragment_length::*;
+use crate::extension::extension_record_size_limit::*;
+use crate::extension::extension_status_request::*;
 use crate::extension::*;
 use crate::handshake::*;
 use crate::record_layer::record_layer_header::*;
@@ -23,27 +28,22 @@ impl fmt::Display for Flight0 {
     }
 }
 
-#[async_trait]
 impl Flight for Flight0 {
-    async fn parse(
+    fn parse(
         &self,
-        _tx: &mut mpsc::Sender<mpsc::Sender<()>>,
         state: &mut State,
         cache: &HandshakeCache,
         cfg: &HandshakeConfig,
     ) -> Result<Box<dyn Flight + Send + Sync>, (Option<Alert>, Option<Error>)> {
-        let (seq, msgs) = match cache
-            .full_pull_map(
-                0,
-                &[HandshakeCachePullRule {
-                    typ: HandshakeType::ClientHello,
-                    epoch: cfg.initial_epoch,
-                    is_client: true,
-                    optional: false,
-                }],
-            )
-            .await
-        {
+        let (seq, msgs) = match cache.full_pull_map(
+            0,
+            &[HandshakeCachePullRule {
+                typ: HandshakeType::ClientHello,
+                epoch: cfg.initial_epoch,
+                is_client: true,
+                optional: false,
+            }],
+        ) {
             Ok((seq, msgs)) => (seq, msgs),
             Err(_) => return Err((None, None)),
         };
@@ -86,7 +86,7 @@ impl Flight for Flight0 {
                         srv_cli_str(state.is_client),
                         cipher_suite.to_string()
                     );
-                    let mut cs = state.cipher_suite.lock().await;
+                    let mut cs = state.cipher_suite.lock();
                     *cs = Some(cipher_suite);
                 }
             } else {
@@ -99,10 +99,17 @@ impl Flight for Flight0 {
                 ));
             }
 
+            let mut record_size_limit = 0;
+            let mut max_fragment_length = MaxFragmentLength::Unsupported;
             for extension in &client_hello.extensions {
                 match extension {
                     Extension::SupportedEllipticCurves(e) => {
-                        if e.elliptic_curves.is_empty() {
+                        // Pick the client's most preferred group we support
+                        let named_curve = e
+                            .elliptic_curves
+                            .iter()
+                            .find(|c| **c != NamedCurve::Unsupported);
+                        let Some(named_curve) = named_curve else {
                             return Err((
                                 Some(Alert {
                                     alert_level: AlertLevel::Fatal,
@@ -110,8 +117,8 @@ impl Flight for Flight0 {
                                 }),
                                 Some(Error::ErrNoSupportedEllipticCurves),
                             ));
-                        }
-                        state.named_curve = e.elliptic_curves[0];
+                        };
+                        state.named_curve = *named_curve;
                     }
                     Extension::UseSrtp(e) => {
                         if let Ok(profile) = find_matching_srtp_profile(
@@ -129,6 +136,15 @@ impl Flight for Flight0 {
                             ));
                         }
                     }
+                    Extension::SupportedEktCiphers(e) => {
+                        // EKT is optional, so the extension is simply omitted
+                        // from the ServerHello if there is no common cipher
+                        if let Ok(ekt_cipher) =
+                            find_matching_ekt_cipher(&e.ekt_ciphers, &cfg.local_ekt_ciphers)
+                        {
+                            state.ekt_cipher = ekt_cipher;
+                        }
+                    }
                     Extension::UseExtendedMasterSecret(_) => {
                         if cfg.extended_master_secret != ExtendedMasterSecretType::Disable {
                             state.extended_master_secret = true;
@@ -137,10 +153,47 @@ impl Flight for Flight0 {
                     Extension::ServerName(e) => {
                         state.server_name.clone_from(&e.server_name); // remote server name
                     }
+                    Extension::RecordSizeLimit(e) => {
+                        record_size_limit = e.record_size_limit.min(RECORD_SIZE_LIMIT_MAX);
+                    }
+                    Extension::MaxFragmentLength(e) => {
+                        max_fragment_length = e.max_fragment_length;
+                    }
+                    Extension::StatusRequest(e) => {
+                        state.ocsp_stapling = e.status_type == CertificateStatusType::Ocsp
+                            && !cfg.local_ocsp_response.is_empty();
+                    }
+                    Extension::Alpn(e) => {
+                        state.negotiated_protocol = match alpn_protocol_selection(
+                            &cfg.supported_protocols,
+                            &e.protocol_name_list,
+                        ) {
+                            Ok(protocol) => protocol,
+                            Err(err) => {
+                                return Err((
+                                    Some(Alert {
+                                        alert_level: AlertLevel::Fatal,
+                                        alert_description: AlertDescription::NoApplicationProtocol,
+                                    }),
+                                    Some(err),
+                                ))
+                            }
+                        };
+                    }
                     _ => {}
                 }
             }
 
+            // https://tools.ietf.org/html/rfc8449#section-5
+            // record_size_limit takes precedence over max_fragment_length
+            if record_size_limit != 0 {
+                state.remote_record_size_limit = record_size_limit;
+                state.max_fragment_length = MaxFragmentLength::Unsupported;
+            } else {
+                state.remote_record_size_limit = max_fragment_length.length();
+                state.max_fragment_length = max_fragment_length;
+            }
+
             if cfg.extended_master_secret == ExtendedMasterSecretType::Require
                 && !state.extended_master_secret
             {
@@ -168,7 +221,11 @@ impl Flight for Flight0 {
                 };
             }
 
-            Ok(Box::new(Flight2 {}))
+            if cfg.insecure_skip_hello_verify {
+                Ok(Box::new(Flight4 {}))
+            } else {
+                Ok(Box::new(Flight2 {}))
+            }
         } else {
             Err((
                 Some(Alert {
@@ -180,7 +237,7 @@ impl Flight for Flight0 {
         }
     }
 
-    async fn generate(
+    fn generate(
         &self,
         state: &mut State,
         _cache: &HandshakeCache,
@@ -1,8 +1,6 @@
 use std::fmt;
 use std::sync::atomic::Ordering;
 
-use async_trait::async_trait;
-
 use super::flight3::*;
 use super::*;
 use crate::compression_methods::*;
@@ -11,7 +9,12 @@ use crate::conn::*;
 use crate::content::*;
 use crate::curve::named_curve::*;
 use crate::error::Error;
+use crate::extension::extension_alpn::*;
+use crate::extension::extension_max_fragment_length::*;
+use crate::extension::extension_record_size_limit::*;
 use crate::extension::extension_server_name::*;
+use crate::extension::extension_status_request::*;
+use crate::extension::extension_supported_ekt_ciphers::*;
 use crate::extension::extension_supported_elliptic_curves::*;
 use crate::extension::extension_supported_point_formats::*;
 use crate::extension::extension_supported_signature_algorithms::*;
@@ -33,37 +36,32 @@ impl fmt::Display for Flight1 {
     }
 }
 
-#[async_trait]
 impl Flight for Flight1 {
-    async fn parse(
+    fn parse(
         &self,
-        tx: &mut mpsc::Sender<mpsc::Sender<()>>,
         state: &mut State,
         cache: &HandshakeCache,
         cfg: &HandshakeConfig,
     ) -> Result<Box<dyn Flight + Send + Sync>, (Option<Alert>, Option<Error>)> {
         // HelloVerifyRequest can be skipped by the server,
         // so allow ServerHello during flight1 also
-        let (seq, msgs) = match cache
-            .full_pull_map(
-                state.handshake_recv_sequence,
-                &[
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::HelloVerifyRequest,
-                        epoch: cfg.initial_epoch,
-                        is_client: false,
-                        optional: true,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::ServerHello,
-                        epoch: cfg.initial_epoch,
-                        is_client: false,
-                        optional: true,
-                    },
-                ],
-            )
-            .await
-        {
+        let (seq, msgs) = match cache.full_pull_map(
+            state.handshake_recv_sequence,
+            &[
+                HandshakeCachePullRule {
+                    typ: HandshakeType::HelloVerifyRequest,
+                    epoch: cfg.initial_epoch,
+                    is_client: false,
+                    optional: true,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::ServerHello,
+                    epoch: cfg.initial_epoch,
+                    is_client: false,
+                    optional: true,
+                },
+            ],
+        ) {
             // No valid message received. Keep reading
             Ok((seq, msgs)) => (seq, msgs),
             Err(_) => return Err((None, None)),
@@ -73,7 +71,7 @@ impl Flight for Flight1 {
             // Flight1 and flight2 were skipped.
             // Parse as flight3.
             let flight3 = Flight3 {};
-            return flight3.parse(tx, state, cache, cfg).await;
+            return flight3.parse(state, cache, cfg);
         }
 
         if let Some(message) = msgs.get(&HandshakeType::HelloVerifyRequest) {
@@ -116,7 +114,7 @@ impl Flight for Flight1 {
         }
     }
 
-    async fn generate(
+    fn generate(
         &self,
         state: &mut State,
         _cache: &HandshakeCache,
@@ -142,7 +140,7 @@ impl Flight for Flight1 {
         if cfg.local_psk_callback.is_none() {
             extensions.extend_from_slice(&[
                 Extension::SupportedEllipticCurves(ExtensionSupportedEllipticCurves {
-                    elliptic_curves: vec![NamedCurve::P256, NamedCurve::X25519, NamedCurve::P384],
+                    elliptic_curves: default_named_curves(),
                 }),
                 Extension::SupportedPointFormats(ExtensionSupportedPointFormats {
                     point_formats: vec![ELLIPTIC_CURVE_POINT_FORMAT_UNCOMPRESSED],
@@ -156,6 +154,15 @@ impl Flight for Flight1 {
             }));
         }
 
+        if !cfg.local_ekt_ciphers.is_empty() {
+            extensions.push(Extension::SupportedEktCiphers(
+                ExtensionSupportedEktCiphers {
+                    ekt_ciphers: cfg.local_ekt_ciphers.clone(),
+                    is_server_selection: false,
+                },
+            ));
+        }
+
         if cfg.extended_master_secret == ExtendedMasterSecretType::Request
             || cfg.extended_master_secret == ExtendedMasterSecretType::Require
         {
@@ -170,6 +177,33 @@ impl Flight for Flight1 {
             }));
         }
 
+        if !cfg.supported_protocols.is_empty() {
+            extensions.push(Extension::Alpn(ExtensionAlpn {
+                protocol_name_list: cfg.supported_protocols.clone(),
+            }));
+        }
+
+        if cfg.local_record_size_limit != 0 {
+            extensions.push(Extension::RecordSizeLimit(ExtensionRecordSizeLimit {
+                record_size_limit: cfg.local_record_size_limit,
+            }));
+        }
+
+        if let Some(max_fragment_length) = cfg.local_max_fragment_length {
+            extensions.push(Extension::MaxFragmentLength(ExtensionMaxFragmentLength {
+                max_fragment_length,
+            }));
+        }
+
+        if cfg.verify_ocsp_response.is_some() {
+            extensions.push(Extension::StatusRequest(ExtensionStatusRequest {
+                status_type: CertificateStatusType::Ocsp,
+                responder_id_list: vec![],
+                request_extensions: vec![],
+                is_server_response: false,
+            }));
+        }
+
         Ok(vec![Packet {
             record: RecordLayer::new(
                 PROTOCOL_VERSION1_2,
@@ -1,6 +1,5 @@
 use std::fmt;
 
-use async_trait::async_trait;
 use log::*;
 
 use super::flight5::*;
@@ -11,7 +10,12 @@ use crate::config::*;
 use crate::content::*;
 use crate::curve::named_curve::*;
 use crate::error::Error;
+use crate::extension::extension_alpn::*;
+use crate::extension::extension_max_fragment_length::*;
+use crate::extension::extension_record_size_limit::*;
 use crate::extension::extension_server_name::*;
+use crate::extension::extension_status_request::*;
+use crate::extension::extension_supported_ekt_ciphers::*;
 use crate::extension::extension_supported_elliptic_curves::*;
 use crate::extension::extension_supported_point_formats::*;
 use crate::extension::extension_supported_signature_algorithms::*;
@@ -25,7 +29,7 @@ use crate::handshake::*;
 use crate::prf::{prf_pre_master_secret, prf_psk_pre_master_secret};
 use crate::record_layer::record_layer_header::*;
 use crate::record_layer::*;
-use crate::{find_matching_cipher_suite, find_matching_srtp_profile};
+use crate::{find_matching_cipher_suite, find_matching_ekt_cipher, find_matching_srtp_profile};
 
 #[derive(Debug, PartialEq)]
 pub(crate) struct Flight3;
@@ -36,11 +40,9 @@ impl fmt::Display for Flight3 {
     }
 }
 
-#[async_trait]
 impl Flight for Flight3 {
-    async fn parse(
+    fn parse(
         &self,
-        _tx: &mut mpsc::Sender<mpsc::Sender<()>>,
         state: &mut State,
         cache: &HandshakeCache,
         cfg: &HandshakeConfig,
@@ -48,18 +50,15 @@ impl Flight for Flight3 {
         // Clients may receive multiple HelloVerifyRequest messages with different cookies.
         // Clients SHOULD handle this by sending a new ClientHello with a cookie in response
         // to the new HelloVerifyRequest. RFC 6347 Section 4.2.1
-        if let Ok((seq, msgs)) = cache
-            .full_pull_map(
-                state.handshake_recv_sequence,
-                &[HandshakeCachePullRule {
-                    typ: HandshakeType::HelloVerifyRequest,
-                    epoch: cfg.initial_epoch,
-                    is_client: false,
-                    optional: true,
-                }],
-            )
-            .await
-        {
+        if let Ok((seq, msgs)) = cache.full_pull_map(
+            state.handshake_recv_sequence,
+            &[HandshakeCachePullRule {
+                typ: HandshakeType::HelloVerifyRequest,
+                epoch: cfg.initial_epoch,
+                is_client: false,
+                optional: true,
+            }],
+        ) {
             if let Some(message) = msgs.get(&HandshakeType::HelloVerifyRequest) {
                 // DTLS 1.2 clients must not assume that the server will use the protocol version
                 // specified in HelloVerifyRequest message. RFC 6347 Section 4.2.1
@@ -95,69 +94,71 @@ impl Flight for Flight3 {
         }
 
         let result = if cfg.local_psk_callback.is_some() {
-            cache
-                .full_pull_map(
-                    state.handshake_recv_sequence,
-                    &[
-                        HandshakeCachePullRule {
-                            typ: HandshakeType::ServerHello,
-                            epoch: cfg.initial_epoch,
-                            is_client: false,
-                            optional: false,
-                        },
-                        HandshakeCachePullRule {
-                            typ: HandshakeType::ServerKeyExchange,
-                            epoch: cfg.initial_epoch,
-                            is_client: false,
-                            optional: true,
-                        },
-                        HandshakeCachePullRule {
-                            typ: HandshakeType::ServerHelloDone,
-                            epoch: cfg.initial_epoch,
-                            is_client: false,
-                            optional: false,
-                        },
-                    ],
-                )
-                .await
+            cache.full_pull_map(
+                state.handshake_recv_sequence,
+                &[
+                    HandshakeCachePullRule {
+                        typ: HandshakeType::ServerHello,
+                        epoch: cfg.initial_epoch,
+                        is_client: false,
+                        optional: false,
+                    },
+                    HandshakeCachePullRule {
+                        typ: HandshakeType::ServerKeyExchange,
+                        epoch: cfg.initial_epoch,
+                        is_client: false,
+                        optional: true,
+                    },
+                    HandshakeCachePullRule {
+                        typ: HandshakeType::ServerHelloDone,
+                        epoch: cfg.initial_epoch,
+                        is_client: false,
+                        optional: false,
+                    },
+                ],
+            )
         } else {
-            cache
-                .full_pull_map(
-                    state.handshake_recv_sequence,
-                    &[
-                        HandshakeCachePullRule {
-                            typ: HandshakeType::ServerHello,
-                            epoch: cfg.initial_epoch,
-                            is_client: false,
-                            optional: false,
-                        },
-                        HandshakeCachePullRule {
-                            typ: HandshakeType::Certificate,
-                            epoch: cfg.initial_epoch,
-                            is_client: false,
-                            optional: true,
-                        },
-                        HandshakeCachePullRule {
-                            typ: HandshakeType::ServerKeyExchange,
-                            epoch: cfg.initial_epoch,
-                            is_client: false,
-                            optional: false,
-                        },
-                        HandshakeCachePullRule {
-                            typ: HandshakeType::CertificateRequest,
-                            epoch: cfg.initial_epoch,
-                            is_client: false,
-                            optional: true,
-                        },
-                        HandshakeCachePullRule {
-                            typ: HandshakeType::ServerHelloDone,
-                            epoch: cfg.initial_epoch,
-                            is_client: false,
-                            optional: false,
-                        },
-                    ],
-                )
-                .await
+            cache.full_pull_map(
+                state.handshake_recv_sequence,
+                &[
+                    HandshakeCachePullRule {
+                        typ: HandshakeType::ServerHello,
+                        epoch: cfg.initial_epoch,
+                        is_client: false,
+                        optional: false,
+                    },
+                    HandshakeCachePullRule {
+                        typ: HandshakeType::Certificate,
+                        epoch: cfg.initial_epoch,
+                        is_client: false,
+                        optional: true,
+                    },
+                    HandshakeCachePullRule {
+                        typ: HandshakeType::CertificateStatus,
+                        epoch: cfg.initial_epoch,
+                        is_client: false,
+                        optional: true,
+                    },
+                    HandshakeCachePullRule {
+                        typ: HandshakeType::ServerKeyExchange,
+                        epoch: cfg.initial_epoch,
+                        is_client: false,
+                        optional: false,
+                    },
+                    HandshakeCachePullRule {
+                        typ: HandshakeType::CertificateRequest,
+                        epoch: cfg.initial_epoch,
+                        is_client: false,
+                        optional: true,
+                    },
+                    HandshakeCachePullRule {
+                        typ: HandshakeType::ServerHelloDone,
+                        epoch: cfg.initial_epoch,
+                        is_client: false,
+                        optional: false,
+                    },
+                ],
+            )
         };
 
         let (seq, msgs) = match result {
@@ -216,6 +217,82 @@ impl Flight for Flight3 {
                             state.extended_master_secret = true;
                         }
                     }
+                    Extension::SupportedEktCiphers(e) => {
+                        let ekt_cipher = match find_matching_ekt_cipher(
+                            &e.ekt_ciphers,
+                            &cfg.local_ekt_ciphers,
+                        ) {
+                            Ok(ekt_cipher) if e.ekt_ciphers.len() == 1 => ekt_cipher,
+                            _ => {
+                                return Err((
+                                    Some(Alert {
+                                        alert_level: AlertLevel::Fatal,
+                                        alert_description: AlertDescription::IllegalParameter,
+                                    }),
+                                    Some(Error::ErrClientNoMatchingEktCipher),
+                                ))
+                            }
+                        };
+                        state.ekt_cipher = ekt_cipher;
+                    }
+                    Extension::Alpn(e) => {
+                        // https://tools.ietf.org/html/rfc7301#section-3.1
+                        // The server response MUST contain exactly one protocol name.
+                        if e.protocol_name_list.len() != 1
+                            || !cfg.supported_protocols.contains(&e.protocol_name_list[0])
+                        {
+                            return Err((
+                                Some(Alert {
+                                    alert_level: AlertLevel::Fatal,
+                                    alert_description: AlertDescription::IllegalParameter,
+                                }),
+                                Some(Error::ErrAlpnUnexpectedProtocol),
+                            ));
+                        }
+                        state
+                            .negotiated_protocol
+                            .clone_from(&e.protocol_name_list[0]);
+                    }
+                    Extension::RecordSizeLimit(e) => {
+                        if cfg.local_record_size_limit == 0 {
+                            return Err((
+                                Some(Alert {
+                                    alert_level: AlertLevel::Fatal,
+                                    alert_description: AlertDescription::UnsupportedExtension,
+                                }),
+                                Some(Error::ErrInvalidRecordSizeLimit),
+                            ));
+                        }
+                        state.remote_record_size_limit =
+                            e.record_size_limit.min(RECORD_SIZE_LIMIT_MAX);
+                    }
+                    Extension::MaxFragmentLength(e) => {
+                        // https://tools.ietf.org/html/rfc6066#section-4
+                        // The server must echo the requested value
+                        if cfg.local_max_fragment_length != Some(e.max_fragment_length) {
+                            return Err((
+                                Some(Alert {
+                                    alert_level: AlertLevel::Fatal,
+                                    alert_description: AlertDescription::IllegalParameter,
+                                }),
+                                Some(Error::ErrInvalidMaxFragmentLength),
+                            ));
+                        }
+                        state.max_fragment_length = e.max_fragment_length;
+                        state.remote_record_size_limit = e.max_fragment_length.length();
+                    }
+                    Extension::StatusRequest(_) => {
+                        if cfg.verify_ocsp_response.is_none() {
+                            return Err((
+                                Some(Alert {
+                                    alert_level: AlertLevel::Fatal,
+                                    alert_description: AlertDescription::UnsupportedExtension,
+                                }),
+                                Some(Error::ErrUnexpectedCertificateStatus),
+                            ));
+                        }
+                        state.ocsp_stapling = true;
+                    }
                     _ => {}
                 };
             }
@@ -283,7 +360,7 @@ impl Flight for Flight3 {
                 cipher_suite.to_string()
             );
             {
-                let mut cs = state.cipher_suite.lock().await;
+                let mut cs = state.cipher_suite.lock();
                 *cs = Some(cipher_suite);
             }
             state.remote_random = h.random.clone();
@@ -305,6 +382,34 @@ impl Flight for Flight3 {
             state.peer_certificates.clone_from(&h.certificate);
         }
 
+        if let Some(message) = msgs.get(&HandshakeType::CertificateStatus) {
+            let h = match message {
+                HandshakeMessage::CertificateStatus(h) => h,
+                _ => {
+                    return Err((
+                        Some(Alert {
+                            alert_level: AlertLevel::Fatal,
+                            alert_description: AlertDescription::InternalError,
+                        }),
+                        None,
+                    ))
+                }
+            };
+
+            // https://tools.ietf.org/html/rfc6066#section-8
+            // CertificateStatus must only follow an acknowledged status_request
+            if !state.ocsp_stapling {
+                return Err((
+                    Some(Alert {
+                        alert_level: AlertLevel::Fatal,
+                        alert_description: AlertDescription::UnexpectedMessage,
+                    }),
+                    Some(Error::ErrUnexpectedCertificateStatus),
+                ));
+            }
+            state.ocsp_response.clone_from(&h.response);
+        }
+
         if let Some(message) = msgs.get(&HandshakeType::ServerKeyExchange) {
             let h = match message {
                 HandshakeMessage::ServerKeyExchange(h) => h,
@@ -343,7 +448,7 @@ impl Flight for Flight3 {
         Ok(Box::new(Flight5 {}) as Box<dyn Flight + Send + Sync>)
     }
 
-    async fn generate(
+    fn generate(
         &self,
         state: &mut State,
         _cache: &HandshakeCache,
@@ -361,7 +466,7 @@ impl Flight for Flight3 {
         if cfg.local_psk_callback.is_none() {
             extensions.extend_from_slice(&[
                 Extension::SupportedEllipticCurves(ExtensionSupportedEllipticCurves {
-                    elliptic_curves: vec![NamedCurve::P256, NamedCurve::X25519, NamedCurve::P384],
+                    elliptic_curves: default_named_curves(),
                 }),
                 Extension::SupportedPointFormats(ExtensionSupportedPointFormats {
                     point_formats: vec![ELLIPTIC_CURVE_POINT_FORMAT_UNCOMPRESSED],
@@ -375,6 +480,15 @@ impl Flight for Flight3 {
             }));
         }
 
+        if !cfg.local_ekt_ciphers.is_empty() {
+            extensions.push(Extension::SupportedEktCiphers(
+                ExtensionSupportedEktCiphers {
+                    ekt_ciphers: cfg.local_ekt_ciphers.clone(),
+                    is_server_selection: false,
+                },
+            ));
+        }
+
         if cfg.extended_master_secret == ExtendedMasterSecretType::Request
             || cfg.extended_master_secret == ExtendedMasterSecretType::Require
         {
@@ -389,6 +503,33 @@ impl Flight for Flight3 {
             }));
         }
 
+        if !cfg.supported_protocols.is_empty() {
+            extensions.push(Extension::Alpn(ExtensionAlpn {
+                protocol_name_list: cfg.supported_protocols.clone(),
+            }));
+        }
+
+        if cfg.local_record_size_limit != 0 {
+            extensions.push(Extension::RecordSizeLimit(ExtensionRecordSizeLimit {
+                record_size_limit: cfg.local_record_size_limit,
+            }));
+        }
+
+        if let Some(max_fragment_length) = cfg.local_max_fragment_length {
+            extensions.push(Extension::MaxFragmentLength(ExtensionMaxFragmentLength {
+                max_fragment_length,
+            }));
+        }
+
+        if cfg.verify_ocsp_response.is_some() {
+            extensions.push(Extension::StatusRequest(ExtensionStatusRequest {
+                status_type: CertificateStatusType::Ocsp,
+                responder_id_list: vec![],
+                request_extensions: vec![],
+                is_server_response: false,
+            }));
+        }
+
         Ok(vec![Packet {
             record: RecordLayer::new(
                 PROTOCOL_VERSION1_2,
@@ -433,7 +574,7 @@ pub(crate) fn handle_server_key_exchange(
         state.identity_hint.clone_from(&h.identity_hint);
         state.pre_master_secret = prf_psk_pre_master_secret(&psk);
     } else {
-        let local_keypair = match h.named_curve.generate_keypair() {
+        let local_keypair = match h.named_curve.generate_client_keypair(&h.public_key) {
             Ok(local_keypair) => local_keypair,
             Err(err) => {
                 return Err((
@@ -1,7 +1,6 @@
 use std::fmt;
 use std::io::BufWriter;
 
-use async_trait::async_trait;
 use log::*;
 
 use super::flight6::*;
@@ -15,6 +14,11 @@ use crate::crypto::*;
 use crate::curve::named_curve::*;
 use crate::curve::*;
 use crate::error::Error;
+use crate::extension::extension_alpn::*;
+use crate::extension::extension_max_fragment_length::*;
+use crate::extension::extension_record_size_limit::*;
+use crate::extension::extension_status_request::*;
+use crate::extension::extension_supported_ekt_ciphers::*;
 use crate::extension::extension_supported_elliptic_curves::*;
 use crate::extension::extension_supported_point_formats::*;
 use crate::extension::extension_use_extended_master_secret::*;
@@ -23,6 +27,7 @@ use crate::extension::renegotiation_info::ExtensionRenegotiationInfo;
 use crate::extension::*;
 use crate::handshake::handshake_message_certificate::*;
 use crate::handshake::handshake_message_certificate_request::*;
+use crate::handshake::handshake_message_certificate_status::*;
 use crate::handshake::handshake_message_server_hello::*;
 use crate::handshake::handshake_message_server_hello_done::*;
 use crate::handshake::handshake_message_server_key_exchange::*;
@@ -41,41 +46,36 @@ impl fmt::Display for Flight4 {
     }
 }
 
-#[async_trait]
 impl Flight for Flight4 {
-    async fn parse(
+    fn parse(
         &self,
-        tx: &mut mpsc::Sender<mpsc::Sender<()>>,
         state: &mut State,
         cache: &HandshakeCache,
         cfg: &HandshakeConfig,
     ) -> Result<Box<dyn Flight + Send + Sync>, (Option<Alert>, Option<Error>)> {
-        let (seq, msgs) = match cache
-            .full_pull_map(
-                state.handshake_recv_sequence,
-                &[
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::Certificate,
-                        epoch: cfg.initial_epoch,
-                        is_client: true,
-                        optional: true,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::ClientKeyExchange,
-                        epoch: cfg.initial_epoch,
-                        is_client: true,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::CertificateVerify,
-                        epoch: cfg.initial_epoch,
-                        is_client: true,
-                        optional: true,
-                    },
-                ],
-            )
-            .await
-        {
+        let (seq, msgs) = match cache.full_pull_map(
+            state.handshake_recv_sequence,
+            &[
+                HandshakeCachePullRule {
+                    typ: HandshakeType::Certificate,
+                    epoch: cfg.initial_epoch,
+                    is_client: true,
+                    optional: true,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::ClientKeyExchange,
+                    epoch: cfg.initial_epoch,
+                    is_client: true,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::CertificateVerify,
+                    epoch: cfg.initial_epoch,
+                    is_client: true,
+                    optional: true,
+                },
+            ],
+        ) {
             Ok((seq, msgs)) => (seq, msgs),
             Err(_) => return Err((None, None)),
         };
@@ -139,58 +139,62 @@ impl Flight for Flight4 {
                 ));
             }
 
-            let plain_text = cache
-                .pull_and_merge(&[
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::ClientHello,
-                        epoch: cfg.initial_epoch,
-                        is_client: true,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::ServerHello,
-                        epoch: cfg.initial_epoch,
-                        is_client: false,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::Certificate,
-                        epoch: cfg.initial_epoch,
-                        is_client: false,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::ServerKeyExchange,
-                        epoch: cfg.initial_epoch,
-                        is_client: false,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::CertificateRequest,
-                        epoch: cfg.initial_epoch,
-                        is_client: false,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::ServerHelloDone,
-                        epoch: cfg.initial_epoch,
-                        is_client: false,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::Certificate,
-                        epoch: cfg.initial_epoch,
-                        is_client: true,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::ClientKeyExchange,
-                        epoch: cfg.initial_epoch,
-                        is_client: true,
-                        optional: false,
-                    },
-                ])
-                .await;
+            let plain_text = cache.pull_and_merge(&[
+                HandshakeCachePullRule {
+                    typ: HandshakeType::ClientHello,
+                    epoch: cfg.initial_epoch,
+                    is_client: true,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::ServerHello,
+                    epoch: cfg.initial_epoch,
+                    is_client: false,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::Certificate,
+                    epoch: cfg.initial_epoch,
+                    is_client: false,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::CertificateStatus,
+                    epoch: cfg.initial_epoch,
+                    is_client: false,
+                    optional: true,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::ServerKeyExchange,
+                    epoch: cfg.initial_epoch,
+                    is_client: false,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::CertificateRequest,
+                    epoch: cfg.initial_epoch,
+                    is_client: false,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::ServerHelloDone,
+                    epoch: cfg.initial_epoch,
+                    is_client: false,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::Certificate,
+                    epoch: cfg.initial_epoch,
+                    is_client: true,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::ClientKeyExchange,
+                    epoch: cfg.initial_epoch,
+                    is_client: true,
+                    optional: false,
+                },
+            ]);
 
             // Verify that the pair of hash algorithm and signature is listed.
             let mut valid_signature_scheme = false;
@@ -274,7 +278,7 @@ impl Flight for Flight4 {
         }
 
         {
-            let mut cipher_suite = state.cipher_suite.lock().await;
+            let mut cipher_suite = state.cipher_suite.lock();
             if let Some(cipher_suite) = &mut *cipher_suite {
                 if !cipher_suite.is_initialized() {
                     let mut server_random = vec![];
@@ -308,8 +312,17 @@ impl Flight for Flight4 {
                             .clone_from(&client_key_exchange.identity_hint);
                         pre_master_secret = prf_psk_pre_master_secret(&psk);
                     } else if let Some(local_keypair) = &state.local_keypair {
+                        // Hybrid group public keys carry a two byte length prefix,
+                        // which makes them parse as a PSK identity
+                        let public_key = if local_keypair.curve.is_hybrid()
+                            && client_key_exchange.public_key.is_empty()
+                        {
+                            &client_key_exchange.identity_hint
+                        } else {
+                            &client_key_exchange.public_key
+                        };
                         pre_master_secret = match prf_pre_master_secret(
-                            &client_key_exchange.public_key,
+                            public_key,
                             &local_keypair.private_key,
                             local_keypair.curve,
                         ) {
@@ -328,19 +341,18 @@ impl Flight for Flight4 {
 
                     if state.extended_master_secret {
                         let hf = cipher_suite.hash_func();
-                        let session_hash =
-                            match cache.session_hash(hf, cfg.initial_epoch, &[]).await {
-                                Ok(s) => s,
-                                Err(err) => {
-                                    return Err((
-                                        Some(Alert {
-                                            alert_level: AlertLevel::Fatal,
-                                            alert_description: AlertDescription::InternalError,
-                                        }),
-                                        Some(err),
-                                    ))
-                                }
-                            };
+                        let session_hash = match cache.session_hash(hf, cfg.initial_epoch, &[]) {
+                            Ok(s) => s,
+                            Err(err) => {
+                                return Err((
+                                    Some(Alert {
+                                        alert_level: AlertLevel::Fatal,
+                                        alert_description: AlertDescription::InternalError,
+                                    }),
+                                    Some(err),
+                                ))
+                            }
+                        };
 
                         state.master_secret = match prf_extended_master_secret(
                             &pre_master_secret,
@@ -396,32 +408,17 @@ impl Flight for Flight4 {
             }
         }
 
-        // Now, encrypted packets can be handled
-        let (done_tx, mut done_rx) = mpsc::channel(1);
-        if let Err(err) = tx.send(done_tx).await {
-            return Err((
-                Some(Alert {
-                    alert_level: AlertLevel::Fatal,
-                    alert_description: AlertDescription::InternalError,
-                }),
-                Some(Error::Other(err.to_string())),
-            ));
-        }
-
-        done_rx.recv().await;
-
-        let (seq, msgs) = match cache
-            .full_pull_map(
-                seq,
-                &[HandshakeCachePullRule {
-                    typ: HandshakeType::Finished,
-                    epoch: cfg.initial_epoch + 1,
-                    is_client: true,
-                    optional: false,
-                }],
-            )
-            .await
-        {
+        // The Finished is pulled once the records queued until the cipher suite was
+        // initialized have been handled, see DTLSDriver::handle_queued_packets
+        let (seq, msgs) = match cache.full_pull_map(
+            seq,
+            &[HandshakeCachePullRule {
+                typ: HandshakeType::Finished,
+                epoch: cfg.initial_epoch + 1,
+                is_client: true,
+                optional: false,
+            }],
+        ) {
             Ok((seq, msgs)) => (seq, msgs),
             // No valid message received. Keep reading
             Err(_) => return Err((None, None)),
@@ -497,7 +494,7 @@ impl Flight for Flight4 {
         Ok(Box::new(Flight6 {}) as Box<dyn Flight + Send + Sync>)
     }
 
-    async fn generate(
+    fn generate(
         &self,
         state: &mut State,
         _cache: &HandshakeCache,
@@ -521,6 +518,45 @@ impl Flight for Flight4 {
             }));
         }
 
+        if state.ekt_cipher != EktCipher::Unsupported {
+            extensions.push(Extension::SupportedEktCiphers(
+                ExtensionSupportedEktCiphers {
+                    ekt_ciphers: vec![state.ekt_cipher],
+                    is_server_selection: true,
+                },
+            ));
+        }
+
+        if !state.negotiated_protocol.is_empty() {
+            extensions.push(Extension::Alpn(ExtensionAlpn {
+                protocol_name_list: vec![state.negotiated_protocol.clone()],
+            }));
+        }
+
+        if state.max_fragment_length != MaxFragmentLength::Unsupported {
+            extensions.push(Extension::MaxFragmentLength(ExtensionMaxFragmentLength {
+                max_fragment_length: state.max_fragment_length,
+            }));
+        } else if state.remote_record_size_limit != 0 {
+            // Servers without a limit of their own advertise the protocol maximum
+            extensions.push(Extension::RecordSizeLimit(ExtensionRecordSizeLimit {
+                record_size_limit: if cfg.local_record_size_limit != 0 {
+                    cfg.local_record_size_limit
+                } else {
+                    RECORD_SIZE_LIMIT_MAX
+                },
+            }));
+        }
+
+        if state.ocsp_stapling && cfg.local_psk_callback.is_none() {
+            extensions.push(Extension::StatusRequest(ExtensionStatusRequest {
+                status_type: CertificateStatusType::Ocsp,
+                responder_id_list: vec![],
+                request_extensions: vec![],
+                is_server_response: true,
+            }));
+        }
+
         if cfg.local_psk_callback.is_none() {
             extensions.extend_from_slice(&[
                 Extension::SupportedEllipticCurves(ExtensionSupportedEllipticCurves {
@@ -541,7 +577,7 @@ impl Flight for Flight4 {
                         version: PROTOCOL_VERSION1_2,
                         random: state.local_random.clone(),
                         cipher_suite: {
-                            let cipher_suite = state.cipher_suite.lock().await;
+                            let cipher_suite = state.cipher_suite.lock();
                             if let Some(cipher_suite) = &*cipher_suite {
                                 cipher_suite.id()
                             } else {
@@ -589,6 +625,23 @@ impl Flight for Flight4 {
                 reset_local_sequence_number: false,
             });
 
+            if state.ocsp_stapling {
+                pkts.push(Packet {
+                    record: RecordLayer::new(
+                        PROTOCOL_VERSION1_2,
+                        0,
+                        Content::Handshake(Handshake::new(HandshakeMessage::CertificateStatus(
+                            HandshakeMessageCertificateStatus {
+                                status_type: CertificateStatusType::Ocsp,
+                                response: cfg.local_ocsp_response.clone(),
+                            },
+                        ))),
+                    ),
+                    should_encrypt: false,
+                    reset_local_sequence_number: false,
+                });
+            }
+
             let mut server_random = vec![];
             {
                 let mut writer = BufWriter::<&mut Vec<u8>>::new(server_random.as_mut());
@@ -730,7 +783,7 @@ impl Flight for Flight4 {
 mod tests {
     use std::sync::Arc;
 
-    use tokio::sync::Mutex;
+    use util::sync::Mutex;
 
     use super::*;
     use crate::error::Result;
@@ -779,8 +832,8 @@ mod tests {
     // Assert that if a client sends a certificate they must also send a `CertificateVerify`
     // message. The `Flight4` must not interact with the `cipher_suite` if the `CertificateVerify`
     // is missing.
-    #[tokio::test]
-    async fn test_flight4_process_certificateverify() {
+    #[test]
+    fn test_flight4_process_certificateverify() {
         let mut state = State {
             cipher_suite: Arc::new(Mutex::new(Some(Box::new(MockCipherSuite {})))),
             ..Default::default()
@@ -828,25 +881,19 @@ mod tests {
         ];
 
         let mut cache = HandshakeCache::new();
-        cache
-            .push(raw_certificate, 0, 0, HandshakeType::Certificate, true)
-            .await;
-        cache
-            .push(
-                raw_client_key_exchange,
-                0,
-                1,
-                HandshakeType::ClientKeyExchange,
-                true,
-            )
-            .await;
+        cache.push(raw_certificate, 0, 0, HandshakeType::Certificate, true);
+        cache.push(
+            raw_client_key_exchange,
+            0,
+            1,
+            HandshakeType::ClientKeyExchange,
+            true,
+        );
 
         let cfg = HandshakeConfig::default();
 
-        let (mut tx, _rx) = mpsc::channel::<mpsc::Sender<()>>(1);
-
         let f = Flight4 {};
-        let res = f.parse(&mut tx, &mut state, &cache, &cfg).await;
+        let res = f.parse(&mut state, &cache, &cfg);
         assert!(res.is_err());
     }
 }
@@ -1,8 +1,6 @@
 use std::fmt;
 use std::io::{BufReader, BufWriter};
 
-use async_trait::async_trait;
-
 use super::flight3::*;
 use super::*;
 use crate::change_cipher_spec::ChangeCipherSpec;
@@ -31,31 +29,26 @@ impl fmt::Display for Flight5 {
     }
 }
 
-#[async_trait]
 impl Flight for Flight5 {
     fn is_last_recv_flight(&self) -> bool {
         true
     }
 
-    async fn parse(
+    fn parse(
         &self,
-        _tx: &mut mpsc::Sender<mpsc::Sender<()>>,
         state: &mut State,
         cache: &HandshakeCache,
         cfg: &HandshakeConfig,
     ) -> Result<Box<dyn Flight + Send + Sync>, (Option<Alert>, Option<Error>)> {
-        let (_seq, msgs) = match cache
-            .full_pull_map(
-                state.handshake_recv_sequence,
-                &[HandshakeCachePullRule {
-                    typ: HandshakeType::Finished,
-                    epoch: cfg.initial_epoch + 1,
-                    is_client: false,
-                    optional: false,
-                }],
-            )
-            .await
-        {
+        let (_seq, msgs) = match cache.full_pull_map(
+            state.handshake_recv_sequence,
+            &[HandshakeCachePullRule {
+                typ: HandshakeType::Finished,
+                epoch: cfg.initial_epoch + 1,
+                is_client: false,
+                optional: false,
+            }],
+        ) {
             Ok((seq, msgs)) => (seq, msgs),
             Err(_) => return Err((None, None)),
         };
@@ -73,73 +66,77 @@ impl Flight for Flight5 {
                 ));
             };
 
-        let plain_text = cache
-            .pull_and_merge(&[
-                HandshakeCachePullRule {
-                    typ: HandshakeType::ClientHello,
-                    epoch: cfg.initial_epoch,
-                    is_client: true,
-                    optional: false,
-                },
-                HandshakeCachePullRule {
-                    typ: HandshakeType::ServerHello,
-                    epoch: cfg.initial_epoch,
-                    is_client: false,
-                    optional: false,
-                },
-                HandshakeCachePullRule {
-                    typ: HandshakeType::Certificate,
-                    epoch: cfg.initial_epoch,
-                    is_client: false,
-                    optional: false,
-                },
-                HandshakeCachePullRule {
-                    typ: HandshakeType::ServerKeyExchange,
-                    epoch: cfg.initial_epoch,
-                    is_client: false,
-                    optional: false,
-                },
-                HandshakeCachePullRule {
-                    typ: HandshakeType::CertificateRequest,
-                    epoch: cfg.initial_epoch,
-                    is_client: false,
-                    optional: false,
-                },
-                HandshakeCachePullRule {
-                    typ: HandshakeType::ServerHelloDone,
-                    epoch: cfg.initial_epoch,
-                    is_client: false,
-                    optional: false,
-                },
-                HandshakeCachePullRule {
-                    typ: HandshakeType::Certificate,
-                    epoch: cfg.initial_epoch,
-                    is_client: true,
-                    optional: false,
-                },
-                HandshakeCachePullRule {
-                    typ: HandshakeType::ClientKeyExchange,
-                    epoch: cfg.initial_epoch,
-                    is_client: true,
-                    optional: false,
-                },
-                HandshakeCachePullRule {
-                    typ: HandshakeType::CertificateVerify,
-                    epoch: cfg.initial_epoch,
-                    is_client: true,
-                    optional: false,
-                },
-                HandshakeCachePullRule {
-                    typ: HandshakeType::Finished,
-                    epoch: cfg.initial_epoch + 1,
-                    is_client: true,
-                    optional: false,
-                },
-            ])
-            .await;
+        let plain_text = cache.pull_and_merge(&[
+            HandshakeCachePullRule {
+                typ: HandshakeType::ClientHello,
+                epoch: cfg.initial_epoch,
+                is_client: true,
+                optional: false,
+            },
+            HandshakeCachePullRule {
+                typ: HandshakeType::ServerHello,
+                epoch: cfg.initial_epoch,
+                is_client: false,
+                optional: false,
+            },
+            HandshakeCachePullRule {
+                typ: HandshakeType::Certificate,
+                epoch: cfg.initial_epoch,
+                is_client: false,
+                optional: false,
+            },
+            HandshakeCachePullRule {
+                typ: HandshakeType::CertificateStatus,
+                epoch: cfg.initial_epoch,
+                is_client: false,
+                optional: true,
+            },
+            HandshakeCachePullRule {
+                typ: HandshakeType::ServerKeyExchange,
+                epoch: cfg.initial_epoch,
+                is_client: false,
+                optional: false,
+            },
+            HandshakeCachePullRule {
+                typ: HandshakeType::CertificateRequest,
+                epoch: cfg.initial_epoch,
+                is_client: false,
+                optional: false,
+            },
+            HandshakeCachePullRule {
+                typ: HandshakeType::ServerHelloDone,
+                epoch: cfg.initial_epoch,
+                is_client: false,
+                optional: false,
+            },
+            HandshakeCachePullRule {
+                typ: HandshakeType::Certificate,
+                epoch: cfg.initial_epoch,
+                is_client: true,
+                optional: false,
+            },
+            HandshakeCachePullRule {
+                typ: HandshakeType::ClientKeyExchange,
+                epoch: cfg.initial_epoch,
+                is_client: true,
+                optional: false,
+            },
+            HandshakeCachePullRule {
+                typ: HandshakeType::CertificateVerify,
+                epoch: cfg.initial_epoch,
+                is_client: true,
+                optional: false,
+            },
+            HandshakeCachePullRule {
+                typ: HandshakeType::Finished,
+                epoch: cfg.initial_epoch + 1,
+                is_client: true,
+                optional: false,
+            },
+        ]);
 
         {
-            let cipher_suite = state.cipher_suite.lock().await;
+            let cipher_suite = state.cipher_suite.lock();
             if let Some(cipher_suite) = &*cipher_suite {
                 let expected_verify_data = match prf_verify_data_server(
                     &state.master_secret,
@@ -173,7 +170,7 @@ impl Flight for Flight5 {
         Ok(Box::new(Flight5 {}))
     }
 
-    async fn generate(
+    fn generate(
         &self,
         state: &mut State,
         cache: &HandshakeCache,
@@ -250,14 +247,12 @@ impl Flight for Flight5 {
             reset_local_sequence_number: false,
         });
 
-        let server_key_exchange_data = cache
-            .pull_and_merge(&[HandshakeCachePullRule {
-                typ: HandshakeType::ServerKeyExchange,
-                epoch: cfg.initial_epoch,
-                is_client: false,
-                optional: false,
-            }])
-            .await;
+        let server_key_exchange_data = cache.pull_and_merge(&[HandshakeCachePullRule {
+            typ: HandshakeType::ServerKeyExchange,
+            epoch: cfg.initial_epoch,
+            is_client: false,
+            optional: false,
+        }]);
 
         let mut server_key_exchange = HandshakeMessageServerKeyExchange {
             identity_hint: vec![],
@@ -343,7 +338,7 @@ impl Flight for Flight5 {
         }
 
         if let Err((alert, err)) =
-            initialize_cipher_suite(state, cache, cfg, &server_key_exchange, &merged).await
+            initialize_cipher_suite(state, cache, cfg, &server_key_exchange, &merged)
         {
             return Err((alert, err));
         }
@@ -352,58 +347,62 @@ impl Flight for Flight5 {
         // CertificateVerify message is sent to explicitly verify possession of the
         // private key in the certificate.
         if state.remote_requested_certificate && !cfg.local_certificates.is_empty() {
-            let mut plain_text = cache
-                .pull_and_merge(&[
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::ClientHello,
-                        epoch: cfg.initial_epoch,
-                        is_client: true,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::ServerHello,
-                        epoch: cfg.initial_epoch,
-                        is_client: false,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::Certificate,
-                        epoch: cfg.initial_epoch,
-                        is_client: false,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::ServerKeyExchange,
-                        epoch: cfg.initial_epoch,
-                        is_client: false,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::CertificateRequest,
-                        epoch: cfg.initial_epoch,
-                        is_client: false,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::ServerHelloDone,
-                        epoch: cfg.initial_epoch,
-                        is_client: false,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::Certificate,
-                        epoch: cfg.initial_epoch,
-                        is_client: true,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::ClientKeyExchange,
-                        epoch: cfg.initial_epoch,
-                        is_client: true,
-                        optional: false,
-                    },
-                ])
-                .await;
+            let mut plain_text = cache.pull_and_merge(&[
+                HandshakeCachePullRule {
+                    typ: HandshakeType::ClientHello,
+                    epoch: cfg.initial_epoch,
+                    is_client: true,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::ServerHello,
+                    epoch: cfg.initial_epoch,
+                    is_client: false,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::Certificate,
+                    epoch: cfg.initial_epoch,
+                    is_client: false,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::CertificateStatus,
+                    epoch: cfg.initial_epoch,
+                    is_client: false,
+                    optional: true,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::ServerKeyExchange,
+                    epoch: cfg.initial_epoch,
+                    is_client: false,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::CertificateRequest,
+                    epoch: cfg.initial_epoch,
+                    is_client: false,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::ServerHelloDone,
+                    epoch: cfg.initial_epoch,
+                    is_client: false,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::Certificate,
+                    epoch: cfg.initial_epoch,
+                    is_client: true,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::ClientKeyExchange,
+                    epoch: cfg.initial_epoch,
+                    is_client: true,
+                    optional: false,
+                },
+            ]);
 
             plain_text.extend_from_slice(&merged);
 
@@ -501,74 +500,78 @@ impl Flight for Flight5 {
         });
 
         if state.local_verify_data.is_empty() {
-            let mut plain_text = cache
-                .pull_and_merge(&[
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::ClientHello,
-                        epoch: cfg.initial_epoch,
-                        is_client: true,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::ServerHello,
-                        epoch: cfg.initial_epoch,
-                        is_client: false,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::Certificate,
-                        epoch: cfg.initial_epoch,
-                        is_client: false,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::ServerKeyExchange,
-                        epoch: cfg.initial_epoch,
-                        is_client: false,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::CertificateRequest,
-                        epoch: cfg.initial_epoch,
-                        is_client: false,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::ServerHelloDone,
-                        epoch: cfg.initial_epoch,
-                        is_client: false,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::Certificate,
-                        epoch: cfg.initial_epoch,
-                        is_client: true,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::ClientKeyExchange,
-                        epoch: cfg.initial_epoch,
-                        is_client: true,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::CertificateVerify,
-                        epoch: cfg.initial_epoch,
-                        is_client: true,
-                        optional: false,
-                    },
-                    HandshakeCachePullRule {
-                        typ: HandshakeType::Finished,
-                        epoch: cfg.initial_epoch + 1,
-                        is_client: true,
-                        optional: false,
-                    },
-                ])
-                .await;
+            let mut plain_text = cache.pull_and_merge(&[
+                HandshakeCachePullRule {
+                    typ: HandshakeType::ClientHello,
+                    epoch: cfg.initial_epoch,
+                    is_client: true,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::ServerHello,
+                    epoch: cfg.initial_epoch,
+                    is_client: false,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::Certificate,
+                    epoch: cfg.initial_epoch,
+                    is_client: false,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::CertificateStatus,
+                    epoch: cfg.initial_epoch,
+                    is_client: false,
+                    optional: true,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::ServerKeyExchange,
+                    epoch: cfg.initial_epoch,
+                    is_client: false,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::CertificateRequest,
+                    epoch: cfg.initial_epoch,
+                    is_client: false,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::ServerHelloDone,
+                    epoch: cfg.initial_epoch,
+                    is_client: false,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::Certificate,
+                    epoch: cfg.initial_epoch,
+                    is_client: true,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::ClientKeyExchange,
+                    epoch: cfg.initial_epoch,
+                    is_client: true,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::CertificateVerify,
+                    epoch: cfg.initial_epoch,
+                    is_client: true,
+                    optional: false,
+                },
+                HandshakeCachePullRule {
+                    typ: HandshakeType::Finished,
+                    epoch: cfg.initial_epoch + 1,
+                    is_client: true,
+                    optional: false,
+                },
+            ]);
 
             plain_text.extend_from_slice(&merged);
 
-            let cipher_suite = state.cipher_suite.lock().await;
+            let cipher_suite = state.cipher_suite.lock();
             if let Some(cipher_suite) = &*cipher_suite {
                 state.local_verify_data = match prf_verify_data_client(
                     &state.master_secret,
@@ -606,14 +609,14 @@ impl Flight for Flight5 {
         Ok(pkts)
     }
 }
-async fn initialize_cipher_suite(
+fn initialize_cipher_suite(
     state: &mut State,
     cache: &HandshakeCache,
     cfg: &HandshakeConfig,
     h: &HandshakeMessageServerKeyExchange,
     sending_plain_text: &[u8],
 ) -> Result<(), (Option<Alert>, Option<Error>)> {
-    let mut cipher_suite = state.cipher_suite.lock().await;
+    let mut cipher_suite = state.cipher_suite.lock();
 
     if let Some(cipher_suite) = &*cipher_suite {
         if cipher_suite.is_initialized() {
@@ -634,14 +637,11 @@ async fn initialize_cipher_suite(
 
     if let Some(cipher_suite) = &*cipher_suite {
         if state.extended_master_secret {
-            let session_hash = match cache
-                .session_hash(
-                    cipher_suite.hash_func(),
-                    cfg.initial_epoch,
-                    sending_plain_text,
-                )
-                .await
-            {
+            let session_hash = match cache.session_hash(
+                cipher_suite.hash_func(),
+                cfg.initial_epoch,
+                sending_plain_text,
+            ) {
                 Ok(s) => s,
                 Err(err) => {
                     return Err((
@@ -758,6 +758,17 @@ async fn initialize_cipher_suite(
                 ));
             }
         }
+        if let Some(verify_ocsp_response) = &cfg.verify_ocsp_response {
+            if let Err(err) = verify_ocsp_response(&state.peer_certificates, &state.ocsp_response) {
+                return Err((
+                    Some(Alert {
+                        alert_level: AlertLevel::Fatal,
+                        alert_description: AlertDescription::BadCertificate,
+                    }),
+                    Some(err),
+                ));
+            }
+        }
     }
 
     if let Some(cipher_suite) = &mut *cipher_suite {
@@ -8,9 +8,6 @@ pub(crate) mod flight6;
 
 use std::fmt;
 
-use async_trait::async_trait;
-use tokio::sync::mpsc;
-
 use crate::alert::*;
 use crate::error::Error;
 use crate::handshake::handshake_cache::*;
@@ -58,7 +55,6 @@ pub(crate) struct Packet {
     pub(crate) reset_local_sequence_number: bool,
 }
 
-#[async_trait]
 pub(crate) trait Flight: fmt::Display + fmt::Debug {
     fn is_last_send_flight(&self) -> bool {
         false
@@ -70,15 +66,14 @@ pub(crate) trait Flight: fmt::Display + fmt::Debug {
         true
     }
 
-    async fn parse(
+    fn parse(
         &self,
-        tx: &mut mpsc::Sender<mpsc::Sender<()>>,
         state: &mut State,
         cache: &HandshakeCache,
         cfg: &HandshakeConfig,
     ) -> Result<Box<dyn Flight + Send + Sync>, (Option<Alert>, Option<Error>)>;
 
-    async fn generate(
+    fn generate(
         &self,
         state: &mut State,
         cache: &HandshakeCache,
@@ -6,7 +6,7 @@ use std::io::BufReader;
 use std::sync::Arc;
 
 use sha2::{Digest, Sha256};
-use tokio::sync::Mutex;
+use util::sync::Mutex;
 
 use crate::cipher_suite::*;
 use crate::handshake::*;
@@ -40,7 +40,7 @@ impl HandshakeCache {
         }
     }
 
-    pub(crate) async fn push(
+    pub(crate) fn push(
         &mut self,
         data: Vec<u8>,
         epoch: u16,
@@ -48,7 +48,7 @@ impl HandshakeCache {
         typ: HandshakeType,
         is_client: bool,
     ) -> bool {
-        let mut cache = self.cache.lock().await;
+        let mut cache = self.cache.lock();
 
         for i in &*cache {
             if i.message_sequence == message_sequence && i.is_client == is_client {
@@ -70,8 +70,8 @@ impl HandshakeCache {
     // returns a list handshakes that match the requested rules
     // the list will contain null entries for rules that can't be satisfied
     // multiple entries may match a rule, but only the last match is returned (ie ClientHello with cookies)
-    pub(crate) async fn pull(&self, rules: &[HandshakeCachePullRule]) -> Vec<HandshakeCacheItem> {
-        let cache = self.cache.lock().await;
+    pub(crate) fn pull(&self, rules: &[HandshakeCachePullRule]) -> Vec<HandshakeCacheItem> {
+        let cache = self.cache.lock();
 
         let mut out = vec![];
         for r in rules {
@@ -97,12 +97,12 @@ impl HandshakeCache {
     }
 
     // full_pull_map pulls all handshakes between rules[0] to rules[len(rules)-1] as map.
-    pub(crate) async fn full_pull_map(
+    pub(crate) fn full_pull_map(
         &self,
         start_seq: isize,
         rules: &[HandshakeCachePullRule],
     ) -> Result<(isize, HashMap<HandshakeType, HandshakeMessage>)> {
-        let cache = self.cache.lock().await;
+        let cache = self.cache.lock();
 
         let mut ci = HashMap::new();
         for r in rules {
@@ -150,10 +150,10 @@ impl HandshakeCache {
     }
 
     // pull_and_merge calls pull and then merges the results, ignoring any null entries
-    pub(crate) async fn pull_and_merge(&self, rules: &[HandshakeCachePullRule]) -> Vec<u8> {
+    pub(crate) fn pull_and_merge(&self, rules: &[HandshakeCachePullRule]) -> Vec<u8> {
         let mut merged = vec![];
 
-        for p in &self.pull(rules).await {
+        for p in &self.pull(rules) {
             merged.extend_from_slice(&p.data);
         }
 
@@ -162,7 +162,7 @@ impl HandshakeCache {
 
     // session_hash returns the session hash for Extended Master Secret support
     // https://tools.ietf.org/html/draft-ietf-tls-session-hash-06#section-4
-    pub(crate) async fn session_hash(
+    pub(crate) fn session_hash(
         &self,
         hf: CipherSuiteHash,
         epoch: u16,
@@ -171,58 +171,56 @@ impl HandshakeCache {
         let mut merged = vec![];
 
         // Order defined by https://tools.ietf.org/html/rfc5246#section-7.3
-        let handshake_buffer = self
-            .pull(&[
-                HandshakeCachePullRule {
-                    typ: HandshakeType::ClientHello,
-                    epoch,
-                    is_client: true,
-                    optional: false,
-                },
-                HandshakeCachePullRule {
-                    typ: HandshakeType::ServerHello,
-                    epoch,
-                    is_client: false,
-                    optional: false,
-                },
-                HandshakeCachePullRule {
-                    typ: HandshakeType::Certificate,
-                    epoch,
-                    is_client: false,
-                    optional: false,
-                },
-                HandshakeCachePullRule {
-                    typ: HandshakeType::ServerKeyExchange,
-                    epoch,
-                    is_client: false,
-                    optional: false,
-                },
-                HandshakeCachePullRule {
-                    typ: HandshakeType::CertificateRequest,
-                    epoch,
-                    is_client: false,
-                    optional: false,
-                },
-                HandshakeCachePullRule {
-                    typ: HandshakeType::ServerHelloDone,
-                    epoch,
-                    is_client: false,
-                    optional: false,
-                },
-                HandshakeCachePullRule {
-                    typ: HandshakeType::Certificate,
-                    epoch,
-                    is_client: true,
-                    optional: false,
-                },
-                HandshakeCachePullRule {
-                    typ: HandshakeType::ClientKeyExchange,
-                    epoch,
-                    is_client: true,
-                    optional: false,
-                },
-            ])
-            .await;
+        let handshake_buffer = self.pull(&[
+            HandshakeCachePullRule {
+                typ: HandshakeType::ClientHello,
+                epoch,
+                is_client: true,
+                optional: false,
+            },
+            HandshakeCachePullRule {
+                typ: HandshakeType::ServerHello,
+                epoch,
+                is_client: false,
+                optional: false,
+            },
+            HandshakeCachePullRule {
+                typ: HandshakeType::Certificate,
+                epoch,
+                is_client: false,
+                optional: false,
+            },
+            HandshakeCachePullRule {
+                typ: HandshakeType::ServerKeyExchange,
+                epoch,
+                is_client: false,
+                optional: false,
+            },
+            HandshakeCachePullRule {
+                typ: HandshakeType::CertificateRequest,
+                epoch,
+                is_client: false,
+                optional: false,
+            },
+            HandshakeCachePullRule {
+                typ: HandshakeType::ServerHelloDone,
+                epoch,
+                is_client: false,
+                optional: false,
+            },
+            HandshakeCachePullRule {
+                typ: HandshakeType::Certificate,
+                epoch,
+                is_client: true,
+                optional: false,
+            },
+            HandshakeCachePullRule {
+                typ: HandshakeType::ClientKeyExchange,
+                epoch,
+                is_client: true,
+                optional: false,
+            },
+        ]);
 
         for p in &handshake_buffer {
             merged.extend_from_slice(&p.data);
@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod handshake_message_certificate_status_test;
+
+use std::io::{Read, Write};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+use super::*;
+use crate::extension::extension_status_request::CertificateStatusType;
+
+/// CertificateStatus carries the OCSP response a server staples to its
+/// Certificate. It is sent right after the Certificate message when the
+/// server acknowledged the status_request extension.
+///
+/// ## Specifications
+///
+/// * [RFC 6066 §8]
+///
+/// [RFC 6066 §8]: https://tools.ietf.org/html/rfc6066#section-8
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct HandshakeMessageCertificateStatus {
+    pub(crate) status_type: CertificateStatusType,
+    pub(crate) response: Vec<u8>,
+}
+
+impl HandshakeMessageCertificateStatus {
+    pub fn handshake_type(&self) -> HandshakeType {
+        HandshakeType::CertificateStatus
+    }
+
+    pub fn size(&self) -> usize {
+        1 + 3 + self.response.len()
+    }
+
+    pub fn marshal<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u8(self.status_type as u8)?;
+        writer.write_u24::<BigEndian>(self.response.len() as u32)?;
+        writer.write_all(&self.response)?;
+
+        Ok(writer.flush()?)
+    }
+
+    pub fn unmarshal<R: Read>(reader: &mut R) -> Result<Self> {
+        let status_type: CertificateStatusType = reader.read_u8()?.into();
+        if status_type != CertificateStatusType::Ocsp {
+            return Err(Error::ErrInvalidCertificateStatusType);
+        }
+
+        // ocsp_response<1..2^24-1>
+        let response_len = reader.read_u24::<BigEndian>()? as usize;
+        if response_len == 0 {
+            return Err(Error::ErrLengthMismatch);
+        }
+        let mut response = vec![0u8; response_len];
+        reader.read_exact(&mut response)?;
+
+        Ok(HandshakeMessageCertificateStatus {
+            status_type,
+            response,
+        })
+    }
+}
@@ -0,0 +1,38 @@
+use std::io::{BufReader, BufWriter};
+
+use super::*;
+
+#[test]
+fn test_handshake_message_certificate_status() -> Result<()> {
+    let raw_certificate_status = vec![0x01, 0x00, 0x00, 0x04, 0x30, 0x02, 0x0a, 0x00];
+    let parsed_certificate_status = HandshakeMessageCertificateStatus {
+        status_type: CertificateStatusType::Ocsp,
+        response: vec![0x30, 0x02, 0x0a, 0x00],
+    };
+
+    let mut reader = BufReader::new(raw_certificate_status.as_slice());
+    let c = HandshakeMessageCertificateStatus::unmarshal(&mut reader)?;
+    assert_eq!(
+        c, parsed_certificate_status,
+        "handshakeMessageCertificateStatus unmarshal: got {c:?}, want {parsed_certificate_status:?}"
+    );
+
+    let mut raw = vec![];
+    {
+        let mut writer = BufWriter::<&mut Vec<u8>>::new(raw.as_mut());
+        c.marshal(&mut writer)?;
+    }
+    assert_eq!(
+        raw, raw_certificate_status,
+        "handshakeMessageCertificateStatus marshal: got {raw:?}, want {raw_certificate_status:?}"
+    );
+
+    let raw_unknown_status = vec![0x02, 0x00, 0x00, 0x01, 0x00];
+    let mut reader = BufReader::new(raw_unknown_status.as_slice());
+    assert_eq!(
+        HandshakeMessageCertificateStatus::unmarshal(&mut reader),
+        Err(Error::ErrInvalidCertificateStatusType)
+    );
+
+    Ok(())
+}
@@ -19,7 +19,9 @@ impl HandshakeMessageClientKeyExchange {
     }
 
     pub fn size(&self) -> usize {
-        if !self.public_key.is_empty() {
+        if self.public_key.len() > u8::MAX as usize {
+            2 + self.public_key.len()
+        } else if !self.public_key.is_empty() {
             1 + self.public_key.len()
         } else {
             2 + self.identity_hint.len()
@@ -33,7 +35,13 @@ impl HandshakeMessageClientKeyExchange {
             return Err(Error::ErrInvalidClientKeyExchange);
         }
 
-        if !self.public_key.is_empty() {
+        if self.public_key.len() > u8::MAX as usize {
+            // Hybrid groups carry public keys larger than the ECPoint one byte
+            // length prefix. These parse as a PSK identity on the receiving side
+            // and are resolved against the negotiated group there.
+            writer.write_u16::<BigEndian>(self.public_key.len() as u16)?;
+            writer.write_all(&self.public_key)?;
+        } else if !self.public_key.is_empty() {
             writer.write_u8(self.public_key.len() as u8)?;
             writer.write_all(&self.public_key)?;
         } else {
@@ -31,7 +31,8 @@ impl HandshakeMessageServerKeyExchange {
         if !self.identity_hint.is_empty() {
             2 + self.identity_hint.len()
         } else {
-            1 + 2 + 1 + self.public_key.len() + 2 + 2 + self.signature.len()
+            let public_key_length_size = if self.named_curve.is_hybrid() { 2 } else { 1 };
+            1 + 2 + public_key_length_size + self.public_key.len() + 2 + 2 + self.signature.len()
         }
     }
 
@@ -45,7 +46,12 @@ impl HandshakeMessageServerKeyExchange {
         writer.write_u8(self.elliptic_curve_type as u8)?;
         writer.write_u16::<BigEndian>(self.named_curve as u16)?;
 
-        writer.write_u8(self.public_key.len() as u8)?;
+        // Hybrid groups carry public keys larger than the ECPoint one byte length prefix
+        if self.named_curve.is_hybrid() {
+            writer.write_u16::<BigEndian>(self.public_key.len() as u16)?;
+        } else {
+            writer.write_u8(self.public_key.len() as u8)?;
+        }
         writer.write_all(&self.public_key)?;
 
         writer.write_u8(self.algorithm.hash as u8)?;
@@ -83,17 +89,24 @@ impl HandshakeMessageServerKeyExchange {
             return Err(Error::ErrBufferTooSmall);
         }
 
-        let named_curve = (((data[1] as u16) << 8) | data[2] as u16).into();
+        let named_curve: NamedCurve = (((data[1] as u16) << 8) | data[2] as u16).into();
         if data.len() < 4 {
             return Err(Error::ErrBufferTooSmall);
         }
 
-        let public_key_length = data[3] as usize;
-        let mut offset = 4 + public_key_length;
+        let (public_key_length, public_key_offset) = if named_curve.is_hybrid() {
+            if data.len() < 5 {
+                return Err(Error::ErrBufferTooSmall);
+            }
+            ((((data[3] as u16) << 8) | data[4] as u16) as usize, 5)
+        } else {
+            (data[3] as usize, 4)
+        };
+        let mut offset = public_key_offset + public_key_length;
         if data.len() < offset {
             return Err(Error::ErrBufferTooSmall);
         }
-        let public_key = data[4..offset].to_vec();
+        let public_key = data[public_key_offset..offset].to_vec();
         if data.len() <= offset {
             return Err(Error::ErrBufferTooSmall);
         }
@@ -2,6 +2,7 @@ pub mod handshake_cache;
 pub mod handshake_header;
 pub mod handshake_message_certificate;
 pub mod handshake_message_certificate_request;
+pub mod handshake_message_certificate_status;
 pub mod handshake_message_certificate_verify;
 pub mod handshake_message_client_hello;
 pub mod handshake_message_client_key_exchange;
@@ -21,6 +22,7 @@ use std::io::{Read, Write};
 use handshake_header::*;
 use handshake_message_certificate::*;
 use handshake_message_certificate_request::*;
+use handshake_message_certificate_status::*;
 use handshake_message_certificate_verify::*;
 use handshake_message_client_hello::*;
 use handshake_message_client_key_exchange::*;
@@ -51,6 +53,7 @@ pub enum HandshakeType {
     CertificateVerify = 15,
     ClientKeyExchange = 16,
     Finished = 20,
+    CertificateStatus = 22,
     #[default]
     Invalid,
 }
@@ -69,6 +72,7 @@ impl fmt::Display for HandshakeType {
             HandshakeType::CertificateVerify => write!(f, "CertificateVerify"),
             HandshakeType::ClientKeyExchange => write!(f, "ClientKeyExchange"),
             HandshakeType::Finished => write!(f, "Finished"),
+            HandshakeType::CertificateStatus => write!(f, "CertificateStatus"),
             HandshakeType::Invalid => write!(f, "Invalid"),
         }
     }
@@ -88,6 +92,7 @@ impl From<u8> for HandshakeType {
             15 => HandshakeType::CertificateVerify,
             16 => HandshakeType::ClientKeyExchange,
             20 => HandshakeType::Finished,
+            22 => HandshakeType::CertificateStatus,
             _ => HandshakeType::Invalid,
         }
     }
@@ -106,6 +111,7 @@ pub enum HandshakeMessage {
     CertificateVerify(HandshakeMessageCertificateVerify),
     ClientKeyExchange(HandshakeMessageClientKeyExchange),
     Finished(HandshakeMessageFinished),
+    CertificateStatus(HandshakeMessageCertificateStatus),
 }
 
 impl HandshakeMessage {
@@ -121,6 +127,7 @@ impl HandshakeMessage {
             HandshakeMessage::CertificateVerify(msg) => msg.handshake_type(),
             HandshakeMessage::ClientKeyExchange(msg) => msg.handshake_type(),
             HandshakeMessage::Finished(msg) => msg.handshake_type(),
+            HandshakeMessage::CertificateStatus(msg) => msg.handshake_type(),
         }
     }
 
@@ -136,6 +143,7 @@ impl HandshakeMessage {
             HandshakeMessage::CertificateVerify(msg) => msg.size(),
             HandshakeMessage::ClientKeyExchange(msg) => msg.size(),
             HandshakeMessage::Finished(msg) => msg.size(),
+            HandshakeMessage::CertificateStatus(msg) => msg.size(),
         }
     }
 
@@ -151,6 +159,7 @@ impl HandshakeMessage {
             HandshakeMessage::CertificateVerify(msg) => msg.marshal(writer)?,
             HandshakeMessage::ClientKeyExchange(msg) => msg.marshal(writer)?,
             HandshakeMessage::Finished(msg) => msg.marshal(writer)?,
+            HandshakeMessage::CertificateStatus(msg) => msg.marshal(writer)?,
         }
 
         Ok(())
@@ -231,6 +240,9 @@ impl Handshake {
             HandshakeType::Finished => {
                 HandshakeMessage::Finished(HandshakeMessageFinished::unmarshal(reader)?)
             }
+            HandshakeType::CertificateStatus => HandshakeMessage::CertificateStatus(
+                HandshakeMessageCertificateStatus::unmarshal(reader)?,
+            ),
             _ => return Err(Error::ErrNotImplemented),
         };
 
@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod handshake_observer_test;
+
+use crate::alert::Alert;
+use crate::cipher_suite::CipherSuiteId;
+use crate::curve::named_curve::NamedCurve;
+use crate::extension::extension_supported_ekt_ciphers::EktCipher;
+use crate::extension::extension_use_srtp::SrtpProtectionProfile;
+
+/// NegotiatedExtensions summarizes the parameters agreed on in the hello exchange.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NegotiatedExtensions {
+    pub named_curve: NamedCurve,
+    pub srtp_protection_profile: SrtpProtectionProfile,
+    pub ekt_cipher: EktCipher,
+    pub extended_master_secret: bool,
+    pub negotiated_protocol: String,
+    /// record_size_limit is the largest record plaintext the peer accepts, 0 if unlimited
+    pub record_size_limit: u16,
+    pub ocsp_stapling: bool,
+}
+
+/// HandshakeEvent is a step of the handshake reported to a `HandshakeObserver`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum HandshakeEvent {
+    /// A flight was written to the transport. retransmit is set when the same
+    /// flight has been sent before.
+    FlightSent { flight: String, retransmit: bool },
+    /// A complete flight of the peer was parsed and the handshake advances to
+    /// next_flight.
+    FlightReceived { next_flight: String },
+    /// The cipher suite was selected by the server.
+    CipherSuiteSelected(CipherSuiteId),
+    /// The hello exchange completed.
+    ExtensionsNegotiated(NegotiatedExtensions),
+    /// An alert was sent to the peer.
+    AlertSent(Alert),
+    /// An alert was received from the peer.
+    AlertReceived(Alert),
+    /// The handshake completed successfully.
+    Completed,
+    /// The handshake failed with the given error.
+    Failed(String),
+}
+
+/// HandshakeObserver receives structured handshake events, so that failed
+/// handshakes can be diagnosed without packet captures.
+///
+/// Events are delivered synchronously from the connection's tasks,
+/// implementations should return quickly and must not block.
+pub trait HandshakeObserver: Send + Sync {
+    fn on_event(&self, is_client: bool, event: &HandshakeEvent);
+}
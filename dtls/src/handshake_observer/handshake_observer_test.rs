@@ -0,0 +1,183 @@
+use std::sync::{Arc, Mutex};
+
+use util::conn::conn_pipe::*;
+
+use super::*;
+use crate::config::*;
+use crate::conn::*;
+use crate::crypto::Certificate;
+use crate::error::*;
+
+#[derive(Default)]
+struct RecordingObserver {
+    events: Mutex<Vec<(bool, HandshakeEvent)>>,
+}
+
+impl RecordingObserver {
+    fn events(&self, is_client: bool) -> Vec<HandshakeEvent> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(c, _)| *c == is_client)
+            .map(|(_, e)| e.clone())
+            .collect()
+    }
+}
+
+impl HandshakeObserver for RecordingObserver {
+    fn on_event(&self, is_client: bool, event: &HandshakeEvent) {
+        self.events.lock().unwrap().push((is_client, event.clone()));
+    }
+}
+
+#[tokio::test]
+async fn test_handshake_observer() -> Result<()> {
+    let observer = Arc::new(RecordingObserver::default());
+
+    let (ca, cb) = pipe();
+    let client_observer = Arc::clone(&observer) as Arc<dyn HandshakeObserver>;
+    let client = tokio::spawn(async move {
+        DTLSConn::new(
+            Arc::new(ca),
+            Config {
+                certificates: vec![Certificate::generate_self_signed(vec![
+                    "localhost".to_owned()
+                ])?],
+                srtp_protection_profiles: vec![SrtpProtectionProfile::Srtp_Aead_Aes_128_Gcm],
+                insecure_skip_verify: true,
+                handshake_observer: Some(client_observer),
+                ..Default::default()
+            },
+            true,
+            None,
+        )
+        .await
+    });
+
+    let server = DTLSConn::new(
+        Arc::new(cb),
+        Config {
+            certificates: vec![Certificate::generate_self_signed(vec![
+                "localhost".to_owned()
+            ])?],
+            srtp_protection_profiles: vec![SrtpProtectionProfile::Srtp_Aead_Aes_128_Gcm],
+            handshake_observer: Some(Arc::clone(&observer) as Arc<dyn HandshakeObserver>),
+            ..Default::default()
+        },
+        false,
+        None,
+    )
+    .await?;
+    let client = client.await.unwrap()?;
+
+    for is_client in [true, false] {
+        let events = observer.events(is_client);
+
+        assert!(events.contains(&HandshakeEvent::Completed));
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, HandshakeEvent::FlightSent { .. })));
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, HandshakeEvent::FlightReceived { .. })));
+
+        let cipher_suites: Vec<&HandshakeEvent> = events
+            .iter()
+            .filter(|e| matches!(e, HandshakeEvent::CipherSuiteSelected(_)))
+            .collect();
+        assert_eq!(cipher_suites.len(), 1, "cipher suite must be reported once");
+
+        let negotiated = events.iter().find_map(|e| match e {
+            HandshakeEvent::ExtensionsNegotiated(n) => Some(n.clone()),
+            _ => None,
+        });
+        assert_eq!(
+            negotiated.map(|n| n.srtp_protection_profile),
+            Some(SrtpProtectionProfile::Srtp_Aead_Aes_128_Gcm)
+        );
+    }
+
+    // The transcript of the client starts with its ClientHello
+    assert_eq!(
+        observer.events(true).first(),
+        Some(&HandshakeEvent::FlightSent {
+            flight: "Flight 1".to_owned(),
+            retransmit: false,
+        })
+    );
+
+    client.close().await?;
+    server.close().await?;
+
+    assert!(observer
+        .events(true)
+        .iter()
+        .any(|e| matches!(e, HandshakeEvent::AlertSent(_))));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_handshake_observer_failure() -> Result<()> {
+    let observer = Arc::new(RecordingObserver::default());
+
+    let (ca, cb) = pipe();
+    let client_observer = Arc::clone(&observer) as Arc<dyn HandshakeObserver>;
+    let client = tokio::spawn(async move {
+        DTLSConn::new(
+            Arc::new(ca),
+            Config {
+                srtp_protection_profiles: vec![SrtpProtectionProfile::Srtp_Aes128_Cm_Hmac_Sha1_80],
+                insecure_skip_verify: true,
+                handshake_observer: Some(client_observer),
+                ..Default::default()
+            },
+            true,
+            None,
+        )
+        .await
+    });
+
+    let server = DTLSConn::new(
+        Arc::new(cb),
+        Config {
+            certificates: vec![Certificate::generate_self_signed(vec![
+                "localhost".to_owned()
+            ])?],
+            srtp_protection_profiles: vec![SrtpProtectionProfile::Srtp_Aead_Aes_128_Gcm],
+            handshake_observer: Some(Arc::clone(&observer) as Arc<dyn HandshakeObserver>),
+            ..Default::default()
+        },
+        false,
+        None,
+    )
+    .await;
+    assert!(server.is_err(), "expected the handshake to fail");
+    assert!(
+        client.await.unwrap().is_err(),
+        "expected the handshake to fail"
+    );
+
+    let server_events = observer.events(false);
+    assert!(server_events.iter().any(|e| matches!(
+        e,
+        HandshakeEvent::AlertSent(a)
+            if a.alert_description == crate::alert::AlertDescription::InsufficientSecurity
+    )));
+    assert!(server_events
+        .iter()
+        .any(|e| matches!(e, HandshakeEvent::Failed(_))));
+
+    let client_events = observer.events(true);
+    assert!(client_events.iter().any(|e| matches!(
+        e,
+        HandshakeEvent::AlertReceived(a)
+            if a.alert_description == crate::alert::AlertDescription::InsufficientSecurity
+    )));
+    assert!(client_events
+        .iter()
+        .any(|e| matches!(e, HandshakeEvent::Failed(_))));
+
+    Ok(())
+}
@@ -3,14 +3,19 @@ use std::fmt;
 use std::sync::Arc;
 
 use log::*;
+use rand::Rng;
+use std::time::{Duration, Instant};
 
 use crate::cipher_suite::*;
 use crate::config::*;
-use crate::conn::*;
 use crate::content::*;
 use crate::crypto::*;
+use crate::driver::*;
 use crate::error::*;
+use crate::extension::extension_max_fragment_length::*;
+use crate::extension::extension_supported_ekt_ciphers::*;
 use crate::extension::extension_use_srtp::*;
+use crate::handshake_observer::*;
 use crate::signature_hash_algorithm::*;
 
 use rustls::client::danger::ServerCertVerifier;
@@ -75,9 +80,11 @@ impl fmt::Display for HandshakeState {
     }
 }
 
-pub(crate) type VerifyPeerCertificateFn =
+pub type VerifyPeerCertificateFn =
     Arc<dyn (Fn(&[Vec<u8>], &[CertificateDer<'static>]) -> Result<()>) + Send + Sync>;
 
+pub type VerifyOcspResponseFn = Arc<dyn (Fn(&[Vec<u8>], &[u8]) -> Result<()>) + Send + Sync>;
+
 pub(crate) struct HandshakeConfig {
     pub(crate) local_psk_callback: Option<PskCallback>,
     pub(crate) local_psk_identity_hint: Option<Vec<u8>>,
@@ -85,6 +92,11 @@ pub(crate) struct HandshakeConfig {
     pub(crate) local_signature_schemes: Vec<SignatureHashAlgorithm>, // Available signature schemes
     pub(crate) extended_master_secret: ExtendedMasterSecretType, // Policy for the Extended Master Support extension
     pub(crate) local_srtp_protection_profiles: Vec<SrtpProtectionProfile>, // Available SRTPProtectionProfiles, if empty no SRTP support
+    pub(crate) local_ekt_ciphers: Vec<EktCipher>, // Available EKT ciphers, if empty no EKT support
+    pub(crate) supported_protocols: Vec<String>, // Available ALPN protocols, if empty no ALPN support
+    pub(crate) local_record_size_limit: u16,     // Advertised record_size_limit, 0 if not sent
+    pub(crate) local_max_fragment_length: Option<MaxFragmentLength>, // Requested max_fragment_length
+    pub(crate) local_ocsp_response: Vec<u8>, // OCSP response stapled by a server, if empty no stapling
     pub(crate) server_name: String,
     pub(crate) client_auth: ClientAuthType, // If we are a client should we request a client certificate
     pub(crate) local_certificates: Vec<Certificate>,
@@ -92,10 +104,15 @@ pub(crate) struct HandshakeConfig {
     pub(crate) insecure_skip_verify: bool,
     pub(crate) insecure_verification: bool,
     pub(crate) verify_peer_certificate: Option<VerifyPeerCertificateFn>,
+    pub(crate) verify_ocsp_response: Option<VerifyOcspResponseFn>,
     pub(crate) server_cert_verifier: Arc<dyn ServerCertVerifier>,
     pub(crate) client_cert_verifier: Option<Arc<dyn ClientCertVerifier>>,
     pub(crate) retransmit_interval: tokio::time::Duration,
+    pub(crate) max_retransmit_interval: tokio::time::Duration,
+    pub(crate) retransmit_jitter: tokio::time::Duration,
+    pub(crate) insecure_skip_hello_verify: bool,
     pub(crate) initial_epoch: u16,
+    pub(crate) handshake_observer: Option<Arc<dyn HandshakeObserver>>,
     //log           logging.LeveledLogger
     //mu sync.Mutex
 }
@@ -123,6 +140,11 @@ impl Default for HandshakeConfig {
             local_signature_schemes: vec![],
             extended_master_secret: ExtendedMasterSecretType::Disable,
             local_srtp_protection_profiles: vec![],
+            local_ekt_ciphers: vec![],
+            supported_protocols: vec![],
+            local_record_size_limit: 0,
+            local_max_fragment_length: None,
+            local_ocsp_response: vec![],
             server_name: String::new(),
             client_auth: ClientAuthType::NoClientCert,
             local_certificates: vec![],
@@ -130,6 +152,7 @@ impl Default for HandshakeConfig {
             insecure_skip_verify: false,
             insecure_verification: false,
             verify_peer_certificate: None,
+            verify_ocsp_response: None,
             server_cert_verifier: rustls::client::WebPkiServerVerifier::builder(Arc::new(
                 gen_self_signed_root_cert(),
             ))
@@ -137,12 +160,22 @@ impl Default for HandshakeConfig {
             .unwrap(),
             client_cert_verifier: None,
             retransmit_interval: tokio::time::Duration::from_secs(0),
+            max_retransmit_interval: tokio::time::Duration::from_secs(0),
+            retransmit_jitter: tokio::time::Duration::from_secs(0),
+            insecure_skip_hello_verify: false,
             initial_epoch: 0,
+            handshake_observer: None,
         }
     }
 }
 
 impl HandshakeConfig {
+    pub(crate) fn observe(&self, is_client: bool, event: HandshakeEvent) {
+        if let Some(observer) = &self.handshake_observer {
+            observer.on_event(is_client, &event);
+        }
+    }
+
     pub(crate) fn get_certificate(&self, server_name: &str) -> Result<Certificate> {
         //TODO
         /*if self.name_to_certificate.is_empty() {
@@ -210,6 +243,18 @@ impl HandshakeConfig {
     }
 }
 
+// next_retransmit_interval doubles the retransmission timeout, capped at
+// max_interval. See RFC 6347 Section 4.2.4.1
+pub(crate) fn next_retransmit_interval(
+    current: tokio::time::Duration,
+    max_interval: tokio::time::Duration,
+) -> tokio::time::Duration {
+    if max_interval <= current {
+        return current;
+    }
+    std::cmp::min(current.saturating_mul(2), max_interval)
+}
+
 pub(crate) fn srv_cli_str(is_client: bool) -> String {
     if is_client {
         return "client".to_owned();
@@ -217,47 +262,48 @@ pub(crate) fn srv_cli_str(is_client: bool) -> String {
     "server".to_owned()
 }
 
-impl DTLSConn {
-    pub(crate) async fn handshake(&mut self, mut state: HandshakeState) -> Result<()> {
+impl DTLSDriver {
+    // handshake runs the handshake until it waits for the peer or the timer
+    pub(crate) fn handshake(&mut self, now: Instant) -> Result<()> {
         loop {
             trace!(
                 "[handshake:{}] {}: {}",
                 srv_cli_str(self.state.is_client),
                 self.current_flight.to_string(),
-                state.to_string()
+                self.handshake_state.to_string()
             );
 
-            if state == HandshakeState::Finished && !self.is_handshake_completed_successfully() {
+            if self.handshake_state == HandshakeState::Finished && !self.is_handshake_complete() {
                 self.set_handshake_completed_successfully();
-                self.handshake_done_tx.take(); // drop it by take
-                return Ok(());
+                self.cfg
+                    .observe(self.state.is_client, HandshakeEvent::Completed);
             }
 
-            state = match state {
-                HandshakeState::Preparing => self.prepare().await?,
-                HandshakeState::Sending => self.send().await?,
-                HandshakeState::Waiting => self.wait().await?,
-                HandshakeState::Finished => self.finish().await?,
+            self.handshake_state = match self.handshake_state {
+                HandshakeState::Preparing => self.prepare()?,
+                HandshakeState::Sending => self.send(now)?,
+                HandshakeState::Waiting | HandshakeState::Finished => return Ok(()),
                 _ => return Err(Error::ErrInvalidFsmTransition),
             };
         }
     }
 
-    async fn prepare(&mut self) -> Result<HandshakeState> {
+    fn prepare(&mut self) -> Result<HandshakeState> {
         self.flights = None;
+        self.flight_transmissions = 0;
 
         // Prepare flights
         self.retransmit = self.current_flight.has_retransmit();
+        self.current_retransmit_interval = self.cfg.retransmit_interval;
 
         let result = self
             .current_flight
-            .generate(&mut self.state, &self.cache, &self.cfg)
-            .await;
+            .generate(&mut self.state, &self.cache, &self.cfg);
 
         match result {
             Err((a, mut err)) => {
                 if let Some(a) = a {
-                    let alert_err = self.notify(a.alert_level, a.alert_description).await;
+                    let alert_err = self.notify(a.alert_level, a.alert_description);
 
                     if let Err(alert_err) = alert_err {
                         if err.is_some() {
@@ -269,22 +315,7 @@ impl DTLSConn {
                     return Err(err);
                 }
             }
-            Ok(pkts) => {
-                /*if !pkts.is_empty() {
-                    let mut s = vec![];
-                    {
-                        let mut writer = BufWriter::<&mut Vec<u8>>::new(s.as_mut());
-                        pkts[0].record.content.marshal(&mut writer)?;
-                    }
-                    trace!(
-                        "[handshake:{}] {}: {:?}",
-                        srv_cli_str(self.state.is_client),
-                        self.current_flight.to_string(),
-                        s,
-                    );
-                }*/
-                self.flights = Some(pkts)
-            }
+            Ok(pkts) => self.flights = Some(pkts),
         };
 
         let epoch = self.cfg.initial_epoch;
@@ -312,118 +343,161 @@ impl DTLSConn {
 
         Ok(HandshakeState::Sending)
     }
-    async fn send(&mut self) -> Result<HandshakeState> {
+
+    fn send(&mut self, now: Instant) -> Result<HandshakeState> {
         // Send flights
         if let Some(pkts) = self.flights.clone() {
-            self.write_packets(pkts).await?;
+            self.write_packets(pkts)?;
         }
+        self.cfg.observe(
+            self.state.is_client,
+            HandshakeEvent::FlightSent {
+                flight: self.current_flight.to_string(),
+                retransmit: self.flight_transmissions > 0,
+            },
+        );
+        self.flight_transmissions += 1;
 
         if self.current_flight.is_last_send_flight() {
             Ok(HandshakeState::Finished)
         } else {
+            self.set_retransmit_timer(now);
             Ok(HandshakeState::Waiting)
         }
     }
-    async fn wait(&mut self) -> Result<HandshakeState> {
-        let retransmit_timer = tokio::time::sleep(self.cfg.retransmit_interval);
-        tokio::pin!(retransmit_timer);
 
-        loop {
-            tokio::select! {
-                 done = self.handshake_rx.recv() =>{
-                    if done.is_none() {
-                        trace!("[handshake:{}] {} handshake_tx is dropped", srv_cli_str(self.state.is_client), self.current_flight.to_string());
-                        return Err(Error::ErrAlertFatalOrClose);
-                    }
+    // set_retransmit_timer arms the retransmission of the flight sent
+    pub(crate) fn set_retransmit_timer(&mut self, now: Instant) {
+        let mut timeout = self.current_retransmit_interval;
+        if !self.cfg.retransmit_jitter.is_zero() {
+            timeout += rand::thread_rng().gen_range(Duration::ZERO..=self.cfg.retransmit_jitter);
+        }
+        self.retransmit_deadline = Some(now + timeout);
+    }
 
-                    //trace!("[handshake:{}] {} received handshake_rx", srv_cli_str(self.state.is_client), self.current_flight.to_string());
-                    let result = self.current_flight.parse(&mut self.handle_queue_tx, &mut self.state, &self.cache, &self.cfg).await;
-                    drop(done);
-                    match result {
-                        Err((alert, mut err)) => {
-                            trace!("[handshake:{}] {} result alert:{:?}, err:{:?}",
-                                    srv_cli_str(self.state.is_client),
-                                    self.current_flight.to_string(),
-                                    alert,
-                                    err);
-
-                            if let Some(alert) = alert {
-                                let alert_err = self.notify(alert.alert_level, alert.alert_description).await;
-
-                                if let Err(alert_err) = alert_err {
-                                    if err.is_some() {
-                                        err = Some(alert_err);
-                                    }
-                                }
-                            }
-                            if let Some(err) = err {
-                                return Err(err);
-                            }
-                        }
-                        Ok(next_flight) => {
-                            trace!("[handshake:{}] {} -> {}", srv_cli_str(self.state.is_client), self.current_flight.to_string(), next_flight.to_string());
-                            if next_flight.is_last_recv_flight() && self.current_flight.to_string() == next_flight.to_string() {
-                                return Ok(HandshakeState::Finished);
-                            }
-                            self.current_flight = next_flight;
-                            return Ok(HandshakeState::Preparing);
+    // wait parses the handshake messages received while waiting for the next
+    // flight of the peer
+    pub(crate) fn wait(&mut self) -> Result<()> {
+        let had_cipher_suite = self.state.cipher_suite.lock().is_some();
+        let result = self
+            .current_flight
+            .parse(&mut self.state, &self.cache, &self.cfg);
+        match result {
+            Err((alert, mut err)) => {
+                trace!(
+                    "[handshake:{}] {} result alert:{:?}, err:{:?}",
+                    srv_cli_str(self.state.is_client),
+                    self.current_flight.to_string(),
+                    alert,
+                    err
+                );
+
+                if let Some(alert) = alert {
+                    let alert_err = self.notify(alert.alert_level, alert.alert_description);
+
+                    if let Err(alert_err) = alert_err {
+                        if err.is_some() {
+                            err = Some(alert_err);
                         }
-                    };
+                    }
+                }
+                if let Some(err) = err {
+                    return Err(err);
+                }
+            }
+            Ok(next_flight) => {
+                trace!(
+                    "[handshake:{}] {} -> {}",
+                    srv_cli_str(self.state.is_client),
+                    self.current_flight.to_string(),
+                    next_flight.to_string()
+                );
+                if !had_cipher_suite {
+                    self.observe_hello_exchange();
                 }
+                self.cfg.observe(
+                    self.state.is_client,
+                    HandshakeEvent::FlightReceived {
+                        next_flight: next_flight.to_string(),
+                    },
+                );
+                self.retransmit_deadline = None;
+                if next_flight.is_last_recv_flight()
+                    && self.current_flight.to_string() == next_flight.to_string()
+                {
+                    self.handshake_state = HandshakeState::Finished;
+                } else {
+                    self.current_flight = next_flight;
+                    self.handshake_state = HandshakeState::Preparing;
+                }
+            }
+        };
 
-                _ = retransmit_timer.as_mut() =>{
-                    trace!("[handshake:{}] {} retransmit_timer", srv_cli_str(self.state.is_client), self.current_flight.to_string());
+        Ok(())
+    }
 
-                    if !self.retransmit {
-                        return Ok(HandshakeState::Waiting);
-                    }
-                    return Ok(HandshakeState::Sending);
-                }
+    // observe_hello_exchange reports the cipher suite and extensions once the
+    // hello messages have been parsed
+    fn observe_hello_exchange(&self) {
+        if self.cfg.handshake_observer.is_none() {
+            return;
+        }
 
-                /*_ = self.done_rx.recv() => {
-                    return Err(Error::new("done_rx recv".to_owned()));
-                }*/
+        let cipher_suite_id = {
+            let cipher_suite = self.state.cipher_suite.lock();
+            match &*cipher_suite {
+                Some(cipher_suite) => cipher_suite.id(),
+                None => return,
             }
-        }
+        };
+
+        self.cfg.observe(
+            self.state.is_client,
+            HandshakeEvent::CipherSuiteSelected(cipher_suite_id),
+        );
+        self.cfg.observe(
+            self.state.is_client,
+            HandshakeEvent::ExtensionsNegotiated(NegotiatedExtensions {
+                named_curve: self.state.named_curve,
+                srtp_protection_profile: self.state.srtp_protection_profile,
+                ekt_cipher: self.state.ekt_cipher,
+                extended_master_secret: self.state.extended_master_secret,
+                negotiated_protocol: self.state.negotiated_protocol.clone(),
+                record_size_limit: self.state.remote_record_size_limit,
+                ocsp_stapling: self.state.ocsp_stapling,
+            }),
+        );
     }
-    async fn finish(&mut self) -> Result<HandshakeState> {
-        let retransmit_timer = tokio::time::sleep(self.cfg.retransmit_interval);
-
-        tokio::select! {
-            done = self.handshake_rx.recv() =>{
-                if done.is_none() {
-                    trace!("[handshake:{}] {} handshake_tx is dropped", srv_cli_str(self.state.is_client), self.current_flight.to_string());
-                    return Err(Error::ErrAlertFatalOrClose);
-                }
-                let result = self.current_flight.parse(&mut self.handle_queue_tx, &mut self.state, &self.cache, &self.cfg).await;
-                drop(done);
-                match result {
-                    Err((alert, mut err)) => {
-                        if let Some(alert) = alert {
-                            let alert_err = self.notify(alert.alert_level, alert.alert_description).await;
-                            if let Err(alert_err) = alert_err {
-                                if err.is_some() {
-                                    err = Some(alert_err);
-                                }
-                            }
-                        }
-                        if let Some(err) = err {
-                            return Err(err);
+
+    // finish parses the handshake messages received once the handshake finished:
+    // the peer retransmits its last flight when it missed ours, which the server
+    // then sends again after the retransmission interval
+    pub(crate) fn finish(&mut self, now: Instant) -> Result<()> {
+        let result = self
+            .current_flight
+            .parse(&mut self.state, &self.cache, &self.cfg);
+        match result {
+            Err((alert, mut err)) => {
+                if let Some(alert) = alert {
+                    let alert_err = self.notify(alert.alert_level, alert.alert_description);
+                    if let Err(alert_err) = alert_err {
+                        if err.is_some() {
+                            err = Some(alert_err);
                         }
                     }
-                    Ok(_) => {
-                        retransmit_timer.await;
-                        // Retransmit last flight
-                        return Ok(HandshakeState::Sending);
-                    }
-                };
+                }
+                if let Some(err) = err {
+                    return Err(err);
+                }
             }
+            Ok(_) => {
+                // Retransmit last flight
+                if self.current_flight.is_last_send_flight() && self.retransmit_deadline.is_none() {
+                    self.retransmit_deadline = Some(now + self.cfg.retransmit_interval);
+                }
+            }
+        };
 
-            /*_ = self.done_rx.recv() => {
-                return Err(Error::new("done_rx recv".to_owned()));
-            }*/
-        }
-
-        Ok(HandshakeState::Finished)
+        Ok(())
     }
 }
@@ -12,11 +12,13 @@ pub mod conn;
 pub mod content;
 pub mod crypto;
 pub mod curve;
+pub mod driver;
 mod error;
 pub mod extension;
 pub mod flight;
 pub mod fragment_buffer;
 pub mod handshake;
+pub mod handshake_observer;
 pub mod handshaker;
 pub mod listener;
 pub mod prf;
@@ -26,6 +28,7 @@ pub mod state;
 
 use cipher_suite::*;
 pub use error::Error;
+use extension::extension_supported_ekt_ciphers::EktCipher;
 use extension::extension_use_srtp::SrtpProtectionProfile;
 
 pub(crate) fn find_matching_srtp_profile(
@@ -42,6 +45,17 @@ pub(crate) fn find_matching_srtp_profile(
     Err(())
 }
 
+pub(crate) fn find_matching_ekt_cipher(a: &[EktCipher], b: &[EktCipher]) -> Result<EktCipher, ()> {
+    for a_cipher in a {
+        for b_cipher in b {
+            if a_cipher == b_cipher {
+                return Ok(*a_cipher);
+            }
+        }
+    }
+    Err(())
+}
+
 pub(crate) fn find_matching_cipher_suite(
     a: &[CipherSuiteId],
     b: &[CipherSuiteId],
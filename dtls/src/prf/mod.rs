@@ -1,21 +1,15 @@
 #[cfg(test)]
 mod prf_test;
 
-use std::convert::TryInto;
 use std::fmt;
 
-use hmac::{Hmac, Mac};
-use sha1::Sha1;
-use sha2::{Digest, Sha256};
-
-type HmacSha256 = Hmac<Sha256>;
-type HmacSha1 = Hmac<Sha1>;
-
 use crate::cipher_suite::CipherSuiteHash;
 use crate::content::ContentType;
+use crate::crypto::provider;
 use crate::curve::named_curve::*;
 use crate::error::*;
 use crate::record_layer::record_layer_header::ProtocolVersion;
+use crate::signature_hash_algorithm::HashAlgorithm;
 
 pub(crate) const PRF_MASTER_SECRET_LABEL: &str = "master secret";
 pub(crate) const PRF_EXTENDED_MASTER_SECRET_LABEL: &str = "extended master secret";
@@ -77,6 +71,8 @@ pub(crate) fn prf_pre_master_secret(
         NamedCurve::P256 => elliptic_curve_pre_master_secret(public_key, private_key, curve),
         NamedCurve::P384 => elliptic_curve_pre_master_secret(public_key, private_key, curve),
         NamedCurve::X25519 => elliptic_curve_pre_master_secret(public_key, private_key, curve),
+        #[cfg(feature = "pq-hybrid")]
+        NamedCurve::X25519MlKem768 => hybrid_pre_master_secret(public_key, private_key),
         _ => Err(Error::ErrInvalidNamedCurve),
     }
 }
@@ -86,34 +82,12 @@ fn elliptic_curve_pre_master_secret(
     private_key: &NamedCurvePrivateKey,
     curve: NamedCurve,
 ) -> Result<Vec<u8>> {
-    match curve {
-        NamedCurve::P256 => {
-            let pub_key = p256::EncodedPoint::from_bytes(public_key)?;
-            let public = p256::PublicKey::from_sec1_bytes(pub_key.as_ref())?;
-            if let NamedCurvePrivateKey::EphemeralSecretP256(secret) = private_key {
-                return Ok(secret.diffie_hellman(&public).raw_secret_bytes().to_vec());
-            }
-        }
-        NamedCurve::P384 => {
-            let pub_key = p384::EncodedPoint::from_bytes(public_key)?;
-            let public = p384::PublicKey::from_sec1_bytes(pub_key.as_ref())?;
-            if let NamedCurvePrivateKey::EphemeralSecretP384(secret) = private_key {
-                return Ok(secret.diffie_hellman(&public).raw_secret_bytes().to_vec());
-            }
+    match private_key {
+        NamedCurvePrivateKey::Ecdhe(key_exchange) if key_exchange.curve() == curve => {
+            key_exchange.diffie_hellman(public_key)
         }
-        NamedCurve::X25519 => {
-            if public_key.len() != 32 {
-                return Err(Error::Other("Public key is not 32 len".into()));
-            }
-            let pub_key: [u8; 32] = public_key.try_into().unwrap();
-            let public = x25519_dalek::PublicKey::from(pub_key);
-            if let NamedCurvePrivateKey::StaticSecretX25519(secret) = private_key {
-                return Ok(secret.diffie_hellman(&public).as_bytes().to_vec());
-            }
-        }
-        _ => return Err(Error::ErrInvalidNamedCurve),
+        _ => Err(Error::ErrNamedCurveAndPrivateKeyMismatch),
     }
-    Err(Error::ErrNamedCurveAndPrivateKeyMismatch)
 }
 
 //  This PRF with the SHA-256 hash function is used for all cipher suites
@@ -140,15 +114,7 @@ fn elliptic_curve_pre_master_secret(
 //
 // https://tools.ietf.org/html/rfc4346w
 fn hmac_sha(h: CipherSuiteHash, key: &[u8], data: &[u8]) -> Result<Vec<u8>> {
-    let mut mac = match h {
-        CipherSuiteHash::Sha256 => {
-            HmacSha256::new_from_slice(key).map_err(|e| Error::Other(e.to_string()))?
-        }
-    };
-    mac.update(data);
-    let result = mac.finalize();
-    let code_bytes = result.into_bytes();
-    Ok(code_bytes.to_vec())
+    provider::get_default().hmac(h.hash_algorithm(), key, &[data])
 }
 
 pub(crate) fn prf_p_hash(
@@ -251,11 +217,7 @@ pub(crate) fn prf_verify_data(
     label: &str,
     h: CipherSuiteHash,
 ) -> Result<Vec<u8>> {
-    let mut hasher = match h {
-        CipherSuiteHash::Sha256 => Sha256::new(),
-    };
-    hasher.update(handshake_bodies);
-    let result = hasher.finalize();
+    let result = provider::get_default().hash(h.hash_algorithm(), handshake_bodies)?;
     let mut seed = label.as_bytes().to_vec();
     seed.extend_from_slice(&result);
 
@@ -297,8 +259,6 @@ pub(crate) fn prf_mac(
     payload: &[u8],
     key: &[u8],
 ) -> Result<Vec<u8>> {
-    let mut hmac = HmacSha1::new_from_slice(key).map_err(|e| Error::Other(e.to_string()))?;
-
     let mut msg = vec![0u8; 13];
     msg[..2].copy_from_slice(&epoch.to_be_bytes());
     msg[2..8].copy_from_slice(&sequence_number.to_be_bytes()[2..]);
@@ -307,9 +267,5 @@ pub(crate) fn prf_mac(
     msg[10] = protocol_version.minor;
     msg[11..].copy_from_slice(&(payload.len() as u16).to_be_bytes());
 
-    hmac.update(&msg);
-    hmac.update(payload);
-    let result = hmac.finalize();
-
-    Ok(result.into_bytes().to_vec())
+    provider::get_default().hmac(HashAlgorithm::Sha1, key, &[&msg, payload])
 }
@@ -1,5 +1,6 @@
 use super::*;
 use crate::cipher_suite::CipherSuiteHash;
+use crate::crypto::provider::rust_crypto::EcdhSecret;
 
 #[test]
 fn test_pre_master_secret() -> Result<()> {
@@ -8,8 +9,9 @@ fn test_pre_master_secret() -> Result<()> {
         0x2f, 0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x3b, 0x3c, 0x3d,
         0x3e, 0x3f,
     ];
-    let private_key =
-        NamedCurvePrivateKey::StaticSecretX25519(x25519_dalek::StaticSecret::from(private_key));
+    let private_key = NamedCurvePrivateKey::Ecdhe(Box::new(EcdhSecret::X25519(
+        x25519_dalek::StaticSecret::from(private_key),
+    )));
     let public_key = [
         0x9f, 0xd7, 0xad, 0x6d, 0xcf, 0xf4, 0x29, 0x8d, 0xd3, 0xf9, 0x6d, 0x5b, 0x1b, 0x2a, 0xf9,
         0x10, 0xa0, 0x53, 0x5b, 0x14, 0x88, 0xd7, 0xf8, 0xfa, 0xbb, 0x34, 0x9a, 0x98, 0x28, 0x80,
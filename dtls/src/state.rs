@@ -6,17 +6,26 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use portable_atomic::AtomicU16;
 use serde::{Deserialize, Serialize};
-use tokio::sync::Mutex;
+use util::replay_detector::{ReplayWindow, SlidingWindowDetector};
+use util::sync::Mutex;
 use util::{KeyingMaterialExporter, KeyingMaterialExporterError};
 
 use super::cipher_suite::*;
 use super::conn::*;
 use super::curve::named_curve::*;
+use super::extension::extension_max_fragment_length::MaxFragmentLength;
+use super::extension::extension_supported_ekt_ciphers::EktCipher;
 use super::extension::extension_use_srtp::SrtpProtectionProfile;
 use super::handshake::handshake_random::*;
 use super::prf::*;
+use super::record_layer::record_layer_header::MAX_SEQUENCE_NUMBER;
 use crate::error::*;
 
+// Leads the output of marshal_binary. Older snapshots had no version; they start with the
+// low byte of the local epoch, which is 0 or 1 since renegotiation is not supported, and
+// are read as SerializedStateV1.
+const SERIALIZED_STATE_VERSION: u8 = 2;
+
 // State holds the dtls connection state and implements both encoding.BinaryMarshaler and encoding.BinaryUnmarshaler
 pub struct State {
     pub(crate) local_epoch: Arc<AtomicU16>,
@@ -28,6 +37,12 @@ pub struct State {
     pub(crate) cipher_suite: Arc<Mutex<Option<Box<dyn CipherSuite + Send + Sync>>>>, // nil if a cipher_suite hasn't been chosen
 
     pub(crate) srtp_protection_profile: SrtpProtectionProfile, // Negotiated srtp_protection_profile
+    pub(crate) ekt_cipher: EktCipher,                          // Negotiated EKT cipher
+    pub(crate) negotiated_protocol: String, // Negotiated ALPN protocol, empty if none
+    pub(crate) remote_record_size_limit: u16, // Largest record plaintext the peer accepts, 0 if unlimited
+    pub(crate) max_fragment_length: MaxFragmentLength, // Negotiated legacy max_fragment_length
+    pub(crate) ocsp_stapling: bool, // Client requested, or server agreed to, OCSP stapling
+    pub(crate) ocsp_response: Vec<u8>, // OCSP response stapled by the server, empty if none
     pub peer_certificates: Vec<Vec<u8>>,
     pub identity_hint: Vec<u8>,
 
@@ -47,11 +62,35 @@ pub struct State {
     pub(crate) local_verify_data: Vec<u8>,         // cached VerifyData
     pub(crate) local_key_signature: Vec<u8>,       // cached keySignature
     pub(crate) peer_certificates_verified: bool,
-    //pub(crate) replay_detector: Vec<Box<dyn ReplayDetector + Send + Sync>>,
+    pub(crate) replay_detector: Arc<util::sync::Mutex<Vec<SlidingWindowDetector>>>, // per remote epoch
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
 struct SerializedState {
+    local_epoch: u16,
+    remote_epoch: u16,
+    // Replay window of remote_epoch, empty if no record was received in it
+    remote_replay_window_size: usize,
+    remote_sequence_number: u64, // uint48, highest accepted
+    remote_replay_window: Vec<u64>,
+    local_random: [u8; HANDSHAKE_RANDOM_LENGTH],
+    remote_random: [u8; HANDSHAKE_RANDOM_LENGTH],
+    cipher_suite_id: u16,
+    master_secret: Vec<u8>,
+    sequence_number: u64,
+    srtp_protection_profile: u16,
+    ekt_cipher: u8,
+    negotiated_protocol: String,
+    remote_record_size_limit: u16,
+    peer_certificates: Vec<Vec<u8>>,
+    identity_hint: Vec<u8>,
+    is_client: bool,
+}
+
+// SerializedStateV1 is the unversioned layout of marshal_binary before the replay window
+// and the negotiated extensions were kept.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct SerializedStateV1 {
     local_epoch: u16,
     remote_epoch: u16,
     local_random: [u8; HANDSHAKE_RANDOM_LENGTH],
@@ -65,6 +104,30 @@ struct SerializedState {
     is_client: bool,
 }
 
+impl From<SerializedStateV1> for SerializedState {
+    fn from(v1: SerializedStateV1) -> Self {
+        SerializedState {
+            local_epoch: v1.local_epoch,
+            remote_epoch: v1.remote_epoch,
+            remote_replay_window_size: 0,
+            remote_sequence_number: 0,
+            remote_replay_window: vec![],
+            local_random: v1.local_random,
+            remote_random: v1.remote_random,
+            cipher_suite_id: v1.cipher_suite_id,
+            master_secret: v1.master_secret,
+            sequence_number: v1.sequence_number,
+            srtp_protection_profile: v1.srtp_protection_profile,
+            ekt_cipher: EktCipher::Unsupported as u8,
+            negotiated_protocol: String::new(),
+            remote_record_size_limit: 0,
+            peer_certificates: v1.peer_certificates,
+            identity_hint: v1.identity_hint,
+            is_client: v1.is_client,
+        }
+    }
+}
+
 impl Default for State {
     fn default() -> Self {
         State {
@@ -77,6 +140,12 @@ impl Default for State {
             cipher_suite: Arc::new(Mutex::new(None)), // nil if a cipher_suite hasn't been chosen
 
             srtp_protection_profile: SrtpProtectionProfile::Unsupported, // Negotiated srtp_protection_profile
+            ekt_cipher: EktCipher::Unsupported,
+            negotiated_protocol: String::new(),
+            remote_record_size_limit: 0,
+            max_fragment_length: MaxFragmentLength::Unsupported,
+            ocsp_stapling: false,
+            ocsp_response: vec![],
             peer_certificates: vec![],
             identity_hint: vec![],
 
@@ -96,23 +165,23 @@ impl Default for State {
             local_verify_data: vec![],           // cached VerifyData
             local_key_signature: vec![],         // cached keySignature
             peer_certificates_verified: false,
-            //replay_detector: vec![],
+            replay_detector: Arc::new(util::sync::Mutex::new(vec![])),
         }
     }
 }
 
 impl State {
-    pub(crate) async fn clone(&self) -> Self {
+    pub(crate) fn clone(&self) -> Self {
         let mut state = State::default();
 
-        if let Ok(serialized) = self.serialize().await {
-            let _ = state.deserialize(&serialized).await;
+        if let Ok(serialized) = self.serialize() {
+            let _ = state.deserialize(&serialized);
         }
 
         state
     }
 
-    async fn serialize(&self) -> Result<SerializedState> {
+    fn serialize(&self) -> Result<SerializedState> {
         let mut local_rand = vec![];
         {
             let mut writer = BufWriter::<&mut Vec<u8>>::new(local_rand.as_mut());
@@ -133,11 +202,17 @@ impl State {
         let local_epoch = self.local_epoch.load(Ordering::SeqCst);
         let remote_epoch = self.remote_epoch.load(Ordering::SeqCst);
         let sequence_number = {
-            let lsn = self.local_sequence_number.lock().await;
+            let lsn = self.local_sequence_number.lock();
             lsn[local_epoch as usize]
         };
+        let remote_replay_window = self
+            .replay_detector
+            .lock()
+            .get(remote_epoch as usize)
+            .map(|detector| detector.window())
+            .unwrap_or_default();
         let cipher_suite_id = {
-            let cipher_suite = self.cipher_suite.lock().await;
+            let cipher_suite = self.cipher_suite.lock();
             match &*cipher_suite {
                 Some(cipher_suite) => cipher_suite.id() as u16,
                 None => return Err(Error::ErrCipherSuiteUnset),
@@ -147,26 +222,52 @@ impl State {
         Ok(SerializedState {
             local_epoch,
             remote_epoch,
+            remote_replay_window_size: remote_replay_window.window_size,
+            remote_sequence_number: remote_replay_window.latest_seq,
+            remote_replay_window: remote_replay_window.accepted,
             local_random,
             remote_random,
             cipher_suite_id,
             master_secret: self.master_secret.clone(),
             sequence_number,
             srtp_protection_profile: self.srtp_protection_profile as u16,
+            ekt_cipher: self.ekt_cipher as u8,
+            negotiated_protocol: self.negotiated_protocol.clone(),
+            remote_record_size_limit: self.remote_record_size_limit,
             peer_certificates: self.peer_certificates.clone(),
             identity_hint: self.identity_hint.clone(),
             is_client: self.is_client,
         })
     }
 
-    async fn deserialize(&mut self, serialized: &SerializedState) -> Result<()> {
+    fn deserialize(&mut self, serialized: &SerializedState) -> Result<()> {
         // Set epoch values
         self.local_epoch
             .store(serialized.local_epoch, Ordering::SeqCst);
         self.remote_epoch
             .store(serialized.remote_epoch, Ordering::SeqCst);
+        if serialized.remote_replay_window_size != 0 {
+            // A restored connection must keep rejecting records seen before the snapshot
+            let window = ReplayWindow {
+                window_size: serialized.remote_replay_window_size,
+                latest_seq: serialized.remote_sequence_number,
+                accepted: serialized.remote_replay_window.clone(),
+            };
+            let mut replay_detector = self.replay_detector.lock();
+            replay_detector.clear();
+            for _ in 0..serialized.remote_epoch {
+                replay_detector.push(SlidingWindowDetector::new(
+                    window.window_size,
+                    MAX_SEQUENCE_NUMBER,
+                ));
+            }
+            replay_detector.push(SlidingWindowDetector::from_window(
+                &window,
+                MAX_SEQUENCE_NUMBER,
+            ));
+        }
         {
-            let mut lsn = self.local_sequence_number.lock().await;
+            let mut lsn = self.local_sequence_number.lock();
             while lsn.len() <= serialized.local_epoch as usize {
                 lsn.push(0);
             }
@@ -191,6 +292,10 @@ impl State {
         )?)));
 
         self.srtp_protection_profile = serialized.srtp_protection_profile.into();
+        self.ekt_cipher = serialized.ekt_cipher.into();
+        self.negotiated_protocol
+            .clone_from(&serialized.negotiated_protocol);
+        self.remote_record_size_limit = serialized.remote_record_size_limit;
 
         // Set remote certificate
         self.peer_certificates
@@ -200,8 +305,8 @@ impl State {
         Ok(())
     }
 
-    pub async fn init_cipher_suite(&mut self) -> Result<()> {
-        let mut cipher_suite = self.cipher_suite.lock().await;
+    pub fn init_cipher_suite(&mut self) -> Result<()> {
+        let mut cipher_suite = self.cipher_suite.lock();
         if let Some(cipher_suite) = &mut *cipher_suite {
             if cipher_suite.is_initialized() {
                 return Ok(());
@@ -230,45 +335,59 @@ impl State {
 
     // marshal_binary is a binary.BinaryMarshaler.marshal_binary implementation
     pub async fn marshal_binary(&self) -> Result<Vec<u8>> {
-        let serialized = self.serialize().await?;
+        self.marshal_state()
+    }
+
+    // unmarshal_binary is a binary.BinaryUnmarshaler.unmarshal_binary implementation.
+    // The unversioned output of older releases is accepted too, but it has no replay window,
+    // so records received before it was taken are not rejected after it is restored.
+    pub async fn unmarshal_binary(&mut self, data: &[u8]) -> Result<()> {
+        self.unmarshal_state(data)
+    }
+
+    pub(crate) fn marshal_state(&self) -> Result<Vec<u8>> {
+        let serialized = self.serialize()?;
 
         match bincode::serialize(&serialized) {
-            Ok(enc) => Ok(enc),
+            Ok(enc) => Ok([&[SERIALIZED_STATE_VERSION][..], &enc].concat()),
             Err(err) => Err(Error::Other(err.to_string())),
         }
     }
 
-    // unmarshal_binary is a binary.BinaryUnmarshaler.unmarshal_binary implementation
-    pub async fn unmarshal_binary(&mut self, data: &[u8]) -> Result<()> {
-        let serialized: SerializedState = match bincode::deserialize(data) {
-            Ok(dec) => dec,
-            Err(err) => return Err(Error::Other(err.to_string())),
+    pub(crate) fn unmarshal_state(&mut self, data: &[u8]) -> Result<()> {
+        let serialized: SerializedState = match data.first() {
+            Some(&SERIALIZED_STATE_VERSION) => match bincode::deserialize(&data[1..]) {
+                Ok(dec) => dec,
+                Err(err) => return Err(Error::Other(err.to_string())),
+            },
+            Some(0 | 1) => match bincode::deserialize::<SerializedStateV1>(data) {
+                Ok(dec) => dec.into(),
+                Err(err) => return Err(Error::Other(err.to_string())),
+            },
+            Some(&version) => return Err(Error::ErrUnsupportedStateVersion(version)),
+            None => return Err(Error::ErrUnsupportedStateVersion(0)),
         };
-        self.deserialize(&serialized).await?;
-        self.init_cipher_suite().await?;
+        self.deserialize(&serialized)?;
+        self.init_cipher_suite()?;
 
         Ok(())
     }
 }
 
-#[async_trait]
-impl KeyingMaterialExporter for State {
-    /// export_keying_material returns length bytes of exported key material in a new
-    /// slice as defined in RFC 5705.
-    /// This allows protocols to use DTLS for key establishment, but
-    /// then use some of the keying material for their own purposes
-    async fn export_keying_material(
+impl State {
+    /// export_keying_material_with_context implements RFC 5705 Section 4.
+    /// When a context is given its length is mixed into the seed, so that
+    /// an empty context and no context produce different keying material.
+    pub(crate) fn export_keying_material_with_context(
         &self,
         label: &str,
-        context: &[u8],
+        context: Option<&[u8]>,
         length: usize,
     ) -> std::result::Result<Vec<u8>, KeyingMaterialExporterError> {
         use KeyingMaterialExporterError::*;
 
         if self.local_epoch.load(Ordering::SeqCst) == 0 {
             return Err(HandshakeInProgress);
-        } else if !context.is_empty() {
-            return Err(ContextUnsupported);
         } else if INVALID_KEYING_LABELS.contains(&label) {
             return Err(ReservedExportKeyingMaterial);
         }
@@ -292,8 +411,12 @@ impl KeyingMaterialExporter for State {
             seed.extend_from_slice(&remote_random);
             seed.extend_from_slice(&local_random);
         }
+        if let Some(context) = context {
+            seed.extend_from_slice(&(context.len() as u16).to_be_bytes());
+            seed.extend_from_slice(context);
+        }
 
-        let cipher_suite = self.cipher_suite.lock().await;
+        let cipher_suite = self.cipher_suite.lock();
         if let Some(cipher_suite) = &*cipher_suite {
             match prf_p_hash(&self.master_secret, &seed, length, cipher_suite.hash_func()) {
                 Ok(v) => Ok(v),
@@ -304,3 +427,27 @@ impl KeyingMaterialExporter for State {
         }
     }
 }
+
+#[async_trait]
+impl KeyingMaterialExporter for State {
+    /// export_keying_material returns length bytes of exported key material in a new
+    /// slice as defined in RFC 5705.
+    /// This allows protocols to use DTLS for key establishment, but
+    /// then use some of the keying material for their own purposes
+    async fn export_keying_material(
+        &self,
+        label: &str,
+        context: &[u8],
+        length: usize,
+    ) -> std::result::Result<Vec<u8>, KeyingMaterialExporterError> {
+        use KeyingMaterialExporterError::*;
+
+        if self.local_epoch.load(Ordering::SeqCst) == 0 {
+            return Err(HandshakeInProgress);
+        } else if !context.is_empty() {
+            return Err(ContextUnsupported);
+        }
+
+        self.export_keying_material_with_context(label, None, length)
+    }
+}
@@ -0,0 +1,80 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use util::Marshal;
+
+use super::*;
+use crate::mock::mock_stream::MockStream;
+use crate::stream_info::RTPHeaderExtension;
+
+#[tokio::test]
+async fn test_abs_capture_time_receiver_interceptor() -> Result<()> {
+    let builder = Receiver::builder();
+    let capture_times = builder.capture_times();
+    let icpr = builder.build("")?;
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            rtp_header_extensions: vec![RTPHeaderExtension {
+                uri: ABS_CAPTURE_TIME_URI.to_owned(),
+                id: 2,
+            }],
+            ..Default::default()
+        },
+        Arc::clone(&icpr),
+    )
+    .await;
+
+    let extension = AbsCaptureTimeExtension {
+        estimated_capture_clock_offset: Some(-(1 << 32)),
+        ..AbsCaptureTimeExtension::new(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+    };
+    let mut header = rtp::header::Header {
+        version: 2,
+        ssrc: 1,
+        timestamp: 90000,
+        extension: true,
+        extension_profile: 0xBEDE,
+        ..Default::default()
+    };
+    header.set_extension(2, extension.marshal()?)?;
+    stream
+        .receive_rtp(rtp::packet::Packet {
+            header,
+            ..Default::default()
+        })
+        .await;
+    stream.read_rtp().await.expect("packet should be read")?;
+
+    assert_eq!(
+        capture_times.get(1),
+        Some(CaptureTime {
+            rtp_timestamp: 90000,
+            extension,
+        })
+    );
+    assert_eq!(capture_times.get(2), None);
+
+    // A packet without the extension leaves the capture time of its frame.
+    stream
+        .receive_rtp(rtp::packet::Packet {
+            header: rtp::header::Header {
+                ssrc: 1,
+                timestamp: 93000,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await;
+    stream.read_rtp().await.expect("packet should be read")?;
+    assert_eq!(capture_times.get(1).map(|c| c.rtp_timestamp), Some(90000));
+
+    stream.close().await?;
+    icpr.unbind_remote_stream(&StreamInfo {
+        ssrc: 1,
+        ..Default::default()
+    })
+    .await;
+    assert_eq!(capture_times.get(1), None);
+
+    Ok(())
+}
@@ -0,0 +1,183 @@
+#[cfg(test)]
+mod abs_capture_time_test;
+
+use std::sync::Arc;
+
+use rtp::extension::abs_capture_time_extension::AbsCaptureTimeExtension;
+use util::sync::Mutex;
+use util::Unmarshal;
+
+use crate::{Attributes, RTPReader, *};
+
+pub(crate) const ABS_CAPTURE_TIME_URI: &str =
+    "http://www.webrtc.org/experiments/rtp-hdrext/abs-capture-time";
+
+/// CaptureTime is the capture time of the frame of a received RTP packet.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct CaptureTime {
+    /// RTP timestamp of the frame.
+    pub rtp_timestamp: u32,
+    pub extension: AbsCaptureTimeExtension,
+}
+
+/// CaptureTimes keeps the latest capture time received on each remote stream, by SSRC.
+#[derive(Debug, Default)]
+pub struct CaptureTimes {
+    times: Mutex<HashMap<u32, CaptureTime>>,
+}
+
+impl CaptureTimes {
+    /// get returns the latest capture time received on the stream with ssrc.
+    pub fn get(&self, ssrc: u32) -> Option<CaptureTime> {
+        let times = self.times.lock();
+        times.get(&ssrc).copied()
+    }
+
+    fn insert(&self, ssrc: u32, capture_time: CaptureTime) {
+        let mut times = self.times.lock();
+        times.insert(ssrc, capture_time);
+    }
+
+    fn remove(&self, ssrc: u32) {
+        let mut times = self.times.lock();
+        times.remove(&ssrc);
+    }
+}
+
+/// ReceiverBuilder is a InterceptorBuilder for a Receiver Interceptor
+#[derive(Default)]
+pub struct ReceiverBuilder {
+    capture_times: Arc<CaptureTimes>,
+}
+
+impl ReceiverBuilder {
+    /// capture_times returns the capture times the interceptors built keep, which can be
+    /// used to synchronize streams of the same capturing system, e.g. audio and video.
+    pub fn capture_times(&self) -> Arc<CaptureTimes> {
+        Arc::clone(&self.capture_times)
+    }
+}
+
+impl InterceptorBuilder for ReceiverBuilder {
+    /// build constructs a new Receiver
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        Ok(Arc::new(Receiver {
+            capture_times: Arc::clone(&self.capture_times),
+        }))
+    }
+}
+
+/// Receiver parses the abs-capture-time header extension of incoming RTP packets, and keeps
+/// the latest capture time of each stream.
+pub struct Receiver {
+    capture_times: Arc<CaptureTimes>,
+}
+
+impl Receiver {
+    /// builder returns a new ReceiverBuilder.
+    pub fn builder() -> ReceiverBuilder {
+        ReceiverBuilder::default()
+    }
+}
+
+#[async_trait]
+impl Interceptor for Receiver {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        reader
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        writer
+    }
+
+    /// bind_local_stream lets you modify any outgoing RTP packets. It is called once for per LocalStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_local_stream(
+        &self,
+        _info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        writer
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, _info: &StreamInfo) {}
+
+    /// bind_remote_stream returns a reader that parses the rtp AbsCaptureTimeExtension
+    /// header of each incoming packet.
+    async fn bind_remote_stream(
+        &self,
+        info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        let hdr_ext_id = info
+            .rtp_header_extensions
+            .iter()
+            .find(|e| e.uri == ABS_CAPTURE_TIME_URI)
+            .map(|e| e.id as u8)
+            .unwrap_or_default();
+        if hdr_ext_id == 0 {
+            return reader;
+        }
+
+        Arc::new(ReceiverStream {
+            parent_rtp_reader: reader,
+            capture_times: Arc::clone(&self.capture_times),
+            hdr_ext_id,
+        })
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, info: &StreamInfo) {
+        self.capture_times.remove(info.ssrc);
+    }
+
+    /// close closes the Interceptor, cleaning up any data if necessary.
+    async fn close(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct ReceiverStream {
+    parent_rtp_reader: Arc<dyn RTPReader + Send + Sync>,
+    capture_times: Arc<CaptureTimes>,
+    hdr_ext_id: u8,
+}
+
+#[async_trait]
+impl RTPReader for ReceiverStream {
+    /// read a rtp packet
+    async fn read(
+        &self,
+        buf: &mut [u8],
+        attributes: &Attributes,
+    ) -> Result<(rtp::packet::Packet, Attributes)> {
+        let (pkt, attr) = self.parent_rtp_reader.read(buf, attributes).await?;
+
+        // The extension is usually sent with some packets only, e.g. the first of a frame.
+        if let Some(mut ext) = pkt.header.get_extension(self.hdr_ext_id) {
+            match AbsCaptureTimeExtension::unmarshal(&mut ext) {
+                Ok(extension) => self.capture_times.insert(
+                    pkt.header.ssrc,
+                    CaptureTime {
+                        rtp_timestamp: pkt.header.timestamp,
+                        extension,
+                    },
+                ),
+                Err(err) => log::warn!("failed to parse abs-capture-time: {}", err),
+            }
+        }
+
+        Ok((pkt, attr))
+    }
+}
@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+use util::Unmarshal;
+
+use super::*;
+use crate::mock::mock_stream::MockStream;
+use crate::stream_info::RTPHeaderExtension;
+
+#[tokio::test]
+async fn test_abs_send_time_sender_interceptor() -> Result<()> {
+    let icpr = Sender::builder().build("")?;
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            rtp_header_extensions: vec![RTPHeaderExtension {
+                uri: ABS_SEND_TIME_URI.to_owned(),
+                id: 3,
+            }],
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    let before = SystemTime::now();
+    stream
+        .write_rtp(&rtp::packet::Packet {
+            header: rtp::header::Header {
+                sequence_number: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await?;
+
+    let p = stream
+        .written_rtp()
+        .await
+        .expect("packet should be written");
+    let mut ext = p.header.get_extension(3).expect("extension should be set");
+    let send_time = AbsSendTimeExtension::unmarshal(&mut ext)?.estimate(SystemTime::now());
+    // abs-send-time has a resolution of about 4 microseconds.
+    let elapsed = send_time
+        .duration_since(before - Duration::from_millis(1))
+        .expect("send time should be after the packet was written");
+    assert!(
+        elapsed < Duration::from_secs(1),
+        "send time {elapsed:?} off"
+    );
+
+    stream.close().await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_abs_send_time_sender_interceptor_not_negotiated() -> Result<()> {
+    let icpr = Sender::builder().build("")?;
+    let stream = MockStream::new(&StreamInfo::default(), icpr).await;
+
+    stream.write_rtp(&rtp::packet::Packet::default()).await?;
+    let p = stream
+        .written_rtp()
+        .await
+        .expect("packet should be written");
+    assert!(!p.header.extension, "no extension should be set");
+
+    stream.close().await?;
+
+    Ok(())
+}
@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod abs_send_time_test;
+
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use rtp::extension::abs_send_time_extension::AbsSendTimeExtension;
+use util::Marshal;
+
+use crate::{Attributes, RTPWriter, *};
+
+pub(crate) const ABS_SEND_TIME_URI: &str =
+    "http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time";
+
+/// SenderBuilder is a InterceptorBuilder for a Sender Interceptor
+#[derive(Default)]
+pub struct SenderBuilder;
+
+impl InterceptorBuilder for SenderBuilder {
+    /// build constructs a new Sender
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        Ok(Arc::new(Sender))
+    }
+}
+
+/// Sender adds the time it is sent at as abs-send-time header extension to each RTP packet,
+/// which the remote peer estimates the bandwidth with.
+pub struct Sender;
+
+impl Sender {
+    /// builder returns a new SenderBuilder.
+    pub fn builder() -> SenderBuilder {
+        SenderBuilder
+    }
+}
+
+#[async_trait]
+impl Interceptor for Sender {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        reader
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        writer
+    }
+
+    /// bind_local_stream returns a writer that adds a rtp AbsSendTimeExtension
+    /// header with the current time to each outgoing packet.
+    async fn bind_local_stream(
+        &self,
+        info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        let hdr_ext_id = info
+            .rtp_header_extensions
+            .iter()
+            .find(|e| e.uri == ABS_SEND_TIME_URI)
+            .map(|e| e.id as u8)
+            .unwrap_or_default();
+        if hdr_ext_id == 0 {
+            // Don't add header extension if ID is 0, because 0 is an invalid extension ID
+            return writer;
+        }
+
+        Arc::new(SenderStream {
+            next_rtp_writer: writer,
+            hdr_ext_id,
+        })
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, _info: &StreamInfo) {}
+
+    /// bind_remote_stream lets you modify any incoming RTP packets. It is called once for per RemoteStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_remote_stream(
+        &self,
+        _info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        reader
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, _info: &StreamInfo) {}
+
+    /// close closes the Interceptor, cleaning up any data if necessary.
+    async fn close(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct SenderStream {
+    next_rtp_writer: Arc<dyn RTPWriter + Send + Sync>,
+    hdr_ext_id: u8,
+}
+
+/// RTPWriter is used by Interceptor.bind_local_stream.
+#[async_trait]
+impl RTPWriter for SenderStream {
+    /// write a rtp packet
+    async fn write(&self, pkt: &rtp::packet::Packet, a: &Attributes) -> Result<usize> {
+        let send_time = AbsSendTimeExtension::new(SystemTime::now());
+        let payload = send_time.marshal()?;
+
+        let mut pkt = pkt.clone();
+        pkt.header.set_extension(self.hdr_ext_id, payload)?;
+
+        self.next_rtp_writer.write(&pkt, a).await
+    }
+}
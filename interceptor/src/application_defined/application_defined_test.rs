@@ -0,0 +1,53 @@
+use bytes::Bytes;
+use tokio::sync::mpsc;
+
+use super::*;
+use crate::mock::mock_stream::MockStream;
+
+#[tokio::test]
+async fn test_application_defined_interceptor() -> Result<()> {
+    let icpr = Arc::new(ApplicationDefinedInterceptor::new());
+    let (tx, mut rx) = mpsc::channel(4);
+    icpr.on_application_defined(
+        *b"TEST",
+        Box::new(move |app: ApplicationDefined| {
+            let tx = tx.clone();
+            Box::pin(async move {
+                let _ = tx.send(app).await;
+            })
+        }),
+    );
+
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            ..Default::default()
+        },
+        Arc::clone(&icpr) as Arc<dyn Interceptor + Send + Sync>,
+    )
+    .await;
+
+    let app = ApplicationDefined::new(2, "TEST", 3, Bytes::from_static(b"ping")).unwrap();
+    let other = ApplicationDefined::new(2, "XYZW", 3, Bytes::new()).unwrap();
+    stream
+        .receive_rtcp(vec![Box::new(other.clone()), Box::new(app.clone())])
+        .await;
+
+    let pkts = stream.read_rtcp().await.unwrap()?;
+    assert_eq!(pkts.len(), 2, "APP packets are still read");
+    assert!(pkts[1].equal(&app));
+    assert_eq!(rx.recv().await, Some(app));
+    assert!(rx.try_recv().is_err(), "only TEST packets are handled");
+
+    icpr.remove_handler(*b"TEST");
+    stream
+        .receive_rtcp(vec![Box::new(
+            ApplicationDefined::new(2, "TEST", 0, Bytes::new()).unwrap(),
+        )])
+        .await;
+    stream.read_rtcp().await.unwrap()?;
+    assert!(rx.try_recv().is_err());
+
+    stream.close().await?;
+    Ok(())
+}
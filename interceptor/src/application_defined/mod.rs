@@ -0,0 +1,130 @@
+#[cfg(test)]
+mod application_defined_test;
+
+use rtcp::application_defined::ApplicationDefined;
+
+use crate::error::Result;
+use crate::*;
+
+/// OnApplicationDefinedFn handles the APP packets of an application received.
+pub type OnApplicationDefinedFn = Box<
+    dyn (FnMut(ApplicationDefined) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>)
+        + Send
+        + Sync,
+>;
+
+type Handlers = HashMap<[u8; 4], Arc<tokio::sync::Mutex<OnApplicationDefinedFn>>>;
+
+/// ApplicationDefinedInterceptor hands the APP packets received to the handler registered
+/// for their name, as the RTCP packets are read. The packets are still returned to the
+/// reader.
+#[derive(Default)]
+pub struct ApplicationDefinedInterceptor {
+    handlers: Arc<util::sync::Mutex<Handlers>>,
+}
+
+impl ApplicationDefinedInterceptor {
+    /// new returns an ApplicationDefinedInterceptor with no handler.
+    pub fn new() -> Self {
+        ApplicationDefinedInterceptor::default()
+    }
+
+    /// on_application_defined sets the handler of the APP packets named name, replacing any
+    /// previous one.
+    pub fn on_application_defined(&self, name: [u8; 4], f: OnApplicationDefinedFn) {
+        let mut handlers = self.handlers.lock();
+        handlers.insert(name, Arc::new(tokio::sync::Mutex::new(f)));
+    }
+
+    /// remove_handler removes the handler of the APP packets named name.
+    pub fn remove_handler(&self, name: [u8; 4]) {
+        let mut handlers = self.handlers.lock();
+        handlers.remove(&name);
+    }
+}
+
+struct ApplicationDefinedReader {
+    parent_rtcp_reader: Arc<dyn RTCPReader + Send + Sync>,
+    handlers: Arc<util::sync::Mutex<Handlers>>,
+}
+
+#[async_trait]
+impl RTCPReader for ApplicationDefinedReader {
+    async fn read(
+        &self,
+        buf: &mut [u8],
+        a: &Attributes,
+    ) -> Result<(Vec<Box<dyn rtcp::packet::Packet + Send + Sync>>, Attributes)> {
+        let (pkts, attr) = self.parent_rtcp_reader.read(buf, a).await?;
+
+        for p in &pkts {
+            if let Some(app) = p.as_any().downcast_ref::<ApplicationDefined>() {
+                let handler = {
+                    let handlers = self.handlers.lock();
+                    handlers.get(&app.name).cloned()
+                };
+                if let Some(handler) = handler {
+                    let mut f = handler.lock().await;
+                    f(app.clone()).await;
+                }
+            }
+        }
+
+        Ok((pkts, attr))
+    }
+}
+
+#[async_trait]
+impl Interceptor for ApplicationDefinedInterceptor {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        Arc::new(ApplicationDefinedReader {
+            parent_rtcp_reader: reader,
+            handlers: Arc::clone(&self.handlers),
+        })
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        writer
+    }
+
+    /// bind_local_stream lets you modify any outgoing RTP packets. It is called once for per LocalStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_local_stream(
+        &self,
+        _info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        writer
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, _info: &StreamInfo) {}
+
+    /// bind_remote_stream lets you modify any incoming RTP packets. It is called once for per RemoteStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_remote_stream(
+        &self,
+        _info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        reader
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, _info: &StreamInfo) {}
+
+    /// close closes the Interceptor, cleaning up any data if necessary.
+    async fn close(&self) -> Result<()> {
+        Ok(())
+    }
+}
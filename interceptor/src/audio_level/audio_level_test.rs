@@ -0,0 +1,51 @@
+use super::*;
+
+#[test]
+fn test_level() {
+    assert_eq!(level(&[]), SILENCE_LEVEL);
+    assert_eq!(level(&[0; 160]), SILENCE_LEVEL);
+    // full scale
+    assert_eq!(level(&[i16::MAX, -i16::MAX]), 0);
+    // -20 dBov, the root mean square of a square wave being its amplitude
+    assert_eq!(level(&[3277, -3277, 3277, -3277]), 20);
+    // a sine wave of full scale is of -3 dBov
+    let sine: Vec<i16> = (0..160)
+        .map(|i| ((i as f64 * std::f64::consts::PI / 40.0).sin() * i16::MAX as f64) as i16)
+        .collect();
+    assert_eq!(level(&sine), 3);
+    assert_eq!(level(&[1, -1]), 90);
+}
+
+#[test]
+fn test_audio_samples() {
+    let samples = AudioSamples::default();
+    assert_eq!(samples.take(1), None);
+
+    samples.push(1, &[3277; 80]);
+    samples.push(1, &[-3277; 80]);
+    samples.push(2, &[0; 160]);
+    assert_eq!(samples.take(1), Some(20));
+    assert_eq!(samples.take(1), None, "taken");
+    assert_eq!(samples.take(2), Some(SILENCE_LEVEL));
+}
+
+#[test]
+fn test_g711_decoding() {
+    assert_eq!(ulaw_to_linear(0xFF), 0);
+    assert_eq!(ulaw_to_linear(0x7F), 0);
+    assert_eq!(ulaw_to_linear(0x80), 32124);
+    assert_eq!(ulaw_to_linear(0x00), -32124);
+    assert_eq!(alaw_to_linear(0xD5), 8);
+    assert_eq!(alaw_to_linear(0x55), -8);
+    assert_eq!(alaw_to_linear(0xAA), 32256);
+    assert_eq!(alaw_to_linear(0x2A), -32256);
+
+    // the silence of each codec
+    assert_eq!(
+        payload_level("audio/PCMU", &[0xFF; 160]),
+        Some(SILENCE_LEVEL)
+    );
+    assert_eq!(payload_level("audio/PCMA", &[0xD5; 160]), Some(72));
+    assert_eq!(payload_level("audio/PCMU", &[0x80, 0x00]), Some(0));
+    assert_eq!(payload_level("audio/opus", &[0xFF; 160]), None);
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod audio_level_test;
+pub mod receiver;
+pub mod sender;
+
+use std::collections::HashMap;
+
+pub(crate) const AUDIO_LEVEL_URI: &str = "urn:ietf:params:rtp-hdrext:ssrc-audio-level";
+
+/// Level of the digital silence, and of the quietest sounds, in -dBov.
+pub const SILENCE_LEVEL: u8 = 127;
+
+/// Level from which a sound is taken for voice by default, in -dBov: the sounds above
+/// -40 dBov.
+const DEFAULT_VOICE_LEVEL: u8 = 40;
+
+/// SampleEnergy is the energy of the samples of a stream, since its level was last taken.
+#[derive(Debug, Default, Copy, Clone)]
+struct SampleEnergy {
+    sum_of_squares: f64,
+    samples: usize,
+}
+
+impl SampleEnergy {
+    fn push(&mut self, samples: &[i16]) {
+        for &sample in samples {
+            self.sum_of_squares += sample as f64 * sample as f64;
+        }
+        self.samples += samples.len();
+    }
+
+    fn level(&self) -> u8 {
+        if self.samples == 0 {
+            return SILENCE_LEVEL;
+        }
+        let rms = (self.sum_of_squares / self.samples as f64).sqrt();
+        let dbov = 20.0 * (rms / i16::MAX as f64).log10();
+        (-dbov).round().clamp(0.0, SILENCE_LEVEL as f64) as u8
+    }
+}
+
+/// level returns the level of the 16 bit linear samples, as the ssrc-audio-level header
+/// extension carries it: the root mean square of the samples, in -dBov, from 0 for the
+/// loudest to 127 for the silence.
+///
+/// ## Specifications
+///
+/// * [RFC 6464 §3]
+///
+/// [RFC 6464 §3]: https://tools.ietf.org/html/rfc6464#section-3
+pub fn level(samples: &[i16]) -> u8 {
+    let mut energy = SampleEnergy::default();
+    energy.push(samples);
+    energy.level()
+}
+
+/// AudioSamples takes the raw samples of the local audio streams, from which the levels of
+/// their packets are computed. The samples of a frame should be pushed before the frame is
+/// written to the track.
+#[derive(Debug, Default)]
+pub struct AudioSamples {
+    energies: util::sync::Mutex<HashMap<u32, SampleEnergy>>,
+}
+
+impl AudioSamples {
+    /// push adds the 16 bit linear samples of the local stream ssrc, for the next packet
+    /// written to it.
+    pub fn push(&self, ssrc: u32, samples: &[i16]) {
+        let mut energies = self.energies.lock();
+        energies.entry(ssrc).or_default().push(samples);
+    }
+
+    /// take returns the level of the samples pushed for ssrc since it was last taken, if any.
+    fn take(&self, ssrc: u32) -> Option<u8> {
+        let mut energies = self.energies.lock();
+        energies.remove(&ssrc).map(|energy| energy.level())
+    }
+
+    fn remove(&self, ssrc: u32) {
+        let mut energies = self.energies.lock();
+        energies.remove(&ssrc);
+    }
+}
+
+/// payload_level returns the level of the payload of a packet of mime_type, for the codecs
+/// the samples of which are in the payloads: PCMU and PCMA.
+fn payload_level(mime_type: &str, payload: &[u8]) -> Option<u8> {
+    let decode = match mime_type.to_lowercase().as_str() {
+        "audio/pcmu" => ulaw_to_linear,
+        "audio/pcma" => alaw_to_linear,
+        _ => return None,
+    };
+    let samples: Vec<i16> = payload.iter().map(|&b| decode(b)).collect();
+    Some(level(&samples))
+}
+
+/// ulaw_to_linear decodes a G.711 μ-law sample.
+fn ulaw_to_linear(u: u8) -> i16 {
+    let u = !u;
+    let exponent = (u >> 4) & 0x07;
+    let mantissa = (u & 0x0F) as i16;
+    let magnitude = (((mantissa << 3) + 0x84) << exponent) - 0x84;
+    if u & 0x80 != 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// alaw_to_linear decodes a G.711 A-law sample.
+fn alaw_to_linear(a: u8) -> i16 {
+    let a = a ^ 0x55;
+    let exponent = (a >> 4) & 0x07;
+    let mantissa = (a & 0x0F) as i16;
+    let magnitude = if exponent == 0 {
+        (mantissa << 4) + 8
+    } else {
+        ((mantissa << 4) + 0x108) << (exponent - 1)
+    };
+    if a & 0x80 != 0 {
+        magnitude
+    } else {
+        -magnitude
+    }
+}
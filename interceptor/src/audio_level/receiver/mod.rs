@@ -0,0 +1,297 @@
+#[cfg(test)]
+mod receiver_test;
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use rtp::extension::audio_level_extension::AudioLevelExtension;
+use tokio::time::Instant;
+use util::Unmarshal;
+
+use crate::audio_level::{AUDIO_LEVEL_URI, DEFAULT_VOICE_LEVEL, SILENCE_LEVEL};
+use crate::error::Result;
+use crate::stream_info::StreamInfo;
+use crate::{
+    Attributes, Interceptor, InterceptorBuilder, RTCPReader, RTCPWriter, RTPReader, RTPWriter,
+};
+
+/// How long a remote stream is still speaking after its last voice packet by default.
+const DEFAULT_SPEAKING_HOLD: Duration = Duration::from_millis(500);
+
+/// OnSpeakingFn is called with the SSRC of a remote stream when it starts speaking, with
+/// true, and when it stops, with false.
+pub type OnSpeakingFn = Arc<
+    dyn (Fn(u32, bool) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>)
+        + Send
+        + Sync
+        + 'static,
+>;
+
+/// AudioLevel is the level of a remote audio stream.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AudioLevel {
+    /// Level of the last packet, in -dBov, from 0 for the loudest to 127 for the silence.
+    pub level: u8,
+    /// Whether the last packet is flagged as voice.
+    pub voice: bool,
+    /// Whether the stream is speaking: it had voice packets for less than the speaking hold.
+    pub speaking: bool,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct StreamLevel {
+    level: AudioLevel,
+    /// When the last voice packet was read.
+    last_voice: Option<Instant>,
+}
+
+/// AudioLevels keeps the level of each remote audio stream, by SSRC.
+#[derive(Debug, Default)]
+pub struct AudioLevels {
+    levels: util::sync::Mutex<HashMap<u32, StreamLevel>>,
+}
+
+impl AudioLevels {
+    /// get returns the level of the remote stream ssrc, once a packet with the
+    /// ssrc-audio-level header extension is read.
+    pub fn get(&self, ssrc: u32) -> Option<AudioLevel> {
+        let levels = self.levels.lock();
+        levels.get(&ssrc).map(|s| s.level)
+    }
+
+    /// speaking returns the SSRCs of the remote streams speaking.
+    pub fn speaking(&self) -> Vec<u32> {
+        let levels = self.levels.lock();
+        let mut speaking: Vec<u32> = levels
+            .iter()
+            .filter(|(_, s)| s.level.speaking)
+            .map(|(ssrc, _)| *ssrc)
+            .collect();
+        speaking.sort_unstable();
+        speaking
+    }
+
+    /// update records the extension of a packet of ssrc read at now, returning whether the
+    /// stream started or stopped speaking.
+    fn update(
+        &self,
+        ssrc: u32,
+        extension: AudioLevelExtension,
+        voice_level: u8,
+        speaking_hold: Duration,
+        now: Instant,
+    ) -> Option<bool> {
+        let mut levels = self.levels.lock();
+        let stream = levels.entry(ssrc).or_insert(StreamLevel {
+            level: AudioLevel {
+                level: SILENCE_LEVEL,
+                voice: false,
+                speaking: false,
+            },
+            last_voice: None,
+        });
+        stream.level.level = extension.level;
+        stream.level.voice = extension.voice;
+        if extension.voice || extension.level <= voice_level {
+            stream.last_voice = Some(now);
+        }
+
+        let speaking = stream
+            .last_voice
+            .map(|last_voice| now.duration_since(last_voice) < speaking_hold)
+            .unwrap_or(false);
+        if speaking == stream.level.speaking {
+            return None;
+        }
+        stream.level.speaking = speaking;
+        Some(speaking)
+    }
+
+    fn remove(&self, ssrc: u32) {
+        let mut levels = self.levels.lock();
+        levels.remove(&ssrc);
+    }
+}
+
+/// ReceiverBuilder is a InterceptorBuilder for a Receiver Interceptor
+#[derive(Default)]
+pub struct ReceiverBuilder {
+    voice_level: Option<u8>,
+    speaking_hold: Option<Duration>,
+    on_speaking: Option<OnSpeakingFn>,
+    levels: Arc<AudioLevels>,
+}
+
+impl ReceiverBuilder {
+    /// with_voice_level sets the level, in -dBov, from which the packets are taken for voice
+    /// even if they aren't flagged so: those of the level or louder.
+    pub fn with_voice_level(mut self, voice_level: u8) -> ReceiverBuilder {
+        self.voice_level = Some(voice_level);
+        self
+    }
+
+    /// with_speaking_hold sets how long a remote stream is still speaking after its last
+    /// voice packet, so that it doesn't stop between the words.
+    pub fn with_speaking_hold(mut self, speaking_hold: Duration) -> ReceiverBuilder {
+        self.speaking_hold = Some(speaking_hold);
+        self
+    }
+
+    /// with_on_speaking sets the handler called when a remote stream starts or stops speaking.
+    pub fn with_on_speaking(mut self, f: OnSpeakingFn) -> ReceiverBuilder {
+        self.on_speaking = Some(f);
+        self
+    }
+
+    /// levels returns the levels the interceptors built keep, e.g. to show the active
+    /// speakers.
+    pub fn levels(&self) -> Arc<AudioLevels> {
+        Arc::clone(&self.levels)
+    }
+}
+
+impl InterceptorBuilder for ReceiverBuilder {
+    /// build constructs a new Receiver
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        Ok(Arc::new(Receiver {
+            voice_level: self.voice_level.unwrap_or(DEFAULT_VOICE_LEVEL),
+            speaking_hold: self.speaking_hold.unwrap_or(DEFAULT_SPEAKING_HOLD),
+            on_speaking: self.on_speaking.clone(),
+            levels: Arc::clone(&self.levels),
+        }))
+    }
+}
+
+/// Receiver parses the ssrc-audio-level header extension of incoming RTP packets, and keeps
+/// the level of each stream, and whether it's speaking. A stream starts speaking with a
+/// packet flagged as voice, or of the voice level, and stops at the first packet read once
+/// it had none for the speaking hold.
+pub struct Receiver {
+    voice_level: u8,
+    speaking_hold: Duration,
+    on_speaking: Option<OnSpeakingFn>,
+    levels: Arc<AudioLevels>,
+}
+
+impl Receiver {
+    /// builder returns a new ReceiverBuilder.
+    pub fn builder() -> ReceiverBuilder {
+        ReceiverBuilder::default()
+    }
+}
+
+#[async_trait]
+impl Interceptor for Receiver {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        reader
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        writer
+    }
+
+    /// bind_local_stream lets you modify any outgoing RTP packets. It is called once for per LocalStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_local_stream(
+        &self,
+        _info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        writer
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, _info: &StreamInfo) {}
+
+    /// bind_remote_stream returns a reader that parses the rtp AudioLevelExtension header of
+    /// each incoming packet.
+    async fn bind_remote_stream(
+        &self,
+        info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        let hdr_ext_id = info
+            .rtp_header_extensions
+            .iter()
+            .find(|e| e.uri == AUDIO_LEVEL_URI)
+            .map(|e| e.id as u8)
+            .unwrap_or_default();
+        if hdr_ext_id == 0 {
+            return reader;
+        }
+
+        Arc::new(ReceiverStream {
+            parent_rtp_reader: reader,
+            hdr_ext_id,
+            voice_level: self.voice_level,
+            speaking_hold: self.speaking_hold,
+            on_speaking: self.on_speaking.clone(),
+            levels: Arc::clone(&self.levels),
+        })
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, info: &StreamInfo) {
+        self.levels.remove(info.ssrc);
+    }
+
+    /// close closes the Interceptor, cleaning up any data if necessary.
+    async fn close(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct ReceiverStream {
+    parent_rtp_reader: Arc<dyn RTPReader + Send + Sync>,
+    hdr_ext_id: u8,
+    voice_level: u8,
+    speaking_hold: Duration,
+    on_speaking: Option<OnSpeakingFn>,
+    levels: Arc<AudioLevels>,
+}
+
+#[async_trait]
+impl RTPReader for ReceiverStream {
+    /// read a rtp packet
+    async fn read(
+        &self,
+        buf: &mut [u8],
+        attributes: &Attributes,
+    ) -> Result<(rtp::packet::Packet, Attributes)> {
+        let (pkt, attr) = self.parent_rtp_reader.read(buf, attributes).await?;
+
+        if let Some(mut ext) = pkt.header.get_extension(self.hdr_ext_id) {
+            match AudioLevelExtension::unmarshal(&mut ext) {
+                Ok(extension) => {
+                    let changed = self.levels.update(
+                        pkt.header.ssrc,
+                        extension,
+                        self.voice_level,
+                        self.speaking_hold,
+                        Instant::now(),
+                    );
+                    if let (Some(speaking), Some(f)) = (changed, &self.on_speaking) {
+                        f(pkt.header.ssrc, speaking).await;
+                    }
+                }
+                Err(err) => log::warn!("failed to parse ssrc-audio-level: {}", err),
+            }
+        }
+
+        Ok((pkt, attr))
+    }
+}
@@ -0,0 +1,83 @@
+use tokio::sync::mpsc;
+use util::Marshal;
+
+use super::*;
+use crate::mock::mock_stream::MockStream;
+use crate::stream_info::RTPHeaderExtension;
+use crate::test::timeout_or_fail;
+
+async fn receive_level(stream: &MockStream, level: u8, voice: bool) -> Result<()> {
+    let mut pkt = rtp::packet::Packet {
+        header: rtp::header::Header {
+            ssrc: 1,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    pkt.header
+        .set_extension(5, AudioLevelExtension { level, voice }.marshal()?)?;
+    stream.receive_rtp(pkt).await;
+    timeout_or_fail(Duration::from_millis(10), stream.read_rtp())
+        .await
+        .expect("A read packet")
+        .expect("Not an error");
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_audio_level_receiver_interceptor() -> Result<()> {
+    let (speaking_tx, mut speaking_rx) = mpsc::unbounded_channel();
+    let builder = Receiver::builder()
+        .with_speaking_hold(Duration::from_millis(300))
+        .with_on_speaking(Arc::new(move |ssrc, speaking| {
+            let _ = speaking_tx.send((ssrc, speaking));
+            Box::pin(async {})
+        }));
+    let levels = builder.levels();
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            mime_type: "audio/opus".to_owned(),
+            rtp_header_extensions: vec![RTPHeaderExtension {
+                uri: AUDIO_LEVEL_URI.to_owned(),
+                id: 5,
+            }],
+            ..Default::default()
+        },
+        builder.build("")?,
+    )
+    .await;
+
+    receive_level(&stream, 90, false).await?;
+    assert_eq!(
+        levels.get(1),
+        Some(AudioLevel {
+            level: 90,
+            voice: false,
+            speaking: false
+        })
+    );
+    assert!(speaking_rx.try_recv().is_err());
+
+    // flagged as voice, or loud enough
+    receive_level(&stream, 60, true).await?;
+    assert_eq!(speaking_rx.try_recv(), Ok((1, true)));
+    assert_eq!(levels.speaking(), vec![1]);
+    tokio::time::advance(Duration::from_millis(200)).await;
+    receive_level(&stream, 90, false).await?;
+    tokio::time::advance(Duration::from_millis(200)).await;
+    receive_level(&stream, 30, false).await?;
+    assert!(speaking_rx.try_recv().is_err(), "still speaking");
+
+    // no voice for the speaking hold
+    tokio::time::advance(Duration::from_millis(200)).await;
+    receive_level(&stream, 90, false).await?;
+    assert!(speaking_rx.try_recv().is_err(), "still speaking");
+    tokio::time::advance(Duration::from_millis(200)).await;
+    receive_level(&stream, 90, false).await?;
+    assert_eq!(speaking_rx.try_recv(), Ok((1, false)));
+    assert!(levels.speaking().is_empty());
+
+    stream.close().await?;
+    Ok(())
+}
@@ -0,0 +1,171 @@
+#[cfg(test)]
+mod sender_test;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use rtp::extension::audio_level_extension::AudioLevelExtension;
+use util::Marshal;
+
+use crate::audio_level::{payload_level, AudioSamples, AUDIO_LEVEL_URI, DEFAULT_VOICE_LEVEL};
+use crate::error::Result;
+use crate::stream_info::StreamInfo;
+use crate::{
+    Attributes, Interceptor, InterceptorBuilder, RTCPReader, RTCPWriter, RTPReader, RTPWriter,
+};
+
+/// SenderBuilder is a InterceptorBuilder for a Sender Interceptor
+#[derive(Default)]
+pub struct SenderBuilder {
+    voice_level: Option<u8>,
+    samples: Arc<AudioSamples>,
+}
+
+impl SenderBuilder {
+    /// with_voice_level sets the level, in -dBov, from which the packets are flagged as voice:
+    /// those of the level or louder.
+    pub fn with_voice_level(mut self, voice_level: u8) -> SenderBuilder {
+        self.voice_level = Some(voice_level);
+        self
+    }
+
+    /// samples returns the samples of the local streams of the interceptors built, which the
+    /// tracks push the raw samples of their frames to.
+    pub fn samples(&self) -> Arc<AudioSamples> {
+        Arc::clone(&self.samples)
+    }
+}
+
+impl InterceptorBuilder for SenderBuilder {
+    /// build constructs a new Sender
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        Ok(Arc::new(Sender {
+            voice_level: self.voice_level.unwrap_or(DEFAULT_VOICE_LEVEL),
+            samples: Arc::clone(&self.samples),
+        }))
+    }
+}
+
+/// Sender adds the ssrc-audio-level header extension to the packets of the local audio
+/// streams, with the level of the samples pushed to its [`AudioSamples`] since the last
+/// packet of the stream, or of the payload for the PCMU and PCMA streams. The packets are
+/// flagged as voice from the voice level, and left unchanged when they have the extension
+/// already, or when there is no level for them.
+pub struct Sender {
+    voice_level: u8,
+    samples: Arc<AudioSamples>,
+}
+
+impl Sender {
+    /// builder returns a new SenderBuilder.
+    pub fn builder() -> SenderBuilder {
+        SenderBuilder::default()
+    }
+}
+
+#[async_trait]
+impl Interceptor for Sender {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        reader
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        writer
+    }
+
+    /// bind_local_stream returns a writer that adds a rtp AudioLevelExtension header to the
+    /// outgoing packets of audio streams.
+    async fn bind_local_stream(
+        &self,
+        info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        if !info.mime_type.to_lowercase().starts_with("audio/") {
+            return writer;
+        }
+        let hdr_ext_id = info
+            .rtp_header_extensions
+            .iter()
+            .find(|e| e.uri == AUDIO_LEVEL_URI)
+            .map(|e| e.id as u8)
+            .unwrap_or_default();
+        if hdr_ext_id == 0 {
+            return writer;
+        }
+
+        Arc::new(SenderStream {
+            next_rtp_writer: writer,
+            mime_type: info.mime_type.clone(),
+            hdr_ext_id,
+            voice_level: self.voice_level,
+            samples: Arc::clone(&self.samples),
+        })
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, info: &StreamInfo) {
+        self.samples.remove(info.ssrc);
+    }
+
+    /// bind_remote_stream lets you modify any incoming RTP packets. It is called once for per RemoteStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_remote_stream(
+        &self,
+        _info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        reader
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, _info: &StreamInfo) {}
+
+    /// close closes the Interceptor, cleaning up any data if necessary.
+    async fn close(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct SenderStream {
+    next_rtp_writer: Arc<dyn RTPWriter + Send + Sync>,
+    mime_type: String,
+    hdr_ext_id: u8,
+    voice_level: u8,
+    samples: Arc<AudioSamples>,
+}
+
+/// RTPWriter is used by Interceptor.bind_local_stream.
+#[async_trait]
+impl RTPWriter for SenderStream {
+    /// write a rtp packet
+    async fn write(&self, pkt: &rtp::packet::Packet, a: &Attributes) -> Result<usize> {
+        let level = self
+            .samples
+            .take(pkt.header.ssrc)
+            .or_else(|| payload_level(&self.mime_type, &pkt.payload));
+        let level = match level {
+            Some(level) if pkt.header.get_extension(self.hdr_ext_id).is_none() => level,
+            _ => return self.next_rtp_writer.write(pkt, a).await,
+        };
+
+        let extension = AudioLevelExtension {
+            level,
+            voice: level <= self.voice_level,
+        };
+        let mut pkt = pkt.clone();
+        pkt.header
+            .set_extension(self.hdr_ext_id, extension.marshal()?)?;
+
+        self.next_rtp_writer.write(&pkt, a).await
+    }
+}
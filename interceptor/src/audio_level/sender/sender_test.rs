@@ -0,0 +1,112 @@
+use bytes::Bytes;
+use util::Unmarshal;
+
+use super::*;
+use crate::audio_level::SILENCE_LEVEL;
+use crate::mock::mock_stream::MockStream;
+use crate::stream_info::RTPHeaderExtension;
+
+fn stream_info(mime_type: &str) -> StreamInfo {
+    StreamInfo {
+        ssrc: 1,
+        mime_type: mime_type.to_owned(),
+        rtp_header_extensions: vec![RTPHeaderExtension {
+            uri: AUDIO_LEVEL_URI.to_owned(),
+            id: 5,
+        }],
+        ..Default::default()
+    }
+}
+
+async fn written_level(stream: &MockStream, payload: Bytes) -> Result<Option<AudioLevelExtension>> {
+    stream
+        .write_rtp(&rtp::packet::Packet {
+            header: rtp::header::Header {
+                ssrc: 1,
+                ..Default::default()
+            },
+            payload,
+            ..Default::default()
+        })
+        .await?;
+    let pkt = stream
+        .written_rtp()
+        .await
+        .expect("packet should be written");
+    Ok(match pkt.header.get_extension(5) {
+        Some(mut ext) => Some(AudioLevelExtension::unmarshal(&mut ext)?),
+        None => None,
+    })
+}
+
+#[tokio::test]
+async fn test_audio_level_sender_interceptor() -> Result<()> {
+    let builder = Sender::builder().with_voice_level(30);
+    let samples = builder.samples();
+    let stream = MockStream::new(&stream_info("audio/opus"), builder.build("")?).await;
+
+    // no samples pushed
+    assert_eq!(
+        written_level(&stream, Bytes::from_static(&[0xFC])).await?,
+        None
+    );
+
+    samples.push(1, &[3277, -3277, 3277, -3277]);
+    assert_eq!(
+        written_level(&stream, Bytes::from_static(&[0xFC])).await?,
+        Some(AudioLevelExtension {
+            level: 20,
+            voice: true
+        })
+    );
+
+    samples.push(1, &[0; 160]);
+    assert_eq!(
+        written_level(&stream, Bytes::from_static(&[0xFC])).await?,
+        Some(AudioLevelExtension {
+            level: SILENCE_LEVEL,
+            voice: false
+        })
+    );
+
+    stream.close().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_audio_level_sender_interceptor_payload() -> Result<()> {
+    let stream = MockStream::new(&stream_info("audio/PCMU"), Sender::builder().build("")?).await;
+    assert_eq!(
+        written_level(&stream, Bytes::from(vec![0xFF; 160])).await?,
+        Some(AudioLevelExtension {
+            level: SILENCE_LEVEL,
+            voice: false
+        })
+    );
+    assert_eq!(
+        written_level(&stream, Bytes::from_static(&[0x80, 0x00])).await?,
+        Some(AudioLevelExtension {
+            level: 0,
+            voice: true
+        })
+    );
+    stream.close().await?;
+
+    // not negotiated, or not audio
+    for info in [
+        StreamInfo {
+            ssrc: 1,
+            mime_type: "audio/PCMU".to_owned(),
+            ..Default::default()
+        },
+        stream_info("video/VP8"),
+    ] {
+        let stream = MockStream::new(&info, Sender::builder().build("")?).await;
+        assert_eq!(
+            written_level(&stream, Bytes::from_static(&[0x80, 0x00])).await?,
+            None
+        );
+        stream.close().await?;
+    }
+    Ok(())
+}
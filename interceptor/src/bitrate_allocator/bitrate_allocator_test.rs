@@ -0,0 +1,118 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tokio::sync::mpsc;
+
+use super::*;
+
+fn layer(min_bitrate: u64, max_bitrate: u64) -> LayerConstraints {
+    LayerConstraints {
+        min_bitrate,
+        max_bitrate,
+    }
+}
+
+fn sender(priority: f64, layers: Vec<LayerConstraints>) -> SenderConstraints {
+    SenderConstraints { priority, layers }
+}
+
+#[test]
+fn test_allocate_min_and_max() {
+    let senders = vec![
+        sender(1.0, vec![layer(100, 500)]),
+        sender(2.0, vec![layer(300, 1_000)]),
+    ];
+
+    // every sender at its max
+    assert_eq!(allocate(10_000, &senders), vec![vec![500], vec![1_000]]);
+    // the sender of lower priority is paused, without bitrate for both mins
+    assert_eq!(allocate(350, &senders), vec![vec![0], vec![350]]);
+    assert_eq!(allocate(50, &senders), vec![vec![0], vec![0]]);
+    // once at their mins, the bitrate left is shared by priority
+    assert_eq!(allocate(700, &senders), vec![vec![200], vec![500]]);
+}
+
+#[test]
+fn test_allocate_priorities() {
+    let senders = vec![
+        sender(1.0, vec![layer(0, 10_000)]),
+        sender(3.0, vec![layer(0, 10_000)]),
+    ];
+    assert_eq!(allocate(400, &senders), vec![vec![100], vec![300]]);
+
+    // the share over the max of a sender goes to the others
+    let senders = vec![
+        sender(1.0, vec![layer(0, 10_000)]),
+        sender(3.0, vec![layer(0, 200)]),
+    ];
+    assert_eq!(allocate(1_000, &senders), vec![vec![800], vec![200]]);
+}
+
+#[test]
+fn test_allocate_layers() {
+    let simulcast = sender(
+        1.0,
+        vec![layer(100, 200), layer(400, 800), layer(1_000, 2_000)],
+    );
+    let senders = vec![simulcast.clone()];
+
+    // the lowest layer gets up to its max before the next one is active
+    assert_eq!(allocate(150, &senders), vec![vec![150, 0, 0]]);
+    assert_eq!(allocate(599, &senders), vec![vec![200, 0, 0]]);
+    assert_eq!(allocate(700, &senders), vec![vec![200, 500, 0]]);
+    assert_eq!(allocate(2_000, &senders), vec![vec![200, 800, 1_000]]);
+    assert_eq!(allocate(5_000, &senders), vec![vec![200, 800, 2_000]]);
+
+    // the base layers of the senders are active before the higher layers
+    let senders = vec![simulcast, sender(1.0, vec![layer(300, 300)])];
+    assert_eq!(allocate(650, &senders), vec![vec![200, 0, 0], vec![300]]);
+}
+
+#[tokio::test]
+async fn test_bitrate_allocator() {
+    let allocator = Arc::new(BitrateAllocator::new(1_000));
+    let notified = Arc::new(AtomicUsize::new(0));
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let on_allocation = |id: &'static str| -> OnAllocationFn {
+        let (tx, notified) = (tx.clone(), Arc::clone(&notified));
+        Arc::new(move |allocation: Vec<u64>| {
+            notified.fetch_add(1, Ordering::SeqCst);
+            let _ = tx.send((id, allocation));
+            Box::pin(async {})
+        })
+    };
+
+    allocator
+        .add_sender(
+            "a",
+            sender(1.0, vec![layer(100, 10_000)]),
+            on_allocation("a"),
+        )
+        .await;
+    assert_eq!(rx.try_recv(), Ok(("a", vec![1_000])));
+    allocator
+        .add_sender(
+            "b",
+            sender(1.0, vec![layer(100, 10_000)]),
+            on_allocation("b"),
+        )
+        .await;
+    assert_eq!(rx.try_recv(), Ok(("a", vec![500])));
+    assert_eq!(rx.try_recv(), Ok(("b", vec![500])));
+
+    // the target bitrate of the congestion controller
+    let on_target_bitrate = allocator.on_target_bitrate();
+    on_target_bitrate(4_000).await;
+    assert_eq!(rx.try_recv(), Ok(("a", vec![2_000])));
+    assert_eq!(rx.try_recv(), Ok(("b", vec![2_000])));
+    assert_eq!(allocator.allocation("b"), Some(vec![2_000]));
+
+    // the senders are only notified of changes
+    let before = notified.load(Ordering::SeqCst);
+    allocator.set_target_bitrate(4_000).await;
+    assert_eq!(notified.load(Ordering::SeqCst), before);
+
+    allocator.remove_sender("a").await;
+    assert_eq!(rx.try_recv(), Ok(("b", vec![4_000])));
+    assert_eq!(allocator.allocation("a"), None);
+    assert!(rx.try_recv().is_err());
+}
@@ -0,0 +1,273 @@
+#[cfg(test)]
+mod bitrate_allocator_test;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::gcc::sender::OnTargetBitrateFn;
+
+const DEFAULT_PRIORITY: f64 = 1.0;
+
+/// OnAllocationFn is called with the bitrates, in bits per second, allocated to each layer of
+/// a sender, in the order of its layers, each time they change. Layers allocated zero should
+/// be paused.
+pub type OnAllocationFn = Arc<
+    dyn (Fn(Vec<u64>) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>)
+        + Send
+        + Sync
+        + 'static,
+>;
+
+/// LayerConstraints are the bitrates, in bits per second, a layer of a sender is sent at: at
+/// least the min bitrate once active, at most the max bitrate.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct LayerConstraints {
+    pub min_bitrate: u64,
+    pub max_bitrate: u64,
+}
+
+/// SenderConstraints are the constraints of the layers of a sender, from the lowest, e.g.
+/// of its simulcast encodings, and its priority among the senders. A higher layer is only
+/// active once the lower ones are at their max bitrate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SenderConstraints {
+    /// Relative share of the bitrate left once the senders are at their min bitrate.
+    pub priority: f64,
+    pub layers: Vec<LayerConstraints>,
+}
+
+impl Default for SenderConstraints {
+    fn default() -> Self {
+        SenderConstraints {
+            priority: DEFAULT_PRIORITY,
+            layers: vec![],
+        }
+    }
+}
+
+/// allocate splits the target bitrate between the senders, returning the bitrates of the
+/// layers of each one:
+///
+/// 1. The lowest layer of each sender gets its min bitrate, in order of priority, as long as
+///    the target allows. The senders it doesn't allow for are paused.
+/// 2. The higher layers are activated one level at a time across the senders, in order of
+///    priority, as long as the target allows for the layer below at its max bitrate and the
+///    layer at its min bitrate.
+/// 3. The bitrate left is shared between the highest active layers in proportion to the
+///    priorities of their senders, up to their max bitrate.
+pub fn allocate(target_bitrate: u64, senders: &[SenderConstraints]) -> Vec<Vec<u64>> {
+    let mut allocations: Vec<Vec<u64>> = senders.iter().map(|s| vec![0; s.layers.len()]).collect();
+    let mut order: Vec<usize> = (0..senders.len()).collect();
+    // stable, so that senders of the same priority keep the order they were added in
+    order.sort_by(|&a, &b| senders[b].priority.total_cmp(&senders[a].priority));
+
+    let mut budget = target_bitrate;
+    let mut active = vec![false; senders.len()];
+    for &i in &order {
+        if let Some(layer) = senders[i].layers.first() {
+            if layer.min_bitrate <= budget {
+                budget -= layer.min_bitrate;
+                allocations[i][0] = layer.min_bitrate;
+                active[i] = true;
+            }
+        }
+    }
+
+    // highest active layer of each sender
+    let mut top = vec![0usize; senders.len()];
+    let max_layers = senders
+        .iter()
+        .map(|s| s.layers.len())
+        .max()
+        .unwrap_or_default();
+    for level in 1..max_layers {
+        for &i in &order {
+            let layers = &senders[i].layers;
+            if !active[i] || top[i] != level - 1 || level >= layers.len() {
+                continue;
+            }
+            let below = layers[level - 1]
+                .max_bitrate
+                .max(layers[level - 1].min_bitrate);
+            let cost = below - allocations[i][level - 1] + layers[level].min_bitrate;
+            if cost <= budget {
+                budget -= cost;
+                allocations[i][level - 1] = below;
+                allocations[i][level] = layers[level].min_bitrate;
+                top[i] = level;
+            }
+        }
+    }
+
+    loop {
+        let headroom = |allocations: &[Vec<u64>], i: usize| {
+            senders[i].layers[top[i]]
+                .max_bitrate
+                .saturating_sub(allocations[i][top[i]])
+        };
+        let growing: Vec<usize> = order
+            .iter()
+            .copied()
+            .filter(|&i| active[i] && headroom(&allocations, i) > 0)
+            .collect();
+        if budget == 0 || growing.is_empty() {
+            break;
+        }
+
+        let total_priority: f64 = growing.iter().map(|&i| senders[i].priority.max(0.0)).sum();
+        let mut given = 0;
+        for &i in &growing {
+            let share = if total_priority > 0.0 {
+                (budget as f64 * senders[i].priority.max(0.0) / total_priority) as u64
+            } else {
+                budget / growing.len() as u64
+            };
+            let share = share.min(headroom(&allocations, i));
+            allocations[i][top[i]] += share;
+            given += share;
+        }
+        if given == 0 {
+            // less than a bit per second per sender is left, given to the first one
+            let i = growing[0];
+            allocations[i][top[i]] += 1;
+            given = 1;
+        }
+        budget -= given;
+    }
+
+    allocations
+}
+
+struct AllocatedSender {
+    id: String,
+    constraints: SenderConstraints,
+    on_allocation: OnAllocationFn,
+    allocation: Option<Vec<u64>>,
+}
+
+struct BitrateAllocatorState {
+    target_bitrate: u64,
+    senders: Vec<AllocatedSender>,
+}
+
+/// BitrateAllocator splits the target bitrate of the congestion controller between the
+/// senders added to it, as [`allocate`] does, and notifies each sender of the bitrates of its
+/// layers when they change, so that its encoders follow the estimate.
+///
+/// It's handed the target bitrate by the handler returned by
+/// [`BitrateAllocator::on_target_bitrate`], e.g. given to
+/// [`crate::gcc::sender::SenderBuilder::with_on_target_bitrate`].
+pub struct BitrateAllocator {
+    state: util::sync::Mutex<BitrateAllocatorState>,
+}
+
+impl BitrateAllocator {
+    /// new returns an allocator of initial_bitrate until it is given a target bitrate.
+    pub fn new(initial_bitrate: u64) -> Self {
+        BitrateAllocator {
+            state: util::sync::Mutex::new(BitrateAllocatorState {
+                target_bitrate: initial_bitrate,
+                senders: vec![],
+            }),
+        }
+    }
+
+    /// add_sender adds the sender id with constraints, or updates them if already added, and
+    /// notifies the senders whose allocation changes.
+    pub async fn add_sender(
+        &self,
+        id: &str,
+        constraints: SenderConstraints,
+        on_allocation: OnAllocationFn,
+    ) {
+        let notifications = {
+            let mut state = self.state.lock();
+            match state.senders.iter_mut().find(|s| s.id == id) {
+                Some(sender) => {
+                    sender.constraints = constraints;
+                    sender.on_allocation = on_allocation;
+                }
+                None => state.senders.push(AllocatedSender {
+                    id: id.to_owned(),
+                    constraints,
+                    on_allocation,
+                    allocation: None,
+                }),
+            }
+            reallocate(&mut state)
+        };
+        notify(notifications).await;
+    }
+
+    /// remove_sender removes the sender id, and notifies the senders whose allocation changes.
+    pub async fn remove_sender(&self, id: &str) {
+        let notifications = {
+            let mut state = self.state.lock();
+            state.senders.retain(|s| s.id != id);
+            reallocate(&mut state)
+        };
+        notify(notifications).await;
+    }
+
+    /// set_target_bitrate sets the bitrate, in bits per second, to split between the senders,
+    /// and notifies the senders whose allocation changes.
+    pub async fn set_target_bitrate(&self, target_bitrate: u64) {
+        let notifications = {
+            let mut state = self.state.lock();
+            state.target_bitrate = target_bitrate;
+            reallocate(&mut state)
+        };
+        notify(notifications).await;
+    }
+
+    /// allocation returns the bitrates last allocated to the layers of the sender id.
+    pub fn allocation(&self, id: &str) -> Option<Vec<u64>> {
+        let state = self.state.lock();
+        state
+            .senders
+            .iter()
+            .find(|s| s.id == id)
+            .and_then(|s| s.allocation.clone())
+    }
+
+    /// on_target_bitrate returns a handler of the target bitrate of the congestion controller,
+    /// which sets it as the bitrate to split between the senders.
+    pub fn on_target_bitrate(self: &Arc<Self>) -> OnTargetBitrateFn {
+        let allocator = Arc::downgrade(self);
+        Arc::new(move |bitrate: u64| {
+            let allocator = allocator.clone();
+            Box::pin(async move {
+                if let Some(allocator) = allocator.upgrade() {
+                    allocator.set_target_bitrate(bitrate).await;
+                }
+            })
+        })
+    }
+}
+
+/// reallocate allocates the target bitrate again, returning the handlers to call with the
+/// allocations which changed.
+fn reallocate(state: &mut BitrateAllocatorState) -> Vec<(OnAllocationFn, Vec<u64>)> {
+    let constraints: Vec<SenderConstraints> = state
+        .senders
+        .iter()
+        .map(|s| s.constraints.clone())
+        .collect();
+    let allocations = allocate(state.target_bitrate, &constraints);
+
+    let mut notifications = vec![];
+    for (sender, allocation) in state.senders.iter_mut().zip(allocations) {
+        if sender.allocation.as_ref() != Some(&allocation) {
+            sender.allocation = Some(allocation.clone());
+            notifications.push((Arc::clone(&sender.on_allocation), allocation));
+        }
+    }
+    notifications
+}
+
+async fn notify(notifications: Vec<(OnAllocationFn, Vec<u64>)>) {
+    for (on_allocation, allocation) in notifications {
+        on_allocation(allocation).await;
+    }
+}
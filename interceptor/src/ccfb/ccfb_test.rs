@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use super::*;
+
+fn received(ms: u64) -> MetricBlock {
+    MetricBlock::received_at((ms * 1024 / 1000) as u16, EcnCodepoint::NotEct)
+}
+
+#[test]
+fn test_recorder_losses_and_arrival_offsets() {
+    let start = Instant::now();
+    let mut recorder = Recorder::new(5000);
+    assert!(recorder.build_feedback_packet(start, 0).is_none());
+
+    recorder.record(123, 10, start);
+    recorder.record(123, 11, start + Duration::from_millis(125));
+    recorder.record(123, 13, start + Duration::from_millis(250));
+    recorder.record(456, 7, start + Duration::from_millis(500));
+
+    let now = start + Duration::from_secs(1);
+    let feedback = recorder.build_feedback_packet(now, 0x11223344).unwrap();
+    assert_eq!(
+        feedback,
+        CongestionControlFeedback {
+            sender_ssrc: 5000,
+            report_blocks: vec![
+                ReportBlock {
+                    media_ssrc: 123,
+                    begin_sequence: 10,
+                    metric_blocks: vec![
+                        received(1000),
+                        received(875),
+                        MetricBlock::default(),
+                        received(750),
+                    ],
+                },
+                ReportBlock {
+                    media_ssrc: 456,
+                    begin_sequence: 7,
+                    metric_blocks: vec![received(500)],
+                },
+            ],
+            report_timestamp: 0x11223344,
+        }
+    );
+
+    // the next feedback starts after the last packet reported, and late packets are dropped
+    recorder.record(123, 12, now);
+    recorder.record(123, 16, now);
+    let feedback = recorder.build_feedback_packet(now, 0).unwrap();
+    assert_eq!(
+        feedback.report_blocks,
+        vec![ReportBlock {
+            media_ssrc: 123,
+            begin_sequence: 14,
+            metric_blocks: vec![MetricBlock::default(), MetricBlock::default(), received(0)],
+        }]
+    );
+    assert!(recorder.build_feedback_packet(now, 0).is_none());
+}
+
+#[test]
+fn test_recorder_sequence_number_wrap_around() {
+    let now = Instant::now();
+    let mut recorder = Recorder::new(5000);
+    recorder.record(123, 0xfffe, now);
+    recorder.record(123, 0x0001, now);
+    recorder.record(123, 0xffff, now);
+
+    let feedback = recorder.build_feedback_packet(now, 0).unwrap();
+    assert_eq!(
+        feedback.report_blocks,
+        vec![ReportBlock {
+            media_ssrc: 123,
+            begin_sequence: 0xfffe,
+            metric_blocks: vec![
+                received(0),
+                received(0),
+                MetricBlock::default(),
+                received(0),
+            ],
+        }]
+    );
+}
+
+#[test]
+fn test_recorder_max_packets_per_feedback() {
+    let now = Instant::now();
+    let mut recorder = Recorder::new(5000);
+    recorder.set_max_packets_per_feedback(2);
+    for sequence_number in 0..5 {
+        recorder.record(123, sequence_number, now);
+    }
+
+    let mut begins = vec![];
+    while let Some(feedback) = recorder.build_feedback_packet(now, 0) {
+        let block = &feedback.report_blocks[0];
+        assert!(block.metric_blocks.len() <= 2);
+        begins.push(block.begin_sequence);
+    }
+    assert_eq!(begins, vec![0, 2, 4]);
+
+    // a run of losses longer than a feedback is skipped
+    recorder.record(123, 100, now);
+    let feedback = recorder.build_feedback_packet(now, 0).unwrap();
+    assert_eq!(feedback.report_blocks[0].begin_sequence, 100);
+    assert_eq!(feedback.report_blocks[0].metric_blocks, vec![received(0)]);
+}
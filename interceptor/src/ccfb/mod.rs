@@ -0,0 +1,170 @@
+#[cfg(test)]
+mod ccfb_test;
+
+pub mod receiver;
+
+use std::collections::BTreeMap;
+
+use rtcp::transport_feedbacks::congestion_control_feedback::{
+    CongestionControlFeedback, EcnCodepoint, MetricBlock, ReportBlock, CCFB_MAX_REPORTS,
+};
+use tokio::time::Instant;
+
+use crate::stream_info::StreamInfo;
+
+/// Default largest number of packets of a stream a feedback packet reports.
+const DEFAULT_MAX_PACKETS_PER_FEEDBACK: u16 = 256;
+
+fn stream_support_ccfb(info: &StreamInfo) -> bool {
+    info.rtcp_feedback
+        .iter()
+        .any(|fb| fb.typ == "ack" && fb.parameter == "ccfb")
+}
+
+#[derive(Default, Debug, Clone)]
+struct StreamRecord {
+    cycles: u64,
+    last_sequence_number: Option<u16>,
+    arrivals: BTreeMap<u64, Instant>,
+    /// Extended sequence number following the last one reported.
+    next_sequence_number: Option<u64>,
+}
+
+impl StreamRecord {
+    fn unwrap(&mut self, sequence_number: u16) -> u64 {
+        let last = match self.last_sequence_number {
+            Some(last) => last,
+            None => {
+                self.last_sequence_number = Some(sequence_number);
+                return sequence_number as u64;
+            }
+        };
+
+        let diff = sequence_number.wrapping_sub(last);
+        if diff < 0x8000 {
+            if sequence_number < last {
+                self.cycles += 1 << 16;
+            }
+            self.last_sequence_number = Some(sequence_number);
+            self.cycles | sequence_number as u64
+        } else if sequence_number > last && self.cycles > 0 {
+            // reordered across the wrap around
+            (self.cycles - (1 << 16)) | sequence_number as u64
+        } else {
+            self.cycles | sequence_number as u64
+        }
+    }
+}
+
+/// Recorder records the arrival of the RTP packets of the streams received, and reports them
+/// in congestion control feedback packets as specified in RFC 8888.
+///
+/// Each feedback reports the packets of each stream from the one following the last reported,
+/// and packets arriving after their sequence number was reported are dropped.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    sender_ssrc: u32,
+    max_packets_per_feedback: u16,
+    streams: BTreeMap<u32, StreamRecord>,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Recorder::new(0)
+    }
+}
+
+impl Recorder {
+    /// new creates a new Recorder which uses the given sender_ssrc in the created
+    /// feedback packets.
+    pub fn new(sender_ssrc: u32) -> Self {
+        Recorder {
+            sender_ssrc,
+            max_packets_per_feedback: DEFAULT_MAX_PACKETS_PER_FEEDBACK,
+            streams: BTreeMap::new(),
+        }
+    }
+
+    /// set_max_packets_per_feedback sets the largest number of packets of a stream, received
+    /// or lost, a feedback packet reports. The following ones are reported by the next
+    /// feedback packets.
+    pub fn set_max_packets_per_feedback(&mut self, max_packets: u16) {
+        self.max_packets_per_feedback = max_packets.clamp(1, CCFB_MAX_REPORTS as u16);
+    }
+
+    /// record marks the packet of media_ssrc with sequence_number as received at arrival_time.
+    pub fn record(&mut self, media_ssrc: u32, sequence_number: u16, arrival_time: Instant) {
+        let stream = self.streams.entry(media_ssrc).or_default();
+        let sequence_number_ext = stream.unwrap(sequence_number);
+        if let Some(next) = stream.next_sequence_number {
+            if sequence_number_ext < next {
+                return;
+            }
+        }
+        stream
+            .arrivals
+            .entry(sequence_number_ext)
+            .or_insert(arrival_time);
+    }
+
+    /// remove forgets the packets of media_ssrc, when its stream is removed.
+    pub fn remove(&mut self, media_ssrc: u32) {
+        self.streams.remove(&media_ssrc);
+    }
+
+    /// build_feedback_packet returns the feedback on the packets recorded since the last one,
+    /// sent at now with report_timestamp, or None if no packet was recorded.
+    pub fn build_feedback_packet(
+        &mut self,
+        now: Instant,
+        report_timestamp: u32,
+    ) -> Option<CongestionControlFeedback> {
+        let max_packets = self.max_packets_per_feedback as u64;
+        let mut report_blocks = vec![];
+        for (&media_ssrc, stream) in &mut self.streams {
+            let (first, last) = match (
+                stream.arrivals.keys().next(),
+                stream.arrivals.keys().next_back(),
+            ) {
+                (Some(&first), Some(&last)) => (first, last),
+                _ => continue,
+            };
+
+            let mut begin = stream.next_sequence_number.unwrap_or(first);
+            if first - begin >= max_packets {
+                // too many packets were lost to report them all
+                begin = first;
+            }
+            let end = last.min(begin + max_packets - 1);
+
+            let remaining = stream.arrivals.split_off(&(end + 1));
+            let reported = std::mem::replace(&mut stream.arrivals, remaining);
+            let metric_blocks = (begin..=end)
+                .map(|sequence_number| match reported.get(&sequence_number) {
+                    Some(&arrival_time) => {
+                        let offset = now.saturating_duration_since(arrival_time);
+                        let offset = (offset.as_secs_f64() * 1024.0).round().min(u16::MAX as f64);
+                        MetricBlock::received_at(offset as u16, EcnCodepoint::NotEct)
+                    }
+                    None => MetricBlock::default(),
+                })
+                .collect();
+            stream.next_sequence_number = Some(end + 1);
+
+            report_blocks.push(ReportBlock {
+                media_ssrc,
+                begin_sequence: (begin & 0xffff) as u16,
+                metric_blocks,
+            });
+        }
+
+        if report_blocks.is_empty() {
+            return None;
+        }
+        Some(CongestionControlFeedback {
+            sender_ssrc: self.sender_ssrc,
+            report_blocks,
+            report_timestamp,
+        })
+    }
+}
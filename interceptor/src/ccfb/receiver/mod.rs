@@ -0,0 +1,273 @@
+mod receiver_stream;
+#[cfg(test)]
+mod receiver_test;
+
+use std::time::{Duration, SystemTime};
+
+use receiver_stream::ReceiverStream;
+use rtp::extension::abs_send_time_extension::unix2ntp;
+use tokio::sync::{mpsc, Mutex};
+use tokio::time::MissedTickBehavior;
+use waitgroup::WaitGroup;
+
+use crate::ccfb::{stream_support_ccfb, Recorder};
+use crate::*;
+
+/// ReceiverBuilder is a InterceptorBuilder for a Receiver
+#[derive(Default)]
+pub struct ReceiverBuilder {
+    interval: Option<Duration>,
+    max_packets_per_feedback: Option<u16>,
+}
+
+impl ReceiverBuilder {
+    /// with_interval sets send interval for the interceptor.
+    pub fn with_interval(mut self, interval: Duration) -> ReceiverBuilder {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// with_max_packets_per_feedback sets the largest number of packets of a stream a
+    /// feedback packet reports, see [`Recorder::set_max_packets_per_feedback`].
+    pub fn with_max_packets_per_feedback(mut self, max_packets: u16) -> ReceiverBuilder {
+        self.max_packets_per_feedback = Some(max_packets);
+        self
+    }
+}
+
+impl InterceptorBuilder for ReceiverBuilder {
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        let (close_tx, close_rx) = mpsc::channel(1);
+        let (packet_chan_tx, packet_chan_rx) = mpsc::channel(1);
+        Ok(Arc::new(Receiver {
+            internal: Arc::new(ReceiverInternal {
+                interval: if let Some(interval) = &self.interval {
+                    *interval
+                } else {
+                    Duration::from_millis(100)
+                },
+                max_packets_per_feedback: self.max_packets_per_feedback,
+                recorder: Mutex::new(Recorder::default()),
+                packet_chan_rx: Mutex::new(Some(packet_chan_rx)),
+                streams: Mutex::new(HashMap::new()),
+                close_rx: Mutex::new(Some(close_rx)),
+            }),
+            packet_chan_tx,
+            wg: Mutex::new(Some(WaitGroup::new())),
+            close_tx: Mutex::new(Some(close_tx)),
+        }))
+    }
+}
+
+struct Packet {
+    ssrc: u32,
+    sequence_number: u16,
+    arrival_time: tokio::time::Instant,
+}
+
+struct ReceiverInternal {
+    interval: Duration,
+    max_packets_per_feedback: Option<u16>,
+    recorder: Mutex<Recorder>,
+    packet_chan_rx: Mutex<Option<mpsc::Receiver<Packet>>>,
+    streams: Mutex<HashMap<u32, Arc<ReceiverStream>>>,
+    close_rx: Mutex<Option<mpsc::Receiver<()>>>,
+}
+
+impl ReceiverInternal {
+    fn new_recorder(&self, sender_ssrc: u32) -> Recorder {
+        let mut recorder = Recorder::new(sender_ssrc);
+        if let Some(max_packets) = self.max_packets_per_feedback {
+            recorder.set_max_packets_per_feedback(max_packets);
+        }
+        recorder
+    }
+
+    async fn send_feedback(&self, rtcp_writer: &Arc<dyn RTCPWriter + Send + Sync>, a: &Attributes) {
+        let report_timestamp = (unix2ntp(SystemTime::now()) >> 16) as u32;
+        let feedback = {
+            let mut recorder = self.recorder.lock().await;
+            recorder.build_feedback_packet(tokio::time::Instant::now(), report_timestamp)
+        };
+
+        if let Some(feedback) = feedback {
+            let pkts: Vec<Box<dyn rtcp::packet::Packet + Send + Sync>> = vec![Box::new(feedback)];
+            if let Err(err) = rtcp_writer.write(&pkts, a).await {
+                log::error!("rtcp_writer.write got err: {}", err);
+            }
+        }
+    }
+}
+
+/// Receiver sends congestion control feedback on the RTP packets received, for the streams
+/// which negotiated it, as specified in:
+/// <https://datatracker.ietf.org/doc/html/rfc8888>
+pub struct Receiver {
+    internal: Arc<ReceiverInternal>,
+    packet_chan_tx: mpsc::Sender<Packet>,
+
+    wg: Mutex<Option<WaitGroup>>,
+    close_tx: Mutex<Option<mpsc::Sender<()>>>,
+}
+
+impl Receiver {
+    /// builder returns a new ReceiverBuilder.
+    pub fn builder() -> ReceiverBuilder {
+        ReceiverBuilder::default()
+    }
+
+    async fn is_closed(&self) -> bool {
+        let close_tx = self.close_tx.lock().await;
+        close_tx.is_none()
+    }
+
+    async fn run(
+        rtcp_writer: Arc<dyn RTCPWriter + Send + Sync>,
+        internal: Arc<ReceiverInternal>,
+    ) -> Result<()> {
+        let mut close_rx = {
+            let mut close_rx = internal.close_rx.lock().await;
+            if let Some(close_rx) = close_rx.take() {
+                close_rx
+            } else {
+                return Err(Error::ErrInvalidCloseRx);
+            }
+        };
+        let mut packet_chan_rx = {
+            let mut packet_chan_rx = internal.packet_chan_rx.lock().await;
+            if let Some(packet_chan_rx) = packet_chan_rx.take() {
+                packet_chan_rx
+            } else {
+                return Err(Error::ErrInvalidPacketRx);
+            }
+        };
+
+        let a = Attributes::new();
+        let mut ticker = tokio::time::interval(internal.interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                _ = close_rx.recv() =>{
+                    return Ok(());
+                }
+                p = packet_chan_rx.recv() => {
+                    if let Some(p) = p {
+                        let mut recorder = internal.recorder.lock().await;
+                        recorder.record(p.ssrc, p.sequence_number, p.arrival_time);
+                    }
+                }
+                _ = ticker.tick() =>{
+                    internal.send_feedback(&rtcp_writer, &a).await;
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Interceptor for Receiver {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        reader
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        if self.is_closed().await {
+            return writer;
+        }
+
+        {
+            let mut recorder = self.internal.recorder.lock().await;
+            *recorder = self.internal.new_recorder(rand::random::<u32>());
+        }
+
+        let mut w = {
+            let wait_group = self.wg.lock().await;
+            wait_group.as_ref().map(|wg| wg.worker())
+        };
+        let writer2 = Arc::clone(&writer);
+        let internal = Arc::clone(&self.internal);
+        tokio::spawn(async move {
+            let _d = w.take();
+            if let Err(err) = Receiver::run(writer2, internal).await {
+                log::warn!("bind_rtcp_writer CCFB Receiver::run got error: {}", err);
+            }
+        });
+
+        writer
+    }
+
+    /// bind_local_stream lets you modify any outgoing RTP packets. It is called once for per LocalStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_local_stream(
+        &self,
+        _info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        writer
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, _info: &StreamInfo) {}
+
+    /// bind_remote_stream lets you modify any incoming RTP packets. It is called once for per RemoteStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_remote_stream(
+        &self,
+        info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        if !stream_support_ccfb(info) {
+            return reader;
+        }
+
+        let stream = Arc::new(ReceiverStream::new(
+            reader,
+            info.ssrc,
+            self.packet_chan_tx.clone(),
+        ));
+
+        {
+            let mut streams = self.internal.streams.lock().await;
+            streams.insert(info.ssrc, Arc::clone(&stream));
+        }
+
+        stream
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, info: &StreamInfo) {
+        {
+            let mut streams = self.internal.streams.lock().await;
+            streams.remove(&info.ssrc);
+        }
+        let mut recorder = self.internal.recorder.lock().await;
+        recorder.remove(info.ssrc);
+    }
+
+    /// close closes the Interceptor, cleaning up any data if necessary.
+    async fn close(&self) -> Result<()> {
+        {
+            let mut close_tx = self.close_tx.lock().await;
+            close_tx.take();
+        }
+
+        {
+            let mut wait_group = self.wg.lock().await;
+            if let Some(wg) = wait_group.take() {
+                wg.wait().await;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use super::*;
+
+pub(super) struct ReceiverStream {
+    parent_rtp_reader: Arc<dyn RTPReader + Send + Sync>,
+    ssrc: u32,
+    packet_chan_tx: mpsc::Sender<Packet>,
+}
+
+impl ReceiverStream {
+    pub(super) fn new(
+        parent_rtp_reader: Arc<dyn RTPReader + Send + Sync>,
+        ssrc: u32,
+        packet_chan_tx: mpsc::Sender<Packet>,
+    ) -> Self {
+        ReceiverStream {
+            parent_rtp_reader,
+            ssrc,
+            packet_chan_tx,
+        }
+    }
+}
+
+#[async_trait]
+impl RTPReader for ReceiverStream {
+    /// read a rtp packet
+    async fn read(
+        &self,
+        buf: &mut [u8],
+        attributes: &Attributes,
+    ) -> Result<(rtp::packet::Packet, Attributes)> {
+        let (pkt, attr) = self.parent_rtp_reader.read(buf, attributes).await?;
+
+        let _ = self
+            .packet_chan_tx
+            .send(Packet {
+                ssrc: self.ssrc,
+                sequence_number: pkt.header.sequence_number,
+                arrival_time: tokio::time::Instant::now(),
+            })
+            .await;
+
+        Ok((pkt, attr))
+    }
+}
@@ -0,0 +1,90 @@
+use rtcp::transport_feedbacks::congestion_control_feedback::{
+    CongestionControlFeedback, MetricBlock,
+};
+
+use super::*;
+use crate::mock::mock_stream::MockStream;
+use crate::stream_info::RTCPFeedback;
+
+fn ccfb_stream_info(ssrc: u32) -> StreamInfo {
+    StreamInfo {
+        ssrc,
+        rtcp_feedback: vec![RTCPFeedback {
+            typ: "ack".to_owned(),
+            parameter: "ccfb".to_owned(),
+        }],
+        ..Default::default()
+    }
+}
+
+async fn receive(stream: &MockStream, sequence_number: u16) {
+    stream
+        .receive_rtp(rtp::packet::Packet {
+            header: rtp::header::Header {
+                sequence_number,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await;
+    let pkt = stream.read_rtp().await.unwrap().unwrap();
+    assert_eq!(pkt.header.sequence_number, sequence_number);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_ccfb_receiver_interceptor() -> Result<()> {
+    let icpr = Receiver::builder()
+        .with_interval(Duration::from_millis(50))
+        .build("")?;
+    let stream = MockStream::new(&ccfb_stream_info(1), icpr).await;
+
+    receive(&stream, 1).await;
+    receive(&stream, 3).await;
+
+    let pkts = stream.written_rtcp().await.unwrap();
+    assert_eq!(pkts.len(), 1);
+    let feedback = pkts[0]
+        .as_any()
+        .downcast_ref::<CongestionControlFeedback>()
+        .unwrap();
+    assert_eq!(feedback.report_blocks.len(), 1);
+    let block = &feedback.report_blocks[0];
+    assert_eq!(block.media_ssrc, 1);
+    assert_eq!(block.begin_sequence, 1);
+    assert_eq!(block.metric_blocks.len(), 3);
+    assert!(block.metric_blocks[0].received);
+    assert_eq!(block.metric_blocks[1], MetricBlock::default());
+    assert!(block.metric_blocks[2].received);
+
+    stream.close().await?;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_ccfb_receiver_interceptor_not_negotiated() -> Result<()> {
+    let icpr = Receiver::builder()
+        .with_interval(Duration::from_millis(50))
+        .build("")?;
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    receive(&stream, 1).await;
+
+    tokio::select! {
+        pkts = stream.written_rtcp() => {
+            assert!(pkts.map(|p| p.is_empty()).unwrap_or(true), "Should not have sent feedback for a stream without ccfb")
+        }
+        _ = tokio::time::sleep(Duration::from_millis(300)) => {
+            // All good
+        }
+    }
+
+    stream.close().await?;
+    Ok(())
+}
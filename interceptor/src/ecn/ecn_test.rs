@@ -0,0 +1,47 @@
+use super::*;
+
+#[test]
+fn test_ecn_recorder() {
+    let mut recorder = EcnRecorder::new(1234);
+    assert!(!recorder.congestion_experienced());
+
+    // 0xfffe is lost, 0x0001 is duplicated, 0xfffd is reordered across the wrap around
+    for (sequence_number, ecn) in [
+        (0xfffc, EcnCodepoint::Ect0),
+        (0xffff, EcnCodepoint::Ect0),
+        (0x0000, EcnCodepoint::Ce),
+        (0xfffd, EcnCodepoint::Ect0),
+        (0x0001, EcnCodepoint::Ect0),
+        (0x0001, EcnCodepoint::Ect0),
+    ] {
+        recorder.record(sequence_number, ecn);
+    }
+
+    let feedback = recorder.feedback(5678);
+    assert_eq!(feedback.sender_ssrc, 5678);
+    assert_eq!(feedback.media_ssrc, 1234);
+    assert_eq!(feedback.extended_highest_sequence_number, 0x00010001);
+    assert_eq!(
+        feedback.counters,
+        EcnCounters {
+            ect0: 4,
+            ect1: 0,
+            ce: 1,
+            not_ect: 0,
+            lost: 1,
+            duplicates: 1,
+        }
+    );
+    assert_eq!(recorder.summary().counters, feedback.counters);
+
+    assert!(recorder.congestion_experienced());
+    assert!(!recorder.congestion_experienced());
+}
+
+#[test]
+fn test_ecn_from_attributes() {
+    let mut attributes = crate::Attributes::new();
+    assert_eq!(ecn_from_attributes(&attributes), None);
+    attributes.insert(ATTR_ECN, EcnCodepoint::Ce as usize);
+    assert_eq!(ecn_from_attributes(&attributes), Some(EcnCodepoint::Ce));
+}
@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod ecn_test;
+
+pub mod receiver;
+
+use std::collections::VecDeque;
+
+use rtcp::extended_report::EcnSummaryReportBlock;
+use rtcp::transport_feedbacks::congestion_control_feedback::EcnCodepoint;
+use rtcp::transport_feedbacks::ecn_feedback::{EcnCounters, EcnFeedback};
+
+/// Key of the Attributes of the RTP packets read which holds the ECN field of the IP header
+/// they were received with, as an [`EcnCodepoint`]. It is set by the transports able to read
+/// it from their socket, and the packets read without it aren't counted.
+pub const ATTR_ECN: usize = 0xEC4;
+
+/// Number of the last sequence numbers received remembered to count duplicates.
+const DUPLICATE_WINDOW: usize = 512;
+
+/// stream_support_ecn returns whether the stream negotiated the ECN feedback, with the
+/// "nack ecn" RTCP feedback.
+pub(crate) fn stream_support_ecn(info: &crate::stream_info::StreamInfo) -> bool {
+    info.rtcp_feedback
+        .iter()
+        .any(|fb| fb.typ == "nack" && fb.parameter == "ecn")
+}
+
+/// ecn_from_attributes returns the ECN marking of a packet read with attributes, if known.
+pub fn ecn_from_attributes(attributes: &crate::Attributes) -> Option<EcnCodepoint> {
+    attributes
+        .get(&ATTR_ECN)
+        .map(|&v| EcnCodepoint::from(v as u8))
+}
+
+/// EcnRecorder counts the ECN marks, the losses and the duplicates of the packets of a stream
+/// received, for its ECN feedback and ECN summary reports.
+///
+/// ## Specifications
+///
+/// * [RFC 6679 §5]
+///
+/// [RFC 6679 §5]: https://tools.ietf.org/html/rfc6679#section-5
+#[derive(Debug, Clone)]
+pub struct EcnRecorder {
+    media_ssrc: u32,
+    counters: EcnCounters,
+    base_sequence_number: Option<u32>,
+    extended_highest_sequence_number: u32,
+    received: u32,
+    recent: VecDeque<u32>,
+    reported_ce: u16,
+}
+
+impl EcnRecorder {
+    /// new returns a recorder of the packets of media_ssrc.
+    pub fn new(media_ssrc: u32) -> Self {
+        EcnRecorder {
+            media_ssrc,
+            counters: EcnCounters::default(),
+            base_sequence_number: None,
+            extended_highest_sequence_number: 0,
+            received: 0,
+            recent: VecDeque::with_capacity(DUPLICATE_WINDOW),
+            reported_ce: 0,
+        }
+    }
+
+    /// record counts the packet with sequence_number received with ecn.
+    pub fn record(&mut self, sequence_number: u16, ecn: EcnCodepoint) {
+        let sequence_number = match self.base_sequence_number {
+            None => {
+                self.base_sequence_number = Some(sequence_number as u32);
+                self.extended_highest_sequence_number = sequence_number as u32;
+                sequence_number as u32
+            }
+            Some(_) => {
+                let highest = self.extended_highest_sequence_number;
+                let diff = sequence_number.wrapping_sub(highest as u16) as i16 as i64;
+                let extended = (highest as i64 + diff).max(0) as u32;
+                self.extended_highest_sequence_number = highest.max(extended);
+                extended
+            }
+        };
+
+        if self.recent.contains(&sequence_number) {
+            self.counters.duplicates = self.counters.duplicates.wrapping_add(1);
+            return;
+        }
+        if self.recent.len() == DUPLICATE_WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(sequence_number);
+        self.received = self.received.wrapping_add(1);
+        self.counters.record(ecn);
+    }
+
+    /// counters returns the counters of the packets received.
+    pub fn counters(&self) -> EcnCounters {
+        let expected = match self.base_sequence_number {
+            Some(base) => self.extended_highest_sequence_number.wrapping_sub(base) + 1,
+            None => 0,
+        };
+        EcnCounters {
+            lost: expected.saturating_sub(self.received) as u16,
+            ..self.counters
+        }
+    }
+
+    /// congestion_experienced returns whether packets marked ECN-CE were received since it
+    /// was last called.
+    pub fn congestion_experienced(&mut self) -> bool {
+        let ce = self.counters.ce;
+        let experienced = ce != self.reported_ce;
+        self.reported_ce = ce;
+        experienced
+    }
+
+    /// feedback returns the ECN feedback of the stream, sent by sender_ssrc.
+    pub fn feedback(&self, sender_ssrc: u32) -> EcnFeedback {
+        EcnFeedback {
+            sender_ssrc,
+            media_ssrc: self.media_ssrc,
+            extended_highest_sequence_number: self.extended_highest_sequence_number,
+            counters: self.counters(),
+        }
+    }
+
+    /// summary returns the ECN summary report block of the stream.
+    pub fn summary(&self) -> EcnSummaryReportBlock {
+        EcnSummaryReportBlock {
+            ssrc: self.media_ssrc,
+            counters: self.counters(),
+        }
+    }
+}
@@ -0,0 +1,242 @@
+mod receiver_stream;
+#[cfg(test)]
+mod receiver_test;
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use receiver_stream::ReceiverStream;
+use rtcp::extended_report::ExtendedReport;
+use tokio::sync::{mpsc, Mutex};
+use tokio::time::MissedTickBehavior;
+use waitgroup::WaitGroup;
+
+use crate::ecn::{stream_support_ecn, EcnRecorder};
+use crate::*;
+
+/// ReceiverBuilder is a InterceptorBuilder for a Receiver
+#[derive(Default)]
+pub struct ReceiverBuilder {
+    interval: Option<Duration>,
+}
+
+impl ReceiverBuilder {
+    /// with_interval sets send interval for the interceptor.
+    pub fn with_interval(mut self, interval: Duration) -> ReceiverBuilder {
+        self.interval = Some(interval);
+        self
+    }
+}
+
+impl InterceptorBuilder for ReceiverBuilder {
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        let (close_tx, close_rx) = mpsc::channel(1);
+        Ok(Arc::new(Receiver {
+            internal: Arc::new(ReceiverInternal {
+                interval: if let Some(interval) = &self.interval {
+                    *interval
+                } else {
+                    Duration::from_secs(1)
+                },
+                sender_ssrc: AtomicU32::new(0),
+                streams: Mutex::new(HashMap::new()),
+                close_rx: Mutex::new(Some(close_rx)),
+            }),
+            wg: Mutex::new(Some(WaitGroup::new())),
+            close_tx: Mutex::new(Some(close_tx)),
+        }))
+    }
+}
+
+struct ReceiverInternal {
+    interval: Duration,
+    sender_ssrc: AtomicU32,
+    streams: Mutex<HashMap<u32, Arc<ReceiverStream>>>,
+    close_rx: Mutex<Option<mpsc::Receiver<()>>>,
+}
+
+impl ReceiverInternal {
+    /// send_feedback sends an ECN summary of the streams which received packets since the
+    /// last one, and an ECN feedback for each of them which received packets marked ECN-CE.
+    async fn send_feedback(&self, rtcp_writer: &Arc<dyn RTCPWriter + Send + Sync>, a: &Attributes) {
+        let sender_ssrc = self.sender_ssrc.load(Ordering::SeqCst);
+        let mut pkts: Vec<Box<dyn rtcp::packet::Packet + Send + Sync>> = vec![];
+        let mut summaries: Vec<Box<dyn rtcp::packet::Packet + Send + Sync>> = vec![];
+        {
+            let streams = self.streams.lock().await;
+            for stream in streams.values() {
+                let mut recorder = stream.recorder.lock();
+                if !stream.take_updated() {
+                    continue;
+                }
+                if recorder.congestion_experienced() {
+                    pkts.push(Box::new(recorder.feedback(sender_ssrc)));
+                }
+                summaries.push(Box::new(recorder.summary()));
+            }
+        }
+
+        if summaries.is_empty() {
+            return;
+        }
+        pkts.push(Box::new(ExtendedReport {
+            sender_ssrc,
+            reports: summaries,
+        }));
+        if let Err(err) = rtcp_writer.write(&pkts, a).await {
+            log::error!("rtcp_writer.write got err: {}", err);
+        }
+    }
+}
+
+/// Receiver reports the ECN marks of the RTP packets received, for the streams which
+/// negotiated the ECN feedback, as specified in:
+/// <https://datatracker.ietf.org/doc/html/rfc6679>
+///
+/// The marks are read from the [`ATTR_ECN`](crate::ecn::ATTR_ECN) attribute of the packets,
+/// set by the transports able to read them, so that nothing is reported over the others.
+pub struct Receiver {
+    internal: Arc<ReceiverInternal>,
+
+    wg: Mutex<Option<WaitGroup>>,
+    close_tx: Mutex<Option<mpsc::Sender<()>>>,
+}
+
+impl Receiver {
+    /// builder returns a new ReceiverBuilder.
+    pub fn builder() -> ReceiverBuilder {
+        ReceiverBuilder::default()
+    }
+
+    async fn is_closed(&self) -> bool {
+        let close_tx = self.close_tx.lock().await;
+        close_tx.is_none()
+    }
+
+    async fn run(
+        rtcp_writer: Arc<dyn RTCPWriter + Send + Sync>,
+        internal: Arc<ReceiverInternal>,
+    ) -> Result<()> {
+        let mut close_rx = {
+            let mut close_rx = internal.close_rx.lock().await;
+            if let Some(close_rx) = close_rx.take() {
+                close_rx
+            } else {
+                return Err(Error::ErrInvalidCloseRx);
+            }
+        };
+
+        let a = Attributes::new();
+        let mut ticker = tokio::time::interval(internal.interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                _ = close_rx.recv() =>{
+                    return Ok(());
+                }
+                _ = ticker.tick() =>{
+                    internal.send_feedback(&rtcp_writer, &a).await;
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Interceptor for Receiver {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        reader
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        if self.is_closed().await {
+            return writer;
+        }
+
+        self.internal
+            .sender_ssrc
+            .store(rand::random::<u32>(), Ordering::SeqCst);
+
+        let mut w = {
+            let wait_group = self.wg.lock().await;
+            wait_group.as_ref().map(|wg| wg.worker())
+        };
+        let writer2 = Arc::clone(&writer);
+        let internal = Arc::clone(&self.internal);
+        tokio::spawn(async move {
+            let _d = w.take();
+            if let Err(err) = Receiver::run(writer2, internal).await {
+                log::warn!("bind_rtcp_writer ECN Receiver::run got error: {}", err);
+            }
+        });
+
+        writer
+    }
+
+    /// bind_local_stream lets you modify any outgoing RTP packets. It is called once for per LocalStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_local_stream(
+        &self,
+        _info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        writer
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, _info: &StreamInfo) {}
+
+    /// bind_remote_stream lets you modify any incoming RTP packets. It is called once for per RemoteStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_remote_stream(
+        &self,
+        info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        if !stream_support_ecn(info) {
+            return reader;
+        }
+
+        let stream = Arc::new(ReceiverStream::new(reader, EcnRecorder::new(info.ssrc)));
+
+        {
+            let mut streams = self.internal.streams.lock().await;
+            streams.insert(info.ssrc, Arc::clone(&stream));
+        }
+
+        stream
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, info: &StreamInfo) {
+        let mut streams = self.internal.streams.lock().await;
+        streams.remove(&info.ssrc);
+    }
+
+    /// close closes the Interceptor, cleaning up any data if necessary.
+    async fn close(&self) -> Result<()> {
+        {
+            let mut close_tx = self.close_tx.lock().await;
+            close_tx.take();
+        }
+
+        {
+            let mut wait_group = self.wg.lock().await;
+            if let Some(wg) = wait_group.take() {
+                wg.wait().await;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use std::sync::atomic::AtomicBool;
+
+use super::*;
+use crate::ecn::ecn_from_attributes;
+
+pub(super) struct ReceiverStream {
+    parent_rtp_reader: Arc<dyn RTPReader + Send + Sync>,
+    pub(super) recorder: util::sync::Mutex<EcnRecorder>,
+    updated: AtomicBool,
+}
+
+impl ReceiverStream {
+    pub(super) fn new(
+        parent_rtp_reader: Arc<dyn RTPReader + Send + Sync>,
+        recorder: EcnRecorder,
+    ) -> Self {
+        ReceiverStream {
+            parent_rtp_reader,
+            recorder: util::sync::Mutex::new(recorder),
+            updated: AtomicBool::new(false),
+        }
+    }
+
+    /// take_updated returns whether packets were recorded since it was last called.
+    pub(super) fn take_updated(&self) -> bool {
+        self.updated.swap(false, Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl RTPReader for ReceiverStream {
+    /// read a rtp packet
+    async fn read(
+        &self,
+        buf: &mut [u8],
+        attributes: &Attributes,
+    ) -> Result<(rtp::packet::Packet, Attributes)> {
+        let (pkt, attr) = self.parent_rtp_reader.read(buf, attributes).await?;
+
+        if let Some(ecn) = ecn_from_attributes(&attr) {
+            let mut recorder = self.recorder.lock();
+            recorder.record(pkt.header.sequence_number, ecn);
+            self.updated.store(true, Ordering::SeqCst);
+        }
+
+        Ok((pkt, attr))
+    }
+}
@@ -0,0 +1,99 @@
+use rtcp::extended_report::EcnSummaryReportBlock;
+use rtcp::transport_feedbacks::congestion_control_feedback::EcnCodepoint;
+use rtcp::transport_feedbacks::ecn_feedback::EcnFeedback;
+
+use super::*;
+use crate::ecn::ATTR_ECN;
+use crate::mock::mock_stream::MockStream;
+use crate::stream_info::RTCPFeedback;
+
+fn ecn_stream_info(ssrc: u32) -> StreamInfo {
+    StreamInfo {
+        ssrc,
+        rtcp_feedback: vec![RTCPFeedback {
+            typ: "nack".to_owned(),
+            parameter: "ecn".to_owned(),
+        }],
+        ..Default::default()
+    }
+}
+
+async fn receive(stream: &MockStream, sequence_number: u16, ecn: Option<EcnCodepoint>) {
+    let mut attributes = Attributes::new();
+    if let Some(ecn) = ecn {
+        attributes.insert(ATTR_ECN, ecn as usize);
+    }
+    stream
+        .receive_rtp_with_attributes(
+            rtp::packet::Packet {
+                header: rtp::header::Header {
+                    sequence_number,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            attributes,
+        )
+        .await;
+    let pkt = stream.read_rtp().await.unwrap().unwrap();
+    assert_eq!(pkt.header.sequence_number, sequence_number);
+}
+
+fn summary(pkt: &(dyn rtcp::packet::Packet + Send + Sync)) -> EcnSummaryReportBlock {
+    let xr = pkt.as_any().downcast_ref::<ExtendedReport>().unwrap();
+    assert_eq!(xr.reports.len(), 1);
+    xr.reports[0]
+        .as_any()
+        .downcast_ref::<EcnSummaryReportBlock>()
+        .unwrap()
+        .clone()
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_ecn_receiver_interceptor() -> Result<()> {
+    let icpr = Receiver::builder()
+        .with_interval(Duration::from_millis(50))
+        .build("")?;
+    let stream = MockStream::new(&ecn_stream_info(1), icpr).await;
+
+    receive(&stream, 1, Some(EcnCodepoint::Ect0)).await;
+    receive(&stream, 3, Some(EcnCodepoint::Ect0)).await;
+
+    let pkts = stream.written_rtcp().await.unwrap();
+    assert_eq!(pkts.len(), 1);
+    let block = summary(pkts[0].as_ref());
+    assert_eq!(block.ssrc, 1);
+    assert_eq!(block.counters.ect0, 2);
+    assert_eq!(block.counters.lost, 1);
+
+    receive(&stream, 4, Some(EcnCodepoint::Ce)).await;
+
+    let pkts = stream.written_rtcp().await.unwrap();
+    assert_eq!(pkts.len(), 2);
+    let feedback = pkts[0].as_any().downcast_ref::<EcnFeedback>().unwrap();
+    assert_eq!(feedback.media_ssrc, 1);
+    assert_eq!(feedback.extended_highest_sequence_number, 4);
+    assert_eq!(feedback.counters.ce, 1);
+    assert_eq!(summary(pkts[1].as_ref()).counters, feedback.counters);
+
+    stream.close().await?;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_ecn_receiver_interceptor_without_marks() -> Result<()> {
+    let icpr = Receiver::builder()
+        .with_interval(Duration::from_millis(50))
+        .build("")?;
+    let stream = MockStream::new(&ecn_stream_info(1), icpr).await;
+
+    // the transport doesn't read the ECN field of the packets
+    receive(&stream, 1, None).await;
+    receive(&stream, 2, None).await;
+
+    let result = tokio::time::timeout(Duration::from_millis(200), stream.written_rtcp()).await;
+    assert!(result.is_err(), "nothing should be reported");
+
+    stream.close().await?;
+    Ok(())
+}
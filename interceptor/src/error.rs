@@ -21,6 +21,20 @@ pub enum Error {
     ErrShortBuffer,
     #[error("Invalid buffer size")]
     ErrInvalidSize,
+    #[error("Packets to protect span too many sequence numbers")]
+    ErrTooManyProtectedPackets,
+    #[error("Codec not supported by frame encryption")]
+    ErrUnsupportedFrameCodec,
+    #[error("No key to encrypt the frame with")]
+    ErrNoFrameKey,
+    #[error("Failed to encrypt the frame")]
+    ErrFrameEncryption,
+    #[error("Failed to decrypt the frame")]
+    ErrFrameDecryption,
+    #[error("No interceptor of this name")]
+    ErrInterceptorNotFound,
+    #[error("An interceptor of this name exists already")]
+    ErrInterceptorExists,
 
     #[error("{0}")]
     Srtp(#[from] srtp::Error),
@@ -0,0 +1,223 @@
+mod rewriter_stream;
+#[cfg(test)]
+mod rewriter_test;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use rewriter_stream::RewriterStream;
+
+use crate::error::Result;
+use crate::stream_info::{RTPHeaderExtension, StreamInfo};
+use crate::{
+    Attributes, Interceptor, InterceptorBuilder, RTCPReader, RTCPWriter, RTPReader, RTPWriter,
+};
+
+/// Attribute of the packets written to a local stream with the SSRC of the remote stream they
+/// are forwarded from, the header extension ids of which they have.
+pub const ATTR_SOURCE_SSRC: usize = 0x50BCE;
+
+const SDES_MID_URI: &str = "urn:ietf:params:rtp-hdrext:sdes:mid";
+const SDES_RTP_STREAM_ID_URI: &str = "urn:ietf:params:rtp-hdrext:sdes:rtp-stream-id";
+
+#[derive(Default)]
+struct RewriterState {
+    /// Header extensions negotiated for each source stream, by SSRC.
+    sources: HashMap<u32, Vec<RTPHeaderExtension>>,
+    /// Source stream of each local stream, for the packets without ATTR_SOURCE_SSRC.
+    local_sources: HashMap<u32, u32>,
+    /// Values the extensions of each local stream are rewritten to, by URI.
+    values: HashMap<u32, HashMap<String, Bytes>>,
+}
+
+/// ExtensionRewrites sets the header extension maps of the streams the packets written through
+/// an ExtensionRewriter are forwarded from, and the values their extensions are rewritten to.
+#[derive(Default)]
+pub struct ExtensionRewrites {
+    state: util::sync::Mutex<RewriterState>,
+}
+
+impl ExtensionRewrites {
+    /// set_source_extensions sets the header extensions negotiated for the source stream ssrc,
+    /// e.g. of a peer connection whose remote streams aren't bound through an ExtensionRewriter.
+    /// The extensions of the remote streams bound through one are known already.
+    pub fn set_source_extensions(&self, ssrc: u32, extensions: Vec<RTPHeaderExtension>) {
+        let mut state = self.state.lock();
+        state.sources.insert(ssrc, extensions);
+    }
+
+    /// remove_source_extensions removes the header extensions of the source stream ssrc.
+    pub fn remove_source_extensions(&self, ssrc: u32) {
+        let mut state = self.state.lock();
+        state.sources.remove(&ssrc);
+    }
+
+    /// set_source sets the source stream of the packets written to the local stream ssrc
+    /// without the [`ATTR_SOURCE_SSRC`] attribute.
+    pub fn set_source(&self, ssrc: u32, source_ssrc: u32) {
+        let mut state = self.state.lock();
+        state.local_sources.insert(ssrc, source_ssrc);
+    }
+
+    /// set_mid sets the MID the sdes:mid header extension of the packets written to the local
+    /// stream ssrc is rewritten to, that of the media of the stream in the local session.
+    pub fn set_mid(&self, ssrc: u32, mid: &str) {
+        self.set_value(ssrc, SDES_MID_URI, Bytes::copy_from_slice(mid.as_bytes()));
+    }
+
+    /// set_rid sets the RID the sdes:rtp-stream-id header extension of the packets written to
+    /// the local stream ssrc is rewritten to.
+    pub fn set_rid(&self, ssrc: u32, rid: &str) {
+        self.set_value(
+            ssrc,
+            SDES_RTP_STREAM_ID_URI,
+            Bytes::copy_from_slice(rid.as_bytes()),
+        );
+    }
+
+    /// set_value sets the payload the header extension uri of the packets written to the local
+    /// stream ssrc is rewritten to. The packets without the extension are left without it.
+    pub fn set_value(&self, ssrc: u32, uri: &str, payload: Bytes) {
+        let mut state = self.state.lock();
+        state
+            .values
+            .entry(ssrc)
+            .or_default()
+            .insert(uri.to_owned(), payload);
+    }
+
+    /// remove_value stops rewriting the header extension uri of the local stream ssrc.
+    pub fn remove_value(&self, ssrc: u32, uri: &str) {
+        let mut state = self.state.lock();
+        if let Some(values) = state.values.get_mut(&ssrc) {
+            values.remove(uri);
+        }
+    }
+
+    /// rewrite returns the extensions of the source stream of the packets written to the
+    /// local stream ssrc with attribute source_ssrc, if known, and the values of the stream.
+    fn rewrite(
+        &self,
+        ssrc: u32,
+        source_ssrc: Option<u32>,
+    ) -> (Option<Vec<RTPHeaderExtension>>, HashMap<String, Bytes>) {
+        let state = self.state.lock();
+        let source = source_ssrc
+            .or_else(|| state.local_sources.get(&ssrc).copied())
+            .and_then(|source_ssrc| state.sources.get(&source_ssrc).cloned());
+        let values = state.values.get(&ssrc).cloned().unwrap_or_default();
+        (source, values)
+    }
+
+    fn remove_local(&self, ssrc: u32) {
+        let mut state = self.state.lock();
+        state.local_sources.remove(&ssrc);
+        state.values.remove(&ssrc);
+    }
+}
+
+/// ExtensionRewriterBuilder can be used to configure ExtensionRewriter Interceptor
+#[derive(Default)]
+pub struct ExtensionRewriterBuilder {
+    rewrites: Arc<ExtensionRewrites>,
+}
+
+impl ExtensionRewriterBuilder {
+    /// rewrites returns the rewrites of the interceptors built, which can be set before or
+    /// after they are built. The interceptors built by the same builder share them, so that
+    /// those of the peer connections forwarded from know the extensions of their streams.
+    pub fn rewrites(&self) -> Arc<ExtensionRewrites> {
+        Arc::clone(&self.rewrites)
+    }
+}
+
+impl InterceptorBuilder for ExtensionRewriterBuilder {
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        Ok(Arc::new(ExtensionRewriter {
+            rewrites: Arc::clone(&self.rewrites),
+        }))
+    }
+}
+
+/// ExtensionRewriter interceptor normalizes the header extensions of the packets an SFU
+/// forwards between peer connections whose negotiated extension maps differ. The extension ids
+/// of the source stream of the packets written to a local stream are remapped to the ids of
+/// the same extensions in the local stream, the extensions not negotiated for the local stream
+/// are stripped, and the MID, RID or any other extension values are rewritten as set with
+/// [`ExtensionRewrites`].
+///
+/// The source stream of a packet is given by its [`ATTR_SOURCE_SSRC`] attribute, or set per
+/// local stream. The packets of an unknown source are assumed to have the local extension ids.
+pub struct ExtensionRewriter {
+    rewrites: Arc<ExtensionRewrites>,
+}
+
+impl ExtensionRewriter {
+    /// builder returns a new ExtensionRewriterBuilder.
+    pub fn builder() -> ExtensionRewriterBuilder {
+        ExtensionRewriterBuilder::default()
+    }
+}
+
+#[async_trait]
+impl Interceptor for ExtensionRewriter {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        reader
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        writer
+    }
+
+    /// bind_local_stream returns a writer which rewrites the header extensions of the packets
+    /// written for the stream.
+    async fn bind_local_stream(
+        &self,
+        info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        Arc::new(RewriterStream::new(
+            info,
+            Arc::clone(&self.rewrites),
+            writer,
+        ))
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, info: &StreamInfo) {
+        self.rewrites.remove_local(info.ssrc);
+    }
+
+    /// bind_remote_stream records the header extensions of the stream, as a source stream.
+    async fn bind_remote_stream(
+        &self,
+        info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        self.rewrites
+            .set_source_extensions(info.ssrc, info.rtp_header_extensions.clone());
+        reader
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, info: &StreamInfo) {
+        self.rewrites.remove_source_extensions(info.ssrc);
+    }
+
+    /// close closes the Interceptor, cleaning up any data if necessary.
+    async fn close(&self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+use rtp::header::{
+    Header, EXTENSION_PROFILE_ONE_BYTE, EXTENSION_PROFILE_TWO_BYTE, EXTENSION_PROFILE_TWO_BYTE_MASK,
+};
+
+use super::*;
+
+pub(super) struct RewriterStream {
+    ssrc: u32,
+    extensions: Vec<RTPHeaderExtension>,
+    rewrites: Arc<ExtensionRewrites>,
+    next_rtp_writer: Arc<dyn RTPWriter + Send + Sync>,
+}
+
+impl RewriterStream {
+    pub(super) fn new(
+        info: &StreamInfo,
+        rewrites: Arc<ExtensionRewrites>,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Self {
+        RewriterStream {
+            ssrc: info.ssrc,
+            extensions: info.rtp_header_extensions.clone(),
+            rewrites,
+            next_rtp_writer: writer,
+        }
+    }
+}
+
+/// rewrite_extensions returns the header with its extensions of the ids of source, or of
+/// local if unknown, remapped to the ids of local, those not in local stripped, and the
+/// payloads of the URIs of values rewritten. It returns None if the header is unchanged.
+pub(super) fn rewrite_extensions(
+    header: &Header,
+    source: Option<&[RTPHeaderExtension]>,
+    local: &[RTPHeaderExtension],
+    values: &HashMap<String, Bytes>,
+) -> Result<Option<Header>> {
+    let rfc8285 = header.extension_profile == EXTENSION_PROFILE_ONE_BYTE
+        || header.extension_profile & EXTENSION_PROFILE_TWO_BYTE_MASK == EXTENSION_PROFILE_TWO_BYTE;
+    if !header.extension || !rfc8285 {
+        return Ok(None);
+    }
+
+    let mut changed = false;
+    let mut extensions = vec![];
+    for extension in &header.extensions {
+        let uri = source
+            .unwrap_or(local)
+            .iter()
+            .find(|e| e.id == extension.id as isize)
+            .map(|e| e.uri.as_str());
+        let id = uri.and_then(|uri| local.iter().find(|e| e.uri == uri).map(|e| e.id as u8));
+        let (uri, id) = match (uri, id) {
+            (Some(uri), Some(id)) => (uri, id),
+            _ => {
+                changed = true;
+                continue;
+            }
+        };
+
+        let payload = match values.get(uri) {
+            Some(payload) => payload.clone(),
+            None => extension.payload.clone(),
+        };
+        changed |= id != extension.id || payload != extension.payload;
+        extensions.push((id, payload));
+    }
+    if !changed {
+        return Ok(None);
+    }
+
+    let mut rewritten = header.clone();
+    rewritten.extension = false;
+    rewritten.extension_profile = 0;
+    rewritten.extensions.clear();
+    rewritten.extensions_padding = 0;
+    for (id, payload) in extensions {
+        rewritten.set_extension(id, payload)?;
+    }
+    Ok(Some(rewritten))
+}
+
+/// RTPWriter is used by Interceptor.bind_local_stream.
+#[async_trait]
+impl RTPWriter for RewriterStream {
+    /// write a rtp packet
+    async fn write(&self, pkt: &rtp::packet::Packet, a: &Attributes) -> Result<usize> {
+        let source_ssrc = a.get(&ATTR_SOURCE_SSRC).map(|ssrc| *ssrc as u32);
+        let (source, values) = self.rewrites.rewrite(self.ssrc, source_ssrc);
+
+        match rewrite_extensions(&pkt.header, source.as_deref(), &self.extensions, &values)? {
+            Some(header) => {
+                let mut pkt = pkt.clone();
+                pkt.header = header;
+                self.next_rtp_writer.write(&pkt, a).await
+            }
+            None => self.next_rtp_writer.write(pkt, a).await,
+        }
+    }
+}
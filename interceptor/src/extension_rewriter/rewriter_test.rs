@@ -0,0 +1,290 @@
+use std::time::Duration;
+
+use util::{Marshal, MarshalSize, Unmarshal};
+
+use super::rewriter_stream::rewrite_extensions;
+use super::*;
+use crate::mock::mock_stream::MockStream;
+use crate::test::timeout_or_fail;
+
+const ABS_SEND_TIME_URI: &str = "http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time";
+const AUDIO_LEVEL_URI: &str = "urn:ietf:params:rtp-hdrext:ssrc-audio-level";
+
+fn extensions(extensions: &[(&str, isize)]) -> Vec<RTPHeaderExtension> {
+    extensions
+        .iter()
+        .map(|(uri, id)| RTPHeaderExtension {
+            uri: (*uri).to_owned(),
+            id: *id,
+        })
+        .collect()
+}
+
+fn packet(ssrc: u32, extensions: &[(u8, &'static [u8])]) -> rtp::packet::Packet {
+    let mut pkt = rtp::packet::Packet {
+        header: rtp::header::Header {
+            ssrc,
+            ..Default::default()
+        },
+        payload: Bytes::from_static(&[0x98, 0x36, 0xbe, 0x88]),
+        ..Default::default()
+    };
+    for (id, payload) in extensions {
+        pkt.header
+            .set_extension(*id, Bytes::from_static(payload))
+            .expect("A valid extension");
+    }
+    pkt
+}
+
+fn header_extensions(pkt: &rtp::packet::Packet) -> Vec<(u8, Bytes)> {
+    let mut extensions: Vec<(u8, Bytes)> = pkt
+        .header
+        .get_extension_ids()
+        .into_iter()
+        .map(|id| (id, pkt.header.get_extension(id).unwrap_or_default()))
+        .collect();
+    extensions.sort();
+    extensions
+}
+
+#[tokio::test]
+async fn test_extension_rewriter_interceptor() -> Result<()> {
+    let builder = ExtensionRewriter::builder();
+    let rewrites = builder.rewrites();
+    let icpr = builder.build("")?;
+
+    // the publisher's stream, of the extensions of the source peer connection
+    let source = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            rtp_header_extensions: extensions(&[
+                (SDES_MID_URI, 1),
+                (SDES_RTP_STREAM_ID_URI, 2),
+                (ABS_SEND_TIME_URI, 3),
+                (AUDIO_LEVEL_URI, 5),
+            ]),
+            ..Default::default()
+        },
+        Arc::clone(&icpr),
+    )
+    .await;
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 2,
+            rtp_header_extensions: extensions(&[
+                (ABS_SEND_TIME_URI, 2),
+                (SDES_MID_URI, 4),
+                (SDES_RTP_STREAM_ID_URI, 5),
+            ]),
+            ..Default::default()
+        },
+        Arc::clone(&icpr),
+    )
+    .await;
+    rewrites.set_mid(2, "video");
+
+    let mut attributes = Attributes::new();
+    attributes.insert(ATTR_SOURCE_SSRC, 1);
+    stream
+        .write_rtp_with_attributes(
+            &packet(
+                1,
+                &[(1, b"0"), (2, b"h"), (3, &[1, 2, 3]), (5, &[0x80 | 30])],
+            ),
+            &attributes,
+        )
+        .await?;
+    let pkt = timeout_or_fail(Duration::from_millis(10), stream.written_rtp())
+        .await
+        .expect("A written packet");
+    assert_eq!(
+        header_extensions(&pkt),
+        vec![
+            (2, Bytes::from_static(&[1, 2, 3])),
+            (4, Bytes::from_static(b"video")),
+            (5, Bytes::from_static(b"h")),
+        ],
+        "remapped, the MID rewritten and the audio level stripped"
+    );
+    assert_eq!(pkt.payload, Bytes::from_static(&[0x98, 0x36, 0xbe, 0x88]));
+
+    // the packets of an unknown source have the local ids, with the unnegotiated stripped
+    stream
+        .write_rtp(&packet(1, &[(2, &[1, 2, 3]), (3, &[4])]))
+        .await?;
+    let pkt = timeout_or_fail(Duration::from_millis(10), stream.written_rtp())
+        .await
+        .expect("A written packet");
+    assert_eq!(
+        header_extensions(&pkt),
+        vec![(2, Bytes::from_static(&[1, 2, 3]))]
+    );
+
+    // unless the source of the local stream is set
+    rewrites.set_source(2, 1);
+    rewrites.set_rid(2, "q");
+    stream
+        .write_rtp(&packet(1, &[(1, b"0"), (2, b"h")]))
+        .await?;
+    let pkt = timeout_or_fail(Duration::from_millis(10), stream.written_rtp())
+        .await
+        .expect("A written packet");
+    assert_eq!(
+        header_extensions(&pkt),
+        vec![
+            (4, Bytes::from_static(b"video")),
+            (5, Bytes::from_static(b"q")),
+        ]
+    );
+
+    // the source is forgotten once unbound
+    icpr.unbind_remote_stream(&StreamInfo {
+        ssrc: 1,
+        ..Default::default()
+    })
+    .await;
+    stream.write_rtp(&packet(1, &[(2, &[1, 2, 3])])).await?;
+    let pkt = timeout_or_fail(Duration::from_millis(10), stream.written_rtp())
+        .await
+        .expect("A written packet");
+    assert_eq!(
+        header_extensions(&pkt),
+        vec![(2, Bytes::from_static(&[1, 2, 3]))]
+    );
+
+    source.close().await?;
+    stream.close().await?;
+    Ok(())
+}
+
+#[test]
+fn test_rewrite_extensions() -> Result<()> {
+    let source = extensions(&[(SDES_MID_URI, 1), (AUDIO_LEVEL_URI, 2)]);
+    let local = extensions(&[(SDES_MID_URI, 1), (AUDIO_LEVEL_URI, 2)]);
+    let mut values = HashMap::new();
+
+    // unchanged
+    let pkt = packet(1, &[(1, b"0"), (2, &[30])]);
+    assert_eq!(
+        rewrite_extensions(&pkt.header, Some(&source), &local, &values)?,
+        None
+    );
+
+    // all stripped
+    let header =
+        rewrite_extensions(&pkt.header, Some(&source), &[], &values)?.expect("A rewritten header");
+    assert!(!header.extension);
+    assert!(header.extensions.is_empty());
+
+    // promoted to the two byte form for a MID too long for the one byte form
+    values.insert(SDES_MID_URI.to_owned(), Bytes::from(vec![b'm'; 20]));
+    let header = rewrite_extensions(&pkt.header, Some(&source), &local, &values)?
+        .expect("A rewritten header");
+    assert_eq!(
+        header.extension_profile,
+        rtp::header::EXTENSION_PROFILE_TWO_BYTE
+    );
+
+    // which marshals to the same header
+    let raw = header.marshal()?;
+    let unmarshaled = rtp::header::Header::unmarshal(&mut raw.clone())?;
+    assert_eq!(
+        unmarshaled.get_extension(1),
+        Some(Bytes::from(vec![b'm'; 20]))
+    );
+    assert_eq!(
+        unmarshaled.get_extension(2),
+        Some(Bytes::from_static(&[30]))
+    );
+    Ok(())
+}
+
+#[test]
+fn test_rewrite_extensions_strips_unmapped() -> Result<()> {
+    let source = extensions(&[(SDES_MID_URI, 1), (ABS_SEND_TIME_URI, 3)]);
+    let local = extensions(&[(SDES_MID_URI, 2), (AUDIO_LEVEL_URI, 3)]);
+    let values = HashMap::new();
+
+    // the extensions not negotiated for the local stream, and those of ids unknown to the
+    // source, are stripped
+    let pkt = packet(1, &[(1, b"0"), (3, &[1, 2, 3]), (7, &[9])]);
+    let header = rewrite_extensions(&pkt.header, Some(&source), &local, &values)?
+        .expect("A rewritten header");
+    let unmarshaled = rtp::header::Header::unmarshal(&mut header.marshal()?)?;
+    assert_eq!(unmarshaled.get_extension_ids(), vec![2]);
+    assert_eq!(unmarshaled.get_extension(2), Some(Bytes::from_static(b"0")));
+
+    // the ids of an unknown source are the local ones
+    let pkt = packet(1, &[(2, b"0"), (3, &[30]), (9, &[1])]);
+    let header =
+        rewrite_extensions(&pkt.header, None, &local, &values)?.expect("A rewritten header");
+    let mut ids = header.get_extension_ids();
+    ids.sort();
+    assert_eq!(ids, vec![2, 3]);
+
+    // the extensions which aren't of RFC 8285 are left alone
+    let mut header = rtp::header::Header {
+        extension: true,
+        extension_profile: 0x1234,
+        ..Default::default()
+    };
+    header.set_extension(0, Bytes::from_static(&[1, 2, 3, 4]))?;
+    assert_eq!(
+        rewrite_extensions(&header, Some(&source), &local, &values)?,
+        None
+    );
+    Ok(())
+}
+
+#[test]
+fn test_rewrite_extensions_switches_form() -> Result<()> {
+    let values = HashMap::new();
+
+    // from the two byte form of the source to the one byte form, all the local ids fitting it
+    let source = extensions(&[(SDES_MID_URI, 15), (AUDIO_LEVEL_URI, 16)]);
+    let local = extensions(&[(SDES_MID_URI, 1), (AUDIO_LEVEL_URI, 2)]);
+    let pkt = packet(1, &[(15, b"0"), (16, &[30])]);
+    assert_eq!(
+        pkt.header.extension_profile,
+        rtp::header::EXTENSION_PROFILE_TWO_BYTE
+    );
+    let header = rewrite_extensions(&pkt.header, Some(&source), &local, &values)?
+        .expect("A rewritten header");
+    assert_eq!(
+        header.extension_profile,
+        rtp::header::EXTENSION_PROFILE_ONE_BYTE
+    );
+    let raw = header.marshal()?;
+    assert_eq!(raw.len(), header.marshal_size());
+    let unmarshaled = rtp::header::Header::unmarshal(&mut raw.clone())?;
+    assert_eq!(unmarshaled.get_extension(1), Some(Bytes::from_static(b"0")));
+    assert_eq!(
+        unmarshaled.get_extension(2),
+        Some(Bytes::from_static(&[30]))
+    );
+
+    // and back, for a local id beyond the one byte form
+    let source = extensions(&[(SDES_MID_URI, 1), (AUDIO_LEVEL_URI, 2)]);
+    let local = extensions(&[(SDES_MID_URI, 1), (AUDIO_LEVEL_URI, 20)]);
+    let pkt = packet(1, &[(1, b"0"), (2, &[30])]);
+    assert_eq!(
+        pkt.header.extension_profile,
+        rtp::header::EXTENSION_PROFILE_ONE_BYTE
+    );
+    let header = rewrite_extensions(&pkt.header, Some(&source), &local, &values)?
+        .expect("A rewritten header");
+    assert_eq!(
+        header.extension_profile,
+        rtp::header::EXTENSION_PROFILE_TWO_BYTE
+    );
+    let raw = header.marshal()?;
+    assert_eq!(raw.len(), header.marshal_size());
+    let unmarshaled = rtp::header::Header::unmarshal(&mut raw.clone())?;
+    assert_eq!(unmarshaled.get_extension(1), Some(Bytes::from_static(b"0")));
+    assert_eq!(
+        unmarshaled.get_extension(20),
+        Some(Bytes::from_static(&[30]))
+    );
+    Ok(())
+}
@@ -0,0 +1,160 @@
+use bytes::Bytes;
+use util::Unmarshal;
+
+use super::*;
+
+const MEDIA_SSRC: u32 = 1;
+
+fn media_packet(sequence_number: u16, payload_size: usize) -> rtp::packet::Packet {
+    rtp::packet::Packet {
+        header: rtp::header::Header {
+            version: 2,
+            payload_type: 96,
+            sequence_number,
+            timestamp: 3000 + sequence_number as u32 / 3 * 3000,
+            ssrc: MEDIA_SSRC,
+            marker: sequence_number % 3 == 2,
+            ..Default::default()
+        },
+        payload: (0..payload_size)
+            .map(|i| (i * 7 + sequence_number as usize) as u8)
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// recover returns the media packet protected by fec which isn't received.
+fn recover(fec: &rtp::packet::Packet, received: &[rtp::packet::Packet]) -> rtp::packet::Packet {
+    let b = &fec.payload;
+    assert_eq!(b[0] & 0xC0, 0, "R and F should be zero");
+    assert_eq!(b[8], 1, "SSRCCount should be one");
+    assert_eq!(u32::from_be_bytes([b[12], b[13], b[14], b[15]]), MEDIA_SSRC);
+    let base_sequence_number = u16::from_be_bytes([b[16], b[17]]);
+
+    let mut offsets = vec![];
+    let chunk0 = u16::from_be_bytes([b[18], b[19]]);
+    offsets.extend((0..15).filter(|i| chunk0 & (1 << (14 - i)) != 0));
+    let mut header_size = 20;
+    if chunk0 & 0x8000 == 0 {
+        let chunk1 = u32::from_be_bytes([b[20], b[21], b[22], b[23]]);
+        offsets.extend(
+            (0..31)
+                .filter(|i| chunk1 & (1 << (30 - i)) != 0)
+                .map(|i| i + 15),
+        );
+        header_size = 24;
+        if chunk1 & 0x8000_0000 == 0 {
+            let chunk2 = u64::from_be_bytes(b[24..32].try_into().unwrap());
+            offsets.extend(
+                (0..63)
+                    .filter(|i| chunk2 & (1 << (62 - i)) != 0)
+                    .map(|i| i + 46),
+            );
+            header_size = 32;
+            assert_ne!(chunk2 & 0x8000_0000_0000_0000, 0);
+        }
+    }
+
+    let (mut first_bytes, mut length, mut timestamp) = (
+        [b[0], b[1]],
+        u16::from_be_bytes([b[2], b[3]]),
+        u32::from_be_bytes([b[4], b[5], b[6], b[7]]),
+    );
+    let mut payload = b[header_size..].to_vec();
+    let mut missing = vec![];
+    for offset in offsets {
+        let sequence_number = base_sequence_number.wrapping_add(offset as u16);
+        let pkt = match received
+            .iter()
+            .find(|p| p.header.sequence_number == sequence_number)
+        {
+            Some(pkt) => pkt,
+            None => {
+                missing.push(sequence_number);
+                continue;
+            }
+        };
+        let raw = pkt.marshal().unwrap();
+        first_bytes[0] ^= raw[0];
+        first_bytes[1] ^= raw[1];
+        length ^= (raw.len() - 12) as u16;
+        timestamp ^= pkt.header.timestamp;
+        for (p, b) in payload.iter_mut().zip(&raw[12..]) {
+            *p ^= b;
+        }
+    }
+    assert_eq!(missing.len(), 1, "a single packet should be missing");
+
+    let mut raw = vec![(first_bytes[0] & 0x3F) | 0x80, first_bytes[1]];
+    raw.extend_from_slice(&missing[0].to_be_bytes());
+    raw.extend_from_slice(&timestamp.to_be_bytes());
+    raw.extend_from_slice(&b[12..16]);
+    raw.extend_from_slice(&payload[..length as usize]);
+    rtp::packet::Packet::unmarshal(&mut Bytes::from(raw)).unwrap()
+}
+
+#[test]
+fn test_flexfec_encoder_recovers() -> Result<()> {
+    let mut encoder = FlexFecEncoder::new(120, 2);
+    // the sequence numbers wrap meanwhile
+    let media: Vec<rtp::packet::Packet> = (0..5u16)
+        .map(|i| media_packet(65_534u16.wrapping_add(i), 100 + i as usize * 30))
+        .collect();
+
+    let fec = encoder.encode(&media, 2)?;
+    assert_eq!(fec.len(), 2);
+    for pkt in &fec {
+        assert_eq!(pkt.header.ssrc, 2);
+        assert_eq!(pkt.header.payload_type, 120);
+        assert_eq!(pkt.header.timestamp, media[4].header.timestamp);
+    }
+    assert_eq!(
+        fec[1].header.sequence_number,
+        fec[0].header.sequence_number.wrapping_add(1)
+    );
+
+    // the media packets are interleaved between the FEC packets
+    for (i, fec) in fec.iter().enumerate() {
+        let protected: Vec<_> = media.iter().skip(i).step_by(2).cloned().collect();
+        for lost in 0..protected.len() {
+            let mut received = protected.clone();
+            let expected = received.remove(lost);
+            let recovered = recover(fec, &received);
+            assert_eq!(recovered.marshal()?, expected.marshal()?);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_flexfec_encoder_mask_chunks() -> Result<()> {
+    let mut encoder = FlexFecEncoder::new(120, 2);
+    for (offsets, header_size) in [
+        (vec![0u16, 14], 20),
+        (vec![0, 15, 45], 24),
+        (vec![0, 20, 108], 32),
+    ] {
+        let media: Vec<rtp::packet::Packet> = offsets
+            .iter()
+            .map(|offset| media_packet(1000 + offset, 50))
+            .collect();
+        let fec = encoder.encode(&media, 1)?;
+        assert_eq!(fec.len(), 1);
+        assert_eq!(fec[0].payload.len(), header_size + 50);
+
+        let recovered = recover(&fec[0], &media[1..]);
+        assert_eq!(recovered.marshal()?, media[0].marshal()?);
+        let last = media.len() - 1;
+        let recovered = recover(&fec[0], &media[..last]);
+        assert_eq!(recovered.marshal()?, media[last].marshal()?);
+    }
+
+    let media = vec![media_packet(0, 10), media_packet(109, 10)];
+    assert_eq!(
+        encoder.encode(&media, 1),
+        Err(Error::ErrTooManyProtectedPackets)
+    );
+
+    Ok(())
+}
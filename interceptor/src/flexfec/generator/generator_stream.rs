@@ -0,0 +1,123 @@
+use super::*;
+use crate::flexfec::MAX_PROTECTED_SPAN;
+
+/// Media packets protected together at most, even without the end of their frame.
+const MAX_GROUP_SIZE: usize = 48;
+/// Overhead sent per fraction of the packets lost, so that most of the losses are recovered.
+const LOSS_OVERHEAD_FACTOR: f64 = 2.0;
+
+struct GeneratorStreamState {
+    encoder: FlexFecEncoder,
+    group: Vec<rtp::packet::Packet>,
+    fraction_lost: f64,
+}
+
+impl GeneratorStreamState {
+    /// on_packet adds pkt to the group of packets to protect, and returns the FEC packets to
+    /// send once it ends a frame or the group is full.
+    fn on_packet(&mut self, pkt: &rtp::packet::Packet, overhead: f64) -> Vec<rtp::packet::Packet> {
+        let mut fec = vec![];
+        // packets too far from the first one of the group are protected in the next one
+        if let Some(first) = self.group.first() {
+            let span = pkt
+                .header
+                .sequence_number
+                .wrapping_sub(first.header.sequence_number) as usize;
+            if span >= MAX_PROTECTED_SPAN {
+                fec = self.encode(overhead, true);
+            }
+        }
+
+        self.group.push(pkt.clone());
+        let is_full = self.group.len() >= MAX_GROUP_SIZE;
+        if pkt.header.marker || is_full {
+            fec.extend(self.encode(overhead, is_full));
+        }
+        fec
+    }
+
+    /// encode returns the FEC packets protecting the group at overhead. Small groups whose
+    /// share is less than half a FEC packet are protected with the next frame, unless forced.
+    fn encode(&mut self, overhead: f64, force: bool) -> Vec<rtp::packet::Packet> {
+        if overhead <= 0.0 {
+            self.group.clear();
+            return vec![];
+        }
+        let mut num_fec_packets = (self.group.len() as f64 * overhead).round() as usize;
+        if num_fec_packets == 0 {
+            if !force {
+                return vec![];
+            }
+            num_fec_packets = 1;
+        }
+
+        let group = std::mem::take(&mut self.group);
+        match self.encoder.encode(&group, num_fec_packets) {
+            Ok(fec) => fec,
+            Err(err) => {
+                log::warn!("failed to generate FEC packets: {}", err);
+                vec![]
+            }
+        }
+    }
+}
+
+pub(super) struct GeneratorStream {
+    state: util::sync::Mutex<GeneratorStreamState>,
+    min_overhead: f64,
+    max_overhead: f64,
+    next_rtp_writer: Arc<dyn RTPWriter + Send + Sync>,
+}
+
+impl GeneratorStream {
+    pub(super) fn new(
+        encoder: FlexFecEncoder,
+        min_overhead: f64,
+        max_overhead: f64,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Self {
+        GeneratorStream {
+            state: util::sync::Mutex::new(GeneratorStreamState {
+                encoder,
+                group: vec![],
+                fraction_lost: 0.0,
+            }),
+            min_overhead,
+            max_overhead,
+            next_rtp_writer: writer,
+        }
+    }
+
+    /// set_fraction_lost sets the fraction of the packets lost the remote peer last reported.
+    pub(super) fn set_fraction_lost(&self, fraction_lost: f64) {
+        let mut state = self.state.lock();
+        state.fraction_lost = fraction_lost;
+    }
+
+    fn overhead(&self, fraction_lost: f64) -> f64 {
+        (fraction_lost * LOSS_OVERHEAD_FACTOR).clamp(self.min_overhead, self.max_overhead)
+    }
+}
+
+/// RTPWriter is used by Interceptor.bind_local_stream.
+#[async_trait]
+impl RTPWriter for GeneratorStream {
+    /// write a rtp packet, followed by the FEC packets protecting it if it ends a group
+    async fn write(&self, pkt: &rtp::packet::Packet, a: &Attributes) -> Result<usize> {
+        let n = self.next_rtp_writer.write(pkt, a).await?;
+
+        let fec = {
+            let mut state = self.state.lock();
+            let overhead = self.overhead(state.fraction_lost);
+            state.on_packet(pkt, overhead)
+        };
+        let attributes = Attributes::new();
+        for fec in &fec {
+            if let Err(err) = self.next_rtp_writer.write(fec, &attributes).await {
+                log::warn!("failed sending FEC packet: {}", err);
+            }
+        }
+
+        Ok(n)
+    }
+}
@@ -0,0 +1,114 @@
+use rtcp::reception_report::ReceptionReport;
+use tokio::time::Duration;
+
+use super::*;
+use crate::mock::mock_stream::MockStream;
+use crate::test::timeout_or_fail;
+
+const FEC_SSRC: u32 = 2;
+const FEC_PAYLOAD_TYPE: u8 = 120;
+
+async fn write_frame(stream: &MockStream, sequence_numbers: std::ops::Range<u16>) -> Result<()> {
+    let last = sequence_numbers.end - 1;
+    for sequence_number in sequence_numbers {
+        stream
+            .write_rtp(&rtp::packet::Packet {
+                header: rtp::header::Header {
+                    version: 2,
+                    ssrc: 1,
+                    payload_type: 96,
+                    sequence_number,
+                    marker: sequence_number == last,
+                    ..Default::default()
+                },
+                payload: vec![0xAB; 100].into(),
+                ..Default::default()
+            })
+            .await?;
+    }
+    Ok(())
+}
+
+async fn written_ssrcs(stream: &MockStream) -> Vec<u32> {
+    let mut ssrcs = vec![];
+    while let Ok(Some(pkt)) =
+        tokio::time::timeout(Duration::from_millis(10), stream.written_rtp()).await
+    {
+        ssrcs.push(pkt.header.ssrc);
+    }
+    ssrcs
+}
+
+#[tokio::test]
+async fn test_flexfec_generator_interceptor() -> Result<()> {
+    let icpr = Generator::builder()
+        .with_min_overhead(0.5)
+        .with_max_overhead(1.0)
+        .build("")?;
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            mime_type: "video/VP8".to_owned(),
+            ssrc_forward_error_correction: FEC_SSRC,
+            payload_type_forward_error_correction: FEC_PAYLOAD_TYPE,
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    write_frame(&stream, 0..4).await?;
+    let pkt = timeout_or_fail(Duration::from_millis(10), stream.written_rtp())
+        .await
+        .expect("A packet");
+    assert_eq!(pkt.header.sequence_number, 0);
+    assert_eq!(
+        written_ssrcs(&stream).await,
+        vec![1, 1, 1, FEC_SSRC, FEC_SSRC]
+    );
+
+    // more FEC packets are sent with the losses reported
+    stream
+        .receive_rtcp(vec![Box::new(ReceiverReport {
+            ssrc: 3,
+            reports: vec![ReceptionReport {
+                ssrc: 1,
+                fraction_lost: 128,
+                ..Default::default()
+            }],
+            ..Default::default()
+        })])
+        .await;
+    stream
+        .read_rtcp()
+        .await
+        .expect("the report should be read")?;
+
+    write_frame(&stream, 4..6).await?;
+    assert_eq!(written_ssrcs(&stream).await, vec![1, 1, FEC_SSRC, FEC_SSRC]);
+
+    stream.close().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_flexfec_generator_interceptor_without_fec_stream() -> Result<()> {
+    let icpr = Generator::builder().with_min_overhead(1.0).build("")?;
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            mime_type: "audio/opus".to_owned(),
+            ssrc_forward_error_correction: FEC_SSRC,
+            payload_type_forward_error_correction: FEC_PAYLOAD_TYPE,
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    write_frame(&stream, 0..2).await?;
+    assert_eq!(written_ssrcs(&stream).await, vec![1, 1]);
+
+    stream.close().await?;
+    Ok(())
+}
@@ -0,0 +1,200 @@
+mod generator_stream;
+#[cfg(test)]
+mod generator_test;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use generator_stream::GeneratorStream;
+use rtcp::receiver_report::ReceiverReport;
+use rtcp::reception_report::ReceptionReport;
+use rtcp::sender_report::SenderReport;
+use tokio::sync::Mutex;
+
+use crate::error::Result;
+use crate::flexfec::FlexFecEncoder;
+use crate::stream_info::StreamInfo;
+use crate::{
+    Attributes, Interceptor, InterceptorBuilder, RTCPReader, RTCPWriter, RTPReader, RTPWriter,
+};
+
+const DEFAULT_MIN_OVERHEAD: f64 = 0.05;
+const DEFAULT_MAX_OVERHEAD: f64 = 0.5;
+
+/// GeneratorBuilder can be used to configure FlexFEC Generator Interceptor
+#[derive(Default)]
+pub struct GeneratorBuilder {
+    min_overhead: Option<f64>,
+    max_overhead: Option<f64>,
+}
+
+impl GeneratorBuilder {
+    /// with_min_overhead sets the ratio of FEC packets to media packets sent without losses.
+    /// Zero doesn't send any until losses are reported.
+    pub fn with_min_overhead(mut self, overhead: f64) -> GeneratorBuilder {
+        self.min_overhead = Some(overhead);
+        self
+    }
+
+    /// with_max_overhead sets the ratio of FEC packets to media packets sent at most, however
+    /// high the losses reported. It can't be more than one.
+    pub fn with_max_overhead(mut self, overhead: f64) -> GeneratorBuilder {
+        self.max_overhead = Some(overhead);
+        self
+    }
+}
+
+impl InterceptorBuilder for GeneratorBuilder {
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        let max_overhead = self.max_overhead.unwrap_or(DEFAULT_MAX_OVERHEAD).min(1.0);
+        Ok(Arc::new(Generator {
+            internal: Arc::new(GeneratorInternal {
+                min_overhead: self
+                    .min_overhead
+                    .unwrap_or(DEFAULT_MIN_OVERHEAD)
+                    .min(max_overhead),
+                max_overhead,
+                streams: Mutex::new(HashMap::new()),
+            }),
+        }))
+    }
+}
+
+struct GeneratorInternal {
+    min_overhead: f64,
+    max_overhead: f64,
+    streams: Mutex<HashMap<u32, Arc<GeneratorStream>>>,
+}
+
+impl GeneratorInternal {
+    async fn on_reception_reports(&self, reports: &[ReceptionReport]) {
+        let streams = self.streams.lock().await;
+        for r in reports {
+            if let Some(stream) = streams.get(&r.ssrc) {
+                stream.set_fraction_lost(r.fraction_lost as f64 / 256.0);
+            }
+        }
+    }
+}
+
+pub struct GeneratorRtcpReader {
+    parent_rtcp_reader: Arc<dyn RTCPReader + Send + Sync>,
+    internal: Arc<GeneratorInternal>,
+}
+
+#[async_trait]
+impl RTCPReader for GeneratorRtcpReader {
+    async fn read(
+        &self,
+        buf: &mut [u8],
+        a: &Attributes,
+    ) -> Result<(Vec<Box<dyn rtcp::packet::Packet + Send + Sync>>, Attributes)> {
+        let (pkts, attr) = self.parent_rtcp_reader.read(buf, a).await?;
+        for p in &pkts {
+            let any = p.as_any();
+            if let Some(sr) = any.downcast_ref::<SenderReport>() {
+                self.internal.on_reception_reports(&sr.reports).await;
+            } else if let Some(rr) = any.downcast_ref::<ReceiverReport>() {
+                self.internal.on_reception_reports(&rr.reports).await;
+            }
+        }
+
+        Ok((pkts, attr))
+    }
+}
+
+/// Generator interceptor protects the outgoing video packets with FlexFEC packets, sent on
+/// the FEC stream of [`StreamInfo::ssrc_forward_error_correction`] of the streams which have
+/// one. The packets of each frame are protected once it is sent, by a number of FEC packets
+/// growing with the fraction of the packets lost the remote peer reports, between the min
+/// and the max overhead.
+pub struct Generator {
+    internal: Arc<GeneratorInternal>,
+}
+
+impl Generator {
+    /// builder returns a new GeneratorBuilder.
+    pub fn builder() -> GeneratorBuilder {
+        GeneratorBuilder::default()
+    }
+}
+
+#[async_trait]
+impl Interceptor for Generator {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        Arc::new(GeneratorRtcpReader {
+            parent_rtcp_reader: reader,
+            internal: Arc::clone(&self.internal),
+        })
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        writer
+    }
+
+    /// bind_local_stream returns a writer which sends the FEC packets protecting the packets
+    /// written, for the video streams with a FEC stream.
+    async fn bind_local_stream(
+        &self,
+        info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        if info.ssrc_forward_error_correction == 0
+            || info.payload_type_forward_error_correction == 0
+            || !info.mime_type.to_lowercase().starts_with("video/")
+        {
+            return writer;
+        }
+
+        let stream = Arc::new(GeneratorStream::new(
+            FlexFecEncoder::new(
+                info.payload_type_forward_error_correction,
+                info.ssrc_forward_error_correction,
+            ),
+            self.internal.min_overhead,
+            self.internal.max_overhead,
+            writer,
+        ));
+        {
+            let mut streams = self.internal.streams.lock().await;
+            streams.insert(info.ssrc, Arc::clone(&stream));
+        }
+
+        stream
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, info: &StreamInfo) {
+        let mut streams = self.internal.streams.lock().await;
+        streams.remove(&info.ssrc);
+    }
+
+    /// bind_remote_stream lets you modify any incoming RTP packets. It is called once for per RemoteStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_remote_stream(
+        &self,
+        _info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        reader
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, _info: &StreamInfo) {}
+
+    /// close closes the Interceptor, cleaning up any data if necessary.
+    async fn close(&self) -> Result<()> {
+        Ok(())
+    }
+}
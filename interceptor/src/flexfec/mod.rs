@@ -0,0 +1,153 @@
+#[cfg(test)]
+mod flexfec_test;
+
+pub mod generator;
+
+use bytes::{BufMut, BytesMut};
+use util::Marshal;
+
+use crate::error::{Error, Result};
+
+/// Number of sequence numbers the media packets protected by a FEC packet may span, from
+/// its base sequence number, with the largest mask.
+pub const MAX_PROTECTED_SPAN: usize = 109;
+
+const RTP_HEADER_SIZE: usize = 12;
+/// Offsets from the base sequence number the first and the second mask chunks can protect.
+const MASK_CHUNK0_SPAN: usize = 15;
+const MASK_CHUNK1_SPAN: usize = 46;
+
+/// FlexFecEncoder generates the FlexFEC packets protecting the media packets of a stream,
+/// sent on their own SSRC, in the format of draft 03 used by libwebrtc, with a flexible mask.
+///
+/// Each FEC packet is the XOR of the media packets it protects, which can recover any one of
+/// them lost.
+///
+/// ## Specifications
+///
+/// * [draft-ietf-payload-flexible-fec-scheme-03]
+///
+/// [draft-ietf-payload-flexible-fec-scheme-03]: https://datatracker.ietf.org/doc/html/draft-ietf-payload-flexible-fec-scheme-03
+#[derive(Debug, Clone)]
+pub struct FlexFecEncoder {
+    payload_type: u8,
+    ssrc: u32,
+    sequence_number: u16,
+}
+
+impl FlexFecEncoder {
+    /// new returns an encoder of FEC packets with payload_type sent on ssrc.
+    pub fn new(payload_type: u8, ssrc: u32) -> Self {
+        FlexFecEncoder {
+            payload_type,
+            ssrc,
+            sequence_number: rand::random::<u16>(),
+        }
+    }
+
+    /// encode returns num_fec_packets FEC packets protecting the media packets, all of the
+    /// same stream. The media packets are interleaved between them, so that a burst of
+    /// num_fec_packets losses can be recovered. The media packets may span
+    /// [`MAX_PROTECTED_SPAN`] sequence numbers at most, from the first one.
+    pub fn encode(
+        &mut self,
+        media: &[rtp::packet::Packet],
+        num_fec_packets: usize,
+    ) -> Result<Vec<rtp::packet::Packet>> {
+        let first = match media.first() {
+            Some(first) => first,
+            None => return Ok(vec![]),
+        };
+        let base_sequence_number = first.header.sequence_number;
+        let mut protected = Vec::with_capacity(media.len());
+        for pkt in media {
+            let offset = pkt
+                .header
+                .sequence_number
+                .wrapping_sub(base_sequence_number) as usize;
+            if offset >= MAX_PROTECTED_SPAN {
+                return Err(Error::ErrTooManyProtectedPackets);
+            }
+            protected.push((offset, pkt.marshal()?));
+        }
+
+        let num_fec_packets = num_fec_packets.min(media.len());
+        let mut fec_packets = Vec::with_capacity(num_fec_packets);
+        for i in 0..num_fec_packets {
+            let group: Vec<_> = protected.iter().skip(i).step_by(num_fec_packets).collect();
+
+            let (mut first_bytes, mut length, mut timestamp) = ([0u8; 2], 0u16, 0u32);
+            let mut payload = vec![];
+            let mut offsets = Vec::with_capacity(group.len());
+            for (offset, raw) in group {
+                offsets.push(*offset);
+                first_bytes[0] ^= raw[0];
+                first_bytes[1] ^= raw[1];
+                length ^= (raw.len() - RTP_HEADER_SIZE) as u16;
+                timestamp ^= u32::from_be_bytes([raw[4], raw[5], raw[6], raw[7]]);
+                if payload.len() < raw.len() - RTP_HEADER_SIZE {
+                    payload.resize(raw.len() - RTP_HEADER_SIZE, 0);
+                }
+                for (p, b) in payload.iter_mut().zip(&raw[RTP_HEADER_SIZE..]) {
+                    *p ^= b;
+                }
+            }
+
+            let mut fec = BytesMut::with_capacity(32 + payload.len());
+            // R and F are zero, for a flexible mask
+            fec.put_u8(first_bytes[0] & 0x3F);
+            fec.put_u8(first_bytes[1]);
+            fec.put_u16(length);
+            fec.put_u32(timestamp);
+            // SSRCCount and reserved
+            fec.put_u32(1 << 24);
+            fec.put_u32(first.header.ssrc);
+            fec.put_u16(base_sequence_number);
+            put_mask(&mut fec, &offsets);
+            fec.put_slice(&payload);
+
+            fec_packets.push(rtp::packet::Packet {
+                header: rtp::header::Header {
+                    version: 2,
+                    payload_type: self.payload_type,
+                    sequence_number: self.sequence_number,
+                    timestamp: media[media.len() - 1].header.timestamp,
+                    ssrc: self.ssrc,
+                    ..Default::default()
+                },
+                payload: fec.freeze(),
+                ..Default::default()
+            });
+            self.sequence_number = self.sequence_number.wrapping_add(1);
+        }
+
+        Ok(fec_packets)
+    }
+}
+
+/// put_mask writes the mask of the offsets protected, from the base sequence number, in as
+/// few chunks as possible. The first bit of each chunk, k, is set on the last one.
+fn put_mask(buf: &mut BytesMut, offsets: &[usize]) {
+    let (mut chunk0, mut chunk1, mut chunk2) = (0u16, 0u32, 0u64);
+    for &offset in offsets {
+        if offset < MASK_CHUNK0_SPAN {
+            chunk0 |= 1 << (14 - offset);
+        } else if offset < MASK_CHUNK1_SPAN {
+            chunk1 |= 1 << (30 - (offset - MASK_CHUNK0_SPAN));
+        } else {
+            chunk2 |= 1 << (62 - (offset - MASK_CHUNK1_SPAN));
+        }
+    }
+
+    let last = offsets.iter().max().copied().unwrap_or_default();
+    if last < MASK_CHUNK0_SPAN {
+        buf.put_u16(0x8000 | chunk0);
+    } else if last < MASK_CHUNK1_SPAN {
+        buf.put_u16(chunk0);
+        buf.put_u32(0x8000_0000 | chunk1);
+    } else {
+        buf.put_u16(chunk0);
+        buf.put_u32(chunk1);
+        buf.put_u64(0x8000_0000_0000_0000 | chunk2);
+    }
+}
@@ -0,0 +1,407 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use util::MarshalSize;
+
+use super::*;
+use crate::keyframe::is_keyframe_start;
+
+/// Window the bitrates of the layers are measured over. The layers without packets in it
+/// aren't available.
+const LAYER_BITRATE_WINDOW: Duration = Duration::from_secs(1);
+/// Clock rate of the timestamps of the video streams without one.
+const DEFAULT_CLOCK_RATE: u32 = 90_000;
+
+#[derive(Default)]
+struct LayerBitrate {
+    packets: VecDeque<(Instant, usize)>,
+    bytes: usize,
+}
+
+impl LayerBitrate {
+    fn add(&mut self, size: usize, now: Instant) {
+        self.packets.push_back((now, size));
+        self.bytes += size;
+        while let Some(&(at, size)) = self.packets.front() {
+            if now.duration_since(at) <= LAYER_BITRATE_WINDOW {
+                break;
+            }
+            self.packets.pop_front();
+            self.bytes -= size;
+        }
+    }
+
+    /// bitrate returns the bitrate of the layer over the window, if it is available at now.
+    fn bitrate(&self, now: Instant) -> Option<u64> {
+        match self.packets.back() {
+            Some(&(at, _)) if now.duration_since(at) <= LAYER_BITRATE_WINDOW => {
+                Some(self.bytes as u64 * 8 * 1000 / LAYER_BITRATE_WINDOW.as_millis() as u64)
+            }
+            _ => None,
+        }
+    }
+}
+
+struct ForwarderStreamState {
+    layers: Vec<LayerBitrate>,
+    /// Layer forwarded.
+    current: Option<usize>,
+    /// Layer a keyframe was requested for, to switch to it.
+    requested: Option<usize>,
+    /// Sequence number of the keyframe the current layer was switched at.
+    switch_sequence_number: u16,
+    sequence_number_offset: u16,
+    timestamp_offset: u32,
+    /// Sequence number and timestamp of the last packet forwarded, and when it was.
+    last_forwarded: Option<(u16, u32, Instant)>,
+}
+
+impl ForwarderStreamState {
+    /// target_layer returns the highest layer available within target_bitrate, or the lowest
+    /// layer available if none is.
+    fn target_layer(&self, target_bitrate: Option<u64>, now: Instant) -> Option<usize> {
+        let available: Vec<(usize, u64)> = self
+            .layers
+            .iter()
+            .enumerate()
+            .filter_map(|(layer, b)| b.bitrate(now).map(|bitrate| (layer, bitrate)))
+            .collect();
+        let within = available
+            .iter()
+            .rev()
+            .find(|&&(_, bitrate)| target_bitrate.is_none_or(|target| bitrate <= target));
+        within.or(available.first()).map(|&(layer, _)| layer)
+    }
+
+    /// switch switches to layer at pkt, rewriting its sequence number and timestamp to follow
+    /// those of the last packet forwarded.
+    fn switch(&mut self, layer: usize, pkt: &rtp::packet::Packet, clock_rate: u32, now: Instant) {
+        let (sequence_number, timestamp) = (pkt.header.sequence_number, pkt.header.timestamp);
+        match self.last_forwarded {
+            Some((last_sequence_number, last_timestamp, last_at)) => {
+                let elapsed = now.duration_since(last_at).as_micros() as u64;
+                let ticks = (elapsed * clock_rate as u64 / 1_000_000).max(1) as u32;
+                self.sequence_number_offset =
+                    sequence_number.wrapping_sub(last_sequence_number.wrapping_add(1));
+                self.timestamp_offset = timestamp.wrapping_sub(last_timestamp.wrapping_add(ticks));
+            }
+            None => {
+                self.sequence_number_offset = 0;
+                self.timestamp_offset = 0;
+            }
+        }
+        self.current = Some(layer);
+        self.requested = None;
+        self.switch_sequence_number = sequence_number;
+    }
+}
+
+pub(super) struct ForwarderStream {
+    ssrc: u32,
+    mime_type: String,
+    clock_rate: u32,
+    state: util::sync::Mutex<ForwarderStreamState>,
+    targets: Arc<ForwarderTargets>,
+    on_keyframe_request: Option<OnLayerKeyframeFn>,
+    next_rtp_writer: Arc<dyn RTPWriter + Send + Sync>,
+}
+
+impl ForwarderStream {
+    pub(super) fn new(
+        info: &StreamInfo,
+        targets: Arc<ForwarderTargets>,
+        on_keyframe_request: Option<OnLayerKeyframeFn>,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Self {
+        ForwarderStream {
+            ssrc: info.ssrc,
+            mime_type: info.mime_type.clone(),
+            clock_rate: if info.clock_rate == 0 {
+                DEFAULT_CLOCK_RATE
+            } else {
+                info.clock_rate
+            },
+            state: util::sync::Mutex::new(ForwarderStreamState {
+                layers: vec![],
+                current: None,
+                requested: None,
+                switch_sequence_number: 0,
+                sequence_number_offset: 0,
+                timestamp_offset: 0,
+                last_forwarded: None,
+            }),
+            targets,
+            on_keyframe_request,
+            next_rtp_writer: writer,
+        }
+    }
+
+    /// forward returns pkt of layer rewritten if it is forwarded, and the layer to request a
+    /// keyframe of to switch to it, if any.
+    fn forward(
+        &self,
+        pkt: &rtp::packet::Packet,
+        layer: usize,
+        now: Instant,
+    ) -> (Option<rtp::packet::Packet>, Option<usize>) {
+        let target_bitrate = self.targets.target_bitrate(self.ssrc);
+        let mut state = self.state.lock();
+        if state.layers.len() <= layer {
+            state.layers.resize_with(layer + 1, LayerBitrate::default);
+        }
+        state.layers[layer].add(pkt.payload.len() + pkt.header.marshal_size(), now);
+
+        let mut keyframe_request = None;
+        let target = state.target_layer(target_bitrate, now);
+        if target.is_some() && target != state.current {
+            if target == Some(layer) && is_keyframe_start(&self.mime_type, &pkt.payload) {
+                state.switch(layer, pkt, self.clock_rate, now);
+                self.targets.set_layer(self.ssrc, state.current);
+            } else if state.requested != target {
+                state.requested = target;
+                keyframe_request = target;
+            }
+        }
+
+        if state.current != Some(layer) {
+            return (None, keyframe_request);
+        }
+        let sequence_number = pkt.header.sequence_number;
+        if (sequence_number.wrapping_sub(state.switch_sequence_number) as i16) < 0 {
+            // before the keyframe switched at
+            return (None, keyframe_request);
+        }
+
+        let mut forwarded = pkt.clone();
+        forwarded.header.ssrc = self.ssrc;
+        forwarded.header.sequence_number =
+            sequence_number.wrapping_sub(state.sequence_number_offset);
+        forwarded.header.timestamp = pkt.header.timestamp.wrapping_sub(state.timestamp_offset);
+        let is_newer = match state.last_forwarded {
+            Some((last, _, _)) => (forwarded.header.sequence_number.wrapping_sub(last) as i16) > 0,
+            None => true,
+        };
+        if is_newer {
+            state.last_forwarded = Some((
+                forwarded.header.sequence_number,
+                forwarded.header.timestamp,
+                now,
+            ));
+        }
+
+        (Some(forwarded), keyframe_request)
+    }
+}
+
+/// RTPWriter is used by Interceptor.bind_local_stream.
+#[async_trait]
+impl RTPWriter for ForwarderStream {
+    /// write forwards a rtp packet if it is of the layer forwarded
+    async fn write(&self, pkt: &rtp::packet::Packet, a: &Attributes) -> Result<usize> {
+        let layer = a.get(&ATTR_SIMULCAST_LAYER).copied().unwrap_or_default();
+        let (forwarded, keyframe_request) = self.forward(pkt, layer, Instant::now());
+
+        if let (Some(layer), Some(f)) = (keyframe_request, &self.on_keyframe_request) {
+            f(self.ssrc, layer).await;
+        }
+
+        match forwarded {
+            Some(forwarded) => self.next_rtp_writer.write(&forwarded, a).await,
+            None => Ok(0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SSRC: u32 = 10;
+
+    struct NoopWriter;
+
+    #[async_trait]
+    impl RTPWriter for NoopWriter {
+        async fn write(&self, _pkt: &rtp::packet::Packet, _a: &Attributes) -> Result<usize> {
+            Ok(0)
+        }
+    }
+
+    fn new_stream(targets: Arc<ForwarderTargets>) -> ForwarderStream {
+        ForwarderStream::new(
+            &StreamInfo {
+                ssrc: SSRC,
+                mime_type: "video/VP8".to_owned(),
+                clock_rate: 90_000,
+                ..Default::default()
+            },
+            targets,
+            None,
+            Arc::new(NoopWriter),
+        )
+    }
+
+    fn packet(sequence_number: u16, timestamp: u32, keyframe: bool) -> rtp::packet::Packet {
+        rtp::packet::Packet {
+            header: rtp::header::Header {
+                ssrc: 100,
+                sequence_number,
+                timestamp,
+                ..Default::default()
+            },
+            payload: vec![0x10, if keyframe { 0x00 } else { 0x01 }, 0, 0].into(),
+            ..Default::default()
+        }
+    }
+
+    /// forward returns the sequence number and timestamp pkt is forwarded with, if it is,
+    /// and the layer requested a keyframe of.
+    fn forward(
+        stream: &ForwarderStream,
+        layer: usize,
+        (sequence_number, timestamp, keyframe): (u16, u32, bool),
+        now: Instant,
+    ) -> (Option<(u16, u32)>, Option<usize>) {
+        let (forwarded, keyframe_request) =
+            stream.forward(&packet(sequence_number, timestamp, keyframe), layer, now);
+        let forwarded = forwarded.map(|p| {
+            assert_eq!(p.header.ssrc, SSRC);
+            (p.header.sequence_number, p.header.timestamp)
+        });
+        (forwarded, keyframe_request)
+    }
+
+    #[test]
+    fn test_forwarder_stream_switches_at_keyframes() {
+        let targets = Arc::new(ForwarderTargets::default());
+        let stream = new_stream(Arc::clone(&targets));
+        let start = Instant::now();
+        let at = |millis: u64| start + Duration::from_millis(millis);
+
+        // the first layer is forwarded from its first keyframe
+        assert_eq!(
+            forward(&stream, 0, (99, 1000, false), at(0)),
+            (None, Some(0))
+        );
+        assert_eq!(
+            forward(&stream, 0, (100, 1000, true), at(0)),
+            (Some((100, 1000)), None)
+        );
+        assert_eq!(targets.layer(SSRC), Some(0));
+
+        // a higher layer is requested a keyframe of once, and the current layer is forwarded
+        // until it arrives
+        assert_eq!(
+            forward(&stream, 1, (500, 50_000, false), at(10)),
+            (None, Some(1))
+        );
+        assert_eq!(
+            forward(&stream, 1, (501, 50_000, false), at(10)),
+            (None, None)
+        );
+        assert_eq!(
+            forward(&stream, 0, (101, 4000, false), at(33)),
+            (Some((101, 4000)), None)
+        );
+        assert_eq!(targets.layer(SSRC), Some(0));
+
+        // switched to at its keyframe, which follows the last packet forwarded, 33ms later,
+        // and the lower layer isn't forwarded anymore
+        assert_eq!(
+            forward(&stream, 1, (502, 60_000, true), at(66)),
+            (Some((102, 6970)), None)
+        );
+        assert_eq!(
+            forward(&stream, 0, (102, 7000, false), at(66)),
+            (None, None)
+        );
+        assert_eq!(
+            forward(&stream, 1, (503, 63_000, false), at(99)),
+            (Some((103, 9970)), None)
+        );
+        assert_eq!(targets.layer(SSRC), Some(1));
+        // nor the packets of the layer before its keyframe
+        assert_eq!(
+            forward(&stream, 1, (501, 50_000, false), at(99)),
+            (None, None)
+        );
+
+        // back to the lower layer once the higher one stops, at its next keyframe
+        assert_eq!(
+            forward(&stream, 0, (160, 200_000, false), at(1200)),
+            (None, Some(0))
+        );
+        assert_eq!(
+            forward(&stream, 0, (161, 200_000, true), at(1200)),
+            (Some((104, 9970 + 99_090)), None)
+        );
+        assert_eq!(
+            forward(&stream, 0, (162, 203_000, false), at(1233)),
+            (Some((105, 9970 + 102_090)), None)
+        );
+        assert_eq!(targets.layer(SSRC), Some(0));
+    }
+
+    #[test]
+    fn test_forwarder_stream_target_bitrate() {
+        let targets = Arc::new(ForwarderTargets::default());
+        let stream = new_stream(Arc::clone(&targets));
+        let start = Instant::now();
+
+        // 16 bytes a packet, 128 bps per packet over the window
+        assert_eq!(
+            forward(&stream, 0, (1, 0, true), start),
+            (Some((1, 0)), None)
+        );
+        assert_eq!(forward(&stream, 1, (10, 0, false), start), (None, Some(1)));
+        targets.set_target_bitrate(SSRC, 100);
+        // the lowest layer is forwarded when none is within the target bitrate
+        assert_eq!(forward(&stream, 1, (11, 0, true), start), (None, None));
+        assert_eq!(
+            forward(&stream, 0, (2, 3000, false), start),
+            (Some((2, 3000)), None)
+        );
+        targets.set_target_bitrate(SSRC, 1000);
+        assert_eq!(
+            forward(&stream, 1, (12, 0, true), start),
+            (Some((3, 3001)), None)
+        );
+        assert_eq!(targets.layer(SSRC), Some(1));
+    }
+
+    #[test]
+    fn test_forwarder_stream_rewrites_across_wraparound() {
+        let targets = Arc::new(ForwarderTargets::default());
+        let stream = new_stream(Arc::clone(&targets));
+        let start = Instant::now();
+        let at = |millis: u64| start + Duration::from_millis(millis);
+
+        assert_eq!(
+            forward(&stream, 0, (65_534, u32::MAX - 1000, true), at(0)),
+            (Some((65_534, u32::MAX - 1000)), None)
+        );
+        assert_eq!(
+            forward(&stream, 0, (65_535, u32::MAX - 100, false), at(10)),
+            (Some((65_535, u32::MAX - 100)), None)
+        );
+        // 10ms later, 900 ticks after the last timestamp forwarded
+        forward(&stream, 1, (30_000, 5_000, false), at(15));
+        assert_eq!(
+            forward(&stream, 1, (30_001, 5_000, true), at(20)),
+            (Some((0, 799)), None)
+        );
+        assert_eq!(
+            forward(&stream, 1, (30_002, 8_000, false), at(53)),
+            (Some((1, 3799)), None)
+        );
+        // the packets reordered are rewritten too
+        assert_eq!(
+            forward(&stream, 1, (30_001, 5_000, false), at(60)),
+            (Some((0, 799)), None)
+        );
+        assert_eq!(
+            forward(&stream, 1, (30_003, 8_000, false), at(60)),
+            (Some((2, 3799)), None)
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use tokio::sync::mpsc;
+use tokio::time::Duration;
+
+use super::*;
+use crate::mock::mock_stream::MockStream;
+use crate::test::timeout_or_fail;
+
+const SSRC: u32 = 10;
+
+/// VP8 payloads of size bytes, starting a keyframe or not.
+fn vp8_payload(keyframe: bool, size: usize) -> bytes::Bytes {
+    let mut payload = vec![0u8; size];
+    payload[0] = 0x10;
+    payload[1] = if keyframe { 0x00 } else { 0x01 };
+    payload.into()
+}
+
+async fn write_layer(
+    stream: &MockStream,
+    layer: usize,
+    sequence_number: u16,
+    timestamp: u32,
+    keyframe: bool,
+    size: usize,
+) -> Result<()> {
+    let mut attributes = Attributes::new();
+    attributes.insert(ATTR_SIMULCAST_LAYER, layer);
+    stream
+        .write_rtp_with_attributes(
+            &rtp::packet::Packet {
+                header: rtp::header::Header {
+                    ssrc: 100 + layer as u32,
+                    sequence_number,
+                    timestamp,
+                    ..Default::default()
+                },
+                payload: vp8_payload(keyframe, size),
+                ..Default::default()
+            },
+            &attributes,
+        )
+        .await?;
+    Ok(())
+}
+
+async fn written(stream: &MockStream) -> Vec<(u16, u32)> {
+    let mut written = vec![];
+    while let Ok(Some(pkt)) =
+        tokio::time::timeout(Duration::from_millis(10), stream.written_rtp()).await
+    {
+        assert_eq!(pkt.header.ssrc, SSRC);
+        written.push((pkt.header.sequence_number, pkt.header.timestamp));
+    }
+    written
+}
+
+async fn forwarder_stream(builder: ForwarderBuilder) -> Result<Arc<MockStream>> {
+    let icpr = builder.build("")?;
+    Ok(MockStream::new(
+        &StreamInfo {
+            ssrc: SSRC,
+            mime_type: "video/VP8".to_owned(),
+            clock_rate: 90_000,
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await)
+}
+
+#[tokio::test]
+async fn test_forwarder_interceptor_switches_at_keyframes() -> Result<()> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let builder = Forwarder::builder().with_on_keyframe_request(Arc::new(move |ssrc, layer| {
+        let _ = tx.send((ssrc, layer));
+        Box::pin(async {})
+    }));
+    let targets = builder.targets();
+    let stream = forwarder_stream(builder).await?;
+
+    // nothing is forwarded before a keyframe
+    write_layer(&stream, 0, 99, 1000, false, 100).await?;
+    assert_eq!(rx.try_recv(), Ok((SSRC, 0)));
+    write_layer(&stream, 0, 100, 1000, true, 100).await?;
+    assert_eq!(written(&stream).await, vec![(100, 1000)]);
+    assert_eq!(targets.layer(SSRC), Some(0));
+
+    // the highest layer is forwarded without a target bitrate, from its next keyframe
+    write_layer(&stream, 1, 500, 70_000, false, 1000).await?;
+    write_layer(&stream, 1, 501, 70_000, false, 1000).await?;
+    assert_eq!(rx.try_recv(), Ok((SSRC, 1)));
+    assert!(rx.try_recv().is_err(), "a keyframe is requested once");
+    write_layer(&stream, 0, 101, 4000, false, 100).await?;
+    assert_eq!(written(&stream).await, vec![(101, 4000)]);
+
+    write_layer(&stream, 1, 502, 73_000, true, 1000).await?;
+    write_layer(&stream, 0, 102, 7000, false, 100).await?;
+    write_layer(&stream, 1, 503, 73_000, false, 1000).await?;
+    write_layer(&stream, 1, 501, 70_000, false, 1000).await?;
+    let w = written(&stream).await;
+    assert_eq!(targets.layer(SSRC), Some(1));
+    // the sequence numbers and timestamps follow those forwarded before
+    assert_eq!(w.len(), 2, "{w:?}");
+    assert_eq!((w[0].0, w[1].0), (102, 103));
+    assert!(w[0].1 > 4000 && w[0].1 < 4000 + 9000, "{w:?}");
+    assert_eq!(w[0].1, w[1].1);
+
+    stream.close().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_forwarder_interceptor_target_bitrate() -> Result<()> {
+    let builder = Forwarder::builder();
+    let targets = builder.targets();
+    targets.set_target_bitrate(SSRC, 20_000);
+    let stream = forwarder_stream(builder).await?;
+
+    write_layer(&stream, 0, 1, 0, true, 100).await?;
+    // 9.7 kbps, then 19.4 kbps, within the target bitrate
+    write_layer(&stream, 1, 50, 0, true, 1200).await?;
+    write_layer(&stream, 1, 51, 0, false, 1200).await?;
+    let w = written(&stream).await;
+    assert_eq!(
+        w.iter().map(|&(s, _)| s).collect::<Vec<u16>>(),
+        vec![1, 2, 3],
+        "{w:?}"
+    );
+    // the timestamps still increase across the switch
+    assert!(w[1].1 > w[0].1 && w[2].1 == w[1].1, "{w:?}");
+    assert_eq!(targets.layer(SSRC), Some(1));
+
+    // above the target, the lower layer is forwarded from its next keyframe
+    write_layer(&stream, 1, 52, 3000, false, 1200).await?;
+    write_layer(&stream, 0, 2, 3000, false, 100).await?;
+    write_layer(&stream, 0, 3, 6000, true, 100).await?;
+    write_layer(&stream, 1, 53, 6000, false, 1200).await?;
+    let w = written(&stream).await;
+    assert_eq!(
+        w.iter().map(|&(s, _)| s).collect::<Vec<u16>>(),
+        vec![4, 5],
+        "{w:?}"
+    );
+    assert_eq!(targets.layer(SSRC), Some(0));
+
+    let pkt = timeout_or_fail(Duration::from_millis(10), async {
+        write_layer(&stream, 0, 4, 9000, false, 100).await.ok();
+        stream.written_rtp().await
+    })
+    .await
+    .expect("A packet");
+    assert_eq!(pkt.header.sequence_number, 6);
+
+    stream.close().await?;
+    Ok(())
+}
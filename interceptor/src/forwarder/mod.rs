@@ -0,0 +1,189 @@
+mod forwarder_stream;
+#[cfg(test)]
+mod forwarder_test;
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use forwarder_stream::ForwarderStream;
+
+use crate::error::Result;
+use crate::stream_info::StreamInfo;
+use crate::{
+    Attributes, Interceptor, InterceptorBuilder, RTCPReader, RTCPWriter, RTPReader, RTPWriter,
+};
+
+/// Attribute of the packets written to a local stream with the layer they are of, from zero
+/// for the lowest. The packets without it are of the lowest layer.
+pub const ATTR_SIMULCAST_LAYER: usize = 0x51A7E;
+
+/// OnLayerKeyframeFn is called with the SSRC of a local stream and a layer when a keyframe of
+/// the layer is needed to switch to it, e.g. to send a PLI to the publisher of the layer.
+pub type OnLayerKeyframeFn = Arc<
+    dyn (Fn(u32, usize) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>)
+        + Send
+        + Sync
+        + 'static,
+>;
+
+#[derive(Debug, Default, Copy, Clone)]
+struct ForwarderTarget {
+    target_bitrate: Option<u64>,
+    layer: Option<usize>,
+}
+
+/// ForwarderTargets sets the target bitrates of the local streams of a Forwarder, and reports
+/// the layers they forward.
+#[derive(Default)]
+pub struct ForwarderTargets {
+    targets: util::sync::Mutex<HashMap<u32, ForwarderTarget>>,
+}
+
+impl ForwarderTargets {
+    /// set_target_bitrate sets the bitrate, in bits per second, the layer forwarded to the
+    /// local stream ssrc is chosen for, e.g. from the estimate of its subscriber. The highest
+    /// layer is forwarded without a target bitrate.
+    pub fn set_target_bitrate(&self, ssrc: u32, target_bitrate: u64) {
+        let mut targets = self.targets.lock();
+        targets.entry(ssrc).or_default().target_bitrate = Some(target_bitrate);
+    }
+
+    /// layer returns the layer forwarded to the local stream ssrc, if any yet.
+    pub fn layer(&self, ssrc: u32) -> Option<usize> {
+        let targets = self.targets.lock();
+        targets.get(&ssrc).and_then(|t| t.layer)
+    }
+
+    fn target_bitrate(&self, ssrc: u32) -> Option<u64> {
+        let targets = self.targets.lock();
+        targets.get(&ssrc).and_then(|t| t.target_bitrate)
+    }
+
+    fn set_layer(&self, ssrc: u32, layer: Option<usize>) {
+        let mut targets = self.targets.lock();
+        targets.entry(ssrc).or_default().layer = layer;
+    }
+
+    fn remove(&self, ssrc: u32) {
+        let mut targets = self.targets.lock();
+        targets.remove(&ssrc);
+    }
+}
+
+/// ForwarderBuilder can be used to configure Forwarder Interceptor
+#[derive(Default)]
+pub struct ForwarderBuilder {
+    on_keyframe_request: Option<OnLayerKeyframeFn>,
+    targets: Arc<ForwarderTargets>,
+}
+
+impl ForwarderBuilder {
+    /// with_on_keyframe_request sets the handler called when a keyframe of a layer is needed
+    /// to switch to it.
+    pub fn with_on_keyframe_request(mut self, f: OnLayerKeyframeFn) -> ForwarderBuilder {
+        self.on_keyframe_request = Some(f);
+        self
+    }
+
+    /// targets returns the target bitrates of the local streams of the interceptors built,
+    /// which can be set before or after they are built.
+    pub fn targets(&self) -> Arc<ForwarderTargets> {
+        Arc::clone(&self.targets)
+    }
+}
+
+impl InterceptorBuilder for ForwarderBuilder {
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        Ok(Arc::new(Forwarder {
+            on_keyframe_request: self.on_keyframe_request.clone(),
+            targets: Arc::clone(&self.targets),
+        }))
+    }
+}
+
+/// Forwarder interceptor forwards a single layer of the simulcast layers written to each
+/// local video stream, the packets of which have the [`ATTR_SIMULCAST_LAYER`] attribute: the
+/// highest layer whose bitrate is within the target bitrate of the stream, set with
+/// [`ForwarderTargets`]. It's what an SFU forwards the layers received from a publisher to a
+/// subscriber through.
+///
+/// The layers are switched at their keyframes, which are requested when needed, and the
+/// sequence numbers and timestamps of the packets forwarded are rewritten, so that they are
+/// continuous across the switches. The payloads, e.g. VP8 picture IDs, aren't rewritten.
+pub struct Forwarder {
+    on_keyframe_request: Option<OnLayerKeyframeFn>,
+    targets: Arc<ForwarderTargets>,
+}
+
+impl Forwarder {
+    /// builder returns a new ForwarderBuilder.
+    pub fn builder() -> ForwarderBuilder {
+        ForwarderBuilder::default()
+    }
+}
+
+#[async_trait]
+impl Interceptor for Forwarder {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        reader
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        writer
+    }
+
+    /// bind_local_stream returns a writer which forwards a single layer of the packets
+    /// written, for the video streams.
+    async fn bind_local_stream(
+        &self,
+        info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        if !info.mime_type.to_lowercase().starts_with("video/") {
+            return writer;
+        }
+
+        Arc::new(ForwarderStream::new(
+            info,
+            Arc::clone(&self.targets),
+            self.on_keyframe_request.clone(),
+            writer,
+        ))
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, info: &StreamInfo) {
+        self.targets.remove(info.ssrc);
+    }
+
+    /// bind_remote_stream lets you modify any incoming RTP packets. It is called once for per RemoteStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_remote_stream(
+        &self,
+        _info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        reader
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, _info: &StreamInfo) {}
+
+    /// close closes the Interceptor, cleaning up any data if necessary.
+    async fn close(&self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,195 @@
+use bytes::{Bytes, BytesMut};
+use rtp::codecs::vp8::{Vp8Packet, Vp8Payloader};
+use rtp::packetizer::{Depacketizer, Payloader};
+
+use crate::error::Result;
+use crate::frame::FrameCodec;
+use crate::Attributes;
+
+/// FrameAssembler reassembles the frames of a stream from its packets, and packetizes them
+/// again once transformed. The sequence numbers of the packets of the frames packetized
+/// follow each other, leaving the gaps of the frames dropped.
+pub(crate) struct FrameAssembler {
+    codec: FrameCodec,
+    vp8_payloader: Vp8Payloader,
+    /// Packets of the frame being reassembled, in sequence number order.
+    packets: Vec<(rtp::packet::Packet, Attributes)>,
+    /// Sequence number of the last packet of the last frame packetized or skipped.
+    last_sequence_number: Option<u16>,
+    /// Sequence number of the next packet packetized.
+    next_sequence_number: Option<u16>,
+}
+
+impl FrameAssembler {
+    pub(crate) fn new(codec: FrameCodec) -> Self {
+        let mut vp8_payloader = Vp8Payloader::default();
+        vp8_payloader.enable_picture_id = true;
+        FrameAssembler {
+            codec,
+            vp8_payloader,
+            packets: vec![],
+            last_sequence_number: None,
+            next_sequence_number: None,
+        }
+    }
+
+    /// push adds a packet, and returns the packets of the frame it completes, if any. The
+    /// packets older than the frame being reassembled are dropped, as is the frame when a
+    /// packet of a newer one is pushed before it is complete.
+    pub(crate) fn push(
+        &mut self,
+        pkt: rtp::packet::Packet,
+        attributes: Attributes,
+    ) -> Option<Vec<(rtp::packet::Packet, Attributes)>> {
+        let sequence_number = pkt.header.sequence_number;
+        if let Some(last) = self.last_sequence_number {
+            if (sequence_number.wrapping_sub(last) as i16) <= 0 {
+                return None;
+            }
+        }
+        if self.codec == FrameCodec::Audio {
+            return Some(vec![(pkt, attributes)]);
+        }
+
+        if let Some((first, _)) = self.packets.first() {
+            let diff = pkt.header.timestamp.wrapping_sub(first.header.timestamp) as i32;
+            if diff < 0 {
+                return None;
+            } else if diff > 0 {
+                self.packets.clear();
+            }
+        }
+        let first_sequence_number = self
+            .packets
+            .first()
+            .map_or(sequence_number, |(p, _)| p.header.sequence_number);
+        let offset = |s: u16| s.wrapping_sub(first_sequence_number) as i16;
+        let index = match self
+            .packets
+            .binary_search_by_key(&offset(sequence_number), |(p, _)| {
+                offset(p.header.sequence_number)
+            }) {
+            Ok(_) => return None,
+            Err(index) => index,
+        };
+        self.packets.insert(index, (pkt, attributes));
+
+        let (first, _) = &self.packets[0];
+        let (last, _) = &self.packets[self.packets.len() - 1];
+        let span = last
+            .header
+            .sequence_number
+            .wrapping_sub(first.header.sequence_number) as usize;
+        let is_complete = last.header.marker
+            && span + 1 == self.packets.len()
+            && Vp8Packet::default().is_partition_head(&first.payload);
+        if is_complete {
+            Some(std::mem::take(&mut self.packets))
+        } else {
+            None
+        }
+    }
+
+    /// depacketize returns the frame of its packets.
+    pub(crate) fn depacketize(
+        &self,
+        packets: &[(rtp::packet::Packet, Attributes)],
+    ) -> Result<Bytes> {
+        match self.codec {
+            FrameCodec::Vp8 => {
+                let mut frame = BytesMut::new();
+                for (pkt, _) in packets {
+                    frame.extend_from_slice(&Vp8Packet::default().depacketize(&pkt.payload)?);
+                }
+                Ok(frame.freeze())
+            }
+            FrameCodec::Audio => Ok(packets
+                .first()
+                .map(|(pkt, _)| pkt.payload.clone())
+                .unwrap_or_default()),
+        }
+    }
+
+    /// packetize returns frame packetized in place of the packets of the frame, in payloads
+    /// of mtu bytes at most. Each packet takes the header and the attributes of the packet of
+    /// the frame at the same index, or of its last packet.
+    pub(crate) fn packetize(
+        &mut self,
+        packets: &[(rtp::packet::Packet, Attributes)],
+        frame: &Bytes,
+        mtu: usize,
+    ) -> Result<Vec<(rtp::packet::Packet, Attributes)>> {
+        let payloads = match self.codec {
+            FrameCodec::Vp8 => self.vp8_payloader.payload(mtu, frame)?,
+            FrameCodec::Audio => vec![frame.clone()],
+        };
+        let (first, last) = match (packets.first(), packets.last()) {
+            (Some((first, _)), Some((last, _))) => {
+                (first.header.sequence_number, last.header.sequence_number)
+            }
+            _ => return Ok(vec![]),
+        };
+        let mut sequence_number = match (self.last_sequence_number, self.next_sequence_number) {
+            (Some(last), Some(next)) => next.wrapping_add(first.wrapping_sub(last.wrapping_add(1))),
+            _ => first,
+        };
+
+        let num_payloads = payloads.len();
+        let mut packetized = Vec::with_capacity(num_payloads);
+        for (i, payload) in payloads.into_iter().enumerate() {
+            let (pkt, attributes) = &packets[i.min(packets.len() - 1)];
+            let mut header = pkt.header.clone();
+            header.sequence_number = sequence_number;
+            if self.codec != FrameCodec::Audio {
+                header.marker = i + 1 == num_payloads;
+            }
+            packetized.push((
+                rtp::packet::Packet {
+                    header,
+                    payload,
+                    ..Default::default()
+                },
+                attributes.clone(),
+            ));
+            sequence_number = sequence_number.wrapping_add(1);
+        }
+
+        self.last_sequence_number = Some(last);
+        self.next_sequence_number = Some(sequence_number);
+        Ok(packetized)
+    }
+
+    /// forward returns the packets of a frame unchanged but for their sequence numbers, which
+    /// follow those of the last frame packetized, as packetize does.
+    pub(crate) fn forward(
+        &mut self,
+        packets: Vec<(rtp::packet::Packet, Attributes)>,
+    ) -> Vec<(rtp::packet::Packet, Attributes)> {
+        let last = match packets.last() {
+            Some((last, _)) => last.header.sequence_number,
+            None => return packets,
+        };
+        let offset = match (self.last_sequence_number, self.next_sequence_number) {
+            (Some(last), Some(next)) => next.wrapping_sub(last.wrapping_add(1)),
+            _ => 0,
+        };
+
+        self.last_sequence_number = Some(last);
+        self.next_sequence_number = Some(last.wrapping_add(offset).wrapping_add(1));
+        packets
+            .into_iter()
+            .map(|(mut pkt, attributes)| {
+                pkt.header.sequence_number = pkt.header.sequence_number.wrapping_add(offset);
+                (pkt, attributes)
+            })
+            .collect()
+    }
+
+    /// skip skips the packets of a frame dropped, so that the sequence numbers of the next
+    /// frame packetized follow those of the last one, without a gap.
+    pub(crate) fn skip(&mut self, packets: &[(rtp::packet::Packet, Attributes)]) {
+        if let Some((last, _)) = packets.last() {
+            self.last_sequence_number = Some(last.header.sequence_number);
+        }
+    }
+}
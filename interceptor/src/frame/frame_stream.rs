@@ -0,0 +1,196 @@
+use std::collections::VecDeque;
+
+use tokio::sync::Mutex;
+
+use super::*;
+use crate::frame::assembler::FrameAssembler;
+
+/// FrameStream reassembles the frames of a stream and passes them through the frame
+/// interceptors, for both a FrameWriter and a FrameReader.
+struct FrameStream {
+    mime_type: String,
+    codec: FrameCodec,
+    frame_interceptors: FrameInterceptors,
+    mtu: usize,
+}
+
+impl FrameStream {
+    /// transform returns the packets of the frame of packets once transformed by the frame
+    /// interceptors, none if it is dropped.
+    async fn transform(
+        &self,
+        assembler: &mut FrameAssembler,
+        packets: Vec<(rtp::packet::Packet, Attributes)>,
+        local: bool,
+    ) -> Result<Vec<(rtp::packet::Packet, Attributes)>> {
+        let data = match assembler.depacketize(&packets) {
+            Ok(data) => data,
+            Err(err) => {
+                assembler.skip(&packets);
+                return Err(err);
+            }
+        };
+        let header = match packets.first() {
+            Some((pkt, _)) => &pkt.header,
+            None => return Ok(vec![]),
+        };
+        let mut frame = Some(Frame {
+            ssrc: header.ssrc,
+            payload_type: header.payload_type,
+            timestamp: header.timestamp,
+            mime_type: self.mime_type.clone(),
+            codec: self.codec,
+            keyframe: self.codec.is_keyframe(&data),
+            data: data.clone(),
+        });
+
+        for frame_interceptor in self.frame_interceptors.iter() {
+            let transformed = match frame.take() {
+                Some(frame) if local => frame_interceptor.transform_local_frame(frame).await,
+                Some(frame) => frame_interceptor.transform_remote_frame(frame).await,
+                None => break,
+            };
+            match transformed {
+                Ok(transformed) => frame = transformed,
+                Err(err) => {
+                    assembler.skip(&packets);
+                    return Err(err);
+                }
+            }
+        }
+
+        match frame {
+            Some(frame) if frame.data == data => Ok(assembler.forward(packets)),
+            Some(frame) => assembler.packetize(&packets, &frame.data, self.mtu),
+            None => {
+                assembler.skip(&packets);
+                Ok(vec![])
+            }
+        }
+    }
+}
+
+pub(super) struct FrameWriter {
+    stream: FrameStream,
+    assembler: Mutex<FrameAssembler>,
+    next_rtp_writer: Arc<dyn RTPWriter + Send + Sync>,
+}
+
+impl FrameWriter {
+    pub(super) fn new(
+        info: &StreamInfo,
+        codec: FrameCodec,
+        frame_interceptors: FrameInterceptors,
+        mtu: usize,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Self {
+        FrameWriter {
+            stream: FrameStream {
+                mime_type: info.mime_type.clone(),
+                codec,
+                frame_interceptors,
+                mtu,
+            },
+            assembler: Mutex::new(FrameAssembler::new(codec)),
+            next_rtp_writer: writer,
+        }
+    }
+}
+
+/// RTPWriter is used by Interceptor.bind_local_stream.
+#[async_trait]
+impl RTPWriter for FrameWriter {
+    /// write buffers a rtp packet until its frame is complete, and writes the packets of the
+    /// frame transformed.
+    async fn write(&self, pkt: &rtp::packet::Packet, a: &Attributes) -> Result<usize> {
+        let transformed = {
+            let mut assembler = self.assembler.lock().await;
+            let packets = match assembler.push(pkt.clone(), a.clone()) {
+                Some(packets) => packets,
+                None => return Ok(0),
+            };
+            self.stream.transform(&mut assembler, packets, true).await?
+        };
+
+        let mut n = 0;
+        for (pkt, attributes) in &transformed {
+            n += self.next_rtp_writer.write(pkt, attributes).await?;
+        }
+        Ok(n)
+    }
+}
+
+struct FrameReaderState {
+    assembler: FrameAssembler,
+    /// Packets of the frames transformed, not read yet.
+    transformed: VecDeque<(rtp::packet::Packet, Attributes)>,
+}
+
+pub(super) struct FrameReader {
+    ssrc: u32,
+    stream: FrameStream,
+    state: Mutex<FrameReaderState>,
+    parent_rtp_reader: Arc<dyn RTPReader + Send + Sync>,
+}
+
+impl FrameReader {
+    pub(super) fn new(
+        info: &StreamInfo,
+        codec: FrameCodec,
+        frame_interceptors: FrameInterceptors,
+        mtu: usize,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Self {
+        FrameReader {
+            ssrc: info.ssrc,
+            stream: FrameStream {
+                mime_type: info.mime_type.clone(),
+                codec,
+                frame_interceptors,
+                mtu,
+            },
+            state: Mutex::new(FrameReaderState {
+                assembler: FrameAssembler::new(codec),
+                transformed: VecDeque::new(),
+            }),
+            parent_rtp_reader: reader,
+        }
+    }
+}
+
+/// RTPReader is used by Interceptor.bind_remote_stream.
+#[async_trait]
+impl RTPReader for FrameReader {
+    /// read returns the next packet of the frames transformed, reading the packets of the next
+    /// frame first if none is left.
+    async fn read(
+        &self,
+        buf: &mut [u8],
+        a: &Attributes,
+    ) -> Result<(rtp::packet::Packet, Attributes)> {
+        loop {
+            {
+                let mut state = self.state.lock().await;
+                if let Some(transformed) = state.transformed.pop_front() {
+                    return Ok(transformed);
+                }
+            }
+
+            let (pkt, attr) = self.parent_rtp_reader.read(buf, a).await?;
+            let mut state = self.state.lock().await;
+            let state = &mut *state;
+            let packets = match state.assembler.push(pkt, attr) {
+                Some(packets) => packets,
+                None => continue,
+            };
+            match self
+                .stream
+                .transform(&mut state.assembler, packets, false)
+                .await
+            {
+                Ok(transformed) => state.transformed.extend(transformed),
+                Err(err) => log::debug!("dropped frame of ssrc {}: {}", self.ssrc, err),
+            }
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use rtp::codecs::vp8::Vp8Packet;
+use rtp::packetizer::Depacketizer;
+use tokio::time::Duration;
+
+use super::*;
+use crate::mock::mock_stream::MockStream;
+use crate::test::timeout_or_fail;
+
+/// vp8_packets returns the packets of a VP8 frame of frame_size bytes, in payloads of 100
+/// bytes, a keyframe or not.
+fn vp8_packets(
+    first_sequence_number: u16,
+    timestamp: u32,
+    frame_size: usize,
+    keyframe: bool,
+) -> Vec<rtp::packet::Packet> {
+    let mut frame: Vec<u8> = (0..frame_size).map(|i| (i as u8) << 1).collect();
+    frame[0] |= !keyframe as u8;
+    let chunks: Vec<&[u8]> = frame.chunks(100).collect();
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut payload = vec![if i == 0 { 0x10 } else { 0x00 }];
+            payload.extend_from_slice(chunk);
+            rtp::packet::Packet {
+                header: rtp::header::Header {
+                    ssrc: 1,
+                    sequence_number: first_sequence_number.wrapping_add(i as u16),
+                    timestamp,
+                    marker: i + 1 == chunks.len(),
+                    ..Default::default()
+                },
+                payload: payload.into(),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+async fn written(stream: &MockStream) -> Vec<rtp::packet::Packet> {
+    let mut written = vec![];
+    while let Ok(Some(pkt)) =
+        tokio::time::timeout(Duration::from_millis(10), stream.written_rtp()).await
+    {
+        written.push(pkt);
+    }
+    written
+}
+
+/// Recorder records the frames, drops the delta frames of the timestamp drop, and reverses
+/// the data of the frames of the timestamp reverse.
+#[derive(Default)]
+struct Recorder {
+    frames: util::sync::Mutex<Vec<Frame>>,
+    drop: u32,
+    reverse: u32,
+}
+
+impl Recorder {
+    fn transform(&self, mut frame: Frame) -> Option<Frame> {
+        self.frames.lock().push(frame.clone());
+        if frame.timestamp == self.drop && !frame.keyframe {
+            return None;
+        }
+        if frame.timestamp == self.reverse {
+            let mut data = frame.data.to_vec();
+            data.reverse();
+            frame.data = data.into();
+        }
+        Some(frame)
+    }
+}
+
+#[async_trait]
+impl FrameInterceptor for Recorder {
+    async fn transform_local_frame(&self, frame: Frame) -> Result<Option<Frame>> {
+        Ok(self.transform(frame))
+    }
+}
+
+#[tokio::test]
+async fn test_frame_layer_interceptor_local_stream() -> Result<()> {
+    let recorder = Arc::new(Recorder {
+        drop: 6000,
+        reverse: 9000,
+        ..Default::default()
+    });
+    let icpr = FrameLayer::builder()
+        .with_frame_interceptor(Arc::clone(&recorder) as Arc<dyn FrameInterceptor + Send + Sync>)
+        .build("")?;
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            mime_type: "video/VP8".to_owned(),
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    let frames = [
+        vp8_packets(10, 3000, 250, true),
+        vp8_packets(13, 6000, 150, false),
+        vp8_packets(15, 9000, 80, false),
+        vp8_packets(16, 12000, 150, false),
+    ];
+    for pkt in frames.iter().flatten() {
+        stream.write_rtp(pkt).await?;
+    }
+    let pkts = written(&stream).await;
+
+    // the unchanged frames as they are, but for the gap of the frame dropped
+    assert_eq!(pkts[..3], frames[0][..]);
+    assert_eq!(
+        pkts[4..]
+            .iter()
+            .map(|p| (
+                p.header.sequence_number,
+                p.header.timestamp,
+                p.payload.clone()
+            ))
+            .collect::<Vec<_>>(),
+        frames[3]
+            .iter()
+            .map(|p| (
+                p.header.sequence_number - 2,
+                p.header.timestamp,
+                p.payload.clone()
+            ))
+            .collect::<Vec<_>>()
+    );
+    // and the frame changed packetized again
+    assert_eq!(
+        (pkts[3].header.sequence_number, pkts[3].header.timestamp),
+        (13, 9000)
+    );
+    let mut reversed = frames[2][0].payload[1..].to_vec();
+    reversed.reverse();
+    assert_eq!(
+        Vp8Packet::default().depacketize(&pkts[3].payload)?,
+        Bytes::from(reversed)
+    );
+
+    let recorded = recorder.frames.lock().clone();
+    assert_eq!(
+        recorded
+            .iter()
+            .map(|f| (f.timestamp, f.keyframe, f.data.len(), f.codec))
+            .collect::<Vec<_>>(),
+        vec![
+            (3000, true, 250, FrameCodec::Vp8),
+            (6000, false, 150, FrameCodec::Vp8),
+            (9000, false, 80, FrameCodec::Vp8),
+            (12000, false, 150, FrameCodec::Vp8),
+        ]
+    );
+
+    stream.close().await?;
+    Ok(())
+}
+
+/// Muter replaces the data of the remote audio frames with silence.
+struct Muter;
+
+#[async_trait]
+impl FrameInterceptor for Muter {
+    async fn transform_remote_frame(&self, mut frame: Frame) -> Result<Option<Frame>> {
+        frame.data = vec![0xff; frame.data.len()].into();
+        Ok(Some(frame))
+    }
+}
+
+#[tokio::test]
+async fn test_frame_layer_interceptor_remote_stream() -> Result<()> {
+    let icpr = FrameLayer::builder()
+        .with_frame_interceptor(Arc::new(Muter))
+        .build("")?;
+    let audio = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            mime_type: "audio/PCMU".to_owned(),
+            ..Default::default()
+        },
+        Arc::clone(&icpr),
+    )
+    .await;
+    let video = MockStream::new(
+        &StreamInfo {
+            ssrc: 2,
+            mime_type: "video/H264".to_owned(),
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    for (stream, ssrc) in [(&audio, 1), (&video, 2)] {
+        stream
+            .receive_rtp(rtp::packet::Packet {
+                header: rtp::header::Header {
+                    ssrc,
+                    sequence_number: 7,
+                    ..Default::default()
+                },
+                payload: Bytes::from_static(&[1, 2, 3]),
+                ..Default::default()
+            })
+            .await;
+    }
+    let pkt = timeout_or_fail(Duration::from_millis(10), audio.read_rtp())
+        .await
+        .expect("A read packet")?;
+    assert_eq!(
+        (pkt.header.sequence_number, pkt.payload),
+        (7, Bytes::from_static(&[0xff; 3]))
+    );
+    // the frames of the codecs not supported are read as they are
+    let pkt = timeout_or_fail(Duration::from_millis(10), video.read_rtp())
+        .await
+        .expect("A read packet")?;
+    assert_eq!(pkt.payload, Bytes::from_static(&[1, 2, 3]));
+
+    audio.close().await?;
+    video.close().await?;
+    Ok(())
+}
+
+#[test]
+fn test_frame_codec() {
+    assert_eq!(
+        FrameCodec::from_mime_type("video/VP8"),
+        Some(FrameCodec::Vp8)
+    );
+    assert_eq!(
+        FrameCodec::from_mime_type("audio/opus"),
+        Some(FrameCodec::Audio)
+    );
+    assert_eq!(FrameCodec::from_mime_type("video/H264"), None);
+    assert!(FrameCodec::Vp8.is_keyframe(&[0x10, 0x02]));
+    assert!(!FrameCodec::Vp8.is_keyframe(&[0x11, 0x02]));
+    assert!(!FrameCodec::Vp8.is_keyframe(&[]));
+    assert!(FrameCodec::Audio.is_keyframe(&[0x11]));
+}
@@ -0,0 +1,215 @@
+pub(crate) mod assembler;
+mod frame_stream;
+#[cfg(test)]
+mod frame_test;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use frame_stream::{FrameReader, FrameWriter};
+
+use crate::error::Result;
+use crate::stream_info::StreamInfo;
+use crate::{
+    Attributes, Interceptor, InterceptorBuilder, RTCPReader, RTCPWriter, RTPReader, RTPWriter,
+};
+
+/// Size, in bytes, of the payloads of the packets of the frames changed by default.
+const DEFAULT_MTU: usize = 1200;
+
+/// FrameCodec is a codec whose frames can be reassembled from their packets.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FrameCodec {
+    /// VP8, the frames of which end with the packet with the marker bit.
+    Vp8,
+    /// Audio codecs, each packet of which is a frame.
+    Audio,
+}
+
+impl FrameCodec {
+    /// from_mime_type returns the codec of the streams of mime_type, if their frames can be
+    /// reassembled.
+    pub fn from_mime_type(mime_type: &str) -> Option<Self> {
+        let mime_type = mime_type.to_lowercase();
+        if mime_type == "video/vp8" {
+            Some(FrameCodec::Vp8)
+        } else if mime_type.starts_with("audio/") {
+            Some(FrameCodec::Audio)
+        } else {
+            None
+        }
+    }
+
+    /// is_keyframe returns whether frame can be decoded on its own, as every audio frame can.
+    pub fn is_keyframe(&self, frame: &[u8]) -> bool {
+        match self {
+            // the P bit of the VP8 frame tag is unset for the keyframes
+            FrameCodec::Vp8 => frame.first().map(|b| b & 0x01 == 0).unwrap_or(false),
+            FrameCodec::Audio => true,
+        }
+    }
+}
+
+/// Frame is a frame of a stream, reassembled from its packets, with the metadata of its codec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub ssrc: u32,
+    pub payload_type: u8,
+    /// RTP timestamp of the packets of the frame.
+    pub timestamp: u32,
+    pub mime_type: String,
+    pub codec: FrameCodec,
+    pub keyframe: bool,
+    /// Data of the frame, without the payload headers of its packets.
+    pub data: Bytes,
+}
+
+/// FrameInterceptor is the frame granular counterpart of Interceptor, for the features working
+/// on whole frames rather than packets, e.g. encryption, layer filtering or recording. Its
+/// hooks are called with the frames reassembled from the packets of the streams, and return
+/// the frame transformed, or None to drop it. They leave the frames unchanged by default.
+///
+/// Only the data of the frames returned is used: their packets keep their headers but for
+/// their sequence numbers, rewritten so that they follow each other across the frames
+/// dropped, resized, or unchanged.
+#[async_trait]
+pub trait FrameInterceptor {
+    /// transform_local_frame is called with each frame written to a local stream, once all
+    /// its packets are written.
+    async fn transform_local_frame(&self, frame: Frame) -> Result<Option<Frame>> {
+        Ok(Some(frame))
+    }
+
+    /// transform_remote_frame is called with each frame read from a remote stream, once all
+    /// its packets are read.
+    async fn transform_remote_frame(&self, frame: Frame) -> Result<Option<Frame>> {
+        Ok(Some(frame))
+    }
+}
+
+/// FrameInterceptors are the frame interceptors of a FrameLayer, each frame going through them
+/// in the order they were added.
+pub(crate) type FrameInterceptors = Arc<Vec<Arc<dyn FrameInterceptor + Send + Sync>>>;
+
+/// FrameLayerBuilder can be used to configure FrameLayer Interceptor
+#[derive(Default)]
+pub struct FrameLayerBuilder {
+    frame_interceptors: Vec<Arc<dyn FrameInterceptor + Send + Sync>>,
+    mtu: Option<usize>,
+}
+
+impl FrameLayerBuilder {
+    /// with_frame_interceptor adds a frame interceptor, called after those added before.
+    pub fn with_frame_interceptor(
+        mut self,
+        frame_interceptor: Arc<dyn FrameInterceptor + Send + Sync>,
+    ) -> FrameLayerBuilder {
+        self.frame_interceptors.push(frame_interceptor);
+        self
+    }
+
+    /// with_mtu sets the size, in bytes, of the payloads of the packets of the frames changed
+    /// by the frame interceptors, which are packetized again.
+    pub fn with_mtu(mut self, mtu: usize) -> FrameLayerBuilder {
+        self.mtu = Some(mtu);
+        self
+    }
+}
+
+impl InterceptorBuilder for FrameLayerBuilder {
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        Ok(Arc::new(FrameLayer {
+            frame_interceptors: Arc::new(self.frame_interceptors.clone()),
+            mtu: self.mtu.unwrap_or(DEFAULT_MTU),
+        }))
+    }
+}
+
+/// FrameLayer interceptor reassembles the frames of the streams of the codecs of
+/// [`FrameCodec`] from their packets, lets its [`FrameInterceptor`]s transform or drop them,
+/// and packetizes them again. The packets of the frames left unchanged are passed on as they
+/// are, while the frames changed are packetized with payload headers of their own. The
+/// packets of the frames never complete, e.g. of a packet lost, are dropped, and those of the
+/// other codecs passed on.
+pub struct FrameLayer {
+    frame_interceptors: FrameInterceptors,
+    mtu: usize,
+}
+
+impl FrameLayer {
+    /// builder returns a new FrameLayerBuilder.
+    pub fn builder() -> FrameLayerBuilder {
+        FrameLayerBuilder::default()
+    }
+}
+
+#[async_trait]
+impl Interceptor for FrameLayer {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        reader
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        writer
+    }
+
+    /// bind_local_stream returns a writer which passes the frames written through the frame
+    /// interceptors, for the streams of the codecs supported.
+    async fn bind_local_stream(
+        &self,
+        info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        match FrameCodec::from_mime_type(&info.mime_type) {
+            Some(codec) if !self.frame_interceptors.is_empty() => Arc::new(FrameWriter::new(
+                info,
+                codec,
+                Arc::clone(&self.frame_interceptors),
+                self.mtu,
+                writer,
+            )),
+            _ => writer,
+        }
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, _info: &StreamInfo) {}
+
+    /// bind_remote_stream returns a reader which passes the frames read through the frame
+    /// interceptors, for the streams of the codecs supported.
+    async fn bind_remote_stream(
+        &self,
+        info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        match FrameCodec::from_mime_type(&info.mime_type) {
+            Some(codec) if !self.frame_interceptors.is_empty() => Arc::new(FrameReader::new(
+                info,
+                codec,
+                Arc::clone(&self.frame_interceptors),
+                self.mtu,
+                reader,
+            )),
+            _ => reader,
+        }
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, _info: &StreamInfo) {}
+
+    /// close closes the Interceptor, cleaning up any data if necessary.
+    async fn close(&self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::VecDeque;
+
+use super::*;
+use crate::error::Error;
+use crate::frame::assembler::FrameAssembler;
+use crate::frame_encryption::{decrypt_frame, frame_key_index, unencrypted_bytes};
+
+struct DecryptorStreamState {
+    assembler: FrameAssembler,
+    /// Packets of the frames decrypted, not read yet.
+    decrypted: VecDeque<(rtp::packet::Packet, Attributes)>,
+}
+
+pub(super) struct DecryptorStream {
+    ssrc: u32,
+    mime_type: String,
+    key: Option<DecryptionKeyFn>,
+    state: util::sync::Mutex<DecryptorStreamState>,
+    parent_rtp_reader: Arc<dyn RTPReader + Send + Sync>,
+}
+
+impl DecryptorStream {
+    pub(super) fn new(
+        info: &StreamInfo,
+        codec: FrameCodec,
+        key: Option<DecryptionKeyFn>,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Self {
+        DecryptorStream {
+            ssrc: info.ssrc,
+            mime_type: info.mime_type.clone(),
+            key,
+            state: util::sync::Mutex::new(DecryptorStreamState {
+                assembler: FrameAssembler::new(codec),
+                decrypted: VecDeque::new(),
+            }),
+            parent_rtp_reader: reader,
+        }
+    }
+
+    /// decrypt returns the packets of the frame of packets decrypted.
+    fn decrypt(
+        &self,
+        assembler: &mut FrameAssembler,
+        packets: &[(rtp::packet::Packet, Attributes)],
+    ) -> Result<Vec<(rtp::packet::Packet, Attributes)>> {
+        let encrypted = assembler.depacketize(packets)?;
+        let key_index = frame_key_index(&encrypted).ok_or(Error::ErrFrameDecryption)?;
+        let key = self
+            .key
+            .as_ref()
+            .and_then(|f| f(self.ssrc, key_index))
+            .ok_or(Error::ErrNoFrameKey)?;
+        let unencrypted = unencrypted_bytes(&self.mime_type, &encrypted);
+        let frame = decrypt_frame(&key, &encrypted, unencrypted)?;
+
+        let mtu = packets
+            .iter()
+            .map(|(pkt, _)| pkt.payload.len())
+            .max()
+            .unwrap_or_default();
+        assembler.packetize(packets, &frame, mtu)
+    }
+}
+
+/// RTPReader is used by Interceptor.bind_remote_stream.
+#[async_trait]
+impl RTPReader for DecryptorStream {
+    /// read returns the next packet of the frames decrypted, reading the packets of the next
+    /// frame first if none is left.
+    async fn read(
+        &self,
+        buf: &mut [u8],
+        a: &Attributes,
+    ) -> Result<(rtp::packet::Packet, Attributes)> {
+        loop {
+            {
+                let mut state = self.state.lock();
+                if let Some(decrypted) = state.decrypted.pop_front() {
+                    return Ok(decrypted);
+                }
+            }
+
+            let (pkt, attr) = self.parent_rtp_reader.read(buf, a).await?;
+            let mut state = self.state.lock();
+            let packets = match state.assembler.push(pkt, attr) {
+                Some(packets) => packets,
+                None => continue,
+            };
+            match self.decrypt(&mut state.assembler, &packets) {
+                Ok(decrypted) => state.decrypted.extend(decrypted),
+                Err(err) => log::debug!("dropped frame of ssrc {}: {}", self.ssrc, err),
+            }
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use bytes::{Bytes, BytesMut};
+use rtp::codecs::vp8::{Vp8Packet, Vp8Payloader};
+use rtp::packetizer::{Depacketizer, Payloader};
+use tokio::time::Duration;
+
+use super::*;
+use crate::frame_encryption::{encrypt_frame, IV_LENGTH};
+use crate::mock::mock_stream::MockStream;
+
+const KEY: [u8; 16] = [7; 16];
+
+/// encrypted_packets returns the packets of a VP8 keyframe of frame_size bytes encrypted with
+/// the key of key_index, in payloads of 100 bytes.
+fn encrypted_packets(
+    first_sequence_number: u16,
+    timestamp: u32,
+    frame_size: usize,
+    key_index: u8,
+) -> Vec<rtp::packet::Packet> {
+    let frame: Vec<u8> = (0..frame_size).map(|i| (i as u8) & 0xFE).collect();
+    let iv = [timestamp as u8; IV_LENGTH];
+    let encrypted = encrypt_frame(&KEY, key_index, &iv, &frame, 10).unwrap();
+    let payloads = Vp8Payloader::default().payload(100, &encrypted).unwrap();
+    let num_payloads = payloads.len();
+    payloads
+        .into_iter()
+        .enumerate()
+        .map(|(i, payload)| rtp::packet::Packet {
+            header: rtp::header::Header {
+                ssrc: 1,
+                sequence_number: first_sequence_number.wrapping_add(i as u16),
+                timestamp,
+                marker: i + 1 == num_payloads,
+                ..Default::default()
+            },
+            payload,
+            ..Default::default()
+        })
+        .collect()
+}
+
+async fn read(stream: &MockStream) -> Vec<rtp::packet::Packet> {
+    let mut read = vec![];
+    while let Ok(Some(Ok(pkt))) =
+        tokio::time::timeout(Duration::from_millis(10), stream.read_rtp()).await
+    {
+        read.push(pkt);
+    }
+    read
+}
+
+fn depacketize(pkts: &[rtp::packet::Packet]) -> Result<Bytes> {
+    let mut frame = BytesMut::new();
+    for pkt in pkts {
+        frame.extend_from_slice(&Vp8Packet::default().depacketize(&pkt.payload)?);
+    }
+    Ok(frame.freeze())
+}
+
+#[tokio::test]
+async fn test_frame_decryptor_interceptor() -> Result<()> {
+    let icpr = FrameDecryptor::builder()
+        .with_key(Arc::new(|ssrc, key_index| {
+            (ssrc == 1 && key_index == 2).then(|| KEY.to_vec())
+        }))
+        .build("")?;
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            mime_type: "video/VP8".to_owned(),
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    for pkt in encrypted_packets(10, 3000, 250, 2) {
+        stream.receive_rtp(pkt).await;
+    }
+    let pkts = read(&stream).await;
+    assert_eq!(
+        pkts.iter()
+            .map(|p| (p.header.sequence_number, p.header.marker))
+            .collect::<Vec<_>>(),
+        vec![(10, false), (11, false), (12, true)]
+    );
+    let frame: Vec<u8> = (0..250).map(|i| (i as u8) & 0xFE).collect();
+    assert_eq!(depacketize(&pkts)?, frame);
+
+    // an incomplete frame and a frame of another key are dropped, leaving a gap
+    let mut incomplete = encrypted_packets(13, 6000, 150, 2);
+    incomplete.remove(1);
+    for pkt in incomplete
+        .into_iter()
+        .chain(encrypted_packets(16, 9000, 150, 3))
+        .chain(encrypted_packets(18, 12000, 150, 2))
+    {
+        stream.receive_rtp(pkt).await;
+    }
+    let pkts = read(&stream).await;
+    assert_eq!(
+        pkts.iter()
+            .map(|p| p.header.sequence_number)
+            .collect::<Vec<_>>(),
+        vec![18, 19]
+    );
+    assert_eq!(depacketize(&pkts)?.len(), 150);
+
+    stream.close().await?;
+    Ok(())
+}
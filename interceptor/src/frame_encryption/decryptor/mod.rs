@@ -0,0 +1,114 @@
+mod decryptor_stream;
+#[cfg(test)]
+mod decryptor_test;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use decryptor_stream::DecryptorStream;
+
+use crate::error::Result;
+use crate::frame::FrameCodec;
+use crate::frame_encryption::DecryptionKeyFn;
+use crate::stream_info::StreamInfo;
+use crate::{
+    Attributes, Interceptor, InterceptorBuilder, RTCPReader, RTCPWriter, RTPReader, RTPWriter,
+};
+
+/// FrameDecryptorBuilder can be used to configure FrameDecryptor Interceptor
+#[derive(Default)]
+pub struct FrameDecryptorBuilder {
+    key: Option<DecryptionKeyFn>,
+}
+
+impl FrameDecryptorBuilder {
+    /// with_key sets the handler returning the keys the frames of each remote stream are
+    /// decrypted with.
+    pub fn with_key(mut self, f: DecryptionKeyFn) -> FrameDecryptorBuilder {
+        self.key = Some(f);
+        self
+    }
+}
+
+impl InterceptorBuilder for FrameDecryptorBuilder {
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        Ok(Arc::new(FrameDecryptor {
+            key: self.key.clone(),
+        }))
+    }
+}
+
+/// FrameDecryptor interceptor decrypts the frames of the incoming streams encrypted by the
+/// remote peer, as [`super::decrypt_frame`] does, with the keys returned by the handler it
+/// is configured with. The packets of each frame are reassembled into the frame, which is
+/// decrypted and packetized again.
+///
+/// The frames which are incomplete or can't be decrypted are dropped, leaving a gap in the
+/// sequence numbers of the packets read. The packets should be read in order, from a jitter
+/// buffer if they can be reordered. The VP8 and the audio streams are supported, the other
+/// streams are read unchanged.
+pub struct FrameDecryptor {
+    key: Option<DecryptionKeyFn>,
+}
+
+impl FrameDecryptor {
+    /// builder returns a new FrameDecryptorBuilder.
+    pub fn builder() -> FrameDecryptorBuilder {
+        FrameDecryptorBuilder::default()
+    }
+}
+
+#[async_trait]
+impl Interceptor for FrameDecryptor {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        reader
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        writer
+    }
+
+    /// bind_local_stream lets you modify any outgoing RTP packets. It is called once for per LocalStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_local_stream(
+        &self,
+        _info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        writer
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, _info: &StreamInfo) {}
+
+    /// bind_remote_stream returns a reader which decrypts the frames of the packets read, for
+    /// the streams of the codecs supported.
+    async fn bind_remote_stream(
+        &self,
+        info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        match FrameCodec::from_mime_type(&info.mime_type) {
+            Some(codec) => Arc::new(DecryptorStream::new(info, codec, self.key.clone(), reader)),
+            None => reader,
+        }
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, _info: &StreamInfo) {}
+
+    /// close closes the Interceptor, cleaning up any data if necessary.
+    async fn close(&self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use super::*;
+use crate::error::Error;
+use crate::frame::assembler::FrameAssembler;
+use crate::frame::FrameCodec;
+use crate::frame_encryption::{encrypt_frame, unencrypted_bytes, FRAME_OVERHEAD, IV_LENGTH};
+
+struct EncryptorStreamState {
+    /// None for the streams of the codecs not supported.
+    assembler: Option<FrameAssembler>,
+    /// Counter of the frames encrypted, making their IVs unique.
+    frame_counter: u32,
+}
+
+pub(super) struct EncryptorStream {
+    ssrc: u32,
+    mime_type: String,
+    key: Option<EncryptionKeyFn>,
+    state: util::sync::Mutex<EncryptorStreamState>,
+    next_rtp_writer: Arc<dyn RTPWriter + Send + Sync>,
+}
+
+impl EncryptorStream {
+    pub(super) fn new(
+        info: &StreamInfo,
+        key: Option<EncryptionKeyFn>,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Self {
+        EncryptorStream {
+            ssrc: info.ssrc,
+            mime_type: info.mime_type.clone(),
+            key,
+            state: util::sync::Mutex::new(EncryptorStreamState {
+                assembler: FrameCodec::from_mime_type(&info.mime_type).map(FrameAssembler::new),
+                frame_counter: rand::random::<u32>(),
+            }),
+            next_rtp_writer: writer,
+        }
+    }
+
+    /// encrypt returns the packets of the frame of packets encrypted.
+    fn encrypt(
+        &self,
+        state: &mut EncryptorStreamState,
+        packets: &[(rtp::packet::Packet, Attributes)],
+    ) -> Result<Vec<(rtp::packet::Packet, Attributes)>> {
+        let assembler = state
+            .assembler
+            .as_mut()
+            .ok_or(Error::ErrUnsupportedFrameCodec)?;
+        let (key_index, key) = self
+            .key
+            .as_ref()
+            .and_then(|f| f(self.ssrc))
+            .ok_or(Error::ErrNoFrameKey)?;
+        let frame = assembler.depacketize(packets)?;
+
+        let timestamp = packets
+            .first()
+            .map(|(pkt, _)| pkt.header.timestamp)
+            .unwrap_or_default();
+        let mut iv = [0u8; IV_LENGTH];
+        iv[..4].copy_from_slice(&self.ssrc.to_be_bytes());
+        iv[4..8].copy_from_slice(&timestamp.to_be_bytes());
+        iv[8..].copy_from_slice(&state.frame_counter.to_be_bytes());
+        state.frame_counter = state.frame_counter.wrapping_add(1);
+
+        let unencrypted = unencrypted_bytes(&self.mime_type, &frame);
+        let encrypted = encrypt_frame(&key, key_index, &iv, &frame, unencrypted)?;
+        // the packets can be larger by the overhead of the encryption, rather than more
+        let mtu = packets
+            .iter()
+            .map(|(pkt, _)| pkt.payload.len())
+            .max()
+            .unwrap_or_default()
+            + FRAME_OVERHEAD;
+        assembler.packetize(packets, &encrypted, mtu)
+    }
+}
+
+/// RTPWriter is used by Interceptor.bind_local_stream.
+#[async_trait]
+impl RTPWriter for EncryptorStream {
+    /// write buffers a rtp packet until its frame is complete, and writes the packets of the
+    /// frame encrypted.
+    async fn write(&self, pkt: &rtp::packet::Packet, a: &Attributes) -> Result<usize> {
+        let encrypted = {
+            let mut state = self.state.lock();
+            let packets = match state.assembler.as_mut() {
+                Some(assembler) => match assembler.push(pkt.clone(), a.clone()) {
+                    Some(packets) => packets,
+                    None => return Ok(0),
+                },
+                None => return Err(Error::ErrUnsupportedFrameCodec),
+            };
+            let encrypted = self.encrypt(&mut state, &packets);
+            if encrypted.is_err() {
+                if let Some(assembler) = state.assembler.as_mut() {
+                    assembler.skip(&packets);
+                }
+            }
+            encrypted?
+        };
+
+        let mut n = 0;
+        for (pkt, attributes) in &encrypted {
+            n += self.next_rtp_writer.write(pkt, attributes).await?;
+        }
+        Ok(n)
+    }
+}
@@ -0,0 +1,144 @@
+use bytes::BytesMut;
+use rtp::codecs::vp8::Vp8Packet;
+use rtp::packetizer::Depacketizer;
+use tokio::time::Duration;
+
+use super::*;
+use crate::error::Error;
+use crate::frame_encryption::decrypt_frame;
+use crate::mock::mock_stream::MockStream;
+
+const KEY: [u8; 16] = [7; 16];
+
+/// write_frame writes a VP8 frame of frame_size bytes in packets of 100 bytes.
+async fn write_frame(
+    stream: &MockStream,
+    first_sequence_number: u16,
+    timestamp: u32,
+    frame_size: usize,
+) -> Result<()> {
+    let frame: Vec<u8> = (0..frame_size).map(|i| i as u8).collect();
+    let chunks: Vec<&[u8]> = frame.chunks(100).collect();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let mut payload = vec![if i == 0 { 0x10 } else { 0x00 }];
+        payload.extend_from_slice(chunk);
+        stream
+            .write_rtp(&rtp::packet::Packet {
+                header: rtp::header::Header {
+                    ssrc: 1,
+                    sequence_number: first_sequence_number.wrapping_add(i as u16),
+                    timestamp,
+                    marker: i + 1 == chunks.len(),
+                    ..Default::default()
+                },
+                payload: payload.into(),
+                ..Default::default()
+            })
+            .await?;
+    }
+    Ok(())
+}
+
+async fn written(stream: &MockStream) -> Vec<rtp::packet::Packet> {
+    let mut written = vec![];
+    while let Ok(Some(pkt)) =
+        tokio::time::timeout(Duration::from_millis(10), stream.written_rtp()).await
+    {
+        written.push(pkt);
+    }
+    written
+}
+
+async fn encryptor_stream(mime_type: &str, key: EncryptionKeyFn) -> Result<Arc<MockStream>> {
+    let icpr = FrameEncryptor::builder().with_key(key).build("")?;
+    Ok(MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            mime_type: mime_type.to_owned(),
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await)
+}
+
+#[tokio::test]
+async fn test_frame_encryptor_interceptor() -> Result<()> {
+    let stream = encryptor_stream("video/VP8", Arc::new(|_| Some((2, KEY.to_vec())))).await?;
+
+    write_frame(&stream, 10, 3000, 250).await?;
+    let pkts = written(&stream).await;
+    assert_eq!(
+        pkts.iter()
+            .map(|p| (p.header.sequence_number, p.header.marker))
+            .collect::<Vec<_>>(),
+        vec![(10, false), (11, false), (12, true)]
+    );
+    assert!(pkts.iter().all(|p| p.header.timestamp == 3000));
+
+    let mut encrypted = BytesMut::new();
+    for pkt in &pkts {
+        encrypted.extend_from_slice(&Vp8Packet::default().depacketize(&pkt.payload)?);
+    }
+    assert_eq!(encrypted[encrypted.len() - 1], 2, "the key index");
+    let frame = decrypt_frame(&KEY, &encrypted, 10)?;
+    assert_eq!(frame, (0..250).map(|i| i as u8).collect::<Vec<u8>>());
+
+    // the sequence numbers follow those of the previous frame
+    write_frame(&stream, 13, 6000, 50).await?;
+    let pkts = written(&stream).await;
+    assert_eq!(pkts[0].header.sequence_number, 13);
+
+    stream.close().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_frame_encryptor_interceptor_without_key() -> Result<()> {
+    let has_key = Arc::new(std::sync::atomic::AtomicBool::new(true));
+    let key: EncryptionKeyFn = {
+        let has_key = Arc::clone(&has_key);
+        Arc::new(move |_| {
+            if has_key.load(std::sync::atomic::Ordering::SeqCst) {
+                Some((0, KEY.to_vec()))
+            } else {
+                None
+            }
+        })
+    };
+    let stream = encryptor_stream("video/VP8", key).await?;
+
+    write_frame(&stream, 10, 3000, 150).await?;
+    assert_eq!(written(&stream).await.len(), 2);
+
+    has_key.store(false, std::sync::atomic::Ordering::SeqCst);
+    assert_eq!(
+        write_frame(&stream, 12, 6000, 150).await,
+        Err(Error::ErrNoFrameKey)
+    );
+    assert!(written(&stream).await.is_empty());
+
+    // the frames sent once there's a key again follow without a gap
+    has_key.store(true, std::sync::atomic::Ordering::SeqCst);
+    write_frame(&stream, 14, 9000, 150).await?;
+    let pkts = written(&stream).await;
+    assert_eq!(pkts.len(), 2);
+    assert_eq!(pkts[0].header.sequence_number, 12);
+
+    stream.close().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_frame_encryptor_interceptor_unsupported_codec() -> Result<()> {
+    let stream = encryptor_stream("video/H264", Arc::new(|_| Some((0, KEY.to_vec())))).await?;
+
+    assert_eq!(
+        write_frame(&stream, 10, 3000, 50).await,
+        Err(Error::ErrUnsupportedFrameCodec)
+    );
+    assert!(written(&stream).await.is_empty());
+
+    stream.close().await?;
+    Ok(())
+}
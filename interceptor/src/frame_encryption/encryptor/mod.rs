@@ -0,0 +1,108 @@
+mod encryptor_stream;
+#[cfg(test)]
+mod encryptor_test;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use encryptor_stream::EncryptorStream;
+
+use crate::error::Result;
+use crate::frame_encryption::EncryptionKeyFn;
+use crate::stream_info::StreamInfo;
+use crate::{
+    Attributes, Interceptor, InterceptorBuilder, RTCPReader, RTCPWriter, RTPReader, RTPWriter,
+};
+
+/// FrameEncryptorBuilder can be used to configure FrameEncryptor Interceptor
+#[derive(Default)]
+pub struct FrameEncryptorBuilder {
+    key: Option<EncryptionKeyFn>,
+}
+
+impl FrameEncryptorBuilder {
+    /// with_key sets the handler returning the key the frames of each local stream are
+    /// encrypted with. The frames aren't sent without one.
+    pub fn with_key(mut self, f: EncryptionKeyFn) -> FrameEncryptorBuilder {
+        self.key = Some(f);
+        self
+    }
+}
+
+impl InterceptorBuilder for FrameEncryptorBuilder {
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        Ok(Arc::new(FrameEncryptor {
+            key: self.key.clone(),
+        }))
+    }
+}
+
+/// FrameEncryptor interceptor encrypts end-to-end the frames of the outgoing streams, as
+/// [`super::encrypt_frame`] does, with the keys returned by the handler it is configured
+/// with. The packets of each frame are reassembled into the frame, which is encrypted and
+/// packetized again, so that the SFUs forwarding the stream can't decrypt it but can still
+/// read its payload headers.
+///
+/// The VP8 and the audio streams are supported. Writing the packets of the streams of the
+/// other codecs fails, rather than sending them unencrypted.
+pub struct FrameEncryptor {
+    key: Option<EncryptionKeyFn>,
+}
+
+impl FrameEncryptor {
+    /// builder returns a new FrameEncryptorBuilder.
+    pub fn builder() -> FrameEncryptorBuilder {
+        FrameEncryptorBuilder::default()
+    }
+}
+
+#[async_trait]
+impl Interceptor for FrameEncryptor {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        reader
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        writer
+    }
+
+    /// bind_local_stream returns a writer which encrypts the frames of the packets written.
+    async fn bind_local_stream(
+        &self,
+        info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        Arc::new(EncryptorStream::new(info, self.key.clone(), writer))
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, _info: &StreamInfo) {}
+
+    /// bind_remote_stream lets you modify any incoming RTP packets. It is called once for per RemoteStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_remote_stream(
+        &self,
+        _info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        reader
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, _info: &StreamInfo) {}
+
+    /// close closes the Interceptor, cleaning up any data if necessary.
+    async fn close(&self) -> Result<()> {
+        Ok(())
+    }
+}
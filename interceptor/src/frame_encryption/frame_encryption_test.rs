@@ -0,0 +1,73 @@
+use super::*;
+
+const KEY: [u8; 16] = [7; 16];
+const IV: [u8; IV_LENGTH] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+
+#[test]
+fn test_unencrypted_bytes() {
+    // keyframe and delta frame
+    assert_eq!(unencrypted_bytes("video/VP8", &[0x10; 100]), 10);
+    assert_eq!(unencrypted_bytes("video/VP8", &[0x11; 100]), 3);
+    assert_eq!(unencrypted_bytes("video/VP8", &[0x10; 5]), 5);
+    assert_eq!(unencrypted_bytes("audio/opus", &[0xFC; 40]), 1);
+    assert_eq!(unencrypted_bytes("video/AV1", &[0; 100]), 0);
+}
+
+#[test]
+fn test_encrypt_frame() -> Result<()> {
+    let frame: Vec<u8> = (0..50).collect();
+    let encrypted = encrypt_frame(&KEY, 3, &IV, &frame, 10)?;
+
+    assert_eq!(encrypted.len(), frame.len() + FRAME_OVERHEAD);
+    assert_eq!(
+        &encrypted[..10],
+        &frame[..10],
+        "the header is left in clear"
+    );
+    assert_ne!(&encrypted[10..50], &frame[10..]);
+    // the trailer
+    let trailer = &encrypted[encrypted.len() - IV_LENGTH - 2..];
+    assert_eq!(&trailer[..IV_LENGTH], &IV);
+    assert_eq!(&trailer[IV_LENGTH..], &[IV_LENGTH as u8, 3]);
+    assert_eq!(frame_key_index(&encrypted), Some(3));
+
+    assert_eq!(decrypt_frame(&KEY, &encrypted, 10)?, frame);
+    Ok(())
+}
+
+#[test]
+fn test_decrypt_frame_authenticated() -> Result<()> {
+    let frame = vec![0xAB; 30];
+    let encrypted = encrypt_frame(&KEY, 0, &IV, &frame, 1)?;
+
+    assert_eq!(
+        decrypt_frame(&[8; 16], &encrypted, 1),
+        Err(Error::ErrFrameDecryption),
+        "another key"
+    );
+    let mut header_changed = encrypted.to_vec();
+    header_changed[0] ^= 0x01;
+    assert_eq!(
+        decrypt_frame(&KEY, &header_changed, 1),
+        Err(Error::ErrFrameDecryption),
+        "the header is authenticated"
+    );
+    assert_eq!(
+        decrypt_frame(&KEY, &encrypted[..FRAME_OVERHEAD - 1], 0),
+        Err(Error::ErrFrameDecryption)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_encrypt_frame_keys() -> Result<()> {
+    let frame = vec![0xCD; 30];
+    let encrypted = encrypt_frame(&[9; 32], 1, &IV, &frame, 0)?;
+    assert_eq!(decrypt_frame(&[9; 32], &encrypted, 0)?, frame);
+
+    assert_eq!(
+        encrypt_frame(&[9; 20], 1, &IV, &frame, 0),
+        Err(Error::ErrFrameEncryption)
+    );
+    Ok(())
+}
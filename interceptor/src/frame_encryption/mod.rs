@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod frame_encryption_test;
+
+pub mod decryptor;
+pub mod encryptor;
+
+use std::sync::Arc;
+
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::{Aes128Gcm, Aes256Gcm, KeyInit, Nonce};
+use bytes::{BufMut, Bytes, BytesMut};
+
+use crate::error::{Error, Result};
+
+/// Length of the IVs of the frames encrypted.
+pub const IV_LENGTH: usize = 12;
+const TAG_LENGTH: usize = 16;
+/// Bytes an encrypted frame has more than the frame: the authentication tag, the IV, its
+/// length and the key index.
+pub const FRAME_OVERHEAD: usize = TAG_LENGTH + IV_LENGTH + 2;
+
+/// Bytes left unencrypted at the start of the VP8 frames, the payload headers the SFUs read.
+const VP8_KEYFRAME_UNENCRYPTED_BYTES: usize = 10;
+const VP8_DELTA_FRAME_UNENCRYPTED_BYTES: usize = 3;
+/// Byte left unencrypted at the start of the audio frames, e.g. the Opus TOC byte.
+const AUDIO_UNENCRYPTED_BYTES: usize = 1;
+
+/// EncryptionKeyFn returns the index and the key the frames of the local stream ssrc are
+/// encrypted with, if it has one. The key is of 16 bytes for AES-128-GCM, or of 32 bytes for
+/// AES-256-GCM. It is called for each frame, so that the keys can be rotated.
+pub type EncryptionKeyFn = Arc<dyn (Fn(u32) -> Option<(u8, Vec<u8>)>) + Send + Sync + 'static>;
+
+/// DecryptionKeyFn returns the key of a key index the frames of the remote stream ssrc are
+/// decrypted with, if it has one.
+pub type DecryptionKeyFn = Arc<dyn (Fn(u32, u8) -> Option<Vec<u8>>) + Send + Sync + 'static>;
+
+/// unencrypted_bytes returns the bytes left unencrypted at the start of a frame of mime_type,
+/// so that the SFUs forwarding it can still read its payload header.
+pub fn unencrypted_bytes(mime_type: &str, frame: &[u8]) -> usize {
+    let mime_type = mime_type.to_lowercase();
+    let unencrypted = if mime_type == "video/vp8" {
+        // the P bit of the VP8 frame tag is unset for the keyframes
+        match frame.first() {
+            Some(b) if b & 0x01 == 0 => VP8_KEYFRAME_UNENCRYPTED_BYTES,
+            _ => VP8_DELTA_FRAME_UNENCRYPTED_BYTES,
+        }
+    } else if mime_type.starts_with("audio/") {
+        AUDIO_UNENCRYPTED_BYTES
+    } else {
+        0
+    };
+    unencrypted.min(frame.len())
+}
+
+/// encrypt_frame encrypts frame with AES-GCM, in the format of the insertable streams E2EE
+/// of the browsers: the first unencrypted bytes of the frame, authenticated but in clear,
+/// followed by the rest encrypted with its tag, the IV, the length of the IV and the key
+/// index.
+pub fn encrypt_frame(
+    key: &[u8],
+    key_index: u8,
+    iv: &[u8; IV_LENGTH],
+    frame: &[u8],
+    unencrypted: usize,
+) -> Result<Bytes> {
+    let (header, data) = frame.split_at(unencrypted.min(frame.len()));
+    let payload = Payload {
+        msg: data,
+        aad: header,
+    };
+    let nonce = Nonce::from_slice(iv);
+    let encrypted = match key.len() {
+        16 => Aes128Gcm::new_from_slice(key)
+            .map_err(|_| Error::ErrFrameEncryption)?
+            .encrypt(nonce, payload),
+        32 => Aes256Gcm::new_from_slice(key)
+            .map_err(|_| Error::ErrFrameEncryption)?
+            .encrypt(nonce, payload),
+        _ => return Err(Error::ErrFrameEncryption),
+    }
+    .map_err(|_| Error::ErrFrameEncryption)?;
+
+    let mut b = BytesMut::with_capacity(frame.len() + FRAME_OVERHEAD);
+    b.put_slice(header);
+    b.put_slice(&encrypted);
+    b.put_slice(iv);
+    b.put_u8(IV_LENGTH as u8);
+    b.put_u8(key_index);
+    Ok(b.freeze())
+}
+
+/// frame_key_index returns the index of the key an encrypted frame was encrypted with.
+pub fn frame_key_index(encrypted: &[u8]) -> Option<u8> {
+    if encrypted.len() < FRAME_OVERHEAD {
+        None
+    } else {
+        encrypted.last().copied()
+    }
+}
+
+/// decrypt_frame returns the frame encrypted by [`encrypt_frame`] with key, its first
+/// unencrypted bytes in clear.
+pub fn decrypt_frame(key: &[u8], encrypted: &[u8], unencrypted: usize) -> Result<Bytes> {
+    if encrypted.len() < FRAME_OVERHEAD {
+        return Err(Error::ErrFrameDecryption);
+    }
+    let iv_length = encrypted[encrypted.len() - 2] as usize;
+    let trailer_length = iv_length + 2;
+    if iv_length != IV_LENGTH || encrypted.len() < trailer_length + TAG_LENGTH + unencrypted {
+        return Err(Error::ErrFrameDecryption);
+    }
+    let iv_start = encrypted.len() - trailer_length;
+    let (header, data) = encrypted[..iv_start].split_at(unencrypted);
+    let payload = Payload {
+        msg: data,
+        aad: header,
+    };
+    let nonce = Nonce::from_slice(&encrypted[iv_start..iv_start + IV_LENGTH]);
+    let decrypted = match key.len() {
+        16 => Aes128Gcm::new_from_slice(key)
+            .map_err(|_| Error::ErrFrameDecryption)?
+            .decrypt(nonce, payload),
+        32 => Aes256Gcm::new_from_slice(key)
+            .map_err(|_| Error::ErrFrameDecryption)?
+            .decrypt(nonce, payload),
+        _ => return Err(Error::ErrFrameDecryption),
+    }
+    .map_err(|_| Error::ErrFrameDecryption)?;
+
+    let mut b = BytesMut::with_capacity(header.len() + decrypted.len());
+    b.put_slice(header);
+    b.put_slice(&decrypted);
+    Ok(b.freeze())
+}
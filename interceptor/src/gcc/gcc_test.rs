@@ -0,0 +1,120 @@
+use super::*;
+
+const PACKET_SIZE: usize = 1200;
+
+/// run sends PACKET_SIZE packets at send_bitrate for duration_us from start_us over a link
+/// of link_bitrate, losing one packet in every lose_every if not zero, and reports them in
+/// feedbacks every 100ms. It returns the lowest target bitrate meanwhile.
+fn run(
+    estimator: &mut BandwidthEstimator,
+    send_bitrate: i64,
+    link_bitrate: i64,
+    lose_every: i64,
+    duration_us: i64,
+) -> u64 {
+    let send_interval_us = PACKET_SIZE as i64 * 8 * 1_000_000 / send_bitrate;
+    let link_interval_us = PACKET_SIZE as i64 * 8 * 1_000_000 / link_bitrate;
+
+    let mut lowest = estimator.target_bitrate();
+    let mut results = vec![];
+    let mut arrival_us = 0;
+    let mut send_us = 0;
+    let mut i = 0;
+    let mut next_feedback_us = 100_000;
+    while send_us < duration_us {
+        // packets queue up when the link is slower than the sender
+        arrival_us = (send_us + 20_000).max(arrival_us + link_interval_us);
+        i += 1;
+        results.push(PacketResult {
+            send_us,
+            size: PACKET_SIZE,
+            arrival_us: if lose_every != 0 && i % lose_every == 0 {
+                None
+            } else {
+                Some(arrival_us)
+            },
+            ..Default::default()
+        });
+        if send_us >= next_feedback_us {
+            lowest = lowest.min(estimator.on_feedback(&results, send_us));
+            results.clear();
+            next_feedback_us += 100_000;
+        }
+        send_us += send_interval_us;
+    }
+    lowest
+}
+
+#[test]
+fn test_bandwidth_estimator_increases_under_capacity() {
+    let mut estimator = BandwidthEstimator::new(300_000, 10_000, 10_000_000);
+    run(&mut estimator, 500_000, 2_000_000, 0, 10_000_000);
+
+    let bitrate = estimator.target_bitrate();
+    assert!(bitrate > 500_000, "target {bitrate} should have increased");
+    assert!(
+        bitrate <= 1_500_000 * 500_000 / 1_000_000 + 10_000,
+        "target {bitrate} shouldn't exceed much what is acknowledged"
+    );
+    assert_eq!(estimator.usage(), BandwidthUsage::Normal);
+    assert_eq!(estimator.loss_fraction(), 0.0);
+}
+
+#[test]
+fn test_bandwidth_estimator_decreases_over_capacity() {
+    let mut estimator = BandwidthEstimator::new(1_000_000, 10_000, 10_000_000);
+    run(&mut estimator, 1_000_000, 500_000, 0, 5_000_000);
+
+    let bitrate = estimator.target_bitrate();
+    assert!(
+        bitrate < 500_000,
+        "target {bitrate} should be below the capacity of the link"
+    );
+    assert_eq!(bitrate, estimator.delay_based_bitrate());
+}
+
+#[test]
+fn test_bandwidth_estimator_decreases_with_losses() {
+    let mut estimator = BandwidthEstimator::new(1_000_000, 10_000, 10_000_000);
+    run(&mut estimator, 1_000_000, 2_000_000, 5, 3_000_000);
+
+    let bitrate = estimator.target_bitrate();
+    assert!(
+        bitrate < 1_000_000 / 2,
+        "target {bitrate} should have decreased with the losses"
+    );
+    assert_eq!(bitrate, estimator.loss_based_bitrate());
+    assert!((estimator.loss_fraction() - 0.2).abs() < 0.05);
+}
+
+#[test]
+fn test_bandwidth_estimator_probe_result() {
+    let mut estimator = BandwidthEstimator::new(300_000, 10_000, 2_000_000);
+    assert_eq!(estimator.on_probe_result(1_500_000, 0), 1_500_000);
+    assert_eq!(estimator.delay_based_bitrate(), 1_500_000);
+    assert_eq!(estimator.loss_based_bitrate(), 1_500_000);
+    assert_eq!(estimator.on_probe_result(5_000_000, 0), 2_000_000);
+}
+
+#[test]
+fn test_probe_bitrate() {
+    // 10 packets sent at 2Mbps, received at 1Mbps
+    let results: Vec<PacketResult> = (0..10)
+        .map(|i| PacketResult {
+            send_us: i * 4_800,
+            size: PACKET_SIZE,
+            arrival_us: Some(50_000 + i * 9_600),
+            ..Default::default()
+        })
+        .collect();
+    assert_eq!(probe_bitrate(&results), Some(1_000_000));
+
+    let lost: Vec<PacketResult> = results
+        .iter()
+        .map(|r| PacketResult {
+            arrival_us: None,
+            ..*r
+        })
+        .collect();
+    assert_eq!(probe_bitrate(&lost), None);
+}
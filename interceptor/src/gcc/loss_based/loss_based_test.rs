@@ -0,0 +1,45 @@
+use super::*;
+
+#[test]
+fn test_loss_based_increases_without_losses() {
+    let mut control = LossBasedControl::new(100_000, 10_000, 10_000_000);
+    assert_eq!(control.update(100, 1, 0), 105_000);
+    assert_eq!(control.update(100, 0, 100_000), 110_250);
+    assert_eq!(control.loss_fraction(), 0.0);
+}
+
+#[test]
+fn test_loss_based_decreases_with_losses() {
+    let mut control = LossBasedControl::new(100_000, 10_000, 10_000_000);
+    assert_eq!(control.update(80, 20, 0), 90_000);
+    assert_eq!(control.loss_fraction(), 0.2);
+    // at most once per 300ms
+    assert_eq!(control.update(80, 20, 100_000), 90_000);
+    assert_eq!(control.update(80, 20, 300_000), 81_000);
+}
+
+#[test]
+fn test_loss_based_holds_with_moderate_losses() {
+    let mut control = LossBasedControl::new(100_000, 10_000, 10_000_000);
+    assert_eq!(control.update(95, 5, 0), 100_000);
+}
+
+#[test]
+fn test_loss_based_waits_for_enough_packets() {
+    let mut control = LossBasedControl::new(100_000, 10_000, 10_000_000);
+    assert_eq!(control.update(5, 5, 0), 100_000);
+    assert_eq!(control.loss_fraction(), 0.0);
+    // the losses accumulate until enough packets were reported
+    assert_eq!(control.update(10, 0, 0), 87_500);
+    assert_eq!(control.loss_fraction(), 0.25);
+}
+
+#[test]
+fn test_loss_based_bounds() {
+    let mut control = LossBasedControl::new(100_000, 80_000, 102_000);
+    assert_eq!(control.update(100, 0, 0), 102_000);
+    for i in 1..10 {
+        control.update(50, 50, i * 1_000_000);
+    }
+    assert_eq!(control.bitrate(), 80_000);
+}
@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod loss_based_test;
+
+/// Loss fraction under which the estimate increases.
+const LOW_LOSS_THRESHOLD: f64 = 0.02;
+/// Loss fraction over which the estimate decreases.
+const HIGH_LOSS_THRESHOLD: f64 = 0.1;
+const INCREASE_FACTOR: f64 = 1.05;
+const DECREASE_INTERVAL_US: i64 = 300_000;
+/// Number of packets the loss fraction is measured over at least.
+const MIN_PACKETS: u32 = 20;
+
+/// LossBasedControl is the loss-based controller of Google congestion control, which
+/// estimates the bandwidth available to the sender from the fraction of its packets lost.
+///
+/// The estimate increases by 5% each time less than 2% of the packets are lost, decreases
+/// proportionally to the losses when more than 10% are, at most once per 300ms, and holds
+/// in between.
+///
+/// ## Specifications
+///
+/// * [draft-ietf-rmcat-gcc-02 §6]
+///
+/// [draft-ietf-rmcat-gcc-02 §6]: https://datatracker.ietf.org/doc/html/draft-ietf-rmcat-gcc-02#section-6
+#[derive(Debug, Clone)]
+pub struct LossBasedControl {
+    min_bitrate: u64,
+    max_bitrate: u64,
+    bitrate: f64,
+
+    received: u32,
+    lost: u32,
+    loss_fraction: f64,
+    last_decrease_us: Option<i64>,
+}
+
+impl LossBasedControl {
+    /// new returns a control starting at initial_bitrate, and never estimating less than
+    /// min_bitrate nor more than max_bitrate, in bits per second.
+    pub fn new(initial_bitrate: u64, min_bitrate: u64, max_bitrate: u64) -> Self {
+        LossBasedControl {
+            min_bitrate,
+            max_bitrate,
+            bitrate: initial_bitrate.clamp(min_bitrate, max_bitrate) as f64,
+
+            received: 0,
+            lost: 0,
+            loss_fraction: 0.0,
+            last_decrease_us: None,
+        }
+    }
+
+    /// bitrate returns the bandwidth estimated, in bits per second.
+    pub fn bitrate(&self) -> u64 {
+        self.bitrate as u64
+    }
+
+    /// loss_fraction returns the fraction of the packets lost last measured.
+    pub fn loss_fraction(&self) -> f64 {
+        self.loss_fraction
+    }
+
+    /// set_estimate sets the estimate, such as to the bitrate a probe measured.
+    pub fn set_estimate(&mut self, bitrate: u64) {
+        self.bitrate = bitrate.clamp(self.min_bitrate, self.max_bitrate) as f64;
+    }
+
+    /// update records that received packets were received and lost packets were lost at
+    /// now_us microseconds. It returns the estimate, updated once enough packets were
+    /// reported to measure the loss fraction.
+    pub fn update(&mut self, received: u32, lost: u32, now_us: i64) -> u64 {
+        self.received += received;
+        self.lost += lost;
+        let total = self.received + self.lost;
+        if total < MIN_PACKETS {
+            return self.bitrate();
+        }
+        self.loss_fraction = self.lost as f64 / total as f64;
+        self.received = 0;
+        self.lost = 0;
+
+        if self.loss_fraction < LOW_LOSS_THRESHOLD {
+            self.bitrate *= INCREASE_FACTOR;
+        } else if self.loss_fraction > HIGH_LOSS_THRESHOLD
+            && self
+                .last_decrease_us
+                .is_none_or(|t| now_us - t >= DECREASE_INTERVAL_US)
+        {
+            self.bitrate *= 1.0 - 0.5 * self.loss_fraction;
+            self.last_decrease_us = Some(now_us);
+        }
+
+        self.bitrate = self
+            .bitrate
+            .clamp(self.min_bitrate as f64, self.max_bitrate as f64);
+        self.bitrate()
+    }
+}
@@ -0,0 +1,231 @@
+#[cfg(test)]
+mod gcc_test;
+
+pub mod loss_based;
+pub mod probe;
+pub mod rate_control;
+pub mod sender;
+pub mod trendline;
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use loss_based::LossBasedControl;
+use rate_control::AimdRateControl;
+use trendline::{BandwidthUsage, TrendlineDetector};
+
+/// Window the acknowledged bitrate is measured over.
+const ACKNOWLEDGED_RATE_WINDOW_US: i64 = 500_000;
+/// Number of packets a probe cluster needs to have been received to measure its bitrate.
+const MIN_PROBE_PACKETS: usize = 5;
+
+/// PacketResult is the fate of a packet sent, as reported by the transport wide congestion
+/// control feedback of the receiver.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct PacketResult {
+    /// Send time of the packet, in microseconds.
+    pub send_us: i64,
+    /// Size of the packet, in bytes.
+    pub size: usize,
+    /// Arrival time of the packet on the clock of the receiver, in microseconds, or None if
+    /// it was lost.
+    pub arrival_us: Option<i64>,
+    /// Id of the probe cluster the packet was sent for, if any.
+    pub probe_cluster_id: Option<usize>,
+}
+
+/// BandwidthEstimator estimates the bandwidth available to a sender from the feedback of the
+/// receiver on the packets sent, as the send-side estimator of Google congestion control.
+///
+/// The target bitrate is the lowest of the estimates of the delay-based controller, whose
+/// AIMD rate control reacts to the overuse the trendline detector sees in the delay
+/// variation of the packets, and of the loss-based controller, which reacts to the fraction
+/// of them lost and never exceeds the former. Probe results set both estimates.
+///
+/// ## Specifications
+///
+/// * [draft-ietf-rmcat-gcc-02]
+///
+/// [draft-ietf-rmcat-gcc-02]: https://datatracker.ietf.org/doc/html/draft-ietf-rmcat-gcc-02
+#[derive(Debug, Clone)]
+pub struct BandwidthEstimator {
+    min_bitrate: u64,
+    max_bitrate: u64,
+    target_bitrate: u64,
+
+    detector: TrendlineDetector,
+    usage: BandwidthUsage,
+    rate_control: AimdRateControl,
+    loss_based: LossBasedControl,
+
+    acknowledged: VecDeque<(i64, usize)>,
+    acknowledged_bytes: usize,
+}
+
+impl BandwidthEstimator {
+    /// new returns an estimator starting at initial_bitrate, and never estimating less than
+    /// min_bitrate nor more than max_bitrate, in bits per second.
+    pub fn new(initial_bitrate: u64, min_bitrate: u64, max_bitrate: u64) -> Self {
+        BandwidthEstimator {
+            min_bitrate,
+            max_bitrate,
+            target_bitrate: initial_bitrate.clamp(min_bitrate, max_bitrate),
+
+            detector: TrendlineDetector::new(),
+            usage: BandwidthUsage::Normal,
+            rate_control: AimdRateControl::new(initial_bitrate, min_bitrate, max_bitrate),
+            loss_based: LossBasedControl::new(initial_bitrate, min_bitrate, max_bitrate),
+
+            acknowledged: VecDeque::new(),
+            acknowledged_bytes: 0,
+        }
+    }
+
+    /// target_bitrate returns the bitrate the sender should send at, in bits per second.
+    pub fn target_bitrate(&self) -> u64 {
+        self.target_bitrate
+    }
+
+    /// delay_based_bitrate returns the estimate of the delay-based controller.
+    pub fn delay_based_bitrate(&self) -> u64 {
+        self.rate_control.bitrate()
+    }
+
+    /// loss_based_bitrate returns the estimate of the loss-based controller.
+    pub fn loss_based_bitrate(&self) -> u64 {
+        self.loss_based.bitrate()
+    }
+
+    /// loss_fraction returns the fraction of the packets lost last measured.
+    pub fn loss_fraction(&self) -> f64 {
+        self.loss_based.loss_fraction()
+    }
+
+    /// usage returns the state of the link last detected.
+    pub fn usage(&self) -> BandwidthUsage {
+        self.usage
+    }
+
+    /// acknowledged_bitrate returns the bitrate received over the last half second, in bits
+    /// per second, or None before any packet was reported received.
+    pub fn acknowledged_bitrate(&self) -> Option<u64> {
+        if self.acknowledged.is_empty() {
+            None
+        } else {
+            Some(
+                (self.acknowledged_bytes as i64 * 8 * 1_000_000 / ACKNOWLEDGED_RATE_WINDOW_US)
+                    as u64,
+            )
+        }
+    }
+
+    /// set_rtt sets the round trip time to the receiver.
+    pub fn set_rtt(&mut self, rtt: Duration) {
+        self.rate_control.set_rtt(rtt);
+    }
+
+    /// on_feedback updates the estimates at now_us microseconds with the results of the
+    /// packets a feedback reported, in the order they were sent. It returns the target
+    /// bitrate.
+    pub fn on_feedback(&mut self, results: &[PacketResult], now_us: i64) -> u64 {
+        let (mut received, mut lost) = (0, 0);
+        let mut usage = None;
+        for result in results {
+            let arrival_us = match result.arrival_us {
+                Some(arrival_us) => arrival_us,
+                None => {
+                    lost += 1;
+                    continue;
+                }
+            };
+            received += 1;
+            self.acknowledge(arrival_us, result.size);
+
+            if let Some(detected) = self.detector.incoming_packet(result.send_us, arrival_us) {
+                // an overuse within the feedback isn't hidden by the packets following it
+                if usage != Some(BandwidthUsage::Overusing) {
+                    usage = Some(detected);
+                }
+            }
+        }
+        if let Some(usage) = usage {
+            self.usage = usage;
+        }
+
+        if received > 0 {
+            self.rate_control
+                .update(self.usage, self.acknowledged_bitrate(), now_us);
+        }
+        if self.usage == BandwidthUsage::Overusing {
+            // a single overuse decreases the estimate once
+            self.usage = BandwidthUsage::Normal;
+        }
+        self.loss_based.update(received, lost, now_us);
+        if self.loss_based.bitrate() > self.rate_control.bitrate() {
+            self.loss_based.set_estimate(self.rate_control.bitrate());
+        }
+
+        self.update_target_bitrate()
+    }
+
+    /// on_probe_result sets the estimates at now_us microseconds to bitrate, in bits per
+    /// second, measured by a probe. It returns the target bitrate.
+    pub fn on_probe_result(&mut self, bitrate: u64, now_us: i64) -> u64 {
+        self.rate_control.set_estimate(bitrate, now_us);
+        self.loss_based.set_estimate(bitrate);
+        self.update_target_bitrate()
+    }
+
+    fn acknowledge(&mut self, arrival_us: i64, size: usize) {
+        self.acknowledged.push_back((arrival_us, size));
+        self.acknowledged_bytes += size;
+        while let Some((t, size)) = self.acknowledged.front().copied() {
+            if arrival_us - t < ACKNOWLEDGED_RATE_WINDOW_US {
+                break;
+            }
+            self.acknowledged.pop_front();
+            self.acknowledged_bytes -= size;
+        }
+    }
+
+    fn update_target_bitrate(&mut self) -> u64 {
+        self.target_bitrate = self
+            .rate_control
+            .bitrate()
+            .min(self.loss_based.bitrate())
+            .clamp(self.min_bitrate, self.max_bitrate);
+        self.target_bitrate
+    }
+}
+
+/// probe_bitrate returns the bitrate, in bits per second, a cluster of probe packets sent
+/// back to back measured: the lowest of the rates they were sent and received at. It returns
+/// None if too few of them were received.
+pub fn probe_bitrate(results: &[PacketResult]) -> Option<u64> {
+    let received: Vec<(i64, i64, usize)> = results
+        .iter()
+        .filter_map(|r| {
+            r.arrival_us
+                .map(|arrival_us| (r.send_us, arrival_us, r.size))
+        })
+        .collect();
+    if received.len() < MIN_PROBE_PACKETS {
+        return None;
+    }
+
+    let first_send = received.iter().min_by_key(|r| r.0)?;
+    let last_send = received.iter().max_by_key(|r| r.0)?;
+    let first_arrival = received.iter().min_by_key(|r| r.1)?;
+    let last_arrival = received.iter().max_by_key(|r| r.1)?;
+    let send_interval_us = last_send.0 - first_send.0;
+    let arrival_interval_us = last_arrival.1 - first_arrival.1;
+    if send_interval_us <= 0 || arrival_interval_us <= 0 {
+        return None;
+    }
+
+    let size: usize = received.iter().map(|r| r.2).sum();
+    // the last packet sent, and the first received, don't count in their interval
+    let send_bitrate = (size - last_send.2) as i64 * 8 * 1_000_000 / send_interval_us;
+    let arrival_bitrate = (size - first_arrival.2) as i64 * 8 * 1_000_000 / arrival_interval_us;
+    Some(send_bitrate.min(arrival_bitrate) as u64)
+}
@@ -0,0 +1,227 @@
+#[cfg(test)]
+mod probe_test;
+
+use std::collections::HashMap;
+
+use super::{probe_bitrate, PacketResult};
+
+/// ATTR_PROBE_CLUSTER_ID is the attribute the pacer sets to the id of the probe cluster an
+/// outgoing RTP packet is sent for.
+pub const ATTR_PROBE_CLUSTER_ID: usize = 0x9B0BE;
+
+/// Multiples of the initial estimate probed at the start.
+const INITIAL_PROBE_FACTORS: [f64; 2] = [3.0, 6.0];
+/// A probe result reaching this fraction of the bitrate probed is probed further.
+const FURTHER_PROBE_THRESHOLD: f64 = 0.7;
+const FURTHER_PROBE_FACTOR: f64 = 2.0;
+/// Probing ends when no result reaches the threshold within this time.
+const PROBE_TIMEOUT_US: i64 = 1_000_000;
+
+/// A decrease of the estimate below this fraction is a large drop, which is probed for
+/// recovery.
+const LARGE_DROP_FRACTION: f64 = 0.66;
+/// Fraction of the estimate before a large drop the recovery is probed at.
+const RECOVERY_PROBE_FRACTION: f64 = 0.85;
+/// The recovery is probed once the queues built up before the drop drained, but not after
+/// the window.
+const RECOVERY_PROBE_DELAY_US: i64 = 1_000_000;
+const RECOVERY_PROBE_WINDOW_US: i64 = 5_000_000;
+
+/// Duration of a probe cluster at its bitrate.
+const PROBE_DURATION_US: u64 = 15_000;
+const MIN_PROBE_CLUSTER_PACKETS: usize = 5;
+/// Clusters whose packets weren't all reported within this time are forgotten.
+const PROBE_RESULT_TIMEOUT_US: i64 = 2_000_000;
+
+/// ProbeCluster is a burst of packets to send at a bitrate higher than the one targeted, to
+/// measure whether the link can carry it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ProbeCluster {
+    /// Id of the cluster, set to the ATTR_PROBE_CLUSTER_ID attribute of its packets.
+    pub id: usize,
+    /// Bitrate the packets are sent at, in bits per second.
+    pub bitrate: u64,
+    /// Number of packets to send at least.
+    pub min_packets: usize,
+    /// Number of bytes to send at least.
+    pub min_bytes: usize,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ProbeState {
+    Init,
+    WaitingForResult { probed_bitrate: u64, since_us: i64 },
+    Done,
+}
+
+/// ProbeController decides when to probe the link, and at which bitrates: at the start, so
+/// that the estimate ramps up quickly, further while the results show the link carries what
+/// is probed, and after a large drop of the estimate, to recover once the network improves.
+#[derive(Debug, Clone)]
+pub struct ProbeController {
+    max_bitrate: u64,
+    next_id: usize,
+    state: ProbeState,
+    last_estimate: u64,
+    /// Estimate before the last large drop, and when it dropped.
+    large_drop: Option<(u64, i64)>,
+}
+
+impl ProbeController {
+    /// new returns a controller never probing more than max_bitrate, in bits per second.
+    pub fn new(max_bitrate: u64) -> Self {
+        ProbeController {
+            max_bitrate,
+            next_id: 1,
+            state: ProbeState::Init,
+            last_estimate: 0,
+            large_drop: None,
+        }
+    }
+
+    /// on_start returns the clusters to probe at multiples of the estimate when the first
+    /// packets are sent, at now_us microseconds.
+    pub fn on_start(&mut self, estimate: u64, now_us: i64) -> Vec<ProbeCluster> {
+        if self.state != ProbeState::Init {
+            return vec![];
+        }
+        self.last_estimate = estimate;
+
+        let mut clusters: Vec<ProbeCluster> = vec![];
+        for factor in INITIAL_PROBE_FACTORS {
+            let bitrate = ((estimate as f64 * factor) as u64).min(self.max_bitrate);
+            let previous = clusters.last().map_or(estimate, |c| c.bitrate);
+            if bitrate > previous {
+                clusters.push(self.new_cluster(bitrate));
+            }
+        }
+        self.state = match clusters.last() {
+            Some(cluster) => ProbeState::WaitingForResult {
+                probed_bitrate: cluster.bitrate,
+                since_us: now_us,
+            },
+            None => ProbeState::Done,
+        };
+        clusters
+    }
+
+    /// on_estimate returns the clusters to probe after the estimate was updated at now_us
+    /// microseconds.
+    pub fn on_estimate(&mut self, estimate: u64, now_us: i64) -> Vec<ProbeCluster> {
+        let mut clusters = vec![];
+        if let ProbeState::WaitingForResult {
+            probed_bitrate,
+            since_us,
+        } = self.state
+        {
+            if estimate as f64 >= FURTHER_PROBE_THRESHOLD * probed_bitrate as f64 {
+                let bitrate =
+                    ((estimate as f64 * FURTHER_PROBE_FACTOR) as u64).min(self.max_bitrate);
+                if bitrate > estimate {
+                    clusters.push(self.new_cluster(bitrate));
+                    self.state = ProbeState::WaitingForResult {
+                        probed_bitrate: bitrate,
+                        since_us: now_us,
+                    };
+                } else {
+                    self.state = ProbeState::Done;
+                }
+            } else if now_us - since_us > PROBE_TIMEOUT_US {
+                self.state = ProbeState::Done;
+            }
+        }
+
+        if (estimate as f64) < LARGE_DROP_FRACTION * self.last_estimate as f64 {
+            self.large_drop = Some((self.last_estimate, now_us));
+        } else if let Some((before, dropped_us)) = self.large_drop {
+            if now_us - dropped_us > RECOVERY_PROBE_WINDOW_US {
+                self.large_drop = None;
+            } else if now_us - dropped_us >= RECOVERY_PROBE_DELAY_US
+                && !matches!(self.state, ProbeState::WaitingForResult { .. })
+            {
+                self.large_drop = None;
+                let bitrate =
+                    ((before as f64 * RECOVERY_PROBE_FRACTION) as u64).min(self.max_bitrate);
+                if bitrate > estimate {
+                    clusters.push(self.new_cluster(bitrate));
+                    self.state = ProbeState::WaitingForResult {
+                        probed_bitrate: bitrate,
+                        since_us: now_us,
+                    };
+                }
+            }
+        }
+        self.last_estimate = estimate;
+        clusters
+    }
+
+    fn new_cluster(&mut self, bitrate: u64) -> ProbeCluster {
+        let id = self.next_id;
+        self.next_id += 1;
+        ProbeCluster {
+            id,
+            bitrate,
+            min_packets: MIN_PROBE_CLUSTER_PACKETS,
+            min_bytes: (bitrate * PROBE_DURATION_US / 8 / 1_000_000) as usize,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct ClusterResults {
+    sent: usize,
+    first_send_us: i64,
+    results: Vec<PacketResult>,
+}
+
+/// ProbeAnalyzer gathers the results of the packets sent for each probe cluster, and measures
+/// the bitrate of the clusters once all their packets were reported.
+#[derive(Debug, Default, Clone)]
+pub struct ProbeAnalyzer {
+    clusters: HashMap<usize, ClusterResults>,
+}
+
+impl ProbeAnalyzer {
+    /// new returns an analyzer which hasn't seen any probe.
+    pub fn new() -> Self {
+        ProbeAnalyzer::default()
+    }
+
+    /// on_packet_sent records a packet sent for cluster_id at send_us microseconds.
+    pub fn on_packet_sent(&mut self, cluster_id: usize, send_us: i64) {
+        let cluster = self
+            .clusters
+            .entry(cluster_id)
+            .or_insert_with(|| ClusterResults {
+                first_send_us: send_us,
+                ..Default::default()
+            });
+        cluster.sent += 1;
+    }
+
+    /// on_packet_result records the result of a packet sent for a cluster.
+    pub fn on_packet_result(&mut self, result: PacketResult) {
+        if let Some(cluster) = result
+            .probe_cluster_id
+            .and_then(|id| self.clusters.get_mut(&id))
+        {
+            cluster.results.push(result);
+        }
+    }
+
+    /// take_results returns the bitrates, in bits per second, measured by the clusters all
+    /// whose packets were reported, and forgets them as well as the ones sent too long before
+    /// now_us microseconds.
+    pub fn take_results(&mut self, now_us: i64) -> Vec<u64> {
+        let mut bitrates = vec![];
+        self.clusters.retain(|_, cluster| {
+            if cluster.sent >= MIN_PROBE_CLUSTER_PACKETS && cluster.results.len() >= cluster.sent {
+                bitrates.extend(probe_bitrate(&cluster.results));
+                false
+            } else {
+                now_us - cluster.first_send_us < PROBE_RESULT_TIMEOUT_US
+            }
+        });
+        bitrates
+    }
+}
@@ -0,0 +1,119 @@
+use super::*;
+
+#[test]
+fn test_probe_controller_initial_probes() {
+    let mut controller = ProbeController::new(10_000_000);
+    let clusters = controller.on_start(300_000, 0);
+    assert_eq!(
+        clusters,
+        vec![
+            ProbeCluster {
+                id: 1,
+                bitrate: 900_000,
+                min_packets: 5,
+                min_bytes: 1_687,
+            },
+            ProbeCluster {
+                id: 2,
+                bitrate: 1_800_000,
+                min_packets: 5,
+                min_bytes: 3_375,
+            },
+        ]
+    );
+    assert!(controller.on_start(300_000, 0).is_empty());
+
+    // the results below the threshold aren't probed further
+    assert!(controller.on_estimate(900_000, 100_000).is_empty());
+    let clusters = controller.on_estimate(1_500_000, 200_000);
+    assert_eq!(clusters.len(), 1);
+    assert_eq!(clusters[0].id, 3);
+    assert_eq!(clusters[0].bitrate, 3_000_000);
+}
+
+#[test]
+fn test_probe_controller_max_bitrate() {
+    let mut controller = ProbeController::new(1_000_000);
+    let clusters = controller.on_start(300_000, 0);
+    let bitrates: Vec<u64> = clusters.iter().map(|c| c.bitrate).collect();
+    assert_eq!(bitrates, vec![900_000, 1_000_000]);
+
+    let clusters = controller.on_estimate(800_000, 100_000);
+    assert_eq!(clusters.len(), 1);
+    assert_eq!(clusters[0].bitrate, 1_000_000);
+
+    // the max bitrate is reached
+    assert!(controller.on_estimate(1_000_000, 200_000).is_empty());
+    assert_eq!(controller.state, ProbeState::Done);
+}
+
+#[test]
+fn test_probe_controller_timeout() {
+    let mut controller = ProbeController::new(10_000_000);
+    controller.on_start(300_000, 0);
+    assert!(controller.on_estimate(300_000, 1_100_000).is_empty());
+    assert_eq!(controller.state, ProbeState::Done);
+    assert!(controller.on_estimate(5_000_000, 1_200_000).is_empty());
+}
+
+#[test]
+fn test_probe_controller_recovery_after_large_drop() {
+    let mut controller = ProbeController::new(10_000_000);
+    controller.on_start(1_000_000, 0);
+    controller.on_estimate(1_000_000, 1_100_000);
+    assert_eq!(controller.state, ProbeState::Done);
+
+    assert!(controller.on_estimate(500_000, 1_200_000).is_empty());
+    // the queues may not have drained yet
+    assert!(controller.on_estimate(500_000, 1_700_000).is_empty());
+    let clusters = controller.on_estimate(500_000, 2_300_000);
+    assert_eq!(clusters.len(), 1);
+    assert_eq!(clusters[0].bitrate, 850_000);
+    assert!(controller.on_estimate(500_000, 2_400_000).is_empty());
+}
+
+fn cluster_results(cluster_id: usize, count: i64) -> Vec<PacketResult> {
+    // sent at 2Mbps, received at 1Mbps
+    (0..count)
+        .map(|i| PacketResult {
+            send_us: i * 1_000,
+            size: 250,
+            arrival_us: Some(20_000 + i * 2_000),
+            probe_cluster_id: Some(cluster_id),
+        })
+        .collect()
+}
+
+#[test]
+fn test_probe_analyzer() {
+    let mut analyzer = ProbeAnalyzer::new();
+    for result in cluster_results(1, 10) {
+        analyzer.on_packet_sent(1, result.send_us);
+    }
+    let results = cluster_results(1, 10);
+    for result in &results[..6] {
+        analyzer.on_packet_result(*result);
+    }
+    // some packets weren't reported yet
+    assert!(analyzer.take_results(50_000).is_empty());
+
+    for result in &results[6..] {
+        analyzer.on_packet_result(*result);
+    }
+    assert_eq!(analyzer.take_results(60_000), vec![1_000_000]);
+    assert!(analyzer.take_results(70_000).is_empty());
+}
+
+#[test]
+fn test_probe_analyzer_timeout() {
+    let mut analyzer = ProbeAnalyzer::new();
+    for result in cluster_results(1, 10) {
+        analyzer.on_packet_sent(1, result.send_us);
+    }
+    // results of clusters not sent are ignored
+    for result in cluster_results(2, 10) {
+        analyzer.on_packet_result(result);
+    }
+    assert!(analyzer.take_results(PROBE_RESULT_TIMEOUT_US).is_empty());
+    assert!(analyzer.clusters.is_empty());
+}
@@ -0,0 +1,201 @@
+#[cfg(test)]
+mod rate_control_test;
+
+use std::time::Duration;
+
+use super::trendline::BandwidthUsage;
+
+const DECREASE_FACTOR: f64 = 0.85;
+/// Multiplicative increase of the estimate per second, while far from the link capacity.
+const INCREASE_FACTOR_PER_SECOND: f64 = 1.08;
+/// Time the detector takes to react to an increase, on top of the round trip time.
+const DETECTOR_RESPONSE_TIME_MS: f64 = 100.0;
+const DEFAULT_RTT: Duration = Duration::from_millis(200);
+/// Size of the packets assumed by the additive increase, in bits.
+const PACKET_SIZE_BITS: f64 = 1200.0 * 8.0;
+const MIN_ADDITIVE_INCREASE_BPS: f64 = 4_000.0;
+/// Smoothing of the link capacity estimated from the acknowledged bitrate at decreases.
+const LINK_CAPACITY_SMOOTHING: f64 = 0.95;
+
+/// RateControlState is the state of the AIMD rate control.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum RateControlState {
+    #[default]
+    Hold,
+    Increase,
+    Decrease,
+}
+
+/// LinkCapacity estimates the capacity of the link from the acknowledged bitrate measured
+/// each time it was overused.
+#[derive(Debug, Copy, Clone)]
+struct LinkCapacity {
+    bitrate: f64,
+    /// Variance normalized by the capacity.
+    variance: f64,
+}
+
+impl LinkCapacity {
+    fn update(&mut self, acknowledged_bitrate: f64) {
+        self.bitrate = LINK_CAPACITY_SMOOTHING * self.bitrate
+            + (1.0 - LINK_CAPACITY_SMOOTHING) * acknowledged_bitrate;
+        let error = self.bitrate - acknowledged_bitrate;
+        self.variance = LINK_CAPACITY_SMOOTHING * self.variance
+            + (1.0 - LINK_CAPACITY_SMOOTHING) * error * error / self.bitrate.max(1.0);
+        self.variance = self.variance.clamp(0.4, 2.5);
+    }
+
+    /// deviation is three standard deviations of the capacity.
+    fn deviation(&self) -> f64 {
+        3.0 * (self.variance * self.bitrate).sqrt()
+    }
+}
+
+/// AimdRateControl is the additive increase, multiplicative decrease rate control of the
+/// delay-based controller of Google congestion control.
+///
+/// The estimate increases multiplicatively while the link is normally used, additively
+/// once close to the capacity the link was overused at, holds while the queues drain, and
+/// decreases to a fraction of the acknowledged bitrate when the link is overused.
+///
+/// ## Specifications
+///
+/// * [draft-ietf-rmcat-gcc-02 §5.5]
+///
+/// [draft-ietf-rmcat-gcc-02 §5.5]: https://datatracker.ietf.org/doc/html/draft-ietf-rmcat-gcc-02#section-5.5
+#[derive(Debug, Clone)]
+pub struct AimdRateControl {
+    min_bitrate: u64,
+    max_bitrate: u64,
+    bitrate: f64,
+    state: RateControlState,
+    rtt: Duration,
+    last_update_us: Option<i64>,
+    link_capacity: Option<LinkCapacity>,
+}
+
+impl AimdRateControl {
+    /// new returns a rate control starting at initial_bitrate, and never estimating less than
+    /// min_bitrate nor more than max_bitrate, in bits per second.
+    pub fn new(initial_bitrate: u64, min_bitrate: u64, max_bitrate: u64) -> Self {
+        AimdRateControl {
+            min_bitrate,
+            max_bitrate,
+            bitrate: initial_bitrate.clamp(min_bitrate, max_bitrate) as f64,
+            state: RateControlState::Hold,
+            rtt: DEFAULT_RTT,
+            last_update_us: None,
+            link_capacity: None,
+        }
+    }
+
+    /// bitrate returns the bandwidth estimated, in bits per second.
+    pub fn bitrate(&self) -> u64 {
+        self.bitrate as u64
+    }
+
+    /// state returns the state of the rate control.
+    pub fn state(&self) -> RateControlState {
+        self.state
+    }
+
+    /// set_rtt sets the round trip time, which paces the additive increase.
+    pub fn set_rtt(&mut self, rtt: Duration) {
+        self.rtt = rtt;
+    }
+
+    /// set_estimate sets the estimate at now_us microseconds, such as to the bitrate a probe
+    /// measured.
+    pub fn set_estimate(&mut self, bitrate: u64, now_us: i64) {
+        self.bitrate = bitrate.clamp(self.min_bitrate, self.max_bitrate) as f64;
+        self.last_update_us = Some(now_us);
+    }
+
+    /// update updates the estimate at now_us microseconds with the state of the link and the
+    /// bitrate acknowledged by the receiver, in bits per second. It returns the estimate.
+    pub fn update(
+        &mut self,
+        usage: BandwidthUsage,
+        acknowledged_bitrate: Option<u64>,
+        now_us: i64,
+    ) -> u64 {
+        let elapsed_us = match self.last_update_us {
+            Some(last) => (now_us - last).clamp(0, 1_000_000),
+            None => 0,
+        };
+        self.last_update_us = Some(now_us);
+
+        self.state = match (usage, self.state) {
+            (BandwidthUsage::Overusing, _) => RateControlState::Decrease,
+            (BandwidthUsage::Underusing, _) => RateControlState::Hold,
+            (BandwidthUsage::Normal, RateControlState::Hold) => RateControlState::Increase,
+            (BandwidthUsage::Normal, state) => state,
+        };
+
+        let acknowledged_bitrate = acknowledged_bitrate.map(|b| b as f64);
+        match self.state {
+            RateControlState::Hold => {}
+            RateControlState::Increase => {
+                if let (Some(capacity), Some(acknowledged)) =
+                    (self.link_capacity, acknowledged_bitrate)
+                {
+                    if acknowledged > capacity.bitrate + capacity.deviation() {
+                        // the capacity of the link went up
+                        self.link_capacity = None;
+                    }
+                }
+
+                let increase = if self.link_capacity.is_some() {
+                    self.additive_increase(elapsed_us)
+                } else {
+                    self.bitrate
+                        * (INCREASE_FACTOR_PER_SECOND.powf(elapsed_us as f64 / 1_000_000.0) - 1.0)
+                };
+                let increased = self.bitrate + increase;
+                // don't increase far beyond what is actually sent
+                self.bitrate = match acknowledged_bitrate {
+                    Some(acknowledged) if increased > 1.5 * acknowledged + 10_000.0 => {
+                        self.bitrate.max(1.5 * acknowledged + 10_000.0)
+                    }
+                    _ => increased,
+                };
+            }
+            RateControlState::Decrease => {
+                if let Some(acknowledged) = acknowledged_bitrate {
+                    self.bitrate = self.bitrate.min(DECREASE_FACTOR * acknowledged);
+                    match &mut self.link_capacity {
+                        Some(capacity)
+                            if acknowledged < capacity.bitrate - capacity.deviation() =>
+                        {
+                            // the capacity of the link went down
+                            self.link_capacity = None;
+                        }
+                        Some(capacity) => capacity.update(acknowledged),
+                        None => {}
+                    }
+                    self.link_capacity.get_or_insert(LinkCapacity {
+                        bitrate: acknowledged,
+                        variance: 0.4,
+                    });
+                } else {
+                    self.bitrate *= DECREASE_FACTOR;
+                }
+                // a single overuse decreases the estimate once
+                self.state = RateControlState::Hold;
+            }
+        }
+
+        self.bitrate = self
+            .bitrate
+            .clamp(self.min_bitrate as f64, self.max_bitrate as f64);
+        self.bitrate()
+    }
+
+    /// additive_increase returns the increase of about a packet per response time.
+    fn additive_increase(&self, elapsed_us: i64) -> f64 {
+        let response_time_ms = 2.0 * (self.rtt.as_secs_f64() * 1000.0 + DETECTOR_RESPONSE_TIME_MS);
+        let increase_bps =
+            (PACKET_SIZE_BITS * 1000.0 / response_time_ms).max(MIN_ADDITIVE_INCREASE_BPS);
+        increase_bps * elapsed_us as f64 / 1_000_000.0
+    }
+}
@@ -0,0 +1,100 @@
+use super::*;
+
+/// run updates the rate control every 100ms for duration_us with usage, from start_us, and
+/// returns the estimate.
+fn run(
+    rate_control: &mut AimdRateControl,
+    usage: BandwidthUsage,
+    acknowledged_bitrate: u64,
+    start_us: i64,
+    duration_us: i64,
+) -> u64 {
+    let mut now_us = start_us;
+    while now_us < start_us + duration_us {
+        rate_control.update(usage, Some(acknowledged_bitrate), now_us);
+        now_us += 100_000;
+    }
+    rate_control.bitrate()
+}
+
+#[test]
+fn test_rate_control_multiplicative_increase() {
+    let mut rate_control = AimdRateControl::new(300_000, 10_000, 10_000_000);
+    let bitrate = run(
+        &mut rate_control,
+        BandwidthUsage::Normal,
+        1_000_000,
+        0,
+        1_100_000,
+    );
+
+    assert_eq!(rate_control.state(), RateControlState::Increase);
+    assert!(
+        (bitrate as f64 - 300_000.0 * 1.08).abs() < 100.0,
+        "estimate {bitrate} should have increased by 8% in a second"
+    );
+}
+
+#[test]
+fn test_rate_control_increase_capped_by_acknowledged() {
+    let mut rate_control = AimdRateControl::new(300_000, 10_000, 10_000_000);
+    let bitrate = run(
+        &mut rate_control,
+        BandwidthUsage::Normal,
+        100_000,
+        0,
+        10_000_000,
+    );
+
+    assert_eq!(
+        bitrate, 300_000,
+        "estimate above the cap shouldn't increase"
+    );
+}
+
+#[test]
+fn test_rate_control_decrease_then_additive_increase() {
+    let mut rate_control = AimdRateControl::new(1_000_000, 10_000, 10_000_000);
+    rate_control.update(BandwidthUsage::Normal, Some(800_000), 0);
+    let bitrate = rate_control.update(BandwidthUsage::Overusing, Some(800_000), 100_000);
+    assert_eq!(bitrate, 680_000);
+    assert_eq!(rate_control.state(), RateControlState::Hold);
+
+    // near the capacity the link was overused at, the increase is additive
+    rate_control.set_rtt(Duration::from_millis(100));
+    let bitrate = run(
+        &mut rate_control,
+        BandwidthUsage::Normal,
+        700_000,
+        200_000,
+        1_000_000,
+    );
+    assert!(
+        bitrate > 680_000 && bitrate < 680_000 + 30_000,
+        "estimate {bitrate} should have increased additively"
+    );
+}
+
+#[test]
+fn test_rate_control_hold_while_underusing() {
+    let mut rate_control = AimdRateControl::new(500_000, 10_000, 10_000_000);
+    let bitrate = run(
+        &mut rate_control,
+        BandwidthUsage::Underusing,
+        1_000_000,
+        0,
+        1_000_000,
+    );
+
+    assert_eq!(bitrate, 500_000);
+    assert_eq!(rate_control.state(), RateControlState::Hold);
+}
+
+#[test]
+fn test_rate_control_set_estimate() {
+    let mut rate_control = AimdRateControl::new(300_000, 10_000, 2_000_000);
+    rate_control.set_estimate(5_000_000, 0);
+    assert_eq!(rate_control.bitrate(), 2_000_000);
+    rate_control.set_estimate(1_000_000, 0);
+    assert_eq!(rate_control.bitrate(), 1_000_000);
+}
@@ -0,0 +1,395 @@
+mod sender_stream;
+#[cfg(test)]
+mod sender_test;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use rtcp::receiver_report::ReceiverReport;
+use rtcp::reception_report::ReceptionReport;
+use rtcp::sender_report::SenderReport;
+use rtcp::transport_feedbacks::transport_layer_cc::TransportLayerCc;
+use sender_stream::SenderStream;
+
+use crate::error::Result;
+use crate::gcc::probe::{ProbeAnalyzer, ProbeCluster, ProbeController};
+use crate::gcc::BandwidthEstimator;
+use crate::pacer::PacerRate;
+use crate::rtt::RttTracker;
+use crate::stream_info::StreamInfo;
+use crate::twcc::history::{self, SendHistory, TwccFeedback};
+use crate::twcc::sender::TRANSPORT_CC_URI;
+use crate::{
+    Attributes, Interceptor, InterceptorBuilder, RTCPReader, RTCPWriter, RTPReader, RTPWriter,
+};
+
+const DEFAULT_INITIAL_BITRATE: u64 = 300_000;
+const DEFAULT_MIN_BITRATE: u64 = 30_000;
+const DEFAULT_MAX_BITRATE: u64 = 10_000_000;
+
+/// OnTargetBitrateFn is called with the bitrate, in bits per second, the senders should send
+/// at, each time it changes.
+pub type OnTargetBitrateFn = Arc<
+    dyn (Fn(u64) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>) + Send + Sync + 'static,
+>;
+
+/// OnTwccFeedbackFn is called with each transport wide congestion control feedback received,
+/// matched against the packets sent, e.g. for an estimator of its own.
+pub type OnTwccFeedbackFn = Arc<
+    dyn (Fn(TwccFeedback) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>)
+        + Send
+        + Sync
+        + 'static,
+>;
+
+/// SenderBuilder can be used to configure the GCC Sender Interceptor
+#[derive(Default)]
+pub struct SenderBuilder {
+    initial_bitrate: Option<u64>,
+    min_bitrate: Option<u64>,
+    max_bitrate: Option<u64>,
+    on_target_bitrate: Option<OnTargetBitrateFn>,
+    on_feedback: Option<OnTwccFeedbackFn>,
+    pacer: Option<Arc<PacerRate>>,
+    history_max_age: Option<Duration>,
+    history_max_bytes: Option<usize>,
+}
+
+impl SenderBuilder {
+    /// with_initial_bitrate sets the bitrate, in bits per second, targeted before any
+    /// feedback is received.
+    pub fn with_initial_bitrate(mut self, bitrate: u64) -> SenderBuilder {
+        self.initial_bitrate = Some(bitrate);
+        self
+    }
+
+    /// with_min_bitrate sets the lowest bitrate, in bits per second, targeted.
+    pub fn with_min_bitrate(mut self, bitrate: u64) -> SenderBuilder {
+        self.min_bitrate = Some(bitrate);
+        self
+    }
+
+    /// with_max_bitrate sets the highest bitrate, in bits per second, targeted.
+    pub fn with_max_bitrate(mut self, bitrate: u64) -> SenderBuilder {
+        self.max_bitrate = Some(bitrate);
+        self
+    }
+
+    /// with_on_target_bitrate sets the handler called with the target bitrate each time
+    /// it changes.
+    pub fn with_on_target_bitrate(mut self, f: OnTargetBitrateFn) -> SenderBuilder {
+        self.on_target_bitrate = Some(f);
+        self
+    }
+
+    /// with_on_feedback sets the handler called with each feedback received.
+    pub fn with_on_feedback(mut self, f: OnTwccFeedbackFn) -> SenderBuilder {
+        self.on_feedback = Some(f);
+        self
+    }
+
+    /// with_history_max_age sets how long the packets sent are kept to be matched against the
+    /// feedbacks, if none reports them before.
+    pub fn with_history_max_age(mut self, max_age: Duration) -> SenderBuilder {
+        self.history_max_age = Some(max_age);
+        self
+    }
+
+    /// with_history_max_bytes sets the bytes of the packets sent kept at most to be matched
+    /// against the feedbacks, the oldest being forgotten first.
+    pub fn with_history_max_bytes(mut self, max_bytes: usize) -> SenderBuilder {
+        self.history_max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// with_pacer sets the pacer the target bitrate is handed to, and which sends the probe
+    /// clusters. The link is probed only with a pacer.
+    pub fn with_pacer(mut self, pacer: Arc<PacerRate>) -> SenderBuilder {
+        self.pacer = Some(pacer);
+        self
+    }
+}
+
+impl InterceptorBuilder for SenderBuilder {
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        let max_bitrate = self.max_bitrate.unwrap_or(DEFAULT_MAX_BITRATE);
+        let estimator = BandwidthEstimator::new(
+            self.initial_bitrate.unwrap_or(DEFAULT_INITIAL_BITRATE),
+            self.min_bitrate.unwrap_or(DEFAULT_MIN_BITRATE),
+            max_bitrate,
+        );
+        if let Some(pacer) = &self.pacer {
+            pacer.set_target_bitrate(estimator.target_bitrate());
+        }
+        Ok(Arc::new(Sender {
+            internal: Arc::new(SenderInternal {
+                start_time: tokio::time::Instant::now(),
+                state: util::sync::Mutex::new(SenderState {
+                    last_notified: estimator.target_bitrate(),
+                    estimator,
+                    history: SendHistory::new(
+                        self.history_max_age.unwrap_or(history::DEFAULT_MAX_AGE),
+                        self.history_max_bytes.unwrap_or(history::DEFAULT_MAX_BYTES),
+                    ),
+                    rtt: RttTracker::new(),
+                    started: false,
+                    probes: ProbeController::new(max_bitrate),
+                    analyzer: ProbeAnalyzer::new(),
+                }),
+                on_target_bitrate: self.on_target_bitrate.clone(),
+                on_feedback: self.on_feedback.clone(),
+                pacer: self.pacer.clone(),
+            }),
+        }))
+    }
+}
+
+struct SenderState {
+    estimator: BandwidthEstimator,
+    history: SendHistory,
+    rtt: RttTracker,
+    last_notified: u64,
+
+    started: bool,
+    probes: ProbeController,
+    analyzer: ProbeAnalyzer,
+}
+
+pub struct SenderInternal {
+    // we use tokio's Instant because it makes testing easier via `tokio::time::advance`.
+    start_time: tokio::time::Instant,
+    state: util::sync::Mutex<SenderState>,
+    on_target_bitrate: Option<OnTargetBitrateFn>,
+    on_feedback: Option<OnTwccFeedbackFn>,
+    pacer: Option<Arc<PacerRate>>,
+}
+
+impl SenderInternal {
+    fn now_us(&self) -> i64 {
+        (tokio::time::Instant::now() - self.start_time).as_micros() as i64
+    }
+
+    fn on_packet_sent(&self, sequence_number: u16, size: usize, probe_cluster_id: Option<usize>) {
+        let now_us = self.now_us();
+        let clusters = {
+            let mut state = self.state.lock();
+            state
+                .history
+                .on_packet_sent(sequence_number, now_us, size, probe_cluster_id);
+            if let Some(id) = probe_cluster_id {
+                state.analyzer.on_packet_sent(id, now_us);
+            }
+
+            if state.started || self.pacer.is_none() {
+                return;
+            }
+            state.started = true;
+            let estimate = state.estimator.target_bitrate();
+            state.probes.on_start(estimate, now_us)
+        };
+        self.probe(clusters);
+    }
+
+    fn probe(&self, clusters: Vec<ProbeCluster>) {
+        if let Some(pacer) = &self.pacer {
+            for cluster in clusters {
+                pacer.probe(cluster);
+            }
+        }
+    }
+
+    /// process_rtcp updates the estimator with the feedbacks and the reports of a batch of
+    /// RTCP packets. It returns the target bitrate if it changed, the clusters to probe, and
+    /// the feedbacks parsed.
+    fn process_rtcp(
+        &self,
+        pkts: &[Box<dyn rtcp::packet::Packet + Send + Sync>],
+    ) -> (Option<u64>, Vec<ProbeCluster>, Vec<TwccFeedback>) {
+        let now_us = self.now_us();
+        let mut state = self.state.lock();
+        let mut feedbacks = vec![];
+        let mut feedback_received = false;
+        for p in pkts {
+            let any = p.as_any();
+            if let Some(feedback) = any.downcast_ref::<TransportLayerCc>() {
+                let feedback = state.history.on_feedback(feedback);
+                let results = feedback.packet_results();
+                if self.on_feedback.is_some() {
+                    feedbacks.push(feedback);
+                }
+                if results.is_empty() {
+                    continue;
+                }
+                feedback_received = true;
+                state.estimator.on_feedback(&results, now_us);
+                for result in results {
+                    state.analyzer.on_packet_result(result);
+                }
+                for bitrate in state.analyzer.take_results(now_us) {
+                    // probes are sent to find out whether the estimate can increase
+                    if bitrate > state.estimator.target_bitrate() {
+                        state.estimator.on_probe_result(bitrate, now_us);
+                    }
+                }
+            } else if let Some(sr) = any.downcast_ref::<SenderReport>() {
+                state.on_reception_reports(&sr.reports);
+            } else if let Some(rr) = any.downcast_ref::<ReceiverReport>() {
+                state.on_reception_reports(&rr.reports);
+            }
+        }
+
+        let target_bitrate = state.estimator.target_bitrate();
+        let clusters = if feedback_received && state.started {
+            state.probes.on_estimate(target_bitrate, now_us)
+        } else {
+            vec![]
+        };
+        if target_bitrate == state.last_notified {
+            return (None, clusters, feedbacks);
+        }
+        state.last_notified = target_bitrate;
+        (Some(target_bitrate), clusters, feedbacks)
+    }
+}
+
+impl SenderState {
+    fn on_reception_reports(&mut self, reports: &[ReceptionReport]) {
+        let now = SystemTime::now();
+        for r in reports {
+            if self
+                .rtt
+                .on_reception_report(r.ssrc, r.last_sender_report, r.delay, now)
+                .is_some()
+            {
+                if let Some(estimate) = self.rtt.estimate(r.ssrc) {
+                    self.estimator.set_rtt(estimate.smoothed);
+                }
+            }
+        }
+    }
+}
+
+pub struct SenderRtcpReader {
+    parent_rtcp_reader: Arc<dyn RTCPReader + Send + Sync>,
+    internal: Arc<SenderInternal>,
+}
+
+#[async_trait]
+impl RTCPReader for SenderRtcpReader {
+    async fn read(
+        &self,
+        buf: &mut [u8],
+        a: &Attributes,
+    ) -> Result<(Vec<Box<dyn rtcp::packet::Packet + Send + Sync>>, Attributes)> {
+        let (pkts, attr) = self.parent_rtcp_reader.read(buf, a).await?;
+        let (target_bitrate, clusters, feedbacks) = self.internal.process_rtcp(&pkts);
+        self.internal.probe(clusters);
+        if let Some(f) = &self.internal.on_feedback {
+            for feedback in feedbacks {
+                f(feedback).await;
+            }
+        }
+        if let Some(target_bitrate) = target_bitrate {
+            if let Some(pacer) = &self.internal.pacer {
+                pacer.set_target_bitrate(target_bitrate);
+            }
+            if let Some(f) = &self.internal.on_target_bitrate {
+                f(target_bitrate).await;
+            }
+        }
+        Ok((pkts, attr))
+    }
+}
+
+/// Sender interceptor estimates the bandwidth available to the local streams from the
+/// transport wide congestion control feedback of the remote peer on the packets sent, with
+/// Google congestion control, and hands the target bitrate to the handler and the pacer set.
+/// With a pacer, it probes the link at the start and to recover from large drops of the
+/// estimate.
+///
+/// It must be registered before the TWCC sender interceptor, so that it sees the transport
+/// wide sequence numbers the latter adds to the packets.
+pub struct Sender {
+    internal: Arc<SenderInternal>,
+}
+
+impl Sender {
+    /// builder returns a new SenderBuilder.
+    pub fn builder() -> SenderBuilder {
+        SenderBuilder::default()
+    }
+}
+
+#[async_trait]
+impl Interceptor for Sender {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        Arc::new(SenderRtcpReader {
+            internal: Arc::clone(&self.internal),
+            parent_rtcp_reader: reader,
+        })
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        writer
+    }
+
+    /// bind_local_stream lets you modify any outgoing RTP packets. It is called once for per LocalStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_local_stream(
+        &self,
+        info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        let mut hdr_ext_id = 0u8;
+        for e in &info.rtp_header_extensions {
+            if e.uri == TRANSPORT_CC_URI {
+                hdr_ext_id = e.id as u8;
+                break;
+            }
+        }
+        if hdr_ext_id == 0 {
+            // the packets of the stream aren't reported
+            return writer;
+        }
+
+        Arc::new(SenderStream::new(
+            writer,
+            hdr_ext_id,
+            Arc::clone(&self.internal),
+        ))
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, _info: &StreamInfo) {}
+
+    /// bind_remote_stream lets you modify any incoming RTP packets. It is called once for per RemoteStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_remote_stream(
+        &self,
+        _info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        reader
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, _info: &StreamInfo) {}
+
+    /// close closes the Interceptor, cleaning up any data if necessary.
+    async fn close(&self) -> Result<()> {
+        Ok(())
+    }
+}
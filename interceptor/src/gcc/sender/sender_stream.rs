@@ -0,0 +1,43 @@
+use rtp::extension::transport_cc_extension::TransportCcExtension;
+use util::{MarshalSize, Unmarshal};
+
+use super::*;
+use crate::gcc::probe::ATTR_PROBE_CLUSTER_ID;
+
+pub(super) struct SenderStream {
+    next_rtp_writer: Arc<dyn RTPWriter + Send + Sync>,
+    hdr_ext_id: u8,
+    internal: Arc<SenderInternal>,
+}
+
+impl SenderStream {
+    pub(super) fn new(
+        next_rtp_writer: Arc<dyn RTPWriter + Send + Sync>,
+        hdr_ext_id: u8,
+        internal: Arc<SenderInternal>,
+    ) -> Self {
+        SenderStream {
+            next_rtp_writer,
+            hdr_ext_id,
+            internal,
+        }
+    }
+}
+
+/// RTPWriter is used by Interceptor.bind_local_stream.
+#[async_trait]
+impl RTPWriter for SenderStream {
+    /// write a rtp packet
+    async fn write(&self, pkt: &rtp::packet::Packet, a: &Attributes) -> Result<usize> {
+        if let Some(mut ext) = pkt.header.get_extension(self.hdr_ext_id) {
+            let tcc_ext = TransportCcExtension::unmarshal(&mut ext)?;
+            self.internal.on_packet_sent(
+                tcc_ext.transport_sequence,
+                pkt.marshal_size(),
+                a.get(&ATTR_PROBE_CLUSTER_ID).copied(),
+            );
+        }
+
+        self.next_rtp_writer.write(pkt, a).await
+    }
+}
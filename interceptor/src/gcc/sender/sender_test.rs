@@ -0,0 +1,275 @@
+use rtp::extension::transport_cc_extension::TransportCcExtension;
+use tokio::sync::mpsc;
+use tokio::time::Duration;
+use util::Marshal;
+
+use super::*;
+use crate::gcc::probe::ATTR_PROBE_CLUSTER_ID;
+use crate::mock::mock_stream::MockStream;
+use crate::stream_info::RTPHeaderExtension;
+use crate::twcc::Recorder;
+
+const PAYLOAD_SIZE: usize = 1000;
+
+async fn new_stream(
+    initial_bitrate: u64,
+) -> Result<(Arc<MockStream>, mpsc::UnboundedReceiver<u64>)> {
+    let (target_tx, target_rx) = mpsc::unbounded_channel();
+    let icpr = Sender::builder()
+        .with_initial_bitrate(initial_bitrate)
+        .with_on_target_bitrate(Arc::new(move |bitrate: u64| {
+            let target_tx = target_tx.clone();
+            Box::pin(async move {
+                let _ = target_tx.send(bitrate);
+            })
+        }))
+        .build("")?;
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            rtp_header_extensions: vec![RTPHeaderExtension {
+                uri: TRANSPORT_CC_URI.to_owned(),
+                id: 1,
+            }],
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+    Ok((stream, target_rx))
+}
+
+async fn send_packet(stream: &MockStream, transport_sequence: u16) -> Result<()> {
+    let mut header = rtp::header::Header {
+        ssrc: 1,
+        ..Default::default()
+    };
+    header.set_extension(1, TransportCcExtension { transport_sequence }.marshal()?)?;
+    stream
+        .write_rtp(&rtp::packet::Packet {
+            header,
+            payload: vec![0u8; PAYLOAD_SIZE].into(),
+            ..Default::default()
+        })
+        .await?;
+    stream
+        .written_rtp()
+        .await
+        .expect("packet should be written");
+    Ok(())
+}
+
+async fn receive_feedback(stream: &MockStream, recorder: &mut Recorder) {
+    stream.receive_rtcp(recorder.build_feedback_packet()).await;
+    stream
+        .read_rtcp()
+        .await
+        .expect("feedback should be read")
+        .unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_gcc_sender_interceptor_decreases_when_queuing() -> Result<()> {
+    let (stream, mut target_rx) = new_stream(1_000_000).await?;
+
+    let mut recorder = Recorder::new(2);
+    // the transport wide sequence numbers wrap meanwhile
+    let start_sequence = 65_500u16;
+    for i in 0..100u16 {
+        let sequence = start_sequence.wrapping_add(i);
+        send_packet(&stream, sequence).await?;
+        // packets queue up on a link slower than the sender
+        recorder.record(1, sequence, 20_000 + i as i64 * 15_000);
+        if i % 10 == 9 {
+            receive_feedback(&stream, &mut recorder).await;
+        }
+        tokio::time::advance(Duration::from_millis(10)).await;
+    }
+
+    let mut lowest = None;
+    while let Ok(bitrate) = target_rx.try_recv() {
+        lowest = Some(lowest.map_or(bitrate, |l: u64| l.min(bitrate)));
+    }
+    let lowest = lowest.expect("the target bitrate should have changed");
+    assert!(
+        lowest < 1_000_000 * 85 / 100,
+        "target {lowest} should have decreased"
+    );
+
+    stream.close().await?;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_gcc_sender_interceptor_decreases_with_losses() -> Result<()> {
+    let (stream, mut target_rx) = new_stream(1_000_000).await?;
+
+    let mut recorder = Recorder::new(2);
+    for i in 0..60u16 {
+        send_packet(&stream, i).await?;
+        if i % 2 == 0 {
+            recorder.record(1, i, 20_000 + i as i64 * 10_000);
+        }
+        tokio::time::advance(Duration::from_millis(10)).await;
+    }
+    receive_feedback(&stream, &mut recorder).await;
+
+    let bitrate = target_rx.try_recv().expect("target should have changed");
+    assert!(
+        bitrate < 1_000_000 && bitrate > 700_000,
+        "target {bitrate} should have decreased with the losses"
+    );
+
+    stream.close().await?;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_gcc_sender_interceptor_on_feedback() -> Result<()> {
+    let (feedback_tx, mut feedback_rx) = mpsc::unbounded_channel();
+    let icpr = Sender::builder()
+        .with_history_max_bytes(5 * PAYLOAD_SIZE)
+        .with_on_feedback(Arc::new(move |feedback: TwccFeedback| {
+            let feedback_tx = feedback_tx.clone();
+            Box::pin(async move {
+                let _ = feedback_tx.send(feedback);
+            })
+        }))
+        .build("")?;
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            rtp_header_extensions: vec![RTPHeaderExtension {
+                uri: TRANSPORT_CC_URI.to_owned(),
+                id: 1,
+            }],
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    let mut recorder = Recorder::new(2);
+    for i in 0..10u16 {
+        send_packet(&stream, i).await?;
+        recorder.record(1, i, 20_000 + i as i64 * 1_000);
+        tokio::time::advance(Duration::from_millis(1)).await;
+    }
+    receive_feedback(&stream, &mut recorder).await;
+
+    let feedback = feedback_rx.try_recv().expect("A feedback");
+    assert_eq!(feedback.packets.len(), 10);
+    assert!(feedback.packets.iter().all(|p| p.status.is_received()));
+    // the oldest packets were forgotten for the max bytes
+    assert_eq!(
+        feedback
+            .packets
+            .iter()
+            .filter_map(|p| p.sent.map(|s| s.sequence_number))
+            .collect::<Vec<_>>(),
+        vec![6, 7, 8, 9]
+    );
+
+    stream.close().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_gcc_sender_interceptor_without_transport_cc() -> Result<()> {
+    let icpr = Sender::builder().build("")?;
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    stream.write_rtp(&rtp::packet::Packet::default()).await?;
+    assert!(stream.written_rtp().await.is_some());
+
+    stream.close().await?;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_gcc_sender_interceptor_probes() -> Result<()> {
+    let rate = crate::pacer::Pacer::builder().rate();
+    let (target_tx, mut target_rx) = mpsc::unbounded_channel();
+    let icpr = Sender::builder()
+        .with_initial_bitrate(300_000)
+        .with_pacer(Arc::clone(&rate))
+        .with_on_target_bitrate(Arc::new(move |bitrate: u64| {
+            let target_tx = target_tx.clone();
+            Box::pin(async move {
+                let _ = target_tx.send(bitrate);
+            })
+        }))
+        .build("")?;
+    assert_eq!(rate.target_bitrate(), 300_000);
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            rtp_header_extensions: vec![RTPHeaderExtension {
+                uri: TRANSPORT_CC_URI.to_owned(),
+                id: 1,
+            }],
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    let mut recorder = Recorder::new(2);
+    send_packet(&stream, 0).await?;
+    recorder.record(1, 0, 20_000);
+    let clusters: Vec<usize> = std::iter::from_fn(|| rate.take_probe())
+        .map(|c| c.id)
+        .collect();
+    assert_eq!(clusters, vec![1, 2]);
+
+    // the pacer sends the first cluster, at about 8Mbps, which the link sustains
+    for i in 1..=10u16 {
+        tokio::time::advance(Duration::from_millis(1)).await;
+        let mut header = rtp::header::Header {
+            ssrc: 1,
+            ..Default::default()
+        };
+        header.set_extension(
+            1,
+            TransportCcExtension {
+                transport_sequence: i,
+            }
+            .marshal()?,
+        )?;
+        let mut attributes = Attributes::new();
+        attributes.insert(ATTR_PROBE_CLUSTER_ID, 1);
+        stream
+            .write_rtp_with_attributes(
+                &rtp::packet::Packet {
+                    header,
+                    payload: vec![0u8; PAYLOAD_SIZE].into(),
+                    ..Default::default()
+                },
+                &attributes,
+            )
+            .await?;
+        stream
+            .written_rtp()
+            .await
+            .expect("packet should be written");
+        recorder.record(1, i, 20_000 + i as i64 * 1_000);
+    }
+    receive_feedback(&stream, &mut recorder).await;
+
+    let bitrate = target_rx.try_recv().expect("target should have changed");
+    assert!(
+        bitrate > 1_000_000,
+        "target {bitrate} should have increased"
+    );
+    assert_eq!(rate.target_bitrate(), bitrate);
+
+    stream.close().await?;
+    Ok(())
+}
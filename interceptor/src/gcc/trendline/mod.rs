@@ -0,0 +1,239 @@
+#[cfg(test)]
+mod trendline_test;
+
+use std::collections::VecDeque;
+
+/// Packets sent within this many microseconds of the first one of a group are a burst, whose
+/// delay is measured as a whole.
+const BURST_DELTA_US: i64 = 5_000;
+
+const TRENDLINE_WINDOW_SIZE: usize = 20;
+const TRENDLINE_SMOOTHING: f64 = 0.9;
+const TRENDLINE_THRESHOLD_GAIN: f64 = 4.0;
+const MAX_NUM_DELTAS: usize = 60;
+
+const OVERUSE_TIME_THRESHOLD_MS: f64 = 10.0;
+const INITIAL_THRESHOLD_MS: f64 = 12.5;
+const THRESHOLD_GAIN_UP: f64 = 0.0087;
+const THRESHOLD_GAIN_DOWN: f64 = 0.039;
+
+/// BandwidthUsage is the state of the link the delay of the packets received tells.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum BandwidthUsage {
+    #[default]
+    Normal,
+    Underusing,
+    Overusing,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct PacketGroup {
+    first_send_us: i64,
+    send_us: i64,
+    arrival_us: i64,
+}
+
+/// TrendlineDetector detects the overuse of a link from the one way delay variation of the
+/// packets sent over it, as the delay-based controller of Google congestion control.
+///
+/// Packets are grouped into bursts by their send time. The trend of the delay between the
+/// groups is estimated with a linear regression over a window of them, and compared to a
+/// threshold adapting to its variations: a growing delay is a queue building up on the link.
+///
+/// ## Specifications
+///
+/// * [draft-ietf-rmcat-gcc-02 §5]
+///
+/// [draft-ietf-rmcat-gcc-02 §5]: https://datatracker.ietf.org/doc/html/draft-ietf-rmcat-gcc-02#section-5
+#[derive(Debug, Clone)]
+pub struct TrendlineDetector {
+    current_group: Option<PacketGroup>,
+    previous_group: Option<PacketGroup>,
+
+    first_arrival_ms: Option<f64>,
+    accumulated_delay_ms: f64,
+    smoothed_delay_ms: f64,
+    delays: VecDeque<(f64, f64)>,
+    num_deltas: usize,
+    trend: f64,
+
+    threshold_ms: f64,
+    last_threshold_update_ms: Option<f64>,
+    time_over_using_ms: Option<f64>,
+    overuse_count: u32,
+    usage: BandwidthUsage,
+}
+
+impl Default for TrendlineDetector {
+    fn default() -> Self {
+        TrendlineDetector {
+            current_group: None,
+            previous_group: None,
+
+            first_arrival_ms: None,
+            accumulated_delay_ms: 0.0,
+            smoothed_delay_ms: 0.0,
+            delays: VecDeque::with_capacity(TRENDLINE_WINDOW_SIZE + 1),
+            num_deltas: 0,
+            trend: 0.0,
+
+            threshold_ms: INITIAL_THRESHOLD_MS,
+            last_threshold_update_ms: None,
+            time_over_using_ms: None,
+            overuse_count: 0,
+            usage: BandwidthUsage::Normal,
+        }
+    }
+}
+
+impl TrendlineDetector {
+    /// new returns a detector which hasn't seen any packet.
+    pub fn new() -> Self {
+        TrendlineDetector::default()
+    }
+
+    /// trend returns the slope of the delay last estimated.
+    pub fn trend(&self) -> f64 {
+        self.trend
+    }
+
+    /// incoming_packet records a packet sent at send_us arriving at arrival_us microseconds,
+    /// both on the clocks of their own side. It returns the state of the link detected when
+    /// the packet starts a new group and the delay of the previous one could be measured.
+    /// An overuse is returned once, the state is back to normal until the next one.
+    pub fn incoming_packet(&mut self, send_us: i64, arrival_us: i64) -> Option<BandwidthUsage> {
+        match &mut self.current_group {
+            Some(group) if send_us < group.first_send_us => {
+                // reordered, the delay of its group was measured already
+                None
+            }
+            Some(group) if send_us - group.first_send_us <= BURST_DELTA_US => {
+                group.send_us = group.send_us.max(send_us);
+                group.arrival_us = group.arrival_us.max(arrival_us);
+                None
+            }
+            _ => {
+                let group = PacketGroup {
+                    first_send_us: send_us,
+                    send_us,
+                    arrival_us,
+                };
+                let usage = if let (Some(previous), Some(current)) =
+                    (self.previous_group, self.current_group)
+                {
+                    self.update_trendline(
+                        (current.send_us - previous.send_us) as f64 / 1000.0,
+                        (current.arrival_us - previous.arrival_us) as f64 / 1000.0,
+                        current.arrival_us as f64 / 1000.0,
+                    );
+                    let usage = self.usage;
+                    if usage == BandwidthUsage::Overusing {
+                        self.usage = BandwidthUsage::Normal;
+                    }
+                    Some(usage)
+                } else {
+                    None
+                };
+                self.previous_group = self.current_group;
+                self.current_group = Some(group);
+                usage
+            }
+        }
+    }
+
+    fn update_trendline(&mut self, send_delta_ms: f64, arrival_delta_ms: f64, arrival_ms: f64) {
+        let first_arrival_ms = *self.first_arrival_ms.get_or_insert(arrival_ms);
+        self.num_deltas = (self.num_deltas + 1).min(1000);
+        self.accumulated_delay_ms += arrival_delta_ms - send_delta_ms;
+        self.smoothed_delay_ms = TRENDLINE_SMOOTHING * self.smoothed_delay_ms
+            + (1.0 - TRENDLINE_SMOOTHING) * self.accumulated_delay_ms;
+
+        self.delays
+            .push_back((arrival_ms - first_arrival_ms, self.smoothed_delay_ms));
+        if self.delays.len() > TRENDLINE_WINDOW_SIZE {
+            self.delays.pop_front();
+        }
+        let previous_trend = self.trend;
+        if self.delays.len() == TRENDLINE_WINDOW_SIZE {
+            if let Some(slope) = linear_fit_slope(&self.delays) {
+                self.trend = slope;
+            }
+        }
+
+        self.detect(previous_trend, send_delta_ms, arrival_ms);
+    }
+
+    fn detect(&mut self, previous_trend: f64, send_delta_ms: f64, now_ms: f64) {
+        let modified_trend =
+            self.num_deltas.min(MAX_NUM_DELTAS) as f64 * self.trend * TRENDLINE_THRESHOLD_GAIN;
+
+        if modified_trend > self.threshold_ms {
+            let time_over_using_ms = match self.time_over_using_ms {
+                None => send_delta_ms / 2.0,
+                Some(t) => t + send_delta_ms,
+            };
+            self.overuse_count += 1;
+            if time_over_using_ms > OVERUSE_TIME_THRESHOLD_MS
+                && self.overuse_count > 1
+                && self.trend >= previous_trend
+            {
+                self.time_over_using_ms = Some(0.0);
+                self.overuse_count = 0;
+                self.usage = BandwidthUsage::Overusing;
+            } else {
+                self.time_over_using_ms = Some(time_over_using_ms);
+            }
+        } else {
+            self.time_over_using_ms = None;
+            self.overuse_count = 0;
+            self.usage = if modified_trend < -self.threshold_ms {
+                BandwidthUsage::Underusing
+            } else {
+                BandwidthUsage::Normal
+            };
+        }
+
+        self.update_threshold(modified_trend, now_ms);
+    }
+
+    /// update_threshold adapts the threshold to the variations of the trend, so that the
+    /// detector isn't starved by concurrent TCP flows.
+    fn update_threshold(&mut self, modified_trend: f64, now_ms: f64) {
+        let last_update_ms = *self.last_threshold_update_ms.get_or_insert(now_ms);
+        if modified_trend.abs() > self.threshold_ms + 15.0 {
+            // don't adapt to spikes
+            self.last_threshold_update_ms = Some(now_ms);
+            return;
+        }
+
+        let gain = if modified_trend.abs() < self.threshold_ms {
+            THRESHOLD_GAIN_DOWN
+        } else {
+            THRESHOLD_GAIN_UP
+        };
+        let elapsed_ms = (now_ms - last_update_ms).min(100.0);
+        self.threshold_ms += gain * (modified_trend.abs() - self.threshold_ms) * elapsed_ms;
+        self.threshold_ms = self.threshold_ms.clamp(6.0, 600.0);
+        self.last_threshold_update_ms = Some(now_ms);
+    }
+}
+
+fn linear_fit_slope(points: &VecDeque<(f64, f64)>) -> Option<f64> {
+    let n = points.len() as f64;
+    let (sum_x, sum_y) = points
+        .iter()
+        .fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+    let (mean_x, mean_y) = (sum_x / n, sum_y / n);
+
+    let (numerator, denominator) = points.iter().fold((0.0, 0.0), |(num, den), (x, y)| {
+        (
+            num + (x - mean_x) * (y - mean_y),
+            den + (x - mean_x) * (x - mean_x),
+        )
+    });
+    if denominator == 0.0 {
+        None
+    } else {
+        Some(numerator / denominator)
+    }
+}
@@ -0,0 +1,58 @@
+use super::*;
+
+/// run feeds the detector a packet every 10ms for count packets, whose one way delay grows
+/// by delay_growth_us each, and returns the states detected.
+fn run(detector: &mut TrendlineDetector, count: i64, delay_growth_us: i64) -> Vec<BandwidthUsage> {
+    (0..count)
+        .filter_map(|i| {
+            let send_us = i * 10_000;
+            detector.incoming_packet(send_us, send_us + 20_000 + i * delay_growth_us)
+        })
+        .collect()
+}
+
+#[test]
+fn test_trendline_steady_delay() {
+    let mut detector = TrendlineDetector::new();
+    let usages = run(&mut detector, 100, 0);
+
+    assert!(!usages.is_empty());
+    assert!(
+        usages.iter().all(|u| *u == BandwidthUsage::Normal),
+        "{usages:?}"
+    );
+    assert_eq!(detector.trend(), 0.0);
+}
+
+#[test]
+fn test_trendline_growing_delay() {
+    let mut detector = TrendlineDetector::new();
+    let usages = run(&mut detector, 100, 2_000);
+
+    let overuses = usages
+        .iter()
+        .position(|u| *u == BandwidthUsage::Overusing)
+        .expect("a growing delay should be detected");
+    // an overuse is returned once, until it is detected again
+    assert_ne!(usages.get(overuses + 1), Some(&BandwidthUsage::Overusing));
+    assert!(detector.trend() > 0.0);
+}
+
+#[test]
+fn test_trendline_burst() {
+    let mut detector = TrendlineDetector::new();
+    // packets sent within the burst delta are a single group
+    for i in 0..3 {
+        assert_eq!(
+            detector.incoming_packet(i * 1_000, 20_000 + i * 1_000),
+            None
+        );
+    }
+    assert_eq!(detector.incoming_packet(10_000, 30_000), None);
+    assert_eq!(
+        detector.incoming_packet(20_000, 40_000),
+        Some(BandwidthUsage::Normal)
+    );
+    // reordered packets of a group measured already are ignored
+    assert_eq!(detector.incoming_packet(1_000, 40_000), None);
+}
@@ -0,0 +1,125 @@
+use tokio::sync::Notify;
+
+use super::*;
+
+struct ImpairmentStreamState {
+    /// Packets in flight, by release time and order sent.
+    packets: BTreeMap<(Instant, u64), (rtp::packet::Packet, Attributes)>,
+    sent: u64,
+    /// Error the parent reader returned, read once the packets in flight are.
+    error: Option<Error>,
+    closed: bool,
+}
+
+/// ImpairmentStream holds the packets of a stream sent over the link until they are released.
+pub(super) struct ImpairmentStream {
+    link: Arc<Link>,
+    state: util::sync::Mutex<ImpairmentStreamState>,
+    pushed: Notify,
+}
+
+impl ImpairmentStream {
+    pub(super) fn new(link: Arc<Link>) -> Self {
+        ImpairmentStream {
+            link,
+            state: util::sync::Mutex::new(ImpairmentStreamState {
+                packets: BTreeMap::new(),
+                sent: 0,
+                error: None,
+                closed: false,
+            }),
+            pushed: Notify::new(),
+        }
+    }
+
+    /// send sends pkt over the link, to be released when the link decides.
+    pub(super) fn send(&self, pkt: &rtp::packet::Packet, attributes: &Attributes) {
+        let releases = self.link.send(pkt.marshal_size(), Instant::now());
+        if releases.is_empty() {
+            return;
+        }
+
+        {
+            let mut state = self.state.lock();
+            for release in releases {
+                let sent = state.sent;
+                state.sent += 1;
+                state
+                    .packets
+                    .insert((release, sent), (pkt.clone(), attributes.clone()));
+            }
+        }
+        self.pushed.notify_one();
+    }
+
+    /// close ends the stream with err, once the packets released are read. The packets still
+    /// in flight are dropped, when closed without error.
+    pub(super) fn close(&self, err: Option<Error>) {
+        {
+            let mut state = self.state.lock();
+            if err.is_none() {
+                state.packets.clear();
+            }
+            state.error = err;
+            state.closed = true;
+        }
+        self.pushed.notify_one();
+    }
+
+    /// next returns the next packet once it is released.
+    pub(super) async fn next(&self) -> Result<(rtp::packet::Packet, Attributes)> {
+        loop {
+            let next_release = {
+                let mut state = self.state.lock();
+                let now = Instant::now();
+                match state.packets.first_key_value() {
+                    Some((&(release, _), _)) if release <= now => {
+                        if let Some((_, packet)) = state.packets.pop_first() {
+                            return Ok(packet);
+                        }
+                    }
+                    _ => {}
+                }
+                if state.closed && state.packets.is_empty() {
+                    return Err(state.error.take().unwrap_or(Error::ErrIoEOF));
+                }
+                state
+                    .packets
+                    .first_key_value()
+                    .map(|(&(release, _), _)| release)
+            };
+
+            tokio::select! {
+                _ = self.pushed.notified() => {}
+                _ = tokio::time::sleep_until(next_release.unwrap_or_else(Instant::now)),
+                    if next_release.is_some() => {}
+            }
+        }
+    }
+}
+
+pub(super) struct ImpairmentRtpWriter {
+    pub(super) stream: Arc<ImpairmentStream>,
+}
+
+/// RTPWriter is used by Interceptor.bind_local_stream.
+#[async_trait]
+impl RTPWriter for ImpairmentRtpWriter {
+    /// write sends pkt over the link, which writes it to the next writer once released.
+    async fn write(&self, pkt: &rtp::packet::Packet, a: &Attributes) -> Result<usize> {
+        self.stream.send(pkt, a);
+        Ok(pkt.marshal_size())
+    }
+}
+
+#[async_trait]
+impl RTPReader for ImpairmentStream {
+    /// read returns the next packet received, once the link releases it.
+    async fn read(
+        &self,
+        _buf: &mut [u8],
+        _attributes: &Attributes,
+    ) -> Result<(rtp::packet::Packet, Attributes)> {
+        self.next().await
+    }
+}
@@ -0,0 +1,215 @@
+use super::*;
+use crate::mock::mock_stream::MockStream;
+use crate::test::timeout_or_fail;
+
+fn conditions() -> LinkConditions {
+    LinkConditions {
+        loss: 0.0,
+        duplication: 0.0,
+        reordering: 0.0,
+        reorder_delay: Duration::ZERO,
+        delay: Duration::ZERO,
+        jitter: Duration::ZERO,
+        bitrate: 0,
+        max_queue_delay: DEFAULT_MAX_QUEUE_DELAY,
+    }
+}
+
+#[test]
+fn test_link_loss_and_duplication() {
+    let send_all = |seed| {
+        let link = Link::new(
+            LinkConditions {
+                loss: 0.2,
+                duplication: 0.1,
+                ..conditions()
+            },
+            seed,
+        );
+        let now = Instant::now();
+        (0..1000)
+            .map(|_| link.send(100, now).len())
+            .collect::<Vec<usize>>()
+    };
+
+    let copies = send_all(1);
+    let lost = copies.iter().filter(|&&n| n == 0).count();
+    let duplicated = copies.iter().filter(|&&n| n == 2).count();
+    assert!((150..250).contains(&lost), "{lost} packets lost");
+    assert!(
+        (50..150).contains(&duplicated),
+        "{duplicated} packets duplicated"
+    );
+
+    // the same seed gives the same decisions
+    assert_eq!(send_all(1), copies);
+    assert_ne!(send_all(2), copies);
+}
+
+#[test]
+fn test_link_delay_and_jitter() {
+    let link = Link::new(
+        LinkConditions {
+            delay: Duration::from_millis(100),
+            jitter: Duration::from_millis(50),
+            ..conditions()
+        },
+        0,
+    );
+    let start = Instant::now();
+    let mut last = start;
+    for i in 0..100 {
+        let now = start + Duration::from_millis(i);
+        let release = link.send(100, now)[0];
+        assert!(release >= now + Duration::from_millis(100));
+        assert!(release <= now + Duration::from_millis(150));
+        // jitter keeps the packets in order
+        assert!(release >= last);
+        last = release;
+    }
+}
+
+#[test]
+fn test_link_reordering() {
+    let link = Link::new(
+        LinkConditions {
+            delay: Duration::from_millis(10),
+            reordering: 0.5,
+            reorder_delay: Duration::from_millis(30),
+            ..conditions()
+        },
+        0,
+    );
+    let start = Instant::now();
+    let releases: Vec<Instant> = (0..100)
+        .map(|i| link.send(100, start + Duration::from_millis(i * 5))[0])
+        .collect();
+    let overtaken = releases
+        .windows(2)
+        .filter(|releases| releases[1] < releases[0])
+        .count();
+    assert!(overtaken > 10, "{overtaken} packets overtaken");
+    for (i, release) in releases.iter().enumerate() {
+        let sent = start + Duration::from_millis(i as u64 * 5);
+        let delay = *release - sent;
+        assert!(
+            delay == Duration::from_millis(10) || delay == Duration::from_millis(40),
+            "delay {delay:?}"
+        );
+    }
+}
+
+#[test]
+fn test_link_bitrate() {
+    // 100 bytes take 100ms to send
+    let link = Link::new(
+        LinkConditions {
+            bitrate: 8_000,
+            max_queue_delay: Duration::from_millis(250),
+            ..conditions()
+        },
+        0,
+    );
+    let now = Instant::now();
+    let releases: Vec<Vec<Instant>> = (0..4).map(|_| link.send(100, now)).collect();
+    for (i, release) in releases.iter().take(3).enumerate() {
+        assert_eq!(
+            release,
+            &vec![now + Duration::from_millis(100 * (i as u64 + 1))]
+        );
+    }
+    // dropped, as it would be queued for 300ms
+    assert!(releases[3].is_empty());
+
+    // the queue is empty once the packets are sent
+    let later = now + Duration::from_secs(1);
+    assert_eq!(
+        link.send(100, later),
+        vec![later + Duration::from_millis(100)]
+    );
+}
+
+#[tokio::test]
+async fn test_impairment_interceptor() -> Result<()> {
+    let icpr = Impairment::builder()
+        .with_delay(Duration::from_millis(50))
+        .with_duplication(1.0)
+        .build("")?;
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    let pkt = rtp::packet::Packet {
+        header: rtp::header::Header {
+            ssrc: 1,
+            sequence_number: 7,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    stream.write_rtp(&pkt).await?;
+    let written = tokio::time::timeout(Duration::from_millis(20), stream.written_rtp()).await;
+    assert!(written.is_err(), "the packet should be delayed");
+    for _ in 0..2 {
+        let written = timeout_or_fail(Duration::from_millis(100), stream.written_rtp())
+            .await
+            .expect("A packet");
+        assert_eq!(written.header.sequence_number, 7);
+    }
+
+    stream.receive_rtp(pkt).await;
+    let read = tokio::time::timeout(Duration::from_millis(20), stream.read_rtp()).await;
+    assert!(read.is_err(), "the packet should be delayed");
+    for _ in 0..2 {
+        let read = timeout_or_fail(Duration::from_millis(100), stream.read_rtp())
+            .await
+            .expect("A packet")?;
+        assert_eq!(read.header.sequence_number, 7);
+    }
+
+    stream.close().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_impairment_interceptor_outgoing_only() -> Result<()> {
+    let icpr = Impairment::builder()
+        .with_direction(ImpairmentDirection::Outgoing)
+        .with_loss(1.0)
+        .build("")?;
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    let pkt = rtp::packet::Packet {
+        header: rtp::header::Header {
+            ssrc: 1,
+            sequence_number: 7,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    stream.write_rtp(&pkt).await?;
+    let written = tokio::time::timeout(Duration::from_millis(20), stream.written_rtp()).await;
+    assert!(written.is_err(), "the packet should be lost");
+
+    // the incoming packets aren't impaired
+    stream.receive_rtp(pkt).await;
+    let read = timeout_or_fail(Duration::from_millis(10), stream.read_rtp())
+        .await
+        .expect("A packet")?;
+    assert_eq!(read.header.sequence_number, 7);
+
+    stream.close().await?;
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::*;
+
+/// LinkConditions are the impairments of a link, applied to each packet sent over it.
+#[derive(Debug, Clone)]
+pub(super) struct LinkConditions {
+    pub(super) loss: f64,
+    pub(super) duplication: f64,
+    pub(super) reordering: f64,
+    pub(super) reorder_delay: Duration,
+    pub(super) delay: Duration,
+    pub(super) jitter: Duration,
+    pub(super) bitrate: u64,
+    pub(super) max_queue_delay: Duration,
+}
+
+struct LinkState {
+    rng: StdRng,
+    /// Time the link is done sending the packets queued at, with a bitrate.
+    busy_until: Option<Instant>,
+    /// Time the last packet not reordered is released at.
+    last_release: Option<Instant>,
+}
+
+/// Link decides when the packets sent over it are released, from their size and the time
+/// they are sent at, using a random generator seeded for the decisions to be reproducible.
+pub(super) struct Link {
+    conditions: LinkConditions,
+    state: util::sync::Mutex<LinkState>,
+}
+
+impl Link {
+    pub(super) fn new(conditions: LinkConditions, seed: u64) -> Self {
+        Link {
+            conditions,
+            state: util::sync::Mutex::new(LinkState {
+                rng: StdRng::seed_from_u64(seed),
+                busy_until: None,
+                last_release: None,
+            }),
+        }
+    }
+
+    /// send returns the times the packet of size bytes sent at now is released at: none if
+    /// it is lost, twice the same time if it is duplicated.
+    pub(super) fn send(&self, size: usize, now: Instant) -> Vec<Instant> {
+        let c = &self.conditions;
+        let mut state = self.state.lock();
+        // the same numbers are drawn for each packet, whatever the decisions
+        let (lost, jitter, reordered, duplicated) = (
+            state.rng.gen::<f64>() < c.loss,
+            c.jitter.mul_f64(state.rng.gen::<f64>()),
+            state.rng.gen::<f64>() < c.reordering,
+            state.rng.gen::<f64>() < c.duplication,
+        );
+        if lost {
+            return vec![];
+        }
+
+        let mut sent = now;
+        if c.bitrate > 0 {
+            let start = state
+                .busy_until
+                .map_or(now, |busy_until| busy_until.max(now));
+            // packets which would be queued for too long are dropped, as by a router
+            if start - now > c.max_queue_delay {
+                return vec![];
+            }
+            sent = start + Duration::from_secs_f64(size as f64 * 8.0 / c.bitrate as f64);
+            state.busy_until = Some(sent);
+        }
+
+        // jitter doesn't reorder the packets, only reordering does
+        let mut release = sent + c.delay + jitter;
+        if let Some(last_release) = state.last_release {
+            release = release.max(last_release);
+        }
+        state.last_release = Some(release);
+        if reordered {
+            release += c.reorder_delay;
+        }
+
+        if duplicated {
+            vec![release, release]
+        } else {
+            vec![release]
+        }
+    }
+}
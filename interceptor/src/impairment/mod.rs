@@ -0,0 +1,340 @@
+mod impairment_stream;
+#[cfg(test)]
+mod impairment_test;
+mod link;
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use impairment_stream::{ImpairmentRtpWriter, ImpairmentStream};
+use link::{Link, LinkConditions};
+use tokio::sync::{mpsc, Mutex};
+use tokio::time::Instant;
+use util::MarshalSize;
+use waitgroup::WaitGroup;
+
+use crate::error::{Error, Result};
+use crate::stream_info::StreamInfo;
+use crate::{
+    Attributes, Interceptor, InterceptorBuilder, RTCPReader, RTCPWriter, RTPReader, RTPWriter,
+};
+
+const DEFAULT_MAX_QUEUE_DELAY: Duration = Duration::from_millis(500);
+const DEFAULT_SEED: u64 = 0;
+
+const RECEIVE_MTU: usize = 1460;
+
+/// ImpairmentDirection is the direction of the RTP packets impaired.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ImpairmentDirection {
+    /// The packets written to the local streams.
+    Outgoing,
+    /// The packets read from the remote streams.
+    Incoming,
+    /// Both, over a link for each direction.
+    Both,
+}
+
+/// ImpairmentBuilder can be used to configure Impairment Interceptor. No impairment is applied
+/// unless configured.
+#[derive(Default)]
+pub struct ImpairmentBuilder {
+    direction: Option<ImpairmentDirection>,
+    loss: Option<f64>,
+    duplication: Option<f64>,
+    reordering: Option<(f64, Duration)>,
+    delay: Option<Duration>,
+    jitter: Option<Duration>,
+    bitrate: Option<u64>,
+    max_queue_delay: Option<Duration>,
+    seed: Option<u64>,
+}
+
+impl ImpairmentBuilder {
+    /// with_direction sets the direction of the packets impaired, both by default.
+    pub fn with_direction(mut self, direction: ImpairmentDirection) -> ImpairmentBuilder {
+        self.direction = Some(direction);
+        self
+    }
+
+    /// with_loss sets the probability of each packet to be lost.
+    pub fn with_loss(mut self, loss: f64) -> ImpairmentBuilder {
+        self.loss = Some(loss);
+        self
+    }
+
+    /// with_duplication sets the probability of each packet to be duplicated.
+    pub fn with_duplication(mut self, duplication: f64) -> ImpairmentBuilder {
+        self.duplication = Some(duplication);
+        self
+    }
+
+    /// with_reordering sets the probability of each packet to be delayed by a further delay,
+    /// for the packets following it to overtake it.
+    pub fn with_reordering(mut self, reordering: f64, delay: Duration) -> ImpairmentBuilder {
+        self.reordering = Some((reordering, delay));
+        self
+    }
+
+    /// with_delay sets the delay of the packets over the link.
+    pub fn with_delay(mut self, delay: Duration) -> ImpairmentBuilder {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// with_jitter sets the max random delay added to the delay of each packet. The packets
+    /// are kept in order.
+    pub fn with_jitter(mut self, jitter: Duration) -> ImpairmentBuilder {
+        self.jitter = Some(jitter);
+        self
+    }
+
+    /// with_bitrate sets the bitrate of the link in bits per second, the packets sent above
+    /// which are queued.
+    pub fn with_bitrate(mut self, bitrate: u64) -> ImpairmentBuilder {
+        self.bitrate = Some(bitrate);
+        self
+    }
+
+    /// with_max_queue_delay sets the time the packets are queued for at most, with a bitrate,
+    /// above which they are dropped.
+    pub fn with_max_queue_delay(mut self, max_queue_delay: Duration) -> ImpairmentBuilder {
+        self.max_queue_delay = Some(max_queue_delay);
+        self
+    }
+
+    /// with_seed sets the seed of the random decisions, which are the same for the packets
+    /// sent in the same order with the same seed.
+    pub fn with_seed(mut self, seed: u64) -> ImpairmentBuilder {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+impl InterceptorBuilder for ImpairmentBuilder {
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        let (reordering, reorder_delay) = self.reordering.unwrap_or_default();
+        let conditions = LinkConditions {
+            loss: self.loss.unwrap_or_default(),
+            duplication: self.duplication.unwrap_or_default(),
+            reordering,
+            reorder_delay,
+            delay: self.delay.unwrap_or_default(),
+            jitter: self.jitter.unwrap_or_default(),
+            bitrate: self.bitrate.unwrap_or_default(),
+            max_queue_delay: self.max_queue_delay.unwrap_or(DEFAULT_MAX_QUEUE_DELAY),
+        };
+        let direction = self.direction.unwrap_or(ImpairmentDirection::Both);
+        let seed = self.seed.unwrap_or(DEFAULT_SEED);
+        let outgoing = match direction {
+            ImpairmentDirection::Outgoing | ImpairmentDirection::Both => {
+                Some(Arc::new(Link::new(conditions.clone(), seed)))
+            }
+            ImpairmentDirection::Incoming => None,
+        };
+        let incoming = match direction {
+            ImpairmentDirection::Incoming | ImpairmentDirection::Both => {
+                Some(Arc::new(Link::new(conditions, seed.wrapping_add(1))))
+            }
+            ImpairmentDirection::Outgoing => None,
+        };
+
+        Ok(Arc::new(Impairment {
+            outgoing,
+            incoming,
+            local_streams: Mutex::new(HashMap::new()),
+            remote_streams: Mutex::new(HashMap::new()),
+            wg: Mutex::new(Some(WaitGroup::new())),
+        }))
+    }
+}
+
+/// Impairment interceptor impairs the network the RTP packets are sent and received over,
+/// with loss, duplication, reordering, delay, jitter and a limited bitrate, for testing how
+/// the other interceptors adapt to it. The random decisions are seeded, so that a test sending
+/// the same packets gets the same impairments.
+///
+/// The outgoing packets should be impaired by an interceptor added first, for the others to
+/// send them over the impaired link, and the incoming packets by one added last.
+pub struct Impairment {
+    outgoing: Option<Arc<Link>>,
+    incoming: Option<Arc<Link>>,
+    local_streams: Mutex<HashMap<u32, Arc<ImpairmentStream>>>,
+    /// Closes the reading of each remote stream, when dropped.
+    remote_streams: Mutex<HashMap<u32, mpsc::Sender<()>>>,
+
+    wg: Mutex<Option<WaitGroup>>,
+}
+
+impl Impairment {
+    /// builder returns a new ImpairmentBuilder.
+    pub fn builder() -> ImpairmentBuilder {
+        ImpairmentBuilder::default()
+    }
+
+    async fn run_local(
+        next_rtp_writer: Arc<dyn RTPWriter + Send + Sync>,
+        stream: Arc<ImpairmentStream>,
+    ) {
+        while let Ok((pkt, attributes)) = stream.next().await {
+            if let Err(err) = next_rtp_writer.write(&pkt, &attributes).await {
+                log::warn!("failed writing impaired packet: {}", err);
+            }
+        }
+    }
+
+    async fn run_remote(
+        parent_rtp_reader: Arc<dyn RTPReader + Send + Sync>,
+        stream: Arc<ImpairmentStream>,
+        mut close_rx: mpsc::Receiver<()>,
+    ) {
+        let mut buf = vec![0u8; RECEIVE_MTU];
+        let a = Attributes::new();
+        loop {
+            tokio::select! {
+                _ = close_rx.recv() => {
+                    stream.close(None);
+                    return;
+                }
+                result = parent_rtp_reader.read(&mut buf, &a) => match result {
+                    Ok((pkt, attributes)) => stream.send(&pkt, &attributes),
+                    Err(err) => {
+                        stream.close(Some(err));
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Interceptor for Impairment {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        reader
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        writer
+    }
+
+    /// bind_local_stream returns a writer sending the packets over the impaired link, which
+    /// writes them to writer once released.
+    async fn bind_local_stream(
+        &self,
+        info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        let link = match &self.outgoing {
+            Some(link) => Arc::clone(link),
+            None => return writer,
+        };
+        let mut w = {
+            let wait_group = self.wg.lock().await;
+            match wait_group.as_ref() {
+                Some(wg) => Some(wg.worker()),
+                None => return writer,
+            }
+        };
+
+        let stream = Arc::new(ImpairmentStream::new(link));
+        {
+            let mut local_streams = self.local_streams.lock().await;
+            if let Some(previous) = local_streams.insert(info.ssrc, Arc::clone(&stream)) {
+                previous.close(None);
+            }
+        }
+
+        let s = Arc::clone(&stream);
+        tokio::spawn(async move {
+            let _d = w.take();
+            Impairment::run_local(writer, s).await;
+        });
+
+        Arc::new(ImpairmentRtpWriter { stream })
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, info: &StreamInfo) {
+        let mut local_streams = self.local_streams.lock().await;
+        if let Some(stream) = local_streams.remove(&info.ssrc) {
+            stream.close(None);
+        }
+    }
+
+    /// bind_remote_stream returns a reader of the packets received over the impaired link,
+    /// which are read from the parent reader as soon as they are received.
+    async fn bind_remote_stream(
+        &self,
+        info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        let link = match &self.incoming {
+            Some(link) => Arc::clone(link),
+            None => return reader,
+        };
+        let mut w = {
+            let wait_group = self.wg.lock().await;
+            match wait_group.as_ref() {
+                Some(wg) => Some(wg.worker()),
+                None => return reader,
+            }
+        };
+
+        let stream = Arc::new(ImpairmentStream::new(link));
+        let (close_tx, close_rx) = mpsc::channel(1);
+        {
+            let mut remote_streams = self.remote_streams.lock().await;
+            remote_streams.insert(info.ssrc, close_tx);
+        }
+
+        let s = Arc::clone(&stream);
+        tokio::spawn(async move {
+            let _d = w.take();
+            Impairment::run_remote(reader, s, close_rx).await;
+        });
+
+        stream
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, info: &StreamInfo) {
+        let mut remote_streams = self.remote_streams.lock().await;
+        remote_streams.remove(&info.ssrc);
+    }
+
+    /// close drops the packets in flight and closes the Interceptor.
+    async fn close(&self) -> Result<()> {
+        {
+            let mut local_streams = self.local_streams.lock().await;
+            for (_, stream) in local_streams.drain() {
+                stream.close(None);
+            }
+        }
+        {
+            let mut remote_streams = self.remote_streams.lock().await;
+            remote_streams.clear();
+        }
+
+        {
+            let mut wait_group = self.wg.lock().await;
+            if let Some(wg) = wait_group.take() {
+                wg.wait().await;
+            }
+        }
+
+        Ok(())
+    }
+}
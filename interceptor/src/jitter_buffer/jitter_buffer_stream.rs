@@ -0,0 +1,90 @@
+use tokio::sync::Notify;
+
+use super::*;
+
+struct JitterBufferState {
+    buffer: PlayoutBuffer<(rtp::packet::Packet, Attributes)>,
+    /// Error the parent reader returned, read once the packets buffered are.
+    error: Option<Error>,
+    closed: bool,
+}
+
+pub(super) struct JitterBufferStream {
+    state: util::sync::Mutex<JitterBufferState>,
+    pushed: Notify,
+}
+
+impl JitterBufferStream {
+    pub(super) fn new(buffer: PlayoutBuffer<(rtp::packet::Packet, Attributes)>) -> Self {
+        JitterBufferStream {
+            state: util::sync::Mutex::new(JitterBufferState {
+                buffer,
+                error: None,
+                closed: false,
+            }),
+            pushed: Notify::new(),
+        }
+    }
+
+    pub(super) fn push(&self, pkt: rtp::packet::Packet, attributes: Attributes) {
+        {
+            let mut state = self.state.lock();
+            let (sequence_number, timestamp) = (pkt.header.sequence_number, pkt.header.timestamp);
+            state.buffer.push(
+                sequence_number,
+                timestamp,
+                (pkt, attributes),
+                Instant::now(),
+            );
+        }
+        self.pushed.notify_one();
+    }
+
+    /// close ends the stream with err, once the packets buffered are read.
+    pub(super) fn close(&self, err: Error) {
+        {
+            let mut state = self.state.lock();
+            state.error = Some(err);
+            state.closed = true;
+        }
+        self.pushed.notify_one();
+    }
+}
+
+#[async_trait]
+impl RTPReader for JitterBufferStream {
+    /// read returns the next packet buffered once it is due, or right away once the stream
+    /// is closed.
+    async fn read(
+        &self,
+        _buf: &mut [u8],
+        _attributes: &Attributes,
+    ) -> Result<(rtp::packet::Packet, Attributes)> {
+        loop {
+            let next_release_time = {
+                let mut state = self.state.lock();
+                let released = if state.closed {
+                    state.buffer.pop_next()
+                } else {
+                    state.buffer.pop(Instant::now())
+                };
+                if let Some(((pkt, mut attributes), lost)) = released {
+                    if lost > 0 {
+                        attributes.insert(ATTR_PACKETS_LOST, lost);
+                    }
+                    return Ok((pkt, attributes));
+                }
+                if state.closed {
+                    return Err(state.error.take().unwrap_or(Error::ErrIoEOF));
+                }
+                state.buffer.next_release_time()
+            };
+
+            tokio::select! {
+                _ = self.pushed.notified() => {}
+                _ = tokio::time::sleep_until(next_release_time.unwrap_or_else(Instant::now)),
+                    if next_release_time.is_some() => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use super::*;
+use crate::mock::mock_stream::MockStream;
+
+const CLOCK_RATE: u32 = 90_000;
+/// A frame every 33.3ms, at 30 frames per second.
+const FRAME_TICKS: u32 = 3_000;
+const TARGET_DELAY: Duration = Duration::from_millis(50);
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn test_playout_buffer_reorders_and_smooths() {
+    let mut buffer = PlayoutBuffer::new(TARGET_DELAY, DEFAULT_MAX_PACKETS, CLOCK_RATE);
+    let start = Instant::now();
+    // the sequence numbers and the timestamps wrap meanwhile
+    let (first_sequence_number, first_timestamp) = (65_534u16, u32::MAX - FRAME_TICKS + 1);
+    let packet = |i: u16| {
+        (
+            first_sequence_number.wrapping_add(i),
+            first_timestamp.wrapping_add(FRAME_TICKS * i as u32),
+            i,
+        )
+    };
+
+    for (i, arrival) in [(0, ms(0)), (2, ms(70)), (1, ms(75)), (3, ms(100))] {
+        let (sequence_number, timestamp, i) = packet(i);
+        assert!(buffer.push(sequence_number, timestamp, i, start + arrival));
+    }
+    assert_eq!(buffer.len(), 4);
+
+    assert_eq!(buffer.pop(start + ms(49)), None);
+    assert_eq!(buffer.next_release_time(), Some(start + TARGET_DELAY));
+    assert_eq!(buffer.pop(start + ms(50)), Some((0, 0)));
+    assert_eq!(buffer.pop(start + ms(50)), None);
+    // released at the pace they were sent at, whatever their jitter
+    let released: Vec<Instant> = std::iter::from_fn(|| {
+        let next = buffer.next_release_time()?;
+        buffer.pop(next).map(|_| next)
+    })
+    .collect();
+    let intervals: Vec<u128> = released
+        .windows(2)
+        .map(|w| (w[1] - w[0]).as_micros())
+        .collect();
+    assert_eq!(released[0] - start, Duration::from_micros(83_333));
+    assert_eq!(intervals, vec![33_333, 33_334]);
+    assert!(buffer.is_empty());
+    assert_eq!(buffer.lost_packets(), 0);
+}
+
+#[test]
+fn test_playout_buffer_losses() {
+    let mut buffer = PlayoutBuffer::new(TARGET_DELAY, DEFAULT_MAX_PACKETS, CLOCK_RATE);
+    let start = Instant::now();
+    assert!(buffer.push(10, 0, 10, start));
+    assert!(buffer.push(13, FRAME_TICKS, 13, start + ms(33)));
+    assert!(!buffer.push(10, 0, 10, start + ms(34)));
+
+    assert_eq!(buffer.pop(start + ms(50)), Some((10, 0)));
+    // the missing packets are waited for until the next one is due
+    assert!(buffer.push(11, FRAME_TICKS, 11, start + ms(60)));
+    assert_eq!(buffer.pop(start + ms(84)), Some((11, 0)));
+    assert_eq!(buffer.pop(start + ms(84)), Some((13, 1)));
+    assert_eq!(buffer.lost_packets(), 1);
+
+    // too late
+    assert!(!buffer.push(12, FRAME_TICKS, 12, start + ms(90)));
+    assert_eq!(buffer.dropped_packets(), 2);
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn test_playout_buffer_max_packets() {
+    let mut buffer = PlayoutBuffer::new(TARGET_DELAY, 2, CLOCK_RATE);
+    let start = Instant::now();
+    for i in 0..3 {
+        assert!(buffer.push(i, 0, i, start));
+    }
+    assert_eq!(buffer.pop(start), Some((0, 0)));
+    assert_eq!(buffer.pop(start), None);
+    assert_eq!(buffer.pop_next(), Some((1, 0)));
+}
+
+#[test]
+fn test_playout_buffer_without_clock_rate() {
+    let mut buffer = PlayoutBuffer::new(TARGET_DELAY, DEFAULT_MAX_PACKETS, 0);
+    let start = Instant::now();
+    assert!(buffer.push(0, 0, 0, start));
+    assert!(buffer.push(1, 90_000, 1, start + ms(10)));
+    assert_eq!(buffer.pop(start + ms(50)), Some((0, 0)));
+    assert_eq!(buffer.next_release_time(), Some(start + ms(60)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_jitter_buffer_interceptor() -> Result<()> {
+    let icpr = JitterBuffer::builder()
+        .with_target_delay(TARGET_DELAY)
+        .build("")?;
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            clock_rate: CLOCK_RATE,
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    let start = Instant::now();
+    for sequence_number in [1u16, 0] {
+        stream
+            .receive_rtp(rtp::packet::Packet {
+                header: rtp::header::Header {
+                    ssrc: 1,
+                    sequence_number,
+                    timestamp: FRAME_TICKS * sequence_number as u32,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .await;
+    }
+
+    for (sequence_number, release) in [(0, Duration::from_micros(16_667)), (1, TARGET_DELAY)] {
+        let pkt = stream.read_rtp().await.unwrap()?;
+        assert_eq!(pkt.header.sequence_number, sequence_number);
+        assert!(Instant::now() - start >= release);
+    }
+
+    stream.close().await?;
+    Ok(())
+}
@@ -0,0 +1,375 @@
+mod jitter_buffer_stream;
+#[cfg(test)]
+mod jitter_buffer_test;
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use jitter_buffer_stream::JitterBufferStream;
+use tokio::sync::{mpsc, Mutex};
+use tokio::time::Instant;
+use waitgroup::WaitGroup;
+
+use crate::error::{Error, Result};
+use crate::stream_info::StreamInfo;
+use crate::{
+    Attributes, Interceptor, InterceptorBuilder, RTCPReader, RTCPWriter, RTPReader, RTPWriter,
+};
+
+/// Key of the Attributes of the RTP packets read from a jitter buffer which holds how many
+/// packets right before them were given up on, as lost. It isn't set when none was.
+pub const ATTR_PACKETS_LOST: usize = 0x1057;
+
+const DEFAULT_TARGET_DELAY: Duration = Duration::from_millis(50);
+const DEFAULT_MAX_PACKETS: usize = 500;
+
+/// Window the least transit time of the packets is tracked over, so that the playout follows
+/// the drift between the clocks of the sender and the receiver.
+const TRANSIT_WINDOW_US: i64 = 2_000_000;
+const RECEIVE_MTU: usize = 1460;
+
+#[derive(Debug, Clone)]
+struct BufferedPacket<T> {
+    packet: T,
+    arrival_us: i64,
+    timestamp_us: i64,
+}
+
+/// PlayoutBuffer reorders the packets of a stream received, and releases them in sequence
+/// order at smoothed times: the time they were sent at on the RTP clock, plus the least
+/// transit time seen, plus the target delay. The packets delayed by less jitter than the
+/// target delay are released at regular intervals, the others as soon as they are received.
+///
+/// The packets still missing when the packets following them are due are given up on as
+/// lost, and the ones received after they were given up on, or twice, are dropped.
+#[derive(Debug, Clone)]
+pub struct PlayoutBuffer<T> {
+    target_delay: Duration,
+    max_packets: usize,
+    clock_rate: u32,
+
+    start: Option<Instant>,
+    packets: BTreeMap<i64, BufferedPacket<T>>,
+    highest_sequence_number: Option<i64>,
+    next_sequence_number: Option<i64>,
+    last_timestamp: Option<(u32, i64)>,
+    /// Least transit times of the window, increasing, with the arrival time they were seen at.
+    transits: VecDeque<(i64, i64)>,
+
+    lost_packets: usize,
+    dropped_packets: usize,
+}
+
+impl<T> PlayoutBuffer<T> {
+    /// new returns a buffer delaying the packets by up to target_delay, of a stream whose RTP
+    /// timestamps have clock_rate. With a clock rate of zero, the packets are released
+    /// target_delay after they are received. More than max_packets packets aren't buffered,
+    /// the first ones are released early instead.
+    pub fn new(target_delay: Duration, max_packets: usize, clock_rate: u32) -> Self {
+        PlayoutBuffer {
+            target_delay,
+            max_packets,
+            clock_rate,
+
+            start: None,
+            packets: BTreeMap::new(),
+            highest_sequence_number: None,
+            next_sequence_number: None,
+            last_timestamp: None,
+            transits: VecDeque::new(),
+
+            lost_packets: 0,
+            dropped_packets: 0,
+        }
+    }
+
+    /// len returns the number of packets buffered.
+    pub fn len(&self) -> usize {
+        self.packets.len()
+    }
+
+    /// is_empty returns whether no packet is buffered.
+    pub fn is_empty(&self) -> bool {
+        self.packets.is_empty()
+    }
+
+    /// lost_packets returns the number of packets given up on so far.
+    pub fn lost_packets(&self) -> usize {
+        self.lost_packets
+    }
+
+    /// dropped_packets returns the number of packets dropped so far, because they were
+    /// received too late or twice.
+    pub fn dropped_packets(&self) -> usize {
+        self.dropped_packets
+    }
+
+    /// push buffers a packet with sequence_number and timestamp received at now. It returns
+    /// false if the packet was dropped.
+    pub fn push(&mut self, sequence_number: u16, timestamp: u32, packet: T, now: Instant) -> bool {
+        let start = *self.start.get_or_insert(now);
+        let arrival_us = now.saturating_duration_since(start).as_micros() as i64;
+
+        let sequence_number = match self.highest_sequence_number {
+            Some(highest) => highest + sequence_number.wrapping_sub(highest as u16) as i16 as i64,
+            None => sequence_number as i64,
+        };
+        self.highest_sequence_number = Some(
+            self.highest_sequence_number
+                .map_or(sequence_number, |highest| highest.max(sequence_number)),
+        );
+        let unwrapped_timestamp = match self.last_timestamp {
+            Some((last, unwrapped)) => unwrapped + timestamp.wrapping_sub(last) as i32 as i64,
+            None => timestamp as i64,
+        };
+        self.last_timestamp = Some((timestamp, unwrapped_timestamp));
+
+        let is_late = matches!(self.next_sequence_number, Some(next) if sequence_number < next);
+        if is_late || self.packets.contains_key(&sequence_number) {
+            self.dropped_packets += 1;
+            return false;
+        }
+
+        let timestamp_us = if self.clock_rate > 0 {
+            unwrapped_timestamp * 1_000_000 / self.clock_rate as i64
+        } else {
+            arrival_us
+        };
+        let transit_us = arrival_us - timestamp_us;
+        while matches!(self.transits.back(), Some((_, t)) if *t >= transit_us) {
+            self.transits.pop_back();
+        }
+        self.transits.push_back((arrival_us, transit_us));
+        while matches!(self.transits.front(), Some((t, _)) if arrival_us - *t > TRANSIT_WINDOW_US) {
+            self.transits.pop_front();
+        }
+
+        self.packets.insert(
+            sequence_number,
+            BufferedPacket {
+                packet,
+                arrival_us,
+                timestamp_us,
+            },
+        );
+        true
+    }
+
+    fn release_time(&self, packet: &BufferedPacket<T>) -> Instant {
+        let start = self.start.unwrap_or_else(Instant::now);
+        let release_us = match self.transits.front() {
+            Some((_, min_transit_us)) if self.clock_rate > 0 => {
+                packet.timestamp_us + min_transit_us
+            }
+            _ => packet.arrival_us,
+        };
+        start + Duration::from_micros(release_us.max(0) as u64) + self.target_delay
+    }
+
+    /// next_release_time returns when the next packet is due, or None if no packet is
+    /// buffered.
+    pub fn next_release_time(&self) -> Option<Instant> {
+        let (_, packet) = self.packets.iter().next()?;
+        if self.packets.len() > self.max_packets {
+            self.start
+        } else {
+            Some(self.release_time(packet))
+        }
+    }
+
+    /// pop returns the next packet if it is due at now, with the number of packets right
+    /// before it given up on.
+    pub fn pop(&mut self, now: Instant) -> Option<(T, usize)> {
+        if self.next_release_time()? > now {
+            return None;
+        }
+        self.pop_next()
+    }
+
+    /// pop_next returns the next packet even if it isn't due yet, such as to flush the buffer
+    /// once the stream ends, with the number of packets right before it given up on.
+    pub fn pop_next(&mut self) -> Option<(T, usize)> {
+        let (sequence_number, packet) = self.packets.pop_first()?;
+        let lost = match self.next_sequence_number {
+            Some(next) => (sequence_number - next) as usize,
+            None => 0,
+        };
+        self.lost_packets += lost;
+        self.next_sequence_number = Some(sequence_number + 1);
+        Some((packet.packet, lost))
+    }
+}
+
+/// JitterBufferBuilder can be used to configure JitterBuffer Interceptor
+#[derive(Default)]
+pub struct JitterBufferBuilder {
+    target_delay: Option<Duration>,
+    max_packets: Option<usize>,
+}
+
+impl JitterBufferBuilder {
+    /// with_target_delay sets how long the packets may be delayed by, to absorb the jitter.
+    pub fn with_target_delay(mut self, target_delay: Duration) -> JitterBufferBuilder {
+        self.target_delay = Some(target_delay);
+        self
+    }
+
+    /// with_max_packets sets how many packets of each stream may be buffered.
+    pub fn with_max_packets(mut self, max_packets: usize) -> JitterBufferBuilder {
+        self.max_packets = Some(max_packets);
+        self
+    }
+}
+
+impl InterceptorBuilder for JitterBufferBuilder {
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        Ok(Arc::new(JitterBuffer {
+            target_delay: self.target_delay.unwrap_or(DEFAULT_TARGET_DELAY),
+            max_packets: self.max_packets.unwrap_or(DEFAULT_MAX_PACKETS),
+            streams: Mutex::new(HashMap::new()),
+
+            wg: Mutex::new(Some(WaitGroup::new())),
+        }))
+    }
+}
+
+/// JitterBuffer interceptor buffers the incoming RTP packets of each remote stream in a
+/// [`PlayoutBuffer`], so that they are read in sequence order, at smoothed times. The number
+/// of packets given up on before a packet read is set in its [`ATTR_PACKETS_LOST`]
+/// attribute.
+///
+/// It reads the packets from the interceptors registered before it as soon as they are
+/// received, so it should be registered last for them to see the packets undelayed.
+pub struct JitterBuffer {
+    target_delay: Duration,
+    max_packets: usize,
+    /// Closes the buffering of each remote stream, when dropped.
+    streams: Mutex<HashMap<u32, mpsc::Sender<()>>>,
+
+    wg: Mutex<Option<WaitGroup>>,
+}
+
+impl JitterBuffer {
+    /// builder returns a new JitterBufferBuilder.
+    pub fn builder() -> JitterBufferBuilder {
+        JitterBufferBuilder::default()
+    }
+
+    async fn run(
+        parent_rtp_reader: Arc<dyn RTPReader + Send + Sync>,
+        stream: Arc<JitterBufferStream>,
+        mut close_rx: mpsc::Receiver<()>,
+    ) {
+        let mut buf = vec![0u8; RECEIVE_MTU];
+        let a = Attributes::new();
+        loop {
+            tokio::select! {
+                _ = close_rx.recv() => {
+                    stream.close(Error::ErrIoEOF);
+                    return;
+                }
+                result = parent_rtp_reader.read(&mut buf, &a) => match result {
+                    Ok((pkt, attributes)) => stream.push(pkt, attributes),
+                    Err(err) => {
+                        stream.close(err);
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Interceptor for JitterBuffer {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        reader
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        writer
+    }
+
+    /// bind_local_stream lets you modify any outgoing RTP packets. It is called once for per LocalStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_local_stream(
+        &self,
+        _info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        writer
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, _info: &StreamInfo) {}
+
+    /// bind_remote_stream returns a reader of the packets buffered, which are read from the
+    /// parent reader as soon as they are received.
+    async fn bind_remote_stream(
+        &self,
+        info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        let mut w = {
+            let wait_group = self.wg.lock().await;
+            match wait_group.as_ref() {
+                Some(wg) => Some(wg.worker()),
+                None => return reader,
+            }
+        };
+
+        let stream = Arc::new(JitterBufferStream::new(PlayoutBuffer::new(
+            self.target_delay,
+            self.max_packets,
+            info.clock_rate,
+        )));
+        let (close_tx, close_rx) = mpsc::channel(1);
+        {
+            let mut streams = self.streams.lock().await;
+            streams.insert(info.ssrc, close_tx);
+        }
+
+        let s = Arc::clone(&stream);
+        tokio::spawn(async move {
+            let _d = w.take();
+            JitterBuffer::run(reader, s, close_rx).await;
+        });
+
+        stream
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, info: &StreamInfo) {
+        let mut streams = self.streams.lock().await;
+        streams.remove(&info.ssrc);
+    }
+
+    /// close closes the Interceptor, cleaning up any data if necessary.
+    async fn close(&self) -> Result<()> {
+        {
+            let mut streams = self.streams.lock().await;
+            streams.clear();
+        }
+
+        {
+            let mut wait_group = self.wg.lock().await;
+            if let Some(wg) = wait_group.take() {
+                wg.wait().await;
+            }
+        }
+
+        Ok(())
+    }
+}
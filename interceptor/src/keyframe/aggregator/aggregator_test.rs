@@ -0,0 +1,126 @@
+use std::time::Duration;
+
+use rtcp::payload_feedbacks::full_intra_request::FirEntry;
+use tokio::sync::mpsc;
+
+use super::*;
+use crate::mock::mock_stream::MockStream;
+
+async fn read_requests(
+    stream: &MockStream,
+    pkts: Vec<Box<dyn rtcp::packet::Packet + Send + Sync>>,
+) -> Vec<Box<dyn rtcp::packet::Packet + Send + Sync>> {
+    stream.receive_rtcp(pkts).await;
+    stream
+        .read_rtcp()
+        .await
+        .expect("packets should be read")
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_aggregator_interceptor() -> Result<()> {
+    let (request_tx, mut request_rx) = mpsc::unbounded_channel();
+    let builder = Aggregator::builder()
+        .with_policy(KeyframeRequestPolicy {
+            min_interval: Duration::from_secs(10),
+            burst: 2,
+            dedup_window: Duration::from_secs(10),
+        })
+        .with_on_request(Arc::new(move |request: KeyframeRequest| {
+            let request_tx = request_tx.clone();
+            Box::pin(async move {
+                let _ = request_tx.send(request);
+            })
+        }));
+    let requests = builder.requests();
+    // two subscribers of the same track
+    let subscribers = [
+        MockStream::new(&StreamInfo::default(), builder.build("")?).await,
+        MockStream::new(&StreamInfo::default(), builder.build("")?).await,
+    ];
+
+    let pli = |sender_ssrc: u32| -> Vec<Box<dyn rtcp::packet::Packet + Send + Sync>> {
+        vec![Box::new(PictureLossIndication {
+            sender_ssrc,
+            media_ssrc: 10,
+        })]
+    };
+    let pkts = read_requests(&subscribers[0], pli(1)).await;
+    assert_eq!(pkts.len(), 1);
+    let pkts = read_requests(&subscribers[1], pli(2)).await;
+    assert!(pkts.is_empty(), "the duplicate request should be removed");
+
+    assert_eq!(
+        request_rx.recv().await,
+        Some(KeyframeRequest {
+            sender_ssrc: 1,
+            media_ssrc: 10,
+            kind: KeyframeRequestKind::Pli,
+            decision: KeyframeRequestDecision::Allowed,
+        })
+    );
+    assert_eq!(
+        request_rx.recv().await.map(|r| (r.sender_ssrc, r.decision)),
+        Some((2, KeyframeRequestDecision::Duplicate))
+    );
+
+    requests.keyframe_received(10);
+    let pkts = read_requests(&subscribers[1], pli(2)).await;
+    assert_eq!(pkts.len(), 1);
+    // the burst is spent
+    requests.keyframe_received(10);
+    let pkts = read_requests(&subscribers[0], pli(1)).await;
+    assert!(pkts.is_empty());
+    assert_eq!(
+        request_rx.recv().await.map(|r| r.decision),
+        Some(KeyframeRequestDecision::Allowed)
+    );
+    assert_eq!(
+        request_rx.recv().await.map(|r| r.decision),
+        Some(KeyframeRequestDecision::Throttled)
+    );
+
+    // only the entries of the streams allowed are kept
+    let pkts = read_requests(
+        &subscribers[0],
+        vec![Box::new(FullIntraRequest {
+            sender_ssrc: 1,
+            media_ssrc: 0,
+            fir: vec![
+                FirEntry {
+                    ssrc: 10,
+                    sequence_number: 1,
+                },
+                FirEntry {
+                    ssrc: 11,
+                    sequence_number: 1,
+                },
+            ],
+        })],
+    )
+    .await;
+    assert_eq!(pkts.len(), 1);
+    let fir = pkts[0]
+        .as_any()
+        .downcast_ref::<FullIntraRequest>()
+        .expect("a FullIntraRequest should be read");
+    assert_eq!(fir.fir.len(), 1);
+    assert_eq!(fir.fir[0].ssrc, 11);
+    assert_eq!(
+        request_rx
+            .recv()
+            .await
+            .map(|r| (r.kind, r.media_ssrc, r.decision)),
+        Some((
+            KeyframeRequestKind::Fir,
+            10,
+            KeyframeRequestDecision::Throttled
+        ))
+    );
+
+    for subscriber in subscribers {
+        subscriber.close().await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,262 @@
+#[cfg(test)]
+mod aggregator_test;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Instant;
+
+use rtcp::payload_feedbacks::full_intra_request::FullIntraRequest;
+use rtcp::payload_feedbacks::picture_loss_indication::PictureLossIndication;
+use util::sync::Mutex;
+
+use crate::keyframe::{KeyframeRequestDecision, KeyframeRequestLimiter, KeyframeRequestPolicy};
+use crate::*;
+
+/// KeyframeRequestKind is the RTCP packet a keyframe was requested with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KeyframeRequestKind {
+    Pli,
+    Fir,
+}
+
+/// KeyframeRequest is a keyframe request read by an aggregator, with what was decided of it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KeyframeRequest {
+    /// SSRC of the subscriber which requested the keyframe.
+    pub sender_ssrc: u32,
+    /// SSRC of the stream the keyframe was requested for.
+    pub media_ssrc: u32,
+    pub kind: KeyframeRequestKind,
+    pub decision: KeyframeRequestDecision,
+}
+
+/// OnKeyframeRequestFn is called with each keyframe request read, whether it was forwarded
+/// or not.
+pub type OnKeyframeRequestFn = Arc<
+    dyn (Fn(KeyframeRequest) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>)
+        + Send
+        + Sync
+        + 'static,
+>;
+
+/// KeyframeRequests limits the keyframe requests read by all the aggregators built by the
+/// same builder, e.g. from the subscribers of a track.
+#[derive(Debug, Default)]
+pub struct KeyframeRequests {
+    limiter: Mutex<KeyframeRequestLimiter>,
+}
+
+impl KeyframeRequests {
+    /// keyframe_received records that a keyframe was received for ssrc, such as from the
+    /// publisher of the track, which ends the deduplication of the requests.
+    pub fn keyframe_received(&self, ssrc: u32) {
+        let mut limiter = self.limiter.lock();
+        limiter.keyframe_received(ssrc);
+    }
+
+    /// remove forgets the requests made for ssrc, when its track is removed.
+    pub fn remove(&self, ssrc: u32) {
+        let mut limiter = self.limiter.lock();
+        limiter.remove(ssrc);
+    }
+
+    fn request(&self, ssrc: u32, now: Instant) -> KeyframeRequestDecision {
+        let mut limiter = self.limiter.lock();
+        limiter.request(ssrc, now)
+    }
+}
+
+/// AggregatorBuilder is a InterceptorBuilder for an Aggregator Interceptor
+#[derive(Default)]
+pub struct AggregatorBuilder {
+    requests: Arc<KeyframeRequests>,
+    on_request: Option<OnKeyframeRequestFn>,
+}
+
+impl AggregatorBuilder {
+    /// with_policy sets how often keyframes may be requested for each stream, by all the
+    /// subscribers together.
+    pub fn with_policy(self, policy: KeyframeRequestPolicy) -> AggregatorBuilder {
+        {
+            let mut limiter = self.requests.limiter.lock();
+            *limiter = KeyframeRequestLimiter::new(policy);
+        }
+        self
+    }
+
+    /// with_on_request sets the handler called with each keyframe request read.
+    pub fn with_on_request(mut self, f: OnKeyframeRequestFn) -> AggregatorBuilder {
+        self.on_request = Some(f);
+        self
+    }
+
+    /// requests returns the limiter of the keyframe requests shared by the interceptors built.
+    pub fn requests(&self) -> Arc<KeyframeRequests> {
+        Arc::clone(&self.requests)
+    }
+}
+
+impl InterceptorBuilder for AggregatorBuilder {
+    /// build constructs a new Aggregator
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        Ok(Arc::new(Aggregator {
+            requests: Arc::clone(&self.requests),
+            on_request: self.on_request.clone(),
+        }))
+    }
+}
+
+/// Aggregator dedupes and rate limits the PLI and FIR packets read, across all the
+/// interceptors built by the same builder. In an SFU, where each subscriber of a track has its
+/// own PeerConnection, the requests of all the subscribers which aren't allowed by the
+/// [`KeyframeRequestPolicy`] are removed from the RTCP packets read, so that at most one
+/// request per interval is forwarded to the publisher.
+///
+/// The requests are aggregated by the SSRC they are made for, which is the same for all
+/// the subscribers of a track unless it is rewritten.
+pub struct Aggregator {
+    requests: Arc<KeyframeRequests>,
+    on_request: Option<OnKeyframeRequestFn>,
+}
+
+impl Aggregator {
+    /// builder returns a new AggregatorBuilder.
+    pub fn builder() -> AggregatorBuilder {
+        AggregatorBuilder::default()
+    }
+}
+
+#[async_trait]
+impl Interceptor for Aggregator {
+    /// bind_rtcp_reader returns a reader which removes the keyframe requests which aren't
+    /// allowed from the RTCP packets read.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        Arc::new(AggregatorRtcpReader {
+            parent_rtcp_reader: reader,
+            requests: Arc::clone(&self.requests),
+            on_request: self.on_request.clone(),
+        })
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        writer
+    }
+
+    /// bind_local_stream lets you modify any outgoing RTP packets. It is called once for per LocalStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_local_stream(
+        &self,
+        _info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        writer
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, _info: &StreamInfo) {}
+
+    /// bind_remote_stream lets you modify any incoming RTP packets. It is called once for per RemoteStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_remote_stream(
+        &self,
+        _info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        reader
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, _info: &StreamInfo) {}
+
+    /// close closes the Interceptor, cleaning up any data if necessary.
+    async fn close(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct AggregatorRtcpReader {
+    parent_rtcp_reader: Arc<dyn RTCPReader + Send + Sync>,
+    requests: Arc<KeyframeRequests>,
+    on_request: Option<OnKeyframeRequestFn>,
+}
+
+impl AggregatorRtcpReader {
+    /// aggregate returns pkt without the keyframe requests which aren't allowed, or None if
+    /// nothing is left of it, and records the requests it made.
+    fn aggregate(
+        &self,
+        pkt: Box<dyn rtcp::packet::Packet + Send + Sync>,
+        requests: &mut Vec<KeyframeRequest>,
+        now: Instant,
+    ) -> Option<Box<dyn rtcp::packet::Packet + Send + Sync>> {
+        if let Some(pli) = pkt.as_any().downcast_ref::<PictureLossIndication>() {
+            let decision = self.requests.request(pli.media_ssrc, now);
+            requests.push(KeyframeRequest {
+                sender_ssrc: pli.sender_ssrc,
+                media_ssrc: pli.media_ssrc,
+                kind: KeyframeRequestKind::Pli,
+                decision,
+            });
+            return if decision.is_allowed() {
+                Some(pkt)
+            } else {
+                None
+            };
+        }
+
+        if let Some(fir) = pkt.as_any().downcast_ref::<FullIntraRequest>() {
+            let mut fir = fir.clone();
+            fir.fir.retain(|entry| {
+                let decision = self.requests.request(entry.ssrc, now);
+                requests.push(KeyframeRequest {
+                    sender_ssrc: fir.sender_ssrc,
+                    media_ssrc: entry.ssrc,
+                    kind: KeyframeRequestKind::Fir,
+                    decision,
+                });
+                decision.is_allowed()
+            });
+            return if fir.fir.is_empty() {
+                None
+            } else {
+                Some(Box::new(fir))
+            };
+        }
+
+        Some(pkt)
+    }
+}
+
+#[async_trait]
+impl RTCPReader for AggregatorRtcpReader {
+    async fn read(
+        &self,
+        buf: &mut [u8],
+        a: &Attributes,
+    ) -> Result<(Vec<Box<dyn rtcp::packet::Packet + Send + Sync>>, Attributes)> {
+        let (pkts, attr) = self.parent_rtcp_reader.read(buf, a).await?;
+
+        let now = Instant::now();
+        let mut requests = vec![];
+        let pkts = pkts
+            .into_iter()
+            .filter_map(|pkt| self.aggregate(pkt, &mut requests, now))
+            .collect();
+        if let Some(f) = &self.on_request {
+            for request in requests {
+                f(request).await;
+            }
+        }
+
+        Ok((pkts, attr))
+    }
+}
@@ -0,0 +1,111 @@
+use super::*;
+
+#[test]
+fn test_keyframe_request_limiter_burst() {
+    let mut limiter = KeyframeRequestLimiter::new(KeyframeRequestPolicy {
+        min_interval: Duration::from_secs(1),
+        burst: 2,
+        dedup_window: Duration::ZERO,
+    });
+    let start = Instant::now();
+
+    assert_eq!(limiter.request(1, start), KeyframeRequestDecision::Allowed);
+    assert_eq!(limiter.request(1, start), KeyframeRequestDecision::Allowed);
+    assert_eq!(
+        limiter.request(1, start),
+        KeyframeRequestDecision::Throttled
+    );
+    // other streams aren't limited
+    assert_eq!(limiter.request(2, start), KeyframeRequestDecision::Allowed);
+
+    let now = start + Duration::from_millis(999);
+    assert_eq!(limiter.request(1, now), KeyframeRequestDecision::Throttled);
+    let now = start + Duration::from_millis(1000);
+    assert_eq!(limiter.request(1, now), KeyframeRequestDecision::Allowed);
+    assert_eq!(limiter.request(1, now), KeyframeRequestDecision::Throttled);
+
+    // no more than the burst is earned back
+    let now = start + Duration::from_secs(10);
+    assert_eq!(limiter.request(1, now), KeyframeRequestDecision::Allowed);
+    assert_eq!(limiter.request(1, now), KeyframeRequestDecision::Allowed);
+    assert_eq!(limiter.request(1, now), KeyframeRequestDecision::Throttled);
+
+    limiter.remove(1);
+    assert_eq!(limiter.request(1, now), KeyframeRequestDecision::Allowed);
+}
+
+#[test]
+fn test_keyframe_request_limiter_dedup() {
+    let mut limiter = KeyframeRequestLimiter::new(KeyframeRequestPolicy {
+        min_interval: Duration::from_secs(1),
+        burst: 3,
+        dedup_window: Duration::from_millis(200),
+    });
+    let start = Instant::now();
+
+    assert!(limiter.request(1, start).is_allowed());
+    let now = start + Duration::from_millis(100);
+    assert_eq!(limiter.request(1, now), KeyframeRequestDecision::Duplicate);
+    // a duplicate doesn't spend the burst
+    let now = start + Duration::from_millis(200);
+    assert_eq!(limiter.request(1, now), KeyframeRequestDecision::Allowed);
+
+    limiter.keyframe_received(1);
+    assert_eq!(limiter.request(1, now), KeyframeRequestDecision::Allowed);
+    assert_eq!(limiter.request(1, now), KeyframeRequestDecision::Duplicate);
+    limiter.keyframe_received(1);
+    assert_eq!(limiter.request(1, now), KeyframeRequestDecision::Throttled);
+}
+
+#[test]
+fn test_keyframe_request_limiter_unlimited() {
+    let mut limiter = KeyframeRequestLimiter::new(KeyframeRequestPolicy {
+        min_interval: Duration::ZERO,
+        burst: 1,
+        dedup_window: Duration::ZERO,
+    });
+    let now = Instant::now();
+    for _ in 0..10 {
+        assert!(limiter.request(1, now).is_allowed());
+    }
+}
+
+#[test]
+fn test_is_keyframe_start() {
+    for (mime_type, payload, expected) in [
+        // VP8, without and with extensions, keyframe or not, start of partition or not
+        ("video/VP8", vec![0x10, 0x00], true),
+        ("video/VP8", vec![0x10, 0x01], false),
+        ("video/VP8", vec![0x00, 0x00], false),
+        (
+            "video/VP8",
+            vec![0x90, 0xE0, 0x81, 0x02, 0x03, 0x20, 0x00],
+            true,
+        ),
+        ("video/VP8", vec![0x90, 0x80, 0x12, 0x01], false),
+        ("video/VP9", vec![0x88], true),
+        ("video/VP9", vec![0xC8], false),
+        ("video/VP9", vec![0x80], false),
+        // H264 IDR, STAP-A with SPS, FU-A start of an IDR and not
+        ("video/H264", vec![0x65, 0x01], true),
+        ("video/H264", vec![0x41, 0x01], false),
+        (
+            "video/H264",
+            vec![0x78, 0x00, 0x02, 0x67, 0x01, 0x00, 0x01, 0x68],
+            true,
+        ),
+        ("video/H264", vec![0x78, 0x00, 0x01, 0x41], false),
+        ("video/H264", vec![0x7C, 0x85, 0x01], true),
+        ("video/H264", vec![0x7C, 0x05, 0x01], false),
+        ("video/AV1", vec![0x18], true),
+        ("video/AV1", vec![0x98], false),
+        ("audio/opus", vec![0xFF], false),
+        ("video/VP8", vec![], false),
+    ] {
+        assert_eq!(
+            is_keyframe_start(mime_type, &payload),
+            expected,
+            "{mime_type} {payload:02X?}"
+        );
+    }
+}
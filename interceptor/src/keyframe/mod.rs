@@ -0,0 +1,235 @@
+#[cfg(test)]
+mod keyframe_test;
+
+pub mod aggregator;
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// KeyframeRequestPolicy configures how often keyframes may be requested for a stream,
+/// with PLI or FIR packets.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KeyframeRequestPolicy {
+    /// Interval a keyframe request is earned back in once the burst is spent. Zero doesn't
+    /// limit the requests.
+    pub min_interval: Duration,
+    /// Number of requests which can be made back to back.
+    pub burst: u32,
+    /// Requests within this duration of the last one allowed, for which no keyframe was
+    /// received yet, are duplicates of it.
+    pub dedup_window: Duration,
+}
+
+impl Default for KeyframeRequestPolicy {
+    fn default() -> Self {
+        KeyframeRequestPolicy {
+            min_interval: Duration::from_secs(1),
+            burst: 3,
+            dedup_window: Duration::from_millis(200),
+        }
+    }
+}
+
+/// KeyframeRequestDecision is what a KeyframeRequestLimiter decides of a request.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KeyframeRequestDecision {
+    /// Request should be sent, or honored with a keyframe.
+    Allowed,
+    /// Request duplicates one allowed whose keyframe is still pending.
+    Duplicate,
+    /// Too many requests were made for the stream lately.
+    Throttled,
+}
+
+impl KeyframeRequestDecision {
+    /// is_allowed returns whether the request should be sent, or honored.
+    pub fn is_allowed(&self) -> bool {
+        *self == KeyframeRequestDecision::Allowed
+    }
+}
+
+#[derive(Debug, Clone)]
+struct KeyframeRequestState {
+    tokens: u32,
+    last_refill: Instant,
+    pending_since: Option<Instant>,
+}
+
+/// KeyframeRequestLimiter rate limits the keyframe requests of each SSRC, so that a lossy
+/// subscriber can't force continuous keyframes. It can be used by a receiver before sending
+/// requests, as well as by a sender before honoring the requests it receives.
+///
+/// Each SSRC can make a burst of requests, which are then earned back one per min_interval,
+/// and concurrent requests made before the keyframe is received count as a single one.
+#[derive(Debug, Clone)]
+pub struct KeyframeRequestLimiter {
+    policy: KeyframeRequestPolicy,
+    streams: HashMap<u32, KeyframeRequestState>,
+}
+
+impl Default for KeyframeRequestLimiter {
+    fn default() -> Self {
+        KeyframeRequestLimiter::new(KeyframeRequestPolicy::default())
+    }
+}
+
+impl KeyframeRequestLimiter {
+    /// new returns a KeyframeRequestLimiter applying policy.
+    pub fn new(policy: KeyframeRequestPolicy) -> Self {
+        KeyframeRequestLimiter {
+            policy,
+            streams: HashMap::new(),
+        }
+    }
+
+    /// policy returns the policy applied.
+    pub fn policy(&self) -> KeyframeRequestPolicy {
+        self.policy
+    }
+
+    /// request records a keyframe request for ssrc at now, and returns whether it should
+    /// be sent, or honored.
+    pub fn request(&mut self, ssrc: u32, now: Instant) -> KeyframeRequestDecision {
+        let policy = self.policy;
+        let state = self
+            .streams
+            .entry(ssrc)
+            .or_insert_with(|| KeyframeRequestState {
+                tokens: policy.burst,
+                last_refill: now,
+                pending_since: None,
+            });
+
+        if policy.min_interval.is_zero() {
+            state.tokens = policy.burst;
+            state.last_refill = now;
+        } else {
+            let elapsed = now.saturating_duration_since(state.last_refill);
+            let earned = (elapsed.as_nanos() / policy.min_interval.as_nanos()) as u32;
+            if state.tokens.saturating_add(earned) >= policy.burst {
+                state.tokens = policy.burst;
+                state.last_refill = now;
+            } else if earned > 0 {
+                state.tokens += earned;
+                state.last_refill += policy.min_interval * earned;
+            }
+        }
+
+        if let Some(pending_since) = state.pending_since {
+            if now.saturating_duration_since(pending_since) < policy.dedup_window {
+                return KeyframeRequestDecision::Duplicate;
+            }
+        }
+
+        if state.tokens == 0 {
+            return KeyframeRequestDecision::Throttled;
+        }
+        state.tokens -= 1;
+        state.pending_since = Some(now);
+        KeyframeRequestDecision::Allowed
+    }
+
+    /// keyframe_received records that a keyframe was received, or sent, for ssrc, which
+    /// ends the deduplication of its requests.
+    pub fn keyframe_received(&mut self, ssrc: u32) {
+        if let Some(state) = self.streams.get_mut(&ssrc) {
+            state.pending_since = None;
+        }
+    }
+
+    /// remove forgets the requests made for ssrc, when its stream is removed.
+    pub fn remove(&mut self, ssrc: u32) {
+        self.streams.remove(&ssrc);
+    }
+}
+
+/// is_keyframe_start returns whether payload, of a RTP packet of the codec of mime_type,
+/// starts a keyframe, which a decoder can start decoding the stream from. It's false for the
+/// codecs other than VP8, VP9, H264 and AV1.
+pub fn is_keyframe_start(mime_type: &str, payload: &[u8]) -> bool {
+    match mime_type.to_lowercase().as_str() {
+        "video/vp8" => is_vp8_keyframe_start(payload),
+        "video/vp9" => is_vp9_keyframe_start(payload),
+        "video/h264" => is_h264_keyframe_start(payload),
+        "video/av1" => is_av1_keyframe_start(payload),
+        _ => false,
+    }
+}
+
+fn is_vp8_keyframe_start(payload: &[u8]) -> bool {
+    let descriptor = match payload.first() {
+        Some(&b) => b,
+        None => return false,
+    };
+    // the start of the first partition
+    if descriptor & 0x10 == 0 || descriptor & 0x07 != 0 {
+        return false;
+    }
+
+    let mut offset = 1;
+    if descriptor & 0x80 != 0 {
+        let extension = match payload.get(offset) {
+            Some(&b) => b,
+            None => return false,
+        };
+        offset += 1;
+        if extension & 0x80 != 0 {
+            // picture ID, of 15 bits with M set
+            offset += match payload.get(offset) {
+                Some(&b) if b & 0x80 != 0 => 2,
+                Some(_) => 1,
+                None => return false,
+            };
+        }
+        if extension & 0x40 != 0 {
+            offset += 1; // TL0PICIDX
+        }
+        if extension & 0x30 != 0 {
+            offset += 1; // TID, Y and KEYIDX
+        }
+    }
+
+    // P of the payload header is zero for keyframes
+    matches!(payload.get(offset), Some(&b) if b & 0x01 == 0)
+}
+
+fn is_vp9_keyframe_start(payload: &[u8]) -> bool {
+    // B, the start of a frame, without P, for frames not predicted from others
+    matches!(payload.first(), Some(&b) if b & 0x08 != 0 && b & 0x40 == 0)
+}
+
+fn is_h264_keyframe_start(payload: &[u8]) -> bool {
+    const NALU_TYPE_IDR: u8 = 5;
+    const NALU_TYPE_SPS: u8 = 7;
+    const NALU_TYPE_STAP_A: u8 = 24;
+    const NALU_TYPE_FU_A: u8 = 28;
+
+    let nalu_type = match payload.first() {
+        Some(&b) => b & 0x1F,
+        None => return false,
+    };
+    match nalu_type {
+        NALU_TYPE_IDR | NALU_TYPE_SPS => true,
+        NALU_TYPE_STAP_A => {
+            let mut offset = 1;
+            while offset + 2 < payload.len() {
+                let size = u16::from_be_bytes([payload[offset], payload[offset + 1]]) as usize;
+                let nalu_type = payload[offset + 2] & 0x1F;
+                if nalu_type == NALU_TYPE_IDR || nalu_type == NALU_TYPE_SPS {
+                    return true;
+                }
+                offset += 2 + size;
+            }
+            false
+        }
+        NALU_TYPE_FU_A => {
+            matches!(payload.get(1), Some(&b) if b & 0x80 != 0 && b & 0x1F == NALU_TYPE_IDR)
+        }
+        _ => false,
+    }
+}
+
+fn is_av1_keyframe_start(payload: &[u8]) -> bool {
+    // N, the start of a new coded video sequence, without Z, for an OBU not continued
+    matches!(payload.first(), Some(&b) if b & 0x08 != 0 && b & 0x80 == 0)
+}
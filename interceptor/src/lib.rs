@@ -10,13 +10,35 @@ use async_trait::async_trait;
 use error::Result;
 use stream_info::StreamInfo;
 
+pub mod abs_capture_time;
+pub mod abs_send_time;
+pub mod application_defined;
+pub mod audio_level;
+pub mod bitrate_allocator;
+pub mod ccfb;
 pub mod chain;
+pub mod ecn;
 mod error;
+pub mod extension_rewriter;
+pub mod flexfec;
+pub mod forwarder;
+pub mod frame;
+pub mod frame_encryption;
+pub mod gcc;
+pub mod impairment;
+pub mod jitter_buffer;
+pub mod keyframe;
+pub mod managed;
 pub mod mock;
 pub mod nack;
 pub mod noop;
+pub mod pacer;
+pub mod pcap;
+pub mod policer;
 pub mod registry;
+pub mod remb;
 pub mod report;
+pub mod rtt;
 pub mod stats;
 pub mod stream_info;
 pub mod stream_reader;
@@ -0,0 +1,401 @@
+use bytes::Bytes;
+use tokio::sync::Notify;
+use tokio::time::Duration;
+
+use super::*;
+use crate::mock::mock_stream::MockStream;
+use crate::test::timeout_or_fail;
+
+type Events = Arc<util::sync::Mutex<Vec<String>>>;
+
+/// Tagger appends its tag to the payloads of the packets written and read, and writes them
+/// once its gate, if any, is opened.
+struct Tagger {
+    tag: u8,
+    events: Events,
+    gate: Option<Arc<Notify>>,
+}
+
+impl Tagger {
+    fn event(&self, event: &str) {
+        let mut events = self.events.lock();
+        events.push(format!("{} {}", self.tag as char, event));
+    }
+}
+
+struct TaggerBuilder {
+    tag: u8,
+    events: Events,
+    gate: Option<Arc<Notify>>,
+}
+
+impl InterceptorBuilder for TaggerBuilder {
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        Ok(Arc::new(Tagger {
+            tag: self.tag,
+            events: Arc::clone(&self.events),
+            gate: self.gate.clone(),
+        }))
+    }
+}
+
+fn tagger(tag: char, events: &Events) -> Box<dyn InterceptorBuilder + Send + Sync> {
+    Box::new(TaggerBuilder {
+        tag: tag as u8,
+        events: Arc::clone(events),
+        gate: None,
+    })
+}
+
+fn gated_tagger(
+    tag: char,
+    events: &Events,
+    gate: &Arc<Notify>,
+) -> Box<dyn InterceptorBuilder + Send + Sync> {
+    Box::new(TaggerBuilder {
+        tag: tag as u8,
+        events: Arc::clone(events),
+        gate: Some(Arc::clone(gate)),
+    })
+}
+
+fn tagged(payload: &Bytes, tag: u8) -> Bytes {
+    let mut payload = payload.to_vec();
+    payload.push(tag);
+    payload.into()
+}
+
+struct TagWriter {
+    tag: u8,
+    gate: Option<Arc<Notify>>,
+    next: Arc<dyn RTPWriter + Send + Sync>,
+}
+
+#[async_trait]
+impl RTPWriter for TagWriter {
+    async fn write(&self, pkt: &rtp::packet::Packet, a: &Attributes) -> Result<usize> {
+        if let Some(gate) = &self.gate {
+            gate.notified().await;
+        }
+        let mut pkt = pkt.clone();
+        pkt.payload = tagged(&pkt.payload, self.tag);
+        self.next.write(&pkt, a).await
+    }
+}
+
+struct TagReader {
+    tag: u8,
+    parent: Arc<dyn RTPReader + Send + Sync>,
+}
+
+#[async_trait]
+impl RTPReader for TagReader {
+    async fn read(
+        &self,
+        buf: &mut [u8],
+        a: &Attributes,
+    ) -> Result<(rtp::packet::Packet, Attributes)> {
+        let (mut pkt, attr) = self.parent.read(buf, a).await?;
+        pkt.payload = tagged(&pkt.payload, self.tag);
+        Ok((pkt, attr))
+    }
+}
+
+#[async_trait]
+impl Interceptor for Tagger {
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        reader
+    }
+
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        writer
+    }
+
+    async fn bind_local_stream(
+        &self,
+        _info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        self.event("bind_local_stream");
+        Arc::new(TagWriter {
+            tag: self.tag,
+            gate: self.gate.clone(),
+            next: writer,
+        })
+    }
+
+    async fn unbind_local_stream(&self, _info: &StreamInfo) {
+        self.event("unbind_local_stream");
+    }
+
+    async fn bind_remote_stream(
+        &self,
+        _info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        self.event("bind_remote_stream");
+        Arc::new(TagReader {
+            tag: self.tag,
+            parent: reader,
+        })
+    }
+
+    async fn unbind_remote_stream(&self, _info: &StreamInfo) {
+        self.event("unbind_remote_stream");
+    }
+
+    async fn close(&self) -> Result<()> {
+        self.event("close");
+        Ok(())
+    }
+}
+
+fn take_events(events: &Events) -> Vec<String> {
+    let mut events = events.lock();
+    std::mem::take(&mut *events)
+}
+
+async fn written_payload(stream: &MockStream) -> Result<Bytes> {
+    stream
+        .write_rtp(&rtp::packet::Packet {
+            payload: Bytes::from_static(b"-"),
+            ..Default::default()
+        })
+        .await?;
+    let pkt = timeout_or_fail(Duration::from_millis(10), stream.written_rtp())
+        .await
+        .expect("A written packet");
+    Ok(pkt.payload)
+}
+
+async fn read_payload(stream: &MockStream) -> Bytes {
+    stream
+        .receive_rtp(rtp::packet::Packet {
+            payload: Bytes::from_static(b"-"),
+            ..Default::default()
+        })
+        .await;
+    timeout_or_fail(Duration::from_millis(10), stream.read_rtp())
+        .await
+        .expect("A read packet")
+        .expect("Not an error")
+        .payload
+}
+
+#[tokio::test]
+async fn test_managed_chain() -> Result<()> {
+    let events: Events = Arc::new(util::sync::Mutex::new(vec![]));
+    let builder = ManagedChain::builder().with_interceptor("a", tagger('a', &events));
+    let handle = builder.handle();
+    let stream = MockStream::new(&StreamInfo::default(), builder.build("")?).await;
+    assert_eq!(
+        take_events(&events),
+        vec!["a bind_local_stream", "a bind_remote_stream"]
+    );
+    assert_eq!(written_payload(&stream).await?, "-a");
+
+    // the interceptors later are closer to the application
+    handle.add("b", tagger('b', &events)).await?;
+    assert_eq!(
+        take_events(&events),
+        vec!["b bind_local_stream", "b bind_remote_stream"]
+    );
+    assert_eq!(written_payload(&stream).await?, "-ba");
+    assert_eq!(read_payload(&stream).await, "-ab");
+
+    handle.insert(0, "c", tagger('c', &events)).await?;
+    take_events(&events);
+    assert_eq!(written_payload(&stream).await?, "-bac");
+    assert_eq!(handle.names(), vec!["c", "a", "b"]);
+
+    handle.move_to("b", 0).await?;
+    assert_eq!(
+        take_events(&events),
+        vec![
+            "b unbind_local_stream",
+            "b unbind_remote_stream",
+            "b close",
+            "b bind_local_stream",
+            "b bind_remote_stream"
+        ]
+    );
+    assert_eq!(written_payload(&stream).await?, "-acb");
+    // the packet being read when the chain changed is read as it was
+    assert_eq!(read_payload(&stream).await, "-ab");
+    assert_eq!(read_payload(&stream).await, "-bca");
+
+    handle.remove("a").await?;
+    assert_eq!(
+        take_events(&events),
+        vec!["a unbind_local_stream", "a unbind_remote_stream", "a close"]
+    );
+    assert_eq!(written_payload(&stream).await?, "-cb");
+    assert_eq!(read_payload(&stream).await, "-bca");
+    assert_eq!(read_payload(&stream).await, "-bc");
+
+    assert_eq!(handle.remove("a").await, Err(Error::ErrInterceptorNotFound));
+    assert_eq!(
+        handle.add("b", tagger('d', &events)).await,
+        Err(Error::ErrInterceptorExists)
+    );
+
+    stream.close().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_managed_chain_built_after_changes() -> Result<()> {
+    let events: Events = Arc::new(util::sync::Mutex::new(vec![]));
+    let mut registry = crate::registry::Registry::new();
+    let handle = registry.add_managed();
+    handle.add("a", tagger('a', &events)).await?;
+    handle.add("b", tagger('b', &events)).await?;
+    handle.remove("a").await?;
+
+    let stream = MockStream::new(&StreamInfo::default(), registry.build("")?).await;
+    assert_eq!(written_payload(&stream).await?, "-b");
+
+    // the interceptors are closed with the chain, and no longer changed then
+    stream.close().await?;
+    assert_eq!(
+        take_events(&events),
+        vec!["b bind_local_stream", "b bind_remote_stream", "b close"]
+    );
+    handle.add("c", tagger('c', &events)).await?;
+    assert!(take_events(&events).is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_managed_chain_changed_while_in_use() -> Result<()> {
+    let events: Events = Arc::new(util::sync::Mutex::new(vec![]));
+    let gate = Arc::new(Notify::new());
+    let builder = ManagedChain::builder()
+        .with_interceptor("g", gated_tagger('g', &events, &gate))
+        .with_interceptor("a", tagger('a', &events));
+    let handle = builder.handle();
+    let stream = MockStream::new(&StreamInfo::default(), builder.build("")?).await;
+
+    // a packet is being written, waiting in the first interceptor, and one is being read
+    let writing = {
+        let stream = Arc::clone(&stream);
+        tokio::spawn(async move {
+            stream
+                .write_rtp(&rtp::packet::Packet {
+                    payload: Bytes::from_static(b"-"),
+                    ..Default::default()
+                })
+                .await
+        })
+    };
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    // the last and the first interceptors are removed, and one inserted first, meanwhile
+    timeout_or_fail(Duration::from_millis(100), async {
+        handle.remove("a").await?;
+        handle.insert(0, "b", tagger('b', &events)).await?;
+        handle.remove("g").await
+    })
+    .await?;
+    assert_eq!(handle.names(), vec!["b"]);
+
+    // the packet being read is read through the interceptors it started in, the one being
+    // written continues through those after the interceptor it waits in
+    gate.notify_one();
+    writing.await.expect("the write should complete")?;
+    let pkt = timeout_or_fail(Duration::from_millis(10), stream.written_rtp())
+        .await
+        .expect("A written packet");
+    assert_eq!(pkt.payload, "-agb");
+    assert_eq!(read_payload(&stream).await, "-ga");
+
+    // and the next ones through those left, the endpoints included
+    assert_eq!(written_payload(&stream).await?, "-b");
+    assert_eq!(read_payload(&stream).await, "-b");
+
+    handle.remove("b").await?;
+    assert_eq!(written_payload(&stream).await?, "-");
+    assert_eq!(read_payload(&stream).await, "-b");
+    assert_eq!(read_payload(&stream).await, "-");
+    handle.add("c", tagger('c', &events)).await?;
+    assert_eq!(written_payload(&stream).await?, "-c");
+
+    stream.close().await?;
+    Ok(())
+}
+
+/// RecordWriter records the payloads of the packets written.
+#[derive(Default)]
+struct RecordWriter {
+    payloads: util::sync::Mutex<Vec<Bytes>>,
+}
+
+#[async_trait]
+impl RTPWriter for RecordWriter {
+    async fn write(&self, pkt: &rtp::packet::Packet, _a: &Attributes) -> Result<usize> {
+        let mut payloads = self.payloads.lock();
+        payloads.push(pkt.payload.clone());
+        Ok(0)
+    }
+}
+
+#[tokio::test]
+async fn test_pipeline() -> Result<()> {
+    type Writer = Arc<dyn RTPWriter + Send + Sync>;
+    let endpoint = Arc::new(RecordWriter::default());
+    let (mut pipeline, top) = Pipeline::new(Arc::clone(&endpoint) as Writer);
+    let insert = |pipeline: &mut Pipeline<dyn RTPWriter + Send + Sync>, index: usize, tag: char| {
+        let input = pipeline.input(index);
+        let output = Arc::new(TagWriter {
+            tag: tag as u8,
+            gate: None,
+            next: Arc::clone(&input) as Writer,
+        });
+        pipeline.insert(index, input, output);
+    };
+    let written = |top: &Writer| {
+        let (top, endpoint) = (Arc::clone(top), Arc::clone(&endpoint));
+        async move {
+            let pkt = rtp::packet::Packet {
+                payload: Bytes::from_static(b"-"),
+                ..Default::default()
+            };
+            top.write(&pkt, &Attributes::new()).await?;
+            let mut payloads = endpoint.payloads.lock();
+            Result::<Bytes>::Ok(payloads.pop().expect("A written packet"))
+        }
+    };
+    let top = top as Writer;
+
+    assert_eq!(written(&top).await?, "-");
+    insert(&mut pipeline, 0, 'a');
+    assert_eq!(written(&top).await?, "-a");
+    // last, then first
+    insert(&mut pipeline, 1, 'b');
+    insert(&mut pipeline, 0, 'c');
+    assert_eq!(written(&top).await?, "-bac");
+
+    // the last one is removed from the proxy returned
+    pipeline.remove(2);
+    assert_eq!(written(&top).await?, "-ac");
+    // the first one from the endpoint
+    pipeline.remove(0);
+    assert_eq!(written(&top).await?, "-a");
+    pipeline.remove(0);
+    assert_eq!(written(&top).await?, "-");
+
+    // the pipeline can still be changed once the proxy returned is dropped
+    assert!(pipeline.is_live());
+    drop(top);
+    assert!(!pipeline.is_live());
+    insert(&mut pipeline, 0, 'd');
+    pipeline.remove(0);
+
+    Ok(())
+}
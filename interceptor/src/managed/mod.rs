@@ -0,0 +1,414 @@
+#[cfg(test)]
+mod managed_test;
+mod pipeline;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+
+use async_trait::async_trait;
+use pipeline::Pipeline;
+use tokio::sync::Mutex;
+
+use crate::error::{flatten_errs, Error, Result};
+use crate::stream_info::StreamInfo;
+use crate::{
+    Attributes, Interceptor, InterceptorBuilder, RTCPReader, RTCPWriter, RTPReader, RTPWriter,
+};
+
+type NamedBuilder = (String, Arc<dyn InterceptorBuilder + Send + Sync>);
+type ChainInterceptors = Vec<(Arc<ManagedChain>, Arc<dyn Interceptor + Send + Sync>)>;
+
+#[derive(Default)]
+struct ManagedChainHandleState {
+    builders: Vec<NamedBuilder>,
+    chains: Vec<Weak<ManagedChain>>,
+}
+
+impl ManagedChainHandleState {
+    fn position(&self, name: &str) -> Option<usize> {
+        self.builders.iter().position(|(n, _)| n == name)
+    }
+
+    /// build returns the chains in use, each with the interceptor builder builds for it.
+    fn build(
+        &mut self,
+        builder: &(dyn InterceptorBuilder + Send + Sync),
+    ) -> Result<ChainInterceptors> {
+        self.chains.retain(|chain| chain.strong_count() > 0);
+        self.chains
+            .iter()
+            .filter_map(|chain| chain.upgrade())
+            .map(|chain| {
+                let icpr = builder.build(&chain.id)?;
+                Ok((chain, icpr))
+            })
+            .collect()
+    }
+}
+
+/// ManagedChainHandle changes the interceptors of the chains a ManagedChainBuilder builds,
+/// those in use included. The interceptors are named, and ordered as in a registry: the ones
+/// later are closer to the application.
+///
+/// The interceptors inserted are bound to the streams, the readers and the writers the
+/// chains are bound to already, and the ones removed are unbound from the streams and
+/// closed. The others are left bound. The packets being read when an interceptor is
+/// inserted or removed are read as they were, the next ones through the interceptors then.
+#[derive(Default)]
+pub struct ManagedChainHandle {
+    /// Keeps the changes in order, while the chains are bound to each.
+    changes: Mutex<()>,
+    state: util::sync::Mutex<ManagedChainHandleState>,
+}
+
+impl ManagedChainHandle {
+    /// names returns the names of the interceptors, in order.
+    pub fn names(&self) -> Vec<String> {
+        let state = self.state.lock();
+        state
+            .builders
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// add adds the interceptors builder builds, named name, after the others.
+    pub async fn add(
+        &self,
+        name: &str,
+        builder: Box<dyn InterceptorBuilder + Send + Sync>,
+    ) -> Result<()> {
+        self.insert(usize::MAX, name, builder).await
+    }
+
+    /// insert inserts the interceptors builder builds, named name, at index, or after the
+    /// others if index is past them. It returns ErrInterceptorExists if an interceptor is
+    /// named name already.
+    pub async fn insert(
+        &self,
+        index: usize,
+        name: &str,
+        builder: Box<dyn InterceptorBuilder + Send + Sync>,
+    ) -> Result<()> {
+        let _changes = self.changes.lock().await;
+        let (index, chains) = {
+            let mut state = self.state.lock();
+            if state.position(name).is_some() {
+                return Err(Error::ErrInterceptorExists);
+            }
+            let builder: Arc<dyn InterceptorBuilder + Send + Sync> = Arc::from(builder);
+            let chains = state.build(builder.as_ref())?;
+            let index = index.min(state.builders.len());
+            state.builders.insert(index, (name.to_owned(), builder));
+            (index, chains)
+        };
+
+        for (chain, icpr) in chains {
+            chain.insert(index, name, icpr).await;
+        }
+        Ok(())
+    }
+
+    /// remove removes the interceptors named name, closing them. It returns
+    /// ErrInterceptorNotFound if no interceptor is named name.
+    pub async fn remove(&self, name: &str) -> Result<()> {
+        let _changes = self.changes.lock().await;
+        let (index, chains) = {
+            let mut state = self.state.lock();
+            let index = state.position(name).ok_or(Error::ErrInterceptorNotFound)?;
+            state.builders.remove(index);
+            state.chains.retain(|chain| chain.strong_count() > 0);
+            let chains: Vec<Arc<ManagedChain>> =
+                state.chains.iter().filter_map(|c| c.upgrade()).collect();
+            (index, chains)
+        };
+
+        let mut errs = vec![];
+        for chain in chains {
+            if let Err(err) = chain.remove(index).await {
+                errs.push(err);
+            }
+        }
+        flatten_errs(errs)
+    }
+
+    /// move_to moves the interceptors named name to index, or after the others if index is
+    /// past them. They are built again, and the ones moved closed. It returns
+    /// ErrInterceptorNotFound if no interceptor is named name.
+    pub async fn move_to(&self, name: &str, index: usize) -> Result<()> {
+        let _changes = self.changes.lock().await;
+        let (from, index, chains) = {
+            let mut state = self.state.lock();
+            let from = state.position(name).ok_or(Error::ErrInterceptorNotFound)?;
+            let builder = Arc::clone(&state.builders[from].1);
+            let chains = state.build(builder.as_ref())?;
+            let named = state.builders.remove(from);
+            let index = index.min(state.builders.len());
+            state.builders.insert(index, named);
+            (from, index, chains)
+        };
+
+        let mut errs = vec![];
+        for (chain, icpr) in chains {
+            if let Err(err) = chain.remove(from).await {
+                errs.push(err);
+            }
+            chain.insert(index, name, icpr).await;
+        }
+        flatten_errs(errs)
+    }
+}
+
+/// ManagedChainBuilder can be used to configure ManagedChain Interceptor
+#[derive(Default)]
+pub struct ManagedChainBuilder {
+    handle: Arc<ManagedChainHandle>,
+}
+
+impl ManagedChainBuilder {
+    /// with_interceptor adds the interceptors builder builds, named name, after the others.
+    /// An interceptor named name already is replaced.
+    pub fn with_interceptor(
+        self,
+        name: &str,
+        builder: Box<dyn InterceptorBuilder + Send + Sync>,
+    ) -> ManagedChainBuilder {
+        {
+            let mut state = self.handle.state.lock();
+            state.builders.retain(|(n, _)| n != name);
+            state.builders.push((name.to_owned(), Arc::from(builder)));
+        }
+        self
+    }
+
+    /// handle returns the handle changing the interceptors of the chains built, which can be
+    /// used before or after they are built.
+    pub fn handle(&self) -> Arc<ManagedChainHandle> {
+        Arc::clone(&self.handle)
+    }
+}
+
+impl InterceptorBuilder for ManagedChainBuilder {
+    fn build(&self, id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        let mut state = self.handle.state.lock();
+        let interceptors: Result<Vec<_>> = state
+            .builders
+            .iter()
+            .map(|(name, b)| Ok((name.clone(), b.build(id)?)))
+            .collect();
+
+        let chain = Arc::new(ManagedChain {
+            id: id.to_owned(),
+            state: Mutex::new(ManagedChainState {
+                interceptors: interceptors?,
+                ..Default::default()
+            }),
+        });
+        state.chains.retain(|chain| chain.strong_count() > 0);
+        state.chains.push(Arc::downgrade(&chain));
+
+        Ok(chain)
+    }
+}
+
+#[derive(Default)]
+struct ManagedChainState {
+    interceptors: Vec<(String, Arc<dyn Interceptor + Send + Sync>)>,
+
+    rtcp_readers: Vec<Pipeline<dyn RTCPReader + Send + Sync>>,
+    rtcp_writers: Vec<Pipeline<dyn RTCPWriter + Send + Sync>>,
+    local_streams: HashMap<u32, (StreamInfo, Pipeline<dyn RTPWriter + Send + Sync>)>,
+    remote_streams: HashMap<u32, (StreamInfo, Pipeline<dyn RTPReader + Send + Sync>)>,
+    closed: bool,
+}
+
+/// ManagedChain is an interceptor that runs all child interceptors in order, as Chain does,
+/// the child interceptors of which can be changed while it's in use with the
+/// [`ManagedChainHandle`] of its builder.
+pub struct ManagedChain {
+    id: String,
+    state: Mutex<ManagedChainState>,
+}
+
+impl ManagedChain {
+    /// builder returns a new ManagedChainBuilder.
+    pub fn builder() -> ManagedChainBuilder {
+        ManagedChainBuilder::default()
+    }
+
+    /// insert inserts icpr at index, binding it to what the chain is bound to.
+    async fn insert(&self, index: usize, name: &str, icpr: Arc<dyn Interceptor + Send + Sync>) {
+        let mut state = self.state.lock().await;
+        if state.closed {
+            return;
+        }
+        let state = &mut *state;
+
+        state.rtcp_writers.retain(|p| p.is_live());
+        for pipeline in &mut state.rtcp_writers {
+            let input = pipeline.input(index);
+            let output = icpr.bind_rtcp_writer(Arc::clone(&input) as _).await;
+            pipeline.insert(index, input, output);
+        }
+        for (info, pipeline) in state.local_streams.values_mut() {
+            let input = pipeline.input(index);
+            let output = icpr.bind_local_stream(info, Arc::clone(&input) as _).await;
+            pipeline.insert(index, input, output);
+        }
+        state.rtcp_readers.retain(|p| p.is_live());
+        for pipeline in &mut state.rtcp_readers {
+            let input = pipeline.input(index);
+            let output = icpr.bind_rtcp_reader(Arc::clone(&input) as _).await;
+            pipeline.insert(index, input, output);
+        }
+        for (info, pipeline) in state.remote_streams.values_mut() {
+            let input = pipeline.input(index);
+            let output = icpr.bind_remote_stream(info, Arc::clone(&input) as _).await;
+            pipeline.insert(index, input, output);
+        }
+
+        state.interceptors.insert(index, (name.to_owned(), icpr));
+    }
+
+    /// remove removes the interceptor at index, unbinding it from the streams and closing it.
+    async fn remove(&self, index: usize) -> Result<()> {
+        let icpr = {
+            let mut state = self.state.lock().await;
+            if state.closed {
+                return Ok(());
+            }
+            let state = &mut *state;
+            let (_, icpr) = state.interceptors.remove(index);
+
+            state.rtcp_writers.retain(|p| p.is_live());
+            for pipeline in &mut state.rtcp_writers {
+                pipeline.remove(index);
+            }
+            for (info, pipeline) in state.local_streams.values_mut() {
+                pipeline.remove(index);
+                icpr.unbind_local_stream(info).await;
+            }
+            state.rtcp_readers.retain(|p| p.is_live());
+            for pipeline in &mut state.rtcp_readers {
+                pipeline.remove(index);
+            }
+            for (info, pipeline) in state.remote_streams.values_mut() {
+                pipeline.remove(index);
+                icpr.unbind_remote_stream(info).await;
+            }
+            icpr
+        };
+
+        icpr.close().await
+    }
+}
+
+#[async_trait]
+impl Interceptor for ManagedChain {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        let mut state = self.state.lock().await;
+        let (mut pipeline, top) = Pipeline::new(reader);
+        for (index, (_, icpr)) in state.interceptors.iter().enumerate() {
+            let input = pipeline.input(index);
+            let output = icpr.bind_rtcp_reader(Arc::clone(&input) as _).await;
+            pipeline.insert(index, input, output);
+        }
+        state.rtcp_readers.retain(|p| p.is_live());
+        state.rtcp_readers.push(pipeline);
+        top
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        let mut state = self.state.lock().await;
+        let (mut pipeline, top) = Pipeline::new(writer);
+        for (index, (_, icpr)) in state.interceptors.iter().enumerate() {
+            let input = pipeline.input(index);
+            let output = icpr.bind_rtcp_writer(Arc::clone(&input) as _).await;
+            pipeline.insert(index, input, output);
+        }
+        state.rtcp_writers.retain(|p| p.is_live());
+        state.rtcp_writers.push(pipeline);
+        top
+    }
+
+    /// bind_local_stream lets you modify any outgoing RTP packets. It is called once for per LocalStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_local_stream(
+        &self,
+        info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        let mut state = self.state.lock().await;
+        let (mut pipeline, top) = Pipeline::new(writer);
+        for (index, (_, icpr)) in state.interceptors.iter().enumerate() {
+            let input = pipeline.input(index);
+            let output = icpr.bind_local_stream(info, Arc::clone(&input) as _).await;
+            pipeline.insert(index, input, output);
+        }
+        state
+            .local_streams
+            .insert(info.ssrc, (info.clone(), pipeline));
+        top
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, info: &StreamInfo) {
+        let mut state = self.state.lock().await;
+        state.local_streams.remove(&info.ssrc);
+        for (_, icpr) in &state.interceptors {
+            icpr.unbind_local_stream(info).await;
+        }
+    }
+
+    /// bind_remote_stream lets you modify any incoming RTP packets. It is called once for per RemoteStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_remote_stream(
+        &self,
+        info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        let mut state = self.state.lock().await;
+        let (mut pipeline, top) = Pipeline::new(reader);
+        for (index, (_, icpr)) in state.interceptors.iter().enumerate() {
+            let input = pipeline.input(index);
+            let output = icpr.bind_remote_stream(info, Arc::clone(&input) as _).await;
+            pipeline.insert(index, input, output);
+        }
+        state
+            .remote_streams
+            .insert(info.ssrc, (info.clone(), pipeline));
+        top
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, info: &StreamInfo) {
+        let mut state = self.state.lock().await;
+        state.remote_streams.remove(&info.ssrc);
+        for (_, icpr) in &state.interceptors {
+            icpr.unbind_remote_stream(info).await;
+        }
+    }
+
+    /// close closes the Interceptor, cleaning up any data if necessary.
+    async fn close(&self) -> Result<()> {
+        let mut state = self.state.lock().await;
+        state.closed = true;
+        let mut errs = vec![];
+        for (_, icpr) in &state.interceptors {
+            if let Err(err) = icpr.close().await {
+                errs.push(err);
+            }
+        }
+        flatten_errs(errs)
+    }
+}
@@ -0,0 +1,134 @@
+use std::sync::Weak;
+
+use super::*;
+
+/// Proxy forwards to a target which can be changed while it's in use.
+pub(super) struct Proxy<T: ?Sized> {
+    target: util::sync::RwLock<Arc<T>>,
+}
+
+impl<T: ?Sized> Proxy<T> {
+    fn new(target: Arc<T>) -> Arc<Self> {
+        Arc::new(Proxy {
+            target: util::sync::RwLock::new(target),
+        })
+    }
+
+    fn target(&self) -> Arc<T> {
+        let target = self.target.read();
+        Arc::clone(&target)
+    }
+
+    fn set_target(&self, target: Arc<T>) {
+        let mut t = self.target.write();
+        *t = target;
+    }
+}
+
+#[async_trait]
+impl RTCPReader for Proxy<dyn RTCPReader + Send + Sync> {
+    async fn read(
+        &self,
+        buf: &mut [u8],
+        a: &Attributes,
+    ) -> Result<(Vec<Box<dyn rtcp::packet::Packet + Send + Sync>>, Attributes)> {
+        self.target().read(buf, a).await
+    }
+}
+
+#[async_trait]
+impl RTCPWriter for Proxy<dyn RTCPWriter + Send + Sync> {
+    async fn write(
+        &self,
+        pkts: &[Box<dyn rtcp::packet::Packet + Send + Sync>],
+        attributes: &Attributes,
+    ) -> Result<usize> {
+        self.target().write(pkts, attributes).await
+    }
+}
+
+#[async_trait]
+impl RTPReader for Proxy<dyn RTPReader + Send + Sync> {
+    async fn read(
+        &self,
+        buf: &mut [u8],
+        a: &Attributes,
+    ) -> Result<(rtp::packet::Packet, Attributes)> {
+        self.target().read(buf, a).await
+    }
+}
+
+#[async_trait]
+impl RTPWriter for Proxy<dyn RTPWriter + Send + Sync> {
+    async fn write(&self, pkt: &rtp::packet::Packet, attributes: &Attributes) -> Result<usize> {
+        self.target().write(pkt, attributes).await
+    }
+}
+
+/// Pipeline wires a reader or a writer bound through the interceptors of a chain. Each
+/// interceptor is bound to a proxy of the output of the one before it, and the reader or
+/// writer returned is a proxy of the output of the last one, so that an interceptor is
+/// inserted or removed by changing the target of the proxy after it, without binding the
+/// others again.
+pub(super) struct Pipeline<T: ?Sized> {
+    endpoint: Arc<T>,
+    inputs: Vec<Arc<Proxy<T>>>,
+    outputs: Vec<Arc<T>>,
+    /// The proxy returned, which is forgotten once it's dropped.
+    top: Weak<Proxy<T>>,
+}
+
+impl<T: ?Sized> Pipeline<T> {
+    /// new returns the pipeline of the reader or the writer endpoint, and the proxy it's
+    /// used through, before any interceptor is inserted.
+    pub(super) fn new(endpoint: Arc<T>) -> (Self, Arc<Proxy<T>>) {
+        let top = Proxy::new(Arc::clone(&endpoint));
+        (
+            Pipeline {
+                endpoint,
+                inputs: vec![],
+                outputs: vec![],
+                top: Arc::downgrade(&top),
+            },
+            top,
+        )
+    }
+
+    /// is_live returns whether the proxy returned is still in use.
+    pub(super) fn is_live(&self) -> bool {
+        self.top.strong_count() > 0
+    }
+
+    /// input returns the proxy to bind the interceptor inserted at index to.
+    pub(super) fn input(&self, index: usize) -> Arc<Proxy<T>> {
+        match index.checked_sub(1) {
+            Some(i) => Proxy::new(Arc::clone(&self.outputs[i])),
+            None => Proxy::new(Arc::clone(&self.endpoint)),
+        }
+    }
+
+    /// insert inserts at index the interceptor bound to input, which returned output.
+    pub(super) fn insert(&mut self, index: usize, input: Arc<Proxy<T>>, output: Arc<T>) {
+        if let Some(next) = self.next(index) {
+            next.set_target(Arc::clone(&output));
+        }
+        self.inputs.insert(index, input);
+        self.outputs.insert(index, output);
+    }
+
+    /// remove removes the interceptor at index.
+    pub(super) fn remove(&mut self, index: usize) {
+        let input = self.inputs.remove(index);
+        self.outputs.remove(index);
+        if let Some(next) = self.next(index) {
+            next.set_target(input.target());
+        }
+    }
+
+    fn next(&self, index: usize) -> Option<Arc<Proxy<T>>> {
+        match self.inputs.get(index) {
+            Some(input) => Some(Arc::clone(input)),
+            None => self.top.upgrade(),
+        }
+    }
+}
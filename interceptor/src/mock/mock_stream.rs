@@ -20,12 +20,12 @@ pub struct MockStream {
     rtcp_out_modified_tx: mpsc::Sender<RTCPPackets>,
     rtp_out_modified_tx: mpsc::Sender<rtp::packet::Packet>,
     rtcp_in_rx: Mutex<mpsc::Receiver<RTCPPackets>>,
-    rtp_in_rx: Mutex<mpsc::Receiver<rtp::packet::Packet>>,
+    rtp_in_rx: Mutex<mpsc::Receiver<(rtp::packet::Packet, Attributes)>>,
 
     rtcp_out_modified_rx: Mutex<mpsc::Receiver<RTCPPackets>>,
     rtp_out_modified_rx: Mutex<mpsc::Receiver<rtp::packet::Packet>>,
     rtcp_in_tx: Mutex<Option<mpsc::Sender<RTCPPackets>>>,
-    rtp_in_tx: Mutex<Option<mpsc::Sender<rtp::packet::Packet>>>,
+    rtp_in_tx: Mutex<Option<mpsc::Sender<(rtp::packet::Packet, Attributes)>>>,
 
     rtcp_in_modified_rx: Mutex<mpsc::Receiver<Result<RTCPPackets>>>,
     rtp_in_modified_rx: Mutex<mpsc::Receiver<Result<rtp::packet::Packet>>>,
@@ -142,10 +142,20 @@ impl MockStream {
 
     /// write_rtp writes an rtp packet to the stream, using the interceptor
     pub async fn write_rtp(&self, pkt: &rtp::packet::Packet) -> Result<usize> {
-        let a = Attributes::new();
+        self.write_rtp_with_attributes(pkt, &Attributes::new())
+            .await
+    }
+
+    /// write_rtp_with_attributes writes an rtp packet to the stream with attributes, as set by
+    /// the sender, using the interceptor
+    pub async fn write_rtp_with_attributes(
+        &self,
+        pkt: &rtp::packet::Packet,
+        attributes: &Attributes,
+    ) -> Result<usize> {
         let rtp_writer = self.rtp_writer.lock().await;
         if let Some(writer) = &*rtp_writer {
-            writer.write(pkt, &a).await
+            writer.write(pkt, attributes).await
         } else {
             Err(Error::Other("invalid rtp_writer".to_owned()))
         }
@@ -161,9 +171,20 @@ impl MockStream {
 
     /// receive_rtp schedules a rtp packet, so it can be read be the stream
     pub async fn receive_rtp(&self, pkt: rtp::packet::Packet) {
+        self.receive_rtp_with_attributes(pkt, Attributes::new())
+            .await;
+    }
+
+    /// receive_rtp_with_attributes schedules a rtp packet, so it can be read be the stream
+    /// with attributes, as set by the transport
+    pub async fn receive_rtp_with_attributes(
+        &self,
+        pkt: rtp::packet::Packet,
+        attributes: Attributes,
+    ) {
         let rtp_in_tx = self.rtp_in_tx.lock().await;
         if let Some(tx) = &*rtp_in_tx {
-            let _ = tx.send(pkt).await;
+            let _ = tx.send((pkt, attributes)).await;
         }
     }
 
@@ -274,7 +295,7 @@ impl RTPReader for MockStream {
         buf: &mut [u8],
         a: &Attributes,
     ) -> Result<(rtp::packet::Packet, Attributes)> {
-        let pkt = {
+        let (pkt, attributes) = {
             let mut rtp_in = self.rtp_in_rx.lock().await;
             rtp_in.recv().await.ok_or(Error::ErrIoEOF)?
         };
@@ -286,7 +307,9 @@ impl RTPReader for MockStream {
         }
 
         buf[..n].copy_from_slice(&marshaled);
-        Ok((pkt, a.clone()))
+        let mut a = a.clone();
+        a.extend(attributes);
+        Ok((pkt, a))
     }
 }
 
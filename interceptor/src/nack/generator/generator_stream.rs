@@ -1,69 +1,97 @@
+use rtp::sequence::{SequenceTracker, SequenceUpdate};
+use tokio::time::Instant;
 use util::sync::Mutex;
 
 use super::*;
 use crate::nack::UINT16SIZE_HALF;
 
+/// NackPolicy tells which of the packets missing are requested.
+pub(super) struct NackPolicy {
+    /// Number of the last packets not requested yet.
+    pub(super) skip_last_n: u16,
+    /// How long a packet is missing before it is requested.
+    pub(super) skip_window: Duration,
+    /// Number of times a packet is requested at most, 0 for no limit.
+    pub(super) max_nacks_per_packet: u16,
+    /// How long after a packet is requested it is requested again.
+    pub(super) retry_after: Duration,
+}
+
+/// MissingPacket is a packet missing and the requests sent for it.
+struct MissingPacket {
+    detected: Instant,
+    last_nack: Option<Instant>,
+    nacks: u16,
+}
+
 struct GeneratorStreamInternal {
     packets: Vec<u64>,
     size: u16,
-    end: u16,
-    started: bool,
+    sequence: SequenceTracker,
     last_consecutive: u16,
+    missing: HashMap<u16, MissingPacket>,
 }
 
 impl GeneratorStreamInternal {
     fn new(log2_size_minus_6: u8) -> Self {
+        // jumps are handled by clearing the packets skipped like any gap
+        let mut sequence = SequenceTracker::with_limits(UINT16SIZE_HALF, UINT16SIZE_HALF);
+        // so that the packets late for the first one are behind it, even across a roll over
+        sequence.set_roll_over_count(1);
         GeneratorStreamInternal {
             packets: vec![0u64; 1 << log2_size_minus_6],
             size: 1 << (log2_size_minus_6 + 6),
-            end: 0,
-            started: false,
+            sequence,
             last_consecutive: 0,
+            missing: HashMap::new(),
         }
     }
 
-    fn add(&mut self, seq: u16) {
-        if !self.started {
-            self.set_received(seq);
-            self.end = seq;
-            self.started = true;
-            self.last_consecutive = seq;
-            return;
-        }
+    /// end returns the highest sequence number received.
+    fn end(&self) -> u16 {
+        self.sequence.max_extended().unwrap_or(0) as u16
+    }
 
+    fn add(&mut self, seq: u16) {
+        let end = self.end();
         let last_consecutive_plus1 = self.last_consecutive.wrapping_add(1);
-        let diff = seq.wrapping_sub(self.end);
-        if diff == 0 {
-            return;
-        } else if diff < UINT16SIZE_HALF {
-            // this means a positive diff, in other words seq > end (with counting for rollovers)
-            let mut i = self.end.wrapping_add(1);
-            while i != seq {
-                // clear packets between end and seq (these may contain packets from a "size" ago)
-                self.del_received(i);
-                i = i.wrapping_add(1);
+        match self.sequence.update(seq) {
+            SequenceUpdate::First { .. } => {
+                self.last_consecutive = seq;
             }
-            self.end = seq;
+            SequenceUpdate::Duplicate { .. } if seq == end => return,
+            SequenceUpdate::InOrder { .. } => {
+                // seq > end (with counting for rollovers)
+                let mut i = end.wrapping_add(1);
+                while i != seq {
+                    // clear packets between end and seq (these may contain packets from a "size" ago)
+                    self.del_received(i);
+                    i = i.wrapping_add(1);
+                }
 
-            let seq_sub_last_consecutive = seq.wrapping_sub(self.last_consecutive);
-            if last_consecutive_plus1 == seq {
-                self.last_consecutive = seq;
-            } else if seq_sub_last_consecutive > self.size {
-                let diff = seq.wrapping_sub(self.size);
-                self.last_consecutive = diff;
-                self.fix_last_consecutive(); // there might be valid packets at the beginning of the buffer now
+                let seq_sub_last_consecutive = seq.wrapping_sub(self.last_consecutive);
+                if last_consecutive_plus1 == seq {
+                    self.last_consecutive = seq;
+                } else if seq_sub_last_consecutive > self.size {
+                    let diff = seq.wrapping_sub(self.size);
+                    self.last_consecutive = diff;
+                    self.fix_last_consecutive(); // there might be valid packets at the beginning of the buffer now
+                }
+            }
+            _ => {
+                // seq < end (with counting for rollovers)
+                if last_consecutive_plus1 == seq {
+                    self.last_consecutive = seq;
+                    self.fix_last_consecutive(); // there might be other valid packets after seq
+                }
             }
-        } else if last_consecutive_plus1 == seq {
-            // negative diff, seq < end (with counting for rollovers)
-            self.last_consecutive = seq;
-            self.fix_last_consecutive(); // there might be other valid packets after seq
         }
 
         self.set_received(seq);
     }
 
     fn get(&self, seq: u16) -> bool {
-        let diff = self.end.wrapping_sub(seq);
+        let diff = self.end().wrapping_sub(seq);
         if diff >= UINT16SIZE_HALF {
             return false;
         }
@@ -76,7 +104,7 @@ impl GeneratorStreamInternal {
     }
 
     fn missing_seq_numbers(&self, skip_last_n: u16) -> Vec<u16> {
-        let until = self.end.wrapping_sub(skip_last_n);
+        let until = self.end().wrapping_sub(skip_last_n);
         let diff = until.wrapping_sub(self.last_consecutive);
         if diff >= UINT16SIZE_HALF {
             // until < s.last_consecutive (counting for rollover)
@@ -96,6 +124,47 @@ impl GeneratorStreamInternal {
         missing_packet_seq_nums
     }
 
+    /// nacks_to_send returns the packets missing to request at now with policy, recording
+    /// the requests.
+    fn nacks_to_send(&mut self, policy: &NackPolicy, now: Instant) -> Vec<u16> {
+        let missing_seq_nums = self.missing_seq_numbers(policy.skip_last_n);
+        // forget the packets received since, or out of the buffer
+        let mut missing: HashMap<u16, MissingPacket> = HashMap::new();
+        for seq in &missing_seq_nums {
+            let packet = self.missing.remove(seq).unwrap_or(MissingPacket {
+                detected: now,
+                last_nack: None,
+                nacks: 0,
+            });
+            missing.insert(*seq, packet);
+        }
+        self.missing = missing;
+
+        let mut nacks = vec![];
+        for seq in missing_seq_nums {
+            let packet = match self.missing.get_mut(&seq) {
+                Some(packet) => packet,
+                None => continue,
+            };
+            if now.duration_since(packet.detected) < policy.skip_window {
+                continue;
+            }
+            if policy.max_nacks_per_packet != 0 && packet.nacks >= policy.max_nacks_per_packet {
+                continue;
+            }
+            if let Some(last_nack) = packet.last_nack {
+                if now.duration_since(last_nack) < policy.retry_after {
+                    continue;
+                }
+            }
+            packet.last_nack = Some(now);
+            packet.nacks += 1;
+            nacks.push(seq);
+        }
+
+        nacks
+    }
+
     fn set_received(&mut self, seq: u16) {
         let pos = (seq % self.size) as usize;
         self.packets[pos / 64] |= 1u64 << (pos % 64);
@@ -113,7 +182,7 @@ impl GeneratorStreamInternal {
 
     fn fix_last_consecutive(&mut self) {
         let mut i = self.last_consecutive.wrapping_add(1);
-        while i != self.end.wrapping_add(1) && self.get_received(i) {
+        while i != self.end().wrapping_add(1) && self.get_received(i) {
             // find all consecutive packets
             i = i.wrapping_add(1);
         }
@@ -135,9 +204,9 @@ impl GeneratorStream {
         }
     }
 
-    pub(super) fn missing_seq_numbers(&self, skip_last_n: u16) -> Vec<u16> {
-        let internal = self.internal.lock();
-        internal.missing_seq_numbers(skip_last_n)
+    pub(super) fn nacks_to_send(&self, policy: &NackPolicy, now: Instant) -> Vec<u16> {
+        let mut internal = self.internal.lock();
+        internal.nacks_to_send(policy, now)
     }
 
     pub(super) fn add(&self, seq: u16) {
@@ -297,6 +366,47 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_generator_stream_nacks_to_send() {
+        let mut rl = GeneratorStreamInternal::new(1);
+        let policy = NackPolicy {
+            skip_last_n: 0,
+            skip_window: Duration::from_millis(20),
+            max_nacks_per_packet: 2,
+            retry_after: Duration::from_millis(100),
+        };
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        for seq in [10, 11, 13, 15] {
+            rl.add(seq);
+        }
+        assert!(
+            rl.nacks_to_send(&policy, at(0)).is_empty(),
+            "missing for less than the skip window"
+        );
+        rl.add(14);
+        assert_eq!(rl.nacks_to_send(&policy, at(20)), vec![12]);
+        rl.add(17);
+        assert!(
+            rl.nacks_to_send(&policy, at(60)).is_empty(),
+            "requested less than a round trip time ago"
+        );
+        assert_eq!(rl.nacks_to_send(&policy, at(120)), vec![12, 16]);
+        assert_eq!(
+            rl.nacks_to_send(&policy, at(300)),
+            vec![16],
+            "requested as many times as the limit"
+        );
+        assert!(rl.nacks_to_send(&policy, at(500)).is_empty());
+
+        // the packets received are forgotten
+        rl.add(12);
+        rl.add(16);
+        assert!(rl.nacks_to_send(&policy, at(600)).is_empty());
+        assert!(rl.missing.is_empty());
+    }
+
     #[test]
     fn test_generator_stream_rollover() {
         let mut rl = GeneratorStreamInternal::new(1);
@@ -310,5 +420,13 @@ mod test {
         rl.add(65534);
         rl.add(0);
         rl.add(65535);
+
+        // a packet late for the first one is behind it across the roll over
+        let mut rl = GeneratorStreamInternal::new(1);
+        rl.add(1);
+        rl.add(65535);
+        assert_eq!(rl.end(), 1);
+        rl.add(3);
+        assert_eq!(rl.missing_seq_numbers(0), vec![2]);
     }
 }
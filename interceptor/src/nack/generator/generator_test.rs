@@ -1,7 +1,10 @@
+use rtcp::extended_report::{DLRRReport, DLRRReportBlock, ExtendedReport};
 use rtcp::transport_feedbacks::transport_layer_nack::TransportLayerNack;
+use rtp::extension::abs_send_time_extension::unix2ntp;
 
 use super::*;
 use crate::mock::mock_stream::MockStream;
+use crate::rtt::ntp_short;
 use crate::stream_info::RTCPFeedback;
 use crate::test::timeout_or_fail;
 
@@ -64,3 +67,75 @@ async fn test_generator_interceptor() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_generator_interceptor_rtt() -> Result<()> {
+    const INTERVAL: Duration = Duration::from_millis(10);
+    const RTT: Duration = Duration::from_millis(200);
+    let icpr: Arc<dyn Interceptor + Send + Sync> =
+        Generator::builder().with_interval(INTERVAL).build("")?;
+
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            rtcp_feedback: vec![RTCPFeedback {
+                typ: "nack".to_owned(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    // the sender replies right away to a receiver reference time sent a round trip time ago
+    stream
+        .receive_rtcp(vec![Box::new(ExtendedReport {
+            sender_ssrc: 1,
+            reports: vec![Box::new(DLRRReportBlock {
+                reports: vec![DLRRReport {
+                    ssrc: 2,
+                    last_rr: ntp_short(unix2ntp(SystemTime::now() - RTT)),
+                    dlrr: 0,
+                }],
+            })],
+        })])
+        .await;
+    timeout_or_fail(Duration::from_millis(10), stream.read_rtcp())
+        .await
+        .expect("A read rtcp")
+        .expect("Not an error");
+
+    for seq_num in [10, 12] {
+        stream
+            .receive_rtp(rtp::packet::Packet {
+                header: rtp::header::Header {
+                    sequence_number: seq_num,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .await;
+        timeout_or_fail(Duration::from_millis(10), stream.read_rtp())
+            .await
+            .expect("A read packet")
+            .expect("Not an error");
+    }
+
+    let r = timeout_or_fail(Duration::from_millis(50), stream.written_rtcp())
+        .await
+        .expect("Write rtcp");
+    if let Some(p) = r[0].as_any().downcast_ref::<TransportLayerNack>() {
+        assert_eq!(p.nacks[0].packet_id, 11);
+    } else {
+        panic!("single packet RTCP Compound Packet expected");
+    }
+
+    // not requested again before a retransmission could have arrived
+    let r = tokio::time::timeout(INTERVAL * 10, stream.written_rtcp()).await;
+    assert!(r.is_err(), "nack sent again within the round trip time");
+
+    stream.close().await?;
+
+    Ok(())
+}
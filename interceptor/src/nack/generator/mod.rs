@@ -4,10 +4,10 @@ mod generator_test;
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use async_trait::async_trait;
-use generator_stream::GeneratorStream;
+use generator_stream::{GeneratorStream, NackPolicy};
 use rtcp::transport_feedbacks::transport_layer_nack::{
     nack_pairs_from_sequence_numbers, TransportLayerNack,
 };
@@ -16,17 +16,23 @@ use waitgroup::WaitGroup;
 
 use crate::error::{Error, Result};
 use crate::nack::stream_support_nack;
+use crate::rtt::RttTracker;
 use crate::stream_info::StreamInfo;
 use crate::{
     Attributes, Interceptor, InterceptorBuilder, RTCPReader, RTCPWriter, RTPReader, RTPWriter,
 };
 
+/// Number of times a missing packet is requested by default.
+const DEFAULT_MAX_NACKS_PER_PACKET: u16 = 10;
+
 /// GeneratorBuilder can be used to configure Generator Interceptor
 #[derive(Default)]
 pub struct GeneratorBuilder {
     log2_size_minus_6: Option<u8>,
     skip_last_n: Option<u16>,
     interval: Option<Duration>,
+    max_nacks_per_packet: Option<u16>,
+    skip_window: Option<Duration>,
 }
 
 impl GeneratorBuilder {
@@ -49,6 +55,20 @@ impl GeneratorBuilder {
         self.interval = Some(interval);
         self
     }
+
+    /// with_max_nacks_per_packet sets the number of times a missing packet is requested before
+    /// giving up on it, 0 requesting it for as long as it is missing.
+    pub fn with_max_nacks_per_packet(mut self, max_nacks_per_packet: u16) -> GeneratorBuilder {
+        self.max_nacks_per_packet = Some(max_nacks_per_packet);
+        self
+    }
+
+    /// with_skip_window sets how long a packet has to be missing before it is requested, so that
+    /// the packets only reordered aren't.
+    pub fn with_skip_window(mut self, skip_window: Duration) -> GeneratorBuilder {
+        self.skip_window = Some(skip_window);
+        self
+    }
 }
 
 impl InterceptorBuilder for GeneratorBuilder {
@@ -67,7 +87,12 @@ impl InterceptorBuilder for GeneratorBuilder {
                 } else {
                     Duration::from_millis(100)
                 },
+                max_nacks_per_packet: self
+                    .max_nacks_per_packet
+                    .unwrap_or(DEFAULT_MAX_NACKS_PER_PACKET),
+                skip_window: self.skip_window.unwrap_or_default(),
 
+                rtt: util::sync::Mutex::new(RttTracker::new()),
                 streams: Mutex::new(HashMap::new()),
                 close_rx: Mutex::new(Some(close_rx)),
             }),
@@ -82,12 +107,23 @@ struct GeneratorInternal {
     log2_size_minus_6: u8,
     skip_last_n: u16,
     interval: Duration,
+    max_nacks_per_packet: u16,
+    skip_window: Duration,
+
+    /// Round trip times measured with the senders of the remote streams.
+    rtt: util::sync::Mutex<RttTracker>,
 
     streams: Mutex<HashMap<u32, Arc<GeneratorStream>>>,
     close_rx: Mutex<Option<mpsc::Receiver<()>>>,
 }
 
 /// Generator interceptor generates nack feedback messages.
+///
+/// A missing packet is requested again only once a round trip time has passed since it was
+/// requested, when a retransmission could have arrived already. The round trip time of a
+/// remote stream is measured with the DLRR blocks its sender replies to the receiver reference
+/// times with, see [`crate::report::ReportBuilder::with_extended_reports`]. Until it
+/// is, the packets missing are requested at each interval.
 pub struct Generator {
     internal: Arc<GeneratorInternal>,
 
@@ -126,9 +162,22 @@ impl Generator {
                 _ = ticker.tick() =>{
                     let nacks = {
                         let mut nacks = vec![];
+                        let now = tokio::time::Instant::now();
                         let streams = internal.streams.lock().await;
                         for (ssrc, stream) in streams.iter() {
-                            let missing = stream.missing_seq_numbers(internal.skip_last_n);
+                            let rtt = {
+                                let rtt = internal.rtt.lock();
+                                rtt.estimate(*ssrc).map(|e| e.smoothed)
+                            };
+                            let missing = stream.nacks_to_send(
+                                &NackPolicy {
+                                    skip_last_n: internal.skip_last_n,
+                                    skip_window: internal.skip_window,
+                                    max_nacks_per_packet: internal.max_nacks_per_packet,
+                                    retry_after: rtt.unwrap_or_default(),
+                                },
+                                now,
+                            );
                             if missing.is_empty(){
                                 continue;
                             }
@@ -165,7 +214,10 @@ impl Interceptor for Generator {
         &self,
         reader: Arc<dyn RTCPReader + Send + Sync>,
     ) -> Arc<dyn RTCPReader + Send + Sync> {
-        reader
+        Arc::new(GeneratorRtcpReader {
+            parent_rtcp_reader: reader,
+            internal: Arc::clone(&self.internal),
+        })
     }
 
     /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
@@ -232,8 +284,12 @@ impl Interceptor for Generator {
 
     /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
     async fn unbind_remote_stream(&self, info: &StreamInfo) {
-        let mut receive_logs = self.internal.streams.lock().await;
-        receive_logs.remove(&info.ssrc);
+        {
+            let mut receive_logs = self.internal.streams.lock().await;
+            receive_logs.remove(&info.ssrc);
+        }
+        let mut rtt = self.internal.rtt.lock();
+        rtt.remove(info.ssrc);
     }
 
     /// close closes the Interceptor, cleaning up any data if necessary.
@@ -253,3 +309,25 @@ impl Interceptor for Generator {
         Ok(())
     }
 }
+
+/// GeneratorRtcpReader measures the round trip times with the reports read.
+struct GeneratorRtcpReader {
+    parent_rtcp_reader: Arc<dyn RTCPReader + Send + Sync>,
+    internal: Arc<GeneratorInternal>,
+}
+
+#[async_trait]
+impl RTCPReader for GeneratorRtcpReader {
+    async fn read(
+        &self,
+        buf: &mut [u8],
+        a: &Attributes,
+    ) -> Result<(Vec<Box<dyn rtcp::packet::Packet + Send + Sync>>, Attributes)> {
+        let (pkts, attr) = self.parent_rtcp_reader.read(buf, a).await?;
+        {
+            let mut rtt = self.internal.rtt.lock();
+            rtt.process_rtcp(&pkts, SystemTime::now());
+        }
+        Ok((pkts, attr))
+    }
+}
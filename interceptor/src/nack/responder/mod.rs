@@ -19,10 +19,14 @@ use crate::{
     Attributes, Interceptor, InterceptorBuilder, RTCPReader, RTCPWriter, RTPReader, RTPWriter,
 };
 
+/// Bytes of the packets kept for retransmission on each stream, by default.
+const DEFAULT_MAX_HISTORY_BYTES: usize = 4 * 1024 * 1024;
+
 /// GeneratorBuilder can be used to configure Responder Interceptor
 #[derive(Default)]
 pub struct ResponderBuilder {
     log2_size: Option<u8>,
+    max_history_bytes: Option<usize>,
 }
 
 impl ResponderBuilder {
@@ -32,6 +36,14 @@ impl ResponderBuilder {
         self.log2_size = Some(log2_size);
         self
     }
+
+    /// with_max_history_bytes sets how many bytes of packets are kept for retransmission on
+    /// each stream at most, the oldest packets being forgotten first. Zero only bounds the
+    /// number of packets, by the size.
+    pub fn with_max_history_bytes(mut self, max_history_bytes: usize) -> ResponderBuilder {
+        self.max_history_bytes = Some(max_history_bytes);
+        self
+    }
 }
 
 impl InterceptorBuilder for ResponderBuilder {
@@ -43,6 +55,7 @@ impl InterceptorBuilder for ResponderBuilder {
                 } else {
                     13 // 8192 = 1 << 13
                 },
+                max_history_bytes: self.max_history_bytes.unwrap_or(DEFAULT_MAX_HISTORY_BYTES),
                 streams: Arc::new(Mutex::new(HashMap::new())),
             }),
         }))
@@ -51,6 +64,7 @@ impl InterceptorBuilder for ResponderBuilder {
 
 pub struct ResponderInternal {
     log2_size: u8,
+    max_history_bytes: usize,
     streams: Arc<Mutex<HashMap<u32, Arc<ResponderStream>>>>,
 }
 
@@ -77,6 +91,7 @@ impl ResponderInternal {
                     let stream3 = Arc::clone(&stream2);
                     Box::pin(async move {
                         if let Some(p) = stream3.get(seq).await {
+                            let p = stream3.retransmission(p);
                             let a = Attributes::new();
                             if let Err(err) = stream3.next_rtp_writer.write(&p, &a).await {
                                 log::warn!("failed resending nacked packet: {}", err);
@@ -122,7 +137,10 @@ impl RTCPReader for ResponderRtcpReader {
     }
 }
 
-/// Responder responds to nack feedback messages
+/// Responder responds to nack feedback messages, by resending the packets nacked which it
+/// still has. The packets are resent on the RTX stream of [`StreamInfo::ssrc_retransmission`]
+/// when the stream has one, as specified in:
+/// <https://datatracker.ietf.org/doc/html/rfc4588>
 pub struct Responder {
     internal: Arc<ResponderInternal>,
 }
@@ -168,7 +186,17 @@ impl Interceptor for Responder {
             return writer;
         }
 
-        let stream = Arc::new(ResponderStream::new(self.internal.log2_size, writer));
+        let rtx = if info.ssrc_retransmission != 0 && info.payload_type_retransmission != 0 {
+            Some((info.ssrc_retransmission, info.payload_type_retransmission))
+        } else {
+            None
+        };
+        let stream = Arc::new(ResponderStream::new(
+            self.internal.log2_size,
+            self.internal.max_history_bytes,
+            rtx,
+            writer,
+        ));
         {
             let mut streams = self.internal.streams.lock().await;
             streams.insert(info.ssrc, Arc::clone(&stream));
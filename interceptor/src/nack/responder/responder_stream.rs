@@ -1,7 +1,10 @@
+use std::sync::atomic::{AtomicU16, Ordering};
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use bytes::{BufMut, BytesMut};
 use tokio::sync::Mutex;
+use util::MarshalSize;
 
 use crate::error::Result;
 use crate::nack::UINT16SIZE_HALF;
@@ -12,24 +15,64 @@ struct ResponderStreamInternal {
     size: u16,
     last_added: u16,
     started: bool,
+    /// Bytes of the packets kept, bounded by max_bytes unless zero.
+    bytes: usize,
+    max_bytes: usize,
+    /// Sequence number the oldest packets are forgotten from, to keep within max_bytes.
+    evict_from: u16,
 }
 
 impl ResponderStreamInternal {
-    fn new(log2_size: u8) -> Self {
+    fn new(log2_size: u8, max_bytes: usize) -> Self {
         ResponderStreamInternal {
             packets: vec![None; 1 << log2_size],
             size: 1 << log2_size,
             last_added: 0,
             started: false,
+            bytes: 0,
+            max_bytes,
+            evict_from: 0,
         }
     }
 
+    fn set(&mut self, seq: u16, packet: Option<rtp::packet::Packet>) {
+        let index = (seq % self.size) as usize;
+        if let Some(old) = &self.packets[index] {
+            self.bytes -= old.marshal_size();
+        }
+        if let Some(new) = &packet {
+            self.bytes += new.marshal_size();
+        }
+        self.packets[index] = packet;
+    }
+
+    /// evict forgets the oldest packets until the ones kept are within max_bytes, but the
+    /// last one added.
+    fn evict(&mut self) {
+        if self.max_bytes == 0 || self.bytes <= self.max_bytes {
+            return;
+        }
+
+        let mut seq = if self.last_added.wrapping_sub(self.evict_from) < self.size {
+            self.evict_from
+        } else {
+            self.last_added.wrapping_sub(self.size - 1)
+        };
+        while self.bytes > self.max_bytes && seq != self.last_added {
+            self.set(seq, None);
+            seq = seq.wrapping_add(1);
+        }
+        self.evict_from = seq;
+    }
+
     fn add(&mut self, packet: &rtp::packet::Packet) {
         let seq = packet.header.sequence_number;
         if !self.started {
-            self.packets[(seq % self.size) as usize] = Some(packet.clone());
+            self.set(seq, Some(packet.clone()));
             self.last_added = seq;
+            self.evict_from = seq;
             self.started = true;
+            self.evict();
             return;
         }
 
@@ -39,13 +82,14 @@ impl ResponderStreamInternal {
         } else if diff < UINT16SIZE_HALF {
             let mut i = self.last_added.wrapping_add(1);
             while i != seq {
-                self.packets[(i % self.size) as usize] = None;
+                self.set(i, None);
                 i = i.wrapping_add(1);
             }
         }
 
-        self.packets[(seq % self.size) as usize] = Some(packet.clone());
+        self.set(seq, Some(packet.clone()));
         self.last_added = seq;
+        self.evict();
     }
 
     fn get(&self, seq: u16) -> Option<&rtp::packet::Packet> {
@@ -64,17 +108,51 @@ impl ResponderStreamInternal {
 
 pub(super) struct ResponderStream {
     internal: Mutex<ResponderStreamInternal>,
+    /// SSRC and payload type of the RTX stream the packets are resent on, if any.
+    rtx: Option<(u32, u8)>,
+    rtx_sequence_number: AtomicU16,
     pub(super) next_rtp_writer: Arc<dyn RTPWriter + Send + Sync>,
 }
 
 impl ResponderStream {
-    pub(super) fn new(log2_size: u8, writer: Arc<dyn RTPWriter + Send + Sync>) -> Self {
+    pub(super) fn new(
+        log2_size: u8,
+        max_bytes: usize,
+        rtx: Option<(u32, u8)>,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Self {
         ResponderStream {
-            internal: Mutex::new(ResponderStreamInternal::new(log2_size)),
+            internal: Mutex::new(ResponderStreamInternal::new(log2_size, max_bytes)),
+            rtx,
+            rtx_sequence_number: AtomicU16::new(rand::random::<u16>()),
             next_rtp_writer: writer,
         }
     }
 
+    /// retransmission returns the packet resending pkt: pkt itself, or else its RTX packet,
+    /// whose payload starts with the original sequence number.
+    pub(super) fn retransmission(&self, pkt: rtp::packet::Packet) -> rtp::packet::Packet {
+        let (ssrc, payload_type) = match self.rtx {
+            Some(rtx) => rtx,
+            None => return pkt,
+        };
+
+        let mut payload = BytesMut::with_capacity(2 + pkt.payload.len());
+        payload.put_u16(pkt.header.sequence_number);
+        payload.put_slice(&pkt.payload);
+        rtp::packet::Packet {
+            header: rtp::header::Header {
+                ssrc,
+                payload_type,
+                sequence_number: self.rtx_sequence_number.fetch_add(1, Ordering::SeqCst),
+                padding: false,
+                ..pkt.header
+            },
+            payload: payload.freeze(),
+            ..Default::default()
+        }
+    }
+
     async fn add(&self, pkt: &rtp::packet::Packet) {
         let mut internal = self.internal.lock().await;
         internal.add(pkt);
@@ -91,6 +169,17 @@ impl ResponderStream {
 impl RTPWriter for ResponderStream {
     /// write a rtp packet
     async fn write(&self, pkt: &rtp::packet::Packet, a: &Attributes) -> Result<usize> {
+        // the packets of the RTX stream sent upstream, such as the padding of the pacer, take
+        // the next sequence numbers of the retransmissions
+        if let Some((ssrc, _)) = self.rtx {
+            if pkt.header.ssrc == ssrc {
+                let mut pkt = pkt.clone();
+                pkt.header.sequence_number =
+                    self.rtx_sequence_number.fetch_add(1, Ordering::SeqCst);
+                return self.next_rtp_writer.write(&pkt, a).await;
+            }
+        }
+
         self.add(pkt).await;
 
         self.next_rtp_writer.write(pkt, a).await
@@ -108,7 +197,7 @@ mod test {
             65530, 65531, 65532, 65533, 65534, 65535,
         ];
         for start in tests {
-            let mut sb = ResponderStreamInternal::new(3);
+            let mut sb = ResponderStreamInternal::new(3, 0);
 
             let add = |sb: &mut ResponderStreamInternal, nums: &[u16]| {
                 for n in nums {
@@ -173,4 +262,35 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_responder_stream_max_bytes() {
+        let packet = |sequence_number: u16| rtp::packet::Packet {
+            header: rtp::header::Header {
+                sequence_number,
+                ..Default::default()
+            },
+            payload: vec![0u8; 100].into(),
+            ..Default::default()
+        };
+        let packet_size = packet(0).marshal_size();
+        let mut sb = ResponderStreamInternal::new(3, 3 * packet_size);
+
+        // the sequence numbers wrap meanwhile
+        for seq in [65_534u16, 65_535, 0, 1, 2] {
+            sb.add(&packet(seq));
+        }
+        assert_eq!(sb.bytes, 3 * packet_size);
+        assert!(sb.get(65_534).is_none());
+        assert!(sb.get(65_535).is_none());
+        for seq in [0, 1, 2] {
+            assert!(sb.get(seq).is_some(), "packet {seq} should be kept");
+        }
+
+        // the packets which went out of the buffer aren't counted anymore
+        sb.add(&packet(20));
+        assert_eq!(sb.bytes, packet_size);
+        sb.add(&packet(21));
+        assert!(sb.get(20).is_some());
+    }
 }
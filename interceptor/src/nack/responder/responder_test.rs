@@ -74,3 +74,117 @@ async fn test_responder_interceptor() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_responder_interceptor_rtx() -> Result<()> {
+    let icpr: Arc<dyn Interceptor + Send + Sync> =
+        Responder::builder().with_log2_size(3).build("")?;
+
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            payload_type: 96,
+            ssrc_retransmission: 2,
+            payload_type_retransmission: 97,
+            rtcp_feedback: vec![RTCPFeedback {
+                typ: "nack".to_owned(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    for seq_num in [10u16, 11, 12] {
+        stream
+            .write_rtp(&rtp::packet::Packet {
+                header: rtp::header::Header {
+                    ssrc: 1,
+                    payload_type: 96,
+                    sequence_number: seq_num,
+                    timestamp: 3000,
+                    ..Default::default()
+                },
+                payload: vec![0xAA; 10].into(),
+                ..Default::default()
+            })
+            .await?;
+        timeout_or_fail(Duration::from_millis(10), stream.written_rtp())
+            .await
+            .expect("A packet");
+    }
+
+    stream
+        .receive_rtcp(vec![Box::new(TransportLayerNack {
+            media_ssrc: 1,
+            sender_ssrc: 3,
+            nacks: vec![NackPair {
+                packet_id: 10,
+                lost_packets: 0b10,
+            }], // sequence numbers: 10, 12
+        })])
+        .await;
+
+    let mut rtx_sequence_number = None;
+    for seq_num in [10u16, 12] {
+        let p = timeout_or_fail(Duration::from_millis(50), stream.written_rtp())
+            .await
+            .expect("A retransmission");
+        assert_eq!(p.header.ssrc, 2);
+        assert_eq!(p.header.payload_type, 97);
+        assert_eq!(p.header.timestamp, 3000);
+        // the RTX stream has its own sequence numbers
+        let expected =
+            rtx_sequence_number.map_or(p.header.sequence_number, |s: u16| s.wrapping_add(1));
+        assert_eq!(p.header.sequence_number, expected);
+        rtx_sequence_number = Some(p.header.sequence_number);
+        // prefixed by the original sequence number
+        assert_eq!(&p.payload[..2], &seq_num.to_be_bytes());
+        assert_eq!(&p.payload[2..], &[0xAA; 10]);
+    }
+
+    // the padding sent upstream on the RTX stream continues its sequence numbers
+    stream
+        .write_rtp(&rtp::packet::Packet {
+            header: rtp::header::Header {
+                ssrc: 2,
+                payload_type: 97,
+                sequence_number: 12,
+                padding: true,
+                ..Default::default()
+            },
+            padding_size: 255,
+            ..Default::default()
+        })
+        .await?;
+    let p = timeout_or_fail(Duration::from_millis(10), stream.written_rtp())
+        .await
+        .expect("A padding packet");
+    assert_eq!(
+        (p.header.ssrc, p.header.sequence_number),
+        (2, rtx_sequence_number.unwrap().wrapping_add(1))
+    );
+    // and is not resent instead of the media
+    stream
+        .receive_rtcp(vec![Box::new(TransportLayerNack {
+            media_ssrc: 1,
+            sender_ssrc: 3,
+            nacks: vec![NackPair {
+                packet_id: 12,
+                lost_packets: 0,
+            }],
+        })])
+        .await;
+    let p = timeout_or_fail(Duration::from_millis(50), stream.written_rtp())
+        .await
+        .expect("A retransmission");
+    assert_eq!(
+        &p.payload[..],
+        &[&12u16.to_be_bytes()[..], &[0xAA; 10]].concat()[..]
+    );
+
+    stream.close().await?;
+
+    Ok(())
+}
@@ -0,0 +1,682 @@
+mod pacer_stream;
+#[cfg(test)]
+mod pacer_test;
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use pacer_stream::PacerStream;
+use portable_atomic::AtomicU64;
+use tokio::sync::{mpsc, Mutex, Notify};
+use tokio::time::Instant;
+use waitgroup::WaitGroup;
+
+use crate::error::{Error, Result};
+use crate::gcc::probe::{ProbeCluster, ATTR_PROBE_CLUSTER_ID};
+use crate::stream_info::StreamInfo;
+use crate::{
+    Attributes, Interceptor, InterceptorBuilder, RTCPReader, RTCPWriter, RTPReader, RTPWriter,
+};
+
+const DEFAULT_INITIAL_BITRATE: u64 = 300_000;
+const DEFAULT_PACING_FACTOR: f64 = 2.5;
+const DEFAULT_BURST_INTERVAL: Duration = Duration::from_millis(40);
+const DEFAULT_MAX_QUEUE_DELAY: Duration = Duration::from_secs(2);
+
+/// Debt of the media budget, in bytes, small enough to send the next packet, which absorbs
+/// the rounding of the send times.
+const BUDGET_TOLERANCE: f64 = 1.0;
+/// Size of the padding of the packets sent to probe, when there isn't enough media.
+const PROBE_PADDING_SIZE: u8 = 255;
+const RTP_HEADER_SIZE: usize = 12;
+
+/// PacerRate is the target bitrate the pacers built pace the packets by, such as estimated by
+/// the GCC sender interceptor, and the probe clusters they are requested to send.
+#[derive(Debug)]
+pub struct PacerRate {
+    target_bitrate: AtomicU64,
+    probes: util::sync::Mutex<VecDeque<ProbeCluster>>,
+    probe_requested: Notify,
+}
+
+impl PacerRate {
+    /// target_bitrate returns the target bitrate, in bits per second.
+    pub fn target_bitrate(&self) -> u64 {
+        self.target_bitrate.load(Ordering::SeqCst)
+    }
+
+    /// set_target_bitrate sets the target bitrate, in bits per second. Zero doesn't pace the
+    /// packets at all.
+    pub fn set_target_bitrate(&self, bitrate: u64) {
+        self.target_bitrate.store(bitrate, Ordering::SeqCst);
+    }
+
+    /// probe requests a cluster of packets sent at the bitrate of cluster, with the media
+    /// queued or else padding, after the clusters requested before it.
+    pub fn probe(&self, cluster: ProbeCluster) {
+        {
+            let mut probes = self.probes.lock();
+            probes.push_back(cluster);
+        }
+        self.probe_requested.notify_waiters();
+    }
+
+    /// take_probe returns the next probe cluster requested.
+    pub(crate) fn take_probe(&self) -> Option<ProbeCluster> {
+        let mut probes = self.probes.lock();
+        probes.pop_front()
+    }
+}
+
+/// PacerBuilder can be used to configure Pacer Interceptor
+pub struct PacerBuilder {
+    rate: Arc<PacerRate>,
+    pacing_factor: Option<f64>,
+    burst_interval: Option<Duration>,
+    max_queue_delay: Option<Duration>,
+}
+
+impl Default for PacerBuilder {
+    fn default() -> Self {
+        PacerBuilder {
+            rate: Arc::new(PacerRate {
+                target_bitrate: AtomicU64::new(DEFAULT_INITIAL_BITRATE),
+                probes: util::sync::Mutex::new(VecDeque::new()),
+                probe_requested: Notify::new(),
+            }),
+            pacing_factor: None,
+            burst_interval: None,
+            max_queue_delay: None,
+        }
+    }
+}
+
+impl PacerBuilder {
+    /// with_initial_bitrate sets the target bitrate, in bits per second, the packets are
+    /// paced by until it is updated.
+    pub fn with_initial_bitrate(self, bitrate: u64) -> PacerBuilder {
+        self.rate.set_target_bitrate(bitrate);
+        self
+    }
+
+    /// with_pacing_factor sets how many times the target bitrate the packets are sent at,
+    /// so that the queue drains quickly.
+    pub fn with_pacing_factor(mut self, pacing_factor: f64) -> PacerBuilder {
+        self.pacing_factor = Some(pacing_factor);
+        self
+    }
+
+    /// with_burst_interval sets the duration of the packets which can be sent back to back
+    /// after the pacer was idle.
+    pub fn with_burst_interval(mut self, burst_interval: Duration) -> PacerBuilder {
+        self.burst_interval = Some(burst_interval);
+        self
+    }
+
+    /// with_max_queue_delay sets how long the packets queued may take to be sent at most.
+    /// The pacing rate is raised above the target bitrate as needed to keep up.
+    pub fn with_max_queue_delay(mut self, max_queue_delay: Duration) -> PacerBuilder {
+        self.max_queue_delay = Some(max_queue_delay);
+        self
+    }
+
+    /// rate returns the target bitrate the interceptors built pace the packets by, which can
+    /// be updated with the estimate of the bandwidth.
+    pub fn rate(&self) -> Arc<PacerRate> {
+        Arc::clone(&self.rate)
+    }
+}
+
+impl InterceptorBuilder for PacerBuilder {
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        let (close_tx, close_rx) = mpsc::channel(1);
+        Ok(Arc::new(Pacer {
+            internal: Arc::new(PacerInternal {
+                rate: Arc::clone(&self.rate),
+                queue: util::sync::Mutex::new(PacerQueue::new(
+                    self.pacing_factor.unwrap_or(DEFAULT_PACING_FACTOR),
+                    self.burst_interval.unwrap_or(DEFAULT_BURST_INTERVAL),
+                    self.max_queue_delay.unwrap_or(DEFAULT_MAX_QUEUE_DELAY),
+                )),
+                streams: util::sync::Mutex::new(PacedStreams::default()),
+                enqueued: Notify::new(),
+                close_rx: Mutex::new(Some(close_rx)),
+            }),
+
+            wg: Mutex::new(Some(WaitGroup::new())),
+            close_tx: Mutex::new(Some(close_tx)),
+        }))
+    }
+}
+
+/// ActiveProbe is the probe cluster being sent.
+#[derive(Debug, Copy, Clone)]
+struct ActiveProbe {
+    cluster: ProbeCluster,
+    start: Instant,
+    sent_bytes: usize,
+    sent_packets: usize,
+}
+
+impl ActiveProbe {
+    fn next_send_time(&self) -> Instant {
+        let elapsed_us = (self.sent_bytes as f64 * 8.0 * 1_000_000.0 / self.cluster.bitrate as f64)
+            .ceil() as u64;
+        self.start + Duration::from_micros(elapsed_us)
+    }
+}
+
+/// Paced is what the pacer sends next.
+#[derive(Debug, PartialEq, Eq)]
+enum Paced<T> {
+    /// A packet queued, and the id of the probe cluster it is sent for.
+    Packet(T, Option<usize>),
+    /// A padding packet for the probe cluster with the id.
+    Padding(usize),
+}
+
+/// PacerQueue queues the packets to send, and tells when they may be sent according to the
+/// media budget earned at the pacing rate, or to the probe cluster being sent.
+struct PacerQueue<T> {
+    pacing_factor: f64,
+    burst_interval: Duration,
+    max_queue_delay: Duration,
+
+    packets: VecDeque<(T, usize, Instant)>,
+    queued_bytes: usize,
+    /// Bytes which may be sent right away, negative after large packets.
+    budget: f64,
+    last_update: Option<Instant>,
+    probe: Option<ActiveProbe>,
+}
+
+impl<T> PacerQueue<T> {
+    fn new(pacing_factor: f64, burst_interval: Duration, max_queue_delay: Duration) -> Self {
+        PacerQueue {
+            pacing_factor,
+            burst_interval,
+            max_queue_delay,
+
+            packets: VecDeque::new(),
+            queued_bytes: 0,
+            budget: 0.0,
+            last_update: None,
+            probe: None,
+        }
+    }
+
+    fn is_probing(&self) -> bool {
+        self.probe.is_some()
+    }
+
+    /// start_probe starts sending cluster at now. The packets it sends don't count in the
+    /// media budget.
+    fn start_probe(&mut self, cluster: ProbeCluster, now: Instant) {
+        self.probe = Some(ActiveProbe {
+            cluster,
+            start: now,
+            sent_bytes: 0,
+            sent_packets: 0,
+        });
+    }
+
+    /// stop_probe stops sending the probe cluster, such as when no padding can be sent.
+    fn stop_probe(&mut self) {
+        self.probe = None;
+    }
+
+    fn push(&mut self, packet: T, size: usize, now: Instant) {
+        self.packets.push_back((packet, size, now));
+        self.queued_bytes += size;
+    }
+
+    /// pacing_rate returns the rate the packets are sent at, in bits per second: a multiple of
+    /// the target bitrate, or more if the oldest packet queued wouldn't be sent within the max
+    /// queue delay.
+    fn pacing_rate(&self, target_bitrate: u64, now: Instant) -> f64 {
+        let rate = target_bitrate as f64 * self.pacing_factor;
+        match self.packets.front() {
+            Some((_, _, enqueued_at)) if target_bitrate > 0 && !self.max_queue_delay.is_zero() => {
+                let remaining = self
+                    .max_queue_delay
+                    .saturating_sub(now.saturating_duration_since(*enqueued_at))
+                    .max(Duration::from_millis(1));
+                rate.max(self.queued_bytes as f64 * 8.0 / remaining.as_secs_f64())
+            }
+            _ => rate,
+        }
+    }
+
+    fn update_budget(&mut self, now: Instant, rate: f64) {
+        let elapsed = match self.last_update {
+            Some(last_update) => now.saturating_duration_since(last_update),
+            None => Duration::ZERO,
+        };
+        self.last_update = Some(now);
+
+        let max_budget = rate * self.burst_interval.as_secs_f64() / 8.0;
+        self.budget = (self.budget + rate * elapsed.as_secs_f64() / 8.0).min(max_budget);
+    }
+
+    /// next_send_time returns when the next packet queued may be sent, or None if there is
+    /// none.
+    fn next_send_time(&mut self, now: Instant, target_bitrate: u64) -> Option<Instant> {
+        let rate = self.pacing_rate(target_bitrate, now);
+        self.update_budget(now, rate);
+        if let Some(probe) = &self.probe {
+            return Some(probe.next_send_time().max(now));
+        }
+        if self.packets.is_empty() {
+            return None;
+        }
+        if rate <= 0.0 || self.budget > -BUDGET_TOLERANCE {
+            return Some(now);
+        }
+        let wait_us = (-self.budget * 8.0 / rate * 1_000_000.0).ceil();
+        Some(now + Duration::from_micros(wait_us as u64))
+    }
+
+    /// poll returns what should be sent at now, if anything: the next packet queued, or
+    /// padding when a probe cluster runs out of media.
+    fn poll(&mut self, now: Instant, target_bitrate: u64) -> Option<Paced<T>> {
+        let mut probe = match self.probe {
+            Some(probe) => probe,
+            None => {
+                return self
+                    .pop(now, target_bitrate)
+                    .map(|p| Paced::Packet(p, None))
+            }
+        };
+        if probe.next_send_time() > now {
+            return None;
+        }
+
+        let id = probe.cluster.id;
+        let (paced, size) = match self.packets.pop_front() {
+            Some((packet, size, _)) => {
+                self.queued_bytes -= size;
+                (Paced::Packet(packet, Some(id)), size)
+            }
+            None => (
+                Paced::Padding(id),
+                RTP_HEADER_SIZE + PROBE_PADDING_SIZE as usize,
+            ),
+        };
+        probe.sent_bytes += size;
+        probe.sent_packets += 1;
+        self.probe = if probe.sent_bytes >= probe.cluster.min_bytes
+            && probe.sent_packets >= probe.cluster.min_packets
+        {
+            None
+        } else {
+            Some(probe)
+        };
+        Some(paced)
+    }
+
+    /// pop returns the next packet queued if it may be sent at now.
+    fn pop(&mut self, now: Instant, target_bitrate: u64) -> Option<T> {
+        let rate = self.pacing_rate(target_bitrate, now);
+        self.update_budget(now, rate);
+        if rate > 0.0 && self.budget <= -BUDGET_TOLERANCE {
+            return None;
+        }
+
+        let (packet, size, _) = self.packets.pop_front()?;
+        self.queued_bytes -= size;
+        if rate > 0.0 {
+            self.budget -= size as f64;
+        }
+        Some(packet)
+    }
+}
+
+struct QueuedPacket {
+    pkt: rtp::packet::Packet,
+    attributes: Attributes,
+    writer: Arc<dyn RTPWriter + Send + Sync>,
+}
+
+/// PacedStream is a local stream the pacer can send padding on, on its RTX stream, so that
+/// the sequence numbers of the media, which the NACK responder resends by, are left alone.
+struct PacedStream {
+    writer: Arc<dyn RTPWriter + Send + Sync>,
+    /// SSRC and payload type of the RTX stream the padding is sent on, if any.
+    rtx: Option<(u32, u8)>,
+    /// RTP timestamp of the last packet sent, which the padding takes.
+    last_timestamp: Option<u32>,
+}
+
+/// RtxSequence keeps the sequence numbers of an RTX stream the pacer sends padding on
+/// continuous: the padding packets take sequence numbers, which the retransmissions following
+/// them are shifted by.
+#[derive(Debug, Default)]
+struct RtxSequence {
+    last_sequence_number: Option<u16>,
+    sequence_offset: Option<u16>,
+}
+
+impl RtxSequence {
+    /// rewrite returns the sequence number a retransmission of sequence_number is sent with.
+    fn rewrite(&mut self, sequence_number: u16) -> u16 {
+        // the retransmissions continue after the padding sent before the first of them
+        let offset = *self
+            .sequence_offset
+            .get_or_insert_with(|| match self.last_sequence_number {
+                Some(last) => last.wrapping_add(1).wrapping_sub(sequence_number),
+                None => 0,
+            });
+        let sequence_number = sequence_number.wrapping_add(offset);
+        self.last_sequence_number = Some(sequence_number);
+        sequence_number
+    }
+
+    /// next_padding returns the sequence number of the next padding packet.
+    fn next_padding(&mut self) -> u16 {
+        let sequence_number = match self.last_sequence_number {
+            Some(last) => last.wrapping_add(1),
+            None => rand::random::<u16>(),
+        };
+        self.last_sequence_number = Some(sequence_number);
+        if let Some(offset) = self.sequence_offset.as_mut() {
+            *offset = offset.wrapping_add(1);
+        }
+        sequence_number
+    }
+}
+
+#[derive(Default)]
+struct PacedStreams {
+    streams: HashMap<u32, PacedStream>,
+    /// Sequence numbers of the RTX streams of the streams, by RTX SSRC.
+    rtx: HashMap<u32, RtxSequence>,
+    /// SSRC of the stream with an RTX stream which sent last.
+    last_ssrc: Option<u32>,
+}
+
+struct PacerInternal {
+    rate: Arc<PacerRate>,
+    queue: util::sync::Mutex<PacerQueue<QueuedPacket>>,
+    streams: util::sync::Mutex<PacedStreams>,
+    enqueued: Notify,
+    close_rx: Mutex<Option<mpsc::Receiver<()>>>,
+}
+
+impl PacerInternal {
+    fn enqueue(&self, packet: QueuedPacket, size: usize) {
+        {
+            let mut queue = self.queue.lock();
+            queue.push(packet, size, Instant::now());
+        }
+        self.enqueued.notify_one();
+    }
+
+    async fn send(&self, packet: QueuedPacket, probe_cluster_id: Option<usize>) {
+        let QueuedPacket {
+            mut pkt,
+            mut attributes,
+            writer,
+        } = packet;
+        {
+            let mut streams = self.streams.lock();
+            let streams = &mut *streams;
+            let ssrc = pkt.header.ssrc;
+            if let Some(rtx) = streams.rtx.get_mut(&ssrc) {
+                pkt.header.sequence_number = rtx.rewrite(pkt.header.sequence_number);
+            } else if let Some(stream) = streams.streams.get_mut(&ssrc) {
+                stream.last_timestamp = Some(pkt.header.timestamp);
+                if stream.rtx.is_some() {
+                    streams.last_ssrc = Some(ssrc);
+                }
+            }
+        }
+        if let Some(id) = probe_cluster_id {
+            attributes.insert(ATTR_PROBE_CLUSTER_ID, id);
+        }
+
+        if let Err(err) = writer.write(&pkt, &attributes).await {
+            log::warn!("failed sending paced packet: {}", err);
+        }
+    }
+
+    /// send_padding sends a padding packet for a probe cluster on the RTX stream of the stream
+    /// which sent the last packet. It returns false if no stream with an RTX stream sent a
+    /// packet yet to pad after.
+    async fn send_padding(&self, probe_cluster_id: usize) -> bool {
+        let (pkt, writer) = {
+            let mut streams = self.streams.lock();
+            let streams = &mut *streams;
+            let stream = match streams
+                .last_ssrc
+                .and_then(|ssrc| streams.streams.get(&ssrc))
+            {
+                Some(stream) => stream,
+                None => return false,
+            };
+            let ((ssrc, payload_type), timestamp) = match (stream.rtx, stream.last_timestamp) {
+                (Some(rtx), Some(timestamp)) => (rtx, timestamp),
+                _ => return false,
+            };
+
+            let header = rtp::header::Header {
+                version: 2,
+                padding: true,
+                payload_type,
+                sequence_number: streams.rtx.entry(ssrc).or_default().next_padding(),
+                timestamp,
+                ssrc,
+                ..Default::default()
+            };
+            let pkt = rtp::packet::Packet {
+                header,
+                padding_size: PROBE_PADDING_SIZE,
+                ..Default::default()
+            };
+            (pkt, Arc::clone(&stream.writer))
+        };
+
+        let mut attributes = Attributes::new();
+        attributes.insert(ATTR_PROBE_CLUSTER_ID, probe_cluster_id);
+        if let Err(err) = writer.write(&pkt, &attributes).await {
+            log::warn!("failed sending padding: {}", err);
+        }
+        true
+    }
+}
+
+/// Pacer interceptor smooths the outgoing RTP packets of all the local streams to a multiple
+/// of the target bitrate, so that a large keyframe isn't sent as a single burst which
+/// overflows the queues of the network.
+///
+/// It sends the probe clusters requested, with the packets queued or else with padding on the
+/// RTX stream of [`StreamInfo::ssrc_retransmission`] of the stream which sent last. The
+/// padding never takes sequence numbers of the media, so the NACK responder can be on either
+/// side of the pacer: the retransmissions going through it are shifted after the padding, and
+/// the responder renumbers the padding going through it. Without RTX streams, the probe
+/// clusters are of the media only.
+///
+/// The interceptors recording the send time of the packets, such as the GCC sender one,
+/// should be between it and the transport.
+pub struct Pacer {
+    internal: Arc<PacerInternal>,
+
+    pub(crate) wg: Mutex<Option<WaitGroup>>,
+    pub(crate) close_tx: Mutex<Option<mpsc::Sender<()>>>,
+}
+
+impl Pacer {
+    /// builder returns a new PacerBuilder.
+    pub fn builder() -> PacerBuilder {
+        PacerBuilder::default()
+    }
+
+    async fn is_closed(&self) -> bool {
+        let close_tx = self.close_tx.lock().await;
+        close_tx.is_none()
+    }
+
+    async fn run(internal: Arc<PacerInternal>) -> Result<()> {
+        let mut close_rx = {
+            let mut close_rx = internal.close_rx.lock().await;
+            if let Some(close) = close_rx.take() {
+                close
+            } else {
+                return Err(Error::ErrInvalidCloseRx);
+            }
+        };
+
+        loop {
+            let next_send_time = {
+                let mut queue = internal.queue.lock();
+                if !queue.is_probing() {
+                    if let Some(cluster) = internal.rate.take_probe() {
+                        queue.start_probe(cluster, Instant::now());
+                    }
+                }
+                queue.next_send_time(Instant::now(), internal.rate.target_bitrate())
+            };
+            tokio::select! {
+                _ = internal.enqueued.notified() => {}
+                _ = internal.rate.probe_requested.notified() => {}
+                _ = tokio::time::sleep_until(next_send_time.unwrap_or_else(Instant::now)),
+                    if next_send_time.is_some() => {}
+                _ = close_rx.recv() => {
+                    return Ok(());
+                }
+            }
+
+            loop {
+                let paced = {
+                    let mut queue = internal.queue.lock();
+                    queue.poll(Instant::now(), internal.rate.target_bitrate())
+                };
+                match paced {
+                    Some(Paced::Packet(packet, probe_cluster_id)) => {
+                        internal.send(packet, probe_cluster_id).await;
+                    }
+                    Some(Paced::Padding(probe_cluster_id)) => {
+                        if !internal.send_padding(probe_cluster_id).await {
+                            let mut queue = internal.queue.lock();
+                            queue.stop_probe();
+                        }
+                    }
+                    None => break,
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Interceptor for Pacer {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        reader
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        if self.is_closed().await {
+            return writer;
+        }
+
+        let mut w = {
+            let wait_group = self.wg.lock().await;
+            wait_group.as_ref().map(|wg| wg.worker())
+        };
+        let internal = Arc::clone(&self.internal);
+        tokio::spawn(async move {
+            let _d = w.take();
+            if let Err(err) = Pacer::run(internal).await {
+                log::warn!("bind_rtcp_writer Pacer::run got error: {}", err);
+            }
+        });
+
+        writer
+    }
+
+    /// bind_local_stream returns a writer which queues the outgoing packets, to be written
+    /// at the pacing rate.
+    async fn bind_local_stream(
+        &self,
+        info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        let rtx = if info.ssrc_retransmission != 0 && info.payload_type_retransmission != 0 {
+            Some((info.ssrc_retransmission, info.payload_type_retransmission))
+        } else {
+            None
+        };
+        {
+            let mut streams = self.internal.streams.lock();
+            if let Some((ssrc, _)) = rtx {
+                streams.rtx.insert(ssrc, RtxSequence::default());
+            }
+            streams.streams.insert(
+                info.ssrc,
+                PacedStream {
+                    writer: Arc::clone(&writer),
+                    rtx,
+                    last_timestamp: None,
+                },
+            );
+        }
+        Arc::new(PacerStream::new(writer, Arc::clone(&self.internal)))
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, info: &StreamInfo) {
+        let mut streams = self.internal.streams.lock();
+        if let Some(PacedStream {
+            rtx: Some((ssrc, _)),
+            ..
+        }) = streams.streams.remove(&info.ssrc)
+        {
+            streams.rtx.remove(&ssrc);
+        }
+        if streams.last_ssrc == Some(info.ssrc) {
+            streams.last_ssrc = None;
+        }
+    }
+
+    /// bind_remote_stream lets you modify any incoming RTP packets. It is called once for per RemoteStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_remote_stream(
+        &self,
+        _info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        reader
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, _info: &StreamInfo) {}
+
+    /// close closes the Interceptor, cleaning up any data if necessary.
+    async fn close(&self) -> Result<()> {
+        {
+            let mut close_tx = self.close_tx.lock().await;
+            close_tx.take();
+        }
+
+        {
+            let mut wait_group = self.wg.lock().await;
+            if let Some(wg) = wait_group.take() {
+                wg.wait().await;
+            }
+        }
+
+        Ok(())
+    }
+}
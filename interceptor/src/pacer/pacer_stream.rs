@@ -0,0 +1,38 @@
+use util::MarshalSize;
+
+use super::*;
+
+pub(super) struct PacerStream {
+    next_rtp_writer: Arc<dyn RTPWriter + Send + Sync>,
+    internal: Arc<PacerInternal>,
+}
+
+impl PacerStream {
+    pub(super) fn new(
+        next_rtp_writer: Arc<dyn RTPWriter + Send + Sync>,
+        internal: Arc<PacerInternal>,
+    ) -> Self {
+        PacerStream {
+            next_rtp_writer,
+            internal,
+        }
+    }
+}
+
+/// RTPWriter is used by Interceptor.bind_local_stream.
+#[async_trait]
+impl RTPWriter for PacerStream {
+    /// write queues a rtp packet, and returns its size
+    async fn write(&self, pkt: &rtp::packet::Packet, a: &Attributes) -> Result<usize> {
+        let size = pkt.marshal_size();
+        self.internal.enqueue(
+            QueuedPacket {
+                pkt: pkt.clone(),
+                attributes: a.clone(),
+                writer: Arc::clone(&self.next_rtp_writer),
+            },
+            size,
+        );
+        Ok(size)
+    }
+}
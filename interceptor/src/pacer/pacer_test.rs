@@ -0,0 +1,316 @@
+use super::*;
+use crate::mock::mock_stream::MockStream;
+
+const PACKET_SIZE: usize = 1000;
+
+fn new_queue(pacing_factor: f64, max_queue_delay: Duration) -> PacerQueue<u16> {
+    PacerQueue::new(pacing_factor, Duration::from_millis(40), max_queue_delay)
+}
+
+#[test]
+fn test_pacer_queue_paces() {
+    // 100kbps, a 1000 bytes packet every 80ms
+    let mut queue = new_queue(1.0, DEFAULT_MAX_QUEUE_DELAY);
+    let start = Instant::now();
+    for i in 0..3 {
+        queue.push(i, PACKET_SIZE, start);
+    }
+
+    assert_eq!(queue.next_send_time(start, 100_000), Some(start));
+    assert_eq!(queue.pop(start, 100_000), Some(0));
+    assert_eq!(queue.pop(start, 100_000), None);
+
+    let next = queue.next_send_time(start, 100_000).unwrap();
+    assert_eq!(next - start, Duration::from_millis(80));
+    assert_eq!(queue.pop(start + Duration::from_millis(40), 100_000), None);
+    assert_eq!(queue.pop(next, 100_000), Some(1));
+    assert_eq!(queue.pop(next, 100_000), None);
+}
+
+#[test]
+fn test_pacer_queue_burst_after_idle() {
+    // 1Mbps, a 40ms burst is 5000 bytes
+    let mut queue = new_queue(1.0, DEFAULT_MAX_QUEUE_DELAY);
+    let start = Instant::now();
+    assert_eq!(queue.next_send_time(start, 1_000_000), None);
+
+    let now = start + Duration::from_secs(10);
+    for i in 0..10 {
+        queue.push(i, PACKET_SIZE, now);
+    }
+    let sent: Vec<u16> = std::iter::from_fn(|| queue.pop(now, 1_000_000)).collect();
+    assert_eq!(sent, vec![0, 1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn test_pacer_queue_pacing_factor() {
+    let mut queue = new_queue(2.5, DEFAULT_MAX_QUEUE_DELAY);
+    let now = Instant::now();
+    assert_eq!(queue.pacing_rate(100_000, now), 250_000.0);
+    queue.push(0, PACKET_SIZE, now);
+    assert_eq!(queue.pacing_rate(100_000, now), 250_000.0);
+    assert_eq!(queue.pacing_rate(0, now), 0.0);
+}
+
+#[test]
+fn test_pacer_queue_max_queue_delay() {
+    // 10 packets can't be sent within a second at 8kbps
+    let mut queue = new_queue(1.0, Duration::from_secs(1));
+    let start = Instant::now();
+    for i in 0..10 {
+        queue.push(i, PACKET_SIZE, start);
+    }
+    assert_eq!(queue.pacing_rate(8_000, start), 80_000.0);
+
+    let mut now = start;
+    let mut sent = 0;
+    while let Some(next) = queue.next_send_time(now, 8_000) {
+        now = next;
+        if queue.pop(now, 8_000).is_some() {
+            sent += 1;
+        }
+    }
+    assert_eq!(sent, 10);
+    assert!(
+        now - start <= Duration::from_secs(1),
+        "queue drained in {:?}",
+        now - start
+    );
+}
+
+#[test]
+fn test_pacer_queue_unpaced() {
+    let mut queue = new_queue(1.0, DEFAULT_MAX_QUEUE_DELAY);
+    let now = Instant::now();
+    for i in 0..10 {
+        queue.push(i, PACKET_SIZE, now);
+    }
+    let sent: Vec<u16> = std::iter::from_fn(|| queue.pop(now, 0)).collect();
+    assert_eq!(sent.len(), 10);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_pacer_interceptor() -> Result<()> {
+    let builder = Pacer::builder()
+        .with_initial_bitrate(80_000)
+        .with_pacing_factor(1.0);
+    let rate = builder.rate();
+    let icpr = builder.build("")?;
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    for seq in 0..4u16 {
+        let header = rtp::header::Header {
+            ssrc: 1,
+            sequence_number: seq,
+            ..Default::default()
+        };
+        let size = stream
+            .write_rtp(&rtp::packet::Packet {
+                header,
+                payload: vec![0u8; PACKET_SIZE - 12].into(),
+                ..Default::default()
+            })
+            .await?;
+        assert_eq!(size, PACKET_SIZE);
+    }
+
+    // 80kbps, a 1000 bytes packet every 100ms
+    let start = Instant::now();
+    for seq in 0..3u16 {
+        let pkt = stream.written_rtp().await.unwrap();
+        assert_eq!(pkt.header.sequence_number, seq);
+        let elapsed = Instant::now() - start;
+        let expected = Duration::from_millis(100) * seq as u32;
+        assert!(
+            elapsed >= expected && elapsed < expected + Duration::from_millis(5),
+            "packet {seq} sent after {elapsed:?}"
+        );
+    }
+
+    // unpaced once the target is zero
+    rate.set_target_bitrate(0);
+    let pkt = tokio::time::timeout(Duration::from_millis(150), stream.written_rtp())
+        .await
+        .expect("packet should be sent")
+        .unwrap();
+    assert_eq!(pkt.header.sequence_number, 3);
+
+    stream.close().await?;
+    Ok(())
+}
+
+#[test]
+fn test_pacer_queue_probe() {
+    let mut queue = new_queue(1.0, DEFAULT_MAX_QUEUE_DELAY);
+    let start = Instant::now();
+    for i in 0..2 {
+        queue.push(i, PACKET_SIZE, start);
+    }
+    // 800kbps, a 1000 bytes packet every 10ms
+    queue.start_probe(
+        ProbeCluster {
+            id: 7,
+            bitrate: 800_000,
+            min_packets: 4,
+            min_bytes: 2_000,
+        },
+        start,
+    );
+
+    assert_eq!(queue.poll(start, 100_000), Some(Paced::Packet(0, Some(7))));
+    assert_eq!(queue.poll(start, 100_000), None);
+    let next = queue.next_send_time(start, 100_000).unwrap();
+    assert_eq!(next - start, Duration::from_millis(10));
+    assert_eq!(queue.poll(next, 100_000), Some(Paced::Packet(1, Some(7))));
+    // padding once the media runs out, until the packets are sent
+    let next = queue.next_send_time(next, 100_000).unwrap();
+    assert_eq!(next - start, Duration::from_millis(20));
+    assert_eq!(queue.poll(next, 100_000), Some(Paced::Padding(7)));
+    assert!(queue.is_probing());
+    let next = queue.next_send_time(next, 100_000).unwrap();
+    assert_eq!(queue.poll(next, 100_000), Some(Paced::Padding(7)));
+    assert!(!queue.is_probing());
+    assert_eq!(queue.next_send_time(next, 100_000), None);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_pacer_interceptor_probe_padding() -> Result<()> {
+    let builder = Pacer::builder();
+    let rate = builder.rate();
+    let icpr = builder.build("")?;
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            ssrc_retransmission: 2,
+            payload_type_retransmission: 97,
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    let write = |ssrc: u32, sequence_number: u16| {
+        let stream = Arc::clone(&stream);
+        async move {
+            stream
+                .write_rtp(&rtp::packet::Packet {
+                    header: rtp::header::Header {
+                        version: 2,
+                        payload_type: if ssrc == 1 { 96 } else { 97 },
+                        ssrc,
+                        sequence_number,
+                        timestamp: 3000,
+                        ..Default::default()
+                    },
+                    payload: vec![0u8; 100].into(),
+                    ..Default::default()
+                })
+                .await
+        }
+    };
+
+    write(1, 10).await?;
+    let pkt = stream.written_rtp().await.unwrap();
+    assert_eq!(pkt.header.sequence_number, 10);
+    // a retransmission before any padding is sent as it is
+    write(2, 500).await?;
+    let pkt = stream.written_rtp().await.unwrap();
+    assert_eq!((pkt.header.ssrc, pkt.header.sequence_number), (2, 500));
+
+    rate.probe(ProbeCluster {
+        id: 1,
+        bitrate: 1_000_000,
+        min_packets: 3,
+        min_bytes: 0,
+    });
+    for sequence_number in 501..504 {
+        let pkt = stream.written_rtp().await.unwrap();
+        assert!(pkt.header.padding);
+        assert_eq!(pkt.padding_size, PROBE_PADDING_SIZE);
+        assert!(pkt.payload.is_empty());
+        assert_eq!(pkt.header.sequence_number, sequence_number);
+        assert_eq!(pkt.header.ssrc, 2);
+        assert_eq!(pkt.header.payload_type, 97);
+        assert_eq!(pkt.header.timestamp, 3000);
+    }
+
+    // the media following the padding is left alone, the retransmissions are shifted
+    write(1, 11).await?;
+    let pkt = stream.written_rtp().await.unwrap();
+    assert!(!pkt.header.padding);
+    assert_eq!((pkt.header.ssrc, pkt.header.sequence_number), (1, 11));
+    write(2, 501).await?;
+    let pkt = stream.written_rtp().await.unwrap();
+    assert_eq!((pkt.header.ssrc, pkt.header.sequence_number), (2, 504));
+
+    stream.close().await?;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_pacer_interceptor_probe_without_rtx() -> Result<()> {
+    let builder = Pacer::builder();
+    let rate = builder.rate();
+    let icpr = builder.build("")?;
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    let pkt = rtp::packet::Packet {
+        header: rtp::header::Header {
+            ssrc: 1,
+            sequence_number: 10,
+            ..Default::default()
+        },
+        payload: vec![0u8; 100].into(),
+        ..Default::default()
+    };
+    stream.write_rtp(&pkt).await?;
+    assert_eq!(
+        stream.written_rtp().await.unwrap().header.sequence_number,
+        10
+    );
+
+    // no padding is sent on the media, the probe stops
+    rate.probe(ProbeCluster {
+        id: 1,
+        bitrate: 1_000_000,
+        min_packets: 3,
+        min_bytes: 0,
+    });
+    assert!(
+        tokio::time::timeout(Duration::from_millis(50), stream.written_rtp())
+            .await
+            .is_err()
+    );
+
+    stream.close().await?;
+    Ok(())
+}
+
+#[test]
+fn test_rtx_sequence() {
+    // padding first, then the retransmissions continue after it
+    let mut rtx = RtxSequence::default();
+    let first = rtx.next_padding();
+    assert_eq!(rtx.next_padding(), first.wrapping_add(1));
+    assert_eq!(rtx.rewrite(100), first.wrapping_add(2));
+    assert_eq!(rtx.rewrite(101), first.wrapping_add(3));
+
+    let mut rtx = RtxSequence::default();
+    assert_eq!(rtx.rewrite(65_535), 65_535);
+    assert_eq!(rtx.next_padding(), 0);
+    assert_eq!(rtx.rewrite(0), 1);
+}
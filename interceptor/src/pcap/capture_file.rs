@@ -0,0 +1,200 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use bytes::{BufMut, BytesMut};
+
+use super::pcapng::{PcapngWriter, LINKTYPE_IPV4};
+use crate::error::Result;
+
+/// Addresses and port of the endpoints of the UDP datagrams the packets are captured in, as
+/// the packets are captured before SRTP and don't have any.
+const LOCAL_ADDRESS: [u8; 4] = [192, 0, 2, 1];
+const REMOTE_ADDRESS: [u8; 4] = [192, 0, 2, 2];
+const PORT: u16 = 5004;
+
+const IPV4_HEADER_SIZE: usize = 20;
+const UDP_HEADER_SIZE: usize = 8;
+
+/// Direction of the packets captured.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) enum Direction {
+    Outgoing,
+    Incoming,
+}
+
+struct CaptureFileState {
+    writer: Option<PcapngWriter<BufWriter<File>>>,
+    /// Time the first packet of the file was captured at.
+    started: Option<SystemTime>,
+}
+
+/// CaptureFile writes the packets captured to a pcapng file, rotated once it reaches its max
+/// size or, if any, its max duration, keeping up to max_files files.
+pub(super) struct CaptureFile {
+    path: PathBuf,
+    max_file_size: u64,
+    max_files: usize,
+    max_file_duration: Option<Duration>,
+    state: util::sync::Mutex<CaptureFileState>,
+}
+
+impl CaptureFile {
+    pub(super) fn create(
+        path: PathBuf,
+        max_file_size: u64,
+        max_files: usize,
+        max_file_duration: Option<Duration>,
+    ) -> Result<Self> {
+        let writer = create_writer(&path)?;
+        Ok(CaptureFile {
+            path,
+            max_file_size,
+            max_files: max_files.max(1),
+            max_file_duration,
+            state: util::sync::Mutex::new(CaptureFileState {
+                writer: Some(writer),
+                started: None,
+            }),
+        })
+    }
+
+    /// write writes the packet to the file, in an UDP datagram between the fake addresses
+    /// of direction. The capture stops on the first error, which is logged.
+    pub(super) fn write(&self, direction: Direction, packet: &[u8], comment: &str) {
+        self.write_at(SystemTime::now(), direction, packet, comment)
+    }
+
+    /// write_at writes the packet captured at now.
+    pub(super) fn write_at(
+        &self,
+        now: SystemTime,
+        direction: Direction,
+        packet: &[u8],
+        comment: &str,
+    ) {
+        let datagram = udp_datagram(direction, packet);
+        let mut guard = self.state.lock();
+        let state = &mut *guard;
+        let writer = match state.writer.as_mut() {
+            Some(writer) => writer,
+            None => return,
+        };
+
+        let size = PcapngWriter::<BufWriter<File>>::packet_size(&datagram, comment);
+        let expired = match (self.max_file_duration, state.started) {
+            (Some(max_file_duration), Some(started)) => {
+                now.duration_since(started).unwrap_or_default() >= max_file_duration
+            }
+            _ => false,
+        };
+        let full = writer.packets() > 0 && writer.written() + size > self.max_file_size;
+        let result = if full || expired {
+            self.rotate(state)
+        } else {
+            Ok(())
+        };
+        let result = result.and_then(|_| match state.writer.as_mut() {
+            Some(writer) => {
+                state.started.get_or_insert(now);
+                writer.write_packet(now, &datagram, comment)
+            }
+            None => Ok(()),
+        });
+        if let Err(err) = result {
+            log::warn!("failed capturing packets to {:?}: {}", self.path, err);
+            state.writer = None;
+        }
+    }
+
+    /// flush flushes the packets written to the file.
+    pub(super) fn flush(&self) -> Result<()> {
+        let mut state = self.state.lock();
+        match state.writer.as_mut() {
+            Some(writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// rotate renames the file to the first rotated one, shifting the rotated files and
+    /// removing the oldest beyond max_files, and starts a new file.
+    fn rotate(&self, state: &mut CaptureFileState) -> Result<()> {
+        if let Some(mut writer) = state.writer.take() {
+            writer.flush()?;
+        }
+
+        if self.max_files > 1 {
+            for i in (1..self.max_files - 1).rev() {
+                let from = rotated_path(&self.path, i);
+                if from.exists() {
+                    std::fs::rename(&from, rotated_path(&self.path, i + 1))
+                        .map_err(util::Error::from)?;
+                }
+            }
+            std::fs::rename(&self.path, rotated_path(&self.path, 1)).map_err(util::Error::from)?;
+        }
+
+        state.writer = Some(create_writer(&self.path)?);
+        state.started = None;
+        Ok(())
+    }
+}
+
+fn create_writer(path: &Path) -> Result<PcapngWriter<BufWriter<File>>> {
+    let file = File::create(path).map_err(util::Error::from)?;
+    PcapngWriter::new(BufWriter::new(file), LINKTYPE_IPV4)
+}
+
+/// rotated_path returns the path of the i-th rotated file, with i before the extension of
+/// path: `capture.pcapng` is rotated to `capture.1.pcapng`.
+pub(super) fn rotated_path(path: &Path, i: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let file_name = match path.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, i, extension.to_string_lossy()),
+        None => format!("{stem}.{i}"),
+    };
+    path.with_file_name(file_name)
+}
+
+/// udp_datagram returns the IPv4 packet of the UDP datagram carrying packet in direction.
+fn udp_datagram(direction: Direction, packet: &[u8]) -> BytesMut {
+    let (source, destination) = match direction {
+        Direction::Outgoing => (LOCAL_ADDRESS, REMOTE_ADDRESS),
+        Direction::Incoming => (REMOTE_ADDRESS, LOCAL_ADDRESS),
+    };
+    let udp_length = UDP_HEADER_SIZE + packet.len();
+    let total_length = IPV4_HEADER_SIZE + udp_length;
+
+    let mut b = BytesMut::with_capacity(total_length);
+    b.put_u8(0x45); // version 4, header of 5 words
+    b.put_u8(0); // DSCP and ECN
+    b.put_u16(total_length as u16);
+    b.put_u16(0); // identification
+    b.put_u16(0x4000); // don't fragment
+    b.put_u8(64); // TTL
+    b.put_u8(17); // UDP
+    b.put_u16(0); // checksum, set below
+    b.put_slice(&source);
+    b.put_slice(&destination);
+    let checksum = ipv4_checksum(&b[..IPV4_HEADER_SIZE]);
+    b[10..12].copy_from_slice(&checksum.to_be_bytes());
+
+    b.put_u16(PORT);
+    b.put_u16(PORT);
+    b.put_u16(udp_length as u16);
+    b.put_u16(0); // checksum, optional over IPv4
+    b.put_slice(packet);
+    b
+}
+
+fn ipv4_checksum(header: &[u8]) -> u16 {
+    let mut sum: u32 = header
+        .chunks(2)
+        .map(|w| u16::from_be_bytes([w[0], w[1]]) as u32)
+        .sum();
+    while sum > 0xFFFF {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    !(sum as u16)
+}
@@ -0,0 +1,228 @@
+mod capture_file;
+mod pcap_stream;
+#[cfg(test)]
+mod pcap_test;
+pub mod pcapng;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use capture_file::{CaptureFile, Direction};
+use pcap_stream::{PcapRtpReader, PcapRtpWriter, PcapStream};
+use util::Marshal;
+
+use crate::error::Result;
+use crate::stream_info::StreamInfo;
+use crate::{
+    Attributes, Interceptor, InterceptorBuilder, RTCPReader, RTCPWriter, RTPReader, RTPWriter,
+};
+
+const DEFAULT_PATH: &str = "webrtc.pcapng";
+const DEFAULT_MAX_FILE_SIZE: u64 = 16 * 1024 * 1024;
+const DEFAULT_MAX_FILES: usize = 4;
+
+/// PcapBuilder can be used to configure Pcap Interceptor.
+///
+/// The interceptors it builds share the capture file, which is created with the first one.
+#[derive(Default)]
+pub struct PcapBuilder {
+    path: Option<PathBuf>,
+    max_file_size: Option<u64>,
+    max_files: Option<usize>,
+    max_file_duration: Option<Duration>,
+    file: util::sync::Mutex<Option<Arc<CaptureFile>>>,
+}
+
+impl PcapBuilder {
+    /// with_path sets the path of the capture file.
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> PcapBuilder {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// with_max_file_size sets the size the capture file is rotated at, in bytes.
+    pub fn with_max_file_size(mut self, max_file_size: u64) -> PcapBuilder {
+        self.max_file_size = Some(max_file_size);
+        self
+    }
+
+    /// with_max_file_duration sets the duration the capture file is rotated after, whatever its
+    /// size. The files are only rotated by size by default.
+    pub fn with_max_file_duration(mut self, max_file_duration: Duration) -> PcapBuilder {
+        self.max_file_duration = Some(max_file_duration);
+        self
+    }
+
+    /// with_max_files sets the number of capture files kept, the current one included. The
+    /// rotated files are numbered before their extension, from the most recent one:
+    /// `webrtc.1.pcapng`, `webrtc.2.pcapng`...
+    pub fn with_max_files(mut self, max_files: usize) -> PcapBuilder {
+        self.max_files = Some(max_files);
+        self
+    }
+}
+
+impl InterceptorBuilder for PcapBuilder {
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        let mut file = self.file.lock();
+        let file = match file.as_ref() {
+            Some(file) => Arc::clone(file),
+            None => {
+                let created = Arc::new(CaptureFile::create(
+                    self.path
+                        .clone()
+                        .unwrap_or_else(|| PathBuf::from(DEFAULT_PATH)),
+                    self.max_file_size.unwrap_or(DEFAULT_MAX_FILE_SIZE),
+                    self.max_files.unwrap_or(DEFAULT_MAX_FILES),
+                    self.max_file_duration,
+                )?);
+                *file = Some(Arc::clone(&created));
+                created
+            }
+        };
+
+        Ok(Arc::new(Pcap { file }))
+    }
+}
+
+pub struct PcapRtcpReader {
+    parent_rtcp_reader: Arc<dyn RTCPReader + Send + Sync>,
+    file: Arc<CaptureFile>,
+}
+
+#[async_trait]
+impl RTCPReader for PcapRtcpReader {
+    async fn read(
+        &self,
+        buf: &mut [u8],
+        a: &Attributes,
+    ) -> Result<(Vec<Box<dyn rtcp::packet::Packet + Send + Sync>>, Attributes)> {
+        let (pkts, attr) = self.parent_rtcp_reader.read(buf, a).await?;
+        capture_rtcp(&self.file, Direction::Incoming, &pkts);
+        Ok((pkts, attr))
+    }
+}
+
+pub struct PcapRtcpWriter {
+    next_rtcp_writer: Arc<dyn RTCPWriter + Send + Sync>,
+    file: Arc<CaptureFile>,
+}
+
+#[async_trait]
+impl RTCPWriter for PcapRtcpWriter {
+    async fn write(
+        &self,
+        pkts: &[Box<dyn rtcp::packet::Packet + Send + Sync>],
+        a: &Attributes,
+    ) -> Result<usize> {
+        capture_rtcp(&self.file, Direction::Outgoing, pkts);
+        self.next_rtcp_writer.write(pkts, a).await
+    }
+}
+
+/// capture_rtcp captures the compound packet of pkts, commented with their media SSRCs.
+fn capture_rtcp(
+    file: &CaptureFile,
+    direction: Direction,
+    pkts: &[Box<dyn rtcp::packet::Packet + Send + Sync>],
+) {
+    let raw = match rtcp::packet::marshal(pkts) {
+        Ok(raw) => raw,
+        Err(err) => {
+            log::warn!("failed capturing RTCP packets: {}", err);
+            return;
+        }
+    };
+
+    let mut ssrcs: Vec<String> = vec![];
+    for ssrc in pkts.iter().flat_map(|p| p.destination_ssrc()) {
+        let ssrc = ssrc.to_string();
+        if !ssrcs.contains(&ssrc) {
+            ssrcs.push(ssrc);
+        }
+    }
+    file.write(direction, &raw, &format!("RTCP ssrc={}", ssrcs.join(",")));
+}
+
+/// Pcap interceptor captures the RTP and RTCP packets sent and received, before SRTP, to a
+/// pcapng file for debugging. The packets are captured in UDP datagrams between the fake
+/// addresses 192.0.2.1 (local) and 192.0.2.2 (remote), on port 5004, and commented with the
+/// SSRC and MID of their stream.
+///
+/// It should be added first, so that it captures the packets as they are sent and received.
+pub struct Pcap {
+    file: Arc<CaptureFile>,
+}
+
+impl Pcap {
+    /// builder returns a new PcapBuilder.
+    pub fn builder() -> PcapBuilder {
+        PcapBuilder::default()
+    }
+}
+
+#[async_trait]
+impl Interceptor for Pcap {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        Arc::new(PcapRtcpReader {
+            parent_rtcp_reader: reader,
+            file: Arc::clone(&self.file),
+        })
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        Arc::new(PcapRtcpWriter {
+            next_rtcp_writer: writer,
+            file: Arc::clone(&self.file),
+        })
+    }
+
+    /// bind_local_stream lets you modify any outgoing RTP packets. It is called once for per LocalStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_local_stream(
+        &self,
+        info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        Arc::new(PcapRtpWriter {
+            stream: PcapStream::new(Arc::clone(&self.file), Direction::Outgoing, info),
+            next_rtp_writer: writer,
+        })
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, _info: &StreamInfo) {}
+
+    /// bind_remote_stream lets you modify any incoming RTP packets. It is called once for per RemoteStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_remote_stream(
+        &self,
+        info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        Arc::new(PcapRtpReader {
+            stream: PcapStream::new(Arc::clone(&self.file), Direction::Incoming, info),
+            parent_rtp_reader: reader,
+        })
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, _info: &StreamInfo) {}
+
+    /// close flushes the packets captured to the file.
+    async fn close(&self) -> Result<()> {
+        self.file.flush()
+    }
+}
@@ -0,0 +1,92 @@
+use super::*;
+
+pub(super) const SDES_MID_URI: &str = "urn:ietf:params:rtp-hdrext:sdes:mid";
+
+/// PcapStream captures the RTP packets of a stream, commented with its SSRC and MID. The MID
+/// is known from the first packet carrying it in the sdes:mid header extension, if negotiated.
+pub(super) struct PcapStream {
+    file: Arc<CaptureFile>,
+    direction: Direction,
+    ssrc: u32,
+    mid_hdr_ext_id: u8,
+    mid: util::sync::Mutex<String>,
+}
+
+impl PcapStream {
+    pub(super) fn new(file: Arc<CaptureFile>, direction: Direction, info: &StreamInfo) -> Self {
+        let mid_hdr_ext_id = info
+            .rtp_header_extensions
+            .iter()
+            .find(|e| e.uri == SDES_MID_URI)
+            .map(|e| e.id as u8)
+            .unwrap_or_default();
+
+        PcapStream {
+            file,
+            direction,
+            ssrc: info.ssrc,
+            mid_hdr_ext_id,
+            mid: util::sync::Mutex::new(String::new()),
+        }
+    }
+
+    fn capture(&self, pkt: &rtp::packet::Packet) {
+        let raw = match pkt.marshal() {
+            Ok(raw) => raw,
+            Err(err) => {
+                log::warn!("failed capturing RTP packet: {}", err);
+                return;
+            }
+        };
+
+        let comment = {
+            let mut mid = self.mid.lock();
+            if self.mid_hdr_ext_id != 0 {
+                if let Some(ext) = pkt.header.get_extension(self.mid_hdr_ext_id) {
+                    *mid = String::from_utf8_lossy(&ext).into_owned();
+                }
+            }
+            if mid.is_empty() {
+                format!("RTP ssrc={}", self.ssrc)
+            } else {
+                format!("RTP ssrc={} mid={}", self.ssrc, *mid)
+            }
+        };
+        self.file.write(self.direction, &raw, &comment);
+    }
+}
+
+pub(super) struct PcapRtpWriter {
+    pub(super) stream: PcapStream,
+    pub(super) next_rtp_writer: Arc<dyn RTPWriter + Send + Sync>,
+}
+
+/// RTPWriter is used by Interceptor.bind_local_stream.
+#[async_trait]
+impl RTPWriter for PcapRtpWriter {
+    /// write a rtp packet
+    async fn write(&self, pkt: &rtp::packet::Packet, a: &Attributes) -> Result<usize> {
+        self.stream.capture(pkt);
+        self.next_rtp_writer.write(pkt, a).await
+    }
+}
+
+pub(super) struct PcapRtpReader {
+    pub(super) stream: PcapStream,
+    pub(super) parent_rtp_reader: Arc<dyn RTPReader + Send + Sync>,
+}
+
+/// RTPReader is used by Interceptor.bind_remote_stream.
+#[async_trait]
+impl RTPReader for PcapRtpReader {
+    /// read a rtp packet
+    async fn read(
+        &self,
+        buf: &mut [u8],
+        a: &Attributes,
+    ) -> Result<(rtp::packet::Packet, Attributes)> {
+        let (pkt, attr) = self.parent_rtp_reader.read(buf, a).await?;
+        self.stream.capture(&pkt);
+        Ok((pkt, attr))
+    }
+}
@@ -0,0 +1,361 @@
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bytes::Bytes;
+use rtcp::payload_feedbacks::picture_loss_indication::PictureLossIndication;
+
+use super::pcap_stream::SDES_MID_URI;
+use super::pcapng::PcapngWriter;
+use super::*;
+use crate::mock::mock_stream::MockStream;
+use crate::stream_info::RTPHeaderExtension;
+
+/// CapturedPacket is an enhanced packet block of a capture file.
+struct CapturedPacket {
+    timestamp: u64,
+    source: [u8; 4],
+    payload: Vec<u8>,
+    comment: String,
+}
+
+fn u16_le(b: &[u8]) -> u16 {
+    u16::from_le_bytes([b[0], b[1]])
+}
+
+fn u32_le(b: &[u8]) -> u32 {
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+/// read_capture returns the packets of the capture file at path, checking its headers.
+fn read_capture(path: &Path) -> Vec<CapturedPacket> {
+    let b = std::fs::read(path).expect("capture file should be readable");
+    assert_eq!(u32_le(&b[0..]), 0x0A0D_0D0A, "section header block");
+    assert_eq!(u32_le(&b[8..]), 0x1A2B_3C4D, "byte order magic");
+
+    let mut packets = vec![];
+    let mut offset = 0;
+    let mut interfaces = 0;
+    while offset < b.len() {
+        let (block_type, length) = (u32_le(&b[offset..]), u32_le(&b[offset + 4..]) as usize);
+        assert_eq!(u32_le(&b[offset + length - 4..]), length as u32);
+        let body = &b[offset + 8..offset + length - 4];
+        match block_type {
+            1 => {
+                assert_eq!(&body[0..2], &pcapng::LINKTYPE_IPV4.to_le_bytes());
+                interfaces += 1;
+            }
+            6 => {
+                let captured = u32_le(&body[12..]) as usize;
+                let data = &body[20..20 + captured];
+                assert_eq!(data[0], 0x45, "IPv4 header");
+                assert_eq!(data[9], 17, "UDP");
+
+                let mut comment = String::new();
+                let options = &body[20 + ((captured + 3) & !3)..];
+                if options.len() >= 4 && u16::from_le_bytes([options[0], options[1]]) == 1 {
+                    let len = u16::from_le_bytes([options[2], options[3]]) as usize;
+                    comment = String::from_utf8(options[4..4 + len].to_vec()).unwrap();
+                }
+                packets.push(CapturedPacket {
+                    timestamp: (u32_le(&body[4..]) as u64) << 32 | u32_le(&body[8..]) as u64,
+                    source: data[12..16].try_into().unwrap(),
+                    payload: data[28..].to_vec(),
+                    comment,
+                });
+            }
+            _ => {}
+        }
+        offset += length;
+    }
+    assert_eq!(interfaces, 1);
+    packets
+}
+
+fn capture_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("webrtc-{}-{}.pcapng", name, std::process::id()))
+}
+
+#[tokio::test]
+async fn test_pcap_interceptor() -> Result<()> {
+    let path = capture_path("pcap-interceptor");
+    let icpr = Pcap::builder().with_path(&path).build("")?;
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            rtp_header_extensions: vec![RTPHeaderExtension {
+                uri: SDES_MID_URI.to_owned(),
+                id: 4,
+            }],
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    let mut outgoing = rtp::packet::Packet {
+        header: rtp::header::Header {
+            version: 2,
+            ssrc: 1,
+            sequence_number: 7,
+            ..Default::default()
+        },
+        payload: Bytes::from_static(&[1, 2, 3]),
+        ..Default::default()
+    };
+    outgoing
+        .header
+        .set_extension(4, Bytes::from_static(b"video"))?;
+    stream.write_rtp(&outgoing).await?;
+    // the MID is remembered once seen
+    let mut without_mid = outgoing.clone();
+    without_mid.header.extension = false;
+    without_mid.header.extensions.clear();
+    stream.write_rtp(&without_mid).await?;
+
+    let incoming = rtp::packet::Packet {
+        header: rtp::header::Header {
+            version: 2,
+            ssrc: 1,
+            sequence_number: 9,
+            ..Default::default()
+        },
+        payload: Bytes::from_static(&[4, 5]),
+        ..Default::default()
+    };
+    stream.receive_rtp(incoming.clone()).await;
+    stream.read_rtp().await.expect("a packet")?;
+
+    let pli: Vec<Box<dyn rtcp::packet::Packet + Send + Sync>> =
+        vec![Box::new(PictureLossIndication {
+            sender_ssrc: 2,
+            media_ssrc: 1,
+        })];
+    stream.write_rtcp(&pli).await?;
+    stream.receive_rtcp(pli.clone()).await;
+    stream.read_rtcp().await.expect("a packet")?;
+
+    stream.close().await?;
+
+    let packets = read_capture(&path);
+    std::fs::remove_file(&path).ok();
+    assert_eq!(packets.len(), 5);
+
+    assert_eq!(packets[0].payload, outgoing.marshal()?);
+    assert_eq!(packets[0].source, [192, 0, 2, 1]);
+    assert_eq!(packets[0].comment, "RTP ssrc=1 mid=video");
+    assert_eq!(packets[1].payload, without_mid.marshal()?);
+    assert_eq!(packets[1].comment, "RTP ssrc=1 mid=video");
+
+    assert_eq!(packets[2].payload, incoming.marshal()?);
+    assert_eq!(packets[2].source, [192, 0, 2, 2]);
+    assert_eq!(packets[2].comment, "RTP ssrc=1");
+
+    let raw_pli = rtcp::packet::marshal(&pli)?;
+    for (pkt, source) in packets[3..].iter().zip([[192, 0, 2, 1], [192, 0, 2, 2]]) {
+        assert_eq!(pkt.payload, raw_pli);
+        assert_eq!(pkt.source, source);
+        assert_eq!(pkt.comment, "RTCP ssrc=1");
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pcap_interceptor_rotation() -> Result<()> {
+    let path = capture_path("pcap-rotation");
+    let rotated = [
+        capture_file::rotated_path(&path, 1),
+        capture_file::rotated_path(&path, 2),
+    ];
+    let icpr = Pcap::builder()
+        .with_path(&path)
+        .with_max_file_size(600)
+        .with_max_files(2)
+        .build("")?;
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    for sequence_number in 0..10 {
+        stream
+            .write_rtp(&rtp::packet::Packet {
+                header: rtp::header::Header {
+                    version: 2,
+                    ssrc: 1,
+                    sequence_number,
+                    ..Default::default()
+                },
+                payload: vec![0xAB; 100].into(),
+                ..Default::default()
+            })
+            .await?;
+    }
+    stream.close().await?;
+
+    let (current, previous) = (read_capture(&path), read_capture(&rotated[0]));
+    let sizes = [
+        std::fs::metadata(&path)
+            .map(|m| m.len())
+            .unwrap_or_default(),
+        std::fs::metadata(&rotated[0])
+            .map(|m| m.len())
+            .unwrap_or_default(),
+    ];
+    let oldest_removed = !rotated[1].exists();
+    for p in [&path, &rotated[0], &rotated[1]] {
+        std::fs::remove_file(p).ok();
+    }
+
+    assert!(sizes.iter().all(|&size| size <= 600), "sizes {sizes:?}");
+    assert!(oldest_removed, "only max_files files should be kept");
+    // the most recent packets are in the current file, following the previous one
+    let sequence_numbers: Vec<u16> = previous
+        .iter()
+        .chain(&current)
+        .map(|p| u16::from_be_bytes([p.payload[2], p.payload[3]]))
+        .collect();
+    assert_eq!(sequence_numbers.last(), Some(&9));
+    assert!(
+        sequence_numbers.windows(2).all(|w| w[1] == w[0] + 1),
+        "{sequence_numbers:?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_pcapng_writer() -> Result<()> {
+    let timestamp = UNIX_EPOCH + Duration::from_micros(0x1_2345_6789);
+    let mut b = vec![];
+    let (headers, written, packets) = {
+        let mut writer = PcapngWriter::new(&mut b, pcapng::LINKTYPE_IPV4)?;
+        let headers = writer.written();
+        writer.write_packet(timestamp, &[1, 2, 3, 4, 5], "a comment")?;
+        writer.write_packet(timestamp, &[6, 7, 8, 9], "")?;
+        (headers, writer.written(), writer.packets())
+    };
+    assert_eq!(written, b.len() as u64);
+    assert_eq!(packets, 2);
+
+    // section header block
+    assert_eq!(u32_le(&b[0..]), 0x0A0D_0D0A);
+    let length = u32_le(&b[4..]) as usize;
+    assert_eq!(u32_le(&b[length - 4..]), length as u32);
+    assert_eq!(u32_le(&b[8..]), 0x1A2B_3C4D, "byte order magic");
+    assert_eq!((u16_le(&b[12..]), u16_le(&b[14..])), (1, 0), "version");
+    assert_eq!(&b[16..24], &(-1i64).to_le_bytes(), "section length");
+    assert_eq!((u16_le(&b[24..]), u16_le(&b[26..])), (4, 9), "shb_userappl");
+    assert_eq!(&b[28..37], b"webrtc-rs");
+    assert_eq!(&b[37..40], &[0, 0, 0], "padding");
+    assert_eq!(u32_le(&b[40..]), 0, "opt_endofopt");
+    assert_eq!(length, 48);
+
+    // interface description block
+    let b = &b[length..];
+    assert_eq!(u32_le(&b[0..]), 1);
+    assert_eq!(u32_le(&b[4..]), 20);
+    assert_eq!(u16_le(&b[8..]), pcapng::LINKTYPE_IPV4);
+    assert_eq!(u16_le(&b[10..]), 0, "reserved");
+    assert_eq!(u32_le(&b[12..]), 0, "snap length");
+    assert_eq!(u32_le(&b[16..]), 20);
+    assert_eq!(headers, 48 + 20);
+
+    // enhanced packet block, with the comment after the padded data
+    let b = &b[20..];
+    let length = u32_le(&b[4..]) as usize;
+    assert_eq!(u32_le(&b[0..]), 6);
+    assert_eq!(
+        length as u64,
+        PcapngWriter::<Vec<u8>>::packet_size(&[1, 2, 3, 4, 5], "a comment")
+    );
+    assert_eq!(u32_le(&b[length - 4..]), length as u32);
+    assert_eq!(u32_le(&b[8..]), 0, "interface id");
+    assert_eq!((u32_le(&b[12..]), u32_le(&b[16..])), (1, 0x2345_6789));
+    assert_eq!((u32_le(&b[20..]), u32_le(&b[24..])), (5, 5), "lengths");
+    assert_eq!(&b[28..36], &[1, 2, 3, 4, 5, 0, 0, 0]);
+    assert_eq!((u16_le(&b[36..]), u16_le(&b[38..])), (1, 9), "opt_comment");
+    assert_eq!(&b[40..52], b"a comment\0\0\0");
+    assert_eq!(u32_le(&b[52..]), 0, "opt_endofopt");
+    assert_eq!(length, 60);
+
+    // and without any option
+    let b = &b[length..];
+    assert_eq!(u32_le(&b[0..]), 6);
+    assert_eq!(u32_le(&b[4..]), 36);
+    assert_eq!(PcapngWriter::<Vec<u8>>::packet_size(&[6, 7, 8, 9], ""), 36);
+    assert_eq!((u32_le(&b[20..]), u32_le(&b[24..])), (4, 4), "lengths");
+    assert_eq!(&b[28..32], &[6, 7, 8, 9]);
+    assert_eq!(u32_le(&b[32..]), 36);
+    assert_eq!(b.len(), 36);
+
+    Ok(())
+}
+
+#[test]
+fn test_capture_file_rotation() -> Result<()> {
+    let (payload, comment) = ([0xAB; 100], "RTP ssrc=1");
+    let headers = PcapngWriter::new(vec![], pcapng::LINKTYPE_IPV4)?.written();
+    // the packets are captured in their IPv4 and UDP headers
+    let packet_size = PcapngWriter::<Vec<u8>>::packet_size(&[0; 28 + 100], comment);
+
+    // by size, two packets a file
+    let path = capture_path("capture-file-size");
+    let file = CaptureFile::create(path.clone(), headers + 2 * packet_size, 3, None)?;
+    let start = SystemTime::now();
+    for i in 0..5 {
+        let now = start + Duration::from_secs(i * 3600);
+        file.write_at(now, Direction::Outgoing, &payload, comment);
+    }
+    file.flush()?;
+    assert_eq!(files_packets(&path), vec![1, 2, 2]);
+
+    // by time, whatever the size
+    let path = capture_path("capture-file-time");
+    let file = CaptureFile::create(path.clone(), u64::MAX, 3, Some(Duration::from_secs(1)))?;
+    let start = UNIX_EPOCH + Duration::from_secs(1_000_000);
+    for millis in [0, 500, 1000, 1500, 1900, 2000] {
+        let now = start + Duration::from_millis(millis);
+        file.write_at(now, Direction::Incoming, &payload, comment);
+    }
+    file.flush()?;
+    let timestamps: Vec<Vec<u64>> = [
+        capture_file::rotated_path(&path, 2),
+        capture_file::rotated_path(&path, 1),
+        path.clone(),
+    ]
+    .iter()
+    .map(|p| read_capture(p).iter().map(|p| p.timestamp).collect())
+    .collect();
+    assert_eq!(files_packets(&path), vec![1, 3, 2]);
+    // the files last from their first packet
+    let micros = |millis: u64| 1_000_000_000_000 + millis * 1000;
+    assert_eq!(
+        timestamps,
+        vec![
+            vec![micros(0), micros(500)],
+            vec![micros(1000), micros(1500), micros(1900)],
+            vec![micros(2000)],
+        ]
+    );
+
+    Ok(())
+}
+
+/// files_packets returns the number of packets of the capture file at path and of its rotated
+/// files, from the current one, and removes them.
+fn files_packets(path: &Path) -> Vec<usize> {
+    let mut counts = vec![read_capture(path).len()];
+    std::fs::remove_file(path).ok();
+    let mut i = 1;
+    while capture_file::rotated_path(path, i).exists() {
+        counts.push(read_capture(&capture_file::rotated_path(path, i)).len());
+        std::fs::remove_file(capture_file::rotated_path(path, i)).ok();
+        i += 1;
+    }
+    counts
+}
@@ -0,0 +1,145 @@
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bytes::{BufMut, BytesMut};
+
+use crate::error::Result;
+
+/// Link type of the packets which start with their IPv4 header.
+pub const LINKTYPE_IPV4: u16 = 228;
+
+const BLOCK_TYPE_SECTION_HEADER: u32 = 0x0A0D_0D0A;
+const BLOCK_TYPE_INTERFACE_DESCRIPTION: u32 = 0x0000_0001;
+const BLOCK_TYPE_ENHANCED_PACKET: u32 = 0x0000_0006;
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+
+const OPTION_END_OF_OPT: u16 = 0;
+const OPTION_COMMENT: u16 = 1;
+const OPTION_SHB_USER_APPL: u16 = 4;
+
+const USER_APPLICATION: &str = "webrtc-rs";
+
+/// PcapngWriter writes packets in the pcapng format, in a single section captured on a single
+/// interface, with timestamps in microseconds.
+///
+/// ## Specifications
+///
+/// * [draft-ietf-opsawg-pcapng]
+///
+/// [draft-ietf-opsawg-pcapng]: https://datatracker.ietf.org/doc/html/draft-ietf-opsawg-pcapng
+pub struct PcapngWriter<W: Write> {
+    writer: W,
+    written: u64,
+    packets: u64,
+}
+
+impl<W: Write> PcapngWriter<W> {
+    /// new writes the section header and the interface description of link_type to writer,
+    /// and returns a PcapngWriter writing the packets after them.
+    pub fn new(writer: W, link_type: u16) -> Result<Self> {
+        let mut w = PcapngWriter {
+            writer,
+            written: 0,
+            packets: 0,
+        };
+
+        let mut shb = BytesMut::new();
+        shb.put_u32_le(BYTE_ORDER_MAGIC);
+        shb.put_u16_le(1); // major version
+        shb.put_u16_le(0); // minor version
+        shb.put_i64_le(-1); // section length, unspecified
+        put_option(&mut shb, OPTION_SHB_USER_APPL, USER_APPLICATION.as_bytes());
+        put_option(&mut shb, OPTION_END_OF_OPT, &[]);
+        w.write_block(BLOCK_TYPE_SECTION_HEADER, &shb)?;
+
+        let mut idb = BytesMut::new();
+        idb.put_u16_le(link_type);
+        idb.put_u16_le(0); // reserved
+        idb.put_u32_le(0); // snap length, unlimited
+        w.write_block(BLOCK_TYPE_INTERFACE_DESCRIPTION, &idb)?;
+
+        Ok(w)
+    }
+
+    /// write_packet writes data captured at timestamp, with comment unless it is empty.
+    pub fn write_packet(
+        &mut self,
+        timestamp: SystemTime,
+        data: &[u8],
+        comment: &str,
+    ) -> Result<()> {
+        let micros = timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64;
+
+        let mut epb = BytesMut::with_capacity(32 + data.len() + comment.len());
+        epb.put_u32_le(0); // interface id
+        epb.put_u32_le((micros >> 32) as u32);
+        epb.put_u32_le(micros as u32);
+        epb.put_u32_le(data.len() as u32); // captured length
+        epb.put_u32_le(data.len() as u32); // original length
+        put_padded(&mut epb, data);
+        if !comment.is_empty() {
+            put_option(&mut epb, OPTION_COMMENT, comment.as_bytes());
+            put_option(&mut epb, OPTION_END_OF_OPT, &[]);
+        }
+        self.write_block(BLOCK_TYPE_ENHANCED_PACKET, &epb)?;
+        self.packets += 1;
+        Ok(())
+    }
+
+    /// packet_size returns the number of bytes write_packet writes for data and comment.
+    pub fn packet_size(data: &[u8], comment: &str) -> u64 {
+        let options = if comment.is_empty() {
+            0
+        } else {
+            4 + padded_len(comment.len()) + 4
+        };
+        (12 + 20 + padded_len(data.len()) + options) as u64
+    }
+
+    /// written returns the number of bytes written, headers included.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// packets returns the number of packets written.
+    pub fn packets(&self) -> u64 {
+        self.packets
+    }
+
+    /// flush flushes the underlying writer.
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush().map_err(util::Error::from)?;
+        Ok(())
+    }
+
+    fn write_block(&mut self, block_type: u32, body: &[u8]) -> Result<()> {
+        let total_length = (12 + body.len()) as u32;
+        let mut block = BytesMut::with_capacity(total_length as usize);
+        block.put_u32_le(block_type);
+        block.put_u32_le(total_length);
+        block.put_slice(body);
+        block.put_u32_le(total_length);
+
+        self.writer.write_all(&block).map_err(util::Error::from)?;
+        self.written += block.len() as u64;
+        Ok(())
+    }
+}
+
+fn padded_len(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+fn put_padded(buf: &mut BytesMut, value: &[u8]) {
+    buf.put_slice(value);
+    buf.put_bytes(0, padded_len(value.len()) - value.len());
+}
+
+fn put_option(buf: &mut BytesMut, code: u16, value: &[u8]) {
+    buf.put_u16_le(code);
+    buf.put_u16_le(value.len() as u16);
+    put_padded(buf, value);
+}
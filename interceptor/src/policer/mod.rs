@@ -0,0 +1,321 @@
+mod policer_stream;
+#[cfg(test)]
+mod policer_test;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use policer_stream::PolicerStream;
+use tokio::time::Instant;
+
+use crate::error::Result;
+use crate::stream_info::StreamInfo;
+use crate::{
+    Attributes, Interceptor, InterceptorBuilder, RTCPReader, RTCPWriter, RTPReader, RTPWriter,
+};
+
+/// Attribute of the packets read over the limits, when the Policer flags them instead of
+/// dropping them.
+pub const ATTR_POLICED: usize = 0x9011CE;
+
+/// How long a stream can be sent above its limit by default, at the limit again after the
+/// burst.
+const DEFAULT_BURST: Duration = Duration::from_millis(500);
+
+/// PolicerAction is what a Policer does with the packets read over the limits.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum PolicerAction {
+    /// The packets are dropped, never read.
+    #[default]
+    Drop,
+    /// The packets are read with the [`ATTR_POLICED`] attribute.
+    Flag,
+}
+
+/// PolicerStats are the packets of a remote stream read over the limits.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct PolicerStats {
+    pub packets_policed: u64,
+    pub bytes_policed: u64,
+}
+
+/// TokenBucket meters a rate, in bytes, with a burst of its bitrate over the burst duration.
+#[derive(Debug, Copy, Clone)]
+struct TokenBucket {
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    fn new(bitrate: u64, burst: Duration, now: Instant) -> Self {
+        TokenBucket {
+            tokens: Self::capacity(bitrate, burst),
+            last: now,
+        }
+    }
+
+    fn capacity(bitrate: u64, burst: Duration) -> f64 {
+        bitrate as f64 / 8.0 * burst.as_secs_f64()
+    }
+
+    /// refill adds the tokens of the bitrate since the bucket was last refilled, returning
+    /// whether it has size of them.
+    fn refill(&mut self, bitrate: u64, burst: Duration, size: usize, now: Instant) -> bool {
+        let elapsed = now.duration_since(self.last).as_secs_f64();
+        self.tokens =
+            (self.tokens + bitrate as f64 / 8.0 * elapsed).min(Self::capacity(bitrate, burst));
+        self.last = now;
+        self.tokens >= size as f64
+    }
+}
+
+#[derive(Default)]
+struct PolicerState {
+    ssrc_limits: HashMap<u32, u64>,
+    mid_limits: HashMap<String, u64>,
+    ssrc_buckets: HashMap<u32, TokenBucket>,
+    mid_buckets: HashMap<String, TokenBucket>,
+    /// MID of each remote stream, once read.
+    mids: HashMap<u32, String>,
+    stats: HashMap<u32, PolicerStats>,
+}
+
+/// PolicerLimits sets the bitrate limits of the remote streams of a Policer, and reports the
+/// packets policed.
+#[derive(Default)]
+pub struct PolicerLimits {
+    state: util::sync::Mutex<PolicerState>,
+}
+
+impl PolicerLimits {
+    /// set_ssrc_limit sets the limit, in bits per second, of the remote stream ssrc, instead
+    /// of the default limit.
+    pub fn set_ssrc_limit(&self, ssrc: u32, bitrate: u64) {
+        let mut state = self.state.lock();
+        state.ssrc_limits.insert(ssrc, bitrate);
+    }
+
+    /// remove_ssrc_limit removes the limit of the remote stream ssrc, which the default limit
+    /// applies to again.
+    pub fn remove_ssrc_limit(&self, ssrc: u32) {
+        let mut state = self.state.lock();
+        state.ssrc_limits.remove(&ssrc);
+    }
+
+    /// set_mid_limit sets the limit, in bits per second, of the remote streams of the media
+    /// mid together, e.g. of all the simulcast layers of a publisher. The MID of a stream is
+    /// known from the sdes:mid header extension of its packets, if negotiated.
+    pub fn set_mid_limit(&self, mid: &str, bitrate: u64) {
+        let mut state = self.state.lock();
+        state.mid_limits.insert(mid.to_owned(), bitrate);
+    }
+
+    /// remove_mid_limit removes the limit of the remote streams of the media mid.
+    pub fn remove_mid_limit(&self, mid: &str) {
+        let mut state = self.state.lock();
+        state.mid_limits.remove(mid);
+        state.mid_buckets.remove(mid);
+    }
+
+    /// stats returns the packets policed of the remote stream ssrc.
+    pub fn stats(&self, ssrc: u32) -> PolicerStats {
+        let state = self.state.lock();
+        state.stats.get(&ssrc).copied().unwrap_or_default()
+    }
+
+    fn set_mid(&self, ssrc: u32, mid: String) {
+        let mut state = self.state.lock();
+        state.mids.insert(ssrc, mid);
+    }
+
+    /// conform returns whether a packet of size bytes of ssrc read at now is within the
+    /// limits of the stream and of its MID, metering it if so, and counting it as policed
+    /// otherwise.
+    fn conform(
+        &self,
+        ssrc: u32,
+        size: usize,
+        default_limit: Option<u64>,
+        burst: Duration,
+        now: Instant,
+    ) -> bool {
+        let mut state = self.state.lock();
+        let state = &mut *state;
+
+        let ssrc_limit = state.ssrc_limits.get(&ssrc).copied().or(default_limit);
+        let mid_limit = state
+            .mids
+            .get(&ssrc)
+            .and_then(|mid| Some((mid.clone(), *state.mid_limits.get(mid)?)));
+
+        let mut conform = true;
+        if let Some(bitrate) = ssrc_limit {
+            let bucket = state
+                .ssrc_buckets
+                .entry(ssrc)
+                .or_insert_with(|| TokenBucket::new(bitrate, burst, now));
+            conform &= bucket.refill(bitrate, burst, size, now);
+        }
+        if let Some((mid, bitrate)) = &mid_limit {
+            let bucket = state
+                .mid_buckets
+                .entry(mid.clone())
+                .or_insert_with(|| TokenBucket::new(*bitrate, burst, now));
+            conform &= bucket.refill(*bitrate, burst, size, now);
+        }
+
+        if !conform {
+            let stats = state.stats.entry(ssrc).or_default();
+            stats.packets_policed += 1;
+            stats.bytes_policed += size as u64;
+            return false;
+        }
+        if ssrc_limit.is_some() {
+            if let Some(bucket) = state.ssrc_buckets.get_mut(&ssrc) {
+                bucket.tokens -= size as f64;
+            }
+        }
+        if let Some((mid, _)) = &mid_limit {
+            if let Some(bucket) = state.mid_buckets.get_mut(mid) {
+                bucket.tokens -= size as f64;
+            }
+        }
+        true
+    }
+
+    fn remove(&self, ssrc: u32) {
+        let mut state = self.state.lock();
+        state.ssrc_buckets.remove(&ssrc);
+        state.mids.remove(&ssrc);
+        state.stats.remove(&ssrc);
+    }
+}
+
+/// PolicerBuilder can be used to configure Policer Interceptor
+#[derive(Default)]
+pub struct PolicerBuilder {
+    default_limit: Option<u64>,
+    burst: Option<Duration>,
+    action: PolicerAction,
+    limits: Arc<PolicerLimits>,
+}
+
+impl PolicerBuilder {
+    /// with_default_limit sets the limit, in bits per second, of each remote stream without
+    /// a limit of its own. The streams are only limited by their own limits and the limits of
+    /// their MID by default.
+    pub fn with_default_limit(mut self, bitrate: u64) -> PolicerBuilder {
+        self.default_limit = Some(bitrate);
+        self
+    }
+
+    /// with_burst sets how long a stream can be sent above its limit, e.g. for a keyframe,
+    /// before its packets are policed.
+    pub fn with_burst(mut self, burst: Duration) -> PolicerBuilder {
+        self.burst = Some(burst);
+        self
+    }
+
+    /// with_action sets what is done with the packets over the limits.
+    pub fn with_action(mut self, action: PolicerAction) -> PolicerBuilder {
+        self.action = action;
+        self
+    }
+
+    /// limits returns the limits of the remote streams of the interceptors built, which can be
+    /// set before or after they are built.
+    pub fn limits(&self) -> Arc<PolicerLimits> {
+        Arc::clone(&self.limits)
+    }
+}
+
+impl InterceptorBuilder for PolicerBuilder {
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        Ok(Arc::new(Policer {
+            default_limit: self.default_limit,
+            burst: self.burst.unwrap_or(DEFAULT_BURST),
+            action: self.action,
+            limits: Arc::clone(&self.limits),
+        }))
+    }
+}
+
+/// Policer interceptor enforces bitrate limits on the incoming streams, per SSRC and per MID,
+/// dropping or flagging the packets over them, as an SFU protects itself and its subscribers
+/// from the publishers sending too much. The packets, headers included, are metered with a
+/// token bucket the size of the limit over the burst duration.
+pub struct Policer {
+    default_limit: Option<u64>,
+    burst: Duration,
+    action: PolicerAction,
+    limits: Arc<PolicerLimits>,
+}
+
+impl Policer {
+    /// builder returns a new PolicerBuilder.
+    pub fn builder() -> PolicerBuilder {
+        PolicerBuilder::default()
+    }
+}
+
+#[async_trait]
+impl Interceptor for Policer {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        reader
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        writer
+    }
+
+    /// bind_local_stream lets you modify any outgoing RTP packets. It is called once for per LocalStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_local_stream(
+        &self,
+        _info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        writer
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, _info: &StreamInfo) {}
+
+    /// bind_remote_stream returns a reader which polices the packets read over the limits.
+    async fn bind_remote_stream(
+        &self,
+        info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        Arc::new(PolicerStream::new(
+            info,
+            self.default_limit,
+            self.burst,
+            self.action,
+            Arc::clone(&self.limits),
+            reader,
+        ))
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, info: &StreamInfo) {
+        self.limits.remove(info.ssrc);
+    }
+
+    /// close closes the Interceptor, cleaning up any data if necessary.
+    async fn close(&self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use util::MarshalSize;
+
+use super::*;
+
+const SDES_MID_URI: &str = "urn:ietf:params:rtp-hdrext:sdes:mid";
+
+pub(super) struct PolicerStream {
+    ssrc: u32,
+    mid_hdr_ext_id: u8,
+    default_limit: Option<u64>,
+    burst: Duration,
+    action: PolicerAction,
+    limits: Arc<PolicerLimits>,
+    parent_rtp_reader: Arc<dyn RTPReader + Send + Sync>,
+}
+
+impl PolicerStream {
+    pub(super) fn new(
+        info: &StreamInfo,
+        default_limit: Option<u64>,
+        burst: Duration,
+        action: PolicerAction,
+        limits: Arc<PolicerLimits>,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Self {
+        let mid_hdr_ext_id = info
+            .rtp_header_extensions
+            .iter()
+            .find(|e| e.uri == SDES_MID_URI)
+            .map(|e| e.id as u8)
+            .unwrap_or_default();
+
+        PolicerStream {
+            ssrc: info.ssrc,
+            mid_hdr_ext_id,
+            default_limit,
+            burst,
+            action,
+            limits,
+            parent_rtp_reader: reader,
+        }
+    }
+}
+
+/// RTPReader is used by Interceptor.bind_remote_stream.
+#[async_trait]
+impl RTPReader for PolicerStream {
+    /// read returns the next packet within the limits, or flagged if over them.
+    async fn read(
+        &self,
+        buf: &mut [u8],
+        a: &Attributes,
+    ) -> Result<(rtp::packet::Packet, Attributes)> {
+        loop {
+            let (pkt, mut attr) = self.parent_rtp_reader.read(buf, a).await?;
+
+            if self.mid_hdr_ext_id != 0 {
+                if let Some(ext) = pkt.header.get_extension(self.mid_hdr_ext_id) {
+                    self.limits
+                        .set_mid(self.ssrc, String::from_utf8_lossy(&ext).into_owned());
+                }
+            }
+
+            if self.limits.conform(
+                self.ssrc,
+                pkt.marshal_size(),
+                self.default_limit,
+                self.burst,
+                Instant::now(),
+            ) {
+                return Ok((pkt, attr));
+            }
+            match self.action {
+                PolicerAction::Drop => continue,
+                PolicerAction::Flag => {
+                    attr.insert(ATTR_POLICED, 1);
+                    return Ok((pkt, attr));
+                }
+            }
+        }
+    }
+}
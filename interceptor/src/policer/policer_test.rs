@@ -0,0 +1,189 @@
+use std::collections::VecDeque;
+
+use bytes::Bytes;
+
+use super::*;
+use crate::error::Error;
+use crate::mock::mock_stream::MockStream;
+use crate::stream_info::RTPHeaderExtension;
+use crate::test::timeout_or_fail;
+
+/// packet returns a packet of 100 bytes of ssrc.
+fn packet(ssrc: u32, sequence_number: u16) -> rtp::packet::Packet {
+    rtp::packet::Packet {
+        header: rtp::header::Header {
+            ssrc,
+            sequence_number,
+            ..Default::default()
+        },
+        payload: Bytes::from(vec![0; 88]),
+        ..Default::default()
+    }
+}
+
+async fn read_sequence_numbers(stream: &MockStream, n: usize) -> Vec<u16> {
+    let mut read = vec![];
+    for _ in 0..n {
+        let pkt = timeout_or_fail(Duration::from_millis(10), stream.read_rtp())
+            .await
+            .expect("A read packet")
+            .expect("Not an error");
+        read.push(pkt.header.sequence_number);
+    }
+    read
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_policer_interceptor() -> Result<()> {
+    // 1000 bytes per second, with a burst of 1000 bytes
+    let builder = Policer::builder()
+        .with_default_limit(8_000)
+        .with_burst(Duration::from_secs(1));
+    let limits = builder.limits();
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            ..Default::default()
+        },
+        builder.build("")?,
+    )
+    .await;
+
+    for seq in 0..12 {
+        stream.receive_rtp(packet(1, seq)).await;
+    }
+    assert_eq!(
+        read_sequence_numbers(&stream, 10).await,
+        (0..10).collect::<Vec<u16>>()
+    );
+
+    // the packets over the burst are dropped
+    tokio::time::advance(Duration::from_millis(500)).await;
+    for seq in 12..18 {
+        stream.receive_rtp(packet(1, seq)).await;
+    }
+    assert_eq!(
+        read_sequence_numbers(&stream, 5).await,
+        (12..17).collect::<Vec<u16>>()
+    );
+
+    // a limit of its own
+    limits.set_ssrc_limit(1, 80_000);
+    tokio::time::advance(Duration::from_millis(100)).await;
+    for seq in 18..28 {
+        stream.receive_rtp(packet(1, seq)).await;
+    }
+    assert_eq!(
+        read_sequence_numbers(&stream, 10).await,
+        (18..28).collect::<Vec<u16>>()
+    );
+    assert_eq!(
+        limits.stats(1),
+        PolicerStats {
+            packets_policed: 3,
+            bytes_policed: 300,
+        },
+        "10, 11 and 17 dropped"
+    );
+
+    stream.close().await?;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_policer_interceptor_mid_limit() -> Result<()> {
+    let builder = Policer::builder().with_burst(Duration::from_secs(1));
+    let limits = builder.limits();
+    limits.set_mid_limit("0", 8_000);
+    let icpr = builder.build("")?;
+    let info = |ssrc| StreamInfo {
+        ssrc,
+        rtp_header_extensions: vec![RTPHeaderExtension {
+            uri: "urn:ietf:params:rtp-hdrext:sdes:mid".to_owned(),
+            id: 4,
+        }],
+        ..Default::default()
+    };
+    let streams = [
+        MockStream::new(&info(1), Arc::clone(&icpr)).await,
+        MockStream::new(&info(2), Arc::clone(&icpr)).await,
+    ];
+
+    // the streams of the MID share its limit, once their MID is read
+    for (i, stream) in streams.iter().enumerate() {
+        let ssrc = i as u32 + 1;
+        let mut first = packet(ssrc, 0);
+        first.header.set_extension(4, Bytes::from_static(b"0"))?;
+        stream.receive_rtp(first).await;
+        for seq in 1..6 {
+            stream.receive_rtp(packet(ssrc, seq)).await;
+        }
+        // 608 bytes, then 308 of the 392 left
+        let read = if ssrc == 1 { 6 } else { 3 };
+        assert_eq!(
+            read_sequence_numbers(stream, read).await,
+            (0..read as u16).collect::<Vec<u16>>()
+        );
+    }
+
+    tokio::time::advance(Duration::from_millis(100)).await;
+    streams[1].receive_rtp(packet(2, 6)).await;
+    assert_eq!(read_sequence_numbers(&streams[1], 1).await, vec![6]);
+    assert_eq!(limits.stats(1).packets_policed, 0);
+    assert_eq!(limits.stats(2).packets_policed, 3);
+
+    for stream in streams {
+        stream.close().await?;
+    }
+    Ok(())
+}
+
+/// PacketsReader reads the packets it has, then fails.
+struct PacketsReader(util::sync::Mutex<VecDeque<rtp::packet::Packet>>);
+
+#[async_trait]
+impl RTPReader for PacketsReader {
+    async fn read(
+        &self,
+        _buf: &mut [u8],
+        a: &Attributes,
+    ) -> Result<(rtp::packet::Packet, Attributes)> {
+        let mut packets = self.0.lock();
+        match packets.pop_front() {
+            Some(pkt) => Ok((pkt, a.clone())),
+            None => Err(Error::ErrIoEOF),
+        }
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_policer_stream_flag() -> Result<()> {
+    let limits = Arc::new(PolicerLimits::default());
+    let stream = PolicerStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            ..Default::default()
+        },
+        Some(8_000),
+        Duration::from_millis(200),
+        PolicerAction::Flag,
+        Arc::clone(&limits),
+        Arc::new(PacketsReader(util::sync::Mutex::new(
+            (0..4).map(|seq| packet(1, seq)).collect(),
+        ))),
+    );
+
+    let mut buf = vec![0u8; 1500];
+    let mut policed = vec![];
+    for _ in 0..4 {
+        let (_, attr) = stream.read(&mut buf, &Attributes::new()).await?;
+        policed.push(attr.get(&ATTR_POLICED).copied());
+    }
+    assert_eq!(policed, vec![None, None, Some(1), Some(1)]);
+    assert_eq!(limits.stats(1).packets_policed, 2);
+    assert_eq!(
+        stream.read(&mut buf, &Attributes::new()).await,
+        Err(Error::ErrIoEOF)
+    );
+    Ok(())
+}
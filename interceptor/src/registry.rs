@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use crate::chain::Chain;
 use crate::error::Result;
+use crate::managed::{ManagedChainBuilder, ManagedChainHandle};
 use crate::noop::NoOp;
 use crate::{Interceptor, InterceptorBuilder};
 
@@ -21,6 +22,16 @@ impl Registry {
         self.builders.push(builder);
     }
 
+    /// add_managed adds a new ManagedChainBuilder to the registry, returning the handle which
+    /// adds, removes and moves its interceptors, in the interceptors built from the registry
+    /// too once they're in use.
+    pub fn add_managed(&mut self) -> Arc<ManagedChainHandle> {
+        let builder = ManagedChainBuilder::default();
+        let handle = builder.handle();
+        self.builders.push(Box::new(builder));
+        handle
+    }
+
     /// build constructs a single Interceptor from an InterceptorRegistry
     pub fn build(&self, id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
         if self.builders.is_empty() {
@@ -0,0 +1,81 @@
+use super::*;
+
+const PACKET_SIZE: usize = 1200;
+
+/// us_to_abs_send_time returns the 24 bit abs-send-time of a send time in microseconds.
+fn us_to_abs_send_time(us: i64) -> u32 {
+    ((us << 18) / 1_000_000) as u32 & 0xFFFFFF
+}
+
+/// run sends PACKET_SIZE packets at send_bitrate for duration_us over a link of
+/// link_bitrate, and returns the lowest bitrate estimated meanwhile.
+fn run(estimator: &mut Estimator, send_bitrate: i64, link_bitrate: i64, duration_us: i64) -> u64 {
+    let send_interval_us = PACKET_SIZE as i64 * 8 * 1_000_000 / send_bitrate;
+    let link_interval_us = PACKET_SIZE as i64 * 8 * 1_000_000 / link_bitrate;
+
+    let mut lowest = estimator.bitrate();
+    let mut arrival_us = 0;
+    let mut send_us = 0;
+    while send_us < duration_us {
+        // packets queue up when the link is slower than the sender
+        arrival_us = (send_us + 20_000).max(arrival_us + link_interval_us);
+        let bitrate =
+            estimator.incoming_packet(arrival_us, us_to_abs_send_time(send_us), PACKET_SIZE);
+        lowest = lowest.min(bitrate);
+        send_us += send_interval_us;
+    }
+    lowest
+}
+
+#[test]
+fn test_estimator_increases_under_capacity() {
+    let mut estimator = Estimator::new(300_000, 10_000, 10_000_000);
+    run(&mut estimator, 500_000, 2_000_000, 10_000_000);
+
+    let bitrate = estimator.bitrate();
+    assert!(
+        bitrate > 500_000,
+        "estimate {bitrate} should have increased"
+    );
+    assert!(
+        bitrate <= 1_500_000 * 500_000 / 1_000_000 + 10_000,
+        "estimate {bitrate} shouldn't exceed much what is received"
+    );
+    assert_eq!(estimator.usage(), BandwidthUsage::Normal);
+}
+
+#[test]
+fn test_estimator_decreases_over_capacity() {
+    let mut estimator = Estimator::new(1_000_000, 10_000, 10_000_000);
+    run(&mut estimator, 1_000_000, 500_000, 5_000_000);
+
+    let bitrate = estimator.bitrate();
+    assert!(
+        bitrate < 500_000,
+        "estimate {bitrate} should be below the capacity of the link"
+    );
+}
+
+#[test]
+fn test_estimator_bounds() {
+    let mut estimator = Estimator::new(1_000_000, 50_000, 400_000);
+    assert_eq!(estimator.bitrate(), 400_000);
+
+    let lowest = run(&mut estimator, 1_000_000, 20_000, 5_000_000);
+    assert_eq!(lowest, 50_000);
+}
+
+#[test]
+fn test_estimator_send_time_wraps() {
+    let mut estimator = Estimator::new(300_000, 10_000, 10_000_000);
+    // abs-send-time wraps every 64 seconds
+    let start_us = 64_000_000 - 100_000;
+    for i in 0..20 {
+        let send_us = start_us + i * 10_000;
+        estimator.incoming_packet(send_us, us_to_abs_send_time(send_us), PACKET_SIZE);
+    }
+    assert_eq!(estimator.usage(), BandwidthUsage::Normal);
+    // abs-send-time has a resolution of about 4 microseconds.
+    let send_us = estimator.send_us.unwrap();
+    assert!((send_us - (start_us + 19 * 10_000)).abs() < 10, "{send_us}");
+}
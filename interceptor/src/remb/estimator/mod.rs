@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod estimator_test;
+
+use std::collections::VecDeque;
+
+pub use crate::gcc::trendline::BandwidthUsage;
+use crate::gcc::trendline::TrendlineDetector;
+
+/// Window the incoming bitrate is measured over.
+const RATE_WINDOW_US: i64 = 500_000;
+
+const DECREASE_FACTOR: f64 = 0.85;
+/// Multiplicative increase of the estimate per second, while the link isn't overused.
+const INCREASE_FACTOR_PER_SECOND: f64 = 1.08;
+
+/// Estimator estimates the bandwidth available to a sender from the delay variation of the
+/// packets received, as the receive-side estimator of Google congestion control.
+///
+/// Packets are grouped into bursts by the abs-send-time of their sender. A growing one way delay
+/// between the groups is a queue building up on the link, which makes the estimate decrease,
+/// else it increases up to a bit more than the bitrate received.
+///
+/// ## Specifications
+///
+/// * [draft-ietf-rmcat-gcc-02]
+///
+/// [draft-ietf-rmcat-gcc-02]: https://datatracker.ietf.org/doc/html/draft-ietf-rmcat-gcc-02
+#[derive(Debug, Clone)]
+pub struct Estimator {
+    min_bitrate: u64,
+    max_bitrate: u64,
+    bitrate: f64,
+
+    last_abs_send_time: u32,
+    send_us: Option<i64>,
+    detector: TrendlineDetector,
+    usage: BandwidthUsage,
+
+    received: VecDeque<(i64, usize)>,
+    received_bytes: usize,
+    last_update_us: Option<i64>,
+}
+
+impl Estimator {
+    /// new returns an estimator starting at initial_bitrate, and never estimating less than
+    /// min_bitrate nor more than max_bitrate, in bits per second.
+    pub fn new(initial_bitrate: u64, min_bitrate: u64, max_bitrate: u64) -> Self {
+        Estimator {
+            min_bitrate,
+            max_bitrate,
+            bitrate: initial_bitrate.clamp(min_bitrate, max_bitrate) as f64,
+
+            last_abs_send_time: 0,
+            send_us: None,
+            detector: TrendlineDetector::new(),
+            usage: BandwidthUsage::Normal,
+
+            received: VecDeque::new(),
+            received_bytes: 0,
+            last_update_us: None,
+        }
+    }
+
+    /// bitrate returns the bandwidth estimated, in bits per second.
+    pub fn bitrate(&self) -> u64 {
+        self.bitrate as u64
+    }
+
+    /// usage returns the state of the link last detected.
+    pub fn usage(&self) -> BandwidthUsage {
+        self.usage
+    }
+
+    /// incoming_bitrate returns the bitrate received over the last half second, in bits per
+    /// second.
+    pub fn incoming_bitrate(&self) -> u64 {
+        (self.received_bytes as i64 * 8 * 1_000_000 / RATE_WINDOW_US) as u64
+    }
+
+    /// incoming_packet records a packet of size bytes arriving at arrival_us microseconds,
+    /// with abs_send_time, the 24 bit abs-send-time header extension. It returns the bandwidth
+    /// estimated after it.
+    pub fn incoming_packet(&mut self, arrival_us: i64, abs_send_time: u32, size: usize) -> u64 {
+        self.received.push_back((arrival_us, size));
+        self.received_bytes += size;
+        while let Some((t, size)) = self.received.front().copied() {
+            if arrival_us - t < RATE_WINDOW_US {
+                break;
+            }
+            self.received.pop_front();
+            self.received_bytes -= size;
+        }
+
+        let send_us = self.unwrap_send_time(abs_send_time);
+        if let Some(usage) = self.detector.incoming_packet(send_us, arrival_us) {
+            self.usage = usage;
+        }
+
+        self.update_bitrate(arrival_us);
+        self.bitrate()
+    }
+
+    fn unwrap_send_time(&mut self, abs_send_time: u32) -> i64 {
+        let abs_send_time = abs_send_time & 0xFFFFFF;
+        let send_us = match self.send_us {
+            None => abs_send_time_to_us(abs_send_time as i64),
+            Some(send_us) => {
+                let mut diff =
+                    (abs_send_time.wrapping_sub(self.last_abs_send_time) & 0xFFFFFF) as i64;
+                if diff >= 0x800000 {
+                    diff -= 0x1000000;
+                }
+                send_us + abs_send_time_to_us(diff)
+            }
+        };
+        self.last_abs_send_time = abs_send_time;
+        self.send_us = Some(send_us);
+        send_us
+    }
+
+    fn update_bitrate(&mut self, now_us: i64) {
+        let elapsed_us = match self.last_update_us {
+            Some(last) => (now_us - last).clamp(0, 1_000_000),
+            None => 0,
+        };
+        self.last_update_us = Some(now_us);
+        let incoming_bitrate = self.incoming_bitrate() as f64;
+
+        match self.usage {
+            BandwidthUsage::Overusing => {
+                let decreased = if incoming_bitrate > 0.0 {
+                    DECREASE_FACTOR * incoming_bitrate
+                } else {
+                    DECREASE_FACTOR * self.bitrate
+                };
+                self.bitrate = self.bitrate.min(decreased);
+                // a single overuse decreases the estimate once
+                self.usage = BandwidthUsage::Normal;
+            }
+            // the queue drains, hold the estimate
+            BandwidthUsage::Underusing => {}
+            BandwidthUsage::Normal => {
+                let increased =
+                    self.bitrate * INCREASE_FACTOR_PER_SECOND.powf(elapsed_us as f64 / 1_000_000.0);
+                // don't increase far beyond what the sender actually sends
+                let cap = 1.5 * incoming_bitrate + 10_000.0;
+                self.bitrate = if increased > cap {
+                    self.bitrate.max(cap)
+                } else {
+                    increased
+                };
+            }
+        }
+
+        self.bitrate = self
+            .bitrate
+            .clamp(self.min_bitrate as f64, self.max_bitrate as f64);
+    }
+}
+
+fn abs_send_time_to_us(abs_send_time: i64) -> i64 {
+    // 6.18 fixed point seconds
+    abs_send_time * 1_000_000 / (1 << 18)
+}
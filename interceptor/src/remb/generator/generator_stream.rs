@@ -0,0 +1,49 @@
+use rtp::extension::abs_send_time_extension::AbsSendTimeExtension;
+use util::{MarshalSize, Unmarshal};
+
+use super::*;
+
+pub(super) struct GeneratorStream {
+    parent_rtp_reader: Arc<dyn RTPReader + Send + Sync>,
+    hdr_ext_id: u8,
+    internal: Arc<GeneratorInternal>,
+}
+
+impl GeneratorStream {
+    pub(super) fn new(
+        parent_rtp_reader: Arc<dyn RTPReader + Send + Sync>,
+        hdr_ext_id: u8,
+        internal: Arc<GeneratorInternal>,
+    ) -> Self {
+        GeneratorStream {
+            parent_rtp_reader,
+            hdr_ext_id,
+            internal,
+        }
+    }
+}
+
+/// RTPReader is used by Interceptor.bind_remote_stream.
+#[async_trait]
+impl RTPReader for GeneratorStream {
+    /// read a rtp packet
+    async fn read(
+        &self,
+        buf: &mut [u8],
+        a: &Attributes,
+    ) -> Result<(rtp::packet::Packet, Attributes)> {
+        let (pkt, attr) = self.parent_rtp_reader.read(buf, a).await?;
+
+        if let Some(mut ext) = pkt.header.get_extension(self.hdr_ext_id) {
+            let send_time = AbsSendTimeExtension::unmarshal(&mut ext)?;
+            let arrival_us = (tokio::time::Instant::now() - self.internal.start_time).as_micros();
+            self.internal.incoming_packet(
+                arrival_us as i64,
+                send_time.timestamp as u32,
+                pkt.header.marshal_size() + pkt.payload.len(),
+            );
+        }
+
+        Ok((pkt, attr))
+    }
+}
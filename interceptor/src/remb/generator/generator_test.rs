@@ -0,0 +1,87 @@
+use std::time::SystemTime;
+
+use rtp::extension::abs_send_time_extension::AbsSendTimeExtension;
+use util::Marshal;
+
+use super::*;
+use crate::mock::mock_stream::MockStream;
+use crate::stream_info::{RTCPFeedback, RTPHeaderExtension};
+
+fn stream_info(rtcp_feedback: &str) -> StreamInfo {
+    StreamInfo {
+        ssrc: 123456,
+        rtcp_feedback: vec![RTCPFeedback {
+            typ: rtcp_feedback.to_owned(),
+            ..Default::default()
+        }],
+        rtp_header_extensions: vec![RTPHeaderExtension {
+            uri: ABS_SEND_TIME_URI.to_owned(),
+            id: 3,
+        }],
+        ..Default::default()
+    }
+}
+
+async fn receive_packets(stream: &MockStream) -> Result<()> {
+    for seq in 0..10u16 {
+        let mut header = rtp::header::Header {
+            ssrc: 123456,
+            sequence_number: seq,
+            ..Default::default()
+        };
+        header.set_extension(3, AbsSendTimeExtension::new(SystemTime::now()).marshal()?)?;
+        stream
+            .receive_rtp(rtp::packet::Packet {
+                header,
+                payload: vec![0u8; 1000].into(),
+                ..Default::default()
+            })
+            .await;
+        stream.read_rtp().await.expect("packet should be read")?;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_remb_generator_interceptor() -> Result<()> {
+    let icpr = Generator::builder()
+        .with_interval(Duration::from_millis(50))
+        .with_initial_bitrate(500_000)
+        .build("")?;
+    let stream = MockStream::new(&stream_info("goog-remb"), icpr).await;
+
+    receive_packets(&stream).await?;
+
+    let pkts = tokio::time::timeout(Duration::from_secs(1), stream.written_rtcp())
+        .await
+        .expect("a remb should be sent")
+        .unwrap();
+    assert_eq!(pkts.len(), 1);
+    let remb = pkts[0]
+        .as_any()
+        .downcast_ref::<ReceiverEstimatedMaximumBitrate>()
+        .expect("packet should be a remb");
+    assert_eq!(remb.ssrcs, vec![123456]);
+    assert!(remb.bitrate >= 500_000.0, "bitrate {}", remb.bitrate);
+
+    stream.close().await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_remb_generator_interceptor_not_negotiated() -> Result<()> {
+    let icpr = Generator::builder()
+        .with_interval(Duration::from_millis(50))
+        .build("")?;
+    let stream = MockStream::new(&stream_info("nack"), icpr).await;
+
+    receive_packets(&stream).await?;
+
+    let result = tokio::time::timeout(Duration::from_millis(200), stream.written_rtcp()).await;
+    assert!(result.is_err(), "no remb should be sent");
+
+    stream.close().await?;
+
+    Ok(())
+}
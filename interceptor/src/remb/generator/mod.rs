@@ -0,0 +1,310 @@
+mod generator_stream;
+#[cfg(test)]
+mod generator_test;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use generator_stream::GeneratorStream;
+use rtcp::payload_feedbacks::receiver_estimated_maximum_bitrate::ReceiverEstimatedMaximumBitrate;
+use tokio::sync::{mpsc, Mutex, Notify};
+use waitgroup::WaitGroup;
+
+use crate::abs_send_time::ABS_SEND_TIME_URI;
+use crate::error::{Error, Result};
+use crate::remb::estimator::Estimator;
+use crate::remb::stream_support_remb;
+use crate::stream_info::StreamInfo;
+use crate::{
+    Attributes, Interceptor, InterceptorBuilder, RTCPReader, RTCPWriter, RTPReader, RTPWriter,
+};
+
+const DEFAULT_INITIAL_BITRATE: u64 = 300_000;
+const DEFAULT_MIN_BITRATE: u64 = 30_000;
+const DEFAULT_MAX_BITRATE: u64 = 10_000_000;
+
+/// A decrease of the estimate by more than this fraction is sent right away.
+const IMMEDIATE_DECREASE_FRACTION: f64 = 0.03;
+
+/// GeneratorBuilder can be used to configure Generator Interceptor
+#[derive(Default)]
+pub struct GeneratorBuilder {
+    interval: Option<Duration>,
+    initial_bitrate: Option<u64>,
+    min_bitrate: Option<u64>,
+    max_bitrate: Option<u64>,
+}
+
+impl GeneratorBuilder {
+    /// with_interval sets the REMB send interval for the interceptor
+    pub fn with_interval(mut self, interval: Duration) -> GeneratorBuilder {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// with_initial_bitrate sets the bitrate, in bits per second, estimated before any
+    /// packet is received.
+    pub fn with_initial_bitrate(mut self, bitrate: u64) -> GeneratorBuilder {
+        self.initial_bitrate = Some(bitrate);
+        self
+    }
+
+    /// with_min_bitrate sets the lowest bitrate, in bits per second, estimated.
+    pub fn with_min_bitrate(mut self, bitrate: u64) -> GeneratorBuilder {
+        self.min_bitrate = Some(bitrate);
+        self
+    }
+
+    /// with_max_bitrate sets the highest bitrate, in bits per second, estimated.
+    pub fn with_max_bitrate(mut self, bitrate: u64) -> GeneratorBuilder {
+        self.max_bitrate = Some(bitrate);
+        self
+    }
+}
+
+impl InterceptorBuilder for GeneratorBuilder {
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>> {
+        let (close_tx, close_rx) = mpsc::channel(1);
+        let estimator = Estimator::new(
+            self.initial_bitrate.unwrap_or(DEFAULT_INITIAL_BITRATE),
+            self.min_bitrate.unwrap_or(DEFAULT_MIN_BITRATE),
+            self.max_bitrate.unwrap_or(DEFAULT_MAX_BITRATE),
+        );
+        Ok(Arc::new(Generator {
+            internal: Arc::new(GeneratorInternal {
+                interval: if let Some(interval) = self.interval {
+                    interval
+                } else {
+                    Duration::from_secs(1)
+                },
+                start_time: tokio::time::Instant::now(),
+
+                state: util::sync::Mutex::new(GeneratorState {
+                    estimator,
+                    ssrcs: vec![],
+                    received: false,
+                    last_sent: None,
+                }),
+                decreased: Notify::new(),
+                close_rx: Mutex::new(Some(close_rx)),
+            }),
+
+            wg: Mutex::new(Some(WaitGroup::new())),
+            close_tx: Mutex::new(Some(close_tx)),
+        }))
+    }
+}
+
+struct GeneratorState {
+    estimator: Estimator,
+    ssrcs: Vec<u32>,
+    received: bool,
+    last_sent: Option<u64>,
+}
+
+struct GeneratorInternal {
+    interval: Duration,
+    // we use tokio's Instant because it makes testing easier via `tokio::time::advance`.
+    start_time: tokio::time::Instant,
+
+    state: util::sync::Mutex<GeneratorState>,
+    decreased: Notify,
+    close_rx: Mutex<Option<mpsc::Receiver<()>>>,
+}
+
+impl GeneratorInternal {
+    fn incoming_packet(&self, arrival_us: i64, abs_send_time: u32, size: usize) {
+        let mut state = self.state.lock();
+        state.received = true;
+        let bitrate = state
+            .estimator
+            .incoming_packet(arrival_us, abs_send_time, size);
+        if let Some(last_sent) = state.last_sent {
+            if (bitrate as f64) < last_sent as f64 * (1.0 - IMMEDIATE_DECREASE_FRACTION) {
+                self.decreased.notify_one();
+            }
+        }
+    }
+
+    fn generate_remb(&self, sender_ssrc: u32) -> Option<ReceiverEstimatedMaximumBitrate> {
+        let mut state = self.state.lock();
+        if !state.received || state.ssrcs.is_empty() {
+            return None;
+        }
+
+        let bitrate = state.estimator.bitrate();
+        state.last_sent = Some(bitrate);
+        Some(ReceiverEstimatedMaximumBitrate {
+            sender_ssrc,
+            bitrate: bitrate as f32,
+            ssrcs: state.ssrcs.clone(),
+        })
+    }
+}
+
+/// Generator interceptor estimates the bandwidth available to the remote peer from the
+/// abs-send-time of the packets received, and sends it in REMB packets, for senders which
+/// don't support transport-cc feedback.
+pub struct Generator {
+    internal: Arc<GeneratorInternal>,
+
+    pub(crate) wg: Mutex<Option<WaitGroup>>,
+    pub(crate) close_tx: Mutex<Option<mpsc::Sender<()>>>,
+}
+
+impl Generator {
+    /// builder returns a new GeneratorBuilder.
+    pub fn builder() -> GeneratorBuilder {
+        GeneratorBuilder::default()
+    }
+
+    async fn is_closed(&self) -> bool {
+        let close_tx = self.close_tx.lock().await;
+        close_tx.is_none()
+    }
+
+    async fn run(
+        rtcp_writer: Arc<dyn RTCPWriter + Send + Sync>,
+        internal: Arc<GeneratorInternal>,
+    ) -> Result<()> {
+        let mut ticker = tokio::time::interval(internal.interval);
+        let mut close_rx = {
+            let mut close_rx = internal.close_rx.lock().await;
+            if let Some(close) = close_rx.take() {
+                close
+            } else {
+                return Err(Error::ErrInvalidCloseRx);
+            }
+        };
+
+        let sender_ssrc = rand::random::<u32>();
+        let a = Attributes::new();
+        loop {
+            tokio::select! {
+                _ = ticker.tick() =>{}
+                _ = internal.decreased.notified() =>{
+                    ticker.reset();
+                }
+                _ = close_rx.recv() =>{
+                    return Ok(());
+                }
+            }
+
+            if let Some(remb) = internal.generate_remb(sender_ssrc) {
+                if let Err(err) = rtcp_writer.write(&[Box::new(remb)], &a).await {
+                    log::warn!("failed sending remb: {}", err);
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Interceptor for Generator {
+    /// bind_rtcp_reader lets you modify any incoming RTCP packets. It is called once per sender/receiver, however this might
+    /// change in the future. The returned method will be called once per packet batch.
+    async fn bind_rtcp_reader(
+        &self,
+        reader: Arc<dyn RTCPReader + Send + Sync>,
+    ) -> Arc<dyn RTCPReader + Send + Sync> {
+        reader
+    }
+
+    /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
+    /// will be called once per packet batch.
+    async fn bind_rtcp_writer(
+        &self,
+        writer: Arc<dyn RTCPWriter + Send + Sync>,
+    ) -> Arc<dyn RTCPWriter + Send + Sync> {
+        if self.is_closed().await {
+            return writer;
+        }
+
+        let mut w = {
+            let wait_group = self.wg.lock().await;
+            wait_group.as_ref().map(|wg| wg.worker())
+        };
+        let writer2 = Arc::clone(&writer);
+        let internal = Arc::clone(&self.internal);
+        tokio::spawn(async move {
+            let _d = w.take();
+            if let Err(err) = Generator::run(writer2, internal).await {
+                log::warn!("bind_rtcp_writer REMB Generator::run got error: {}", err);
+            }
+        });
+
+        writer
+    }
+
+    /// bind_local_stream lets you modify any outgoing RTP packets. It is called once for per LocalStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_local_stream(
+        &self,
+        _info: &StreamInfo,
+        writer: Arc<dyn RTPWriter + Send + Sync>,
+    ) -> Arc<dyn RTPWriter + Send + Sync> {
+        writer
+    }
+
+    /// unbind_local_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_local_stream(&self, _info: &StreamInfo) {}
+
+    /// bind_remote_stream lets you modify any incoming RTP packets. It is called once for per RemoteStream. The returned method
+    /// will be called once per rtp packet.
+    async fn bind_remote_stream(
+        &self,
+        info: &StreamInfo,
+        reader: Arc<dyn RTPReader + Send + Sync>,
+    ) -> Arc<dyn RTPReader + Send + Sync> {
+        if !stream_support_remb(info) {
+            return reader;
+        }
+        let hdr_ext_id = info
+            .rtp_header_extensions
+            .iter()
+            .find(|e| e.uri == ABS_SEND_TIME_URI)
+            .map(|e| e.id as u8)
+            .unwrap_or_default();
+        if hdr_ext_id == 0 {
+            // Don't try to read header extension if ID is 0, because 0 is an invalid extension ID
+            return reader;
+        }
+
+        {
+            let mut state = self.internal.state.lock();
+            if !state.ssrcs.contains(&info.ssrc) {
+                state.ssrcs.push(info.ssrc);
+            }
+        }
+
+        Arc::new(GeneratorStream::new(
+            reader,
+            hdr_ext_id,
+            Arc::clone(&self.internal),
+        ))
+    }
+
+    /// unbind_remote_stream is called when the Stream is removed. It can be used to clean up any data related to that track.
+    async fn unbind_remote_stream(&self, info: &StreamInfo) {
+        let mut state = self.internal.state.lock();
+        state.ssrcs.retain(|ssrc| *ssrc != info.ssrc);
+    }
+
+    /// close closes the Interceptor, cleaning up any data if necessary.
+    async fn close(&self) -> Result<()> {
+        {
+            let mut close_tx = self.close_tx.lock().await;
+            close_tx.take();
+        }
+
+        {
+            let mut wait_group = self.wg.lock().await;
+            if let Some(wg) = wait_group.take() {
+                wg.wait().await;
+            }
+        }
+
+        Ok(())
+    }
+}
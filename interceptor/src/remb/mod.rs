@@ -0,0 +1,12 @@
+use crate::stream_info::StreamInfo;
+
+pub mod estimator;
+pub mod generator;
+
+pub(crate) const TYPE_RTCP_FB_GOOG_REMB: &str = "goog-remb";
+
+fn stream_support_remb(info: &StreamInfo) -> bool {
+    info.rtcp_feedback
+        .iter()
+        .any(|fb| fb.typ == TYPE_RTCP_FB_GOOG_REMB)
+}
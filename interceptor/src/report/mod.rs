@@ -2,13 +2,19 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 
+use bytes::Bytes;
+use rtcp::source_description::{
+    SdesType, SourceDescription, SourceDescriptionChunk, SourceDescriptionItem,
+};
 use tokio::sync::{mpsc, Mutex};
 use waitgroup::WaitGroup;
 
 pub mod receiver;
+pub mod scheduler;
 pub mod sender;
 
 use receiver::{ReceiverReport, ReceiverReportInternal};
+use scheduler::{RtcpScheduler, RtcpSchedulerConfig, RTCP_LOWER_LAYERS_OVERHEAD};
 use sender::{SenderReport, SenderReportInternal};
 
 use crate::error::Result;
@@ -22,6 +28,10 @@ pub struct ReportBuilder {
     is_rr: bool,
     interval: Option<Duration>,
     now: Option<FnTimeGen>,
+    extended_reports: bool,
+    cname: Option<String>,
+    source_description_items: Vec<SourceDescriptionItem>,
+    rtcp_scheduler: Option<RtcpSchedulerConfig>,
 }
 
 impl ReportBuilder {
@@ -37,6 +47,68 @@ impl ReportBuilder {
         self
     }
 
+    /// with_extended_reports sets whether a ReceiverReport interceptor also sends RTCP XR
+    /// receiver reference time blocks (RFC 3611 section 4.4). The SenderReport interceptor
+    /// of the remote answers them with DLRR blocks, which gives the round trip time of
+    /// streams that are only received.
+    pub fn with_extended_reports(mut self, extended_reports: bool) -> ReportBuilder {
+        self.extended_reports = extended_reports;
+        self
+    }
+
+    /// with_cname sets the CNAME of the SDES packet sent along with each report. A random
+    /// CNAME is used when only other SDES items are set.
+    pub fn with_cname(mut self, cname: String) -> ReportBuilder {
+        self.cname = Some(cname);
+        self
+    }
+
+    /// with_source_description_items sets the SDES items, such as NAME, TOOL or NOTE, sent
+    /// after the CNAME in the SDES packet sent along with each report.
+    pub fn with_source_description_items(
+        mut self,
+        items: Vec<SourceDescriptionItem>,
+    ) -> ReportBuilder {
+        self.source_description_items = items;
+        self
+    }
+
+    /// with_rtcp_scheduler makes the interceptor schedule its reports with the bandwidth
+    /// based interval of RFC 3550, and the AVPF rules of RFC 4585 if enabled, instead of
+    /// sending them at a fixed interval.
+    pub fn with_rtcp_scheduler(mut self, config: RtcpSchedulerConfig) -> ReportBuilder {
+        self.rtcp_scheduler = Some(config);
+        self
+    }
+
+    fn scheduler(&self) -> Option<util::sync::Mutex<RtcpScheduler>> {
+        self.rtcp_scheduler.map(|config| {
+            util::sync::Mutex::new(RtcpScheduler::new(config, tokio::time::Instant::now()))
+        })
+    }
+
+    fn source_description(&self) -> Option<SourceDescriptionTemplate> {
+        if self.cname.is_none() && self.source_description_items.is_empty() {
+            return None;
+        }
+
+        let cname = if let Some(cname) = &self.cname {
+            cname.clone()
+        } else {
+            // short-term persistent random CNAME, see RFC 7022 section 4.2
+            format!("{:024x}", rand::random::<u128>() >> 32)
+        };
+        Some(SourceDescriptionTemplate {
+            cname: Bytes::from(cname),
+            items: self
+                .source_description_items
+                .iter()
+                .filter(|it| it.sdes_type != SdesType::SdesCname)
+                .cloned()
+                .collect(),
+        })
+    }
+
     fn build_rr(&self) -> ReceiverReport {
         let (close_tx, close_rx) = mpsc::channel(1);
         ReceiverReport {
@@ -47,6 +119,9 @@ impl ReportBuilder {
                     Duration::from_secs(1)
                 },
                 now: self.now.clone(),
+                extended_reports: self.extended_reports,
+                source_description: self.source_description(),
+                scheduler: self.scheduler(),
                 streams: Mutex::new(HashMap::new()),
                 close_rx: Mutex::new(Some(close_rx)),
             }),
@@ -66,7 +141,10 @@ impl ReportBuilder {
                     Duration::from_secs(1)
                 },
                 now: self.now.clone(),
+                source_description: self.source_description(),
+                scheduler: self.scheduler(),
                 streams: Mutex::new(HashMap::new()),
+                reference_times: Mutex::new(HashMap::new()),
                 close_rx: Mutex::new(Some(close_rx)),
             }),
 
@@ -85,3 +163,54 @@ impl InterceptorBuilder for ReportBuilder {
         }
     }
 }
+
+/// SourceDescriptionTemplate holds the items of the SDES packets sent along with reports.
+#[derive(Debug, Clone)]
+pub(crate) struct SourceDescriptionTemplate {
+    cname: Bytes,
+    items: Vec<SourceDescriptionItem>,
+}
+
+impl SourceDescriptionTemplate {
+    /// generate returns the SDES packet describing ssrc.
+    pub(crate) fn generate(&self, ssrc: u32) -> SourceDescription {
+        let mut items = vec![SourceDescriptionItem {
+            sdes_type: SdesType::SdesCname,
+            text: self.cname.clone(),
+        }];
+        items.extend(self.items.iter().cloned());
+        SourceDescription {
+            chunks: vec![SourceDescriptionChunk {
+                source: ssrc,
+                items,
+            }],
+        }
+    }
+}
+
+/// wait_next_report waits until the reports should be sent, as scheduled by scheduler if
+/// any, else by ticker.
+async fn wait_next_report(
+    ticker: &mut tokio::time::Interval,
+    scheduler: &Option<util::sync::Mutex<RtcpScheduler>>,
+) {
+    let scheduler = if let Some(scheduler) = scheduler {
+        scheduler
+    } else {
+        ticker.tick().await;
+        return;
+    };
+
+    loop {
+        let next = scheduler.lock().next_transmission();
+        tokio::time::sleep_until(next).await;
+        if scheduler.lock().on_timer(tokio::time::Instant::now()) {
+            return;
+        }
+    }
+}
+
+/// rtcp_size returns the size of pkts on the wire, for the average RTCP packet size.
+fn rtcp_size(pkts: &[Box<dyn rtcp::packet::Packet + Send + Sync>]) -> usize {
+    pkts.iter().map(|p| p.marshal_size()).sum::<usize>() + RTCP_LOWER_LAYERS_OVERHEAD
+}
@@ -16,10 +16,25 @@ use crate::*;
 pub(crate) struct ReceiverReportInternal {
     pub(crate) interval: Duration,
     pub(crate) now: Option<FnTimeGen>,
+    pub(crate) extended_reports: bool,
+    pub(crate) source_description: Option<SourceDescriptionTemplate>,
+    pub(crate) scheduler: Option<util::sync::Mutex<RtcpScheduler>>,
     pub(crate) streams: Mutex<HashMap<u32, Arc<ReceiverStream>>>,
     pub(crate) close_rx: Mutex<Option<mpsc::Receiver<()>>>,
 }
 
+impl ReceiverReportInternal {
+    /// update_members counts this receiver and the senders of the streams received as the
+    /// members of the session.
+    fn update_members(&self, senders: usize) {
+        if let Some(scheduler) = &self.scheduler {
+            scheduler
+                .lock()
+                .set_members(senders + 1, senders, tokio::time::Instant::now());
+        }
+    }
+}
+
 pub(crate) struct ReceiverReportRtcpReader {
     pub(crate) internal: Arc<ReceiverReportInternal>,
     pub(crate) parent_rtcp_reader: Arc<dyn RTCPReader + Send + Sync>,
@@ -33,6 +48,9 @@ impl RTCPReader for ReceiverReportRtcpReader {
         a: &Attributes,
     ) -> Result<(Vec<Box<dyn rtcp::packet::Packet + Send + Sync>>, Attributes)> {
         let (pkts, attr) = self.parent_rtcp_reader.read(buf, a).await?;
+        if let Some(scheduler) = &self.internal.scheduler {
+            scheduler.lock().on_rtcp_received(rtcp_size(&pkts));
+        }
 
         let now = if let Some(f) = &self.internal.now {
             f()
@@ -97,7 +115,7 @@ impl ReceiverReport {
 
         loop {
             tokio::select! {
-                _ = ticker.tick() =>{
+                _ = wait_next_report(&mut ticker, &internal.scheduler) =>{
                     // TODO(cancel safety): This branch isn't cancel safe
 
                     let now = if let Some(f) = &internal.now {
@@ -109,14 +127,28 @@ impl ReceiverReport {
                         let m = internal.streams.lock().await;
                         m.values().cloned().collect()
                     };
+                    let mut size = 0;
                     for stream in streams {
-                        let pkt = stream.generate_report(now);
+                        let report = stream.generate_report(now);
+                        let ssrc = report.ssrc;
+                        let mut pkts: Vec<Box<dyn rtcp::packet::Packet + Send + Sync>> =
+                            vec![Box::new(report)];
+                        if let Some(source_description) = &internal.source_description {
+                            pkts.push(Box::new(source_description.generate(ssrc)));
+                        }
+                        if internal.extended_reports {
+                            pkts.push(Box::new(stream.generate_extended_report(now)));
+                        }
 
+                        size += rtcp_size(&pkts);
                         let a = Attributes::new();
-                        if let Err(err) = rtcp_writer.write(&[Box::new(pkt)], &a).await{
+                        if let Err(err) = rtcp_writer.write(&pkts, &a).await{
                             log::warn!("failed sending: {}", err);
                         }
                     }
+                    if let Some(scheduler) = &internal.scheduler {
+                        scheduler.lock().on_report_sent(size, tokio::time::Instant::now());
+                    }
                 }
                 _ = close_rx.recv() =>{
                     return Ok(());
@@ -195,6 +227,7 @@ impl Interceptor for ReceiverReport {
         {
             let mut streams = self.internal.streams.lock().await;
             streams.insert(info.ssrc, Arc::clone(&stream));
+            self.internal.update_members(streams.len());
         }
 
         stream
@@ -204,6 +237,7 @@ impl Interceptor for ReceiverReport {
     async fn unbind_remote_stream(&self, info: &StreamInfo) {
         let mut streams = self.internal.streams.lock().await;
         streams.remove(&info.ssrc);
+        self.internal.update_members(streams.len());
     }
 
     /// close closes the Interceptor, cleaning up any data if necessary.
@@ -1,6 +1,9 @@
 use std::time::SystemTime;
 
 use async_trait::async_trait;
+use rtcp::extended_report::{ExtendedReport, ReceiverReferenceTimeReportBlock};
+use rtp::extension::abs_send_time_extension::unix2ntp;
+use rtp::sequence::SequenceTracker;
 use util::sync::Mutex;
 
 use super::*;
@@ -13,7 +16,7 @@ struct ReceiverStreamInternal {
 
     packets: Vec<u64>,
     started: bool,
-    seq_num_cycles: u16,
+    sequence: SequenceTracker,
     last_seq_num: i32,
     last_report_seq_num: i32,
     last_rtp_time_rtp: u32,
@@ -41,6 +44,7 @@ impl ReceiverStreamInternal {
     }
 
     fn process_rtp(&mut self, now: SystemTime, pkt: &rtp::packet::Packet) {
+        self.sequence.update(pkt.header.sequence_number);
         if !self.started {
             // first frame
             self.started = true;
@@ -53,11 +57,6 @@ impl ReceiverStreamInternal {
 
             let diff = pkt.header.sequence_number as i32 - self.last_seq_num;
             if !(-0x0FFF..=0).contains(&diff) {
-                // overflow
-                if diff < -0x0FFF {
-                    self.seq_num_cycles += 1;
-                }
-
                 // set missing packets as missing
                 for i in self.last_seq_num + 1..pkt.header.sequence_number as i32 {
                     self.del_received(i as u16);
@@ -118,7 +117,7 @@ impl ReceiverStreamInternal {
             ssrc: self.receiver_ssrc,
             reports: vec![rtcp::reception_report::ReceptionReport {
                 ssrc: self.ssrc,
-                last_sequence_number: (self.seq_num_cycles as u32) << 16
+                last_sequence_number: (self.sequence.roll_over_count() as u32) << 16
                     | (self.last_seq_num as u32),
                 last_sender_report: self.last_sender_report,
                 fraction_lost: ((total_lost_since_report * 256) as f64 / total_since_report as f64)
@@ -143,6 +142,15 @@ impl ReceiverStreamInternal {
 
         r
     }
+
+    fn generate_extended_report(&self, now: SystemTime) -> ExtendedReport {
+        ExtendedReport {
+            sender_ssrc: self.receiver_ssrc,
+            reports: vec![Box::new(ReceiverReferenceTimeReportBlock {
+                ntp_timestamp: unix2ntp(now),
+            })],
+        }
+    }
 }
 
 pub(crate) struct ReceiverStream {
@@ -171,7 +179,7 @@ impl ReceiverStream {
 
                 packets: vec![0u64; 128],
                 started: false,
-                seq_num_cycles: 0,
+                sequence: SequenceTracker::new(),
                 last_seq_num: 0,
                 last_report_seq_num: 0,
                 last_rtp_time_rtp: 0,
@@ -202,6 +210,11 @@ impl ReceiverStream {
         let mut internal = self.internal.lock();
         internal.generate_report(now)
     }
+
+    pub(crate) fn generate_extended_report(&self, now: SystemTime) -> ExtendedReport {
+        let internal = self.internal.lock();
+        internal.generate_extended_report(now)
+    }
 }
 
 /// RTPReader is used by Interceptor.bind_remote_stream.
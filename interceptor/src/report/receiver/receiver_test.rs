@@ -770,3 +770,134 @@ async fn test_receiver_interceptor_delay() -> Result<()> {
     stream.close().await?;
     Ok(())
 }
+
+#[tokio::test]
+async fn test_receiver_interceptor_extended_reports() -> Result<()> {
+    let mt = Arc::new(MockTime::default());
+    mt.set_now(Utc.with_ymd_and_hms(2009, 11, 10, 23, 0, 0).unwrap().into());
+    let time_gen = {
+        let mt = Arc::clone(&mt);
+        Arc::new(move || mt.now())
+    };
+
+    let icpr: Arc<dyn Interceptor + Send + Sync> = ReceiverReport::builder()
+        .with_interval(Duration::from_millis(50))
+        .with_now_fn(time_gen)
+        .with_extended_reports(true)
+        .build("")?;
+
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 123456,
+            clock_rate: 90000,
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    let pkts = stream.written_rtcp().await.unwrap();
+    assert_eq!(pkts.len(), 2);
+    let rr = pkts[0]
+        .as_any()
+        .downcast_ref::<rtcp::receiver_report::ReceiverReport>()
+        .expect("first packet should be a receiver report");
+    let xr = pkts[1]
+        .as_any()
+        .downcast_ref::<rtcp::extended_report::ExtendedReport>()
+        .expect("second packet should be an extended report");
+    assert_eq!(xr.sender_ssrc, rr.ssrc);
+    assert_eq!(xr.reports.len(), 1);
+    assert_eq!(
+        xr.reports[0]
+            .as_any()
+            .downcast_ref::<rtcp::extended_report::ReceiverReferenceTimeReportBlock>(),
+        Some(&rtcp::extended_report::ReceiverReferenceTimeReportBlock {
+            ntp_timestamp: unix2ntp(mt.now()),
+        })
+    );
+
+    stream.close().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_receiver_interceptor_source_description_random_cname() -> Result<()> {
+    use rtcp::source_description::{SdesType, SourceDescription, SourceDescriptionItem};
+
+    let icpr: Arc<dyn Interceptor + Send + Sync> = ReceiverReport::builder()
+        .with_interval(Duration::from_millis(50))
+        .with_source_description_items(vec![SourceDescriptionItem {
+            sdes_type: SdesType::SdesNote,
+            text: Bytes::from_static(b"on air"),
+        }])
+        .build("")?;
+
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 123456,
+            clock_rate: 90000,
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    let pkts = stream.written_rtcp().await.unwrap();
+    assert_eq!(pkts.len(), 2);
+    let rr = pkts[0]
+        .as_any()
+        .downcast_ref::<rtcp::receiver_report::ReceiverReport>()
+        .expect("first packet should be a receiver report");
+    let sdes = pkts[1]
+        .as_any()
+        .downcast_ref::<SourceDescription>()
+        .expect("second packet should be a source description");
+    let chunk = &sdes.chunks[0];
+    assert_eq!(chunk.source, rr.ssrc);
+    assert_eq!(chunk.text(SdesType::SdesCname).unwrap().len(), 24);
+    assert_eq!(chunk.text(SdesType::SdesNote).unwrap(), "on air");
+
+    stream.close().await?;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_receiver_interceptor_rtcp_scheduler() -> Result<()> {
+    let icpr: Arc<dyn Interceptor + Send + Sync> = ReceiverReport::builder()
+        .with_interval(Duration::from_millis(50))
+        .with_rtcp_scheduler(scheduler::RtcpSchedulerConfig::default())
+        .build("")?;
+
+    let start = tokio::time::Instant::now();
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 123456,
+            clock_rate: 90000,
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    // the first report waits half the minimum interval of 5s, randomized in [0.5, 1.5]
+    // and compensated by e-3/2
+    let pkts = stream.written_rtcp().await.unwrap();
+    assert_eq!(pkts.len(), 1);
+    let first = start.elapsed();
+    assert!(
+        first >= Duration::from_millis(1000) && first <= Duration::from_millis(3100),
+        "first report sent after {first:?}"
+    );
+
+    let pkts = stream.written_rtcp().await.unwrap();
+    assert_eq!(pkts.len(), 1);
+    let second = start.elapsed() - first;
+    assert!(
+        second >= Duration::from_millis(2000) && second <= Duration::from_millis(6200),
+        "second report sent after {second:?}"
+    );
+
+    stream.close().await?;
+    Ok(())
+}
@@ -0,0 +1,260 @@
+#[cfg(test)]
+mod scheduler_test;
+
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// Fraction of the RTCP bandwidth shared by the senders, when they are few.
+const RTCP_SENDER_BW_FRACTION: f64 = 0.25;
+const RTCP_RCVR_BW_FRACTION: f64 = 1.0 - RTCP_SENDER_BW_FRACTION;
+/// The randomized interval is divided by e-3/2, so that its average stays the
+/// deterministic one despite the reconsideration.
+const COMPENSATION: f64 = std::f64::consts::E - 1.5;
+/// Size of the IP and UDP headers, accounted in the average RTCP packet size.
+pub const RTCP_LOWER_LAYERS_OVERHEAD: usize = 28;
+/// Fraction of the regular interval an early feedback packet may be dithered by.
+const EARLY_FEEDBACK_DITHER_FRACTION: f64 = 0.5;
+
+/// RtcpSchedulerConfig configures the RTCP transmission interval computation.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RtcpSchedulerConfig {
+    /// Session bandwidth, in bits per second.
+    pub session_bandwidth: u64,
+    /// Fraction of the session bandwidth used by RTCP, 5% as recommended by RFC 3550.
+    pub rtcp_fraction: f64,
+    /// Minimum interval between regular reports. Only half of it is waited for the first
+    /// report.
+    pub min_interval: Duration,
+    /// Whether the session uses the AVPF profile of RFC 4585, which removes the minimum
+    /// interval and allows early feedback packets.
+    pub avpf: bool,
+    /// Minimum interval between regular reports of AVPF sessions, the trr-int parameter of
+    /// RFC 4585. Zero doesn't suppress any regular report.
+    pub trr_interval: Duration,
+}
+
+impl Default for RtcpSchedulerConfig {
+    fn default() -> Self {
+        RtcpSchedulerConfig {
+            session_bandwidth: 1_000_000,
+            rtcp_fraction: 0.05,
+            min_interval: Duration::from_secs(5),
+            avpf: false,
+            trr_interval: Duration::ZERO,
+        }
+    }
+}
+
+/// RtcpScheduler schedules the transmission of RTCP reports following the bandwidth
+/// based interval computation of RFC 3550, so that RTCP stays within its share of the
+/// session bandwidth whatever the number of members, and the early feedback mode of
+/// RFC 4585.
+///
+/// ## Specifications
+///
+/// * [RFC 3550 §6.3]
+/// * [RFC 4585 §3.5]
+///
+/// [RFC 3550 §6.3]: https://tools.ietf.org/html/rfc3550#section-6.3
+/// [RFC 4585 §3.5]: https://tools.ietf.org/html/rfc4585#section-3.5
+#[derive(Debug, Clone)]
+pub struct RtcpScheduler {
+    config: RtcpSchedulerConfig,
+
+    /// Last time an RTCP packet was sent.
+    tp: Instant,
+    /// Next scheduled transmission time of a regular report.
+    tn: Instant,
+    pmembers: usize,
+    members: usize,
+    senders: usize,
+    we_sent: bool,
+    /// Average compound RTCP packet size, in bytes, including the lower layers overhead.
+    avg_rtcp_size: f64,
+    initial: bool,
+
+    /// Interval last computed for a regular report.
+    t_rr: Duration,
+    /// Last time a regular report was sent.
+    t_rr_last: Option<Instant>,
+    allow_early: bool,
+}
+
+impl RtcpScheduler {
+    /// new returns a scheduler for a session joined at now, whose first report is scheduled
+    /// after half the minimum interval.
+    pub fn new(config: RtcpSchedulerConfig, now: Instant) -> Self {
+        let mut scheduler = RtcpScheduler {
+            config,
+
+            tp: now,
+            tn: now,
+            pmembers: 1,
+            members: 1,
+            senders: 0,
+            we_sent: false,
+            // a receiver report and a SDES with a short CNAME
+            avg_rtcp_size: (60 + RTCP_LOWER_LAYERS_OVERHEAD) as f64,
+            initial: true,
+
+            t_rr: Duration::ZERO,
+            t_rr_last: None,
+            allow_early: true,
+        };
+        scheduler.t_rr = scheduler.interval();
+        scheduler.tn = now + scheduler.t_rr;
+        scheduler
+    }
+
+    /// next_transmission returns when the next regular report is scheduled.
+    pub fn next_transmission(&self) -> Instant {
+        self.tn
+    }
+
+    /// deterministic_interval returns the interval between regular reports, before its
+    /// randomization.
+    pub fn deterministic_interval(&self) -> Duration {
+        let min_interval = if self.config.avpf {
+            // RFC 4585 removes the minimum interval, but for the first report
+            if self.initial {
+                Duration::from_secs(1)
+            } else {
+                Duration::ZERO
+            }
+        } else if self.initial {
+            self.config.min_interval / 2
+        } else {
+            self.config.min_interval
+        };
+
+        // rtcp bandwidth, in bytes per second
+        let mut rtcp_bw = self.config.session_bandwidth as f64 * self.config.rtcp_fraction / 8.0;
+        let mut n = self.members as f64;
+        if self.senders as f64 <= self.members as f64 * RTCP_SENDER_BW_FRACTION {
+            if self.we_sent {
+                rtcp_bw *= RTCP_SENDER_BW_FRACTION;
+                n = self.senders as f64;
+            } else {
+                rtcp_bw *= RTCP_RCVR_BW_FRACTION;
+                n -= self.senders as f64;
+            }
+        }
+
+        if rtcp_bw <= 0.0 {
+            return min_interval.max(self.config.min_interval);
+        }
+        let t = Duration::from_secs_f64(self.avg_rtcp_size * n.max(1.0) / rtcp_bw);
+        t.max(min_interval)
+    }
+
+    /// interval returns the randomized interval between regular reports.
+    fn interval(&self) -> Duration {
+        let factor = rand::random::<f64>() + 0.5;
+        self.deterministic_interval().mul_f64(factor / COMPENSATION)
+    }
+
+    /// set_members updates the number of members of the session, including this one, and
+    /// how many of them are senders. The next report is brought forward when members
+    /// leave (reverse reconsideration).
+    pub fn set_members(&mut self, members: usize, senders: usize, now: Instant) {
+        let members = members.max(1);
+        self.senders = senders.min(members);
+        if members < self.pmembers {
+            let ratio = members as f64 / self.pmembers as f64;
+            self.tn = now + self.tn.saturating_duration_since(now).mul_f64(ratio);
+            self.tp = now - now.saturating_duration_since(self.tp).mul_f64(ratio);
+            self.pmembers = members;
+        }
+        self.members = members;
+    }
+
+    /// set_we_sent sets whether RTP packets were sent since the second to last report.
+    pub fn set_we_sent(&mut self, we_sent: bool) {
+        self.we_sent = we_sent;
+    }
+
+    /// on_rtcp_received records the size, in bytes, of an RTCP compound packet received.
+    pub fn on_rtcp_received(&mut self, size: usize) {
+        self.update_avg_rtcp_size(size);
+    }
+
+    /// on_timer is called when the transmission time is reached. It reconsiders the
+    /// interval with the current members, and returns whether a regular report should be
+    /// sent now, else its transmission is rescheduled.
+    pub fn on_timer(&mut self, now: Instant) -> bool {
+        if now < self.tn {
+            return false;
+        }
+
+        let t = self.interval();
+        if self.tp + t > now {
+            self.tn = self.tp + t;
+            return false;
+        }
+
+        if let Some(t_rr_last) = self.t_rr_last {
+            let trr_interval = self.config.trr_interval;
+            if self.config.avpf
+                && !trr_interval.is_zero()
+                && now.saturating_duration_since(t_rr_last) < trr_interval
+            {
+                // the regular report is suppressed, see RFC 4585 section 3.5.3
+                self.tp = now;
+                self.t_rr = t;
+                self.tn = now + t;
+                self.allow_early = true;
+                return false;
+            }
+        }
+        true
+    }
+
+    /// on_report_sent records that a regular report of size bytes, including the lower
+    /// layers overhead, was sent at now, and schedules the next one.
+    pub fn on_report_sent(&mut self, size: usize, now: Instant) {
+        self.update_avg_rtcp_size(size);
+        self.tp = now;
+        self.t_rr_last = Some(now);
+        self.initial = false;
+        self.pmembers = self.members;
+        self.t_rr = self.interval();
+        self.tn = now + self.t_rr;
+        self.allow_early = true;
+    }
+
+    /// request_early_feedback returns when an early feedback packet requested at now
+    /// should be sent, in AVPF sessions. It returns None when it should wait for the next
+    /// regular report instead, because it is soon enough or an early packet was already
+    /// sent since the last regular report.
+    pub fn request_early_feedback(&mut self, now: Instant) -> Option<Instant> {
+        if !self.config.avpf || !self.allow_early {
+            return None;
+        }
+
+        let dither_max = if self.members <= 2 {
+            // point to point sessions don't need to avoid feedback implosion
+            Duration::ZERO
+        } else {
+            self.t_rr.mul_f64(EARLY_FEEDBACK_DITHER_FRACTION)
+        };
+        if now + dither_max >= self.tn {
+            return None;
+        }
+
+        let send_at = now + dither_max.mul_f64(rand::random::<f64>());
+        self.allow_early = false;
+        self.tn = self.tp + self.t_rr * 2;
+        Some(send_at)
+    }
+
+    /// on_early_feedback_sent records that an early feedback packet of size bytes was sent.
+    pub fn on_early_feedback_sent(&mut self, size: usize, now: Instant) {
+        self.update_avg_rtcp_size(size);
+        self.tp = now;
+    }
+
+    fn update_avg_rtcp_size(&mut self, size: usize) {
+        self.avg_rtcp_size = size as f64 / 16.0 + self.avg_rtcp_size * 15.0 / 16.0;
+    }
+}
@@ -0,0 +1,156 @@
+use super::*;
+
+fn config() -> RtcpSchedulerConfig {
+    RtcpSchedulerConfig {
+        session_bandwidth: 64_000,
+        ..Default::default()
+    }
+}
+
+fn assert_randomized(interval: Duration, deterministic: Duration) {
+    let min = deterministic.mul_f64(0.5 / COMPENSATION);
+    let max = deterministic.mul_f64(1.5 / COMPENSATION);
+    assert!(
+        interval >= min && interval <= max,
+        "{interval:?} isn't within [{min:?}, {max:?}]"
+    );
+}
+
+/// next_report returns when the scheduler sends its next regular report, after the
+/// reconsiderations.
+fn next_report(scheduler: &mut RtcpScheduler) -> Instant {
+    loop {
+        let at = scheduler.next_transmission();
+        if scheduler.on_timer(at) {
+            return at;
+        }
+    }
+}
+
+#[test]
+fn test_rtcp_scheduler_minimum_interval() {
+    let now = Instant::now();
+    let mut scheduler = RtcpScheduler::new(config(), now);
+
+    // half the minimum interval before the first report
+    assert_eq!(
+        scheduler.deterministic_interval(),
+        Duration::from_millis(2500)
+    );
+    assert_randomized(
+        scheduler.next_transmission() - now,
+        Duration::from_millis(2500),
+    );
+    // not before its time
+    assert!(!scheduler.on_timer(now));
+
+    let now = next_report(&mut scheduler);
+    scheduler.on_report_sent(100, now);
+    assert_eq!(scheduler.deterministic_interval(), Duration::from_secs(5));
+    assert_randomized(scheduler.next_transmission() - now, Duration::from_secs(5));
+}
+
+#[test]
+fn test_rtcp_scheduler_bandwidth_share() {
+    let now = Instant::now();
+    let mut scheduler = RtcpScheduler::new(config(), now);
+    scheduler.on_report_sent(100, now);
+    scheduler.avg_rtcp_size = 100.0;
+
+    // 5% of 64 kbps is 400 bytes per second, of which 75% for the 300 receivers
+    scheduler.set_members(301, 1, now);
+    assert_eq!(scheduler.deterministic_interval(), Duration::from_secs(100));
+
+    // and 25% for the sender, which is less than the minimum interval
+    scheduler.set_we_sent(true);
+    assert_eq!(scheduler.deterministic_interval(), Duration::from_secs(5));
+
+    // the bandwidth is shared by all when there are many senders
+    scheduler.set_we_sent(false);
+    scheduler.set_members(400, 200, now);
+    assert_eq!(scheduler.deterministic_interval(), Duration::from_secs(100));
+}
+
+#[test]
+fn test_rtcp_scheduler_reconsideration() {
+    let now = Instant::now();
+    let mut scheduler = RtcpScheduler::new(config(), now);
+    scheduler.avg_rtcp_size = 100.0;
+
+    // many members join before the first report, which is postponed
+    scheduler.set_members(1000, 0, now);
+    let at = scheduler.next_transmission();
+    assert!(!scheduler.on_timer(at));
+    assert!(scheduler.next_transmission() > at);
+
+    // reverse reconsideration brings the report forward when members leave
+    let now = next_report(&mut scheduler);
+    scheduler.on_report_sent(100, now);
+    let now = now + Duration::from_secs(10);
+    let remaining = scheduler.next_transmission() - now;
+    scheduler.set_members(100, 0, now);
+    let brought_forward = scheduler.next_transmission() - now;
+    assert!(
+        (brought_forward.as_secs_f64() - remaining.as_secs_f64() / 10.0).abs() < 0.001,
+        "{brought_forward:?} should be a tenth of {remaining:?}"
+    );
+}
+
+#[test]
+fn test_rtcp_scheduler_early_feedback() {
+    let now = Instant::now();
+    let mut scheduler = RtcpScheduler::new(
+        RtcpSchedulerConfig {
+            avpf: true,
+            ..config()
+        },
+        now,
+    );
+    // one second before the first report in AVPF sessions
+    assert_eq!(scheduler.deterministic_interval(), Duration::from_secs(1));
+    scheduler.on_report_sent(100, now);
+    scheduler.avg_rtcp_size = 100.0;
+    scheduler.set_members(2, 1, now);
+    // no minimum interval after it
+    assert_eq!(
+        scheduler.deterministic_interval(),
+        Duration::from_millis(500)
+    );
+    scheduler.on_report_sent(100, now);
+
+    // point to point sessions send early feedback right away
+    assert_eq!(scheduler.request_early_feedback(now), Some(now));
+    scheduler.on_early_feedback_sent(100, now);
+    // and not again until the next regular report, which is postponed
+    assert_eq!(scheduler.request_early_feedback(now), None);
+    assert_eq!(scheduler.next_transmission(), now + scheduler.t_rr * 2);
+
+    let now = next_report(&mut scheduler);
+    scheduler.on_report_sent(100, now);
+    assert!(scheduler.request_early_feedback(now).is_some());
+
+    // early feedback isn't allowed without AVPF
+    let mut scheduler = RtcpScheduler::new(config(), now);
+    assert_eq!(scheduler.request_early_feedback(now), None);
+}
+
+#[test]
+fn test_rtcp_scheduler_trr_interval() {
+    let now = Instant::now();
+    let mut scheduler = RtcpScheduler::new(
+        RtcpSchedulerConfig {
+            avpf: true,
+            trr_interval: Duration::from_secs(5),
+            ..config()
+        },
+        now,
+    );
+    scheduler.on_report_sent(100, now);
+
+    // regular reports are suppressed within trr-int of the last one
+    let at = next_report(&mut scheduler);
+    assert!(at - now >= Duration::from_secs(5), "{:?}", at - now);
+    scheduler.on_report_sent(100, at);
+    let next = next_report(&mut scheduler);
+    assert!(next - at >= Duration::from_secs(5), "{:?}", next - at);
+}
@@ -5,6 +5,9 @@ mod sender_test;
 use std::collections::HashMap;
 use std::time::{Duration, SystemTime};
 
+use rtcp::extended_report::{
+    DLRRReport, DLRRReportBlock, ExtendedReport, ReceiverReferenceTimeReportBlock,
+};
 use sender_stream::SenderStream;
 use tokio::sync::{mpsc, Mutex};
 use waitgroup::WaitGroup;
@@ -16,10 +19,91 @@ use crate::*;
 pub(crate) struct SenderReportInternal {
     pub(crate) interval: Duration,
     pub(crate) now: Option<FnTimeGen>,
+    pub(crate) source_description: Option<SourceDescriptionTemplate>,
+    pub(crate) scheduler: Option<util::sync::Mutex<RtcpScheduler>>,
     pub(crate) streams: Mutex<HashMap<u32, Arc<SenderStream>>>,
+    /// Middle 32 bits of the last receiver reference time of each receiver, by its ssrc,
+    /// and when it was received.
+    pub(crate) reference_times: Mutex<HashMap<u32, (u32, SystemTime)>>,
     pub(crate) close_rx: Mutex<Option<mpsc::Receiver<()>>>,
 }
 
+impl SenderReportInternal {
+    /// update_members counts the streams sent and their receiver as the members of the
+    /// session.
+    fn update_members(&self, senders: usize) {
+        if let Some(scheduler) = &self.scheduler {
+            let mut scheduler = scheduler.lock();
+            scheduler.set_members(senders + 1, senders, tokio::time::Instant::now());
+            scheduler.set_we_sent(senders > 0);
+        }
+    }
+
+    /// generate_dlrr returns the DLRR block answering the receiver reference times received,
+    /// if any.
+    async fn generate_dlrr(&self, now: SystemTime) -> Option<DLRRReportBlock> {
+        let reference_times = self.reference_times.lock().await;
+        if reference_times.is_empty() {
+            return None;
+        }
+
+        let reports = reference_times
+            .iter()
+            .map(|(ssrc, (last_rr, received))| DLRRReport {
+                ssrc: *ssrc,
+                last_rr: *last_rr,
+                dlrr: match now.duration_since(*received) {
+                    Ok(d) => (d.as_secs_f64() * 65536.0) as u32,
+                    Err(_) => 0,
+                },
+            })
+            .collect();
+        Some(DLRRReportBlock { reports })
+    }
+}
+
+pub(crate) struct SenderReportRtcpReader {
+    pub(crate) internal: Arc<SenderReportInternal>,
+    pub(crate) parent_rtcp_reader: Arc<dyn RTCPReader + Send + Sync>,
+}
+
+#[async_trait]
+impl RTCPReader for SenderReportRtcpReader {
+    async fn read(
+        &self,
+        buf: &mut [u8],
+        a: &Attributes,
+    ) -> Result<(Vec<Box<dyn rtcp::packet::Packet + Send + Sync>>, Attributes)> {
+        let (pkts, attr) = self.parent_rtcp_reader.read(buf, a).await?;
+        if let Some(scheduler) = &self.internal.scheduler {
+            scheduler.lock().on_rtcp_received(rtcp_size(&pkts));
+        }
+
+        let now = if let Some(f) = &self.internal.now {
+            f()
+        } else {
+            SystemTime::now()
+        };
+
+        for p in &pkts {
+            if let Some(xr) = p.as_any().downcast_ref::<ExtendedReport>() {
+                for report in &xr.reports {
+                    if let Some(rrt) = report
+                        .as_any()
+                        .downcast_ref::<ReceiverReferenceTimeReportBlock>()
+                    {
+                        let mut reference_times = self.internal.reference_times.lock().await;
+                        reference_times
+                            .insert(xr.sender_ssrc, ((rrt.ntp_timestamp >> 16) as u32, now));
+                    }
+                }
+            }
+        }
+
+        Ok((pkts, attr))
+    }
+}
+
 /// SenderReport interceptor generates sender reports.
 pub struct SenderReport {
     pub(crate) internal: Arc<SenderReportInternal>,
@@ -58,7 +142,7 @@ impl SenderReport {
 
         loop {
             tokio::select! {
-                _ = ticker.tick() =>{
+                _ = wait_next_report(&mut ticker, &internal.scheduler) =>{
                     // TODO(cancel safety): This branch isn't cancel safe
                     let now = if let Some(f) = &internal.now {
                         f()
@@ -69,14 +153,33 @@ impl SenderReport {
                         let m = internal.streams.lock().await;
                         m.values().cloned().collect()
                     };
+                    let dlrr = internal.generate_dlrr(now).await;
+                    let mut size = 0;
                     for stream in streams {
                         let pkt = stream.generate_report(now).await;
+                        let ssrc = pkt.ssrc;
 
+                        let mut pkts: Vec<Box<dyn rtcp::packet::Packet + Send + Sync>> =
+                            vec![Box::new(pkt)];
+                        if let Some(source_description) = &internal.source_description {
+                            pkts.push(Box::new(source_description.generate(ssrc)));
+                        }
+                        if let Some(dlrr) = &dlrr {
+                            pkts.push(Box::new(ExtendedReport {
+                                sender_ssrc: ssrc,
+                                reports: vec![Box::new(dlrr.clone())],
+                            }));
+                        }
+
+                        size += rtcp_size(&pkts);
                         let a = Attributes::new();
-                        if let Err(err) = rtcp_writer.write(&[Box::new(pkt)], &a).await{
+                        if let Err(err) = rtcp_writer.write(&pkts, &a).await{
                             log::warn!("failed sending: {}", err);
                         }
                     }
+                    if let Some(scheduler) = &internal.scheduler {
+                        scheduler.lock().on_report_sent(size, tokio::time::Instant::now());
+                    }
                 }
                 _ = close_rx.recv() =>{
                     return Ok(());
@@ -94,7 +197,10 @@ impl Interceptor for SenderReport {
         &self,
         reader: Arc<dyn RTCPReader + Send + Sync>,
     ) -> Arc<dyn RTCPReader + Send + Sync> {
-        reader
+        Arc::new(SenderReportRtcpReader {
+            internal: Arc::clone(&self.internal),
+            parent_rtcp_reader: reader,
+        })
     }
 
     /// bind_rtcp_writer lets you modify any outgoing RTCP packets. It is called once per PeerConnection. The returned method
@@ -139,6 +245,7 @@ impl Interceptor for SenderReport {
         {
             let mut streams = self.internal.streams.lock().await;
             streams.insert(info.ssrc, Arc::clone(&stream));
+            self.internal.update_members(streams.len());
         }
 
         stream
@@ -148,6 +255,7 @@ impl Interceptor for SenderReport {
     async fn unbind_local_stream(&self, info: &StreamInfo) {
         let mut streams = self.internal.streams.lock().await;
         streams.remove(&info.ssrc);
+        self.internal.update_members(streams.len());
     }
 
     /// bind_remote_stream lets you modify any incoming RTP packets. It is called once for per RemoteStream. The returned method
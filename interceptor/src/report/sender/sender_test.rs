@@ -89,6 +89,7 @@ async fn test_sender_interceptor_after_rtp_packets() -> Result<()> {
                     ..Default::default()
                 },
                 payload: Bytes::from_static(b"\x00\x00"),
+                ..Default::default()
             })
             .await?;
     }
@@ -152,6 +153,7 @@ async fn test_sender_interceptor_after_rtp_packets_overflow() -> Result<()> {
                 ..Default::default()
             },
             payload: Bytes::from_static(b"\x00\x00"),
+            ..Default::default()
         })
         .await?;
 
@@ -162,6 +164,7 @@ async fn test_sender_interceptor_after_rtp_packets_overflow() -> Result<()> {
                 ..Default::default()
             },
             payload: Bytes::from_static(b"\x00\x00"),
+            ..Default::default()
         })
         .await?;
 
@@ -172,6 +175,7 @@ async fn test_sender_interceptor_after_rtp_packets_overflow() -> Result<()> {
                 ..Default::default()
             },
             payload: Bytes::from_static(b"\x00\x00"),
+            ..Default::default()
         })
         .await?;
 
@@ -182,6 +186,7 @@ async fn test_sender_interceptor_after_rtp_packets_overflow() -> Result<()> {
                 ..Default::default()
             },
             payload: Bytes::from_static(b"\x00\x00"),
+            ..Default::default()
         })
         .await?;
 
@@ -192,6 +197,7 @@ async fn test_sender_interceptor_after_rtp_packets_overflow() -> Result<()> {
                 ..Default::default()
             },
             payload: Bytes::from_static(b"\x00\x00"),
+            ..Default::default()
         })
         .await?;
 
@@ -258,3 +264,135 @@ async fn test_stream_octet_counter_saturates_u32_from_usize() -> Result<()> {
     assert_eq!(counters.octet_count(), 0xffffffff_u32);
     Ok(())
 }
+
+#[tokio::test]
+async fn test_sender_interceptor_dlrr() -> Result<()> {
+    let mt = Arc::new(MockTime::default());
+    let time_gen = {
+        let mt = Arc::clone(&mt);
+        Arc::new(move || mt.now())
+    };
+
+    let icpr: Arc<dyn Interceptor + Send + Sync> = SenderReport::builder()
+        .with_interval(Duration::from_millis(50))
+        .with_now_fn(time_gen)
+        .build("")?;
+
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 123456,
+            clock_rate: 90000,
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    let reference_time = Utc.with_ymd_and_hms(2009, 11, 10, 23, 0, 0).unwrap();
+    mt.set_now(reference_time.into());
+    stream
+        .receive_rtcp(vec![Box::new(ExtendedReport {
+            sender_ssrc: 5000,
+            reports: vec![Box::new(ReceiverReferenceTimeReportBlock {
+                ntp_timestamp: unix2ntp(reference_time.into()),
+            })],
+        })])
+        .await;
+    stream.read_rtcp().await;
+
+    mt.set_now(Utc.with_ymd_and_hms(2009, 11, 10, 23, 0, 1).unwrap().into());
+
+    // Skip the reports written before the reference time was read, or the time moved.
+    let dlrr = loop {
+        let pkts = stream.written_rtcp().await.unwrap();
+        if pkts.len() < 2 {
+            continue;
+        }
+        assert_eq!(pkts.len(), 2);
+        let xr = pkts[1]
+            .as_any()
+            .downcast_ref::<ExtendedReport>()
+            .expect("second packet should be an extended report");
+        assert_eq!(xr.sender_ssrc, 123456);
+        let dlrr = xr.reports[0]
+            .as_any()
+            .downcast_ref::<DLRRReportBlock>()
+            .expect("extended report should have a DLRR block")
+            .clone();
+        if dlrr.reports[0].dlrr != 0 {
+            break dlrr;
+        }
+    };
+    assert_eq!(
+        dlrr,
+        DLRRReportBlock {
+            reports: vec![DLRRReport {
+                ssrc: 5000,
+                last_rr: 1861222400,
+                dlrr: 65536,
+            }],
+        }
+    );
+
+    stream.close().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sender_interceptor_source_description() -> Result<()> {
+    use rtcp::source_description::{SdesType, SourceDescription, SourceDescriptionItem};
+
+    let icpr: Arc<dyn Interceptor + Send + Sync> = SenderReport::builder()
+        .with_interval(Duration::from_millis(50))
+        .with_cname("cname".to_owned())
+        .with_source_description_items(vec![
+            SourceDescriptionItem {
+                sdes_type: SdesType::SdesTool,
+                text: Bytes::from_static(b"webrtc-rs"),
+            },
+            SourceDescriptionItem::private(b"x-recorder", b"session=42")?,
+        ])
+        .build("")?;
+
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 123456,
+            clock_rate: 90000,
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    let pkts = stream.written_rtcp().await.unwrap();
+    assert_eq!(pkts.len(), 2);
+    assert!(pkts[0]
+        .as_any()
+        .downcast_ref::<rtcp::sender_report::SenderReport>()
+        .is_some());
+    let sdes = pkts[1]
+        .as_any()
+        .downcast_ref::<SourceDescription>()
+        .expect("second packet should be a source description");
+    assert_eq!(sdes.chunks.len(), 1);
+    let chunk = &sdes.chunks[0];
+    assert_eq!(chunk.source, 123456);
+    assert_eq!(chunk.items[0].sdes_type, SdesType::SdesCname);
+    assert_eq!(chunk.text(SdesType::SdesCname).unwrap(), "cname");
+    assert_eq!(chunk.text(SdesType::SdesTool).unwrap(), "webrtc-rs");
+    assert_eq!(
+        chunk.items[2].private_extension(),
+        Some((
+            Bytes::from_static(b"x-recorder"),
+            Bytes::from_static(b"session=42")
+        ))
+    );
+
+    // the reports are valid compound packets
+    let compound = rtcp::compound_packet::CompoundPacket(pkts);
+    compound.validate()?;
+
+    stream.close().await?;
+
+    Ok(())
+}
@@ -0,0 +1,243 @@
+#[cfg(test)]
+mod rtt_test;
+
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use rtcp::extended_report::{
+    DLRRReport, DLRRReportBlock, ExtendedReport, ReceiverReferenceTimeReportBlock,
+};
+use rtcp::receiver_report::ReceiverReport;
+use rtcp::reception_report::ReceptionReport;
+use rtcp::sender_report::SenderReport;
+use rtp::extension::abs_send_time_extension::unix2ntp;
+
+/// Gain of a new measurement in the smoothed round trip time, the one of RFC 6298.
+const RTT_SMOOTHING_GAIN: f64 = 1.0 / 8.0;
+
+/// ntp_short returns the middle 32 bits of a 64 bit NTP timestamp, in which the LSR and
+/// LRR fields of the reports echo the timestamps received.
+pub fn ntp_short(ntp_time: u64) -> u32 {
+    (ntp_time >> 16) as u32
+}
+
+/// ntp_short_to_duration converts a delay in units of 1/65536 seconds, as the DLSR and
+/// DLRR fields of the reports, to a Duration.
+pub fn ntp_short_to_duration(delay: u32) -> Duration {
+    Duration::from_nanos((delay as u64 * 1_000_000_000) >> 16)
+}
+
+/// duration_to_ntp_short converts a Duration to a delay in units of 1/65536 seconds, as
+/// the DLSR and DLRR fields of the reports. It saturates past 65536 seconds.
+pub fn duration_to_ntp_short(delay: Duration) -> u32 {
+    ((delay.as_nanos() << 16) / 1_000_000_000).min(u32::MAX as u128) as u32
+}
+
+/// round_trip_time returns the round trip time a report received at now tells, from the
+/// timestamp it echoes and the delay since it was received, all in the middle 32 bits of
+/// NTP timestamps. It returns None when the report echoes no timestamp, or when the delay
+/// exceeds the time elapsed since the timestamp was sent.
+///
+/// The timestamps wrap around every 18 hours, which the computation is modulo of:
+///
+/// ```text
+/// A     0xb710:8000 (46864.500 s)
+/// DLSR -0x0005:4000 (    5.250 s)
+/// LSR  -0xb705:2000 (46853.125 s)
+/// -------------------------------
+/// delay 0x0006:2000 (    6.125 s)
+/// ```
+///
+/// ## Specifications
+///
+/// * [RFC 3550 §6.4.1]
+/// * [RFC 3611 §4.5]
+///
+/// [RFC 3550 §6.4.1]: https://tools.ietf.org/html/rfc3550#section-6.4.1
+/// [RFC 3611 §4.5]: https://tools.ietf.org/html/rfc3611#section-4.5
+pub fn round_trip_time(now: u32, last_report: u32, delay: u32) -> Option<Duration> {
+    if last_report == 0 {
+        return None;
+    }
+    let rtt = now.wrapping_sub(last_report).wrapping_sub(delay);
+    if rtt > i32::MAX as u32 {
+        // negative
+        return None;
+    }
+    Some(ntp_short_to_duration(rtt))
+}
+
+/// RttEstimate is the round trip time measured with a remote.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RttEstimate {
+    /// Last round trip time measured.
+    pub latest: Duration,
+    /// Round trip time smoothed over the measurements.
+    pub smoothed: Duration,
+    /// Lowest round trip time measured.
+    pub min: Duration,
+    /// Number of measurements.
+    pub measurements: u64,
+}
+
+impl RttEstimate {
+    /// new returns the estimate of a first measurement.
+    pub fn new(rtt: Duration) -> Self {
+        RttEstimate {
+            latest: rtt,
+            smoothed: rtt,
+            min: rtt,
+            measurements: 1,
+        }
+    }
+
+    /// update records a new measurement.
+    pub fn update(&mut self, rtt: Duration) {
+        self.latest = rtt;
+        self.smoothed =
+            self.smoothed.mul_f64(1.0 - RTT_SMOOTHING_GAIN) + rtt.mul_f64(RTT_SMOOTHING_GAIN);
+        self.min = self.min.min(rtt);
+        self.measurements += 1;
+    }
+}
+
+/// RttTracker tracks the timestamps of the reports exchanged with the remotes, and the round
+/// trip times they tell of each SSRC.
+///
+/// A sender measures the round trip time with the LSR and DLSR fields of the reception
+/// reports on its streams it receives. A receiver measures it with the DLRR blocks replying to
+/// its receiver reference times, which are estimates of the SSRC of the remote sending them.
+/// The tracker also remembers the reports received, to fill in the reports sent back.
+#[derive(Debug, Default, Clone)]
+pub struct RttTracker {
+    estimates: HashMap<u32, RttEstimate>,
+    /// NTP timestamps of the last SenderReport of each SSRC, and when it was received.
+    sender_reports: HashMap<u32, (u32, SystemTime)>,
+    /// NTP timestamps of the last receiver reference time of each SSRC, and when it was
+    /// received.
+    reference_times: HashMap<u32, (u32, SystemTime)>,
+}
+
+impl RttTracker {
+    /// new returns an empty RttTracker.
+    pub fn new() -> Self {
+        RttTracker::default()
+    }
+
+    /// estimate returns the round trip time measured for ssrc, if any.
+    pub fn estimate(&self, ssrc: u32) -> Option<RttEstimate> {
+        self.estimates.get(&ssrc).copied()
+    }
+
+    /// remove forgets ssrc, when its stream is removed.
+    pub fn remove(&mut self, ssrc: u32) {
+        self.estimates.remove(&ssrc);
+        self.sender_reports.remove(&ssrc);
+        self.reference_times.remove(&ssrc);
+    }
+
+    /// on_sender_report_received records the SenderReport of ssrc received at now.
+    pub fn on_sender_report_received(&mut self, ssrc: u32, ntp_time: u64, now: SystemTime) {
+        self.sender_reports.insert(ssrc, (ntp_short(ntp_time), now));
+    }
+
+    /// on_reference_time_received records the receiver reference time of ssrc received at now.
+    pub fn on_reference_time_received(&mut self, ssrc: u32, ntp_time: u64, now: SystemTime) {
+        self.reference_times
+            .insert(ssrc, (ntp_short(ntp_time), now));
+    }
+
+    /// last_sender_report returns the LSR and DLSR fields of a reception report on ssrc sent
+    /// at now, which are zero until a SenderReport of ssrc is received.
+    pub fn last_sender_report(&self, ssrc: u32, now: SystemTime) -> (u32, u32) {
+        match self.sender_reports.get(&ssrc) {
+            Some(&(last_sender_report, received_at)) => (
+                last_sender_report,
+                duration_to_ntp_short(now.duration_since(received_at).unwrap_or_default()),
+            ),
+            None => (0, 0),
+        }
+    }
+
+    /// dlrr_report returns the DLRR sub-block replying at now to the last receiver reference
+    /// time of ssrc, if any was received.
+    pub fn dlrr_report(&self, ssrc: u32, now: SystemTime) -> Option<DLRRReport> {
+        self.reference_times
+            .get(&ssrc)
+            .map(|&(last_rr, received_at)| DLRRReport {
+                ssrc,
+                last_rr,
+                dlrr: duration_to_ntp_short(now.duration_since(received_at).unwrap_or_default()),
+            })
+    }
+
+    /// on_reception_report records the LSR and DLSR fields of a reception report on ssrc,
+    /// received at now, and returns the round trip time they tell.
+    pub fn on_reception_report(
+        &mut self,
+        ssrc: u32,
+        last_sender_report: u32,
+        delay: u32,
+        now: SystemTime,
+    ) -> Option<Duration> {
+        let rtt = round_trip_time(ntp_short(unix2ntp(now)), last_sender_report, delay)?;
+        self.record(ssrc, rtt);
+        Some(rtt)
+    }
+
+    /// on_dlrr records a DLRR sub-block sent by ssrc in reply to a receiver reference time,
+    /// received at now, and returns the round trip time it tells.
+    pub fn on_dlrr(
+        &mut self,
+        ssrc: u32,
+        last_rr: u32,
+        dlrr: u32,
+        now: SystemTime,
+    ) -> Option<Duration> {
+        let rtt = round_trip_time(ntp_short(unix2ntp(now)), last_rr, dlrr)?;
+        self.record(ssrc, rtt);
+        Some(rtt)
+    }
+
+    /// process_rtcp records the reports and the reference times of a batch of RTCP packets
+    /// received at now.
+    pub fn process_rtcp(
+        &mut self,
+        pkts: &[Box<dyn rtcp::packet::Packet + Send + Sync>],
+        now: SystemTime,
+    ) {
+        for p in pkts {
+            let any = p.as_any();
+            if let Some(sr) = any.downcast_ref::<SenderReport>() {
+                self.on_sender_report_received(sr.ssrc, sr.ntp_time, now);
+                self.process_reception_reports(&sr.reports, now);
+            } else if let Some(rr) = any.downcast_ref::<ReceiverReport>() {
+                self.process_reception_reports(&rr.reports, now);
+            } else if let Some(xr) = any.downcast_ref::<ExtendedReport>() {
+                for report in &xr.reports {
+                    let any = report.as_any();
+                    if let Some(rrt) = any.downcast_ref::<ReceiverReferenceTimeReportBlock>() {
+                        self.on_reference_time_received(xr.sender_ssrc, rrt.ntp_timestamp, now);
+                    } else if let Some(dlrr) = any.downcast_ref::<DLRRReportBlock>() {
+                        for r in &dlrr.reports {
+                            self.on_dlrr(xr.sender_ssrc, r.last_rr, r.dlrr, now);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn process_reception_reports(&mut self, reports: &[ReceptionReport], now: SystemTime) {
+        for r in reports {
+            self.on_reception_report(r.ssrc, r.last_sender_report, r.delay, now);
+        }
+    }
+
+    fn record(&mut self, ssrc: u32, rtt: Duration) {
+        self.estimates
+            .entry(ssrc)
+            .and_modify(|e| e.update(rtt))
+            .or_insert_with(|| RttEstimate::new(rtt));
+    }
+}
@@ -0,0 +1,138 @@
+use super::*;
+
+#[test]
+fn test_round_trip_time() {
+    let tests = vec![
+        // RFC 3550 example
+        (0xb710_8000, 0xb705_2000, 0x0005_4000, Some(6125)),
+        // the timestamps wrapped around between the report and its reply
+        (0x0000_8000, 0xffff_c000, 0x0000_4000, Some(500)),
+        // no SenderReport received yet
+        (0xb710_8000, 0, 0, None),
+        // the delay exceeds the time elapsed
+        (0xb710_8000, 0xb710_0000, 0x0001_0000, None),
+    ];
+
+    for (now, last_report, delay, expected_ms) in tests {
+        assert_eq!(
+            round_trip_time(now, last_report, delay).map(|rtt| rtt.as_millis()),
+            expected_ms,
+            "now={now:#x} last_report={last_report:#x} delay={delay:#x}"
+        );
+    }
+}
+
+#[test]
+fn test_ntp_short_duration() {
+    assert_eq!(
+        ntp_short_to_duration(0x0001_8000),
+        Duration::from_millis(1500)
+    );
+    assert_eq!(
+        duration_to_ntp_short(Duration::from_millis(1500)),
+        0x0001_8000
+    );
+    assert_eq!(duration_to_ntp_short(Duration::from_secs(70_000)), u32::MAX);
+}
+
+#[test]
+fn test_rtt_estimate() {
+    let mut estimate = RttEstimate::new(Duration::from_millis(100));
+    estimate.update(Duration::from_millis(180));
+    assert_eq!(estimate.latest, Duration::from_millis(180));
+    assert_eq!(estimate.smoothed, Duration::from_millis(110));
+    assert_eq!(estimate.min, Duration::from_millis(100));
+    assert_eq!(estimate.measurements, 2);
+}
+
+#[test]
+fn test_rtt_tracker_reception_report() {
+    let mut tracker = RttTracker::new();
+    let sent_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let lsr = ntp_short(unix2ntp(sent_at));
+
+    // the remote held the SenderReport for 50ms, and replied 250ms after it was sent
+    let now = sent_at + Duration::from_millis(250);
+    let pkts: Vec<Box<dyn rtcp::packet::Packet + Send + Sync>> = vec![Box::new(ReceiverReport {
+        ssrc: 2,
+        reports: vec![ReceptionReport {
+            ssrc: 1,
+            last_sender_report: lsr,
+            delay: duration_to_ntp_short(Duration::from_millis(50)),
+            ..Default::default()
+        }],
+        ..Default::default()
+    })];
+    tracker.process_rtcp(&pkts, now);
+
+    let estimate = tracker.estimate(1).unwrap();
+    assert!(
+        estimate.latest.abs_diff(Duration::from_millis(200)) < Duration::from_millis(1),
+        "{:?}",
+        estimate.latest
+    );
+    assert_eq!(estimate.measurements, 1);
+    assert!(tracker.estimate(2).is_none());
+
+    tracker.remove(1);
+    assert!(tracker.estimate(1).is_none());
+}
+
+#[test]
+fn test_rtt_tracker_reference_time() {
+    let mut tracker = RttTracker::new();
+    let sent_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let ntp_time = unix2ntp(sent_at);
+
+    assert!(tracker.dlrr_report(2, sent_at).is_none());
+    assert_eq!(tracker.last_sender_report(2, sent_at), (0, 0));
+
+    let pkts: Vec<Box<dyn rtcp::packet::Packet + Send + Sync>> = vec![
+        Box::new(SenderReport {
+            ssrc: 2,
+            ntp_time,
+            ..Default::default()
+        }),
+        Box::new(ExtendedReport {
+            sender_ssrc: 2,
+            reports: vec![Box::new(ReceiverReferenceTimeReportBlock {
+                ntp_timestamp: ntp_time,
+            })],
+        }),
+    ];
+    tracker.process_rtcp(&pkts, sent_at);
+
+    let now = sent_at + Duration::from_millis(500);
+    assert_eq!(
+        tracker.last_sender_report(2, now),
+        (ntp_short(ntp_time), 0x0000_8000)
+    );
+    assert_eq!(
+        tracker.dlrr_report(2, now),
+        Some(DLRRReport {
+            ssrc: 2,
+            last_rr: ntp_short(ntp_time),
+            dlrr: 0x0000_8000,
+        })
+    );
+
+    // replies to a receiver reference time, held 100ms by the remote
+    let pkts: Vec<Box<dyn rtcp::packet::Packet + Send + Sync>> = vec![Box::new(ExtendedReport {
+        sender_ssrc: 3,
+        reports: vec![Box::new(DLRRReportBlock {
+            reports: vec![DLRRReport {
+                ssrc: 1,
+                last_rr: ntp_short(ntp_time),
+                dlrr: duration_to_ntp_short(Duration::from_millis(100)),
+            }],
+        })],
+    })];
+    tracker.process_rtcp(&pkts, now);
+
+    let estimate = tracker.estimate(3).unwrap();
+    assert!(
+        estimate.latest.abs_diff(Duration::from_millis(400)) < Duration::from_millis(1),
+        "{:?}",
+        estimate.latest
+    );
+}
@@ -1,10 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::sync::Arc;
 use std::time::SystemTime;
 
 use async_trait::async_trait;
-use rtcp::extended_report::{DLRRReportBlock, ExtendedReport};
+use rtcp::extended_report::{DLRRReportBlock, ExtendedReport, ReceiverReferenceTimeReportBlock};
 use rtcp::payload_feedbacks::full_intra_request::FullIntraRequest;
 use rtcp::payload_feedbacks::picture_loss_indication::PictureLossIndication;
 use rtcp::receiver_report::ReceiverReport;
@@ -12,12 +12,13 @@ use rtcp::sender_report::SenderReport;
 use rtcp::transport_feedbacks::transport_layer_nack::TransportLayerNack;
 use rtp::extension::abs_send_time_extension::unix2ntp;
 use tokio::sync::{mpsc, oneshot};
-use tokio::time::Duration;
+use tokio::time::{Duration, Instant};
 use util::sync::Mutex;
 use util::MarshalSize;
 
-use super::{inbound, outbound, StatsContainer};
+use super::{inbound, outbound, Metric, MetricsSink, StatsContainer};
 use crate::error::Result;
+use crate::rtt::{ntp_short, round_trip_time};
 use crate::stream_info::StreamInfo;
 use crate::{Attributes, Interceptor, RTCPReader, RTCPWriter, RTPReader, RTPWriter};
 
@@ -45,6 +46,10 @@ enum StatsUpdate {
         header_bytes: u64,
         payload_bytes: u64,
         last_packet_timestamp: SystemTime,
+        sequence_number: u16,
+        rtp_timestamp: u32,
+        clock_rate: u32,
+        arrival: Instant,
     },
     /// Stats collected on the sending end(outbound) of an RTP stream.
     OutboundRTP {
@@ -52,6 +57,7 @@ enum StatsUpdate {
         header_bytes: u64,
         payload_bytes: u64,
         last_packet_timestamp: SystemTime,
+        sent: Instant,
     },
     /// Stats collected from received RTCP packets.
     InboundRTCP {
@@ -86,6 +92,9 @@ pub struct StatsInterceptor {
     // Wrapped RTP streams
     recv_streams: Mutex<HashMap<u32, Arc<RTPReadRecorder>>>,
     send_streams: Mutex<HashMap<u32, Arc<RTPWriteRecorder>>>,
+    // SSRCs of the receiver reference times sent, the DLRR blocks answering them measure the
+    // round trip time of the inbound streams.
+    reference_time_ssrcs: Arc<Mutex<HashSet<u32>>>,
 
     tx: mpsc::Sender<Message>,
 
@@ -95,30 +104,38 @@ pub struct StatsInterceptor {
 
 impl StatsInterceptor {
     pub fn new(id: String) -> Self {
-        let (tx, rx) = mpsc::channel(100);
-
-        tokio::spawn(run_stats_reducer(rx));
+        Self::with_time_gen_and_sink(id, SystemTime::now, None)
+    }
 
-        Self {
-            id,
-            recv_streams: Default::default(),
-            send_streams: Default::default(),
-            tx,
-            now_gen: Arc::new(SystemTime::now),
-        }
+    /// with_metrics_sink returns a stats interceptor which also hands the metrics of the
+    /// streams to sink as they are measured.
+    pub fn with_metrics_sink(id: String, sink: Arc<dyn MetricsSink + Send + Sync>) -> Self {
+        Self::with_time_gen_and_sink(id, SystemTime::now, Some(sink))
     }
 
     fn with_time_gen<F>(id: String, now_gen: F) -> Self
+    where
+        F: Fn() -> SystemTime + Send + Sync + 'static,
+    {
+        Self::with_time_gen_and_sink(id, now_gen, None)
+    }
+
+    fn with_time_gen_and_sink<F>(
+        id: String,
+        now_gen: F,
+        sink: Option<Arc<dyn MetricsSink + Send + Sync>>,
+    ) -> Self
     where
         F: Fn() -> SystemTime + Send + Sync + 'static,
     {
         let (tx, rx) = mpsc::channel(100);
-        tokio::spawn(run_stats_reducer(rx));
+        tokio::spawn(run_stats_reducer(rx, sink));
 
         Self {
             id,
             recv_streams: Default::default(),
             send_streams: Default::default(),
+            reference_time_ssrcs: Default::default(),
             tx,
             now_gen: Arc::new(now_gen),
         }
@@ -169,7 +186,10 @@ impl StatsInterceptor {
     }
 }
 
-async fn run_stats_reducer(mut rx: mpsc::Receiver<Message>) {
+async fn run_stats_reducer(
+    mut rx: mpsc::Receiver<Message>,
+    sink: Option<Arc<dyn MetricsSink + Send + Sync>>,
+) {
     let mut ssrc_stats: StatsContainer = Default::default();
     let mut cleanup_ticker = tokio::time::interval(Duration::from_secs(10));
 
@@ -183,7 +203,12 @@ async fn run_stats_reducer(mut rx: mpsc::Receiver<Message>) {
 
                 match msg {
                     Message::StatUpdate { ssrc, update } => {
-                        handle_stats_update(&mut ssrc_stats, ssrc, update);
+                        let metrics = handle_stats_update(&mut ssrc_stats, ssrc, update);
+                        if let Some(sink) = &sink {
+                            for metric in metrics {
+                                sink.record(ssrc, metric);
+                            }
+                        }
                     }
                     Message::RequestInboundSnapshot { ssrcs, chan} => {
                         let result = ssrcs
@@ -212,19 +237,37 @@ async fn run_stats_reducer(mut rx: mpsc::Receiver<Message>) {
     }
 }
 
-fn handle_stats_update(ssrc_stats: &mut StatsContainer, ssrc: u32, update: StatsUpdate) {
+/// handle_stats_update applies update to the stats of the stream ssrc, and returns the metrics
+/// measured.
+fn handle_stats_update(
+    ssrc_stats: &mut StatsContainer,
+    ssrc: u32,
+    update: StatsUpdate,
+) -> Vec<Metric> {
+    let mut metrics = vec![];
     match update {
         StatsUpdate::InboundRTP {
             packets,
             header_bytes,
             payload_bytes,
             last_packet_timestamp,
+            sequence_number,
+            rtp_timestamp,
+            clock_rate,
+            arrival,
         } => {
             let stats = ssrc_stats.get_or_create_inbound_stream_stats(ssrc);
 
             stats
                 .rtp_stats
                 .update(header_bytes, payload_bytes, packets, last_packet_timestamp);
+            metrics = stats.record_packet(
+                sequence_number,
+                rtp_timestamp,
+                clock_rate,
+                header_bytes + payload_bytes,
+                arrival,
+            );
             stats.mark_updated();
         }
         StatsUpdate::OutboundRTP {
@@ -232,11 +275,13 @@ fn handle_stats_update(ssrc_stats: &mut StatsContainer, ssrc: u32, update: Stats
             header_bytes,
             payload_bytes,
             last_packet_timestamp,
+            sent,
         } => {
             let stats = ssrc_stats.get_or_create_outbound_stream_stats(ssrc);
             stats
                 .rtp_stats
                 .update(header_bytes, payload_bytes, packets, last_packet_timestamp);
+            metrics = stats.record_packet(header_bytes + payload_bytes, sent);
             stats.mark_updated();
         }
         StatsUpdate::InboundRTCP {
@@ -271,6 +316,7 @@ fn handle_stats_update(ssrc_stats: &mut StatsContainer, ssrc: u32, update: Stats
         } => {
             let stats = ssrc_stats.get_or_create_outbound_stream_stats(ssrc);
             stats.record_remote_round_trip_time(rtt_ms);
+            metrics.extend(rtt_ms.map(Metric::OutboundRoundTripTime));
             stats.update_remote_fraction_lost(fraction_lost);
             stats.update_remote_total_lost(total_lost);
             stats.update_remote_inbound_packets_received(ext_seq_num, total_lost);
@@ -290,10 +336,12 @@ fn handle_stats_update(ssrc_stats: &mut StatsContainer, ssrc: u32, update: Stats
                 stats.record_sender_report(packets_sent, bytes_sent);
             }
             stats.record_remote_round_trip_time(rtt_ms);
+            metrics.extend(rtt_ms.map(Metric::InboundRoundTripTime));
 
             stats.mark_updated();
         }
     }
+    metrics
 }
 
 #[async_trait]
@@ -307,9 +355,13 @@ impl Interceptor for StatsInterceptor {
     ) -> Arc<dyn RTPReader + Send + Sync> {
         let mut lock = self.recv_streams.lock();
 
-        let e = lock
-            .entry(info.ssrc)
-            .or_insert_with(|| Arc::new(RTPReadRecorder::new(reader, self.tx.clone())));
+        let e = lock.entry(info.ssrc).or_insert_with(|| {
+            Arc::new(RTPReadRecorder::new(
+                reader,
+                info.clock_rate,
+                self.tx.clone(),
+            ))
+        });
 
         e.clone()
     }
@@ -358,6 +410,7 @@ impl Interceptor for StatsInterceptor {
 
         Arc::new(RTCPWriteInterceptor {
             rtcp_writer: writer,
+            reference_time_ssrcs: Arc::clone(&self.reference_time_ssrcs),
             tx: self.tx.clone(),
             now_gen: move || now(),
         })
@@ -373,6 +426,7 @@ impl Interceptor for StatsInterceptor {
 
         Arc::new(RTCPReadInterceptor {
             rtcp_reader: reader,
+            reference_time_ssrcs: Arc::clone(&self.reference_time_ssrcs),
             tx: self.tx.clone(),
             now_gen: move || now(),
         })
@@ -381,6 +435,7 @@ impl Interceptor for StatsInterceptor {
 
 pub struct RTCPReadInterceptor<F> {
     rtcp_reader: Arc<dyn RTCPReader + Send + Sync>,
+    reference_time_ssrcs: Arc<Mutex<HashSet<u32>>>,
     tx: mpsc::Sender<Message>,
     now_gen: F,
 }
@@ -398,8 +453,7 @@ where
     ) -> Result<(Vec<Box<dyn rtcp::packet::Packet + Send + Sync>>, Attributes)> {
         let (pkts, attributes) = self.rtcp_reader.read(buf, attributes).await?;
 
-        // Middle 32 bits
-        let now = (unix2ntp((self.now_gen)()) >> 16) as u32;
+        let now = ntp_short(unix2ntp((self.now_gen)()));
 
         #[derive(Default, Debug)]
         struct GenericRTCP {
@@ -452,7 +506,8 @@ where
                         let e = acc.entry(recp.ssrc).or_default();
 
                         let rtt_ms = if recp.delay != 0 {
-                            calculate_rtt_ms(now, recp.delay, recp.last_sender_report)
+                            round_trip_time(now, recp.last_sender_report, recp.delay)
+                                .map(|rtt| rtt.as_secs_f64() * 1000.0)
                         } else {
                             None
                         };
@@ -511,7 +566,14 @@ where
                     });
 
                     for dlrr in dlrrs {
-                        let e = acc.entry(dlrr.ssrc).or_default();
+                        // A DLRR answering one of our receiver reference times concerns the
+                        // stream of the remote sending it.
+                        let ssrc = if self.reference_time_ssrcs.lock().contains(&dlrr.ssrc) {
+                            xr.sender_ssrc
+                        } else {
+                            dlrr.ssrc
+                        };
+                        let e = acc.entry(ssrc).or_default();
                         let sr_e = {
                             let need_new_entry = e
                                 .sender_reports
@@ -582,7 +644,8 @@ where
             let futures = sender_reports.into_iter().map(|sr| {
                 let rtt_ms = match (sr.dlrr_last_rr, sr.dlrr_delay_rr, sr.sr_packets_sent) {
                     (Some(last_rr), Some(delay_rr), Some(_)) if last_rr != 0 && delay_rr != 0 => {
-                        calculate_rtt_ms(now, delay_rr, last_rr)
+                        round_trip_time(now, last_rr, delay_rr)
+                            .map(|rtt| rtt.as_secs_f64() * 1000.0)
                     }
                     _ => None,
                 };
@@ -609,6 +672,7 @@ where
 
 pub struct RTCPWriteInterceptor<F> {
     rtcp_writer: Arc<dyn RTCPWriter + Send + Sync>,
+    reference_time_ssrcs: Arc<Mutex<HashSet<u32>>>,
     tx: mpsc::Sender<Message>,
     now_gen: F,
 }
@@ -661,6 +725,16 @@ where
                             _ => {}
                         }
                     }
+                } else if let Some(xr) = p.as_any().downcast_ref::<ExtendedReport>() {
+                    let has_reference_time = xr.reports.iter().any(|report| {
+                        report
+                            .as_any()
+                            .downcast_ref::<ReceiverReferenceTimeReportBlock>()
+                            .is_some()
+                    });
+                    if has_reference_time {
+                        self.reference_time_ssrcs.lock().insert(xr.sender_ssrc);
+                    }
                 }
 
                 acc
@@ -705,12 +779,21 @@ where
 
 pub struct RTPReadRecorder {
     rtp_reader: Arc<dyn RTPReader + Send + Sync>,
+    clock_rate: u32,
     tx: mpsc::Sender<Message>,
 }
 
 impl RTPReadRecorder {
-    fn new(rtp_reader: Arc<dyn RTPReader + Send + Sync>, tx: mpsc::Sender<Message>) -> Self {
-        Self { rtp_reader, tx }
+    fn new(
+        rtp_reader: Arc<dyn RTPReader + Send + Sync>,
+        clock_rate: u32,
+        tx: mpsc::Sender<Message>,
+    ) -> Self {
+        Self {
+            rtp_reader,
+            clock_rate,
+            tx,
+        }
     }
 }
 
@@ -738,6 +821,10 @@ impl RTPReader for RTPReadRecorder {
                     header_bytes: pkt.header.marshal_size() as u64,
                     payload_bytes: pkt.payload.len() as u64,
                     last_packet_timestamp: SystemTime::now(),
+                    sequence_number: pkt.header.sequence_number,
+                    rtp_timestamp: pkt.header.timestamp,
+                    clock_rate: self.clock_rate,
+                    arrival: Instant::now(),
                 },
             })
             .await;
@@ -778,6 +865,7 @@ impl RTPWriter for RTPWriteRecorder {
                     header_bytes: pkt.header.marshal_size() as u64,
                     payload_bytes: pkt.payload.len() as u64,
                     last_packet_timestamp: SystemTime::now(),
+                    sent: Instant::now(),
                 },
             })
             .await;
@@ -786,40 +874,6 @@ impl RTPWriter for RTPWriteRecorder {
     }
 }
 
-/// Calculate the round trip time for a given peer as described in
-/// [RFC3550 6.4.1](https://datatracker.ietf.org/doc/html/rfc3550#section-6.4.1).
-///
-/// ## Params
-///
-/// - `now` the current middle 32 bits of an NTP timestamp for the current time.
-/// - `delay` the delay(`DLSR`) since last sender report expressed as fractions of a second in 32 bits.
-/// - `last_report` the middle 32 bits of an NTP timestamp for the most recent sender report(LSR) or Receiver Report(LRR).
-fn calculate_rtt_ms(now: u32, delay: u32, last_report: u32) -> Option<f64> {
-    // [10 Nov 1995 11:33:25.125 UTC]       [10 Nov 1995 11:33:36.5 UTC]
-    // n                 SR(n)              A=b710:8000 (46864.500 s)
-    // ---------------------------------------------------------------->
-    //                    v                 ^
-    // ntp_sec =0xb44db705 v               ^ dlsr=0x0005:4000 (    5.250s)
-    // ntp_frac=0x20000000  v             ^  lsr =0xb705:2000 (46853.125s)
-    //   (3024992005.125 s)  v           ^
-    // r                      v         ^ RR(n)
-    // ---------------------------------------------------------------->
-    //                        |<-DLSR->|
-    //                         (5.250 s)
-    //
-    // A     0xb710:8000 (46864.500 s)
-    // DLSR -0x0005:4000 (    5.250 s)
-    // LSR  -0xb705:2000 (46853.125 s)
-    // -------------------------------
-    // delay 0x0006:2000 (    6.125 s)
-
-    let rtt = now.checked_sub(delay)?.checked_sub(last_report)?;
-    let rtt_seconds = rtt >> 16;
-    let rtt_fraction = (rtt & (u16::MAX as u32)) as f64 / (u16::MAX as u32) as f64;
-
-    Some(rtt_seconds as f64 * 1000.0 + rtt_fraction * 1000.0)
-}
-
 #[cfg(test)]
 mod test {
     // Silence warning on `..Default::default()` with no effect:
@@ -840,7 +894,9 @@ mod test {
     use std::time::{Duration, SystemTime};
 
     use bytes::Bytes;
-    use rtcp::extended_report::{DLRRReport, DLRRReportBlock, ExtendedReport};
+    use rtcp::extended_report::{
+        DLRRReport, DLRRReportBlock, ExtendedReport, ReceiverReferenceTimeReportBlock,
+    };
     use rtcp::payload_feedbacks::full_intra_request::{FirEntry, FullIntraRequest};
     use rtcp::payload_feedbacks::picture_loss_indication::PictureLossIndication;
     use rtcp::receiver_report::ReceiverReport;
@@ -851,6 +907,7 @@ mod test {
     use super::StatsInterceptor;
     use crate::error::Result;
     use crate::mock::mock_stream::MockStream;
+    use crate::stats::{Metric, MetricsSink};
     use crate::stream_info::StreamInfo;
 
     #[tokio::test]
@@ -882,6 +939,7 @@ mod test {
                     ..Default::default()
                 },
                 payload: Bytes::from_static(b"\xde\xad\xbe\xef"),
+                ..Default::default()
             })
             .await;
 
@@ -897,6 +955,7 @@ mod test {
                     ..Default::default()
                 },
                 payload: Bytes::from_static(b"\xde\xad\xbe\xef\xde\xad\xbe\xef"),
+                ..Default::default()
             })
             .await;
 
@@ -907,6 +966,7 @@ mod test {
                     ..Default::default()
                 },
                 payload: Bytes::from_static(&[0x13, 0x37]),
+                ..Default::default()
             })
             .await;
 
@@ -1188,6 +1248,203 @@ mod test {
         assert_eq!(recv_snapshot.remote_reports_sent(), 2);
         assert_eq!(recv_snapshot.remote_round_trip_time_measurements(), 1);
         assert_feq!(recv_snapshot.remote_total_round_trip_time(), 6125.0);
+        assert_feq!(
+            recv_snapshot.remote_smoothed_round_trip_time().unwrap(),
+            6125.0
+        );
+        // above the highest bound of the histogram
+        let histogram = recv_snapshot.remote_round_trip_time_histogram();
+        assert_eq!(histogram.count(), 1);
+        assert_eq!(histogram.counts().last(), Some(&1));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stats_interceptor_dlrr_answering_reference_time() -> Result<()> {
+        let icpr: Arc<_> = Arc::new(StatsInterceptor::with_time_gen("Hello".to_owned(), || {
+            // 10 Nov 1995 11:33:36.5 UTC
+            SystemTime::UNIX_EPOCH + Duration::from_secs_f64(816003216.5)
+        }));
+
+        let recv_stream = MockStream::new(
+            &StreamInfo {
+                ssrc: 123456,
+                ..Default::default()
+            },
+            icpr.clone(),
+        )
+        .await;
+
+        recv_stream
+            .write_rtcp(&[Box::new(ExtendedReport {
+                sender_ssrc: 5000,
+                reports: vec![Box::new(ReceiverReferenceTimeReportBlock {
+                    ntp_timestamp: 0xb44db705_20000000,
+                })],
+            })])
+            .await
+            .expect("Failed to write RTCP packets for recv_stream");
+
+        // The remote answers the reference time of 5000 with the SR of its stream.
+        recv_stream
+            .receive_rtcp(vec![
+                Box::new(SenderReport {
+                    ssrc: 123456,
+                    ntp_time: 12345,
+                    packet_count: 52,
+                    octet_count: 8172,
+                    reports: vec![],
+                    ..Default::default()
+                }),
+                Box::new(ExtendedReport {
+                    sender_ssrc: 123456,
+                    reports: vec![Box::new(DLRRReportBlock {
+                        reports: vec![DLRRReport {
+                            ssrc: 5000,
+                            last_rr: 0xb705_2000,
+                            dlrr: 0x0005_4000,
+                        }],
+                    })],
+                }),
+            ])
+            .await;
+        let _ = recv_stream.read_rtcp().await.expect("read_rtcp failed");
+
+        let snapshots = icpr.fetch_inbound_stats(vec![123456, 5000]).await;
+        let recv_snapshot = snapshots[0]
+            .as_ref()
+            .expect("Stats should exist for ssrc: 123456");
+        let rtt_ms = recv_snapshot
+            .remote_round_trip_time()
+            .expect("After receiving the DLRR we should have a round trip time");
+        assert_feq!(rtt_ms, 6125.0);
+        assert!(
+            snapshots[1].is_none(),
+            "No stats for the reference time ssrc"
+        );
+
+        Ok(())
+    }
+
+    #[derive(Default)]
+    struct RecordingSink {
+        metrics: util::sync::Mutex<Vec<(u32, Metric)>>,
+    }
+
+    impl MetricsSink for RecordingSink {
+        fn record(&self, ssrc: u32, metric: Metric) {
+            self.metrics.lock().push((ssrc, metric));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stats_interceptor_extended_metrics() -> Result<()> {
+        let sink = Arc::new(RecordingSink::default());
+        let icpr: Arc<_> = Arc::new(StatsInterceptor::with_metrics_sink(
+            "Hello".to_owned(),
+            sink.clone(),
+        ));
+
+        let recv_stream = MockStream::new(
+            &StreamInfo {
+                ssrc: 123456,
+                clock_rate: 90_000,
+                ..Default::default()
+            },
+            icpr.clone(),
+        )
+        .await;
+        let send_stream = MockStream::new(
+            &StreamInfo {
+                ssrc: 234567,
+                ..Default::default()
+            },
+            icpr.clone(),
+        )
+        .await;
+
+        // a packet of 112 bytes every 50ms, with 3 lost after the tenth one
+        for i in 0..=20u16 {
+            let sequence_number = if i < 10 { i } else { i + 3 };
+            recv_stream
+                .receive_rtp(rtp::packet::Packet {
+                    header: rtp::header::Header {
+                        ssrc: 123456,
+                        sequence_number,
+                        timestamp: i as u32 * 4500,
+                        ..Default::default()
+                    },
+                    payload: vec![0; 100].into(),
+                    ..Default::default()
+                })
+                .await;
+            let _ = recv_stream.read_rtp().await;
+            send_stream
+                .write_rtp(&rtp::packet::Packet {
+                    header: rtp::header::Header {
+                        ssrc: 234567,
+                        sequence_number: i,
+                        ..Default::default()
+                    },
+                    payload: vec![0; 100].into(),
+                    ..Default::default()
+                })
+                .await?;
+            tokio::time::advance(Duration::from_millis(50)).await;
+        }
+        // 30ms late
+        tokio::time::advance(Duration::from_millis(30)).await;
+        recv_stream
+            .receive_rtp(rtp::packet::Packet {
+                header: rtp::header::Header {
+                    ssrc: 123456,
+                    sequence_number: 24,
+                    timestamp: 21 * 4500,
+                    ..Default::default()
+                },
+                payload: vec![0; 100].into(),
+                ..Default::default()
+            })
+            .await;
+        let _ = recv_stream.read_rtp().await;
+
+        let snapshots = icpr.fetch_inbound_stats(vec![123456]).await;
+        let recv_snapshot = snapshots[0]
+            .as_ref()
+            .expect("Stats should exist for ssrc: 123456");
+        assert_feq!(recv_snapshot.bitrate_received(), 20.0 * 112.0 * 8.0);
+        assert_feq!(recv_snapshot.packet_rate_received(), 20.0);
+        assert_feq!(recv_snapshot.jitter(), 0.03 / 16.0, 0.0001);
+        assert_eq!(recv_snapshot.jitter_histogram().count(), 1);
+        let bursts = recv_snapshot.burst_loss_histogram();
+        assert_eq!((bursts.count(), bursts.sum()), (1, 3.0));
+        assert_eq!(bursts.counts()[2], 1);
+
+        let snapshots = icpr.fetch_outbound_stats(vec![234567]).await;
+        let send_snapshot = snapshots[0]
+            .as_ref()
+            .expect("Outbound Stats should exist for ssrc: 234567");
+        assert_feq!(send_snapshot.bitrate_sent(), 20.0 * 112.0 * 8.0);
+        assert_feq!(send_snapshot.packet_rate_sent(), 20.0);
+
+        let metrics = sink.metrics.lock().clone();
+        assert!(metrics.contains(&(123456, Metric::InboundBurstLoss(3))));
+        assert!(metrics.contains(&(123456, Metric::InboundJitter(0.0))));
+        assert!(metrics.contains(&(
+            123456,
+            Metric::InboundRate {
+                bitrate: 20.0 * 112.0 * 8.0,
+                packet_rate: 20.0
+            }
+        )));
+        assert!(metrics.contains(&(
+            234567,
+            Metric::OutboundRate {
+                bitrate: 20.0 * 112.0 * 8.0,
+                packet_rate: 20.0
+            }
+        )));
 
         Ok(())
     }
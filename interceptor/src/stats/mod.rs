@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::SystemTime;
 
-use tokio::time::Duration;
+use tokio::time::{Duration, Instant};
 
 mod interceptor;
 
@@ -12,13 +12,157 @@ pub fn make_stats_interceptor(id: &str) -> Arc<StatsInterceptor> {
     Arc::new(StatsInterceptor::new(id.to_owned()))
 }
 
+/// make_stats_interceptor_with_metrics_sink returns a stats interceptor which also hands the
+/// metrics of the streams to sink as they are measured.
+pub fn make_stats_interceptor_with_metrics_sink(
+    id: &str,
+    sink: Arc<dyn MetricsSink + Send + Sync>,
+) -> Arc<StatsInterceptor> {
+    Arc::new(StatsInterceptor::with_metrics_sink(id.to_owned(), sink))
+}
+
+/// Upper bounds of the buckets of the histograms of the interarrival jitter, in seconds.
+const JITTER_HISTOGRAM_BOUNDS: &[f64] = &[0.001, 0.002, 0.005, 0.01, 0.02, 0.05, 0.1, 0.2];
+/// Upper bounds of the buckets of the histograms of the round trip time, in ms.
+const ROUND_TRIP_TIME_HISTOGRAM_BOUNDS: &[f64] =
+    &[10.0, 25.0, 50.0, 100.0, 200.0, 400.0, 800.0, 1600.0];
+/// Upper bounds of the buckets of the histograms of the lengths of the bursts of packets lost.
+const BURST_LOSS_HISTOGRAM_BOUNDS: &[f64] = &[1.0, 2.0, 3.0, 5.0, 10.0, 20.0, 50.0];
+
+/// Metric is a measurement of an RTP stream, handed to the [`MetricsSink`] of the stats
+/// interceptor as it is taken.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Metric {
+    /// The interarrival jitter of an inbound stream in seconds, each second.
+    InboundJitter(f64),
+    /// The bitrate in bits per second and the packet rate of an inbound stream, over each
+    /// second.
+    InboundRate { bitrate: f64, packet_rate: f64 },
+    /// The bitrate in bits per second and the packet rate of an outbound stream, over each
+    /// second.
+    OutboundRate { bitrate: f64, packet_rate: f64 },
+    /// A round trip time in ms measured for an inbound stream, from the XR DLRR blocks of the
+    /// remote.
+    InboundRoundTripTime(f64),
+    /// A round trip time in ms measured for an outbound stream, from the RRs of the remote.
+    OutboundRoundTripTime(f64),
+    /// The length of a burst of packets of an inbound stream lost, from a gap of its sequence
+    /// numbers.
+    InboundBurstLoss(u64),
+}
+
+/// MetricsSink receives the metrics of the RTP streams of the stats interceptor, e.g. to
+/// export them to a monitoring system.
+pub trait MetricsSink {
+    /// record is called with each metric of the stream ssrc as it is measured. It shouldn't
+    /// block, as the stats are collected in the meantime.
+    fn record(&self, ssrc: u32, metric: Metric);
+}
+
+/// Histogram counts the values recorded in buckets of increasing upper bounds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    bounds: &'static [f64],
+    /// Counts of the buckets, and of the values above the highest bound last.
+    counts: Vec<u64>,
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            counts: vec![0; bounds.len() + 1],
+            count: 0,
+            sum: 0.0,
+        }
+    }
+
+    fn record(&mut self, value: f64) {
+        let bucket = self
+            .bounds
+            .iter()
+            .position(|&bound| value <= bound)
+            .unwrap_or(self.bounds.len());
+        self.counts[bucket] += 1;
+        self.count += 1;
+        self.sum += value;
+    }
+
+    /// The upper bounds of the buckets, inclusive.
+    pub fn bounds(&self) -> &[f64] {
+        self.bounds
+    }
+
+    /// The counts of the values of each bucket, followed by the count of the values above the
+    /// highest bound.
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// The number of values recorded.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The sum of the values recorded.
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// The mean of the values recorded, if any.
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+}
+
+/// RateTracker measures the bitrate and the packet rate of a stream over each second.
+#[derive(Debug, Clone, Default)]
+struct RateTracker {
+    window_start: Option<Instant>,
+    bytes: u64,
+    packets: u64,
+    /// The bitrate in bits per second over the last whole second.
+    bitrate: f64,
+    /// The packets per second over the last whole second.
+    packet_rate: f64,
+}
+
+impl RateTracker {
+    /// update records a packet of bytes at now, and returns the rates once a second is over.
+    fn update(&mut self, bytes: u64, now: Instant) -> Option<(f64, f64)> {
+        let window_start = *self.window_start.get_or_insert(now);
+        let elapsed = now.duration_since(window_start);
+        let rates = if elapsed >= Duration::from_secs(1) {
+            let seconds = elapsed.as_secs_f64();
+            self.bitrate = self.bytes as f64 * 8.0 / seconds;
+            self.packet_rate = self.packets as f64 / seconds;
+            self.window_start = Some(now);
+            self.bytes = 0;
+            self.packets = 0;
+            Some((self.bitrate, self.packet_rate))
+        } else {
+            None
+        };
+        self.bytes += bytes;
+        self.packets += 1;
+        rates
+    }
+}
+
 /// Types related to inbound RTP streams.
 mod inbound {
     use std::time::SystemTime;
 
+    use rtp::sequence::{SequenceTracker, SequenceUpdate};
     use tokio::time::{Duration, Instant};
 
-    use super::{RTCPStats, RTPStats};
+    use super::{
+        Histogram, Metric, RTCPStats, RTPStats, RateTracker, BURST_LOSS_HISTOGRAM_BOUNDS,
+        JITTER_HISTOGRAM_BOUNDS, ROUND_TRIP_TIME_HISTOGRAM_BOUNDS,
+    };
+    use crate::rtt::RttEstimate;
 
     #[derive(Debug, Clone)]
     /// Stats collected for an inbound RTP stream.
@@ -51,6 +195,30 @@ mod inbound {
 
         /// The total number of measurements of the remote round trip time.
         remote_round_trip_time_measurements: u64,
+
+        /// The round trip time measured with the remote, smoothed over the measurements.
+        remote_rtt_estimate: Option<RttEstimate>,
+
+        /// The round trip times measured with the remote in ms.
+        remote_round_trip_time_histogram: Histogram,
+
+        /// The arrival time and the RTP timestamp of the last packet received.
+        last_arrival: Option<(Instant, u32)>,
+
+        /// The interarrival jitter in seconds, as defined in RFC 3550 section 6.4.1.
+        jitter: f64,
+
+        /// The interarrival jitter sampled each second.
+        jitter_histogram: Histogram,
+
+        /// The sequence numbers received.
+        sequence: SequenceTracker,
+
+        /// The lengths of the bursts of packets lost.
+        burst_loss_histogram: Histogram,
+
+        /// Rates at which packets are received.
+        rates: RateTracker,
     }
 
     impl Default for StreamStats {
@@ -65,6 +233,14 @@ mod inbound {
                 remote_round_trip_time: None,
                 remote_total_round_trip_time: 0.0,
                 remote_round_trip_time_measurements: 0,
+                remote_rtt_estimate: None,
+                remote_round_trip_time_histogram: Histogram::new(ROUND_TRIP_TIME_HISTOGRAM_BOUNDS),
+                last_arrival: None,
+                jitter: 0.0,
+                jitter_histogram: Histogram::new(JITTER_HISTOGRAM_BOUNDS),
+                sequence: SequenceTracker::new(),
+                burst_loss_histogram: Histogram::new(BURST_LOSS_HISTOGRAM_BOUNDS),
+                rates: RateTracker::default(),
             }
         }
     }
@@ -88,6 +264,49 @@ mod inbound {
             self.remote_bytes_sent = bytes_sent;
         }
 
+        /// record_packet records the arrival of a packet of bytes, updating the jitter, the
+        /// bursts lost and the rates, and returns the metrics measured.
+        pub(super) fn record_packet(
+            &mut self,
+            sequence_number: u16,
+            rtp_timestamp: u32,
+            clock_rate: u32,
+            bytes: u64,
+            arrival: Instant,
+        ) -> Vec<Metric> {
+            let mut metrics = vec![];
+
+            // a jump of the sequence numbers is a restart of the stream, not a loss
+            if let SequenceUpdate::InOrder { lost, .. } = self.sequence.update(sequence_number) {
+                if lost > 0 {
+                    self.burst_loss_histogram.record(lost as f64);
+                    metrics.push(Metric::InboundBurstLoss(lost));
+                }
+            }
+
+            // RFC 3550 section 6.4.1, from the difference of the transit times of the packets
+            if let (Some((last_arrival, last_timestamp)), true) =
+                (self.last_arrival, clock_rate != 0)
+            {
+                let timestamp_diff = rtp_timestamp.wrapping_sub(last_timestamp) as i32;
+                let d = arrival.duration_since(last_arrival).as_secs_f64()
+                    - timestamp_diff as f64 / clock_rate as f64;
+                self.jitter += (d.abs() - self.jitter) / 16.0;
+            }
+            self.last_arrival = Some((arrival, rtp_timestamp));
+
+            if let Some((bitrate, packet_rate)) = self.rates.update(bytes, arrival) {
+                self.jitter_histogram.record(self.jitter);
+                metrics.push(Metric::InboundJitter(self.jitter));
+                metrics.push(Metric::InboundRate {
+                    bitrate,
+                    packet_rate,
+                });
+            }
+
+            metrics
+        }
+
         pub(super) fn record_remote_round_trip_time(&mut self, round_trip_time: Option<f64>) {
             // Store the latest measurement, even if it's None.
             self.remote_round_trip_time = round_trip_time;
@@ -96,6 +315,14 @@ mod inbound {
                 // Only if we have a valid measurement do we update the totals
                 self.remote_total_round_trip_time += rtt;
                 self.remote_round_trip_time_measurements += 1;
+
+                self.remote_round_trip_time_histogram.record(rtt);
+
+                let rtt = Duration::from_secs_f64(rtt / 1000.0);
+                match &mut self.remote_rtt_estimate {
+                    Some(estimate) => estimate.update(rtt),
+                    None => self.remote_rtt_estimate = Some(RttEstimate::new(rtt)),
+                }
             }
         }
     }
@@ -128,6 +355,28 @@ mod inbound {
 
         /// The total number of measurements of the remote round trip time.
         remote_round_trip_time_measurements: u64,
+
+        /// The remote round trip time in ms, smoothed over the measurements. [`None`] if no round
+        /// trip time has been derived yet.
+        remote_smoothed_round_trip_time: Option<f64>,
+
+        /// The round trip times measured with the remote in ms.
+        remote_round_trip_time_histogram: Histogram,
+
+        /// The interarrival jitter in seconds.
+        jitter: f64,
+
+        /// The interarrival jitter sampled each second.
+        jitter_histogram: Histogram,
+
+        /// The lengths of the bursts of packets lost.
+        burst_loss_histogram: Histogram,
+
+        /// The bitrate received over the last whole second, in bits per second.
+        bitrate: f64,
+
+        /// The packets received per second over the last whole second.
+        packet_rate: f64,
     }
 
     impl StatsSnapshot {
@@ -181,6 +430,41 @@ mod inbound {
         pub fn remote_round_trip_time_measurements(&self) -> u64 {
             self.remote_round_trip_time_measurements
         }
+
+        pub fn remote_smoothed_round_trip_time(&self) -> Option<f64> {
+            self.remote_smoothed_round_trip_time
+        }
+
+        /// The round trip times measured with the remote in ms.
+        pub fn remote_round_trip_time_histogram(&self) -> &Histogram {
+            &self.remote_round_trip_time_histogram
+        }
+
+        /// The interarrival jitter in seconds, as defined in RFC 3550 section 6.4.1.
+        pub fn jitter(&self) -> f64 {
+            self.jitter
+        }
+
+        /// The interarrival jitter in seconds, sampled each second.
+        pub fn jitter_histogram(&self) -> &Histogram {
+            &self.jitter_histogram
+        }
+
+        /// The lengths of the bursts of packets lost, from the gaps of the sequence numbers
+        /// received.
+        pub fn burst_loss_histogram(&self) -> &Histogram {
+            &self.burst_loss_histogram
+        }
+
+        /// The bitrate received over the last whole second, in bits per second.
+        pub fn bitrate_received(&self) -> f64 {
+            self.bitrate
+        }
+
+        /// The packets received per second over the last whole second.
+        pub fn packet_rate_received(&self) -> f64 {
+            self.packet_rate
+        }
     }
 
     impl From<&StreamStats> for StatsSnapshot {
@@ -195,6 +479,17 @@ mod inbound {
                 remote_total_round_trip_time: stream_stats.remote_total_round_trip_time,
                 remote_round_trip_time_measurements: stream_stats
                     .remote_round_trip_time_measurements,
+                remote_smoothed_round_trip_time: stream_stats
+                    .remote_rtt_estimate
+                    .map(|estimate| estimate.smoothed.as_secs_f64() * 1000.0),
+                remote_round_trip_time_histogram: stream_stats
+                    .remote_round_trip_time_histogram
+                    .clone(),
+                jitter: stream_stats.jitter,
+                jitter_histogram: stream_stats.jitter_histogram.clone(),
+                burst_loss_histogram: stream_stats.burst_loss_histogram.clone(),
+                bitrate: stream_stats.rates.bitrate,
+                packet_rate: stream_stats.rates.packet_rate,
             }
         }
     }
@@ -206,7 +501,10 @@ mod outbound {
 
     use tokio::time::{Duration, Instant};
 
-    use super::{RTCPStats, RTPStats};
+    use super::{
+        Histogram, Metric, RTCPStats, RTPStats, RateTracker, ROUND_TRIP_TIME_HISTOGRAM_BOUNDS,
+    };
+    use crate::rtt::RttEstimate;
 
     #[derive(Debug, Clone)]
     /// Stats collected for an outbound RTP stream.
@@ -246,8 +544,17 @@ mod outbound {
         /// The total number of measurements of the remote round trip time.
         remote_round_trip_time_measurements: u64,
 
+        /// The round trip time measured with the remote, smoothed over the measurements.
+        remote_rtt_estimate: Option<RttEstimate>,
+
         /// The latest fraction lost value from RR.
         remote_fraction_lost: Option<u8>,
+
+        /// The round trip times measured with the remote in ms.
+        remote_round_trip_time_histogram: Histogram,
+
+        /// Rates at which packets are sent.
+        rates: RateTracker,
     }
 
     impl Default for StreamStats {
@@ -263,7 +570,10 @@ mod outbound {
                 remote_round_trip_time: None,
                 remote_total_round_trip_time: 0.0,
                 remote_round_trip_time_measurements: 0,
+                remote_rtt_estimate: None,
                 remote_fraction_lost: None,
+                remote_round_trip_time_histogram: Histogram::new(ROUND_TRIP_TIME_HISTOGRAM_BOUNDS),
+                rates: RateTracker::default(),
             }
         }
     }
@@ -301,6 +611,18 @@ mod outbound {
             }
         }
 
+        /// record_packet records the sending of a packet of bytes, updating the rates, and
+        /// returns the metrics measured.
+        pub(super) fn record_packet(&mut self, bytes: u64, now: Instant) -> Vec<Metric> {
+            match self.rates.update(bytes, now) {
+                Some((bitrate, packet_rate)) => vec![Metric::OutboundRate {
+                    bitrate,
+                    packet_rate,
+                }],
+                None => vec![],
+            }
+        }
+
         #[inline(always)]
         pub(super) fn record_sr_ext_seq_num(&mut self, seq_num: u32) {
             // Only record the initial value
@@ -317,6 +639,14 @@ mod outbound {
                 // Only if we have a valid measurement do we update the totals
                 self.remote_total_round_trip_time += rtt;
                 self.remote_round_trip_time_measurements += 1;
+
+                self.remote_round_trip_time_histogram.record(rtt);
+
+                let rtt = Duration::from_secs_f64(rtt / 1000.0);
+                match &mut self.remote_rtt_estimate {
+                    Some(estimate) => estimate.update(rtt),
+                    None => self.remote_rtt_estimate = Some(RttEstimate::new(rtt)),
+                }
             }
         }
 
@@ -361,9 +691,22 @@ mod outbound {
         /// The total number of measurements of the remote round trip time.
         remote_round_trip_time_measurements: u64,
 
+        /// The remote round trip time in ms, smoothed over the measurements. [`None`] if no round
+        /// trip time has been derived yet.
+        remote_smoothed_round_trip_time: Option<f64>,
+
         /// The fraction of packets lost reported for this stream.
         /// Calculated as defined in [RFC3550](https://www.rfc-editor.org/rfc/rfc3550) section 6.4.1 and Appendix A.3.
         remote_fraction_lost: Option<f64>,
+
+        /// The round trip times measured with the remote in ms.
+        remote_round_trip_time_histogram: Histogram,
+
+        /// The bitrate sent over the last whole second, in bits per second.
+        bitrate: f64,
+
+        /// The packets sent per second over the last whole second.
+        packet_rate: f64,
     }
 
     impl StatsSnapshot {
@@ -425,10 +768,31 @@ mod outbound {
             self.remote_round_trip_time_measurements
         }
 
+        /// The RTT in ms smoothed over the measurements, if enough data is available to measure
+        /// it.
+        pub fn remote_smoothed_round_trip_time(&self) -> Option<f64> {
+            self.remote_smoothed_round_trip_time
+        }
+
         /// The latest fraction lost value from the remote or None if it hasn't been reported yet.
         pub fn remote_fraction_lost(&self) -> Option<f64> {
             self.remote_fraction_lost
         }
+
+        /// The RTTs measured in ms.
+        pub fn remote_round_trip_time_histogram(&self) -> &Histogram {
+            &self.remote_round_trip_time_histogram
+        }
+
+        /// The bitrate sent over the last whole second, in bits per second.
+        pub fn bitrate_sent(&self) -> f64 {
+            self.bitrate
+        }
+
+        /// The packets sent per second over the last whole second.
+        pub fn packet_rate_sent(&self) -> f64 {
+            self.packet_rate
+        }
     }
 
     impl From<&StreamStats> for StatsSnapshot {
@@ -443,9 +807,17 @@ mod outbound {
                 remote_total_round_trip_time: stream_stats.remote_total_round_trip_time,
                 remote_round_trip_time_measurements: stream_stats
                     .remote_round_trip_time_measurements,
+                remote_smoothed_round_trip_time: stream_stats
+                    .remote_rtt_estimate
+                    .map(|estimate| estimate.smoothed.as_secs_f64() * 1000.0),
                 remote_fraction_lost: stream_stats
                     .remote_fraction_lost
                     .map(|fraction| (fraction as f64) / (u8::MAX as f64)),
+                remote_round_trip_time_histogram: stream_stats
+                    .remote_round_trip_time_histogram
+                    .clone(),
+                bitrate: stream_stats.rates.bitrate,
+                packet_rate: stream_stats.rates.packet_rate,
             }
         }
     }
@@ -603,6 +975,38 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_histogram() {
+        let mut histogram = Histogram::new(&[1.0, 5.0]);
+        assert_eq!(histogram.mean(), None);
+
+        for value in [0.5, 1.0, 3.0, 10.0] {
+            histogram.record(value);
+        }
+
+        assert_eq!(histogram.counts(), &[2, 1, 1]);
+        assert_eq!((histogram.count(), histogram.sum()), (4, 14.5));
+        assert_eq!(histogram.mean(), Some(14.5 / 4.0));
+    }
+
+    #[test]
+    fn test_inbound_burst_loss() {
+        let mut stats = inbound::StreamStats::default();
+        let now = Instant::now();
+        let mut record = |sequence_number| stats.record_packet(sequence_number, 0, 0, 100, now);
+
+        // across a roll over
+        assert!(record(65_534).is_empty());
+        assert_eq!(record(1), vec![Metric::InboundBurstLoss(2)]);
+        // late and duplicate packets don't count
+        assert!(record(0).is_empty());
+        assert!(record(1).is_empty());
+        // nor does a jump restarting the stream
+        assert!(record(20_000).is_empty());
+        assert!(record(20_001).is_empty());
+        assert_eq!(record(20_003), vec![Metric::InboundBurstLoss(1)]);
+    }
+
     #[test]
     fn test_rtp_stats_send_sync() {
         fn test_send_sync<T: Send + Sync>() {}
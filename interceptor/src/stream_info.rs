@@ -20,6 +20,14 @@ pub struct StreamInfo {
     pub channels: u16,
     pub sdp_fmtp_line: String,
     pub rtcp_feedback: Vec<RTCPFeedback>,
+    /// SSRC of the RTX stream the packets of the stream are retransmitted on, or 0 if none.
+    pub ssrc_retransmission: u32,
+    /// Payload type of the RTX stream the packets of the stream are retransmitted on.
+    pub payload_type_retransmission: u8,
+    /// SSRC of the FEC stream protecting the packets of the stream, or 0 if none.
+    pub ssrc_forward_error_correction: u32,
+    /// Payload type of the FEC stream protecting the packets of the stream.
+    pub payload_type_forward_error_correction: u8,
 }
 
 /// RTCPFeedback signals the connection to use additional RTCP packet types.
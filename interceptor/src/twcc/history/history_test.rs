@@ -0,0 +1,132 @@
+use super::*;
+use crate::twcc::Recorder;
+
+fn feedback(recorder: &mut Recorder) -> TransportLayerCc {
+    let pkts = recorder.build_feedback_packet();
+    pkts[0]
+        .as_any()
+        .downcast_ref::<TransportLayerCc>()
+        .expect("A feedback")
+        .clone()
+}
+
+#[test]
+fn test_send_history_feedback() {
+    let mut history = SendHistory::default();
+    for (i, sequence) in [65_534u16, 65_535, 0, 1].iter().enumerate() {
+        history.on_packet_sent(*sequence, i as i64 * 1_000, 100 + i, None);
+    }
+    assert_eq!(history.last_sequence_number(), Some(65_537));
+    assert_eq!(history.bytes(), 406);
+
+    let mut recorder = Recorder::new(2);
+    recorder.record(1, 65_534, 10_000);
+    recorder.record(1, 1, 12_000);
+    let feedback = feedback(&mut recorder);
+
+    let parsed = history.on_feedback(&feedback);
+    assert_eq!(parsed.sender_ssrc, 2);
+    assert_eq!(parsed.base_sequence_number, 65_534);
+    assert_eq!(
+        parsed
+            .packets
+            .iter()
+            .map(|p| (p.sequence_number, p.status, p.delta_us))
+            .collect::<Vec<_>>(),
+        vec![
+            (65_534, PacketStatus::ReceivedSmallDelta, Some(10_000)),
+            (65_535, PacketStatus::NotReceived, None),
+            (65_536, PacketStatus::NotReceived, None),
+            (65_537, PacketStatus::ReceivedSmallDelta, Some(2_000)),
+        ]
+    );
+    assert_eq!(
+        parsed.packets[3].arrival_us,
+        Some(parsed.reference_time_us + 12_000)
+    );
+    assert!(!parsed.packets[1].status.is_received());
+
+    let results = parsed.packet_results();
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[0].send_us, 0);
+    assert_eq!(results[0].size, 100);
+    assert_eq!(results[1].arrival_us, None);
+    assert_eq!(results[2].arrival_us, None);
+    let (first, last) = (
+        results[0].arrival_us.unwrap(),
+        results[3].arrival_us.unwrap(),
+    );
+    assert_eq!(last - first, 2_000);
+
+    // reported packets are forgotten
+    assert!(history.is_empty());
+    assert_eq!(history.bytes(), 0);
+    let parsed = history.on_feedback(&feedback);
+    assert_eq!(parsed.packets.len(), 4);
+    assert!(parsed.packet_results().is_empty());
+}
+
+#[test]
+fn test_send_history_expires() {
+    let mut history = SendHistory::default();
+    history.on_packet_sent(0, 0, 100, None);
+    history.on_packet_sent(1, DEFAULT_MAX_AGE.as_micros() as i64, 100, None);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history.bytes(), 100);
+    assert!(history.packets.contains_key(&1));
+}
+
+#[test]
+fn test_send_history_max_bytes() {
+    let mut history = SendHistory::new(DEFAULT_MAX_AGE, 1_000);
+    for i in 0..20u16 {
+        history.on_packet_sent(i, i as i64, 300, None);
+    }
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.bytes(), 900);
+    assert_eq!(
+        history.packets.keys().copied().collect::<Vec<_>>(),
+        vec![17, 18, 19]
+    );
+
+    // the last packet sent is kept, even larger than the max bytes
+    history.on_packet_sent(20, 20, 2_000, None);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history.bytes(), 2_000);
+}
+
+#[test]
+fn test_send_history_wraps() {
+    let mut history = SendHistory::new(DEFAULT_MAX_AGE, usize::MAX);
+    // more than three wraparounds, with a packet every 10us
+    let count = 200_000i64;
+    for i in 0..count {
+        let sequence_number = history.on_packet_sent(i as u16, i * 10, 100, None);
+        assert_eq!(sequence_number, i);
+    }
+    // the packets sent in the last 2 seconds are kept
+    assert_eq!(history.len(), 200_000);
+
+    // a feedback for packets far behind the last sent, across a wraparound
+    let mut recorder = Recorder::new(2);
+    let reported = count - 60_000;
+    recorder.record(1, reported as u16, 10_000);
+    recorder.record(1, (reported + 1) as u16, 11_000);
+    let parsed = history.on_feedback(&feedback(&mut recorder));
+    assert_eq!(parsed.base_sequence_number, reported);
+    assert_eq!(
+        parsed
+            .packets
+            .iter()
+            .map(|p| p.sent.map(|s| s.sequence_number))
+            .collect::<Vec<_>>(),
+        vec![Some(reported), Some(reported + 1)]
+    );
+
+    // reordered packets keep their place
+    let mut history = SendHistory::default();
+    history.on_packet_sent(65_535, 0, 100, None);
+    assert_eq!(history.on_packet_sent(1, 1, 100, None), 65_537);
+    assert_eq!(history.on_packet_sent(0, 2, 100, None), 65_536);
+    assert_eq!(history.last_sequence_number(), Some(65_537));
+}
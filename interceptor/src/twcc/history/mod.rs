@@ -0,0 +1,276 @@
+#[cfg(test)]
+mod history_test;
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use rtcp::transport_feedbacks::transport_layer_cc::{
+    PacketStatusChunk, SymbolTypeTcc, TransportLayerCc,
+};
+
+use crate::gcc::PacketResult;
+
+/// How long the packets sent are kept by default if no feedback reports them.
+pub(crate) const DEFAULT_MAX_AGE: Duration = Duration::from_secs(2);
+/// Bytes of the packets sent kept at most by default, those of 2 seconds at 40 Mbit/s.
+pub(crate) const DEFAULT_MAX_BYTES: usize = 10_000_000;
+
+/// SentPacket is a packet sent, kept by a SendHistory until a feedback reports it.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct SentPacket {
+    /// Transport wide sequence number of the packet, unwrapped.
+    pub sequence_number: i64,
+    /// Send time of the packet, in microseconds.
+    pub send_us: i64,
+    /// Size of the packet, in bytes.
+    pub size: usize,
+    /// Id of the probe cluster the packet was sent for, if any.
+    pub probe_cluster_id: Option<usize>,
+}
+
+/// PacketStatus is the status a feedback reports for a packet.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PacketStatus {
+    NotReceived,
+    /// Received, with a delta of less than 255.75ms since the previous packet received.
+    ReceivedSmallDelta,
+    /// Received, with a larger or negative delta since the previous packet received.
+    ReceivedLargeDelta,
+    /// Received, but when is unknown.
+    ReceivedWithoutDelta,
+}
+
+impl PacketStatus {
+    /// is_received returns whether the packet was received.
+    pub fn is_received(&self) -> bool {
+        *self != PacketStatus::NotReceived
+    }
+}
+
+/// PacketFeedback is the status of a packet reported by a feedback.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PacketFeedback {
+    /// Transport wide sequence number of the packet, unwrapped.
+    pub sequence_number: i64,
+    pub status: PacketStatus,
+    /// Arrival time of the packet since the arrival of the previous packet received, in
+    /// microseconds, if received with a delta.
+    pub delta_us: Option<i64>,
+    /// Arrival time of the packet on the clock of the receiver, in microseconds, if received
+    /// with a delta.
+    pub arrival_us: Option<i64>,
+    /// The packet as sent, if it was still in the history.
+    pub sent: Option<SentPacket>,
+}
+
+/// TwccFeedback is a transport wide congestion control feedback, parsed and matched against
+/// the packets sent.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TwccFeedback {
+    pub sender_ssrc: u32,
+    pub media_ssrc: u32,
+    /// Count of the feedback, incremented by the receiver for each feedback sent.
+    pub feedback_count: u8,
+    /// Transport wide sequence number of the first packet reported, unwrapped.
+    pub base_sequence_number: i64,
+    /// Time the arrival times are relative to on the clock of the receiver, in microseconds.
+    pub reference_time_us: i64,
+    /// Status of each packet reported, in sequence number order.
+    pub packets: Vec<PacketFeedback>,
+}
+
+impl TwccFeedback {
+    /// packet_results returns the results of the packets the feedback reports, which were
+    /// still in the history, in the order they were sent. The packets received without a
+    /// delta are left out, as their arrival time is unknown.
+    pub fn packet_results(&self) -> Vec<PacketResult> {
+        self.packets
+            .iter()
+            .filter(|p| p.status != PacketStatus::ReceivedWithoutDelta)
+            .filter_map(|p| {
+                p.sent.map(|sent| PacketResult {
+                    send_us: sent.send_us,
+                    size: sent.size,
+                    arrival_us: p.arrival_us,
+                    probe_cluster_id: sent.probe_cluster_id,
+                })
+            })
+            .collect()
+    }
+}
+
+/// SendHistory keeps the packets sent until a feedback reports them, by unwrapped transport
+/// wide sequence number, so that the feedbacks can be matched against them. It keeps the
+/// packets sent within the max age, of the max bytes at most, the oldest being forgotten
+/// first.
+///
+/// The 16 bit sequence numbers of the packets sent are unwrapped against the last one, so that
+/// they keep increasing across the wraparounds of long sessions, and those of the feedbacks
+/// against the last sent, as they can only report the packets sent before.
+#[derive(Debug)]
+pub struct SendHistory {
+    max_age_us: i64,
+    max_bytes: usize,
+    packets: BTreeMap<i64, SentPacket>,
+    bytes: usize,
+    last_sequence_number: Option<i64>,
+}
+
+impl Default for SendHistory {
+    fn default() -> Self {
+        SendHistory::new(DEFAULT_MAX_AGE, DEFAULT_MAX_BYTES)
+    }
+}
+
+impl SendHistory {
+    /// new returns a history keeping the packets sent within max_age, of max_bytes at most.
+    pub fn new(max_age: Duration, max_bytes: usize) -> Self {
+        SendHistory {
+            max_age_us: max_age.as_micros() as i64,
+            max_bytes,
+            packets: BTreeMap::new(),
+            bytes: 0,
+            last_sequence_number: None,
+        }
+    }
+
+    /// len returns the number of packets kept.
+    pub fn len(&self) -> usize {
+        self.packets.len()
+    }
+
+    /// is_empty returns whether no packet is kept.
+    pub fn is_empty(&self) -> bool {
+        self.packets.is_empty()
+    }
+
+    /// bytes returns the bytes of the packets kept, those sent and not reported yet.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// last_sequence_number returns the unwrapped sequence number of the last packet sent.
+    pub fn last_sequence_number(&self) -> Option<i64> {
+        self.last_sequence_number
+    }
+
+    /// on_packet_sent records a packet of size bytes sent at send_us microseconds, and returns
+    /// its unwrapped sequence number.
+    pub fn on_packet_sent(
+        &mut self,
+        sequence_number: u16,
+        send_us: i64,
+        size: usize,
+        probe_cluster_id: Option<usize>,
+    ) -> i64 {
+        let sequence_number = match self.last_sequence_number {
+            None => sequence_number as i64,
+            Some(last) => last + sequence_number.wrapping_sub(last as u16) as i16 as i64,
+        };
+        if self
+            .last_sequence_number
+            .is_none_or(|last| sequence_number > last)
+        {
+            self.last_sequence_number = Some(sequence_number);
+        }
+
+        let packet = SentPacket {
+            sequence_number,
+            send_us,
+            size,
+            probe_cluster_id,
+        };
+        if let Some(replaced) = self.packets.insert(sequence_number, packet) {
+            self.bytes -= replaced.size;
+        }
+        self.bytes += size;
+
+        while let Some((&first, oldest)) = self.packets.iter().next() {
+            let expired = send_us - oldest.send_us >= self.max_age_us;
+            // the last packet sent is kept whatever its size
+            let oversized = self.bytes > self.max_bytes && first != sequence_number;
+            if !expired && !oversized {
+                break;
+            }
+            self.bytes -= oldest.size;
+            self.packets.remove(&first);
+        }
+        sequence_number
+    }
+
+    /// on_feedback parses a feedback, matches the packets it reports with the packets sent,
+    /// and forgets them.
+    pub fn on_feedback(&mut self, feedback: &TransportLayerCc) -> TwccFeedback {
+        let symbols = feedback
+            .packet_chunks
+            .iter()
+            .flat_map(|chunk| match chunk {
+                PacketStatusChunk::RunLengthChunk(c) => {
+                    vec![c.packet_status_symbol; c.run_length as usize]
+                }
+                PacketStatusChunk::StatusVectorChunk(c) => c.symbol_list.clone(),
+            })
+            .take(feedback.packet_status_count as usize);
+
+        let base_sequence_number = self.unwrap_reported(feedback.base_sequence_number);
+        let reference_time_us = feedback.reference_time as i64 * 64_000;
+        let mut arrival_us = reference_time_us;
+        let mut deltas = feedback.recv_deltas.iter();
+        let mut packets = vec![];
+        for (i, symbol) in symbols.enumerate() {
+            let (status, delta_us) = match symbol {
+                SymbolTypeTcc::PacketNotReceived => (PacketStatus::NotReceived, None),
+                SymbolTypeTcc::PacketReceivedSmallDelta
+                | SymbolTypeTcc::PacketReceivedLargeDelta => {
+                    let status = if symbol == SymbolTypeTcc::PacketReceivedSmallDelta {
+                        PacketStatus::ReceivedSmallDelta
+                    } else {
+                        PacketStatus::ReceivedLargeDelta
+                    };
+                    match deltas.next() {
+                        Some(delta) => (status, Some(delta.delta)),
+                        // a feedback truncated
+                        None => break,
+                    }
+                }
+                SymbolTypeTcc::PacketReceivedWithoutDelta => {
+                    (PacketStatus::ReceivedWithoutDelta, None)
+                }
+            };
+            if let Some(delta_us) = delta_us {
+                arrival_us += delta_us;
+            }
+
+            let sequence_number = base_sequence_number + i as i64;
+            let sent = self.packets.remove(&sequence_number);
+            if let Some(sent) = &sent {
+                self.bytes -= sent.size;
+            }
+            packets.push(PacketFeedback {
+                sequence_number,
+                status,
+                delta_us,
+                arrival_us: delta_us.map(|_| arrival_us),
+                sent,
+            });
+        }
+
+        TwccFeedback {
+            sender_ssrc: feedback.sender_ssrc,
+            media_ssrc: feedback.media_ssrc,
+            feedback_count: feedback.fb_pkt_count,
+            base_sequence_number,
+            reference_time_us,
+            packets,
+        }
+    }
+
+    /// unwrap_reported returns the unwrapped sequence number of a packet a feedback reports,
+    /// the closest to the last packet sent not after it.
+    fn unwrap_reported(&self, sequence_number: u16) -> i64 {
+        match self.last_sequence_number {
+            None => sequence_number as i64,
+            Some(last) => last - (last as u16).wrapping_sub(sequence_number) as i64,
+        }
+    }
+}
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod twcc_test;
 
+pub mod history;
 pub mod receiver;
 pub mod sender;
 
@@ -30,6 +31,11 @@ pub struct Recorder {
     sender_ssrc: u32,
     media_ssrc: u32,
     fb_pkt_cnt: u8,
+
+    max_packets_per_feedback: Option<u16>,
+    reorder_window: Option<u32>,
+    /// Sequence number following the last one reported.
+    next_sequence_number: Option<u32>,
 }
 
 impl Recorder {
@@ -42,17 +48,37 @@ impl Recorder {
         }
     }
 
+    /// set_max_packets_per_feedback sets the largest number of packets, received or lost, a
+    /// feedback packet reports. Longer runs of packets are split over several of them.
+    pub fn set_max_packets_per_feedback(&mut self, max_packets: u16) {
+        self.max_packets_per_feedback = Some(max_packets.max(1));
+    }
+
+    /// set_reorder_window sets how many sequence numbers before the last one reported a
+    /// packet arriving late may be and still get reported. Older packets are dropped. By
+    /// default all late packets are reported, in the feedback following their arrival.
+    pub fn set_reorder_window(&mut self, window: u16) {
+        self.reorder_window = Some(window as u32);
+    }
+
     /// record marks a packet with media_ssrc and a transport wide sequence number sequence_number as received at arrival_time.
     pub fn record(&mut self, media_ssrc: u32, sequence_number: u16, arrival_time: i64) {
         self.media_ssrc = media_ssrc;
         if sequence_number < 0x0fff && self.last_sequence_number > 0xf000 {
             self.cycles += 1 << 16;
         }
+        let sequence_number_ext = self.cycles | sequence_number as u32;
+        self.last_sequence_number = sequence_number;
+
+        if let (Some(window), Some(next)) = (self.reorder_window, self.next_sequence_number) {
+            if sequence_number_ext.saturating_add(window) < next {
+                return;
+            }
+        }
         self.received_packets.push(PktInfo {
-            sequence_number: self.cycles | sequence_number as u32,
+            sequence_number: sequence_number_ext,
             arrival_time,
         });
-        self.last_sequence_number = sequence_number;
     }
 
     /// build_feedback_packet creates a new RTCP packet containing a TWCC feedback report.
@@ -74,6 +100,21 @@ impl Recorder {
 
         let mut pkts = vec![];
         for pkt in &self.received_packets {
+            if let Some(max_packets) = self.max_packets_per_feedback {
+                let count = feedback.sequence_number_count as u32
+                    + (pkt.sequence_number as u16).wrapping_sub(feedback.next_sequence_number)
+                        as u32
+                    + 1;
+                if feedback.sequence_number_count > 0 && count > max_packets as u32 {
+                    let p: Box<dyn rtcp::packet::Packet + Send + Sync> =
+                        Box::new(feedback.get_rtcp());
+                    pkts.push(p);
+                    feedback = Feedback::new(self.sender_ssrc, self.media_ssrc, self.fb_pkt_cnt);
+                    self.fb_pkt_cnt = self.fb_pkt_cnt.wrapping_add(1);
+                    feedback.set_base((pkt.sequence_number & 0xffff) as u16, pkt.arrival_time);
+                }
+            }
+
             let built =
                 feedback.add_received((pkt.sequence_number & 0xffff) as u16, pkt.arrival_time);
             if !built {
@@ -84,6 +125,12 @@ impl Recorder {
                 feedback.add_received((pkt.sequence_number & 0xffff) as u16, pkt.arrival_time);
             }
         }
+        if let Some(last) = self.received_packets.last() {
+            let next = last.sequence_number + 1;
+            if self.next_sequence_number.is_none_or(|n| n < next) {
+                self.next_sequence_number = Some(next);
+            }
+        }
         self.received_packets.clear();
         let p: Box<dyn rtcp::packet::Packet + Send + Sync> = Box::new(feedback.get_rtcp());
         pkts.push(p);
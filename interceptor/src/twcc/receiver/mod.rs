@@ -15,10 +15,15 @@ use crate::twcc::sender::TRANSPORT_CC_URI;
 use crate::twcc::Recorder;
 use crate::*;
 
+type FnFeedbackTrigger = Arc<dyn Fn(&rtp::header::Header) -> bool + Send + Sync>;
+
 /// ReceiverBuilder is a InterceptorBuilder for a SenderInterceptor
 #[derive(Default)]
 pub struct ReceiverBuilder {
     interval: Option<Duration>,
+    max_packets_per_feedback: Option<u16>,
+    reorder_window: Option<u16>,
+    feedback_trigger: Option<FnFeedbackTrigger>,
 }
 
 impl ReceiverBuilder {
@@ -27,6 +32,33 @@ impl ReceiverBuilder {
         self.interval = Some(interval);
         self
     }
+
+    /// with_max_packets_per_feedback sets the largest number of packets a feedback packet
+    /// reports, see [`Recorder::set_max_packets_per_feedback`].
+    pub fn with_max_packets_per_feedback(mut self, max_packets: u16) -> ReceiverBuilder {
+        self.max_packets_per_feedback = Some(max_packets);
+        self
+    }
+
+    /// with_reorder_window sets how late a packet may be reported, see
+    /// [`Recorder::set_reorder_window`].
+    pub fn with_reorder_window(mut self, window: u16) -> ReceiverBuilder {
+        self.reorder_window = Some(window);
+        self
+    }
+
+    /// with_feedback_trigger sets a function deciding from the header of each packet received
+    /// whether to send feedback right away, instead of waiting for the interval.
+    pub fn with_feedback_trigger(mut self, trigger: FnFeedbackTrigger) -> ReceiverBuilder {
+        self.feedback_trigger = Some(trigger);
+        self
+    }
+
+    /// with_feedback_on_marker sends feedback right away on receiving a packet with the marker
+    /// bit set, e.g. the last packet of a video frame.
+    pub fn with_feedback_on_marker(self) -> ReceiverBuilder {
+        self.with_feedback_trigger(Arc::new(|header| header.marker))
+    }
 }
 
 impl InterceptorBuilder for ReceiverBuilder {
@@ -40,6 +72,9 @@ impl InterceptorBuilder for ReceiverBuilder {
                 } else {
                     Duration::from_millis(100)
                 },
+                max_packets_per_feedback: self.max_packets_per_feedback,
+                reorder_window: self.reorder_window,
+                feedback_trigger: self.feedback_trigger.clone(),
                 recorder: Mutex::new(Recorder::default()),
                 packet_chan_rx: Mutex::new(Some(packet_chan_rx)),
                 streams: Mutex::new(HashMap::new()),
@@ -62,12 +97,44 @@ struct Packet {
 
 struct ReceiverInternal {
     interval: Duration,
+    max_packets_per_feedback: Option<u16>,
+    reorder_window: Option<u16>,
+    feedback_trigger: Option<FnFeedbackTrigger>,
     recorder: Mutex<Recorder>,
     packet_chan_rx: Mutex<Option<mpsc::Receiver<Packet>>>,
     streams: Mutex<HashMap<u32, Arc<ReceiverStream>>>,
     close_rx: Mutex<Option<mpsc::Receiver<()>>>,
 }
 
+impl ReceiverInternal {
+    fn new_recorder(&self, sender_ssrc: u32) -> Recorder {
+        let mut recorder = Recorder::new(sender_ssrc);
+        if let Some(max_packets) = self.max_packets_per_feedback {
+            recorder.set_max_packets_per_feedback(max_packets);
+        }
+        if let Some(window) = self.reorder_window {
+            recorder.set_reorder_window(window);
+        }
+        recorder
+    }
+
+    async fn send_feedback(&self, rtcp_writer: &Arc<dyn RTCPWriter + Send + Sync>, a: &Attributes) {
+        // build and send twcc
+        let pkts = {
+            let mut recorder = self.recorder.lock().await;
+            recorder.build_feedback_packet()
+        };
+
+        if pkts.is_empty() {
+            return;
+        }
+
+        if let Err(err) = rtcp_writer.write(&pkts, a).await {
+            log::error!("rtcp_writer.write got err: {}", err);
+        }
+    }
+}
+
 /// Receiver sends transport-wide congestion control reports as specified in:
 /// <https://datatracker.ietf.org/doc/html/draft-holmer-rmcat-transport-wide-cc-extensions-01>
 pub struct Receiver {
@@ -123,24 +190,23 @@ impl Receiver {
                 }
                 p = packet_chan_rx.recv() => {
                     if let Some(p) = p {
-                        let mut recorder = internal.recorder.lock().await;
-                        recorder.record(p.ssrc, p.sequence_number, p.arrival_time);
+                        {
+                            let mut recorder = internal.recorder.lock().await;
+                            recorder.record(p.ssrc, p.sequence_number, p.arrival_time);
+                        }
+
+                        let triggered = internal
+                            .feedback_trigger
+                            .as_ref()
+                            .is_some_and(|trigger| trigger(&p.hdr));
+                        if triggered {
+                            internal.send_feedback(&rtcp_writer, &a).await;
+                            ticker.reset();
+                        }
                     }
                 }
                 _ = ticker.tick() =>{
-                    // build and send twcc
-                    let pkts = {
-                        let mut recorder = internal.recorder.lock().await;
-                        recorder.build_feedback_packet()
-                    };
-
-                    if pkts.is_empty() {
-                        continue;
-                    }
-
-                    if let Err(err) = rtcp_writer.write(&pkts, &a).await{
-                        log::error!("rtcp_writer.write got err: {}", err);
-                    }
+                    internal.send_feedback(&rtcp_writer, &a).await;
                 }
             }
         }
@@ -170,7 +236,7 @@ impl Interceptor for Receiver {
 
         {
             let mut recorder = self.internal.recorder.lock().await;
-            *recorder = Recorder::new(rand::random::<u32>());
+            *recorder = self.internal.new_recorder(rand::random::<u32>());
         }
 
         let mut w = {
@@ -359,3 +359,56 @@ async fn test_twcc_receiver_interceptor_overflow() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_twcc_receiver_interceptor_feedback_on_marker() -> Result<()> {
+    let builder = Receiver::builder()
+        .with_interval(Duration::from_secs(60))
+        .with_feedback_on_marker();
+    let icpr = builder.build("")?;
+
+    let stream = MockStream::new(
+        &StreamInfo {
+            ssrc: 1,
+            rtp_header_extensions: vec![RTPHeaderExtension {
+                uri: TRANSPORT_CC_URI.to_owned(),
+                id: 1,
+                ..Default::default()
+            }],
+            ..Default::default()
+        },
+        icpr,
+    )
+    .await;
+
+    for i in 0..3 {
+        let mut hdr = rtp::header::Header {
+            marker: i == 2,
+            ..Default::default()
+        };
+        let tcc = TransportCcExtension {
+            transport_sequence: i,
+        }
+        .marshal()?;
+        hdr.set_extension(1, tcc)?;
+        stream
+            .receive_rtp(rtp::packet::Packet {
+                header: hdr,
+                ..Default::default()
+            })
+            .await;
+    }
+
+    let pkts = tokio::time::timeout(Duration::from_secs(1), stream.written_rtcp())
+        .await
+        .expect("feedback should be sent on the marker packet")
+        .unwrap();
+    assert_eq!(pkts.len(), 1);
+    let cc = pkts[0].as_any().downcast_ref::<TransportLayerCc>().unwrap();
+    assert_eq!(cc.base_sequence_number, 0);
+    assert_eq!(cc.packet_status_count, 3);
+
+    stream.close().await?;
+
+    Ok(())
+}
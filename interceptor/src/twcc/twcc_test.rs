@@ -563,3 +563,81 @@ fn test_build_feedback_packet_rolling() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_build_feedback_packet_max_packets() -> Result<()> {
+    let mut r = Recorder::new(5000);
+    r.set_max_packets_per_feedback(4);
+
+    let mut arrival_time = SCALE_FACTOR_REFERENCE_TIME;
+    let sequence_numbers = [0, 1, 2, 5, 6, 7];
+    let arrival_times: Vec<i64> = sequence_numbers
+        .iter()
+        .map(|_| increase_time(&mut arrival_time, TYPE_TCC_DELTA_SCALE_FACTOR))
+        .collect();
+    add_run(&mut r, &sequence_numbers, &arrival_times);
+
+    let rtcp_packets = r.build_feedback_packet();
+    let feedbacks: Vec<&TransportLayerCc> = rtcp_packets
+        .iter()
+        .map(|p| p.as_any().downcast_ref::<TransportLayerCc>().unwrap())
+        .collect();
+    assert_eq!(
+        feedbacks
+            .iter()
+            .map(|f| (
+                f.fb_pkt_count,
+                f.base_sequence_number,
+                f.packet_status_count
+            ))
+            .collect::<Vec<_>>(),
+        vec![(0, 0, 3), (1, 5, 3)]
+    );
+    assert_eq!(feedbacks[1].recv_deltas.len(), 3);
+
+    marshal_all(&rtcp_packets[..])?;
+
+    Ok(())
+}
+
+#[test]
+fn test_build_feedback_packet_reorder_window() -> Result<()> {
+    let mut r = Recorder::new(5000);
+    r.set_reorder_window(3);
+
+    let mut arrival_time = SCALE_FACTOR_REFERENCE_TIME;
+    add_run(
+        &mut r,
+        &[0, 2, 3, 9, 10],
+        &[
+            arrival_time,
+            increase_time(&mut arrival_time, TYPE_TCC_DELTA_SCALE_FACTOR),
+            increase_time(&mut arrival_time, TYPE_TCC_DELTA_SCALE_FACTOR),
+            increase_time(&mut arrival_time, TYPE_TCC_DELTA_SCALE_FACTOR),
+            increase_time(&mut arrival_time, TYPE_TCC_DELTA_SCALE_FACTOR),
+        ],
+    );
+    assert_eq!(r.build_feedback_packet().len(), 1);
+
+    // 1 is too late to be reported, 8 is within the window.
+    add_run(
+        &mut r,
+        &[1, 8, 11],
+        &[
+            increase_time(&mut arrival_time, TYPE_TCC_DELTA_SCALE_FACTOR),
+            increase_time(&mut arrival_time, TYPE_TCC_DELTA_SCALE_FACTOR),
+            increase_time(&mut arrival_time, TYPE_TCC_DELTA_SCALE_FACTOR),
+        ],
+    );
+    let rtcp_packets = r.build_feedback_packet();
+    assert_eq!(rtcp_packets.len(), 1);
+    let tcc = rtcp_packets[0]
+        .as_any()
+        .downcast_ref::<TransportLayerCc>()
+        .unwrap();
+    assert_eq!(tcc.base_sequence_number, 8);
+    assert_eq!(tcc.packet_status_count, 4);
+    assert_eq!(tcc.recv_deltas.len(), 2);
+
+    Ok(())
+}
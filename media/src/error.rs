@@ -38,6 +38,9 @@ pub enum Error {
     #[error("expected and actual checksum do not match")]
     ErrChecksumMismatch,
 
+    #[error("no track to write to the WebM file")]
+    ErrNoWebmTrack,
+
     #[error("data is not a H264 bitstream")]
     ErrDataIsNotH264Stream,
     #[error("Io EOF")]
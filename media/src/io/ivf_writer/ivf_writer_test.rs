@@ -28,6 +28,7 @@ fn test_ivf_writer_add_packet_and_close() -> Result<()> {
             extensions_padding: 0,
         },
         payload: raw_valid_pkt.slice(20..),
+        ..Default::default()
     };
     valid_packet
         .header
@@ -56,6 +57,7 @@ fn test_ivf_writer_add_packet_and_close() -> Result<()> {
             extensions_padding: 0,
         },
         payload: raw_mid_part_pkt.slice(20..),
+        ..Default::default()
     };
     mid_part_packet
         .header
@@ -84,6 +86,7 @@ fn test_ivf_writer_add_packet_and_close() -> Result<()> {
             extensions_padding: 0,
         },
         payload: raw_keyframe_pkt.slice(20..),
+        ..Default::default()
     };
     keyframe_packet
         .header
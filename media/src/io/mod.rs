@@ -7,6 +7,7 @@ pub mod ivf_writer;
 pub mod ogg_reader;
 pub mod ogg_writer;
 pub mod sample_builder;
+pub mod webm_writer;
 
 pub type ResetFn<R> = Box<dyn FnMut(usize) -> R>;
 
@@ -27,12 +27,27 @@ pub struct OggReader<R: Read> {
     do_checksum: bool,
 }
 
+/// OggChannelMapping is the channel mapping table of the ID header, present for all
+/// channel mapping families but 0, e.g. family 1 for up to 8 channels in Vorbis order.
+/// <https://tools.ietf.org/html/rfc7845.html#section-5.1.1>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OggChannelMapping {
+    /// Number of Opus streams in each packet.
+    pub stream_count: u8,
+    /// Number of the streams which are stereo, the first ones of them.
+    pub coupled_count: u8,
+    /// Stream channel of each output channel, 255 for a silent one.
+    pub channel_mapping: Vec<u8>,
+}
+
 /// OggHeader is the metadata from the first two pages
 /// in the file (ID and Comment)
 /// <https://tools.ietf.org/html/rfc7845.html#section-3>
 pub struct OggHeader {
     pub channel_map: u8,
     pub channels: u8,
+    /// Channel mapping table, None for channel mapping family 0 (mono or stereo).
+    pub channel_mapping: Option<OggChannelMapping>,
     pub output_gain: u16,
     pub pre_skip: u16,
     pub sample_rate: u32,
@@ -80,7 +95,7 @@ impl<R: Read> OggReader<R> {
             return Err(Error::ErrBadIDPageType);
         }
 
-        if payload.len() != ID_PAGE_PAYLOAD_SIZE {
+        if payload.len() < ID_PAGE_PAYLOAD_SIZE {
             return Err(Error::ErrBadIDPageLength);
         }
 
@@ -97,9 +112,30 @@ impl<R: Read> OggReader<R> {
         let output_gain = reader.read_u16::<LittleEndian>()?; //16-17
         let channel_map = reader.read_u8()?; //18
 
+        let channel_mapping = if channel_map == 0 {
+            if payload.len() != ID_PAGE_PAYLOAD_SIZE {
+                return Err(Error::ErrBadIDPageLength);
+            }
+            None
+        } else {
+            if payload.len() != ID_PAGE_PAYLOAD_SIZE + 2 + channels as usize {
+                return Err(Error::ErrBadIDPageLength);
+            }
+            let stream_count = reader.read_u8()?; //19
+            let coupled_count = reader.read_u8()?; //20
+            let mut channel_mapping = vec![0u8; channels as usize];
+            reader.read_exact(&mut channel_mapping)?; //21-
+            Some(OggChannelMapping {
+                stream_count,
+                coupled_count,
+                channel_mapping,
+            })
+        };
+
         Ok(OggHeader {
             channel_map,
             channels,
+            channel_mapping,
             output_gain,
             pre_skip,
             sample_rate,
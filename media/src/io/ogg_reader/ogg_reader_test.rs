@@ -22,6 +22,7 @@ fn test_ogg_reader_parse_valid_header() -> Result<()> {
 
     assert_eq!(header.channel_map, 0);
     assert_eq!(header.channels, 2);
+    assert_eq!(header.channel_mapping, None);
     assert_eq!(header.output_gain, 0);
     assert_eq!(header.pre_skip, 0xf00);
     assert_eq!(header.sample_rate, 48000);
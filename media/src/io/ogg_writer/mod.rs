@@ -16,6 +16,7 @@ pub struct OggWriter<W: Write + Seek> {
     writer: W,
     sample_rate: u32,
     channel_count: u8,
+    channel_mapping: Option<OggChannelMapping>,
     serial: u32,
     page_index: u32,
     checksum_table: [u32; 256],
@@ -28,10 +29,32 @@ pub struct OggWriter<W: Write + Seek> {
 impl<W: Write + Seek> OggWriter<W> {
     /// new initialize a new OGG Opus writer with an io.Writer output
     pub fn new(writer: W, sample_rate: u32, channel_count: u8) -> Result<Self> {
+        OggWriter::with_channel_mapping(writer, sample_rate, channel_count, None)
+    }
+
+    /// new_multichannel initialize a new OGG Opus writer for multichannel Opus, e.g. as
+    /// received for audio/multiopus, with channel mapping family 1 and a channel for each
+    /// entry of the channel mapping table.
+    pub fn new_multichannel(
+        writer: W,
+        sample_rate: u32,
+        channel_mapping: OggChannelMapping,
+    ) -> Result<Self> {
+        let channel_count = channel_mapping.channel_mapping.len() as u8;
+        OggWriter::with_channel_mapping(writer, sample_rate, channel_count, Some(channel_mapping))
+    }
+
+    fn with_channel_mapping(
+        writer: W,
+        sample_rate: u32,
+        channel_count: u8,
+        channel_mapping: Option<OggChannelMapping>,
+    ) -> Result<Self> {
         let mut w = OggWriter {
             writer,
             sample_rate,
             channel_count,
+            channel_mapping,
             serial: rand::random::<u32>(),
             page_index: 0,
             checksum_table: generate_checksum_table(),
@@ -83,7 +106,14 @@ impl<W: Write + Seek> OggWriter<W> {
             header_writer.write_u16::<LittleEndian>(DEFAULT_PRE_SKIP)?; // pre-skip //10-11
             header_writer.write_u32::<LittleEndian>(self.sample_rate)?; // original sample rate, any valid sample e.g 48000, //12-15
             header_writer.write_u16::<LittleEndian>(0)?; // output gain // 16-17
-            header_writer.write_u8(0)?; // channel map 0 = one stream: mono or stereo, //18
+            if let Some(channel_mapping) = &self.channel_mapping {
+                header_writer.write_u8(1)?; // channel map 1 = up to 8 channels, //18
+                header_writer.write_u8(channel_mapping.stream_count)?; // stream count //19
+                header_writer.write_u8(channel_mapping.coupled_count)?; // coupled count //20
+                header_writer.write_all(&channel_mapping.channel_mapping)?; // channel mapping //21-
+            } else {
+                header_writer.write_u8(0)?; // channel map 0 = one stream: mono or stereo, //18
+            }
         }
 
         // Reference: https://tools.ietf.org/html/rfc7845.html#page-6
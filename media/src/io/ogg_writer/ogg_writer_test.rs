@@ -27,6 +27,7 @@ fn test_ogg_writer_add_packet_and_close() -> Result<()> {
             extensions_padding: 0,
         },
         payload: raw_pkt.slice(20..),
+        ..Default::default()
     };
     valid_packet
         .header
@@ -84,6 +85,7 @@ fn test_ogg_writer_add_packet() -> Result<()> {
             extensions_padding: 0,
         },
         payload: raw_pkt,
+        ..Default::default()
     };
     valid_packet
         .header
@@ -125,6 +127,7 @@ fn test_ogg_writer_add_packet_of_255() -> Result<()> {
             extensions_padding: 0,
         },
         payload: raw_pkt,
+        ..Default::default()
     };
     valid_packet
         .header
@@ -166,6 +169,7 @@ fn test_ogg_writer_add_large_packet() -> Result<()> {
             extensions_padding: 0,
         },
         payload: raw_pkt,
+        ..Default::default()
     };
     valid_packet
         .header
@@ -207,6 +211,7 @@ fn test_ogg_writer_add_large_packet_with_multiple_of_255() -> Result<()> {
             extensions_padding: 0,
         },
         payload: raw_pkt,
+        ..Default::default()
     };
     valid_packet
         .header
@@ -227,3 +232,27 @@ fn test_ogg_writer_add_large_packet_with_multiple_of_255() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_ogg_writer_multichannel_header() -> Result<()> {
+    // 5.1 surround, as negotiated for audio/multiopus.
+    let channel_mapping = OggChannelMapping {
+        stream_count: 4,
+        coupled_count: 2,
+        channel_mapping: vec![0, 4, 1, 2, 3, 5],
+    };
+    let writer = OggWriter::new_multichannel(
+        Cursor::new(Vec::<u8>::new()),
+        48000,
+        channel_mapping.clone(),
+    )?;
+
+    let ogg = writer.writer.into_inner();
+    let (_reader, header) = OggReader::new(Cursor::new(ogg), true)?;
+    assert_eq!(header.channel_map, 1);
+    assert_eq!(header.channels, 6);
+    assert_eq!(header.sample_rate, 48000);
+    assert_eq!(header.channel_mapping, Some(channel_mapping));
+
+    Ok(())
+}
@@ -8,8 +8,12 @@ pub mod sample_sequence_location;
 use std::time::{Duration, SystemTime};
 
 use bytes::Bytes;
+use rtp::extension::playout_delay_extension::PlayoutDelayExtension;
+use rtp::extension::video_orientation_extension::VideoOrientationExtension;
 use rtp::packet::Packet;
 use rtp::packetizer::Depacketizer;
+use rtp::sequence::{SequenceTracker, SequenceUpdate};
+use util::marshal::Unmarshal;
 
 use self::sample_sequence_location::{Comparison, SampleSequenceLocation};
 use crate::Sample;
@@ -39,12 +43,25 @@ pub struct SampleBuilder<T: Depacketizer> {
     /// prepared contains the samples that have been processed to date
     prepared: SampleSequenceLocation,
 
+    /// sequence numbers of the packets pushed, to drop the duplicates
+    sequence: SequenceTracker,
+
     /// number of packets forced to be dropped
     dropped_packets: u16,
 
     /// number of padding packets detected and dropped. This number will be a subset of
     /// `dropped_packets`
     padding_packets: u16,
+
+    /// id of the video orientation header extension, 0 if it isn't read
+    video_orientation_id: u8,
+    /// latest video orientation received, which applies until another one is received
+    video_orientation: Option<VideoOrientationExtension>,
+
+    /// id of the playout delay header extension, 0 if it isn't read
+    playout_delay_id: u8,
+    /// latest playout delay received
+    playout_delay: Option<PlayoutDelayExtension>,
 }
 
 impl<T: Depacketizer> SampleBuilder<T> {
@@ -66,8 +83,14 @@ impl<T: Depacketizer> SampleBuilder<T> {
             filled: SampleSequenceLocation::new(),
             active: SampleSequenceLocation::new(),
             prepared: SampleSequenceLocation::new(),
+            // gaps of any size are dropped by max_late, not detected as jumps
+            sequence: SequenceTracker::with_limits(1 << 15, 1 << 15),
             dropped_packets: 0,
             padding_packets: 0,
+            video_orientation_id: 0,
+            video_orientation: None,
+            playout_delay_id: 0,
+            playout_delay: None,
         }
     }
 
@@ -77,6 +100,28 @@ impl<T: Depacketizer> SampleBuilder<T> {
         self
     }
 
+    /// with_video_orientation_extension reads the urn:3gpp:video-orientation header extension
+    /// with the negotiated id into [`Sample::video_orientation`]. Senders signal the
+    /// orientation when it changes only, so it applies to all later samples.
+    pub fn with_video_orientation_extension(mut self, id: u8) -> Self {
+        self.video_orientation_id = id;
+        self
+    }
+
+    /// with_playout_delay_extension reads the playout-delay header extension with the
+    /// negotiated id. The maximum delay the sender asks for replaces the one of
+    /// [`SampleBuilder::with_max_time_delay`], so incomplete samples aren't waited for longer.
+    pub fn with_playout_delay_extension(mut self, id: u8) -> Self {
+        self.playout_delay_id = id;
+        self
+    }
+
+    /// playout_delay returns the latest playout delay received, whose minimum delay samples
+    /// should be rendered with.
+    pub fn playout_delay(&self) -> Option<PlayoutDelayExtension> {
+        self.playout_delay
+    }
+
     fn too_old(&self, location: &SampleSequenceLocation) -> bool {
         if self.max_late_timestamp == 0 {
             return false;
@@ -199,6 +244,11 @@ impl<T: Depacketizer> SampleBuilder<T> {
     /// this memory make sure to copy before calling push
     pub fn push(&mut self, p: Packet) {
         let sequence_number = p.header.sequence_number;
+        if let SequenceUpdate::Duplicate { .. } = self.sequence.update(sequence_number) {
+            // already buffered, or built into a sample
+            return;
+        }
+
         self.buffer[sequence_number as usize] = Some(p);
         match self.filled.compare(sequence_number) {
             Comparison::Void => {
@@ -328,10 +378,30 @@ impl<T: Depacketizer> SampleBuilder<T> {
         let mut data: Vec<u8> = Vec::new();
         let mut i = consume.head;
         while i != consume.tail {
-            let payload = self.buffer[i as usize]
+            let packet = self.buffer[i as usize]
                 .as_ref()
-                .map(|p| &p.payload)
                 .ok_or(BuildError::GapInSegment)?;
+            let payload = &packet.payload;
+
+            if self.video_orientation_id != 0 {
+                if let Some(mut ext) = packet.header.get_extension(self.video_orientation_id) {
+                    if let Ok(orientation) = VideoOrientationExtension::unmarshal(&mut ext) {
+                        self.video_orientation = Some(orientation);
+                    }
+                }
+            }
+            if self.playout_delay_id != 0 {
+                if let Some(mut ext) = packet.header.get_extension(self.playout_delay_id) {
+                    if let Ok(playout_delay) = PlayoutDelayExtension::unmarshal(&mut ext) {
+                        // Zero asks for no delay at all, while a zero limit means no limit.
+                        self.max_late_timestamp = ((self.sample_rate as u128
+                            * playout_delay.max_delay_duration().as_millis()
+                            / 1000) as u32)
+                            .max(1);
+                        self.playout_delay = Some(playout_delay);
+                    }
+                }
+            }
 
             let p = self
                 .depacketizer
@@ -350,6 +420,7 @@ impl<T: Depacketizer> SampleBuilder<T> {
             packet_timestamp: sample_timestamp,
             prev_dropped_packets: self.dropped_packets,
             prev_padding_packets: self.padding_packets,
+            video_orientation: self.video_orientation,
         };
 
         self.dropped_packets = 0;
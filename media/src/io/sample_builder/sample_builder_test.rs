@@ -1239,6 +1239,7 @@ fn test_sample_builder_max_late() {
             ..Default::default()
         },
         payload: bytes!(0x01),
+        ..Default::default()
     });
     s.push(Packet {
         header: Header {
@@ -1247,6 +1248,7 @@ fn test_sample_builder_max_late() {
             ..Default::default()
         },
         payload: bytes!(0x01),
+        ..Default::default()
     });
     s.push(Packet {
         header: Header {
@@ -1255,6 +1257,7 @@ fn test_sample_builder_max_late() {
             ..Default::default()
         },
         payload: bytes!(0x01),
+        ..Default::default()
     });
     assert_eq!(
         s.pop(),
@@ -1274,6 +1277,7 @@ fn test_sample_builder_max_late() {
             ..Default::default()
         },
         payload: bytes!(0x02),
+        ..Default::default()
     });
     s.push(Packet {
         header: Header {
@@ -1282,6 +1286,7 @@ fn test_sample_builder_max_late() {
             ..Default::default()
         },
         payload: bytes!(0x02),
+        ..Default::default()
     });
     s.push(Packet {
         header: Header {
@@ -1290,6 +1295,7 @@ fn test_sample_builder_max_late() {
             ..Default::default()
         },
         payload: bytes!(0x02),
+        ..Default::default()
     });
 
     assert_eq!(
@@ -1311,6 +1317,7 @@ fn test_sample_builder_max_late() {
             ..Default::default()
         },
         payload: bytes!(0x03),
+        ..Default::default()
     });
     assert_eq!(
         s.pop(),
@@ -1335,6 +1342,109 @@ fn test_sample_builder_max_late() {
     );
 }
 
+#[test]
+fn test_sample_builder_video_orientation() {
+    use rtp::extension::video_orientation_extension::{CameraDirection, VideoRotation};
+
+    let orientation = VideoOrientationExtension {
+        direction: CameraDirection::Back,
+        flip: false,
+        rotation: VideoRotation::Degree90,
+    };
+    let mut s =
+        SampleBuilder::new(50, FakeDepacketizer::new(), 1).with_video_orientation_extension(4);
+
+    for (sequence_number, with_orientation) in [(0, false), (1, true), (2, false), (3, false)] {
+        let mut header = Header {
+            version: 2,
+            sequence_number,
+            timestamp: sequence_number as u32 + 1,
+            extension: true,
+            extension_profile: 0xBEDE,
+            ..Default::default()
+        };
+        if with_orientation {
+            header
+                .set_extension(4, Bytes::from_static(&[0b1001]))
+                .unwrap();
+        }
+        s.push(Packet {
+            header,
+            payload: bytes!(0x01),
+            ..Default::default()
+        });
+    }
+
+    // The orientation received applies to the later samples too.
+    let orientations: Vec<_> = std::iter::from_fn(|| s.pop())
+        .map(|sample| sample.video_orientation)
+        .collect();
+    assert_eq!(
+        orientations,
+        vec![None, Some(orientation), Some(orientation)]
+    );
+}
+
+#[test]
+fn test_sample_builder_playout_delay() {
+    let mut s =
+        SampleBuilder::new(50, FakeDepacketizer::new(), 1000).with_playout_delay_extension(6);
+
+    // A maximum delay of 20ms, the builder waits for 50 packets without it.
+    let mut header = Header {
+        version: 2,
+        sequence_number: 0,
+        timestamp: 0,
+        extension: true,
+        extension_profile: 0xBEDE,
+        ..Default::default()
+    };
+    header
+        .set_extension(6, Bytes::from_static(&[0x00, 0x00, 0x02]))
+        .unwrap();
+    s.push(Packet {
+        header,
+        payload: bytes!(0x01),
+        ..Default::default()
+    });
+    for (sequence_number, timestamp) in [(1, 10), (3, 20)] {
+        s.push(Packet {
+            header: Header {
+                sequence_number,
+                timestamp,
+                ..Default::default()
+            },
+            payload: bytes!(0x01),
+            ..Default::default()
+        });
+    }
+    assert_eq!(s.pop().map(|sample| sample.packet_timestamp), Some(0));
+    assert_eq!(
+        s.playout_delay(),
+        Some(PlayoutDelayExtension {
+            min_delay: 0,
+            max_delay: 2
+        })
+    );
+    assert_eq!(s.pop(), None, "sample should wait for the missing packet");
+
+    // The sample before the gap is dropped once it's 20ms late.
+    for (sequence_number, timestamp) in [(4, 40), (5, 50)] {
+        s.push(Packet {
+            header: Header {
+                sequence_number,
+                timestamp,
+                ..Default::default()
+            },
+            payload: bytes!(0x01),
+            ..Default::default()
+        });
+    }
+    let sample = s.pop().expect("sample after the gap should be built");
+    assert_eq!(sample.packet_timestamp, 20);
+    assert_eq!(sample.prev_dropped_packets, 2);
+}
+
 #[test]
 fn test_seqnum_distance() {
     struct TestData {
@@ -1399,6 +1509,7 @@ fn test_sample_builder_clean_reference() {
                 ..Default::default()
             },
             payload: bytes!(0x01),
+            ..Default::default()
         });
         s.push(Packet {
             header: Header {
@@ -1407,6 +1518,7 @@ fn test_sample_builder_clean_reference() {
                 ..Default::default()
             },
             payload: bytes!(0x02),
+            ..Default::default()
         });
         s.push(Packet {
             header: Header {
@@ -1415,6 +1527,7 @@ fn test_sample_builder_clean_reference() {
                 ..Default::default()
             },
             payload: bytes!(0x03),
+            ..Default::default()
         });
         let pkt4 = Packet {
             header: Header {
@@ -1423,6 +1536,7 @@ fn test_sample_builder_clean_reference() {
                 ..Default::default()
             },
             payload: bytes!(0x04),
+            ..Default::default()
         };
         s.push(pkt4.clone());
         let pkt5 = Packet {
@@ -1432,6 +1546,7 @@ fn test_sample_builder_clean_reference() {
                 ..Default::default()
             },
             payload: bytes!(0x05),
+            ..Default::default()
         };
         s.push(pkt5.clone());
 
@@ -1457,6 +1572,7 @@ fn test_sample_builder_push_max_zero() {
             ..Default::default()
         },
         payload: bytes!(0x01),
+        ..Default::default()
     };
     let d = FakeDepacketizer {
         head_checker: true,
@@ -1467,6 +1583,39 @@ fn test_sample_builder_push_max_zero() {
     assert!(s.pop().is_some(), "Should expect a popped sample.")
 }
 
+#[test]
+fn test_sample_builder_duplicate() {
+    let packet = |sequence_number: u16| Packet {
+        header: Header {
+            sequence_number,
+            timestamp: sequence_number as u32 + 1,
+            ..Default::default()
+        },
+        payload: bytes!(0x01),
+        ..Default::default()
+    };
+    let mut s = SampleBuilder::new(10, FakeDepacketizer::new(), 1);
+
+    s.push(packet(0));
+    s.push(packet(1));
+    assert_eq!(s.pop().map(|s| s.packet_timestamp), Some(1));
+
+    // a duplicate of a packet built into a sample already isn't built again
+    s.push(packet(0));
+    s.push(packet(1));
+    s.push(packet(2));
+    assert_eq!(
+        s.pop(),
+        Some(Sample {
+            data: bytes!(0x01),
+            duration: Duration::from_secs(1),
+            packet_timestamp: 2,
+            ..Default::default()
+        })
+    );
+    assert_eq!(s.pop(), None);
+}
+
 #[test]
 fn test_pop_with_timestamp() {
     let mut s = SampleBuilder::new(0, FakeDepacketizer::new(), 1);
@@ -1485,6 +1634,7 @@ fn test_sample_builder_data() {
                 ..Default::default()
             },
             payload: Bytes::copy_from_slice(&[i as u8]),
+            ..Default::default()
         };
         s.push(p);
         while let Some((sample, ts)) = s.pop_with_timestamp() {
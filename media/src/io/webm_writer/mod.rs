@@ -0,0 +1,684 @@
+#[cfg(test)]
+mod webm_writer_test;
+
+use std::io::{Seek, SeekFrom, Write};
+use std::time::Instant;
+
+use byteorder::{BigEndian, WriteBytesExt};
+use bytes::BytesMut;
+use rtp::codecs::av1::Av1Packet;
+use rtp::codecs::opus::OpusPacket;
+use rtp::codecs::vp8::Vp8Packet;
+use rtp::codecs::vp9::Vp9Packet;
+use rtp::packetizer::Depacketizer;
+
+use crate::error::{Error, Result};
+use crate::io::ogg_reader::{DEFAULT_PRE_SKIP, ID_PAGE_SIGNATURE};
+use crate::io::Writer;
+
+const EBML_ID: u32 = 0x1A45DFA3;
+const EBML_VERSION_ID: u32 = 0x4286;
+const EBML_READ_VERSION_ID: u32 = 0x42F7;
+const EBML_MAX_ID_LENGTH_ID: u32 = 0x42F2;
+const EBML_MAX_SIZE_LENGTH_ID: u32 = 0x42F3;
+const DOC_TYPE_ID: u32 = 0x4282;
+const DOC_TYPE_VERSION_ID: u32 = 0x4287;
+const DOC_TYPE_READ_VERSION_ID: u32 = 0x4285;
+
+const SEGMENT_ID: u32 = 0x18538067;
+const SEEK_HEAD_ID: u32 = 0x114D9B74;
+const SEEK_ID: u32 = 0x4DBB;
+const SEEK_ID_ID: u32 = 0x53AB;
+const SEEK_POSITION_ID: u32 = 0x53AC;
+const VOID_ID: u32 = 0xEC;
+
+const INFO_ID: u32 = 0x1549A966;
+const TIMESTAMP_SCALE_ID: u32 = 0x2AD7B1;
+const DURATION_ID: u32 = 0x4489;
+const MUXING_APP_ID: u32 = 0x4D80;
+const WRITING_APP_ID: u32 = 0x5741;
+
+const TRACKS_ID: u32 = 0x1654AE6B;
+const TRACK_ENTRY_ID: u32 = 0xAE;
+const TRACK_NUMBER_ID: u32 = 0xD7;
+const TRACK_UID_ID: u32 = 0x73C5;
+const TRACK_TYPE_ID: u32 = 0x83;
+const FLAG_LACING_ID: u32 = 0x9C;
+const CODEC_ID_ID: u32 = 0x86;
+const CODEC_PRIVATE_ID: u32 = 0x63A2;
+const CODEC_DELAY_ID: u32 = 0x56AA;
+const SEEK_PRE_ROLL_ID: u32 = 0x56BB;
+const VIDEO_ID: u32 = 0xE0;
+const PIXEL_WIDTH_ID: u32 = 0xB0;
+const PIXEL_HEIGHT_ID: u32 = 0xBA;
+const AUDIO_ID: u32 = 0xE1;
+const SAMPLING_FREQUENCY_ID: u32 = 0xB5;
+const CHANNELS_ID: u32 = 0x9F;
+
+const CLUSTER_ID: u32 = 0x1F43B675;
+const CLUSTER_TIMESTAMP_ID: u32 = 0xE7;
+const SIMPLE_BLOCK_ID: u32 = 0xA3;
+
+const CUES_ID: u32 = 0x1C53BB6B;
+const CUE_POINT_ID: u32 = 0xBB;
+const CUE_TIME_ID: u32 = 0xB3;
+const CUE_TRACK_POSITIONS_ID: u32 = 0xB7;
+const CUE_TRACK_ID: u32 = 0xF7;
+const CUE_CLUSTER_POSITION_ID: u32 = 0xF1;
+
+const TRACK_TYPE_VIDEO: u64 = 1;
+const TRACK_TYPE_AUDIO: u64 = 2;
+const SIMPLE_BLOCK_FLAG_KEYFRAME: u8 = 0x80;
+
+/// The timestamps are in milliseconds.
+const TIMESTAMP_SCALE: u64 = 1_000_000;
+/// Duration after which a new cluster is started, if no video keyframe started one before,
+/// well within the 16 bit timestamps of the blocks relative to their cluster.
+const MAX_CLUSTER_DURATION_MS: i64 = 5_000;
+const VIDEO_CLOCK_RATE: i64 = 90_000;
+/// Clock rate of the RTP timestamps of Opus, whatever its sample rate.
+const OPUS_CLOCK_RATE: i64 = 48_000;
+/// How long Opus needs to be decoded before a seek point to converge, in nanoseconds.
+const OPUS_SEEK_PRE_ROLL_NS: u64 = 80_000_000;
+/// Size of a Seek entry with an 8 byte position, reserved for the Cues in the SeekHead.
+const SEEK_ENTRY_SIZE: usize = 21;
+
+/// WebmCodec is a codec a WebmWriter muxes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WebmCodec {
+    Vp8,
+    Vp9,
+    Av1,
+    Opus,
+}
+
+impl WebmCodec {
+    fn codec_id(&self) -> &'static str {
+        match self {
+            WebmCodec::Vp8 => "V_VP8",
+            WebmCodec::Vp9 => "V_VP9",
+            WebmCodec::Av1 => "V_AV1",
+            WebmCodec::Opus => "A_OPUS",
+        }
+    }
+
+    fn is_video(&self) -> bool {
+        *self != WebmCodec::Opus
+    }
+}
+
+/// WebmTrack is a track of a WebM file, of the RTP packets of a payload type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebmTrack {
+    pub codec: WebmCodec,
+    pub payload_type: u8,
+    /// Size of the video frames, in pixels.
+    pub width: u16,
+    pub height: u16,
+    /// Sample rate and channels of the audio.
+    pub sample_rate: u32,
+    pub channel_count: u8,
+}
+
+impl WebmTrack {
+    /// video returns a video track of codec, whose frames are of width by height pixels.
+    pub fn video(codec: WebmCodec, payload_type: u8, width: u16, height: u16) -> Self {
+        WebmTrack {
+            codec,
+            payload_type,
+            width,
+            height,
+            sample_rate: 0,
+            channel_count: 0,
+        }
+    }
+
+    /// opus returns an Opus audio track.
+    pub fn opus(payload_type: u8, sample_rate: u32, channel_count: u8) -> Self {
+        WebmTrack {
+            codec: WebmCodec::Opus,
+            payload_type,
+            width: 0,
+            height: 0,
+            sample_rate,
+            channel_count,
+        }
+    }
+
+    fn clock_rate(&self) -> i64 {
+        if self.codec.is_video() {
+            VIDEO_CLOCK_RATE
+        } else {
+            OPUS_CLOCK_RATE
+        }
+    }
+
+    /// entry returns the TrackEntry element of the track, of number.
+    fn entry(&self, number: u64) -> Vec<u8> {
+        let mut entry = vec![];
+        put_uint(&mut entry, TRACK_NUMBER_ID, number);
+        put_uint(&mut entry, TRACK_UID_ID, number);
+        put_uint(&mut entry, FLAG_LACING_ID, 0);
+        put_bytes(&mut entry, CODEC_ID_ID, self.codec.codec_id().as_bytes());
+        match self.codec {
+            WebmCodec::Opus => {
+                put_uint(&mut entry, TRACK_TYPE_ID, TRACK_TYPE_AUDIO);
+                put_bytes(&mut entry, CODEC_PRIVATE_ID, &self.opus_head());
+                put_uint(
+                    &mut entry,
+                    CODEC_DELAY_ID,
+                    DEFAULT_PRE_SKIP as u64 * 1_000_000_000 / OPUS_CLOCK_RATE as u64,
+                );
+                put_uint(&mut entry, SEEK_PRE_ROLL_ID, OPUS_SEEK_PRE_ROLL_NS);
+                let mut audio = vec![];
+                put_float(&mut audio, SAMPLING_FREQUENCY_ID, self.sample_rate as f64);
+                put_uint(&mut audio, CHANNELS_ID, self.channel_count as u64);
+                put_bytes(&mut entry, AUDIO_ID, &audio);
+            }
+            codec => {
+                put_uint(&mut entry, TRACK_TYPE_ID, TRACK_TYPE_VIDEO);
+                if codec == WebmCodec::Av1 {
+                    // av1C of the main profile, level 4.0, 8 bit 4:2:0, without the
+                    // sequence header OBU, which each keyframe has
+                    put_bytes(&mut entry, CODEC_PRIVATE_ID, &[0x81, 0x08, 0x0C, 0x00]);
+                }
+                let mut video = vec![];
+                put_uint(&mut video, PIXEL_WIDTH_ID, self.width as u64);
+                put_uint(&mut video, PIXEL_HEIGHT_ID, self.height as u64);
+                put_bytes(&mut entry, VIDEO_ID, &video);
+            }
+        }
+
+        let mut b = vec![];
+        put_bytes(&mut b, TRACK_ENTRY_ID, &entry);
+        b
+    }
+
+    /// opus_head returns the Opus ID header, the codec private data of the Opus tracks.
+    /// <https://tools.ietf.org/html/rfc7845.html#section-5.1>
+    fn opus_head(&self) -> Vec<u8> {
+        let mut head = Vec::with_capacity(19);
+        head.extend_from_slice(ID_PAGE_SIGNATURE);
+        head.push(1); // version
+        head.push(self.channel_count);
+        head.extend_from_slice(&DEFAULT_PRE_SKIP.to_le_bytes());
+        head.extend_from_slice(&self.sample_rate.to_le_bytes());
+        head.extend_from_slice(&0u16.to_le_bytes()); // output gain
+        head.push(0); // channel mapping family 0, mono or stereo
+        head
+    }
+}
+
+enum TrackDepacketizer {
+    Vp8(Vp8Packet),
+    Vp9(Vp9Packet),
+    Av1(Av1Packet),
+    Opus(OpusPacket),
+}
+
+impl TrackDepacketizer {
+    fn new(codec: WebmCodec) -> Self {
+        match codec {
+            WebmCodec::Vp8 => TrackDepacketizer::Vp8(Vp8Packet::default()),
+            WebmCodec::Vp9 => TrackDepacketizer::Vp9(Vp9Packet::default()),
+            WebmCodec::Av1 => TrackDepacketizer::Av1(Av1Packet::default()),
+            WebmCodec::Opus => TrackDepacketizer::Opus(OpusPacket),
+        }
+    }
+
+    fn depacketizer(&mut self) -> &mut dyn Depacketizer {
+        match self {
+            TrackDepacketizer::Vp8(p) => p,
+            TrackDepacketizer::Vp9(p) => p,
+            TrackDepacketizer::Av1(p) => p,
+            TrackDepacketizer::Opus(p) => p,
+        }
+    }
+
+    /// is_keyframe returns whether the frame starting with payload, the first packet of the
+    /// frame depacketized last, is a keyframe.
+    fn is_keyframe(&self, payload: &[u8]) -> bool {
+        match self {
+            // the P bit of the VP8 frame tag is unset for the keyframes
+            TrackDepacketizer::Vp8(_) => payload.first().is_some_and(|b| b & 0x01 == 0),
+            // the frames not inter-picture predicted
+            TrackDepacketizer::Vp9(p) => !p.p,
+            // the first packet of a coded video sequence
+            TrackDepacketizer::Av1(p) => p.n,
+            TrackDepacketizer::Opus(_) => true,
+        }
+    }
+}
+
+struct TrackState {
+    track: WebmTrack,
+    depacketizer: TrackDepacketizer,
+    /// Frame being reassembled, with its RTP timestamp and whether it's a keyframe.
+    frame: Option<(BytesMut, u32, bool)>,
+    seen_keyframe: bool,
+    /// RTP timestamp of the last frame written, and its ticks since the first frame.
+    last_timestamp: Option<(u32, i64)>,
+    /// Time of the first frame written since the first frame of the file, in milliseconds.
+    offset_ms: i64,
+}
+
+struct Cluster {
+    /// Position of the cluster, relative to the segment data.
+    position: u64,
+    /// Position of the size of the cluster in the file.
+    size_position: u64,
+    timestamp_ms: i64,
+    blocks: usize,
+}
+
+/// WebmWriter is used to take RTP packets of VP8, VP9 or AV1 video and of Opus audio and mux
+/// them to a WebM file, which browsers can play and seek in directly.
+///
+/// Each track takes the packets of its payload type: the others are ignored. The video frames
+/// are written once complete, from the first keyframe of each track, and the audio frames once
+/// a video keyframe is written, if there is a video track. A cluster is started at each video
+/// keyframe, with a cue point, or every 5 seconds. The timestamps of each track follow its RTP
+/// timestamps from its first frame, which is at the time it is written since the first frame
+/// of the file.
+pub struct WebmWriter<W: Write + Seek> {
+    writer: W,
+    tracks: Vec<TrackState>,
+    has_video: bool,
+    /// Position of the segment data in the file.
+    segment_position: u64,
+    /// Position of the Void reserved for the Seek entry of the Cues in the file.
+    cues_seek_position: u64,
+    /// Position of the duration value of the Info in the file.
+    duration_position: u64,
+    start: Option<Instant>,
+    cluster: Option<Cluster>,
+    /// Time and position, relative to the segment data, of the clusters with a cue point, and
+    /// the track they are for.
+    cue_points: Vec<(i64, u64, u64)>,
+    duration_ms: i64,
+    closed: bool,
+}
+
+impl<W: Write + Seek> WebmWriter<W> {
+    /// new initialize a new WebM writer of tracks with an io.Writer output
+    pub fn new(writer: W, tracks: &[WebmTrack]) -> Result<Self> {
+        if tracks.is_empty() {
+            return Err(Error::ErrNoWebmTrack);
+        }
+
+        let mut w = WebmWriter {
+            writer,
+            tracks: tracks
+                .iter()
+                .map(|track| TrackState {
+                    track: track.clone(),
+                    depacketizer: TrackDepacketizer::new(track.codec),
+                    frame: None,
+                    seen_keyframe: false,
+                    last_timestamp: None,
+                    offset_ms: 0,
+                })
+                .collect(),
+            has_video: tracks.iter().any(|t| t.codec.is_video()),
+            segment_position: 0,
+            cues_seek_position: 0,
+            duration_position: 0,
+            start: None,
+            cluster: None,
+            cue_points: vec![],
+            duration_ms: 0,
+            closed: false,
+        };
+
+        w.write_header()?;
+
+        Ok(w)
+    }
+
+    /*
+        ref: https://www.matroska.org/technical/elements.html
+        https://www.webmproject.org/docs/container/
+
+        EBML header
+        Segment, of its size once closed
+        |- SeekHead: Info, Tracks, and Cues once closed
+        |- Info: timestamp scale of 1ms, duration once closed
+        |- Tracks
+        |- Cluster, each of its size once complete
+        |  |- Timestamp
+        |  |- SimpleBlock ...
+        |- Cluster ...
+        |- Cues, once closed
+    */
+
+    fn write_header(&mut self) -> Result<()> {
+        let mut ebml = vec![];
+        put_uint(&mut ebml, EBML_VERSION_ID, 1);
+        put_uint(&mut ebml, EBML_READ_VERSION_ID, 1);
+        put_uint(&mut ebml, EBML_MAX_ID_LENGTH_ID, 4);
+        put_uint(&mut ebml, EBML_MAX_SIZE_LENGTH_ID, 8);
+        put_bytes(&mut ebml, DOC_TYPE_ID, b"webm");
+        put_uint(&mut ebml, DOC_TYPE_VERSION_ID, 4);
+        put_uint(&mut ebml, DOC_TYPE_READ_VERSION_ID, 2);
+        let start = self.writer.stream_position()?;
+        let mut header = vec![];
+        put_bytes(&mut header, EBML_ID, &ebml);
+
+        // the segment is of an unknown size until closed
+        put_id(&mut header, SEGMENT_ID);
+        header.extend_from_slice(&UNKNOWN_SIZE);
+        let segment_position = start + header.len() as u64;
+
+        let mut tracks = vec![];
+        for (i, track) in self.tracks.iter().enumerate() {
+            tracks.extend_from_slice(&track.track.entry(i as u64 + 1));
+        }
+        let mut tracks_element = vec![];
+        put_bytes(&mut tracks_element, TRACKS_ID, &tracks);
+
+        let mut info = vec![];
+        put_uint(&mut info, TIMESTAMP_SCALE_ID, TIMESTAMP_SCALE);
+        put_bytes(&mut info, MUXING_APP_ID, b"WebRTC.rs");
+        put_bytes(&mut info, WRITING_APP_ID, b"WebRTC.rs");
+        let duration_offset = info.len() + 3;
+        put_float(&mut info, DURATION_ID, 0.0);
+        let mut info_element = vec![];
+        put_bytes(&mut info_element, INFO_ID, &info);
+        let duration_offset = duration_offset + info_element.len() - info.len();
+
+        // the SeekHead is of a known size, with the room of the Seek entry of the Cues
+        let seek_head_size = 2 * SEEK_ENTRY_SIZE + SEEK_ENTRY_SIZE + 4 + 1;
+        let info_position = seek_head_size as u64;
+        let tracks_position = info_position + info_element.len() as u64;
+        let mut seeks = vec![];
+        put_seek(&mut seeks, INFO_ID, info_position);
+        put_seek(&mut seeks, TRACKS_ID, tracks_position);
+        let cues_seek_position = start + (header.len() + 4 + 1 + seeks.len()) as u64;
+        put_void(&mut seeks, SEEK_ENTRY_SIZE);
+        put_bytes(&mut header, SEEK_HEAD_ID, &seeks);
+        debug_assert_eq!(
+            start + header.len() as u64,
+            segment_position + info_position
+        );
+
+        self.duration_position = segment_position + info_position + duration_offset as u64;
+        header.extend_from_slice(&info_element);
+        header.extend_from_slice(&tracks_element);
+
+        self.writer.write_all(&header)?;
+        self.segment_position = segment_position;
+        self.cues_seek_position = cues_seek_position;
+        Ok(())
+    }
+
+    /// timestamp_ms returns the time of a frame of the track of index of RTP timestamp, in
+    /// milliseconds since the first frame of the file, unwrapping the RTP timestamps.
+    fn timestamp_ms(&mut self, index: usize, timestamp: u32) -> i64 {
+        let now = Instant::now();
+        let start = *self.start.get_or_insert(now);
+        let state = &mut self.tracks[index];
+        let ticks = match state.last_timestamp {
+            None => {
+                state.offset_ms = now.duration_since(start).as_millis() as i64;
+                0
+            }
+            Some((last, ticks)) => ticks + timestamp.wrapping_sub(last) as i32 as i64,
+        };
+        state.last_timestamp = Some((timestamp, ticks));
+        state.offset_ms + ticks * 1000 / state.track.clock_rate()
+    }
+
+    /// write_frame writes a frame of the track of index at timestamp_ms.
+    fn write_frame(
+        &mut self,
+        index: usize,
+        timestamp_ms: i64,
+        keyframe: bool,
+        frame: &[u8],
+    ) -> Result<()> {
+        let is_video = self.tracks[index].track.codec.is_video();
+        let new_cluster = match &self.cluster {
+            None => true,
+            Some(cluster) => {
+                (is_video && keyframe && cluster.blocks > 0)
+                    || timestamp_ms - cluster.timestamp_ms >= MAX_CLUSTER_DURATION_MS
+            }
+        };
+        if new_cluster {
+            self.start_cluster(timestamp_ms)?;
+            if keyframe && (is_video || !self.has_video) {
+                if let Some(cluster) = &self.cluster {
+                    self.cue_points
+                        .push((timestamp_ms, cluster.position, index as u64 + 1));
+                }
+            }
+        }
+
+        let cluster = match self.cluster.as_mut() {
+            Some(cluster) => cluster,
+            None => return Ok(()),
+        };
+        // the blocks can't be before their cluster
+        let relative = (timestamp_ms - cluster.timestamp_ms).clamp(0, i16::MAX as i64) as i16;
+        cluster.blocks += 1;
+
+        let mut block = Vec::with_capacity(frame.len() + 4);
+        put_size(&mut block, index as u64 + 1);
+        block.write_i16::<BigEndian>(relative)?;
+        block.push(if keyframe {
+            SIMPLE_BLOCK_FLAG_KEYFRAME
+        } else {
+            0
+        });
+        block.extend_from_slice(frame);
+        let mut element = vec![];
+        put_bytes(&mut element, SIMPLE_BLOCK_ID, &block);
+        self.writer.write_all(&element)?;
+
+        self.duration_ms = self.duration_ms.max(timestamp_ms);
+        Ok(())
+    }
+
+    fn start_cluster(&mut self, timestamp_ms: i64) -> Result<()> {
+        self.end_cluster()?;
+
+        let position = self.writer.stream_position()?;
+        let mut cluster = vec![];
+        put_id(&mut cluster, CLUSTER_ID);
+        cluster.extend_from_slice(&UNKNOWN_SIZE);
+        put_uint(&mut cluster, CLUSTER_TIMESTAMP_ID, timestamp_ms as u64);
+        self.writer.write_all(&cluster)?;
+
+        self.cluster = Some(Cluster {
+            position: position - self.segment_position,
+            size_position: position + 4,
+            timestamp_ms,
+            blocks: 0,
+        });
+        Ok(())
+    }
+
+    /// end_cluster writes the size of the current cluster, if any.
+    fn end_cluster(&mut self) -> Result<()> {
+        if let Some(cluster) = self.cluster.take() {
+            let end = self.writer.stream_position()?;
+            let size = end - (cluster.size_position + UNKNOWN_SIZE.len() as u64);
+            self.writer.seek(SeekFrom::Start(cluster.size_position))?;
+            self.writer.write_all(&size_8(size))?;
+            self.writer.seek(SeekFrom::Start(end))?;
+        }
+        Ok(())
+    }
+
+    /// write_cues writes the Cues, and their Seek entry in the SeekHead.
+    fn write_cues(&mut self) -> Result<()> {
+        if self.cue_points.is_empty() {
+            return Ok(());
+        }
+        let mut cue_points = vec![];
+        for (time, position, track) in &self.cue_points {
+            let mut track_positions = vec![];
+            put_uint(&mut track_positions, CUE_TRACK_ID, *track);
+            put_uint(&mut track_positions, CUE_CLUSTER_POSITION_ID, *position);
+            let mut cue_point = vec![];
+            put_uint(&mut cue_point, CUE_TIME_ID, *time as u64);
+            put_bytes(&mut cue_point, CUE_TRACK_POSITIONS_ID, &track_positions);
+            put_bytes(&mut cue_points, CUE_POINT_ID, &cue_point);
+        }
+        let mut cues = vec![];
+        put_bytes(&mut cues, CUES_ID, &cue_points);
+
+        let position = self.writer.stream_position()?;
+        self.writer.write_all(&cues)?;
+        let end = self.writer.stream_position()?;
+
+        let mut seek = vec![];
+        put_seek(&mut seek, CUES_ID, position - self.segment_position);
+        self.writer.seek(SeekFrom::Start(self.cues_seek_position))?;
+        self.writer.write_all(&seek)?;
+        self.writer.seek(SeekFrom::Start(end))?;
+        Ok(())
+    }
+}
+
+impl<W: Write + Seek> Writer for WebmWriter<W> {
+    /// write_rtp adds a new packet to the frame of its track, and writes the frame once complete
+    fn write_rtp(&mut self, packet: &rtp::packet::Packet) -> Result<()> {
+        if self.closed {
+            return Err(Error::ErrFileNotOpened);
+        }
+        let index = match self
+            .tracks
+            .iter()
+            .position(|t| t.track.payload_type == packet.header.payload_type)
+        {
+            Some(index) => index,
+            None => return Ok(()),
+        };
+
+        let (frame, timestamp, keyframe) = {
+            let state = &mut self.tracks[index];
+            let depacketizer = state.depacketizer.depacketizer();
+            let is_head = depacketizer.is_partition_head(&packet.payload);
+            let payload = depacketizer.depacketize(&packet.payload)?;
+            let timestamp = packet.header.timestamp;
+
+            // a frame is dropped if a packet of another frame comes before its last one
+            if let Some((_, frame_timestamp, _)) = &state.frame {
+                if *frame_timestamp != timestamp {
+                    state.frame = None;
+                }
+            }
+            match state.frame.as_mut() {
+                Some((frame, _, _)) => frame.extend_from_slice(&payload),
+                None if is_head || !state.track.codec.is_video() => {
+                    let keyframe = state.depacketizer.is_keyframe(&payload);
+                    state.frame = Some((BytesMut::from(&payload[..]), timestamp, keyframe));
+                }
+                None => return Ok(()),
+            }
+            if state.track.codec.is_video() && !packet.header.marker {
+                return Ok(());
+            }
+            match state.frame.take() {
+                Some((frame, timestamp, keyframe)) => (frame.freeze(), timestamp, keyframe),
+                None => return Ok(()),
+            }
+        };
+        if frame.is_empty() {
+            return Ok(());
+        }
+
+        let is_video = self.tracks[index].track.codec.is_video();
+        let state = &self.tracks[index];
+        if is_video && !state.seen_keyframe && !keyframe {
+            return Ok(());
+        }
+        // the audio waits for the video to start with a keyframe
+        if !is_video && self.has_video && self.start.is_none() {
+            return Ok(());
+        }
+
+        let timestamp_ms = self.timestamp_ms(index, timestamp);
+        self.tracks[index].seen_keyframe = true;
+        self.write_frame(index, timestamp_ms, keyframe, &frame)
+    }
+
+    /// close stops the recording
+    fn close(&mut self) -> Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+        self.closed = true;
+
+        self.end_cluster()?;
+        self.write_cues()?;
+
+        let end = self.writer.stream_position()?;
+        self.writer.seek(SeekFrom::Start(self.duration_position))?;
+        self.writer
+            .write_f64::<BigEndian>(self.duration_ms as f64)?;
+        self.writer
+            .seek(SeekFrom::Start(self.segment_position - 8))?;
+        self.writer
+            .write_all(&size_8(end - self.segment_position))?;
+        self.writer.seek(SeekFrom::Start(end))?;
+
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// The size of the elements whose size is written once known, of 8 bytes.
+const UNKNOWN_SIZE: [u8; 8] = [0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+
+/// size_8 returns size as an 8 byte variable size integer.
+fn size_8(size: u64) -> [u8; 8] {
+    (size | 0x01 << 56).to_be_bytes()
+}
+
+fn put_id(b: &mut Vec<u8>, id: u32) {
+    let bytes = id.to_be_bytes();
+    let leading = bytes.iter().take_while(|b| **b == 0).count();
+    b.extend_from_slice(&bytes[leading.min(3)..]);
+}
+
+/// put_size writes size as a variable size integer of the fewest bytes.
+fn put_size(b: &mut Vec<u8>, size: u64) {
+    let mut length = 1;
+    // all ones is reserved for the unknown sizes
+    while length < 8 && size >= (1 << (7 * length)) - 1 {
+        length += 1;
+    }
+    let marked = size | 1 << (7 * length);
+    b.extend_from_slice(&marked.to_be_bytes()[8 - length..]);
+}
+
+fn put_bytes(b: &mut Vec<u8>, id: u32, data: &[u8]) {
+    put_id(b, id);
+    put_size(b, data.len() as u64);
+    b.extend_from_slice(data);
+}
+
+fn put_uint(b: &mut Vec<u8>, id: u32, value: u64) {
+    let bytes = value.to_be_bytes();
+    let leading = bytes.iter().take_while(|b| **b == 0).count();
+    put_bytes(b, id, &bytes[leading.min(7)..]);
+}
+
+fn put_float(b: &mut Vec<u8>, id: u32, value: f64) {
+    put_bytes(b, id, &value.to_be_bytes());
+}
+
+/// put_seek writes a Seek entry of SEEK_ENTRY_SIZE bytes, of the element id at position.
+fn put_seek(b: &mut Vec<u8>, id: u32, position: u64) {
+    let mut seek = vec![];
+    put_bytes(&mut seek, SEEK_ID_ID, &id.to_be_bytes());
+    put_bytes(&mut seek, SEEK_POSITION_ID, &position.to_be_bytes());
+    put_bytes(b, SEEK_ID, &seek);
+}
+
+/// put_void writes a Void element of size bytes.
+fn put_void(b: &mut Vec<u8>, size: usize) {
+    put_bytes(b, VOID_ID, &vec![0; size - 2]);
+}
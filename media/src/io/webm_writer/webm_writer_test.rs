@@ -0,0 +1,417 @@
+use std::io::Cursor;
+
+use super::*;
+
+/// Element is an EBML element parsed, with its position in the file.
+#[derive(Debug)]
+struct Element {
+    id: u32,
+    position: usize,
+    data: Vec<u8>,
+}
+
+impl Element {
+    fn children(&self) -> Vec<Element> {
+        parse(&self.data, 0)
+    }
+
+    fn child(&self, id: u32) -> Element {
+        self.children()
+            .into_iter()
+            .find(|e| e.id == id)
+            .unwrap_or_else(|| panic!("no element {id:x} in {:x}", self.id))
+    }
+
+    fn uint(&self) -> u64 {
+        self.data.iter().fold(0, |v, b| v << 8 | *b as u64)
+    }
+}
+
+fn read_vint(b: &[u8]) -> (u64, usize) {
+    let length = b[0].leading_zeros() as usize + 1;
+    let value = b[1..length]
+        .iter()
+        .fold(b[0] as u64 & (0xFF >> length), |v, b| v << 8 | *b as u64);
+    (value, length)
+}
+
+fn parse(b: &[u8], base: usize) -> Vec<Element> {
+    let mut elements = vec![];
+    let mut i = 0;
+    while i < b.len() {
+        let id_length = b[i].leading_zeros() as usize + 1;
+        let id = b[i..i + id_length]
+            .iter()
+            .fold(0, |v, b| v << 8 | *b as u32);
+        let (size, size_length) = read_vint(&b[i + id_length..]);
+        let start = i + id_length + size_length;
+        elements.push(Element {
+            id,
+            position: base + i,
+            data: b[start..start + size as usize].to_vec(),
+        });
+        i = start + size as usize;
+    }
+    elements
+}
+
+/// Block is a SimpleBlock of a cluster parsed.
+#[derive(Debug, PartialEq)]
+struct Block {
+    track: u64,
+    timestamp_ms: i64,
+    keyframe: bool,
+    data: Vec<u8>,
+}
+
+/// WebmFile is a WebM file written, parsed.
+struct WebmFile {
+    file: Vec<u8>,
+    segment: Element,
+    segment_position: usize,
+}
+
+impl WebmFile {
+    fn parse(file: Vec<u8>) -> Self {
+        let elements = parse(&file, 0);
+        assert_eq!(elements.len(), 2);
+        assert_eq!(elements[0].id, EBML_ID);
+        assert_eq!(elements[0].child(DOC_TYPE_ID).data, b"webm");
+        let segment = elements.into_iter().nth(1).unwrap();
+        assert_eq!(segment.id, SEGMENT_ID);
+        // the data of the segment is after its 4 byte id and 8 byte size
+        let segment_position = segment.position + 12;
+        WebmFile {
+            file,
+            segment,
+            segment_position,
+        }
+    }
+
+    fn elements(&self, id: u32) -> Vec<Element> {
+        parse(&self.segment.data, self.segment_position)
+            .into_iter()
+            .filter(|e| e.id == id)
+            .collect()
+    }
+
+    fn clusters(&self) -> Vec<(i64, Vec<Block>)> {
+        self.elements(CLUSTER_ID)
+            .iter()
+            .map(|cluster| {
+                let timestamp = cluster.child(CLUSTER_TIMESTAMP_ID).uint() as i64;
+                let blocks = cluster
+                    .children()
+                    .iter()
+                    .filter(|e| e.id == SIMPLE_BLOCK_ID)
+                    .map(|e| {
+                        let (track, length) = read_vint(&e.data);
+                        let relative = i16::from_be_bytes([e.data[length], e.data[length + 1]]);
+                        Block {
+                            track,
+                            timestamp_ms: timestamp + relative as i64,
+                            keyframe: e.data[length + 2] & SIMPLE_BLOCK_FLAG_KEYFRAME != 0,
+                            data: e.data[length + 3..].to_vec(),
+                        }
+                    })
+                    .collect();
+                (timestamp, blocks)
+            })
+            .collect()
+    }
+
+    /// cues returns the time, track and cluster of each cue point.
+    fn cues(&self) -> Vec<(u64, u64, usize)> {
+        let cues = self.elements(CUES_ID);
+        assert_eq!(cues.len(), 1);
+        cues[0]
+            .children()
+            .iter()
+            .map(|cue_point| {
+                let positions = cue_point.child(CUE_TRACK_POSITIONS_ID);
+                (
+                    cue_point.child(CUE_TIME_ID).uint(),
+                    positions.child(CUE_TRACK_ID).uint(),
+                    self.segment_position
+                        + positions.child(CUE_CLUSTER_POSITION_ID).uint() as usize,
+                )
+            })
+            .collect()
+    }
+
+    /// seeks returns the id and position in the file of each seek entry.
+    fn seeks(&self) -> Vec<(u32, usize)> {
+        self.elements(SEEK_HEAD_ID)[0]
+            .children()
+            .iter()
+            .filter(|e| e.id == SEEK_ID)
+            .map(|seek| {
+                (
+                    seek.child(SEEK_ID_ID).uint() as u32,
+                    self.segment_position + seek.child(SEEK_POSITION_ID).uint() as usize,
+                )
+            })
+            .collect()
+    }
+
+    fn duration(&self) -> f64 {
+        let duration = self.elements(INFO_ID)[0].child(DURATION_ID);
+        f64::from_be_bytes(duration.data.try_into().unwrap())
+    }
+}
+
+fn packet(
+    payload_type: u8,
+    sequence_number: u16,
+    timestamp: u32,
+    marker: bool,
+    payload: Vec<u8>,
+) -> rtp::packet::Packet {
+    rtp::packet::Packet {
+        header: rtp::header::Header {
+            payload_type,
+            sequence_number,
+            timestamp,
+            marker,
+            ..Default::default()
+        },
+        payload: payload.into(),
+        ..Default::default()
+    }
+}
+
+/// vp8_packets returns the two packets of a VP8 frame, a keyframe or not, and the frame.
+fn vp8_packets(
+    sequence_number: u16,
+    timestamp: u32,
+    keyframe: bool,
+) -> (Vec<rtp::packet::Packet>, Vec<u8>) {
+    let frame = vec![!keyframe as u8, 0x02, 0x03, 0x04, 0x05, 0x06];
+    let mut first = vec![0x10];
+    first.extend_from_slice(&frame[..3]);
+    let mut second = vec![0x00];
+    second.extend_from_slice(&frame[3..]);
+    (
+        vec![
+            packet(96, sequence_number, timestamp, false, first),
+            packet(96, sequence_number.wrapping_add(1), timestamp, true, second),
+        ],
+        frame,
+    )
+}
+
+fn opus_packet(sequence_number: u16, timestamp: u32) -> rtp::packet::Packet {
+    packet(
+        111,
+        sequence_number,
+        timestamp,
+        true,
+        vec![0x78, 0x01, 0x02],
+    )
+}
+
+fn write(tracks: &[WebmTrack], packets: &[rtp::packet::Packet]) -> Result<WebmFile> {
+    let mut writer = WebmWriter::new(Cursor::new(vec![]), tracks)?;
+    for packet in packets {
+        writer.write_rtp(packet)?;
+    }
+    writer.close()?;
+    Ok(WebmFile::parse(writer.writer.into_inner()))
+}
+
+#[test]
+fn test_webm_writer_vp8_opus() -> Result<()> {
+    let tracks = [
+        WebmTrack::video(WebmCodec::Vp8, 96, 640, 480),
+        WebmTrack::opus(111, 48_000, 2),
+    ];
+    let mut packets = vec![];
+    // audio before the first video keyframe, and a video delta frame
+    packets.push(opus_packet(0, 0));
+    packets.extend(vp8_packets(0, 0, false).0);
+    let (keyframe, keyframe_data) = vp8_packets(2, 90_000, true);
+    packets.extend(keyframe);
+    packets.push(opus_packet(1, 960));
+    packets.push(opus_packet(2, 1_920));
+    packets.extend(vp8_packets(4, 90_000 + 3_000, false).0);
+    packets.extend(vp8_packets(6, 90_000 + 9_000, true).0);
+    packets.push(opus_packet(3, 9_600));
+    let file = write(&tracks, &packets)?;
+
+    let entries = file.elements(TRACKS_ID)[0].children();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].child(CODEC_ID_ID).data, b"V_VP8");
+    assert_eq!(entries[0].child(TRACK_TYPE_ID).uint(), TRACK_TYPE_VIDEO);
+    let video = entries[0].child(VIDEO_ID);
+    assert_eq!(video.child(PIXEL_WIDTH_ID).uint(), 640);
+    assert_eq!(video.child(PIXEL_HEIGHT_ID).uint(), 480);
+    assert_eq!(entries[1].child(CODEC_ID_ID).data, b"A_OPUS");
+    assert_eq!(entries[1].child(TRACK_TYPE_ID).uint(), TRACK_TYPE_AUDIO);
+    let opus_head = entries[1].child(CODEC_PRIVATE_ID).data;
+    assert_eq!(&opus_head[..8], ID_PAGE_SIGNATURE);
+    assert_eq!(opus_head[9], 2);
+    assert_eq!(entries[1].child(CODEC_DELAY_ID).uint(), 80_000_000);
+    assert_eq!(entries[1].child(AUDIO_ID).child(CHANNELS_ID).uint(), 2);
+
+    let clusters = file.clusters();
+    assert_eq!(clusters.len(), 2);
+    // the file starts with the first video keyframe, then the audio
+    let (timestamp, blocks) = &clusters[0];
+    assert_eq!(*timestamp, 0);
+    assert_eq!(
+        blocks[0],
+        Block {
+            track: 1,
+            timestamp_ms: 0,
+            keyframe: true,
+            data: keyframe_data,
+        }
+    );
+    assert_eq!(
+        blocks
+            .iter()
+            .map(|b| (b.track, b.keyframe))
+            .collect::<Vec<_>>(),
+        vec![(1, true), (2, true), (2, true), (1, false)]
+    );
+    assert_eq!(blocks[2].timestamp_ms - blocks[1].timestamp_ms, 20);
+    assert_eq!(blocks[3].timestamp_ms, 33);
+    assert_eq!(blocks[2].data, vec![0x78, 0x01, 0x02]);
+    // and a cluster at the next video keyframe
+    let (timestamp, blocks) = &clusters[1];
+    assert_eq!(*timestamp, 100);
+    assert_eq!(blocks.len(), 2);
+    assert!(blocks[0].keyframe);
+
+    // with a cue point of each, and the duration
+    let cue_points = file.cues();
+    let positions: Vec<usize> = file
+        .elements(CLUSTER_ID)
+        .iter()
+        .map(|c| c.position)
+        .collect();
+    assert_eq!(
+        cue_points,
+        vec![(0, 1, positions[0]), (100, 1, positions[1])]
+    );
+    assert!(file.duration() >= 100.0);
+
+    // the seek entries of the top level elements
+    let seeks = file.seeks();
+    assert_eq!(seeks.len(), 3);
+    for (id, position) in seeks {
+        assert_eq!(file.elements(id)[0].position, position);
+    }
+    assert_eq!(
+        file.segment.data.len(),
+        file.file.len() - file.segment_position
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_webm_writer_audio_only() -> Result<()> {
+    let tracks = [WebmTrack::opus(111, 48_000, 1)];
+    // 12 seconds of audio, across a wraparound of the RTP timestamps
+    let first = u32::MAX - 48_000 * 3;
+    let packets: Vec<_> = (0..600u32)
+        .map(|i| opus_packet(i as u16, first.wrapping_add(i * 960)))
+        .collect();
+    let file = write(&tracks, &packets)?;
+
+    let clusters = file.clusters();
+    assert_eq!(
+        clusters.iter().map(|(t, _)| *t).collect::<Vec<_>>(),
+        vec![0, 5_000, 10_000]
+    );
+    let blocks: Vec<Block> = clusters.into_iter().flat_map(|(_, b)| b).collect();
+    assert_eq!(blocks.len(), 600);
+    for (i, block) in blocks.iter().enumerate() {
+        assert_eq!(block.timestamp_ms, i as i64 * 20);
+    }
+    assert_eq!(file.duration(), 11_980.0);
+    // a cue point of each cluster, without video
+    assert_eq!(
+        file.cues().iter().map(|c| c.0).collect::<Vec<_>>(),
+        vec![0, 5_000, 10_000]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_webm_writer_drops() -> Result<()> {
+    let tracks = [WebmTrack::video(WebmCodec::Vp8, 96, 320, 240)];
+    let mut writer = WebmWriter::new(Cursor::new(vec![]), &tracks)?;
+
+    let (keyframe, _) = vp8_packets(0, 0, true);
+    for packet in &keyframe {
+        writer.write_rtp(packet)?;
+    }
+    // a frame whose last packet is lost, and a frame without its first packet
+    writer.write_rtp(&vp8_packets(2, 3_000, false).0[0])?;
+    writer.write_rtp(&vp8_packets(4, 6_000, false).0[1])?;
+    // the packets of other payload types
+    writer.write_rtp(&opus_packet(0, 0))?;
+    let (frame, frame_data) = vp8_packets(6, 9_000, false);
+    for packet in &frame {
+        writer.write_rtp(packet)?;
+    }
+
+    writer.close()?;
+    writer.close()?;
+    assert_eq!(writer.write_rtp(&frame[0]), Err(Error::ErrFileNotOpened));
+
+    let file = WebmFile::parse(writer.writer.into_inner());
+    let clusters = file.clusters();
+    assert_eq!(clusters.len(), 1);
+    let blocks = &clusters[0].1;
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[1].timestamp_ms, 100);
+    assert_eq!(blocks[1].data, frame_data);
+
+    assert_eq!(
+        WebmWriter::new(Cursor::new(vec![]), &[]).err(),
+        Some(Error::ErrNoWebmTrack)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_webm_writer_vp9_av1() -> Result<()> {
+    let tracks = [
+        WebmTrack::video(WebmCodec::Vp9, 98, 1280, 720),
+        WebmTrack::video(WebmCodec::Av1, 45, 1280, 720),
+    ];
+    let packets = [
+        // VP9 delta frame, then a keyframe: B and E set, P unset
+        packet(98, 0, 0, true, vec![0x4C, 0x01]),
+        packet(98, 1, 3_000, true, vec![0x0C, 0x02]),
+        packet(98, 2, 6_000, true, vec![0x4C, 0x03]),
+        // AV1 frames of a single OBU, the first of a coded video sequence then not
+        packet(45, 0, 3_000, true, vec![0x18, 0x32, 0x01]),
+        packet(45, 1, 6_000, true, vec![0x10, 0x32, 0x02]),
+    ];
+    let file = write(&tracks, &packets)?;
+
+    let entries = file.elements(TRACKS_ID)[0].children();
+    assert_eq!(entries[0].child(CODEC_ID_ID).data, b"V_VP9");
+    assert_eq!(entries[1].child(CODEC_ID_ID).data, b"V_AV1");
+    assert_eq!(entries[1].child(CODEC_PRIVATE_ID).data[0], 0x81);
+
+    let blocks: Vec<Block> = file.clusters().into_iter().flat_map(|(_, b)| b).collect();
+    assert_eq!(
+        blocks
+            .iter()
+            .map(|b| (b.track, b.keyframe, b.data.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (1, true, vec![0x02]),
+            (1, false, vec![0x03]),
+            (2, true, vec![0x32, 0x01]),
+            (2, false, vec![0x32, 0x02]),
+        ]
+    );
+    Ok(())
+}
@@ -10,6 +10,7 @@ use std::time::{Duration, SystemTime};
 
 use bytes::Bytes;
 pub use error::Error;
+use rtp::extension::video_orientation_extension::VideoOrientationExtension;
 
 /// A Sample contains encoded media and timing information
 #[derive(Debug)]
@@ -59,13 +60,21 @@ pub struct Sample {
     /// #   duration: Duration::from_secs(0),
     /// #   packet_timestamp: 0,
     /// #   prev_dropped_packets: 10,
-    /// #   prev_padding_packets: 15
+    /// #   prev_padding_packets: 15,
+    /// #   video_orientation: None,
     /// # };
     /// #
     /// let adjusted_dropped =
     /// sample.prev_dropped_packets.saturating_sub(sample.prev_padding_packets);
     /// ```
     pub prev_padding_packets: u16,
+
+    /// The orientation of the video, if the sender signals it with the
+    /// urn:3gpp:video-orientation header extension.
+    ///
+    /// Receivers should rotate and flip the video accordingly when displaying it. See
+    /// [`io::sample_builder::SampleBuilder::with_video_orientation_extension`].
+    pub video_orientation: Option<VideoOrientationExtension>,
 }
 
 impl Default for Sample {
@@ -77,6 +86,7 @@ impl Default for Sample {
             packet_timestamp: 0,
             prev_dropped_packets: 0,
             prev_padding_packets: 0,
+            video_orientation: None,
         }
     }
 }
@@ -104,6 +114,9 @@ impl PartialEq for Sample {
         if self.prev_padding_packets != other.prev_padding_packets {
             equal = false;
         }
+        if self.video_orientation != other.video_orientation {
+            equal = false;
+        }
 
         equal
     }
@@ -0,0 +1,141 @@
+use bytes::Bytes;
+
+use super::*;
+
+#[test]
+fn test_application_defined_unmarshal() {
+    let tests = vec![
+        (
+            "valid",
+            Bytes::from_static(&[
+                0x85, 0xcc, 0x00, 0x03, // v=2, p=0, subtype=5, APP, len=3
+                0x90, 0x2f, 0x9e, 0x2e, // ssrc=0x902f9e2e
+                b'T', b'E', b'S', b'T', // name=TEST
+                0x01, 0x02, 0x03, 0x04, // data
+            ]),
+            ApplicationDefined {
+                sub_type: 5,
+                ssrc: 0x902f9e2e,
+                name: *b"TEST",
+                data: Bytes::from_static(&[1, 2, 3, 4]),
+            },
+            None,
+        ),
+        (
+            "padded data",
+            Bytes::from_static(&[
+                0xa0, 0xcc, 0x00, 0x03, // v=2, p=1, subtype=0, APP, len=3
+                0x90, 0x2f, 0x9e, 0x2e, // ssrc=0x902f9e2e
+                b'T', b'E', b'S', b'T', // name=TEST
+                0x01, 0x00, 0x00, 0x03, // data, padding
+            ]),
+            ApplicationDefined {
+                sub_type: 0,
+                ssrc: 0x902f9e2e,
+                name: *b"TEST",
+                data: Bytes::from_static(&[1]),
+            },
+            None,
+        ),
+        (
+            "no data",
+            Bytes::from_static(&[
+                0x81, 0xcc, 0x00, 0x02, // v=2, p=0, subtype=1, APP, len=2
+                0x90, 0x2f, 0x9e, 0x2e, // ssrc=0x902f9e2e
+                b'T', b'E', b'S', b'T', // name=TEST
+            ]),
+            ApplicationDefined {
+                sub_type: 1,
+                ssrc: 0x902f9e2e,
+                name: *b"TEST",
+                data: Bytes::new(),
+            },
+            None,
+        ),
+        (
+            "missing name",
+            Bytes::from_static(&[
+                0x81, 0xcc, 0x00, 0x01, // v=2, p=0, subtype=1, APP, len=1
+                0x90, 0x2f, 0x9e, 0x2e, // ssrc=0x902f9e2e
+            ]),
+            ApplicationDefined::default(),
+            Some(Error::PacketTooShort),
+        ),
+        (
+            "wrong type",
+            Bytes::from_static(&[
+                0x81, 0xcb, 0x00, 0x02, // v=2, p=0, count=1, BYE, len=2
+                0x90, 0x2f, 0x9e, 0x2e, // ssrc=0x902f9e2e
+                b'T', b'E', b'S', b'T', // name=TEST
+            ]),
+            ApplicationDefined::default(),
+            Some(Error::WrongType),
+        ),
+    ];
+
+    for (name, mut data, want, want_error) in tests {
+        let got = ApplicationDefined::unmarshal(&mut data);
+
+        assert_eq!(
+            got.is_err(),
+            want_error.is_some(),
+            "Unmarshal {name}: err = {got:?}, want {want_error:?}"
+        );
+
+        if let Some(err) = want_error {
+            let got_err = got.err().unwrap();
+            assert_eq!(
+                err, got_err,
+                "Unmarshal {name}: err = {got_err:?}, want {err:?}",
+            );
+        } else {
+            let actual = got.unwrap();
+            assert_eq!(
+                actual, want,
+                "Unmarshal {name}: got {actual:?}, want {want:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_application_defined_roundtrip() {
+    for data in [&b""[..], b"a", b"abcd", b"abcdefg"] {
+        let want =
+            ApplicationDefined::new(0x902f9e2e, "TEST", 31, Bytes::copy_from_slice(data)).unwrap();
+        assert_eq!(want.name_str(), Some("TEST"));
+
+        let raw = want.marshal().unwrap();
+        assert_eq!(raw.len() % 4, 0);
+        let pkts = unmarshal(&mut raw.clone()).unwrap();
+        assert_eq!(pkts.len(), 1);
+        assert_eq!(
+            pkts[0].as_any().downcast_ref::<ApplicationDefined>(),
+            Some(&want),
+            "{data:?}"
+        );
+    }
+}
+
+#[test]
+fn test_application_defined_new() {
+    assert_eq!(
+        ApplicationDefined::new(1, "TOOLONG", 0, Bytes::new()),
+        Err(Error::InvalidApplicationName)
+    );
+    assert_eq!(
+        ApplicationDefined::new(1, "tést", 0, Bytes::new()),
+        Err(Error::InvalidApplicationName)
+    );
+    assert_eq!(
+        ApplicationDefined::new(1, "TEST", 32, Bytes::new()),
+        Err(Error::InvalidApplicationSubtype)
+    );
+
+    let invalid = ApplicationDefined {
+        sub_type: 32,
+        ..Default::default()
+    };
+    let err = invalid.marshal().unwrap_err();
+    assert_eq!(Error::InvalidApplicationSubtype, err);
+}
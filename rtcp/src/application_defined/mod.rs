@@ -0,0 +1,224 @@
+#[cfg(test)]
+mod application_defined_test;
+
+use std::any::Any;
+use std::fmt;
+
+use bytes::{Buf, BufMut, Bytes};
+use util::marshal::{Marshal, MarshalSize, Unmarshal};
+
+use crate::error::Error;
+use crate::header::*;
+use crate::packet::*;
+use crate::util::*;
+
+type Result<T> = std::result::Result<T, util::Error>;
+
+const APP_NAME_LENGTH: usize = 4;
+/// Largest subtype, which is carried in the 5 bits count field of the header.
+pub const APP_SUBTYPE_MAX: u8 = 0x1F;
+
+/// ApplicationDefined is a packet carrying data of an application or of a protocol extension,
+/// identified by its name and subtype.
+///
+/// ## Specifications
+///
+/// * [RFC 3550 §6.7]
+///
+/// [RFC 3550 §6.7]: https://tools.ietf.org/html/rfc3550#section-6.7
+#[derive(Debug, PartialEq, Eq, Default, Clone)]
+pub struct ApplicationDefined {
+    /// Subtype of the packet, defined by the application, at most APP_SUBTYPE_MAX.
+    pub sub_type: u8,
+    /// SSRC or CSRC of the sender
+    pub ssrc: u32,
+    /// Name of the application, 4 ASCII characters.
+    pub name: [u8; APP_NAME_LENGTH],
+    /// Application dependent data, padded to 32 bits when sent.
+    pub data: Bytes,
+}
+
+impl fmt::Display for ApplicationDefined {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ApplicationDefined from {:x} {:?}/{}: {} bytes",
+            self.ssrc,
+            String::from_utf8_lossy(&self.name),
+            self.sub_type,
+            self.data.len()
+        )
+    }
+}
+
+impl ApplicationDefined {
+    /// new returns the ApplicationDefined packet of ssrc, or an error if name isn't 4 ASCII
+    /// characters or sub_type doesn't fit in 5 bits.
+    pub fn new(
+        ssrc: u32,
+        name: &str,
+        sub_type: u8,
+        data: impl Into<Bytes>,
+    ) -> std::result::Result<Self, Error> {
+        if name.len() != APP_NAME_LENGTH || !name.is_ascii() {
+            return Err(Error::InvalidApplicationName);
+        }
+        if sub_type > APP_SUBTYPE_MAX {
+            return Err(Error::InvalidApplicationSubtype);
+        }
+
+        let mut n = [0u8; APP_NAME_LENGTH];
+        n.copy_from_slice(name.as_bytes());
+        Ok(ApplicationDefined {
+            sub_type,
+            ssrc,
+            name: n,
+            data: data.into(),
+        })
+    }
+
+    /// name_str returns the name of the application, if it is ASCII.
+    pub fn name_str(&self) -> Option<&str> {
+        if self.name.is_ascii() {
+            std::str::from_utf8(&self.name).ok()
+        } else {
+            None
+        }
+    }
+}
+
+impl Packet for ApplicationDefined {
+    /// Header returns the Header associated with this packet.
+    fn header(&self) -> Header {
+        Header {
+            padding: get_padding_size(self.raw_size()) != 0,
+            count: self.sub_type,
+            packet_type: PacketType::ApplicationDefined,
+            length: ((self.marshal_size() / 4) - 1) as u16,
+        }
+    }
+
+    /// destination_ssrc returns an array of SSRC values that this packet refers to.
+    fn destination_ssrc(&self) -> Vec<u32> {
+        vec![self.ssrc]
+    }
+
+    fn raw_size(&self) -> usize {
+        HEADER_LENGTH + SSRC_LENGTH + APP_NAME_LENGTH + self.data.len()
+    }
+
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+
+    fn equal(&self, other: &(dyn Packet + Send + Sync)) -> bool {
+        other
+            .as_any()
+            .downcast_ref::<ApplicationDefined>()
+            .is_some_and(|a| self == a)
+    }
+
+    fn cloned(&self) -> Box<dyn Packet + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+impl MarshalSize for ApplicationDefined {
+    fn marshal_size(&self) -> usize {
+        let l = self.raw_size();
+        // align to 32-bit boundary
+        l + get_padding_size(l)
+    }
+}
+
+impl Marshal for ApplicationDefined {
+    /// Marshal encodes the ApplicationDefined packet in binary
+    fn marshal_to(&self, mut buf: &mut [u8]) -> Result<usize> {
+        /*
+         *  0                   1                   2                   3
+         *  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+         * +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+         * |V=2|P| subtype |   PT=APP=204  |             length            |
+         * +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+         * |                           SSRC/CSRC                           |
+         * +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+         * |                          name (ASCII)                         |
+         * +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+         * |                   application-dependent data                ...
+         * +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+         */
+        if self.sub_type > APP_SUBTYPE_MAX {
+            return Err(Error::InvalidApplicationSubtype.into());
+        }
+        if buf.remaining_mut() < self.marshal_size() {
+            return Err(Error::BufferTooShort.into());
+        }
+
+        let h = self.header();
+        let n = h.marshal_to(buf)?;
+        buf = &mut buf[n..];
+
+        buf.put_u32(self.ssrc);
+        buf.put_slice(&self.name);
+        buf.put(self.data.clone());
+
+        if h.padding {
+            put_padding(buf, self.raw_size());
+        }
+
+        Ok(self.marshal_size())
+    }
+}
+
+impl Unmarshal for ApplicationDefined {
+    /// Unmarshal decodes the ApplicationDefined packet from binary
+    fn unmarshal<B>(raw_packet: &mut B) -> Result<Self>
+    where
+        Self: Sized,
+        B: Buf,
+    {
+        let raw_packet_len = raw_packet.remaining();
+        if raw_packet_len < HEADER_LENGTH + SSRC_LENGTH + APP_NAME_LENGTH {
+            return Err(Error::PacketTooShort.into());
+        }
+
+        let h = Header::unmarshal(raw_packet)?;
+        if h.packet_type != PacketType::ApplicationDefined {
+            return Err(Error::WrongType.into());
+        }
+
+        let total_length = (h.length as usize + 1) * 4;
+        if total_length < HEADER_LENGTH + SSRC_LENGTH + APP_NAME_LENGTH
+            || raw_packet_len < total_length
+        {
+            return Err(Error::PacketTooShort.into());
+        }
+
+        let ssrc = raw_packet.get_u32();
+        let mut name = [0u8; APP_NAME_LENGTH];
+        raw_packet.copy_to_slice(&mut name);
+
+        let mut data =
+            raw_packet.copy_to_bytes(total_length - HEADER_LENGTH - SSRC_LENGTH - APP_NAME_LENGTH);
+        if h.padding {
+            let padding_length = *data.last().unwrap_or(&0) as usize;
+            if padding_length == 0 || padding_length > data.len() {
+                return Err(Error::WrongPadding.into());
+            }
+            data.truncate(data.len() - padding_length);
+        }
+
+        if
+        /*h.padding &&*/
+        raw_packet.has_remaining() {
+            raw_packet.advance(raw_packet.remaining());
+        }
+
+        Ok(ApplicationDefined {
+            sub_type: h.count,
+            ssrc,
+            name,
+            data,
+        })
+    }
+}
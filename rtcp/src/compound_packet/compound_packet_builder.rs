@@ -0,0 +1,223 @@
+use super::*;
+
+/// CompoundPacketBuilder assembles a CompoundPacket in the order RFC 3550 requires, whatever
+/// the order its packets are added in: the SenderReport or ReceiverReport first, followed by
+/// any additional ReceiverReport, the SourceDescription with the CNAME, and then the other
+/// packets.
+///
+/// An empty ReceiverReport is sent when no report was added, and the reception reports
+/// beyond the 31 a report can carry are moved to additional ReceiverReports.
+///
+/// ## Specifications
+///
+/// * [RFC 3550 §6.1]
+///
+/// [RFC 3550 §6.1]: https://tools.ietf.org/html/rfc3550#section-6.1
+#[derive(Debug, Default, Clone)]
+pub struct CompoundPacketBuilder {
+    sender_report: Option<SenderReport>,
+    receiver_report: Option<ReceiverReport>,
+    source_description: SourceDescription,
+    packets: Vec<Box<dyn Packet + Send + Sync>>,
+}
+
+impl CompoundPacketBuilder {
+    /// new returns an empty CompoundPacketBuilder.
+    pub fn new() -> Self {
+        CompoundPacketBuilder::default()
+    }
+
+    /// with_sender_report sets the report the compound starts with.
+    pub fn with_sender_report(mut self, sender_report: SenderReport) -> Self {
+        self.sender_report = Some(sender_report);
+        self
+    }
+
+    /// with_receiver_report sets the report the compound starts with, when there is no
+    /// SenderReport. Else its reception reports are sent in additional ReceiverReports.
+    pub fn with_receiver_report(mut self, receiver_report: ReceiverReport) -> Self {
+        self.receiver_report = Some(receiver_report);
+        self
+    }
+
+    /// with_cname adds the CNAME of the source ssrc to the SourceDescription.
+    pub fn with_cname(mut self, ssrc: u32, cname: impl Into<Bytes>) -> Self {
+        let item = SourceDescriptionItem {
+            sdes_type: SdesType::SdesCname,
+            text: cname.into(),
+        };
+        if let Some(chunk) = self
+            .source_description
+            .chunks
+            .iter_mut()
+            .find(|c| c.source == ssrc)
+        {
+            chunk.items.retain(|it| it.sdes_type != SdesType::SdesCname);
+            chunk.items.insert(0, item);
+        } else {
+            self.source_description.chunks.push(SourceDescriptionChunk {
+                source: ssrc,
+                items: vec![item],
+            });
+        }
+        self
+    }
+
+    /// with_source_description sets the SourceDescription, which must contain a CNAME.
+    pub fn with_source_description(mut self, source_description: SourceDescription) -> Self {
+        self.source_description = source_description;
+        self
+    }
+
+    /// with_packet adds a packet to the compound. SenderReport, ReceiverReport and
+    /// SourceDescription packets are put in their place, the other packets are sent after
+    /// them in the order they are added.
+    pub fn with_packet(mut self, packet: Box<dyn Packet + Send + Sync>) -> Self {
+        let any = packet.as_any();
+        if let Some(sr) = any.downcast_ref::<SenderReport>() {
+            self.sender_report = Some(sr.clone());
+        } else if let Some(rr) = any.downcast_ref::<ReceiverReport>() {
+            self.receiver_report = Some(rr.clone());
+        } else if let Some(sdes) = any.downcast_ref::<SourceDescription>() {
+            self.source_description = sdes.clone();
+        } else {
+            self.packets.push(packet);
+        }
+        self
+    }
+
+    /// build returns the CompoundPacket, or an error if it isn't RFC-compliant, such as
+    /// when no CNAME was added.
+    pub fn build(&self) -> Result<CompoundPacket> {
+        let (first, additional_reports) = self.reports();
+        let mut packets = vec![first];
+        packets.extend(additional_reports);
+        packets.push(Box::new(self.source_description.clone()));
+        packets.extend(self.packets.iter().map(|p| p.cloned()));
+
+        let compound = CompoundPacket(packets);
+        compound.validate()?;
+        Ok(compound)
+    }
+
+    /// build_datagrams returns CompoundPackets of at most mtu bytes each, which together
+    /// carry all the packets. Each of them starts with a report and the SourceDescription,
+    /// the first one with the SenderReport or ReceiverReport set and the others with an
+    /// additional ReceiverReport, even if empty.
+    pub fn build_datagrams(&self, mtu: usize) -> Result<Vec<CompoundPacket>> {
+        // checks the CNAME and the order of the packets
+        self.build()?;
+
+        let (first, additional_reports) = self.reports();
+        let source_description: Box<dyn Packet + Send + Sync> =
+            Box::new(self.source_description.clone());
+        let sdes_size = source_description.marshal_size();
+
+        let mut reports = additional_reports.into_iter().peekable();
+        let mut others = self.packets.iter().peekable();
+        let mut first = Some(first);
+        let mut datagrams = vec![];
+        loop {
+            let report = if let Some(first) = first.take() {
+                first
+            } else if let Some(report) = reports.next() {
+                report
+            } else {
+                Box::new(self.empty_receiver_report())
+            };
+
+            let mut size = report.marshal_size() + sdes_size;
+            if size > mtu {
+                return Err(Error::PacketTooLargeForDatagram.into());
+            }
+            let mut packets = vec![report];
+            while let Some(report) = reports.next_if(|r| size + r.marshal_size() <= mtu) {
+                size += report.marshal_size();
+                packets.push(report);
+            }
+            packets.push(source_description.clone());
+
+            let prefix_len = packets.len();
+            while let Some(packet) = others.next_if(|p| size + p.marshal_size() <= mtu) {
+                size += packet.marshal_size();
+                packets.push(packet.cloned());
+            }
+
+            let done = reports.peek().is_none() && others.peek().is_none();
+            if !done && packets.len() == prefix_len && reports.peek().is_none() {
+                // the next packet doesn't fit even alone with the report and SourceDescription
+                return Err(Error::PacketTooLargeForDatagram.into());
+            }
+            datagrams.push(CompoundPacket(packets));
+            if done {
+                return Ok(datagrams);
+            }
+        }
+    }
+
+    /// reports returns the report the compound starts with, and the additional
+    /// ReceiverReports carrying the reception reports it can't.
+    fn reports(
+        &self,
+    ) -> (
+        Box<dyn Packet + Send + Sync>,
+        Vec<Box<dyn Packet + Send + Sync>>,
+    ) {
+        let ssrc = self.reporter_ssrc();
+        let mut overflow = vec![];
+        let first: Box<dyn Packet + Send + Sync> = if let Some(sr) = &self.sender_report {
+            let mut sr = sr.clone();
+            if sr.reports.len() > COUNT_MAX {
+                overflow.extend(sr.reports.drain(COUNT_MAX..));
+            }
+            if let Some(rr) = &self.receiver_report {
+                overflow.extend(rr.reports.iter().cloned());
+            }
+            Box::new(sr)
+        } else {
+            let mut rr = self
+                .receiver_report
+                .clone()
+                .unwrap_or_else(|| self.empty_receiver_report());
+            if rr.reports.len() > COUNT_MAX {
+                overflow.extend(rr.reports.drain(COUNT_MAX..));
+            }
+            Box::new(rr)
+        };
+
+        let additional_reports = overflow
+            .chunks(COUNT_MAX)
+            .map(|reports| {
+                Box::new(ReceiverReport {
+                    ssrc,
+                    reports: reports.to_vec(),
+                    ..Default::default()
+                }) as Box<dyn Packet + Send + Sync>
+            })
+            .collect();
+
+        (first, additional_reports)
+    }
+
+    /// reporter_ssrc is the ssrc of the report set, else of the first source described.
+    fn reporter_ssrc(&self) -> u32 {
+        if let Some(sr) = &self.sender_report {
+            sr.ssrc
+        } else if let Some(rr) = &self.receiver_report {
+            rr.ssrc
+        } else {
+            self.source_description
+                .chunks
+                .first()
+                .map(|c| c.source)
+                .unwrap_or_default()
+        }
+    }
+
+    fn empty_receiver_report(&self) -> ReceiverReport {
+        ReceiverReport {
+            ssrc: self.reporter_ssrc(),
+            ..Default::default()
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use super::*;
 use crate::goodbye::Goodbye;
 use crate::payload_feedbacks::picture_loss_indication::PictureLossIndication;
+use crate::reception_report::ReceptionReport;
 
 // An RTCP packet from a packet dump
 const REAL_PACKET: [u8; 116] = [
@@ -327,3 +328,138 @@ fn test_compound_packet_roundtrip() {
         )
     }
 }
+
+#[test]
+fn test_compound_packet_builder() -> Result<()> {
+    let pli = PictureLossIndication {
+        sender_ssrc: 1234,
+        media_ssrc: 5678,
+    };
+
+    // packets are ordered whatever the order they are added in
+    let compound = CompoundPacketBuilder::new()
+        .with_packet(Box::new(pli))
+        .with_cname(1234, "cname")
+        .with_packet(Box::new(SenderReport {
+            ssrc: 1234,
+            ..Default::default()
+        }))
+        .build()?;
+    assert_eq!(compound.0.len(), 3);
+    assert!(compound.0[0]
+        .as_any()
+        .downcast_ref::<SenderReport>()
+        .is_some());
+    assert_eq!(compound.cname()?, "cname");
+    assert!(compound.0[2]
+        .as_any()
+        .downcast_ref::<PictureLossIndication>()
+        .is_some());
+    let data = compound.marshal()?;
+    assert_eq!(CompoundPacket::unmarshal(&mut data.clone())?, compound);
+
+    // an empty receiver report is sent without any report
+    let compound = CompoundPacketBuilder::new()
+        .with_cname(1234, "cname")
+        .with_packet(Box::new(Goodbye {
+            sources: vec![1234],
+            ..Default::default()
+        }))
+        .build()?;
+    assert_eq!(
+        compound.0[0].as_any().downcast_ref::<ReceiverReport>(),
+        Some(&ReceiverReport {
+            ssrc: 1234,
+            ..Default::default()
+        })
+    );
+
+    // reception reports beyond 31 are moved to additional receiver reports
+    let reports: Vec<ReceptionReport> = (0..40)
+        .map(|ssrc| ReceptionReport {
+            ssrc,
+            ..Default::default()
+        })
+        .collect();
+    let compound = CompoundPacketBuilder::new()
+        .with_receiver_report(ReceiverReport {
+            ssrc: 1234,
+            reports: reports.clone(),
+            ..Default::default()
+        })
+        .with_cname(1234, "cname")
+        .build()?;
+    assert_eq!(compound.0.len(), 3);
+    let first = compound.0[0]
+        .as_any()
+        .downcast_ref::<ReceiverReport>()
+        .unwrap();
+    let additional = compound.0[1]
+        .as_any()
+        .downcast_ref::<ReceiverReport>()
+        .unwrap();
+    assert_eq!(first.reports, reports[..31]);
+    assert_eq!(additional.ssrc, 1234);
+    assert_eq!(additional.reports, reports[31..]);
+    compound.marshal()?;
+
+    let result = CompoundPacketBuilder::new()
+        .with_sender_report(SenderReport::default())
+        .build();
+    assert_eq!(Error::MissingCname, result.unwrap_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_compound_packet_builder_datagrams() -> Result<()> {
+    let mut builder = CompoundPacketBuilder::new()
+        .with_sender_report(SenderReport {
+            ssrc: 1234,
+            ..Default::default()
+        })
+        .with_cname(1234, "cname");
+    for media_ssrc in 0..10 {
+        builder = builder.with_packet(Box::new(PictureLossIndication {
+            sender_ssrc: 1234,
+            media_ssrc,
+        }));
+    }
+
+    // SR: 28 bytes, empty RR: 8 bytes, SDES: 16 bytes, PLI: 12 bytes
+    let datagrams = builder.build_datagrams(80)?;
+    assert_eq!(datagrams.len(), 3);
+    let mut media_ssrcs = vec![];
+    for (i, datagram) in datagrams.iter().enumerate() {
+        assert!(datagram.marshal_size() <= 80);
+        datagram.validate()?;
+        if i == 0 {
+            assert!(datagram.0[0]
+                .as_any()
+                .downcast_ref::<SenderReport>()
+                .is_some());
+        } else {
+            assert_eq!(
+                datagram.0[0].as_any().downcast_ref::<ReceiverReport>(),
+                Some(&ReceiverReport {
+                    ssrc: 1234,
+                    ..Default::default()
+                })
+            );
+        }
+        for p in &datagram.0[2..] {
+            let pli = p.as_any().downcast_ref::<PictureLossIndication>().unwrap();
+            media_ssrcs.push(pli.media_ssrc);
+        }
+    }
+    assert_eq!(media_ssrcs, (0..10).collect::<Vec<u32>>());
+
+    let datagrams = builder.build_datagrams(1200)?;
+    assert_eq!(datagrams.len(), 1);
+    assert_eq!(datagrams[0], builder.build()?);
+
+    let result = builder.build_datagrams(50);
+    assert_eq!(Error::PacketTooLargeForDatagram, result.unwrap_err());
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
+mod compound_packet_builder;
 #[cfg(test)]
 mod compound_packet_test;
 
@@ -7,6 +8,8 @@ use std::fmt;
 use bytes::{Buf, Bytes};
 use util::marshal::{Marshal, MarshalSize, Unmarshal};
 
+pub use compound_packet_builder::CompoundPacketBuilder;
+
 use crate::error::Error;
 use crate::header::*;
 use crate::packet::*;
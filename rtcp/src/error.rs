@@ -28,6 +28,9 @@ pub enum Error {
     /// Packet was defined before CNAME.
     #[error("Feedback packet seen before CNAME")]
     PacketBeforeCname,
+    /// Packet doesn't fit in a datagram of the size requested.
+    #[error("Packet too large for the datagram")]
+    PacketTooLargeForDatagram,
     /// Too many reports.
     #[error("Too many reports")]
     TooManyReports,
@@ -55,6 +58,12 @@ pub enum Error {
     /// Reason is too long.
     #[error("Reason must be < 255 octets long")]
     ReasonTooLong,
+    /// APP packet name isn't 4 ASCII characters.
+    #[error("APP name must be 4 ASCII characters")]
+    InvalidApplicationName,
+    /// APP packet subtype doesn't fit in 5 bits.
+    #[error("APP subtype must be < 32")]
+    InvalidApplicationSubtype,
     /// Invalid packet version.
     #[error("Invalid packet version")]
     BadVersion,
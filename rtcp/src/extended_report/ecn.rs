@@ -0,0 +1,121 @@
+use super::*;
+use crate::transport_feedbacks::ecn_feedback::{EcnCounters, ECN_COUNTERS_LENGTH};
+
+const ECN_REPORT_BLOCK_LENGTH: u16 = (SSRC_LENGTH + ECN_COUNTERS_LENGTH) as u16;
+
+/// EcnSummaryReportBlock encodes an ECN Summary report block as described in RFC 6679
+/// section 5.2, sent with the regular reports of the streams received with ECN.
+///
+///  0                   1                   2                   3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |     BT=13     |   Reserved    |         Block Length = 5      |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// | SSRC of Media Sender                                          |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// | ECT (0) Counter                                               |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// | ECT (1) Counter                                               |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// | ECN-CE Counter                | not-ECT Counter               |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// | Lost Packets Counter          | Duplication Counter           |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct EcnSummaryReportBlock {
+    pub ssrc: u32,
+    pub counters: EcnCounters,
+}
+
+impl fmt::Display for EcnSummaryReportBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl EcnSummaryReportBlock {
+    pub fn xr_header(&self) -> XRHeader {
+        XRHeader {
+            block_type: BlockType::EcnSummary,
+            type_specific: 0,
+            block_length: (self.raw_size() / 4 - 1) as u16,
+        }
+    }
+}
+
+impl Packet for EcnSummaryReportBlock {
+    fn header(&self) -> Header {
+        Header::default()
+    }
+
+    /// destination_ssrc returns an array of ssrc values that this report block refers to.
+    fn destination_ssrc(&self) -> Vec<u32> {
+        vec![self.ssrc]
+    }
+
+    fn raw_size(&self) -> usize {
+        XR_HEADER_LENGTH + ECN_REPORT_BLOCK_LENGTH as usize
+    }
+
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+    fn equal(&self, other: &(dyn Packet + Send + Sync)) -> bool {
+        other
+            .as_any()
+            .downcast_ref::<EcnSummaryReportBlock>()
+            .is_some_and(|a| self == a)
+    }
+    fn cloned(&self) -> Box<dyn Packet + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+impl MarshalSize for EcnSummaryReportBlock {
+    fn marshal_size(&self) -> usize {
+        self.raw_size()
+    }
+}
+
+impl Marshal for EcnSummaryReportBlock {
+    /// marshal_to encodes the EcnSummaryReportBlock in binary
+    fn marshal_to(&self, mut buf: &mut [u8]) -> Result<usize> {
+        if buf.remaining_mut() < self.marshal_size() {
+            return Err(error::Error::BufferTooShort.into());
+        }
+
+        let h = self.xr_header();
+        let n = h.marshal_to(buf)?;
+        buf = &mut buf[n..];
+
+        buf.put_u32(self.ssrc);
+        self.counters.marshal_to(&mut buf);
+
+        Ok(self.marshal_size())
+    }
+}
+
+impl Unmarshal for EcnSummaryReportBlock {
+    /// Unmarshal decodes the EcnSummaryReportBlock from binary
+    fn unmarshal<B>(raw_packet: &mut B) -> Result<Self>
+    where
+        Self: Sized,
+        B: Buf,
+    {
+        if raw_packet.remaining() < XR_HEADER_LENGTH {
+            return Err(error::Error::PacketTooShort.into());
+        }
+
+        let xr_header = XRHeader::unmarshal(raw_packet)?;
+        let block_length = xr_header.block_length * 4;
+        if block_length != ECN_REPORT_BLOCK_LENGTH || raw_packet.remaining() < block_length as usize
+        {
+            return Err(error::Error::PacketTooShort.into());
+        }
+
+        let ssrc = raw_packet.get_u32();
+        let counters = EcnCounters::unmarshal(raw_packet);
+
+        Ok(EcnSummaryReportBlock { ssrc, counters })
+    }
+}
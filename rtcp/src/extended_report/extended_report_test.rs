@@ -182,3 +182,93 @@ fn test_decode() -> Result<()> {
     assert_eq!(actual.to_string(), expected.to_string());
     Ok(())
 }
+
+#[test]
+fn test_statistics_summary() {
+    let mut summary = StatisticsSummary::new(0xFEDCBA98);
+    assert_eq!(summary.report().lost_packets, 0);
+
+    // 65534 and 1 are lost, 0 is duplicated, 65533 arrives late.
+    for (seq, jitter) in [(65535, 10), (0, 20), (0, 30), (2, 40), (65533, 50)] {
+        summary.add(seq, jitter);
+    }
+    assert_eq!(
+        summary.report(),
+        StatisticsSummaryReportBlock {
+            loss_reports: true,
+            duplicate_reports: true,
+            jitter_reports: true,
+            ttl_or_hop_limit: TTLorHopLimitType::Missing,
+
+            ssrc: 0xFEDCBA98,
+            begin_seq: 65533,
+            end_seq: 3,
+            lost_packets: 2,
+            dup_packets: 1,
+            min_jitter: 10,
+            max_jitter: 50,
+            mean_jitter: 30,
+            dev_jitter: 14,
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_voip_metrics() {
+    let vm = VoIPMetricsReportBlock {
+        loss_rate: encode_fraction(0.25),
+        discard_rate: encode_fraction(1.0),
+        rfactor: 93,
+        mos_lq: encode_mos(4.32),
+        mos_cq: VM_UNAVAILABLE,
+        ..Default::default()
+    };
+    assert_eq!(vm.loss_rate, 64);
+    assert_eq!(vm.loss_fraction(), 0.25);
+    assert_eq!(vm.discard_rate, 255);
+    assert_eq!(vm.r_factor(), Some(93));
+    assert_eq!(vm.mos_lq_score(), Some(4.3));
+    assert_eq!(vm.mos_cq_score(), None);
+
+    assert_eq!(encode_mos(0.0), 10);
+    assert!((mos_from_r_factor(93) - 4.41).abs() < 0.01);
+    assert_eq!(mos_from_r_factor(0), 1.0);
+    assert_eq!(mos_from_r_factor(100), 4.5);
+}
+
+#[test]
+fn test_ecn_summary() -> Result<()> {
+    use crate::transport_feedbacks::ecn_feedback::EcnCounters;
+
+    let xr = ExtendedReport {
+        sender_ssrc: 0x01020304,
+        reports: vec![Box::new(EcnSummaryReportBlock {
+            ssrc: 0x902f9e2e,
+            counters: EcnCounters {
+                ect0: 1000,
+                ect1: 0,
+                ce: 30,
+                not_ect: 4,
+                lost: 5,
+                duplicates: 6,
+            },
+        })],
+    };
+    let mut encoded = xr.marshal()?;
+    assert_eq!(
+        encoded,
+        Bytes::from_static(&[
+            0x80, 0xcf, 0x00, 0x07, // v=2, p=0, XR, len=7
+            0x01, 0x02, 0x03, 0x04, // ssrc=0x01020304
+            0x0d, 0x00, 0x00, 0x05, // BT=13, block length=5
+            0x90, 0x2f, 0x9e, 0x2e, // media ssrc=0x902f9e2e
+            0x00, 0x00, 0x03, 0xe8, // ECT(0)=1000
+            0x00, 0x00, 0x00, 0x00, // ECT(1)=0
+            0x00, 0x1e, 0x00, 0x04, // CE=30, not-ECT=4
+            0x00, 0x05, 0x00, 0x06, // lost=5, duplicates=6
+        ])
+    );
+    assert_eq!(ExtendedReport::unmarshal(&mut encoded)?, xr);
+    Ok(())
+}
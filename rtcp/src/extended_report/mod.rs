@@ -2,6 +2,7 @@
 mod extended_report_test;
 
 pub mod dlrr;
+pub mod ecn;
 pub mod prt;
 pub mod rle;
 pub mod rrt;
@@ -14,13 +15,16 @@ use std::fmt;
 
 use bytes::{Buf, BufMut, Bytes};
 pub use dlrr::{DLRRReport, DLRRReportBlock};
+pub use ecn::EcnSummaryReportBlock;
 pub use prt::PacketReceiptTimesReportBlock;
 pub use rle::{Chunk, ChunkType, DuplicateRLEReportBlock, LossRLEReportBlock, RLEReportBlock};
 pub use rrt::ReceiverReferenceTimeReportBlock;
-pub use ssr::{StatisticsSummaryReportBlock, TTLorHopLimitType};
+pub use ssr::{StatisticsSummary, StatisticsSummaryReportBlock, TTLorHopLimitType};
 pub use unknown::UnknownReportBlock;
 use util::marshal::{Marshal, MarshalSize, Unmarshal};
-pub use vm::VoIPMetricsReportBlock;
+pub use vm::{
+    encode_fraction, encode_mos, mos_from_r_factor, VoIPMetricsReportBlock, VM_UNAVAILABLE,
+};
 
 use crate::error;
 use crate::header::{Header, PacketType, HEADER_LENGTH, SSRC_LENGTH};
@@ -44,6 +48,7 @@ pub enum BlockType {
     DLRR = 5,                  // RFC 3611, section 4.5
     StatisticsSummary = 6,     // RFC 3611, section 4.6
     VoIPMetrics = 7,           // RFC 3611, section 4.7
+    EcnSummary = 13,           // RFC 6679, section 5.2
 }
 
 impl From<u8> for BlockType {
@@ -56,6 +61,7 @@ impl From<u8> for BlockType {
             5 => BlockType::DLRR,
             6 => BlockType::StatisticsSummary,
             7 => BlockType::VoIPMetrics,
+            13 => BlockType::EcnSummary,
             _ => BlockType::Unknown,
         }
     }
@@ -72,6 +78,7 @@ impl fmt::Display for BlockType {
             BlockType::DLRR => "DLRRReportBlockType",
             BlockType::StatisticsSummary => "StatisticsSummaryReportBlockType",
             BlockType::VoIPMetrics => "VoIPMetricsReportBlockType",
+            BlockType::EcnSummary => "EcnSummaryReportBlockType",
             _ => "UnknownReportBlockType",
         };
         write!(f, "{s}")
@@ -287,6 +294,7 @@ impl Unmarshal for ExtendedReport {
                     Box::new(StatisticsSummaryReportBlock::unmarshal(raw_packet)?)
                 }
                 BlockType::VoIPMetrics => Box::new(VoIPMetricsReportBlock::unmarshal(raw_packet)?),
+                BlockType::EcnSummary => Box::new(EcnSummaryReportBlock::unmarshal(raw_packet)?),
                 _ => Box::new(UnknownReportBlock::unmarshal(raw_packet)?),
             };
 
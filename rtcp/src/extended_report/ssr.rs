@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use super::*;
 
 const SSR_REPORT_BLOCK_LENGTH: u16 = 4 + 2 * 2 + 4 * 6 + 4;
@@ -111,6 +113,98 @@ impl StatisticsSummaryReportBlock {
     }
 }
 
+/// StatisticsSummary accumulates the statistics of the packets received from a source over a
+/// range of sequence numbers, to report them in a [`StatisticsSummaryReportBlock`].
+#[derive(Debug, Default, Clone)]
+pub struct StatisticsSummary {
+    ssrc: u32,
+    /// First and last sequence numbers of the range, if a packet was received.
+    range: Option<(u16, u16)>,
+    received: HashSet<u16>,
+    dup_packets: u32,
+
+    jitter_count: u32,
+    min_jitter: u32,
+    max_jitter: u32,
+    jitter_sum: f64,
+    jitter_square_sum: f64,
+}
+
+impl StatisticsSummary {
+    pub fn new(ssrc: u32) -> Self {
+        StatisticsSummary {
+            ssrc,
+            ..Default::default()
+        }
+    }
+
+    /// add records a packet received with sequence number seq, and the interarrival jitter
+    /// computed when receiving it, in timestamp units.
+    pub fn add(&mut self, seq: u16, jitter: u32) {
+        self.range = Some(match self.range {
+            None => (seq, seq),
+            Some((begin, end)) if (begin.wrapping_sub(seq) as i16) > 0 => (seq, end),
+            Some((begin, end)) if (seq.wrapping_sub(end) as i16) > 0 => (begin, seq),
+            Some(range) => range,
+        });
+        if !self.received.insert(seq) {
+            self.dup_packets += 1;
+        }
+
+        if self.jitter_count == 0 {
+            self.min_jitter = jitter;
+            self.max_jitter = jitter;
+        } else {
+            self.min_jitter = self.min_jitter.min(jitter);
+            self.max_jitter = self.max_jitter.max(jitter);
+        }
+        self.jitter_count += 1;
+        self.jitter_sum += jitter as f64;
+        self.jitter_square_sum += jitter as f64 * jitter as f64;
+    }
+
+    /// report returns the block reporting the losses, duplicates and jitter of the packets
+    /// added. The range ends after the last sequence number received.
+    pub fn report(&self) -> StatisticsSummaryReportBlock {
+        let (begin_seq, end_seq) = match self.range {
+            Some((begin, end)) => (begin, end.wrapping_add(1)),
+            None => (0, 0),
+        };
+        let expected = end_seq.wrapping_sub(begin_seq) as u32;
+        let expected = if expected == 0 && self.range.is_some() {
+            u16::MAX as u32 + 1
+        } else {
+            expected
+        };
+
+        let (mean_jitter, dev_jitter) = if self.jitter_count == 0 {
+            (0.0, 0.0)
+        } else {
+            let mean = self.jitter_sum / self.jitter_count as f64;
+            let variance = self.jitter_square_sum / self.jitter_count as f64 - mean * mean;
+            (mean, variance.max(0.0).sqrt())
+        };
+
+        StatisticsSummaryReportBlock {
+            loss_reports: true,
+            duplicate_reports: true,
+            jitter_reports: true,
+            ttl_or_hop_limit: TTLorHopLimitType::Missing,
+
+            ssrc: self.ssrc,
+            begin_seq,
+            end_seq,
+            lost_packets: expected.saturating_sub(self.received.len() as u32),
+            dup_packets: self.dup_packets,
+            min_jitter: self.min_jitter,
+            max_jitter: self.max_jitter,
+            mean_jitter: mean_jitter.round() as u32,
+            dev_jitter: dev_jitter.round() as u32,
+            ..Default::default()
+        }
+    }
+}
+
 impl Packet for StatisticsSummaryReportBlock {
     fn header(&self) -> Header {
         Header::default()
@@ -2,6 +2,10 @@ use super::*;
 
 const VM_REPORT_BLOCK_LENGTH: u16 = 4 + 4 + 2 * 4 + 10 + 2 * 3;
 
+/// Value of the signal level, noise level, RERL, R factors and MOS scores which aren't
+/// available.
+pub const VM_UNAVAILABLE: u8 = 127;
+
 /// VoIPMetricsReportBlock encodes a VoIP Metrics Report Block as described
 /// in RFC 3611, section 4.7.
 ///
@@ -66,6 +70,69 @@ impl VoIPMetricsReportBlock {
             block_length: (self.raw_size() / 4 - 1) as u16,
         }
     }
+    /// loss_fraction returns the fraction of packets lost, 0 to 1.
+    pub fn loss_fraction(&self) -> f64 {
+        self.loss_rate as f64 / 256.0
+    }
+
+    /// discard_fraction returns the fraction of packets discarded on arrival, 0 to 1.
+    pub fn discard_fraction(&self) -> f64 {
+        self.discard_rate as f64 / 256.0
+    }
+
+    /// burst_density_fraction returns the fraction of packets lost or discarded within bursts.
+    pub fn burst_density_fraction(&self) -> f64 {
+        self.burst_density as f64 / 256.0
+    }
+
+    /// gap_density_fraction returns the fraction of packets lost or discarded within gaps.
+    pub fn gap_density_fraction(&self) -> f64 {
+        self.gap_density as f64 / 256.0
+    }
+
+    /// r_factor returns the R factor, 0 to 100, if available.
+    pub fn r_factor(&self) -> Option<u8> {
+        available(self.rfactor)
+    }
+
+    /// mos_lq_score returns the listening quality MOS, 1 to 5, if available.
+    pub fn mos_lq_score(&self) -> Option<f64> {
+        available(self.mos_lq).map(|mos| mos as f64 / 10.0)
+    }
+
+    /// mos_cq_score returns the conversational quality MOS, 1 to 5, if available.
+    pub fn mos_cq_score(&self) -> Option<f64> {
+        available(self.mos_cq).map(|mos| mos as f64 / 10.0)
+    }
+}
+
+fn available(v: u8) -> Option<u8> {
+    if v == VM_UNAVAILABLE {
+        None
+    } else {
+        Some(v)
+    }
+}
+
+/// encode_fraction returns the value of the loss rate, discard rate, burst density or gap
+/// density of a fraction, 0 to 1.
+pub fn encode_fraction(fraction: f64) -> u8 {
+    (fraction * 256.0).clamp(0.0, 255.0) as u8
+}
+
+/// encode_mos returns the value of the MOS-LQ or MOS-CQ of a score, 1 to 5.
+pub fn encode_mos(score: f64) -> u8 {
+    (score * 10.0).round().clamp(10.0, 50.0) as u8
+}
+
+/// mos_from_r_factor estimates the MOS of an R factor with the E-model (ITU-T G.107 annex B).
+pub fn mos_from_r_factor(r_factor: u8) -> f64 {
+    let r = r_factor as f64;
+    if r >= 100.0 {
+        4.5
+    } else {
+        1.0 + 0.035 * r + r * (r - 60.0) * (100.0 - r) * 7.0e-6
+    }
 }
 
 impl Packet for VoIPMetricsReportBlock {
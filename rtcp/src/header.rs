@@ -14,7 +14,7 @@ pub enum PacketType {
     ReceiverReport = 201,            // RFC 3550, 6.4.2
     SourceDescription = 202,         // RFC 3550, 6.5
     Goodbye = 203,                   // RFC 3550, 6.6
-    ApplicationDefined = 204,        // RFC 3550, 6.7 (unimplemented)
+    ApplicationDefined = 204,        // RFC 3550, 6.7
     TransportSpecificFeedback = 205, // RFC 4585, 6051
     PayloadSpecificFeedback = 206,   // RFC 4585, 6.3
     ExtendedReport = 207,            // RFC 3611
@@ -26,6 +26,9 @@ pub const FORMAT_SLI: u8 = 2;
 pub const FORMAT_PLI: u8 = 1;
 /// Transport and Payload specific feedback messages overload the count field to act as a message type. those are listed here
 pub const FORMAT_FIR: u8 = 4;
+/// Transport and Payload specific feedback messages overload the count field to act as a message type. those are listed here.
+/// https://tools.ietf.org/html/draft-ietf-avtext-lrr-07#section-3.1
+pub const FORMAT_LRR: u8 = 10;
 /// Transport and Payload specific feedback messages overload the count field to act as a message type. those are listed here
 pub const FORMAT_TLN: u8 = 1;
 /// Transport and Payload specific feedback messages overload the count field to act as a message type. those are listed here
@@ -35,6 +38,12 @@ pub const FORMAT_REMB: u8 = 15;
 /// Transport and Payload specific feedback messages overload the count field to act as a message type. those are listed here.
 /// https://tools.ietf.org/html/draft-holmer-rmcat-transport-wide-cc-extensions-01#page-5
 pub const FORMAT_TCC: u8 = 15;
+/// Transport and Payload specific feedback messages overload the count field to act as a message type. those are listed here.
+/// https://tools.ietf.org/html/rfc8888#section-3.1
+pub const FORMAT_CCFB: u8 = 11;
+/// Transport and Payload specific feedback messages overload the count field to act as a message type. those are listed here.
+/// https://tools.ietf.org/html/rfc6679#section-5.1
+pub const FORMAT_ECN: u8 = 8;
 
 impl std::fmt::Display for PacketType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -60,7 +69,7 @@ impl From<u8> for PacketType {
             201 => PacketType::ReceiverReport,            // RFC 3550, 6.4.2
             202 => PacketType::SourceDescription,         // RFC 3550, 6.5
             203 => PacketType::Goodbye,                   // RFC 3550, 6.6
-            204 => PacketType::ApplicationDefined,        // RFC 3550, 6.7 (unimplemented)
+            204 => PacketType::ApplicationDefined,        // RFC 3550, 6.7
             205 => PacketType::TransportSpecificFeedback, // RFC 4585, 6051
             206 => PacketType::PayloadSpecificFeedback,   // RFC 4585, 6.3
             207 => PacketType::ExtendedReport,            // RFC 3611
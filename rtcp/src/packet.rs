@@ -2,20 +2,25 @@ use std::any::Any;
 use std::fmt;
 
 use bytes::{Buf, BufMut, Bytes, BytesMut};
-use util::marshal::{Marshal, Unmarshal};
+use util::marshal::{Marshal, MarshalSize, Unmarshal};
 
+use crate::application_defined::ApplicationDefined;
 use crate::error::{Error, Result};
 use crate::extended_report::ExtendedReport;
 use crate::goodbye::*;
 use crate::header::*;
 use crate::payload_feedbacks::full_intra_request::*;
+use crate::payload_feedbacks::layer_refresh_request::LayerRefreshRequest;
 use crate::payload_feedbacks::picture_loss_indication::*;
 use crate::payload_feedbacks::receiver_estimated_maximum_bitrate::*;
 use crate::payload_feedbacks::slice_loss_indication::*;
 use crate::raw_packet::*;
 use crate::receiver_report::*;
+use crate::reception_report::{ReceptionReport, RECEPTION_REPORT_LENGTH};
 use crate::sender_report::*;
 use crate::source_description::*;
+use crate::transport_feedbacks::congestion_control_feedback::CongestionControlFeedback;
+use crate::transport_feedbacks::ecn_feedback::EcnFeedback;
 use crate::transport_feedbacks::rapid_resynchronization_request::*;
 use crate::transport_feedbacks::transport_layer_cc::*;
 use crate::transport_feedbacks::transport_layer_nack::*;
@@ -53,6 +58,196 @@ pub fn marshal(packets: &[Box<dyn Packet + Send + Sync>]) -> Result<Bytes> {
     Ok(out.freeze())
 }
 
+/// marshal_datagrams serializes packets to datagrams of at most mtu bytes each, for the
+/// batches which don't fit in a single one.
+///
+/// The reception reports of a SenderReport or ReceiverReport which doesn't fit are moved to
+/// additional ReceiverReports. When the batch is a compound starting with a report, each
+/// datagram stays a compound: it starts with a report, an empty ReceiverReport if none is
+/// left, followed by the SourceDescription of the batch. It fails with
+/// PacketTooLargeForDatagram when a packet doesn't fit even alone.
+pub fn marshal_datagrams(
+    packets: &[Box<dyn Packet + Send + Sync>],
+    mtu: usize,
+) -> Result<Vec<Bytes>> {
+    let size: usize = packets.iter().map(|p| p.marshal_size()).sum();
+    let too_many_reports = packets.iter().any(|p| {
+        let any = p.as_any();
+        any.downcast_ref::<SenderReport>()
+            .map(|sr| sr.reports.len())
+            .or_else(|| {
+                any.downcast_ref::<ReceiverReport>()
+                    .map(|rr| rr.reports.len())
+            })
+            .is_some_and(|n| n > COUNT_MAX)
+    });
+    if size <= mtu && !too_many_reports {
+        return Ok(vec![marshal(packets)?]);
+    }
+
+    let reporter_ssrc = packets.first().and_then(|p| report_ssrc(p.as_ref()));
+    let source_description = reporter_ssrc.and_then(|_| {
+        packets
+            .iter()
+            .find(|p| p.as_any().is::<SourceDescription>())
+            .cloned()
+    });
+    let sdes_size = source_description
+        .as_ref()
+        .map(|p| p.marshal_size())
+        .unwrap_or_default();
+    let budget = mtu.saturating_sub(sdes_size);
+
+    let mut pieces: Vec<Box<dyn Packet + Send + Sync>> = vec![];
+    for p in packets {
+        let any = p.as_any();
+        if let Some(sr) = any.downcast_ref::<SenderReport>() {
+            let mut sr = sr.clone();
+            let report_size = sr.marshal_size();
+            let overflow = split_reception_reports(&mut sr.reports, report_size, budget);
+            let ssrc = sr.ssrc;
+            pieces.push(Box::new(sr));
+            pieces.extend(overflow_receiver_reports(ssrc, overflow, budget));
+        } else if let Some(rr) = any.downcast_ref::<ReceiverReport>() {
+            let mut rr = rr.clone();
+            let report_size = rr.marshal_size();
+            let overflow = split_reception_reports(&mut rr.reports, report_size, budget);
+            let ssrc = rr.ssrc;
+            pieces.push(Box::new(rr));
+            pieces.extend(overflow_receiver_reports(ssrc, overflow, budget));
+        } else if source_description.is_none() || !any.is::<SourceDescription>() {
+            pieces.push(p.cloned());
+        }
+    }
+
+    let empty_report_size = ReceiverReport::default().marshal_size();
+    let mut datagrams = vec![];
+    let mut current: Vec<Box<dyn Packet + Send + Sync>> = vec![];
+    let mut size = 0;
+    let mut described = false;
+    for piece in pieces {
+        let piece_is_report = report_ssrc(piece.as_ref()).is_some();
+        // bytes added to the datagram along with the piece
+        let needed = |current: &Vec<Box<dyn Packet + Send + Sync>>, described: bool| {
+            let mut needed = piece.marshal_size();
+            if reporter_ssrc.is_some() {
+                if current.is_empty() && !piece_is_report {
+                    needed += empty_report_size;
+                }
+                if !described {
+                    needed += sdes_size;
+                }
+            }
+            needed
+        };
+
+        // the reports of a compound precede its SourceDescription
+        if !current.is_empty()
+            && (described && piece_is_report || size + needed(&current, described) > mtu)
+        {
+            datagrams.push(finish_datagram(
+                &mut current,
+                &source_description,
+                described,
+            )?);
+            size = 0;
+            described = false;
+        }
+        if size + needed(&current, described) > mtu {
+            return Err(Error::PacketTooLargeForDatagram);
+        }
+
+        if let Some(ssrc) = reporter_ssrc {
+            if current.is_empty() && !piece_is_report {
+                let rr = ReceiverReport {
+                    ssrc,
+                    ..Default::default()
+                };
+                size += rr.marshal_size();
+                current.push(Box::new(rr));
+            }
+            if let (Some(sdes), false, false) = (&source_description, described, piece_is_report) {
+                size += sdes_size;
+                current.push(sdes.clone());
+                described = true;
+            }
+        }
+        size += piece.marshal_size();
+        current.push(piece);
+    }
+    if !current.is_empty() {
+        datagrams.push(finish_datagram(
+            &mut current,
+            &source_description,
+            described,
+        )?);
+    }
+
+    Ok(datagrams)
+}
+
+fn report_ssrc(p: &(dyn Packet + Send + Sync)) -> Option<u32> {
+    let any = p.as_any();
+    if let Some(sr) = any.downcast_ref::<SenderReport>() {
+        Some(sr.ssrc)
+    } else {
+        any.downcast_ref::<ReceiverReport>().map(|rr| rr.ssrc)
+    }
+}
+
+/// finish_datagram marshals the packets of a datagram, adding the SourceDescription of the
+/// compound if it wasn't yet.
+fn finish_datagram(
+    packets: &mut Vec<Box<dyn Packet + Send + Sync>>,
+    source_description: &Option<Box<dyn Packet + Send + Sync>>,
+    described: bool,
+) -> Result<Bytes> {
+    if let (Some(sdes), false) = (source_description, described) {
+        packets.push(sdes.clone());
+    }
+    let datagram = marshal(packets)?;
+    packets.clear();
+    Ok(datagram)
+}
+
+/// split_reception_reports keeps in reports the ones a report of report_size bytes can
+/// carry within budget, and returns the others.
+fn split_reception_reports(
+    reports: &mut Vec<ReceptionReport>,
+    report_size: usize,
+    budget: usize,
+) -> Vec<ReceptionReport> {
+    let base_size = report_size - reports.len() * RECEPTION_REPORT_LENGTH;
+    let fit = (budget.saturating_sub(base_size) / RECEPTION_REPORT_LENGTH).min(COUNT_MAX);
+    if reports.len() > fit {
+        reports.split_off(fit)
+    } else {
+        vec![]
+    }
+}
+
+/// overflow_receiver_reports returns the additional ReceiverReports of ssrc carrying the
+/// reception reports, within budget each.
+fn overflow_receiver_reports(
+    ssrc: u32,
+    reports: Vec<ReceptionReport>,
+    budget: usize,
+) -> Vec<Box<dyn Packet + Send + Sync>> {
+    let empty_size = ReceiverReport::default().marshal_size();
+    let per_report =
+        ((budget.saturating_sub(empty_size)) / RECEPTION_REPORT_LENGTH).clamp(1, COUNT_MAX);
+    reports
+        .chunks(per_report)
+        .map(|reports| {
+            Box::new(ReceiverReport {
+                ssrc,
+                reports: reports.to_vec(),
+                ..Default::default()
+            }) as Box<dyn Packet + Send + Sync>
+        })
+        .collect()
+}
+
 /// Unmarshal takes an entire udp datagram (which may consist of multiple RTCP packets) and
 /// returns the unmarshaled packets it contains.
 ///
@@ -99,11 +294,14 @@ where
         PacketType::ReceiverReport => Box::new(ReceiverReport::unmarshal(&mut in_packet)?),
         PacketType::SourceDescription => Box::new(SourceDescription::unmarshal(&mut in_packet)?),
         PacketType::Goodbye => Box::new(Goodbye::unmarshal(&mut in_packet)?),
+        PacketType::ApplicationDefined => Box::new(ApplicationDefined::unmarshal(&mut in_packet)?),
 
         PacketType::TransportSpecificFeedback => match h.count {
             FORMAT_TLN => Box::new(TransportLayerNack::unmarshal(&mut in_packet)?),
             FORMAT_RRR => Box::new(RapidResynchronizationRequest::unmarshal(&mut in_packet)?),
             FORMAT_TCC => Box::new(TransportLayerCc::unmarshal(&mut in_packet)?),
+            FORMAT_CCFB => Box::new(CongestionControlFeedback::unmarshal(&mut in_packet)?),
+            FORMAT_ECN => Box::new(EcnFeedback::unmarshal(&mut in_packet)?),
             _ => Box::new(RawPacket::unmarshal(&mut in_packet)?),
         },
         PacketType::PayloadSpecificFeedback => match h.count {
@@ -111,6 +309,7 @@ where
             FORMAT_SLI => Box::new(SliceLossIndication::unmarshal(&mut in_packet)?),
             FORMAT_REMB => Box::new(ReceiverEstimatedMaximumBitrate::unmarshal(&mut in_packet)?),
             FORMAT_FIR => Box::new(FullIntraRequest::unmarshal(&mut in_packet)?),
+            FORMAT_LRR => Box::new(LayerRefreshRequest::unmarshal(&mut in_packet)?),
             _ => Box::new(RawPacket::unmarshal(&mut in_packet)?),
         },
         PacketType::ExtendedReport => Box::new(ExtendedReport::unmarshal(&mut in_packet)?),
@@ -125,6 +324,7 @@ mod test {
     use bytes::Bytes;
 
     use super::*;
+    use crate::compound_packet::CompoundPacket;
     use crate::reception_report::*;
 
     #[test]
@@ -273,4 +473,103 @@ mod test {
 
         Ok(())
     }
+
+    fn report(ssrc: u32, reports: u32) -> ReceiverReport {
+        ReceiverReport {
+            ssrc,
+            reports: (0..reports)
+                .map(|i| ReceptionReport {
+                    ssrc: 1000 + i,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_marshal_datagrams() -> Result<()> {
+        let sdes = SourceDescription {
+            chunks: vec![SourceDescriptionChunk {
+                source: 1,
+                items: vec![SourceDescriptionItem {
+                    sdes_type: SdesType::SdesCname,
+                    text: Bytes::from_static(b"cname"),
+                }],
+            }],
+        };
+        let pli = PictureLossIndication {
+            sender_ssrc: 1,
+            media_ssrc: 2,
+        };
+        let packets: Vec<Box<dyn Packet + Send + Sync>> = vec![
+            Box::new(report(1, 40)),
+            Box::new(sdes.clone()),
+            Box::new(pli.clone()),
+        ];
+
+        // fits in a single datagram
+        let datagrams = marshal_datagrams(&packets[1..], 1500)?;
+        assert_eq!(datagrams, vec![marshal(&packets[1..])?]);
+
+        // the reception reports beyond 31 are moved to an additional ReceiverReport
+        let datagrams = marshal_datagrams(&packets, 1500)?;
+        assert_eq!(datagrams.len(), 1);
+        let mut buf = datagrams[0].clone();
+        let unmarshaled = unmarshal(&mut buf)?;
+        assert_eq!(unmarshaled.len(), 4);
+        assert_eq!(
+            unmarshaled[2].as_ref(),
+            &sdes as &(dyn Packet + Send + Sync)
+        );
+
+        let datagrams = marshal_datagrams(&packets, 300)?;
+        assert!(datagrams.len() > 1);
+        let mut reception_reports = vec![];
+        let mut plis = 0;
+        for datagram in &datagrams {
+            assert!(datagram.len() <= 300, "{} bytes", datagram.len());
+            let mut buf = datagram.clone();
+            let compound = CompoundPacket(unmarshal(&mut buf)?);
+            compound.validate()?;
+            for p in &compound.0 {
+                if let Some(rr) = p.as_any().downcast_ref::<ReceiverReport>() {
+                    assert_eq!(rr.ssrc, 1);
+                    reception_reports.extend(rr.reports.iter().map(|r| r.ssrc));
+                } else if p.as_any().is::<PictureLossIndication>() {
+                    plis += 1;
+                }
+            }
+        }
+        assert_eq!(reception_reports, (1000..1040).collect::<Vec<u32>>());
+        assert_eq!(plis, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_marshal_datagrams_feedback() -> Result<()> {
+        let packets: Vec<Box<dyn Packet + Send + Sync>> = (0..10)
+            .map(|i| {
+                Box::new(PictureLossIndication {
+                    sender_ssrc: 1,
+                    media_ssrc: i,
+                }) as Box<dyn Packet + Send + Sync>
+            })
+            .collect();
+
+        let datagrams = marshal_datagrams(&packets, 40)?;
+        assert_eq!(datagrams.len(), 4);
+        let mut unmarshaled = vec![];
+        for mut datagram in datagrams {
+            assert!(datagram.len() <= 40);
+            unmarshaled.extend(unmarshal(&mut datagram)?);
+        }
+        assert_eq!(unmarshaled, packets);
+
+        let result = marshal_datagrams(&packets, 8);
+        assert_eq!(result, Err(Error::PacketTooLargeForDatagram));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,162 @@
+use bytes::Bytes;
+
+use super::*;
+
+#[test]
+fn test_layer_refresh_request_unmarshal() {
+    let tests = vec![
+        (
+            "valid",
+            Bytes::from_static(&[
+                0x8a, 0xce, 0x00, 0x05, // v=2, p=0, FMT=10, PSFB, len=5
+                0x90, 0x2f, 0x9e, 0x2e, // sender=0x902f9e2e
+                0x00, 0x00, 0x00, 0x00, // media=0x0
+                0x12, 0x34, 0x56, 0x78, // ssrc=0x12345678
+                0x42, 0xe0, 0x00, 0x00, // Seqno=0x42, C=1, PT=96
+                0x01, 0x02, 0x00, 0x01, // target T1L2, current T0L1
+            ]),
+            LayerRefreshRequest {
+                sender_ssrc: 0x902f9e2e,
+                media_ssrc: 0,
+                lrr: vec![LrrEntry {
+                    ssrc: 0x12345678,
+                    sequence_number: 0x42,
+                    payload_type: 96,
+                    target: LayerIndex {
+                        temporal_id: 1,
+                        layer_id: 2,
+                    },
+                    current: Some(LayerIndex {
+                        temporal_id: 0,
+                        layer_id: 1,
+                    }),
+                }],
+            },
+            None,
+        ),
+        (
+            "current layer unknown",
+            Bytes::from_static(&[
+                0x8a, 0xce, 0x00, 0x05, // v=2, p=0, FMT=10, PSFB, len=5
+                0x90, 0x2f, 0x9e, 0x2e, // sender=0x902f9e2e
+                0x00, 0x00, 0x00, 0x00, // media=0x0
+                0x12, 0x34, 0x56, 0x78, // ssrc=0x12345678
+                0x43, 0x60, 0x00, 0x00, // Seqno=0x43, C=0, PT=96
+                0x02, 0x00, 0x00, 0x00, // target T2L0
+            ]),
+            LayerRefreshRequest {
+                sender_ssrc: 0x902f9e2e,
+                media_ssrc: 0,
+                lrr: vec![LrrEntry {
+                    ssrc: 0x12345678,
+                    sequence_number: 0x43,
+                    payload_type: 96,
+                    target: LayerIndex {
+                        temporal_id: 2,
+                        layer_id: 0,
+                    },
+                    current: None,
+                }],
+            },
+            None,
+        ),
+        (
+            "truncated entry",
+            Bytes::from_static(&[
+                0x8a, 0xce, 0x00, 0x04, // v=2, p=0, FMT=10, PSFB, len=4
+                0x90, 0x2f, 0x9e, 0x2e, // sender=0x902f9e2e
+                0x00, 0x00, 0x00, 0x00, // media=0x0
+                0x12, 0x34, 0x56, 0x78, // ssrc=0x12345678
+                0x42, 0xe0, 0x00, 0x00, // Seqno=0x42, C=1, PT=96
+            ]),
+            LayerRefreshRequest::default(),
+            Some(Error::PacketTooShort),
+        ),
+        (
+            "packet too short",
+            Bytes::from_static(&[0x00, 0x00, 0x00, 0x00]),
+            LayerRefreshRequest::default(),
+            Some(Error::PacketTooShort),
+        ),
+        (
+            "wrong fmt",
+            Bytes::from_static(&[
+                0x84, 0xce, 0x00, 0x05, // v=2, p=0, FMT=4, PSFB, len=5
+                0x90, 0x2f, 0x9e, 0x2e, // sender=0x902f9e2e
+                0x00, 0x00, 0x00, 0x00, // media=0x0
+                0x12, 0x34, 0x56, 0x78, // ssrc=0x12345678
+                0x42, 0xe0, 0x00, 0x00, // Seqno=0x42, C=1, PT=96
+                0x01, 0x02, 0x00, 0x01, // target T1L2, current T0L1
+            ]),
+            LayerRefreshRequest::default(),
+            Some(Error::WrongType),
+        ),
+    ];
+
+    for (name, mut data, want, want_error) in tests {
+        let got = LayerRefreshRequest::unmarshal(&mut data);
+
+        assert_eq!(
+            got.is_err(),
+            want_error.is_some(),
+            "Unmarshal {name} rr: err = {got:?}, want {want_error:?}"
+        );
+
+        if let Some(err) = want_error {
+            let got_err = got.err().unwrap();
+            assert_eq!(
+                err, got_err,
+                "Unmarshal {name} rr: err = {got_err:?}, want {err:?}",
+            );
+        } else {
+            let actual = got.unwrap();
+            assert_eq!(
+                actual, want,
+                "Unmarshal {name} rr: got {actual:?}, want {want:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_layer_refresh_request_round_trip() {
+    let want = LayerRefreshRequest {
+        sender_ssrc: 1,
+        media_ssrc: 0,
+        lrr: vec![
+            LrrEntry {
+                ssrc: 2,
+                sequence_number: 7,
+                payload_type: 98,
+                target: LayerIndex {
+                    temporal_id: 2,
+                    layer_id: 1,
+                },
+                current: None,
+            },
+            LrrEntry {
+                ssrc: 3,
+                sequence_number: 8,
+                payload_type: 100,
+                target: LayerIndex {
+                    temporal_id: 0,
+                    layer_id: 3,
+                },
+                current: Some(LayerIndex {
+                    temporal_id: 1,
+                    layer_id: 2,
+                }),
+            },
+        ],
+    };
+
+    let data = want.marshal().unwrap();
+    assert_eq!(data.len(), want.marshal_size());
+    let pkts = unmarshal(&mut data.clone()).unwrap();
+    assert_eq!(pkts.len(), 1);
+    assert_eq!(
+        pkts[0].as_any().downcast_ref::<LayerRefreshRequest>(),
+        Some(&want)
+    );
+    assert_eq!(pkts[0].destination_ssrc(), vec![2, 3]);
+}
@@ -0,0 +1,228 @@
+#[cfg(test)]
+mod layer_refresh_request_test;
+
+use std::any::Any;
+use std::fmt;
+
+use bytes::{Buf, BufMut};
+use util::marshal::{Marshal, MarshalSize, Unmarshal};
+
+use crate::error::Error;
+use crate::header::*;
+use crate::packet::*;
+use crate::util::*;
+
+type Result<T> = std::result::Result<T, util::Error>;
+
+/// A LayerIndex identifies a layer of a scalable stream, by the temporal id and layer id of
+/// its codec, such as the TID and LID of H.264 SVC or the TID and SID of VP9.
+#[derive(Debug, PartialEq, Eq, Default, Copy, Clone)]
+pub struct LayerIndex {
+    /// Temporal layer id, 3 bits.
+    pub temporal_id: u8,
+    /// Spatial or quality layer id.
+    pub layer_id: u8,
+}
+
+impl LayerIndex {
+    fn marshal(&self) -> u16 {
+        ((self.temporal_id & 0x07) as u16) << 8 | self.layer_id as u16
+    }
+
+    fn unmarshal(b: u16) -> Self {
+        LayerIndex {
+            temporal_id: ((b >> 8) & 0x07) as u8,
+            layer_id: b as u8,
+        }
+    }
+}
+
+/// A LrrEntry requests the refresh of a layer of the stream ssrc.
+#[derive(Debug, PartialEq, Eq, Default, Clone)]
+pub struct LrrEntry {
+    pub ssrc: u32,
+    /// Command sequence number, incremented for each new request, as in FirEntry.
+    pub sequence_number: u8,
+    /// Payload type the layer indices refer to, 7 bits.
+    pub payload_type: u8,
+    /// Layer to refresh.
+    pub target: LayerIndex,
+    /// Layer currently decoded by the requester, if known.
+    pub current: Option<LayerIndex>,
+}
+
+/// The LayerRefreshRequest packet requests the refresh of a layer of a scalable video stream,
+/// to be able to decode it without a full keyframe, which FullIntraRequest would require.
+///
+/// ## Specifications
+///
+/// * [draft-ietf-avtext-lrr §3]
+///
+/// [draft-ietf-avtext-lrr §3]: https://tools.ietf.org/html/draft-ietf-avtext-lrr-07#section-3
+#[derive(Debug, PartialEq, Eq, Default, Clone)]
+pub struct LayerRefreshRequest {
+    pub sender_ssrc: u32,
+    pub media_ssrc: u32,
+    pub lrr: Vec<LrrEntry>,
+}
+
+const LRR_OFFSET: usize = 8;
+const LRR_ENTRY_LENGTH: usize = 12;
+
+impl fmt::Display for LayerRefreshRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = format!(
+            "LayerRefreshRequest {} {}",
+            self.sender_ssrc, self.media_ssrc
+        );
+        for e in &self.lrr {
+            out += format!(
+                " ({} {} T{}L{})",
+                e.ssrc, e.sequence_number, e.target.temporal_id, e.target.layer_id
+            )
+            .as_str();
+        }
+        write!(f, "{out}")
+    }
+}
+
+impl Packet for LayerRefreshRequest {
+    fn header(&self) -> Header {
+        Header {
+            padding: get_padding_size(self.raw_size()) != 0,
+            count: FORMAT_LRR,
+            packet_type: PacketType::PayloadSpecificFeedback,
+            length: ((self.marshal_size() / 4) - 1) as u16,
+        }
+    }
+
+    /// destination_ssrc returns an array of SSRC values that this packet refers to.
+    fn destination_ssrc(&self) -> Vec<u32> {
+        self.lrr.iter().map(|entry| entry.ssrc).collect()
+    }
+
+    fn raw_size(&self) -> usize {
+        HEADER_LENGTH + LRR_OFFSET + self.lrr.len() * LRR_ENTRY_LENGTH
+    }
+
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+
+    fn equal(&self, other: &(dyn Packet + Send + Sync)) -> bool {
+        other
+            .as_any()
+            .downcast_ref::<LayerRefreshRequest>()
+            .is_some_and(|a| self == a)
+    }
+
+    fn cloned(&self) -> Box<dyn Packet + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+impl MarshalSize for LayerRefreshRequest {
+    fn marshal_size(&self) -> usize {
+        let l = self.raw_size();
+        // align to 32-bit boundary
+        l + get_padding_size(l)
+    }
+}
+
+impl Marshal for LayerRefreshRequest {
+    /// Marshal encodes the LayerRefreshRequest
+    fn marshal_to(&self, mut buf: &mut [u8]) -> Result<usize> {
+        if buf.remaining_mut() < self.marshal_size() {
+            return Err(Error::BufferTooShort.into());
+        }
+
+        let h = self.header();
+        let n = h.marshal_to(buf)?;
+        buf = &mut buf[n..];
+
+        buf.put_u32(self.sender_ssrc);
+        buf.put_u32(self.media_ssrc);
+
+        for lrr in self.lrr.iter() {
+            buf.put_u32(lrr.ssrc);
+            buf.put_u8(lrr.sequence_number);
+            let c = if lrr.current.is_some() { 0x80 } else { 0 };
+            buf.put_u8(c | (lrr.payload_type & 0x7F));
+            buf.put_u16(0);
+            buf.put_u16(lrr.target.marshal());
+            buf.put_u16(lrr.current.map(|l| l.marshal()).unwrap_or(0));
+        }
+
+        if h.padding {
+            put_padding(buf, self.raw_size());
+        }
+
+        Ok(self.marshal_size())
+    }
+}
+
+impl Unmarshal for LayerRefreshRequest {
+    /// Unmarshal decodes the LayerRefreshRequest
+    fn unmarshal<B>(raw_packet: &mut B) -> Result<Self>
+    where
+        Self: Sized,
+        B: Buf,
+    {
+        let raw_packet_len = raw_packet.remaining();
+        if raw_packet_len < (HEADER_LENGTH + LRR_OFFSET) {
+            return Err(Error::PacketTooShort.into());
+        }
+
+        let h = Header::unmarshal(raw_packet)?;
+
+        if raw_packet_len < (HEADER_LENGTH + (4 * h.length) as usize) {
+            return Err(Error::PacketTooShort.into());
+        }
+
+        if h.packet_type != PacketType::PayloadSpecificFeedback || h.count != FORMAT_LRR {
+            return Err(Error::WrongType.into());
+        }
+
+        if (4 * h.length as usize) < LRR_OFFSET
+            || !(4 * h.length as usize - LRR_OFFSET).is_multiple_of(LRR_ENTRY_LENGTH)
+        {
+            return Err(Error::PacketTooShort.into());
+        }
+
+        let sender_ssrc = raw_packet.get_u32();
+        let media_ssrc = raw_packet.get_u32();
+
+        let mut i = HEADER_LENGTH + LRR_OFFSET;
+        let mut lrr = vec![];
+        while i < HEADER_LENGTH + (h.length * 4) as usize {
+            let ssrc = raw_packet.get_u32();
+            let sequence_number = raw_packet.get_u8();
+            let b = raw_packet.get_u8();
+            raw_packet.get_u16();
+            let target = LayerIndex::unmarshal(raw_packet.get_u16());
+            let current = LayerIndex::unmarshal(raw_packet.get_u16());
+
+            lrr.push(LrrEntry {
+                ssrc,
+                sequence_number,
+                payload_type: b & 0x7F,
+                target,
+                current: if b & 0x80 != 0 { Some(current) } else { None },
+            });
+
+            i += LRR_ENTRY_LENGTH;
+        }
+
+        if
+        /*h.padding &&*/
+        raw_packet.has_remaining() {
+            raw_packet.advance(raw_packet.remaining());
+        }
+
+        Ok(LayerRefreshRequest {
+            sender_ssrc,
+            media_ssrc,
+            lrr,
+        })
+    }
+}
@@ -4,7 +4,7 @@ mod source_description_test;
 use std::any::Any;
 use std::fmt;
 
-use bytes::{Buf, BufMut, Bytes};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use util::marshal::{Marshal, MarshalSize, Unmarshal};
 
 use crate::error::Error;
@@ -36,7 +36,7 @@ pub enum SdesType {
     SdesLocation = 5, // geographic user location        RFC 3550, 6.5.5
     SdesTool = 6,     // name of application or tool     RFC 3550, 6.5.6
     SdesNote = 7,     // notice about the source         RFC 3550, 6.5.7
-    SdesPrivate = 8,  // private extensions              RFC 3550, 6.5.8  (not implemented)
+    SdesPrivate = 8,  // private extensions              RFC 3550, 6.5.8
 }
 
 impl fmt::Display for SdesType {
@@ -81,6 +81,14 @@ pub struct SourceDescriptionChunk {
 }
 
 impl SourceDescriptionChunk {
+    /// text returns the text of the first item of sdes_type, if any.
+    pub fn text(&self, sdes_type: SdesType) -> Option<&Bytes> {
+        self.items
+            .iter()
+            .find(|it| it.sdes_type == sdes_type)
+            .map(|it| &it.text)
+    }
+
     fn raw_size(&self) -> usize {
         let mut len = SDES_SOURCE_LEN;
         for it in &self.items {
@@ -185,6 +193,51 @@ pub struct SourceDescriptionItem {
     pub text: Bytes,
 }
 
+impl SourceDescriptionItem {
+    /// private returns a PRIV item, whose text is the prefix naming the extension
+    /// followed by its value.
+    pub fn private(prefix: &[u8], value: &[u8]) -> Result<Self> {
+        /*
+         *   0                   1                   2                   3
+         *   0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+         *  +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+         *  |     PRIV=8    |     length    | prefix length |prefix string...
+         *  +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+         *  ...             |                  value string               ...
+         *  +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+         */
+        if 1 + prefix.len() + value.len() > SDES_MAX_OCTET_COUNT {
+            return Err(Error::SdesTextTooLong.into());
+        }
+
+        let mut text = BytesMut::with_capacity(1 + prefix.len() + value.len());
+        text.put_u8(prefix.len() as u8);
+        text.put_slice(prefix);
+        text.put_slice(value);
+        Ok(SourceDescriptionItem {
+            sdes_type: SdesType::SdesPrivate,
+            text: text.freeze(),
+        })
+    }
+
+    /// private_extension returns the prefix and the value of a PRIV item, or None if this
+    /// isn't a well formed PRIV item.
+    pub fn private_extension(&self) -> Option<(Bytes, Bytes)> {
+        if self.sdes_type != SdesType::SdesPrivate || self.text.is_empty() {
+            return None;
+        }
+
+        let prefix_len = self.text[0] as usize;
+        if 1 + prefix_len > self.text.len() {
+            return None;
+        }
+        Some((
+            self.text.slice(1..1 + prefix_len),
+            self.text.slice(1 + prefix_len..),
+        ))
+    }
+}
+
 impl MarshalSize for SourceDescriptionItem {
     fn marshal_size(&self) -> usize {
         /*
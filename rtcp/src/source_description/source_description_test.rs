@@ -357,3 +357,68 @@ fn test_source_description_roundtrip() {
         }
     }
 }
+
+#[test]
+fn test_source_description_items() -> Result<()> {
+    let private = SourceDescriptionItem::private(b"x-recorder", b"session=42")?;
+    assert_eq!(
+        private.private_extension(),
+        Some((
+            Bytes::from_static(b"x-recorder"),
+            Bytes::from_static(b"session=42")
+        ))
+    );
+
+    let want = SourceDescription {
+        chunks: vec![SourceDescriptionChunk {
+            source: 0x902f9e2e,
+            items: vec![
+                SourceDescriptionItem {
+                    sdes_type: SdesType::SdesCname,
+                    text: Bytes::from_static(b"cname"),
+                },
+                SourceDescriptionItem {
+                    sdes_type: SdesType::SdesName,
+                    text: Bytes::from_static(b"name"),
+                },
+                SourceDescriptionItem {
+                    sdes_type: SdesType::SdesEmail,
+                    text: Bytes::from_static(b"user@example.com"),
+                },
+                SourceDescriptionItem {
+                    sdes_type: SdesType::SdesTool,
+                    text: Bytes::from_static(b"webrtc-rs"),
+                },
+                SourceDescriptionItem {
+                    sdes_type: SdesType::SdesNote,
+                    text: Bytes::from_static(b"on air"),
+                },
+                private,
+            ],
+        }],
+    };
+
+    let mut data = want.marshal()?;
+    let got = SourceDescription::unmarshal(&mut data)?;
+    assert_eq!(got, want);
+
+    let chunk = &got.chunks[0];
+    assert_eq!(
+        chunk.text(SdesType::SdesTool),
+        Some(&Bytes::from_static(b"webrtc-rs"))
+    );
+    assert_eq!(
+        chunk.text(SdesType::SdesNote),
+        Some(&Bytes::from_static(b"on air"))
+    );
+    assert_eq!(chunk.text(SdesType::SdesPhone), None);
+    let (prefix, value) = chunk.items[5].private_extension().unwrap();
+    assert_eq!(prefix, "x-recorder");
+    assert_eq!(value, "session=42");
+
+    assert_eq!(chunk.items[0].private_extension(), None);
+    let result = SourceDescriptionItem::private(&[b'x'; 200], &[b'y'; 60]);
+    assert_eq!(Error::SdesTextTooLong, result.unwrap_err());
+
+    Ok(())
+}
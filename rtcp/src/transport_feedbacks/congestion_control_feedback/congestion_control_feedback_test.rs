@@ -0,0 +1,171 @@
+use bytes::Bytes;
+
+use super::*;
+
+fn feedback() -> CongestionControlFeedback {
+    CongestionControlFeedback {
+        sender_ssrc: 0x902f9e2e,
+        report_blocks: vec![ReportBlock {
+            media_ssrc: 0x12345678,
+            begin_sequence: 0x10,
+            metric_blocks: vec![
+                MetricBlock::received_at(0x100, EcnCodepoint::Ect0),
+                MetricBlock::default(),
+                MetricBlock::received_at(0x1FFE, EcnCodepoint::Ce),
+            ],
+        }],
+        report_timestamp: 0x11223344,
+    }
+}
+
+#[test]
+fn test_congestion_control_feedback_unmarshal() {
+    let tests = vec![
+        (
+            "valid",
+            Bytes::from_static(&[
+                0x8b, 0xcd, 0x0, 0x6, // v=2, p=0, fmt=11, RTPFB, len=6
+                0x90, 0x2f, 0x9e, 0x2e, // sender=0x902f9e2e
+                0x12, 0x34, 0x56, 0x78, // media=0x12345678
+                0x0, 0x10, 0x0, 0x3, // begin_seq=16, num_reports=3
+                0xc1, 0x0, 0x0, 0x0, // received ECT(0) ato=256, not received
+                0xff, 0xfe, 0x0, 0x0, // received CE ato=over-range, padding
+                0x11, 0x22, 0x33, 0x44, // report timestamp
+            ]),
+            feedback(),
+            None,
+        ),
+        (
+            "no report block",
+            Bytes::from_static(&[
+                0x8b, 0xcd, 0x0, 0x2, // v=2, p=0, fmt=11, RTPFB, len=2
+                0x90, 0x2f, 0x9e, 0x2e, // sender=0x902f9e2e
+                0x11, 0x22, 0x33, 0x44, // report timestamp
+            ]),
+            CongestionControlFeedback {
+                sender_ssrc: 0x902f9e2e,
+                report_blocks: vec![],
+                report_timestamp: 0x11223344,
+            },
+            None,
+        ),
+        (
+            "metric blocks past the end",
+            Bytes::from_static(&[
+                0x8b, 0xcd, 0x0, 0x4, // v=2, p=0, fmt=11, RTPFB, len=4
+                0x90, 0x2f, 0x9e, 0x2e, // sender=0x902f9e2e
+                0x12, 0x34, 0x56, 0x78, // media=0x12345678
+                0x0, 0x10, 0x0, 0x3, // begin_seq=16, num_reports=3
+                0x11, 0x22, 0x33, 0x44, // report timestamp
+            ]),
+            CongestionControlFeedback::default(),
+            Some(Error::PacketTooShort),
+        ),
+        (
+            "wrong type",
+            Bytes::from_static(&[
+                0x81, 0xcd, 0x0, 0x2, // v=2, p=0, fmt=1, RTPFB, len=2
+                0x90, 0x2f, 0x9e, 0x2e, // sender=0x902f9e2e
+                0x11, 0x22, 0x33, 0x44, // report timestamp
+            ]),
+            CongestionControlFeedback::default(),
+            Some(Error::WrongType),
+        ),
+        (
+            "nil",
+            Bytes::from_static(&[]),
+            CongestionControlFeedback::default(),
+            Some(Error::PacketTooShort),
+        ),
+    ];
+
+    for (name, mut data, want, want_error) in tests {
+        let got = CongestionControlFeedback::unmarshal(&mut data);
+
+        assert_eq!(
+            got.is_err(),
+            want_error.is_some(),
+            "Unmarshal {name}: err = {got:?}, want {want_error:?}"
+        );
+
+        if let Some(err) = want_error {
+            let got_err = got.err().unwrap();
+            assert_eq!(
+                err, got_err,
+                "Unmarshal {name}: err = {got_err:?}, want {err:?}",
+            );
+        } else {
+            let actual = got.unwrap();
+            assert_eq!(
+                actual, want,
+                "Unmarshal {name}: got {actual:?}, want {want:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_congestion_control_feedback_roundtrip() {
+    let mut two_blocks = feedback();
+    two_blocks.report_blocks.push(ReportBlock {
+        media_ssrc: 0x9abcdef0,
+        begin_sequence: 0xfffe,
+        metric_blocks: vec![MetricBlock::received_at(0, EcnCodepoint::NotEct); 4],
+    });
+
+    let tests: Vec<(&str, CongestionControlFeedback, Option<Error>)> = vec![
+        ("valid", feedback(), None),
+        ("two report blocks", two_blocks, None),
+        (
+            "too many reports",
+            CongestionControlFeedback {
+                report_blocks: vec![ReportBlock {
+                    metric_blocks: vec![MetricBlock::default(); CCFB_MAX_REPORTS + 1],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            Some(Error::TooManyReports),
+        ),
+    ];
+
+    for (name, want, want_error) in tests {
+        let got = want.marshal();
+
+        assert_eq!(
+            got.is_ok(),
+            want_error.is_none(),
+            "Marshal {name}: err = {got:?}, want {want_error:?}"
+        );
+
+        if let Some(err) = want_error {
+            let got_err = got.err().unwrap();
+            assert_eq!(
+                err, got_err,
+                "Marshal {name}: err = {got_err:?}, want {err:?}",
+            );
+        } else {
+            let mut data = got.ok().unwrap();
+            assert_eq!(data.len() % 4, 0, "{name} is not 32-bit aligned");
+            let actual = CongestionControlFeedback::unmarshal(&mut data)
+                .unwrap_or_else(|_| panic!("Unmarshal {name}"));
+
+            assert_eq!(
+                actual, want,
+                "{name} round trip: got {actual:?}, want {want:?}"
+            )
+        }
+    }
+}
+
+#[test]
+fn test_congestion_control_feedback_in_compound() {
+    let data = feedback().marshal().unwrap();
+    let pkts = unmarshal(&mut data.clone()).unwrap();
+    assert_eq!(pkts.len(), 1);
+    assert_eq!(
+        pkts[0].as_any().downcast_ref::<CongestionControlFeedback>(),
+        Some(&feedback())
+    );
+    assert_eq!(pkts[0].destination_ssrc(), vec![0x12345678]);
+}
@@ -0,0 +1,326 @@
+#[cfg(test)]
+mod congestion_control_feedback_test;
+
+use std::any::Any;
+use std::fmt;
+
+use bytes::{Buf, BufMut};
+use util::marshal::{Marshal, MarshalSize, Unmarshal};
+
+use crate::error::Error;
+use crate::header::*;
+use crate::packet::*;
+use crate::util::*;
+
+type Result<T> = std::result::Result<T, util::Error>;
+
+const CCFB_SENDER_SSRC_LENGTH: usize = SSRC_LENGTH;
+const CCFB_REPORT_BLOCK_HEADER_LENGTH: usize = SSRC_LENGTH + 4;
+const CCFB_METRIC_BLOCK_LENGTH: usize = 2;
+const CCFB_REPORT_TIMESTAMP_LENGTH: usize = 4;
+
+/// Largest number of packets a report block can report.
+pub const CCFB_MAX_REPORTS: usize = 16384;
+/// Largest arrival time offset, which stands for any offset at least as large.
+pub const CCFB_ARRIVAL_TIME_OFFSET_OVERRANGE: u16 = 0x1FFE;
+/// Arrival time offset of a packet whose arrival time is unavailable.
+pub const CCFB_ARRIVAL_TIME_OFFSET_UNAVAILABLE: u16 = 0x1FFF;
+
+/// EcnCodepoint is the ECN marking a packet was received with.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum EcnCodepoint {
+    /// Not ECN-Capable Transport
+    #[default]
+    NotEct = 0,
+    /// ECN Capable Transport (1)
+    Ect1 = 1,
+    /// ECN Capable Transport (0)
+    Ect0 = 2,
+    /// Congestion Experienced
+    Ce = 3,
+}
+
+impl From<u8> for EcnCodepoint {
+    fn from(b: u8) -> Self {
+        match b & 0x03 {
+            1 => EcnCodepoint::Ect1,
+            2 => EcnCodepoint::Ect0,
+            3 => EcnCodepoint::Ce,
+            _ => EcnCodepoint::NotEct,
+        }
+    }
+}
+
+/// MetricBlock is the feedback on one RTP packet of a report block.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct MetricBlock {
+    /// Whether the packet was received. The other fields are zero when it wasn't.
+    pub received: bool,
+    pub ecn: EcnCodepoint,
+    /// Arrival time of the packet before the report timestamp, in 1/1024 of seconds.
+    pub arrival_time_offset: u16,
+}
+
+impl MetricBlock {
+    /// received_at returns the MetricBlock of a packet received arrival_time_offset 1/1024
+    /// of seconds before the report timestamp.
+    pub fn received_at(arrival_time_offset: u16, ecn: EcnCodepoint) -> Self {
+        MetricBlock {
+            received: true,
+            ecn,
+            arrival_time_offset: arrival_time_offset.min(CCFB_ARRIVAL_TIME_OFFSET_OVERRANGE),
+        }
+    }
+
+    fn marshal(&self) -> u16 {
+        if !self.received {
+            return 0;
+        }
+        0x8000 | (self.ecn as u16) << 13 | (self.arrival_time_offset & 0x1FFF)
+    }
+
+    fn unmarshal(b: u16) -> Self {
+        MetricBlock {
+            received: b & 0x8000 != 0,
+            ecn: EcnCodepoint::from((b >> 13) as u8),
+            arrival_time_offset: b & 0x1FFF,
+        }
+    }
+}
+
+/// ReportBlock is the feedback on the RTP packets of a stream, from begin_sequence on.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ReportBlock {
+    /// SSRC of the RTP stream reported.
+    pub media_ssrc: u32,
+    /// Sequence number of the first packet reported.
+    pub begin_sequence: u16,
+    /// Feedback on each packet with consecutive sequence numbers, starting at begin_sequence.
+    pub metric_blocks: Vec<MetricBlock>,
+}
+
+impl ReportBlock {
+    fn raw_size(&self) -> usize {
+        let n =
+            CCFB_REPORT_BLOCK_HEADER_LENGTH + self.metric_blocks.len() * CCFB_METRIC_BLOCK_LENGTH;
+        // metric blocks are padded to 32 bits
+        n + get_padding_size(n)
+    }
+}
+
+/// CongestionControlFeedback is the RTCP feedback a receiver sends on the arrival of the RTP
+/// packets of its streams, for the congestion control of their sender.
+///
+/// ## Specifications
+///
+/// * [RFC 8888 §3.1]
+///
+/// [RFC 8888 §3.1]: https://tools.ietf.org/html/rfc8888#section-3.1
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CongestionControlFeedback {
+    /// SSRC of sender
+    pub sender_ssrc: u32,
+    pub report_blocks: Vec<ReportBlock>,
+    /// Time the report was sent, the middle 32 bits of a NTP timestamp, which the arrival
+    /// time offsets are relative to.
+    pub report_timestamp: u32,
+}
+
+impl fmt::Display for CongestionControlFeedback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = format!(
+            "CongestionControlFeedback from {:x} at {:x}\n",
+            self.sender_ssrc, self.report_timestamp
+        );
+        for block in &self.report_blocks {
+            out += format!(
+                "\t{:x} {} packets from {}\n",
+                block.media_ssrc,
+                block.metric_blocks.len(),
+                block.begin_sequence
+            )
+            .as_str();
+        }
+        write!(f, "{out}")
+    }
+}
+
+impl Packet for CongestionControlFeedback {
+    /// Header returns the Header associated with this packet.
+    fn header(&self) -> Header {
+        Header {
+            padding: get_padding_size(self.raw_size()) != 0,
+            count: FORMAT_CCFB,
+            packet_type: PacketType::TransportSpecificFeedback,
+            length: ((self.marshal_size() / 4) - 1) as u16,
+        }
+    }
+
+    /// destination_ssrc returns an array of SSRC values that this packet refers to.
+    fn destination_ssrc(&self) -> Vec<u32> {
+        self.report_blocks.iter().map(|b| b.media_ssrc).collect()
+    }
+
+    fn raw_size(&self) -> usize {
+        HEADER_LENGTH
+            + CCFB_SENDER_SSRC_LENGTH
+            + self
+                .report_blocks
+                .iter()
+                .map(|b| b.raw_size())
+                .sum::<usize>()
+            + CCFB_REPORT_TIMESTAMP_LENGTH
+    }
+
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+
+    fn equal(&self, other: &(dyn Packet + Send + Sync)) -> bool {
+        other
+            .as_any()
+            .downcast_ref::<CongestionControlFeedback>()
+            .is_some_and(|a| self == a)
+    }
+
+    fn cloned(&self) -> Box<dyn Packet + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+impl MarshalSize for CongestionControlFeedback {
+    fn marshal_size(&self) -> usize {
+        let l = self.raw_size();
+        // align to 32-bit boundary
+        l + get_padding_size(l)
+    }
+}
+
+impl Marshal for CongestionControlFeedback {
+    /// Marshal encodes the CongestionControlFeedback in binary
+    fn marshal_to(&self, mut buf: &mut [u8]) -> Result<usize> {
+        if self
+            .report_blocks
+            .iter()
+            .any(|b| b.metric_blocks.len() > CCFB_MAX_REPORTS)
+        {
+            return Err(Error::TooManyReports.into());
+        }
+        if buf.remaining_mut() < self.marshal_size() {
+            return Err(Error::BufferTooShort.into());
+        }
+
+        let h = self.header();
+        let n = h.marshal_to(buf)?;
+        buf = &mut buf[n..];
+
+        buf.put_u32(self.sender_ssrc);
+        for block in &self.report_blocks {
+            buf.put_u32(block.media_ssrc);
+            buf.put_u16(block.begin_sequence);
+            buf.put_u16(block.metric_blocks.len() as u16);
+            for metric in &block.metric_blocks {
+                buf.put_u16(metric.marshal());
+            }
+            if block.metric_blocks.len() % 2 == 1 {
+                buf.put_u16(0);
+            }
+        }
+        buf.put_u32(self.report_timestamp);
+
+        Ok(self.marshal_size())
+    }
+}
+
+impl Unmarshal for CongestionControlFeedback {
+    /// Unmarshal decodes the CongestionControlFeedback from binary
+    fn unmarshal<B>(raw_packet: &mut B) -> Result<Self>
+    where
+        Self: Sized,
+        B: Buf,
+    {
+        let raw_packet_len = raw_packet.remaining();
+        if raw_packet_len < HEADER_LENGTH + CCFB_SENDER_SSRC_LENGTH + CCFB_REPORT_TIMESTAMP_LENGTH {
+            return Err(Error::PacketTooShort.into());
+        }
+
+        let h = Header::unmarshal(raw_packet)?;
+        if h.packet_type != PacketType::TransportSpecificFeedback || h.count != FORMAT_CCFB {
+            return Err(Error::WrongType.into());
+        }
+
+        let total_length = (h.length as usize + 1) * 4;
+        if raw_packet_len < total_length
+            || total_length < HEADER_LENGTH + CCFB_SENDER_SSRC_LENGTH + CCFB_REPORT_TIMESTAMP_LENGTH
+        {
+            return Err(Error::PacketTooShort.into());
+        }
+        let mut body = raw_packet.copy_to_bytes(total_length - HEADER_LENGTH);
+        if h.padding {
+            let padding_length = *body.last().unwrap_or(&0) as usize;
+            if padding_length == 0 || padding_length > body.len() {
+                return Err(Error::WrongPadding.into());
+            }
+            body.truncate(body.len() - padding_length);
+        }
+        let ccfb = CongestionControlFeedback::unmarshal_body(&mut body)?;
+
+        if
+        /*h.padding &&*/
+        raw_packet.has_remaining() {
+            raw_packet.advance(raw_packet.remaining());
+        }
+
+        Ok(ccfb)
+    }
+}
+
+impl CongestionControlFeedback {
+    fn unmarshal_body<B: Buf>(body: &mut B) -> Result<Self> {
+        if body.remaining() < CCFB_SENDER_SSRC_LENGTH + CCFB_REPORT_TIMESTAMP_LENGTH {
+            return Err(Error::PacketTooShort.into());
+        }
+        let sender_ssrc = body.get_u32();
+
+        let mut report_blocks = vec![];
+        while body.remaining() > CCFB_REPORT_TIMESTAMP_LENGTH {
+            if body.remaining() < CCFB_REPORT_BLOCK_HEADER_LENGTH + CCFB_REPORT_TIMESTAMP_LENGTH {
+                return Err(Error::PacketTooShort.into());
+            }
+            let media_ssrc = body.get_u32();
+            let begin_sequence = body.get_u16();
+            let num_reports = body.get_u16() as usize;
+            if num_reports > CCFB_MAX_REPORTS {
+                return Err(Error::TooManyReports.into());
+            }
+
+            let padded = num_reports + num_reports % 2;
+            if body.remaining() < padded * CCFB_METRIC_BLOCK_LENGTH + CCFB_REPORT_TIMESTAMP_LENGTH {
+                return Err(Error::PacketTooShort.into());
+            }
+            let metric_blocks = (0..num_reports)
+                .map(|_| MetricBlock::unmarshal(body.get_u16()))
+                .collect();
+            if num_reports % 2 == 1 {
+                body.advance(CCFB_METRIC_BLOCK_LENGTH);
+            }
+
+            report_blocks.push(ReportBlock {
+                media_ssrc,
+                begin_sequence,
+                metric_blocks,
+            });
+        }
+
+        if body.remaining() != CCFB_REPORT_TIMESTAMP_LENGTH {
+            return Err(Error::PacketTooShort.into());
+        }
+        let report_timestamp = body.get_u32();
+
+        Ok(CongestionControlFeedback {
+            sender_ssrc,
+            report_blocks,
+            report_timestamp,
+        })
+    }
+}
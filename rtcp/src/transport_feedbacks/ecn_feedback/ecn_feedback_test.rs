@@ -0,0 +1,123 @@
+use bytes::Bytes;
+
+use super::*;
+
+fn feedback() -> EcnFeedback {
+    EcnFeedback {
+        sender_ssrc: 0x902f9e2e,
+        media_ssrc: 0x12345678,
+        extended_highest_sequence_number: 0x00011234,
+        counters: EcnCounters {
+            ect0: 1000,
+            ect1: 2,
+            ce: 30,
+            not_ect: 4,
+            lost: 5,
+            duplicates: 6,
+        },
+    }
+}
+
+#[test]
+fn test_ecn_feedback_unmarshal() {
+    let tests = vec![
+        (
+            "valid",
+            Bytes::from_static(&[
+                0x88, 0xcd, 0x0, 0x7, // v=2, p=0, FMT=8, RTPFB, len=7
+                0x90, 0x2f, 0x9e, 0x2e, // sender=0x902f9e2e
+                0x12, 0x34, 0x56, 0x78, // media=0x12345678
+                0x0, 0x1, 0x12, 0x34, // extended highest sequence number
+                0x0, 0x0, 0x3, 0xe8, // ECT(0)=1000
+                0x0, 0x0, 0x0, 0x2, // ECT(1)=2
+                0x0, 0x1e, 0x0, 0x4, // CE=30, not-ECT=4
+                0x0, 0x5, 0x0, 0x6, // lost=5, duplicates=6
+            ]),
+            feedback(),
+            None,
+        ),
+        (
+            "short report",
+            Bytes::from_static(&[
+                0x88, 0xcd, 0x0, 0x3, // v=2, p=0, FMT=8, RTPFB, len=3
+                0x90, 0x2f, 0x9e, 0x2e, // sender=0x902f9e2e
+                0x12, 0x34, 0x56, 0x78, // media=0x12345678
+                0x0, 0x1, 0x12, 0x34, // extended highest sequence number
+            ]),
+            EcnFeedback::default(),
+            Some(Error::PacketTooShort),
+        ),
+        (
+            "wrong fmt",
+            Bytes::from_static(&[
+                0x81, 0xcd, 0x0, 0x7, // v=2, p=0, FMT=1, RTPFB, len=7
+                0x90, 0x2f, 0x9e, 0x2e, // sender=0x902f9e2e
+                0x12, 0x34, 0x56, 0x78, // media=0x12345678
+                0x0, 0x1, 0x12, 0x34, // extended highest sequence number
+                0x0, 0x0, 0x3, 0xe8, // ECT(0)=1000
+                0x0, 0x0, 0x0, 0x2, // ECT(1)=2
+                0x0, 0x1e, 0x0, 0x4, // CE=30, not-ECT=4
+                0x0, 0x5, 0x0, 0x6, // lost=5, duplicates=6
+            ]),
+            EcnFeedback::default(),
+            Some(Error::WrongType),
+        ),
+    ];
+
+    for (name, mut data, want, want_error) in tests {
+        let got = EcnFeedback::unmarshal(&mut data);
+
+        assert_eq!(
+            got.is_err(),
+            want_error.is_some(),
+            "Unmarshal {name}: err = {got:?}, want {want_error:?}"
+        );
+
+        if let Some(err) = want_error {
+            let got_err = got.err().unwrap();
+            assert_eq!(
+                err, got_err,
+                "Unmarshal {name}: err = {got_err:?}, want {err:?}",
+            );
+        } else {
+            let actual = got.unwrap();
+            assert_eq!(
+                actual, want,
+                "Unmarshal {name}: got {actual:?}, want {want:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_ecn_feedback_roundtrip() {
+    let want = feedback();
+    let data = want.marshal().unwrap();
+    assert_eq!(data.len(), 32);
+
+    let pkts = unmarshal(&mut data.clone()).unwrap();
+    assert_eq!(pkts.len(), 1);
+    assert_eq!(pkts[0].as_any().downcast_ref::<EcnFeedback>(), Some(&want));
+}
+
+#[test]
+fn test_ecn_counters_record() {
+    let mut counters = EcnCounters {
+        ce: u16::MAX,
+        ..Default::default()
+    };
+    counters.record(EcnCodepoint::Ect0);
+    counters.record(EcnCodepoint::Ect1);
+    counters.record(EcnCodepoint::Ce);
+    counters.record(EcnCodepoint::NotEct);
+    assert_eq!(
+        counters,
+        EcnCounters {
+            ect0: 1,
+            ect1: 1,
+            ce: 0,
+            not_ect: 1,
+            ..Default::default()
+        }
+    );
+}
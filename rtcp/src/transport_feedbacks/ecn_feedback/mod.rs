@@ -0,0 +1,213 @@
+#[cfg(test)]
+mod ecn_feedback_test;
+
+use std::any::Any;
+use std::fmt;
+
+use bytes::{Buf, BufMut};
+use util::marshal::{Marshal, MarshalSize, Unmarshal};
+
+use crate::error::Error;
+use crate::header::*;
+use crate::packet::*;
+use crate::transport_feedbacks::congestion_control_feedback::EcnCodepoint;
+use crate::util::*;
+
+type Result<T> = std::result::Result<T, util::Error>;
+
+const ECN_OFFSET: usize = 8;
+/// Length of the ECN counters, as carried by EcnFeedback and EcnSummaryReportBlock.
+pub const ECN_COUNTERS_LENGTH: usize = 16;
+const ECN_FCI_LENGTH: usize = 4 + ECN_COUNTERS_LENGTH;
+
+/// EcnCounters count the ECN marks of the packets of a stream received since the start of
+/// the session. The counters wrap around.
+#[derive(Debug, PartialEq, Eq, Default, Copy, Clone)]
+pub struct EcnCounters {
+    /// Packets received marked ECT(0).
+    pub ect0: u32,
+    /// Packets received marked ECT(1).
+    pub ect1: u32,
+    /// Packets received marked ECN-CE.
+    pub ce: u16,
+    /// Packets received not marked ECN-capable.
+    pub not_ect: u16,
+    /// Packets expected but not received.
+    pub lost: u16,
+    /// Packets received more than once.
+    pub duplicates: u16,
+}
+
+impl EcnCounters {
+    /// record counts a packet received with ecn.
+    pub fn record(&mut self, ecn: EcnCodepoint) {
+        match ecn {
+            EcnCodepoint::Ect0 => self.ect0 = self.ect0.wrapping_add(1),
+            EcnCodepoint::Ect1 => self.ect1 = self.ect1.wrapping_add(1),
+            EcnCodepoint::Ce => self.ce = self.ce.wrapping_add(1),
+            EcnCodepoint::NotEct => self.not_ect = self.not_ect.wrapping_add(1),
+        }
+    }
+
+    pub(crate) fn marshal_to<B: BufMut>(&self, buf: &mut B) {
+        buf.put_u32(self.ect0);
+        buf.put_u32(self.ect1);
+        buf.put_u16(self.ce);
+        buf.put_u16(self.not_ect);
+        buf.put_u16(self.lost);
+        buf.put_u16(self.duplicates);
+    }
+
+    pub(crate) fn unmarshal<B: Buf>(buf: &mut B) -> Self {
+        EcnCounters {
+            ect0: buf.get_u32(),
+            ect1: buf.get_u32(),
+            ce: buf.get_u16(),
+            not_ect: buf.get_u16(),
+            lost: buf.get_u16(),
+            duplicates: buf.get_u16(),
+        }
+    }
+}
+
+/// The EcnFeedback packet reports the ECN marks of the packets of a stream received, so that
+/// its sender reacts to the congestion marked ECN-CE, as specified in RFC 6679 section 5.1.
+#[derive(Debug, PartialEq, Eq, Default, Clone)]
+pub struct EcnFeedback {
+    /// SSRC of sender
+    pub sender_ssrc: u32,
+    /// SSRC of the media source
+    pub media_ssrc: u32,
+    /// Extended highest sequence number received, as in the reception reports.
+    pub extended_highest_sequence_number: u32,
+    pub counters: EcnCounters,
+}
+
+impl fmt::Display for EcnFeedback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "EcnFeedback {:x} {:x} {} {:?}",
+            self.sender_ssrc, self.media_ssrc, self.extended_highest_sequence_number, self.counters
+        )
+    }
+}
+
+impl Packet for EcnFeedback {
+    /// Header returns the Header associated with this packet.
+    fn header(&self) -> Header {
+        Header {
+            padding: get_padding_size(self.raw_size()) != 0,
+            count: FORMAT_ECN,
+            packet_type: PacketType::TransportSpecificFeedback,
+            length: ((self.marshal_size() / 4) - 1) as u16,
+        }
+    }
+
+    /// destination_ssrc returns an array of SSRC values that this packet refers to.
+    fn destination_ssrc(&self) -> Vec<u32> {
+        vec![self.media_ssrc]
+    }
+
+    fn raw_size(&self) -> usize {
+        HEADER_LENGTH + ECN_OFFSET + ECN_FCI_LENGTH
+    }
+
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+
+    fn equal(&self, other: &(dyn Packet + Send + Sync)) -> bool {
+        other
+            .as_any()
+            .downcast_ref::<EcnFeedback>()
+            .is_some_and(|a| self == a)
+    }
+
+    fn cloned(&self) -> Box<dyn Packet + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+impl MarshalSize for EcnFeedback {
+    fn marshal_size(&self) -> usize {
+        let l = self.raw_size();
+        // align to 32-bit boundary
+        l + get_padding_size(l)
+    }
+}
+
+impl Marshal for EcnFeedback {
+    /// Marshal encodes the EcnFeedback in binary
+    fn marshal_to(&self, mut buf: &mut [u8]) -> Result<usize> {
+        /*
+         *  0                   1                   2                   3
+         *  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+         * +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+         * | Extended Highest Sequence Number                              |
+         * +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+         * | ECT (0) Counter                                               |
+         * +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+         * | ECT (1) Counter                                               |
+         * +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+         * | ECN-CE Counter                | not-ECT Counter               |
+         * +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+         * | Lost Packets Counter          | Duplication Counter           |
+         * +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+         */
+        if buf.remaining_mut() < self.marshal_size() {
+            return Err(Error::BufferTooShort.into());
+        }
+
+        let h = self.header();
+        let n = h.marshal_to(buf)?;
+        buf = &mut buf[n..];
+
+        buf.put_u32(self.sender_ssrc);
+        buf.put_u32(self.media_ssrc);
+        buf.put_u32(self.extended_highest_sequence_number);
+        self.counters.marshal_to(&mut buf);
+
+        Ok(self.marshal_size())
+    }
+}
+
+impl Unmarshal for EcnFeedback {
+    /// Unmarshal decodes the EcnFeedback from binary
+    fn unmarshal<B>(raw_packet: &mut B) -> Result<Self>
+    where
+        Self: Sized,
+        B: Buf,
+    {
+        let raw_packet_len = raw_packet.remaining();
+        if raw_packet_len < HEADER_LENGTH + ECN_OFFSET + ECN_FCI_LENGTH {
+            return Err(Error::PacketTooShort.into());
+        }
+
+        let h = Header::unmarshal(raw_packet)?;
+        if h.packet_type != PacketType::TransportSpecificFeedback || h.count != FORMAT_ECN {
+            return Err(Error::WrongType.into());
+        }
+        if (h.length as usize + 1) * 4 < HEADER_LENGTH + ECN_OFFSET + ECN_FCI_LENGTH {
+            return Err(Error::PacketTooShort.into());
+        }
+
+        let sender_ssrc = raw_packet.get_u32();
+        let media_ssrc = raw_packet.get_u32();
+        let extended_highest_sequence_number = raw_packet.get_u32();
+        let counters = EcnCounters::unmarshal(raw_packet);
+
+        if
+        /*h.padding &&*/
+        raw_packet.has_remaining() {
+            raw_packet.advance(raw_packet.remaining());
+        }
+
+        Ok(EcnFeedback {
+            sender_ssrc,
+            media_ssrc,
+            extended_highest_sequence_number,
+            counters,
+        })
+    }
+}
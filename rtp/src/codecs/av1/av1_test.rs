@@ -452,3 +452,106 @@ fn test_split_two_obus_into_two_packets() -> Result<()> {
     );
     Ok(())
 }
+
+#[test]
+fn test_packetize_large_obu_sizes() -> Result<()> {
+    // The size of the first OBU element takes two bytes.
+    let frame = build_av1_frame(&vec![
+        Av1Obu::new(OBU_TYPE_SEQUENCE_HEADER).with_payload(vec![11; 299]),
+        Av1Obu::new(OBU_TYPE_FRAME).with_payload(vec![1, 2]),
+    ]);
+    let mut payloader = Av1Payloader {};
+    let result = payloader.payload(1200, &frame)?;
+    assert_eq!(result.len(), 1);
+    assert_eq!(
+        &result[0][..4],
+        &[0b0010_1000, 0b1010_1100, 0b0000_0010, 1 << 3]
+    );
+    Ok(())
+}
+
+fn depacketize_all(packets: &[Bytes]) -> Result<Bytes> {
+    let mut depacketizer = Av1Packet::default();
+    let mut frame = BytesMut::new();
+    for packet in packets {
+        frame.extend_from_slice(&depacketizer.depacketize(packet)?);
+    }
+    Ok(frame.freeze())
+}
+
+#[test]
+fn test_depacketize_round_trip() -> Result<()> {
+    let obus = || {
+        vec![
+            Av1Obu::new(OBU_TYPE_SEQUENCE_HEADER).with_payload(vec![11; 200]),
+            Av1Obu::new(OBU_TYPE_FRAME_HEADER)
+                .with_extension(OBU_EXTENSION_S1T1)
+                .with_payload(vec![21, 22, 23]),
+            Av1Obu::new(OBU_TYPE_TILE_GROUP).with_payload((0..=255).cycle().take(3000).collect()),
+        ]
+    };
+    let mut with_temporal_delimiter = vec![Av1Obu::new(OBU_TYPE_TEMPORAL_DELIMITER)];
+    with_temporal_delimiter.extend(obus());
+    let frame = build_av1_frame(&with_temporal_delimiter);
+
+    for mtu in [100, 1200, 5000] {
+        let mut payloader = Av1Payloader {};
+        let packets = payloader.payload(mtu, &frame)?;
+        assert_eq!(
+            depacketize_all(&packets)?,
+            build_av1_frame(&obus()),
+            "the frame should be restored without the temporal delimiter, mtu {mtu}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_depacketize_aggregation_header() -> Result<()> {
+    let mut depacketizer = Av1Packet::default();
+    let payload = depacketizer.depacketize(&Bytes::from_static(&[
+        0b0001_1000,         // aggregation header
+        OBU_TYPE_FRAME << 3, // header of the only OBU, without its size
+        1,
+        2,
+    ]))?;
+    assert!(!depacketizer.z);
+    assert!(!depacketizer.y);
+    assert_eq!(depacketizer.w, 1);
+    assert!(depacketizer.n);
+    assert_eq!(
+        &payload[..],
+        &[OBU_TYPE_FRAME << 3 | OBU_HAS_SIZE_BIT, 2, 1, 2]
+    );
+    assert!(depacketizer.is_partition_head(&Bytes::from_static(&[0b0001_1000])));
+    assert!(!depacketizer.is_partition_head(&Bytes::from_static(&[0b1001_0000])));
+    assert!(depacketizer.is_partition_tail(true, &Bytes::new()));
+
+    assert_eq!(
+        depacketizer.depacketize(&Bytes::from_static(&[0b0001_0000])),
+        Err(Error::ErrShortPacket)
+    );
+    assert_eq!(
+        depacketizer.depacketize(&Bytes::from_static(&[0b0000_0000, 5, 1 << 3])),
+        Err(Error::ErrPayloadTooSmallForObuPayloadSize)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_depacketize_drops_fragment_without_start() -> Result<()> {
+    let frame = build_av1_frame(&vec![
+        Av1Obu::new(OBU_TYPE_FRAME).with_payload(vec![1, 2, 3, 4, 5, 6, 7, 8, 9]),
+        Av1Obu::new(OBU_TYPE_METADATA).with_payload(vec![31]),
+    ]);
+    let mut payloader = Av1Payloader {};
+    let packets = payloader.payload(8, &frame)?;
+    assert_eq!(packets.len(), 2);
+
+    // The first packet was lost, so only the OBU which starts in the second one is left.
+    assert_eq!(
+        depacketize_all(&packets[1..])?,
+        build_av1_frame(&vec![Av1Obu::new(OBU_TYPE_METADATA).with_payload(vec![31])])
+    );
+    Ok(())
+}
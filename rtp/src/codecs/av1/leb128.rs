@@ -1,19 +1,5 @@
 use bytes::{BufMut, Bytes, BytesMut};
 
-pub fn encode_leb128(mut val: u32) -> u32 {
-    let mut b = 0;
-    loop {
-        b |= val & 0b_0111_1111;
-        val >>= 7;
-        if val != 0 {
-            b |= 0b_1000_0000;
-            b <<= 8;
-        } else {
-            return b;
-        }
-    }
-}
-
 pub fn decode_leb128(mut val: u32) -> u32 {
     let mut b = 0;
     loop {
@@ -53,12 +39,12 @@ pub trait BytesMutExt {
 }
 
 impl BytesMutExt for BytesMut {
-    fn put_leb128(&mut self, n: u32) {
-        let mut encoded = encode_leb128(n);
-        while encoded >= 0b_1000_0000 {
-            self.put_u8(0b_1000_0000 | (encoded & 0b_0111_1111) as u8);
-            encoded >>= 7;
+    fn put_leb128(&mut self, mut n: u32) {
+        // Seven bits at a time, least significant first.
+        while n >= 0b_1000_0000 {
+            self.put_u8(0b_1000_0000 | (n & 0b_0111_1111) as u8);
+            n >>= 7;
         }
-        self.put_u8(encoded as u8);
+        self.put_u8(n as u8);
     }
 }
@@ -1,11 +1,17 @@
 use bytes::{BufMut, Bytes, BytesMut};
 
-use crate::codecs::av1::leb128::BytesMutExt;
-use crate::codecs::av1::obu::{obu_has_extension, parse_obus, OBU_HAS_SIZE_BIT};
+use crate::codecs::av1::leb128::{read_leb128, BytesMutExt};
+use crate::codecs::av1::obu::{
+    obu_has_extension, obu_has_size, obu_type, parse_obus, OBU_HAS_SIZE_BIT, OBU_TYPE_PADDING,
+    OBU_TYPE_TEMPORAL_DELIMITER, OBU_TYPE_TILE_LIST,
+};
 use crate::codecs::av1::packetizer::{
-    get_aggregation_header, packetize, AGGREGATION_HEADER_SIZE, MAX_NUM_OBUS_TO_OMIT_SIZE,
+    get_aggregation_header, packetize, AGGREGATION_HEADER_N_BIT, AGGREGATION_HEADER_SIZE,
+    AGGREGATION_HEADER_W_MASK, AGGREGATION_HEADER_Y_BIT, AGGREGATION_HEADER_Z_BIT,
+    MAX_NUM_OBUS_TO_OMIT_SIZE,
 };
-use crate::packetizer::Payloader;
+use crate::error::{Error, Result};
+use crate::packetizer::{Depacketizer, Payloader};
 
 #[cfg(test)]
 mod av1_test;
@@ -19,7 +25,7 @@ pub struct Av1Payloader {}
 impl Payloader for Av1Payloader {
     /// Based on <https://chromium.googlesource.com/external/webrtc/+/4e513346ec56c829b3a6010664998469fc237b35/modules/rtp_rtcp/source/rtp_packetizer_av1.cc>
     /// Reference: <https://aomediacodec.github.io/av1-rtp-spec/#45-payload-structure>
-    fn payload(&mut self, mtu: usize, payload: &Bytes) -> crate::error::Result<Vec<Bytes>> {
+    fn payload(&mut self, mtu: usize, payload: &Bytes) -> Result<Vec<Bytes>> {
         // 0                   1                   2                   3
         // 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
         // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
@@ -120,3 +126,127 @@ impl Payloader for Av1Payloader {
         Box::new(self.clone())
     }
 }
+
+/// Av1Packet depacketizes AV1 RTP packets into OBUs of the low overhead bitstream format, as
+/// read by AV1 decoders, i.e. each one with its obu_size field. OBUs fragmented over several
+/// packets are put back together, so packets must be depacketized in order, and temporal
+/// delimiter, tile list and padding OBUs are dropped.
+/// Reference: <https://aomediacodec.github.io/av1-rtp-spec/#7-depacketization>
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct Av1Packet {
+    /// The first OBU element is the continuation of an OBU from the previous packet.
+    pub z: bool,
+    /// The last OBU element continues in the next packet.
+    pub y: bool,
+    /// Number of OBU elements, or zero if each one is preceded by its size.
+    pub w: u8,
+    /// The packet is the first of a coded video sequence.
+    pub n: bool,
+
+    /// The start of an OBU continued in the next packet.
+    fragment: Option<BytesMut>,
+}
+
+impl Depacketizer for Av1Packet {
+    fn depacketize(&mut self, packet: &Bytes) -> Result<Bytes> {
+        if packet.len() <= AGGREGATION_HEADER_SIZE {
+            return Err(Error::ErrShortPacket);
+        }
+
+        let header = packet[0];
+        self.z = header & AGGREGATION_HEADER_Z_BIT != 0;
+        self.y = header & AGGREGATION_HEADER_Y_BIT != 0;
+        self.w = (header & AGGREGATION_HEADER_W_MASK) >> 4;
+        self.n = header & AGGREGATION_HEADER_N_BIT != 0;
+
+        // Without a continuation, the start of an OBU from a lost packet is of no use.
+        let mut fragment = self.fragment.take();
+        if !self.z {
+            fragment = None;
+        }
+
+        let mut payload = BytesMut::new();
+        let mut rest = packet.slice(AGGREGATION_HEADER_SIZE..);
+        let mut index = 0;
+        while !rest.is_empty() {
+            index += 1;
+            let element = if self.w != 0 && index == self.w as usize {
+                // The size of the last element is omitted if W is set.
+                std::mem::take(&mut rest)
+            } else {
+                let (size, leb128_size) = read_leb128(&rest);
+                if leb128_size == 0 || rest.len() - leb128_size < size as usize {
+                    return Err(Error::ErrPayloadTooSmallForObuPayloadSize);
+                }
+                let element = rest.slice(leb128_size..leb128_size + size as usize);
+                rest = rest.slice(leb128_size + size as usize..);
+                element
+            };
+
+            let obu = if index == 1 && self.z {
+                match fragment.take() {
+                    Some(mut obu) => {
+                        obu.extend_from_slice(&element);
+                        obu
+                    }
+                    // The start of this OBU was lost.
+                    None => continue,
+                }
+            } else {
+                BytesMut::from(&element[..])
+            };
+
+            if rest.is_empty() && self.y {
+                self.fragment = Some(obu);
+                break;
+            }
+            put_obu(&mut payload, &obu)?;
+        }
+
+        Ok(payload.freeze())
+    }
+
+    fn is_partition_head(&self, payload: &Bytes) -> bool {
+        if payload.is_empty() {
+            false
+        } else {
+            (payload[0] & AGGREGATION_HEADER_Z_BIT) == 0
+        }
+    }
+
+    fn is_partition_tail(&self, marker: bool, _payload: &Bytes) -> bool {
+        marker
+    }
+}
+
+/// put_obu appends an OBU as received in an OBU element to payload, with its obu_size field.
+fn put_obu(payload: &mut BytesMut, obu: &[u8]) -> Result<()> {
+    if obu.is_empty() {
+        return Ok(());
+    }
+
+    let header = obu[0];
+    let obu_type = obu_type(header);
+    if obu_type == OBU_TYPE_TEMPORAL_DELIMITER
+        || obu_type == OBU_TYPE_TILE_LIST
+        || obu_type == OBU_TYPE_PADDING
+    {
+        return Ok(());
+    }
+
+    let header_size = if obu_has_extension(header) { 2 } else { 1 };
+    if obu.len() < header_size {
+        return Err(Error::ErrPayloadTooSmallForObuExtensionHeader);
+    }
+    // A sender may keep the obu_size field, although it should not.
+    if obu_has_size(header) {
+        payload.extend_from_slice(obu);
+        return Ok(());
+    }
+
+    payload.put_u8(header | OBU_HAS_SIZE_BIT);
+    payload.extend_from_slice(&obu[1..header_size]);
+    payload.put_leb128((obu.len() - header_size) as u32);
+    payload.extend_from_slice(&obu[header_size..]);
+    Ok(())
+}
@@ -11,6 +11,15 @@ use crate::codecs::av1::obu::{obu_type, Obu, OBU_TYPE_SEQUENCE_HEADER};
 pub const MAX_NUM_OBUS_TO_OMIT_SIZE: usize = 3;
 pub const AGGREGATION_HEADER_SIZE: usize = 1;
 
+/// Z: the first OBU element is the continuation of an OBU from the previous packet.
+pub const AGGREGATION_HEADER_Z_BIT: u8 = 0b1000_0000;
+/// Y: the last OBU element continues in the next packet.
+pub const AGGREGATION_HEADER_Y_BIT: u8 = 0b0100_0000;
+/// W: number of OBU elements, or zero if each one is preceded by its size.
+pub const AGGREGATION_HEADER_W_MASK: u8 = 0b0011_0000;
+/// N: the packet is the first of a coded video sequence.
+pub const AGGREGATION_HEADER_N_BIT: u8 = 0b0000_1000;
+
 pub struct PacketMetadata {
     pub first_obu_index: usize,
     pub num_obu_elements: usize,
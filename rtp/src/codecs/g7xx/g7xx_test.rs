@@ -48,3 +48,12 @@ fn test_g7xx_payload() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_g7xx_timestamp_increment() {
+    // 20ms of audio.
+    let payload = Bytes::from(vec![0u8; 160]);
+    assert_eq!(g711_timestamp_increment(&payload), 160);
+    assert_eq!(g722_timestamp_increment(&payload), 160);
+    assert_eq!(g722_samples_to_timestamp(320), 160);
+}
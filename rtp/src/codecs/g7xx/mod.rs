@@ -6,6 +6,29 @@ use bytes::Bytes;
 use crate::error::Result;
 use crate::packetizer::Payloader;
 
+/// Clock rate of G.711 and G.722. G.722 samples at 16kHz, but its RTP clock rate is 8kHz for
+/// historical reasons (RFC 3551 section 4.5.2).
+pub const G7XX_CLOCK_RATE: u32 = 8000;
+/// Sample rate of G.722 audio.
+pub const G722_SAMPLE_RATE: u32 = 16000;
+
+/// g711_timestamp_increment returns the timestamp increment of a G.711 payload, one per byte.
+pub fn g711_timestamp_increment(payload: &Bytes) -> u32 {
+    payload.len() as u32
+}
+
+/// g722_timestamp_increment returns the timestamp increment of a G.722 payload. Each byte
+/// carries two samples, which take one tick of the 8kHz clock.
+pub fn g722_timestamp_increment(payload: &Bytes) -> u32 {
+    payload.len() as u32
+}
+
+/// g722_samples_to_timestamp converts a number of 16kHz G.722 samples to the timestamp
+/// increment of the 8kHz clock.
+pub fn g722_samples_to_timestamp(samples: u32) -> u32 {
+    samples / (G722_SAMPLE_RATE / G7XX_CLOCK_RATE)
+}
+
 /// G711Payloader payloads G711 packets
 pub type G711Payloader = G7xxPayloader;
 /// G722Payloader payloads G722 packets
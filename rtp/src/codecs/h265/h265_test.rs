@@ -887,3 +887,85 @@ fn test_h265_packet_real() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_hevc_payloader_round_trip() -> Result<()> {
+    let vps = Bytes::from_static(&[0x40, 0x01, 0x0c, 0x01]);
+    let sps = Bytes::from_static(&[0x42, 0x01, 0x01, 0x60]);
+    let pps = Bytes::from_static(&[0x44, 0x01, 0xc0, 0xf2]);
+    // A CRA NAL unit which doesn't fit into a packet.
+    let mut cra = BytesMut::from(&[0x2a, 0x01][..]);
+    cra.extend((0..2500).map(|i| (i % 251) as u8));
+    let cra = cra.freeze();
+
+    let mut frame = BytesMut::new();
+    for nalu in [&vps, &sps, &pps, &cra] {
+        frame.extend_from_slice(&ANNEXB_NALUSTART_CODE);
+        frame.extend_from_slice(nalu);
+    }
+
+    let mut payloader = HevcPayloader::default();
+    let payloads = payloader.payload(1200, &frame.freeze())?;
+    assert_eq!(payloads.len(), 4, "expected an AP and three FUs");
+    assert_eq!(
+        &payloads[0][..2],
+        &[0x60, 0x01],
+        "invalid AP payload header"
+    );
+    for (i, payload) in payloads[1..].iter().enumerate() {
+        assert!(payload.len() <= 1200);
+        assert_eq!(&payload[..2], &[0x62, 0x01], "invalid FU payload header");
+        let fu_header = H265FragmentationUnitHeader(payload[2]);
+        assert_eq!(fu_header.fu_type(), 21);
+        assert_eq!(fu_header.s(), i == 0);
+        assert_eq!(fu_header.e(), i == 2);
+    }
+
+    let mut pck = H265Packet::default();
+    assert!(pck.is_partition_head(&payloads[1]));
+    assert!(!pck.is_partition_head(&payloads[2]));
+
+    let mut expected = BytesMut::new();
+    for nalu in [&vps, &sps, &pps] {
+        expected.extend_from_slice(&ANNEXB_NALUSTART_CODE);
+        expected.extend_from_slice(nalu);
+    }
+    assert_eq!(pck.depacketize(&payloads[0])?, expected.freeze());
+    assert!(pck.depacketize(&payloads[1])?.is_empty());
+    assert!(pck.depacketize(&payloads[2])?.is_empty());
+    let mut expected = BytesMut::from(&ANNEXB_NALUSTART_CODE[..]);
+    expected.extend_from_slice(&cra);
+    assert_eq!(pck.depacketize(&payloads[3])?, expected.freeze());
+
+    Ok(())
+}
+
+#[test]
+fn test_hevc_payloader_last_fragment_fills_packet() -> Result<()> {
+    // Two fragments of 97 bytes each for an MTU of 100.
+    let mut nalu = BytesMut::from(&[0x26, 0x01][..]);
+    nalu.extend(std::iter::repeat_n(0xaa, 2 * 97));
+    let mut frame = BytesMut::from(&ANNEXB_NALUSTART_CODE[..]);
+    frame.extend_from_slice(&nalu);
+
+    let mut payloader = HevcPayloader::default();
+    let payloads = payloader.payload(100, &frame.freeze())?;
+    assert_eq!(payloads.len(), 2);
+    assert_eq!(payloads[0][2], 0x80 | 19);
+    assert_eq!(payloads[1][2], 0x40 | 19, "the E bit should be set");
+
+    Ok(())
+}
+
+#[test]
+fn test_h265_packet_depacketize_single_nalu() -> Result<()> {
+    let mut pck = H265Packet::default();
+    let out = pck.depacketize(&Bytes::from_static(&[0x02, 0x01, 0xab, 0xcd]))?;
+    assert_eq!(&out[..], &[0x00, 0x00, 0x00, 0x01, 0x02, 0x01, 0xab, 0xcd]);
+
+    // Fragments are dropped until the start of a NAL unit.
+    let out = pck.depacketize(&Bytes::from_static(&[0x62, 0x01, 0x41, 0xab]))?;
+    assert!(out.is_empty());
+
+    Ok(())
+}
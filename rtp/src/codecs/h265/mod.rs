@@ -20,6 +20,8 @@ pub static FU_HDR_P_E: u8 = 0x41;
 pub static FU_HDR_B_S: u8 = 0x80;
 pub static FU_HDR_B_M: u8 = 0x00;
 pub static FU_HDR_B_E: u8 = 0x40;
+pub const FU_HDR_S_BIT: u8 = 0x80;
+pub const FU_HDR_E_BIT: u8 = 0x40;
 pub const RTP_OUTBOUND_MTU: usize = 1200;
 pub const H265FRAGMENTATION_UNIT_HEADER_SIZE: usize = 1;
 pub const NAL_HEADER_SIZE: usize = 2;
@@ -81,10 +83,12 @@ impl HevcPayloader {
         }
         let payload_header = H265NALUHeader::new(nalu[0], nalu[1]);
         let payload_nalu_type = payload_header.nalu_type();
-        let nalu_type = UnitType::for_id(payload_nalu_type).unwrap_or(UnitType::IGNORE);
-        if nalu_type == UnitType::IGNORE {
+        // Types from 48 on are reserved for the payload structures of RFC 7798.
+        if payload_nalu_type >= H265NALU_AGGREGATION_PACKET_TYPE {
             return;
-        } else if nalu_type == UnitType::VPS {
+        }
+        let nalu_type = UnitType::for_id(payload_nalu_type).unwrap_or(UnitType::IGNORE);
+        if nalu_type == UnitType::VPS {
             self.vps_nalu.replace(nalu.clone());
         } else if nalu_type == UnitType::SPS {
             self.sps_nalu.replace(nalu.clone());
@@ -99,9 +103,9 @@ impl HevcPayloader {
             let sps_len = (sps_nalu.len() as u16).to_be_bytes();
             let pps_len = (pps_nalu.len() as u16).to_be_bytes();
 
-            // TODO DONL not impl yet
+            // DONL is not sent, as sprop-max-don-diff is 0.
             let mut aggr_nalu = BytesMut::new();
-            aggr_nalu.extend_from_slice(&AGGR_PAYLOAD_HDR);
+            aggr_nalu.put_u16(aggregation_payload_header(&[vps_nalu, sps_nalu, pps_nalu]));
             aggr_nalu.extend_from_slice(&vps_len);
             aggr_nalu.extend_from_slice(vps_nalu);
             aggr_nalu.extend_from_slice(&sps_len);
@@ -133,6 +137,10 @@ impl HevcPayloader {
         }
         let max_fragment_size =
             mtu as isize - NAL_HEADER_SIZE as isize - H265FRAGMENTATION_UNIT_HEADER_SIZE as isize;
+        // The payload header keeps F, layer_id and tid of the NAL unit, its type goes into
+        // the FU header.
+        let fu_payload_header = (payload_header.0 & !H265NALU_TYPE_MASK)
+            | ((H265NALU_FRAGMENTATION_UNIT_TYPE as u16) << 9);
         let nalu_data = nalu;
         let mut nalu_data_index = 2;
         let nalu_data_length = nalu.len() as isize - nalu_data_index;
@@ -144,11 +152,13 @@ impl HevcPayloader {
             let current_fragment_size = std::cmp::min(max_fragment_size, nalu_data_remaining);
             //out: = make([]byte, fuaHeaderSize + currentFragmentSize)
             let mut out = BytesMut::with_capacity(
-                H265FRAGMENTATION_UNIT_HEADER_SIZE + current_fragment_size as usize,
+                NAL_HEADER_SIZE
+                    + H265FRAGMENTATION_UNIT_HEADER_SIZE
+                    + current_fragment_size as usize,
             );
-            out.extend_from_slice(&FRAG_PAYLOAD_HDR);
+            out.put_u16(fu_payload_header);
             let is_first = nalu_data_index == 2;
-            let is_last = !is_first && current_fragment_size < max_fragment_size;
+            let is_last = current_fragment_size == nalu_data_remaining;
             /*
             +---------------+
             |0|1|2|3|4|5|6|7|
@@ -156,31 +166,13 @@ impl HevcPayloader {
             |S|E|  fu_type  |
             +---------------+
             */
-            if nalu_type == UnitType::IDR {
-                if is_first {
-                    out.put_u8(FU_HDR_IDR_S);
-                } else if is_last {
-                    out.put_u8(FU_HDR_IDR_E);
-                } else {
-                    out.put_u8(FU_HDR_IDR_M);
-                }
-            } else if nalu_type == UnitType::PFR {
-                if is_first {
-                    out.put_u8(FU_HDR_P_S);
-                } else if is_last {
-                    out.put_u8(FU_HDR_P_E);
-                } else {
-                    out.put_u8(FU_HDR_P_M);
-                }
-            } else if nalu_type == UnitType::BFR {
-                if is_first {
-                    out.put_u8(FU_HDR_B_S);
-                } else if is_last {
-                    out.put_u8(FU_HDR_B_E);
-                } else {
-                    out.put_u8(FU_HDR_B_M);
-                }
+            let mut fu_header = payload_nalu_type;
+            if is_first {
+                fu_header |= FU_HDR_S_BIT;
+            } else if is_last {
+                fu_header |= FU_HDR_E_BIT;
             }
+            out.put_u8(fu_header);
 
             out.extend_from_slice(
                 &nalu_data
@@ -195,8 +187,21 @@ impl HevcPayloader {
     }
 }
 
+/// aggregation_payload_header returns the payload header of an aggregation packet of
+/// nalus: F is set if it is set for any of them, layer_id and tid are the lowest of all.
+fn aggregation_payload_header(nalus: &[&Bytes]) -> u16 {
+    let headers = nalus
+        .iter()
+        .map(|nalu| H265NALUHeader::new(nalu[0], nalu[1]))
+        .collect::<Vec<_>>();
+    let f = headers.iter().any(|header| header.f()) as u16;
+    let layer_id = headers.iter().map(|h| h.layer_id()).min().unwrap_or(0) as u16;
+    let tid = headers.iter().map(|h| h.tid()).min().unwrap_or(1) as u16;
+    (f << 15) | ((H265NALU_AGGREGATION_PACKET_TYPE as u16) << 9) | (layer_id << 3) | tid
+}
+
 impl Payloader for HevcPayloader {
-    /// Payload fragments a H264 packet across one or more byte arrays
+    /// Payload fragments a H265 packet across one or more byte arrays
     fn payload(&mut self, mtu: usize, payload: &Bytes) -> Result<Vec<Bytes>> {
         if payload.is_empty() || mtu == 0 {
             return Ok(vec![]);
@@ -236,6 +241,7 @@ impl Payloader for HevcPayloader {
 ///
 
 const H265NALU_HEADER_SIZE: usize = 2;
+const H265NALU_TYPE_MASK: u16 = 0b01111110 << 8;
 /// <https://datatracker.ietf.org/doc/html/rfc7798#section-4.4.2>
 const H265NALU_AGGREGATION_PACKET_TYPE: u8 = 48;
 /// <https://datatracker.ietf.org/doc/html/rfc7798#section-4.4.3>
@@ -274,8 +280,7 @@ impl H265NALUHeader {
     /// nalu_type of NAL Unit.
     pub fn nalu_type(&self) -> u8 {
         // 01111110 00000000
-        const MASK: u16 = 0b01111110 << 8;
-        ((self.0 & MASK) >> (8 + 1)) as u8
+        ((self.0 & H265NALU_TYPE_MASK) >> (8 + 1)) as u8
     }
 
     /// is_type_vcl_unit returns whether or not the NAL Unit type is a VCL NAL unit.
@@ -978,10 +983,13 @@ impl Default for H265Payload {
 ///
 
 /// H265Packet represents a H265 packet, stored in the payload of an RTP packet.
+/// Depacketizing it returns the NAL units of the packet in Annex B format, the ones of
+/// fragmentation units once the last fragment has been received.
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub struct H265Packet {
     payload: H265Payload,
     might_need_donl: bool,
+    fu_buffer: Option<BytesMut>,
 }
 
 impl H265Packet {
@@ -1018,37 +1026,81 @@ impl Depacketizer for H265Packet {
             let mut decoded = H265PACIPacket::default();
             decoded.depacketize(payload)?;
 
+            // The type of the NAL unit is carried in cType.
+            let nalu_header = (decoded.payload_header().0 & !H265NALU_TYPE_MASK)
+                | ((decoded.ctype() as u16) << 9);
+            let mut out = BytesMut::new();
+            out.put(&*ANNEXB_NALUSTART_CODE);
+            out.put_u16(nalu_header);
+            out.put(&*decoded.payload());
             self.payload = H265Payload::H265PACIPacket(decoded);
+            Ok(out.freeze())
         } else if payload_header.is_fragmentation_unit() {
             let mut decoded = H265FragmentationUnitPacket::default();
             decoded.with_donl(self.might_need_donl);
 
             decoded.depacketize(payload)?;
 
+            let fu_header = decoded.fu_header();
+            if fu_header.s() {
+                self.fu_buffer = Some(BytesMut::new());
+            }
+            // Fragments are dropped until the start of the next NAL unit, if one got lost.
+            if let Some(fu_buffer) = &mut self.fu_buffer {
+                fu_buffer.put(&*decoded.payload());
+            }
+            let mut out = BytesMut::new();
+            if fu_header.e() {
+                if let Some(fu_buffer) = self.fu_buffer.take() {
+                    let nalu_header = (decoded.payload_header().0 & !H265NALU_TYPE_MASK)
+                        | ((fu_header.fu_type() as u16) << 9);
+                    out.put(&*ANNEXB_NALUSTART_CODE);
+                    out.put_u16(nalu_header);
+                    out.put(fu_buffer);
+                }
+            }
             self.payload = H265Payload::H265FragmentationUnitPacket(decoded);
+            Ok(out.freeze())
         } else if payload_header.is_aggregation_packet() {
             let mut decoded = H265AggregationPacket::default();
             decoded.with_donl(self.might_need_donl);
 
             decoded.depacketize(payload)?;
 
+            let mut out = BytesMut::new();
+            if let Some(first_unit) = decoded.first_unit() {
+                out.put(&*ANNEXB_NALUSTART_CODE);
+                out.put(&*first_unit.nal_unit());
+            }
+            for unit in decoded.other_units() {
+                out.put(&*ANNEXB_NALUSTART_CODE);
+                out.put(&*unit.nal_unit());
+            }
             self.payload = H265Payload::H265AggregationPacket(decoded);
+            Ok(out.freeze())
         } else {
             let mut decoded = H265SingleNALUnitPacket::default();
             decoded.with_donl(self.might_need_donl);
 
             decoded.depacketize(payload)?;
 
+            let mut out = BytesMut::new();
+            out.put(&*ANNEXB_NALUSTART_CODE);
+            out.put_u16(decoded.payload_header().0);
+            out.put(&*decoded.payload());
             self.payload = H265Payload::H265SingleNALUnitPacket(decoded);
+            Ok(out.freeze())
         }
-
-        Ok(payload.clone())
     }
 
     /// is_partition_head checks if this is the head of a packetized nalu stream.
-    fn is_partition_head(&self, _payload: &Bytes) -> bool {
-        //TODO:
-        true
+    fn is_partition_head(&self, payload: &Bytes) -> bool {
+        if payload.len() <= H265NALU_HEADER_SIZE + H265FRAGMENTATION_UNIT_HEADER_SIZE {
+            return false;
+        }
+
+        let payload_header = H265NALUHeader::new(payload[0], payload[1]);
+        !payload_header.is_fragmentation_unit() || H265FragmentationUnitHeader(payload[2]).s()
     }
 
     fn is_partition_tail(&self, marker: bool, _payload: &Bytes) -> bool {
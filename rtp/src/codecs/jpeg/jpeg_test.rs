@@ -0,0 +1,179 @@
+use super::*;
+
+/// jpeg_frame returns a frame as the depacketizer rebuilds it, with made up scan data.
+fn jpeg_frame(typ: u8, restart_interval: u16, tables: [QuantizationTable; 2]) -> Bytes {
+    let frame = JpegFrame {
+        typ,
+        width: 80,
+        height: 45,
+        restart_interval,
+        tables,
+        scan: Bytes::new(),
+    };
+    let mut out = BytesMut::new();
+    frame.marshal_headers(&mut out);
+    out.extend((0..3000u32).map(|i| (i % 251) as u8));
+    out.put_u16(0xFF00 | MARKER_EOI as u16);
+    out.freeze()
+}
+
+fn depacketize_frame(depacketizer: &mut JpegPacket, payloads: &[Bytes]) -> Result<Bytes> {
+    let mut out = BytesMut::new();
+    for payload in payloads {
+        out.put(&*depacketizer.depacketize(payload)?);
+    }
+    Ok(out.freeze())
+}
+
+#[test]
+fn test_jpeg_huffman_tables() {
+    for (_, code_lens, symbols) in HUFFMAN_TABLES {
+        let count: usize = code_lens.iter().map(|&n| n as usize).sum();
+        assert_eq!(count, symbols.len());
+    }
+}
+
+#[test]
+fn test_jpeg_quantization_tables() {
+    let [luma, chroma] = quantization_tables(50);
+    assert_eq!(&luma.values[..], &LUMA_QUANTIZER[..]);
+    assert_eq!(&chroma.values[..], &CHROMA_QUANTIZER[..]);
+
+    let [luma, _] = quantization_tables(99);
+    assert!(luma.values.iter().all(|&v| v == 1 || v == 2));
+}
+
+#[test]
+fn test_jpeg_payload_round_trip() -> Result<()> {
+    let frame = jpeg_frame(1, 0, quantization_tables(80));
+
+    let mut payloader = JpegPayloader;
+    let payloads = payloader.payload(1200, &frame)?;
+    assert_eq!(payloads.len(), 3);
+    assert!(payloads.iter().all(|p| p.len() <= 1200));
+    // Offset 0, type 1, dynamic Q, 640x360, followed by two 8 bit tables
+    assert_eq!(
+        &payloads[0][..JPEG_HEADER_SIZE + JPEG_QUANTIZATION_HEADER_SIZE],
+        &[0, 0, 0, 0, 1, 255, 80, 45, 0, 0, 0, 128]
+    );
+
+    let mut depacketizer = JpegPacket::default();
+    assert!(depacketizer.is_partition_head(&payloads[0]));
+    assert!(!depacketizer.is_partition_head(&payloads[1]));
+    assert_eq!(depacketize_frame(&mut depacketizer, &payloads)?, frame);
+    assert_eq!(depacketizer.fragment_offset, 1060 + 1192);
+    assert_eq!((depacketizer.width, depacketizer.height), (80, 45));
+
+    Ok(())
+}
+
+#[test]
+fn test_jpeg_payload_restart_markers() -> Result<()> {
+    let frame = jpeg_frame(0, 4, quantization_tables(50));
+
+    let payloads = JpegPayloader.payload(1000, &frame)?;
+    for payload in &payloads {
+        assert_eq!(payload[4], JPEG_TYPE_RESTART);
+        assert_eq!(&payload[8..12], &[0, 4, 0xFF, 0xFF]);
+    }
+
+    let mut depacketizer = JpegPacket::default();
+    assert_eq!(depacketize_frame(&mut depacketizer, &payloads)?, frame);
+    assert_eq!(depacketizer.restart_interval, 4);
+
+    Ok(())
+}
+
+#[test]
+fn test_jpeg_depacketize_q() -> Result<()> {
+    let mut depacketizer = JpegPacket::default();
+    let frame = jpeg_frame(1, 0, quantization_tables(30));
+    let scan = frame.slice(frame.len() - 3002..);
+
+    // The tables of Q below 100 are computed.
+    let mut payload = BytesMut::new();
+    payload.put(&[0, 0, 0, 0, 1, 30, 80, 45][..]);
+    payload.put(&*scan);
+    assert_eq!(depacketizer.depacketize(&payload.freeze())?, frame);
+
+    // The tables of Q from 128 to 254 are only sent once.
+    let tables = quantization_tables(70);
+    let frame = jpeg_frame(1, 0, tables.clone());
+    let mut payload = BytesMut::new();
+    payload.put(&[0, 0, 0, 0, 1, 130, 80, 45, 0, 0, 0, 128][..]);
+    payload.put(&*tables[0].values);
+    payload.put(&*tables[1].values);
+    payload.put(&*scan);
+    assert_eq!(depacketizer.depacketize(&payload.freeze())?, frame);
+
+    let mut payload = BytesMut::new();
+    payload.put(&[0, 0, 0, 0, 1, 130, 80, 45, 0, 0, 0, 0][..]);
+    payload.put(&*scan);
+    assert_eq!(depacketizer.depacketize(&payload.freeze())?, frame);
+
+    let payload = Bytes::from_static(&[0, 0, 0, 0, 1, 131, 80, 45, 0, 0, 0, 0]);
+    assert_eq!(
+        Error::ErrInvalidJpeg,
+        depacketizer
+            .depacketize(&payload)
+            .expect_err("tables of Q should be unknown")
+    );
+
+    let payload = Bytes::from_static(&[0, 0, 0, 0, 3, 50, 80, 45]);
+    assert_eq!(
+        Error::ErrUnsupportedJpeg,
+        depacketizer
+            .depacketize(&payload)
+            .expect_err("type should be unsupported")
+    );
+
+    let payload = Bytes::from_static(&[0, 0, 0, 0, 1, 50, 80]);
+    assert_eq!(
+        Error::ErrShortPacket,
+        depacketizer
+            .depacketize(&payload)
+            .expect_err("payload should be too short")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_jpeg_payload_unsupported() {
+    let frame = jpeg_frame(1, 0, quantization_tables(50));
+    let sof = frame
+        .windows(2)
+        .position(|w| w == [0xFF, MARKER_SOF0])
+        .unwrap();
+    let dht = frame
+        .windows(2)
+        .position(|w| w == [0xFF, MARKER_DHT])
+        .unwrap();
+
+    let mut progressive = BytesMut::from(&frame[..]);
+    progressive[sof + 1] = 0xC2;
+    let mut optimized_huffman = BytesMut::from(&frame[..]);
+    optimized_huffman[dht + 5 + 16] = 1;
+    let mut grayscale = BytesMut::from(&frame[..]);
+    grayscale[sof + 9] = 1;
+    let tests = vec![
+        (
+            "Progressive",
+            progressive.freeze(),
+            Error::ErrUnsupportedJpeg,
+        ),
+        (
+            "OptimizedHuffman",
+            optimized_huffman.freeze(),
+            Error::ErrUnsupportedJpeg,
+        ),
+        ("Grayscale", grayscale.freeze(), Error::ErrUnsupportedJpeg),
+        ("NoSOI", frame.slice(2..), Error::ErrInvalidJpeg),
+        ("NoSOS", frame.slice(..sof), Error::ErrInvalidJpeg),
+    ];
+
+    for (name, frame, err) in tests {
+        let result = JpegPayloader.payload(1200, &frame);
+        assert_eq!(err, result.expect_err(name), "{name}");
+    }
+}
@@ -0,0 +1,549 @@
+#[cfg(test)]
+mod jpeg_test;
+
+use std::collections::HashMap;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::error::{Error, Result};
+use crate::packetizer::{Depacketizer, Payloader};
+
+/// Size of the main JPEG header, in front of every payload.
+pub const JPEG_HEADER_SIZE: usize = 8;
+/// Size of the restart marker header, which follows the main header for types 64 to 127.
+pub const JPEG_RESTART_HEADER_SIZE: usize = 4;
+/// Size of the quantization table header, which follows the other headers in the first
+/// payload of a frame if Q is 128 or above.
+pub const JPEG_QUANTIZATION_HEADER_SIZE: usize = 4;
+/// Type of frames with 4:2:2 chroma subsampling, 4:2:0 ones are type 1. Adding this makes
+/// the type of frames with restart markers.
+pub const JPEG_TYPE_RESTART: u8 = 64;
+/// Q of frames whose quantization tables are sent in each of them.
+pub const JPEG_Q_DYNAMIC: u8 = 255;
+
+const MARKER_SOF0: u8 = 0xC0;
+const MARKER_DHT: u8 = 0xC4;
+const MARKER_SOI: u8 = 0xD8;
+const MARKER_EOI: u8 = 0xD9;
+const MARKER_SOS: u8 = 0xDA;
+const MARKER_DQT: u8 = 0xDB;
+const MARKER_DRI: u8 = 0xDD;
+
+/// Quantization tables in zigzag order, which Q from 1 to 99 scale, see RFC 2435 appendix A.
+const LUMA_QUANTIZER: [u8; 64] = [
+    16, 11, 12, 14, 12, 10, 16, 14, 13, 14, 18, 17, 16, 19, 24, 40, 26, 24, 22, 22, 24, 49, 35, 37,
+    29, 40, 58, 51, 61, 60, 57, 51, 56, 55, 64, 72, 92, 78, 64, 68, 87, 69, 55, 56, 80, 109, 81,
+    87, 95, 98, 103, 104, 103, 62, 77, 113, 121, 112, 100, 120, 92, 101, 103, 99,
+];
+const CHROMA_QUANTIZER: [u8; 64] = [
+    17, 18, 18, 24, 21, 24, 47, 26, 26, 47, 99, 66, 56, 66, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99,
+];
+
+/// Huffman tables of JPEG annex K.3, which RFC 2435 frames are always encoded with. Each is
+/// the number of codes of each length from 1 to 16, followed by the symbols.
+const LUMA_DC_CODE_LENS: [u8; 16] = [0, 1, 5, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0];
+const LUMA_DC_SYMBOLS: [u8; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+const LUMA_AC_CODE_LENS: [u8; 16] = [0, 2, 1, 3, 3, 2, 4, 3, 5, 5, 4, 4, 0, 0, 1, 0x7d];
+const LUMA_AC_SYMBOLS: [u8; 162] = [
+    0x01, 0x02, 0x03, 0x00, 0x04, 0x11, 0x05, 0x12, 0x21, 0x31, 0x41, 0x06, 0x13, 0x51, 0x61, 0x07,
+    0x22, 0x71, 0x14, 0x32, 0x81, 0x91, 0xa1, 0x08, 0x23, 0x42, 0xb1, 0xc1, 0x15, 0x52, 0xd1, 0xf0,
+    0x24, 0x33, 0x62, 0x72, 0x82, 0x09, 0x0a, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x25, 0x26, 0x27, 0x28,
+    0x29, 0x2a, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49,
+    0x4a, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69,
+    0x6a, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7a, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89,
+    0x8a, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9a, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7,
+    0xa8, 0xa9, 0xaa, 0xb2, 0xb3, 0xb4, 0xb5, 0xb6, 0xb7, 0xb8, 0xb9, 0xba, 0xc2, 0xc3, 0xc4, 0xc5,
+    0xc6, 0xc7, 0xc8, 0xc9, 0xca, 0xd2, 0xd3, 0xd4, 0xd5, 0xd6, 0xd7, 0xd8, 0xd9, 0xda, 0xe1, 0xe2,
+    0xe3, 0xe4, 0xe5, 0xe6, 0xe7, 0xe8, 0xe9, 0xea, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8,
+    0xf9, 0xfa,
+];
+const CHROMA_DC_CODE_LENS: [u8; 16] = [0, 3, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0];
+const CHROMA_DC_SYMBOLS: [u8; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+const CHROMA_AC_CODE_LENS: [u8; 16] = [0, 2, 1, 2, 4, 4, 3, 4, 7, 5, 4, 4, 0, 1, 2, 0x77];
+const CHROMA_AC_SYMBOLS: [u8; 162] = [
+    0x00, 0x01, 0x02, 0x03, 0x11, 0x04, 0x05, 0x21, 0x31, 0x06, 0x12, 0x41, 0x51, 0x07, 0x61, 0x71,
+    0x13, 0x22, 0x32, 0x81, 0x08, 0x14, 0x42, 0x91, 0xa1, 0xb1, 0xc1, 0x09, 0x23, 0x33, 0x52, 0xf0,
+    0x15, 0x62, 0x72, 0xd1, 0x0a, 0x16, 0x24, 0x34, 0xe1, 0x25, 0xf1, 0x17, 0x18, 0x19, 0x1a, 0x26,
+    0x27, 0x28, 0x29, 0x2a, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48,
+    0x49, 0x4a, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68,
+    0x69, 0x6a, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7a, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87,
+    0x88, 0x89, 0x8a, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9a, 0xa2, 0xa3, 0xa4, 0xa5,
+    0xa6, 0xa7, 0xa8, 0xa9, 0xaa, 0xb2, 0xb3, 0xb4, 0xb5, 0xb6, 0xb7, 0xb8, 0xb9, 0xba, 0xc2, 0xc3,
+    0xc4, 0xc5, 0xc6, 0xc7, 0xc8, 0xc9, 0xca, 0xd2, 0xd3, 0xd4, 0xd5, 0xd6, 0xd7, 0xd8, 0xd9, 0xda,
+    0xe2, 0xe3, 0xe4, 0xe5, 0xe6, 0xe7, 0xe8, 0xe9, 0xea, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8,
+    0xf9, 0xfa,
+];
+
+/// Huffman tables by the class and id byte of their DHT segment.
+const HUFFMAN_TABLES: [(u8, &[u8], &[u8]); 4] = [
+    (0x00, &LUMA_DC_CODE_LENS, &LUMA_DC_SYMBOLS),
+    (0x10, &LUMA_AC_CODE_LENS, &LUMA_AC_SYMBOLS),
+    (0x01, &CHROMA_DC_CODE_LENS, &CHROMA_DC_SYMBOLS),
+    (0x11, &CHROMA_AC_CODE_LENS, &CHROMA_AC_SYMBOLS),
+];
+
+/// QuantizationTable is a quantization table of a frame, in zigzag order.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct QuantizationTable {
+    /// Whether the values are 16 bits, rather than 8.
+    pub precision_16: bool,
+    pub values: Bytes,
+}
+
+impl QuantizationTable {
+    fn new(values: &[u8]) -> Self {
+        QuantizationTable {
+            precision_16: false,
+            values: Bytes::copy_from_slice(values),
+        }
+    }
+}
+
+/// quantization_tables returns the luma and chroma tables Q from 1 to 99 stands for.
+pub fn quantization_tables(q: u8) -> [QuantizationTable; 2] {
+    let factor = q.clamp(1, 99) as u32;
+    let scale = if factor < 50 {
+        5000 / factor
+    } else {
+        200 - factor * 2
+    };
+    let scaled = |table: &[u8; 64]| -> Vec<u8> {
+        table
+            .iter()
+            .map(|&v| ((v as u32 * scale + 50) / 100).clamp(1, 255) as u8)
+            .collect()
+    };
+
+    [
+        QuantizationTable::new(&scaled(&LUMA_QUANTIZER)),
+        QuantizationTable::new(&scaled(&CHROMA_QUANTIZER)),
+    ]
+}
+
+/// JpegFrame is what the RTP payload format keeps of the headers of a JPEG frame.
+#[derive(PartialEq, Eq, Debug, Clone)]
+struct JpegFrame {
+    /// 0 for 4:2:2 chroma subsampling, 1 for 4:2:0.
+    typ: u8,
+    /// Width and height in 8 pixel blocks.
+    width: u8,
+    height: u8,
+    restart_interval: u16,
+    /// Luma and chroma quantization tables.
+    tables: [QuantizationTable; 2],
+    /// Entropy coded data of the scan, up to the end of the frame.
+    scan: Bytes,
+}
+
+impl JpegFrame {
+    /// parse reads the headers of a baseline JPEG frame, as sent by cameras, and the scan
+    /// data behind them.
+    fn parse(frame: &Bytes) -> Result<Self> {
+        let mut reader = frame.clone();
+        if reader.remaining() < 2 || reader.get_u16() != 0xFF00 | MARKER_SOI as u16 {
+            return Err(Error::ErrInvalidJpeg);
+        }
+
+        let mut tables: [Option<QuantizationTable>; 4] = Default::default();
+        let mut components: Option<(u8, [u8; 3])> = None;
+        let (mut width, mut height) = (0, 0);
+        let mut restart_interval = 0;
+        loop {
+            if reader.remaining() < 2 || reader.get_u8() != 0xFF {
+                return Err(Error::ErrInvalidJpeg);
+            }
+            let mut marker = reader.get_u8();
+            // Any number of fill bytes may come before a marker
+            while marker == 0xFF && reader.has_remaining() {
+                marker = reader.get_u8();
+            }
+            if marker == MARKER_EOI || reader.remaining() < 2 {
+                return Err(Error::ErrInvalidJpeg);
+            }
+            let length = reader.get_u16() as usize;
+            if length < 2 || reader.remaining() < length - 2 {
+                return Err(Error::ErrInvalidJpeg);
+            }
+            let mut segment = reader.split_to(length - 2);
+
+            match marker {
+                MARKER_SOF0 => {
+                    if segment.remaining() < 15 || segment.get_u8() != 8 {
+                        return Err(Error::ErrUnsupportedJpeg);
+                    }
+                    height = segment.get_u16();
+                    width = segment.get_u16();
+                    if segment.get_u8() != 3 {
+                        return Err(Error::ErrUnsupportedJpeg);
+                    }
+                    let mut sampling = [0u8; 3];
+                    let mut table_ids = [0u8; 3];
+                    for i in 0..3 {
+                        segment.advance(1);
+                        sampling[i] = segment.get_u8();
+                        table_ids[i] = segment.get_u8() & 0x03;
+                    }
+                    let typ = match sampling {
+                        [0x21, 0x11, 0x11] => 0,
+                        [0x22, 0x11, 0x11] => 1,
+                        _ => return Err(Error::ErrUnsupportedJpeg),
+                    };
+                    if table_ids[1] != table_ids[2] {
+                        return Err(Error::ErrUnsupportedJpeg);
+                    }
+                    components = Some((typ, table_ids));
+                }
+                // Progressive, lossless and arithmetic coded frames
+                0xC1..=0xCF if marker != MARKER_DHT && marker != 0xC8 && marker != 0xCC => {
+                    return Err(Error::ErrUnsupportedJpeg);
+                }
+                MARKER_DHT => {
+                    while segment.has_remaining() {
+                        if !is_standard_huffman_table(&mut segment)? {
+                            return Err(Error::ErrUnsupportedJpeg);
+                        }
+                    }
+                }
+                MARKER_DQT => {
+                    while segment.has_remaining() {
+                        let b = segment.get_u8();
+                        let precision_16 = b >> 4 != 0;
+                        let size = if precision_16 { 128 } else { 64 };
+                        if segment.remaining() < size {
+                            return Err(Error::ErrInvalidJpeg);
+                        }
+                        tables[(b & 0x03) as usize] = Some(QuantizationTable {
+                            precision_16,
+                            values: segment.split_to(size),
+                        });
+                    }
+                }
+                MARKER_DRI => {
+                    if segment.remaining() < 2 {
+                        return Err(Error::ErrInvalidJpeg);
+                    }
+                    restart_interval = segment.get_u16();
+                }
+                MARKER_SOS => break,
+                _ => {}
+            }
+        }
+
+        let (typ, table_ids) = components.ok_or(Error::ErrInvalidJpeg)?;
+        if width == 0 || height == 0 || width > 2040 || height > 2040 {
+            return Err(Error::ErrUnsupportedJpeg);
+        }
+        let mut table = |id: u8| tables[id as usize].take().ok_or(Error::ErrInvalidJpeg);
+        let luma = table(table_ids[0])?;
+        let chroma = if table_ids[1] == table_ids[0] {
+            luma.clone()
+        } else {
+            table(table_ids[1])?
+        };
+
+        Ok(JpegFrame {
+            typ,
+            width: width.div_ceil(8) as u8,
+            height: height.div_ceil(8) as u8,
+            restart_interval,
+            tables: [luma, chroma],
+            scan: reader,
+        })
+    }
+
+    /// marshal_headers writes the headers the frame was parsed from, as in RFC 2435
+    /// appendix B.
+    fn marshal_headers(&self, out: &mut BytesMut) {
+        out.put_u16(0xFF00 | MARKER_SOI as u16);
+
+        for (id, table) in self.tables.iter().enumerate() {
+            out.put_u16(0xFF00 | MARKER_DQT as u16);
+            out.put_u16(3 + table.values.len() as u16);
+            out.put_u8(((table.precision_16 as u8) << 4) | id as u8);
+            out.put(&*table.values);
+        }
+
+        if self.restart_interval != 0 {
+            out.put_u16(0xFF00 | MARKER_DRI as u16);
+            out.put_u16(4);
+            out.put_u16(self.restart_interval);
+        }
+
+        out.put_u16(0xFF00 | MARKER_SOF0 as u16);
+        out.put_u16(17);
+        out.put_u8(8);
+        out.put_u16(self.height as u16 * 8);
+        out.put_u16(self.width as u16 * 8);
+        out.put_u8(3);
+        out.put(&[0, if self.typ == 0 { 0x21 } else { 0x22 }, 0][..]);
+        out.put(&[1, 0x11, 1][..]);
+        out.put(&[2, 0x11, 1][..]);
+
+        for (class_id, code_lens, symbols) in HUFFMAN_TABLES {
+            out.put_u16(0xFF00 | MARKER_DHT as u16);
+            out.put_u16(3 + (code_lens.len() + symbols.len()) as u16);
+            out.put_u8(class_id);
+            out.put(code_lens);
+            out.put(symbols);
+        }
+
+        out.put_u16(0xFF00 | MARKER_SOS as u16);
+        out.put_u16(12);
+        out.put_u8(3);
+        out.put(&[0, 0x00, 1, 0x11, 2, 0x11][..]);
+        out.put(&[0, 63, 0][..]);
+    }
+}
+
+/// is_standard_huffman_table reads a table of a DHT segment, and returns whether it's the
+/// annex K.3 one RFC 2435 receivers decode with.
+fn is_standard_huffman_table(segment: &mut Bytes) -> Result<bool> {
+    if segment.remaining() < 17 {
+        return Err(Error::ErrInvalidJpeg);
+    }
+    let class_id = segment.get_u8();
+    let code_lens = segment.split_to(16);
+    let count = code_lens.iter().map(|&n| n as usize).sum();
+    if segment.remaining() < count {
+        return Err(Error::ErrInvalidJpeg);
+    }
+    let symbols = segment.split_to(count);
+
+    Ok(HUFFMAN_TABLES
+        .iter()
+        .any(|&(c, l, s)| c == class_id && l == &code_lens[..] && s == &symbols[..]))
+}
+
+/// JpegPayloader payloads baseline JPEG frames, e.g. the ones of MJPEG cameras.
+///
+/// The frames have to be encoded with the Huffman tables of JPEG annex K.3 and 4:2:2 or 4:2:0
+/// chroma subsampling, the quantization tables are sent with each frame.
+///
+/// ## Specifications
+///
+/// * [RFC 2435]
+///
+/// [RFC 2435]: https://tools.ietf.org/html/rfc2435
+#[derive(Default, Debug, Copy, Clone)]
+pub struct JpegPayloader;
+
+impl Payloader for JpegPayloader {
+    /// Payload fragments the scan data of a JPEG frame across one or more byte arrays
+    fn payload(&mut self, mtu: usize, payload: &Bytes) -> Result<Vec<Bytes>> {
+        if payload.is_empty() || mtu == 0 {
+            return Ok(vec![]);
+        }
+
+        /*
+         * https://tools.ietf.org/html/rfc2435#section-3.1
+         *
+         *  0                   1                   2                   3
+         *  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+         * +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+         * | Type-specific |              Fragment Offset                  |
+         * +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+         * |      Type     |       Q       |     Width     |     Height    |
+         * +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+         */
+        let frame = JpegFrame::parse(payload)?;
+        let mut typ = frame.typ;
+        let mut header_size = JPEG_HEADER_SIZE;
+        if frame.restart_interval != 0 {
+            typ += JPEG_TYPE_RESTART;
+            header_size += JPEG_RESTART_HEADER_SIZE;
+        }
+        let tables_size: usize = frame.tables.iter().map(|t| t.values.len()).sum();
+        let first_header_size = header_size + JPEG_QUANTIZATION_HEADER_SIZE + tables_size;
+        if mtu <= first_header_size || frame.scan.len() >= 1 << 24 {
+            return Ok(vec![]);
+        }
+
+        let mut payloads = vec![];
+        let mut offset = 0;
+        while offset < frame.scan.len() {
+            let header_size = if offset == 0 {
+                first_header_size
+            } else {
+                header_size
+            };
+            let fragment_size = (mtu - header_size).min(frame.scan.len() - offset);
+            let mut out = BytesMut::with_capacity(header_size + fragment_size);
+            out.put_u32(offset as u32);
+            out.put(&[typ, JPEG_Q_DYNAMIC, frame.width, frame.height][..]);
+
+            // The packets aren't aligned to restart intervals, so none can be decoded on its own
+            if frame.restart_interval != 0 {
+                out.put_u16(frame.restart_interval);
+                out.put_u16(0xFFFF);
+            }
+            if offset == 0 {
+                let precision =
+                    frame.tables[0].precision_16 as u8 | (frame.tables[1].precision_16 as u8) << 1;
+                out.put(&[0, precision][..]);
+                out.put_u16(tables_size as u16);
+                for table in &frame.tables {
+                    out.put(&*table.values);
+                }
+            }
+
+            out.put(&*frame.scan.slice(offset..offset + fragment_size));
+            payloads.push(out.freeze());
+            offset += fragment_size;
+        }
+
+        Ok(payloads)
+    }
+
+    fn clone_to(&self) -> Box<dyn Payloader + Send + Sync> {
+        Box::new(*self)
+    }
+}
+
+/// JpegPacket represents the JPEG headers of an RTP packet, and depacketizes the payloads of
+/// a frame back into a JPEG frame, whose headers are rebuilt in front of the first one.
+///
+/// The scan data is kept as sent, so the frame ends with an EOI marker only if the sender
+/// didn't drop it.
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct JpegPacket {
+    pub type_specific: u8,
+    /// Offset of the payload in the scan data of the frame.
+    pub fragment_offset: u32,
+    pub typ: u8,
+    pub q: u8,
+    /// Width and height in 8 pixel blocks.
+    pub width: u8,
+    pub height: u8,
+    /// Restart interval of the frame, for types 64 to 127.
+    pub restart_interval: u16,
+
+    /// Quantization tables of Q from 128 to 254, which senders don't have to send with
+    /// each frame.
+    quantization_tables: HashMap<u8, [QuantizationTable; 2]>,
+}
+
+impl Depacketizer for JpegPacket {
+    /// depacketize parses the passed byte slice and stores the result in the JpegPacket this
+    /// method is called upon
+    fn depacketize(&mut self, packet: &Bytes) -> Result<Bytes> {
+        if packet.len() < JPEG_HEADER_SIZE {
+            return Err(Error::ErrShortPacket);
+        }
+
+        let mut reader = packet.clone();
+        let b = reader.get_u32();
+        self.type_specific = (b >> 24) as u8;
+        self.fragment_offset = b & 0x00FF_FFFF;
+        self.typ = reader.get_u8();
+        self.q = reader.get_u8();
+        self.width = reader.get_u8();
+        self.height = reader.get_u8();
+        if self.typ & !JPEG_TYPE_RESTART > 1 || self.q == 0 || (100..128).contains(&self.q) {
+            return Err(Error::ErrUnsupportedJpeg);
+        }
+
+        self.restart_interval = 0;
+        if self.typ & JPEG_TYPE_RESTART != 0 {
+            if reader.remaining() < JPEG_RESTART_HEADER_SIZE {
+                return Err(Error::ErrShortPacket);
+            }
+            self.restart_interval = reader.get_u16();
+            reader.advance(2);
+        }
+
+        if self.fragment_offset != 0 {
+            return Ok(reader);
+        }
+
+        let tables = if self.q < 128 {
+            quantization_tables(self.q)
+        } else {
+            self.read_quantization_tables(&mut reader)?
+        };
+        let frame = JpegFrame {
+            typ: self.typ & !JPEG_TYPE_RESTART,
+            width: self.width,
+            height: self.height,
+            restart_interval: self.restart_interval,
+            tables,
+            scan: reader,
+        };
+
+        let mut out = BytesMut::new();
+        frame.marshal_headers(&mut out);
+        out.put(&*frame.scan);
+        Ok(out.freeze())
+    }
+
+    /// is_partition_head checks if this is the head of a JPEG frame
+    fn is_partition_head(&self, payload: &Bytes) -> bool {
+        payload.len() >= JPEG_HEADER_SIZE && payload[1..4] == [0, 0, 0]
+    }
+
+    /// is_partition_tail checks if this is the tail of a JPEG frame
+    fn is_partition_tail(&self, marker: bool, _payload: &Bytes) -> bool {
+        marker
+    }
+}
+
+impl JpegPacket {
+    /// read_quantization_tables reads the quantization table header of the first payload of a
+    /// frame, and the luma and chroma tables following it.
+    fn read_quantization_tables(&mut self, reader: &mut Bytes) -> Result<[QuantizationTable; 2]> {
+        /*
+         * https://tools.ietf.org/html/rfc2435#section-3.1.8
+         *
+         *  0                   1                   2                   3
+         *  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+         * +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+         * |      MBZ      |   Precision   |             Length            |
+         * +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+         * |                    Quantization Table Data                    |
+         * |                              ...                              |
+         * +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+         */
+        if reader.remaining() < JPEG_QUANTIZATION_HEADER_SIZE {
+            return Err(Error::ErrShortPacket);
+        }
+        reader.advance(1);
+        let precision = reader.get_u8();
+        let length = reader.get_u16() as usize;
+        if length == 0 && self.q != JPEG_Q_DYNAMIC {
+            return self
+                .quantization_tables
+                .get(&self.q)
+                .cloned()
+                .ok_or(Error::ErrInvalidJpeg);
+        }
+        if reader.remaining() < length {
+            return Err(Error::ErrShortPacket);
+        }
+
+        let mut data = reader.split_to(length);
+        let table = |data: &mut Bytes, precision_16: bool| {
+            let size = if precision_16 { 128 } else { 64 };
+            if data.remaining() < size {
+                return Err(Error::ErrInvalidJpeg);
+            }
+            Ok(QuantizationTable {
+                precision_16,
+                values: data.split_to(size),
+            })
+        };
+        let luma = table(&mut data, precision & 0x01 != 0)?;
+        // A single table is used for chroma too
+        let chroma = if data.has_remaining() {
+            table(&mut data, precision & 0x02 != 0)?
+        } else {
+            luma.clone()
+        };
+
+        let tables = [luma, chroma];
+        if self.q != JPEG_Q_DYNAMIC {
+            self.quantization_tables.insert(self.q, tables.clone());
+        }
+        Ok(tables)
+    }
+}
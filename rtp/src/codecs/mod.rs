@@ -2,6 +2,12 @@ pub mod av1;
 pub mod g7xx;
 pub mod h264;
 pub mod h265;
+pub mod jpeg;
 pub mod opus;
+pub mod pcm;
+pub mod red;
+pub mod rtx;
+pub mod telephone_event;
+pub mod ulpfec;
 pub mod vp8;
 pub mod vp9;
@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod pcm_test;
+
+use bytes::Bytes;
+
+use crate::error::{Error, Result};
+use crate::packetizer::{Depacketizer, Payloader};
+
+/// Size of an L16 sample, a 16 bit signed integer in network byte order.
+pub const L16_SAMPLE_SIZE: usize = 2;
+/// Size of an L24 sample, a 24 bit signed integer in network byte order.
+pub const L24_SAMPLE_SIZE: usize = 3;
+
+/// LinearPcmPayloader payloads uncompressed L16 (RFC 3551) or L24 (RFC 3190) audio, with the
+/// samples of the channels interleaved. Every payload holds whole sample frames, i.e. one
+/// sample of each channel.
+#[derive(Debug, Copy, Clone)]
+pub struct LinearPcmPayloader {
+    sample_size: usize,
+    channels: u16,
+}
+
+impl LinearPcmPayloader {
+    /// l16 returns a payloader of L16 audio with channels.
+    pub fn l16(channels: u16) -> Self {
+        LinearPcmPayloader {
+            sample_size: L16_SAMPLE_SIZE,
+            channels: channels.max(1),
+        }
+    }
+
+    /// l24 returns a payloader of L24 audio with channels.
+    pub fn l24(channels: u16) -> Self {
+        LinearPcmPayloader {
+            sample_size: L24_SAMPLE_SIZE,
+            channels: channels.max(1),
+        }
+    }
+
+    /// samples returns the timestamp increment of a payload, the number of its sample frames.
+    pub fn samples(&self, payload: &Bytes) -> u32 {
+        (payload.len() / self.frame_size()) as u32
+    }
+
+    fn frame_size(&self) -> usize {
+        self.sample_size * self.channels as usize
+    }
+}
+
+impl Payloader for LinearPcmPayloader {
+    /// Payload fragments linear PCM audio across one or more byte arrays, splitting it between
+    /// sample frames only
+    fn payload(&mut self, mtu: usize, payload: &Bytes) -> Result<Vec<Bytes>> {
+        let frame_size = self.frame_size();
+        if payload.is_empty() || mtu < frame_size {
+            return Ok(vec![]);
+        }
+        if !payload.len().is_multiple_of(frame_size) {
+            return Err(Error::ErrInvalidLinearPcm);
+        }
+
+        let max_fragment_size = mtu - mtu % frame_size;
+        let mut payloads = Vec::with_capacity(payload.len().div_ceil(max_fragment_size));
+        let mut index = 0;
+        while index < payload.len() {
+            let end = std::cmp::min(index + max_fragment_size, payload.len());
+            payloads.push(payload.slice(index..end));
+            index = end;
+        }
+
+        Ok(payloads)
+    }
+
+    fn clone_to(&self) -> Box<dyn Payloader + Send + Sync> {
+        Box::new(*self)
+    }
+}
+
+/// LinearPcmPacket represents a packet of L16 or L24 audio.
+#[derive(Debug, Copy, Clone)]
+pub struct LinearPcmPacket {
+    sample_size: usize,
+    channels: u16,
+}
+
+impl LinearPcmPacket {
+    /// l16 returns a depacketizer of L16 audio with channels.
+    pub fn l16(channels: u16) -> Self {
+        LinearPcmPacket {
+            sample_size: L16_SAMPLE_SIZE,
+            channels: channels.max(1),
+        }
+    }
+
+    /// l24 returns a depacketizer of L24 audio with channels.
+    pub fn l24(channels: u16) -> Self {
+        LinearPcmPacket {
+            sample_size: L24_SAMPLE_SIZE,
+            channels: channels.max(1),
+        }
+    }
+}
+
+impl Depacketizer for LinearPcmPacket {
+    /// depacketize returns the samples of the packet, checking that it holds whole sample
+    /// frames
+    fn depacketize(&mut self, packet: &Bytes) -> Result<Bytes> {
+        if packet.is_empty() {
+            return Err(Error::ErrShortPacket);
+        }
+        if !packet
+            .len()
+            .is_multiple_of(self.sample_size * self.channels as usize)
+        {
+            return Err(Error::ErrInvalidLinearPcm);
+        }
+
+        Ok(packet.clone())
+    }
+
+    fn is_partition_head(&self, _payload: &Bytes) -> bool {
+        true
+    }
+
+    fn is_partition_tail(&self, _marker: bool, _payload: &Bytes) -> bool {
+        true
+    }
+}
@@ -0,0 +1,54 @@
+use super::*;
+
+#[test]
+fn test_linear_pcm_payload() -> Result<()> {
+    // 10 stereo L24 sample frames of 6 bytes.
+    let samples = Bytes::from((0..60).collect::<Vec<u8>>());
+    let mut pck = LinearPcmPayloader::l24(2);
+    assert_eq!(pck.samples(&samples), 10);
+
+    let payloads = pck.payload(20, &samples)?;
+    assert_eq!(
+        payloads.iter().map(|p| p.len()).collect::<Vec<_>>(),
+        vec![18, 18, 18, 6]
+    );
+    assert_eq!(payloads.concat(), samples);
+
+    let mut pck = LinearPcmPayloader::l16(0);
+    assert_eq!(pck.payload(1500, &samples)?.len(), 1);
+    assert!(pck.payload(1, &samples)?.is_empty());
+    assert!(pck.payload(1500, &Bytes::new())?.is_empty());
+
+    let result = pck.payload(1500, &samples.slice(..3));
+    assert_eq!(
+        Error::ErrInvalidLinearPcm,
+        result.expect_err("payload should be half a sample")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_linear_pcm_depacketize() -> Result<()> {
+    let mut pck = LinearPcmPacket::l16(2);
+    let payload = Bytes::from_static(&[0x00, 0x01, 0x00, 0x02, 0xff, 0xff, 0x80, 0x00]);
+    assert_eq!(pck.depacketize(&payload)?, payload);
+    assert!(pck.is_partition_head(&payload));
+    assert!(pck.is_partition_tail(false, &payload));
+
+    let result = pck.depacketize(&payload.slice(..6));
+    assert_eq!(
+        Error::ErrInvalidLinearPcm,
+        result.expect_err("payload should be one and a half sample frames")
+    );
+    let result = pck.depacketize(&Bytes::new());
+    assert_eq!(
+        Error::ErrShortPacket,
+        result.expect_err("payload should be empty")
+    );
+
+    let mut pck = LinearPcmPacket::l24(1);
+    assert_eq!(pck.depacketize(&payload.slice(..6))?.len(), 6);
+
+    Ok(())
+}
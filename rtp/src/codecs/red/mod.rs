@@ -0,0 +1,271 @@
+#[cfg(test)]
+mod red_test;
+
+use std::collections::VecDeque;
+
+use bytes::{Buf, BufMut, Bytes};
+use util::marshal::{Marshal, MarshalSize, Unmarshal};
+
+use crate::error::{Error, Result};
+use crate::packet::Packet;
+
+/// Size of the header of a redundant block.
+pub const RED_BLOCK_HEADER_SIZE: usize = 4;
+/// Size of the header of the primary block.
+pub const RED_PRIMARY_HEADER_SIZE: usize = 1;
+/// Largest timestamp offset of a redundant block, a 14 bit field.
+pub const RED_MAX_TIMESTAMP_OFFSET: u32 = (1 << 14) - 1;
+/// Largest payload of a redundant block, as its length is a 10 bit field.
+pub const RED_MAX_BLOCK_LENGTH: usize = (1 << 10) - 1;
+
+/// RedBlock is a redundant block of a [`RedPayload`], the payload of a packet sent earlier.
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct RedBlock {
+    /// Payload type of the block.
+    pub payload_type: u8,
+    /// Timestamp of the RED packet minus the one of the block.
+    pub timestamp_offset: u16,
+    pub payload: Bytes,
+}
+
+/// RedPayload is the payload of a RED packet, which carries the payload of an RTP packet
+/// together with redundant copies of earlier ones.
+///
+/// ```text
+///  0                   1                    2                   3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |1|   block PT  |  timestamp offset         |   block length    |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// :                              ...                              :
+/// +-+-+-+-+-+-+-+-+
+/// |0|   block PT  |
+/// +-+-+-+-+-+-+-+-+
+/// |  redundant blocks ... | primary block ...                     |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// ## Specifications
+///
+/// * [RFC 2198]
+///
+/// [RFC 2198]: https://tools.ietf.org/html/rfc2198
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct RedPayload {
+    /// Redundant blocks, the oldest first.
+    pub redundant: Vec<RedBlock>,
+    /// Payload type of the primary block.
+    pub payload_type: u8,
+    /// Payload of the primary block.
+    pub payload: Bytes,
+}
+
+impl Unmarshal for RedPayload {
+    /// Unmarshal parses the passed byte slice and stores the result in the members
+    fn unmarshal<B>(raw_payload: &mut B) -> std::result::Result<Self, util::Error>
+    where
+        Self: Sized,
+        B: Buf,
+    {
+        let mut headers = vec![];
+        loop {
+            if raw_payload.remaining() < RED_PRIMARY_HEADER_SIZE {
+                return Err(Error::ErrShortPacket.into());
+            }
+            if raw_payload.chunk()[0] & 0x80 == 0 {
+                break;
+            }
+
+            if raw_payload.remaining() < RED_BLOCK_HEADER_SIZE {
+                return Err(Error::ErrShortPacket.into());
+            }
+            let header = raw_payload.get_u32();
+            let payload_type = ((header >> 24) & 0x7F) as u8;
+            let timestamp_offset = ((header >> 10) & RED_MAX_TIMESTAMP_OFFSET) as u16;
+            let length = (header & RED_MAX_BLOCK_LENGTH as u32) as usize;
+            headers.push((payload_type, timestamp_offset, length));
+        }
+        let payload_type = raw_payload.get_u8() & 0x7F;
+
+        let mut redundant = Vec::with_capacity(headers.len());
+        for (payload_type, timestamp_offset, length) in headers {
+            if raw_payload.remaining() < length {
+                return Err(Error::ErrShortPacket.into());
+            }
+            redundant.push(RedBlock {
+                payload_type,
+                timestamp_offset,
+                payload: raw_payload.copy_to_bytes(length),
+            });
+        }
+
+        Ok(RedPayload {
+            redundant,
+            payload_type,
+            payload: raw_payload.copy_to_bytes(raw_payload.remaining()),
+        })
+    }
+}
+
+impl MarshalSize for RedPayload {
+    /// MarshalSize returns the size of the RedPayload once marshaled.
+    fn marshal_size(&self) -> usize {
+        self.redundant
+            .iter()
+            .map(|block| RED_BLOCK_HEADER_SIZE + block.payload.len())
+            .sum::<usize>()
+            + RED_PRIMARY_HEADER_SIZE
+            + self.payload.len()
+    }
+}
+
+impl Marshal for RedPayload {
+    /// MarshalTo serializes the members to buffer
+    fn marshal_to(&self, mut buf: &mut [u8]) -> std::result::Result<usize, util::Error> {
+        let size = self.marshal_size();
+        if buf.remaining_mut() < size {
+            return Err(Error::ErrBufferTooSmall.into());
+        }
+        if self.payload_type > 0x7F {
+            return Err(Error::ErrInvalidRedBlock.into());
+        }
+
+        for block in &self.redundant {
+            if block.payload_type > 0x7F
+                || block.timestamp_offset as u32 > RED_MAX_TIMESTAMP_OFFSET
+                || block.payload.len() > RED_MAX_BLOCK_LENGTH
+            {
+                return Err(Error::ErrInvalidRedBlock.into());
+            }
+            buf.put_u32(
+                0x8000_0000
+                    | ((block.payload_type as u32) << 24)
+                    | ((block.timestamp_offset as u32) << 10)
+                    | block.payload.len() as u32,
+            );
+        }
+        buf.put_u8(self.payload_type);
+        for block in &self.redundant {
+            buf.put_slice(&block.payload);
+        }
+        buf.put_slice(&self.payload);
+
+        Ok(size)
+    }
+}
+
+/// RedEncoder encapsulates payloads in RED payloads, each together with redundant copies of
+/// the previous ones, so that a receiver can recover from losing some of the packets.
+#[derive(Debug, Clone)]
+pub struct RedEncoder {
+    distance: usize,
+    /// Payload type, timestamp and payload of the previous payloads, the oldest first.
+    history: VecDeque<(u8, u32, Bytes)>,
+}
+
+impl RedEncoder {
+    /// new returns a RedEncoder which adds up to distance previous payloads to every RED
+    /// payload.
+    pub fn new(distance: usize) -> Self {
+        RedEncoder {
+            distance,
+            history: VecDeque::with_capacity(distance),
+        }
+    }
+
+    /// encode returns the RED payload of the payload of an RTP packet. Previous payloads
+    /// which are too large or too old for a redundant block are left out.
+    pub fn encode(&mut self, payload_type: u8, timestamp: u32, payload: &Bytes) -> Result<Bytes> {
+        let redundant = self
+            .history
+            .iter()
+            .filter_map(|(pt, ts, data)| {
+                let offset = timestamp.wrapping_sub(*ts);
+                if offset > RED_MAX_TIMESTAMP_OFFSET || data.len() > RED_MAX_BLOCK_LENGTH {
+                    return None;
+                }
+                Some(RedBlock {
+                    payload_type: *pt,
+                    timestamp_offset: offset as u16,
+                    payload: data.clone(),
+                })
+            })
+            .collect();
+        let red = RedPayload {
+            redundant,
+            payload_type,
+            payload: payload.clone(),
+        }
+        .marshal()?;
+
+        if self.distance > 0 {
+            if self.history.len() == self.distance {
+                self.history.pop_front();
+            }
+            self.history
+                .push_back((payload_type, timestamp, payload.clone()));
+        }
+
+        Ok(red)
+    }
+}
+
+/// RedDecoder decapsulates RED packets into the packets they carry.
+#[derive(Debug, Default, Clone)]
+pub struct RedDecoder {
+    last_sequence_number: Option<u16>,
+}
+
+impl RedDecoder {
+    pub fn new() -> Self {
+        RedDecoder::default()
+    }
+
+    /// decode returns the packets carried by a RED packet, in order. Those of redundant
+    /// blocks are returned only if they are newer than all packets returned before, i.e.
+    /// were lost, and get the sequence numbers right before the RED packet.
+    pub fn decode(&mut self, packet: &Packet) -> Result<Vec<Packet>> {
+        let red = RedPayload::unmarshal(&mut packet.payload.clone())?;
+
+        let sequence_number = packet.header.sequence_number;
+        let is_new = |last: Option<u16>, seq: u16| {
+            last.is_none_or(|last| (seq.wrapping_sub(last) as i16) > 0)
+        };
+
+        let mut packets = Vec::with_capacity(red.redundant.len() + 1);
+        let count = red.redundant.len();
+        for (i, block) in red.redundant.into_iter().enumerate() {
+            let seq = sequence_number.wrapping_sub((count - i) as u16);
+            if !is_new(self.last_sequence_number, seq) {
+                continue;
+            }
+
+            let mut header = packet.header.clone();
+            header.payload_type = block.payload_type;
+            header.sequence_number = seq;
+            header.timestamp = packet
+                .header
+                .timestamp
+                .wrapping_sub(block.timestamp_offset as u32);
+            header.marker = false;
+            packets.push(Packet {
+                header,
+                payload: block.payload,
+                ..Default::default()
+            });
+        }
+
+        if is_new(self.last_sequence_number, sequence_number) {
+            self.last_sequence_number = Some(sequence_number);
+        }
+        let mut header = packet.header.clone();
+        header.payload_type = red.payload_type;
+        packets.push(Packet {
+            header,
+            payload: red.payload,
+            ..Default::default()
+        });
+
+        Ok(packets)
+    }
+}
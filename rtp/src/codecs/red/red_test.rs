@@ -0,0 +1,133 @@
+use super::*;
+use crate::header::Header;
+
+#[test]
+fn test_red_payload_round_trip() -> Result<()> {
+    let red = RedPayload {
+        redundant: vec![RedBlock {
+            payload_type: 111,
+            timestamp_offset: 960,
+            payload: Bytes::from_static(&[0x01, 0x02, 0x03]),
+        }],
+        payload_type: 111,
+        payload: Bytes::from_static(&[0x04, 0x05]),
+    };
+
+    let raw = red.marshal()?;
+    assert_eq!(raw.len(), red.marshal_size());
+    assert_eq!(
+        raw,
+        Bytes::from_static(&[
+            0xEF, 0x0F, 0x00, 0x03, // F=1 PT=111 offset=960 length=3
+            0x6F, // F=0 PT=111
+            0x01, 0x02, 0x03, 0x04, 0x05,
+        ])
+    );
+    assert_eq!(RedPayload::unmarshal(&mut raw.clone())?, red);
+
+    Ok(())
+}
+
+#[test]
+fn test_red_payload_unmarshal_errors() {
+    let tests = vec![
+        ("Empty", Bytes::new()),
+        ("ShortBlockHeader", Bytes::from_static(&[0xEF, 0x0F])),
+        (
+            "NoPrimaryHeader",
+            Bytes::from_static(&[0xEF, 0x0F, 0x00, 0x00]),
+        ),
+        (
+            "ShortBlock",
+            Bytes::from_static(&[0xEF, 0x0F, 0x00, 0x03, 0x6F, 0x01]),
+        ),
+    ];
+
+    for (name, raw) in tests {
+        let err = RedPayload::unmarshal(&mut raw.clone()).expect_err(name);
+        assert_eq!(Error::ErrShortPacket, err, "{name}");
+    }
+}
+
+#[test]
+fn test_red_encoder() -> Result<()> {
+    let mut encoder = RedEncoder::new(2);
+    let payloads = [
+        Bytes::from_static(&[0x01]),
+        Bytes::from_static(&[0x02]),
+        Bytes::from_static(&[0x03]),
+        Bytes::from_static(&[0x04]),
+    ];
+
+    let mut reds = vec![];
+    for (i, payload) in payloads.iter().enumerate() {
+        let raw = encoder.encode(111, 1000 + 960 * i as u32, payload)?;
+        reds.push(RedPayload::unmarshal(&mut raw.clone())?);
+    }
+
+    assert!(reds[0].redundant.is_empty());
+    assert_eq!(reds[1].redundant.len(), 1);
+    assert_eq!(
+        reds[3],
+        RedPayload {
+            redundant: vec![
+                RedBlock {
+                    payload_type: 111,
+                    timestamp_offset: 1920,
+                    payload: payloads[1].clone(),
+                },
+                RedBlock {
+                    payload_type: 111,
+                    timestamp_offset: 960,
+                    payload: payloads[2].clone(),
+                },
+            ],
+            payload_type: 111,
+            payload: payloads[3].clone(),
+        }
+    );
+
+    // Payloads too old for the 14 bit timestamp offset aren't sent again.
+    let raw = encoder.encode(111, 1000 + 960 * 3 + 20000, &payloads[0])?;
+    assert!(RedPayload::unmarshal(&mut raw.clone())?
+        .redundant
+        .is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_red_decoder() -> Result<()> {
+    let mut encoder = RedEncoder::new(2);
+    let mut packets = vec![];
+    for i in 0..4u16 {
+        let timestamp = 1000 + 960 * i as u32;
+        let payload = Bytes::from(vec![i as u8; 4]);
+        packets.push(Packet {
+            header: Header {
+                payload_type: 63,
+                sequence_number: 65534u16.wrapping_add(i),
+                timestamp,
+                ..Default::default()
+            },
+            payload: encoder.encode(111, timestamp, &payload)?,
+            ..Default::default()
+        });
+    }
+
+    let mut decoder = RedDecoder::new();
+    let decoded = decoder.decode(&packets[0])?;
+    assert_eq!(decoded.len(), 1);
+    assert_eq!(decoded[0].header.payload_type, 111);
+    assert_eq!(decoded[0].payload, Bytes::from_static(&[0, 0, 0, 0]));
+
+    // packets[1] and packets[2] got lost, packets[3] brings both of them back.
+    let decoded = decoder.decode(&packets[3])?;
+    let decoded = decoded
+        .iter()
+        .map(|p| (p.header.sequence_number, p.header.timestamp, p.payload[0]))
+        .collect::<Vec<_>>();
+    assert_eq!(decoded, vec![(65535, 1960, 1), (0, 2920, 2), (1, 3880, 3)]);
+
+    Ok(())
+}
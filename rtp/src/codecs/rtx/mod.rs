@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod rtx_test;
+
+use bytes::{Buf, BufMut, BytesMut};
+
+use crate::error::{Error, Result};
+use crate::packet::Packet;
+
+/// Size of the original sequence number in front of the payload of an RTX packet.
+pub const RTX_OSN_SIZE: usize = 2;
+
+/// encapsulate wraps packet, as cached for a retransmission, into an RTX packet of the repair
+/// stream with ssrc, payload_type and sequence_number, the original sequence number is put in
+/// front of the payload.
+///
+/// ```text
+///  0                   1                   2                   3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |                         RTP Header                            |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |            OSN                |                               |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+                               |
+/// |                  Original RTP Packet Payload                  |
+/// |                                                               |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// ## Specifications
+///
+/// * [RFC 4588 §4]
+///
+/// [RFC 4588 §4]: https://tools.ietf.org/html/rfc4588#section-4
+pub fn encapsulate(packet: &Packet, ssrc: u32, payload_type: u8, sequence_number: u16) -> Packet {
+    let mut payload = BytesMut::with_capacity(RTX_OSN_SIZE + packet.payload.len());
+    payload.put_u16(packet.header.sequence_number);
+    payload.put(&*packet.payload);
+
+    let mut header = packet.header.clone();
+    header.ssrc = ssrc;
+    header.payload_type = payload_type;
+    header.sequence_number = sequence_number;
+
+    Packet {
+        header,
+        payload: payload.freeze(),
+        padding_size: packet.padding_size,
+    }
+}
+
+/// decapsulate unwraps an RTX packet back into the original packet, whose ssrc and
+/// payload_type are the ones the repair stream is associated with.
+pub fn decapsulate(packet: &Packet, ssrc: u32, payload_type: u8) -> Result<Packet> {
+    if packet.payload.len() < RTX_OSN_SIZE {
+        return Err(Error::ErrShortPacket);
+    }
+
+    let mut payload = packet.payload.clone();
+    let mut header = packet.header.clone();
+    header.ssrc = ssrc;
+    header.payload_type = payload_type;
+    header.sequence_number = payload.get_u16();
+
+    Ok(Packet {
+        header,
+        payload,
+        padding_size: packet.padding_size,
+    })
+}
@@ -0,0 +1,61 @@
+use bytes::Bytes;
+use util::marshal::{Marshal, Unmarshal};
+
+use super::*;
+use crate::header::Header;
+
+fn packet() -> Packet {
+    let mut packet = Packet {
+        header: Header {
+            version: 2,
+            marker: true,
+            payload_type: 96,
+            sequence_number: 0x1234,
+            timestamp: 3653407706,
+            ssrc: 476325762,
+            ..Default::default()
+        },
+        payload: Bytes::from_static(&[0x01, 0x02, 0x03]),
+        ..Default::default()
+    };
+    packet
+        .header
+        .set_extension(1, Bytes::from_static(&[0xAA]))
+        .unwrap();
+    packet
+}
+
+#[test]
+fn test_rtx_round_trip() -> Result<()> {
+    let original = packet();
+
+    let rtx = encapsulate(&original, 0xDEADBEEF, 97, 7);
+    assert_eq!(rtx.header.ssrc, 0xDEADBEEF);
+    assert_eq!(rtx.header.payload_type, 97);
+    assert_eq!(rtx.header.sequence_number, 7);
+    assert_eq!(rtx.header.timestamp, original.header.timestamp);
+    assert_eq!(rtx.header.extensions, original.header.extensions);
+    assert_eq!(
+        rtx.payload,
+        Bytes::from_static(&[0x12, 0x34, 0x01, 0x02, 0x03])
+    );
+
+    // The RTX packet goes over the wire like any other one.
+    let raw = rtx.marshal().unwrap();
+    let received = Packet::unmarshal(&mut raw.clone()).unwrap();
+    assert_eq!(decapsulate(&received, 476325762, 96)?, original);
+
+    Ok(())
+}
+
+#[test]
+fn test_rtx_decapsulate_short_packet() {
+    let mut rtx = encapsulate(&packet(), 0xDEADBEEF, 97, 7);
+    rtx.payload = rtx.payload.slice(..1);
+
+    let result = decapsulate(&rtx, 476325762, 96);
+    assert_eq!(
+        Error::ErrShortPacket,
+        result.expect_err("RTX payload should be too short")
+    );
+}
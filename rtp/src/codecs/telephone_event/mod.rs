@@ -0,0 +1,188 @@
+#[cfg(test)]
+mod telephone_event_test;
+
+use bytes::{Buf, BufMut, Bytes};
+use util::marshal::{Marshal, MarshalSize, Unmarshal};
+
+use crate::error::{Error, Result};
+
+/// Size of a telephone event.
+pub const TELEPHONE_EVENT_SIZE: usize = 4;
+/// Largest volume of a telephone event, its power level in -dBm0, a 6 bit field.
+pub const TELEPHONE_EVENT_MAX_VOLUME: u8 = 63;
+/// Number of times the packet ending an event is sent, so that a receiver still gets the end
+/// of the event if some of them are lost (RFC 4733 section 2.5.1.4).
+pub const TELEPHONE_EVENT_END_REPETITIONS: usize = 3;
+
+/// DTMF digits of the events 0 to 15 (RFC 4733 section 3.2).
+const DTMF_DIGITS: &[u8; 16] = b"0123456789*#ABCD";
+
+/// dtmf_event returns the event of a DTMF digit, one of `0-9`, `*`, `#` and `A-D`.
+pub fn dtmf_event(digit: char) -> Option<u8> {
+    let digit = digit.to_ascii_uppercase();
+    DTMF_DIGITS
+        .iter()
+        .position(|d| *d as char == digit)
+        .map(|event| event as u8)
+}
+
+/// dtmf_digit returns the DTMF digit of an event, if it is one.
+pub fn dtmf_digit(event: u8) -> Option<char> {
+    DTMF_DIGITS.get(event as usize).map(|d| *d as char)
+}
+
+/// TelephoneEvent is the payload of a telephone-event packet, carrying a DTMF digit or
+/// another telephony event.
+///
+/// ```text
+///  0                   1                   2                   3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |     event     |E|R| volume    |          duration             |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// All packets of an event have the timestamp of its start, and the duration of the event
+/// so far in timestamp units.
+///
+/// ## Specifications
+///
+/// * [RFC 4733]
+///
+/// [RFC 4733]: https://tools.ietf.org/html/rfc4733
+#[derive(PartialEq, Eq, Debug, Default, Copy, Clone)]
+pub struct TelephoneEvent {
+    pub event: u8,
+    /// Whether this is the end of the event.
+    pub end: bool,
+    /// Power level of tones in -dBm0, up to [`TELEPHONE_EVENT_MAX_VOLUME`].
+    pub volume: u8,
+    pub duration: u16,
+}
+
+impl Unmarshal for TelephoneEvent {
+    /// Unmarshal parses the passed byte slice and stores the result in the members
+    fn unmarshal<B>(raw_payload: &mut B) -> std::result::Result<Self, util::Error>
+    where
+        Self: Sized,
+        B: Buf,
+    {
+        if raw_payload.remaining() < TELEPHONE_EVENT_SIZE {
+            return Err(Error::ErrShortPacket.into());
+        }
+
+        let event = raw_payload.get_u8();
+        let b = raw_payload.get_u8();
+        let duration = raw_payload.get_u16();
+
+        Ok(TelephoneEvent {
+            event,
+            end: b & 0x80 != 0,
+            volume: b & TELEPHONE_EVENT_MAX_VOLUME,
+            duration,
+        })
+    }
+}
+
+impl MarshalSize for TelephoneEvent {
+    /// MarshalSize returns the size of the TelephoneEvent once marshaled.
+    fn marshal_size(&self) -> usize {
+        TELEPHONE_EVENT_SIZE
+    }
+}
+
+impl Marshal for TelephoneEvent {
+    /// MarshalTo serializes the members to buffer
+    fn marshal_to(&self, mut buf: &mut [u8]) -> std::result::Result<usize, util::Error> {
+        if buf.remaining_mut() < TELEPHONE_EVENT_SIZE {
+            return Err(Error::ErrBufferTooSmall.into());
+        }
+        if self.volume > TELEPHONE_EVENT_MAX_VOLUME {
+            return Err(Error::ErrInvalidTelephoneEvent.into());
+        }
+
+        buf.put_u8(self.event);
+        buf.put_u8(if self.end { 0x80 } else { 0 } | self.volume);
+        buf.put_u16(self.duration);
+
+        Ok(TELEPHONE_EVENT_SIZE)
+    }
+}
+
+/// TelephoneEventEncoder encodes the payloads of the packets of an event. The packets are
+/// sent with the timestamp of the start of the event, the first of them with the marker bit.
+#[derive(Debug, Copy, Clone)]
+pub struct TelephoneEventEncoder {
+    event: u8,
+    volume: u8,
+}
+
+impl TelephoneEventEncoder {
+    /// new returns an encoder of event, e.g. one of [`dtmf_event`], played at volume.
+    pub fn new(event: u8, volume: u8) -> Result<Self> {
+        if volume > TELEPHONE_EVENT_MAX_VOLUME {
+            return Err(Error::ErrInvalidTelephoneEvent);
+        }
+        Ok(TelephoneEventEncoder { event, volume })
+    }
+
+    /// encode returns the payload of the event lasting duration so far.
+    pub fn encode(&self, duration: u16) -> Result<Bytes> {
+        Ok(self.event(false, duration).marshal()?)
+    }
+
+    /// encode_end returns the payloads of the packets ending the event after duration, the
+    /// same payload [`TELEPHONE_EVENT_END_REPETITIONS`] times.
+    pub fn encode_end(&self, duration: u16) -> Result<Vec<Bytes>> {
+        let payload = self.event(true, duration).marshal()?;
+        Ok(vec![payload; TELEPHONE_EVENT_END_REPETITIONS])
+    }
+
+    fn event(&self, end: bool, duration: u16) -> TelephoneEvent {
+        TelephoneEvent {
+            event: self.event,
+            end,
+            volume: self.volume,
+            duration,
+        }
+    }
+}
+
+/// TelephoneEventDecoder detects the start and end of events in the payloads of received
+/// telephone-event packets, ignoring those repeating what was seen before.
+#[derive(Debug, Default, Clone)]
+pub struct TelephoneEventDecoder {
+    /// Timestamp of the last event, and whether its end was seen.
+    last: Option<(u32, bool)>,
+}
+
+impl TelephoneEventDecoder {
+    pub fn new() -> Self {
+        TelephoneEventDecoder::default()
+    }
+
+    /// decode returns the event of the payload of a packet with timestamp, if it starts a
+    /// new event or ends one. Packets continuing an event, repeating its end or of events
+    /// older than the last one return None.
+    pub fn decode(&mut self, timestamp: u32, payload: &Bytes) -> Result<Option<TelephoneEvent>> {
+        let event = TelephoneEvent::unmarshal(&mut payload.clone())?;
+
+        let is_new = match self.last {
+            None => true,
+            Some((last, _)) if last == timestamp => false,
+            Some((last, _)) => (timestamp.wrapping_sub(last) as i32) > 0,
+        };
+        if is_new {
+            self.last = Some((timestamp, event.end));
+            return Ok(Some(event));
+        }
+
+        match &mut self.last {
+            Some((last, ended)) if *last == timestamp && event.end && !*ended => {
+                *ended = true;
+                Ok(Some(event))
+            }
+            _ => Ok(None),
+        }
+    }
+}
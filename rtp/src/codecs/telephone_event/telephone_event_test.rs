@@ -0,0 +1,112 @@
+use super::*;
+
+#[test]
+fn test_telephone_event_round_trip() -> Result<()> {
+    let event = TelephoneEvent {
+        event: 11,
+        end: true,
+        volume: 10,
+        duration: 1600,
+    };
+
+    let raw = event.marshal()?;
+    assert_eq!(raw, Bytes::from_static(&[0x0B, 0x8A, 0x06, 0x40]));
+    assert_eq!(TelephoneEvent::unmarshal(&mut raw.clone())?, event);
+
+    // The reserved bit is ignored.
+    let raw = Bytes::from_static(&[0x05, 0x4A, 0x00, 0xA0]);
+    assert_eq!(
+        TelephoneEvent::unmarshal(&mut raw.clone())?,
+        TelephoneEvent {
+            event: 5,
+            end: false,
+            volume: 10,
+            duration: 160,
+        }
+    );
+
+    let result = TelephoneEvent::unmarshal(&mut Bytes::from_static(&[0x05, 0x4A, 0x00]));
+    assert_eq!(
+        Error::ErrShortPacket,
+        result.expect_err("event should be too short")
+    );
+
+    let result = TelephoneEvent {
+        volume: 64,
+        ..event
+    }
+    .marshal();
+    assert_eq!(
+        Error::ErrInvalidTelephoneEvent,
+        result.expect_err("volume should be invalid")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_dtmf_digits() {
+    for (digit, event) in [
+        ('0', 0),
+        ('9', 9),
+        ('*', 10),
+        ('#', 11),
+        ('a', 12),
+        ('D', 15),
+    ] {
+        assert_eq!(dtmf_event(digit), Some(event), "{digit}");
+    }
+    assert_eq!(dtmf_event('E'), None);
+
+    assert_eq!(dtmf_digit(11), Some('#'));
+    assert_eq!(dtmf_digit(16), None);
+}
+
+#[test]
+fn test_telephone_event_encoder_decoder() -> Result<()> {
+    let encoder = TelephoneEventEncoder::new(dtmf_event('7').unwrap(), 10)?;
+    let mut packets = vec![(8000, encoder.encode(160)?), (8000, encoder.encode(320)?)];
+    let ends = encoder.encode_end(480)?;
+    assert_eq!(ends.len(), TELEPHONE_EVENT_END_REPETITIONS);
+    packets.extend(ends.into_iter().map(|payload| (8000, payload)));
+
+    let encoder = TelephoneEventEncoder::new(dtmf_event('#').unwrap(), 10)?;
+    packets.push((9600, encoder.encode(160)?));
+    // A late packet of the previous event.
+    packets.push((8000, encoder.encode_end(480)?[0].clone()));
+
+    let mut decoder = TelephoneEventDecoder::new();
+    let mut events = vec![];
+    for (timestamp, payload) in packets {
+        if let Some(event) = decoder.decode(timestamp, &payload)? {
+            events.push(event);
+        }
+    }
+    assert_eq!(
+        events,
+        vec![
+            TelephoneEvent {
+                event: 7,
+                end: false,
+                volume: 10,
+                duration: 160,
+            },
+            TelephoneEvent {
+                event: 7,
+                end: true,
+                volume: 10,
+                duration: 480,
+            },
+            TelephoneEvent {
+                event: 11,
+                end: false,
+                volume: 10,
+                duration: 160,
+            },
+        ]
+    );
+
+    assert!(TelephoneEventEncoder::new(1, 64).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,365 @@
+#[cfg(test)]
+mod ulpfec_test;
+
+use std::collections::{HashMap, VecDeque};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use util::marshal::{Marshal, MarshalSize, Unmarshal};
+
+use crate::error::{Error, Result};
+use crate::packet::Packet;
+
+/// Size of the FEC header.
+pub const ULPFEC_HEADER_SIZE: usize = 10;
+/// Size of the ULP level header with a 16 bit mask (L=0).
+pub const ULPFEC_LEVEL_HEADER_SIZE: usize = 4;
+/// Size of the ULP level header with a 48 bit mask (L=1).
+pub const ULPFEC_LEVEL_HEADER_SIZE_LONG: usize = 8;
+/// Largest number of media packets a FEC packet can protect.
+pub const ULPFEC_MAX_MEDIA_PACKETS: usize = 48;
+
+/// Size of the fixed RTP header, the part of media packets which isn't protected as it is.
+const RTP_FIXED_HEADER_SIZE: usize = 12;
+/// Number of media packets a [`UlpfecDecoder`] keeps to recover others with.
+const DECODER_MEDIA_PACKETS: usize = 256;
+/// Number of FEC packets a [`UlpfecDecoder`] keeps until they recovered a packet.
+const DECODER_FEC_PACKETS: usize = 32;
+
+/// UlpfecPacket is the payload of a FEC packet, which protects the media packets of a
+/// single SSRC with a single level of protection. Each recovery field is the XOR of the
+/// respective field of all protected packets.
+///
+/// ```text
+///  0                   1                   2                   3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |E|L|P|X|  CC   |M| PT recovery |            SN base            |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |                          TS recovery                          |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |        length recovery        |       Protection Length       |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |             mask              | mask cont. (present only when L = 1) |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |                       payload recovery ...                    |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// ## Specifications
+///
+/// * [RFC 5109]
+///
+/// [RFC 5109]: https://tools.ietf.org/html/rfc5109
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct UlpfecPacket {
+    /// First two bytes of the RTP header, P, X, CC, M and PT, without the version.
+    pub header_recovery: [u8; 2],
+    /// Sequence number of the first packet which can be protected.
+    pub sn_base: u16,
+    pub ts_recovery: u32,
+    /// Length of the protected packets after the fixed RTP header.
+    pub length_recovery: u16,
+    /// Bit 47 is set if sn_base is protected, bit 46 for sn_base + 1, and so on. The long
+    /// mask is sent if any of the lowest 32 bits is set.
+    pub mask: u64,
+    /// Protected packets after the fixed RTP header, as long as the longest of them.
+    pub payload_recovery: Bytes,
+}
+
+impl UlpfecPacket {
+    fn is_long(&self) -> bool {
+        self.mask & 0xFFFF_FFFF != 0
+    }
+
+    /// protected returns the sequence numbers of the protected packets.
+    pub fn protected(&self) -> Vec<u16> {
+        (0..ULPFEC_MAX_MEDIA_PACKETS)
+            .filter(|i| self.mask & (1 << (47 - i)) != 0)
+            .map(|i| self.sn_base.wrapping_add(i as u16))
+            .collect()
+    }
+
+    /// xor adds a marshaled media packet to the recovery fields.
+    fn xor(&mut self, raw: &[u8], payload_recovery: &mut BytesMut) {
+        self.header_recovery[0] ^= raw[0] & 0x3F;
+        self.header_recovery[1] ^= raw[1];
+        self.ts_recovery ^= u32::from_be_bytes([raw[4], raw[5], raw[6], raw[7]]);
+        let protected = &raw[RTP_FIXED_HEADER_SIZE..];
+        self.length_recovery ^= protected.len() as u16;
+        if payload_recovery.len() < protected.len() {
+            payload_recovery.resize(protected.len(), 0);
+        }
+        for (r, b) in payload_recovery.iter_mut().zip(protected) {
+            *r ^= b;
+        }
+    }
+}
+
+impl Unmarshal for UlpfecPacket {
+    /// Unmarshal parses the passed byte slice and stores the result in the members
+    fn unmarshal<B>(raw_packet: &mut B) -> std::result::Result<Self, util::Error>
+    where
+        Self: Sized,
+        B: Buf,
+    {
+        if raw_packet.remaining() < ULPFEC_HEADER_SIZE + ULPFEC_LEVEL_HEADER_SIZE {
+            return Err(Error::ErrShortPacket.into());
+        }
+
+        let b0 = raw_packet.get_u8();
+        let long = b0 & 0x40 != 0;
+        let header_recovery = [b0 & 0x3F, raw_packet.get_u8()];
+        let sn_base = raw_packet.get_u16();
+        let ts_recovery = raw_packet.get_u32();
+        let length_recovery = raw_packet.get_u16();
+
+        let protection_length = raw_packet.get_u16() as usize;
+        let mut mask = (raw_packet.get_u16() as u64) << 32;
+        if long {
+            if raw_packet.remaining() < ULPFEC_LEVEL_HEADER_SIZE_LONG - ULPFEC_LEVEL_HEADER_SIZE {
+                return Err(Error::ErrShortPacket.into());
+            }
+            mask |= raw_packet.get_u32() as u64;
+        }
+        if raw_packet.remaining() < protection_length {
+            return Err(Error::ErrShortPacket.into());
+        }
+
+        Ok(UlpfecPacket {
+            header_recovery,
+            sn_base,
+            ts_recovery,
+            length_recovery,
+            mask,
+            payload_recovery: raw_packet.copy_to_bytes(protection_length),
+        })
+    }
+}
+
+impl MarshalSize for UlpfecPacket {
+    /// MarshalSize returns the size of the UlpfecPacket once marshaled.
+    fn marshal_size(&self) -> usize {
+        let level_header_size = if self.is_long() {
+            ULPFEC_LEVEL_HEADER_SIZE_LONG
+        } else {
+            ULPFEC_LEVEL_HEADER_SIZE
+        };
+        ULPFEC_HEADER_SIZE + level_header_size + self.payload_recovery.len()
+    }
+}
+
+impl Marshal for UlpfecPacket {
+    /// MarshalTo serializes the members to buffer
+    fn marshal_to(&self, mut buf: &mut [u8]) -> std::result::Result<usize, util::Error> {
+        let size = self.marshal_size();
+        if buf.remaining_mut() < size {
+            return Err(Error::ErrBufferTooSmall.into());
+        }
+        if self.payload_recovery.len() > u16::MAX as usize || self.mask >> 48 != 0 {
+            return Err(Error::ErrInvalidUlpfecPacket.into());
+        }
+
+        let long = self.is_long();
+        buf.put_u8(((long as u8) << 6) | (self.header_recovery[0] & 0x3F));
+        buf.put_u8(self.header_recovery[1]);
+        buf.put_u16(self.sn_base);
+        buf.put_u32(self.ts_recovery);
+        buf.put_u16(self.length_recovery);
+        buf.put_u16(self.payload_recovery.len() as u16);
+        buf.put_u16((self.mask >> 32) as u16);
+        if long {
+            buf.put_u32(self.mask as u32);
+        }
+        buf.put_slice(&self.payload_recovery);
+
+        Ok(size)
+    }
+}
+
+/// UlpfecEncoder generates a FEC packet for every group of consecutive media packets.
+#[derive(Debug, Clone)]
+pub struct UlpfecEncoder {
+    group_size: usize,
+    sn_base: u16,
+    group: Vec<Bytes>,
+    mask: u64,
+}
+
+impl UlpfecEncoder {
+    /// new returns a UlpfecEncoder protecting groups of group_size media packets, from 1 to
+    /// [`ULPFEC_MAX_MEDIA_PACKETS`]. Any one packet of a group can be recovered.
+    pub fn new(group_size: usize) -> Self {
+        UlpfecEncoder {
+            group_size: group_size.clamp(1, ULPFEC_MAX_MEDIA_PACKETS),
+            sn_base: 0,
+            group: Vec::with_capacity(group_size),
+            mask: 0,
+        }
+    }
+
+    /// push_media adds a media packet to the current group, and returns the payload of the
+    /// FEC packet protecting it once the group is complete. The FEC packet should be sent
+    /// with the SSRC of the media packets, e.g. in RED.
+    pub fn push_media(&mut self, packet: &Packet) -> Result<Option<Bytes>> {
+        let raw = packet.marshal()?;
+        let seq = packet.header.sequence_number;
+
+        let mut fec = None;
+        let offset = seq.wrapping_sub(self.sn_base) as usize;
+        if !self.group.is_empty() && offset >= ULPFEC_MAX_MEDIA_PACKETS {
+            // Packets missing from the group would leave it too long for the mask.
+            fec = self.flush()?;
+        }
+
+        if self.group.is_empty() {
+            self.sn_base = seq;
+        }
+        self.mask |= 1 << (47 - seq.wrapping_sub(self.sn_base) as usize);
+        self.group.push(raw);
+
+        if self.group.len() >= self.group_size {
+            fec = self.flush()?;
+        }
+        Ok(fec)
+    }
+
+    /// flush returns the payload of the FEC packet protecting the current group, if any,
+    /// even when it isn't complete.
+    pub fn flush(&mut self) -> Result<Option<Bytes>> {
+        if self.group.is_empty() {
+            return Ok(None);
+        }
+
+        let mut fec = UlpfecPacket {
+            sn_base: self.sn_base,
+            mask: self.mask,
+            ..Default::default()
+        };
+        let mut payload_recovery = BytesMut::new();
+        for raw in self.group.drain(..) {
+            fec.xor(&raw, &mut payload_recovery);
+        }
+        fec.payload_recovery = payload_recovery.freeze();
+        self.mask = 0;
+
+        Ok(Some(fec.marshal()?))
+    }
+}
+
+/// UlpfecDecoder recovers lost media packets of a single SSRC from FEC packets.
+#[derive(Debug, Default, Clone)]
+pub struct UlpfecDecoder {
+    media: HashMap<u16, Bytes>,
+    media_order: VecDeque<u16>,
+    fec: VecDeque<(u32, UlpfecPacket)>,
+}
+
+impl UlpfecDecoder {
+    pub fn new() -> Self {
+        UlpfecDecoder::default()
+    }
+
+    /// push_media adds a received media packet, and returns the packets it allowed to
+    /// recover.
+    pub fn push_media(&mut self, packet: &Packet) -> Result<Vec<Packet>> {
+        self.insert_media(packet.header.sequence_number, packet.marshal()?);
+        Ok(self.recover())
+    }
+
+    /// push_fec adds a received FEC packet, e.g. the primary block of a RED packet, and
+    /// returns the packets it allowed to recover.
+    pub fn push_fec(&mut self, packet: &Packet) -> Result<Vec<Packet>> {
+        let fec = UlpfecPacket::unmarshal(&mut packet.payload.clone())?;
+        if self.fec.len() == DECODER_FEC_PACKETS {
+            self.fec.pop_front();
+        }
+        self.fec.push_back((packet.header.ssrc, fec));
+        Ok(self.recover())
+    }
+
+    fn insert_media(&mut self, seq: u16, raw: Bytes) {
+        if self.media.insert(seq, raw).is_some() {
+            return;
+        }
+        self.media_order.push_back(seq);
+        if self.media_order.len() > DECODER_MEDIA_PACKETS {
+            if let Some(seq) = self.media_order.pop_front() {
+                self.media.remove(&seq);
+            }
+        }
+    }
+
+    /// recover recovers packets until no FEC packet misses just one of its packets any more.
+    /// A recovery that doesn't make a valid packet is dropped along with its FEC packet.
+    fn recover(&mut self) -> Vec<Packet> {
+        let mut recovered = vec![];
+        loop {
+            let mut progress = false;
+            let mut i = 0;
+            while i < self.fec.len() {
+                let protected = self.fec[i].1.protected();
+                let missing = protected
+                    .iter()
+                    .filter(|seq| !self.media.contains_key(seq))
+                    .collect::<Vec<_>>();
+                match missing[..] {
+                    [] => {
+                        self.fec.remove(i);
+                    }
+                    [&seq] => {
+                        let (ssrc, fec) = self.fec.remove(i).unwrap_or_default();
+                        let raw = self.recover_packet(&fec, &protected, seq, ssrc);
+                        if let Some((raw, packet)) = raw.and_then(|raw| {
+                            let packet = Packet::unmarshal(&mut raw.clone()).ok()?;
+                            Some((raw, packet))
+                        }) {
+                            self.insert_media(seq, raw);
+                            recovered.push(packet);
+                            progress = true;
+                        }
+                    }
+                    _ => i += 1,
+                }
+            }
+            if !progress {
+                return recovered;
+            }
+        }
+    }
+
+    /// recover_packet returns the marshaled lost packet seq, None if fec doesn't fit the
+    /// packets received.
+    fn recover_packet(
+        &self,
+        fec: &UlpfecPacket,
+        protected: &[u16],
+        seq: u16,
+        ssrc: u32,
+    ) -> Option<Bytes> {
+        let mut recovery = fec.clone();
+        let mut payload_recovery = BytesMut::from(&fec.payload_recovery[..]);
+        let mut ssrc = ssrc;
+        for s in protected {
+            if let Some(raw) = self.media.get(s) {
+                if raw.len() - RTP_FIXED_HEADER_SIZE > fec.payload_recovery.len() {
+                    return None;
+                }
+                recovery.xor(raw, &mut payload_recovery);
+                ssrc = u32::from_be_bytes([raw[8], raw[9], raw[10], raw[11]]);
+            }
+        }
+
+        let length = recovery.length_recovery as usize;
+        if length > payload_recovery.len() {
+            return None;
+        }
+        let mut raw = BytesMut::with_capacity(RTP_FIXED_HEADER_SIZE + length);
+        raw.put_u8(0x80 | recovery.header_recovery[0]);
+        raw.put_u8(recovery.header_recovery[1]);
+        raw.put_u16(seq);
+        raw.put_u32(recovery.ts_recovery);
+        raw.put_u32(ssrc);
+        raw.put_slice(&payload_recovery[..length]);
+        Some(raw.freeze())
+    }
+}
@@ -0,0 +1,233 @@
+use super::*;
+use crate::header::Header;
+
+fn media_packet(sequence_number: u16, timestamp: u32, marker: bool, payload: &[u8]) -> Packet {
+    Packet {
+        header: Header {
+            version: 2,
+            marker,
+            payload_type: 96,
+            sequence_number,
+            timestamp,
+            ssrc: 0x1234_5678,
+            ..Default::default()
+        },
+        payload: Bytes::copy_from_slice(payload),
+        ..Default::default()
+    }
+}
+
+fn fec_packet(sequence_number: u16, payload: Bytes) -> Packet {
+    Packet {
+        header: Header {
+            version: 2,
+            payload_type: 97,
+            sequence_number,
+            ssrc: 0x1234_5678,
+            ..Default::default()
+        },
+        payload,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_ulpfec_packet_round_trip() -> Result<()> {
+    let tests = vec![
+        (
+            "ShortMask",
+            UlpfecPacket {
+                header_recovery: [0x01, 0xE0],
+                sn_base: 0xFFFE,
+                ts_recovery: 0x0102_0304,
+                length_recovery: 5,
+                mask: 0xC000 << 32,
+                payload_recovery: Bytes::from_static(&[0x01, 0x02, 0x03, 0x04, 0x05]),
+            },
+            vec![
+                0x01, 0xE0, 0xFF, 0xFE, 0x01, 0x02, 0x03, 0x04, 0x00, 0x05, 0x00, 0x05, 0xC0, 0x00,
+                0x01, 0x02, 0x03, 0x04, 0x05,
+            ],
+        ),
+        (
+            "LongMask",
+            UlpfecPacket {
+                header_recovery: [0x00, 0x60],
+                sn_base: 10,
+                ts_recovery: 0,
+                length_recovery: 1,
+                mask: 0x8000_0000_0001,
+                payload_recovery: Bytes::from_static(&[0xAA]),
+            },
+            vec![
+                0x40, 0x60, 0x00, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x01, 0x80, 0x00,
+                0x00, 0x00, 0x00, 0x01, 0xAA,
+            ],
+        ),
+    ];
+
+    for (name, packet, raw) in tests {
+        let marshaled = packet.marshal()?;
+        assert_eq!(&marshaled[..], &raw[..], "{name}: marshal");
+        assert_eq!(packet.marshal_size(), raw.len(), "{name}: marshal_size");
+        let unmarshaled = UlpfecPacket::unmarshal(&mut Bytes::from(raw))?;
+        assert_eq!(unmarshaled, packet, "{name}: unmarshal");
+    }
+
+    let protected = UlpfecPacket {
+        sn_base: 0xFFFF,
+        mask: 0x8000_0000_0001,
+        ..Default::default()
+    }
+    .protected();
+    assert_eq!(protected, vec![0xFFFF, 46]);
+
+    Ok(())
+}
+
+#[test]
+fn test_ulpfec_packet_unmarshal_short() {
+    let raw = [
+        0x40, 0x60, 0x00, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x01, 0x80, 0x00,
+    ];
+    let err = UlpfecPacket::unmarshal(&mut Bytes::copy_from_slice(&raw))
+        .expect_err("long mask should be missing");
+    assert_eq!(Error::ErrShortPacket, err);
+
+    let err = UlpfecPacket::unmarshal(&mut Bytes::copy_from_slice(&raw[..12]))
+        .expect_err("protection length should exceed the payload");
+    assert_eq!(Error::ErrShortPacket, err);
+}
+
+#[test]
+fn test_ulpfec_recover_lost_packet() -> Result<()> {
+    let media = [
+        media_packet(65534, 3000, false, &[0x01, 0x02, 0x03]),
+        media_packet(65535, 3000, false, &[0x04, 0x05, 0x06, 0x07, 0x08]),
+        media_packet(0, 3000, true, &[0x09]),
+    ];
+
+    let mut encoder = UlpfecEncoder::new(3);
+    assert_eq!(encoder.push_media(&media[0])?, None);
+    assert_eq!(encoder.push_media(&media[1])?, None);
+    let fec = encoder
+        .push_media(&media[2])?
+        .expect("group should be complete");
+
+    for lost in 0..media.len() {
+        // The FEC packet arrives after the other packets.
+        let mut decoder = UlpfecDecoder::new();
+        for (i, packet) in media.iter().enumerate() {
+            if i != lost {
+                assert!(decoder.push_media(packet)?.is_empty());
+            }
+        }
+        let recovered = decoder.push_fec(&fec_packet(1, fec.clone()))?;
+        assert_eq!(recovered, vec![media[lost].clone()], "lost packet {lost}");
+
+        // The FEC packet arrives before the other packets.
+        let mut decoder = UlpfecDecoder::new();
+        assert!(decoder.push_fec(&fec_packet(1, fec.clone()))?.is_empty());
+        let mut recovered = vec![];
+        for (i, packet) in media.iter().enumerate() {
+            if i != lost {
+                recovered.extend(decoder.push_media(packet)?);
+            }
+        }
+        assert_eq!(recovered, vec![media[lost].clone()], "lost packet {lost}");
+    }
+
+    // Nothing can be recovered with two packets lost.
+    let mut decoder = UlpfecDecoder::new();
+    decoder.push_media(&media[0])?;
+    assert!(decoder.push_fec(&fec_packet(1, fec))?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_ulpfec_recover_cascade() -> Result<()> {
+    let media = (0..4)
+        .map(|i| media_packet(100 + i, 9000 * i as u32, i % 2 == 1, &[i as u8; 4]))
+        .collect::<Vec<_>>();
+
+    // The first FEC packet protects packets 100 and 101, the second 100 to 103.
+    let mut encoder = UlpfecEncoder::new(2);
+    encoder.push_media(&media[0])?;
+    let fec0 = encoder
+        .push_media(&media[1])?
+        .expect("group should be complete");
+    let mut encoder = UlpfecEncoder::new(4);
+    for packet in &media[..3] {
+        assert_eq!(encoder.push_media(packet)?, None);
+    }
+    let fec1 = encoder
+        .push_media(&media[3])?
+        .expect("group should be complete");
+
+    // Packets 101 and 102 are lost, 101 is recovered with fec0 and then 102 with fec1.
+    let mut decoder = UlpfecDecoder::new();
+    decoder.push_media(&media[0])?;
+    decoder.push_media(&media[3])?;
+    assert!(decoder.push_fec(&fec_packet(1, fec1))?.is_empty());
+    let recovered = decoder.push_fec(&fec_packet(2, fec0))?;
+    assert_eq!(recovered, vec![media[1].clone(), media[2].clone()]);
+
+    Ok(())
+}
+
+#[test]
+fn test_ulpfec_recover_skips_corrupt_fec() -> Result<()> {
+    let media = (0..3)
+        .map(|i| media_packet(100 + i, 9000, i == 2, &[i as u8; 4]))
+        .collect::<Vec<_>>();
+
+    // The good FEC packet protects packets 100 and 101, the corrupt one 100 and 102.
+    let mut encoder = UlpfecEncoder::new(2);
+    encoder.push_media(&media[0])?;
+    let good = encoder
+        .push_media(&media[1])?
+        .expect("group should be complete");
+    encoder.push_media(&media[0])?;
+    let corrupt = encoder
+        .push_media(&media[2])?
+        .expect("group should be complete");
+    // The CSRC count recovered exceeds the packet.
+    let mut corrupt = BytesMut::from(&corrupt[..]);
+    corrupt[0] ^= 0x0F;
+
+    // Both become recoverable once packet 100 arrives, only the good one recovers.
+    let mut decoder = UlpfecDecoder::new();
+    assert!(decoder.push_fec(&fec_packet(1, good))?.is_empty());
+    assert!(decoder
+        .push_fec(&fec_packet(2, corrupt.freeze()))?
+        .is_empty());
+    let recovered = decoder.push_media(&media[0])?;
+    assert_eq!(recovered, vec![media[1].clone()]);
+
+    // The corrupt FEC packet is dropped.
+    assert!(decoder.fec.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_ulpfec_encoder_flush() -> Result<()> {
+    let mut encoder = UlpfecEncoder::new(10);
+    assert_eq!(encoder.flush()?, None);
+    encoder.push_media(&media_packet(1, 0, false, &[0x01]))?;
+
+    // Packet 49 can't be protected together with packet 1 any more.
+    let fec = encoder
+        .push_media(&media_packet(49, 0, false, &[0x02]))?
+        .expect("group should be flushed");
+    let fec = UlpfecPacket::unmarshal(&mut fec.clone())?;
+    assert_eq!(fec.protected(), vec![1]);
+
+    let fec = encoder.flush()?.expect("group shouldn't be empty");
+    let fec = UlpfecPacket::unmarshal(&mut fec.clone())?;
+    assert_eq!(fec.protected(), vec![49]);
+    assert_eq!(encoder.flush()?, None);
+
+    Ok(())
+}
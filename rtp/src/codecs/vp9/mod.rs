@@ -9,21 +9,79 @@ use bytes::{Buf, BufMut, Bytes, BytesMut};
 use crate::error::{Error, Result};
 use crate::packetizer::{Depacketizer, Payloader};
 
-/// Flexible mode 15 bit picture ID
-const VP9HEADER_SIZE: usize = 3;
 const MAX_SPATIAL_LAYERS: u8 = 5;
 const MAX_VP9REF_PICS: usize = 3;
+const MAX_TEMPORAL_LAYERS: u8 = 8;
 
 /// InitialPictureIDFn is a function that returns random initial picture ID.
 pub type InitialPictureIDFn = Arc<dyn (Fn() -> u16) + Send + Sync>;
 
+/// Vp9LayerInfo holds the layer indices of a VP9 frame, and how it references other frames.
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct Vp9LayerInfo {
+    /// Temporal layer ID
+    pub tid: u8,
+    /// Switching up point
+    pub u: bool,
+    /// Spatial layer ID
+    pub sid: u8,
+    /// Inter-layer dependency used
+    pub d: bool,
+    /// Inter-picture predicted frame
+    pub p: bool,
+    /// Not a reference frame for upper spatial layers
+    pub z: bool,
+    /// Reference indices of an inter-picture predicted frame, 1 to 3 of them (F=1)
+    pub pdiff: Vec<u8>,
+    /// Temporal layer zero index (F=0)
+    pub tl0picidx: u8,
+}
+
+/// Vp9ScalabilityStructure describes the spatial layers of a VP9 stream and the pictures
+/// of its Picture Group (PG).
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct Vp9ScalabilityStructure {
+    /// N_S + 1 indicates the number of spatial layers present in the VP9 stream
+    pub ns: u8,
+    /// Frame resolution of each spatial layer, N_S + 1 or none of them
+    pub width: Vec<u16>,
+    pub height: Vec<u16>,
+    /// Temporal layer ID of pictures in a Picture Group
+    pub pgtid: Vec<u8>,
+    /// Switching up point of pictures in a Picture Group
+    pub pgu: Vec<bool>,
+    /// Reference indices of pictures in a Picture Group, up to 3 for each
+    pub pgpdiff: Vec<Vec<u8>>,
+}
+
 /// Vp9Payloader payloads VP9 packets
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct Vp9Payloader {
     picture_id: u16,
     initialized: bool,
 
     pub initial_picture_id_fn: Option<InitialPictureIDFn>,
+    /// Flexible mode (F=1), in which frames signal their reference indices, or non-flexible
+    /// mode (F=0), in which they follow the picture group. Defaults to flexible mode.
+    pub flexible_mode: bool,
+    /// Layer indices of the next frames, not sent if None. Frames of an upper spatial
+    /// layer get the picture ID of the last base layer frame.
+    pub layer_info: Option<Vp9LayerInfo>,
+    /// Scalability structure sent with the first packet of the next frame only.
+    pub scalability_structure: Option<Vp9ScalabilityStructure>,
+}
+
+impl Default for Vp9Payloader {
+    fn default() -> Self {
+        Vp9Payloader {
+            picture_id: 0,
+            initialized: false,
+            initial_picture_id_fn: None,
+            flexible_mode: true,
+            layer_info: None,
+            scalability_structure: None,
+        }
+    }
 }
 
 impl fmt::Debug for Vp9Payloader {
@@ -31,10 +89,123 @@ impl fmt::Debug for Vp9Payloader {
         f.debug_struct("Vp9Payloader")
             .field("picture_id", &self.picture_id)
             .field("initialized", &self.initialized)
+            .field("flexible_mode", &self.flexible_mode)
+            .field("layer_info", &self.layer_info)
+            .field("scalability_structure", &self.scalability_structure)
             .finish()
     }
 }
 
+impl Vp9Payloader {
+    /// validate checks that the layer info and scalability structure can be sent.
+    fn validate(&self) -> Result<()> {
+        if let Some(layer_info) = &self.layer_info {
+            if layer_info.sid >= MAX_SPATIAL_LAYERS {
+                return Err(Error::ErrTooManySpatialLayers);
+            }
+            if layer_info.tid >= MAX_TEMPORAL_LAYERS {
+                return Err(Error::ErrInvalidVp9PayloadDescriptor);
+            }
+            if self.flexible_mode && layer_info.p {
+                if layer_info.pdiff.len() > MAX_VP9REF_PICS {
+                    return Err(Error::ErrTooManyPDiff);
+                }
+                if layer_info.pdiff.is_empty() || layer_info.pdiff.iter().any(|&d| d > 0x7F) {
+                    return Err(Error::ErrInvalidVp9PayloadDescriptor);
+                }
+            }
+        }
+
+        if let Some(ss) = &self.scalability_structure {
+            if ss.ns >= MAX_SPATIAL_LAYERS {
+                return Err(Error::ErrTooManySpatialLayers);
+            }
+            let resolutions = ss.width.len();
+            if resolutions != ss.height.len()
+                || (resolutions != 0 && resolutions != ss.ns as usize + 1)
+                || ss.pgtid.len() != ss.pgu.len()
+                || ss.pgtid.len() != ss.pgpdiff.len()
+                || ss.pgtid.len() > u8::MAX as usize
+                || ss.pgtid.iter().any(|&tid| tid >= MAX_TEMPORAL_LAYERS)
+                || ss.pgpdiff.iter().any(|pdiff| pdiff.len() > MAX_VP9REF_PICS)
+            {
+                return Err(Error::ErrInvalidVp9PayloadDescriptor);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// marshal_descriptor writes the payload descriptor of a packet of a frame.
+    fn marshal_descriptor(
+        &self,
+        out: &mut BytesMut,
+        picture_id: u16,
+        first: bool,
+        last: bool,
+        ss: Option<&Vp9ScalabilityStructure>,
+    ) {
+        let mut b = 0x80; // I=1
+        if self.flexible_mode {
+            b |= 0x10; // F=1
+        }
+        if first {
+            b |= 0x08; // B=1
+        }
+        if last {
+            b |= 0x04; // E=1
+        }
+        if ss.is_some() {
+            b |= 0x02; // V=1
+        }
+        if let Some(layer_info) = &self.layer_info {
+            b |= 0x20; // L=1
+            if layer_info.p {
+                b |= 0x40; // P=1
+            }
+            if layer_info.z {
+                b |= 0x01; // Z=1
+            }
+        }
+        out.put_u8(b);
+        out.put_u16(picture_id | 0x8000);
+
+        if let Some(layer_info) = &self.layer_info {
+            out.put_u8(
+                (layer_info.tid << 5)
+                    | ((layer_info.u as u8) << 4)
+                    | (layer_info.sid << 1)
+                    | layer_info.d as u8,
+            );
+            if !self.flexible_mode {
+                out.put_u8(layer_info.tl0picidx);
+            } else if layer_info.p {
+                for (i, pdiff) in layer_info.pdiff.iter().enumerate() {
+                    let n = (i + 1 < layer_info.pdiff.len()) as u8;
+                    out.put_u8((pdiff << 1) | n);
+                }
+            }
+        }
+
+        if let Some(ss) = ss {
+            let y = !ss.width.is_empty();
+            let g = !ss.pgtid.is_empty();
+            out.put_u8((ss.ns << 5) | ((y as u8) << 4) | ((g as u8) << 3));
+            for (width, height) in ss.width.iter().zip(&ss.height) {
+                out.put_u16(*width);
+                out.put_u16(*height);
+            }
+            if g {
+                out.put_u8(ss.pgtid.len() as u8);
+                for ((tid, u), pdiff) in ss.pgtid.iter().zip(&ss.pgu).zip(&ss.pgpdiff) {
+                    out.put_u8((tid << 5) | ((*u as u8) << 4) | ((pdiff.len() as u8) << 2));
+                    out.put_slice(pdiff);
+                }
+            }
+        }
+    }
+}
+
 impl Payloader for Vp9Payloader {
     /// Payload fragments an Vp9Payloader packet across one or more byte arrays
     fn payload(&mut self, mtu: usize, payload: &Bytes) -> Result<Vec<Bytes>> {
@@ -93,31 +264,34 @@ impl Payloader for Vp9Payloader {
             self.initialized = true;
         }
 
-        let max_fragment_size = mtu as isize - VP9HEADER_SIZE as isize;
+        self.validate()?;
+
+        // Frames of the upper spatial layers belong to the picture of the base layer.
+        let upper_spatial_layer = matches!(&self.layer_info, Some(l) if l.sid > 0);
+        let picture_id = if upper_spatial_layer {
+            self.picture_id.wrapping_sub(1) & 0x7FFF
+        } else {
+            self.picture_id
+        };
+
+        let mut ss = self.scalability_structure.as_ref();
         let mut payloads = vec![];
         let mut payload_data_remaining = payload.len();
         let mut payload_data_index = 0;
 
-        if std::cmp::min(max_fragment_size, payload_data_remaining as isize) <= 0 {
-            return Ok(vec![]);
-        }
-
         while payload_data_remaining > 0 {
+            let mut out = BytesMut::with_capacity(mtu);
+            self.marshal_descriptor(&mut out, picture_id, payload_data_index == 0, false, ss);
+            let max_fragment_size = mtu as isize - out.len() as isize;
+            if max_fragment_size <= 0 {
+                return Ok(vec![]);
+            }
+
             let current_fragment_size =
                 std::cmp::min(max_fragment_size as usize, payload_data_remaining);
-            let mut out = BytesMut::with_capacity(VP9HEADER_SIZE + current_fragment_size);
-            let mut buf = [0u8; VP9HEADER_SIZE];
-            buf[0] = 0x90; // F=1 I=1
-            if payload_data_index == 0 {
-                buf[0] |= 0x08; // B=1
-            }
             if payload_data_remaining == current_fragment_size {
-                buf[0] |= 0x04; // E=1
+                out[0] |= 0x04; // E=1
             }
-            buf[1] = (self.picture_id >> 8) as u8 | 0x80;
-            buf[2] = (self.picture_id & 0xFF) as u8;
-
-            out.put(&buf[..]);
 
             out.put(
                 &*payload.slice(payload_data_index..payload_data_index + current_fragment_size),
@@ -127,10 +301,14 @@ impl Payloader for Vp9Payloader {
 
             payload_data_remaining -= current_fragment_size;
             payload_data_index += current_fragment_size;
+            ss = None;
         }
 
-        self.picture_id += 1;
-        self.picture_id &= 0x7FFF;
+        self.scalability_structure = None;
+        if !upper_spatial_layer {
+            self.picture_id += 1;
+            self.picture_id &= 0x7FFF;
+        }
 
         Ok(payloads)
     }
@@ -206,6 +384,9 @@ impl Depacketizer for Vp9Packet {
             return Err(Error::ErrShortPacket);
         }
 
+        // Nothing of the previous packet is kept.
+        *self = Vp9Packet::default();
+
         let reader = &mut packet.clone();
         let b = reader.get_u8();
 
@@ -254,6 +435,40 @@ impl Depacketizer for Vp9Packet {
 }
 
 impl Vp9Packet {
+    /// layer_info returns the layer indices of the frame, if present (L=1).
+    pub fn layer_info(&self) -> Option<Vp9LayerInfo> {
+        if !self.l {
+            return None;
+        }
+
+        Some(Vp9LayerInfo {
+            tid: self.tid,
+            u: self.u,
+            sid: self.sid,
+            d: self.d,
+            p: self.p,
+            z: self.z,
+            pdiff: self.pdiff.clone(),
+            tl0picidx: self.tl0picidx,
+        })
+    }
+
+    /// scalability_structure returns the scalability structure, if present (V=1).
+    pub fn scalability_structure(&self) -> Option<Vp9ScalabilityStructure> {
+        if !self.v {
+            return None;
+        }
+
+        Some(Vp9ScalabilityStructure {
+            ns: self.ns,
+            width: self.width.clone(),
+            height: self.height.clone(),
+            pgtid: self.pgtid.clone(),
+            pgu: self.pgu.clone(),
+            pgpdiff: self.pgpdiff.clone(),
+        })
+    }
+
     // Picture ID:
     //
     //      +-+-+-+-+-+-+-+-+
@@ -372,7 +587,7 @@ impl Vp9Packet {
             payload_index += 1;
 
             self.pdiff.push(b >> 1);
-            if self.pdiff.len() >= MAX_VP9REF_PICS {
+            if self.pdiff.len() > MAX_VP9REF_PICS {
                 return Err(Error::ErrTooManyPDiff);
             }
         }
@@ -410,7 +625,7 @@ impl Vp9Packet {
 
         self.ns = b >> 5;
         self.y = b & 0x10 != 0;
-        self.g = (b >> 1) & 0x7 != 0;
+        self.g = b & 0x08 != 0;
 
         let ns = (self.ns + 1) as usize;
         self.ng = 0;
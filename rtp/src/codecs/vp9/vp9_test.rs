@@ -113,6 +113,20 @@ fn test_vp9_packet_unmarshal() -> Result<()> {
             Bytes::from_static(&[0xAA]),
             None,
         ),
+        (
+            "FlexiblePictureIDRefIndex_ThreePDiff",
+            Bytes::from_static(&[0xD0, 0x02, 0x03, 0x05, 0x06, 0xAA]),
+            Vp9Packet {
+                i: true,
+                p: true,
+                f: true,
+                picture_id: 0x02,
+                pdiff: vec![0x01, 0x02, 0x03],
+                ..Default::default()
+            },
+            Bytes::from_static(&[0xAA]),
+            None,
+        ),
         (
             "FlexiblePictureIDRefIndex_TooManyPDiff",
             Bytes::from_static(&[0xD0, 0x02, 0x03, 0x05, 0x07, 0x09, 0x10, 0xAA]),
@@ -340,6 +354,92 @@ fn test_vp9_payloader_payload() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_vp9_payloader_layers() -> Result<()> {
+    let ss = Vp9ScalabilityStructure {
+        ns: 1,
+        width: vec![640, 1280],
+        height: vec![360, 720],
+        pgtid: vec![0, 1],
+        pgu: vec![false, true],
+        pgpdiff: vec![vec![2], vec![1]],
+    };
+    let mut pck = Vp9Payloader {
+        initial_picture_id_fn: Some(Arc::new(|| -> u16 { 0x1234 })),
+        flexible_mode: false,
+        layer_info: Some(Vp9LayerInfo {
+            tl0picidx: 7,
+            ..Default::default()
+        }),
+        scalability_structure: Some(ss.clone()),
+        ..Default::default()
+    };
+
+    // The scalability structure is sent with the first packet of the base layer frame, its
+    // descriptor of 19 bytes leaves room for 11 bytes of the frame.
+    let base = pck.payload(30, &Bytes::from_static(&[0xAA; 20]))?;
+    assert_eq!(base.len(), 2);
+    let mut p = Vp9Packet::default();
+    assert_eq!(p.depacketize(&base[0])?, Bytes::from_static(&[0xAA; 11]));
+    assert!(p.b && !p.e && !p.f);
+    assert_eq!(p.picture_id, 0x1234);
+    assert_eq!(p.scalability_structure(), Some(ss));
+    assert_eq!(
+        p.layer_info(),
+        Some(Vp9LayerInfo {
+            tl0picidx: 7,
+            ..Default::default()
+        })
+    );
+    assert_eq!(p.depacketize(&base[1])?, Bytes::from_static(&[0xAA; 9]));
+    assert!(!p.b && p.e);
+    assert_eq!(p.scalability_structure(), None);
+
+    // The upper spatial layer frame belongs to the same picture.
+    let layer_info = Vp9LayerInfo {
+        sid: 1,
+        d: true,
+        tl0picidx: 7,
+        ..Default::default()
+    };
+    pck.layer_info = Some(layer_info.clone());
+    let upper = pck.payload(30, &Bytes::from_static(&[0xBB; 4]))?;
+    p.depacketize(&upper[0])?;
+    assert_eq!(p.picture_id, 0x1234);
+    assert_eq!(p.layer_info(), Some(layer_info));
+
+    // Flexible mode sends the reference indices instead of tl0picidx.
+    let layer_info = Vp9LayerInfo {
+        tid: 1,
+        u: true,
+        p: true,
+        pdiff: vec![1, 2],
+        ..Default::default()
+    };
+    pck.flexible_mode = true;
+    pck.layer_info = Some(layer_info.clone());
+    let next = pck.payload(30, &Bytes::from_static(&[0xCC; 4]))?;
+    assert_eq!(
+        &next[0][..],
+        &[0xFC, 0x92, 0x35, 0x30, 0x03, 0x04, 0xCC, 0xCC, 0xCC, 0xCC]
+    );
+    assert_eq!(p.depacketize(&next[0])?, Bytes::from_static(&[0xCC; 4]));
+    assert_eq!(p.picture_id, 0x1235);
+    assert_eq!(p.layer_info(), Some(layer_info));
+
+    pck.layer_info = Some(Vp9LayerInfo {
+        p: true,
+        ..Default::default()
+    });
+    assert_eq!(
+        pck.payload(30, &Bytes::from_static(&[0xCC])),
+        Err(Error::ErrInvalidVp9PayloadDescriptor),
+        "an inter-picture predicted frame needs reference indices in flexible mode"
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_vp9_partition_head_checker_is_partition_head() -> Result<()> {
     let vp9 = Vp9Packet::default();
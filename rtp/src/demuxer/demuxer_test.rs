@@ -0,0 +1,152 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use util::marshal::Marshal;
+
+use super::*;
+use crate::error::Error;
+use crate::header::Header;
+use crate::packet::Packet;
+
+fn rtp_packet(ssrc: u32, payload_type: u8, mid: Option<&'static str>) -> Bytes {
+    let mut packet = Packet {
+        header: Header {
+            version: 2,
+            payload_type,
+            ssrc,
+            ..Default::default()
+        },
+        payload: Bytes::from_static(&[0x01]),
+        ..Default::default()
+    };
+    if let Some(mid) = mid {
+        packet
+            .header
+            .set_extension(1, Bytes::from_static(mid.as_bytes()))
+            .unwrap();
+    }
+    packet.marshal().unwrap()
+}
+
+#[test]
+fn test_classify() {
+    let tests = vec![
+        ("Rtp", vec![0x80, 0x60, 0x00, 0x01], Some(PacketKind::Rtp)),
+        (
+            "RtpMarker",
+            vec![0x80, 0xE0, 0x00, 0x01],
+            Some(PacketKind::Rtp),
+        ),
+        (
+            "SenderReport",
+            vec![0x80, 200, 0x00, 0x06],
+            Some(PacketKind::Rtcp),
+        ),
+        ("Nack", vec![0x81, 205, 0x00, 0x03], Some(PacketKind::Rtcp)),
+        ("Short", vec![0x80, 0x60], None),
+        ("Version1", vec![0x40, 0x60, 0x00, 0x01], None),
+    ];
+
+    for (name, buf, expected) in tests {
+        assert_eq!(classify(&buf), expected, "{name}");
+    }
+}
+
+#[test]
+fn test_demuxer() -> Result<()> {
+    let mut demuxer = Demuxer::new().with_mid_extension(1);
+    demuxer.add_mid("audio", 0);
+    demuxer.add_mid("video", 1);
+    demuxer.add_ssrc(5000, 1);
+    demuxer.add_payload_types(111..=111, 0);
+    demuxer.add_payload_types(96..=99, 1);
+    demuxer.add_payload_types(99..=100, 2);
+
+    let tests = vec![
+        ("Mid", rtp_packet(1000, 96, Some("audio")), Demuxed::Rtp(0)),
+        // The SSRC got bound to the audio MID.
+        ("BoundSsrc", rtp_packet(1000, 96, None), Demuxed::Rtp(0)),
+        // The MID takes precedence over the SSRC.
+        (
+            "MidChange",
+            rtp_packet(1000, 96, Some("video")),
+            Demuxed::Rtp(1),
+        ),
+        (
+            "BoundSsrcChange",
+            rtp_packet(1000, 111, None),
+            Demuxed::Rtp(1),
+        ),
+        ("KnownSsrc", rtp_packet(5000, 111, None), Demuxed::Rtp(1)),
+        (
+            "UnknownMid",
+            rtp_packet(2000, 111, Some("data")),
+            Demuxed::Rtp(0),
+        ),
+        ("PayloadType", rtp_packet(3000, 97, None), Demuxed::Rtp(1)),
+        (
+            "AmbiguousPayloadType",
+            rtp_packet(4000, 99, None),
+            Demuxed::Unhandled,
+        ),
+        (
+            "UnknownPayloadType",
+            rtp_packet(4000, 0, None),
+            Demuxed::Unhandled,
+        ),
+        (
+            "Rtcp",
+            Bytes::from_static(&[0x80, 200, 0x00, 0x06]),
+            Demuxed::Rtcp,
+        ),
+        (
+            "Stun",
+            Bytes::from_static(&[0x00, 0x01, 0x00, 0x00]),
+            Demuxed::Unknown,
+        ),
+    ];
+
+    for (name, buf, expected) in tests {
+        assert_eq!(demuxer.demux(&buf)?, expected, "{name}");
+    }
+    assert_eq!(demuxer.ssrc_route(2000), Some(&0));
+    assert_eq!(demuxer.ssrc_route(3000), Some(&1));
+    assert_eq!(demuxer.ssrc_route(4000), None);
+
+    assert_eq!(demuxer.remove_ssrc(5000), Some(1));
+    assert_eq!(
+        demuxer.demux(&rtp_packet(5000, 111, None))?,
+        Demuxed::Rtp(0)
+    );
+
+    let result = demuxer.demux(&[0x80, 0x60, 0x00, 0x01]);
+    assert_eq!(
+        Error::ErrHeaderSizeInsufficient,
+        result.expect_err("packet should be too short")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_demuxer_unhandled() -> Result<()> {
+    let mut demuxer = Demuxer::new();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let calls2 = Arc::clone(&calls);
+    demuxer.on_unhandled(Box::new(move |packet| {
+        calls2.fetch_add(1, Ordering::SeqCst);
+        (packet.ssrc() != 2).then_some(packet.ssrc() * 10)
+    }));
+
+    assert_eq!(demuxer.demux(&rtp_packet(1, 96, None))?, Demuxed::Rtp(10));
+    assert_eq!(demuxer.demux(&rtp_packet(1, 96, None))?, Demuxed::Rtp(10));
+    assert_eq!(demuxer.demux(&rtp_packet(2, 96, None))?, Demuxed::Unhandled);
+    assert_eq!(
+        calls.load(Ordering::SeqCst),
+        2,
+        "bound SSRCs shouldn't be handled"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,192 @@
+#[cfg(test)]
+mod demuxer_test;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use crate::error::Result;
+use crate::header::{VERSION_MASK, VERSION_SHIFT};
+use crate::packet_ref::RtpPacketRef;
+
+/// Packet types of RTCP, which don't collide with RTP payload types when both are sent on the
+/// same port (RFC 5761 section 4).
+pub const RTCP_PACKET_TYPES: RangeInclusive<u8> = 192..=223;
+
+/// PacketKind is the kind of a packet received on a port shared by RTP and RTCP.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PacketKind {
+    Rtp,
+    Rtcp,
+}
+
+/// classify returns whether buf is an RTP or an RTCP packet, or None if it is neither.
+pub fn classify(buf: &[u8]) -> Option<PacketKind> {
+    if buf.len() < 4 || buf[0] >> VERSION_SHIFT & VERSION_MASK != 2 {
+        return None;
+    }
+    if RTCP_PACKET_TYPES.contains(&buf[1]) {
+        Some(PacketKind::Rtcp)
+    } else {
+        Some(PacketKind::Rtp)
+    }
+}
+
+/// Demuxed tells what [`Demuxer::demux`] found a packet to be.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Demuxed<T> {
+    /// An RTP packet of the route.
+    Rtp(T),
+    /// An RTCP packet, which is left to the caller as it may concern several routes.
+    Rtcp,
+    /// An RTP packet no route was found for.
+    Unhandled,
+    /// Neither an RTP nor an RTCP packet.
+    Unknown,
+}
+
+/// UnhandledHandler is called for RTP packets no route was found for, the route it returns
+/// is used for all later packets of the SSRC.
+pub type UnhandledHandler<T> = Box<dyn (FnMut(&RtpPacketRef<'_>) -> Option<T>) + Send + Sync>;
+
+/// Demuxer routes the RTP packets of streams sharing a transport, as with BUNDLE, to the
+/// routes they belong to, e.g. the media sections of a session. A packet is routed by
+///
+/// 1. the MID header extension, binding its SSRC to the route of the MID,
+/// 2. the route its SSRC was bound to,
+/// 3. the payload type, if only a single route uses it,
+/// 4. the [`UnhandledHandler`].
+///
+/// The routes found by payload type and handler are bound to the SSRC too.
+///
+/// ## Specifications
+///
+/// * [RFC 8843 section 9.2]
+///
+/// [RFC 8843 section 9.2]: https://tools.ietf.org/html/rfc8843#section-9.2
+pub struct Demuxer<T> {
+    mid_extension_id: Option<u8>,
+    mids: HashMap<String, T>,
+    ssrcs: HashMap<u32, T>,
+    payload_types: Vec<(RangeInclusive<u8>, T)>,
+    on_unhandled: Option<UnhandledHandler<T>>,
+}
+
+impl<T> Default for Demuxer<T> {
+    fn default() -> Self {
+        Demuxer {
+            mid_extension_id: None,
+            mids: HashMap::new(),
+            ssrcs: HashMap::new(),
+            payload_types: vec![],
+            on_unhandled: None,
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Demuxer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Demuxer")
+            .field("mid_extension_id", &self.mid_extension_id)
+            .field("mids", &self.mids)
+            .field("ssrcs", &self.ssrcs)
+            .field("payload_types", &self.payload_types)
+            .finish()
+    }
+}
+
+impl<T: Clone + PartialEq> Demuxer<T> {
+    pub fn new() -> Self {
+        Demuxer::default()
+    }
+
+    /// with_mid_extension routes packets by the MID header extension with id.
+    pub fn with_mid_extension(mut self, id: u8) -> Self {
+        self.mid_extension_id = Some(id);
+        self
+    }
+
+    /// add_mid routes packets with the MID to route.
+    pub fn add_mid(&mut self, mid: impl Into<String>, route: T) {
+        self.mids.insert(mid.into(), route);
+    }
+
+    /// add_ssrc routes packets of the SSRC to route, e.g. one signaled with a=ssrc.
+    pub fn add_ssrc(&mut self, ssrc: u32, route: T) {
+        self.ssrcs.insert(ssrc, route);
+    }
+
+    /// remove_ssrc forgets the route of the SSRC, returning it.
+    pub fn remove_ssrc(&mut self, ssrc: u32) -> Option<T> {
+        self.ssrcs.remove(&ssrc)
+    }
+
+    /// ssrc_route returns the route the SSRC is bound to.
+    pub fn ssrc_route(&self, ssrc: u32) -> Option<&T> {
+        self.ssrcs.get(&ssrc)
+    }
+
+    /// add_payload_types routes packets of unknown SSRCs with the payload types to route,
+    /// unless another route uses them too.
+    pub fn add_payload_types(&mut self, payload_types: RangeInclusive<u8>, route: T) {
+        self.payload_types.push((payload_types, route));
+    }
+
+    /// on_unhandled sets a handler called for RTP packets no route was found for.
+    pub fn on_unhandled(&mut self, handler: UnhandledHandler<T>) {
+        self.on_unhandled = Some(handler);
+    }
+
+    /// demux returns the route of the packet in buf, returning an error if it is an invalid
+    /// RTP packet.
+    pub fn demux(&mut self, buf: &[u8]) -> Result<Demuxed<T>> {
+        match classify(buf) {
+            Some(PacketKind::Rtp) => {}
+            Some(PacketKind::Rtcp) => return Ok(Demuxed::Rtcp),
+            None => return Ok(Demuxed::Unknown),
+        };
+
+        let packet = RtpPacketRef::parse(buf)?;
+        Ok(match self.route(&packet) {
+            Some(route) => Demuxed::Rtp(route),
+            None => Demuxed::Unhandled,
+        })
+    }
+
+    /// route returns the route of an RTP packet.
+    pub fn route(&mut self, packet: &RtpPacketRef<'_>) -> Option<T> {
+        let ssrc = packet.ssrc();
+
+        let mid_route = self
+            .mid_extension_id
+            .and_then(|id| packet.get_extension(id))
+            .and_then(|mid| std::str::from_utf8(mid).ok())
+            .and_then(|mid| self.mids.get(mid));
+        if let Some(route) = mid_route {
+            self.ssrcs.insert(ssrc, route.clone());
+            return Some(route.clone());
+        }
+
+        if let Some(route) = self.ssrcs.get(&ssrc) {
+            return Some(route.clone());
+        }
+
+        let payload_type = packet.payload_type();
+        let mut matching = self
+            .payload_types
+            .iter()
+            .filter(|(payload_types, _)| payload_types.contains(&payload_type))
+            .map(|(_, route)| route);
+        let unique = match matching.next() {
+            Some(route) if matching.all(|other| other == route) => Some(route.clone()),
+            _ => None,
+        };
+
+        let route = match unique {
+            Some(route) => route,
+            None => self.on_unhandled.as_mut()?(packet)?,
+        };
+        self.ssrcs.insert(ssrc, route.clone());
+        Some(route)
+    }
+}
@@ -37,6 +37,24 @@ pub enum Error {
     ErrTooManyPDiff,
     #[error("too many spatial layers")]
     ErrTooManySpatialLayers,
+    #[error("invalid VP9 payload descriptor")]
+    ErrInvalidVp9PayloadDescriptor,
+    #[error("invalid RED block")]
+    ErrInvalidRedBlock,
+    #[error("invalid ULPFEC packet")]
+    ErrInvalidUlpfecPacket,
+    #[error("invalid dependency descriptor")]
+    ErrInvalidDependencyDescriptor,
+    #[error("dependency descriptor without frame dependency structure")]
+    ErrDependencyDescriptorNoStructure,
+    #[error("invalid JPEG frame")]
+    ErrInvalidJpeg,
+    #[error("JPEG frame can't be sent with RFC 2435")]
+    ErrUnsupportedJpeg,
+    #[error("invalid telephone event")]
+    ErrInvalidTelephoneEvent,
+    #[error("linear PCM payload isn't made of whole sample frames")]
+    ErrInvalidLinearPcm,
     #[error("NALU Type is unhandled")]
     ErrUnhandledNaluType,
 
@@ -0,0 +1,63 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use bytes::Bytes;
+
+use super::*;
+use crate::error::Result;
+
+#[test]
+fn test_abs_capture_time_extension_round_trip() -> Result<()> {
+    let tests = vec![
+        (
+            "WithoutOffset",
+            AbsCaptureTimeExtension {
+                timestamp: 0x0102_0304_0506_0708,
+                estimated_capture_clock_offset: None,
+            },
+            vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08],
+        ),
+        (
+            "WithOffset",
+            AbsCaptureTimeExtension {
+                timestamp: 0x0102_0304_0506_0708,
+                estimated_capture_clock_offset: Some(-(1 << 31)),
+            },
+            vec![
+                0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0xFF, 0xFF, 0xFF, 0xFF, 0x80, 0x00,
+                0x00, 0x00,
+            ],
+        ),
+    ];
+
+    for (name, ext, raw) in tests {
+        assert_eq!(ext.marshal_size(), raw.len(), "{name}");
+        assert_eq!(ext.marshal()?, Bytes::from(raw.clone()), "{name}");
+        let out = AbsCaptureTimeExtension::unmarshal(&mut Bytes::from(raw))?;
+        assert_eq!(out, ext, "{name}");
+    }
+
+    let result = AbsCaptureTimeExtension::unmarshal(&mut Bytes::from_static(&[0x01; 7]));
+    assert!(result.is_err(), "extension should be too short");
+
+    Ok(())
+}
+
+#[test]
+fn test_abs_capture_time_extension_sender_capture_time() {
+    let capture_time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let mut ext = AbsCaptureTimeExtension::new(capture_time);
+    assert_eq!(ext.capture_time(), capture_time);
+    assert_eq!(ext.sender_capture_time(), capture_time);
+
+    // Half a second behind the clock of the sender.
+    ext.estimated_capture_clock_offset = Some(1 << 31);
+    assert_eq!(
+        ext.sender_capture_time(),
+        capture_time + Duration::from_millis(500)
+    );
+    ext.estimated_capture_clock_offset = Some(-(3 << 31));
+    assert_eq!(
+        ext.sender_capture_time(),
+        capture_time - Duration::from_millis(1500)
+    );
+}
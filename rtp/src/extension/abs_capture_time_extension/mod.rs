@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod abs_capture_time_extension_test;
+
+use std::time::{Duration, SystemTime};
+
+use bytes::{Buf, BufMut};
+use util::marshal::{Marshal, MarshalSize, Unmarshal};
+
+use crate::error::Error;
+use crate::extension::abs_send_time_extension::{ntp2unix, unix2ntp};
+
+pub const ABS_CAPTURE_TIME_EXTENSION_SIZE: usize = 8;
+pub const ABS_CAPTURE_TIME_EXTENSION_SIZE_WITH_OFFSET: usize = 16;
+
+/// AbsCaptureTimeExtension is a extension payload format in
+/// http://www.webrtc.org/experiments/rtp-hdrext/abs-capture-time
+///
+/// ```text
+///  0                   1                   2                   3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |  ID   | len=15|     absolute capture timestamp (bit 0-23)     |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |             absolute capture timestamp (bit 24-55)            |
+/// |  ... (56-63)  |   estimated capture clock offset (bit 0-23)   |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |           estimated capture clock offset (bit 24-55)          |
+/// |  ... (56-63)  |
+/// +-+-+-+-+-+-+-+-+
+/// ```
+#[derive(PartialEq, Eq, Debug, Default, Copy, Clone)]
+pub struct AbsCaptureTimeExtension {
+    /// NTP time (UQ32.32) the first packet of the frame was captured at, on the clock of
+    /// the capturing system.
+    pub timestamp: u64,
+    /// Estimated offset (Q32.32) of the clock of the capturing system to the one of the
+    /// sender, sent by senders which aren't the capturing system themselves.
+    pub estimated_capture_clock_offset: Option<i64>,
+}
+
+impl Unmarshal for AbsCaptureTimeExtension {
+    /// Unmarshal parses the passed byte slice and stores the result in the members.
+    fn unmarshal<B>(raw_packet: &mut B) -> Result<Self, util::Error>
+    where
+        Self: Sized,
+        B: Buf,
+    {
+        if raw_packet.remaining() < ABS_CAPTURE_TIME_EXTENSION_SIZE {
+            return Err(Error::ErrBufferTooSmall.into());
+        }
+
+        let timestamp = raw_packet.get_u64();
+        let estimated_capture_clock_offset =
+            if raw_packet.remaining() >= ABS_CAPTURE_TIME_EXTENSION_SIZE {
+                Some(raw_packet.get_i64())
+            } else {
+                None
+            };
+
+        Ok(AbsCaptureTimeExtension {
+            timestamp,
+            estimated_capture_clock_offset,
+        })
+    }
+}
+
+impl MarshalSize for AbsCaptureTimeExtension {
+    /// MarshalSize returns the size of the AbsCaptureTimeExtension once marshaled.
+    fn marshal_size(&self) -> usize {
+        if self.estimated_capture_clock_offset.is_some() {
+            ABS_CAPTURE_TIME_EXTENSION_SIZE_WITH_OFFSET
+        } else {
+            ABS_CAPTURE_TIME_EXTENSION_SIZE
+        }
+    }
+}
+
+impl Marshal for AbsCaptureTimeExtension {
+    /// MarshalTo serializes the members to buffer.
+    fn marshal_to(&self, mut buf: &mut [u8]) -> Result<usize, util::Error> {
+        let size = self.marshal_size();
+        if buf.remaining_mut() < size {
+            return Err(Error::ErrBufferTooSmall.into());
+        }
+
+        buf.put_u64(self.timestamp);
+        if let Some(offset) = self.estimated_capture_clock_offset {
+            buf.put_i64(offset);
+        }
+
+        Ok(size)
+    }
+}
+
+impl AbsCaptureTimeExtension {
+    /// new makes a new AbsCaptureTimeExtension from the capture time, without an offset.
+    pub fn new(capture_time: SystemTime) -> Self {
+        AbsCaptureTimeExtension {
+            timestamp: unix2ntp(capture_time),
+            estimated_capture_clock_offset: None,
+        }
+    }
+
+    /// capture_time returns the capture time on the clock of the capturing system.
+    pub fn capture_time(&self) -> SystemTime {
+        ntp2unix(self.timestamp)
+    }
+
+    /// sender_capture_time returns the capture time on the clock of the sender, which is the
+    /// capture time itself if there's no estimated clock offset. The receiver can map it to
+    /// its own clock with the offset it estimates for the sender, e.g. from the RTT.
+    pub fn sender_capture_time(&self) -> SystemTime {
+        let capture_time = self.capture_time();
+        let Some(offset) = self.estimated_capture_clock_offset else {
+            return capture_time;
+        };
+
+        let abs = offset.unsigned_abs();
+        let offset = Duration::new(
+            abs >> 32,
+            (((abs & 0xFFFFFFFF) * 1_000_000_000) >> 32) as u32,
+        );
+        if self.estimated_capture_clock_offset < Some(0) {
+            capture_time.checked_sub(offset).unwrap_or(capture_time)
+        } else {
+            capture_time.checked_add(offset).unwrap_or(capture_time)
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use bytes::Bytes;
+
+use super::*;
+use crate::error::Result;
+use crate::header::Header;
+
+/// One spatial and two temporal layers, a decode target for each temporal layer.
+fn l1t2_structure() -> FrameDependencyStructure {
+    use DecodeTargetIndication::*;
+
+    FrameDependencyStructure {
+        structure_id: 62,
+        num_decode_targets: 2,
+        num_chains: 1,
+        decode_target_protected_by_chain: vec![0, 0],
+        resolutions: vec![RenderResolution {
+            width: 640,
+            height: 360,
+        }],
+        templates: vec![
+            FrameDependencyTemplate {
+                spatial_id: 0,
+                temporal_id: 0,
+                decode_target_indications: vec![Switch, Switch],
+                frame_diffs: vec![],
+                chain_diffs: vec![0],
+            },
+            FrameDependencyTemplate {
+                spatial_id: 0,
+                temporal_id: 0,
+                decode_target_indications: vec![Switch, Switch],
+                frame_diffs: vec![2],
+                chain_diffs: vec![2],
+            },
+            FrameDependencyTemplate {
+                spatial_id: 0,
+                temporal_id: 1,
+                decode_target_indications: vec![NotPresent, Discardable],
+                frame_diffs: vec![1],
+                chain_diffs: vec![1],
+            },
+        ],
+    }
+}
+
+fn descriptor(structure: &FrameDependencyStructure, template: usize) -> DependencyDescriptor {
+    DependencyDescriptor {
+        first_packet_in_frame: true,
+        last_packet_in_frame: true,
+        frame_number: 0x1234,
+        frame_dependencies: structure.templates[template].clone(),
+        resolution: structure.resolutions.first().copied(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_dependency_descriptor_attached_structure() -> Result<()> {
+    let structure = l1t2_structure();
+    let test = DependencyDescriptor {
+        active_decode_targets_bitmask: Some(0b11),
+        attached_structure: Some(structure.clone()),
+        ..descriptor(&structure, 0)
+    };
+
+    let raw = test.marshal_with_structure(None)?;
+    let out = DependencyDescriptor::unmarshal_with_structure(&mut raw.clone(), None)?;
+    assert_eq!(test, out);
+
+    // The structure attached replaces the one passed.
+    let other = FrameDependencyStructure {
+        structure_id: 0,
+        ..structure
+    };
+    let out = DependencyDescriptor::unmarshal_with_structure(&mut raw.clone(), Some(&other))?;
+    assert_eq!(test, out);
+
+    Ok(())
+}
+
+#[test]
+fn test_dependency_descriptor_template() -> Result<()> {
+    let structure = l1t2_structure();
+    let test = descriptor(&structure, 2);
+
+    // The template id is offset by the structure id.
+    let raw = test.marshal_with_structure(Some(&structure))?;
+    assert_eq!(raw, Bytes::from_static(&[0xC0, 0x12, 0x34]));
+    let out = DependencyDescriptor::unmarshal_with_structure(&mut raw.clone(), Some(&structure))?;
+    assert_eq!(test, out);
+
+    let result = DependencyDescriptor::unmarshal_with_structure(&mut raw.clone(), None);
+    assert_eq!(
+        Error::ErrDependencyDescriptorNoStructure,
+        result.expect_err("descriptor should need a structure")
+    );
+
+    let result = DependencyDescriptor::unmarshal_with_structure(&mut &raw[..2], None);
+    assert_eq!(
+        Error::ErrBufferTooSmall,
+        result.expect_err("descriptor should be too short")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_dependency_descriptor_custom_fields() -> Result<()> {
+    use DecodeTargetIndication::*;
+
+    let structure = l1t2_structure();
+    let tests = vec![
+        (
+            "CustomDtis",
+            FrameDependencyTemplate {
+                decode_target_indications: vec![Required, Discardable],
+                ..structure.templates[1].clone()
+            },
+            None,
+        ),
+        (
+            "CustomFrameDiffs",
+            FrameDependencyTemplate {
+                frame_diffs: vec![1, 17, 300, 4096],
+                ..structure.templates[2].clone()
+            },
+            None,
+        ),
+        (
+            "CustomChainDiffs",
+            FrameDependencyTemplate {
+                chain_diffs: vec![200],
+                ..structure.templates[1].clone()
+            },
+            None,
+        ),
+        (
+            "ActiveDecodeTargets",
+            structure.templates[0].clone(),
+            Some(0b01),
+        ),
+    ];
+
+    for (name, frame_dependencies, active_decode_targets_bitmask) in tests {
+        let test = DependencyDescriptor {
+            frame_dependencies,
+            active_decode_targets_bitmask,
+            ..descriptor(&structure, 0)
+        };
+        let raw = test.marshal_with_structure(Some(&structure))?;
+        assert!(raw.len() > DEPENDENCY_DESCRIPTOR_MIN_SIZE, "{name}");
+        let out =
+            DependencyDescriptor::unmarshal_with_structure(&mut raw.clone(), Some(&structure))?;
+        assert_eq!(test, out, "{name}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_dependency_descriptor_invalid() {
+    let structure = l1t2_structure();
+
+    // No template has the layer of the frame.
+    let test = DependencyDescriptor {
+        frame_dependencies: FrameDependencyTemplate {
+            temporal_id: 2,
+            ..structure.templates[2].clone()
+        },
+        ..descriptor(&structure, 2)
+    };
+    let result = test.marshal_with_structure(Some(&structure));
+    assert_eq!(
+        Error::ErrInvalidDependencyDescriptor,
+        result.expect_err("frame layer should be unknown")
+    );
+
+    // Temporal layers have to follow each other.
+    let mut invalid = structure.clone();
+    invalid.templates[2].temporal_id = 2;
+    let test = DependencyDescriptor {
+        attached_structure: Some(invalid),
+        ..descriptor(&structure, 0)
+    };
+    let result = test.marshal_with_structure(None);
+    assert_eq!(
+        Error::ErrInvalidDependencyDescriptor,
+        result.expect_err("structure should be invalid")
+    );
+}
+
+#[test]
+fn test_dependency_descriptor_decode_target_layers() {
+    assert_eq!(
+        l1t2_structure().decode_target_layers(),
+        vec![
+            DecodeTargetLayer {
+                spatial_id: 0,
+                temporal_id: 0
+            },
+            DecodeTargetLayer {
+                spatial_id: 0,
+                temporal_id: 1
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_dependency_descriptor_set_on_frame() -> Result<()> {
+    let structure = l1t2_structure();
+    let test = DependencyDescriptor {
+        attached_structure: Some(structure.clone()),
+        ..descriptor(&structure, 0)
+    };
+
+    let mut packets = vec![Packet::default(); 3];
+    for packet in &mut packets {
+        packet.header = Header {
+            version: 2,
+            extension: true,
+            extension_profile: 0xBEDE,
+            ..Default::default()
+        };
+    }
+    test.set_on_frame(&mut packets, 5, None)?;
+
+    for (i, packet) in packets.iter().enumerate() {
+        let mut raw = packet
+            .header
+            .get_extension(5)
+            .expect("extension should be set");
+        let out = DependencyDescriptor::unmarshal_with_structure(&mut raw, Some(&structure))?;
+        assert_eq!(out.first_packet_in_frame, i == 0, "packet {i}");
+        assert_eq!(out.last_packet_in_frame, i == 2, "packet {i}");
+        assert_eq!(out.attached_structure.is_some(), i == 0, "packet {i}");
+        assert_eq!(out.frame_dependencies, structure.templates[0], "packet {i}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_dependency_descriptor_extension_marshal() -> Result<()> {
+    let structure = l1t2_structure();
+    let ext = DependencyDescriptorExtension {
+        descriptor: descriptor(&structure, 1),
+        structure: Some(structure),
+    };
+    assert_eq!(ext.marshal_size(), DEPENDENCY_DESCRIPTOR_MIN_SIZE);
+    assert_eq!(ext.marshal()?, Bytes::from_static(&[0xFF, 0x12, 0x34]));
+
+    Ok(())
+}
@@ -0,0 +1,653 @@
+#[cfg(test)]
+mod dependency_descriptor_extension_test;
+
+use bytes::{Buf, Bytes};
+use util::marshal::{Marshal, MarshalSize};
+
+use crate::error::Error;
+use crate::packet::Packet;
+
+pub const DEPENDENCY_DESCRIPTOR_URI: &str =
+    "https://aomediacodec.github.io/av1-rtp-spec/#dependency-descriptor-rtp-header-extension";
+
+/// Size of the mandatory fields, the smallest descriptor.
+pub const DEPENDENCY_DESCRIPTOR_MIN_SIZE: usize = 3;
+/// Largest number of decode targets of a structure.
+pub const DEPENDENCY_DESCRIPTOR_MAX_DECODE_TARGETS: usize = 32;
+/// Largest number of templates of a structure.
+pub const DEPENDENCY_DESCRIPTOR_MAX_TEMPLATES: usize = 64;
+
+const MAX_TEMPLATE_ID: u8 = 63;
+const MAX_TEMPLATE_FRAME_DIFF: u16 = 16;
+const MAX_TEMPLATE_CHAIN_DIFF: u8 = 15;
+const MAX_FRAME_DIFF: u16 = 1 << 12;
+const MAX_RESOLUTION: u32 = 1 << 16;
+
+/// DecodeTargetIndication tells how a frame is used by a decode target.
+#[derive(Default, PartialEq, Eq, Debug, Copy, Clone)]
+pub enum DecodeTargetIndication {
+    /// The frame isn't part of the decode target.
+    #[default]
+    NotPresent = 0,
+    /// No frame of the decode target depends on the frame.
+    Discardable = 1,
+    /// Decoding the decode target can start with the frame, all later frames of it depend on
+    /// no frame before it.
+    Switch = 2,
+    /// The frame is needed to decode the decode target.
+    Required = 3,
+}
+
+impl From<u32> for DecodeTargetIndication {
+    fn from(v: u32) -> Self {
+        match v & 0b11 {
+            0 => DecodeTargetIndication::NotPresent,
+            1 => DecodeTargetIndication::Discardable,
+            2 => DecodeTargetIndication::Switch,
+            _ => DecodeTargetIndication::Required,
+        }
+    }
+}
+
+/// RenderResolution is the resolution frames of a spatial layer are rendered at.
+#[derive(Default, PartialEq, Eq, Debug, Copy, Clone)]
+pub struct RenderResolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// FrameDependencyTemplate describes the dependencies of a frame, either as one of the
+/// templates of a [`FrameDependencyStructure`] or for a single frame.
+#[derive(Default, PartialEq, Eq, Debug, Clone)]
+pub struct FrameDependencyTemplate {
+    pub spatial_id: u8,
+    pub temporal_id: u8,
+    /// How the frame is used by each of the decode targets.
+    pub decode_target_indications: Vec<DecodeTargetIndication>,
+    /// Differences of the frame numbers of the frames this one depends on to its own.
+    pub frame_diffs: Vec<u16>,
+    /// Differences of the frame numbers of the previous frame of each chain to this one's.
+    pub chain_diffs: Vec<u8>,
+}
+
+/// DecodeTargetLayer is the highest layer of the frames a decode target is made of.
+#[derive(Default, PartialEq, Eq, Debug, Copy, Clone)]
+pub struct DecodeTargetLayer {
+    pub spatial_id: u8,
+    pub temporal_id: u8,
+}
+
+/// FrameDependencyStructure describes the decode targets and chains of a stream, and the
+/// templates the dependencies of its frames are described with.
+#[derive(Default, PartialEq, Eq, Debug, Clone)]
+pub struct FrameDependencyStructure {
+    /// Template id of the first template, from 0 to 63. A new structure should change it, so
+    /// descriptors referring to the previous one are recognized.
+    pub structure_id: u8,
+    /// Number of decode targets, from 1 to 32.
+    pub num_decode_targets: usize,
+    /// Number of chains, up to num_decode_targets.
+    pub num_chains: usize,
+    /// Chain protecting each decode target, empty without chains.
+    pub decode_target_protected_by_chain: Vec<usize>,
+    /// Resolution of each spatial layer, or empty.
+    pub resolutions: Vec<RenderResolution>,
+    /// Templates ordered by spatial layer and then by temporal layer, where each temporal
+    /// layer of a spatial layer follows the one below it. Up to 64 templates.
+    pub templates: Vec<FrameDependencyTemplate>,
+}
+
+impl FrameDependencyStructure {
+    /// decode_target_layers returns the highest layer of the frames of each decode target.
+    pub fn decode_target_layers(&self) -> Vec<DecodeTargetLayer> {
+        (0..self.num_decode_targets)
+            .map(|dt| {
+                self.templates
+                    .iter()
+                    .filter(|t| {
+                        matches!(t.decode_target_indications.get(dt),
+                            Some(&dti) if dti != DecodeTargetIndication::NotPresent)
+                    })
+                    .fold(DecodeTargetLayer::default(), |layer, t| DecodeTargetLayer {
+                        spatial_id: layer.spatial_id.max(t.spatial_id),
+                        temporal_id: layer.temporal_id.max(t.temporal_id),
+                    })
+            })
+            .collect()
+    }
+
+    fn max_spatial_id(&self) -> u8 {
+        self.templates
+            .last()
+            .map(|t| t.spatial_id)
+            .unwrap_or_default()
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        let num_dt = self.num_decode_targets;
+        if self.structure_id > MAX_TEMPLATE_ID
+            || num_dt == 0
+            || num_dt > DEPENDENCY_DESCRIPTOR_MAX_DECODE_TARGETS
+            || self.num_chains > num_dt
+            || self.templates.is_empty()
+            || self.templates.len() > DEPENDENCY_DESCRIPTOR_MAX_TEMPLATES
+        {
+            return Err(Error::ErrInvalidDependencyDescriptor);
+        }
+        if self.num_chains > 0
+            && (self.decode_target_protected_by_chain.len() != num_dt
+                || self
+                    .decode_target_protected_by_chain
+                    .iter()
+                    .any(|&chain| chain >= self.num_chains))
+        {
+            return Err(Error::ErrInvalidDependencyDescriptor);
+        }
+        if !self.resolutions.is_empty()
+            && (self.resolutions.len() != self.max_spatial_id() as usize + 1
+                || self.resolutions.iter().any(|r| {
+                    !(1..=MAX_RESOLUTION).contains(&r.width)
+                        || !(1..=MAX_RESOLUTION).contains(&r.height)
+                }))
+        {
+            return Err(Error::ErrInvalidDependencyDescriptor);
+        }
+
+        let first = &self.templates[0];
+        if first.spatial_id != 0 || first.temporal_id != 0 {
+            return Err(Error::ErrInvalidDependencyDescriptor);
+        }
+        for pair in self.templates.windows(2) {
+            next_layer_idc(&pair[0], &pair[1])?;
+        }
+        for t in &self.templates {
+            if t.decode_target_indications.len() != num_dt
+                || t.chain_diffs.len() != self.num_chains
+                || t.chain_diffs.iter().any(|&d| d > MAX_TEMPLATE_CHAIN_DIFF)
+                || t.frame_diffs
+                    .iter()
+                    .any(|&d| d == 0 || d > MAX_TEMPLATE_FRAME_DIFF)
+            {
+                return Err(Error::ErrInvalidDependencyDescriptor);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// next_layer_idc returns how the layer of template next follows the one of template prev.
+fn next_layer_idc(
+    prev: &FrameDependencyTemplate,
+    next: &FrameDependencyTemplate,
+) -> Result<u32, Error> {
+    if next.spatial_id == prev.spatial_id && next.temporal_id == prev.temporal_id {
+        Ok(0)
+    } else if next.spatial_id == prev.spatial_id && next.temporal_id == prev.temporal_id + 1 {
+        Ok(1)
+    } else if next.spatial_id == prev.spatial_id + 1 && next.temporal_id == 0 {
+        Ok(2)
+    } else {
+        Err(Error::ErrInvalidDependencyDescriptor)
+    }
+}
+
+/// DependencyDescriptor is the payload of the AV1 Dependency Descriptor RTP header
+/// extension, which describes how a frame depends on others so it can be forwarded without
+/// parsing the video, whatever the codec.
+///
+/// Most of a descriptor refers to a [`FrameDependencyStructure`], which is attached to
+/// some descriptors only, usually the ones of key frames, so reading or writing one needs
+/// the latest structure of the stream.
+///
+/// ## Specifications
+///
+/// * [AV1 RTP Payload Format, Appendix A]
+///
+/// [AV1 RTP Payload Format, Appendix A]: https://aomediacodec.github.io/av1-rtp-spec/#appendix
+#[derive(Default, PartialEq, Eq, Debug, Clone)]
+pub struct DependencyDescriptor {
+    pub first_packet_in_frame: bool,
+    pub last_packet_in_frame: bool,
+    pub frame_number: u16,
+    pub frame_dependencies: FrameDependencyTemplate,
+    /// Resolution of the frame, if the structure has resolutions.
+    pub resolution: Option<RenderResolution>,
+    /// Bit i is set if decode target i is active, all of them are if none.
+    pub active_decode_targets_bitmask: Option<u32>,
+    /// Structure attached to the descriptor, which replaces the previous one.
+    pub attached_structure: Option<FrameDependencyStructure>,
+}
+
+impl DependencyDescriptor {
+    /// unmarshal_with_structure parses the payload of the extension. structure is the latest
+    /// structure of the stream, which is needed unless the descriptor has one attached.
+    pub fn unmarshal_with_structure<B>(
+        buf: &mut B,
+        structure: Option<&FrameDependencyStructure>,
+    ) -> util::Result<Self>
+    where
+        B: Buf,
+    {
+        if buf.remaining() < DEPENDENCY_DESCRIPTOR_MIN_SIZE {
+            return Err(Error::ErrBufferTooSmall.into());
+        }
+        let raw = buf.copy_to_bytes(buf.remaining());
+        let mut r = BitReader::new(&raw);
+
+        let mut descriptor = DependencyDescriptor {
+            first_packet_in_frame: r.read_bool()?,
+            last_packet_in_frame: r.read_bool()?,
+            ..Default::default()
+        };
+        let template_id = r.read(6)? as u8;
+        descriptor.frame_number = r.read(16)? as u16;
+
+        let (mut custom_dtis, mut custom_fdiffs, mut custom_chains) = (false, false, false);
+        let mut active_decode_targets_present = false;
+        if raw.len() > DEPENDENCY_DESCRIPTOR_MIN_SIZE {
+            let structure_present = r.read_bool()?;
+            active_decode_targets_present = r.read_bool()?;
+            custom_dtis = r.read_bool()?;
+            custom_fdiffs = r.read_bool()?;
+            custom_chains = r.read_bool()?;
+            if structure_present {
+                let attached = read_structure(&mut r)?;
+                descriptor.active_decode_targets_bitmask =
+                    Some(all_decode_targets(attached.num_decode_targets));
+                descriptor.attached_structure = Some(attached);
+            }
+        }
+
+        let structure = descriptor
+            .attached_structure
+            .as_ref()
+            .or(structure)
+            .ok_or(Error::ErrDependencyDescriptorNoStructure)?;
+        let num_dt = structure.num_decode_targets;
+        if active_decode_targets_present {
+            descriptor.active_decode_targets_bitmask = Some(r.read(num_dt)?);
+        }
+
+        let index = (template_id as usize + 64 - structure.structure_id as usize) % 64;
+        let mut frame = structure
+            .templates
+            .get(index)
+            .cloned()
+            .ok_or(Error::ErrInvalidDependencyDescriptor)?;
+        if custom_dtis {
+            frame.decode_target_indications = (0..num_dt)
+                .map(|_| r.read(2).map(DecodeTargetIndication::from))
+                .collect::<Result<_, _>>()?;
+        }
+        if custom_fdiffs {
+            frame.frame_diffs.clear();
+            loop {
+                let size = r.read(2)? as usize;
+                if size == 0 {
+                    break;
+                }
+                frame.frame_diffs.push(r.read(4 * size)? as u16 + 1);
+            }
+        }
+        if custom_chains {
+            frame.chain_diffs = (0..structure.num_chains)
+                .map(|_| r.read(8).map(|d| d as u8))
+                .collect::<Result<_, _>>()?;
+        }
+        descriptor.resolution = structure
+            .resolutions
+            .get(frame.spatial_id as usize)
+            .copied();
+        descriptor.frame_dependencies = frame;
+
+        Ok(descriptor)
+    }
+
+    /// marshal_with_structure serializes the descriptor. structure is the latest structure of
+    /// the stream, which is needed unless the descriptor has one attached.
+    pub fn marshal_with_structure(
+        &self,
+        structure: Option<&FrameDependencyStructure>,
+    ) -> util::Result<Bytes> {
+        let structure = self
+            .attached_structure
+            .as_ref()
+            .or(structure)
+            .ok_or(Error::ErrDependencyDescriptorNoStructure)?;
+        if self.attached_structure.is_some() {
+            structure.validate()?;
+        }
+        let num_dt = structure.num_decode_targets;
+
+        let frame = &self.frame_dependencies;
+        if frame.decode_target_indications.len() != num_dt
+            || frame.chain_diffs.len() != structure.num_chains
+            || frame
+                .frame_diffs
+                .iter()
+                .any(|&d| d == 0 || d > MAX_FRAME_DIFF)
+        {
+            return Err(Error::ErrInvalidDependencyDescriptor.into());
+        }
+
+        // The template of the layer of the frame which leaves the fewest fields custom.
+        let (index, template) = structure
+            .templates
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.spatial_id == frame.spatial_id && t.temporal_id == frame.temporal_id)
+            .min_by_key(|(_, t)| {
+                (t.decode_target_indications != frame.decode_target_indications) as u8
+                    + (t.frame_diffs != frame.frame_diffs) as u8
+                    + (t.chain_diffs != frame.chain_diffs) as u8
+            })
+            .ok_or(Error::ErrInvalidDependencyDescriptor)?;
+        let custom_dtis = template.decode_target_indications != frame.decode_target_indications;
+        let custom_fdiffs = template.frame_diffs != frame.frame_diffs;
+        let custom_chains = template.chain_diffs != frame.chain_diffs;
+
+        // All decode targets are active after a structure, unless the bitmask tells otherwise.
+        let active_decode_targets = self.active_decode_targets_bitmask.filter(|&mask| {
+            self.attached_structure.is_none() || mask != all_decode_targets(num_dt)
+        });
+
+        let mut w = BitWriter::default();
+        w.write_bool(self.first_packet_in_frame);
+        w.write_bool(self.last_packet_in_frame);
+        w.write(((index + structure.structure_id as usize) % 64) as u32, 6);
+        w.write(self.frame_number as u32, 16);
+
+        if self.attached_structure.is_some()
+            || active_decode_targets.is_some()
+            || custom_dtis
+            || custom_fdiffs
+            || custom_chains
+        {
+            w.write_bool(self.attached_structure.is_some());
+            w.write_bool(active_decode_targets.is_some());
+            w.write_bool(custom_dtis);
+            w.write_bool(custom_fdiffs);
+            w.write_bool(custom_chains);
+            if let Some(attached) = &self.attached_structure {
+                write_structure(&mut w, attached);
+            }
+            if let Some(mask) = active_decode_targets {
+                w.write(mask, num_dt);
+            }
+        }
+
+        if custom_dtis {
+            for &dti in &frame.decode_target_indications {
+                w.write(dti as u32, 2);
+            }
+        }
+        if custom_fdiffs {
+            for &diff in &frame.frame_diffs {
+                let minus_one = (diff - 1) as u32;
+                let size = match minus_one {
+                    0..=0xF => 1,
+                    0x10..=0xFF => 2,
+                    _ => 3,
+                };
+                w.write(size, 2);
+                w.write(minus_one, 4 * size as usize);
+            }
+            w.write(0, 2);
+        }
+        if custom_chains {
+            for &diff in &frame.chain_diffs {
+                w.write(diff as u32, 8);
+            }
+        }
+
+        Ok(Bytes::from(w.into_bytes()))
+    }
+
+    /// set_on_frame sets the descriptor as extension id on all packets of a frame, marking
+    /// the first and the last one. An attached structure is sent with the first packet only.
+    pub fn set_on_frame(
+        &self,
+        packets: &mut [Packet],
+        id: u8,
+        structure: Option<&FrameDependencyStructure>,
+    ) -> util::Result<()> {
+        let structure = self.attached_structure.as_ref().or(structure);
+        let last = packets.len().saturating_sub(1);
+        for (i, packet) in packets.iter_mut().enumerate() {
+            let descriptor = DependencyDescriptor {
+                first_packet_in_frame: i == 0,
+                last_packet_in_frame: i == last,
+                attached_structure: if i == 0 {
+                    self.attached_structure.clone()
+                } else {
+                    None
+                },
+                ..self.clone()
+            };
+            let payload = descriptor.marshal_with_structure(structure)?;
+            packet.header.set_extension(id, payload)?;
+        }
+        Ok(())
+    }
+}
+
+/// DependencyDescriptorExtension is a [`DependencyDescriptor`] together with the structure
+/// it refers to, so it can be marshaled like the other extensions.
+#[derive(Default, PartialEq, Eq, Debug, Clone)]
+pub struct DependencyDescriptorExtension {
+    pub descriptor: DependencyDescriptor,
+    pub structure: Option<FrameDependencyStructure>,
+}
+
+impl MarshalSize for DependencyDescriptorExtension {
+    /// MarshalSize returns the size of the DependencyDescriptorExtension once marshaled, or
+    /// zero if it can't be.
+    fn marshal_size(&self) -> usize {
+        self.descriptor
+            .marshal_with_structure(self.structure.as_ref())
+            .map(|raw| raw.len())
+            .unwrap_or_default()
+    }
+}
+
+impl Marshal for DependencyDescriptorExtension {
+    /// MarshalTo serializes the members to buffer
+    fn marshal_to(&self, buf: &mut [u8]) -> util::Result<usize> {
+        let raw = self
+            .descriptor
+            .marshal_with_structure(self.structure.as_ref())?;
+        if buf.len() < raw.len() {
+            return Err(Error::ErrBufferTooSmall.into());
+        }
+        buf[..raw.len()].copy_from_slice(&raw);
+        Ok(raw.len())
+    }
+}
+
+fn all_decode_targets(num_decode_targets: usize) -> u32 {
+    ((1u64 << num_decode_targets) - 1) as u32
+}
+
+fn read_structure(r: &mut BitReader<'_>) -> Result<FrameDependencyStructure, Error> {
+    let mut structure = FrameDependencyStructure {
+        structure_id: r.read(6)? as u8,
+        num_decode_targets: r.read(5)? as usize + 1,
+        ..Default::default()
+    };
+    let num_dt = structure.num_decode_targets;
+
+    let mut template = FrameDependencyTemplate::default();
+    loop {
+        if structure.templates.len() == DEPENDENCY_DESCRIPTOR_MAX_TEMPLATES {
+            return Err(Error::ErrInvalidDependencyDescriptor);
+        }
+        structure.templates.push(template.clone());
+        match r.read(2)? {
+            0 => {}
+            1 => template.temporal_id += 1,
+            2 => {
+                template.spatial_id += 1;
+                template.temporal_id = 0;
+            }
+            _ => break,
+        }
+    }
+
+    for t in &mut structure.templates {
+        t.decode_target_indications = (0..num_dt)
+            .map(|_| r.read(2).map(DecodeTargetIndication::from))
+            .collect::<Result<_, _>>()?;
+    }
+    for t in &mut structure.templates {
+        while r.read_bool()? {
+            t.frame_diffs.push(r.read(4)? as u16 + 1);
+        }
+    }
+
+    structure.num_chains = r.read_ns(num_dt as u32 + 1)? as usize;
+    if structure.num_chains > 0 {
+        structure.decode_target_protected_by_chain = (0..num_dt)
+            .map(|_| r.read_ns(structure.num_chains as u32).map(|c| c as usize))
+            .collect::<Result<_, _>>()?;
+        for t in &mut structure.templates {
+            t.chain_diffs = (0..structure.num_chains)
+                .map(|_| r.read(4).map(|d| d as u8))
+                .collect::<Result<_, _>>()?;
+        }
+    }
+
+    if r.read_bool()? {
+        for _ in 0..=structure.max_spatial_id() {
+            structure.resolutions.push(RenderResolution {
+                width: r.read(16)? + 1,
+                height: r.read(16)? + 1,
+            });
+        }
+    }
+
+    Ok(structure)
+}
+
+/// write_structure writes a structure which has been validated.
+fn write_structure(w: &mut BitWriter, structure: &FrameDependencyStructure) {
+    w.write(structure.structure_id as u32, 6);
+    w.write(structure.num_decode_targets as u32 - 1, 5);
+
+    for pair in structure.templates.windows(2) {
+        w.write(next_layer_idc(&pair[0], &pair[1]).unwrap_or_default(), 2);
+    }
+    w.write(3, 2);
+
+    for t in &structure.templates {
+        for &dti in &t.decode_target_indications {
+            w.write(dti as u32, 2);
+        }
+    }
+    for t in &structure.templates {
+        for &diff in &t.frame_diffs {
+            w.write_bool(true);
+            w.write(diff as u32 - 1, 4);
+        }
+        w.write_bool(false);
+    }
+
+    w.write_ns(
+        structure.num_chains as u32,
+        structure.num_decode_targets as u32 + 1,
+    );
+    if structure.num_chains > 0 {
+        for &chain in &structure.decode_target_protected_by_chain {
+            w.write_ns(chain as u32, structure.num_chains as u32);
+        }
+        for t in &structure.templates {
+            for &diff in &t.chain_diffs {
+                w.write(diff as u32, 4);
+            }
+        }
+    }
+
+    w.write_bool(!structure.resolutions.is_empty());
+    for resolution in &structure.resolutions {
+        w.write(resolution.width - 1, 16);
+        w.write(resolution.height - 1, 16);
+    }
+}
+
+/// BitReader reads the most significant bits first.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        BitReader { data, pos: 0 }
+    }
+
+    fn read(&mut self, bits: usize) -> Result<u32, Error> {
+        if self.pos + bits > self.data.len() * 8 {
+            return Err(Error::ErrBufferTooSmall);
+        }
+        let mut v = 0u32;
+        for _ in 0..bits {
+            let bit = (self.data[self.pos / 8] >> (7 - self.pos % 8)) & 1;
+            v = (v << 1) | bit as u32;
+            self.pos += 1;
+        }
+        Ok(v)
+    }
+
+    fn read_bool(&mut self) -> Result<bool, Error> {
+        Ok(self.read(1)? == 1)
+    }
+
+    /// read_ns reads a non-symmetric unsigned value below n.
+    fn read_ns(&mut self, n: u32) -> Result<u32, Error> {
+        let w = 32 - n.leading_zeros() as usize;
+        let m = (1 << w) - n;
+        let v = self.read(w - 1)?;
+        if v < m {
+            return Ok(v);
+        }
+        Ok((v << 1) - m + self.read(1)?)
+    }
+}
+
+/// BitWriter writes the most significant bits first, padding the last byte with zeros.
+#[derive(Default)]
+struct BitWriter {
+    data: Vec<u8>,
+    pos: usize,
+}
+
+impl BitWriter {
+    fn write(&mut self, v: u32, bits: usize) {
+        for i in (0..bits).rev() {
+            if self.pos.is_multiple_of(8) {
+                self.data.push(0);
+            }
+            let bit = ((v >> i) & 1) as u8;
+            *self.data.last_mut().unwrap_or(&mut 0) |= bit << (7 - self.pos % 8);
+            self.pos += 1;
+        }
+    }
+
+    fn write_bool(&mut self, v: bool) {
+        self.write(v as u32, 1);
+    }
+
+    /// write_ns writes a non-symmetric unsigned value below n.
+    fn write_ns(&mut self, v: u32, n: u32) {
+        let w = 32 - n.leading_zeros() as usize;
+        let m = (1 << w) - n;
+        if v < m {
+            self.write(v, w - 1);
+        } else {
+            self.write(v + m, w);
+        }
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+}
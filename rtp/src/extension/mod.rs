@@ -3,8 +3,10 @@ use std::fmt;
 
 use util::{Marshal, MarshalSize};
 
+pub mod abs_capture_time_extension;
 pub mod abs_send_time_extension;
 pub mod audio_level_extension;
+pub mod dependency_descriptor_extension;
 pub mod playout_delay_extension;
 pub mod transport_cc_extension;
 pub mod video_orientation_extension;
@@ -45,6 +47,7 @@ impl HeaderExtension {
         match (self, other) {
             (AbsSendTime(_), AbsSendTime(_)) => true,
             (AudioLevel(_), AudioLevel(_)) => true,
+            (PlayoutDelay(_), PlayoutDelay(_)) => true,
             (TransportCc(_), TransportCc(_)) => true,
             (VideoOrientation(_), VideoOrientation(_)) => true,
             (Custom { uri, .. }, Custom { uri: other_uri, .. }) => uri == other_uri,
@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod playout_delay_extension_test;
 
+use std::time::Duration;
+
 use bytes::BufMut;
 use util::marshal::{Marshal, MarshalSize, Unmarshal};
 
@@ -8,9 +10,12 @@ use crate::error::Error;
 
 pub const PLAYOUT_DELAY_EXTENSION_SIZE: usize = 3;
 pub const PLAYOUT_DELAY_MAX_VALUE: u16 = (1 << 12) - 1;
+/// Granularity of the delays, in milliseconds.
+pub const PLAYOUT_DELAY_GRANULARITY_MS: u64 = 10;
 
 /// PlayoutDelayExtension is an extension payload format described in
 /// http://www.webrtc.org/experiments/rtp-hdrext/playout-delay
+/// The delays are in units of 10 ms, from the capture of a frame to its rendering.
 /// 0                   1                   2                   3
 /// 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
 /// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
@@ -79,4 +84,14 @@ impl PlayoutDelayExtension {
             max_delay,
         }
     }
+
+    /// min_delay_duration returns the minimum delay the receiver should render frames with.
+    pub fn min_delay_duration(&self) -> Duration {
+        Duration::from_millis(self.min_delay as u64 * PLAYOUT_DELAY_GRANULARITY_MS)
+    }
+
+    /// max_delay_duration returns the maximum delay the receiver should render frames with.
+    pub fn max_delay_duration(&self) -> Duration {
+        Duration::from_millis(self.max_delay as u64 * PLAYOUT_DELAY_GRANULARITY_MS)
+    }
 }
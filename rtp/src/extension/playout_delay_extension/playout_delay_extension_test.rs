@@ -36,3 +36,16 @@ fn test_playout_delay_value_overflow() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_playout_delay_durations() {
+    let test = PlayoutDelayExtension::new(2, 15);
+    assert_eq!(
+        test.min_delay_duration(),
+        std::time::Duration::from_millis(20)
+    );
+    assert_eq!(
+        test.max_delay_duration(),
+        std::time::Duration::from_millis(150)
+    );
+}
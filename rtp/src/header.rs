@@ -12,6 +12,9 @@ pub const EXTENSION_SHIFT: u8 = 4;
 pub const EXTENSION_MASK: u8 = 0x1;
 pub const EXTENSION_PROFILE_ONE_BYTE: u16 = 0xBEDE;
 pub const EXTENSION_PROFILE_TWO_BYTE: u16 = 0x1000;
+/// The lowest 4 bits of the two byte extension profile are appbits, which are application
+/// specific and don't change how the extensions are parsed.
+pub const EXTENSION_PROFILE_TWO_BYTE_MASK: u16 = 0xFFF0;
 pub const EXTENSION_ID_RESERVED: u8 = 0xF;
 pub const CC_MASK: u8 = 0xF;
 pub const MARKER_SHIFT: u8 = 7;
@@ -135,6 +138,9 @@ impl Unmarshal for Header {
                         if extid == EXTENSION_ID_RESERVED {
                             break;
                         }
+                        if curr_offset + len > end {
+                            return Err(Error::ErrHeaderSizeInsufficientForExtension.into());
+                        }
 
                         extensions.push(Extension {
                             id: extid,
@@ -144,7 +150,7 @@ impl Unmarshal for Header {
                     }
                 }
                 // RFC 8285 RTP Two Byte Header Extension
-                EXTENSION_PROFILE_TWO_BYTE => {
+                profile if is_two_byte_extension_profile(profile) => {
                     let end = curr_offset + extension_length;
                     while curr_offset < end {
                         let b = raw_packet.get_u8();
@@ -157,9 +163,15 @@ impl Unmarshal for Header {
 
                         let extid = b;
                         curr_offset += 1;
+                        if curr_offset >= end {
+                            return Err(Error::ErrHeaderSizeInsufficientForExtension.into());
+                        }
 
                         let len = raw_packet.get_u8() as usize;
                         curr_offset += 1;
+                        if curr_offset + len > end {
+                            return Err(Error::ErrHeaderSizeInsufficientForExtension.into());
+                        }
 
                         extensions.push(Extension {
                             id: extid,
@@ -269,7 +281,7 @@ impl Marshal for Header {
             // calculate extensions size and round to 4 bytes boundaries
             let extension_payload_len = self.get_extension_payload_len();
             if self.extension_profile != EXTENSION_PROFILE_ONE_BYTE
-                && self.extension_profile != EXTENSION_PROFILE_TWO_BYTE
+                && !is_two_byte_extension_profile(self.extension_profile)
                 && extension_payload_len % 4 != 0
             {
                 //the payload must be in 32-bit words.
@@ -287,7 +299,7 @@ impl Marshal for Header {
                     }
                 }
                 // RFC 8285 RTP Two Byte Header Extension
-                EXTENSION_PROFILE_TWO_BYTE => {
+                profile if is_two_byte_extension_profile(profile) => {
                     for extension in &self.extensions {
                         buf.put_u8(extension.id);
                         buf.put_u8(extension.payload.len() as u8);
@@ -332,7 +344,7 @@ impl Header {
         let profile_len = self.extensions.len()
             * match self.extension_profile {
                 EXTENSION_PROFILE_ONE_BYTE => 1,
-                EXTENSION_PROFILE_TWO_BYTE => 2,
+                profile if is_two_byte_extension_profile(profile) => 2,
                 _ => 0,
             };
 
@@ -340,9 +352,19 @@ impl Header {
     }
 
     /// SetExtension sets an RTP header extension
+    ///
+    /// One byte extensions are promoted to two byte ones if the id is above 14 or the payload
+    /// is empty or longer than 16 bytes, which requires the extmap-allow-mixed attribute to be
+    /// negotiated, see RFC 8285 section 6.
     pub fn set_extension(&mut self, id: u8, payload: Bytes) -> Result<(), Error> {
         let payload_len = payload.len() as isize;
         if self.extension {
+            if self.extension_profile == EXTENSION_PROFILE_ONE_BYTE
+                && !fits_one_byte_extension(id, payload_len)
+            {
+                self.promote_to_two_byte_extensions();
+            }
+
             let extension_profile_len = match self.extension_profile {
                 EXTENSION_PROFILE_ONE_BYTE => {
                     if !(1..=14).contains(&id) {
@@ -353,7 +375,7 @@ impl Header {
                     }
                     1
                 }
-                EXTENSION_PROFILE_TWO_BYTE => {
+                profile if is_two_byte_extension_profile(profile) => {
                     if id < 1 {
                         return Err(Error::ErrRfc8285twoByteHeaderIdrange);
                     }
@@ -404,11 +426,11 @@ impl Header {
             self.extension = true;
             let mut extension_profile_len = 0;
             self.extension_profile = match payload_len {
-                0..=16 => {
+                _ if fits_one_byte_extension(id, payload_len) => {
                     extension_profile_len = 1;
                     EXTENSION_PROFILE_ONE_BYTE
                 }
-                17..=255 => {
+                0..=255 => {
                     extension_profile_len = 2;
                     EXTENSION_PROFILE_TWO_BYTE
                 }
@@ -426,6 +448,13 @@ impl Header {
         Ok(())
     }
 
+    /// promote_to_two_byte_extensions switches the one byte extensions to two byte ones, which
+    /// take ids up to 255 and payloads up to 255 bytes.
+    fn promote_to_two_byte_extensions(&mut self) {
+        self.extension_profile = EXTENSION_PROFILE_TWO_BYTE;
+        self.extensions_padding = (4 - self.get_extension_payload_len() % 4) % 4;
+    }
+
     /// returns an extension id array
     pub fn get_extension_ids(&self) -> Vec<u8> {
         if self.extension {
@@ -459,7 +488,7 @@ impl Header {
 
                 let extension_profile_len = match self.extension_profile {
                     EXTENSION_PROFILE_ONE_BYTE => 1,
-                    EXTENSION_PROFILE_TWO_BYTE => 2,
+                    profile if is_two_byte_extension_profile(profile) => 2,
                     _ => 0,
                 };
 
@@ -475,3 +504,15 @@ impl Header {
         }
     }
 }
+
+/// is_two_byte_extension_profile returns true for the RFC 8285 two byte extension profile,
+/// whatever its appbits are.
+pub(crate) fn is_two_byte_extension_profile(extension_profile: u16) -> bool {
+    extension_profile & EXTENSION_PROFILE_TWO_BYTE_MASK == EXTENSION_PROFILE_TWO_BYTE
+}
+
+/// fits_one_byte_extension returns false if the extension needs the two byte form, id 0 is
+/// left for the one byte form to refuse.
+fn fits_one_byte_extension(id: u8, payload_len: isize) -> bool {
+    id <= 14 && (1..=16).contains(&payload_len)
+}
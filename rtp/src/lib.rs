@@ -2,11 +2,14 @@
 #![allow(dead_code)]
 
 pub mod codecs;
+pub mod demuxer;
 mod error;
 pub mod extension;
 pub mod header;
 pub mod packet;
+pub mod packet_ref;
 pub mod packetizer;
+pub mod padding;
 pub mod sequence;
 
 pub use error::Error;
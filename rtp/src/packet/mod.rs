@@ -15,6 +15,9 @@ use crate::header::*;
 pub struct Packet {
     pub header: Header,
     pub payload: Bytes,
+    /// Size of the padding, if the padding bit of the header is set. 0 pads the payload to a
+    /// multiple of 4 bytes, or with 4 bytes if it's already one.
+    pub padding_size: u8,
 }
 
 impl fmt::Display for Packet {
@@ -50,6 +53,7 @@ impl Unmarshal for Packet {
                     Ok(Packet {
                         header,
                         payload: payload.slice(..payload_len - padding_len),
+                        padding_size: padding_len as u8,
                     })
                 } else {
                     Err(Error::ErrShortPacket.into())
@@ -58,7 +62,11 @@ impl Unmarshal for Packet {
                 Err(Error::ErrShortPacket.into())
             }
         } else {
-            Ok(Packet { header, payload })
+            Ok(Packet {
+                header,
+                payload,
+                padding_size: 0,
+            })
         }
     }
 }
@@ -66,18 +74,7 @@ impl Unmarshal for Packet {
 impl MarshalSize for Packet {
     /// MarshalSize returns the size of the packet once marshaled.
     fn marshal_size(&self) -> usize {
-        let payload_len = self.payload.len();
-        let padding_len = if self.header.padding {
-            let padding_len = get_padding(payload_len);
-            if padding_len == 0 {
-                4
-            } else {
-                padding_len
-            }
-        } else {
-            0
-        };
-        self.header.marshal_size() + payload_len + padding_len
+        self.header.marshal_size() + self.payload.len() + self.padding_len()
     }
 }
 
@@ -91,27 +88,35 @@ impl Marshal for Packet {
         let n = self.header.marshal_to(buf)?;
         buf = &mut buf[n..];
         buf.put(&*self.payload);
-        let padding_len = if self.header.padding {
-            let mut padding_len = get_padding(self.payload.len());
-            if padding_len == 0 {
-                padding_len = 4;
-            }
-            for i in 0..padding_len {
-                if i != padding_len - 1 {
-                    buf.put_u8(0);
-                } else {
-                    buf.put_u8(padding_len as u8);
-                }
+        let padding_len = self.padding_len();
+        for i in 0..padding_len {
+            if i != padding_len - 1 {
+                buf.put_u8(0);
+            } else {
+                buf.put_u8(padding_len as u8);
             }
-            padding_len
-        } else {
-            0
-        };
+        }
 
         Ok(n + self.payload.len() + padding_len)
     }
 }
 
+impl Packet {
+    /// padding_len returns the number of padding bytes written after the payload.
+    fn padding_len(&self) -> usize {
+        if !self.header.padding {
+            0
+        } else if self.padding_size != 0 {
+            self.padding_size as usize
+        } else {
+            match get_padding(self.payload.len()) {
+                0 => 4,
+                padding_len => padding_len,
+            }
+        }
+    }
+}
+
 /// getPadding Returns the padding required to make the length a multiple of 4
 fn get_padding(len: usize) -> usize {
     if len % 4 == 0 {
@@ -38,6 +38,7 @@ fn test_basic() -> Result<()> {
             ..Default::default()
         },
         payload: Bytes::from_static(&[0x98, 0x36, 0xbe, 0x88, 0x9e]),
+        ..Default::default()
     };
     let buf = &mut raw_pkt.clone();
     let packet = Packet::unmarshal(buf)?;
@@ -109,6 +110,7 @@ fn test_extension() -> Result<()> {
             ..Default::default()
         },
         payload: Bytes::from_static(&[]),
+        ..Default::default()
     };
 
     let mut raw = BytesMut::new();
@@ -198,6 +200,7 @@ fn test_rfc_8285_one_byte_extension() -> Result<()> {
             ..Default::default()
         },
         payload: raw_pkt.slice(20..),
+        ..Default::default()
     };
 
     let dst = p.marshal()?;
@@ -264,6 +267,7 @@ fn test_rfc_8285_one_byte_two_extension_of_two_bytes() -> Result<()> {
             ..Default::default()
         },
         payload: raw_pkt.slice(20..),
+        ..Default::default()
     };
 
     let dst = p.marshal()?;
@@ -398,6 +402,7 @@ fn test_rfc_8285_one_byte_multiple_extension() -> Result<()> {
             ..Default::default()
         },
         payload: raw_pkt[28..].into(),
+        ..Default::default()
     };
 
     let dst_data = p.marshal()?;
@@ -440,6 +445,7 @@ fn test_rfc_8285_two_byte_extension() -> Result<()> {
             ..Default::default()
         },
         payload: raw_pkt.slice(44..),
+        ..Default::default()
     };
 
     let dst_data = p.marshal()?;
@@ -551,6 +557,7 @@ fn test_rfc8285_two_byte_multiple_extension_with_large_extension() -> Result<()>
             ..Default::default()
         },
         payload: raw_pkt.slice(40..),
+        ..Default::default()
     };
 
     let dst_data = p.marshal()?;
@@ -839,7 +846,8 @@ fn test_rfc8285_set_extension_should_update_existing_extension() -> Result<()> {
     Ok(())
 }
 
-fn test_rfc8285_one_byte_set_extension_should_error_when_invalid_id_provided() {
+#[test]
+fn test_rfc8285_one_byte_set_extension_should_promote_when_id_too_large() -> Result<()> {
     let payload = Bytes::from_static(&[0x98u8, 0x36, 0xbe, 0x88, 0x9e]);
 
     let mut p = Packet {
@@ -851,6 +859,7 @@ fn test_rfc8285_one_byte_set_extension_should_error_when_invalid_id_provided() {
                 id: 1,
                 payload: Bytes::from_static(&[0xAA]),
             }],
+            extensions_padding: 2,
             version: 2,
             payload_type: 96,
             sequence_number: 27023,
@@ -868,12 +877,26 @@ fn test_rfc8285_one_byte_set_extension_should_error_when_invalid_id_provided() {
             .is_err(),
         "set_extension did not error on invalid id"
     );
-    assert!(
-        p.header
-            .set_extension(15, Bytes::from_static(&[0xBBu8]))
-            .is_err(),
-        "set_extension did not error on invalid id"
+
+    p.header.set_extension(15, Bytes::from_static(&[0xBBu8]))?;
+    assert_eq!(
+        p.header.extension_profile, 0x1000,
+        "Extension profile should be promoted to 0x1000"
     );
+
+    let raw = p.marshal()?;
+    let out = Packet::unmarshal(&mut raw.clone())?;
+    assert_eq!(
+        out.header.get_extension(1),
+        Some(Bytes::from_static(&[0xAA]))
+    );
+    assert_eq!(
+        out.header.get_extension(15),
+        Some(Bytes::from_static(&[0xBB]))
+    );
+    assert_eq!(out.payload, p.payload);
+
+    Ok(())
 }
 
 fn test_rfc8285_one_byte_extension_terminate_processing_when_reserved_id_encountered() -> Result<()>
@@ -897,7 +920,8 @@ fn test_rfc8285_one_byte_extension_terminate_processing_when_reserved_id_encount
     Ok(())
 }
 
-fn test_rfc8285_one_byte_set_extension_should_error_when_payload_too_large() {
+#[test]
+fn test_rfc8285_one_byte_set_extension_should_promote_when_payload_too_large() -> Result<()> {
     let payload = Bytes::from_static(&[0x98u8, 0x36, 0xbe, 0x88, 0x9e]);
 
     let mut p = Packet {
@@ -909,6 +933,7 @@ fn test_rfc8285_one_byte_set_extension_should_error_when_payload_too_large() {
                 id: 1,
                 payload: Bytes::from_static(&[0xAAu8]),
             }],
+            extensions_padding: 2,
             version: 2,
             payload_type: 96,
             sequence_number: 27023,
@@ -920,18 +945,70 @@ fn test_rfc8285_one_byte_set_extension_should_error_when_payload_too_large() {
         ..Default::default()
     };
 
-    let res = p.header.set_extension(
-        1,
-        Bytes::from_static(&[
-            0xBBu8, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB,
-            0xBB, 0xBB, 0xBB,
-        ]),
+    let extension = Bytes::from_static(&[
+        0xBBu8, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB, 0xBB,
+        0xBB, 0xBB,
+    ]);
+    p.header.set_extension(2, extension.clone())?;
+    p.header.set_extension(3, Bytes::new())?;
+    assert_eq!(
+        p.header.extension_profile, 0x1000,
+        "Extension profile should be promoted to 0x1000"
     );
 
-    assert!(
-        res.is_err(),
-        "set_extension did not error on too large payload"
+    let raw = p.marshal()?;
+    assert_eq!(raw.len(), p.marshal_size());
+    let out = Packet::unmarshal(&mut raw.clone())?;
+    assert_eq!(
+        out.header.get_extension(1),
+        Some(Bytes::from_static(&[0xAA]))
+    );
+    assert_eq!(out.header.get_extension(2), Some(extension));
+    assert_eq!(out.header.get_extension(3), Some(Bytes::new()));
+    assert_eq!(out.payload, p.payload);
+
+    Ok(())
+}
+
+#[test]
+fn test_rfc8285_set_extension_should_use_two_byte_profile_for_large_id() {
+    let mut header = Header::default();
+    let res = header.set_extension(20, Bytes::from_static(&[0xAA]));
+    assert!(res.is_ok(), "Error setting extension");
+    assert_eq!(
+        header.extension_profile, 0x1000,
+        "Extension profile should be 0x1000"
+    );
+}
+
+#[test]
+fn test_rfc8285_two_byte_extension_with_appbits() -> Result<()> {
+    let raw_pkt = Bytes::from_static(&[
+        0x90u8, 0xe0, 0x69, 0x8f, 0xd9, 0xc2, 0x93, 0xda, 0x1c, 0x64, 0x27, 0x82, 0x10, 0x0F, 0x00,
+        0x01, 0x20, 0x01, 0xAA, 0x00, 0x98, 0x36, 0xbe, 0x88, 0x9e,
+    ]);
+
+    let p = Packet::unmarshal(&mut raw_pkt.clone())?;
+    assert_eq!(p.header.extension_profile, 0x100F);
+    assert_eq!(
+        p.header.get_extension(32),
+        Some(Bytes::from_static(&[0xAA]))
     );
+    assert_eq!(p.payload, raw_pkt.slice(20..));
+    assert_eq!(p.marshal()?, raw_pkt);
+
+    // The payload length runs past the extensions.
+    let raw_pkt = Bytes::from_static(&[
+        0x90u8, 0xe0, 0x69, 0x8f, 0xd9, 0xc2, 0x93, 0xda, 0x1c, 0x64, 0x27, 0x82, 0x10, 0x00, 0x00,
+        0x01, 0x20, 0x03, 0xAA, 0xAA, 0x98, 0x36, 0xbe, 0x88, 0x9e,
+    ]);
+    let result = Packet::unmarshal(&mut raw_pkt.clone());
+    assert_eq!(
+        Error::ErrHeaderSizeInsufficientForExtension,
+        result.expect_err("extension should be too short")
+    );
+
+    Ok(())
 }
 
 fn test_rfc8285_two_bytes_set_extension_should_enable_extension_when_adding() -> Result<()> {
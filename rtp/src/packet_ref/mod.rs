@@ -0,0 +1,332 @@
+#[cfg(test)]
+mod packet_ref_test;
+
+use std::ops::Range;
+
+use util::marshal::Unmarshal;
+
+use crate::error::{Error, Result};
+use crate::header::*;
+use crate::packet::Packet;
+
+/// Offsets of the parts of an RTP packet in its buffer.
+#[derive(Debug, Clone)]
+struct Layout {
+    /// Profile of the extension and the range of its data.
+    extension: Option<(u16, Range<usize>)>,
+    payload: Range<usize>,
+}
+
+impl Layout {
+    /// parse returns the layout of an RTP packet, checking it like [`Packet::unmarshal`].
+    fn parse(buf: &[u8]) -> Result<Self> {
+        if buf.len() < CSRC_OFFSET {
+            return Err(Error::ErrHeaderSizeInsufficient);
+        }
+        let cc = (buf[0] & CC_MASK) as usize;
+        let mut offset = CSRC_OFFSET + cc * CSRC_LENGTH;
+        if buf.len() < offset {
+            return Err(Error::ErrHeaderSizeInsufficient);
+        }
+
+        let extension = if (buf[0] >> EXTENSION_SHIFT & EXTENSION_MASK) > 0 {
+            if buf.len() < offset + 4 {
+                return Err(Error::ErrHeaderSizeInsufficientForExtension);
+            }
+            let profile = u16::from_be_bytes([buf[offset], buf[offset + 1]]);
+            let length = u16::from_be_bytes([buf[offset + 2], buf[offset + 3]]) as usize * 4;
+            offset += 4;
+            if buf.len() < offset + length {
+                return Err(Error::ErrHeaderSizeInsufficientForExtension);
+            }
+
+            let mut extensions = ExtensionsRef::new(profile, &buf[offset..offset + length]);
+            while extensions.next_range()?.is_some() {}
+
+            offset += length;
+            Some((profile, offset - length..offset))
+        } else {
+            None
+        };
+
+        let mut end = buf.len();
+        if (buf[0] >> PADDING_SHIFT & PADDING_MASK) > 0 {
+            if end == offset || buf[end - 1] as usize > end - offset {
+                return Err(Error::ErrShortPacket);
+            }
+            end -= buf[end - 1] as usize;
+        }
+
+        Ok(Layout {
+            extension,
+            payload: offset..end,
+        })
+    }
+
+    /// extension_range returns the range of the data of the extension id in buf.
+    fn extension_range(&self, buf: &[u8], id: u8) -> Option<Range<usize>> {
+        let (profile, range) = self.extension.clone()?;
+        let mut extensions = ExtensionsRef::new(profile, &buf[range.clone()]);
+        while let Ok(Some((ext_id, ext_range))) = extensions.next_range() {
+            if ext_id == id {
+                return Some(range.start + ext_range.start..range.start + ext_range.end);
+            }
+        }
+        None
+    }
+}
+
+/// ExtensionsRef iterates over the id and data of the header extensions of an
+/// [`RtpPacketRef`]. Packets without RFC 8285 extensions have a single one with id 0.
+#[derive(Debug, Clone)]
+pub struct ExtensionsRef<'a> {
+    profile: u16,
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ExtensionsRef<'a> {
+    fn new(profile: u16, data: &'a [u8]) -> Self {
+        ExtensionsRef {
+            profile,
+            data,
+            offset: 0,
+        }
+    }
+
+    /// next_range returns the id and range of the next extension in data.
+    fn next_range(&mut self) -> Result<Option<(u8, Range<usize>)>> {
+        let data = self.data;
+        match self.profile {
+            // RFC 8285 RTP One Byte Header Extension
+            EXTENSION_PROFILE_ONE_BYTE => {
+                while self.offset < data.len() && data[self.offset] == 0x00 {
+                    self.offset += 1;
+                }
+                if self.offset == data.len() || data[self.offset] >> 4 == EXTENSION_ID_RESERVED {
+                    self.offset = data.len();
+                    return Ok(None);
+                }
+
+                let id = data[self.offset] >> 4;
+                let start = self.offset + 1;
+                let end = start + (data[self.offset] & 0x0F) as usize + 1;
+                if end > data.len() {
+                    return Err(Error::ErrHeaderSizeInsufficientForExtension);
+                }
+                self.offset = end;
+                Ok(Some((id, start..end)))
+            }
+            // RFC 8285 RTP Two Byte Header Extension
+            profile if is_two_byte_extension_profile(profile) => {
+                while self.offset < data.len() && data[self.offset] == 0x00 {
+                    self.offset += 1;
+                }
+                if self.offset == data.len() {
+                    return Ok(None);
+                }
+                if self.offset + 2 > data.len() {
+                    return Err(Error::ErrHeaderSizeInsufficientForExtension);
+                }
+
+                let id = data[self.offset];
+                let start = self.offset + 2;
+                let end = start + data[self.offset + 1] as usize;
+                if end > data.len() {
+                    return Err(Error::ErrHeaderSizeInsufficientForExtension);
+                }
+                self.offset = end;
+                Ok(Some((id, start..end)))
+            }
+            // RFC3550 Extension
+            _ => {
+                // The offset is past the data once the extension was returned.
+                if self.offset > data.len() {
+                    return Ok(None);
+                }
+                self.offset = data.len() + 1;
+                Ok(Some((0, 0..data.len())))
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for ExtensionsRef<'a> {
+    type Item = (u8, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_range() {
+            Ok(Some((id, range))) => Some((id, &self.data[range])),
+            _ => None,
+        }
+    }
+}
+
+/// RtpPacketRef is a view of an RTP packet in a buffer, whose fields are read from the buffer
+/// when needed. Parsing it checks the packet like [`Packet::unmarshal`], but doesn't copy or
+/// allocate anything, which suits forwarding packets mostly unchanged.
+#[derive(Debug, Clone)]
+pub struct RtpPacketRef<'a> {
+    buf: &'a [u8],
+    layout: Layout,
+}
+
+impl<'a> RtpPacketRef<'a> {
+    /// parse returns a view of the RTP packet in buf.
+    pub fn parse(buf: &'a [u8]) -> Result<Self> {
+        let layout = Layout::parse(buf)?;
+        Ok(RtpPacketRef { buf, layout })
+    }
+
+    pub fn version(&self) -> u8 {
+        self.buf[0] >> VERSION_SHIFT & VERSION_MASK
+    }
+
+    pub fn padding(&self) -> bool {
+        (self.buf[0] >> PADDING_SHIFT & PADDING_MASK) > 0
+    }
+
+    pub fn extension(&self) -> bool {
+        self.layout.extension.is_some()
+    }
+
+    pub fn marker(&self) -> bool {
+        (self.buf[1] >> MARKER_SHIFT & MARKER_MASK) > 0
+    }
+
+    pub fn payload_type(&self) -> u8 {
+        self.buf[1] & PT_MASK
+    }
+
+    pub fn sequence_number(&self) -> u16 {
+        u16::from_be_bytes([self.buf[SEQ_NUM_OFFSET], self.buf[SEQ_NUM_OFFSET + 1]])
+    }
+
+    pub fn timestamp(&self) -> u32 {
+        read_u32(self.buf, TIMESTAMP_OFFSET)
+    }
+
+    pub fn ssrc(&self) -> u32 {
+        read_u32(self.buf, SSRC_OFFSET)
+    }
+
+    /// csrc returns the contributing sources of the packet.
+    pub fn csrc(&self) -> impl Iterator<Item = u32> + 'a {
+        let buf = self.buf;
+        let cc = (buf[0] & CC_MASK) as usize;
+        (0..cc).map(move |i| read_u32(buf, CSRC_OFFSET + i * CSRC_LENGTH))
+    }
+
+    /// extension_profile returns the profile of the header extensions, if the packet has any.
+    pub fn extension_profile(&self) -> Option<u16> {
+        self.layout.extension.as_ref().map(|(profile, _)| *profile)
+    }
+
+    /// extensions returns the header extensions of the packet.
+    pub fn extensions(&self) -> ExtensionsRef<'a> {
+        match &self.layout.extension {
+            Some((profile, range)) => ExtensionsRef::new(*profile, &self.buf[range.clone()]),
+            None => ExtensionsRef::new(EXTENSION_PROFILE_ONE_BYTE, &[]),
+        }
+    }
+
+    /// get_extension returns the data of the header extension id.
+    pub fn get_extension(&self, id: u8) -> Option<&'a [u8]> {
+        let buf = self.buf;
+        self.layout
+            .extension_range(buf, id)
+            .map(|range| &buf[range])
+    }
+
+    /// header_size returns the size of the header, including CSRCs and extensions.
+    pub fn header_size(&self) -> usize {
+        self.layout.payload.start
+    }
+
+    /// payload returns the payload of the packet, without padding.
+    pub fn payload(&self) -> &'a [u8] {
+        &self.buf[self.layout.payload.clone()]
+    }
+
+    /// padding_size returns the size of the padding, 0 if the packet isn't padded.
+    pub fn padding_size(&self) -> u8 {
+        (self.buf.len() - self.layout.payload.end) as u8
+    }
+
+    /// as_bytes returns the whole packet.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.buf
+    }
+
+    /// to_packet unmarshals the packet, copying its parts.
+    pub fn to_packet(&self) -> Result<Packet> {
+        Ok(Packet::unmarshal(&mut &self.buf[..])?)
+    }
+}
+
+/// RtpPacketMut is a view of an RTP packet in a mutable buffer, which rewrites the fields of
+/// the header in place, e.g. to forward a packet with the SSRC, sequence number and timestamp
+/// of another stream.
+#[derive(Debug)]
+pub struct RtpPacketMut<'a> {
+    buf: &'a mut [u8],
+    layout: Layout,
+}
+
+impl<'a> RtpPacketMut<'a> {
+    /// parse returns a view of the RTP packet in buf.
+    pub fn parse(buf: &'a mut [u8]) -> Result<Self> {
+        let layout = Layout::parse(buf)?;
+        Ok(RtpPacketMut { buf, layout })
+    }
+
+    /// as_packet_ref returns a view reading the fields of the packet.
+    pub fn as_packet_ref(&self) -> RtpPacketRef<'_> {
+        RtpPacketRef {
+            buf: self.buf,
+            layout: self.layout.clone(),
+        }
+    }
+
+    pub fn set_marker(&mut self, marker: bool) {
+        self.buf[1] = self.buf[1] & PT_MASK | (marker as u8) << MARKER_SHIFT;
+    }
+
+    pub fn set_payload_type(&mut self, payload_type: u8) {
+        self.buf[1] = self.buf[1] & !PT_MASK | payload_type & PT_MASK;
+    }
+
+    pub fn set_sequence_number(&mut self, sequence_number: u16) {
+        self.buf[SEQ_NUM_OFFSET..SEQ_NUM_OFFSET + SEQ_NUM_LENGTH]
+            .copy_from_slice(&sequence_number.to_be_bytes());
+    }
+
+    pub fn set_timestamp(&mut self, timestamp: u32) {
+        self.buf[TIMESTAMP_OFFSET..TIMESTAMP_OFFSET + TIMESTAMP_LENGTH]
+            .copy_from_slice(&timestamp.to_be_bytes());
+    }
+
+    pub fn set_ssrc(&mut self, ssrc: u32) {
+        self.buf[SSRC_OFFSET..SSRC_OFFSET + SSRC_LENGTH].copy_from_slice(&ssrc.to_be_bytes());
+    }
+
+    /// extension_mut returns the data of the header extension id, to be rewritten in place.
+    pub fn extension_mut(&mut self, id: u8) -> Option<&mut [u8]> {
+        let range = self.layout.extension_range(self.buf, id)?;
+        Some(&mut self.buf[range])
+    }
+
+    /// payload_mut returns the payload of the packet, without padding.
+    pub fn payload_mut(&mut self) -> &mut [u8] {
+        &mut self.buf[self.layout.payload.clone()]
+    }
+}
+
+fn read_u32(buf: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        buf[offset],
+        buf[offset + 1],
+        buf[offset + 2],
+        buf[offset + 3],
+    ])
+}
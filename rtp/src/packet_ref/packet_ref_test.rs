@@ -0,0 +1,179 @@
+use bytes::{Bytes, BytesMut};
+use util::marshal::{Marshal, MarshalSize};
+
+use super::*;
+
+fn packet(extension_profile: u16) -> Packet {
+    let mut packet = Packet {
+        header: Header {
+            version: 2,
+            padding: true,
+            marker: true,
+            payload_type: 96,
+            sequence_number: 27023,
+            timestamp: 3653407706,
+            ssrc: 476325762,
+            csrc: vec![1, 2],
+            extension: true,
+            extension_profile,
+            ..Default::default()
+        },
+        payload: Bytes::from_static(&[0x98, 0x36, 0xbe, 0x88, 0x9e]),
+        padding_size: 3,
+    };
+    packet
+        .header
+        .set_extension(1, Bytes::from_static(&[0xAA]))
+        .unwrap();
+    packet
+        .header
+        .set_extension(2, Bytes::from_static(&[0xBB, 0xCC]))
+        .unwrap();
+    packet
+}
+
+#[test]
+fn test_rtp_packet_ref() -> Result<()> {
+    for profile in [EXTENSION_PROFILE_ONE_BYTE, EXTENSION_PROFILE_TWO_BYTE] {
+        let raw = packet(profile).marshal()?;
+        let expected = Packet::unmarshal(&mut raw.clone())?;
+
+        let packet = RtpPacketRef::parse(&raw)?;
+        assert_eq!(packet.version(), 2);
+        assert!(packet.padding());
+        assert!(packet.extension());
+        assert!(packet.marker());
+        assert_eq!(packet.payload_type(), 96);
+        assert_eq!(packet.sequence_number(), 27023);
+        assert_eq!(packet.timestamp(), 3653407706);
+        assert_eq!(packet.ssrc(), 476325762);
+        assert_eq!(packet.csrc().collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(packet.extension_profile(), Some(profile));
+        assert_eq!(
+            packet.extensions().collect::<Vec<_>>(),
+            vec![(1, &[0xAA][..]), (2, &[0xBB, 0xCC][..])]
+        );
+        assert_eq!(packet.get_extension(2), Some(&[0xBB, 0xCC][..]));
+        assert_eq!(packet.get_extension(3), None);
+        assert_eq!(packet.header_size(), expected.header.marshal_size());
+        assert_eq!(packet.payload(), &expected.payload[..]);
+        assert_eq!(packet.padding_size(), 3);
+        assert_eq!(packet.as_bytes(), &raw[..]);
+        assert_eq!(packet.to_packet()?, expected);
+    }
+
+    // A packet without extensions and padding.
+    let raw = Bytes::from_static(&[
+        0x80, 0x60, 0x69, 0x8f, 0xd9, 0xc2, 0x93, 0xda, 0x1c, 0x64, 0x27, 0x82, 0x98, 0x36,
+    ]);
+    let packet = RtpPacketRef::parse(&raw)?;
+    assert!(!packet.extension());
+    assert_eq!(packet.extension_profile(), None);
+    assert_eq!(packet.extensions().count(), 0);
+    assert_eq!(packet.payload(), &[0x98, 0x36]);
+    assert_eq!(packet.padding_size(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_rtp_packet_ref_rfc3550_extension() -> Result<()> {
+    let raw = Bytes::from_static(&[
+        0x90, 0x60, 0x69, 0x8f, 0xd9, 0xc2, 0x93, 0xda, 0x1c, 0x64, 0x27, 0x82, 0x00, 0x01, 0x00,
+        0x01, 0xAA, 0xBB, 0xCC, 0xDD, 0x98, 0x36,
+    ]);
+    let packet = RtpPacketRef::parse(&raw)?;
+    assert_eq!(
+        packet.extensions().collect::<Vec<_>>(),
+        vec![(0, &[0xAA, 0xBB, 0xCC, 0xDD][..])]
+    );
+    assert_eq!(packet.payload(), &[0x98, 0x36]);
+
+    Ok(())
+}
+
+#[test]
+fn test_rtp_packet_mut() -> Result<()> {
+    let mut raw = BytesMut::from(&packet(EXTENSION_PROFILE_ONE_BYTE).marshal()?[..]);
+
+    let mut packet = RtpPacketMut::parse(&mut raw)?;
+    packet.set_marker(false);
+    packet.set_payload_type(111);
+    packet.set_sequence_number(1);
+    packet.set_timestamp(2);
+    packet.set_ssrc(3);
+    packet
+        .extension_mut(2)
+        .expect("extension should be set")
+        .copy_from_slice(&[0x11, 0x22]);
+    packet.payload_mut()[0] = 0x00;
+    assert_eq!(packet.as_packet_ref().sequence_number(), 1);
+
+    let mut expected = self::packet(EXTENSION_PROFILE_ONE_BYTE);
+    expected.header.marker = false;
+    expected.header.payload_type = 111;
+    expected.header.sequence_number = 1;
+    expected.header.timestamp = 2;
+    expected.header.ssrc = 3;
+    expected
+        .header
+        .set_extension(2, Bytes::from_static(&[0x11, 0x22]))?;
+    expected.payload = Bytes::from_static(&[0x00, 0x36, 0xbe, 0x88, 0x9e]);
+    assert_eq!(Packet::unmarshal(&mut raw.freeze())?, expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_rtp_packet_ref_errors() -> Result<()> {
+    let raw = packet(EXTENSION_PROFILE_ONE_BYTE).marshal()?;
+    let tests = vec![
+        ("Empty", Bytes::new(), Error::ErrHeaderSizeInsufficient),
+        (
+            "ShortCsrc",
+            raw.slice(..16),
+            Error::ErrHeaderSizeInsufficient,
+        ),
+        (
+            "ShortExtensionHeader",
+            raw.slice(..22),
+            Error::ErrHeaderSizeInsufficientForExtension,
+        ),
+        (
+            "ShortExtension",
+            raw.slice(..26),
+            Error::ErrHeaderSizeInsufficientForExtension,
+        ),
+        (
+            "ExtensionOverflow",
+            {
+                let mut raw = BytesMut::from(&raw[..]);
+                raw[24] = 0x2F;
+                raw.freeze()
+            },
+            Error::ErrHeaderSizeInsufficientForExtension,
+        ),
+        (
+            "ShortPadding",
+            {
+                let mut raw = BytesMut::from(&raw[..]);
+                let last = raw.len() - 1;
+                raw[last] = 100;
+                raw.freeze()
+            },
+            Error::ErrShortPacket,
+        ),
+    ];
+
+    for (name, raw, expected) in tests {
+        let result = RtpPacketRef::parse(&raw);
+        assert_eq!(
+            expected,
+            result.expect_err("packet should be invalid"),
+            "{name}"
+        );
+        assert!(Packet::unmarshal(&mut raw.clone()).is_err(), "{name}");
+    }
+
+    Ok(())
+}
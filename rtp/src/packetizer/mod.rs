@@ -14,6 +14,10 @@ use crate::header::*;
 use crate::packet::*;
 use crate::sequence::*;
 
+// Size of the header extension added for abs-send-time, the extension header and a one byte
+// extension.
+const ABS_SEND_TIME_OVERHEAD: usize = 4 + 1 + ABS_SEND_TIME_EXTENSION_SIZE;
+
 /// Payloader payloads a byte array for use as rtp.Packet payloads
 pub trait Payloader: fmt::Debug {
     fn payload(&mut self, mtu: usize, b: &Bytes) -> Result<Vec<Bytes>>;
@@ -31,6 +35,12 @@ pub trait Packetizer: fmt::Debug {
     fn enable_abs_send_time(&mut self, value: u8);
     fn packetize(&mut self, payload: &Bytes, samples: u32) -> Result<Vec<Packet>>;
     fn skip_samples(&mut self, skipped_samples: u32);
+    /// set_mtu changes the largest size of the packets, e.g. after the network path changed.
+    fn set_mtu(&mut self, mtu: usize);
+    fn mtu(&self) -> usize;
+    /// set_overhead reserves bytes of each packet for what is added after packetizing it,
+    /// e.g. header extensions or the SRTP authentication tag.
+    fn set_overhead(&mut self, overhead: usize);
     fn clone_to(&self) -> Box<dyn Packetizer + Send + Sync>;
 }
 
@@ -62,6 +72,7 @@ pub type FnTimeGen = Arc<dyn (Fn() -> SystemTime) + Send + Sync>;
 #[derive(Clone)]
 pub(crate) struct PacketizerImpl {
     pub(crate) mtu: usize,
+    pub(crate) overhead: usize,
     pub(crate) payload_type: u8,
     pub(crate) ssrc: u32,
     pub(crate) payloader: Box<dyn Payloader + Send + Sync>,
@@ -76,6 +87,7 @@ impl fmt::Debug for PacketizerImpl {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("PacketizerImpl")
             .field("mtu", &self.mtu)
+            .field("overhead", &self.overhead)
             .field("payload_type", &self.payload_type)
             .field("ssrc", &self.ssrc)
             .field("timestamp", &self.timestamp)
@@ -95,6 +107,7 @@ pub fn new_packetizer(
 ) -> impl Packetizer {
     PacketizerImpl {
         mtu,
+        overhead: 0,
         payload_type,
         ssrc,
         payloader,
@@ -112,7 +125,13 @@ impl Packetizer for PacketizerImpl {
     }
 
     fn packetize(&mut self, payload: &Bytes, samples: u32) -> Result<Vec<Packet>> {
-        let payloads = self.payloader.payload(self.mtu - 12, payload)?;
+        let mut overhead = CSRC_OFFSET + self.overhead;
+        if self.abs_send_time != 0 {
+            overhead += ABS_SEND_TIME_OVERHEAD;
+        }
+        let payloads = self
+            .payloader
+            .payload(self.mtu.saturating_sub(overhead), payload)?;
         let payloads_len = payloads.len();
         let mut packets = Vec::with_capacity(payloads_len);
         for (i, payload) in payloads.into_iter().enumerate() {
@@ -129,6 +148,7 @@ impl Packetizer for PacketizerImpl {
                     ..Default::default()
                 },
                 payload,
+                ..Default::default()
             });
         }
 
@@ -159,6 +179,18 @@ impl Packetizer for PacketizerImpl {
         self.timestamp = self.timestamp.wrapping_add(skipped_samples);
     }
 
+    fn set_mtu(&mut self, mtu: usize) {
+        self.mtu = mtu;
+    }
+
+    fn mtu(&self) -> usize {
+        self.mtu
+    }
+
+    fn set_overhead(&mut self, overhead: usize) {
+        self.overhead = overhead;
+    }
+
     fn clone_to(&self) -> Box<dyn Packetizer + Send + Sync> {
         Box::new(self.clone())
     }
@@ -48,6 +48,7 @@ fn test_packetizer_abs_send_time() -> Result<()> {
     //use the G722 payloader here, because it's very simple and all 0s is valid G722 data.
     let mut pktizer = PacketizerImpl {
         mtu: 100,
+        overhead: 0,
         payload_type: 98,
         ssrc: 0x1234ABCD,
         payloader: g722,
@@ -81,6 +82,7 @@ fn test_packetizer_abs_send_time() -> Result<()> {
             extensions_padding: 0,
         },
         payload: Bytes::from_static(&[0x11, 0x12, 0x13, 0x14]),
+        ..Default::default()
     };
 
     if packets.len() != 1 {
@@ -108,3 +110,38 @@ fn test_packetizer_timestamp_rollover_does_not_panic() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_packetizer_mtu() -> Result<()> {
+    let g722 = Box::new(g7xx::G722Payloader {});
+    let seq = Box::new(new_random_sequencer());
+
+    let payload = Bytes::from_static(&[0; 300]);
+    let mut packetizer = new_packetizer(112, 98, 0x1234ABCD, g722, seq, 90000);
+    let sizes =
+        |packets: Vec<Packet>| -> Vec<usize> { packets.iter().map(|p| p.marshal_size()).collect() };
+    assert_eq!(
+        sizes(packetizer.packetize(&payload, 10)?),
+        vec![112, 112, 112]
+    );
+
+    packetizer.set_mtu(212);
+    assert_eq!(packetizer.mtu(), 212);
+    assert_eq!(sizes(packetizer.packetize(&payload, 10)?), vec![212, 112]);
+
+    // The overhead is left free in every packet.
+    packetizer.set_overhead(10);
+    assert_eq!(sizes(packetizer.packetize(&payload, 10)?), vec![202, 122]);
+
+    packetizer.enable_abs_send_time(1);
+    let packets = packetizer.packetize(&payload, 10)?;
+    assert_eq!(packets.len(), 2);
+    // Each packet can take the extension, not only the last one which gets it.
+    assert_eq!(packets[0].marshal_size() + 10 + ABS_SEND_TIME_OVERHEAD, 212);
+
+    // Nothing fits in a packet smaller than the header.
+    packetizer.set_mtu(10);
+    assert!(packetizer.packetize(&payload, 10)?.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod padding_test;
+
+use bytes::Bytes;
+
+use crate::header::Header;
+use crate::packet::Packet;
+use crate::sequence::Sequencer;
+
+/// Largest padding of a packet, as its size is a single byte.
+pub const PADDING_MAX_SIZE: usize = 255;
+
+/// PaddingGenerator generates padding-only packets for a stream, e.g. to probe the bandwidth
+/// or keep the stream alive while no media is sent.
+///
+/// Padding is sent on the media stream itself, sharing its sequence numbers, or on the RTX
+/// stream used for retransmissions, with its SSRC, payload type and sequencer. Either way the
+/// packets take the timestamp of the latest media packet, so the receiver doesn't mistake them
+/// for a new frame.
+#[derive(Debug, Clone)]
+pub struct PaddingGenerator {
+    ssrc: u32,
+    payload_type: u8,
+    sequencer: Box<dyn Sequencer + Send + Sync>,
+    timestamp: u32,
+}
+
+impl PaddingGenerator {
+    /// new returns a generator of padding for the stream of ssrc and payload_type. sequencer is
+    /// shared with the packets of the stream, e.g. a clone of the one of its packetizer.
+    pub fn new(ssrc: u32, payload_type: u8, sequencer: Box<dyn Sequencer + Send + Sync>) -> Self {
+        PaddingGenerator {
+            ssrc,
+            payload_type,
+            sequencer,
+            timestamp: 0,
+        }
+    }
+
+    /// on_media records a media packet sent, whose timestamp the padding packets after it
+    /// take over.
+    pub fn on_media(&mut self, packet: &Packet) {
+        self.timestamp = packet.header.timestamp;
+    }
+
+    /// padding_packet returns a packet with padding_size bytes of padding and no payload.
+    pub fn padding_packet(&mut self, padding_size: u8) -> Packet {
+        Packet {
+            header: Header {
+                version: 2,
+                padding: true,
+                payload_type: self.payload_type,
+                sequence_number: self.sequencer.next_sequence_number(),
+                timestamp: self.timestamp,
+                ssrc: self.ssrc,
+                ..Default::default()
+            },
+            payload: Bytes::new(),
+            padding_size,
+        }
+    }
+
+    /// generate returns as few padding packets as carry size bytes of padding.
+    pub fn generate(&mut self, size: usize) -> Vec<Packet> {
+        let mut packets = Vec::with_capacity(size.div_ceil(PADDING_MAX_SIZE));
+        let mut remaining = size;
+        while remaining > 0 {
+            let padding_size = remaining.min(PADDING_MAX_SIZE);
+            packets.push(self.padding_packet(padding_size as u8));
+            remaining -= padding_size;
+        }
+        packets
+    }
+}
@@ -0,0 +1,45 @@
+use util::marshal::{Marshal, MarshalSize, Unmarshal};
+
+use super::*;
+use crate::error::Result;
+use crate::sequence::new_fixed_sequencer;
+
+#[test]
+fn test_padding_generator() -> Result<()> {
+    let sequencer: Box<dyn Sequencer + Send + Sync> = Box::new(new_fixed_sequencer(65534));
+    let mut generator = PaddingGenerator::new(0x1234, 96, sequencer.clone());
+    assert_eq!(sequencer.next_sequence_number(), 65534);
+
+    generator.on_media(&Packet {
+        header: Header {
+            timestamp: 90000,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    let packets = generator.generate(600);
+    assert_eq!(
+        packets
+            .iter()
+            .map(|p| (p.header.sequence_number, p.padding_size))
+            .collect::<Vec<_>>(),
+        vec![(65535, 255), (0, 255), (1, 90)]
+    );
+    assert_eq!(sequencer.next_sequence_number(), 2);
+
+    for packet in &packets {
+        assert_eq!(packet.header.ssrc, 0x1234);
+        assert_eq!(packet.header.payload_type, 96);
+        assert_eq!(packet.header.timestamp, 90000);
+
+        let raw = packet.marshal().unwrap();
+        assert_eq!(raw.len(), packet.marshal_size());
+        assert_eq!(raw.len(), 12 + packet.padding_size as usize);
+        assert_eq!(raw[raw.len() - 1], packet.padding_size);
+        assert_eq!(&Packet::unmarshal(&mut raw.clone()).unwrap(), packet);
+    }
+
+    assert!(generator.generate(0).is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,258 @@
+#[cfg(test)]
+mod sequence_test;
+
+use std::fmt;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use portable_atomic::{AtomicU16, AtomicU64};
+
+/// Sequencer generates sequential sequence numbers for building RTP packets
+pub trait Sequencer: fmt::Debug {
+    fn next_sequence_number(&self) -> u16;
+    fn roll_over_count(&self) -> u64;
+    fn clone_to(&self) -> Box<dyn Sequencer + Send + Sync>;
+}
+
+impl Clone for Box<dyn Sequencer + Send + Sync> {
+    fn clone(&self) -> Box<dyn Sequencer + Send + Sync> {
+        self.clone_to()
+    }
+}
+
+/// NewRandomSequencer returns a new sequencer starting from a random sequence
+/// number
+pub fn new_random_sequencer() -> impl Sequencer {
+    let c = Counters {
+        sequence_number: Arc::new(AtomicU16::new(rand::random::<u16>())),
+        roll_over_count: Arc::new(AtomicU64::new(0)),
+    };
+    SequencerImpl(c)
+}
+
+/// NewFixedSequencer returns a new sequencer starting from a specific
+/// sequence number
+pub fn new_fixed_sequencer(s: u16) -> impl Sequencer {
+    let sequence_number = if s == 0 { u16::MAX } else { s - 1 };
+
+    let c = Counters {
+        sequence_number: Arc::new(AtomicU16::new(sequence_number)),
+        roll_over_count: Arc::new(AtomicU64::new(0)),
+    };
+
+    SequencerImpl(c)
+}
+
+#[derive(Debug, Clone)]
+struct SequencerImpl(Counters);
+
+#[derive(Debug, Clone)]
+struct Counters {
+    sequence_number: Arc<AtomicU16>,
+    roll_over_count: Arc<AtomicU64>,
+}
+
+impl Sequencer for SequencerImpl {
+    /// NextSequenceNumber increment and returns a new sequence number for
+    /// building RTP packets
+    fn next_sequence_number(&self) -> u16 {
+        if self.0.sequence_number.load(Ordering::SeqCst) == u16::MAX {
+            self.0.roll_over_count.fetch_add(1, Ordering::SeqCst);
+            self.0.sequence_number.store(0, Ordering::SeqCst);
+            0
+        } else {
+            self.0.sequence_number.fetch_add(1, Ordering::SeqCst) + 1
+        }
+    }
+
+    /// RollOverCount returns the amount of times the 16bit sequence number
+    /// has wrapped
+    fn roll_over_count(&self) -> u64 {
+        self.0.roll_over_count.load(Ordering::SeqCst)
+    }
+
+    fn clone_to(&self) -> Box<dyn Sequencer + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+/// Number of sequence numbers a stream may skip before it is considered a jump
+/// (RFC 3550 appendix A.1).
+pub const MAX_DROPOUT: u16 = 3000;
+/// Number of sequence numbers a packet may be late by before it is considered a jump
+/// (RFC 3550 appendix A.1).
+pub const MAX_MISORDER: u16 = 100;
+
+// Number of sequence numbers before the highest one that duplicates are detected for.
+const HISTORY_SIZE: u64 = u128::BITS as u64;
+
+/// SequenceUpdate tells how a sequence number given to [`SequenceTracker::update`]
+/// relates to the ones before it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SequenceUpdate {
+    /// The first sequence number of the stream.
+    First { extended: u64 },
+    /// A sequence number higher than all others before it, lost sequence numbers
+    /// were skipped.
+    InOrder { extended: u64, lost: u64 },
+    /// A sequence number lower than the highest one, which wasn't seen before.
+    OutOfOrder { extended: u64 },
+    /// A sequence number seen before.
+    Duplicate { extended: u64 },
+    /// A sequence number too far from the highest one, which is ignored unless the
+    /// next sequence number follows it.
+    Jump { extended: u64 },
+    /// A sequence number following a jump, the stream continues from it.
+    Restart { extended: u64 },
+}
+
+impl SequenceUpdate {
+    /// extended returns the extended sequence number of the update.
+    pub fn extended(&self) -> u64 {
+        match *self {
+            SequenceUpdate::First { extended }
+            | SequenceUpdate::InOrder { extended, .. }
+            | SequenceUpdate::OutOfOrder { extended }
+            | SequenceUpdate::Duplicate { extended }
+            | SequenceUpdate::Jump { extended }
+            | SequenceUpdate::Restart { extended } => extended,
+        }
+    }
+}
+
+/// SequenceTracker extends the 16 bit sequence numbers of a received stream to 64 bit,
+/// counting how often they rolled over, and detects duplicates and jumps.
+#[derive(Debug, Copy, Clone)]
+pub struct SequenceTracker {
+    max_dropout: u16,
+    max_misorder: u16,
+    started: bool,
+    max_extended: u64,
+    // Bit n is set if the sequence number n before max_extended was seen.
+    history: u128,
+    bad_sequence_number: Option<u16>,
+}
+
+impl Default for SequenceTracker {
+    fn default() -> Self {
+        SequenceTracker::new()
+    }
+}
+
+impl SequenceTracker {
+    /// new returns a tracker using the limits of RFC 3550, [`MAX_DROPOUT`] and
+    /// [`MAX_MISORDER`].
+    pub fn new() -> Self {
+        SequenceTracker::with_limits(MAX_DROPOUT, MAX_MISORDER)
+    }
+
+    /// with_limits returns a tracker which considers a sequence number a jump if it is more
+    /// than max_dropout ahead of or more than max_misorder behind the highest one. Limits
+    /// of half the sequence number space or more never detect jumps.
+    pub fn with_limits(max_dropout: u16, max_misorder: u16) -> Self {
+        SequenceTracker {
+            max_dropout,
+            max_misorder,
+            started: false,
+            max_extended: 0,
+            history: 0,
+            bad_sequence_number: None,
+        }
+    }
+
+    /// extend returns the extended sequence number closest to the highest one, without
+    /// recording sequence_number.
+    pub fn extend(&self, sequence_number: u16) -> u64 {
+        let cycles = self.max_extended & !(u16::MAX as u64);
+        if !self.started {
+            return cycles | sequence_number as u64;
+        }
+
+        let delta = sequence_number.wrapping_sub(self.max_extended as u16);
+        if delta <= i16::MAX as u16 {
+            self.max_extended + delta as u64
+        } else {
+            // Before the first sequence number, the stream can't be behind.
+            let behind = delta.wrapping_neg() as u64;
+            self.max_extended
+                .checked_sub(behind)
+                .unwrap_or(self.max_extended + delta as u64)
+        }
+    }
+
+    /// update records sequence_number, returning its extended sequence number and how it
+    /// relates to the sequence numbers before it.
+    pub fn update(&mut self, sequence_number: u16) -> SequenceUpdate {
+        let extended = self.extend(sequence_number);
+        if !self.started {
+            self.started = true;
+            self.max_extended = extended;
+            self.history = 1;
+            return SequenceUpdate::First { extended };
+        }
+
+        if extended > self.max_extended {
+            let delta = extended - self.max_extended;
+            if delta > self.max_dropout as u64 {
+                return self.jump(sequence_number, extended);
+            }
+
+            self.history = if delta < HISTORY_SIZE {
+                self.history << delta | 1
+            } else {
+                1
+            };
+            self.max_extended = extended;
+            self.bad_sequence_number = None;
+            SequenceUpdate::InOrder {
+                extended,
+                lost: delta - 1,
+            }
+        } else {
+            let delta = self.max_extended - extended;
+            if delta > self.max_misorder as u64 {
+                return self.jump(sequence_number, extended);
+            }
+
+            if delta >= HISTORY_SIZE {
+                return SequenceUpdate::OutOfOrder { extended };
+            }
+            let bit = 1u128 << delta;
+            if self.history & bit != 0 {
+                SequenceUpdate::Duplicate { extended }
+            } else {
+                self.history |= bit;
+                SequenceUpdate::OutOfOrder { extended }
+            }
+        }
+    }
+
+    fn jump(&mut self, sequence_number: u16, extended: u64) -> SequenceUpdate {
+        if self.bad_sequence_number != Some(sequence_number) {
+            self.bad_sequence_number = Some(sequence_number.wrapping_add(1));
+            return SequenceUpdate::Jump { extended };
+        }
+
+        // The sequence number of the jump was seen just before.
+        self.max_extended = extended;
+        self.history = 0b11;
+        self.bad_sequence_number = None;
+        SequenceUpdate::Restart { extended }
+    }
+
+    /// max_extended returns the highest extended sequence number, if any was recorded.
+    pub fn max_extended(&self) -> Option<u64> {
+        self.started.then_some(self.max_extended)
+    }
+
+    /// roll_over_count returns how often the highest sequence number rolled over.
+    pub fn roll_over_count(&self) -> u64 {
+        self.max_extended >> 16
+    }
+
+    /// set_roll_over_count replaces the roll over count, e.g. the one of a stream joined
+    /// in progress.
+    pub fn set_roll_over_count(&mut self, roll_over_count: u64) {
+        self.max_extended = roll_over_count << 16 | (self.max_extended & u16::MAX as u64);
+    }
+}
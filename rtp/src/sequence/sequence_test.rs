@@ -0,0 +1,110 @@
+use super::*;
+
+#[test]
+fn test_sequencer_roll_over() {
+    let sequencer = new_fixed_sequencer(u16::MAX);
+    assert_eq!(sequencer.next_sequence_number(), u16::MAX);
+    assert_eq!(sequencer.roll_over_count(), 0);
+    assert_eq!(sequencer.next_sequence_number(), 0);
+    assert_eq!(sequencer.roll_over_count(), 1);
+}
+
+#[test]
+fn test_sequence_tracker() {
+    let mut tracker = SequenceTracker::new();
+    assert_eq!(tracker.max_extended(), None);
+
+    let tests = vec![
+        (0xfffe, SequenceUpdate::First { extended: 0xfffe }),
+        (
+            0xffff,
+            SequenceUpdate::InOrder {
+                extended: 0xffff,
+                lost: 0,
+            },
+        ),
+        (
+            2,
+            SequenceUpdate::InOrder {
+                extended: 0x10002,
+                lost: 2,
+            },
+        ),
+        (0, SequenceUpdate::OutOfOrder { extended: 0x10000 }),
+        (0xffff, SequenceUpdate::Duplicate { extended: 0xffff }),
+        (2, SequenceUpdate::Duplicate { extended: 0x10002 }),
+        (1, SequenceUpdate::OutOfOrder { extended: 0x10001 }),
+    ];
+    for (sequence_number, expected) in tests {
+        assert_eq!(
+            tracker.update(sequence_number),
+            expected,
+            "sequence number {sequence_number}"
+        );
+    }
+    assert_eq!(tracker.max_extended(), Some(0x10002));
+    assert_eq!(tracker.roll_over_count(), 1);
+}
+
+#[test]
+fn test_sequence_tracker_jump() {
+    let mut tracker = SequenceTracker::new();
+    tracker.update(10);
+
+    // A single packet far ahead is ignored, two in a row restart the stream.
+    assert_eq!(
+        tracker.update(20000),
+        SequenceUpdate::Jump { extended: 20000 }
+    );
+    assert_eq!(
+        tracker.update(11),
+        SequenceUpdate::InOrder {
+            extended: 11,
+            lost: 0
+        }
+    );
+    assert_eq!(
+        tracker.update(30000),
+        SequenceUpdate::Jump { extended: 30000 }
+    );
+    assert_eq!(
+        tracker.update(30001),
+        SequenceUpdate::Restart { extended: 30001 }
+    );
+    assert_eq!(
+        tracker.update(30000),
+        SequenceUpdate::Duplicate { extended: 30000 }
+    );
+    assert_eq!(tracker.max_extended(), Some(30001));
+
+    // The stream can't go back before its first packet.
+    let mut tracker = SequenceTracker::new();
+    tracker.update(5);
+    assert_eq!(
+        tracker.update(0xfffa),
+        SequenceUpdate::Jump { extended: 0xfffa }
+    );
+}
+
+#[test]
+fn test_sequence_tracker_extend() {
+    let mut tracker = SequenceTracker::with_limits(u16::MAX, u16::MAX);
+    tracker.set_roll_over_count(3);
+    assert_eq!(tracker.extend(7), 3 << 16 | 7);
+    tracker.update(0xfff0);
+
+    assert_eq!(tracker.extend(0x10), 4 << 16 | 0x10);
+    assert_eq!(tracker.extend(0x7ff0), 3 << 16 | 0x7ff0);
+    assert_eq!(tracker.extend(0x7fef), 4 << 16 | 0x7fef);
+    assert_eq!(tracker.max_extended(), Some(3 << 16 | 0xfff0));
+
+    // Without limits, a packet half the sequence numbers ahead is in order.
+    assert_eq!(
+        tracker.update(0x7fef),
+        SequenceUpdate::InOrder {
+            extended: 4 << 16 | 0x7fef,
+            lost: 0x7ffe
+        }
+    );
+    assert_eq!(tracker.roll_over_count(), 4);
+}
@@ -1,5 +1,6 @@
 use std::net::Shutdown;
 use std::sync::Arc;
+use std::time::Duration;
 
 use bytes::Bytes;
 use clap::{App, AppSettings, Arg};
@@ -8,6 +9,7 @@ use tokio::signal;
 use tokio::sync::mpsc;
 use webrtc_sctp::association::*;
 use webrtc_sctp::chunk::chunk_payload_data::PayloadProtocolIdentifier;
+use webrtc_sctp::congestion::CongestionControlAlgorithm;
 use webrtc_sctp::stream::*;
 use webrtc_sctp::Error;
 
@@ -67,6 +69,19 @@ async fn main() -> Result<(), Error> {
         max_receive_buffer_size: 0,
         max_message_size: 0,
         name: "client".to_owned(),
+        stream_scheduler: StreamScheduler::default(),
+        congestion_control: CongestionControlAlgorithm::default(),
+        sack_delay: Duration::ZERO,
+        packets_per_sack: 0,
+        immediate_sack: false,
+        zero_checksum: false,
+        max_num_outbound_streams: 0,
+        max_num_inbound_streams: 0,
+        heartbeat_interval: Duration::ZERO,
+        path_max_retransmits: 0,
+        association_max_retransmits: 0,
+        ecn: false,
+        max_send_buffer_size: 0,
     };
     let a = Association::client(config).await?;
     println!("created a client");
@@ -1,13 +1,17 @@
 #[cfg(test)]
 mod association_internal_test;
 
-use async_trait::async_trait;
 use portable_atomic::AtomicBool;
 
 use super::*;
+use crate::param::param_add_streams_request::ParamAddStreamsRequest;
+use crate::param::param_ecn_capable::ParamEcnCapable;
 use crate::param::param_forward_tsn_supported::ParamForwardTsnSupported;
+use crate::param::param_incoming_reset_request::ParamIncomingResetRequest;
+use crate::param::param_ssn_tsn_reset_request::ParamSsnTsnResetRequest;
 use crate::param::param_type::ParamType;
 use crate::param::param_unrecognized::ParamUnrecognized;
+use crate::param::param_zero_checksum::{ParamZeroChecksumAcceptable, ZERO_CHECKSUM_EDMID_DTLS};
 
 #[derive(Default)]
 pub struct AssociationInternal {
@@ -16,7 +20,7 @@ pub struct AssociationInternal {
     pub(crate) max_message_size: Arc<AtomicU32>,
     pub(crate) inflight_queue_length: Arc<AtomicUsize>,
     pub(crate) will_send_shutdown: Arc<AtomicBool>,
-    awake_write_loop_ch: Option<Arc<mpsc::Sender<()>>>,
+    awake_write_loop: Option<AwakeWriteLoopFn>,
 
     peer_verification_tag: u32,
     pub(crate) my_verification_tag: u32,
@@ -35,6 +39,11 @@ pub struct AssociationInternal {
     my_next_rsn: u32,
     reconfigs: HashMap<u32, ChunkReconfig>,
     reconfig_requests: HashMap<u32, ParamOutgoingResetRequest>,
+    /// Our Add Streams requests waiting for the answer of the peer.
+    add_streams_requests: HashMap<u32, ParamAddStreamsRequest>,
+    /// Answers to the latest Add Streams requests of the peer, to repeat
+    /// them if a request is retransmitted.
+    add_streams_responses: HashMap<u32, ReconfigResult>,
 
     // Non-RFC internal data
     source_port: u16,
@@ -45,30 +54,42 @@ pub struct AssociationInternal {
     payload_queue: PayloadQueue,
     inflight_queue: PayloadQueue,
     pending_queue: Arc<PendingQueue>,
+    pub(crate) send_buffer: Arc<SendBuffer>,
     control_queue: ControlQueue,
     pub(crate) mtu: u32,
     max_payload_size: u32, // max DATA chunk payload size
     cumulative_tsn_ack_point: u32,
     advanced_peer_tsn_ack_point: u32,
     use_forward_tsn: bool,
+    use_interleaving: bool,
 
     // Congestion control parameters
     pub(crate) max_receive_buffer_size: u32,
-    pub(crate) cwnd: u32,     // my congestion window size
-    rwnd: u32,                // calculated peer's receiver windows size
-    pub(crate) ssthresh: u32, // slow start threshold
-    partial_bytes_acked: u32,
+    pub(crate) congestion_control: Box<dyn CongestionControl>,
+    rwnd: u32, // calculated peer's receiver windows size
     pub(crate) in_fast_recovery: bool,
     fast_recover_exit_point: u32,
 
     // RTX & Ack timer
     pub(crate) rto_mgr: RtoManager,
-    pub(crate) t1init: Option<RtxTimer<AssociationInternal>>,
-    pub(crate) t1cookie: Option<RtxTimer<AssociationInternal>>,
-    pub(crate) t2shutdown: Option<RtxTimer<AssociationInternal>>,
-    pub(crate) t3rtx: Option<RtxTimer<AssociationInternal>>,
-    pub(crate) treconfig: Option<RtxTimer<AssociationInternal>>,
-    pub(crate) ack_timer: Option<AckTimer<AssociationInternal>>,
+    pub(crate) t1init: RtxTimer,
+    pub(crate) t1cookie: RtxTimer,
+    pub(crate) t2shutdown: RtxTimer,
+    pub(crate) t3rtx: RtxTimer,
+    pub(crate) treconfig: RtxTimer,
+    pub(crate) ack_timer: AckTimer,
+    pub(crate) heartbeat_timer: HeartbeatTimer,
+    /// Time passed to the call being handled.
+    now: Option<Instant>,
+
+    // Path management (RFC 4960 sec 8)
+    path_max_retrans: usize,
+    max_retrans: usize,
+    /// Consecutive retransmission timeouts and unanswered heartbeats.
+    error_count: usize,
+    path_inactive: bool,
+    /// Nonce and send time of the HEARTBEAT waiting to be acknowledged.
+    heartbeat_sent: Option<(u64, Instant)>,
 
     // Chunks stored for retransmission
     pub(crate) stored_init: Option<ChunkInit>,
@@ -76,9 +97,7 @@ pub struct AssociationInternal {
 
     streams: HashMap<u16, Arc<Stream>>,
 
-    close_loop_ch_tx: Option<broadcast::Sender<()>>,
-    accept_ch_tx: Option<mpsc::Sender<Arc<Stream>>>,
-    handshake_completed_ch_tx: Option<mpsc::Sender<Option<Error>>>,
+    events: VecDeque<AssociationEvent>,
 
     // local error
     silent_error: Option<Error>,
@@ -89,17 +108,33 @@ pub struct AssociationInternal {
 
     pub(crate) stats: Arc<AssociationStats>,
     ack_state: AckState,
+    sack_delay: Duration,
+    packets_per_sack: u32,
+    packets_since_sack: u32,
+    immediate_sack: bool,
+    /// Whether packets with a zero checksum are advertised and accepted.
+    pub(crate) zero_checksum: bool,
+    /// Whether the peer accepts packets with a zero checksum, so none is sent.
+    pub(crate) use_zero_checksum: bool,
+    /// Whether ECN is advertised.
+    ecn: bool,
+    /// Whether both sides advertised ECN.
+    use_ecn: bool,
+    /// Lowest TSN of a CE-marked packet, echoed with ECNE chunks until the
+    /// peer answers with a CWR chunk covering it.
+    ecne_tsn: Option<u32>,
+    /// Highest TSN sent when the window was last reduced for an ECNE chunk.
+    /// ECNE chunks for TSNs up to it are for the same congestion event.
+    cwr_tsn: Option<u32>,
     pub(crate) ack_mode: AckMode, // for testing
 }
 
 impl AssociationInternal {
-    pub(crate) fn new(
-        config: Config,
-        close_loop_ch_tx: broadcast::Sender<()>,
-        accept_ch_tx: mpsc::Sender<Arc<Stream>>,
-        handshake_completed_ch_tx: mpsc::Sender<Option<Error>>,
-        awake_write_loop_ch: Arc<mpsc::Sender<()>>,
-    ) -> Self {
+    /// new creates the state machine of an association. It and its streams
+    /// call awake_write_loop whenever they have something to send, after
+    /// which poll_output should be called. Config::net_conn is not used:
+    /// packets come in through handle_input and go out through poll_output.
+    pub fn new(config: Config, awake_write_loop: AwakeWriteLoopFn) -> Self {
         let max_receive_buffer_size = if config.max_receive_buffer_size == 0 {
             INITIAL_RECV_BUF_SIZE
         } else {
@@ -114,6 +149,9 @@ impl AssociationInternal {
 
         let inflight_queue_length = Arc::new(AtomicUsize::new(0));
 
+        let pending_queue = Arc::new(PendingQueue::new());
+        pending_queue.set_scheduler(config.stream_scheduler);
+
         let mut tsn = random::<u32>();
         if tsn == 0 {
             tsn += 1;
@@ -123,14 +161,35 @@ impl AssociationInternal {
             max_receive_buffer_size,
             max_message_size: Arc::new(AtomicU32::new(max_message_size)),
 
-            my_max_num_outbound_streams: u16::MAX,
-            my_max_num_inbound_streams: u16::MAX,
+            my_max_num_outbound_streams: if config.max_num_outbound_streams == 0 {
+                u16::MAX
+            } else {
+                config.max_num_outbound_streams
+            },
+            my_max_num_inbound_streams: if config.max_num_inbound_streams == 0 {
+                u16::MAX
+            } else {
+                config.max_num_inbound_streams
+            },
             payload_queue: PayloadQueue::new(Arc::new(AtomicUsize::new(0))),
             inflight_queue: PayloadQueue::new(Arc::clone(&inflight_queue_length)),
             inflight_queue_length,
-            pending_queue: Arc::new(PendingQueue::new()),
+            pending_queue,
+            send_buffer: Arc::new(SendBuffer::new(config.max_send_buffer_size as usize)),
             control_queue: ControlQueue::new(),
             mtu: INITIAL_MTU,
+            congestion_control: config.congestion_control.build(INITIAL_MTU),
+            sack_delay: config.sack_delay,
+            packets_per_sack: config.packets_per_sack,
+            immediate_sack: config.immediate_sack,
+            zero_checksum: config.zero_checksum,
+            ecn: config.ecn,
+            path_max_retrans: if config.path_max_retransmits == 0 {
+                PATH_MAX_RETRANS
+            } else {
+                config.path_max_retransmits as usize
+            },
+            max_retrans: config.association_max_retransmits as usize,
             max_payload_size: INITIAL_MTU - (COMMON_HEADER_SIZE + DATA_CHUNK_HEADER_SIZE),
             my_verification_tag: random::<u32>(),
             my_next_tsn: tsn,
@@ -141,37 +200,62 @@ impl AssociationInternal {
             streams: HashMap::new(),
             reconfigs: HashMap::new(),
             reconfig_requests: HashMap::new(),
-            accept_ch_tx: Some(accept_ch_tx),
-            close_loop_ch_tx: Some(close_loop_ch_tx),
-            handshake_completed_ch_tx: Some(handshake_completed_ch_tx),
+            add_streams_requests: HashMap::new(),
+            add_streams_responses: HashMap::new(),
+            t1init: RtxTimer::new(MAX_INIT_RETRANS),
+            t1cookie: RtxTimer::new(MAX_INIT_RETRANS),
+            t2shutdown: RtxTimer::new(NO_MAX_RETRANS), // retransmit forever
+            t3rtx: RtxTimer::new(NO_MAX_RETRANS),      // retransmit forever
+            treconfig: RtxTimer::new(NO_MAX_RETRANS),  // retransmit forever
+            heartbeat_timer: HeartbeatTimer::new(config.heartbeat_interval),
             cumulative_tsn_ack_point: tsn - 1,
             advanced_peer_tsn_ack_point: tsn - 1,
             silent_error: Some(Error::ErrSilentlyDiscard),
             stats: Arc::new(AssociationStats::default()),
-            awake_write_loop_ch: Some(awake_write_loop_ch),
+            awake_write_loop: Some(awake_write_loop),
             ..Default::default()
         };
+        a.ack_timer = AckTimer::new(a.sack_delay());
 
-        // RFC 4690 Sec 7.2.1
-        //  o  The initial cwnd before DATA transmission or after a sufficiently
-        //     long idle period MUST be set to min(4*MTU, max (2*MTU, 4380
-        //     bytes)).
-        //     TODO: Consider whether this should use `clamp`
-        #[allow(clippy::manual_clamp)]
-        {
-            a.cwnd = std::cmp::min(4 * a.mtu, std::cmp::max(2 * a.mtu, 4380));
-        }
         log::trace!(
             "[{}] updated cwnd={} ssthresh={} inflight={} (INI)",
             a.name,
-            a.cwnd,
-            a.ssthresh,
+            a.congestion_control.cwnd(),
+            a.congestion_control.ssthresh(),
             a.inflight_queue.get_num_bytes()
         );
 
         a
     }
 
+    /// connect starts the handshake of a client by sending an INIT chunk.
+    pub fn connect(&mut self, now: Instant) -> Result<()> {
+        self.now = Some(now);
+
+        let mut init = ChunkInit {
+            initial_tsn: self.my_next_tsn,
+            num_outbound_streams: self.my_max_num_outbound_streams,
+            num_inbound_streams: self.my_max_num_inbound_streams,
+            initiate_tag: self.my_verification_tag,
+            advertised_receiver_window_credit: self.max_receive_buffer_size,
+            ..Default::default()
+        };
+        init.set_supported_extensions();
+        if self.zero_checksum {
+            init.set_zero_checksum_acceptable();
+        }
+        if self.ecn {
+            init.set_ecn_capable();
+        }
+
+        self.set_state(AssociationState::CookieWait);
+        self.stored_init = Some(init);
+        self.send_init()?;
+        self.t1init.start(now, self.rto_mgr.get_rto());
+
+        Ok(())
+    }
+
     /// caller must hold self.lock
     pub(crate) fn send_init(&mut self) -> Result<()> {
         if let Some(stored_init) = self.stored_init.clone() {
@@ -216,21 +300,26 @@ impl AssociationInternal {
         }
     }
 
-    pub(crate) async fn close(&mut self) -> Result<()> {
+    /// close ends the association and cleans up any state. It is reported by
+    /// an AssociationEvent::Closed.
+    pub fn close(&mut self) -> Result<()> {
         if self.get_state() != AssociationState::Closed {
             self.set_state(AssociationState::Closed);
 
             log::debug!("[{}] closing association..", self.name);
 
-            self.close_all_timers().await;
+            self.close_all_timers();
 
-            // awake read/write_loop to exit
-            self.close_loop_ch_tx.take();
+            // the driver of the association stops its loops
+            self.events.push_back(AssociationEvent::Closed);
 
             for si in self.streams.keys().cloned().collect::<Vec<u16>>() {
                 self.unregister_stream(si);
             }
 
+            // Unanswered Add Streams requests are never answered
+            self.add_streams_requests.clear();
+
             // Wait for read_loop to end
             //if let Some(read_loop_close_ch) = &mut self.read_loop_close_ch {
             //    let _ = read_loop_close_ch.recv().await;
@@ -267,38 +356,26 @@ impl AssociationInternal {
         Ok(())
     }
 
-    async fn close_all_timers(&mut self) {
+    fn close_all_timers(&mut self) {
         // Close all retransmission & ack timers
-        if let Some(t1init) = &self.t1init {
-            t1init.stop().await;
-        }
-        if let Some(t1cookie) = &self.t1cookie {
-            t1cookie.stop().await;
-        }
-        if let Some(t2shutdown) = &self.t2shutdown {
-            t2shutdown.stop().await;
-        }
-        if let Some(t3rtx) = &self.t3rtx {
-            t3rtx.stop().await;
-        }
-        if let Some(treconfig) = &self.treconfig {
-            treconfig.stop().await;
-        }
-        if let Some(ack_timer) = &mut self.ack_timer {
-            ack_timer.stop();
-        }
+        self.t1init.stop();
+        self.t1cookie.stop();
+        self.t2shutdown.stop();
+        self.t3rtx.stop();
+        self.treconfig.stop();
+        self.ack_timer.stop();
+        self.heartbeat_timer.stop();
     }
 
     fn awake_write_loop(&self) {
-        //log::debug!("[{}] awake_write_loop_ch.notify_one", self.name);
-        if let Some(awake_write_loop_ch) = &self.awake_write_loop_ch {
-            let _ = awake_write_loop_ch.try_send(());
+        if let Some(awake_write_loop) = &self.awake_write_loop {
+            awake_write_loop();
         }
     }
 
     /// unregister_stream un-registers a stream from the association
     /// The caller should hold the association write lock.
-    fn unregister_stream(&mut self, stream_identifier: u16) {
+    pub(crate) fn unregister_stream(&mut self, stream_identifier: u16) {
         let s = self.streams.remove(&stream_identifier);
         if let Some(s) = s {
             // NOTE: shutdown is not used here because it resets the stream.
@@ -306,12 +383,33 @@ impl AssociationInternal {
                 s.read_notifier.notify_waiters();
             }
             s.write_shutdown.store(true, Ordering::SeqCst);
+            s.writable_notifier.notify_waiters();
+            // Acknowledgements for the stream are not accounted to it anymore.
+            s.release_send_buffer(s.buffered_amount());
         }
     }
 
-    /// handle_inbound parses incoming raw packets
-    pub(crate) async fn handle_inbound(&mut self, raw: &Bytes) -> Result<()> {
-        let p = match Packet::unmarshal(raw) {
+    /// send_buffer_used returns the number of bytes all streams have buffered for sending.
+    pub fn send_buffer_used(&self) -> usize {
+        self.send_buffer.used()
+    }
+
+    /// handle_input parses and handles a packet received from the peer.
+    pub fn handle_input(&mut self, now: Instant, raw: &Bytes) -> Result<()> {
+        self.handle_input_with_ecn(now, raw, EcnCodepoint::NotEct)
+    }
+
+    /// handle_input_with_ecn is handle_input for transports reporting the ECN
+    /// field the packet was received with.
+    pub fn handle_input_with_ecn(
+        &mut self,
+        now: Instant,
+        raw: &Bytes,
+        ecn: EcnCodepoint,
+    ) -> Result<()> {
+        self.now = Some(now);
+
+        let p = match Packet::unmarshal_with(raw, self.zero_checksum) {
             Ok(p) => p,
             Err(err) => {
                 log::warn!("[{}] unable to parse SCTP packet {}", self.name, err);
@@ -326,8 +424,12 @@ impl AssociationInternal {
 
         self.handle_chunk_start();
 
+        if ecn == EcnCodepoint::Ce && self.use_ecn {
+            self.handle_congestion_experienced(&p);
+        }
+
         for c in &p.chunks {
-            self.handle_chunk(&p, c).await?;
+            self.handle_chunk(&p, c)?;
         }
 
         self.handle_chunk_end();
@@ -342,19 +444,17 @@ impl AssociationInternal {
         raw_packets
     }
 
-    async fn gather_outbound_data_and_reconfig_packets(
+    fn gather_outbound_data_and_reconfig_packets(
         &mut self,
         mut raw_packets: Vec<Packet>,
     ) -> Vec<Packet> {
         // Pop unsent data chunks from the pending queue to send as much as
         // cwnd and rwnd allow.
-        let (chunks, sis_to_reset) = self.pop_pending_data_chunks_to_send().await;
+        let (chunks, sis_to_reset) = self.pop_pending_data_chunks_to_send();
         if !chunks.is_empty() {
             // Start timer. (noop if already started)
             log::trace!("[{}] T3-rtx timer start (pt1)", self.name);
-            if let Some(t3rtx) = &self.t3rtx {
-                t3rtx.start(self.rto_mgr.get_rto()).await;
-            }
+            self.t3rtx.start(self.now(), self.rto_mgr.get_rto());
             for p in self.bundle_data_chunks_into_packets(chunks) {
                 raw_packets.push(p);
             }
@@ -401,9 +501,7 @@ impl AssociationInternal {
             }
 
             if !self.reconfigs.is_empty() {
-                if let Some(treconfig) = &self.treconfig {
-                    treconfig.start(self.rto_mgr.get_rto()).await;
-                }
+                self.treconfig.start(self.now(), self.rto_mgr.get_rto());
             }
         }
 
@@ -439,7 +537,8 @@ impl AssociationInternal {
                     //      of cwnd and SHOULD NOT delay retransmission for this single
                     //		packet.
 
-                    let data_chunk_size = DATA_CHUNK_HEADER_SIZE + c.user_data.len() as u32;
+                    let data_chunk_size =
+                        data_chunk_header_size(c.interleaved) + c.user_data.len() as u32;
                     if self.mtu < fast_retrans_size + data_chunk_size {
                         break;
                     }
@@ -452,6 +551,7 @@ impl AssociationInternal {
                 }
 
                 if let Some(c) = self.inflight_queue.get(tsn) {
+                    self.count_retransmission(c);
                     self.check_partial_reliability_status(c);
                     to_fast_retrans.push(Box::new(c.clone()));
                     log::trace!(
@@ -474,12 +574,17 @@ impl AssociationInternal {
         raw_packets
     }
 
-    async fn gather_outbound_sack_packets(&mut self, mut raw_packets: Vec<Packet>) -> Vec<Packet> {
+    fn gather_outbound_sack_packets(&mut self, mut raw_packets: Vec<Packet>) -> Vec<Packet> {
         if self.ack_state == AckState::Immediate {
             self.ack_state = AckState::Idle;
-            let sack = self.create_selective_ack_chunk().await;
+            self.packets_since_sack = 0;
+            let sack = self.create_selective_ack_chunk();
             log::debug!("[{}] sending SACK: {}", self.name, sack);
-            let p = self.create_packet(vec![Box::new(sack)]);
+            let mut chunks: Vec<Box<dyn Chunk + Send + Sync>> = vec![Box::new(sack)];
+            if let Some(lowest_tsn) = self.ecne_tsn {
+                chunks.push(Box::new(ChunkEcne { lowest_tsn }));
+            }
+            let p = self.create_packet(chunks);
             raw_packets.push(p);
         }
 
@@ -498,8 +603,12 @@ impl AssociationInternal {
                 self.advanced_peer_tsn_ack_point,
                 self.cumulative_tsn_ack_point,
             ) {
-                let fwd_tsn = self.create_forward_tsn();
-                let p = self.create_packet(vec![Box::new(fwd_tsn)]);
+                let fwd_tsn: Box<dyn Chunk + Send + Sync> = if self.use_interleaving {
+                    Box::new(self.create_i_forward_tsn())
+                } else {
+                    Box::new(self.create_forward_tsn())
+                };
+                let p = self.create_packet(vec![fwd_tsn]);
                 raw_packets.push(p);
             }
         }
@@ -507,7 +616,7 @@ impl AssociationInternal {
         raw_packets
     }
 
-    async fn gather_outbound_shutdown_packets(
+    fn gather_outbound_shutdown_packets(
         &mut self,
         mut raw_packets: Vec<Packet>,
     ) -> (Vec<Packet>, bool) {
@@ -521,9 +630,7 @@ impl AssociationInternal {
             };
 
             let p = self.create_packet(vec![Box::new(shutdown)]);
-            if let Some(t2shutdown) = &self.t2shutdown {
-                t2shutdown.start(self.rto_mgr.get_rto()).await;
-            }
+            self.t2shutdown.start(self.now(), self.rto_mgr.get_rto());
             raw_packets.push(p);
         } else if self.will_send_shutdown_ack {
             self.will_send_shutdown_ack = false;
@@ -531,9 +638,7 @@ impl AssociationInternal {
             let shutdown_ack = ChunkShutdownAck {};
 
             let p = self.create_packet(vec![Box::new(shutdown_ack)]);
-            if let Some(t2shutdown) = &self.t2shutdown {
-                t2shutdown.start(self.rto_mgr.get_rto()).await;
-            }
+            self.t2shutdown.start(self.now(), self.rto_mgr.get_rto());
             raw_packets.push(p);
         } else if self.will_send_shutdown_complete {
             self.will_send_shutdown_complete = false;
@@ -550,7 +655,9 @@ impl AssociationInternal {
 
     /// gather_outbound gathers outgoing packets. The returned bool value set to
     /// false means the association should be closed down after the final send.
-    pub(crate) async fn gather_outbound(&mut self) -> (Vec<Packet>, bool) {
+    pub(crate) fn gather_outbound(&mut self, now: Instant) -> (Vec<Packet>, bool) {
+        self.now = Some(now);
+
         let mut raw_packets = Vec::with_capacity(16);
 
         if !self.control_queue.is_empty() {
@@ -563,11 +670,9 @@ impl AssociationInternal {
         match state {
             AssociationState::Established => {
                 raw_packets = self.gather_data_packets_to_retransmit(raw_packets);
-                raw_packets = self
-                    .gather_outbound_data_and_reconfig_packets(raw_packets)
-                    .await;
+                raw_packets = self.gather_outbound_data_and_reconfig_packets(raw_packets);
                 raw_packets = self.gather_outbound_fast_retransmission_packets(raw_packets);
-                raw_packets = self.gather_outbound_sack_packets(raw_packets).await;
+                raw_packets = self.gather_outbound_sack_packets(raw_packets);
                 raw_packets = self.gather_outbound_forward_tsn_packets(raw_packets);
                 (raw_packets, true)
             }
@@ -576,12 +681,10 @@ impl AssociationInternal {
             | AssociationState::ShutdownReceived => {
                 raw_packets = self.gather_data_packets_to_retransmit(raw_packets);
                 raw_packets = self.gather_outbound_fast_retransmission_packets(raw_packets);
-                raw_packets = self.gather_outbound_sack_packets(raw_packets).await;
-                self.gather_outbound_shutdown_packets(raw_packets).await
-            }
-            AssociationState::ShutdownAckSent => {
-                self.gather_outbound_shutdown_packets(raw_packets).await
+                raw_packets = self.gather_outbound_sack_packets(raw_packets);
+                self.gather_outbound_shutdown_packets(raw_packets)
             }
+            AssociationState::ShutdownAckSent => self.gather_outbound_shutdown_packets(raw_packets),
             _ => (raw_packets, true),
         }
     }
@@ -599,12 +702,45 @@ impl AssociationInternal {
         }
     }
 
+    /// set_interleaving switches outbound user messages to I-DATA chunks
+    /// (RFC 8260) when both sides announced support for them.
+    fn set_interleaving(&mut self, use_interleaving: bool) {
+        self.use_interleaving = use_interleaving;
+        self.max_payload_size =
+            self.mtu - (COMMON_HEADER_SIZE + data_chunk_header_size(use_interleaving));
+        self.pending_queue.set_interleaving(use_interleaving);
+    }
+
+    /// set_zero_checksum stops sending the CRC32c (RFC 9653) when both sides
+    /// announced that packets are protected by DTLS instead.
+    fn set_zero_checksum(&mut self, zero_checksum_acceptable: bool) {
+        self.use_zero_checksum = self.zero_checksum && zero_checksum_acceptable;
+        if self.use_zero_checksum {
+            log::debug!("[{}] use zero checksum", self.name);
+        }
+    }
+
+    /// set_ecn enables Explicit Congestion Notification (RFC 4960 Appendix A)
+    /// when both sides advertised it.
+    fn set_ecn(&mut self, ecn_capable: bool) {
+        self.use_ecn = self.ecn && ecn_capable;
+        if self.use_ecn {
+            log::debug!("[{}] use ECN", self.name);
+        }
+    }
+
+    /// ecn_capable returns whether ECN has been negotiated, in which case
+    /// packets returned by poll_output should be sent marked ECT(0).
+    pub fn ecn_capable(&self) -> bool {
+        self.use_ecn
+    }
+
     /// get_state atomically returns the state of the Association.
     fn get_state(&self) -> AssociationState {
         self.state.load(Ordering::SeqCst).into()
     }
 
-    async fn handle_init(&mut self, p: &Packet, i: &ChunkInit) -> Result<Vec<Packet>> {
+    fn handle_init(&mut self, p: &Packet, i: &ChunkInit) -> Result<Vec<Packet>> {
         let state = self.get_state();
         log::debug!("[{}] chunkInit received in state '{}'", self.name, state);
 
@@ -625,10 +761,12 @@ impl AssociationInternal {
         }
 
         // Should we be setting any of these permanently until we've ACKed further?
+        // The streams the peer can receive limit the ones we send on, and
+        // the other way round.
         self.my_max_num_inbound_streams =
-            std::cmp::min(i.num_inbound_streams, self.my_max_num_inbound_streams);
+            std::cmp::min(i.num_outbound_streams, self.my_max_num_inbound_streams);
         self.my_max_num_outbound_streams =
-            std::cmp::min(i.num_outbound_streams, self.my_max_num_outbound_streams);
+            std::cmp::min(i.num_inbound_streams, self.my_max_num_outbound_streams);
         self.peer_verification_tag = i.initiate_tag;
         self.source_port = p.destination_port;
         self.destination_port = p.source_port;
@@ -643,19 +781,32 @@ impl AssociationInternal {
             i.initial_tsn - 1
         };
 
+        let mut use_interleaving = false;
+        let mut zero_checksum_acceptable = false;
+        let mut ecn_capable = false;
         for param in &i.params {
             if let Some(v) = param.as_any().downcast_ref::<ParamSupportedExtensions>() {
                 for t in &v.chunk_types {
                     if *t == CT_FORWARD_TSN {
                         log::debug!("[{}] use ForwardTSN (on init)", self.name);
                         self.use_forward_tsn = true;
+                    } else if *t == CT_I_DATA {
+                        log::debug!("[{}] use I-DATA (on init)", self.name);
+                        use_interleaving = true;
                     }
                 }
+            } else if let Some(v) = param.as_any().downcast_ref::<ParamZeroChecksumAcceptable>() {
+                zero_checksum_acceptable = v.edmid == ZERO_CHECKSUM_EDMID_DTLS;
+            } else if param.as_any().downcast_ref::<ParamEcnCapable>().is_some() {
+                ecn_capable = true;
             }
         }
         if !self.use_forward_tsn {
             log::warn!("[{}] not using ForwardTSN (on init)", self.name);
         }
+        self.set_interleaving(use_interleaving);
+        self.set_zero_checksum(zero_checksum_acceptable);
+        self.set_ecn(ecn_capable);
 
         let mut outbound = Packet {
             verification_tag: self.peer_verification_tag,
@@ -704,13 +855,19 @@ impl AssociationInternal {
         }
 
         init_ack.set_supported_extensions();
+        if self.zero_checksum {
+            init_ack.set_zero_checksum_acceptable();
+        }
+        if self.ecn {
+            init_ack.set_ecn_capable();
+        }
 
         outbound.chunks = vec![Box::new(init_ack)];
 
         Ok(vec![outbound])
     }
 
-    async fn handle_init_ack(&mut self, p: &Packet, i: &ChunkInit) -> Result<Vec<Packet>> {
+    fn handle_init_ack(&mut self, p: &Packet, i: &ChunkInit) -> Result<Vec<Packet>> {
         let state = self.get_state();
         log::debug!("[{}] chunkInitAck received in state '{}'", self.name, state);
         if state != AssociationState::CookieWait {
@@ -723,10 +880,12 @@ impl AssociationInternal {
             return Ok(vec![]);
         }
 
+        // The streams the peer can receive limit the ones we send on, and
+        // the other way round.
         self.my_max_num_inbound_streams =
-            std::cmp::min(i.num_inbound_streams, self.my_max_num_inbound_streams);
+            std::cmp::min(i.num_outbound_streams, self.my_max_num_inbound_streams);
         self.my_max_num_outbound_streams =
-            std::cmp::min(i.num_outbound_streams, self.my_max_num_outbound_streams);
+            std::cmp::min(i.num_inbound_streams, self.my_max_num_outbound_streams);
         self.peer_verification_tag = i.initiate_tag;
         self.peer_last_tsn = if i.initial_tsn == 0 {
             u32::MAX
@@ -745,21 +904,22 @@ impl AssociationInternal {
         //  o  The initial value of ssthresh MAY be arbitrarily high (for
         //     example, implementations MAY use the size of the receiver
         //     advertised window).
-        self.ssthresh = self.rwnd;
+        self.congestion_control.set_ssthresh(self.rwnd);
         log::trace!(
             "[{}] updated cwnd={} ssthresh={} inflight={} (INI)",
             self.name,
-            self.cwnd,
-            self.ssthresh,
+            self.congestion_control.cwnd(),
+            self.congestion_control.ssthresh(),
             self.inflight_queue.get_num_bytes()
         );
 
-        if let Some(t1init) = &self.t1init {
-            t1init.stop().await;
-        }
+        self.t1init.stop();
         self.stored_init = None;
 
         let mut cookie_param = None;
+        let mut use_interleaving = false;
+        let mut zero_checksum_acceptable = false;
+        let mut ecn_capable = false;
         for param in &i.params {
             if let Some(v) = param.as_any().downcast_ref::<ParamStateCookie>() {
                 cookie_param = Some(v);
@@ -768,6 +928,9 @@ impl AssociationInternal {
                     if *t == CT_FORWARD_TSN {
                         log::debug!("[{}] use ForwardTSN (on initAck)", self.name);
                         self.use_forward_tsn = true;
+                    } else if *t == CT_I_DATA {
+                        log::debug!("[{}] use I-DATA (on initAck)", self.name);
+                        use_interleaving = true;
                     }
                 }
             } else if param
@@ -776,11 +939,18 @@ impl AssociationInternal {
                 .is_some()
             {
                 self.use_forward_tsn = true;
+            } else if let Some(v) = param.as_any().downcast_ref::<ParamZeroChecksumAcceptable>() {
+                zero_checksum_acceptable = v.edmid == ZERO_CHECKSUM_EDMID_DTLS;
+            } else if param.as_any().downcast_ref::<ParamEcnCapable>().is_some() {
+                ecn_capable = true;
             }
         }
         if !self.use_forward_tsn {
             log::warn!("[{}] not using ForwardTSN (on initAck)", self.name);
         }
+        self.set_interleaving(use_interleaving);
+        self.set_zero_checksum(zero_checksum_acceptable);
+        self.set_ecn(ecn_capable);
 
         if let Some(v) = cookie_param {
             self.stored_cookie_echo = Some(ChunkCookieEcho {
@@ -789,9 +959,7 @@ impl AssociationInternal {
 
             self.send_cookie_echo()?;
 
-            if let Some(t1cookie) = &self.t1cookie {
-                t1cookie.start(self.rto_mgr.get_rto()).await;
-            }
+            self.t1cookie.start(self.now(), self.rto_mgr.get_rto());
 
             self.set_state(AssociationState::CookieEchoed);
 
@@ -801,7 +969,7 @@ impl AssociationInternal {
         }
     }
 
-    async fn handle_heartbeat(&self, c: &ChunkHeartbeat) -> Result<Vec<Packet>> {
+    fn handle_heartbeat(&self, c: &ChunkHeartbeat) -> Result<Vec<Packet>> {
         log::trace!("[{}] chunkHeartbeat", self.name);
         if let Some(p) = c.params.first() {
             if let Some(hbi) = p.as_any().downcast_ref::<ParamHeartbeatInfo>() {
@@ -826,7 +994,123 @@ impl AssociationInternal {
         Ok(vec![])
     }
 
-    async fn handle_cookie_echo(&mut self, c: &ChunkCookieEcho) -> Result<Vec<Packet>> {
+    fn handle_heartbeat_ack(&mut self, c: &ChunkHeartbeatAck) -> Result<Vec<Packet>> {
+        log::trace!("[{}] chunkHeartbeatAck", self.name);
+        let hbi = c
+            .params
+            .first()
+            .and_then(|p| p.as_any().downcast_ref::<ParamHeartbeatInfo>());
+        if let (Some(hbi), Some((nonce, since))) = (hbi, self.heartbeat_sent) {
+            if hbi.heartbeat_information[..] != nonce.to_be_bytes() {
+                return Ok(vec![]);
+            }
+            self.heartbeat_sent = None;
+
+            // RFC 4960 sec 8.3
+            //  Upon the receipt of the HEARTBEAT ACK, the sender of the HEARTBEAT
+            //  should clear the error counter of the destination transport
+            //  address to which the HEARTBEAT was sent, and mark the destination
+            //  transport address as active if it is not so marked.  The endpoint
+            //  may optionally report to the upper layer when an inactive
+            //  destination address is marked as active due to the reception of
+            //  the latest HEARTBEAT ACK.  The receiver of the HEARTBEAT ACK must
+            //  also clear the association overall error count as well.
+            let rtt = self.now().saturating_duration_since(since);
+            let srtt = self.rto_mgr.set_new_rtt(rtt.as_millis() as u64);
+            self.congestion_control.on_rtt_measured(rtt);
+            log::trace!(
+                "[{}] HEARTBEAT ACK: measured-rtt={} srtt={} new-rto={}",
+                self.name,
+                rtt.as_millis(),
+                srtt,
+                self.rto_mgr.get_rto()
+            );
+            self.on_path_success();
+        }
+
+        Ok(vec![])
+    }
+
+    /// send_heartbeat probes the reachability of the peer.
+    fn send_heartbeat(&mut self) {
+        let nonce = random::<u64>();
+        self.heartbeat_sent = Some((nonce, self.now()));
+
+        self.control_queue.push_back(Packet {
+            verification_tag: self.peer_verification_tag,
+            source_port: self.source_port,
+            destination_port: self.destination_port,
+            chunks: vec![Box::new(ChunkHeartbeat {
+                params: vec![Box::new(ParamHeartbeatInfo {
+                    heartbeat_information: Bytes::copy_from_slice(&nonce.to_be_bytes()),
+                })],
+            })],
+        });
+        self.awake_write_loop();
+    }
+
+    /// path_status returns whether the peer is reachable.
+    pub(crate) fn path_status(&self) -> PathStatus {
+        if self.path_inactive {
+            PathStatus::Inactive
+        } else {
+            PathStatus::Active
+        }
+    }
+
+    /// on_path_success clears the error counter once DATA or a HEARTBEAT
+    /// has been acknowledged.
+    fn on_path_success(&mut self) {
+        self.error_count = 0;
+        if self.path_inactive {
+            self.path_inactive = false;
+            log::debug!("[{}] path is active again", self.name);
+            self.notify_path_status();
+        }
+    }
+
+    /// on_path_failure counts a retransmission timeout or an unanswered
+    /// heartbeat (RFC 4960 sec 8.1 and 8.2).
+    fn on_path_failure(&mut self) {
+        self.error_count += 1;
+
+        if !self.path_inactive && self.error_count > self.path_max_retrans {
+            self.path_inactive = true;
+            log::warn!(
+                "[{}] path is inactive after {} errors",
+                self.name,
+                self.error_count
+            );
+            self.notify_path_status();
+        }
+
+        if self.max_retrans != 0 && self.error_count > self.max_retrans {
+            log::error!(
+                "[{}] peer is unreachable after {} errors, closing association",
+                self.name,
+                self.error_count
+            );
+            if let Err(err) = self.close() {
+                log::warn!("[{}] failed to close association: {:?}", self.name, err);
+            }
+        }
+    }
+
+    fn notify_path_status(&mut self) {
+        self.events
+            .push_back(AssociationEvent::PathStatus(self.path_status()));
+    }
+
+    /// on_established completes the handshake and starts probing the path.
+    fn on_established(&mut self) {
+        self.set_state(AssociationState::Established);
+        self.events.push_back(AssociationEvent::HandshakeCompleted);
+        if !self.heartbeat_timer.interval.is_zero() {
+            self.heartbeat_timer.start(self.now());
+        }
+    }
+
+    fn handle_cookie_echo(&mut self, c: &ChunkCookieEcho) -> Result<Vec<Packet>> {
         let state = self.get_state();
         log::debug!("[{}] COOKIE-ECHO received in state '{}'", self.name, state);
 
@@ -844,20 +1128,13 @@ impl AssociationInternal {
                         return Ok(vec![]);
                     }
 
-                    if let Some(t1init) = &self.t1init {
-                        t1init.stop().await;
-                    }
+                    self.t1init.stop();
                     self.stored_init = None;
 
-                    if let Some(t1cookie) = &self.t1cookie {
-                        t1cookie.stop().await;
-                    }
+                    self.t1cookie.stop();
                     self.stored_cookie_echo = None;
 
-                    self.set_state(AssociationState::Established);
-                    if let Some(handshake_completed_ch) = &self.handshake_completed_ch_tx {
-                        let _ = handshake_completed_ch.send(None).await;
-                    }
+                    self.on_established();
                 }
                 _ => return Ok(vec![]),
             };
@@ -874,7 +1151,7 @@ impl AssociationInternal {
         }])
     }
 
-    async fn handle_cookie_ack(&mut self) -> Result<Vec<Packet>> {
+    fn handle_cookie_ack(&mut self) -> Result<Vec<Packet>> {
         let state = self.get_state();
         log::debug!("[{}] COOKIE-ACK received in state '{}'", self.name, state);
         if state != AssociationState::CookieEchoed {
@@ -885,20 +1162,15 @@ impl AssociationInternal {
             return Ok(vec![]);
         }
 
-        if let Some(t1cookie) = &self.t1cookie {
-            t1cookie.stop().await;
-        }
+        self.t1cookie.stop();
         self.stored_cookie_echo = None;
 
-        self.set_state(AssociationState::Established);
-        if let Some(handshake_completed_ch) = &self.handshake_completed_ch_tx {
-            let _ = handshake_completed_ch.send(None).await;
-        }
+        self.on_established();
 
         Ok(vec![])
     }
 
-    async fn handle_data(&mut self, d: &ChunkPayloadData) -> Result<Vec<Packet>> {
+    fn handle_data(&mut self, d: &ChunkPayloadData) -> Result<Vec<Packet>> {
         log::trace!(
             "[{}] DATA: tsn={} immediateSack={} len={}",
             self.name,
@@ -908,11 +1180,23 @@ impl AssociationInternal {
         );
         self.stats.inc_datas();
 
+        // RFC 8260 Sec 2.1: receiving DATA once I-DATA is negotiated, or the
+        // other way round, is a protocol violation.
+        if d.interleaved != self.use_interleaving {
+            log::warn!(
+                "[{}] received {} but I-DATA use is {}",
+                self.name,
+                d.header().typ,
+                self.use_interleaving
+            );
+            return Err(Error::ErrMixedDataChunkTypes);
+        }
+
         let can_push = self.payload_queue.can_push(d, self.peer_last_tsn);
         let mut stream_handle_data = false;
         if can_push {
             if let Some(_s) = self.get_or_create_stream(d.stream_identifier) {
-                if self.get_my_receiver_window_credit().await > 0 {
+                if self.get_my_receiver_window_credit() > 0 {
                     // Pass the new chunk to stream level as soon as it arrives
                     self.payload_queue.push(d.clone(), self.peer_last_tsn);
                     stream_handle_data = true;
@@ -945,7 +1229,7 @@ impl AssociationInternal {
 
         if stream_handle_data {
             if let Some(s) = self.streams.get_mut(&d.stream_identifier) {
-                s.handle_data(d.clone()).await;
+                s.handle_data(d.clone());
             }
         }
 
@@ -994,7 +1278,7 @@ impl AssociationInternal {
             && self.ack_mode == AckMode::Normal)
             || self.ack_mode == AckMode::AlwaysDelay
         {
-            if self.ack_state == AckState::Idle {
+            if self.packets_since_sack + 1 < self.packets_per_sack() {
                 self.delayed_ack_triggered = true;
             } else {
                 self.immediate_ack_triggered = true;
@@ -1006,10 +1290,28 @@ impl AssociationInternal {
         Ok(reply)
     }
 
-    pub(crate) async fn get_my_receiver_window_credit(&self) -> u32 {
+    /// sack_delay returns how long received DATA may go unacknowledged.
+    pub(crate) fn sack_delay(&self) -> Duration {
+        if self.sack_delay.is_zero() {
+            ACK_INTERVAL
+        } else {
+            std::cmp::min(self.sack_delay, MAX_ACK_INTERVAL)
+        }
+    }
+
+    /// packets_per_sack returns the number of packets carrying DATA acknowledged by one SACK.
+    fn packets_per_sack(&self) -> u32 {
+        if self.packets_per_sack == 0 {
+            DEFAULT_PACKETS_PER_SACK
+        } else {
+            self.packets_per_sack
+        }
+    }
+
+    pub(crate) fn get_my_receiver_window_credit(&self) -> u32 {
         let mut bytes_queued = 0;
         for s in self.streams.values() {
-            bytes_queued += s.get_num_bytes_in_reassembly_queue().await as u32;
+            bytes_queued += s.get_num_bytes_in_reassembly_queue() as u32;
         }
 
         if bytes_queued >= self.max_receive_buffer_size {
@@ -1028,6 +1330,10 @@ impl AssociationInternal {
             return Err(Error::ErrStreamAlreadyExist);
         }
 
+        if stream_identifier >= self.my_max_num_outbound_streams {
+            return Err(Error::ErrStreamIdentifierOutOfRange);
+        }
+
         if let Some(s) = self.create_stream(stream_identifier, false) {
             s.set_default_payload_type(default_payload_type);
             Ok(Arc::clone(&s))
@@ -1044,29 +1350,19 @@ impl AssociationInternal {
             self.max_payload_size,
             Arc::clone(&self.max_message_size),
             Arc::clone(&self.state),
-            self.awake_write_loop_ch.clone(),
+            self.awake_write_loop.clone(),
             Arc::clone(&self.pending_queue),
+            Arc::clone(&self.send_buffer),
         ));
 
         if accept {
-            if let Some(accept_ch) = &self.accept_ch_tx {
-                if accept_ch.try_send(Arc::clone(&s)).is_ok() {
-                    log::debug!(
-                        "[{}] accepted a new stream (streamIdentifier: {})",
-                        self.name,
-                        stream_identifier
-                    );
-                } else {
-                    log::debug!("[{}] dropped a new stream due to accept_ch full", self.name);
-                    return None;
-                }
-            } else {
-                log::debug!(
-                    "[{}] dropped a new stream due to accept_ch_tx is None",
-                    self.name
-                );
-                return None;
-            }
+            log::debug!(
+                "[{}] accepted a new stream (streamIdentifier: {})",
+                self.name,
+                stream_identifier
+            );
+            self.events
+                .push_back(AssociationEvent::StreamAccepted(Arc::clone(&s)));
         }
         self.streams.insert(stream_identifier, Arc::clone(&s));
         Some(s)
@@ -1076,16 +1372,22 @@ impl AssociationInternal {
     fn get_or_create_stream(&mut self, stream_identifier: u16) -> Option<Arc<Stream>> {
         if self.streams.contains_key(&stream_identifier) {
             self.streams.get(&stream_identifier).cloned()
+        } else if stream_identifier >= self.my_max_num_inbound_streams {
+            log::warn!(
+                "[{}] dropped DATA for stream {} beyond the {} incoming streams",
+                self.name,
+                stream_identifier,
+                self.my_max_num_inbound_streams
+            );
+            None
         } else {
             self.create_stream(stream_identifier, true)
         }
     }
 
-    async fn process_selective_ack(
-        &mut self,
-        d: &ChunkSelectiveAck,
-    ) -> Result<(HashMap<u16, i64>, u32)> {
+    fn process_selective_ack(&mut self, d: &ChunkSelectiveAck) -> Result<(HashMap<u16, i64>, u32)> {
         let mut bytes_acked_per_stream = HashMap::new();
+        let now = self.now();
 
         // New ack point, so pop all ACKed packets from inflight_queue
         // We add 1 because the "currentAckPoint" has already been popped from the inflight queue
@@ -1102,9 +1404,7 @@ impl AssociationInternal {
                     //        still outstanding data on that address).
                     if i == self.cumulative_tsn_ack_point + 1 {
                         // T3 timer needs to be reset. Stop it for now.
-                        if let Some(t3rtx) = &self.t3rtx {
-                            t3rtx.stop().await;
-                        }
+                        self.t3rtx.stop();
                     }
 
                     let n_bytes_acked = c.user_data.len() as i64;
@@ -1127,11 +1427,9 @@ impl AssociationInternal {
                     //        chunk or for a later instance)
                     if c.nsent == 1 && sna32gte(c.tsn, self.min_tsn2measure_rtt) {
                         self.min_tsn2measure_rtt = self.my_next_tsn;
-                        let rtt = match SystemTime::now().duration_since(c.since) {
-                            Ok(rtt) => rtt,
-                            Err(_) => return Err(Error::ErrInvalidSystemTime),
-                        };
+                        let rtt = now.saturating_duration_since(c.since);
                         let srtt = self.rto_mgr.set_new_rtt(rtt.as_millis() as u64);
+                        self.congestion_control.on_rtt_measured(rtt);
                         log::trace!(
                             "[{}] SACK: measured-rtt={} srtt={} new-rto={}",
                             self.name,
@@ -1184,11 +1482,9 @@ impl AssociationInternal {
 
                         if c.nsent == 1 {
                             self.min_tsn2measure_rtt = self.my_next_tsn;
-                            let rtt = match SystemTime::now().duration_since(c.since) {
-                                Ok(rtt) => rtt,
-                                Err(_) => return Err(Error::ErrInvalidSystemTime),
-                            };
+                            let rtt = now.saturating_duration_since(c.since);
                             let srtt = self.rto_mgr.set_new_rtt(rtt.as_millis() as u64);
+                            self.congestion_control.on_rtt_measured(rtt);
                             log::trace!(
                                 "[{}] SACK: measured-rtt={} srtt={} new-rto={}",
                                 self.name,
@@ -1211,7 +1507,10 @@ impl AssociationInternal {
         Ok((bytes_acked_per_stream, htna))
     }
 
-    async fn on_cumulative_tsn_ack_point_advanced(&mut self, total_bytes_acked: i64) {
+    fn on_cumulative_tsn_ack_point_advanced(&mut self, total_bytes_acked: i64) {
+        // RFC 4960 sec 8.1 and 8.2: an acknowledged TSN clears the error counters.
+        self.on_path_success();
+
         // RFC 4096, sec 6.3.2.  Retransmission Timer Rules
         //   R2)  Whenever all outstanding data sent to an address have been
         //        acknowledged, turn off the T3-rtx timer of that address.
@@ -1221,76 +1520,27 @@ impl AssociationInternal {
                 self.name,
                 self.pending_queue.len()
             );
-            if let Some(t3rtx) = &self.t3rtx {
-                t3rtx.stop().await;
-            }
+            self.t3rtx.stop();
         } else {
             log::trace!("[{}] T3-rtx timer start (pt2)", self.name);
-            if let Some(t3rtx) = &self.t3rtx {
-                t3rtx.start(self.rto_mgr.get_rto()).await;
-            }
+            self.t3rtx.start(self.now(), self.rto_mgr.get_rto());
         }
 
         // Update congestion control parameters
-        if self.cwnd <= self.ssthresh {
-            // RFC 4096, sec 7.2.1.  Slow-Start
-            //   o  When cwnd is less than or equal to ssthresh, an SCTP endpoint MUST
-            //		use the slow-start algorithm to increase cwnd only if the current
-            //      congestion window is being fully utilized, an incoming SACK
-            //      advances the Cumulative TSN Ack Point, and the data sender is not
-            //      in Fast Recovery.  Only when these three conditions are met can
-            //      the cwnd be increased; otherwise, the cwnd MUST not be increased.
-            //		If these conditions are met, then cwnd MUST be increased by, at
-            //      most, the lesser of 1) the total size of the previously
-            //      outstanding DATA chunk(s) acknowledged, and 2) the destination's
-            //      path MTU.
-            if !self.in_fast_recovery && self.pending_queue.len() > 0 {
-                self.cwnd += std::cmp::min(total_bytes_acked as u32, self.cwnd); // TCP way
-                                                                                 // self.cwnd += min32(uint32(total_bytes_acked), self.mtu) // SCTP way (slow)
-                log::trace!(
-                    "[{}] updated cwnd={} ssthresh={} acked={} (SS)",
-                    self.name,
-                    self.cwnd,
-                    self.ssthresh,
-                    total_bytes_acked
-                );
-            } else {
-                log::trace!(
-                    "[{}] cwnd did not grow: cwnd={} ssthresh={} acked={} FR={} pending={}",
-                    self.name,
-                    self.cwnd,
-                    self.ssthresh,
-                    total_bytes_acked,
-                    self.in_fast_recovery,
-                    self.pending_queue.len()
-                );
-            }
-        } else {
-            // RFC 4096, sec 7.2.2.  Congestion Avoidance
-            //   o  Whenever cwnd is greater than ssthresh, upon each SACK arrival
-            //      that advances the Cumulative TSN Ack Point, increase
-            //      partial_bytes_acked by the total number of bytes of all new chunks
-            //      acknowledged in that SACK including chunks acknowledged by the new
-            //      Cumulative TSN Ack and by Gap Ack Blocks.
-            self.partial_bytes_acked += total_bytes_acked as u32;
-
-            //   o  When partial_bytes_acked is equal to or greater than cwnd and
-            //      before the arrival of the SACK the sender had cwnd or more bytes
-            //      of data outstanding (i.e., before arrival of the SACK, flight size
-            //      was greater than or equal to cwnd), increase cwnd by MTU, and
-            //      reset partial_bytes_acked to (partial_bytes_acked - cwnd).
-            if self.partial_bytes_acked >= self.cwnd && self.pending_queue.len() > 0 {
-                self.partial_bytes_acked -= self.cwnd;
-                self.cwnd += self.mtu;
-                log::trace!(
-                    "[{}] updated cwnd={} ssthresh={} acked={} (CA)",
-                    self.name,
-                    self.cwnd,
-                    self.ssthresh,
-                    total_bytes_acked
-                );
-            }
-        }
+        self.congestion_control.on_ack(Ack {
+            bytes_acked: total_bytes_acked as u32,
+            in_fast_recovery: self.in_fast_recovery,
+            has_pending_data: !self.pending_queue.is_empty(),
+        });
+        log::trace!(
+            "[{}] updated cwnd={} ssthresh={} acked={} FR={} pending={}",
+            self.name,
+            self.congestion_control.cwnd(),
+            self.congestion_control.ssthresh(),
+            total_bytes_acked,
+            self.in_fast_recovery,
+            self.pending_queue.len()
+        );
     }
 
     fn process_fast_retransmission(
@@ -1328,16 +1578,15 @@ impl AssociationInternal {
                             //     last sent, according to the formula described in Section 7.2.3.
                             self.in_fast_recovery = true;
                             self.fast_recover_exit_point = htna;
-                            self.ssthresh = std::cmp::max(self.cwnd / 2, 4 * self.mtu);
-                            self.cwnd = self.ssthresh;
-                            self.partial_bytes_acked = 0;
+                            self.congestion_control.on_fast_retransmit();
+                            self.stats.inc_fast_recoveries();
                             self.will_retransmit_fast = true;
 
                             log::trace!(
                                 "[{}] updated cwnd={} ssthresh={} inflight={} (FR)",
                                 self.name,
-                                self.cwnd,
-                                self.ssthresh,
+                                self.congestion_control.cwnd(),
+                                self.congestion_control.ssthresh(),
                                 self.inflight_queue.get_num_bytes()
                             );
                         }
@@ -1357,7 +1606,7 @@ impl AssociationInternal {
         Ok(())
     }
 
-    async fn handle_sack(&mut self, d: &ChunkSelectiveAck) -> Result<Vec<Packet>> {
+    fn handle_sack(&mut self, d: &ChunkSelectiveAck) -> Result<Vec<Packet>> {
         log::trace!(
             "[{}] {}, SACK: cumTSN={} a_rwnd={}",
             self.name,
@@ -1395,7 +1644,7 @@ impl AssociationInternal {
         }
 
         // Process selective ack
-        let (bytes_acked_per_stream, htna) = self.process_selective_ack(d).await?;
+        let (bytes_acked_per_stream, htna) = self.process_selective_ack(d)?;
 
         let mut total_bytes_acked = 0;
         for n_bytes_acked in bytes_acked_per_stream.values() {
@@ -1413,13 +1662,15 @@ impl AssociationInternal {
 
             self.cumulative_tsn_ack_point = d.cumulative_tsn_ack;
             cum_tsn_ack_point_advanced = true;
-            self.on_cumulative_tsn_ack_point_advanced(total_bytes_acked)
-                .await;
+            self.on_cumulative_tsn_ack_point_advanced(total_bytes_acked);
         }
 
         for (si, n_bytes_acked) in &bytes_acked_per_stream {
-            if let Some(s) = self.streams.get_mut(si) {
-                s.on_buffer_released(*n_bytes_acked).await;
+            if let Some(s) = self.streams.get(si) {
+                if s.on_buffer_released(*n_bytes_acked) {
+                    self.events
+                        .push_back(AssociationEvent::BufferedAmountLow(Arc::clone(s)));
+                }
             }
         }
 
@@ -1476,25 +1727,18 @@ impl AssociationInternal {
             self.awake_write_loop();
         }
 
-        self.postprocess_sack(state, cum_tsn_ack_point_advanced)
-            .await;
+        self.postprocess_sack(state, cum_tsn_ack_point_advanced);
 
         Ok(vec![])
     }
 
     /// The caller must hold the lock. This method was only added because the
     /// linter was complaining about the "cognitive complexity" of handle_sack.
-    async fn postprocess_sack(
-        &mut self,
-        state: AssociationState,
-        mut should_awake_write_loop: bool,
-    ) {
+    fn postprocess_sack(&mut self, state: AssociationState, mut should_awake_write_loop: bool) {
         if !self.inflight_queue.is_empty() {
             // Start timer. (noop if already started)
             log::trace!("[{}] T3-rtx timer start (pt3)", self.name);
-            if let Some(t3rtx) = &self.t3rtx {
-                t3rtx.start(self.rto_mgr.get_rto()).await;
-            }
+            self.t3rtx.start(self.now(), self.rto_mgr.get_rto());
         } else if state == AssociationState::ShutdownPending {
             // No more outstanding, send shutdown.
             should_awake_write_loop = true;
@@ -1512,7 +1756,7 @@ impl AssociationInternal {
         }
     }
 
-    async fn handle_shutdown(&mut self, _: &ChunkShutdown) -> Result<Vec<Packet>> {
+    fn handle_shutdown(&mut self, _: &ChunkShutdown) -> Result<Vec<Packet>> {
         let state = self.get_state();
 
         if state == AssociationState::Established {
@@ -1537,12 +1781,10 @@ impl AssociationInternal {
         Ok(vec![])
     }
 
-    async fn handle_shutdown_ack(&mut self, _: &ChunkShutdownAck) -> Result<Vec<Packet>> {
+    fn handle_shutdown_ack(&mut self, _: &ChunkShutdownAck) -> Result<Vec<Packet>> {
         let state = self.get_state();
         if state == AssociationState::ShutdownSent || state == AssociationState::ShutdownAckSent {
-            if let Some(t2shutdown) = &self.t2shutdown {
-                t2shutdown.stop().await;
-            }
+            self.t2shutdown.stop();
             self.will_send_shutdown_complete = true;
 
             self.awake_write_loop();
@@ -1551,13 +1793,66 @@ impl AssociationInternal {
         Ok(vec![])
     }
 
-    async fn handle_shutdown_complete(&mut self, _: &ChunkShutdownComplete) -> Result<Vec<Packet>> {
+    /// handle_congestion_experienced starts echoing the lowest TSN of a
+    /// CE-marked packet to the peer, right away (RFC 4960 Appendix A).
+    fn handle_congestion_experienced(&mut self, p: &Packet) {
+        let lowest_tsn = p
+            .chunks
+            .iter()
+            .filter_map(|c| c.as_any().downcast_ref::<ChunkPayloadData>())
+            .map(|d| d.tsn)
+            .reduce(|a, b| if sna32lt(b, a) { b } else { a });
+        if let Some(lowest_tsn) = lowest_tsn {
+            log::debug!("[{}] CE received for TSN={}", self.name, lowest_tsn);
+            self.ecne_tsn.get_or_insert(lowest_tsn);
+            self.immediate_ack_triggered = true;
+        }
+    }
+
+    /// handle_ecne reduces the congestion window once per window of data the
+    /// peer echoes CE marks for, and answers every ECNE with a CWR chunk.
+    fn handle_ecne(&mut self, c: &ChunkEcne) -> Result<Vec<Packet>> {
+        if !self.use_ecn {
+            return Ok(vec![]);
+        }
+
+        let cwr_tsn = match self.cwr_tsn {
+            Some(cwr_tsn) if sna32lte(c.lowest_tsn, cwr_tsn) => cwr_tsn,
+            _ => {
+                self.congestion_control.on_congestion_experienced();
+                self.stats.inc_ecn_reductions();
+                log::trace!(
+                    "[{}] updated cwnd={} ssthresh={} inflight={} (ECNE)",
+                    self.name,
+                    self.congestion_control.cwnd(),
+                    self.congestion_control.ssthresh(),
+                    self.inflight_queue.get_num_bytes()
+                );
+                let cwr_tsn = self.my_next_tsn.wrapping_sub(1);
+                self.cwr_tsn = Some(cwr_tsn);
+                cwr_tsn
+            }
+        };
+
+        Ok(vec![self.create_packet(vec![Box::new(ChunkCwr {
+            lowest_tsn: cwr_tsn,
+        })])])
+    }
+
+    /// handle_cwr stops echoing CE marks the peer has reacted to.
+    fn handle_cwr(&mut self, c: &ChunkCwr) -> Result<Vec<Packet>> {
+        if matches!(self.ecne_tsn, Some(ecne_tsn) if sna32gte(c.lowest_tsn, ecne_tsn)) {
+            self.ecne_tsn = None;
+        }
+
+        Ok(vec![])
+    }
+
+    fn handle_shutdown_complete(&mut self, _: &ChunkShutdownComplete) -> Result<Vec<Packet>> {
         let state = self.get_state();
         if state == AssociationState::ShutdownAckSent {
-            if let Some(t2shutdown) = &self.t2shutdown {
-                t2shutdown.stop().await;
-            }
-            self.close().await?;
+            self.t2shutdown.stop();
+            self.close()?;
         }
 
         Ok(vec![])
@@ -1571,6 +1866,11 @@ impl AssociationInternal {
         let mut i = self.cumulative_tsn_ack_point + 1;
         while sna32lte(i, self.advanced_peer_tsn_ack_point) {
             if let Some(c) = self.inflight_queue.get(i) {
+                // Unordered chunks carry no SSN of their own to be skipped.
+                if c.unordered {
+                    i += 1;
+                    continue;
+                }
                 if let Some(ssn) = stream_map.get(&c.stream_identifier) {
                     if sna16lt(*ssn, c.stream_sequence_number) {
                         // to report only once with greatest SSN
@@ -1610,6 +1910,64 @@ impl AssociationInternal {
         fwd_tsn
     }
 
+    /// create_i_forward_tsn generates I-FORWARD-TSN chunk, used instead of
+    /// ForwardTSN once I-DATA is negotiated.
+    fn create_i_forward_tsn(&self) -> ChunkIForwardTsn {
+        // RFC 8260 Sec 2.3.1: ordered messages are reported once per SI with
+        // the greatest MID, unordered ones individually.
+        let mut stream_map: HashMap<u16, u32> = HashMap::new();
+        let mut unordered = vec![];
+        let mut i = self.cumulative_tsn_ack_point + 1;
+        while sna32lte(i, self.advanced_peer_tsn_ack_point) {
+            if let Some(c) = self.inflight_queue.get(i) {
+                if c.unordered {
+                    if !unordered.contains(&(c.stream_identifier, c.message_identifier)) {
+                        unordered.push((c.stream_identifier, c.message_identifier));
+                    }
+                } else if let Some(mid) = stream_map.get(&c.stream_identifier) {
+                    if sna32lt(*mid, c.message_identifier) {
+                        stream_map.insert(c.stream_identifier, c.message_identifier);
+                    }
+                } else {
+                    stream_map.insert(c.stream_identifier, c.message_identifier);
+                }
+            } else {
+                break;
+            }
+
+            i += 1;
+        }
+
+        let mut fwd_tsn = ChunkIForwardTsn {
+            new_cumulative_tsn: self.advanced_peer_tsn_ack_point,
+            streams: vec![],
+        };
+
+        for (si, mid) in &stream_map {
+            fwd_tsn.streams.push(ChunkIForwardTsnStream {
+                identifier: *si,
+                unordered: false,
+                message_identifier: *mid,
+            });
+        }
+        for (si, mid) in unordered {
+            fwd_tsn.streams.push(ChunkIForwardTsnStream {
+                identifier: si,
+                unordered: true,
+                message_identifier: mid,
+            });
+        }
+        log::trace!(
+            "[{}] building i_fwd_tsn: newCumulativeTSN={} cumTSN={} - {}",
+            self.name,
+            fwd_tsn.new_cumulative_tsn,
+            self.cumulative_tsn_ack_point,
+            fwd_tsn
+        );
+
+        fwd_tsn
+    }
+
     /// create_packet wraps chunks in a packet.
     /// The caller should hold the read lock.
     pub(crate) fn create_packet(&self, chunks: Vec<Box<dyn Chunk + Send + Sync>>) -> Packet {
@@ -1621,26 +1979,78 @@ impl AssociationInternal {
         }
     }
 
-    async fn handle_reconfig(&mut self, c: &ChunkReconfig) -> Result<Vec<Packet>> {
+    fn handle_reconfig(&mut self, c: &ChunkReconfig) -> Result<Vec<Packet>> {
         log::trace!("[{}] handle_reconfig", self.name);
 
         let mut pp = vec![];
 
         if let Some(param_a) = &c.param_a {
-            self.handle_reconfig_param(param_a, &mut pp).await?;
+            self.handle_reconfig_param(param_a, &mut pp)?;
         }
 
         if let Some(param_b) = &c.param_b {
-            self.handle_reconfig_param(param_b, &mut pp).await?;
+            self.handle_reconfig_param(param_b, &mut pp)?;
         }
 
         Ok(pp)
     }
 
-    async fn handle_forward_tsn(&mut self, c: &ChunkForwardTsn) -> Result<Vec<Packet>> {
+    fn handle_forward_tsn(&mut self, c: &ChunkForwardTsn) -> Result<Vec<Packet>> {
         log::trace!("[{}] FwdTSN: {}", self.name, c.to_string());
 
-        if !self.use_forward_tsn {
+        if let Some(reply) = self.forward_peer_last_tsn(c.new_cumulative_tsn, self.use_forward_tsn)
+        {
+            return Ok(reply);
+        }
+
+        // Report new peer_last_tsn value and abandoned largest SSN value to
+        // corresponding streams so that the abandoned chunks can be removed
+        // from the reassemblyQueue.
+        for forwarded in &c.streams {
+            if let Some(s) = self.streams.get_mut(&forwarded.identifier) {
+                s.handle_forward_tsn_for_ordered(forwarded.sequence);
+            }
+        }
+
+        // TSN may be forewared for unordered chunks. ForwardTSN chunk does not
+        // report which stream identifier it skipped for unordered chunks.
+        // Therefore, we need to broadcast this event to all existing streams for
+        // unordered chunks.
+        // See https://github.com/pion/sctp/issues/106
+        for s in self.streams.values_mut() {
+            s.handle_forward_tsn_for_unordered(c.new_cumulative_tsn);
+        }
+
+        self.handle_peer_last_tsn_and_acknowledgement(false)
+    }
+
+    fn handle_i_forward_tsn(&mut self, c: &ChunkIForwardTsn) -> Result<Vec<Packet>> {
+        log::trace!("[{}] I-FwdTSN: {}", self.name, c.to_string());
+
+        let enabled = self.use_forward_tsn && self.use_interleaving;
+        if let Some(reply) = self.forward_peer_last_tsn(c.new_cumulative_tsn, enabled) {
+            return Ok(reply);
+        }
+
+        // Unlike ForwardTSN, I-FORWARD-TSN names unordered messages as well.
+        for forwarded in &c.streams {
+            if let Some(s) = self.streams.get_mut(&forwarded.identifier) {
+                s.handle_i_forward_tsn(forwarded.unordered, forwarded.message_identifier);
+            }
+        }
+
+        self.handle_peer_last_tsn_and_acknowledgement(false)
+    }
+
+    /// forward_peer_last_tsn performs the TSN handling shared by ForwardTSN and
+    /// I-FORWARD-TSN. It returns the reply to send instead of processing the
+    /// chunk any further, if any.
+    fn forward_peer_last_tsn(
+        &mut self,
+        new_cumulative_tsn: u32,
+        enabled: bool,
+    ) -> Option<Vec<Packet>> {
+        if !enabled {
             log::warn!("[{}] received FwdTSN but not enabled", self.name);
             // Return an error chunk
             let cerr = ChunkError {
@@ -1653,7 +2063,7 @@ impl AssociationInternal {
                 destination_port: self.destination_port,
                 chunks: vec![Box::new(cerr)],
             };
-            return Ok(vec![outbound]);
+            return Some(vec![outbound]);
         }
 
         // From RFC 3758 Sec 3.6:
@@ -1667,17 +2077,15 @@ impl AssociationInternal {
         log::trace!(
             "[{}] should send ack? newCumTSN={} peer_last_tsn={}",
             self.name,
-            c.new_cumulative_tsn,
+            new_cumulative_tsn,
             self.peer_last_tsn
         );
-        if sna32lte(c.new_cumulative_tsn, self.peer_last_tsn) {
+        if sna32lte(new_cumulative_tsn, self.peer_last_tsn) {
             log::trace!("[{}] sending ack on Forward TSN", self.name);
             self.ack_state = AckState::Immediate;
-            if let Some(ack_timer) = &mut self.ack_timer {
-                ack_timer.stop();
-            }
+            self.ack_timer.stop();
             self.awake_write_loop();
-            return Ok(vec![]);
+            return Some(vec![]);
         }
 
         // From RFC 3758 Sec 3.6:
@@ -1691,34 +2099,15 @@ impl AssociationInternal {
         //   chunk,
 
         // Advance peer_last_tsn
-        while sna32lt(self.peer_last_tsn, c.new_cumulative_tsn) {
+        while sna32lt(self.peer_last_tsn, new_cumulative_tsn) {
             self.payload_queue.pop(self.peer_last_tsn + 1); // may not exist
             self.peer_last_tsn += 1;
         }
 
-        // Report new peer_last_tsn value and abandoned largest SSN value to
-        // corresponding streams so that the abandoned chunks can be removed
-        // from the reassemblyQueue.
-        for forwarded in &c.streams {
-            if let Some(s) = self.streams.get_mut(&forwarded.identifier) {
-                s.handle_forward_tsn_for_ordered(forwarded.sequence).await;
-            }
-        }
-
-        // TSN may be forewared for unordered chunks. ForwardTSN chunk does not
-        // report which stream identifier it skipped for unordered chunks.
-        // Therefore, we need to broadcast this event to all existing streams for
-        // unordered chunks.
-        // See https://github.com/pion/sctp/issues/106
-        for s in self.streams.values_mut() {
-            s.handle_forward_tsn_for_unordered(c.new_cumulative_tsn)
-                .await;
-        }
-
-        self.handle_peer_last_tsn_and_acknowledgement(false)
+        None
     }
 
-    async fn send_reset_request(&mut self, stream_identifier: u16) -> Result<()> {
+    fn send_reset_request(&mut self, stream_identifier: u16) -> Result<()> {
         let state = self.get_state();
         if state != AssociationState::Established {
             return Err(Error::ErrResetPacketInStateNotExist);
@@ -1734,36 +2123,253 @@ impl AssociationInternal {
             ..Default::default()
         };
 
-        self.pending_queue.push(c).await;
+        self.pending_queue.push_reset(c);
         self.awake_write_loop();
 
         Ok(())
     }
 
     #[allow(clippy::borrowed_box)]
-    async fn handle_reconfig_param(
+    fn handle_reconfig_param(
         &mut self,
         raw: &Box<dyn Param + Send + Sync>,
         reply: &mut Vec<Packet>,
     ) -> Result<()> {
         if let Some(p) = raw.as_any().downcast_ref::<ParamOutgoingResetRequest>() {
+            // An Outgoing SSN Reset Request answering our Incoming SSN Reset
+            // Request is its implicit response.
+            let rsn = p.reconfig_response_sequence_number;
+            if self.reconfigs.get(&rsn).is_some_and(|c| {
+                c.param_a
+                    .as_ref()
+                    .is_some_and(|param| param.header().typ == ParamType::IncSsnResetReq)
+            }) {
+                self.remove_reconfig(rsn);
+            }
+
             self.reconfig_requests
                 .insert(p.reconfig_request_sequence_number, p.clone());
             self.reset_streams_if_any(p, true, reply)?;
             Ok(())
+        } else if let Some(p) = raw.as_any().downcast_ref::<ParamIncomingResetRequest>() {
+            self.handle_incoming_reset_request(p, reply)
+        } else if let Some(p) = raw.as_any().downcast_ref::<ParamSsnTsnResetRequest>() {
+            // Renumbering the TSNs while DATA may be in flight either way is
+            // not supported. RFC 6525 Sec 5.2.4 lets us deny the request.
+            log::debug!(
+                "[{}] denied SSN/TSN reset request rsn={}",
+                self.name,
+                p.reconfig_request_sequence_number
+            );
+            reply.push(self.create_reconfig_response_packet(
+                p.reconfig_request_sequence_number,
+                ReconfigResult::Denied,
+            ));
+            Ok(())
+        } else if let Some(p) = raw.as_any().downcast_ref::<ParamAddStreamsRequest>() {
+            self.handle_add_streams_request(p, reply);
+            Ok(())
         } else if let Some(p) = raw.as_any().downcast_ref::<ParamReconfigResponse>() {
-            self.reconfigs.remove(&p.reconfig_response_sequence_number);
-            if self.reconfigs.is_empty() {
-                if let Some(treconfig) = &self.treconfig {
-                    treconfig.stop().await;
+            let rsn = p.reconfig_response_sequence_number;
+            if let Some(req) = self.add_streams_requests.remove(&rsn) {
+                let performed = p.result == ReconfigResult::SuccessPerformed;
+                if performed {
+                    // The peer added the streams on its side already.
+                    if req.incoming {
+                        self.my_max_num_inbound_streams = self
+                            .my_max_num_inbound_streams
+                            .saturating_add(req.number_of_new_streams);
+                    } else {
+                        self.my_max_num_outbound_streams = self
+                            .my_max_num_outbound_streams
+                            .saturating_add(req.number_of_new_streams);
+                    }
                 }
+                self.events.push_back(AssociationEvent::AddStreamsAnswered {
+                    request_sequence_number: rsn,
+                    performed,
+                });
             }
+            self.remove_reconfig(rsn);
             Ok(())
         } else {
             Err(Error::ErrParameterType)
         }
     }
 
+    /// remove_reconfig stops retransmitting an answered request.
+    fn remove_reconfig(&mut self, rsn: u32) {
+        self.reconfigs.remove(&rsn);
+        if self.reconfigs.is_empty() {
+            self.treconfig.stop();
+        }
+    }
+
+    /// handle_incoming_reset_request resets the outgoing streams the peer asks
+    /// for, all of them if it lists none. As for Stream::shutdown, data already
+    /// queued on them is sent before the Outgoing SSN Reset Request.
+    fn handle_incoming_reset_request(
+        &mut self,
+        p: &ParamIncomingResetRequest,
+        reply: &mut Vec<Packet>,
+    ) -> Result<()> {
+        let mut sis_to_reset: Vec<u16> = if p.stream_identifiers.is_empty() {
+            self.streams.keys().cloned().collect()
+        } else {
+            p.stream_identifiers
+                .iter()
+                .filter(|id| self.streams.contains_key(id))
+                .cloned()
+                .collect()
+        };
+        sis_to_reset.sort_unstable();
+
+        log::debug!(
+            "[{}] incoming reset request rsn={} streams={:?}",
+            self.name,
+            p.reconfig_request_sequence_number,
+            sis_to_reset
+        );
+
+        let result = if sis_to_reset.is_empty() {
+            ReconfigResult::SuccessNop
+        } else {
+            ReconfigResult::SuccessPerformed
+        };
+        for stream_identifier in sis_to_reset {
+            self.send_reset_request(stream_identifier)?;
+        }
+
+        reply
+            .push(self.create_reconfig_response_packet(p.reconfig_request_sequence_number, result));
+
+        Ok(())
+    }
+
+    /// handle_add_streams_request grants a request of the peer to add streams,
+    /// unless there would be more than u16::MAX of them.
+    fn handle_add_streams_request(&mut self, p: &ParamAddStreamsRequest, reply: &mut Vec<Packet>) {
+        let rsn = p.reconfig_request_sequence_number;
+        let result = if let Some(result) = self.add_streams_responses.get(&rsn) {
+            // Retransmitted request, the streams have been added already.
+            *result
+        } else {
+            // Streams the peer adds to its outgoing side are our incoming
+            // ones, and the other way round.
+            let num_streams = if p.incoming {
+                &mut self.my_max_num_outbound_streams
+            } else {
+                &mut self.my_max_num_inbound_streams
+            };
+            let result = if let Some(n) = num_streams.checked_add(p.number_of_new_streams) {
+                *num_streams = n;
+                ReconfigResult::SuccessPerformed
+            } else {
+                ReconfigResult::Denied
+            };
+
+            log::debug!(
+                "[{}] add streams request rsn={} incoming={} streams={}: {}",
+                self.name,
+                rsn,
+                p.incoming,
+                p.number_of_new_streams,
+                result
+            );
+
+            self.add_streams_responses
+                .retain(|r, _| sna32gte(*r, rsn.wrapping_sub(1)));
+            self.add_streams_responses.insert(rsn, result);
+            result
+        };
+
+        reply.push(self.create_reconfig_response_packet(rsn, result));
+    }
+
+    fn create_reconfig_response_packet(&self, rsn: u32, result: ReconfigResult) -> Packet {
+        self.create_packet(vec![Box::new(ChunkReconfig {
+            param_a: Some(Box::new(ParamReconfigResponse {
+                reconfig_response_sequence_number: rsn,
+                result,
+            })),
+            param_b: None,
+        })])
+    }
+
+    /// send_reconfig sends a new request and retransmits it until it is answered.
+    fn send_reconfig(&mut self, rsn: u32, c: ChunkReconfig) {
+        log::debug!("[{}] sending RECONFIG: {}", self.name, c);
+        self.reconfigs.insert(rsn, c.clone()); // store in the map for retransmission
+        let p = self.create_packet(vec![Box::new(c)]);
+        self.control_queue.push_back(p);
+        self.treconfig.start(self.now(), self.rto_mgr.get_rto());
+        self.awake_write_loop();
+    }
+
+    /// send_incoming_reset_request asks the peer to reset the given streams
+    /// in the direction towards us, all of them if none are given.
+    pub(crate) fn send_incoming_reset_request(
+        &mut self,
+        now: Instant,
+        stream_identifiers: Vec<u16>,
+    ) -> Result<()> {
+        self.now = Some(now);
+        if self.get_state() != AssociationState::Established {
+            return Err(Error::ErrResetPacketInStateNotExist);
+        }
+
+        let rsn = self.generate_next_rsn();
+        let c = ChunkReconfig {
+            param_a: Some(Box::new(ParamIncomingResetRequest {
+                reconfig_request_sequence_number: rsn,
+                stream_identifiers,
+            })),
+            param_b: None,
+        };
+        self.send_reconfig(rsn, c);
+
+        Ok(())
+    }
+
+    /// send_add_streams_request asks the peer to add incoming or outgoing
+    /// streams, and returns the sequence number of the request. The answer
+    /// of the peer is reported by an AssociationEvent::AddStreamsAnswered.
+    pub fn send_add_streams_request(
+        &mut self,
+        now: Instant,
+        incoming: bool,
+        number_of_new_streams: u16,
+    ) -> Result<u32> {
+        self.now = Some(now);
+        if self.get_state() != AssociationState::Established {
+            return Err(Error::ErrResetPacketInStateNotExist);
+        }
+
+        let num_streams = if incoming {
+            self.my_max_num_inbound_streams
+        } else {
+            self.my_max_num_outbound_streams
+        };
+        if num_streams.checked_add(number_of_new_streams).is_none() {
+            return Err(Error::ErrTooManyStreams);
+        }
+
+        let rsn = self.generate_next_rsn();
+        let p = ParamAddStreamsRequest {
+            incoming,
+            reconfig_request_sequence_number: rsn,
+            number_of_new_streams,
+        };
+        self.add_streams_requests.insert(rsn, p.clone());
+        let c = ChunkReconfig {
+            param_a: Some(Box::new(p)),
+            param_b: None,
+        };
+        self.send_reconfig(rsn, c);
+
+        Ok(rsn)
+    }
+
     fn reset_streams_if_any(
         &mut self,
         p: &ParamOutgoingResetRequest,
@@ -1823,13 +2429,8 @@ impl AssociationInternal {
             reply.push(p);
         }
 
-        let packet = self.create_packet(vec![Box::new(ChunkReconfig {
-            param_a: Some(Box::new(ParamReconfigResponse {
-                reconfig_response_sequence_number: p.reconfig_request_sequence_number,
-                result,
-            })),
-            param_b: None,
-        })]);
+        let packet =
+            self.create_reconfig_response_packet(p.reconfig_request_sequence_number, result);
 
         log::debug!("[{}] RESET RESPONSE: {}", self.name, packet);
 
@@ -1839,7 +2440,7 @@ impl AssociationInternal {
     }
 
     /// Move the chunk peeked with self.pending_queue.peek() to the inflight_queue.
-    async fn move_pending_data_chunk_to_inflight_queue(
+    fn move_pending_data_chunk_to_inflight_queue(
         &mut self,
         beginning_fragment: bool,
         unordered: bool,
@@ -1852,8 +2453,11 @@ impl AssociationInternal {
 
             // Assign TSN
             c.tsn = self.generate_next_tsn();
+            c.interleaved = self.use_interleaving;
+            c.immediate_sack =
+                self.immediate_sack && c.ending_fragment && self.pending_queue.is_empty();
 
-            c.since = SystemTime::now(); // use to calculate RTT and also for maxPacketLifeTime
+            c.since = self.now(); // use to calculate RTT and also for maxPacketLifeTime
             c.nsent = 1; // being sent for the first time
 
             self.check_partial_reliability_status(&c);
@@ -1881,7 +2485,7 @@ impl AssociationInternal {
 
     /// pop_pending_data_chunks_to_send pops chunks from the pending queues as many as
     /// the cwnd and rwnd allows to send.
-    async fn pop_pending_data_chunks_to_send(&mut self) -> (Vec<ChunkPayloadData>, Vec<u16>) {
+    fn pop_pending_data_chunks_to_send(&mut self) -> (Vec<ChunkPayloadData>, Vec<u16>) {
         let mut chunks = vec![];
         let mut sis_to_reset = vec![]; // stream identifiers to reset
 
@@ -1916,7 +2520,9 @@ impl AssociationInternal {
                 continue;
             }
 
-            if self.inflight_queue.get_num_bytes() + data_len > self.cwnd as usize {
+            if self.inflight_queue.get_num_bytes() + data_len
+                > self.congestion_control.cwnd() as usize
+            {
                 break; // would exceed cwnd
             }
 
@@ -1926,9 +2532,8 @@ impl AssociationInternal {
 
             self.rwnd -= data_len as u32;
 
-            if let Some(chunk) = self
-                .move_pending_data_chunk_to_inflight_queue(beginning_fragment, unordered)
-                .await
+            if let Some(chunk) =
+                self.move_pending_data_chunk_to_inflight_queue(beginning_fragment, unordered)
             {
                 chunks.push(chunk);
             }
@@ -1940,9 +2545,8 @@ impl AssociationInternal {
             if let Some(c) = self.pending_queue.peek() {
                 let (beginning_fragment, unordered) = (c.beginning_fragment, c.unordered);
 
-                if let Some(chunk) = self
-                    .move_pending_data_chunk_to_inflight_queue(beginning_fragment, unordered)
-                    .await
+                if let Some(chunk) =
+                    self.move_pending_data_chunk_to_inflight_queue(beginning_fragment, unordered)
                 {
                     chunks.push(chunk);
                 }
@@ -1972,7 +2576,7 @@ impl AssociationInternal {
                 bytes_in_packet = COMMON_HEADER_SIZE;
             }
 
-            bytes_in_packet += DATA_CHUNK_HEADER_SIZE + c.user_data.len() as u32;
+            bytes_in_packet += data_chunk_header_size(c.interleaved) + c.user_data.len() as u32;
             chunks_to_send.push(Box::new(c));
         }
 
@@ -1999,9 +2603,12 @@ impl AssociationInternal {
 
         // PR-SCTP
         if let Some(s) = self.streams.get(&c.stream_identifier) {
-            let reliability_type: ReliabilityType =
-                s.reliability_type.load(Ordering::SeqCst).into();
-            let reliability_value = s.reliability_value.load(Ordering::SeqCst);
+            let (reliability_type, reliability_value) = c.reliability.unwrap_or_else(|| {
+                (
+                    s.reliability_type.load(Ordering::SeqCst).into(),
+                    s.reliability_value.load(Ordering::SeqCst),
+                )
+            });
 
             if reliability_type == ReliabilityType::Rexmit {
                 if c.nsent >= reliability_value {
@@ -2015,17 +2622,16 @@ impl AssociationInternal {
                     );
                 }
             } else if reliability_type == ReliabilityType::Timed {
-                if let Ok(elapsed) = SystemTime::now().duration_since(c.since) {
-                    if elapsed.as_millis() as u32 >= reliability_value {
-                        c.set_abandoned(true);
-                        log::trace!(
-                            "[{}] marked as abandoned: tsn={} ppi={} (timed: {:?})",
-                            self.name,
-                            c.tsn,
-                            c.payload_type,
-                            elapsed
-                        );
-                    }
+                let elapsed = self.now().saturating_duration_since(c.since);
+                if elapsed.as_millis() as u32 >= reliability_value {
+                    c.set_abandoned(true);
+                    log::trace!(
+                        "[{}] marked as abandoned: tsn={} ppi={} (timed: {:?})",
+                        self.name,
+                        c.tsn,
+                        c.payload_type,
+                        elapsed
+                    );
                 }
             }
         } else {
@@ -2033,10 +2639,42 @@ impl AssociationInternal {
         }
     }
 
+    /// count_retransmission accounts a DATA chunk sent again to the
+    /// association and its stream.
+    fn count_retransmission(&self, c: &ChunkPayloadData) {
+        self.stats.inc_retrans();
+        if let Some(s) = self.streams.get(&c.stream_identifier) {
+            s.retransmitted_chunks.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// get_stats returns a snapshot of the association's statistics.
+    pub(crate) fn get_stats(&self) -> AssociationStatsReport {
+        AssociationStatsReport {
+            cwnd: self.congestion_control.cwnd(),
+            ssthresh: self.congestion_control.ssthresh(),
+            bytes_in_flight: self.inflight_queue.get_num_bytes(),
+            peer_receiver_window: self.rwnd,
+            receiver_window: self.get_my_receiver_window_credit(),
+            mtu: self.mtu,
+            srtt: Duration::from_millis(self.rto_mgr.srtt),
+            rto: Duration::from_millis(self.rto_mgr.get_rto()),
+            datas_received: self.stats.get_num_datas(),
+            sacks_received: self.stats.get_num_sacks(),
+            retransmitted_chunks: self.stats.get_num_retrans(),
+            fast_retransmits: self.stats.get_num_fast_recoveries(),
+            fast_retransmitted_chunks: self.stats.get_num_fast_retrans(),
+            t3_timeouts: self.stats.get_num_t3timeouts(),
+            ecn_reductions: self.stats.get_num_ecn_reductions(),
+            bytes_buffered: self.send_buffer.used(),
+            ..Default::default()
+        }
+    }
+
     /// get_data_packets_to_retransmit is called when T3-rtx is timed out and retransmit outstanding data chunks
     /// that are not acked or abandoned yet.
     fn get_data_packets_to_retransmit(&mut self) -> Vec<Packet> {
-        let awnd = std::cmp::min(self.cwnd, self.rwnd);
+        let awnd = std::cmp::min(self.congestion_control.cwnd(), self.rwnd);
         let mut chunks = vec![];
         let mut bytes_to_send = 0;
         let mut done = false;
@@ -2067,6 +2705,7 @@ impl AssociationInternal {
             }
 
             if let Some(c) = self.inflight_queue.get(tsn) {
+                self.count_retransmission(c);
                 self.check_partial_reliability_status(c);
 
                 log::trace!(
@@ -2099,10 +2738,10 @@ impl AssociationInternal {
         rsn
     }
 
-    async fn create_selective_ack_chunk(&mut self) -> ChunkSelectiveAck {
+    fn create_selective_ack_chunk(&mut self) -> ChunkSelectiveAck {
         ChunkSelectiveAck {
             cumulative_tsn_ack: self.peer_last_tsn,
-            advertised_receiver_window_credit: self.get_my_receiver_window_credit().await,
+            advertised_receiver_window_credit: self.get_my_receiver_window_credit(),
             gap_ack_blocks: self.payload_queue.get_gap_ack_blocks(self.peer_last_tsn),
             duplicate_tsn: self.payload_queue.pop_duplicates(),
         }
@@ -2120,57 +2759,58 @@ impl AssociationInternal {
     fn handle_chunk_end(&mut self) {
         if self.immediate_ack_triggered {
             self.ack_state = AckState::Immediate;
-            if let Some(ack_timer) = &mut self.ack_timer {
-                ack_timer.stop();
-            }
+            self.ack_timer.stop();
             self.awake_write_loop();
         } else if self.delayed_ack_triggered {
             // Will send delayed ack in the next ack timeout
             self.ack_state = AckState::Delay;
-            if let Some(ack_timer) = &mut self.ack_timer {
-                ack_timer.start();
-            }
+            self.packets_since_sack += 1;
+            self.ack_timer.start(self.now());
         }
     }
 
     #[allow(clippy::borrowed_box)]
-    async fn handle_chunk(
-        &mut self,
-        p: &Packet,
-        chunk: &Box<dyn Chunk + Send + Sync>,
-    ) -> Result<()> {
+    fn handle_chunk(&mut self, p: &Packet, chunk: &Box<dyn Chunk + Send + Sync>) -> Result<()> {
         chunk.check()?;
         let chunk_any = chunk.as_any();
         let packets = if let Some(c) = chunk_any.downcast_ref::<ChunkInit>() {
             if c.is_ack {
-                self.handle_init_ack(p, c).await?
+                self.handle_init_ack(p, c)?
             } else {
-                self.handle_init(p, c).await?
+                self.handle_init(p, c)?
             }
         } else if chunk_any.downcast_ref::<ChunkAbort>().is_some()
             || chunk_any.downcast_ref::<ChunkError>().is_some()
         {
             return Err(Error::ErrChunk);
         } else if let Some(c) = chunk_any.downcast_ref::<ChunkHeartbeat>() {
-            self.handle_heartbeat(c).await?
+            self.handle_heartbeat(c)?
+        } else if let Some(c) = chunk_any.downcast_ref::<ChunkHeartbeatAck>() {
+            self.handle_heartbeat_ack(c)?
         } else if let Some(c) = chunk_any.downcast_ref::<ChunkCookieEcho>() {
-            self.handle_cookie_echo(c).await?
+            self.handle_cookie_echo(c)?
         } else if chunk_any.downcast_ref::<ChunkCookieAck>().is_some() {
-            self.handle_cookie_ack().await?
+            self.handle_cookie_ack()?
         } else if let Some(c) = chunk_any.downcast_ref::<ChunkPayloadData>() {
-            self.handle_data(c).await?
+            self.handle_data(c)?
         } else if let Some(c) = chunk_any.downcast_ref::<ChunkSelectiveAck>() {
-            self.handle_sack(c).await?
+            self.handle_sack(c)?
         } else if let Some(c) = chunk_any.downcast_ref::<ChunkReconfig>() {
-            self.handle_reconfig(c).await?
+            self.handle_reconfig(c)?
         } else if let Some(c) = chunk_any.downcast_ref::<ChunkForwardTsn>() {
-            self.handle_forward_tsn(c).await?
+            self.handle_forward_tsn(c)?
+        } else if let Some(c) = chunk_any.downcast_ref::<ChunkIForwardTsn>() {
+            self.handle_i_forward_tsn(c)?
         } else if let Some(c) = chunk_any.downcast_ref::<ChunkShutdown>() {
-            self.handle_shutdown(c).await?
+            self.handle_shutdown(c)?
         } else if let Some(c) = chunk_any.downcast_ref::<ChunkShutdownAck>() {
-            self.handle_shutdown_ack(c).await?
+            self.handle_shutdown_ack(c)?
         } else if let Some(c) = chunk_any.downcast_ref::<ChunkShutdownComplete>() {
-            self.handle_shutdown_complete(c).await?
+            self.handle_shutdown_complete(c)?
+        } else if let Some(c) = chunk_any.downcast_ref::<ChunkEcne>() {
+            self.handle_ecne(c)?
+        } else if let Some(c) = chunk_any.downcast_ref::<ChunkCwr>() {
+            self.handle_cwr(c)?
         } else {
             /*
             https://datatracker.ietf.org/doc/html/rfc4960#section-3
@@ -2250,11 +2890,88 @@ impl AssociationInternal {
     pub(crate) fn buffered_amount(&self) -> usize {
         self.pending_queue.get_num_bytes() + self.inflight_queue.get_num_bytes()
     }
-}
 
-#[async_trait]
-impl AckTimerObserver for AssociationInternal {
-    async fn on_ack_timeout(&mut self) {
+    /// poll_output returns the packets to send to the peer, as datagrams.
+    /// The association is closed after its SHUTDOWN COMPLETE has been returned.
+    pub fn poll_output(&mut self, now: Instant) -> Result<Vec<Bytes>> {
+        let (packets, continue_loop) = self.gather_outbound(now);
+
+        let mut buf = BytesMut::new();
+        let mut datagrams = Vec::with_capacity(packets.len());
+        for p in packets {
+            p.marshal_to_with(&mut buf, self.use_zero_checksum)?;
+            datagrams.push(buf.split().freeze());
+        }
+
+        if !continue_loop {
+            self.close()?;
+        }
+
+        Ok(datagrams)
+    }
+
+    /// poll_timeout returns when handle_timeout should be called next, if
+    /// any timer is running.
+    pub fn poll_timeout(&self) -> Option<Instant> {
+        [
+            self.t1init.deadline(),
+            self.t1cookie.deadline(),
+            self.t2shutdown.deadline(),
+            self.t3rtx.deadline(),
+            self.treconfig.deadline(),
+            self.ack_timer.deadline(),
+            self.heartbeat_timer.deadline(),
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+    }
+
+    /// handle_timeout handles the timers expired by now.
+    pub fn handle_timeout(&mut self, now: Instant) {
+        self.now = Some(now);
+
+        for id in [
+            RtxTimerId::T1Init,
+            RtxTimerId::T1Cookie,
+            RtxTimerId::T2Shutdown,
+            RtxTimerId::T3RTX,
+            RtxTimerId::Reconfig,
+        ] {
+            let timer = match id {
+                RtxTimerId::T1Init => &mut self.t1init,
+                RtxTimerId::T1Cookie => &mut self.t1cookie,
+                RtxTimerId::T2Shutdown => &mut self.t2shutdown,
+                RtxTimerId::T3RTX => &mut self.t3rtx,
+                RtxTimerId::Reconfig => &mut self.treconfig,
+            };
+            match timer.handle_timeout(now) {
+                Some(RtxTimerEvent::Timeout(n_rtos)) => self.on_retransmission_timeout(id, n_rtos),
+                Some(RtxTimerEvent::Failure) => self.on_retransmission_failure(id),
+                None => {}
+            }
+        }
+
+        if self.ack_timer.handle_timeout(now) {
+            self.on_ack_timeout();
+        }
+
+        if self.heartbeat_timer.handle_timeout(now) {
+            self.on_heartbeat_timeout();
+        }
+    }
+
+    /// poll_event returns the next event of the association.
+    pub fn poll_event(&mut self) -> Option<AssociationEvent> {
+        self.events.pop_front()
+    }
+
+    /// now returns the time passed to the call being handled.
+    fn now(&self) -> Instant {
+        self.now.unwrap_or_else(Instant::now)
+    }
+
+    fn on_ack_timeout(&mut self) {
         log::trace!(
             "[{}] ack timed out (ack_state: {})",
             self.name,
@@ -2264,11 +2981,37 @@ impl AckTimerObserver for AssociationInternal {
         self.ack_state = AckState::Immediate;
         self.awake_write_loop();
     }
-}
 
-#[async_trait]
-impl RtxTimerObserver for AssociationInternal {
-    async fn on_retransmission_timeout(&mut self, id: RtxTimerId, n_rtos: usize) {
+    pub(crate) fn on_heartbeat_timeout(&mut self) {
+        if self.get_state() != AssociationState::Established {
+            return;
+        }
+
+        // RFC 4960 sec 8.3
+        //  An endpoint should increment the respective error counter of the
+        //  destination transport address each time a HEARTBEAT is sent to that
+        //  address and not acknowledged within one RTO.
+        if let Some((_, since)) = self.heartbeat_sent {
+            let rto = Duration::from_millis(self.rto_mgr.get_rto());
+            if self.now().saturating_duration_since(since) < rto {
+                return;
+            }
+
+            log::debug!("[{}] heartbeat not acknowledged", self.name);
+            self.heartbeat_sent = None;
+            self.on_path_failure();
+            if self.get_state() == AssociationState::Closed {
+                return;
+            }
+        }
+
+        // Only idle paths are probed, the T3-rtx timer looks after the others.
+        if self.inflight_queue.is_empty() || self.path_inactive {
+            self.send_heartbeat();
+        }
+    }
+
+    fn on_retransmission_timeout(&mut self, id: RtxTimerId, n_rtos: usize) {
         match id {
             RtxTimerId::T1Init => {
                 if let Err(err) = self.send_init() {
@@ -2315,23 +3058,21 @@ impl RtxTimerObserver for AssociationInternal {
             RtxTimerId::T3RTX => {
                 self.stats.inc_t3timeouts();
 
+                self.on_path_failure();
+                if self.get_state() == AssociationState::Closed {
+                    return;
+                }
+
                 // RFC 4960 sec 6.3.3
                 //  E1)  For the destination address for which the timer expires, adjust
                 //       its ssthresh with rules defined in Section 7.2.3 and set the
                 //       cwnd <- MTU.
-                // RFC 4960 sec 7.2.3
-                //   When the T3-rtx timer expires on an address, SCTP should perform slow
-                //   start by:
-                //      ssthresh = max(cwnd/2, 4*MTU)
-                //      cwnd = 1*MTU
-
-                self.ssthresh = std::cmp::max(self.cwnd / 2, 4 * self.mtu);
-                self.cwnd = self.mtu;
+                self.congestion_control.on_retransmission_timeout();
                 log::trace!(
                     "[{}] updated cwnd={} ssthresh={} inflight={} (RTO)",
                     self.name,
-                    self.cwnd,
-                    self.ssthresh,
+                    self.congestion_control.cwnd(),
+                    self.congestion_control.ssthresh(),
                     self.inflight_queue.get_num_bytes()
                 );
 
@@ -2370,8 +3111,8 @@ impl RtxTimerObserver for AssociationInternal {
                     "[{}] T3-rtx timed out: n_rtos={} cwnd={} ssthresh={}",
                     self.name,
                     n_rtos,
-                    self.cwnd,
-                    self.ssthresh
+                    self.congestion_control.cwnd(),
+                    self.congestion_control.ssthresh()
                 );
 
                 self.inflight_queue.mark_all_to_retrasmit();
@@ -2385,23 +3126,19 @@ impl RtxTimerObserver for AssociationInternal {
         }
     }
 
-    async fn on_retransmission_failure(&mut self, id: RtxTimerId) {
+    fn on_retransmission_failure(&mut self, id: RtxTimerId) {
         match id {
             RtxTimerId::T1Init => {
                 log::error!("[{}] retransmission failure: T1-init", self.name);
-                if let Some(handshake_completed_ch) = &self.handshake_completed_ch_tx {
-                    let _ = handshake_completed_ch
-                        .send(Some(Error::ErrHandshakeInitAck))
-                        .await;
-                }
+                self.events.push_back(AssociationEvent::HandshakeFailed(
+                    Error::ErrHandshakeInitAck,
+                ));
             }
             RtxTimerId::T1Cookie => {
                 log::error!("[{}] retransmission failure: T1-cookie", self.name);
-                if let Some(handshake_completed_ch) = &self.handshake_completed_ch_tx {
-                    let _ = handshake_completed_ch
-                        .send(Some(Error::ErrHandshakeCookieEcho))
-                        .await;
-                }
+                self.events.push_back(AssociationEvent::HandshakeFailed(
+                    Error::ErrHandshakeCookieEcho,
+                ));
             }
 
             RtxTimerId::T2Shutdown => {
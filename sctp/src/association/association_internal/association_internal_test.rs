@@ -1,6 +1,8 @@
 use std::io;
 use std::net::SocketAddr;
 
+use async_trait::async_trait;
+
 use super::*;
 
 type Result<T> = std::result::Result<T, util::Error>;
@@ -53,17 +55,7 @@ impl Conn for DumbConn {
 }
 
 fn create_association_internal(config: Config) -> AssociationInternal {
-    let (close_loop_ch_tx, _close_loop_ch_rx) = broadcast::channel(1);
-    let (accept_ch_tx, _accept_ch_rx) = mpsc::channel(1);
-    let (handshake_completed_ch_tx, _handshake_completed_ch_rx) = mpsc::channel(1);
-    let (awake_write_loop_ch_tx, _awake_write_loop_ch_rx) = mpsc::channel(1);
-    AssociationInternal::new(
-        config,
-        close_loop_ch_tx,
-        accept_ch_tx,
-        handshake_completed_ch_tx,
-        Arc::new(awake_write_loop_ch_tx),
-    )
+    AssociationInternal::new(config, Arc::new(|| {}))
 }
 
 #[test]
@@ -164,6 +156,50 @@ fn test_create_forward_tsn_forward_two_abandoned_with_the_same_si() -> Result<()
     Ok(())
 }
 
+#[test]
+fn test_create_forward_tsn_skips_unordered() -> Result<()> {
+    let mut a = AssociationInternal {
+        cumulative_tsn_ack_point: 9,
+        ..Default::default()
+    };
+
+    // An unordered message sent on an ordered stream carries the SSN of the
+    // next ordered message, which must not be skipped.
+    a.advanced_peer_tsn_ack_point = 11;
+    a.inflight_queue.push_no_check(ChunkPayloadData {
+        beginning_fragment: true,
+        ending_fragment: true,
+        tsn: 10,
+        stream_identifier: 1,
+        stream_sequence_number: 2,
+        user_data: Bytes::from_static(b"ABC"),
+        nsent: 1,
+        abandoned: Arc::new(AtomicBool::new(true)),
+        ..Default::default()
+    });
+    a.inflight_queue.push_no_check(ChunkPayloadData {
+        unordered: true,
+        beginning_fragment: true,
+        ending_fragment: true,
+        tsn: 11,
+        stream_identifier: 1,
+        stream_sequence_number: 3,
+        user_data: Bytes::from_static(b"DEF"),
+        nsent: 1,
+        abandoned: Arc::new(AtomicBool::new(true)),
+        ..Default::default()
+    });
+
+    let fwdtsn = a.create_forward_tsn();
+
+    assert_eq!(fwdtsn.new_cumulative_tsn, 11, "should be able to serialize");
+    assert_eq!(fwdtsn.streams.len(), 1, "there should be one stream");
+    assert_eq!(fwdtsn.streams[0].identifier, 1, "si should be 1");
+    assert_eq!(fwdtsn.streams[0].sequence, 2, "ssn should be 2");
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_handle_forward_tsn_forward_3unreceived_chunks() -> Result<()> {
     let mut a = AssociationInternal {
@@ -181,7 +217,7 @@ async fn test_handle_forward_tsn_forward_3unreceived_chunks() -> Result<()> {
         }],
     };
 
-    let p = a.handle_forward_tsn(&fwdtsn).await?;
+    let p = a.handle_forward_tsn(&fwdtsn)?;
 
     let delayed_ack_triggered = a.delayed_ack_triggered;
     let immediate_ack_triggered = a.immediate_ack_triggered;
@@ -231,7 +267,7 @@ async fn test_handle_forward_tsn_forward_1for1_missing() -> Result<()> {
         }],
     };
 
-    let p = a.handle_forward_tsn(&fwdtsn).await?;
+    let p = a.handle_forward_tsn(&fwdtsn)?;
 
     let delayed_ack_triggered = a.delayed_ack_triggered;
     let immediate_ack_triggered = a.immediate_ack_triggered;
@@ -281,7 +317,7 @@ async fn test_handle_forward_tsn_forward_1for2_missing() -> Result<()> {
         }],
     };
 
-    let p = a.handle_forward_tsn(&fwdtsn).await?;
+    let p = a.handle_forward_tsn(&fwdtsn)?;
 
     let immediate_ack_triggered = a.immediate_ack_triggered;
     assert_eq!(
@@ -315,7 +351,7 @@ async fn test_handle_forward_tsn_dup_forward_tsn_chunk_should_generate_sack() ->
         }],
     };
 
-    let p = a.handle_forward_tsn(&fwdtsn).await?;
+    let p = a.handle_forward_tsn(&fwdtsn)?;
 
     assert_eq!(a.peer_last_tsn, prev_tsn, "peerLastTSN should not advance");
     assert_eq!(a.ack_state, AckState::Immediate, "sack should be requested");
@@ -326,9 +362,21 @@ async fn test_handle_forward_tsn_dup_forward_tsn_chunk_should_generate_sack() ->
 
 #[tokio::test]
 async fn test_assoc_create_new_stream() -> Result<()> {
-    let (accept_ch_tx, _accept_ch_rx) = mpsc::channel(ACCEPT_CH_SIZE);
+    let (accept_ch_tx, mut accept_ch_rx) = mpsc::channel(ACCEPT_CH_SIZE);
+    let (handshake_completed_ch_tx, _handshake_completed_ch_rx) = mpsc::channel(1);
+    let driver = Driver {
+        senders: SyncMutex::new(EventSenders {
+            close_loop_ch_tx: None,
+            accept_ch_tx,
+            path_status_ch_tx: None,
+            handshake_completed_ch_tx,
+            add_streams_answers: HashMap::new(),
+        }),
+        timeout: SyncMutex::new(None),
+        timeout_changed: Notify::new(),
+    };
     let mut a = AssociationInternal {
-        accept_ch_tx: Some(accept_ch_tx),
+        my_max_num_inbound_streams: u16::MAX,
         ..Default::default()
     };
 
@@ -340,13 +388,20 @@ async fn test_assoc_create_new_stream() -> Result<()> {
         } else {
             panic!("{i} should success");
         }
+        driver.dispatch(&mut a);
     }
 
+    // The stream is dropped once the accept queue is full.
     let new_si = ACCEPT_CH_SIZE as u16;
     let s = a.create_stream(new_si, true);
-    assert!(s.is_none(), "should be none");
+    assert!(s.is_some(), "should be created");
+    driver.dispatch(&mut a);
     let result = a.streams.get(&new_si);
     assert!(result.is_none(), "should NOT be in a.streams map");
+    for i in 0..ACCEPT_CH_SIZE {
+        let s = accept_ch_rx.try_recv().expect("should be accepted");
+        assert_eq!(s.stream_identifier, i as u16);
+    }
 
     let to_be_ignored = ChunkPayloadData {
         beginning_fragment: true,
@@ -357,7 +412,7 @@ async fn test_assoc_create_new_stream() -> Result<()> {
         ..Default::default()
     };
 
-    let p = a.handle_data(&to_be_ignored).await?;
+    let p = a.handle_data(&to_be_ignored)?;
     assert!(p.is_empty(), "should return empty");
 
     Ok(())
@@ -369,6 +424,19 @@ async fn handle_init_test(name: &str, initial_state: AssociationState, expect_er
         max_receive_buffer_size: 0,
         max_message_size: 0,
         name: "client".to_owned(),
+        stream_scheduler: StreamScheduler::default(),
+        congestion_control: CongestionControlAlgorithm::default(),
+        sack_delay: Duration::ZERO,
+        packets_per_sack: 0,
+        immediate_sack: false,
+        zero_checksum: false,
+        max_num_outbound_streams: 0,
+        max_num_inbound_streams: 0,
+        heartbeat_interval: Duration::ZERO,
+        path_max_retransmits: 0,
+        association_max_retransmits: 0,
+        ecn: false,
+        max_send_buffer_size: 0,
     });
     a.set_state(initial_state);
     let pkt = Packet {
@@ -386,7 +454,7 @@ async fn handle_init_test(name: &str, initial_state: AssociationState, expect_er
     };
     init.set_supported_extensions();
 
-    let result = a.handle_init(&pkt, &init).await;
+    let result = a.handle_init(&pkt, &init);
     if expect_err {
         assert!(result.is_err(), "{name} should fail");
         return;
@@ -402,12 +470,18 @@ async fn handle_init_test(name: &str, initial_state: AssociationState, expect_er
         },
         "{name} should match"
     );
-    assert_eq!(a.my_max_num_outbound_streams, 1001, "{name} should match");
-    assert_eq!(a.my_max_num_inbound_streams, 1002, "{name} should match");
+    assert_eq!(a.my_max_num_outbound_streams, 1002, "{name} should match");
+    assert_eq!(a.my_max_num_inbound_streams, 1001, "{name} should match");
     assert_eq!(a.peer_verification_tag, 5678, "{name} should match");
     assert_eq!(a.destination_port, pkt.source_port, "{name} should match");
     assert_eq!(a.source_port, pkt.destination_port, "{name} should match");
     assert!(a.use_forward_tsn, "{name} should be set to true");
+    assert!(a.use_interleaving, "{name} should be set to true");
+    assert_eq!(
+        a.max_payload_size,
+        a.mtu - (COMMON_HEADER_SIZE + I_DATA_CHUNK_HEADER_SIZE),
+        "{name} should match"
+    );
 }
 
 #[tokio::test]
@@ -459,6 +533,19 @@ async fn test_assoc_max_message_size_default() -> Result<()> {
         max_receive_buffer_size: 0,
         max_message_size: 0,
         name: "client".to_owned(),
+        stream_scheduler: StreamScheduler::default(),
+        congestion_control: CongestionControlAlgorithm::default(),
+        sack_delay: Duration::ZERO,
+        packets_per_sack: 0,
+        immediate_sack: false,
+        zero_checksum: false,
+        max_num_outbound_streams: 0,
+        max_num_inbound_streams: 0,
+        heartbeat_interval: Duration::ZERO,
+        path_max_retransmits: 0,
+        association_max_retransmits: 0,
+        ecn: false,
+        max_send_buffer_size: 0,
     });
     assert_eq!(
         a.max_message_size.load(Ordering::SeqCst),
@@ -504,6 +591,19 @@ async fn test_assoc_max_message_size_explicit() -> Result<()> {
         max_receive_buffer_size: 0,
         max_message_size: 30000,
         name: "client".to_owned(),
+        stream_scheduler: StreamScheduler::default(),
+        congestion_control: CongestionControlAlgorithm::default(),
+        sack_delay: Duration::ZERO,
+        packets_per_sack: 0,
+        immediate_sack: false,
+        zero_checksum: false,
+        max_num_outbound_streams: 0,
+        max_num_inbound_streams: 0,
+        heartbeat_interval: Duration::ZERO,
+        path_max_retransmits: 0,
+        association_max_retransmits: 0,
+        ecn: false,
+        max_send_buffer_size: 0,
     });
 
     assert_eq!(
@@ -542,3 +642,690 @@ async fn test_assoc_max_message_size_explicit() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_assoc_packets_per_sack() -> Result<()> {
+    for (packets_per_sack, n_delayed) in [(0, 1), (1, 0), (3, 2)] {
+        let mut a = AssociationInternal {
+            packets_per_sack,
+            ..Default::default()
+        };
+
+        for _ in 0..n_delayed {
+            a.handle_chunk_start();
+            a.handle_peer_last_tsn_and_acknowledgement(false)?;
+            a.handle_chunk_end();
+            assert_eq!(a.ack_state, AckState::Delay, "sack should be delayed");
+        }
+
+        a.handle_chunk_start();
+        a.handle_peer_last_tsn_and_acknowledgement(false)?;
+        a.handle_chunk_end();
+        assert_eq!(a.ack_state, AckState::Immediate, "sack should be requested");
+
+        let packets = a.gather_outbound_sack_packets(vec![]);
+        assert_eq!(packets.len(), 1, "should send a sack");
+        assert_eq!(a.ack_state, AckState::Idle);
+        assert_eq!(a.packets_since_sack, 0);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_assoc_sack_delay() {
+    for (sack_delay, expected) in [
+        (Duration::ZERO, ACK_INTERVAL),
+        (Duration::from_millis(20), Duration::from_millis(20)),
+        (Duration::from_secs(1), MAX_ACK_INTERVAL),
+    ] {
+        let a = AssociationInternal {
+            sack_delay,
+            ..Default::default()
+        };
+        assert_eq!(a.sack_delay(), expected);
+    }
+}
+
+#[tokio::test]
+async fn test_assoc_immediate_sack() -> Result<()> {
+    for immediate_sack in [false, true] {
+        let mut a = AssociationInternal {
+            immediate_sack,
+            rwnd: 1000,
+            ..Default::default()
+        };
+
+        for (beginning_fragment, ending_fragment) in [(true, true), (true, false), (false, true)] {
+            a.pending_queue
+                .push(ChunkPayloadData {
+                    beginning_fragment,
+                    ending_fragment,
+                    user_data: Bytes::from_static(b"ABC"),
+                    ..Default::default()
+                })
+                .await;
+        }
+
+        let (chunks, _) = a.pop_pending_data_chunks_to_send();
+        assert_eq!(
+            chunks.iter().map(|c| c.immediate_sack).collect::<Vec<_>>(),
+            [false, false, immediate_sack],
+            "only the last chunk should ask for an immediate sack"
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_assoc_handle_init_zero_checksum() -> Result<()> {
+    for (zero_checksum, peer_edmid, expected) in [
+        (false, None, false),
+        (false, Some(ZERO_CHECKSUM_EDMID_DTLS), false),
+        (true, None, false),
+        (true, Some(ZERO_CHECKSUM_EDMID_DTLS + 1), false),
+        (true, Some(ZERO_CHECKSUM_EDMID_DTLS), true),
+    ] {
+        let mut a = AssociationInternal {
+            zero_checksum,
+            mtu: INITIAL_MTU,
+            ..Default::default()
+        };
+        let pkt = Packet {
+            source_port: 5001,
+            destination_port: 5002,
+            ..Default::default()
+        };
+        let mut init = ChunkInit {
+            initial_tsn: 1234,
+            num_outbound_streams: 1,
+            num_inbound_streams: 1,
+            initiate_tag: 5678,
+            ..Default::default()
+        };
+        if let Some(edmid) = peer_edmid {
+            init.params
+                .push(Box::new(ParamZeroChecksumAcceptable { edmid }));
+        }
+
+        let packets = a.handle_init(&pkt, &init)?;
+        assert_eq!(
+            a.use_zero_checksum, expected,
+            "{zero_checksum} {peer_edmid:?}"
+        );
+
+        let init_ack = packets[0].chunks[0]
+            .as_any()
+            .downcast_ref::<ChunkInit>()
+            .unwrap();
+        let advertised = init_ack.params.iter().any(|p| {
+            p.as_any()
+                .downcast_ref::<ParamZeroChecksumAcceptable>()
+                .is_some()
+        });
+        assert_eq!(advertised, zero_checksum, "INIT ACK should advertise it");
+    }
+
+    Ok(())
+}
+
+fn reconfig_responses(packets: &[Packet]) -> Vec<ParamReconfigResponse> {
+    packets
+        .iter()
+        .flat_map(|p| p.chunks.iter())
+        .filter_map(|c| c.as_any().downcast_ref::<ChunkReconfig>())
+        .filter_map(|c| c.param_a.as_ref())
+        .filter_map(|p| p.as_any().downcast_ref::<ParamReconfigResponse>())
+        .cloned()
+        .collect()
+}
+
+#[tokio::test]
+async fn test_assoc_stream_identifier_out_of_range() -> Result<()> {
+    let mut a = AssociationInternal {
+        my_max_num_outbound_streams: 2,
+        my_max_num_inbound_streams: 2,
+        ..Default::default()
+    };
+
+    a.open_stream(1, PayloadProtocolIdentifier::Binary)?;
+    assert_eq!(
+        a.open_stream(2, PayloadProtocolIdentifier::Binary).err(),
+        Some(Error::ErrStreamIdentifierOutOfRange)
+    );
+
+    let to_be_ignored = ChunkPayloadData {
+        beginning_fragment: true,
+        ending_fragment: true,
+        tsn: a.peer_last_tsn + 1,
+        stream_identifier: 2,
+        user_data: Bytes::from_static(b"ABC"),
+        ..Default::default()
+    };
+    a.handle_data(&to_be_ignored)?;
+    assert!(!a.streams.contains_key(&2), "should not accept the stream");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_assoc_handle_add_streams_request() -> Result<()> {
+    let mut a = AssociationInternal {
+        my_max_num_outbound_streams: 10,
+        my_max_num_inbound_streams: 20,
+        ..Default::default()
+    };
+
+    let tests = [
+        // The peer adds outgoing streams, which are our incoming ones.
+        (1, false, 5, ReconfigResult::SuccessPerformed, 10, 25),
+        // A retransmitted request is answered the same but not applied twice.
+        (1, false, 5, ReconfigResult::SuccessPerformed, 10, 25),
+        (2, true, 3, ReconfigResult::SuccessPerformed, 13, 25),
+        (3, true, u16::MAX, ReconfigResult::Denied, 13, 25),
+    ];
+
+    for (rsn, incoming, number_of_new_streams, result, outbound, inbound) in tests {
+        let c = ChunkReconfig {
+            param_a: Some(Box::new(ParamAddStreamsRequest {
+                incoming,
+                reconfig_request_sequence_number: rsn,
+                number_of_new_streams,
+            })),
+            param_b: None,
+        };
+        let packets = a.handle_reconfig(&c)?;
+        let responses = reconfig_responses(&packets);
+        assert_eq!(responses.len(), 1, "rsn {rsn}");
+        assert_eq!(responses[0].reconfig_response_sequence_number, rsn);
+        assert_eq!(responses[0].result, result, "rsn {rsn}");
+        assert_eq!(a.my_max_num_outbound_streams, outbound, "rsn {rsn}");
+        assert_eq!(a.my_max_num_inbound_streams, inbound, "rsn {rsn}");
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_assoc_send_add_streams_request() -> Result<()> {
+    let mut a = AssociationInternal {
+        my_max_num_outbound_streams: 10,
+        my_max_num_inbound_streams: 20,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        a.send_add_streams_request(Instant::now(), false, 1).err(),
+        Some(Error::ErrResetPacketInStateNotExist)
+    );
+
+    a.set_state(AssociationState::Established);
+    assert_eq!(
+        a.send_add_streams_request(Instant::now(), false, u16::MAX)
+            .err(),
+        Some(Error::ErrTooManyStreams)
+    );
+
+    let rsn = a.my_next_rsn;
+    assert_eq!(a.send_add_streams_request(Instant::now(), false, 5)?, rsn);
+    assert!(a.reconfigs.contains_key(&rsn), "should be retransmitted");
+    assert_eq!(a.control_queue.len(), 1, "request should be sent");
+
+    // Nothing changes before the peer answers.
+    assert_eq!(a.my_max_num_outbound_streams, 10);
+
+    let c = ChunkReconfig {
+        param_a: Some(Box::new(ParamReconfigResponse {
+            reconfig_response_sequence_number: rsn,
+            result: ReconfigResult::SuccessPerformed,
+        })),
+        param_b: None,
+    };
+    a.handle_reconfig(&c)?;
+    assert!(matches!(
+        a.poll_event(),
+        Some(AssociationEvent::AddStreamsAnswered {
+            request_sequence_number,
+            performed: true,
+        }) if request_sequence_number == rsn
+    ));
+    assert_eq!(a.my_max_num_outbound_streams, 15);
+    assert_eq!(a.my_max_num_inbound_streams, 20);
+    assert!(a.reconfigs.is_empty(), "answered request should be removed");
+
+    let rsn = a.my_next_rsn;
+    assert_eq!(a.send_add_streams_request(Instant::now(), true, 5)?, rsn);
+    let c = ChunkReconfig {
+        param_a: Some(Box::new(ParamReconfigResponse {
+            reconfig_response_sequence_number: rsn,
+            result: ReconfigResult::Denied,
+        })),
+        param_b: None,
+    };
+    a.handle_reconfig(&c)?;
+    assert!(matches!(
+        a.poll_event(),
+        Some(AssociationEvent::AddStreamsAnswered {
+            request_sequence_number,
+            performed: false,
+        }) if request_sequence_number == rsn
+    ));
+    assert_eq!(a.my_max_num_inbound_streams, 20);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_assoc_handle_incoming_reset_request() -> Result<()> {
+    let mut a = AssociationInternal {
+        my_max_num_outbound_streams: u16::MAX,
+        ..Default::default()
+    };
+    a.set_state(AssociationState::Established);
+    a.open_stream(1, PayloadProtocolIdentifier::Binary)?;
+    a.open_stream(2, PayloadProtocolIdentifier::Binary)?;
+
+    let tests = [
+        (1, vec![3], ReconfigResult::SuccessNop, 0),
+        (2, vec![1, 3], ReconfigResult::SuccessPerformed, 1),
+        // No stream listed resets all of them.
+        (3, vec![], ReconfigResult::SuccessPerformed, 3),
+    ];
+
+    for (rsn, stream_identifiers, result, queued) in tests {
+        let c = ChunkReconfig {
+            param_a: Some(Box::new(ParamIncomingResetRequest {
+                reconfig_request_sequence_number: rsn,
+                stream_identifiers,
+            })),
+            param_b: None,
+        };
+        let packets = a.handle_reconfig(&c)?;
+        let responses = reconfig_responses(&packets);
+        assert_eq!(responses.len(), 1, "rsn {rsn}");
+        assert_eq!(responses[0].result, result, "rsn {rsn}");
+        // Each reset stream queues the end of its data before the reset.
+        assert_eq!(a.pending_queue.len(), queued, "rsn {rsn}");
+    }
+
+    let c = ChunkReconfig {
+        param_a: Some(Box::new(ParamSsnTsnResetRequest {
+            reconfig_request_sequence_number: 4,
+        })),
+        param_b: None,
+    };
+    let packets = a.handle_reconfig(&c)?;
+    let responses = reconfig_responses(&packets);
+    assert_eq!(responses.len(), 1);
+    assert_eq!(responses[0].result, ReconfigResult::Denied);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_assoc_send_incoming_reset_request() -> Result<()> {
+    let mut a = AssociationInternal::default();
+    a.set_state(AssociationState::Established);
+
+    let rsn = a.my_next_rsn;
+    a.send_incoming_reset_request(Instant::now(), vec![1])?;
+    assert!(a.reconfigs.contains_key(&rsn), "should be retransmitted");
+
+    // The peer answers by resetting its outgoing stream.
+    let c = ChunkReconfig {
+        param_a: Some(Box::new(ParamOutgoingResetRequest {
+            reconfig_request_sequence_number: 7,
+            reconfig_response_sequence_number: rsn,
+            sender_last_tsn: a.peer_last_tsn,
+            stream_identifiers: vec![1],
+        })),
+        param_b: None,
+    };
+    a.handle_reconfig(&c)?;
+    assert!(a.reconfigs.is_empty(), "request should be answered");
+
+    Ok(())
+}
+
+/// last_heartbeat_information returns the Heartbeat Information of the
+/// HEARTBEAT queued last.
+fn last_heartbeat_information(a: &AssociationInternal) -> Bytes {
+    let p = a.control_queue.back().expect("should send a HEARTBEAT");
+    let hb = p.chunks[0]
+        .as_any()
+        .downcast_ref::<ChunkHeartbeat>()
+        .expect("should be a HEARTBEAT");
+    let hbi = hb.params[0]
+        .as_any()
+        .downcast_ref::<ParamHeartbeatInfo>()
+        .expect("should have ParamHeartbeatInfo");
+    hbi.heartbeat_information.clone()
+}
+
+#[tokio::test]
+async fn test_assoc_heartbeat_path_status() -> Result<()> {
+    let mut a = AssociationInternal {
+        path_max_retrans: 1,
+        ..Default::default()
+    };
+    a.set_state(AssociationState::Established);
+    a.rto_mgr.set_rto(0, true);
+
+    // An idle path is probed.
+    a.on_heartbeat_timeout();
+    assert_eq!(a.control_queue.len(), 1, "should send a HEARTBEAT");
+
+    // The first unanswered heartbeat stays within path_max_retrans.
+    a.on_heartbeat_timeout();
+    assert_eq!(a.control_queue.len(), 2, "should send another HEARTBEAT");
+    assert_eq!(a.path_status(), PathStatus::Active);
+
+    a.on_heartbeat_timeout();
+    assert_eq!(a.path_status(), PathStatus::Inactive);
+    assert!(matches!(
+        a.poll_event(),
+        Some(AssociationEvent::PathStatus(PathStatus::Inactive))
+    ));
+
+    // A stale HEARTBEAT ACK is ignored.
+    let stale = ChunkHeartbeatAck {
+        params: vec![Box::new(ParamHeartbeatInfo {
+            heartbeat_information: Bytes::from_static(&[0; 8]),
+        })],
+    };
+    a.handle_heartbeat_ack(&stale)?;
+    assert_eq!(a.path_status(), PathStatus::Inactive);
+
+    let ack = ChunkHeartbeatAck {
+        params: vec![Box::new(ParamHeartbeatInfo {
+            heartbeat_information: last_heartbeat_information(&a),
+        })],
+    };
+    a.handle_heartbeat_ack(&ack)?;
+    assert_eq!(a.path_status(), PathStatus::Active);
+    assert_eq!(a.error_count, 0, "should clear the error counter");
+    assert!(matches!(
+        a.poll_event(),
+        Some(AssociationEvent::PathStatus(PathStatus::Active))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_assoc_heartbeat_association_max_retrans() -> Result<()> {
+    let mut a = AssociationInternal {
+        path_max_retrans: PATH_MAX_RETRANS,
+        max_retrans: 2,
+        ..Default::default()
+    };
+    a.set_state(AssociationState::Established);
+    a.rto_mgr.set_rto(0, true);
+
+    for _ in 0..3 {
+        a.on_heartbeat_timeout();
+    }
+    assert_eq!(a.get_state(), AssociationState::Established);
+
+    // The third unanswered heartbeat exceeds max_retrans.
+    a.on_heartbeat_timeout();
+    assert_eq!(a.get_state(), AssociationState::Closed);
+
+    Ok(())
+}
+
+fn create_sans_io_association_internal(name: &str) -> AssociationInternal {
+    create_association_internal(Config {
+        net_conn: Arc::new(DumbConn {}),
+        max_receive_buffer_size: 0,
+        max_message_size: 0,
+        name: name.to_owned(),
+        stream_scheduler: StreamScheduler::default(),
+        congestion_control: CongestionControlAlgorithm::default(),
+        sack_delay: Duration::ZERO,
+        packets_per_sack: 0,
+        immediate_sack: false,
+        zero_checksum: false,
+        max_num_outbound_streams: 0,
+        max_num_inbound_streams: 0,
+        heartbeat_interval: Duration::ZERO,
+        path_max_retransmits: 0,
+        association_max_retransmits: 0,
+        ecn: false,
+        max_send_buffer_size: 0,
+    })
+}
+
+/// deliver passes the datagrams of from to to, and returns how many there were.
+async fn deliver(
+    from: &mut AssociationInternal,
+    to: &mut AssociationInternal,
+    now: Instant,
+) -> Result<usize> {
+    let datagrams = from.poll_output(now)?;
+    for d in &datagrams {
+        to.handle_input(now, d)?;
+    }
+    Ok(datagrams.len())
+}
+
+#[tokio::test]
+async fn test_assoc_sans_io_handshake_and_retransmission() -> Result<()> {
+    let mut client = create_sans_io_association_internal("client");
+    let mut server = create_sans_io_association_internal("server");
+    let mut now = Instant::now();
+
+    // The first INIT is lost, T1-init sends it again.
+    client.connect(now)?;
+    assert_eq!(client.poll_output(now)?.len(), 1, "should send INIT");
+    assert_eq!(client.poll_output(now)?.len(), 0);
+    let t1init = client.poll_timeout().expect("T1-init should be running");
+    assert_eq!(t1init - now, Duration::from_millis(RTO_INITIAL));
+    now = t1init;
+    client.handle_timeout(now);
+
+    // INIT, INIT ACK, COOKIE ECHO, COOKIE ACK
+    for _ in 0..2 {
+        assert_eq!(deliver(&mut client, &mut server, now).await?, 1);
+        assert_eq!(deliver(&mut server, &mut client, now).await?, 1);
+    }
+    assert!(matches!(
+        client.poll_event(),
+        Some(AssociationEvent::HandshakeCompleted)
+    ));
+    assert!(matches!(
+        server.poll_event(),
+        Some(AssociationEvent::HandshakeCompleted)
+    ));
+    assert_eq!(client.poll_timeout(), None, "no timer should be running");
+
+    // The first DATA is lost, T3-rtx sends it again.
+    let s = client.open_stream(1, PayloadProtocolIdentifier::Binary)?;
+    s.write_sctp(
+        &Bytes::from_static(b"ABC"),
+        PayloadProtocolIdentifier::Binary,
+    )
+    .await?;
+    assert_eq!(client.poll_output(now)?.len(), 1, "should send DATA");
+    let t3rtx = client.poll_timeout().expect("T3-rtx should be running");
+    client.handle_timeout(t3rtx - Duration::from_millis(1));
+    assert_eq!(client.poll_output(now)?.len(), 0, "too early");
+
+    now = t3rtx;
+    client.handle_timeout(now);
+    assert_eq!(deliver(&mut client, &mut server, now).await?, 1);
+    let accepted = match server.poll_event() {
+        Some(AssociationEvent::StreamAccepted(s)) => s,
+        event => panic!("should accept the stream, got {event:?}"),
+    };
+    let mut buf = [0u8; 16];
+    let (n, ppi) = accepted.read_sctp(&mut buf).await?;
+    assert_eq!(&buf[..n], b"ABC");
+    assert_eq!(ppi, PayloadProtocolIdentifier::Binary);
+
+    // The delayed SACK stops T3-rtx.
+    assert_eq!(deliver(&mut server, &mut client, now).await?, 0);
+    now = server.poll_timeout().expect("ack timer should be running");
+    server.handle_timeout(now);
+    assert_eq!(deliver(&mut server, &mut client, now).await?, 1);
+    assert!(client.inflight_queue.is_empty(), "DATA should be acked");
+    assert_eq!(client.poll_timeout(), None, "T3-rtx should be stopped");
+    assert!(matches!(
+        client.poll_event(),
+        Some(AssociationEvent::BufferedAmountLow(_))
+    ));
+
+    client.close()?;
+    assert!(matches!(
+        client.poll_event(),
+        Some(AssociationEvent::Closed)
+    ));
+
+    Ok(())
+}
+
+fn has_chunk<T: 'static>(datagram: &Bytes) -> bool {
+    Packet::unmarshal(datagram)
+        .map(|p| {
+            p.chunks
+                .iter()
+                .any(|c| c.as_any().downcast_ref::<T>().is_some())
+        })
+        .unwrap_or(false)
+}
+
+#[tokio::test]
+async fn test_assoc_sans_io_ecn() -> Result<()> {
+    let mut client = create_sans_io_association_internal("client");
+    let mut server = create_sans_io_association_internal("server");
+    client.ecn = true;
+    server.ecn = true;
+    let now = Instant::now();
+
+    client.connect(now)?;
+    for _ in 0..2 {
+        assert_eq!(deliver(&mut client, &mut server, now).await?, 1);
+        assert_eq!(deliver(&mut server, &mut client, now).await?, 1);
+    }
+    assert!(client.ecn_capable(), "client should negotiate ECN");
+    assert!(server.ecn_capable(), "server should negotiate ECN");
+
+    // A CE-marked DATA is echoed right away, bundled with the SACK.
+    let s = client.open_stream(1, PayloadProtocolIdentifier::Binary)?;
+    s.write_sctp(
+        &Bytes::from_static(b"ABC"),
+        PayloadProtocolIdentifier::Binary,
+    )
+    .await?;
+    for d in client.poll_output(now)? {
+        server.handle_input_with_ecn(now, &d, EcnCodepoint::Ce)?;
+    }
+    let datagrams = server.poll_output(now)?;
+    assert_eq!(datagrams.len(), 1, "should send SACK");
+    assert!(has_chunk::<ChunkSelectiveAck>(&datagrams[0]));
+    assert!(has_chunk::<ChunkEcne>(&datagrams[0]), "should echo CE");
+
+    // The sender reduces its window once and confirms with CWR.
+    let ssthresh = client.congestion_control.ssthresh();
+    client.handle_input(now, &datagrams[0])?;
+    assert!(client.congestion_control.ssthresh() < ssthresh);
+    client.handle_input(now, &datagrams[0])?;
+    assert_eq!(client.get_stats().ecn_reductions, 1);
+    let datagrams = client.poll_output(now)?;
+    assert_eq!(datagrams.len(), 2, "should answer every ECNE");
+    assert!(has_chunk::<ChunkCwr>(&datagrams[0]));
+
+    // The receiver stops echoing once the CWR comes in.
+    for d in &datagrams {
+        server.handle_input(now, d)?;
+    }
+    assert_eq!(server.ecne_tsn, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_assoc_sans_io_ecn_not_negotiated() -> Result<()> {
+    let mut client = create_sans_io_association_internal("client");
+    let mut server = create_sans_io_association_internal("server");
+    client.ecn = true;
+    let now = Instant::now();
+
+    client.connect(now)?;
+    for _ in 0..2 {
+        assert_eq!(deliver(&mut client, &mut server, now).await?, 1);
+        assert_eq!(deliver(&mut server, &mut client, now).await?, 1);
+    }
+    assert!(!client.ecn_capable());
+    assert!(!server.ecn_capable());
+
+    // CE marks are ignored without ECN.
+    let s = client.open_stream(1, PayloadProtocolIdentifier::Binary)?;
+    s.write_sctp(
+        &Bytes::from_static(b"ABC"),
+        PayloadProtocolIdentifier::Binary,
+    )
+    .await?;
+    for d in client.poll_output(now)? {
+        server.handle_input_with_ecn(now, &d, EcnCodepoint::Ce)?;
+    }
+    assert_eq!(server.ecne_tsn, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_assoc_sans_io_send_buffer_limit() -> Result<()> {
+    let mut client = create_sans_io_association_internal("client");
+    let mut server = create_sans_io_association_internal("server");
+    client.send_buffer = Arc::new(SendBuffer::new(1000));
+    let now = Instant::now();
+
+    client.connect(now)?;
+    for _ in 0..2 {
+        assert_eq!(deliver(&mut client, &mut server, now).await?, 1);
+        assert_eq!(deliver(&mut server, &mut client, now).await?, 1);
+    }
+
+    // Streams share the budget of the association.
+    let s1 = client.open_stream(1, PayloadProtocolIdentifier::Binary)?;
+    let s2 = client.open_stream(2, PayloadProtocolIdentifier::Binary)?;
+    s1.write_sctp(
+        &Bytes::from_static(&[0; 600]),
+        PayloadProtocolIdentifier::Binary,
+    )
+    .await?;
+    assert_eq!(
+        s2.write_sctp(
+            &Bytes::from_static(&[0; 600]),
+            PayloadProtocolIdentifier::Binary,
+        )
+        .await,
+        Err(Error::ErrSendBufferFull)
+    );
+    s2.write_sctp(
+        &Bytes::from_static(&[0; 300]),
+        PayloadProtocolIdentifier::Binary,
+    )
+    .await?;
+    assert_eq!(client.send_buffer_used(), 900);
+    assert_eq!(client.get_stats().bytes_buffered, 900);
+
+    // Acknowledged data is released.
+    assert_eq!(deliver(&mut client, &mut server, now).await?, 1);
+    server.ack_state = AckState::Immediate;
+    assert_eq!(deliver(&mut server, &mut client, now).await?, 1);
+    assert_eq!(client.send_buffer_used(), 0);
+
+    // Data of a stream that goes away is released too.
+    s1.write_sctp(
+        &Bytes::from_static(&[0; 100]),
+        PayloadProtocolIdentifier::Binary,
+    )
+    .await?;
+    client.unregister_stream(1);
+    assert_eq!(client.send_buffer_used(), 0);
+
+    Ok(())
+}
@@ -1,5 +1,48 @@
 use portable_atomic::AtomicU64;
 use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+/// AssociationStatsReport is a snapshot of the state and counters of an
+/// association, to diagnose its throughput.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct AssociationStatsReport {
+    /// Number of bytes sent on the underlying conn.
+    pub bytes_sent: usize,
+    /// Number of bytes received on the underlying conn.
+    pub bytes_received: usize,
+    /// Congestion window in bytes.
+    pub cwnd: u32,
+    /// Slow start threshold in bytes.
+    pub ssthresh: u32,
+    /// Number of bytes of DATA sent but not acknowledged yet.
+    pub bytes_in_flight: usize,
+    /// Number of bytes all streams have buffered for sending, queued or in flight.
+    pub bytes_buffered: usize,
+    /// Receiver window last advertised by the peer.
+    pub peer_receiver_window: u32,
+    /// Receiver window currently advertised to the peer.
+    pub receiver_window: u32,
+    /// Path MTU in bytes.
+    pub mtu: u32,
+    /// Smoothed round-trip time, zero until the first measurement.
+    pub srtt: Duration,
+    /// Retransmission timeout.
+    pub rto: Duration,
+    /// Number of DATA chunks received.
+    pub datas_received: u64,
+    /// Number of SACK chunks received.
+    pub sacks_received: u64,
+    /// Number of DATA chunks sent again, after a T3-rtx timeout or by fast retransmit.
+    pub retransmitted_chunks: u64,
+    /// Number of times fast recovery was entered after loss was detected from SACKs.
+    pub fast_retransmits: u64,
+    /// Number of DATA chunks sent again by fast retransmit.
+    pub fast_retransmitted_chunks: u64,
+    /// Number of T3-rtx timer expirations.
+    pub t3_timeouts: u64,
+    /// Number of times the congestion window was reduced for CE marks echoed by the peer.
+    pub ecn_reductions: u64,
+}
 
 #[derive(Default, Debug)]
 pub(crate) struct AssociationStats {
@@ -8,6 +51,9 @@ pub(crate) struct AssociationStats {
     n_t3timeouts: AtomicU64,
     n_ack_timeouts: AtomicU64,
     n_fast_retrans: AtomicU64,
+    n_retrans: AtomicU64,
+    n_fast_recoveries: AtomicU64,
+    n_ecn_reductions: AtomicU64,
 }
 
 impl AssociationStats {
@@ -51,11 +97,38 @@ impl AssociationStats {
         self.n_fast_retrans.load(Ordering::SeqCst)
     }
 
+    pub(crate) fn inc_retrans(&self) {
+        self.n_retrans.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn get_num_retrans(&self) -> u64 {
+        self.n_retrans.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn inc_fast_recoveries(&self) {
+        self.n_fast_recoveries.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn get_num_fast_recoveries(&self) -> u64 {
+        self.n_fast_recoveries.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn inc_ecn_reductions(&self) {
+        self.n_ecn_reductions.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn get_num_ecn_reductions(&self) -> u64 {
+        self.n_ecn_reductions.load(Ordering::SeqCst)
+    }
+
     pub(crate) fn reset(&self) {
         self.n_datas.store(0, Ordering::SeqCst);
         self.n_sacks.store(0, Ordering::SeqCst);
         self.n_t3timeouts.store(0, Ordering::SeqCst);
         self.n_ack_timeouts.store(0, Ordering::SeqCst);
         self.n_fast_retrans.store(0, Ordering::SeqCst);
+        self.n_retrans.store(0, Ordering::SeqCst);
+        self.n_fast_recoveries.store(0, Ordering::SeqCst);
+        self.n_ecn_reductions.store(0, Ordering::SeqCst);
     }
 }
@@ -35,6 +35,19 @@ async fn create_new_association_pair(
             max_receive_buffer_size: recv_buf_size,
             max_message_size: 0,
             name: "client".to_owned(),
+            stream_scheduler: StreamScheduler::default(),
+            congestion_control: CongestionControlAlgorithm::default(),
+            sack_delay: Duration::ZERO,
+            packets_per_sack: 0,
+            immediate_sack: false,
+            zero_checksum: false,
+            max_num_outbound_streams: 0,
+            max_num_inbound_streams: 0,
+            heartbeat_interval: Duration::ZERO,
+            path_max_retransmits: 0,
+            association_max_retransmits: 0,
+            ecn: false,
+            max_send_buffer_size: 0,
         })
         .await;
 
@@ -51,6 +64,19 @@ async fn create_new_association_pair(
             max_receive_buffer_size: recv_buf_size,
             max_message_size: 0,
             name: "server".to_owned(),
+            stream_scheduler: StreamScheduler::default(),
+            congestion_control: CongestionControlAlgorithm::default(),
+            sack_delay: Duration::ZERO,
+            packets_per_sack: 0,
+            immediate_sack: false,
+            zero_checksum: false,
+            max_num_outbound_streams: 0,
+            max_num_inbound_streams: 0,
+            heartbeat_interval: Duration::ZERO,
+            path_max_retransmits: 0,
+            association_max_retransmits: 0,
+            ecn: false,
+            max_send_buffer_size: 0,
         })
         .await;
 
@@ -273,7 +299,7 @@ async fn test_assoc_reliable_simple() -> Result<()> {
     assert_eq!(ppi, PayloadProtocolIdentifier::Binary, "unexpected ppi");
 
     {
-        let q = s0.reassembly_queue.lock().await;
+        let q = s0.reassembly_queue.lock();
         assert!(!q.is_readable(), "should no longer be readable");
     }
 
@@ -376,7 +402,7 @@ async fn test_assoc_reliable_ordered_reordered() -> Result<()> {
     br.process().await;
 
     {
-        let q = s0.reassembly_queue.lock().await;
+        let q = s0.reassembly_queue.lock();
         assert!(!q.is_readable(), "should no longer be readable");
     }
 
@@ -443,7 +469,7 @@ async fn test_assoc_reliable_ordered_fragmented_then_defragmented() -> Result<()
     br.process().await;
 
     {
-        let q = s0.reassembly_queue.lock().await;
+        let q = s0.reassembly_queue.lock();
         assert!(!q.is_readable(), "should no longer be readable");
     }
 
@@ -454,6 +480,84 @@ async fn test_assoc_reliable_ordered_fragmented_then_defragmented() -> Result<()
 
 //use std::io::Write;
 
+#[tokio::test]
+async fn test_assoc_reliable_ordered_partial_write() -> Result<()> {
+    const SI: u16 = 3;
+    let mut sbuf = vec![0u8; 3500];
+    for i in 0..sbuf.len() {
+        sbuf[i] = (i & 0xff) as u8;
+    }
+    let sbuf = Bytes::from(sbuf);
+
+    let (br, ca, cb) = Bridge::new(0, None, None);
+
+    let (a0, mut a1) =
+        create_new_association_pair(&br, Arc::new(ca), Arc::new(cb), AckMode::NoDelay, 0).await?;
+
+    let (s0, s1) = establish_session_pair(&br, &a0, &mut a1, SI).await?;
+
+    for part in [
+        sbuf.slice(..1500),
+        sbuf.slice(1500..3000),
+        sbuf.slice(3000..),
+    ] {
+        let n = s0
+            .write_sctp_partial(&part, PayloadProtocolIdentifier::Binary, false)
+            .await?;
+        assert_eq!(n, part.len(), "unexpected length of written data");
+    }
+    assert_eq!(s0.buffered_amount(), sbuf.len(), "incorrect bufferedAmount");
+
+    let result = s0
+        .write_sctp(&sbuf, PayloadProtocolIdentifier::Binary)
+        .await;
+    assert_eq!(
+        result,
+        Err(Error::ErrPartialMessageInProgress),
+        "should not write while a partial message is in progress"
+    );
+
+    flush_buffers(&br, &a0, &a1).await;
+
+    let mut rbuf = vec![0u8; 4000];
+    {
+        let q = s1.reassembly_queue.lock();
+        assert!(
+            !q.is_readable(),
+            "should not be readable before end of record"
+        );
+    }
+
+    // An empty part ends the message.
+    let n = s0
+        .write_sctp_partial(&Bytes::new(), PayloadProtocolIdentifier::Binary, true)
+        .await?;
+    assert_eq!(n, 0, "unexpected length of written data");
+    let n = s0
+        .write_sctp(&sbuf.slice(..100), PayloadProtocolIdentifier::String)
+        .await?;
+    assert_eq!(n, 100, "unexpected length of written data");
+
+    flush_buffers(&br, &a0, &a1).await;
+
+    let (n, ppi) = s1.read_sctp(&mut rbuf).await?;
+    assert_eq!(n, sbuf.len(), "unexpected length of received data");
+    assert_eq!(&rbuf[..n], &sbuf, "unexpected received data");
+    assert_eq!(ppi, PayloadProtocolIdentifier::Binary, "unexpected ppi");
+
+    let (n, ppi) = s1.read_sctp(&mut rbuf).await?;
+    assert_eq!(n, 100, "unexpected length of received data");
+    assert_eq!(ppi, PayloadProtocolIdentifier::String, "unexpected ppi");
+
+    br.process().await;
+
+    assert_eq!(s0.buffered_amount(), 0, "incorrect bufferedAmount");
+
+    close_association_pair(&br, a0, a1).await;
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_assoc_reliable_unordered_fragmented_then_defragmented() -> Result<()> {
     /*env_logger::Builder::new()
@@ -510,7 +614,7 @@ async fn test_assoc_reliable_unordered_fragmented_then_defragmented() -> Result<
     br.process().await;
 
     {
-        let q = s0.reassembly_queue.lock().await;
+        let q = s0.reassembly_queue.lock();
         assert!(!q.is_readable(), "should no longer be readable");
     }
 
@@ -603,7 +707,7 @@ async fn test_assoc_reliable_unordered_ordered() -> Result<()> {
     br.process().await;
 
     {
-        let q = s0.reassembly_queue.lock().await;
+        let q = s0.reassembly_queue.lock();
         assert!(!q.is_readable(), "should no longer be readable");
     }
 
@@ -684,7 +788,7 @@ async fn test_assoc_reliable_retransmission() -> Result<()> {
     br.process().await;
 
     {
-        let q = s0.reassembly_queue.lock().await;
+        let q = s0.reassembly_queue.lock();
         assert!(!q.is_readable(), "should no longer be readable");
     }
 
@@ -750,7 +854,7 @@ async fn test_assoc_reliable_short_buffer() -> Result<()> {
     }
 
     {
-        let q = s0.reassembly_queue.lock().await;
+        let q = s0.reassembly_queue.lock();
         assert!(!q.is_readable(), "should no longer be readable");
     }
 
@@ -840,7 +944,7 @@ async fn test_assoc_unreliable_rexmit_ordered_no_fragment() -> Result<()> {
     br.process().await;
 
     {
-        let q = s0.reassembly_queue.lock().await;
+        let q = s0.reassembly_queue.lock();
         assert!(!q.is_readable(), "should no longer be readable");
     }
 
@@ -930,7 +1034,7 @@ async fn test_assoc_unreliable_rexmit_ordered_fragment() -> Result<()> {
     br.process().await;
 
     {
-        let q = s0.reassembly_queue.lock().await;
+        let q = s0.reassembly_queue.lock();
         assert!(!q.is_readable(), "should no longer be readable");
     }
 
@@ -1015,7 +1119,7 @@ async fn test_assoc_unreliable_rexmit_unordered_no_fragment() -> Result<()> {
     br.process().await;
 
     {
-        let q = s0.reassembly_queue.lock().await;
+        let q = s0.reassembly_queue.lock();
         assert!(!q.is_readable(), "should no longer be readable");
     }
 
@@ -1103,7 +1207,7 @@ async fn test_assoc_unreliable_rexmit_unordered_fragment() -> Result<()> {
     br.process().await;
 
     {
-        let q = s0.reassembly_queue.lock().await;
+        let q = s0.reassembly_queue.lock();
         assert!(!q.is_readable(), "should no longer be readable");
         assert_eq!(
             q.unordered.len(),
@@ -1198,7 +1302,7 @@ async fn test_assoc_unreliable_rexmit_timed_ordered() -> Result<()> {
     br.process().await;
 
     {
-        let q = s0.reassembly_queue.lock().await;
+        let q = s0.reassembly_queue.lock();
         assert!(!q.is_readable(), "should no longer be readable");
     }
 
@@ -1283,7 +1387,7 @@ async fn test_assoc_unreliable_rexmit_timed_unordered() -> Result<()> {
     br.process().await;
 
     {
-        let q = s0.reassembly_queue.lock().await;
+        let q = s0.reassembly_queue.lock();
         assert!(!q.is_readable(), "should no longer be readable");
         assert_eq!(
             q.unordered.len(),
@@ -1302,6 +1406,200 @@ async fn test_assoc_unreliable_rexmit_timed_unordered() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_assoc_unreliable_rexmit_per_message() -> Result<()> {
+    const SI: u16 = 2;
+    let mut sbuf = vec![0u8; 1000];
+    for i in 0..sbuf.len() {
+        sbuf[i] = (i & 0xff) as u8;
+    }
+
+    let (br, ca, cb) = Bridge::new(0, None, None);
+
+    let (a0, mut a1) =
+        create_new_association_pair(&br, Arc::new(ca), Arc::new(cb), AckMode::NoDelay, 0).await?;
+
+    let (s0, s1) = establish_session_pair(&br, &a0, &mut a1, SI).await?;
+
+    // The stream stays reliable, only the first message is abandoned
+    // immediately after its first transmission.
+    br.drop_next_nwrites(0, 1); // drop the first packet (second one should be sacked)
+
+    sbuf[0..4].copy_from_slice(&0u32.to_be_bytes());
+    let n = s0
+        .write_sctp_with_reliability(
+            &Bytes::from(sbuf.clone()),
+            PayloadProtocolIdentifier::Binary,
+            ReliabilityType::Rexmit,
+            0,
+        )
+        .await?;
+    assert_eq!(n, sbuf.len(), "unexpected length of received data");
+
+    sbuf[0..4].copy_from_slice(&1u32.to_be_bytes());
+    let n = s0
+        .write_sctp(
+            &Bytes::from(sbuf.clone()),
+            PayloadProtocolIdentifier::Binary,
+        )
+        .await?;
+    assert_eq!(n, sbuf.len(), "unexpected length of received data");
+
+    flush_buffers(&br, &a0, &a1).await;
+
+    let mut buf = vec![0u8; 2000];
+    let (n, ppi) = s1.read_sctp(&mut buf).await?;
+    assert_eq!(n, sbuf.len(), "unexpected length of received data");
+    assert_eq!(ppi, PayloadProtocolIdentifier::Binary, "unexpected ppi");
+    assert_eq!(
+        u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]),
+        1,
+        "unexpected received data"
+    );
+
+    br.process().await;
+
+    assert_eq!(
+        a0.get_stats().await.bytes_in_flight,
+        0,
+        "should be nothing in flight"
+    );
+    let reliability_type: ReliabilityType = s0.reliability_type.load(Ordering::SeqCst).into();
+    assert_eq!(
+        reliability_type,
+        ReliabilityType::Reliable,
+        "stream should stay reliable"
+    );
+
+    close_association_pair(&br, a0, a1).await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_assoc_reliable_ordered_unordered_per_message() -> Result<()> {
+    const SI: u16 = 5;
+    let mut sbuf = vec![0u8; 1000];
+    for i in 0..sbuf.len() {
+        sbuf[i] = (i & 0xff) as u8;
+    }
+
+    let (br, ca, cb) = Bridge::new(0, None, None);
+
+    let (a0, mut a1) =
+        create_new_association_pair(&br, Arc::new(ca), Arc::new(cb), AckMode::NoDelay, 0).await?;
+
+    let (s0, s1) = establish_session_pair(&br, &a0, &mut a1, SI).await?;
+
+    // The stream stays ordered, only the second message overtakes the first.
+    br.reorder_next_nwrites(0, 2);
+
+    let unordered = WriteOptions {
+        unordered: true,
+        ..Default::default()
+    };
+    for (i, options) in [WriteOptions::default(), unordered, WriteOptions::default()]
+        .into_iter()
+        .enumerate()
+    {
+        sbuf[0..4].copy_from_slice(&(i as u32).to_be_bytes());
+        let n = s0
+            .write_sctp_with_options(
+                &Bytes::from(sbuf.clone()),
+                PayloadProtocolIdentifier::Binary,
+                options,
+            )
+            .await?;
+        assert_eq!(n, sbuf.len(), "unexpected length of received data");
+    }
+
+    flush_buffers(&br, &a0, &a1).await;
+
+    let mut buf = vec![0u8; 2000];
+    for expected in [1u32, 0, 2] {
+        let (n, ppi) = s1.read_sctp(&mut buf).await?;
+        assert_eq!(n, sbuf.len(), "unexpected length of received data");
+        assert_eq!(ppi, PayloadProtocolIdentifier::Binary, "unexpected ppi");
+        assert_eq!(
+            u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]),
+            expected,
+            "unexpected received data"
+        );
+    }
+
+    close_association_pair(&br, a0, a1).await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_assoc_unreliable_unordered_per_message_on_ordered_stream() -> Result<()> {
+    const SI: u16 = 2;
+    let mut sbuf = vec![0u8; 1000];
+    for i in 0..sbuf.len() {
+        sbuf[i] = (i & 0xff) as u8;
+    }
+
+    let (br, ca, cb) = Bridge::new(0, None, None);
+
+    let (a0, mut a1) =
+        create_new_association_pair(&br, Arc::new(ca), Arc::new(cb), AckMode::NoDelay, 0).await?;
+
+    let (s0, s1) = establish_session_pair(&br, &a0, &mut a1, SI).await?;
+
+    // The abandoned unordered message must not make the peer skip the
+    // ordered message sent after it.
+    br.drop_next_nwrites(0, 1); // drop the first packet
+
+    sbuf[0..4].copy_from_slice(&0u32.to_be_bytes());
+    let n = s0
+        .write_sctp_with_options(
+            &Bytes::from(sbuf.clone()),
+            PayloadProtocolIdentifier::Binary,
+            WriteOptions {
+                unordered: true,
+                reliability: Some((ReliabilityType::Rexmit, 0)),
+            },
+        )
+        .await?;
+    assert_eq!(n, sbuf.len(), "unexpected length of received data");
+
+    flush_buffers(&br, &a0, &a1).await;
+
+    sbuf[0..4].copy_from_slice(&1u32.to_be_bytes());
+    let n = s0
+        .write_sctp(
+            &Bytes::from(sbuf.clone()),
+            PayloadProtocolIdentifier::Binary,
+        )
+        .await?;
+    assert_eq!(n, sbuf.len(), "unexpected length of received data");
+
+    flush_buffers(&br, &a0, &a1).await;
+
+    let mut buf = vec![0u8; 2000];
+    let (n, ppi) = s1.read_sctp(&mut buf).await?;
+    assert_eq!(n, sbuf.len(), "unexpected length of received data");
+    assert_eq!(ppi, PayloadProtocolIdentifier::Binary, "unexpected ppi");
+    assert_eq!(
+        u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]),
+        1,
+        "unexpected received data"
+    );
+
+    br.process().await;
+
+    assert_eq!(
+        a0.get_stats().await.bytes_in_flight,
+        0,
+        "should be nothing in flight"
+    );
+
+    close_association_pair(&br, a0, a1).await;
+
+    Ok(())
+}
+
 //TODO: TestAssocT1InitTimer
 //TODO: TestAssocT1CookieTimer
 //TODO: TestAssocT3RtxTimer
@@ -1367,7 +1665,7 @@ async fn test_assoc_congestion_control_fast_retransmission() -> Result<()> {
     // Try to read all 4 packets
     for i in 0..4 {
         {
-            let q = s1.reassembly_queue.lock().await;
+            let q = s1.reassembly_queue.lock();
             assert!(q.is_readable(), "should be readable");
         }
 
@@ -1396,6 +1694,23 @@ async fn test_assoc_congestion_control_fast_retransmission() -> Result<()> {
         assert_eq!(a.stats.get_num_fast_retrans(), 1, "should be 1");
     }
 
+    let stats = a0.get_stats().await;
+    assert_eq!(stats.fast_retransmits, 1, "should enter fast recovery once");
+    assert_eq!(stats.fast_retransmitted_chunks, 1, "should be 1");
+    assert_eq!(stats.retransmitted_chunks, 1, "should be 1");
+    assert_eq!(stats.t3_timeouts, 0, "should be no T3-rtx timeout");
+    assert_eq!(stats.bytes_in_flight, 0, "all data should be acked");
+    assert_eq!(
+        stats.sacks_received,
+        a0.association_internal.lock().await.stats.get_num_sacks()
+    );
+    assert!(stats.bytes_sent > 4 * sbuf.len(), "should count sent bytes");
+    assert_eq!(
+        s0.retransmitted_chunks(),
+        1,
+        "should be counted on the stream"
+    );
+
     close_association_pair(&br, a0, a1).await;
 
     Ok(())
@@ -1474,7 +1789,7 @@ async fn test_assoc_congestion_control_congestion_avoidance() -> Result<()> {
 
         loop {
             let readable = {
-                let q = s1.reassembly_queue.lock().await;
+                let q = s1.reassembly_queue.lock();
                 q.is_readable()
             };
             if !readable {
@@ -1506,19 +1821,19 @@ async fn test_assoc_congestion_control_congestion_avoidance() -> Result<()> {
 
         assert!(!a.in_fast_recovery, "should not be in fast-recovery");
         assert!(
-            a.cwnd > a.ssthresh,
+            a.congestion_control.cwnd() > a.congestion_control.ssthresh(),
             "should be in congestion avoidance mode"
         );
         assert!(
-            a.ssthresh >= MAX_RECEIVE_BUFFER_SIZE,
+            a.congestion_control.ssthresh() >= MAX_RECEIVE_BUFFER_SIZE,
             "{} should not be less than the initial size of 128KB {}",
-            a.ssthresh,
+            a.congestion_control.ssthresh(),
             MAX_RECEIVE_BUFFER_SIZE
         );
 
         assert_eq!(
             0,
-            s1.get_num_bytes_in_reassembly_queue().await,
+            s1.get_num_bytes_in_reassembly_queue(),
             "reassembly queue should be empty"
         );
 
@@ -1614,8 +1929,8 @@ async fn test_assoc_congestion_control_slow_reader() -> Result<()> {
             let a = a0.association_internal.lock().await;
             let b = a1.association_internal.lock().await;
 
-            let rwnd = b.get_my_receiver_window_credit().await;
-            let cwnd = a.cwnd;
+            let rwnd = b.get_my_receiver_window_credit();
+            let cwnd = a.congestion_control.cwnd();
             if cwnd > a.mtu || rwnd > 0 {
                 // Do not read until a1.getMyReceiverWindowCredit() becomes zero
                 continue;
@@ -1626,7 +1941,7 @@ async fn test_assoc_congestion_control_slow_reader() -> Result<()> {
 
         loop {
             let readable = {
-                let q = s1.reassembly_queue.lock().await;
+                let q = s1.reassembly_queue.lock();
                 q.is_readable()
             };
             if !readable {
@@ -1654,7 +1969,7 @@ async fn test_assoc_congestion_control_slow_reader() -> Result<()> {
         "unexpected num of packets received"
     );
     assert_eq!(
-        s1.get_num_bytes_in_reassembly_queue().await,
+        s1.get_num_bytes_in_reassembly_queue(),
         0,
         "reassembly queue should be empty"
     );
@@ -2038,6 +2353,95 @@ async fn test_assoc_reset_close_both_ways() -> Result<()> {
 
 //use std::io::Write;
 
+#[tokio::test]
+async fn test_assoc_reset_incoming_streams() -> Result<()> {
+    const SI: u16 = 1;
+
+    let (br, ca, cb) = Bridge::new(0, None, None);
+
+    let (a0, mut a1) =
+        create_new_association_pair(&br, Arc::new(ca), Arc::new(cb), AckMode::NoDelay, 0).await?;
+
+    let (s0, s1) = establish_session_pair(&br, &a0, &mut a1, SI).await?;
+
+    // a1 resets the stream it sends on to a0, which answers by resetting its
+    // own side as well.
+    a0.reset_incoming_streams(&[SI]).await?;
+
+    let mut closed = false;
+    for _ in 0..100 {
+        br.process().await;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        if s0.read_shutdown.load(Ordering::SeqCst) && s1.read_shutdown.load(Ordering::SeqCst) {
+            closed = true;
+            break;
+        }
+    }
+    assert!(closed, "both sides should have reset the stream");
+    assert!(s0.write_shutdown.load(Ordering::SeqCst));
+    assert!(s1.write_shutdown.load(Ordering::SeqCst));
+
+    close_association_pair(&br, a0, a1).await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_assoc_add_streams() -> Result<()> {
+    const SI: u16 = 2;
+
+    let (br, ca, cb) = Bridge::new(0, None, None);
+
+    let (a0, mut a1) =
+        create_new_association_pair(&br, Arc::new(ca), Arc::new(cb), AckMode::NoDelay, 0).await?;
+
+    for a in [&a0, &a1] {
+        let mut ai = a.association_internal.lock().await;
+        ai.my_max_num_outbound_streams = SI;
+        ai.my_max_num_inbound_streams = SI;
+    }
+
+    let result = a0.open_stream(SI, PayloadProtocolIdentifier::Binary).await;
+    assert_eq!(result.err(), Some(Error::ErrStreamIdentifierOutOfRange));
+
+    {
+        let add_streams = a0.add_streams(1, 1);
+        tokio::pin!(add_streams);
+        let mut i = 0;
+        loop {
+            br.process().await;
+
+            let timer = tokio::time::sleep(Duration::from_millis(10));
+            tokio::pin!(timer);
+
+            tokio::select! {
+                _ = timer.as_mut() => {},
+                result = add_streams.as_mut() => {
+                    result?;
+                    break;
+                }
+            }
+
+            i += 1;
+            assert!(i < 100, "add_streams should be answered");
+        }
+    }
+
+    assert_eq!(a0.num_outbound_streams().await, SI + 1);
+    assert_eq!(a0.num_inbound_streams().await, SI + 1);
+    assert_eq!(a1.num_outbound_streams().await, SI + 1);
+    assert_eq!(a1.num_inbound_streams().await, SI + 1);
+
+    let (s0, s1) = establish_session_pair(&br, &a0, &mut a1, SI).await?;
+    assert_eq!(s0.stream_identifier(), SI);
+    assert_eq!(s1.stream_identifier(), SI);
+
+    close_association_pair(&br, a0, a1).await;
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_assoc_abort() -> Result<()> {
     /*env_logger::Builder::new()
@@ -2203,6 +2607,19 @@ async fn test_stats() -> Result<()> {
         max_receive_buffer_size: 0,
         max_message_size: 0,
         name: "client".to_owned(),
+        stream_scheduler: StreamScheduler::default(),
+        congestion_control: CongestionControlAlgorithm::default(),
+        sack_delay: Duration::ZERO,
+        packets_per_sack: 0,
+        immediate_sack: false,
+        zero_checksum: false,
+        max_num_outbound_streams: 0,
+        max_num_inbound_streams: 0,
+        heartbeat_interval: Duration::ZERO,
+        path_max_retransmits: 0,
+        association_max_retransmits: 0,
+        ecn: false,
+        max_send_buffer_size: 0,
     })
     .await?;
 
@@ -2238,6 +2655,19 @@ async fn create_assocs() -> Result<(Association, Association)> {
             max_receive_buffer_size: 0,
             max_message_size: 0,
             name: "client".to_owned(),
+            stream_scheduler: StreamScheduler::default(),
+            congestion_control: CongestionControlAlgorithm::default(),
+            sack_delay: Duration::ZERO,
+            packets_per_sack: 0,
+            immediate_sack: false,
+            zero_checksum: false,
+            max_num_outbound_streams: 0,
+            max_num_inbound_streams: 0,
+            heartbeat_interval: Duration::ZERO,
+            path_max_retransmits: 0,
+            association_max_retransmits: 0,
+            ecn: false,
+            max_send_buffer_size: 0,
         })
         .await?;
 
@@ -2252,6 +2682,19 @@ async fn create_assocs() -> Result<(Association, Association)> {
             max_receive_buffer_size: 0,
             max_message_size: 0,
             name: "server".to_owned(),
+            stream_scheduler: StreamScheduler::default(),
+            congestion_control: CongestionControlAlgorithm::default(),
+            sack_delay: Duration::ZERO,
+            packets_per_sack: 0,
+            immediate_sack: false,
+            zero_checksum: false,
+            max_num_outbound_streams: 0,
+            max_num_inbound_streams: 0,
+            heartbeat_interval: Duration::ZERO,
+            path_max_retransmits: 0,
+            association_max_retransmits: 0,
+            ecn: false,
+            max_send_buffer_size: 0,
         })
         .await?;
 
@@ -2596,6 +3039,19 @@ async fn test_association_handle_packet_before_init() -> Result<()> {
                 max_message_size: 0,
                 max_receive_buffer_size: 0,
                 name: "client".to_owned(),
+                stream_scheduler: StreamScheduler::default(),
+                congestion_control: CongestionControlAlgorithm::default(),
+                sack_delay: Duration::ZERO,
+                packets_per_sack: 0,
+                immediate_sack: false,
+                zero_checksum: false,
+                max_num_outbound_streams: 0,
+                max_num_inbound_streams: 0,
+                heartbeat_interval: Duration::ZERO,
+                path_max_retransmits: 0,
+                association_max_retransmits: 0,
+                ecn: false,
+                max_send_buffer_size: 0,
             },
             true,
         )
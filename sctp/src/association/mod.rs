@@ -6,25 +6,31 @@ mod association_stats;
 
 use std::collections::{HashMap, VecDeque};
 use std::fmt;
+use std::io::IoSlice;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use std::time::SystemTime;
+use std::time::{Duration, Instant};
 
-use association_internal::*;
+pub use association_internal::AssociationInternal;
+pub use association_stats::AssociationStatsReport;
 use association_stats::*;
 use bytes::{Bytes, BytesMut};
 use portable_atomic::{AtomicBool, AtomicU32, AtomicU8, AtomicUsize};
 use rand::random;
-use tokio::sync::{broadcast, mpsc, Mutex};
+use tokio::sync::{broadcast, mpsc, oneshot, Mutex, Notify};
+use util::sync::Mutex as SyncMutex;
 use util::Conn;
 
 use crate::chunk::chunk_abort::ChunkAbort;
 use crate::chunk::chunk_cookie_ack::ChunkCookieAck;
 use crate::chunk::chunk_cookie_echo::ChunkCookieEcho;
+use crate::chunk::chunk_cwr::ChunkCwr;
+use crate::chunk::chunk_ecne::ChunkEcne;
 use crate::chunk::chunk_error::ChunkError;
 use crate::chunk::chunk_forward_tsn::{ChunkForwardTsn, ChunkForwardTsnStream};
 use crate::chunk::chunk_heartbeat::ChunkHeartbeat;
 use crate::chunk::chunk_heartbeat_ack::ChunkHeartbeatAck;
+use crate::chunk::chunk_i_forward_tsn::{ChunkIForwardTsn, ChunkIForwardTsnStream};
 use crate::chunk::chunk_init::ChunkInit;
 use crate::chunk::chunk_payload_data::{ChunkPayloadData, PayloadProtocolIdentifier};
 use crate::chunk::chunk_reconfig::ChunkReconfig;
@@ -34,6 +40,7 @@ use crate::chunk::chunk_shutdown_ack::ChunkShutdownAck;
 use crate::chunk::chunk_shutdown_complete::ChunkShutdownComplete;
 use crate::chunk::chunk_type::*;
 use crate::chunk::Chunk;
+use crate::congestion::{Ack, CongestionControl, CongestionControlAlgorithm};
 use crate::error::{Error, Result};
 use crate::error_cause::*;
 use crate::packet::Packet;
@@ -46,8 +53,10 @@ use crate::param::Param;
 use crate::queue::control_queue::ControlQueue;
 use crate::queue::payload_queue::PayloadQueue;
 use crate::queue::pending_queue::PendingQueue;
+use crate::stream::send_buffer::SendBuffer;
 use crate::stream::*;
 use crate::timer::ack_timer::*;
+use crate::timer::heartbeat_timer::*;
 use crate::timer::rtx_timer::*;
 use crate::util::*;
 
@@ -58,10 +67,21 @@ pub(crate) const INITIAL_MTU: u32 = 1228;
 pub(crate) const INITIAL_RECV_BUF_SIZE: u32 = 1024 * 1024;
 pub(crate) const COMMON_HEADER_SIZE: u32 = 12;
 pub(crate) const DATA_CHUNK_HEADER_SIZE: u32 = 16;
+pub(crate) const I_DATA_CHUNK_HEADER_SIZE: u32 = 20;
 pub(crate) const DEFAULT_MAX_MESSAGE_SIZE: u32 = 65536;
 
+/// data_chunk_header_size returns the size of a DATA or I-DATA chunk header.
+pub(crate) fn data_chunk_header_size(interleaved: bool) -> u32 {
+    if interleaved {
+        I_DATA_CHUNK_HEADER_SIZE
+    } else {
+        DATA_CHUNK_HEADER_SIZE
+    }
+}
+
 /// other constants
 pub(crate) const ACCEPT_CH_SIZE: usize = 16;
+pub(crate) const PATH_STATUS_CH_SIZE: usize = 16;
 
 /// association state enums
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -107,6 +127,85 @@ impl fmt::Display for AssociationState {
     }
 }
 
+/// PathStatus tells whether the peer is reachable (RFC 4960 sec 8.2).
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PathStatus {
+    /// DATA or HEARTBEAT chunks are acknowledged.
+    #[default]
+    Active,
+    /// More than path_max_retransmits consecutive retransmission timeouts
+    /// and heartbeats went unanswered.
+    Inactive,
+}
+
+impl fmt::Display for PathStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match *self {
+            PathStatus::Active => "Active",
+            PathStatus::Inactive => "Inactive",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// EcnCodepoint is the ECN field of the IP header (RFC 3168) a packet was
+/// received with.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EcnCodepoint {
+    /// Not ECN-Capable Transport.
+    #[default]
+    NotEct,
+    /// ECN-Capable Transport, ECT(1).
+    Ect1,
+    /// ECN-Capable Transport, ECT(0).
+    Ect0,
+    /// Congestion Experienced, set by a router instead of dropping the packet.
+    Ce,
+}
+
+impl fmt::Display for EcnCodepoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match *self {
+            EcnCodepoint::NotEct => "Not-ECT",
+            EcnCodepoint::Ect1 => "ECT(1)",
+            EcnCodepoint::Ect0 => "ECT(0)",
+            EcnCodepoint::Ce => "CE",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// AwakeWriteLoopFn is called by an [`AssociationInternal`] and its streams
+/// when they have something to send, after which
+/// [`AssociationInternal::poll_output`] should be called.
+pub type AwakeWriteLoopFn = Arc<dyn Fn() + Send + Sync>;
+
+/// AssociationEvent is reported by [`AssociationInternal::poll_event`].
+#[derive(Debug)]
+pub enum AssociationEvent {
+    /// The handshake has completed and the association is established.
+    HandshakeCompleted,
+    /// The peer did not answer the handshake.
+    HandshakeFailed(Error),
+    /// The peer has started sending on a new stream.
+    StreamAccepted(Arc<Stream>),
+    /// The peer has become unreachable or reachable again.
+    PathStatus(PathStatus),
+    /// The peer acknowledged enough data of the stream for its buffered
+    /// amount to drop to the low threshold, so the handler set with
+    /// [`Stream::on_buffered_amount_low`] is due.
+    BufferedAmountLow(Arc<Stream>),
+    /// The peer answered an Add Streams request, see
+    /// [`AssociationInternal::send_add_streams_request`]. Unless it was
+    /// performed, the streams are not added.
+    AddStreamsAnswered {
+        request_sequence_number: u32,
+        performed: bool,
+    },
+    /// The association has been closed.
+    Closed,
+}
+
 /// retransmission timer IDs
 #[derive(Default, Debug, Copy, Clone, PartialEq)]
 pub(crate) enum RtxTimerId {
@@ -178,6 +277,48 @@ pub struct Config {
     pub max_receive_buffer_size: u32,
     pub max_message_size: u32,
     pub name: String,
+    /// How queued messages of different streams share the association.
+    pub stream_scheduler: StreamScheduler,
+    /// How the congestion window is managed.
+    pub congestion_control: CongestionControlAlgorithm,
+    /// How long received DATA may go unacknowledged, 200ms if zero and at most 500ms.
+    pub sack_delay: Duration,
+    /// Number of packets carrying DATA after which a SACK is sent without waiting
+    /// for the delay, 2 if zero. 1 acknowledges every packet right away.
+    pub packets_per_sack: u32,
+    /// Whether to set the I bit (RFC 7053) on the last DATA chunk sent before the
+    /// send queue runs empty, so the peer acknowledges it without delay.
+    pub immediate_sack: bool,
+    /// Whether to advertise and accept packets without a CRC32c checksum
+    /// (RFC 9653). Only enable this when the association runs over DTLS,
+    /// which already protects the integrity of every packet.
+    pub zero_checksum: bool,
+    /// Number of outgoing streams to ask the peer for, u16::MAX if zero.
+    pub max_num_outbound_streams: u16,
+    /// Number of incoming streams to accept, u16::MAX if zero. More streams
+    /// can be added later on with [`Association::add_streams`].
+    pub max_num_inbound_streams: u16,
+    /// How often to send a HEARTBEAT while no DATA is in flight, to find out
+    /// whether the peer is still reachable. Zero disables heartbeats.
+    pub heartbeat_interval: Duration,
+    /// Number of consecutive retransmission timeouts and unanswered
+    /// heartbeats after which the path is reported inactive, 5 if zero.
+    pub path_max_retransmits: u32,
+    /// Number of consecutive retransmission timeouts and unanswered
+    /// heartbeats after which the association is closed. Zero keeps it
+    /// open for as long as the peer does.
+    pub association_max_retransmits: u32,
+    /// Whether to negotiate Explicit Congestion Notification (RFC 4960
+    /// Appendix A). Only useful when the transport reports the ECN field of
+    /// received packets to [`AssociationInternal::handle_input_with_ecn`] and
+    /// marks sent packets ECT(0) while
+    /// [`AssociationInternal::ecn_capable`] holds.
+    pub ecn: bool,
+    /// Maximum number of bytes all streams may have buffered for sending,
+    /// queued or not acknowledged yet. Once several streams have data
+    /// buffered, each one may only use an equal share, and writes beyond it
+    /// fail with `Error::ErrSendBufferFull`. Zero means no limit.
+    pub max_send_buffer_size: u32,
 }
 
 ///Association represents an SCTP association
@@ -197,18 +338,25 @@ pub struct Config {
 ///
 /// No Closed state is illustrated since if a
 /// association is Closed its TCB SHOULD be removed.
+///
+/// The state machine itself is an [`AssociationInternal`], which does no I/O
+/// and keeps no timers running. Association drives it on tokio over the
+/// Conn of its Config.
 pub struct Association {
     name: String,
     state: Arc<AtomicU8>,
     max_message_size: Arc<AtomicU32>,
     inflight_queue_length: Arc<AtomicUsize>,
+    send_buffer: Arc<SendBuffer>,
     will_send_shutdown: Arc<AtomicBool>,
     awake_write_loop_ch: Arc<mpsc::Sender<()>>,
     close_loop_ch_rx: Mutex<broadcast::Receiver<()>>,
     accept_ch_rx: Mutex<mpsc::Receiver<Arc<Stream>>>,
+    path_status_ch_rx: Mutex<mpsc::Receiver<PathStatus>>,
     net_conn: Arc<dyn Conn + Send + Sync>,
     bytes_received: Arc<AtomicUsize>,
     bytes_sent: Arc<AtomicUsize>,
+    driver: Arc<Driver>,
 
     pub(crate) association_internal: Arc<Mutex<AssociationInternal>>,
 }
@@ -280,7 +428,9 @@ impl Association {
         let _ = self.net_conn.close().await;
 
         let mut ai = self.association_internal.lock().await;
-        ai.close().await
+        let result = ai.close();
+        self.driver.dispatch(&mut ai);
+        result
     }
 
     async fn new(config: Config, is_client: bool) -> Result<(Self, mpsc::Receiver<Option<Error>>)> {
@@ -288,19 +438,26 @@ impl Association {
 
         let (awake_write_loop_ch_tx, awake_write_loop_ch_rx) = mpsc::channel(1);
         let (accept_ch_tx, accept_ch_rx) = mpsc::channel(ACCEPT_CH_SIZE);
+        let (path_status_ch_tx, path_status_ch_rx) = mpsc::channel(PATH_STATUS_CH_SIZE);
         let (handshake_completed_ch_tx, handshake_completed_ch_rx) = mpsc::channel(1);
         let (close_loop_ch_tx, close_loop_ch_rx) = broadcast::channel(1);
-        let (close_loop_ch_rx1, close_loop_ch_rx2) =
-            (close_loop_ch_tx.subscribe(), close_loop_ch_tx.subscribe());
+        let (close_loop_ch_rx1, close_loop_ch_rx2, close_loop_ch_rx3) = (
+            close_loop_ch_tx.subscribe(),
+            close_loop_ch_tx.subscribe(),
+            close_loop_ch_tx.subscribe(),
+        );
         let awake_write_loop_ch = Arc::new(awake_write_loop_ch_tx);
+        let awake_write_loop_ch1 = Arc::clone(&awake_write_loop_ch);
 
-        let ai = AssociationInternal::new(
+        let mut ai = AssociationInternal::new(
             config,
-            close_loop_ch_tx,
-            accept_ch_tx,
-            handshake_completed_ch_tx,
-            Arc::clone(&awake_write_loop_ch),
+            Arc::new(move || {
+                let _ = awake_write_loop_ch1.try_send(());
+            }),
         );
+        if is_client {
+            ai.connect(Instant::now())?;
+        }
 
         let bytes_received = Arc::new(AtomicUsize::new(0));
         let bytes_sent = Arc::new(AtomicUsize::new(0));
@@ -308,20 +465,24 @@ impl Association {
         let state = Arc::clone(&ai.state);
         let max_message_size = Arc::clone(&ai.max_message_size);
         let inflight_queue_length = Arc::clone(&ai.inflight_queue_length);
+        let send_buffer = Arc::clone(&ai.send_buffer);
         let will_send_shutdown = Arc::clone(&ai.will_send_shutdown);
 
-        let mut init = ChunkInit {
-            initial_tsn: ai.my_next_tsn,
-            num_outbound_streams: ai.my_max_num_outbound_streams,
-            num_inbound_streams: ai.my_max_num_inbound_streams,
-            initiate_tag: ai.my_verification_tag,
-            advertised_receiver_window_credit: ai.max_receive_buffer_size,
-            ..Default::default()
-        };
-        init.set_supported_extensions();
+        let driver = Arc::new(Driver {
+            senders: SyncMutex::new(EventSenders {
+                close_loop_ch_tx: Some(close_loop_ch_tx),
+                accept_ch_tx,
+                path_status_ch_tx: Some(path_status_ch_tx),
+                handshake_completed_ch_tx,
+                add_streams_answers: HashMap::new(),
+            }),
+            timeout: SyncMutex::new(None),
+            timeout_changed: Notify::new(),
+        });
 
         let name1 = name.clone();
         let name2 = name.clone();
+        let name3 = name.clone();
 
         let bytes_received1 = Arc::clone(&bytes_received);
         let bytes_sent2 = Arc::clone(&bytes_sent);
@@ -329,44 +490,14 @@ impl Association {
         let net_conn1 = Arc::clone(&net_conn);
         let net_conn2 = Arc::clone(&net_conn);
 
+        let driver1 = Arc::clone(&driver);
+        let driver2 = Arc::clone(&driver);
+        let driver3 = Arc::clone(&driver);
+
         let association_internal = Arc::new(Mutex::new(ai));
         let association_internal1 = Arc::clone(&association_internal);
         let association_internal2 = Arc::clone(&association_internal);
-
-        {
-            let association_internal3 = Arc::clone(&association_internal);
-
-            let mut ai = association_internal.lock().await;
-            ai.t1init = Some(RtxTimer::new(
-                Arc::downgrade(&association_internal3),
-                RtxTimerId::T1Init,
-                MAX_INIT_RETRANS,
-            ));
-            ai.t1cookie = Some(RtxTimer::new(
-                Arc::downgrade(&association_internal3),
-                RtxTimerId::T1Cookie,
-                MAX_INIT_RETRANS,
-            ));
-            ai.t2shutdown = Some(RtxTimer::new(
-                Arc::downgrade(&association_internal3),
-                RtxTimerId::T2Shutdown,
-                NO_MAX_RETRANS,
-            )); // retransmit forever
-            ai.t3rtx = Some(RtxTimer::new(
-                Arc::downgrade(&association_internal3),
-                RtxTimerId::T3RTX,
-                NO_MAX_RETRANS,
-            )); // retransmit forever
-            ai.treconfig = Some(RtxTimer::new(
-                Arc::downgrade(&association_internal3),
-                RtxTimerId::Reconfig,
-                NO_MAX_RETRANS,
-            )); // retransmit forever
-            ai.ack_timer = Some(AckTimer::new(
-                Arc::downgrade(&association_internal3),
-                ACK_INTERVAL,
-            ));
-        }
+        let association_internal3 = Arc::clone(&association_internal);
 
         tokio::spawn(async move {
             Association::read_loop(
@@ -375,6 +506,7 @@ impl Association {
                 net_conn1,
                 close_loop_ch_rx1,
                 association_internal1,
+                driver1,
             )
             .await;
         });
@@ -386,21 +518,15 @@ impl Association {
                 net_conn2,
                 close_loop_ch_rx2,
                 association_internal2,
+                driver2,
                 awake_write_loop_ch_rx,
             )
             .await;
         });
 
-        if is_client {
-            let mut ai = association_internal.lock().await;
-            ai.set_state(AssociationState::CookieWait);
-            ai.stored_init = Some(init);
-            ai.send_init()?;
-            let rto = ai.rto_mgr.get_rto();
-            if let Some(t1init) = &ai.t1init {
-                t1init.start(rto).await;
-            }
-        }
+        tokio::spawn(async move {
+            Association::timer_loop(name3, close_loop_ch_rx3, association_internal3, driver3).await;
+        });
 
         Ok((
             Association {
@@ -408,13 +534,16 @@ impl Association {
                 state,
                 max_message_size,
                 inflight_queue_length,
+                send_buffer,
                 will_send_shutdown,
                 awake_write_loop_ch,
                 close_loop_ch_rx: Mutex::new(close_loop_ch_rx),
                 accept_ch_rx: Mutex::new(accept_ch_rx),
+                path_status_ch_rx: Mutex::new(path_status_ch_rx),
                 net_conn,
                 bytes_received,
                 bytes_sent,
+                driver,
                 association_internal,
             },
             handshake_completed_ch_rx,
@@ -427,6 +556,7 @@ impl Association {
         net_conn: Arc<dyn Conn + Send + Sync>,
         mut close_loop_ch: broadcast::Receiver<()>,
         association_internal: Arc<Mutex<AssociationInternal>>,
+        driver: Arc<Driver>,
     ) {
         log::debug!("[{}] read_loop entered", name);
 
@@ -459,18 +589,20 @@ impl Association {
 
             {
                 let mut ai = association_internal.lock().await;
-                if let Err(err) = ai.handle_inbound(&inbound).await {
-                    log::warn!("[{}] failed to handle_inbound: {:?}", name, err);
+                if let Err(err) = ai.handle_input(Instant::now(), &inbound) {
+                    log::warn!("[{}] failed to handle_input: {:?}", name, err);
                     done = true;
                 }
+                driver.dispatch(&mut ai);
             }
         }
 
         {
             let mut ai = association_internal.lock().await;
-            if let Err(err) = ai.close().await {
+            if let Err(err) = ai.close() {
                 log::warn!("[{}] failed to close association: {:?}", name, err);
             }
+            driver.dispatch(&mut ai);
         }
 
         log::debug!("[{}] read_loop exited", name);
@@ -482,6 +614,7 @@ impl Association {
         net_conn: Arc<dyn Conn + Send + Sync>,
         mut close_loop_ch: broadcast::Receiver<()>,
         association_internal: Arc<Mutex<AssociationInternal>>,
+        driver: Arc<Driver>,
         mut awake_write_loop_ch: mpsc::Receiver<()>,
     ) {
         log::debug!("[{}] write_loop entered", name);
@@ -490,9 +623,11 @@ impl Association {
 
         'outer: while !done.load(Ordering::Relaxed) {
             //log::debug!("[{}] gather_outbound begin", name);
-            let (packets, continue_loop) = {
+            let (packets, continue_loop, zero_checksum) = {
                 let mut ai = association_internal.lock().await;
-                ai.gather_outbound().await
+                let (packets, continue_loop) = ai.gather_outbound(Instant::now());
+                driver.dispatch(&mut ai);
+                (packets, continue_loop, ai.use_zero_checksum)
             };
             //log::debug!("[{}] gather_outbound done with {}", name, packets.len());
 
@@ -510,19 +645,33 @@ impl Association {
                 // If we don't tokio tends to run the write_loop and read_loop of one connection on the same OS thread
                 // This means that even though we release the lock above, the read_loop isn't able to take it, simply because it is not being scheduled by tokio
                 // Doing it this way, tokio schedules this work on a dedicated blocking thread, this future is suspended, and the read_loop can make progress
-                match tokio::task::spawn_blocking(move || raw.marshal_to(&mut buf).map(|_| buf))
-                    .await
+                // User data is passed on as is, only the headers are written to buf
+                match tokio::task::spawn_blocking(move || {
+                    raw.marshal_vectored(&mut buf, zero_checksum)
+                        .map(|segments| (segments, buf))
+                })
+                .await
                 {
-                    Ok(Ok(mut buf)) => {
-                        let raw = buf.as_ref();
-                        if let Err(err) = net_conn.send(raw.as_ref()).await {
-                            log::warn!("[{}] failed to write packets on net_conn: {}", name2, err);
-                            done2.store(true, Ordering::Relaxed)
-                        } else {
-                            bytes_sent.fetch_add(raw.len(), Ordering::SeqCst);
+                    Ok(Ok((segments, mut buf))) => {
+                        let bufs: Vec<IoSlice<'_>> =
+                            segments.iter().map(|s| IoSlice::new(s)).collect();
+                        match net_conn.send_vectored(&bufs).await {
+                            Ok(n) => {
+                                bytes_sent.fetch_add(n, Ordering::SeqCst);
+                            }
+                            Err(err) => {
+                                log::warn!(
+                                    "[{}] failed to write packets on net_conn: {}",
+                                    name2,
+                                    err
+                                );
+                                done2.store(true, Ordering::Relaxed)
+                            }
                         }
 
-                        // Reuse allocation. Have to use options, since spawn blocking can't borrow, has to take ownership.
+                        // Reuse allocation once the segments referring to it are gone.
+                        // Have to use options, since spawn blocking can't borrow, has to take ownership.
+                        drop(segments);
                         buf.clear();
                         buffer = Some(buf);
                     }
@@ -561,14 +710,54 @@ impl Association {
 
         {
             let mut ai = association_internal.lock().await;
-            if let Err(err) = ai.close().await {
+            if let Err(err) = ai.close() {
                 log::warn!("[{}] failed to close association: {:?}", name, err);
             }
+            driver.dispatch(&mut ai);
         }
 
         log::debug!("[{}] write_loop exited", name);
     }
 
+    async fn timer_loop(
+        name: String,
+        mut close_loop_ch: broadcast::Receiver<()>,
+        association_internal: Arc<Mutex<AssociationInternal>>,
+        driver: Arc<Driver>,
+    ) {
+        log::debug!("[{}] timer_loop entered", name);
+
+        loop {
+            let timeout = {
+                let ai = association_internal.lock().await;
+                let timeout = ai.poll_timeout();
+                *driver.timeout.lock() = timeout;
+                timeout
+            };
+
+            let timer = async {
+                match timeout {
+                    Some(timeout) => {
+                        tokio::time::sleep_until(tokio::time::Instant::from_std(timeout)).await
+                    }
+                    None => std::future::pending().await,
+                }
+            };
+
+            tokio::select! {
+                _ = close_loop_ch.recv() => break,
+                _ = driver.timeout_changed.notified() => {}
+                _ = timer => {
+                    let mut ai = association_internal.lock().await;
+                    ai.handle_timeout(Instant::now());
+                    driver.dispatch(&mut ai);
+                }
+            };
+        }
+
+        log::debug!("[{}] timer_loop exited", name);
+    }
+
     /// bytes_sent returns the number of bytes sent
     pub fn bytes_sent(&self) -> usize {
         self.bytes_sent.load(Ordering::SeqCst)
@@ -579,6 +768,28 @@ impl Association {
         self.bytes_received.load(Ordering::SeqCst)
     }
 
+    /// send_buffer_used returns the number of bytes all streams have buffered for sending.
+    pub fn send_buffer_used(&self) -> usize {
+        self.send_buffer.used()
+    }
+
+    /// max_send_buffer_size returns how many bytes all streams may buffer for sending,
+    /// zero meaning no limit. See [`Config::max_send_buffer_size`].
+    pub fn max_send_buffer_size(&self) -> usize {
+        self.send_buffer.capacity()
+    }
+
+    /// get_stats returns a snapshot of the congestion control state, round-trip
+    /// time estimates and retransmission counters of the association.
+    pub async fn get_stats(&self) -> AssociationStatsReport {
+        let ai = self.association_internal.lock().await;
+        AssociationStatsReport {
+            bytes_sent: self.bytes_sent(),
+            bytes_received: self.bytes_received(),
+            ..ai.get_stats()
+        }
+    }
+
     /// open_stream opens a stream
     pub async fn open_stream(
         &self,
@@ -589,12 +800,77 @@ impl Association {
         ai.open_stream(stream_identifier, default_payload_type)
     }
 
+    /// num_outbound_streams returns the number of streams we can send on.
+    /// Stream identifiers from this number on cannot be opened.
+    pub async fn num_outbound_streams(&self) -> u16 {
+        let ai = self.association_internal.lock().await;
+        ai.my_max_num_outbound_streams
+    }
+
+    /// num_inbound_streams returns the number of streams the peer can send on.
+    pub async fn num_inbound_streams(&self) -> u16 {
+        let ai = self.association_internal.lock().await;
+        ai.my_max_num_inbound_streams
+    }
+
+    /// add_streams asks the peer to add outgoing and incoming streams to the
+    /// association (RFC 6525 Sec 5.1.5 and 5.1.6) and waits for its answer.
+    pub async fn add_streams(&self, outgoing: u16, incoming: u16) -> Result<()> {
+        let mut answers = vec![];
+        {
+            let mut ai = self.association_internal.lock().await;
+            for (incoming, number_of_new_streams) in [(false, outgoing), (true, incoming)] {
+                if number_of_new_streams > 0 {
+                    let rsn = ai.send_add_streams_request(
+                        Instant::now(),
+                        incoming,
+                        number_of_new_streams,
+                    )?;
+                    answers.push(self.driver.wait_add_streams_answer(rsn));
+                }
+            }
+            self.driver.dispatch(&mut ai);
+        }
+
+        for answer in answers {
+            match answer.await {
+                Ok(true) => {}
+                Ok(false) => return Err(Error::ErrAddStreamsDenied),
+                Err(_) => return Err(Error::ErrAddStreamsAborted),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// reset_incoming_streams asks the peer to reset the streams it sends on
+    /// to us (RFC 6525 Sec 5.1.3), all of them if none are given.
+    pub async fn reset_incoming_streams(&self, stream_identifiers: &[u16]) -> Result<()> {
+        let mut ai = self.association_internal.lock().await;
+        let result = ai.send_incoming_reset_request(Instant::now(), stream_identifiers.to_vec());
+        self.driver.dispatch(&mut ai);
+        result
+    }
+
     /// accept_stream accepts a stream
     pub async fn accept_stream(&self) -> Option<Arc<Stream>> {
         let mut accept_ch_rx = self.accept_ch_rx.lock().await;
         accept_ch_rx.recv().await
     }
 
+    /// path_status returns whether the peer is currently reachable.
+    pub async fn path_status(&self) -> PathStatus {
+        let ai = self.association_internal.lock().await;
+        ai.path_status()
+    }
+
+    /// path_status_changed waits for the peer to become unreachable or
+    /// reachable again. Returns None once the association is closed.
+    pub async fn path_status_changed(&self) -> Option<PathStatus> {
+        let mut path_status_ch_rx = self.path_status_ch_rx.lock().await;
+        path_status_ch_rx.recv().await
+    }
+
     /// max_message_size returns the maximum message size you can send.
     pub fn max_message_size(&self) -> u32 {
         self.max_message_size.load(Ordering::SeqCst)
@@ -624,3 +900,96 @@ impl Association {
         self.state.load(Ordering::SeqCst).into()
     }
 }
+
+/// EventSenders are the channels the events of an AssociationInternal are
+/// passed on to.
+struct EventSenders {
+    close_loop_ch_tx: Option<broadcast::Sender<()>>,
+    accept_ch_tx: mpsc::Sender<Arc<Stream>>,
+    path_status_ch_tx: Option<mpsc::Sender<PathStatus>>,
+    handshake_completed_ch_tx: mpsc::Sender<Option<Error>>,
+    /// Callers of add_streams waiting for the answers to their requests.
+    add_streams_answers: HashMap<u32, oneshot::Sender<bool>>,
+}
+
+/// Driver is shared by the loops of an Association, which call dispatch()
+/// after every call into the AssociationInternal. It owns everything that
+/// depends on tokio, so that the AssociationInternal doesn't.
+struct Driver {
+    senders: SyncMutex<EventSenders>,
+    /// Deadline the timer_loop is waiting for.
+    timeout: SyncMutex<Option<Instant>>,
+    timeout_changed: Notify,
+}
+
+impl Driver {
+    /// wait_add_streams_answer returns the receiver of the answer to the Add
+    /// Streams request with rsn, which is dropped if the association closes
+    /// before.
+    fn wait_add_streams_answer(&self, rsn: u32) -> oneshot::Receiver<bool> {
+        let (answer_tx, answer_rx) = oneshot::channel();
+        self.senders
+            .lock()
+            .add_streams_answers
+            .insert(rsn, answer_tx);
+        answer_rx
+    }
+
+    /// dispatch passes the events of the association on, and wakes up the
+    /// timer_loop if a timer expires before the deadline it is waiting for.
+    fn dispatch(&self, ai: &mut AssociationInternal) {
+        {
+            let mut senders = self.senders.lock();
+            while let Some(event) = ai.poll_event() {
+                match event {
+                    AssociationEvent::HandshakeCompleted => {
+                        let _ = senders.handshake_completed_ch_tx.try_send(None);
+                    }
+                    AssociationEvent::HandshakeFailed(err) => {
+                        let _ = senders.handshake_completed_ch_tx.try_send(Some(err));
+                    }
+                    AssociationEvent::StreamAccepted(s) => {
+                        let stream_identifier = s.stream_identifier;
+                        if senders.accept_ch_tx.try_send(s).is_err() {
+                            log::debug!("[{}] dropped a new stream due to accept_ch full", ai.name);
+                            ai.unregister_stream(stream_identifier);
+                        }
+                    }
+                    AssociationEvent::PathStatus(path_status) => {
+                        if let Some(path_status_ch) = &senders.path_status_ch_tx {
+                            let _ = path_status_ch.try_send(path_status);
+                        }
+                    }
+                    AssociationEvent::BufferedAmountLow(s) => {
+                        tokio::spawn(async move {
+                            s.notify_buffered_amount_low().await;
+                        });
+                    }
+                    AssociationEvent::AddStreamsAnswered {
+                        request_sequence_number,
+                        performed,
+                    } => {
+                        if let Some(answer_tx) =
+                            senders.add_streams_answers.remove(&request_sequence_number)
+                        {
+                            let _ = answer_tx.send(performed);
+                        }
+                    }
+                    AssociationEvent::Closed => {
+                        // awake read/write/timer_loop to exit
+                        senders.close_loop_ch_tx.take();
+                        senders.path_status_ch_tx.take();
+                        // add_streams callers see the association closing
+                        senders.add_streams_answers.clear();
+                    }
+                }
+            }
+        }
+
+        if let Some(timeout) = ai.poll_timeout() {
+            if self.timeout.lock().is_none_or(|t| timeout < t) {
+                self.timeout_changed.notify_one();
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::chunk_header::*;
+use super::chunk_type::*;
+use super::*;
+
+///chunkCwr represents an SCTP Chunk of type CWR (RFC 4960 Appendix A). The
+///sender of DATA confirms with it that it has reduced its congestion window
+///in answer to the ECNE chunks up to the lowest TSN.
+///
+///0                   1                   2                   3
+///0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|   Type = 13   | Chunk  Flags  |      Length = 8               |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|                      Lowest TSN Number                        |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+#[derive(Default, Debug, Clone)]
+pub(crate) struct ChunkCwr {
+    pub(crate) lowest_tsn: u32,
+}
+
+pub(crate) const CWR_LENGTH: usize = 4;
+
+/// makes chunkCwr printable
+impl fmt::Display for ChunkCwr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.header(), self.lowest_tsn)
+    }
+}
+
+impl Chunk for ChunkCwr {
+    fn header(&self) -> ChunkHeader {
+        ChunkHeader {
+            typ: CT_CWR,
+            flags: 0,
+            value_length: self.value_length() as u16,
+        }
+    }
+
+    fn unmarshal(raw: &Bytes) -> Result<Self> {
+        let header = ChunkHeader::unmarshal(raw)?;
+
+        if header.typ != CT_CWR {
+            return Err(Error::ErrChunkTypeNotCwr);
+        }
+
+        if raw.len() != CHUNK_HEADER_SIZE + CWR_LENGTH {
+            return Err(Error::ErrInvalidChunkSize);
+        }
+
+        let reader = &mut raw.slice(CHUNK_HEADER_SIZE..CHUNK_HEADER_SIZE + header.value_length());
+
+        let lowest_tsn = reader.get_u32();
+
+        Ok(ChunkCwr { lowest_tsn })
+    }
+
+    fn marshal_to(&self, writer: &mut BytesMut) -> Result<usize> {
+        self.header().marshal_to(writer)?;
+        writer.put_u32(self.lowest_tsn);
+        Ok(writer.len())
+    }
+
+    fn check(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn value_length(&self) -> usize {
+        CWR_LENGTH
+    }
+
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+}
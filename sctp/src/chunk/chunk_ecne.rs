@@ -0,0 +1,78 @@
+use std::fmt;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::chunk_header::*;
+use super::chunk_type::*;
+use super::*;
+
+///chunkEcne represents an SCTP Chunk of type ECNE (RFC 4960 Appendix A). The
+///receiver of a packet marked Congestion Experienced echoes the lowest TSN
+///of the DATA it carried until the sender confirms with a CWR chunk.
+///
+///0                   1                   2                   3
+///0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|   Type = 12   | Chunk  Flags  |      Length = 8               |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|                      Lowest TSN Number                        |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+#[derive(Default, Debug, Clone)]
+pub(crate) struct ChunkEcne {
+    pub(crate) lowest_tsn: u32,
+}
+
+pub(crate) const ECNE_LENGTH: usize = 4;
+
+/// makes chunkEcne printable
+impl fmt::Display for ChunkEcne {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.header(), self.lowest_tsn)
+    }
+}
+
+impl Chunk for ChunkEcne {
+    fn header(&self) -> ChunkHeader {
+        ChunkHeader {
+            typ: CT_ECNE,
+            flags: 0,
+            value_length: self.value_length() as u16,
+        }
+    }
+
+    fn unmarshal(raw: &Bytes) -> Result<Self> {
+        let header = ChunkHeader::unmarshal(raw)?;
+
+        if header.typ != CT_ECNE {
+            return Err(Error::ErrChunkTypeNotEcne);
+        }
+
+        if raw.len() != CHUNK_HEADER_SIZE + ECNE_LENGTH {
+            return Err(Error::ErrInvalidChunkSize);
+        }
+
+        let reader = &mut raw.slice(CHUNK_HEADER_SIZE..CHUNK_HEADER_SIZE + header.value_length());
+
+        let lowest_tsn = reader.get_u32();
+
+        Ok(ChunkEcne { lowest_tsn })
+    }
+
+    fn marshal_to(&self, writer: &mut BytesMut) -> Result<usize> {
+        self.header().marshal_to(writer)?;
+        writer.put_u32(self.lowest_tsn);
+        Ok(writer.len())
+    }
+
+    fn check(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn value_length(&self) -> usize {
+        ECNE_LENGTH
+    }
+
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+}
@@ -0,0 +1,198 @@
+use std::fmt;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::chunk_forward_tsn::NEW_CUMULATIVE_TSN_LENGTH;
+use super::chunk_header::*;
+use super::chunk_type::*;
+use super::*;
+
+pub(crate) const I_FORWARD_TSN_STREAM_LENGTH: usize = 8;
+pub(crate) const I_FORWARD_TSN_UNORDERED_BITMASK: u16 = 1;
+
+///I-FORWARD-TSN is the FORWARD-TSN counterpart used once message
+///interleaving is negotiated (RFC 8260 section 2.3.1). Messages are
+///identified by their Message Identifier instead of their SSN, and
+///unordered messages are reported as well.
+/// 0                   1                   2                   3
+/// 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|   Type = 194  |  Flags = 0x00 |      Length = Variable        |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|                       New Cumulative TSN                      |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|       Stream Identifier       |          Reserved           |U|
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|                       Message Identifier                      |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|                                                               |
+///|                                                               |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|       Stream Identifier       |          Reserved           |U|
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|                       Message Identifier                      |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+#[derive(Default, Debug, Clone)]
+pub(crate) struct ChunkIForwardTsn {
+    /// Same meaning as in FORWARD-TSN.
+    pub(crate) new_cumulative_tsn: u32,
+    pub(crate) streams: Vec<ChunkIForwardTsnStream>,
+}
+
+/// makes ChunkIForwardTsn printable
+impl fmt::Display for ChunkIForwardTsn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut res = vec![self.header().to_string()];
+        res.push(format!("New Cumulative TSN: {}", self.new_cumulative_tsn));
+        for s in &self.streams {
+            res.push(format!(
+                " - si={}, u={}, mid={}",
+                s.identifier, s.unordered, s.message_identifier
+            ));
+        }
+
+        write!(f, "{}", res.join("\n"))
+    }
+}
+
+impl Chunk for ChunkIForwardTsn {
+    fn header(&self) -> ChunkHeader {
+        ChunkHeader {
+            typ: CT_I_FORWARD_TSN,
+            flags: 0,
+            value_length: self.value_length() as u16,
+        }
+    }
+
+    fn unmarshal(buf: &Bytes) -> Result<Self> {
+        let header = ChunkHeader::unmarshal(buf)?;
+
+        if header.typ != CT_I_FORWARD_TSN {
+            return Err(Error::ErrChunkTypeNotIForwardTsn);
+        }
+
+        let mut offset = CHUNK_HEADER_SIZE + NEW_CUMULATIVE_TSN_LENGTH;
+        if buf.len() < offset {
+            return Err(Error::ErrChunkTooShort);
+        }
+
+        let reader = &mut buf.slice(CHUNK_HEADER_SIZE..CHUNK_HEADER_SIZE + header.value_length());
+        let new_cumulative_tsn = reader.get_u32();
+
+        let mut streams = vec![];
+        let mut remaining = buf.len() - offset;
+        while remaining > 0 {
+            let s = ChunkIForwardTsnStream::unmarshal(
+                &buf.slice(offset..CHUNK_HEADER_SIZE + header.value_length()),
+            )?;
+            offset += s.value_length();
+            remaining -= s.value_length();
+            streams.push(s);
+        }
+
+        Ok(ChunkIForwardTsn {
+            new_cumulative_tsn,
+            streams,
+        })
+    }
+
+    fn marshal_to(&self, writer: &mut BytesMut) -> Result<usize> {
+        self.header().marshal_to(writer)?;
+
+        writer.put_u32(self.new_cumulative_tsn);
+
+        for s in &self.streams {
+            writer.extend(s.marshal()?);
+        }
+
+        Ok(writer.len())
+    }
+
+    fn check(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn value_length(&self) -> usize {
+        NEW_CUMULATIVE_TSN_LENGTH + I_FORWARD_TSN_STREAM_LENGTH * self.streams.len()
+    }
+
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ChunkIForwardTsnStream {
+    /// This field holds a stream number that was skipped by this
+    /// I-FORWARD-TSN.
+    pub(crate) identifier: u16,
+
+    /// Whether the skipped message was sent unordered. Ordered and
+    /// unordered messages of a stream are numbered independently.
+    pub(crate) unordered: bool,
+
+    /// This field holds the largest message identifier of the stream
+    /// being skipped.
+    pub(crate) message_identifier: u32,
+}
+
+/// makes ChunkIForwardTsnStream printable
+impl fmt::Display for ChunkIForwardTsnStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}, {}, {}",
+            self.identifier, self.unordered, self.message_identifier
+        )
+    }
+}
+
+impl Chunk for ChunkIForwardTsnStream {
+    fn header(&self) -> ChunkHeader {
+        ChunkHeader {
+            typ: ChunkType(0),
+            flags: 0,
+            value_length: self.value_length() as u16,
+        }
+    }
+
+    fn unmarshal(buf: &Bytes) -> Result<Self> {
+        if buf.len() < I_FORWARD_TSN_STREAM_LENGTH {
+            return Err(Error::ErrChunkTooShort);
+        }
+
+        let reader = &mut buf.clone();
+        let identifier = reader.get_u16();
+        let unordered = (reader.get_u16() & I_FORWARD_TSN_UNORDERED_BITMASK) != 0;
+        let message_identifier = reader.get_u32();
+
+        Ok(ChunkIForwardTsnStream {
+            identifier,
+            unordered,
+            message_identifier,
+        })
+    }
+
+    fn marshal_to(&self, writer: &mut BytesMut) -> Result<usize> {
+        writer.put_u16(self.identifier);
+        writer.put_u16(if self.unordered {
+            I_FORWARD_TSN_UNORDERED_BITMASK
+        } else {
+            0
+        });
+        writer.put_u32(self.message_identifier);
+        Ok(writer.len())
+    }
+
+    fn check(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn value_length(&self) -> usize {
+        I_FORWARD_TSN_STREAM_LENGTH
+    }
+
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+}
@@ -5,8 +5,10 @@ use bytes::{Buf, BufMut, Bytes, BytesMut};
 use super::chunk_header::*;
 use super::chunk_type::*;
 use super::*;
+use crate::param::param_ecn_capable::ParamEcnCapable;
 use crate::param::param_header::*;
 use crate::param::param_supported_extensions::ParamSupportedExtensions;
+use crate::param::param_zero_checksum::{ParamZeroChecksumAcceptable, ZERO_CHECKSUM_EDMID_DTLS};
 use crate::param::*;
 use crate::util::get_padding_size;
 
@@ -302,7 +304,21 @@ impl ChunkInit {
         // extension MUST list the ASCONF, the ASCONF-ACK, and the AUTH chunks
         // in its INIT and INIT-ACK parameters.
         self.params.push(Box::new(ParamSupportedExtensions {
-            chunk_types: vec![CT_RECONFIG, CT_FORWARD_TSN],
+            chunk_types: vec![CT_RECONFIG, CT_FORWARD_TSN, CT_I_DATA, CT_I_FORWARD_TSN],
         }));
     }
+
+    /// set_zero_checksum_acceptable advertises that packets with a zero
+    /// checksum are accepted, the DTLS layer protecting their integrity.
+    pub(crate) fn set_zero_checksum_acceptable(&mut self) {
+        self.params.push(Box::new(ParamZeroChecksumAcceptable {
+            edmid: ZERO_CHECKSUM_EDMID_DTLS,
+        }));
+    }
+
+    /// set_ecn_capable advertises support for Explicit Congestion
+    /// Notification (RFC 4960 Appendix A).
+    pub(crate) fn set_ecn_capable(&mut self) {
+        self.params.push(Box::new(ParamEcnCapable));
+    }
 }
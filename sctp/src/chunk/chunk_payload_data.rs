@@ -1,7 +1,7 @@
 use std::fmt;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use std::time::SystemTime;
+use std::time::Instant;
 
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use portable_atomic::AtomicBool;
@@ -9,12 +9,14 @@ use portable_atomic::AtomicBool;
 use super::chunk_header::*;
 use super::chunk_type::*;
 use super::*;
+use crate::stream::ReliabilityType;
 
 pub(crate) const PAYLOAD_DATA_ENDING_FRAGMENT_BITMASK: u8 = 1;
 pub(crate) const PAYLOAD_DATA_BEGINNING_FRAGMENT_BITMASK: u8 = 2;
 pub(crate) const PAYLOAD_DATA_UNORDERED_BITMASK: u8 = 4;
 pub(crate) const PAYLOAD_DATA_IMMEDIATE_SACK: u8 = 8;
 pub(crate) const PAYLOAD_DATA_HEADER_SIZE: usize = 12;
+pub(crate) const PAYLOAD_I_DATA_HEADER_SIZE: usize = 16;
 
 /// PayloadProtocolIdentifier is an enum for DataChannel payload types
 /// PayloadProtocolIdentifier enums
@@ -92,6 +94,29 @@ impl From<u32> for PayloadProtocolIdentifier {
 ///============================================================
 ///|             Table 1: Fragment Description Flags          |
 ///============================================================
+///
+///When message interleaving is negotiated (RFC 8260), the same chunk is sent
+///as I-DATA instead. The SSN is replaced by a 32-bit Message Identifier, and
+///every fragment after the first carries its Fragment Sequence Number in place
+///of the Payload Protocol Identifier:
+///
+/// 0                   1                   2                   3
+/// 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|   Type = 64   |  Res  |I|U|B|E|       Length = Variable       |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|                              TSN                              |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|        Stream Identifier      |           Reserved            |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|                      Message Identifier                       |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|    Payload Protocol Identifier / Fragment Sequence Number     |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///\                                                               \
+///\                           User Data                           \
+///\                                                               \
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
 #[derive(Debug, Clone)]
 pub struct ChunkPayloadData {
     pub(crate) unordered: bool,
@@ -105,14 +130,23 @@ pub struct ChunkPayloadData {
     pub(crate) payload_type: PayloadProtocolIdentifier,
     pub(crate) user_data: Bytes,
 
+    /// Whether this chunk is sent as I-DATA (RFC 8260)
+    pub(crate) interleaved: bool,
+    /// I-DATA only: message identifier, replacing the stream sequence number
+    pub(crate) message_identifier: u32,
+    /// I-DATA only: position of this fragment within its message
+    pub(crate) fragment_sequence_number: u32,
+
     /// Whether this data chunk was acknowledged (received by peer)
     pub(crate) acked: bool,
     pub(crate) miss_indicator: u32,
 
     /// Partial-reliability parameters used only by sender
-    pub(crate) since: SystemTime,
+    pub(crate) since: Instant,
     /// number of transmission made for this chunk
     pub(crate) nsent: u32,
+    /// Partial-reliability of this chunk's message, overriding the stream's
+    pub(crate) reliability: Option<(ReliabilityType, u32)>,
 
     /// valid only with the first fragment
     pub(crate) abandoned: Arc<AtomicBool>,
@@ -136,10 +170,14 @@ impl Default for ChunkPayloadData {
             stream_sequence_number: 0,
             payload_type: PayloadProtocolIdentifier::default(),
             user_data: Bytes::new(),
+            interleaved: false,
+            message_identifier: 0,
+            fragment_sequence_number: 0,
             acked: false,
             miss_indicator: 0,
-            since: SystemTime::now(),
+            since: Instant::now(),
             nsent: 0,
+            reliability: None,
             abandoned: Arc::new(AtomicBool::new(false)),
             all_inflight: Arc::new(AtomicBool::new(false)),
             retransmit: false,
@@ -171,7 +209,11 @@ impl Chunk for ChunkPayloadData {
         }
 
         ChunkHeader {
-            typ: CT_PAYLOAD_DATA,
+            typ: if self.interleaved {
+                CT_I_DATA
+            } else {
+                CT_PAYLOAD_DATA
+            },
             flags,
             value_length: self.value_length() as u16,
         }
@@ -180,9 +222,16 @@ impl Chunk for ChunkPayloadData {
     fn unmarshal(raw: &Bytes) -> Result<Self> {
         let header = ChunkHeader::unmarshal(raw)?;
 
-        if header.typ != CT_PAYLOAD_DATA {
-            return Err(Error::ErrChunkTypeNotPayloadData);
-        }
+        let interleaved = match header.typ {
+            CT_PAYLOAD_DATA => false,
+            CT_I_DATA => true,
+            _ => return Err(Error::ErrChunkTypeNotPayloadData),
+        };
+        let header_size = if interleaved {
+            PAYLOAD_I_DATA_HEADER_SIZE
+        } else {
+            PAYLOAD_DATA_HEADER_SIZE
+        };
 
         let immediate_sack = (header.flags & PAYLOAD_DATA_IMMEDIATE_SACK) != 0;
         let unordered = (header.flags & PAYLOAD_DATA_UNORDERED_BITMASK) != 0;
@@ -190,7 +239,7 @@ impl Chunk for ChunkPayloadData {
         let ending_fragment = (header.flags & PAYLOAD_DATA_ENDING_FRAGMENT_BITMASK) != 0;
 
         // validity of value_length is checked in ChunkHeader::unmarshal
-        if header.value_length() < PAYLOAD_DATA_HEADER_SIZE {
+        if header.value_length() < header_size {
             return Err(Error::ErrChunkPayloadSmall);
         }
 
@@ -198,11 +247,24 @@ impl Chunk for ChunkPayloadData {
 
         let tsn = reader.get_u32();
         let stream_identifier = reader.get_u16();
-        let stream_sequence_number = reader.get_u16();
-        let payload_type: PayloadProtocolIdentifier = reader.get_u32().into();
-        let user_data = raw.slice(
-            CHUNK_HEADER_SIZE + PAYLOAD_DATA_HEADER_SIZE..CHUNK_HEADER_SIZE + header.value_length(),
-        );
+        let mut stream_sequence_number = 0;
+        let mut message_identifier = 0;
+        let mut fragment_sequence_number = 0;
+        let mut payload_type = PayloadProtocolIdentifier::default();
+        if interleaved {
+            reader.advance(2); // reserved
+            message_identifier = reader.get_u32();
+            if beginning_fragment {
+                payload_type = reader.get_u32().into();
+            } else {
+                fragment_sequence_number = reader.get_u32();
+            }
+        } else {
+            stream_sequence_number = reader.get_u16();
+            payload_type = reader.get_u32().into();
+        }
+        let user_data =
+            raw.slice(CHUNK_HEADER_SIZE + header_size..CHUNK_HEADER_SIZE + header.value_length());
 
         Ok(ChunkPayloadData {
             unordered,
@@ -215,10 +277,14 @@ impl Chunk for ChunkPayloadData {
             stream_sequence_number,
             payload_type,
             user_data,
+            interleaved,
+            message_identifier,
+            fragment_sequence_number,
             acked: false,
             miss_indicator: 0,
-            since: SystemTime::now(),
+            since: Instant::now(),
             nsent: 0,
+            reliability: None,
             abandoned: Arc::new(AtomicBool::new(false)),
             all_inflight: Arc::new(AtomicBool::new(false)),
             retransmit: false,
@@ -226,12 +292,7 @@ impl Chunk for ChunkPayloadData {
     }
 
     fn marshal_to(&self, writer: &mut BytesMut) -> Result<usize> {
-        self.header().marshal_to(writer)?;
-
-        writer.put_u32(self.tsn);
-        writer.put_u16(self.stream_identifier);
-        writer.put_u16(self.stream_sequence_number);
-        writer.put_u32(self.payload_type as u32);
+        self.marshal_header_to(writer)?;
         writer.extend_from_slice(&self.user_data);
 
         Ok(writer.len())
@@ -242,7 +303,12 @@ impl Chunk for ChunkPayloadData {
     }
 
     fn value_length(&self) -> usize {
-        PAYLOAD_DATA_HEADER_SIZE + self.user_data.len()
+        let header_size = if self.interleaved {
+            PAYLOAD_I_DATA_HEADER_SIZE
+        } else {
+            PAYLOAD_DATA_HEADER_SIZE
+        };
+        header_size + self.user_data.len()
     }
 
     fn as_any(&self) -> &(dyn Any + Send + Sync) {
@@ -251,6 +317,28 @@ impl Chunk for ChunkPayloadData {
 }
 
 impl ChunkPayloadData {
+    /// marshal_header_to serializes everything of the chunk but its user data.
+    pub(crate) fn marshal_header_to(&self, writer: &mut BytesMut) -> Result<usize> {
+        self.header().marshal_to(writer)?;
+
+        writer.put_u32(self.tsn);
+        writer.put_u16(self.stream_identifier);
+        if self.interleaved {
+            writer.put_u16(0); // reserved
+            writer.put_u32(self.message_identifier);
+            if self.beginning_fragment {
+                writer.put_u32(self.payload_type as u32);
+            } else {
+                writer.put_u32(self.fragment_sequence_number);
+            }
+        } else {
+            writer.put_u16(self.stream_sequence_number);
+            writer.put_u32(self.payload_type as u32);
+        }
+
+        Ok(writer.len())
+    }
+
     pub(crate) fn abandoned(&self) -> bool {
         let (abandoned, all_inflight) = (
             self.abandoned.load(Ordering::SeqCst),
@@ -22,8 +22,10 @@ fn test_chunk_type_string() -> Result<()> {
         (CT_ECNE, "ECNE"),
         (CT_CWR, "CWR"),
         (CT_SHUTDOWN_COMPLETE, "SHUTDOWN-COMPLETE"),
+        (CT_I_DATA, "I-DATA"),
         (CT_RECONFIG, "RECONFIG"),
         (CT_FORWARD_TSN, "FORWARD-TSN"),
+        (CT_I_FORWARD_TSN, "I-FORWARD-TSN"),
         (ChunkType(255), "Unknown ChunkType: 255"),
     ];
 
@@ -229,6 +231,67 @@ fn test_chunk_forward_tsn_unmarshal_failure() -> Result<()> {
     Ok(())
 }
 
+///////////////////////////////////////////////////////////////////
+//chunk_i_forward_tsn_test
+///////////////////////////////////////////////////////////////////
+use super::chunk_i_forward_tsn::*;
+
+#[test]
+fn test_chunk_i_forward_tsn_success() -> Result<()> {
+    let tests = vec![
+        Bytes::from_static(&[0xc2, 0x0, 0x0, 0x8, 0x0, 0x0, 0x0, 0x3]),
+        Bytes::from_static(&[
+            0xc2, 0x0, 0x0, 0x10, 0x0, 0x0, 0x0, 0x3, 0x0, 0x4, 0x0, 0x1, 0x0, 0x0, 0x0, 0x5,
+        ]),
+        Bytes::from_static(&[
+            0xc2, 0x0, 0x0, 0x18, 0x0, 0x0, 0x0, 0x3, 0x0, 0x4, 0x0, 0x1, 0x0, 0x0, 0x0, 0x5, 0x0,
+            0x6, 0x0, 0x0, 0x0, 0x0, 0x0, 0x7,
+        ]),
+    ];
+
+    for binary in tests {
+        let actual = ChunkIForwardTsn::unmarshal(&binary)?;
+        let b = actual.marshal()?;
+        assert_eq!(b, binary, "test not equal");
+    }
+
+    let c = ChunkIForwardTsn::unmarshal(&Bytes::from_static(&[
+        0xc2, 0x0, 0x0, 0x10, 0x0, 0x0, 0x0, 0x3, 0x0, 0x4, 0x0, 0x1, 0x0, 0x0, 0x0, 0x5,
+    ]))?;
+    assert_eq!(c.new_cumulative_tsn, 3);
+    assert_eq!(c.streams[0].identifier, 4);
+    assert!(c.streams[0].unordered);
+    assert_eq!(c.streams[0].message_identifier, 5);
+
+    Ok(())
+}
+
+#[test]
+fn test_chunk_i_forward_tsn_unmarshal_failure() -> Result<()> {
+    let tests = vec![
+        ("chunk header to short", Bytes::from_static(&[0xc2])),
+        (
+            "missing New Cumulative TSN",
+            Bytes::from_static(&[0xc2, 0x0, 0x0, 0x4]),
+        ),
+        (
+            "missing message identifier",
+            Bytes::from_static(&[0xc2, 0x0, 0x0, 0xc, 0x0, 0x0, 0x0, 0x3, 0x0, 0x4, 0x0, 0x1]),
+        ),
+        (
+            "not an I-FORWARD-TSN",
+            Bytes::from_static(&[0xc0, 0x0, 0x0, 0x8, 0x0, 0x0, 0x0, 0x3]),
+        ),
+    ];
+
+    for (name, binary) in tests {
+        let result = ChunkIForwardTsn::unmarshal(&binary);
+        assert!(result.is_err(), "expected unmarshal: {name} to fail.");
+    }
+
+    Ok(())
+}
+
 ///////////////////////////////////////////////////////////////////
 //chunk_reconfig_test
 ///////////////////////////////////////////////////////////////////
@@ -458,6 +521,106 @@ fn test_chunk_shutdown_complete_failure() -> Result<()> {
     Ok(())
 }
 
+///////////////////////////////////////////////////////////////////
+//chunk_ecne_test
+///////////////////////////////////////////////////////////////////
+use super::chunk_ecne::*;
+
+#[test]
+fn test_chunk_ecne_success() -> Result<()> {
+    let tests = vec![Bytes::from_static(&[
+        0x0c, 0x00, 0x00, 0x08, 0x12, 0x34, 0x56, 0x78,
+    ])];
+
+    for binary in tests {
+        let actual = ChunkEcne::unmarshal(&binary)?;
+        assert_eq!(actual.lowest_tsn, 0x12345678);
+        let b = actual.marshal()?;
+        assert_eq!(b, binary, "test not equal");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_chunk_ecne_failure() -> Result<()> {
+    let tests = vec![
+        (
+            "length too short",
+            Bytes::from_static(&[0x0c, 0x00, 0x00, 0x07, 0x12, 0x34, 0x56, 0x78]),
+        ),
+        (
+            "payload too short",
+            Bytes::from_static(&[0x0c, 0x00, 0x00, 0x08, 0x12, 0x34, 0x56]),
+        ),
+        (
+            "payload too long",
+            Bytes::from_static(&[0x0c, 0x00, 0x00, 0x08, 0x12, 0x34, 0x56, 0x78, 0x9f]),
+        ),
+        (
+            "invalid type",
+            Bytes::from_static(&[0x0d, 0x00, 0x00, 0x08, 0x12, 0x34, 0x56, 0x78]),
+        ),
+    ];
+
+    for (name, binary) in tests {
+        let result = ChunkEcne::unmarshal(&binary);
+        assert!(result.is_err(), "expected unmarshal: {name} to fail.");
+    }
+
+    Ok(())
+}
+
+///////////////////////////////////////////////////////////////////
+//chunk_cwr_test
+///////////////////////////////////////////////////////////////////
+use super::chunk_cwr::*;
+
+#[test]
+fn test_chunk_cwr_success() -> Result<()> {
+    let tests = vec![Bytes::from_static(&[
+        0x0d, 0x00, 0x00, 0x08, 0x12, 0x34, 0x56, 0x78,
+    ])];
+
+    for binary in tests {
+        let actual = ChunkCwr::unmarshal(&binary)?;
+        assert_eq!(actual.lowest_tsn, 0x12345678);
+        let b = actual.marshal()?;
+        assert_eq!(b, binary, "test not equal");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_chunk_cwr_failure() -> Result<()> {
+    let tests = vec![
+        (
+            "length too short",
+            Bytes::from_static(&[0x0d, 0x00, 0x00, 0x07, 0x12, 0x34, 0x56, 0x78]),
+        ),
+        (
+            "payload too short",
+            Bytes::from_static(&[0x0d, 0x00, 0x00, 0x08, 0x12, 0x34, 0x56]),
+        ),
+        (
+            "payload too long",
+            Bytes::from_static(&[0x0d, 0x00, 0x00, 0x08, 0x12, 0x34, 0x56, 0x78, 0x9f]),
+        ),
+        (
+            "invalid type",
+            Bytes::from_static(&[0x0c, 0x00, 0x00, 0x08, 0x12, 0x34, 0x56, 0x78]),
+        ),
+    ];
+
+    for (name, binary) in tests {
+        let result = ChunkCwr::unmarshal(&binary);
+        assert!(result.is_err(), "expected unmarshal: {name} to fail.");
+    }
+
+    Ok(())
+}
+
 ///////////////////////////////////////////////////////////////////
 //chunk_test
 ///////////////////////////////////////////////////////////////////
@@ -652,6 +815,52 @@ fn test_payload_data_marshal_unmarshal() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_i_data_marshal_unmarshal() -> Result<()> {
+    let tests = vec![
+        // first fragment carries the PPID
+        (
+            Bytes::from_static(&[
+                0x40, 0x02, 0x00, 0x17, 0x00, 0x00, 0x00, 0x07, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x02, 0x00, 0x00, 0x00, 0x35, 0x66, 0x6f, 0x6f,
+            ]),
+            PayloadProtocolIdentifier::Binary,
+            0,
+        ),
+        // later fragments carry the FSN instead
+        (
+            Bytes::from_static(&[
+                0x40, 0x05, 0x00, 0x17, 0x00, 0x00, 0x00, 0x08, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x02, 0x00, 0x00, 0x00, 0x01, 0x62, 0x61, 0x72,
+            ]),
+            PayloadProtocolIdentifier::Unknown,
+            1,
+        ),
+    ];
+
+    for (binary, payload_type, fsn) in tests {
+        let c = ChunkPayloadData::unmarshal(&binary)?;
+        assert!(c.interleaved);
+        assert_eq!(c.stream_identifier, 1);
+        assert_eq!(c.message_identifier, 2);
+        assert_eq!(c.payload_type, payload_type);
+        assert_eq!(c.fragment_sequence_number, fsn);
+        assert_eq!(c.user_data.len(), 3);
+        assert_eq!(c.marshal()?, binary);
+    }
+
+    assert!(
+        ChunkPayloadData::unmarshal(&Bytes::from_static(&[
+            0x40, 0x03, 0x00, 0x10, 0x00, 0x00, 0x00, 0x07, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x02
+        ]))
+        .is_err(),
+        "I-DATA without PPID/FSN must fail"
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_select_ack_chunk() -> Result<()> {
     let raw_pkt = Bytes::from_static(&[
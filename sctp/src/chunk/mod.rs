@@ -4,11 +4,14 @@ mod chunk_test;
 pub(crate) mod chunk_abort;
 pub(crate) mod chunk_cookie_ack;
 pub(crate) mod chunk_cookie_echo;
+pub(crate) mod chunk_cwr;
+pub(crate) mod chunk_ecne;
 pub(crate) mod chunk_error;
 pub(crate) mod chunk_forward_tsn;
 pub(crate) mod chunk_header;
 pub(crate) mod chunk_heartbeat;
 pub(crate) mod chunk_heartbeat_ack;
+pub(crate) mod chunk_i_forward_tsn;
 pub(crate) mod chunk_init;
 pub mod chunk_payload_data;
 pub(crate) mod chunk_reconfig;
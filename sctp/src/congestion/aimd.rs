@@ -0,0 +1,94 @@
+use super::{initial_cwnd, Ack, CongestionControl};
+
+/// Aimd is the congestion control of RFC 4960 section 7.2: slow start up to
+/// ssthresh, then one MTU more per round trip, halving the window on loss.
+#[derive(Debug)]
+pub struct Aimd {
+    mtu: u32,
+    cwnd: u32,
+    ssthresh: u32,
+    partial_bytes_acked: u32,
+}
+
+impl Aimd {
+    pub fn new(mtu: u32) -> Self {
+        Aimd {
+            mtu,
+            cwnd: initial_cwnd(mtu),
+            ssthresh: 0,
+            partial_bytes_acked: 0,
+        }
+    }
+}
+
+impl CongestionControl for Aimd {
+    fn cwnd(&self) -> u32 {
+        self.cwnd
+    }
+
+    fn ssthresh(&self) -> u32 {
+        self.ssthresh
+    }
+
+    fn set_ssthresh(&mut self, ssthresh: u32) {
+        self.ssthresh = ssthresh;
+    }
+
+    fn on_ack(&mut self, ack: Ack) {
+        if self.cwnd <= self.ssthresh {
+            // RFC 4096, sec 7.2.1.  Slow-Start
+            //   o  When cwnd is less than or equal to ssthresh, an SCTP endpoint MUST
+            //		use the slow-start algorithm to increase cwnd only if the current
+            //      congestion window is being fully utilized, an incoming SACK
+            //      advances the Cumulative TSN Ack Point, and the data sender is not
+            //      in Fast Recovery.  Only when these three conditions are met can
+            //      the cwnd be increased; otherwise, the cwnd MUST not be increased.
+            //		If these conditions are met, then cwnd MUST be increased by, at
+            //      most, the lesser of 1) the total size of the previously
+            //      outstanding DATA chunk(s) acknowledged, and 2) the destination's
+            //      path MTU.
+            if !ack.in_fast_recovery && ack.has_pending_data {
+                self.cwnd += std::cmp::min(ack.bytes_acked, self.cwnd); // TCP way
+                                                                        // self.cwnd += min32(uint32(total_bytes_acked), self.mtu) // SCTP way (slow)
+            }
+        } else {
+            // RFC 4096, sec 7.2.2.  Congestion Avoidance
+            //   o  Whenever cwnd is greater than ssthresh, upon each SACK arrival
+            //      that advances the Cumulative TSN Ack Point, increase
+            //      partial_bytes_acked by the total number of bytes of all new chunks
+            //      acknowledged in that SACK including chunks acknowledged by the new
+            //      Cumulative TSN Ack and by Gap Ack Blocks.
+            self.partial_bytes_acked += ack.bytes_acked;
+
+            //   o  When partial_bytes_acked is equal to or greater than cwnd and
+            //      before the arrival of the SACK the sender had cwnd or more bytes
+            //      of data outstanding (i.e., before arrival of the SACK, flight size
+            //      was greater than or equal to cwnd), increase cwnd by MTU, and
+            //      reset partial_bytes_acked to (partial_bytes_acked - cwnd).
+            if self.partial_bytes_acked >= self.cwnd && ack.has_pending_data {
+                self.partial_bytes_acked -= self.cwnd;
+                self.cwnd += self.mtu;
+            }
+        }
+    }
+
+    fn on_fast_retransmit(&mut self) {
+        // RFC 4960 sec 7.2.3
+        //   ssthresh = max(cwnd/2, 4*MTU)
+        //   cwnd = ssthresh
+        //   partial_bytes_acked = 0
+        self.ssthresh = std::cmp::max(self.cwnd / 2, 4 * self.mtu);
+        self.cwnd = self.ssthresh;
+        self.partial_bytes_acked = 0;
+    }
+
+    fn on_retransmission_timeout(&mut self) {
+        // RFC 4960 sec 7.2.3
+        //   When the T3-rtx timer expires on an address, SCTP should perform slow
+        //   start by:
+        //      ssthresh = max(cwnd/2, 4*MTU)
+        //      cwnd = 1*MTU
+        self.ssthresh = std::cmp::max(self.cwnd / 2, 4 * self.mtu);
+        self.cwnd = self.mtu;
+    }
+}
@@ -0,0 +1,175 @@
+use std::time::Duration;
+
+use super::*;
+
+const MTU: u32 = 1000;
+
+fn ack(bytes_acked: u32) -> Ack {
+    Ack {
+        bytes_acked,
+        in_fast_recovery: false,
+        has_pending_data: true,
+    }
+}
+
+#[test]
+fn test_initial_cwnd() {
+    assert_eq!(initial_cwnd(1228), 4380);
+    assert_eq!(initial_cwnd(1000), 4000);
+    assert_eq!(initial_cwnd(3000), 6000);
+
+    for algorithm in [
+        CongestionControlAlgorithm::Aimd,
+        CongestionControlAlgorithm::Vegas,
+    ] {
+        let cc = algorithm.build(MTU);
+        assert_eq!(cc.cwnd(), 4000, "{algorithm}");
+        assert_eq!(cc.ssthresh(), 0, "{algorithm}");
+    }
+}
+
+#[test]
+fn test_aimd_slow_start_and_congestion_avoidance() {
+    let mut cc = CongestionControlAlgorithm::Aimd.build(MTU);
+    cc.set_ssthresh(10000);
+
+    // The window only grows while it is used and outside fast recovery.
+    cc.on_ack(Ack {
+        has_pending_data: false,
+        ..ack(1000)
+    });
+    assert_eq!(cc.cwnd(), 4000);
+    cc.on_ack(Ack {
+        in_fast_recovery: true,
+        ..ack(1000)
+    });
+    assert_eq!(cc.cwnd(), 4000);
+
+    // Slow start grows by the bytes acked.
+    cc.on_ack(ack(1000));
+    assert_eq!(cc.cwnd(), 5000);
+    cc.on_ack(ack(6000));
+    assert_eq!(cc.cwnd(), 10000);
+    cc.on_ack(ack(1000));
+    assert_eq!(cc.cwnd(), 11000);
+
+    // Congestion avoidance grows by one MTU per window acked.
+    cc.on_ack(ack(6000));
+    assert_eq!(cc.cwnd(), 11000);
+    cc.on_ack(ack(6000));
+    assert_eq!(cc.cwnd(), 12000);
+}
+
+#[test]
+fn test_aimd_loss() {
+    let mut cc = CongestionControlAlgorithm::Aimd.build(MTU);
+    cc.set_ssthresh(100000);
+    cc.on_ack(ack(4000));
+    cc.on_ack(ack(8000));
+    assert_eq!(cc.cwnd(), 16000);
+
+    cc.on_fast_retransmit();
+    assert_eq!(cc.cwnd(), 8000);
+    assert_eq!(cc.ssthresh(), 8000);
+
+    cc.on_retransmission_timeout();
+    assert_eq!(cc.cwnd(), MTU);
+    assert_eq!(cc.ssthresh(), 4 * MTU);
+}
+
+#[test]
+fn test_vegas_slow_start_ends_on_queueing() {
+    let mut cc = CongestionControlAlgorithm::Vegas.build(MTU);
+    cc.set_ssthresh(1_000_000);
+
+    // No queueing: slow start goes on past the first round trip.
+    cc.on_rtt_measured(Duration::from_millis(100));
+    cc.on_ack(ack(4000));
+    assert_eq!(cc.cwnd(), 8000);
+    assert_eq!(cc.ssthresh(), 1_000_000);
+
+    // The RTT doubles as 4 packets queue up, so slow start ends.
+    cc.on_rtt_measured(Duration::from_millis(200));
+    cc.on_ack(ack(8000));
+    assert_eq!(cc.cwnd(), 14000);
+    assert_eq!(cc.ssthresh(), 14000);
+}
+
+#[test]
+fn test_vegas_congestion_avoidance() {
+    let mut cc = CongestionControlAlgorithm::Vegas.build(MTU);
+    cc.set_ssthresh(4000);
+
+    // Without RTT measurements the window grows by one MTU per round trip.
+    cc.on_ack(ack(4000));
+    assert_eq!(cc.cwnd(), 5000);
+
+    // Without queueing it grows by an eighth of the window, at least one MTU.
+    for exp in [6000, 7000, 8000, 9000, 10125] {
+        cc.on_rtt_measured(Duration::from_millis(100));
+        cc.on_ack(ack(cc.cwnd()));
+        assert_eq!(cc.cwnd(), exp);
+    }
+
+    // Between ALPHA and BETA packets queued the window holds.
+    cc.on_rtt_measured(Duration::from_millis(160));
+    cc.on_ack(ack(10125));
+    assert_eq!(cc.cwnd(), 10125);
+
+    // Beyond BETA it shrinks, once per round trip.
+    cc.on_rtt_measured(Duration::from_millis(250));
+    cc.on_ack(ack(5000));
+    assert_eq!(cc.cwnd(), 10125);
+    cc.on_ack(ack(5125));
+    assert_eq!(cc.cwnd(), 9125);
+}
+
+#[test]
+fn test_vegas_loss() {
+    let mut cc = CongestionControlAlgorithm::Vegas.build(MTU);
+    cc.set_ssthresh(4000);
+    for _ in 0..6 {
+        cc.on_rtt_measured(Duration::from_millis(100));
+        cc.on_ack(ack(cc.cwnd()));
+    }
+    assert_eq!(cc.cwnd(), 10125);
+
+    // Loss without queueing takes a quarter off the window.
+    cc.on_fast_retransmit();
+    assert_eq!(cc.cwnd(), 7594);
+    assert_eq!(cc.ssthresh(), 7594);
+
+    // Loss with a queue built up halves it.
+    cc.on_rtt_measured(Duration::from_millis(400));
+    cc.on_ack(ack(7594));
+    assert_eq!(cc.cwnd(), 6594);
+    cc.on_fast_retransmit();
+    assert_eq!(cc.cwnd(), 4 * MTU);
+
+    cc.on_retransmission_timeout();
+    assert_eq!(cc.cwnd(), MTU);
+    assert_eq!(cc.ssthresh(), 4 * MTU);
+}
+
+#[test]
+fn test_congestion_experienced() {
+    let mut cc = CongestionControlAlgorithm::Aimd.build(MTU);
+    cc.set_ssthresh(100000);
+    cc.on_ack(ack(4000));
+    assert_eq!(cc.cwnd(), 8000);
+    cc.on_congestion_experienced();
+    assert_eq!(cc.cwnd(), 4 * MTU);
+    assert_eq!(cc.ssthresh(), 4 * MTU);
+
+    // Vegas halves on a CE mark even without any queueing measured.
+    let mut cc = CongestionControlAlgorithm::Vegas.build(MTU);
+    cc.set_ssthresh(4000);
+    for _ in 0..6 {
+        cc.on_rtt_measured(Duration::from_millis(100));
+        cc.on_ack(ack(cc.cwnd()));
+    }
+    assert_eq!(cc.cwnd(), 10125);
+    cc.on_congestion_experienced();
+    assert_eq!(cc.cwnd(), 5062);
+    assert_eq!(cc.ssthresh(), 5062);
+}
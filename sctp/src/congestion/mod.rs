@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod congestion_test;
+
+pub mod aimd;
+pub mod vegas;
+
+use std::fmt;
+use std::time::Duration;
+
+use aimd::Aimd;
+use vegas::Vegas;
+
+/// CongestionControlAlgorithm selects the congestion controller of an association.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CongestionControlAlgorithm {
+    /// The loss-based additive increase, multiplicative decrease scheme of
+    /// RFC 4960 section 7.2.
+    #[default]
+    Aimd,
+    /// A delay-based controller modeled after TCP Vegas, which grows the window
+    /// quickly for as long as the round-trip time shows no queueing, for
+    /// paths with a high bandwidth-delay product.
+    Vegas,
+}
+
+impl fmt::Display for CongestionControlAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match *self {
+            CongestionControlAlgorithm::Aimd => "Aimd",
+            CongestionControlAlgorithm::Vegas => "Vegas",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl CongestionControlAlgorithm {
+    /// build creates a congestion controller for a path with the given MTU.
+    pub fn build(&self, mtu: u32) -> Box<dyn CongestionControl> {
+        match *self {
+            CongestionControlAlgorithm::Aimd => Box::new(Aimd::new(mtu)),
+            CongestionControlAlgorithm::Vegas => Box::new(Vegas::new(mtu)),
+        }
+    }
+}
+
+/// Ack describes a SACK that advanced the cumulative TSN ack point.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Ack {
+    /// Number of bytes of all new chunks acknowledged by the SACK.
+    pub bytes_acked: u32,
+    /// Whether the sender is in fast recovery.
+    pub in_fast_recovery: bool,
+    /// Whether more data is waiting to be sent, i.e. the window is being used.
+    pub has_pending_data: bool,
+}
+
+/// CongestionControl manages the congestion window (cwnd) and slow start
+/// threshold (ssthresh) limiting how much data an association may have in
+/// flight. Loss detection and retransmission stay with the association,
+/// which only reports their outcome.
+pub trait CongestionControl: fmt::Debug + Send + Sync {
+    /// cwnd returns the congestion window in bytes.
+    fn cwnd(&self) -> u32;
+
+    /// ssthresh returns the slow start threshold in bytes.
+    fn ssthresh(&self) -> u32;
+
+    /// set_ssthresh sets the slow start threshold, initially to the receiver
+    /// window advertised by the peer.
+    fn set_ssthresh(&mut self, ssthresh: u32);
+
+    /// on_rtt_measured is called with every new round-trip time measurement.
+    fn on_rtt_measured(&mut self, _rtt: Duration) {}
+
+    /// on_ack is called for every SACK that advances the cumulative TSN ack point.
+    fn on_ack(&mut self, ack: Ack);
+
+    /// on_fast_retransmit is called when loss is detected from SACKs and
+    /// fast recovery is entered.
+    fn on_fast_retransmit(&mut self);
+
+    /// on_congestion_experienced is called when the peer echoes a Congestion
+    /// Experienced mark (ECNE chunk), at most once per round trip. It reacts
+    /// as to a loss detected from SACKs by default (RFC 4960 Appendix A).
+    fn on_congestion_experienced(&mut self) {
+        self.on_fast_retransmit();
+    }
+
+    /// on_retransmission_timeout is called when the T3-rtx timer expires.
+    fn on_retransmission_timeout(&mut self);
+}
+
+impl Default for Box<dyn CongestionControl> {
+    fn default() -> Self {
+        CongestionControlAlgorithm::default().build(crate::association::INITIAL_MTU)
+    }
+}
+
+/// initial_cwnd returns the window used before sending any DATA.
+///
+/// RFC 4960 Sec 7.2.1
+///  o  The initial cwnd before DATA transmission or after a sufficiently
+///     long idle period MUST be set to min(4*MTU, max (2*MTU, 4380
+///     bytes)).
+pub(crate) fn initial_cwnd(mtu: u32) -> u32 {
+    // TODO: Consider whether this should use `clamp`
+    #[allow(clippy::manual_clamp)]
+    let cwnd = std::cmp::min(4 * mtu, std::cmp::max(2 * mtu, 4380));
+    cwnd
+}
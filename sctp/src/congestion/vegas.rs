@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use super::{initial_cwnd, Ack, CongestionControl};
+
+/// Below this many packets queued at the bottleneck the window grows.
+const ALPHA: u32 = 2;
+/// Above this many packets queued at the bottleneck the window shrinks.
+const BETA: u32 = 4;
+/// Above this many packets queued at the bottleneck slow start ends.
+const GAMMA: u32 = 1;
+
+/// Vegas is a delay-based congestion control modeled after TCP Vegas. Once per
+/// round trip it estimates how many of its packets are queued at the bottleneck
+/// from how far the RTT has risen above the lowest RTT seen, and keeps that
+/// number between ALPHA and BETA instead of waiting for loss. Slow start ends
+/// as soon as a queue builds up, and while none does the window grows by an
+/// eighth per round trip, so high bandwidth-delay paths fill quickly. Loss
+/// without a queue is likely not caused by congestion and only takes a quarter
+/// off the window.
+#[derive(Debug)]
+pub struct Vegas {
+    mtu: u32,
+    cwnd: u32,
+    ssthresh: u32,
+    base_rtt: Option<Duration>,
+    /// Lowest RTT measured in the current round trip.
+    round_rtt: Option<Duration>,
+    round_bytes_acked: u32,
+    /// The round trip ends once the window it started with has been acked.
+    round_cwnd: u32,
+    /// Packets queued at the bottleneck at the end of the last round trip.
+    queued: Option<u32>,
+}
+
+impl Vegas {
+    pub fn new(mtu: u32) -> Self {
+        let cwnd = initial_cwnd(mtu);
+        Vegas {
+            mtu,
+            cwnd,
+            ssthresh: 0,
+            base_rtt: None,
+            round_rtt: None,
+            round_bytes_acked: 0,
+            round_cwnd: cwnd,
+            queued: None,
+        }
+    }
+
+    /// base_rtt returns the lowest RTT measured on the association.
+    pub fn base_rtt(&self) -> Option<Duration> {
+        self.base_rtt
+    }
+
+    // Expected minus actual throughput over the round trip, times the base
+    // RTT, in packets.
+    fn queued_packets(&self, rtt: Duration) -> u32 {
+        let base_rtt = self.base_rtt.unwrap_or(rtt);
+        if rtt <= base_rtt {
+            return 0;
+        }
+
+        let queued_bytes =
+            self.round_cwnd as u128 * (rtt - base_rtt).as_micros() / rtt.as_micros().max(1);
+        (queued_bytes / self.mtu as u128) as u32
+    }
+
+    fn reset_round(&mut self) {
+        self.round_rtt = None;
+        self.round_bytes_acked = 0;
+        self.round_cwnd = self.cwnd;
+    }
+}
+
+impl CongestionControl for Vegas {
+    fn cwnd(&self) -> u32 {
+        self.cwnd
+    }
+
+    fn ssthresh(&self) -> u32 {
+        self.ssthresh
+    }
+
+    fn set_ssthresh(&mut self, ssthresh: u32) {
+        self.ssthresh = ssthresh;
+    }
+
+    fn on_rtt_measured(&mut self, rtt: Duration) {
+        self.base_rtt = Some(self.base_rtt.map_or(rtt, |base_rtt| base_rtt.min(rtt)));
+        self.round_rtt = Some(self.round_rtt.map_or(rtt, |round_rtt| round_rtt.min(rtt)));
+    }
+
+    fn on_ack(&mut self, ack: Ack) {
+        // As in RFC 4960 sec 7.2, the window only grows while it is being used.
+        if ack.in_fast_recovery || !ack.has_pending_data {
+            return;
+        }
+
+        let slow_start = self.cwnd < self.ssthresh;
+        if slow_start {
+            self.cwnd += std::cmp::min(ack.bytes_acked, self.cwnd);
+        }
+
+        self.round_bytes_acked += ack.bytes_acked;
+        if self.round_bytes_acked < self.round_cwnd {
+            return;
+        }
+
+        if let Some(rtt) = self.round_rtt {
+            let queued = self.queued_packets(rtt);
+            self.queued = Some(queued);
+            if slow_start {
+                if queued > GAMMA {
+                    self.cwnd = std::cmp::max(self.cwnd - self.cwnd / 8, 4 * self.mtu);
+                    self.ssthresh = self.cwnd;
+                }
+            } else if queued < ALPHA {
+                self.cwnd += std::cmp::max(self.mtu, self.cwnd / 8);
+            } else if queued > BETA {
+                self.cwnd = std::cmp::max(self.cwnd - self.mtu, 2 * self.mtu);
+            }
+        } else if !slow_start {
+            // Without RTT measurements fall back to additive increase.
+            self.cwnd += self.mtu;
+        }
+        self.reset_round();
+    }
+
+    fn on_fast_retransmit(&mut self) {
+        let cwnd = if matches!(self.queued, Some(queued) if queued < ALPHA) {
+            self.cwnd - self.cwnd / 4
+        } else {
+            self.cwnd / 2
+        };
+        self.ssthresh = std::cmp::max(cwnd, 4 * self.mtu);
+        self.cwnd = self.ssthresh;
+        self.reset_round();
+    }
+
+    fn on_congestion_experienced(&mut self) {
+        // A CE mark is a queue signal from the network, so unlike a loss it is
+        // never mistaken for random corruption: always halve.
+        self.ssthresh = std::cmp::max(self.cwnd / 2, 4 * self.mtu);
+        self.cwnd = self.ssthresh;
+        self.reset_round();
+    }
+
+    fn on_retransmission_timeout(&mut self) {
+        self.ssthresh = std::cmp::max(self.cwnd / 2, 4 * self.mtu);
+        self.cwnd = self.mtu;
+        self.queued = None;
+        self.reset_round();
+    }
+}
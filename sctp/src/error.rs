@@ -34,6 +34,8 @@ pub enum Error {
     ErrChunkTooShort,
     #[error("ChunkType is not of type ForwardTsn")]
     ErrChunkTypeNotForwardTsn,
+    #[error("ChunkType is not of type I-FORWARD-TSN")]
+    ErrChunkTypeNotIForwardTsn,
     #[error("ChunkType is not of type HEARTBEAT")]
     ErrChunkTypeNotHeartbeat,
     #[error("ChunkType is not of type HEARTBEATACK")]
@@ -77,8 +79,12 @@ pub enum Error {
     ErrParamPacketTooShort,
     #[error("outgoing SSN reset request parameter too short")]
     ErrSsnResetRequestParamTooShort,
+    #[error("add streams request parameter too short")]
+    ErrAddStreamsRequestParamTooShort,
     #[error("reconfig response parameter too short")]
     ErrReconfigRespParamTooShort,
+    #[error("zero checksum acceptable parameter too short")]
+    ErrZeroChecksumParamTooShort,
     #[error("invalid algorithm type")]
     ErrInvalidAlgorithmType,
 
@@ -138,6 +144,10 @@ pub enum Error {
     ErrChunkTypeNotShutdownAck,
     #[error("ChunkType is not of type SHUTDOWN-COMPLETE")]
     ErrChunkTypeNotShutdownComplete,
+    #[error("ChunkType is not of type ECNE")]
+    ErrChunkTypeNotEcne,
+    #[error("ChunkType is not of type CWR")]
+    ErrChunkTypeNotCwr,
 
     #[error("raw is smaller than the minimum length for a SCTP packet")]
     ErrPacketRawTooSmall,
@@ -159,6 +169,8 @@ pub enum Error {
 
     #[error("abort chunk, with following errors")]
     ErrChunk,
+    #[error("DATA and I-DATA chunks must not be mixed in an association")]
+    ErrMixedDataChunkTypes,
     #[error("shutdown called in non-Established state")]
     ErrShutdownNonEstablished,
     #[error("association closed before connecting")]
@@ -197,6 +209,14 @@ pub enum Error {
     ErrResetPacketInStateNotExist,
     #[error("unexpected parameter type")]
     ErrParameterType,
+    #[error("stream identifier exceeds the number of negotiated streams")]
+    ErrStreamIdentifierOutOfRange,
+    #[error("too many streams requested")]
+    ErrTooManyStreams,
+    #[error("request to add streams denied by peer")]
+    ErrAddStreamsDenied,
+    #[error("association closed before the request to add streams was answered")]
+    ErrAddStreamsAborted,
     #[error("sending payload data in non-Established state")]
     ErrPayloadDataStateNotExist,
     #[error("unhandled chunk type")]
@@ -208,6 +228,10 @@ pub enum Error {
 
     #[error("outbound packet larger than maximum message size")]
     ErrOutboundPacketTooLarge,
+    #[error("partial message in progress on the stream")]
+    ErrPartialMessageInProgress,
+    #[error("send buffer of the association is full")]
+    ErrSendBufferFull,
     #[error("Stream closed")]
     ErrStreamClosed,
     #[error("Short buffer (size: {size:?}) to be filled")]
@@ -5,10 +5,13 @@ use bytes::{Buf, BufMut, Bytes, BytesMut};
 use crate::chunk::chunk_abort::ChunkAbort;
 use crate::chunk::chunk_cookie_ack::ChunkCookieAck;
 use crate::chunk::chunk_cookie_echo::ChunkCookieEcho;
+use crate::chunk::chunk_cwr::ChunkCwr;
+use crate::chunk::chunk_ecne::ChunkEcne;
 use crate::chunk::chunk_error::ChunkError;
 use crate::chunk::chunk_forward_tsn::ChunkForwardTsn;
 use crate::chunk::chunk_header::*;
 use crate::chunk::chunk_heartbeat::ChunkHeartbeat;
+use crate::chunk::chunk_i_forward_tsn::ChunkIForwardTsn;
 use crate::chunk::chunk_init::ChunkInit;
 use crate::chunk::chunk_payload_data::ChunkPayloadData;
 use crate::chunk::chunk_reconfig::ChunkReconfig;
@@ -89,6 +92,12 @@ pub(crate) const PACKET_HEADER_SIZE: usize = 12;
 
 impl Packet {
     pub(crate) fn unmarshal(raw: &Bytes) -> Result<Self> {
+        Self::unmarshal_with(raw, false)
+    }
+
+    /// unmarshal_with parses a packet, also accepting a checksum of zero
+    /// in place of the CRC32c if zero_checksum_acceptable is set (RFC 9653).
+    pub(crate) fn unmarshal_with(raw: &Bytes, zero_checksum_acceptable: bool) -> Result<Self> {
         if raw.len() < PACKET_HEADER_SIZE {
             return Err(Error::ErrPacketRawTooSmall);
         }
@@ -105,7 +114,8 @@ impl Packet {
             let their_checksum = reader.get_u32_le();
             let our_checksum = generate_packet_checksum(raw);
 
-            if their_checksum != our_checksum {
+            if their_checksum != our_checksum && !(zero_checksum_acceptable && their_checksum == 0)
+            {
                 return Err(Error::ErrChecksumMismatch);
             }
         }
@@ -128,16 +138,21 @@ impl Packet {
                 CT_COOKIE_ECHO => Box::new(ChunkCookieEcho::unmarshal(&raw.slice(offset..))?),
                 CT_COOKIE_ACK => Box::new(ChunkCookieAck::unmarshal(&raw.slice(offset..))?),
                 CT_HEARTBEAT => Box::new(ChunkHeartbeat::unmarshal(&raw.slice(offset..))?),
-                CT_PAYLOAD_DATA => Box::new(ChunkPayloadData::unmarshal(&raw.slice(offset..))?),
+                CT_PAYLOAD_DATA | CT_I_DATA => {
+                    Box::new(ChunkPayloadData::unmarshal(&raw.slice(offset..))?)
+                }
                 CT_SACK => Box::new(ChunkSelectiveAck::unmarshal(&raw.slice(offset..))?),
                 CT_RECONFIG => Box::new(ChunkReconfig::unmarshal(&raw.slice(offset..))?),
                 CT_FORWARD_TSN => Box::new(ChunkForwardTsn::unmarshal(&raw.slice(offset..))?),
+                CT_I_FORWARD_TSN => Box::new(ChunkIForwardTsn::unmarshal(&raw.slice(offset..))?),
                 CT_ERROR => Box::new(ChunkError::unmarshal(&raw.slice(offset..))?),
                 CT_SHUTDOWN => Box::new(ChunkShutdown::unmarshal(&raw.slice(offset..))?),
                 CT_SHUTDOWN_ACK => Box::new(ChunkShutdownAck::unmarshal(&raw.slice(offset..))?),
                 CT_SHUTDOWN_COMPLETE => {
                     Box::new(ChunkShutdownComplete::unmarshal(&raw.slice(offset..))?)
                 }
+                CT_ECNE => Box::new(ChunkEcne::unmarshal(&raw.slice(offset..))?),
+                CT_CWR => Box::new(ChunkCwr::unmarshal(&raw.slice(offset..))?),
                 _ => Box::new(ChunkUnknown::unmarshal(&raw.slice(offset..))?),
             };
 
@@ -155,6 +170,18 @@ impl Packet {
     }
 
     pub(crate) fn marshal_to(&self, writer: &mut BytesMut) -> Result<usize> {
+        self.marshal_to_with(writer, false)
+    }
+
+    /// marshal_to_with serializes the packet, leaving the checksum zero if
+    /// zero_checksum is set (RFC 9653). Packets carrying an INIT or COOKIE
+    /// ECHO chunk always get a CRC32c, since the peer may not know yet that
+    /// they can be accepted without one.
+    pub(crate) fn marshal_to_with(
+        &self,
+        writer: &mut BytesMut,
+        zero_checksum: bool,
+    ) -> Result<usize> {
         // Populate static headers
         // 8-12 is Checksum which will be populated when packet is complete
         writer.put_u16(self.source_port);
@@ -176,6 +203,10 @@ impl Packet {
             }
         }
 
+        if zero_checksum && !self.requires_checksum() {
+            return Ok(writer.len());
+        }
+
         let mut digest = ISCSI_CRC.digest();
         digest.update(writer);
         let checksum = digest.finalize();
@@ -188,11 +219,89 @@ impl Packet {
         Ok(writer.len())
     }
 
+    /// marshal_vectored serializes the packet like marshal_to_with, except
+    /// that the user data of DATA chunks is not copied: the packet is the
+    /// concatenation of the returned segments, which refer to writer for
+    /// everything else.
+    pub(crate) fn marshal_vectored(
+        &self,
+        writer: &mut BytesMut,
+        zero_checksum: bool,
+    ) -> Result<Vec<Bytes>> {
+        writer.put_u16(self.source_port);
+        writer.put_u16(self.destination_port);
+        writer.put_u32(self.verification_tag);
+        writer.extend_from_slice(&[0, 0, 0, 0]);
+        let mut common_header = writer.split();
+
+        let mut segments = vec![];
+        let mut len = common_header.len();
+        for c in &self.chunks {
+            let start = writer.len();
+            let padding_needed = if let Some(d) = c.as_any().downcast_ref::<ChunkPayloadData>() {
+                d.marshal_header_to(writer)?;
+                len += writer.len() - start;
+                segments.push(writer.split().freeze());
+                if !d.user_data.is_empty() {
+                    len += d.user_data.len();
+                    segments.push(d.user_data.clone());
+                }
+
+                // Keep writer aligned to the packet for the chunks that follow
+                let padding_needed = get_padding_size(len);
+                if padding_needed != 0 {
+                    segments.push(Bytes::from_static(
+                        &[0u8; PADDING_MULTIPLE][..padding_needed],
+                    ));
+                }
+                padding_needed
+            } else {
+                c.marshal_to(writer)?;
+                len += writer.len() - start;
+
+                let padding_needed = get_padding_size(len);
+                if padding_needed != 0 {
+                    writer.extend_from_slice(&[0u8; PADDING_MULTIPLE][..padding_needed]);
+                }
+                padding_needed
+            };
+            len += padding_needed;
+        }
+        if !writer.is_empty() {
+            segments.push(writer.split().freeze());
+        }
+
+        if !zero_checksum || self.requires_checksum() {
+            let mut digest = ISCSI_CRC.digest();
+            digest.update(&common_header);
+            for s in &segments {
+                digest.update(s);
+            }
+            let checksum = digest.finalize();
+
+            // Checksum is already in BigEndian
+            // Using LittleEndian stops it from being flipped
+            common_header[8..12].copy_from_slice(&checksum.to_le_bytes());
+        }
+        segments.insert(0, common_header.freeze());
+
+        Ok(segments)
+    }
+
     pub(crate) fn marshal(&self) -> Result<Bytes> {
         let mut buf = BytesMut::with_capacity(PACKET_HEADER_SIZE);
         self.marshal_to(&mut buf)?;
         Ok(buf.freeze())
     }
+
+    fn requires_checksum(&self) -> bool {
+        self.chunks.iter().any(|c| {
+            c.as_any()
+                .downcast_ref::<ChunkInit>()
+                .is_some_and(|ci| !ci.is_ack)
+                || c.as_any().downcast_ref::<ChunkCookieEcho>().is_some()
+        })
+    }
 }
 
 impl Packet {
@@ -301,6 +410,109 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_packet_zero_checksum() -> Result<()> {
+        let pkt = Packet {
+            source_port: 5000,
+            destination_port: 5000,
+            verification_tag: 1,
+            chunks: vec![Box::new(ChunkCookieAck {})],
+        };
+
+        let mut buf = BytesMut::new();
+        pkt.marshal_to_with(&mut buf, true)?;
+        let raw = buf.freeze();
+        assert_eq!(&raw[8..12], &[0, 0, 0, 0], "checksum should be left zero");
+
+        let result = Packet::unmarshal(&raw);
+        assert_eq!(result.unwrap_err(), Error::ErrChecksumMismatch);
+        let parsed = Packet::unmarshal_with(&raw, true)?;
+        assert_eq!(parsed.chunks.len(), 1);
+
+        // A correct checksum is always accepted.
+        let raw = pkt.marshal()?;
+        assert_ne!(&raw[8..12], &[0, 0, 0, 0]);
+        Packet::unmarshal_with(&raw, true)?;
+
+        // Packets with a COOKIE ECHO keep their checksum.
+        let pkt = Packet {
+            source_port: 5000,
+            destination_port: 5000,
+            verification_tag: 1,
+            chunks: vec![Box::new(ChunkCookieEcho {
+                cookie: Bytes::from_static(&[1, 2, 3, 4]),
+            })],
+        };
+        let mut buf = BytesMut::new();
+        pkt.marshal_to_with(&mut buf, true)?;
+        Packet::unmarshal(&buf.freeze())?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_packet_marshal_vectored() -> Result<()> {
+        let data = |user_data: &'static [u8]| -> Box<dyn Chunk + Send + Sync> {
+            Box::new(ChunkPayloadData {
+                beginning_fragment: true,
+                ending_fragment: true,
+                user_data: Bytes::from_static(user_data),
+                ..Default::default()
+            })
+        };
+        let cookie_echo: Box<dyn Chunk + Send + Sync> = Box::new(ChunkCookieEcho {
+            cookie: Bytes::from_static(&[1, 2, 3]),
+        });
+
+        let tests = vec![
+            vec![data(b"ABCD")],
+            vec![
+                Box::new(ChunkCookieAck {}),
+                data(b"A"),
+                data(b""),
+                data(b"ABCD"),
+            ],
+            // COOKIE ECHO always needs a checksum.
+            vec![data(b"A"), cookie_echo, data(b"ABCD")],
+        ];
+
+        for chunks in tests {
+            let pkt = Packet {
+                source_port: 5000,
+                destination_port: 5000,
+                verification_tag: 1,
+                chunks,
+            };
+            let user_data = pkt
+                .chunks
+                .last()
+                .unwrap()
+                .as_any()
+                .downcast_ref::<ChunkPayloadData>()
+                .unwrap()
+                .user_data
+                .as_ptr();
+
+            for zero_checksum in [false, true] {
+                let mut buf = BytesMut::new();
+                pkt.marshal_to_with(&mut buf, zero_checksum)?;
+
+                let mut writer = BytesMut::new();
+                let segments = pkt.marshal_vectored(&mut writer, zero_checksum)?;
+                assert_eq!(
+                    segments.concat(),
+                    buf,
+                    "{pkt} zero_checksum={zero_checksum}"
+                );
+
+                // The user data is not copied.
+                assert!(segments.iter().any(|s| s.as_ptr() == user_data));
+            }
+        }
+
+        Ok(())
+    }
+
     /*fn BenchmarkPacketGenerateChecksum(b *testing.B) {
         var data [1024]byte
 
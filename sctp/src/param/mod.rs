@@ -1,19 +1,24 @@
 #[cfg(test)]
 mod param_test;
 
+pub(crate) mod param_add_streams_request;
 pub(crate) mod param_chunk_list;
+pub(crate) mod param_ecn_capable;
 pub(crate) mod param_forward_tsn_supported;
 pub(crate) mod param_header;
 pub(crate) mod param_heartbeat_info;
+pub(crate) mod param_incoming_reset_request;
 pub(crate) mod param_outgoing_reset_request;
 pub(crate) mod param_random;
 pub(crate) mod param_reconfig_response;
 pub(crate) mod param_requested_hmac_algorithm;
+pub(crate) mod param_ssn_tsn_reset_request;
 pub(crate) mod param_state_cookie;
 pub(crate) mod param_supported_extensions;
 pub(crate) mod param_type;
 pub(crate) mod param_unknown;
 pub(crate) mod param_unrecognized;
+pub(crate) mod param_zero_checksum;
 
 use std::any::Any;
 use std::fmt;
@@ -23,16 +28,21 @@ use param_header::*;
 use param_type::*;
 
 use crate::error::{Error, Result};
+use crate::param::param_add_streams_request::ParamAddStreamsRequest;
 use crate::param::param_chunk_list::ParamChunkList;
+use crate::param::param_ecn_capable::ParamEcnCapable;
 use crate::param::param_forward_tsn_supported::ParamForwardTsnSupported;
 use crate::param::param_heartbeat_info::ParamHeartbeatInfo;
+use crate::param::param_incoming_reset_request::ParamIncomingResetRequest;
 use crate::param::param_outgoing_reset_request::ParamOutgoingResetRequest;
 use crate::param::param_random::ParamRandom;
 use crate::param::param_reconfig_response::ParamReconfigResponse;
 use crate::param::param_requested_hmac_algorithm::ParamRequestedHmacAlgorithm;
+use crate::param::param_ssn_tsn_reset_request::ParamSsnTsnResetRequest;
 use crate::param::param_state_cookie::ParamStateCookie;
 use crate::param::param_supported_extensions::ParamSupportedExtensions;
 use crate::param::param_unknown::ParamUnknown;
+use crate::param::param_zero_checksum::ParamZeroChecksumAcceptable;
 
 pub(crate) trait Param: fmt::Display + fmt::Debug {
     fn header(&self) -> ParamHeader;
@@ -73,7 +83,16 @@ pub(crate) fn build_param(raw_param: &Bytes) -> Result<Box<dyn Param + Send + Sy
         ParamType::StateCookie => Ok(Box::new(ParamStateCookie::unmarshal(raw_param)?)),
         ParamType::HeartbeatInfo => Ok(Box::new(ParamHeartbeatInfo::unmarshal(raw_param)?)),
         ParamType::OutSsnResetReq => Ok(Box::new(ParamOutgoingResetRequest::unmarshal(raw_param)?)),
+        ParamType::IncSsnResetReq => Ok(Box::new(ParamIncomingResetRequest::unmarshal(raw_param)?)),
+        ParamType::SsnTsnResetReq => Ok(Box::new(ParamSsnTsnResetRequest::unmarshal(raw_param)?)),
         ParamType::ReconfigResp => Ok(Box::new(ParamReconfigResponse::unmarshal(raw_param)?)),
+        ParamType::AddOutStreamsReq | ParamType::AddIncStreamsReq => {
+            Ok(Box::new(ParamAddStreamsRequest::unmarshal(raw_param)?))
+        }
+        ParamType::EcnCapable => Ok(Box::new(ParamEcnCapable::unmarshal(raw_param)?)),
+        ParamType::ZeroChecksumAcceptable => {
+            Ok(Box::new(ParamZeroChecksumAcceptable::unmarshal(raw_param)?))
+        }
         _ => {
             // According to RFC https://datatracker.ietf.org/doc/html/rfc4960#section-3.2.1
             let stop_processing = ((raw_type >> 15) & 0x01) == 0;
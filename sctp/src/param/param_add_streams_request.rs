@@ -0,0 +1,98 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::param_header::*;
+use super::param_type::*;
+use super::*;
+
+///These parameters are used by the sender to request that streams be
+///added to the association: its outgoing streams with the Add Outgoing
+///Streams Request (type 17), or its incoming streams, i.e. the outgoing
+///streams of the peer, with the Add Incoming Streams Request (type 18).
+/// 0                   1                   2                   3
+/// 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|   Parameter Type = 17 or 18   |      Parameter Length = 12    |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|          Re-configuration Request Sequence Number             |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|      Number of new streams    |         Reserved              |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub(crate) struct ParamAddStreamsRequest {
+    /// incoming is set for an Add Incoming Streams Request.
+    pub(crate) incoming: bool,
+    /// reconfig_request_sequence_number is used to identify the request.
+    pub(crate) reconfig_request_sequence_number: u32,
+    /// number_of_new_streams is the number of streams to add.
+    pub(crate) number_of_new_streams: u16,
+}
+
+impl fmt::Display for ParamAddStreamsRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.header(),
+            self.reconfig_request_sequence_number,
+            self.number_of_new_streams
+        )
+    }
+}
+
+impl Param for ParamAddStreamsRequest {
+    fn header(&self) -> ParamHeader {
+        ParamHeader {
+            typ: if self.incoming {
+                ParamType::AddIncStreamsReq
+            } else {
+                ParamType::AddOutStreamsReq
+            },
+            value_length: self.value_length() as u16,
+        }
+    }
+
+    fn unmarshal(raw: &Bytes) -> Result<Self> {
+        let header = ParamHeader::unmarshal(raw)?;
+        let incoming = match header.typ {
+            ParamType::AddOutStreamsReq => false,
+            ParamType::AddIncStreamsReq => true,
+            _ => return Err(Error::ErrParamTypeUnexpected),
+        };
+
+        // validity of value_length is checked in ParamHeader::unmarshal
+        if header.value_length() < 8 {
+            return Err(Error::ErrAddStreamsRequestParamTooShort);
+        }
+
+        let reader =
+            &mut raw.slice(PARAM_HEADER_LENGTH..PARAM_HEADER_LENGTH + header.value_length());
+        let reconfig_request_sequence_number = reader.get_u32();
+        let number_of_new_streams = reader.get_u16();
+
+        Ok(ParamAddStreamsRequest {
+            incoming,
+            reconfig_request_sequence_number,
+            number_of_new_streams,
+        })
+    }
+
+    fn marshal_to(&self, buf: &mut BytesMut) -> Result<usize> {
+        self.header().marshal_to(buf)?;
+        buf.put_u32(self.reconfig_request_sequence_number);
+        buf.put_u16(self.number_of_new_streams);
+        buf.put_u16(0);
+        Ok(buf.len())
+    }
+
+    fn value_length(&self) -> usize {
+        8
+    }
+
+    fn clone_to(&self) -> Box<dyn Param + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+}
@@ -0,0 +1,53 @@
+use bytes::{Bytes, BytesMut};
+
+use super::param_header::*;
+use super::param_type::*;
+use super::*;
+
+/// The sender of the INIT or INIT ACK chunk includes this OPTIONAL
+/// parameter to tell its peer that it supports Explicit Congestion
+/// Notification (RFC 4960 Appendix A)
+///
+/// 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|    Parameter Type = 0x8000    |  Parameter Length = 4         |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub(crate) struct ParamEcnCapable;
+
+impl fmt::Display for ParamEcnCapable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.header())
+    }
+}
+
+impl Param for ParamEcnCapable {
+    fn header(&self) -> ParamHeader {
+        ParamHeader {
+            typ: ParamType::EcnCapable,
+            value_length: self.value_length() as u16,
+        }
+    }
+
+    fn unmarshal(raw: &Bytes) -> Result<Self> {
+        let _ = ParamHeader::unmarshal(raw)?;
+        Ok(ParamEcnCapable {})
+    }
+
+    fn marshal_to(&self, buf: &mut BytesMut) -> Result<usize> {
+        self.header().marshal_to(buf)?;
+        Ok(buf.len())
+    }
+
+    fn value_length(&self) -> usize {
+        0
+    }
+
+    fn clone_to(&self) -> Box<dyn Param + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+}
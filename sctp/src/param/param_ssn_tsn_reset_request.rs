@@ -0,0 +1,75 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::param_header::*;
+use super::param_type::*;
+use super::*;
+
+///This parameter is used by the sender to request a reset of the TSN and
+///SSN numbering of all incoming and outgoing streams.
+/// 0                   1                   2                   3
+/// 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|     Parameter Type = 15       |      Parameter Length = 8     |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|         Re-configuration Request Sequence Number              |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub(crate) struct ParamSsnTsnResetRequest {
+    /// reconfig_request_sequence_number is used to identify the request.
+    pub(crate) reconfig_request_sequence_number: u32,
+}
+
+impl fmt::Display for ParamSsnTsnResetRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            self.header(),
+            self.reconfig_request_sequence_number
+        )
+    }
+}
+
+impl Param for ParamSsnTsnResetRequest {
+    fn header(&self) -> ParamHeader {
+        ParamHeader {
+            typ: ParamType::SsnTsnResetReq,
+            value_length: self.value_length() as u16,
+        }
+    }
+
+    fn unmarshal(raw: &Bytes) -> Result<Self> {
+        let header = ParamHeader::unmarshal(raw)?;
+
+        // validity of value_length is checked in ParamHeader::unmarshal
+        if header.value_length() < 4 {
+            return Err(Error::ErrSsnResetRequestParamTooShort);
+        }
+
+        let reader =
+            &mut raw.slice(PARAM_HEADER_LENGTH..PARAM_HEADER_LENGTH + header.value_length());
+        let reconfig_request_sequence_number = reader.get_u32();
+
+        Ok(ParamSsnTsnResetRequest {
+            reconfig_request_sequence_number,
+        })
+    }
+
+    fn marshal_to(&self, buf: &mut BytesMut) -> Result<usize> {
+        self.header().marshal_to(buf)?;
+        buf.put_u32(self.reconfig_request_sequence_number);
+        Ok(buf.len())
+    }
+
+    fn value_length(&self) -> usize {
+        4
+    }
+
+    fn clone_to(&self) -> Box<dyn Param + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+}
@@ -163,6 +163,158 @@ fn test_param_outgoing_reset_request_failure() -> Result<()> {
     Ok(())
 }
 
+///////////////////////////////////////////////////////////////////
+//param_incoming_reset_request_test
+///////////////////////////////////////////////////////////////////
+use super::param_incoming_reset_request::*;
+
+static PARAM_INCOMING_RESET_REQUEST: Bytes =
+    Bytes::from_static(&[0x0, 0xe, 0x0, 0xc, 0x0, 0x0, 0x0, 0x1, 0x0, 0x4, 0x0, 0x5]);
+
+#[test]
+fn test_param_incoming_reset_request_success() -> Result<()> {
+    let tests = vec![
+        (
+            PARAM_INCOMING_RESET_REQUEST.clone(),
+            ParamIncomingResetRequest {
+                reconfig_request_sequence_number: 1,
+                stream_identifiers: vec![4, 5],
+            },
+        ),
+        (
+            Bytes::from_static(&[0x0, 0xe, 0x0, 0x8, 0x0, 0x0, 0x0, 0x1]),
+            ParamIncomingResetRequest {
+                reconfig_request_sequence_number: 1,
+                stream_identifiers: vec![],
+            },
+        ),
+    ];
+
+    for (binary, parsed) in tests {
+        let actual = ParamIncomingResetRequest::unmarshal(&binary)?;
+        assert_eq!(actual, parsed);
+        let b = actual.marshal()?;
+        assert_eq!(b, binary);
+        assert!(build_param(&binary)?
+            .as_any()
+            .downcast_ref::<ParamIncomingResetRequest>()
+            .is_some());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_param_incoming_reset_request_failure() -> Result<()> {
+    let tests = vec![
+        ("packet too short", PARAM_INCOMING_RESET_REQUEST.slice(..8)),
+        ("param too short", Bytes::from_static(&[0x0, 0xe, 0x0, 0x4])),
+    ];
+
+    for (name, binary) in tests {
+        let result = ParamIncomingResetRequest::unmarshal(&binary);
+        assert!(result.is_err(), "expected unmarshal: {name} to fail.");
+    }
+
+    Ok(())
+}
+
+///////////////////////////////////////////////////////////////////
+//param_ssn_tsn_reset_request_test
+///////////////////////////////////////////////////////////////////
+use super::param_ssn_tsn_reset_request::*;
+
+static PARAM_SSN_TSN_RESET_REQUEST: Bytes =
+    Bytes::from_static(&[0x0, 0xf, 0x0, 0x8, 0x0, 0x0, 0x0, 0x1]);
+
+#[test]
+fn test_param_ssn_tsn_reset_request() -> Result<()> {
+    let actual = ParamSsnTsnResetRequest::unmarshal(&PARAM_SSN_TSN_RESET_REQUEST)?;
+    assert_eq!(
+        actual,
+        ParamSsnTsnResetRequest {
+            reconfig_request_sequence_number: 1
+        }
+    );
+    let b = actual.marshal()?;
+    assert_eq!(b, PARAM_SSN_TSN_RESET_REQUEST);
+
+    let result = ParamSsnTsnResetRequest::unmarshal(&Bytes::from_static(&[0x0, 0xf, 0x0, 0x4]));
+    assert!(result.is_err(), "expected unmarshal to fail.");
+
+    Ok(())
+}
+
+///////////////////////////////////////////////////////////////////
+//param_add_streams_request_test
+///////////////////////////////////////////////////////////////////
+use super::param_add_streams_request::*;
+
+static PARAM_ADD_OUTGOING_STREAMS_REQUEST: Bytes =
+    Bytes::from_static(&[0x0, 0x11, 0x0, 0xc, 0x0, 0x0, 0x0, 0x1, 0x0, 0x10, 0x0, 0x0]);
+static PARAM_ADD_INCOMING_STREAMS_REQUEST: Bytes =
+    Bytes::from_static(&[0x0, 0x12, 0x0, 0xc, 0x0, 0x0, 0x0, 0x2, 0x0, 0x20, 0x0, 0x0]);
+
+#[test]
+fn test_param_add_streams_request_success() -> Result<()> {
+    let tests = vec![
+        (
+            PARAM_ADD_OUTGOING_STREAMS_REQUEST.clone(),
+            ParamAddStreamsRequest {
+                incoming: false,
+                reconfig_request_sequence_number: 1,
+                number_of_new_streams: 16,
+            },
+        ),
+        (
+            PARAM_ADD_INCOMING_STREAMS_REQUEST.clone(),
+            ParamAddStreamsRequest {
+                incoming: true,
+                reconfig_request_sequence_number: 2,
+                number_of_new_streams: 32,
+            },
+        ),
+    ];
+
+    for (binary, parsed) in tests {
+        let actual = ParamAddStreamsRequest::unmarshal(&binary)?;
+        assert_eq!(actual, parsed);
+        let b = actual.marshal()?;
+        assert_eq!(b, binary);
+        assert!(build_param(&binary)?
+            .as_any()
+            .downcast_ref::<ParamAddStreamsRequest>()
+            .is_some());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_param_add_streams_request_failure() -> Result<()> {
+    let tests = vec![
+        (
+            "packet too short",
+            PARAM_ADD_OUTGOING_STREAMS_REQUEST.slice(..8),
+        ),
+        (
+            "param too short",
+            Bytes::from_static(&[0x0, 0x11, 0x0, 0x8, 0x0, 0x0, 0x0, 0x1]),
+        ),
+        (
+            "wrong param type",
+            Bytes::from_static(&[0x0, 0xd, 0x0, 0xc, 0x0, 0x0, 0x0, 0x1, 0x0, 0x10, 0x0, 0x0]),
+        ),
+    ];
+
+    for (name, binary) in tests {
+        let result = ParamAddStreamsRequest::unmarshal(&binary);
+        assert!(result.is_err(), "expected unmarshal: {name} to fail.");
+    }
+
+    Ok(())
+}
+
 ///////////////////////////////////////////////////////////////////
 //param_reconfig_response_test
 ///////////////////////////////////////////////////////////////////
@@ -237,6 +389,78 @@ fn test_reconfig_result_stringer() -> Result<()> {
     Ok(())
 }
 
+///////////////////////////////////////////////////////////////////
+//param_zero_checksum_test
+///////////////////////////////////////////////////////////////////
+use super::param_zero_checksum::*;
+
+static PARAM_ZERO_CHECKSUM_ACCEPTABLE: Bytes =
+    Bytes::from_static(&[0x80, 0x1, 0x0, 0x8, 0x0, 0x0, 0x0, 0x1]);
+
+#[test]
+fn test_param_zero_checksum_acceptable_success() -> Result<()> {
+    let actual = ParamZeroChecksumAcceptable::unmarshal(&PARAM_ZERO_CHECKSUM_ACCEPTABLE)?;
+    assert_eq!(
+        actual,
+        ParamZeroChecksumAcceptable {
+            edmid: ZERO_CHECKSUM_EDMID_DTLS
+        }
+    );
+    let b = actual.marshal()?;
+    assert_eq!(b, PARAM_ZERO_CHECKSUM_ACCEPTABLE);
+
+    let p = build_param(&PARAM_ZERO_CHECKSUM_ACCEPTABLE)?;
+    assert!(p
+        .as_any()
+        .downcast_ref::<ParamZeroChecksumAcceptable>()
+        .is_some());
+
+    Ok(())
+}
+
+#[test]
+fn test_param_zero_checksum_acceptable_failure() -> Result<()> {
+    let tests = vec![
+        (
+            "packet too short",
+            PARAM_ZERO_CHECKSUM_ACCEPTABLE.slice(..6),
+        ),
+        (
+            "param too short",
+            Bytes::from_static(&[0x80, 0x1, 0x0, 0x4]),
+        ),
+    ];
+
+    for (name, binary) in tests {
+        let result = ParamZeroChecksumAcceptable::unmarshal(&binary);
+        assert!(result.is_err(), "expected unmarshal: {name} to fail.");
+    }
+
+    Ok(())
+}
+
+///////////////////////////////////////////////////////////////////
+//param_ecn_capable_test
+///////////////////////////////////////////////////////////////////
+use super::param_ecn_capable::*;
+
+#[test]
+fn test_param_ecn_capable() -> Result<()> {
+    let binary = Bytes::from_static(&[0x80, 0x0, 0x0, 0x4]);
+    let actual = ParamEcnCapable::unmarshal(&binary)?;
+    assert_eq!(actual.marshal()?, binary);
+
+    let p = build_param(&binary)?;
+    assert!(p.as_any().downcast_ref::<ParamEcnCapable>().is_some());
+
+    assert!(
+        ParamEcnCapable::unmarshal(&binary.slice(..3)).is_err(),
+        "expected unmarshal of a short param to fail"
+    );
+
+    Ok(())
+}
+
 ///////////////////////////////////////////////////////////////////
 //param_test
 ///////////////////////////////////////////////////////////////////
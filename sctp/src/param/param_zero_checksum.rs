@@ -0,0 +1,73 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::param_header::*;
+use super::param_type::*;
+use super::*;
+
+/// ZERO_CHECKSUM_EDMID_DTLS is the Error Detection Method Identifier of
+/// SCTP over DTLS, which protects the integrity of every packet itself.
+pub(crate) const ZERO_CHECKSUM_EDMID_DTLS: u32 = 1;
+
+/// An endpoint includes this OPTIONAL parameter in the INIT or INIT ACK
+/// chunk to tell its peer that it accepts packets with a checksum of zero,
+/// because the alternate error detection method identified by the EDMID
+/// is in use (RFC 9653).
+///
+/// 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|   Parameter Type = 0x8001     |  Parameter Length = 8         |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///|         Error Detection Method Identifier (EDMID)             |
+///+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub(crate) struct ParamZeroChecksumAcceptable {
+    pub(crate) edmid: u32,
+}
+
+impl fmt::Display for ParamZeroChecksumAcceptable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.header(), self.edmid)
+    }
+}
+
+impl Param for ParamZeroChecksumAcceptable {
+    fn header(&self) -> ParamHeader {
+        ParamHeader {
+            typ: ParamType::ZeroChecksumAcceptable,
+            value_length: self.value_length() as u16,
+        }
+    }
+
+    fn unmarshal(raw: &Bytes) -> Result<Self> {
+        let header = ParamHeader::unmarshal(raw)?;
+
+        // validity of value_length is checked in ParamHeader::unmarshal
+        if header.value_length < 4 {
+            return Err(Error::ErrZeroChecksumParamTooShort);
+        }
+
+        let reader =
+            &mut raw.slice(PARAM_HEADER_LENGTH..PARAM_HEADER_LENGTH + header.value_length());
+        let edmid = reader.get_u32();
+
+        Ok(ParamZeroChecksumAcceptable { edmid })
+    }
+
+    fn marshal_to(&self, buf: &mut BytesMut) -> Result<usize> {
+        self.header().marshal_to(buf)?;
+        buf.put_u32(self.edmid);
+        Ok(buf.len())
+    }
+
+    fn value_length(&self) -> usize {
+        4
+    }
+
+    fn clone_to(&self) -> Box<dyn Param + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+}
@@ -1,11 +1,12 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::sync::atomic::Ordering;
 
-use portable_atomic::{AtomicBool, AtomicUsize};
+use portable_atomic::{AtomicBool, AtomicU16, AtomicU8, AtomicUsize};
 use tokio::sync::{Mutex, Semaphore};
-use util::sync::RwLock;
+use util::sync::{Mutex as SyncMutex, RwLock};
 
 use crate::chunk::chunk_payload_data::ChunkPayloadData;
+use crate::stream::{StreamScheduler, DEFAULT_STREAM_PRIORITY};
 
 // TODO: benchmark performance between multiple Atomic+Mutex vs one Mutex<PendingQueueInternal>
 
@@ -19,9 +20,63 @@ const QUEUE_BYTES_LIMIT: usize = 128 * 1024;
 /// added to the pending queue one by one.
 const QUEUE_APPEND_LARGE: usize = (QUEUE_BYTES_LIMIT * 2) / 3;
 
+/// Scale applied to the bytes sent by a stream before dividing by its
+/// priority, to keep the virtual times of weighted fair queueing precise.
+const WEIGHTED_FAIR_SCALE: u64 = 1 << 16;
+
 /// Basic queue for either ordered or unordered chunks.
 pub(crate) type PendingBaseQueue = VecDeque<ChunkPayloadData>;
 
+/// State of the round robin and weighted fair schedulers.
+#[derive(Default, Debug)]
+struct SchedulerState {
+    last_stream_identifier: u16,
+    /// Without I-DATA, the stream whose fragmented message is being sent,
+    /// and whether the message is unordered.
+    selected: Option<(u16, bool)>,
+    priorities: HashMap<u16, u16>,
+    /// Start-time fair queueing: the virtual time, and per stream the virtual
+    /// time at which its next chunk may start.
+    virtual_time: u64,
+    finish_tags: HashMap<u16, u64>,
+}
+
+impl SchedulerState {
+    fn start_tag(&self, stream_identifier: u16) -> u64 {
+        let finish_tag = self
+            .finish_tags
+            .get(&stream_identifier)
+            .copied()
+            .unwrap_or_default();
+        std::cmp::max(self.virtual_time, finish_tag)
+    }
+
+    fn on_pop(&mut self, c: &ChunkPayloadData, interleaving: bool) {
+        let si = c.stream_identifier;
+        self.last_stream_identifier = si;
+        if !interleaving {
+            self.selected = if c.ending_fragment {
+                None
+            } else {
+                Some((si, c.unordered))
+            };
+        }
+
+        let priority = self
+            .priorities
+            .get(&si)
+            .copied()
+            .unwrap_or(DEFAULT_STREAM_PRIORITY)
+            .max(1);
+        let start_tag = self.start_tag(si);
+        self.finish_tags.insert(
+            si,
+            start_tag + c.user_data.len() as u64 * WEIGHTED_FAIR_SCALE / priority as u64,
+        );
+        self.virtual_time = start_tag;
+    }
+}
+
 /// A queue for both ordered and unordered chunks.
 #[derive(Debug)]
 pub(crate) struct PendingQueue {
@@ -40,6 +95,13 @@ pub(crate) struct PendingQueue {
     n_bytes: AtomicUsize,
     selected: AtomicBool,
     unordered_is_selected: AtomicBool,
+    /// The stream whose fragmented message is being sent. Its chunks may not
+    /// be at the front of the queue if the message is written in parts.
+    selected_stream_identifier: AtomicU16,
+    /// With I-DATA, chunks of different messages may be interleaved
+    interleaving: AtomicBool,
+    scheduler: AtomicU8,
+    scheduler_state: SyncMutex<SchedulerState>,
 }
 
 impl Default for PendingQueue {
@@ -59,9 +121,35 @@ impl PendingQueue {
             n_bytes: Default::default(),
             selected: Default::default(),
             unordered_is_selected: Default::default(),
+            selected_stream_identifier: Default::default(),
+            interleaving: Default::default(),
+            scheduler: AtomicU8::new(StreamScheduler::default() as u8),
+            scheduler_state: Default::default(),
         }
     }
 
+    /// set_scheduler selects how chunks of different streams are ordered.
+    pub(crate) fn set_scheduler(&self, scheduler: StreamScheduler) {
+        self.scheduler.store(scheduler as u8, Ordering::SeqCst);
+    }
+
+    /// scheduler returns the scheduler in use.
+    pub(crate) fn scheduler(&self) -> StreamScheduler {
+        self.scheduler.load(Ordering::SeqCst).into()
+    }
+
+    /// set_priority sets the weight of a stream for StreamScheduler::WeightedFair.
+    pub(crate) fn set_priority(&self, stream_identifier: u16, priority: u16) {
+        let mut state = self.scheduler_state.lock();
+        state.priorities.insert(stream_identifier, priority);
+    }
+
+    /// set_interleaving enables interleaving messages of different streams
+    /// chunk by chunk, as allowed once I-DATA is negotiated (RFC 8260).
+    pub(crate) fn set_interleaving(&self, interleaving: bool) {
+        self.interleaving.store(interleaving, Ordering::SeqCst);
+    }
+
     /// Appends a chunk to the back of the pending queue.
     pub(crate) async fn push(&self, c: ChunkPayloadData) {
         let user_data_len = c.user_data.len();
@@ -85,6 +173,23 @@ impl PendingQueue {
         self.queue_len.fetch_add(1, Ordering::SeqCst);
     }
 
+    /// Appends the chunk without user data that resets its stream to the back of the pending
+    /// queue. Unlike push it never waits, as the chunk takes no room in the queue, and the
+    /// fragments of other messages are popped by their stream anyway.
+    pub(crate) fn push_reset(&self, c: ChunkPayloadData) {
+        debug_assert!(c.user_data.is_empty());
+
+        if c.unordered {
+            let mut unordered_queue = self.unordered_queue.write();
+            unordered_queue.push_back(c);
+        } else {
+            let mut ordered_queue = self.ordered_queue.write();
+            ordered_queue.push_back(c);
+        }
+
+        self.queue_len.fetch_add(1, Ordering::SeqCst);
+    }
+
     /// Appends chunks to the back of the pending queue.
     ///
     /// # Panics
@@ -113,10 +218,21 @@ impl PendingQueue {
 
     // If this is a very large message we append chunks one by one to allow progress while we are appending
     async fn append_large(&self, chunks: Vec<ChunkPayloadData>) {
-        // lock this for the whole duration
-        let _sem_lock = self.semaphore_lock.lock().await;
+        // lock this for the whole duration, unless other streams may interleave
+        // their chunks with ours anyway
+        let interleaving = self.interleaving.load(Ordering::SeqCst);
+        let _sem_lock = if interleaving {
+            None
+        } else {
+            Some(self.semaphore_lock.lock().await)
+        };
 
         for chunk in chunks.into_iter() {
+            let _chunk_lock = if interleaving {
+                Some(self.semaphore_lock.lock().await)
+            } else {
+                None
+            };
             let user_data_len = chunk.user_data.len();
             let permits = self.semaphore.acquire_many(user_data_len as u32).await;
             // unwrap ok because we never close the semaphore unless we have dropped self
@@ -163,14 +279,26 @@ impl PendingQueue {
     }
 
     pub(crate) fn peek(&self) -> Option<ChunkPayloadData> {
+        if self.scheduler() != StreamScheduler::Fifo {
+            let unordered_queue = self.unordered_queue.read();
+            let ordered_queue = self.ordered_queue.read();
+            let state = self.scheduler_state.lock();
+            return match self.select(&unordered_queue, &ordered_queue, &state) {
+                Some((true, idx)) => unordered_queue.get(idx).cloned(),
+                Some((false, idx)) => ordered_queue.get(idx).cloned(),
+                None => None,
+            };
+        }
+
         if self.selected.load(Ordering::SeqCst) {
-            if self.unordered_is_selected.load(Ordering::SeqCst) {
-                let unordered_queue = self.unordered_queue.read();
-                return unordered_queue.front().cloned();
+            let queue = if self.unordered_is_selected.load(Ordering::SeqCst) {
+                self.unordered_queue.read()
             } else {
-                let ordered_queue = self.ordered_queue.read();
-                return ordered_queue.front().cloned();
-            }
+                self.ordered_queue.read()
+            };
+            return self
+                .selected_position(&queue)
+                .and_then(|idx| queue.get(idx).cloned());
         }
 
         let c = {
@@ -191,14 +319,28 @@ impl PendingQueue {
         beginning_fragment: bool,
         unordered: bool,
     ) -> Option<ChunkPayloadData> {
-        let popped = if self.selected.load(Ordering::SeqCst) {
-            let popped = if self.unordered_is_selected.load(Ordering::SeqCst) {
-                let mut unordered_queue = self.unordered_queue.write();
-                unordered_queue.pop_front()
+        let popped = if self.scheduler() != StreamScheduler::Fifo {
+            let mut unordered_queue = self.unordered_queue.write();
+            let mut ordered_queue = self.ordered_queue.write();
+            let mut state = self.scheduler_state.lock();
+            let popped = match self.select(&unordered_queue, &ordered_queue, &state) {
+                Some((true, idx)) => unordered_queue.remove(idx),
+                Some((false, idx)) => ordered_queue.remove(idx),
+                None => None,
+            };
+            if let Some(p) = &popped {
+                state.on_pop(p, self.interleaving.load(Ordering::SeqCst));
+            }
+            popped
+        } else if self.selected.load(Ordering::SeqCst) {
+            let mut queue = if self.unordered_is_selected.load(Ordering::SeqCst) {
+                self.unordered_queue.write()
             } else {
-                let mut ordered_queue = self.ordered_queue.write();
-                ordered_queue.pop_front()
+                self.ordered_queue.write()
             };
+            let popped = self
+                .selected_position(&queue)
+                .and_then(|idx| queue.remove(idx));
             if let Some(p) = &popped {
                 if p.ending_fragment {
                     self.selected.store(false, Ordering::SeqCst);
@@ -218,6 +360,8 @@ impl PendingQueue {
                     if !p.ending_fragment {
                         self.selected.store(true, Ordering::SeqCst);
                         self.unordered_is_selected.store(true, Ordering::SeqCst);
+                        self.selected_stream_identifier
+                            .store(p.stream_identifier, Ordering::SeqCst);
                     }
                 }
                 popped
@@ -230,6 +374,8 @@ impl PendingQueue {
                     if !p.ending_fragment {
                         self.selected.store(true, Ordering::SeqCst);
                         self.unordered_is_selected.store(false, Ordering::SeqCst);
+                        self.selected_stream_identifier
+                            .store(p.stream_identifier, Ordering::SeqCst);
                     }
                 }
                 popped
@@ -246,6 +392,59 @@ impl PendingQueue {
         popped
     }
 
+    /// selected_position returns the position of the next chunk of the
+    /// fragmented message being sent, usually the front of the queue.
+    fn selected_position(&self, queue: &PendingBaseQueue) -> Option<usize> {
+        let si = self.selected_stream_identifier.load(Ordering::SeqCst);
+        queue.iter().position(|c| c.stream_identifier == si)
+    }
+
+    /// select picks the chunk to send next for the round robin and weighted
+    /// fair schedulers: the oldest chunk of the stream with the earliest start
+    /// tag, or simply the one following the last stream served. Without I-DATA
+    /// a stream keeps its turn until its fragmented message is complete.
+    /// Unordered chunks are preferred within a stream.
+    fn select(
+        &self,
+        unordered_queue: &PendingBaseQueue,
+        ordered_queue: &PendingBaseQueue,
+        state: &SchedulerState,
+    ) -> Option<(bool, usize)> {
+        if !self.interleaving.load(Ordering::SeqCst) {
+            if let Some((si, unordered)) = state.selected {
+                let queue = if unordered {
+                    unordered_queue
+                } else {
+                    ordered_queue
+                };
+                return queue
+                    .iter()
+                    .position(|c| c.stream_identifier == si)
+                    .map(|idx| (unordered, idx));
+            }
+        }
+
+        let weighted = self.scheduler() == StreamScheduler::WeightedFair;
+        let next = state.last_stream_identifier.wrapping_add(1);
+
+        let mut selected: Option<(bool, usize, (u64, u16))> = None;
+        for (unordered, queue) in [(true, unordered_queue), (false, ordered_queue)] {
+            for (idx, c) in queue.iter().enumerate() {
+                let start_tag = if weighted {
+                    state.start_tag(c.stream_identifier)
+                } else {
+                    0
+                };
+                let key = (start_tag, c.stream_identifier.wrapping_sub(next));
+                if selected.is_none_or(|(_, _, k)| key < k) {
+                    selected = Some((unordered, idx, key));
+                }
+            }
+        }
+
+        selected.map(|(unordered, idx, _)| (unordered, idx))
+    }
+
     pub(crate) fn get_num_bytes(&self) -> usize {
         self.n_bytes.load(Ordering::SeqCst)
     }
@@ -191,6 +191,7 @@ fn test_payload_queue_reset_retransmit_flag_on_ack() -> Result<()> {
 //pending_queue_test
 ///////////////////////////////////////////////////////////////////
 use super::pending_queue::*;
+use crate::stream::StreamScheduler;
 
 const NO_FRAGMENT: usize = 0;
 const FRAG_BEGIN: usize = 1;
@@ -424,6 +425,68 @@ async fn test_pending_queue_selection_persistence() -> Result<()> {
     Ok(())
 }
 
+// A message written in parts may have chunks of other streams queued
+// between its parts, which must wait until it is complete.
+#[tokio::test]
+async fn test_pending_queue_selection_skips_other_streams() -> Result<()> {
+    let pq = PendingQueue::new();
+    pq.set_scheduler(StreamScheduler::Fifo);
+    pq.push(make_data_chunk(0, false, FRAG_BEGIN)).await;
+    let mut c = make_data_chunk(1, false, NO_FRAGMENT);
+    c.stream_identifier = 1;
+    pq.push(c).await;
+    pq.push(make_data_chunk(2, false, FRAG_END)).await;
+
+    let expects = vec![0, 2, 1];
+
+    for exp in expects {
+        let c = pq.peek();
+        assert!(c.is_some(), "peek error");
+        let c = c.unwrap();
+        assert_eq!(c.tsn, exp, "TSN should match");
+        let (beginning_fragment, unordered) = (c.beginning_fragment, c.unordered);
+        let result = pq.pop(beginning_fragment, unordered);
+        assert!(result.is_some(), "should not error: {exp}");
+    }
+
+    Ok(())
+}
+
+// A stream reset queued by the association in the middle of another
+// message is sent after it, and takes no room in the queue.
+#[tokio::test]
+async fn test_pending_queue_push_reset() -> Result<()> {
+    let pq = PendingQueue::new();
+    pq.set_scheduler(StreamScheduler::Fifo);
+    pq.push(make_data_chunk(0, false, FRAG_BEGIN)).await;
+    pq.push_reset(ChunkPayloadData {
+        tsn: 1,
+        stream_identifier: 1,
+        beginning_fragment: true,
+        ending_fragment: true,
+        ..Default::default()
+    });
+    pq.push(make_data_chunk(2, false, FRAG_END)).await;
+    assert_eq!(pq.get_num_bytes(), 20, "total bytes mismatch");
+    assert_eq!(pq.len(), 3, "len mismatch");
+
+    let expects = vec![0, 2, 1];
+
+    for exp in expects {
+        let c = pq.peek();
+        assert!(c.is_some(), "peek error");
+        let c = c.unwrap();
+        assert_eq!(c.tsn, exp, "TSN should match");
+        let (beginning_fragment, unordered) = (c.beginning_fragment, c.unordered);
+        let result = pq.pop(beginning_fragment, unordered);
+        assert!(result.is_some(), "should not error: {exp}");
+    }
+    assert!(pq.is_empty(), "should be empty");
+    assert_eq!(pq.get_num_bytes(), 0, "total bytes mismatch");
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_pending_queue_append() -> Result<()> {
     let pq = PendingQueue::new();
@@ -439,6 +502,134 @@ async fn test_pending_queue_append() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_pending_queue_interleaving() -> Result<()> {
+    let pq = PendingQueue::new();
+    pq.set_interleaving(true);
+
+    // A fragmented message on stream 1 followed by single chunk messages on
+    // streams 2 and 3.
+    let chunks = [FRAG_BEGIN, FRAG_MIDDLE, FRAG_END]
+        .into_iter()
+        .enumerate()
+        .map(|(i, frag)| ChunkPayloadData {
+            stream_identifier: 1,
+            ..make_data_chunk(i as u32, false, frag)
+        })
+        .collect();
+    pq.append(chunks).await;
+    for si in [2, 3] {
+        pq.push(ChunkPayloadData {
+            stream_identifier: si,
+            ..make_data_chunk(si as u32 + 1, false, NO_FRAGMENT)
+        })
+        .await;
+    }
+
+    // Streams are served round robin instead of completing stream 1 first.
+    for exp in [0, 3, 4, 1, 2] {
+        let c = pq.peek().expect("should not be none");
+        assert_eq!(c.tsn, exp, "TSN for peek should match");
+
+        let c = pq.pop(c.beginning_fragment, c.unordered);
+        assert_eq!(c.map(|c| c.tsn), Some(exp), "TSN for pop should match");
+    }
+    assert!(pq.is_empty(), "should be empty");
+
+    Ok(())
+}
+
+fn make_stream_chunk(tsn: u32, stream_identifier: u16, frag: usize) -> ChunkPayloadData {
+    ChunkPayloadData {
+        stream_identifier,
+        ..make_data_chunk(tsn, false, frag)
+    }
+}
+
+#[tokio::test]
+async fn test_pending_queue_round_robin() -> Result<()> {
+    let pq = PendingQueue::new();
+    assert_eq!(pq.scheduler(), StreamScheduler::RoundRobin);
+
+    // Two messages on stream 1, the first one fragmented, and one on stream 2.
+    pq.append(vec![
+        make_stream_chunk(0, 1, FRAG_BEGIN),
+        make_stream_chunk(1, 1, FRAG_END),
+    ])
+    .await;
+    pq.push(make_stream_chunk(2, 1, NO_FRAGMENT)).await;
+    pq.push(make_stream_chunk(3, 2, NO_FRAGMENT)).await;
+
+    // Without I-DATA streams only take turns at message boundaries.
+    for exp in [0, 1, 3, 2] {
+        let c = pq.peek().expect("should not be none");
+        assert_eq!(c.tsn, exp, "TSN for peek should match");
+
+        let c = pq.pop(c.beginning_fragment, c.unordered);
+        assert_eq!(c.map(|c| c.tsn), Some(exp), "TSN for pop should match");
+    }
+    assert!(pq.is_empty(), "should be empty");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pending_queue_weighted_fair() -> Result<()> {
+    let pq = PendingQueue::new();
+    pq.set_scheduler(StreamScheduler::WeightedFair);
+    pq.set_priority(1, 512);
+    pq.set_priority(2, 128);
+
+    // A bulk transfer on stream 2 is queued before messages on stream 1.
+    for i in 0..10 {
+        pq.push(make_stream_chunk(i, 2, NO_FRAGMENT)).await;
+    }
+    for i in 10..20 {
+        pq.push(make_stream_chunk(i, 1, NO_FRAGMENT)).await;
+    }
+
+    // Stream 1 has four times the priority of stream 2, so it gets four
+    // times the share of the association.
+    let mut n_sent = [0; 2];
+    for _ in 0..10 {
+        let c = pq.peek().expect("should not be none");
+        let c = pq
+            .pop(c.beginning_fragment, c.unordered)
+            .expect("should not be none");
+        n_sent[c.stream_identifier as usize - 1] += 1;
+    }
+    assert_eq!(n_sent, [8, 2], "chunks sent per stream should match");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pending_queue_fifo() -> Result<()> {
+    let pq = PendingQueue::new();
+    pq.set_scheduler(StreamScheduler::Fifo);
+    assert_eq!(pq.scheduler(), StreamScheduler::Fifo);
+
+    pq.append(vec![
+        make_stream_chunk(0, 1, FRAG_BEGIN),
+        make_stream_chunk(1, 1, FRAG_END),
+    ])
+    .await;
+    pq.push(make_stream_chunk(2, 1, NO_FRAGMENT)).await;
+    pq.push(make_stream_chunk(3, 2, NO_FRAGMENT)).await;
+
+    // Chunks are sent in the order they were queued.
+    for exp in [0, 1, 2, 3] {
+        let c = pq.peek().expect("should not be none");
+        assert_eq!(c.tsn, exp, "TSN for peek should match");
+
+        let c = pq.pop(c.beginning_fragment, c.unordered);
+        assert_eq!(c.map(|c| c.tsn), Some(exp), "TSN for pop should match");
+    }
+    assert!(pq.is_empty(), "should be empty");
+
+    Ok(())
+}
+
 ///////////////////////////////////////////////////////////////////
 //reassembly_queue_test
 ///////////////////////////////////////////////////////////////////
@@ -928,6 +1119,122 @@ fn test_reassembly_queue_forward_tsn_for_unordered_fragments() -> Result<()> {
     Ok(())
 }
 
+fn make_i_data_chunk(
+    tsn: u32,
+    mid: u32,
+    fsn: u32,
+    unordered: bool,
+    frag: usize,
+    user_data: &'static [u8],
+) -> ChunkPayloadData {
+    ChunkPayloadData {
+        interleaved: true,
+        message_identifier: mid,
+        fragment_sequence_number: fsn,
+        payload_type: if fsn == 0 {
+            PayloadProtocolIdentifier::Binary
+        } else {
+            PayloadProtocolIdentifier::Unknown
+        },
+        user_data: Bytes::from_static(user_data),
+        ..make_data_chunk(tsn, unordered, frag)
+    }
+}
+
+#[test]
+fn test_reassembly_queue_interleaved_ordered_fragments() -> Result<()> {
+    let mut rq = ReassemblyQueue::new(0);
+
+    // Fragments of MID 0 and 1 interleaved with each other, so neither has
+    // contiguous TSNs.
+    let chunks = [
+        make_i_data_chunk(10, 0, 0, false, FRAG_BEGIN, b"AB"),
+        make_i_data_chunk(11, 1, 0, false, FRAG_BEGIN, b"XY"),
+        make_i_data_chunk(12, 1, 1, false, FRAG_END, b"Z"),
+        make_i_data_chunk(13, 0, 1, false, FRAG_MIDDLE, b"CD"),
+    ];
+    for c in chunks {
+        rq.push(c);
+    }
+    assert!(!rq.is_readable(), "MID 1 must wait for MID 0");
+
+    assert!(
+        rq.push(make_i_data_chunk(14, 0, 2, false, FRAG_END, b"E")),
+        "chunk set should be complete"
+    );
+    assert_eq!(rq.get_num_bytes(), 8, "num bytes mismatch");
+
+    let mut buf = vec![0u8; 16];
+    let (n, ppi) = rq.read(&mut buf)?;
+    assert_eq!(&buf[..n], b"ABCDE", "data should match");
+    assert_eq!(
+        ppi,
+        PayloadProtocolIdentifier::Binary,
+        "should have valid ppi"
+    );
+
+    let (n, ppi) = rq.read(&mut buf)?;
+    assert_eq!(&buf[..n], b"XYZ", "data should match");
+    assert_eq!(
+        ppi,
+        PayloadProtocolIdentifier::Binary,
+        "should have valid ppi"
+    );
+    assert_eq!(rq.next_mid, 2, "next MID mismatch");
+    assert_eq!(rq.get_num_bytes(), 0, "num bytes mismatch");
+
+    // A stale MID is ignored
+    assert!(!rq.push(make_i_data_chunk(15, 1, 0, false, NO_FRAGMENT, b"Q")));
+    assert_eq!(rq.get_num_bytes(), 0, "num bytes mismatch");
+
+    Ok(())
+}
+
+#[test]
+fn test_reassembly_queue_interleaved_unordered_fragments() -> Result<()> {
+    let mut rq = ReassemblyQueue::new(0);
+
+    assert!(!rq.push(make_i_data_chunk(1, 0, 0, true, FRAG_BEGIN, b"AB")));
+    assert!(!rq.push(make_i_data_chunk(2, 1, 0, true, FRAG_BEGIN, b"XY")));
+    assert!(
+        rq.push(make_i_data_chunk(4, 1, 1, true, FRAG_END, b"Z")),
+        "MID 1 should complete before MID 0"
+    );
+    assert_eq!(rq.unordered_messages.len(), 1, "MID 0 should be pending");
+
+    let mut buf = vec![0u8; 16];
+    let (n, _) = rq.read(&mut buf)?;
+    assert_eq!(&buf[..n], b"XYZ", "data should match");
+
+    // Abandoning MID 0 drops its fragments
+    rq.forward_tsn_for_unordered_mid(0);
+    assert!(rq.unordered_messages.is_empty(), "MID 0 should be removed");
+    assert_eq!(rq.get_num_bytes(), 0, "num bytes mismatch");
+
+    Ok(())
+}
+
+#[test]
+fn test_reassembly_queue_forward_tsn_for_ordered_mid() -> Result<()> {
+    let mut rq = ReassemblyQueue::new(0);
+
+    rq.push(make_i_data_chunk(1, 0, 0, false, FRAG_BEGIN, b"AB"));
+    rq.push(make_i_data_chunk(3, 1, 0, false, NO_FRAGMENT, b"XY"));
+    assert!(!rq.is_readable(), "MID 1 must wait for MID 0");
+
+    rq.forward_tsn_for_ordered_mid(0);
+    assert_eq!(rq.next_mid, 1, "next MID mismatch");
+    assert_eq!(rq.ordered.len(), 1, "MID 0 should be removed");
+    assert!(rq.is_readable(), "MID 1 should be readable");
+
+    let mut buf = vec![0u8; 16];
+    let (n, _) = rq.read(&mut buf)?;
+    assert_eq!(&buf[..n], b"XY", "data should match");
+    assert_eq!(rq.get_num_bytes(), 0, "num bytes mismatch");
+
+    Ok(())
+}
+
 #[test]
 fn test_chunk_set_empty_chunk_set() -> Result<()> {
     let cset = ChunkSet::new(0, PayloadProtocolIdentifier::default());
@@ -957,6 +1264,7 @@ fn test_chunk_set_push_dup_chunks_to_chunk_set() -> Result<()> {
 fn test_chunk_set_incomplete_chunk_set_no_beginning() -> Result<()> {
     let cset = ChunkSet {
         ssn: 0,
+        mid: 0,
         ppi: PayloadProtocolIdentifier::default(),
         chunks: vec![],
     };
@@ -971,6 +1279,7 @@ fn test_chunk_set_incomplete_chunk_set_no_beginning() -> Result<()> {
 fn test_chunk_set_incomplete_chunk_set_no_contiguous_tsn() -> Result<()> {
     let cset = ChunkSet {
         ssn: 0,
+        mid: 0,
         ppi: PayloadProtocolIdentifier::default(),
         chunks: vec![
             ChunkPayloadData {
@@ -24,11 +24,23 @@ fn sort_chunks_by_ssn(c: &mut [ChunkSet]) {
     });
 }
 
-/// chunkSet is a set of chunks that share the same SSN
+fn sort_chunks_by_mid(c: &mut [ChunkSet]) {
+    c.sort_by(|a, b| {
+        if sna32lt(a.mid, b.mid) {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        }
+    });
+}
+
+/// chunkSet is a set of chunks that share the same SSN, or the same MID for I-DATA
 #[derive(Debug, Clone)]
 pub(crate) struct ChunkSet {
     /// used only with the ordered chunks
     pub(crate) ssn: u16,
+    /// used only with I-DATA chunks
+    pub(crate) mid: u32,
     pub(crate) ppi: PayloadProtocolIdentifier,
     pub(crate) chunks: Vec<ChunkPayloadData>,
 }
@@ -37,6 +49,7 @@ impl ChunkSet {
     pub(crate) fn new(ssn: u16, ppi: PayloadProtocolIdentifier) -> Self {
         ChunkSet {
             ssn,
+            mid: 0,
             ppi,
             chunks: vec![],
         }
@@ -50,6 +63,11 @@ impl ChunkSet {
             }
         }
 
+        // Only the first fragment of an I-DATA message carries the PPI
+        if chunk.beginning_fragment {
+            self.ppi = chunk.payload_type;
+        }
+
         // append and sort
         self.chunks.push(chunk);
         sort_chunks_by_tsn(&mut self.chunks);
@@ -63,7 +81,8 @@ impl ChunkSet {
         //   0. Has at least one chunk.
         //   1. Begins with beginningFragment set to true
         //   2. Ends with endingFragment set to true
-        //   3. TSN monotinically increase by 1 from beginning to end
+        //   3. TSN monotinically increase by 1 from beginning to end, or for
+        //      I-DATA, FSN does so starting from 0
 
         // 0.
         let n_chunks = self.chunks.len();
@@ -82,6 +101,16 @@ impl ChunkSet {
         }
 
         // 3.
+        if self.chunks[0].interleaved {
+            // Fragments of different messages may be interleaved, so only the
+            // FSN tells whether one is missing (RFC 8260 section 2.1).
+            return self
+                .chunks
+                .iter()
+                .enumerate()
+                .all(|(i, c)| c.fragment_sequence_number == i as u32);
+        }
+
         let mut last_tsn = 0u32;
         for (i, c) in self.chunks.iter().enumerate() {
             if i > 0 {
@@ -112,6 +141,10 @@ pub(crate) struct ReassemblyQueue {
     pub(crate) ordered: Vec<ChunkSet>,
     pub(crate) unordered: Vec<ChunkSet>,
     pub(crate) unordered_chunks: Vec<ChunkPayloadData>,
+    /// expected MID for next ordered I-DATA chunk
+    pub(crate) next_mid: u32,
+    /// incomplete unordered I-DATA messages
+    pub(crate) unordered_messages: Vec<ChunkSet>,
     pub(crate) n_bytes: usize,
 }
 
@@ -128,6 +161,8 @@ impl ReassemblyQueue {
             ordered: vec![],
             unordered: vec![],
             unordered_chunks: vec![],
+            next_mid: 0,
+            unordered_messages: vec![],
             n_bytes: 0,
         }
     }
@@ -137,6 +172,10 @@ impl ReassemblyQueue {
             return false;
         }
 
+        if chunk.interleaved {
+            return self.push_interleaved(chunk);
+        }
+
         if chunk.unordered {
             // First, insert into unordered_chunks array
             //atomic.AddUint64(&r.n_bytes, uint64(len(chunk.userData)))
@@ -180,6 +219,63 @@ impl ReassemblyQueue {
         }
     }
 
+    /// push_interleaved groups I-DATA chunks by MID, since fragments of
+    /// different messages may arrive interleaved.
+    fn push_interleaved(&mut self, chunk: ChunkPayloadData) -> bool {
+        let mid = chunk.message_identifier;
+
+        if chunk.unordered {
+            self.n_bytes += chunk.user_data.len();
+
+            let idx = match self.unordered_messages.iter().position(|s| s.mid == mid) {
+                Some(idx) => idx,
+                None => {
+                    let mut cset = ChunkSet::new(0, chunk.payload_type);
+                    cset.mid = mid;
+                    self.unordered_messages.push(cset);
+                    self.unordered_messages.len() - 1
+                }
+            };
+
+            if self.unordered_messages[idx].push(chunk) {
+                let cset = self.unordered_messages.remove(idx);
+                self.unordered.push(cset);
+                return true;
+            }
+
+            false
+        } else {
+            if sna32lt(mid, self.next_mid) {
+                return false;
+            }
+
+            self.n_bytes += chunk.user_data.len();
+
+            for s in &mut self.ordered {
+                if s.mid == mid {
+                    return s.push(chunk);
+                }
+            }
+
+            let mut cset = ChunkSet::new(0, chunk.payload_type);
+            cset.mid = mid;
+            let ok = cset.push(chunk);
+            self.ordered.push(cset);
+            sort_chunks_by_mid(&mut self.ordered);
+
+            ok
+        }
+    }
+
+    /// is_due returns whether the ordered set is next in line for delivery.
+    fn is_due(&self, cset: &ChunkSet) -> bool {
+        if cset.chunks.first().is_some_and(|c| c.interleaved) {
+            sna32lte(cset.mid, self.next_mid)
+        } else {
+            sna16lte(cset.ssn, self.next_ssn)
+        }
+    }
+
     pub(crate) fn find_complete_unordered_chunk_set(&mut self) -> Option<ChunkSet> {
         let mut start_idx = -1isize;
         let mut n_chunks = 0usize;
@@ -245,13 +341,30 @@ impl ReassemblyQueue {
         // Check ordered sets
         if !self.ordered.is_empty() {
             let cset = &self.ordered[0];
-            if cset.is_complete() && sna16lte(cset.ssn, self.next_ssn) {
+            if cset.is_complete() && self.is_due(cset) {
                 return true;
             }
         }
         false
     }
 
+    /// next_message_len returns the size of the message read returns next, if
+    /// one is readable.
+    pub(crate) fn next_message_len(&self) -> Option<usize> {
+        let cset = match self.unordered.first() {
+            Some(cset) => cset,
+            None => {
+                let cset = self.ordered.first()?;
+                if !cset.is_complete() || !self.is_due(cset) {
+                    return None;
+                }
+                cset
+            }
+        };
+
+        Some(cset.chunks.iter().map(|c| c.user_data.len()).sum())
+    }
+
     pub(crate) fn read(&mut self, buf: &mut [u8]) -> Result<(usize, PayloadProtocolIdentifier)> {
         // Check unordered first
         let cset = if !self.unordered.is_empty() {
@@ -262,10 +375,14 @@ impl ReassemblyQueue {
             if !cset.is_complete() {
                 return Err(Error::ErrTryAgain);
             }
-            if sna16gt(cset.ssn, self.next_ssn) {
+            if !self.is_due(cset) {
                 return Err(Error::ErrTryAgain);
             }
-            if cset.ssn == self.next_ssn {
+            if cset.chunks[0].interleaved {
+                if cset.mid == self.next_mid {
+                    self.next_mid = self.next_mid.wrapping_add(1);
+                }
+            } else if cset.ssn == self.next_ssn {
                 // From RFC 4960 Sec 6.5:
                 self.next_ssn = self.next_ssn.wrapping_add(1);
             }
@@ -339,6 +456,36 @@ impl ReassemblyQueue {
         }
     }
 
+    /// Same as forward_tsn_for_ordered, with ordered I-DATA messages
+    /// identified by `last_mid`.
+    pub(crate) fn forward_tsn_for_ordered_mid(&mut self, last_mid: u32) {
+        let num_bytes = self
+            .ordered
+            .iter()
+            .filter(|s| sna32lte(s.mid, last_mid) && !s.is_complete())
+            .fold(0, |n, s| {
+                n + s.chunks.iter().fold(0, |acc, c| acc + c.user_data.len())
+            });
+        self.subtract_num_bytes(num_bytes);
+
+        self.ordered
+            .retain(|s| !sna32lte(s.mid, last_mid) || s.is_complete());
+
+        if sna32lte(self.next_mid, last_mid) {
+            self.next_mid = last_mid.wrapping_add(1);
+        }
+    }
+
+    /// Remove the fragments of the unordered I-DATA message `mid`, if the
+    /// message has not been completed yet.
+    pub(crate) fn forward_tsn_for_unordered_mid(&mut self, mid: u32) {
+        if let Some(idx) = self.unordered_messages.iter().position(|s| s.mid == mid) {
+            let cset = self.unordered_messages.remove(idx);
+            let num_bytes = cset.chunks.iter().fold(0, |acc, c| acc + c.user_data.len());
+            self.subtract_num_bytes(num_bytes);
+        }
+    }
+
     pub(crate) fn subtract_num_bytes(&mut self, n_bytes: usize) {
         if self.n_bytes >= n_bytes {
             self.n_bytes -= n_bytes;
@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod stream_test;
 
+pub(crate) mod send_buffer;
+
 use std::future::Future;
 use std::net::Shutdown;
 use std::pin::Pin;
@@ -11,15 +13,17 @@ use std::{fmt, io};
 
 use arc_swap::ArcSwapOption;
 use bytes::Bytes;
-use portable_atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicU8, AtomicUsize};
+use portable_atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicU64, AtomicU8, AtomicUsize};
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
-use tokio::sync::{mpsc, Mutex, Notify};
+use tokio::sync::{Mutex, Notify};
+use util::sync::Mutex as SyncMutex;
 
-use crate::association::AssociationState;
+use crate::association::{AssociationState, AwakeWriteLoopFn};
 use crate::chunk::chunk_payload_data::{ChunkPayloadData, PayloadProtocolIdentifier};
 use crate::error::{Error, Result};
 use crate::queue::pending_queue::PendingQueue;
 use crate::queue::reassembly_queue::ReassemblyQueue;
+use send_buffer::SendBuffer;
 
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(C)]
@@ -54,9 +58,87 @@ impl From<u8> for ReliabilityType {
     }
 }
 
+/// WriteOptions overrides how a single message is sent, see [`Stream::write_sctp_with_options`].
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// unordered delivers the message unordered (with the U bit set), even if the stream is
+    /// ordered.
+    pub unordered: bool,
+    /// reliability retransmits the message as set by the reliability type and value, instead
+    /// of by the reliability parameters of the stream.
+    pub reliability: Option<(ReliabilityType, u32)>,
+}
+
+/// StreamScheduler selects how queued user messages of different streams
+/// share the association (RFC 8260 section 3).
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(C)]
+pub enum StreamScheduler {
+    /// Messages are sent in the order they were queued, unordered ones first.
+    Fifo = 0,
+    /// Streams take turns. Messages are interleaved chunk by chunk when I-DATA
+    /// is in use; otherwise streams switch between messages.
+    #[default]
+    RoundRobin = 1,
+    /// Streams take turns in proportion to their priority, so bulk transfers
+    /// on low priority streams can't starve high priority ones.
+    WeightedFair = 2,
+}
+
+impl fmt::Display for StreamScheduler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match *self {
+            StreamScheduler::Fifo => "Fifo",
+            StreamScheduler::RoundRobin => "RoundRobin",
+            StreamScheduler::WeightedFair => "WeightedFair",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl From<u8> for StreamScheduler {
+    fn from(v: u8) -> StreamScheduler {
+        match v {
+            0 => StreamScheduler::Fifo,
+            2 => StreamScheduler::WeightedFair,
+            _ => StreamScheduler::RoundRobin,
+        }
+    }
+}
+
+/// Priority of a stream unless set otherwise, matching the "normal" WebRTC
+/// data channel priority.
+pub const DEFAULT_STREAM_PRIORITY: u16 = 256;
+
+/// Number of bytes buffered for sending above which [`PollStream`] stops
+/// accepting writes, unless set otherwise.
+pub const DEFAULT_SEND_BUFFER_HIGH_WATERMARK: usize = 1024 * 1024;
+
+/// Number of bytes buffered for sending down to which the send buffer has to
+/// drain before [`PollStream`] accepts writes again, unless set otherwise.
+pub const DEFAULT_SEND_BUFFER_LOW_WATERMARK: usize = 512 * 1024;
+
 pub type OnBufferedAmountLowFn =
     Box<dyn (FnMut() -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>) + Send + Sync>;
 
+/// PartialMessage is a message being written in parts with
+/// Stream::write_sctp_partial.
+#[derive(Debug)]
+struct PartialMessage {
+    unordered: bool,
+    payload_type: PayloadProtocolIdentifier,
+    stream_sequence_number: u16,
+    message_identifier: u32,
+    reliability: Option<(ReliabilityType, u32)>,
+    fragment_sequence_number: u32,
+    len: usize,
+    abandoned: Arc<AtomicBool>,
+    all_inflight: Arc<AtomicBool>,
+    /// The last chunk of the message so far, held back until it is known
+    /// whether it is the ending fragment.
+    tail: Option<ChunkPayloadData>,
+}
+
 // TODO: benchmark performance between multiple Atomic+Mutex vs one Mutex<StreamInternal>
 
 /// Stream represents an SCTP stream
@@ -65,21 +147,36 @@ pub struct Stream {
     pub(crate) max_payload_size: u32,
     pub(crate) max_message_size: Arc<AtomicU32>, // clone from association
     pub(crate) state: Arc<AtomicU8>,             // clone from association
-    pub(crate) awake_write_loop_ch: Option<Arc<mpsc::Sender<()>>>,
+    pub(crate) awake_write_loop: Option<AwakeWriteLoopFn>,
     pub(crate) pending_queue: Arc<PendingQueue>,
+    pub(crate) send_buffer: Arc<SendBuffer>, // clone from association
 
     pub(crate) stream_identifier: u16,
     pub(crate) default_payload_type: AtomicU32, //PayloadProtocolIdentifier,
-    pub(crate) reassembly_queue: Mutex<ReassemblyQueue>,
+    pub(crate) reassembly_queue: SyncMutex<ReassemblyQueue>,
     pub(crate) sequence_number: AtomicU16,
+    /// next message identifiers for I-DATA, numbered separately for ordered
+    /// and unordered messages (RFC 8260 section 2.1)
+    pub(crate) message_identifier: AtomicU32,
+    pub(crate) unordered_message_identifier: AtomicU32,
     pub(crate) read_notifier: Notify,
     pub(crate) read_shutdown: AtomicBool,
     pub(crate) write_shutdown: AtomicBool,
     pub(crate) unordered: AtomicBool,
     pub(crate) reliability_type: AtomicU8, //ReliabilityType,
     pub(crate) reliability_value: AtomicU32,
+    pub(crate) priority: AtomicU16,
     pub(crate) buffered_amount: AtomicUsize,
     pub(crate) buffered_amount_low: AtomicUsize,
+    pub(crate) send_buffer_high: AtomicUsize,
+    pub(crate) send_buffer_low: AtomicUsize,
+    /// Set once buffered_amount reaches the high watermark, until it drains to the low one.
+    send_buffer_full: AtomicBool,
+    /// Notified when the send buffer drains to the low watermark or the
+    /// stream stops being writable.
+    pub(crate) writable_notifier: Notify,
+    pub(crate) retransmitted_chunks: AtomicU64,
+    partial_message: Mutex<Option<PartialMessage>>,
     pub(crate) on_buffered_amount_low: ArcSwapOption<Mutex<OnBufferedAmountLowFn>>,
     pub(crate) name: String,
 }
@@ -90,52 +187,74 @@ impl fmt::Debug for Stream {
             .field("max_payload_size", &self.max_payload_size)
             .field("max_message_size", &self.max_message_size)
             .field("state", &self.state)
-            .field("awake_write_loop_ch", &self.awake_write_loop_ch)
+            .field("awake_write_loop", &self.awake_write_loop.is_some())
             .field("stream_identifier", &self.stream_identifier)
             .field("default_payload_type", &self.default_payload_type)
             .field("reassembly_queue", &self.reassembly_queue)
             .field("sequence_number", &self.sequence_number)
+            .field("message_identifier", &self.message_identifier)
+            .field(
+                "unordered_message_identifier",
+                &self.unordered_message_identifier,
+            )
             .field("read_shutdown", &self.read_shutdown)
             .field("write_shutdown", &self.write_shutdown)
             .field("unordered", &self.unordered)
             .field("reliability_type", &self.reliability_type)
             .field("reliability_value", &self.reliability_value)
+            .field("priority", &self.priority)
             .field("buffered_amount", &self.buffered_amount)
             .field("buffered_amount_low", &self.buffered_amount_low)
+            .field("send_buffer_high", &self.send_buffer_high)
+            .field("send_buffer_low", &self.send_buffer_low)
+            .field("retransmitted_chunks", &self.retransmitted_chunks)
+            .field("partial_message", &self.partial_message)
             .field("name", &self.name)
             .finish()
     }
 }
 
 impl Stream {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         name: String,
         stream_identifier: u16,
         max_payload_size: u32,
         max_message_size: Arc<AtomicU32>,
         state: Arc<AtomicU8>,
-        awake_write_loop_ch: Option<Arc<mpsc::Sender<()>>>,
+        awake_write_loop: Option<AwakeWriteLoopFn>,
         pending_queue: Arc<PendingQueue>,
+        send_buffer: Arc<SendBuffer>,
     ) -> Self {
         Stream {
             max_payload_size,
             max_message_size,
             state,
-            awake_write_loop_ch,
+            awake_write_loop,
             pending_queue,
+            send_buffer,
 
             stream_identifier,
             default_payload_type: AtomicU32::new(0), //PayloadProtocolIdentifier::Unknown,
-            reassembly_queue: Mutex::new(ReassemblyQueue::new(stream_identifier)),
+            reassembly_queue: SyncMutex::new(ReassemblyQueue::new(stream_identifier)),
             sequence_number: AtomicU16::new(0),
+            message_identifier: AtomicU32::new(0),
+            unordered_message_identifier: AtomicU32::new(0),
             read_notifier: Notify::new(),
             read_shutdown: AtomicBool::new(false),
             write_shutdown: AtomicBool::new(false),
             unordered: AtomicBool::new(false),
             reliability_type: AtomicU8::new(0), //ReliabilityType::Reliable,
             reliability_value: AtomicU32::new(0),
+            priority: AtomicU16::new(DEFAULT_STREAM_PRIORITY),
             buffered_amount: AtomicUsize::new(0),
             buffered_amount_low: AtomicUsize::new(0),
+            send_buffer_high: AtomicUsize::new(DEFAULT_SEND_BUFFER_HIGH_WATERMARK),
+            send_buffer_low: AtomicUsize::new(DEFAULT_SEND_BUFFER_LOW_WATERMARK),
+            send_buffer_full: AtomicBool::new(false),
+            writable_notifier: Notify::new(),
+            retransmitted_chunks: AtomicU64::new(0),
+            partial_message: Mutex::new(None),
             on_buffered_amount_low: ArcSwapOption::empty(),
             name,
         }
@@ -167,6 +286,20 @@ impl Stream {
         self.reliability_value.store(rel_val, Ordering::SeqCst);
     }
 
+    /// priority returns the priority of this stream. See set_priority().
+    pub fn priority(&self) -> u16 {
+        self.priority.load(Ordering::SeqCst)
+    }
+
+    /// set_priority sets the priority of this stream, which decides its share of
+    /// the association under StreamScheduler::WeightedFair. Defaults to
+    /// DEFAULT_STREAM_PRIORITY.
+    pub fn set_priority(&self, priority: u16) {
+        self.priority.store(priority, Ordering::SeqCst);
+        self.pending_queue
+            .set_priority(self.stream_identifier, priority);
+    }
+
     /// Reads a packet of len(p) bytes, dropping the Payload Protocol Identifier.
     ///
     /// Returns `Error::ErrShortBuffer` if `p` is too short.
@@ -187,7 +320,7 @@ impl Stream {
             }
 
             let result = {
-                let mut reassembly_queue = self.reassembly_queue.lock().await;
+                let mut reassembly_queue = self.reassembly_queue.lock();
                 reassembly_queue.read(p)
             };
 
@@ -201,9 +334,37 @@ impl Stream {
         }
     }
 
-    pub(crate) async fn handle_data(&self, pd: ChunkPayloadData) {
+    /// read_message reads the next message into a buffer of its size, but at least min_len
+    /// bytes long. Returns an empty buffer if the reading half of this stream is shutdown or it
+    /// (the stream) was reset.
+    pub(crate) async fn read_message(&self, min_len: usize) -> Result<Vec<u8>> {
+        loop {
+            if self.read_shutdown.load(Ordering::SeqCst) {
+                return Ok(vec![]);
+            }
+
+            let result = {
+                let mut reassembly_queue = self.reassembly_queue.lock();
+                reassembly_queue.next_message_len().map(|len| {
+                    let mut buf = vec![0; std::cmp::max(len, min_len)];
+                    reassembly_queue.read(&mut buf).map(|(n, _)| {
+                        buf.truncate(n);
+                        buf
+                    })
+                })
+            };
+
+            match result {
+                Some(result) => return result,
+                // wait for the next chunk to become available
+                None => self.read_notifier.notified().await,
+            }
+        }
+    }
+
+    pub(crate) fn handle_data(&self, pd: ChunkPayloadData) {
         let readable = {
-            let mut reassembly_queue = self.reassembly_queue.lock().await;
+            let mut reassembly_queue = self.reassembly_queue.lock();
             if reassembly_queue.push(pd) {
                 let readable = reassembly_queue.is_readable();
                 log::debug!("[{}] reassemblyQueue readable={}", self.name, readable);
@@ -220,7 +381,7 @@ impl Stream {
         }
     }
 
-    pub(crate) async fn handle_forward_tsn_for_ordered(&self, ssn: u16) {
+    pub(crate) fn handle_forward_tsn_for_ordered(&self, ssn: u16) {
         if self.unordered.load(Ordering::SeqCst) {
             return; // unordered chunks are handled by handleForwardUnordered method
         }
@@ -228,7 +389,7 @@ impl Stream {
         // Remove all chunks older than or equal to the new TSN from
         // the reassembly_queue.
         let readable = {
-            let mut reassembly_queue = self.reassembly_queue.lock().await;
+            let mut reassembly_queue = self.reassembly_queue.lock();
             reassembly_queue.forward_tsn_for_ordered(ssn);
             reassembly_queue.is_readable()
         };
@@ -239,15 +400,14 @@ impl Stream {
         }
     }
 
-    pub(crate) async fn handle_forward_tsn_for_unordered(&self, new_cumulative_tsn: u32) {
-        if !self.unordered.load(Ordering::SeqCst) {
-            return; // ordered chunks are handled by handleForwardTSNOrdered method
-        }
+    pub(crate) fn handle_forward_tsn_for_unordered(&self, new_cumulative_tsn: u32) {
+        // Even an ordered stream may receive messages sent unordered one by one, so there is
+        // no early return here like in handle_forward_tsn_for_ordered.
 
         // Remove all chunks older than or equal to the new TSN from
         // the reassembly_queue.
         let readable = {
-            let mut reassembly_queue = self.reassembly_queue.lock().await;
+            let mut reassembly_queue = self.reassembly_queue.lock();
             reassembly_queue.forward_tsn_for_unordered(new_cumulative_tsn);
             reassembly_queue.is_readable()
         };
@@ -258,9 +418,29 @@ impl Stream {
         }
     }
 
+    /// handle_i_forward_tsn removes the abandoned message `mid` and, for ordered
+    /// messages, everything before it from the reassembly_queue.
+    pub(crate) fn handle_i_forward_tsn(&self, unordered: bool, mid: u32) {
+        let readable = {
+            let mut reassembly_queue = self.reassembly_queue.lock();
+            if unordered {
+                reassembly_queue.forward_tsn_for_unordered_mid(mid);
+            } else {
+                reassembly_queue.forward_tsn_for_ordered_mid(mid);
+            }
+            reassembly_queue.is_readable()
+        };
+
+        // Notify the reader asynchronously if there's a data chunk to read.
+        if readable {
+            self.read_notifier.notify_one();
+        }
+    }
+
     /// Writes `p` to the DTLS connection with the default Payload Protocol Identifier.
     ///
-    /// Returns an error if the write half of this stream is shutdown or `p` is too large.
+    /// Returns an error if the write half of this stream is shutdown or `p` is too large, and
+    /// `Error::ErrSendBufferFull` if `p` doesn't fit into the send buffer of the association.
     pub async fn write(&self, p: &Bytes) -> Result<usize> {
         self.write_sctp(p, self.default_payload_type.load(Ordering::SeqCst).into())
             .await
@@ -268,10 +448,96 @@ impl Stream {
 
     /// Writes `p` to the DTLS connection with the given Payload Protocol Identifier.
     ///
-    /// Returns an error if the write half of this stream is shutdown or `p` is too large.
+    /// Returns an error if the write half of this stream is shutdown or `p` is too large, and
+    /// `Error::ErrSendBufferFull` if `p` doesn't fit into the send buffer of the association.
     pub async fn write_sctp(&self, p: &Bytes, ppi: PayloadProtocolIdentifier) -> Result<usize> {
-        let chunks = self.prepare_write(p, ppi)?;
-        self.send_payload_data(chunks).await?;
+        let chunks = self.prepare_write(p, ppi, WriteOptions::default())?;
+        self.send_payload_data(chunks, p.len()).await?;
+
+        Ok(p.len())
+    }
+
+    /// Writes `p` to the DTLS connection with the given Payload Protocol Identifier, using
+    /// `rel_type` and `rel_val` for this message instead of the reliability parameters of the
+    /// stream. This lets a stream mix reliable messages with messages that may be abandoned,
+    /// which the peer is told to skip with a FORWARD TSN chunk.
+    ///
+    /// Returns an error if the write half of this stream is shutdown or `p` is too large, and
+    /// `Error::ErrSendBufferFull` if `p` doesn't fit into the send buffer of the association.
+    pub async fn write_sctp_with_reliability(
+        &self,
+        p: &Bytes,
+        ppi: PayloadProtocolIdentifier,
+        rel_type: ReliabilityType,
+        rel_val: u32,
+    ) -> Result<usize> {
+        let options = WriteOptions {
+            reliability: Some((rel_type, rel_val)),
+            ..Default::default()
+        };
+        self.write_sctp_with_options(p, ppi, options).await
+    }
+
+    /// Writes `p` to the DTLS connection with the given Payload Protocol Identifier, sending
+    /// this message as set by `options` instead of by the parameters of the stream. This lets
+    /// an ordered stream mix in unordered messages, which neither wait for nor hold back the
+    /// ordered ones.
+    ///
+    /// Returns an error if the write half of this stream is shutdown or `p` is too large, and
+    /// `Error::ErrSendBufferFull` if `p` doesn't fit into the send buffer of the association.
+    pub async fn write_sctp_with_options(
+        &self,
+        p: &Bytes,
+        ppi: PayloadProtocolIdentifier,
+        options: WriteOptions,
+    ) -> Result<usize> {
+        let chunks = self.prepare_write(p, ppi, options)?;
+        self.send_payload_data(chunks, p.len()).await?;
+
+        Ok(p.len())
+    }
+
+    /// Writes `p` as the next part of a message with the given Payload Protocol Identifier. The
+    /// message ends with the part written with `end_of_record` set. Each part is fragmented and
+    /// queued as it is written, so a large message never has to be held in memory as a whole.
+    ///
+    /// The Payload Protocol Identifier of the first part applies to the whole message. Until the
+    /// message is complete, other writes to this stream fail with
+    /// `Error::ErrPartialMessageInProgress` and, unless I-DATA is negotiated, other streams of
+    /// the association can't send.
+    ///
+    /// Returns an error if the write half of this stream is shutdown or the message grows
+    /// larger than the maximum message size, and `Error::ErrSendBufferFull` if `p` doesn't fit
+    /// into the send buffer of the association.
+    pub async fn write_sctp_partial(
+        &self,
+        p: &Bytes,
+        ppi: PayloadProtocolIdentifier,
+        end_of_record: bool,
+    ) -> Result<usize> {
+        let mut partial_message = self.partial_message.lock().await;
+        self.check_writable()?;
+
+        let len = partial_message.as_ref().map_or(0, |m| m.len);
+        if len + p.len() > self.max_message_size.load(Ordering::SeqCst) as usize {
+            return Err(Error::ErrOutboundPacketTooLarge);
+        }
+
+        if !self.reserve_send_buffer(p.len()) {
+            return Err(Error::ErrSendBufferFull);
+        }
+
+        let mut m = match partial_message.take() {
+            Some(m) => m,
+            // Like write_sctp, an empty message is not sent at all.
+            None if p.is_empty() => return Ok(0),
+            None => self.start_message(ppi, WriteOptions::default()),
+        };
+        let chunks = self.fragment(&mut m, p, end_of_record);
+        if !end_of_record {
+            *partial_message = Some(m);
+        }
+        self.send_payload_data(chunks, p.len()).await?;
 
         Ok(p.len())
     }
@@ -281,41 +547,123 @@ impl Stream {
         &self,
         p: &Bytes,
         ppi: PayloadProtocolIdentifier,
+        options: WriteOptions,
     ) -> Result<Vec<ChunkPayloadData>> {
-        if self.write_shutdown.load(Ordering::SeqCst) {
-            return Err(Error::ErrStreamClosed);
-        }
+        self.check_writable()?;
 
         if p.len() > self.max_message_size.load(Ordering::SeqCst) as usize {
             return Err(Error::ErrOutboundPacketTooLarge);
         }
 
+        if !matches!(self.partial_message.try_lock(), Ok(m) if m.is_none()) {
+            return Err(Error::ErrPartialMessageInProgress);
+        }
+
+        if !self.reserve_send_buffer(p.len()) {
+            return Err(Error::ErrSendBufferFull);
+        }
+
+        Ok(self.packetize(p, ppi, options))
+    }
+
+    /// reserve_send_buffer accounts len more bytes of buffered outgoing data, if the send buffer
+    /// of the association has room for them.
+    fn reserve_send_buffer(&self, len: usize) -> bool {
+        if !self.send_buffer.reserve(&self.buffered_amount, len) {
+            log::debug!(
+                "[{}] send buffer full: bufferedAmount = {}, association total = {}",
+                self.name,
+                self.buffered_amount.load(Ordering::SeqCst),
+                self.send_buffer.used()
+            );
+            return false;
+        }
+
+        let new_amount = self.buffered_amount.load(Ordering::SeqCst);
+        let high = self.send_buffer_high.load(Ordering::SeqCst);
+        if high != 0 && new_amount >= high {
+            self.send_buffer_full.store(true, Ordering::SeqCst);
+        }
+        log::trace!("[{}] bufferedAmount = {}", self.name, new_amount);
+        true
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        if self.write_shutdown.load(Ordering::SeqCst) {
+            return Err(Error::ErrStreamClosed);
+        }
+
         let state: AssociationState = self.state.load(Ordering::SeqCst).into();
         match state {
             AssociationState::ShutdownSent
             | AssociationState::ShutdownAckSent
             | AssociationState::ShutdownPending
-            | AssociationState::ShutdownReceived => return Err(Error::ErrStreamClosed),
-            _ => {}
-        };
-
-        Ok(self.packetize(p, ppi))
+            | AssociationState::ShutdownReceived => Err(Error::ErrStreamClosed),
+            _ => Ok(()),
+        }
     }
 
-    fn packetize(&self, raw: &Bytes, ppi: PayloadProtocolIdentifier) -> Vec<ChunkPayloadData> {
-        let mut i = 0;
-        let mut remaining = raw.len();
+    fn packetize(
+        &self,
+        raw: &Bytes,
+        ppi: PayloadProtocolIdentifier,
+        options: WriteOptions,
+    ) -> Vec<ChunkPayloadData> {
+        let mut m = self.start_message(ppi, options);
+        self.fragment(&mut m, raw, true)
+    }
 
+    /// start_message assigns the sequence number and message identifier of a new message.
+    fn start_message(
+        &self,
+        ppi: PayloadProtocolIdentifier,
+        options: WriteOptions,
+    ) -> PartialMessage {
         // From draft-ietf-rtcweb-data-protocol-09, section 6:
         //   All Data Channel Establishment Protocol messages MUST be sent using
         //   ordered delivery and reliable transmission.
-        let unordered =
-            ppi != PayloadProtocolIdentifier::Dcep && self.unordered.load(Ordering::SeqCst);
+        let unordered = ppi != PayloadProtocolIdentifier::Dcep
+            && (options.unordered || self.unordered.load(Ordering::SeqCst));
 
-        let mut chunks = vec![];
+        // I-DATA identifies the message by its MID rather than the SSN.
+        let message_identifier = if unordered {
+            self.unordered_message_identifier
+                .fetch_add(1, Ordering::SeqCst)
+        } else {
+            self.message_identifier.fetch_add(1, Ordering::SeqCst)
+        };
 
-        let head_abandoned = Arc::new(AtomicBool::new(false));
-        let head_all_inflight = Arc::new(AtomicBool::new(false));
+        // RFC 4960 Sec 6.6
+        // Note: When transmitting ordered and unordered data, an endpoint does
+        // not increment its Stream Sequence Number when transmitting a DATA
+        // chunk with U flag set to 1.
+        let stream_sequence_number = if unordered {
+            self.sequence_number.load(Ordering::SeqCst)
+        } else {
+            self.sequence_number.fetch_add(1, Ordering::SeqCst)
+        };
+
+        PartialMessage {
+            unordered,
+            payload_type: ppi,
+            stream_sequence_number,
+            message_identifier,
+            reliability: options.reliability,
+            fragment_sequence_number: 0,
+            len: 0,
+            abandoned: Arc::new(AtomicBool::new(false)),
+            all_inflight: Arc::new(AtomicBool::new(false)),
+            tail: None,
+        }
+    }
+
+    /// fragment splits the next part of message `m` into chunks. Unless the part ends the
+    /// message, its last chunk is held back in `m`.
+    fn fragment(&self, m: &mut PartialMessage, raw: &Bytes, end: bool) -> Vec<ChunkPayloadData> {
+        let mut i = 0;
+        let mut remaining = raw.len();
+
+        let mut chunks: Vec<ChunkPayloadData> = m.tail.take().into_iter().collect();
         while remaining != 0 {
             let fragment_size = std::cmp::min(self.max_payload_size as usize, remaining); //self.association.max_payload_size
 
@@ -326,33 +674,35 @@ impl Stream {
             let chunk = ChunkPayloadData {
                 stream_identifier: self.stream_identifier,
                 user_data,
-                unordered,
-                beginning_fragment: i == 0,
-                ending_fragment: remaining - fragment_size == 0,
+                unordered: m.unordered,
+                beginning_fragment: m.fragment_sequence_number == 0,
+                ending_fragment: false,
                 immediate_sack: false,
-                payload_type: ppi,
-                stream_sequence_number: self.sequence_number.load(Ordering::SeqCst),
-                abandoned: head_abandoned.clone(), // all fragmented chunks use the same abandoned
-                all_inflight: head_all_inflight.clone(), // all fragmented chunks use the same all_inflight
+                payload_type: m.payload_type,
+                stream_sequence_number: m.stream_sequence_number,
+                message_identifier: m.message_identifier,
+                fragment_sequence_number: m.fragment_sequence_number,
+                reliability: m.reliability,
+                abandoned: m.abandoned.clone(), // all fragmented chunks use the same abandoned
+                all_inflight: m.all_inflight.clone(), // all fragmented chunks use the same all_inflight
                 ..Default::default()
             };
 
             chunks.push(chunk);
 
+            m.fragment_sequence_number += 1;
             remaining -= fragment_size;
             i += fragment_size;
         }
 
-        // RFC 4960 Sec 6.6
-        // Note: When transmitting ordered and unordered data, an endpoint does
-        // not increment its Stream Sequence Number when transmitting a DATA
-        // chunk with U flag set to 1.
-        if !unordered {
-            self.sequence_number.fetch_add(1, Ordering::SeqCst);
+        if end {
+            if let Some(c) = chunks.last_mut() {
+                c.ending_fragment = true;
+            }
+        } else {
+            m.tail = chunks.pop();
         }
-
-        let old_value = self.buffered_amount.fetch_add(raw.len(), Ordering::SeqCst);
-        log::trace!("[{}] bufferedAmount = {}", self.name, old_value + raw.len());
+        m.len += raw.len();
 
         chunks
     }
@@ -378,6 +728,14 @@ impl Stream {
 
         if how == Shutdown::Write || how == Shutdown::Both {
             self.write_shutdown.store(true, Ordering::SeqCst);
+            self.writable_notifier.notify_waiters();
+
+            // The rest of an unfinished partial message will never be sent.
+            if let Ok(mut partial_message) = self.partial_message.try_lock() {
+                if let Some(tail) = partial_message.take().and_then(|m| m.tail) {
+                    self.release_send_buffer(tail.user_data.len());
+                }
+            }
         }
 
         if (how == Shutdown::Read || how == Shutdown::Both)
@@ -415,6 +773,92 @@ impl Stream {
         self.buffered_amount_low.store(th, Ordering::SeqCst);
     }
 
+    /// send_buffer_high_watermark returns the number of bytes of buffered outgoing data at which
+    /// the send buffer is considered full. Zero means it never is.
+    pub fn send_buffer_high_watermark(&self) -> usize {
+        self.send_buffer_high.load(Ordering::SeqCst)
+    }
+
+    /// send_buffer_low_watermark returns the number of bytes of buffered outgoing data a full
+    /// send buffer has to drain to before it takes more.
+    pub fn send_buffer_low_watermark(&self) -> usize {
+        self.send_buffer_low.load(Ordering::SeqCst)
+    }
+
+    /// set_send_buffer_watermarks sets the high and low watermarks of the send buffer, see
+    /// [`Stream::writable`]. A low watermark above the high one is lowered to it. Defaults to
+    /// [`DEFAULT_SEND_BUFFER_HIGH_WATERMARK`] and [`DEFAULT_SEND_BUFFER_LOW_WATERMARK`].
+    pub fn set_send_buffer_watermarks(&self, high: usize, low: usize) {
+        self.send_buffer_high.store(high, Ordering::SeqCst);
+        self.send_buffer_low
+            .store(std::cmp::min(low, high), Ordering::SeqCst);
+        let buffered_amount = self.buffered_amount.load(Ordering::SeqCst);
+        self.send_buffer_full
+            .store(high != 0 && buffered_amount >= high, Ordering::SeqCst);
+        // A waiting writer may have room now.
+        self.writable_notifier.notify_waiters();
+    }
+
+    /// send_buffer_room returns how many more bytes fit into the send buffer, before it reaches
+    /// the high watermark or the share of this stream in the send buffer of the association.
+    pub(crate) fn send_buffer_room(&self) -> usize {
+        if self.send_buffer_full.load(Ordering::SeqCst) {
+            return 0;
+        }
+
+        let buffered_amount = self.buffered_amount.load(Ordering::SeqCst);
+        let high = self.send_buffer_high.load(Ordering::SeqCst);
+        let room = if high == 0 {
+            usize::MAX
+        } else {
+            high.saturating_sub(buffered_amount)
+        };
+        std::cmp::min(room, self.send_buffer.room(buffered_amount))
+    }
+
+    /// Waits until the send buffer has room for more data: right away while less than the high
+    /// watermark is buffered, or else once acknowledgements from the peer have drained it to the
+    /// low watermark. It also waits while the stream has used up its share of the send buffer of
+    /// the association. Writes themselves never wait, so this is how a writer keeps its buffered
+    /// data bounded.
+    ///
+    /// Returns an error if the write half of this stream is shutdown.
+    pub async fn writable(&self) -> Result<()> {
+        loop {
+            let notified = self.writable_notifier.notified();
+            let released = self.send_buffer.notifier.notified();
+            tokio::pin!(notified, released);
+            // Register before checking, so a wakeup in between is not missed.
+            notified.as_mut().enable();
+            released.as_mut().enable();
+
+            self.check_writable()?;
+            if self.send_buffer_room() > 0 {
+                return Ok(());
+            }
+
+            tokio::select! {
+                _ = notified => {}
+                _ = released => {}
+            }
+        }
+    }
+
+    /// is_writable returns true if [`Stream::writable`] would return right away, so a writer
+    /// may check for room in the send buffer without waiting.
+    ///
+    /// Returns an error if the write half of this stream is shutdown.
+    pub fn is_writable(&self) -> Result<bool> {
+        self.check_writable()?;
+        Ok(self.send_buffer_room() > 0)
+    }
+
+    /// retransmitted_chunks returns the number of DATA chunks of this stream that had to be
+    /// sent again.
+    pub fn retransmitted_chunks(&self) -> u64 {
+        self.retransmitted_chunks.load(Ordering::SeqCst)
+    }
+
     /// on_buffered_amount_low sets the callback handler which would be called when the number of
     /// bytes of outgoing data buffered is lower than the threshold.
     pub fn on_buffered_amount_low(&self, f: OnBufferedAmountLowFn) {
@@ -422,29 +866,23 @@ impl Stream {
             .store(Some(Arc::new(Mutex::new(f))));
     }
 
-    /// This method is called by association's read_loop (go-)routine to notify this stream
-    /// of the specified amount of outgoing data has been delivered to the peer.
-    pub(crate) async fn on_buffer_released(&self, n_bytes_released: i64) {
+    /// This method is called by the association to notify this stream of the specified amount of
+    /// outgoing data has been delivered to the peer. Returns true if the buffered amount dropped
+    /// to the low threshold, after which notify_buffered_amount_low should be called.
+    pub(crate) fn on_buffer_released(&self, n_bytes_released: i64) -> bool {
         if n_bytes_released <= 0 {
-            return;
+            return false;
         }
 
-        let from_amount = self.buffered_amount.load(Ordering::SeqCst);
-        let new_amount = if from_amount < n_bytes_released as usize {
-            self.buffered_amount.store(0, Ordering::SeqCst);
+        let (from_amount, new_amount) = self.release_send_buffer(n_bytes_released as usize);
+        if from_amount < n_bytes_released as usize {
             log::error!(
                 "[{}] released buffer size {} should be <= {}",
                 self.name,
                 n_bytes_released,
                 0,
             );
-            0
-        } else {
-            self.buffered_amount
-                .fetch_sub(n_bytes_released as usize, Ordering::SeqCst);
-
-            from_amount - n_bytes_released as usize
-        };
+        }
 
         let buffered_amount_low = self.buffered_amount_low.load(Ordering::SeqCst);
 
@@ -456,19 +894,36 @@ impl Stream {
             buffered_amount_low,
         );
 
-        if from_amount > buffered_amount_low && new_amount <= buffered_amount_low {
-            if let Some(handler) = &*self.on_buffered_amount_low.load() {
-                let mut f = handler.lock().await;
-                f().await;
-            }
+        from_amount > buffered_amount_low && new_amount <= buffered_amount_low
+    }
+
+    /// notify_buffered_amount_low calls the on_buffered_amount_low handler, if one is set.
+    pub(crate) async fn notify_buffered_amount_low(&self) {
+        if let Some(handler) = self.on_buffered_amount_low.load_full() {
+            let mut f = handler.lock().await;
+            f().await;
         }
     }
 
+    /// release_send_buffer accounts n fewer bytes of buffered outgoing data, and returns the
+    /// amount before and after.
+    pub(crate) fn release_send_buffer(&self, n: usize) -> (usize, usize) {
+        let (from_amount, new_amount) = self.send_buffer.release(&self.buffered_amount, n);
+
+        let send_buffer_low = self.send_buffer_low.load(Ordering::SeqCst);
+        if from_amount > send_buffer_low && new_amount <= send_buffer_low {
+            self.send_buffer_full.store(false, Ordering::SeqCst);
+            self.writable_notifier.notify_waiters();
+        }
+
+        (from_amount, new_amount)
+    }
+
     /// get_num_bytes_in_reassembly_queue returns the number of bytes of data currently queued to
     /// be read (once chunk is complete).
-    pub(crate) async fn get_num_bytes_in_reassembly_queue(&self) -> usize {
+    pub(crate) fn get_num_bytes_in_reassembly_queue(&self) -> usize {
         // No lock is required as it reads the size with atomic load function.
-        let reassembly_queue = self.reassembly_queue.lock().await;
+        let reassembly_queue = self.reassembly_queue.lock();
         reassembly_queue.get_num_bytes()
     }
 
@@ -478,15 +933,17 @@ impl Stream {
     }
 
     fn awake_write_loop(&self) {
-        //log::debug!("[{}] awake_write_loop_ch.notify_one", self.name);
-        if let Some(awake_write_loop_ch) = &self.awake_write_loop_ch {
-            let _ = awake_write_loop_ch.try_send(());
+        if let Some(awake_write_loop) = &self.awake_write_loop {
+            awake_write_loop();
         }
     }
 
-    async fn send_payload_data(&self, chunks: Vec<ChunkPayloadData>) -> Result<()> {
+    /// send_payload_data queues the chunks of len bytes of user data, which are released from
+    /// the send buffer again if the association can't send them.
+    async fn send_payload_data(&self, chunks: Vec<ChunkPayloadData>, len: usize) -> Result<()> {
         let state = self.get_state();
         if state != AssociationState::Established {
+            self.release_send_buffer(len);
             return Err(Error::ErrPayloadDataStateNotExist);
         }
 
@@ -578,11 +1035,17 @@ impl ShutdownFut {
 ///
 /// Both `poll_read` and `poll_write` calls allocate temporary buffers, which results in an
 /// additional overhead.
+///
+/// `poll_write` returns `Poll::Pending` while the send buffer of the stream is full, and the
+/// task is woken once the peer has acknowledged enough data to drain it to the low watermark
+/// (see [`Stream::set_send_buffer_watermarks`]). A write is cut short to what fits below the
+/// high watermark.
 pub struct PollStream {
     stream: Arc<Stream>,
 
     read_fut: ReadFut,
     write_fut: Option<Pin<Box<dyn Future<Output = Result<usize>>>>>,
+    writable_fut: Option<Pin<Box<dyn Future<Output = Result<()>> + Send>>>,
     shutdown_fut: ShutdownFut,
 
     read_buf_cap: usize,
@@ -605,6 +1068,7 @@ impl PollStream {
             stream,
             read_fut: ReadFut::Idle,
             write_fut: None,
+            writable_fut: None,
             shutdown_fut: ShutdownFut::Idle,
             read_buf_cap: DEFAULT_READ_BUF_SIZE,
         }
@@ -640,13 +1104,20 @@ impl PollStream {
 
     /// get_num_bytes_in_reassembly_queue returns the number of bytes of data currently queued to
     /// be read (once chunk is complete).
-    pub(crate) async fn get_num_bytes_in_reassembly_queue(&self) -> usize {
+    pub(crate) fn get_num_bytes_in_reassembly_queue(&self) -> usize {
         // No lock is required as it reads the size with atomic load function.
-        let reassembly_queue = self.stream.reassembly_queue.lock().await;
+        let reassembly_queue = self.stream.reassembly_queue.lock();
         reassembly_queue.get_num_bytes()
     }
 
-    /// Set the capacity of the temporary read buffer (default: 8192).
+    /// set_send_buffer_watermarks sets the high and low watermarks of the send buffer.
+    /// See [`Stream::set_send_buffer_watermarks`].
+    pub fn set_send_buffer_watermarks(&self, high: usize, low: usize) {
+        self.stream.set_send_buffer_watermarks(high, low)
+    }
+
+    /// Set the capacity of the temporary read buffer (default: 8192). Larger messages are read
+    /// into a buffer of their size.
     pub fn set_read_buf_capacity(&mut self, capacity: usize) {
         self.read_buf_cap = capacity
     }
@@ -667,13 +1138,11 @@ impl AsyncRead for PollStream {
                 // read into a temporary buffer because `buf` has an unonymous lifetime, which can
                 // be shorter than the lifetime of `read_fut`.
                 let stream = self.stream.clone();
-                let mut temp_buf = vec![0; self.read_buf_cap];
-                self.read_fut = ReadFut::Reading(Box::pin(async move {
-                    stream.read(temp_buf.as_mut_slice()).await.map(|n| {
-                        temp_buf.truncate(n);
-                        temp_buf
-                    })
-                }));
+                let read_buf_cap = self.read_buf_cap;
+                self.read_fut =
+                    ReadFut::Reading(Box::pin(
+                        async move { stream.read_message(read_buf_cap).await },
+                    ));
                 self.read_fut.get_reading_mut()
             }
             ReadFut::Reading(ref mut fut) => fut,
@@ -733,52 +1202,56 @@ impl AsyncWrite for PollStream {
             return Poll::Ready(Ok(0));
         }
 
+        // The previous write has been reported as done, its data only has to be queued yet.
         if let Some(fut) = self.write_fut.as_mut() {
             match fut.as_mut().poll(cx) {
-                Poll::Pending => Poll::Pending,
-                Poll::Ready(Err(e)) => {
-                    let stream = self.stream.clone();
-                    let bytes = Bytes::copy_from_slice(buf);
-                    self.write_fut = Some(Box::pin(async move { stream.write(&bytes).await }));
-                    Poll::Ready(Err(e.into()))
-                }
-                // Given the data is buffered, it's okay to ignore the number of written bytes.
-                //
-                // TODO: In the long term, `stream.write` should be made sync. Then we could
-                // remove the whole `if` condition and just call `stream.write`.
-                Poll::Ready(Ok(_)) => {
-                    let stream = self.stream.clone();
-                    let bytes = Bytes::copy_from_slice(buf);
-                    self.write_fut = Some(Box::pin(async move { stream.write(&bytes).await }));
-                    Poll::Ready(Ok(buf.len()))
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(result) => {
+                    self.write_fut = None;
+                    if let Err(e) = result {
+                        return Poll::Ready(Err(e.into()));
+                    }
                 }
             }
-        } else {
-            let stream = self.stream.clone();
-            let bytes = Bytes::copy_from_slice(buf);
-            let fut = self
-                .write_fut
-                .insert(Box::pin(async move { stream.write(&bytes).await }));
+        }
 
+        if self.writable_fut.is_none() && self.stream.send_buffer_room() == 0 {
+            let stream = self.stream.clone();
+            self.writable_fut = Some(Box::pin(async move { stream.writable().await }));
+        }
+        if let Some(fut) = self.writable_fut.as_mut() {
             match fut.as_mut().poll(cx) {
-                // If it's the first time we're polling the future, `Poll::Pending` can't be
-                // returned because that would mean the `PollStream` is not ready for writing. And
-                // this is not true since we've just created a future, which is going to write the
-                // buf to the underlying stream.
-                //
-                // It's okay to return `Poll::Ready` if the data is buffered (this is what the
-                // buffered writer and `File` do).
-                Poll::Pending => Poll::Ready(Ok(buf.len())),
-                Poll::Ready(Err(e)) => {
-                    self.write_fut = None;
-                    Poll::Ready(Err(e.into()))
-                }
-                Poll::Ready(Ok(n)) => {
-                    self.write_fut = None;
-                    Poll::Ready(Ok(n))
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(result) => {
+                    self.writable_fut = None;
+                    if let Err(e) = result {
+                        return Poll::Ready(Err(e.into()));
+                    }
                 }
             }
         }
+
+        let max_message_size = self.stream.max_message_size.load(Ordering::SeqCst) as usize;
+        let n = buf
+            .len()
+            .min(self.stream.send_buffer_room())
+            .min(max_message_size);
+        let stream = self.stream.clone();
+        let bytes = Bytes::copy_from_slice(&buf[..n]);
+        let fut = self
+            .write_fut
+            .insert(Box::pin(async move { stream.write(&bytes).await }));
+
+        match fut.as_mut().poll(cx) {
+            // It's okay to return `Poll::Ready` if the data is buffered (this is what the
+            // buffered writer and `File` do). The future is driven to completion by the next
+            // write or flush.
+            Poll::Pending => Poll::Ready(Ok(n)),
+            Poll::Ready(result) => {
+                self.write_fut = None;
+                Poll::Ready(result.map_err(Into::into))
+            }
+        }
     }
 
     fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
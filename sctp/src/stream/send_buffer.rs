@@ -0,0 +1,107 @@
+use portable_atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use tokio::sync::Notify;
+use util::sync::Mutex as SyncMutex;
+
+/// SendBuffer accounts the data buffered for sending by all streams of an association, queued
+/// or not acknowledged yet. It holds at most capacity bytes, zero meaning no limit. Once
+/// several streams have data buffered, each one gets an equal share of the capacity, so a
+/// single stream can't take all of it.
+#[derive(Debug, Default)]
+pub(crate) struct SendBuffer {
+    capacity: usize,
+    state: SyncMutex<SendBufferState>,
+    /// Notified whenever data is released, if there is a capacity.
+    pub(crate) notifier: Notify,
+}
+
+#[derive(Debug, Default)]
+struct SendBufferState {
+    used: usize,
+    /// Number of streams with data buffered.
+    active_streams: usize,
+}
+
+impl SendBuffer {
+    pub(crate) fn new(capacity: usize) -> Self {
+        SendBuffer {
+            capacity,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// used returns the number of bytes buffered by all streams.
+    pub(crate) fn used(&self) -> usize {
+        self.state.lock().used
+    }
+
+    /// room returns how many more bytes a stream with buffered bytes in the buffer may add.
+    pub(crate) fn room(&self, buffered: usize) -> usize {
+        if self.capacity == 0 {
+            return usize::MAX;
+        }
+
+        let state = self.state.lock();
+        Self::room_locked(self.capacity, &state, buffered)
+    }
+
+    fn room_locked(capacity: usize, state: &SendBufferState, buffered: usize) -> usize {
+        let streams = state.active_streams + usize::from(buffered == 0);
+        let share = capacity / streams.max(1);
+        std::cmp::min(
+            capacity.saturating_sub(state.used),
+            share.saturating_sub(buffered),
+        )
+    }
+
+    /// reserve adds len bytes to buffered_amount of a stream if they fit. A message always fits
+    /// into an empty buffer, and a stream with nothing buffered may take more than its share
+    /// for as long as the capacity allows, so no message size is ever stuck.
+    pub(crate) fn reserve(&self, buffered_amount: &AtomicUsize, len: usize) -> bool {
+        let mut state = self.state.lock();
+        let buffered = buffered_amount.load(Ordering::SeqCst);
+
+        let fits = self.capacity == 0
+            || state.used == 0
+            || len <= Self::room_locked(self.capacity, &state, buffered)
+            || (buffered == 0 && state.used + len <= self.capacity);
+        if !fits {
+            return false;
+        }
+
+        if buffered == 0 && len != 0 {
+            state.active_streams += 1;
+        }
+        state.used += len;
+        buffered_amount.fetch_add(len, Ordering::SeqCst);
+        true
+    }
+
+    /// release removes up to n bytes from buffered_amount of a stream, and returns its amount
+    /// before and after.
+    pub(crate) fn release(&self, buffered_amount: &AtomicUsize, n: usize) -> (usize, usize) {
+        let (from_amount, new_amount) = {
+            let mut state = self.state.lock();
+            let from_amount = buffered_amount.load(Ordering::SeqCst);
+            let n = std::cmp::min(n, from_amount);
+            buffered_amount.fetch_sub(n, Ordering::SeqCst);
+
+            state.used = state.used.saturating_sub(n);
+            let new_amount = from_amount - n;
+            if from_amount != 0 && new_amount == 0 {
+                state.active_streams = state.active_streams.saturating_sub(1);
+            }
+            (from_amount, new_amount)
+        };
+
+        // Without a capacity, writers only ever wait for the watermarks of their stream.
+        if self.capacity != 0 && new_amount < from_amount {
+            self.notifier.notify_waiters();
+        }
+        (from_amount, new_amount)
+    }
+}
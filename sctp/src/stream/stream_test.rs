@@ -41,32 +41,34 @@ async fn test_stream_amount_on_buffered_amount_low() -> Result<()> {
     }));
 
     // Negative value should be ignored (by design)
-    s.on_buffer_released(-32).await; // bufferedAmount = 3072
+    assert!(!s.on_buffer_released(-32)); // bufferedAmount = 3072
     assert_eq!(s.buffered_amount(), 4096, "unexpected bufferedAmount");
     assert_eq!(n_cbs.load(Ordering::SeqCst), 0, "callback count mismatch");
 
     // Above to above, no callback
-    s.on_buffer_released(1024).await; // bufferedAmount = 3072
+    assert!(!s.on_buffer_released(1024)); // bufferedAmount = 3072
     assert_eq!(s.buffered_amount(), 3072, "unexpected bufferedAmount");
     assert_eq!(n_cbs.load(Ordering::SeqCst), 0, "callback count mismatch");
 
     // Above to equal, callback should be made
-    s.on_buffer_released(1024).await; // bufferedAmount = 2048
+    assert!(s.on_buffer_released(1024)); // bufferedAmount = 2048
     assert_eq!(s.buffered_amount(), 2048, "unexpected bufferedAmount");
+    assert_eq!(n_cbs.load(Ordering::SeqCst), 0, "callback count mismatch");
+    s.notify_buffered_amount_low().await;
     assert_eq!(n_cbs.load(Ordering::SeqCst), 1, "callback count mismatch");
 
     // Eaual to below, no callback
-    s.on_buffer_released(1024).await; // bufferedAmount = 1024
+    assert!(!s.on_buffer_released(1024)); // bufferedAmount = 1024
     assert_eq!(s.buffered_amount(), 1024, "unexpected bufferedAmount");
     assert_eq!(n_cbs.load(Ordering::SeqCst), 1, "callback count mismatch");
 
     // Blow to below, no callback
-    s.on_buffer_released(1024).await; // bufferedAmount = 0
+    assert!(!s.on_buffer_released(1024)); // bufferedAmount = 0
     assert_eq!(s.buffered_amount(), 0, "unexpected bufferedAmount");
     assert_eq!(n_cbs.load(Ordering::SeqCst), 1, "callback count mismatch");
 
     // Capped at 0, no callback
-    s.on_buffer_released(1024).await; // bufferedAmount = 0
+    assert!(!s.on_buffer_released(1024)); // bufferedAmount = 0
     assert_eq!(s.buffered_amount(), 0, "unexpected bufferedAmount");
     assert_eq!(n_cbs.load(Ordering::SeqCst), 1, "callback count mismatch");
 
@@ -83,13 +85,14 @@ async fn test_stream() -> std::result::Result<(), io::Error> {
         Arc::new(AtomicU8::new(AssociationState::Established as u8)),
         None,
         Arc::new(PendingQueue::new()),
+        Arc::new(SendBuffer::default()),
     );
 
     // getters
     assert_eq!(s.stream_identifier(), 0);
     assert_eq!(s.buffered_amount(), 0);
     assert_eq!(s.buffered_amount_low_threshold(), 0);
-    assert_eq!(s.get_num_bytes_in_reassembly_queue().await, 0);
+    assert_eq!(s.get_num_bytes_in_reassembly_queue(), 0);
 
     // setters
     s.set_default_payload_type(PayloadProtocolIdentifier::Binary);
@@ -114,8 +117,7 @@ async fn test_stream() -> std::result::Result<(), io::Error> {
         user_data: Bytes::from_static(&[0, 1, 2, 3, 4]),
         payload_type: PayloadProtocolIdentifier::Binary,
         ..Default::default()
-    })
-    .await;
+    });
     //  2. read it
     let mut buf = [0; 5];
     s.read(&mut buf).await?;
@@ -133,8 +135,7 @@ async fn test_stream() -> std::result::Result<(), io::Error> {
         user_data: Bytes::from_static(&[5, 6, 7, 8, 9]),
         payload_type: PayloadProtocolIdentifier::Binary,
         ..Default::default()
-    })
-    .await;
+    });
     let mut buf = [0; 5];
     s.read(&mut buf).await?;
     assert_eq!(buf, [5, 6, 7, 8, 9]);
@@ -157,6 +158,7 @@ async fn test_poll_stream() -> std::result::Result<(), io::Error> {
         Arc::new(AtomicU8::new(AssociationState::Established as u8)),
         None,
         Arc::new(PendingQueue::new()),
+        Arc::new(SendBuffer::default()),
     ));
     let mut poll_stream = PollStream::new(s.clone());
 
@@ -164,7 +166,7 @@ async fn test_poll_stream() -> std::result::Result<(), io::Error> {
     assert_eq!(poll_stream.stream_identifier(), 0);
     assert_eq!(poll_stream.buffered_amount(), 0);
     assert_eq!(poll_stream.buffered_amount_low_threshold(), 0);
-    assert_eq!(poll_stream.get_num_bytes_in_reassembly_queue().await, 0);
+    assert_eq!(poll_stream.get_num_bytes_in_reassembly_queue(), 0);
 
     // async write
     let n = poll_stream.write(&[1, 2, 3]).await?;
@@ -182,8 +184,7 @@ async fn test_poll_stream() -> std::result::Result<(), io::Error> {
         user_data: Bytes::from_static(&[0, 1, 2, 3, 4]),
         payload_type: PayloadProtocolIdentifier::Binary,
         ..Default::default()
-    })
-    .await;
+    });
     //  2. read it
     let mut buf = [0; 5];
     poll_stream.read_exact(&mut buf).await?;
@@ -201,8 +202,7 @@ async fn test_poll_stream() -> std::result::Result<(), io::Error> {
         user_data: Bytes::from_static(&[5, 6, 7, 8, 9]),
         payload_type: PayloadProtocolIdentifier::Binary,
         ..Default::default()
-    })
-    .await;
+    });
     let mut buf = [0; 5];
     poll_stream.read_exact(&mut buf).await?;
     assert_eq!(buf, [5, 6, 7, 8, 9]);
@@ -213,3 +213,190 @@ async fn test_poll_stream() -> std::result::Result<(), io::Error> {
 
     Ok(())
 }
+
+fn create_established_stream() -> Arc<Stream> {
+    create_established_stream_with(Arc::new(SendBuffer::default()))
+}
+
+fn create_established_stream_with(send_buffer: Arc<SendBuffer>) -> Arc<Stream> {
+    Arc::new(Stream::new(
+        "test_stream_backpressure".to_owned(),
+        0,
+        4096,
+        Arc::new(AtomicU32::new(4096)),
+        Arc::new(AtomicU8::new(AssociationState::Established as u8)),
+        None,
+        Arc::new(PendingQueue::new()),
+        send_buffer,
+    ))
+}
+
+#[tokio::test]
+async fn test_stream_writable() -> Result<()> {
+    let s = create_established_stream();
+    assert_eq!(
+        s.send_buffer_high_watermark(),
+        DEFAULT_SEND_BUFFER_HIGH_WATERMARK
+    );
+    assert_eq!(
+        s.send_buffer_low_watermark(),
+        DEFAULT_SEND_BUFFER_LOW_WATERMARK
+    );
+
+    s.set_send_buffer_watermarks(8, 16);
+    assert_eq!(s.send_buffer_low_watermark(), 8, "low should be capped");
+    s.set_send_buffer_watermarks(8, 4);
+    assert_eq!(s.is_writable(), Ok(true));
+    s.write(&Bytes::from_static(&[0; 10])).await?;
+    assert_eq!(s.is_writable(), Ok(false));
+
+    let mut writable = tokio_test::task::spawn(s.writable());
+    assert!(writable.poll().is_pending(), "send buffer should be full");
+
+    // Draining below the high watermark is not enough.
+    s.on_buffer_released(4);
+    assert!(!writable.is_woken());
+    assert!(writable.poll().is_pending());
+
+    s.on_buffer_released(2);
+    assert!(writable.is_woken(), "should wake at the low watermark");
+    assert!(matches!(writable.poll(), Poll::Ready(Ok(()))));
+    assert_eq!(s.is_writable(), Ok(true));
+    drop(writable);
+
+    // Shutting down wakes a waiting writer with an error.
+    s.write(&Bytes::from_static(&[0; 4])).await?;
+    let mut writable = tokio_test::task::spawn(s.writable());
+    assert!(writable.poll().is_pending());
+    s.shutdown(Shutdown::Write).await?;
+    assert_eq!(s.is_writable(), Err(Error::ErrStreamClosed));
+    assert!(writable.is_woken());
+    assert!(matches!(
+        writable.poll(),
+        Poll::Ready(Err(Error::ErrStreamClosed))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_poll_stream_backpressure() -> std::result::Result<(), io::Error> {
+    let s = create_established_stream();
+    let mut poll_stream = PollStream::new(s.clone());
+    poll_stream.set_send_buffer_watermarks(8, 4);
+
+    assert_eq!(poll_stream.write(&[0; 6]).await?, 6);
+    // Cut short to what fits below the high watermark.
+    assert_eq!(poll_stream.write(&[0; 6]).await?, 2);
+    assert_eq!(poll_stream.buffered_amount(), 8);
+
+    {
+        let mut write = tokio_test::task::spawn(poll_stream.write(&[0; 6]));
+        assert!(write.poll().is_pending(), "send buffer should be full");
+
+        s.on_buffer_released(4);
+        assert!(write.is_woken(), "should wake at the low watermark");
+        assert!(matches!(write.poll(), Poll::Ready(Ok(4))));
+    }
+    poll_stream.flush().await?;
+    assert_eq!(poll_stream.buffered_amount(), 8);
+
+    // A message larger than the read buffer is still read whole.
+    poll_stream.set_read_buf_capacity(4);
+    s.handle_data(ChunkPayloadData {
+        unordered: true,
+        beginning_fragment: true,
+        ending_fragment: true,
+        user_data: Bytes::from_static(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]),
+        payload_type: PayloadProtocolIdentifier::Binary,
+        ..Default::default()
+    });
+    let mut buf = [0; 10];
+    poll_stream.read_exact(&mut buf).await?;
+    assert_eq!(buf, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+    // Shutting down fails a write waiting for room.
+    let sc = s.clone();
+    let mut write = tokio_test::task::spawn(poll_stream.write(&[0; 6]));
+    assert!(write.poll().is_pending());
+    sc.shutdown(Shutdown::Write).await?;
+    assert!(write.is_woken());
+    assert!(matches!(write.poll(), Poll::Ready(Err(_))));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_stream_send_buffer_fairness() -> Result<()> {
+    let send_buffer = Arc::new(SendBuffer::new(100));
+    let s1 = create_established_stream_with(Arc::clone(&send_buffer));
+    let s2 = create_established_stream_with(Arc::clone(&send_buffer));
+
+    s1.write(&Bytes::from_static(&[0; 80])).await?;
+    // Beyond the budget of the association.
+    assert_eq!(
+        s2.write(&Bytes::from_static(&[0; 30])).await,
+        Err(Error::ErrSendBufferFull)
+    );
+    s2.write(&Bytes::from_static(&[0; 20])).await?;
+    assert_eq!(send_buffer.used(), 100);
+
+    // s1 is over its half now, so only s2 may add data once there is room.
+    s2.on_buffer_released(10);
+    assert_eq!(
+        s1.write(&Bytes::from_static(&[0; 1])).await,
+        Err(Error::ErrSendBufferFull)
+    );
+    assert_eq!(s1.send_buffer_room(), 0);
+    assert_eq!(s2.send_buffer_room(), 10);
+
+    let mut writable = tokio_test::task::spawn(s1.writable());
+    assert!(writable.poll().is_pending(), "s1 used up its share");
+    s1.on_buffer_released(40);
+    assert!(writable.is_woken());
+    assert!(matches!(writable.poll(), Poll::Ready(Ok(()))));
+    drop(writable);
+    assert_eq!(s1.send_buffer_room(), 10);
+    s1.write(&Bytes::from_static(&[0; 10])).await?;
+    assert_eq!(send_buffer.used(), 60);
+
+    // Once s2 has nothing buffered, s1 may use all of it again.
+    s2.on_buffer_released(10);
+    assert_eq!(s1.send_buffer_room(), 50);
+
+    // Shutting down releases the held back tail of a partial message.
+    s2.write_sctp_partial(
+        &Bytes::from_static(&[0; 10]),
+        PayloadProtocolIdentifier::Binary,
+        false,
+    )
+    .await?;
+    assert_eq!(send_buffer.used(), 60);
+    s2.shutdown(Shutdown::Write).await?;
+    assert_eq!(send_buffer.used(), 50);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_stream_send_buffer_empty_takes_any_message() -> Result<()> {
+    let send_buffer = Arc::new(SendBuffer::new(10));
+    let s = create_established_stream_with(Arc::clone(&send_buffer));
+
+    s.write(&Bytes::from_static(&[0; 20])).await?;
+    assert_eq!(send_buffer.used(), 20);
+    assert_eq!(
+        s.write(&Bytes::from_static(&[0; 1])).await,
+        Err(Error::ErrSendBufferFull)
+    );
+
+    // Data that can't be sent is released again.
+    s.on_buffer_released(20);
+    s.state
+        .store(AssociationState::Closed as u8, Ordering::SeqCst);
+    assert!(s.write(&Bytes::from_static(&[0; 5])).await.is_err());
+    assert_eq!(send_buffer.used(), 0);
+    assert_eq!(s.buffered_amount(), 0);
+
+    Ok(())
+}
@@ -1,74 +1,62 @@
-use std::sync::Weak;
-
-use async_trait::async_trait;
-use tokio::sync::{mpsc, Mutex};
-use tokio::time::Duration;
+use std::time::{Duration, Instant};
 
 pub(crate) const ACK_INTERVAL: Duration = Duration::from_millis(200);
-
-/// ackTimerObserver is the interface to an ack timer observer.
-#[async_trait]
-pub(crate) trait AckTimerObserver {
-    async fn on_ack_timeout(&mut self);
-}
+/// RFC 4960 sec 6.2: the delay MUST NOT be longer than 500 ms.
+pub(crate) const MAX_ACK_INTERVAL: Duration = Duration::from_millis(500);
+/// RFC 4960 sec 6.2: a SACK SHOULD be generated for at least every second packet.
+pub(crate) const DEFAULT_PACKETS_PER_SACK: u32 = 2;
 
 /// ackTimer provides the retnransmission timer conforms with RFC 4960 Sec 6.3.1
 #[derive(Default, Debug)]
-pub(crate) struct AckTimer<T: 'static + AckTimerObserver + Send> {
-    pub(crate) timeout_observer: Weak<Mutex<T>>,
+pub(crate) struct AckTimer {
     pub(crate) interval: Duration,
-    pub(crate) close_tx: Option<mpsc::Sender<()>>,
+    deadline: Option<Instant>,
 }
 
-impl<T: 'static + AckTimerObserver + Send> AckTimer<T> {
+impl AckTimer {
     /// newAckTimer creates a new acknowledgement timer used to enable delayed ack.
-    pub(crate) fn new(timeout_observer: Weak<Mutex<T>>, interval: Duration) -> Self {
+    pub(crate) fn new(interval: Duration) -> Self {
         AckTimer {
-            timeout_observer,
             interval,
-            close_tx: None,
+            deadline: None,
         }
     }
 
     /// start starts the timer.
-    pub(crate) fn start(&mut self) -> bool {
-        // this timer is already closed
-        if self.close_tx.is_some() {
+    pub(crate) fn start(&mut self, now: Instant) -> bool {
+        // this timer is already running
+        if self.deadline.is_some() {
             return false;
         }
 
-        let (close_tx, mut close_rx) = mpsc::channel(1);
-        let interval = self.interval;
-        let timeout_observer = self.timeout_observer.clone();
-
-        tokio::spawn(async move {
-            let timer = tokio::time::sleep(interval);
-            tokio::pin!(timer);
-
-            tokio::select! {
-                _ = timer.as_mut() => {
-                    if let Some(observer) = timeout_observer.upgrade(){
-                        let mut observer = observer.lock().await;
-                        observer.on_ack_timeout().await;
-                    }
-                 }
-                _ = close_rx.recv() => {},
-            }
-        });
-
-        self.close_tx = Some(close_tx);
+        self.deadline = Some(now + self.interval);
         true
     }
 
-    /// stops the timer. this is similar to stop() but subsequent start() call
-    /// will fail (the timer is no longer usable)
+    /// stops the timer.
     pub(crate) fn stop(&mut self) {
-        self.close_tx.take();
+        self.deadline = None;
     }
 
     /// isRunning tests if the timer is running.
     /// Debug purpose only
     pub(crate) fn is_running(&self) -> bool {
-        self.close_tx.is_some()
+        self.deadline.is_some()
+    }
+
+    /// deadline returns when the timer expires, if it is running.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// handle_timeout stops the timer and returns true if its deadline has passed.
+    pub(crate) fn handle_timeout(&mut self, now: Instant) -> bool {
+        match self.deadline {
+            Some(deadline) if deadline <= now => {
+                self.deadline = None;
+                true
+            }
+            _ => false,
+        }
     }
 }
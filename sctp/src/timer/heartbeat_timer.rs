@@ -0,0 +1,58 @@
+use std::time::{Duration, Instant};
+
+/// heartbeatTimer fires every interval until stopped, to probe the reachability of the peer
+/// with HEARTBEAT chunks (RFC 4960 sec 8.3).
+#[derive(Default, Debug)]
+pub(crate) struct HeartbeatTimer {
+    pub(crate) interval: Duration,
+    deadline: Option<Instant>,
+}
+
+impl HeartbeatTimer {
+    /// newHeartbeatTimer creates a new heartbeat timer.
+    pub(crate) fn new(interval: Duration) -> Self {
+        HeartbeatTimer {
+            interval,
+            deadline: None,
+        }
+    }
+
+    /// start starts the timer.
+    pub(crate) fn start(&mut self, now: Instant) -> bool {
+        // this timer is already running
+        if self.deadline.is_some() {
+            return false;
+        }
+
+        self.deadline = Some(now + self.interval);
+        true
+    }
+
+    /// stop stops the timer.
+    pub(crate) fn stop(&mut self) {
+        self.deadline = None;
+    }
+
+    /// isRunning tests if the timer is running.
+    /// Debug purpose only
+    pub(crate) fn is_running(&self) -> bool {
+        self.deadline.is_some()
+    }
+
+    /// deadline returns when the timer fires next, if it is running.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// handle_timeout schedules the next heartbeat and returns true if the
+    /// deadline has passed.
+    pub(crate) fn handle_timeout(&mut self, now: Instant) -> bool {
+        match self.deadline {
+            Some(deadline) if deadline <= now => {
+                self.deadline = Some(now + self.interval);
+                true
+            }
+            _ => false,
+        }
+    }
+}
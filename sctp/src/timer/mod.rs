@@ -2,4 +2,5 @@
 mod timer_test;
 
 pub(crate) mod ack_timer;
+pub(crate) mod heartbeat_timer;
 pub(crate) mod rtx_timer;
@@ -1,10 +1,4 @@
-use std::sync::{Arc, Weak};
-
-use async_trait::async_trait;
-use tokio::sync::{mpsc, Mutex};
-use tokio::time::Duration;
-
-use crate::association::RtxTimerId;
+use std::time::{Duration, Instant};
 
 pub(crate) const RTO_INITIAL: u64 = 3000; // msec
 pub(crate) const RTO_MIN: u64 = 1000; // msec
@@ -94,115 +88,87 @@ pub(crate) fn calculate_next_timeout(rto: u64, n_rtos: usize) -> u64 {
     }
 }
 
-/// rtxTimerObserver is the interface to a timer observer.
-/// NOTE: Observers MUST NOT call start() or stop() method on rtxTimer
-/// from within these callbacks.
-#[async_trait]
-pub(crate) trait RtxTimerObserver {
-    async fn on_retransmission_timeout(&mut self, timer_id: RtxTimerId, n: usize);
-    async fn on_retransmission_failure(&mut self, timer_id: RtxTimerId);
+/// RtxTimerEvent is what an expired retransmission timer asks for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum RtxTimerEvent {
+    /// The n-th retransmission is due.
+    Timeout(usize),
+    /// max_retrans retransmissions went unanswered, the timer has stopped.
+    Failure,
 }
 
 /// rtxTimer provides the retnransmission timer conforms with RFC 4960 Sec 6.3.1
+/// It does not wait by itself: it only keeps its deadline, and whoever drives
+/// the association calls handle_timeout() once the deadline has passed.
 #[derive(Default, Debug)]
-pub(crate) struct RtxTimer<T: 'static + RtxTimerObserver + Send> {
-    pub(crate) timeout_observer: Weak<Mutex<T>>,
-    pub(crate) id: RtxTimerId,
+pub(crate) struct RtxTimer {
     pub(crate) max_retrans: usize,
-    pub(crate) close_tx: Arc<Mutex<Option<mpsc::Sender<()>>>>,
+    rto: u64,
+    n_rtos: usize,
+    deadline: Option<Instant>,
 }
 
-impl<T: 'static + RtxTimerObserver + Send> RtxTimer<T> {
+impl RtxTimer {
     /// newRTXTimer creates a new retransmission timer.
     /// if max_retrans is set to 0, it will keep retransmitting until stop() is called.
-    /// (it will never make on_retransmission_failure() callback.
-    pub(crate) fn new(
-        timeout_observer: Weak<Mutex<T>>,
-        id: RtxTimerId,
-        max_retrans: usize,
-    ) -> Self {
+    /// (it will never report RtxTimerEvent::Failure).
+    pub(crate) fn new(max_retrans: usize) -> Self {
         RtxTimer {
-            timeout_observer,
-            id,
             max_retrans,
-            close_tx: Arc::new(Mutex::new(None)),
+            ..Default::default()
         }
     }
 
     /// start starts the timer.
-    pub(crate) async fn start(&self, rto: u64) -> bool {
+    pub(crate) fn start(&mut self, now: Instant, rto: u64) -> bool {
         // Note: rto value is intentionally not capped by RTO.Min to allow
         // fast timeout for the tests. Non-test code should pass in the
         // rto generated by rtoManager get_rto() method which caps the
         // value at RTO.Min or at RTO.Max.
 
-        // this timer is already closed
-        let mut close_rx = {
-            let mut close = self.close_tx.lock().await;
-            if close.is_some() {
-                return false;
-            }
-
-            let (close_tx, close_rx) = mpsc::channel(1);
-            *close = Some(close_tx);
-            close_rx
-        };
-
-        let id = self.id;
-        let max_retrans = self.max_retrans;
-        let close_tx = Arc::clone(&self.close_tx);
-        let timeout_observer = self.timeout_observer.clone();
-
-        tokio::spawn(async move {
-            let mut n_rtos = 0;
-
-            loop {
-                let interval = calculate_next_timeout(rto, n_rtos);
-                let timer = tokio::time::sleep(Duration::from_millis(interval));
-                tokio::pin!(timer);
-
-                tokio::select! {
-                    _ = timer.as_mut() => {
-                        n_rtos+=1;
-
-                        let failure = {
-                            if let Some(observer) = timeout_observer.upgrade(){
-                                let mut observer = observer.lock().await;
-                                if max_retrans == 0 || n_rtos <= max_retrans {
-                                    observer.on_retransmission_timeout(id, n_rtos).await;
-                                    false
-                                } else {
-                                    observer.on_retransmission_failure(id).await;
-                                    true
-                                }
-                            }else{
-                                true
-                            }
-                        };
-                        if failure {
-                            let mut close = close_tx.lock().await;
-                            *close = None;
-                            break;
-                        }
-                    }
-                    _ = close_rx.recv() => break,
-                }
-            }
-        });
+        // this timer is already running
+        if self.deadline.is_some() {
+            return false;
+        }
 
+        self.rto = rto;
+        self.n_rtos = 0;
+        self.deadline = Some(now + Duration::from_millis(calculate_next_timeout(rto, 0)));
         true
     }
 
     /// stop stops the timer.
-    pub(crate) async fn stop(&self) {
-        let mut close_tx = self.close_tx.lock().await;
-        close_tx.take();
+    pub(crate) fn stop(&mut self) {
+        self.deadline = None;
     }
 
     /// isRunning tests if the timer is running.
     /// Debug purpose only
-    pub(crate) async fn is_running(&self) -> bool {
-        let close_tx = self.close_tx.lock().await;
-        close_tx.is_some()
+    pub(crate) fn is_running(&self) -> bool {
+        self.deadline.is_some()
+    }
+
+    /// deadline returns when the timer expires next, if it is running.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// handle_timeout backs off the timer if its deadline has passed, and
+    /// returns what has to be done about it.
+    pub(crate) fn handle_timeout(&mut self, now: Instant) -> Option<RtxTimerEvent> {
+        match self.deadline {
+            Some(deadline) if deadline <= now => {}
+            _ => return None,
+        }
+
+        self.n_rtos += 1;
+        if self.max_retrans == 0 || self.n_rtos <= self.max_retrans {
+            let interval = calculate_next_timeout(self.rto, self.n_rtos);
+            self.deadline = Some(now + Duration::from_millis(interval));
+            Some(RtxTimerEvent::Timeout(self.n_rtos))
+        } else {
+            self.deadline = None;
+            Some(RtxTimerEvent::Failure)
+        }
     }
 }
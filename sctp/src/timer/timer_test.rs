@@ -1,15 +1,7 @@
 // Silence warning on `for i in 0..vec.len() { … }`:
 #![allow(clippy::needless_range_loop)]
-// Silence warning on `..Default::default()` with no effect:
-#![allow(clippy::needless_update)]
 
-use std::sync::atomic::Ordering;
-use std::sync::Arc;
-
-use async_trait::async_trait;
-use portable_atomic::AtomicU32;
-use tokio::sync::Mutex;
-use tokio::time::{sleep, Duration};
+use std::time::{Duration, Instant};
 
 ///////////////////////////////////////////////////////////////////
 //ack_timer_test
@@ -20,27 +12,13 @@ mod test_ack_timer {
     use super::*;
     use crate::error::Result;
 
-    struct TestAckTimerObserver {
-        ncbs: Arc<AtomicU32>,
-    }
-
-    #[async_trait]
-    impl AckTimerObserver for TestAckTimerObserver {
-        async fn on_ack_timeout(&mut self) {
-            log::trace!("ack timed out");
-            self.ncbs.fetch_add(1, Ordering::SeqCst);
-        }
-    }
-
-    #[tokio::test]
-    async fn test_ack_timer_start_and_stop() -> Result<()> {
-        let ncbs = Arc::new(AtomicU32::new(0));
-        let obs = Arc::new(Mutex::new(TestAckTimerObserver { ncbs: ncbs.clone() }));
-
-        let mut rt = AckTimer::new(Arc::downgrade(&obs), ACK_INTERVAL);
+    #[test]
+    fn test_ack_timer_start_and_stop() -> Result<()> {
+        let now = Instant::now();
+        let mut rt = AckTimer::new(ACK_INTERVAL);
 
         // should start ok
-        let ok = rt.start();
+        let ok = rt.start(now);
         assert!(ok, "start() should succeed");
         assert!(rt.is_running(), "should be running");
 
@@ -48,25 +26,73 @@ mod test_ack_timer {
         rt.stop();
         assert!(!rt.is_running(), "should not be running");
 
-        // Sleep more than 200msec of interval to test if it never times out
-        sleep(ACK_INTERVAL + Duration::from_millis(50)).await;
-
-        assert_eq!(
-            ncbs.load(Ordering::SeqCst),
-            0,
-            "should not be timed out (actual: {})",
-            ncbs.load(Ordering::SeqCst)
-        );
+        // More than 200msec of interval later it never times out
+        let later = now + ACK_INTERVAL + Duration::from_millis(50);
+        assert!(!rt.handle_timeout(later), "should not be timed out");
 
         // can start again
-        let ok = rt.start();
+        let ok = rt.start(later);
         assert!(ok, "start() should succeed again");
         assert!(rt.is_running(), "should be running");
+        assert!(!rt.start(later), "start() should fail while running");
+        assert_eq!(rt.deadline(), Some(later + ACK_INTERVAL));
+
+        assert!(
+            !rt.handle_timeout(later + ACK_INTERVAL - Duration::from_millis(1)),
+            "should not be timed out before the interval"
+        );
+        assert!(
+            rt.handle_timeout(later + ACK_INTERVAL),
+            "should be timed out once"
+        );
+        assert!(!rt.is_running(), "should not be running");
+        assert!(
+            !rt.handle_timeout(later + ACK_INTERVAL * 2),
+            "should not be timed out again"
+        );
+
+        Ok(())
+    }
+}
+
+///////////////////////////////////////////////////////////////////
+//heartbeat_timer_test
+///////////////////////////////////////////////////////////////////
+use super::heartbeat_timer::*;
+
+mod test_heartbeat_timer {
+    use super::*;
+    use crate::error::Result;
+
+    #[test]
+    fn test_heartbeat_timer_fires_until_stopped() -> Result<()> {
+        let interval = Duration::from_millis(20);
+        let now = Instant::now();
+        let mut rt = HeartbeatTimer::new(interval);
+
+        // should start ok
+        let ok = rt.start(now);
+        assert!(ok, "start() should succeed");
+        assert!(rt.is_running(), "should be running");
+        assert!(!rt.start(now), "start() should fail while running");
+
+        let end = now + Duration::from_millis(110);
+        let mut n = 0;
+        while let Some(deadline) = rt.deadline().filter(|deadline| *deadline <= end) {
+            assert!(rt.handle_timeout(deadline), "should be timed out");
+            n += 1;
+        }
+        assert_eq!(n, 5, "should be timed out every interval");
 
-        // should close ok
         rt.stop();
         assert!(!rt.is_running(), "should not be running");
 
+        // More than the interval later it never times out again
+        assert!(
+            !rt.handle_timeout(end + interval * 3),
+            "should not be timed out"
+        );
+
         Ok(())
     }
 }
@@ -158,214 +184,147 @@ mod test_rto_manager {
     }
 }
 
-//TODO: remove this conditional test
-#[cfg(not(any(target_os = "macos", target_os = "windows")))]
 mod test_rtx_timer {
-    use std::time::SystemTime;
-
-    use tokio::sync::mpsc;
-
     use super::*;
-    use crate::association::RtxTimerId;
     use crate::error::Result;
 
-    struct TestTimerObserver {
-        ncbs: Arc<AtomicU32>,
-        timer_id: RtxTimerId,
-        done_tx: Option<mpsc::Sender<SystemTime>>,
-        max_rtos: usize,
-    }
-
-    impl Default for TestTimerObserver {
-        fn default() -> Self {
-            TestTimerObserver {
-                ncbs: Arc::new(AtomicU32::new(0)),
-                timer_id: RtxTimerId::T1Init,
-                done_tx: None,
-                max_rtos: 0,
-            }
+    /// run handles the timeouts of rt until end, and returns at which
+    /// milliseconds since start they occurred.
+    fn run(rt: &mut RtxTimer, start: Instant, end: Duration) -> Vec<(u128, RtxTimerEvent)> {
+        let mut events = vec![];
+        while let Some(deadline) = rt.deadline().filter(|deadline| *deadline <= start + end) {
+            let event = rt
+                .handle_timeout(deadline)
+                .expect("deadline should have passed");
+            events.push(((deadline - start).as_millis(), event));
         }
+        events
     }
 
-    #[async_trait]
-    impl RtxTimerObserver for TestTimerObserver {
-        async fn on_retransmission_timeout(&mut self, timer_id: RtxTimerId, n_rtos: usize) {
-            self.ncbs.fetch_add(1, Ordering::SeqCst);
-            // 30 : 1 (30)
-            // 60 : 2 (90)
-            // 120: 3 (210)
-            // 240: 4 (550) <== expected in 650 msec
-            assert_eq!(self.timer_id, timer_id, "unexpected timer ID: {timer_id}");
-            if (self.max_rtos > 0 && n_rtos == self.max_rtos) || self.max_rtos == usize::MAX {
-                if let Some(done) = &self.done_tx {
-                    let elapsed = SystemTime::now();
-                    let _ = done.send(elapsed).await;
-                }
-            }
-        }
+    #[test]
+    fn test_rtx_timer_callback_interval() -> Result<()> {
+        let mut rt = RtxTimer::new(PATH_MAX_RETRANS);
 
-        async fn on_retransmission_failure(&mut self, timer_id: RtxTimerId) {
-            if self.max_rtos == 0 {
-                if let Some(done) = &self.done_tx {
-                    assert_eq!(self.timer_id, timer_id, "unexpted timer ID: {timer_id}");
-                    let elapsed = SystemTime::now();
-                    //t.Logf("onRtxFailure: elapsed=%.03f\n", elapsed)
-                    let _ = done.send(elapsed).await;
-                }
-            } else {
-                panic!("timer should not fail");
-            }
-        }
-    }
+        assert!(!rt.is_running(), "should not be running");
 
-    #[tokio::test]
-    async fn test_rtx_timer_callback_interval() -> Result<()> {
-        let timer_id = RtxTimerId::T1Init;
-        let ncbs = Arc::new(AtomicU32::new(0));
-        let obs = Arc::new(Mutex::new(TestTimerObserver {
-            ncbs: ncbs.clone(),
-            timer_id,
-            ..Default::default()
-        }));
-        let rt = RtxTimer::new(Arc::downgrade(&obs), timer_id, PATH_MAX_RETRANS);
-
-        assert!(!rt.is_running().await, "should not be running");
-
-        // since := time.Now()
-        let ok = rt.start(30).await;
+        let since = Instant::now();
+        let ok = rt.start(since, 30);
         assert!(ok, "should be true");
-        assert!(rt.is_running().await, "should be running");
+        assert!(rt.is_running(), "should be running");
 
-        sleep(Duration::from_millis(650)).await;
-        rt.stop().await;
-        assert!(!rt.is_running().await, "should not be running");
+        // 30 : 1 (30)
+        // 60 : 2 (90)
+        // 120: 3 (210)
+        // 240: 4 (450) <== expected in 650 msec
+        let events = run(&mut rt, since, Duration::from_millis(650));
+        rt.stop();
+        assert!(!rt.is_running(), "should not be running");
 
-        assert_eq!(ncbs.load(Ordering::SeqCst), 4, "should be called 4 times");
+        assert_eq!(
+            events,
+            vec![
+                (30, RtxTimerEvent::Timeout(1)),
+                (90, RtxTimerEvent::Timeout(2)),
+                (210, RtxTimerEvent::Timeout(3)),
+                (450, RtxTimerEvent::Timeout(4)),
+            ],
+            "should be called 4 times"
+        );
 
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_rtx_timer_last_start_wins() -> Result<()> {
-        let timer_id = RtxTimerId::T3RTX;
-        let ncbs = Arc::new(AtomicU32::new(0));
-        let obs = Arc::new(Mutex::new(TestTimerObserver {
-            ncbs: ncbs.clone(),
-            timer_id,
-            ..Default::default()
-        }));
-        let rt = RtxTimer::new(Arc::downgrade(&obs), timer_id, PATH_MAX_RETRANS);
+    #[test]
+    fn test_rtx_timer_last_start_wins() -> Result<()> {
+        let mut rt = RtxTimer::new(PATH_MAX_RETRANS);
 
+        let since = Instant::now();
         let interval = 30;
-        let ok = rt.start(interval).await;
+        let ok = rt.start(since, interval);
         assert!(ok, "should be accepted");
-        let ok = rt.start(interval * 99).await; // should ignored
+        let ok = rt.start(since, interval * 99); // should ignored
         assert!(!ok, "should be ignored");
-        let ok = rt.start(interval * 99).await; // should ignored
+        let ok = rt.start(since, interval * 99); // should ignored
         assert!(!ok, "should be ignored");
 
-        sleep(Duration::from_millis((interval * 3) / 2)).await;
-        rt.stop().await;
+        let events = run(&mut rt, since, Duration::from_millis((interval * 3) / 2));
+        rt.stop();
 
-        assert!(!rt.is_running().await, "should not be running");
-        assert_eq!(ncbs.load(Ordering::SeqCst), 1, "must be called once");
+        assert!(!rt.is_running(), "should not be running");
+        assert_eq!(events.len(), 1, "must be called once");
 
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_rtx_timer_stop_right_after_start() -> Result<()> {
-        let timer_id = RtxTimerId::T3RTX;
-        let ncbs = Arc::new(AtomicU32::new(0));
-        let obs = Arc::new(Mutex::new(TestTimerObserver {
-            ncbs: ncbs.clone(),
-            timer_id,
-            ..Default::default()
-        }));
-        let rt = RtxTimer::new(Arc::downgrade(&obs), timer_id, PATH_MAX_RETRANS);
+    #[test]
+    fn test_rtx_timer_stop_right_after_start() -> Result<()> {
+        let mut rt = RtxTimer::new(PATH_MAX_RETRANS);
 
+        let since = Instant::now();
         let interval = 30;
-        let ok = rt.start(interval).await;
+        let ok = rt.start(since, interval);
         assert!(ok, "should be accepted");
-        rt.stop().await;
+        rt.stop();
 
-        sleep(Duration::from_millis((interval * 3) / 2)).await;
-        rt.stop().await;
+        let events = run(&mut rt, since, Duration::from_millis((interval * 3) / 2));
+        assert!(
+            rt.handle_timeout(since + Duration::from_millis(interval))
+                .is_none(),
+            "should not time out"
+        );
+        rt.stop();
 
-        assert!(!rt.is_running().await, "should not be running");
-        assert_eq!(ncbs.load(Ordering::SeqCst), 0, "no callback should be made");
+        assert!(!rt.is_running(), "should not be running");
+        assert!(events.is_empty(), "no callback should be made");
 
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_rtx_timer_start_stop_then_start() -> Result<()> {
-        let timer_id = RtxTimerId::T1Cookie;
-        let ncbs = Arc::new(AtomicU32::new(0));
-        let obs = Arc::new(Mutex::new(TestTimerObserver {
-            ncbs: ncbs.clone(),
-            timer_id,
-            ..Default::default()
-        }));
-        let rt = RtxTimer::new(Arc::downgrade(&obs), timer_id, PATH_MAX_RETRANS);
+    #[test]
+    fn test_rtx_timer_start_stop_then_start() -> Result<()> {
+        let mut rt = RtxTimer::new(MAX_INIT_RETRANS);
 
+        let since = Instant::now();
         let interval = 30;
-        let ok = rt.start(interval).await;
+        let ok = rt.start(since, interval);
         assert!(ok, "should be accepted");
-        rt.stop().await;
-        assert!(!rt.is_running().await, "should NOT be running");
-        let ok = rt.start(interval).await;
+        rt.stop();
+        assert!(!rt.is_running(), "should NOT be running");
+        let ok = rt.start(since, interval);
         assert!(ok, "should be accepted");
-        assert!(rt.is_running().await, "should be running");
+        assert!(rt.is_running(), "should be running");
 
-        sleep(Duration::from_millis((interval * 3) / 2)).await;
-        rt.stop().await;
+        let events = run(&mut rt, since, Duration::from_millis((interval * 3) / 2));
+        rt.stop();
 
-        assert!(!rt.is_running().await, "should NOT be running");
-        assert_eq!(ncbs.load(Ordering::SeqCst), 1, "must be called once");
+        assert!(!rt.is_running(), "should NOT be running");
+        assert_eq!(events.len(), 1, "must be called once");
 
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_rtx_timer_start_and_stop_in_atight_loop() -> Result<()> {
-        let timer_id = RtxTimerId::T2Shutdown;
-        let ncbs = Arc::new(AtomicU32::new(0));
-        let obs = Arc::new(Mutex::new(TestTimerObserver {
-            ncbs: ncbs.clone(),
-            timer_id,
-            ..Default::default()
-        }));
-        let rt = RtxTimer::new(Arc::downgrade(&obs), timer_id, PATH_MAX_RETRANS);
+    #[test]
+    fn test_rtx_timer_start_and_stop_in_atight_loop() -> Result<()> {
+        let mut rt = RtxTimer::new(NO_MAX_RETRANS);
 
+        let since = Instant::now();
         for _ in 0..1000 {
-            let ok = rt.start(30).await;
+            let ok = rt.start(since, 30);
             assert!(ok, "should be accepted");
-            assert!(rt.is_running().await, "should be running");
-            rt.stop().await;
-            assert!(!rt.is_running().await, "should NOT be running");
+            assert!(rt.is_running(), "should be running");
+            rt.stop();
+            assert!(!rt.is_running(), "should NOT be running");
         }
 
-        assert_eq!(ncbs.load(Ordering::SeqCst), 0, "no callback should be made");
+        let events = run(&mut rt, since, Duration::from_millis(100));
+        assert!(events.is_empty(), "no callback should be made");
 
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_rtx_timer_should_stop_after_rtx_failure() -> Result<()> {
-        let (done_tx, mut done_rx) = mpsc::channel(1);
-
-        let timer_id = RtxTimerId::Reconfig;
-        let ncbs = Arc::new(AtomicU32::new(0));
-        let obs = Arc::new(Mutex::new(TestTimerObserver {
-            ncbs: ncbs.clone(),
-            timer_id,
-            done_tx: Some(done_tx),
-            ..Default::default()
-        }));
-
-        let since = SystemTime::now();
-        let rt = RtxTimer::new(Arc::downgrade(&obs), timer_id, PATH_MAX_RETRANS);
+    #[test]
+    fn test_rtx_timer_should_stop_after_rtx_failure() -> Result<()> {
+        let mut rt = RtxTimer::new(PATH_MAX_RETRANS);
 
         // RTO(msec) Total(msec)
         //  10          10    1st RTO
@@ -375,48 +334,34 @@ mod test_rtx_timer {
         // 160         310    5th RTO (== Path.Max.Retrans)
         // 320         630    Failure
 
+        let since = Instant::now();
         let interval = 10;
-        let ok = rt.start(interval).await;
+        let ok = rt.start(since, interval);
         assert!(ok, "should be accepted");
-        assert!(rt.is_running().await, "should be running");
-
-        let elapsed = done_rx.recv().await;
-
-        assert!(!rt.is_running().await, "should not be running");
-        assert_eq!(ncbs.load(Ordering::SeqCst), 5, "should be called 5 times");
-
-        if let Some(elapsed) = elapsed {
-            let diff = elapsed.duration_since(since).unwrap();
-            assert!(
-                diff > Duration::from_millis(600),
-                "must have taken more than 600 msec"
-            );
-            assert!(
-                diff < Duration::from_millis(700),
-                "must fail in less than 700 msec"
-            );
-        }
+        assert!(rt.is_running(), "should be running");
+
+        let events = run(&mut rt, since, Duration::from_secs(10));
+
+        assert!(!rt.is_running(), "should not be running");
+        assert_eq!(
+            events,
+            vec![
+                (10, RtxTimerEvent::Timeout(1)),
+                (30, RtxTimerEvent::Timeout(2)),
+                (70, RtxTimerEvent::Timeout(3)),
+                (150, RtxTimerEvent::Timeout(4)),
+                (310, RtxTimerEvent::Timeout(5)),
+                (630, RtxTimerEvent::Failure),
+            ],
+            "should be called 5 times, then fail"
+        );
 
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_rtx_timer_should_not_stop_if_max_retrans_is_zero() -> Result<()> {
-        let (done_tx, mut done_rx) = mpsc::channel(1);
-
-        let timer_id = RtxTimerId::Reconfig;
-        let max_rtos = 6;
-        let ncbs = Arc::new(AtomicU32::new(0));
-        let obs = Arc::new(Mutex::new(TestTimerObserver {
-            ncbs: ncbs.clone(),
-            timer_id,
-            done_tx: Some(done_tx),
-            max_rtos,
-            ..Default::default()
-        }));
-
-        let since = SystemTime::now();
-        let rt = RtxTimer::new(Arc::downgrade(&obs), timer_id, 0);
+    #[test]
+    fn test_rtx_timer_should_not_stop_if_max_retrans_is_zero() -> Result<()> {
+        let mut rt = RtxTimer::new(0);
 
         // RTO(msec) Total(msec)
         //  10          10    1st RTO
@@ -426,85 +371,81 @@ mod test_rtx_timer {
         // 160         310    5th RTO
         // 320         630    6th RTO => exit test (timer should still be running)
 
+        let since = Instant::now();
         let interval = 10;
-        let ok = rt.start(interval).await;
+        let ok = rt.start(since, interval);
         assert!(ok, "should be accepted");
-        assert!(rt.is_running().await, "should be running");
-
-        let elapsed = done_rx.recv().await;
-
-        assert!(rt.is_running().await, "should still be running");
-        assert_eq!(ncbs.load(Ordering::SeqCst), 6, "should be called 6 times");
-
-        if let Some(elapsed) = elapsed {
-            let diff = elapsed.duration_since(since).unwrap();
-            assert!(
-                diff > Duration::from_millis(600),
-                "must have taken more than 600 msec"
-            );
-            assert!(
-                diff < Duration::from_millis(700),
-                "must fail in less than 700 msec"
-            );
-        }
+        assert!(rt.is_running(), "should be running");
+
+        let events = run(&mut rt, since, Duration::from_millis(650));
 
-        rt.stop().await;
+        assert!(rt.is_running(), "should still be running");
+        assert_eq!(events.len(), 6, "should be called 6 times");
+        assert_eq!(events[5], (630, RtxTimerEvent::Timeout(6)));
+
+        rt.stop();
 
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_rtx_timer_stop_timer_that_is_not_running_is_noop() -> Result<()> {
-        let (done_tx, mut done_rx) = mpsc::channel(1);
-
-        let timer_id = RtxTimerId::Reconfig;
-        let obs = Arc::new(Mutex::new(TestTimerObserver {
-            timer_id,
-            done_tx: Some(done_tx),
-            max_rtos: usize::MAX,
-            ..Default::default()
-        }));
-        let rt = RtxTimer::new(Arc::downgrade(&obs), timer_id, PATH_MAX_RETRANS);
+    #[test]
+    fn test_rtx_timer_handle_timeout_late_backs_off_from_now() -> Result<()> {
+        let mut rt = RtxTimer::new(NO_MAX_RETRANS);
+
+        let since = Instant::now();
+        rt.start(since, 10);
+        assert!(
+            rt.handle_timeout(since + Duration::from_millis(9))
+                .is_none(),
+            "should not time out early"
+        );
+
+        let late = since + Duration::from_millis(100);
+        assert_eq!(rt.handle_timeout(late), Some(RtxTimerEvent::Timeout(1)));
+        assert_eq!(rt.deadline(), Some(late + Duration::from_millis(20)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rtx_timer_stop_timer_that_is_not_running_is_noop() -> Result<()> {
+        let mut rt = RtxTimer::new(PATH_MAX_RETRANS);
 
         for _ in 0..10 {
-            rt.stop().await;
+            rt.stop();
         }
 
-        let ok = rt.start(20).await;
+        let since = Instant::now();
+        let ok = rt.start(since, 20);
         assert!(ok, "should be accepted");
-        assert!(rt.is_running().await, "must be running");
+        assert!(rt.is_running(), "must be running");
 
-        let _ = done_rx.recv().await;
-        rt.stop().await;
-        assert!(!rt.is_running().await, "must be false");
+        let events = run(&mut rt, since, Duration::from_millis(20));
+        assert_eq!(events.len(), 1, "must be called once");
+        rt.stop();
+        assert!(!rt.is_running(), "must be false");
 
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_rtx_timer_closed_timer_wont_start() -> Result<()> {
-        let timer_id = RtxTimerId::Reconfig;
-        let ncbs = Arc::new(AtomicU32::new(0));
-        let obs = Arc::new(Mutex::new(TestTimerObserver {
-            ncbs: ncbs.clone(),
-            timer_id,
-            ..Default::default()
-        }));
-        let rt = RtxTimer::new(Arc::downgrade(&obs), timer_id, PATH_MAX_RETRANS);
-
-        let ok = rt.start(20).await;
+    #[test]
+    fn test_rtx_timer_closed_timer_wont_start() -> Result<()> {
+        let mut rt = RtxTimer::new(PATH_MAX_RETRANS);
+
+        let since = Instant::now();
+        let ok = rt.start(since, 20);
         assert!(ok, "should be accepted");
-        assert!(rt.is_running().await, "must be running");
+        assert!(rt.is_running(), "must be running");
 
-        rt.stop().await;
-        assert!(!rt.is_running().await, "must be false");
+        rt.stop();
+        assert!(!rt.is_running(), "must be false");
 
         //let ok = rt.start(obs.clone(), 20).await;
         //assert!(!ok, "should not start");
-        assert!(!rt.is_running().await, "must not be running");
+        assert!(!rt.is_running(), "must not be running");
 
-        sleep(Duration::from_millis(100)).await;
-        assert_eq!(ncbs.load(Ordering::SeqCst), 0, "RTO should not occur");
+        let events = run(&mut rt, since, Duration::from_millis(100));
+        assert!(events.is_empty(), "RTO should not occur");
 
         Ok(())
     }
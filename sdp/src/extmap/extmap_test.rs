@@ -8,6 +8,7 @@ use crate::util::ATTRIBUTE_KEY;
 const EXAMPLE_ATTR_EXTMAP1: &str = "extmap:1 http://example.com/082005/ext.htm#ttime";
 const EXAMPLE_ATTR_EXTMAP2: &str =
     "extmap:2/sendrecv http://example.com/082005/ext.htm#xmeta short";
+const EXAMPLE_ATTR_EXTMAP3: &str = "extmap:255 http://example.com/082005/ext.htm#ttime";
 const FAILING_ATTR_EXTMAP1: &str =
     "extmap:257/sendrecv http://example.com/082005/ext.htm#xmeta short";
 const FAILING_ATTR_EXTMAP2: &str = "extmap:2/blorg http://example.com/082005/ext.htm#xmeta short";
@@ -16,11 +17,13 @@ const FAILING_ATTR_EXTMAP2: &str = "extmap:2/blorg http://example.com/082005/ext
 fn test_extmap() -> Result<()> {
     let example_attr_extmap1_line = EXAMPLE_ATTR_EXTMAP1;
     let example_attr_extmap2_line = EXAMPLE_ATTR_EXTMAP2;
+    let example_attr_extmap3_line = EXAMPLE_ATTR_EXTMAP3;
     let failing_attr_extmap1_line = format!("{ATTRIBUTE_KEY}{FAILING_ATTR_EXTMAP1}{END_LINE}");
     let failing_attr_extmap2_line = format!("{ATTRIBUTE_KEY}{FAILING_ATTR_EXTMAP2}{END_LINE}");
     let passingtests = [
         (EXAMPLE_ATTR_EXTMAP1, example_attr_extmap1_line),
         (EXAMPLE_ATTR_EXTMAP2, example_attr_extmap2_line),
+        (EXAMPLE_ATTR_EXTMAP3, example_attr_extmap3_line),
     ];
     let failingtests = vec![
         (FAILING_ATTR_EXTMAP1, failing_attr_extmap1_line),
@@ -1,7 +1,7 @@
 use bytes::BytesMut;
 use criterion::measurement::WallTime;
 use criterion::{criterion_main, BenchmarkGroup, Criterion};
-use util::Marshal;
+use util::{Marshal, MarshalSize};
 use webrtc_srtp::{context::Context, protection_profile::ProtectionProfile};
 
 const MASTER_KEY: &[u8] = &[
@@ -44,6 +44,7 @@ fn benchmark_encrypt_rtp_aes_128_cm_hmac_sha1(g: &mut BenchmarkGroup<WallTime>)
                         ..Default::default()
                     },
                     payload: pld.clone().into(),
+                    ..Default::default()
                 };
                 seq += 1;
                 pkt.marshal().unwrap()
@@ -56,6 +57,54 @@ fn benchmark_encrypt_rtp_aes_128_cm_hmac_sha1(g: &mut BenchmarkGroup<WallTime>)
     });
 }
 
+fn benchmark_encrypt_rtp_in_place_aes_128_cm_hmac_sha1(g: &mut BenchmarkGroup<WallTime>) {
+    let mut ctx = Context::new(
+        MASTER_KEY,
+        MASTER_SALT,
+        ProtectionProfile::Aes128CmHmacSha1_80,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let mut pld = BytesMut::new();
+    for i in 0..1200 {
+        pld.extend_from_slice(&[i as u8]);
+    }
+
+    let overhead = ctx.rtp_overhead();
+    g.bench_function("Encrypt/RTP/InPlace", |b| {
+        let mut seq = 1;
+        b.iter_batched(
+            || {
+                let pkt = rtp::packet::Packet {
+                    header: rtp::header::Header {
+                        sequence_number: seq,
+                        timestamp: seq.into(),
+                        extension_profile: 48862,
+                        marker: true,
+                        padding: false,
+                        extension: true,
+                        payload_type: 96,
+                        ..Default::default()
+                    },
+                    payload: pld.clone().into(),
+                    ..Default::default()
+                };
+                seq += 1;
+                let mut raw = BytesMut::with_capacity(pkt.marshal_size() + overhead);
+                raw.resize(pkt.marshal_size(), 0);
+                pkt.marshal_to(&mut raw).unwrap();
+                raw
+            },
+            |mut pkt_raw| {
+                ctx.encrypt_rtp_in_place(&mut pkt_raw).unwrap();
+            },
+            criterion::BatchSize::LargeInput,
+        );
+    });
+}
+
 fn benchmark_decrypt_rtp_aes_128_cm_hmac_sha1(g: &mut BenchmarkGroup<WallTime>) {
     let mut setup_ctx = Context::new(
         MASTER_KEY,
@@ -96,6 +145,7 @@ fn benchmark_decrypt_rtp_aes_128_cm_hmac_sha1(g: &mut BenchmarkGroup<WallTime>)
                         ..Default::default()
                     },
                     payload: pld.clone().into(),
+                    ..Default::default()
                 };
                 seq += 1;
                 setup_ctx.encrypt_rtp(&pkt.marshal().unwrap()).unwrap()
@@ -154,6 +204,7 @@ fn benches() {
     let mut g = c.benchmark_group("SRTP");
 
     benchmark_encrypt_rtp_aes_128_cm_hmac_sha1(&mut g);
+    benchmark_encrypt_rtp_in_place_aes_128_cm_hmac_sha1(&mut g);
     benchmark_decrypt_rtp_aes_128_cm_hmac_sha1(&mut g);
     benchmark_encrypt_rtcp_aes_128_cm_hmac_sha1(&mut g);
     benchmark_decrypt_rtcp_aes_128_cm_hmac_sha1(&mut g);
@@ -1,11 +1,9 @@
-use aes_gcm::aead::generic_array::GenericArray;
-use aes_gcm::aead::{Aead, Payload};
-use aes_gcm::{Aes128Gcm, KeyInit, Nonce};
 use byteorder::{BigEndian, ByteOrder};
-use bytes::{Bytes, BytesMut};
-use util::marshal::*;
+use bytes::BytesMut;
 
 use super::Cipher;
+use crate::cryptex;
+use crate::crypto::{self, AeadCipher};
 use crate::error::{Error, Result};
 use crate::key_derivation::*;
 use crate::protection_profile::ProtectionProfile;
@@ -17,8 +15,8 @@ const RTCP_ENCRYPTION_FLAG: u8 = 0x80;
 /// AEAD Cipher based on AES.
 pub(crate) struct CipherAeadAesGcm {
     profile: ProtectionProfile,
-    srtp_cipher: aes_gcm::Aes128Gcm,
-    srtcp_cipher: aes_gcm::Aes128Gcm,
+    srtp_cipher: Box<dyn AeadCipher>,
+    srtcp_cipher: Box<dyn AeadCipher>,
     srtp_session_salt: Vec<u8>,
     srtcp_session_salt: Vec<u8>,
 }
@@ -41,99 +39,90 @@ impl Cipher for CipherAeadAesGcm {
 
     fn encrypt_rtp(
         &mut self,
-        payload: &[u8],
+        packet: &mut BytesMut,
         header: &rtp::header::Header,
         roc: u32,
-    ) -> Result<Bytes> {
-        // Grow the given buffer to fit the output.
-        let header_len = header.marshal_size();
-        let mut writer = BytesMut::with_capacity(payload.len() + self.aead_auth_tag_len());
-
-        // Copy header unencrypted.
-        writer.extend_from_slice(&payload[..header_len]);
-
+    ) -> Result<()> {
+        let header_len = cryptex::encryption_offset(header);
         let nonce = self.rtp_initialization_vector(header, roc);
 
-        let encrypted = self.srtp_cipher.encrypt(
-            Nonce::from_slice(&nonce),
-            Payload {
-                msg: &payload[header_len..],
-                aad: &writer,
-            },
-        )?;
+        // The header is authenticated but left unencrypted.
+        cryptex::reorder(packet, header);
+        let (aad, payload) = packet.split_at_mut(header_len);
+        let tag = self.srtp_cipher.encrypt_in_place(&nonce, aad, payload);
+        cryptex::restore(packet, header);
 
-        writer.extend(encrypted);
-        Ok(writer.freeze())
+        packet.extend_from_slice(&tag?);
+        Ok(())
     }
 
     fn decrypt_rtp(
         &mut self,
-        ciphertext: &[u8],
+        packet: &mut BytesMut,
         header: &rtp::header::Header,
         roc: u32,
-    ) -> Result<Bytes> {
-        if ciphertext.len() < self.aead_auth_tag_len() {
+    ) -> Result<()> {
+        let payload_offset = cryptex::encryption_offset(header);
+        if packet.len() < payload_offset + self.aead_auth_tag_len() {
             return Err(Error::ErrFailedToVerifyAuthTag);
         }
 
         let nonce = self.rtp_initialization_vector(header, roc);
-        let payload_offset = header.marshal_size();
-        let decrypted_msg: Vec<u8> = self.srtp_cipher.decrypt(
-            Nonce::from_slice(&nonce),
-            Payload {
-                msg: &ciphertext[payload_offset..],
-                aad: &ciphertext[..payload_offset],
-            },
-        )?;
-
-        let mut writer = BytesMut::with_capacity(payload_offset + decrypted_msg.len());
-        writer.extend_from_slice(&ciphertext[..payload_offset]);
-        writer.extend(decrypted_msg);
-
-        Ok(writer.freeze())
+        let tag_offset = packet.len() - self.aead_auth_tag_len();
+
+        cryptex::reorder(packet, header);
+        let (aad, encrypted) = packet.split_at_mut(payload_offset);
+        let (ciphertext, tag) = encrypted.split_at_mut(tag_offset - payload_offset);
+        let result = self
+            .srtp_cipher
+            .decrypt_in_place(&nonce, aad, ciphertext, tag);
+        cryptex::restore(packet, header);
+
+        result?;
+        packet.truncate(tag_offset);
+        Ok(())
     }
 
-    fn encrypt_rtcp(&mut self, decrypted: &[u8], srtcp_index: usize, ssrc: u32) -> Result<Bytes> {
+    fn encrypt_rtcp(
+        &mut self,
+        packet: &mut BytesMut,
+        srtcp_index: usize,
+        ssrc: u32,
+        encrypt: bool,
+    ) -> Result<()> {
         let iv = self.rtcp_initialization_vector(srtcp_index, ssrc);
-        let aad = self.rtcp_additional_authenticated_data(decrypted, srtcp_index);
-
-        let encrypted_data = self.srtcp_cipher.encrypt(
-            Nonce::from_slice(&iv),
-            Payload {
-                msg: &decrypted[8..],
-                aad: &aad,
-            },
-        )?;
+        let aad = self.rtcp_additional_authenticated_data(packet, srtcp_index, encrypt);
 
-        let mut writer = BytesMut::with_capacity(encrypted_data.len() + aad.len());
-        writer.extend_from_slice(&decrypted[..8]);
-        writer.extend(encrypted_data);
-        writer.extend_from_slice(&aad[8..]);
+        // Unencrypted packets are authenticated as a whole, with an empty plaintext.
+        let msg = if encrypt { &mut packet[8..] } else { &mut [] };
+        let tag = self.srtcp_cipher.encrypt_in_place(&iv, &aad, msg)?;
 
-        Ok(writer.freeze())
+        packet.extend_from_slice(&tag);
+        packet.extend_from_slice(&aad[aad.len() - SRTCP_INDEX_SIZE..]);
+        Ok(())
     }
 
-    fn decrypt_rtcp(&mut self, encrypted: &[u8], srtcp_index: usize, ssrc: u32) -> Result<Bytes> {
-        if encrypted.len() < self.aead_auth_tag_len() + SRTCP_INDEX_SIZE {
+    fn decrypt_rtcp(&mut self, packet: &mut BytesMut, srtcp_index: usize, ssrc: u32) -> Result<()> {
+        if packet.len() < 8 + self.aead_auth_tag_len() + SRTCP_INDEX_SIZE {
             return Err(Error::ErrFailedToVerifyAuthTag);
         }
 
         let nonce = self.rtcp_initialization_vector(srtcp_index, ssrc);
-        let aad = self.rtcp_additional_authenticated_data(encrypted, srtcp_index);
-
-        let decrypted_data = self.srtcp_cipher.decrypt(
-            Nonce::from_slice(&nonce),
-            Payload {
-                msg: &encrypted[8..(encrypted.len() - SRTCP_INDEX_SIZE)],
-                aad: &aad,
-            },
-        )?;
-
-        let mut writer = BytesMut::with_capacity(8 + decrypted_data.len());
-        writer.extend_from_slice(&encrypted[..8]);
-        writer.extend(decrypted_data);
-
-        Ok(writer.freeze())
+        let encrypted = self.is_rtcp_encrypted(packet);
+        let tag_offset = packet.len() - SRTCP_INDEX_SIZE - self.aead_auth_tag_len();
+        let aad = if encrypted {
+            self.rtcp_additional_authenticated_data(packet, srtcp_index, true)
+        } else {
+            self.rtcp_additional_authenticated_data(&packet[..tag_offset], srtcp_index, false)
+        };
+
+        let (ciphertext, tail) = packet[8..].split_at_mut(tag_offset - 8);
+        let msg = if encrypted { ciphertext } else { &mut [] };
+        self.srtcp_cipher
+            .decrypt_in_place(&nonce, &aad, msg, &tail[..self.aead_auth_tag_len()])?;
+
+        packet.truncate(tag_offset);
+        Ok(())
     }
 
     fn get_rtcp_index(&self, input: &[u8]) -> usize {
@@ -142,6 +131,10 @@ impl Cipher for CipherAeadAesGcm {
 
         (val & !((RTCP_ENCRYPTION_FLAG as u32) << 24)) as usize
     }
+
+    fn is_rtcp_encrypted(&self, input: &[u8]) -> bool {
+        input[input.len() - SRTCP_INDEX_SIZE] & RTCP_ENCRYPTION_FLAG != 0
+    }
 }
 
 impl CipherAeadAesGcm {
@@ -159,9 +152,7 @@ impl CipherAeadAesGcm {
             master_key.len(),
         )?;
 
-        let srtp_block = GenericArray::from_slice(&srtp_session_key);
-
-        let srtp_cipher = Aes128Gcm::new(srtp_block);
+        let srtp_cipher = crypto::get_default().aes_gcm(&srtp_session_key)?;
 
         let srtcp_session_key = aes_cm_key_derivation(
             LABEL_SRTCP_ENCRYPTION,
@@ -171,9 +162,7 @@ impl CipherAeadAesGcm {
             master_key.len(),
         )?;
 
-        let srtcp_block = GenericArray::from_slice(&srtcp_session_key);
-
-        let srtcp_cipher = Aes128Gcm::new(srtcp_block);
+        let srtcp_cipher = crypto::get_default().aes_gcm(&srtcp_session_key)?;
 
         let srtp_session_salt = aes_cm_key_derivation(
             LABEL_SRTP_SALT,
@@ -247,19 +236,26 @@ impl CipherAeadAesGcm {
     /// 31-bit SRTCP index to form a 32-bit value we shall call the
     /// "ESRTCP word"
     ///
+    /// Encrypted packets authenticate their first 8 octets along with the ESRTCP word,
+    /// unencrypted ones the whole packet.
+    ///
     /// https://tools.ietf.org/html/rfc7714#section-17
     pub(crate) fn rtcp_additional_authenticated_data(
         &self,
         rtcp_packet: &[u8],
         srtcp_index: usize,
+        encrypted: bool,
     ) -> Vec<u8> {
-        let mut aad = vec![0u8; 12];
+        let header_len = if encrypted { 8 } else { rtcp_packet.len() };
+        let mut aad = vec![0u8; header_len + SRTCP_INDEX_SIZE];
 
-        aad[..8].copy_from_slice(&rtcp_packet[..8]);
+        aad[..header_len].copy_from_slice(&rtcp_packet[..header_len]);
 
-        BigEndian::write_u32(&mut aad[8..], srtcp_index as u32);
+        BigEndian::write_u32(&mut aad[header_len..], srtcp_index as u32);
 
-        aad[8] |= RTCP_ENCRYPTION_FLAG;
+        if encrypted {
+            aad[header_len] |= RTCP_ENCRYPTION_FLAG;
+        }
         aad
     }
 }
@@ -1,34 +1,195 @@
 use byteorder::{BigEndian, ByteOrder};
-use hmac::{Hmac, Mac};
-use sha1::Sha1;
+use bytes::{BufMut, BytesMut};
+use rtcp::header::{HEADER_LENGTH, SSRC_LENGTH};
+use subtle::ConstantTimeEq;
 
 use super::Cipher;
+use crate::cryptex;
+use crate::crypto::{self, Mac, StreamCipher, HMAC_SHA1_LEN};
 use crate::error::{Error, Result};
 use crate::key_derivation::*;
 use crate::protection_profile::*;
 
-#[cfg(not(feature = "openssl"))]
-mod ctrcipher;
+pub const CIPHER_AES_CM_HMAC_SHA1AUTH_TAG_LEN: usize = 10;
 
-#[cfg(feature = "openssl")]
-mod opensslcipher;
+pub(crate) struct CipherAesCmHmacSha1 {
+    inner: CipherInner,
+    srtp_cipher: Box<dyn StreamCipher>,
+    srtcp_cipher: Box<dyn StreamCipher>,
+}
 
-#[cfg(not(feature = "openssl"))]
-pub(crate) use ctrcipher::CipherAesCmHmacSha1;
+impl CipherAesCmHmacSha1 {
+    pub fn new(profile: ProtectionProfile, master_key: &[u8], master_salt: &[u8]) -> Result<Self> {
+        let inner = CipherInner::new(profile, master_key, master_salt)?;
 
-#[cfg(feature = "openssl")]
-pub(crate) use opensslcipher::CipherAesCmHmacSha1;
+        let srtp_session_key = aes_cm_key_derivation(
+            LABEL_SRTP_ENCRYPTION,
+            master_key,
+            master_salt,
+            0,
+            master_key.len(),
+        )?;
+        let srtcp_session_key = aes_cm_key_derivation(
+            LABEL_SRTCP_ENCRYPTION,
+            master_key,
+            master_salt,
+            0,
+            master_key.len(),
+        )?;
 
-type HmacSha1 = Hmac<Sha1>;
+        Ok(CipherAesCmHmacSha1 {
+            inner,
+            srtp_cipher: crypto::get_default().aes_ctr(&srtp_session_key)?,
+            srtcp_cipher: crypto::get_default().aes_ctr(&srtcp_session_key)?,
+        })
+    }
+}
 
-pub const CIPHER_AES_CM_HMAC_SHA1AUTH_TAG_LEN: usize = 10;
+impl Cipher for CipherAesCmHmacSha1 {
+    /// Get RTP authenticated tag length.
+    fn rtp_auth_tag_len(&self) -> usize {
+        self.inner.profile.rtp_auth_tag_len()
+    }
+
+    /// Get RTCP authenticated tag length.
+    fn rtcp_auth_tag_len(&self) -> usize {
+        self.inner.profile.rtcp_auth_tag_len()
+    }
+
+    /// Get AEAD auth key length of the cipher.
+    fn aead_auth_tag_len(&self) -> usize {
+        self.inner.profile.aead_auth_tag_len()
+    }
+
+    fn get_rtcp_index(&self, input: &[u8]) -> usize {
+        self.inner.get_rtcp_index(input)
+    }
+
+    fn is_rtcp_encrypted(&self, input: &[u8]) -> bool {
+        self.inner.is_rtcp_encrypted(input)
+    }
+
+    fn encrypt_rtp(
+        &mut self,
+        packet: &mut BytesMut,
+        header: &rtp::header::Header,
+        roc: u32,
+    ) -> Result<()> {
+        // Encrypt the payload
+        let counter = generate_counter(
+            header.sequence_number,
+            roc,
+            header.ssrc,
+            &self.inner.srtp_session_salt,
+        );
+        cryptex::reorder(packet, header);
+        let result = self
+            .srtp_cipher
+            .apply_keystream(&counter, &mut packet[cryptex::encryption_offset(header)..]);
+        cryptex::restore(packet, header);
+        result?;
+
+        // Generate and append the auth tag.
+        let auth_tag = self.inner.generate_srtp_auth_tag(packet, roc)?;
+        packet.extend_from_slice(&auth_tag[..self.rtp_auth_tag_len()]);
+
+        Ok(())
+    }
+
+    fn decrypt_rtp(
+        &mut self,
+        packet: &mut BytesMut,
+        header: &rtp::header::Header,
+        roc: u32,
+    ) -> Result<()> {
+        let encrypted_len = packet.len();
+        if encrypted_len < self.rtp_auth_tag_len() {
+            return Err(Error::SrtpTooSmall(encrypted_len, self.rtp_auth_tag_len()));
+        }
+
+        // Split the auth tag and the cipher text into two parts.
+        let cipher_text_len = encrypted_len - self.rtp_auth_tag_len();
+        let (cipher_text, actual_tag) = packet.split_at(cipher_text_len);
+
+        // Generate the auth tag we expect to see from the ciphertext.
+        let expected_tag =
+            &self.inner.generate_srtp_auth_tag(cipher_text, roc)?[..self.rtp_auth_tag_len()];
+
+        // See if the auth tag actually matches.
+        // We use a constant time comparison to prevent timing attacks.
+        if actual_tag.ct_eq(expected_tag).unwrap_u8() != 1 {
+            return Err(Error::RtpFailedToVerifyAuthTag);
+        }
+        packet.truncate(cipher_text_len);
+
+        // Decrypt the ciphertext for the payload.
+        let counter = generate_counter(
+            header.sequence_number,
+            roc,
+            header.ssrc,
+            &self.inner.srtp_session_salt,
+        );
+        cryptex::reorder(packet, header);
+        let result = self
+            .srtp_cipher
+            .apply_keystream(&counter, &mut packet[cryptex::encryption_offset(header)..]);
+        cryptex::restore(packet, header);
+
+        result
+    }
+
+    fn encrypt_rtcp(
+        &mut self,
+        packet: &mut BytesMut,
+        srtcp_index: usize,
+        ssrc: u32,
+        encrypt: bool,
+    ) -> Result<()> {
+        if encrypt {
+            // Encrypt everything after header
+            let counter = generate_counter(
+                (srtcp_index & 0xFFFF) as u16,
+                (srtcp_index >> 16) as u32,
+                ssrc,
+                &self.inner.srtcp_session_salt,
+            );
+            self.srtcp_cipher
+                .apply_keystream(&counter, &mut packet[HEADER_LENGTH + SSRC_LENGTH..])?;
+        }
+
+        // Add SRTCP index and the Encryption bit
+        packet.put_u32(srtcp_index as u32 | ((encrypt as u32) << 31));
+
+        // Generate and append the auth tag.
+        let auth_tag = self.inner.generate_srtcp_auth_tag(packet)?;
+        packet.extend_from_slice(&auth_tag[..self.rtcp_auth_tag_len()]);
+
+        Ok(())
+    }
+
+    fn decrypt_rtcp(&mut self, packet: &mut BytesMut, srtcp_index: usize, ssrc: u32) -> Result<()> {
+        if !self.inner.verify_srtcp(packet)? {
+            return Ok(());
+        }
+
+        let counter = generate_counter(
+            (srtcp_index & 0xFFFF) as u16,
+            (srtcp_index >> 16) as u32,
+            ssrc,
+            &self.inner.srtcp_session_salt,
+        );
+
+        self.srtcp_cipher
+            .apply_keystream(&counter, &mut packet[HEADER_LENGTH + SSRC_LENGTH..])
+    }
+}
 
 pub(crate) struct CipherInner {
     profile: ProtectionProfile,
     srtp_session_salt: Vec<u8>,
-    srtp_session_auth: HmacSha1,
+    srtp_session_auth: Box<dyn Mac>,
     srtcp_session_salt: Vec<u8>,
-    srtcp_session_auth: HmacSha1,
+    srtcp_session_auth: Box<dyn Mac>,
 }
 
 impl CipherInner {
@@ -65,10 +226,8 @@ impl CipherInner {
             auth_key_len,
         )?;
 
-        let srtp_session_auth = HmacSha1::new_from_slice(&srtp_session_auth_tag)
-            .map_err(|e| Error::Other(e.to_string()))?;
-        let srtcp_session_auth = HmacSha1::new_from_slice(&srtcp_session_auth_tag)
-            .map_err(|e| Error::Other(e.to_string()))?;
+        let srtp_session_auth = crypto::get_default().hmac_sha1(&srtp_session_auth_tag)?;
+        let srtcp_session_auth = crypto::get_default().hmac_sha1(&srtcp_session_auth_tag)?;
 
         Ok(Self {
             profile,
@@ -93,15 +252,9 @@ impl CipherInner {
     /// - Authenticated portion of the packet is everything BEFORE MKI
     /// - k_a is the session message authentication key
     /// - n_tag is the bit-length of the output authentication tag
-    fn generate_srtp_auth_tag(&self, buf: &[u8], roc: u32) -> [u8; 20] {
-        let mut signer = self.srtp_session_auth.clone();
-
-        signer.update(buf);
-
+    fn generate_srtp_auth_tag(&self, buf: &[u8], roc: u32) -> Result<[u8; HMAC_SHA1_LEN]> {
         // For SRTP only, we need to hash the rollover counter as well.
-        signer.update(&roc.to_be_bytes());
-
-        signer.finalize().into_bytes().into()
+        self.srtp_session_auth.sign(&[buf, &roc.to_be_bytes()])
     }
 
     /// https://tools.ietf.org/html/rfc3711#section-4.2
@@ -115,12 +268,8 @@ impl CipherInner {
     /// - Authenticated portion of the packet is everything BEFORE MKI
     /// - k_a is the session message authentication key
     /// - n_tag is the bit-length of the output authentication tag
-    fn generate_srtcp_auth_tag(&self, buf: &[u8]) -> [u8; 20] {
-        let mut signer = self.srtcp_session_auth.clone();
-
-        signer.update(buf);
-
-        signer.finalize().into_bytes().into()
+    fn generate_srtcp_auth_tag(&self, buf: &[u8]) -> Result<[u8; HMAC_SHA1_LEN]> {
+        self.srtcp_session_auth.sign(&[buf])
     }
 
     fn get_rtcp_index(&self, input: &[u8]) -> usize {
@@ -128,4 +277,39 @@ impl CipherInner {
         (BigEndian::read_u32(&input[tail_offset..tail_offset + SRTCP_INDEX_SIZE]) & !(1 << 31))
             as usize
     }
+
+    fn is_rtcp_encrypted(&self, input: &[u8]) -> bool {
+        let tail_offset = input.len() - (self.profile.rtcp_auth_tag_len() + SRTCP_INDEX_SIZE);
+        input[tail_offset] >> 7 == 1
+    }
+
+    /// Verifies the auth tag of a SRTCP packet and strips it along with the SRTCP index.
+    /// Returns whether the payload is encrypted.
+    fn verify_srtcp(&self, packet: &mut bytes::BytesMut) -> Result<bool> {
+        let auth_tag_len = self.profile.rtcp_auth_tag_len();
+        let encrypted_len = packet.len();
+        if encrypted_len < auth_tag_len + SRTCP_INDEX_SIZE {
+            return Err(Error::SrtcpTooSmall(
+                encrypted_len,
+                auth_tag_len + SRTCP_INDEX_SIZE,
+            ));
+        }
+
+        // Split the auth tag and the cipher text into two parts.
+        let (cipher_text, actual_tag) = packet.split_at(encrypted_len - auth_tag_len);
+
+        // Generate the auth tag we expect to see from the ciphertext.
+        let expected_tag = &self.generate_srtcp_auth_tag(cipher_text)?[..auth_tag_len];
+
+        // See if the auth tag actually matches.
+        // We use a constant time comparison to prevent timing attacks.
+        if actual_tag.ct_eq(expected_tag).unwrap_u8() != 1 {
+            return Err(Error::RtcpFailedToVerifyAuthTag);
+        }
+
+        // The E flag is authenticated, so it is only trusted from here on.
+        let is_encrypted = self.is_rtcp_encrypted(packet);
+        packet.truncate(encrypted_len - (auth_tag_len + SRTCP_INDEX_SIZE));
+        Ok(is_encrypted)
+    }
 }
@@ -1,7 +1,7 @@
 pub mod cipher_aead_aes_gcm;
 pub mod cipher_aes_cm_hmac_sha1;
 
-use bytes::Bytes;
+use bytes::BytesMut;
 
 use crate::error::Result;
 
@@ -43,25 +43,38 @@ pub(crate) trait Cipher {
     /// Retrieved RTCP index.
     fn get_rtcp_index(&self, input: &[u8]) -> usize;
 
-    /// Encrypt RTP payload.
+    /// Returns the E flag of a SRTCP packet, set if its payload is encrypted.
+    fn is_rtcp_encrypted(&self, input: &[u8]) -> bool;
+
+    /// Encrypt the RTP packet in place, appending the auth tag.
     fn encrypt_rtp(
         &mut self,
-        payload: &[u8],
+        packet: &mut BytesMut,
         header: &rtp::header::Header,
         roc: u32,
-    ) -> Result<Bytes>;
+    ) -> Result<()>;
 
-    /// Decrypt RTP payload.
+    /// Decrypt the RTP packet in place, removing the auth tag.
+    /// The packet is left unchanged if it fails to decrypt.
     fn decrypt_rtp(
         &mut self,
-        payload: &[u8],
+        packet: &mut BytesMut,
         header: &rtp::header::Header,
         roc: u32,
-    ) -> Result<Bytes>;
+    ) -> Result<()>;
 
-    /// Encrypt RTCP payload.
-    fn encrypt_rtcp(&mut self, payload: &[u8], srtcp_index: usize, ssrc: u32) -> Result<Bytes>;
+    /// Encrypt the RTCP packet in place, appending the SRTCP index and the auth tag.
+    /// Unless encrypt is set the payload is only authenticated, and sent with the E flag cleared.
+    fn encrypt_rtcp(
+        &mut self,
+        packet: &mut BytesMut,
+        srtcp_index: usize,
+        ssrc: u32,
+        encrypt: bool,
+    ) -> Result<()>;
 
-    /// Decrypt RTCP payload.
-    fn decrypt_rtcp(&mut self, payload: &[u8], srtcp_index: usize, ssrc: u32) -> Result<Bytes>;
+    /// Decrypt the RTCP packet in place, removing the SRTCP index and the auth tag.
+    /// Packets with the E flag cleared are only authenticated.
+    /// The packet is left unchanged if it fails to decrypt.
+    fn decrypt_rtcp(&mut self, packet: &mut BytesMut, srtcp_index: usize, ssrc: u32) -> Result<()>;
 }
@@ -1,8 +1,11 @@
 use util::KeyingMaterialExporter;
 
-use crate::error::Result;
+use crate::context::{SrtcpEncryptionPolicy, SsrcStateLimits};
+use crate::cryptex::CryptexPolicy;
+use crate::error::{Error, Result};
 use crate::option::*;
 use crate::protection_profile::*;
+use crate::sdes::*;
 
 const LABEL_EXTRACTOR_DTLS_SRTP: &str = "EXTRACTOR-dtls_srtp";
 
@@ -13,6 +16,9 @@ pub struct SessionKeys {
     pub local_master_salt: Vec<u8>,
     pub remote_master_key: Vec<u8>,
     pub remote_master_salt: Vec<u8>,
+    /// MKIs identifying the master keys on the wire, empty if MKIs are not in use.
+    pub local_mki: Vec<u8>,
+    pub remote_mki: Vec<u8>,
 }
 
 /// Config is used to configure a session.
@@ -32,6 +38,15 @@ pub struct Config {
 
     pub local_rtcp_options: Option<ContextOption>,
     pub remote_rtcp_options: Option<ContextOption>,
+
+    /// Whether CSRCs and RTP header extensions are encrypted (RFC 9335).
+    pub cryptex_policy: CryptexPolicy,
+
+    /// Whether SRTCP payloads are encrypted or only authenticated.
+    pub srtcp_encryption_policy: SrtcpEncryptionPolicy,
+
+    /// Bounds on the per-SSRC state of the remote context, unbounded by default.
+    pub remote_ssrc_state_limits: SsrcStateLimits,
 }
 
 impl Config {
@@ -80,4 +95,41 @@ impl Config {
 
         Ok(())
     }
+
+    /// extract_session_keys_from_sdes sets the Config profile and SessionKeys from the
+    /// `a=crypto` attributes negotiated in the SDP, as defined in RFC4568:
+    /// <https://tools.ietf.org/html/rfc4568>
+    /// Each side protects what it sends with the key of its own attribute.
+    /// SRTCP is left unencrypted only if both attributes carry `UNENCRYPTED_SRTCP`.
+    pub fn extract_session_keys_from_sdes(
+        &mut self,
+        local: &CryptoAttribute,
+        remote: &CryptoAttribute,
+    ) -> Result<()> {
+        if local.profile != remote.profile {
+            return Err(Error::SdesCryptoSuiteMismatch(
+                crypto_suite(local.profile).to_owned(),
+                crypto_suite(remote.profile).to_owned(),
+            ));
+        }
+
+        self.profile = local.profile;
+        self.keys.local_master_key = local.master_key.clone();
+        self.keys.local_master_salt = local.master_salt.clone();
+        self.keys.local_mki = local.mki.clone();
+        self.keys.remote_master_key = remote.master_key.clone();
+        self.keys.remote_master_salt = remote.master_salt.clone();
+        self.keys.remote_mki = remote.mki.clone();
+
+        // Unencrypted SRTCP only applies once both sides agreed on it (RFC 4568 section 6.3.2).
+        if local.has_session_param(SESSION_PARAM_UNENCRYPTED_SRTCP)
+            && remote.has_session_param(SESSION_PARAM_UNENCRYPTED_SRTCP)
+        {
+            self.srtcp_encryption_policy = SrtcpEncryptionPolicy::Disabled;
+        } else if self.srtcp_encryption_policy == SrtcpEncryptionPolicy::Disabled {
+            self.srtcp_encryption_policy = SrtcpEncryptionPolicy::Enabled;
+        }
+
+        Ok(())
+    }
 }
@@ -1,4 +1,6 @@
-use bytes::Bytes;
+use std::sync::{Arc, Mutex};
+
+use bytes::{Bytes, BytesMut};
 use lazy_static::lazy_static;
 
 use super::*;
@@ -113,7 +115,7 @@ fn test_valid_packet_counter() -> Result<()> {
         0xcf, 0x90, 0x1e, 0xa5, 0xda, 0xd3, 0x2c, 0x15, 0x00, 0xa2, 0x24, 0xae, 0xae, 0xaf, 0x00,
         0x00,
     ];
-    let counter = generate_counter(32846, s.rollover_counter, s.ssrc, &srtp_session_salt);
+    let counter = generate_counter(32846, s.rollover_counter(), s.ssrc, &srtp_session_salt);
     assert_eq!(
         counter, expected_counter,
         "Session Key {counter:?} does not match expected {expected_counter:?}",
@@ -230,6 +232,21 @@ lazy_static! {
         0x55, 0x85, 0x2b, 0x6c, 0x21, 0xac, 0x8e, 0x70, 0x25, 0xc5, 0x2c, 0x6f, 0xbe, 0xa2, 0xb3,
         0xb4, 0x46, 0xea, 0x31, 0x12, 0x3b, 0xa8, 0x8c, 0xe6, 0x1e, 0x80, 0x00, 0x00, 0x01,
     ]);
+    static ref MASTER_KEY_256: Bytes = Bytes::from_static(&[
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+        0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d,
+        0x1e, 0x1f,
+    ]);
+    static ref ENCRYPTED_RTP_PACKET_256: Bytes = Bytes::from_static(&[
+        0x80, 0x0f, 0x12, 0x34, 0xde, 0xca, 0xfb, 0xad, 0xca, 0xfe, 0xba, 0xbe, 0x0a, 0xf7, 0xf2,
+        0x1e, 0x8a, 0x90, 0xbd, 0xad, 0x7a, 0x42, 0x5c, 0x9c, 0x31, 0xed, 0x4b, 0xb1, 0xd9, 0x02,
+        0x38, 0x91, 0x7e, 0x73, 0x90, 0xa2, 0x79, 0x35, 0x00, 0xe1, 0x68, 0x1a, 0xca, 0xea,
+    ]);
+    static ref ENCRYPTED_RTCP_PACKET_256: Bytes = Bytes::from_static(&[
+        0x81, 0xc8, 0x00, 0x0b, 0xca, 0xfe, 0xba, 0xbe, 0x8a, 0xbf, 0xaf, 0xa7, 0x80, 0x07, 0x0a,
+        0x90, 0xe8, 0x0f, 0x91, 0x61, 0xcf, 0x4a, 0xac, 0x08, 0x1e, 0xd9, 0x3a, 0xaa, 0x53, 0xc1,
+        0x5b, 0x0f, 0x3e, 0xd1, 0xa3, 0xb9, 0x84, 0x16, 0x7b, 0x03, 0x80, 0x00, 0x00, 0x01,
+    ]);
 }
 
 #[test]
@@ -303,3 +320,721 @@ fn test_decrypt_rtcp() {
 
     assert_eq!(gotten_decrypted_rtcp_packet, *DECRYPTED_RTCP_PACKET)
 }
+
+#[test]
+fn test_encrypt_decrypt_rtp_aes_256_gcm() {
+    let mut ctx = Context::new(
+        &MASTER_KEY_256,
+        &MASTER_SALT,
+        ProtectionProfile::AeadAes256Gcm,
+        None,
+        None,
+    )
+    .expect("Error creating srtp context");
+
+    let gotten_encrypted_rtp_packet = ctx
+        .encrypt_rtp(&DECRYPTED_RTP_PACKET)
+        .expect("Error encrypting rtp payload");
+    assert_eq!(gotten_encrypted_rtp_packet, *ENCRYPTED_RTP_PACKET_256);
+
+    let mut ctx = Context::new(
+        &MASTER_KEY_256,
+        &MASTER_SALT,
+        ProtectionProfile::AeadAes256Gcm,
+        None,
+        None,
+    )
+    .expect("Error creating srtp context");
+
+    let gotten_decrypted_rtp_packet = ctx
+        .decrypt_rtp(&ENCRYPTED_RTP_PACKET_256)
+        .expect("Error decrypting rtp payload");
+    assert_eq!(gotten_decrypted_rtp_packet, *DECRYPTED_RTP_PACKET);
+}
+
+#[test]
+fn test_encrypt_decrypt_rtcp_aes_256_gcm() {
+    let mut ctx = Context::new(
+        &MASTER_KEY_256,
+        &MASTER_SALT,
+        ProtectionProfile::AeadAes256Gcm,
+        None,
+        None,
+    )
+    .expect("Error creating srtp context");
+
+    let gotten_encrypted_rtcp_packet = ctx
+        .encrypt_rtcp(&DECRYPTED_RTCP_PACKET)
+        .expect("Error encrypting rtcp payload");
+    assert_eq!(gotten_encrypted_rtcp_packet, *ENCRYPTED_RTCP_PACKET_256);
+
+    let mut ctx = Context::new(
+        &MASTER_KEY_256,
+        &MASTER_SALT,
+        ProtectionProfile::AeadAes256Gcm,
+        None,
+        None,
+    )
+    .expect("Error creating srtp context");
+
+    let gotten_decrypted_rtcp_packet = ctx
+        .decrypt_rtcp(&ENCRYPTED_RTCP_PACKET_256)
+        .expect("Error decrypting rtcp payload");
+    assert_eq!(gotten_decrypted_rtcp_packet, *DECRYPTED_RTCP_PACKET);
+}
+
+const MKI: [u8; 4] = [0x01, 0x02, 0x03, 0x04];
+
+#[test]
+fn test_encrypt_decrypt_aead_with_mki() -> Result<()> {
+    let mut ctx = Context::new_with_mki(
+        &MASTER_KEY,
+        &MASTER_SALT,
+        &MKI,
+        ProtectionProfile::AeadAes128Gcm,
+        None,
+        None,
+    )?;
+
+    // Without an auth tag after the AEAD ciphertext, the MKI comes last.
+    let mut expected_rtp = ENCRYPTED_RTP_PACKET.to_vec();
+    expected_rtp.extend_from_slice(&MKI);
+    assert_eq!(ctx.encrypt_rtp(&DECRYPTED_RTP_PACKET)?, expected_rtp);
+
+    let mut expected_rtcp = ENCRYPTED_RTCP_PACKET.to_vec();
+    expected_rtcp.extend_from_slice(&MKI);
+    assert_eq!(ctx.encrypt_rtcp(&DECRYPTED_RTCP_PACKET)?, expected_rtcp);
+
+    let mut ctx = Context::new_with_mki(
+        &MASTER_KEY,
+        &MASTER_SALT,
+        &MKI,
+        ProtectionProfile::AeadAes128Gcm,
+        None,
+        None,
+    )?;
+    assert_eq!(ctx.decrypt_rtp(&expected_rtp)?, *DECRYPTED_RTP_PACKET);
+    assert_eq!(ctx.decrypt_rtcp(&expected_rtcp)?, *DECRYPTED_RTCP_PACKET);
+
+    Ok(())
+}
+
+#[test]
+fn test_encrypt_decrypt_aes_cm_with_mki() -> Result<()> {
+    let key_len = CIPHER_CONTEXT_ALGO.key_len();
+    let salt_len = CIPHER_CONTEXT_ALGO.salt_len();
+    let auth_tag_len = CIPHER_CONTEXT_ALGO.rtp_auth_tag_len();
+    let rtcp_auth_tag_len = CIPHER_CONTEXT_ALGO.rtcp_auth_tag_len();
+
+    let mut ctx = Context::new(
+        &vec![0; key_len],
+        &vec![0; salt_len],
+        CIPHER_CONTEXT_ALGO,
+        None,
+        None,
+    )?;
+    let encrypted_rtp = ctx.encrypt_rtp(&DECRYPTED_RTP_PACKET)?;
+    let encrypted_rtcp = ctx.encrypt_rtcp(&DECRYPTED_RTCP_PACKET)?;
+
+    let mut ctx = Context::new_with_mki(
+        &vec![0; key_len],
+        &vec![0; salt_len],
+        &MKI,
+        CIPHER_CONTEXT_ALGO,
+        None,
+        None,
+    )?;
+
+    // The MKI is not authenticated and sits in front of the auth tag.
+    let mki_offset = encrypted_rtp.len() - auth_tag_len;
+    let expected_rtp = [
+        &encrypted_rtp[..mki_offset],
+        &MKI,
+        &encrypted_rtp[mki_offset..],
+    ]
+    .concat();
+    assert_eq!(ctx.encrypt_rtp(&DECRYPTED_RTP_PACKET)?, expected_rtp);
+
+    let mki_offset = encrypted_rtcp.len() - rtcp_auth_tag_len;
+    let expected_rtcp = [
+        &encrypted_rtcp[..mki_offset],
+        &MKI,
+        &encrypted_rtcp[mki_offset..],
+    ]
+    .concat();
+    assert_eq!(ctx.encrypt_rtcp(&DECRYPTED_RTCP_PACKET)?, expected_rtcp);
+
+    let mut ctx = Context::new_with_mki(
+        &vec![0; key_len],
+        &vec![0; salt_len],
+        &MKI,
+        CIPHER_CONTEXT_ALGO,
+        None,
+        None,
+    )?;
+    assert_eq!(ctx.decrypt_rtp(&expected_rtp)?, *DECRYPTED_RTP_PACKET);
+    assert_eq!(ctx.decrypt_rtcp(&expected_rtcp)?, *DECRYPTED_RTCP_PACKET);
+
+    Ok(())
+}
+
+#[test]
+fn test_mki_key_selection() -> Result<()> {
+    let other_mki = [0x05, 0x06, 0x07, 0x08];
+    let other_master_key = Bytes::from_static(&[0x55; 16]);
+    let new_context = || {
+        Context::new_with_mki(
+            &MASTER_KEY,
+            &MASTER_SALT,
+            &MKI,
+            ProtectionProfile::AeadAes128Gcm,
+            None,
+            None,
+        )
+    };
+
+    let mut encrypt_context = new_context()?;
+    let mut decrypt_context = new_context()?;
+
+    assert_eq!(
+        encrypt_context.set_send_mki(&other_mki),
+        Err(Error::ErrMkiNotFound)
+    );
+    assert_eq!(
+        encrypt_context.add_cipher_for_mki(&MKI, &other_master_key, &MASTER_SALT),
+        Err(Error::ErrMkiAlreadyInUse)
+    );
+    assert_eq!(
+        encrypt_context.add_cipher_for_mki(&[0x05], &other_master_key, &MASTER_SALT),
+        Err(Error::MkiLength(4, 1))
+    );
+    encrypt_context.add_cipher_for_mki(&other_mki, &other_master_key, &MASTER_SALT)?;
+    decrypt_context.add_cipher_for_mki(&other_mki, &other_master_key, &MASTER_SALT)?;
+
+    encrypt_context.set_send_mki(&other_mki)?;
+    assert_eq!(encrypt_context.send_mki(), &other_mki);
+    let encrypted = encrypt_context.encrypt_rtp(&DECRYPTED_RTP_PACKET)?;
+    assert_ne!(
+        &encrypted[..encrypted.len() - MKI.len()],
+        &ENCRYPTED_RTP_PACKET[..]
+    );
+    assert_eq!(&encrypted[encrypted.len() - MKI.len()..], &other_mki);
+    assert_eq!(
+        decrypt_context.decrypt_rtp(&encrypted)?,
+        *DECRYPTED_RTP_PACKET
+    );
+
+    assert_eq!(
+        encrypt_context.remove_mki(&other_mki),
+        Err(Error::ErrMkiAlreadyInUse)
+    );
+    encrypt_context.remove_mki(&MKI)?;
+    assert_eq!(encrypt_context.remove_mki(&MKI), Err(Error::ErrMkiNotFound));
+
+    decrypt_context.remove_mki(&other_mki)?;
+    assert_eq!(
+        decrypt_context.decrypt_rtcp(&encrypt_context.encrypt_rtcp(&DECRYPTED_RTCP_PACKET)?),
+        Err(Error::ErrMkiNotFound)
+    );
+
+    let mut ctx = Context::new(
+        &MASTER_KEY,
+        &MASTER_SALT,
+        ProtectionProfile::AeadAes128Gcm,
+        None,
+        None,
+    )?;
+    assert_eq!(
+        ctx.add_cipher_for_mki(&other_mki, &other_master_key, &MASTER_SALT),
+        Err(Error::ErrMkiNotEnabled)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_rekey() -> Result<()> {
+    let new_master_key = Bytes::from_static(&[0x55; 16]);
+    let new_context = || {
+        Context::new(
+            &MASTER_KEY,
+            &MASTER_SALT,
+            ProtectionProfile::AeadAes128Gcm,
+            None,
+            None,
+        )
+    };
+
+    let mut encrypt_context = new_context()?;
+    encrypt_context.rekey(&[], &new_master_key, &MASTER_SALT, Duration::ZERO)?;
+    let encrypted_rtp = encrypt_context.encrypt_rtp(&DECRYPTED_RTP_PACKET)?;
+    let encrypted_rtcp = encrypt_context.encrypt_rtcp(&DECRYPTED_RTCP_PACKET)?;
+    assert_ne!(encrypted_rtp, *ENCRYPTED_RTP_PACKET);
+
+    // Both keys are accepted during the overlap window.
+    let mut decrypt_context = new_context()?;
+    decrypt_context.rekey(&[], &new_master_key, &MASTER_SALT, Duration::from_secs(60))?;
+    assert_eq!(
+        decrypt_context.decrypt_rtp(&encrypted_rtp)?,
+        *DECRYPTED_RTP_PACKET
+    );
+    assert_eq!(
+        decrypt_context.decrypt_rtp(&ENCRYPTED_RTP_PACKET)?,
+        *DECRYPTED_RTP_PACKET
+    );
+    assert_eq!(
+        decrypt_context.decrypt_rtcp(&encrypted_rtcp)?,
+        *DECRYPTED_RTCP_PACKET
+    );
+    assert_eq!(
+        decrypt_context.decrypt_rtcp(&ENCRYPTED_RTCP_PACKET)?,
+        *DECRYPTED_RTCP_PACKET
+    );
+
+    // Only the new key is accepted once the window is over.
+    let mut decrypt_context = new_context()?;
+    decrypt_context.rekey(&[], &new_master_key, &MASTER_SALT, Duration::ZERO)?;
+    assert_eq!(
+        decrypt_context.decrypt_rtp(&encrypted_rtp)?,
+        *DECRYPTED_RTP_PACKET
+    );
+    assert!(decrypt_context.decrypt_rtp(&ENCRYPTED_RTP_PACKET).is_err());
+
+    assert_eq!(
+        decrypt_context.rekey(&MKI, &new_master_key, &MASTER_SALT, Duration::ZERO),
+        Err(Error::MkiLength(0, 4))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_rekey_with_mki() -> Result<()> {
+    let new_mki = [0x05, 0x06, 0x07, 0x08];
+    let new_master_key = Bytes::from_static(&[0x55; 16]);
+    let new_context = || {
+        Context::new_with_mki(
+            &MASTER_KEY,
+            &MASTER_SALT,
+            &MKI,
+            ProtectionProfile::AeadAes128Gcm,
+            None,
+            None,
+        )
+    };
+
+    let mut encrypt_context = new_context()?;
+    let old_encrypted = encrypt_context.encrypt_rtp(&DECRYPTED_RTP_PACKET)?;
+    assert_eq!(
+        encrypt_context.rekey(&MKI, &new_master_key, &MASTER_SALT, Duration::ZERO),
+        Err(Error::ErrMkiAlreadyInUse)
+    );
+    encrypt_context.rekey(&new_mki, &new_master_key, &MASTER_SALT, Duration::ZERO)?;
+    assert_eq!(encrypt_context.send_mki(), &new_mki);
+    let new_encrypted = encrypt_context.encrypt_rtp(&DECRYPTED_RTP_PACKET)?;
+
+    let mut decrypt_context = new_context()?;
+    decrypt_context.rekey(
+        &new_mki,
+        &new_master_key,
+        &MASTER_SALT,
+        Duration::from_secs(60),
+    )?;
+    assert_eq!(
+        decrypt_context.decrypt_rtp(&old_encrypted)?,
+        *DECRYPTED_RTP_PACKET
+    );
+    assert_eq!(
+        decrypt_context.decrypt_rtp(&new_encrypted)?,
+        *DECRYPTED_RTP_PACKET
+    );
+
+    let mut decrypt_context = new_context()?;
+    decrypt_context.rekey(&new_mki, &new_master_key, &MASTER_SALT, Duration::ZERO)?;
+    assert_eq!(
+        decrypt_context.decrypt_rtp(&old_encrypted),
+        Err(Error::ErrMkiNotFound)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_ssrc_stats() -> Result<()> {
+    let ssrc = 0xcafebabe;
+    let new_context = || {
+        Context::new(
+            &MASTER_KEY,
+            &[0; 14],
+            ProtectionProfile::Aes128CmHmacSha1_80,
+            None,
+            None,
+        )
+    };
+
+    let mut encrypt_context = new_context()?;
+    let encrypted_rtp = encrypt_context.encrypt_rtp(&DECRYPTED_RTP_PACKET)?;
+    let encrypted_rtcp = encrypt_context.encrypt_rtcp(&DECRYPTED_RTCP_PACKET)?;
+
+    let mut decrypt_context = new_context()?;
+    assert_eq!(decrypt_context.srtp_stats(ssrc), None);
+    assert_eq!(decrypt_context.srtp_replay_protection_window(ssrc), None);
+    decrypt_context.set_srtp_replay_protection_window(ssrc, 64);
+    decrypt_context.set_srtcp_replay_protection_window(ssrc, 32);
+    assert_eq!(
+        decrypt_context.srtp_replay_protection_window(ssrc),
+        Some(64)
+    );
+    assert_eq!(
+        decrypt_context.srtcp_replay_protection_window(ssrc),
+        Some(32)
+    );
+
+    decrypt_context.decrypt_rtp(&encrypted_rtp)?;
+    assert!(decrypt_context.decrypt_rtp(&encrypted_rtp).is_err());
+
+    let mut tampered = encrypted_rtp.to_vec();
+    tampered[2] ^= 0x01;
+    assert_eq!(
+        decrypt_context.decrypt_rtp(&tampered),
+        Err(Error::RtpFailedToVerifyAuthTag)
+    );
+    let mut cryptex = encrypted_rtp[..12].to_vec();
+    cryptex[0] |= 0x10;
+    cryptex[3] ^= 0x01;
+    cryptex.extend_from_slice(&[0xc0, 0xde, 0x00, 0x00]);
+    cryptex.extend_from_slice(&encrypted_rtp[12..]);
+    assert_eq!(
+        decrypt_context.decrypt_rtp(&cryptex),
+        Err(Error::ErrCryptexDisabled)
+    );
+
+    assert_eq!(
+        decrypt_context.srtp_stats(ssrc),
+        Some(SsrcStats {
+            auth_failures: 1,
+            replay_drops: 1,
+            decrypt_errors: 1,
+        })
+    );
+
+    decrypt_context.decrypt_rtcp(&encrypted_rtcp)?;
+    assert!(decrypt_context.decrypt_rtcp(&encrypted_rtcp).is_err());
+    assert_eq!(
+        decrypt_context.srtcp_stats(ssrc),
+        Some(SsrcStats {
+            replay_drops: 1,
+            ..Default::default()
+        })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_encrypt_decrypt_in_place() -> Result<()> {
+    for (profile, master_salt) in [
+        (ProtectionProfile::Aes128CmHmacSha1_80, &[0u8; 14][..]),
+        (ProtectionProfile::AeadAes128Gcm, &MASTER_SALT[..]),
+    ] {
+        let new_context =
+            || Context::new_with_mki(&MASTER_KEY, master_salt, &MKI, profile, None, None);
+
+        let mut encrypt_context = new_context()?;
+        let expected_rtp = new_context()?.encrypt_rtp(&DECRYPTED_RTP_PACKET)?;
+        let expected_rtcp = new_context()?.encrypt_rtcp(&DECRYPTED_RTCP_PACKET)?;
+
+        // Packets with enough spare capacity aren't reallocated.
+        let mut rtp_packet =
+            BytesMut::with_capacity(DECRYPTED_RTP_PACKET.len() + encrypt_context.rtp_overhead());
+        rtp_packet.extend_from_slice(&DECRYPTED_RTP_PACKET);
+        let rtp_buffer = rtp_packet.as_ptr();
+        encrypt_context.encrypt_rtp_in_place(&mut rtp_packet)?;
+        assert_eq!(rtp_packet, expected_rtp, "{profile:?}");
+        assert_eq!(rtp_packet.as_ptr(), rtp_buffer, "{profile:?}");
+
+        let mut rtcp_packet =
+            BytesMut::with_capacity(DECRYPTED_RTCP_PACKET.len() + encrypt_context.rtcp_overhead());
+        rtcp_packet.extend_from_slice(&DECRYPTED_RTCP_PACKET);
+        let rtcp_buffer = rtcp_packet.as_ptr();
+        encrypt_context.encrypt_rtcp_in_place(&mut rtcp_packet)?;
+        assert_eq!(rtcp_packet, expected_rtcp, "{profile:?}");
+        assert_eq!(rtcp_packet.as_ptr(), rtcp_buffer, "{profile:?}");
+
+        let mut decrypt_context = new_context()?;
+        decrypt_context.decrypt_rtp_in_place(&mut rtp_packet)?;
+        assert_eq!(rtp_packet, *DECRYPTED_RTP_PACKET, "{profile:?}");
+        assert_eq!(rtp_packet.as_ptr(), rtp_buffer, "{profile:?}");
+
+        decrypt_context.decrypt_rtcp_in_place(&mut rtcp_packet)?;
+        assert_eq!(rtcp_packet, *DECRYPTED_RTCP_PACKET, "{profile:?}");
+        assert_eq!(rtcp_packet.as_ptr(), rtcp_buffer, "{profile:?}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_key_lifetime() -> Result<()> {
+    let events = Arc::new(Mutex::new(vec![]));
+    let mut ctx = Context::new(
+        &MASTER_KEY,
+        &MASTER_SALT,
+        ProtectionProfile::AeadAes128Gcm,
+        None,
+        None,
+    )?;
+    ctx.set_key_lifetime(32, 16);
+    let events2 = Arc::clone(&events);
+    ctx.on_key_lifetime(Box::new(move |event| events2.lock().unwrap().push(event)));
+
+    for _ in 0..30 {
+        ctx.encrypt_rtp(&DECRYPTED_RTP_PACKET)?;
+    }
+    assert_eq!(
+        *events.lock().unwrap(),
+        [KeyLifetimeEvent::Srtp { remaining: 2 }]
+    );
+    for _ in 0..2 {
+        ctx.encrypt_rtp(&DECRYPTED_RTP_PACKET)?;
+    }
+    assert_eq!(
+        ctx.encrypt_rtp(&DECRYPTED_RTP_PACKET),
+        Err(Error::ErrSrtpKeyLifetimeExhausted)
+    );
+
+    for _ in 0..16 {
+        ctx.encrypt_rtcp(&DECRYPTED_RTCP_PACKET)?;
+    }
+    assert_eq!(
+        ctx.encrypt_rtcp(&DECRYPTED_RTCP_PACKET),
+        Err(Error::ErrSrtcpKeyLifetimeExhausted)
+    );
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            KeyLifetimeEvent::Srtp { remaining: 2 },
+            KeyLifetimeEvent::Srtcp { remaining: 1 }
+        ]
+    );
+    assert_eq!(
+        ctx.key_usage(),
+        KeyUsage {
+            srtp_packets: 32,
+            srtcp_packets: 16
+        }
+    );
+
+    // A new key starts a new lifetime.
+    ctx.rekey(&[], &[0x55; 16], &MASTER_SALT, Duration::ZERO)?;
+    assert_eq!(ctx.key_usage(), KeyUsage::default());
+    ctx.encrypt_rtp(&DECRYPTED_RTP_PACKET)?;
+    ctx.encrypt_rtcp(&DECRYPTED_RTCP_PACKET)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_key_lifetime_short() -> Result<()> {
+    let events = Arc::new(Mutex::new(vec![]));
+    let mut ctx = Context::new(
+        &MASTER_KEY,
+        &MASTER_SALT,
+        ProtectionProfile::AeadAes128Gcm,
+        None,
+        None,
+    )?;
+    ctx.set_key_lifetime(4, 1);
+    let events2 = Arc::clone(&events);
+    ctx.on_key_lifetime(Box::new(move |event| events2.lock().unwrap().push(event)));
+
+    // A lifetime shorter than 16 packets is warned about before the last packet.
+    for _ in 0..3 {
+        ctx.encrypt_rtp(&DECRYPTED_RTP_PACKET)?;
+    }
+    assert_eq!(
+        *events.lock().unwrap(),
+        [KeyLifetimeEvent::Srtp { remaining: 1 }]
+    );
+    ctx.encrypt_rtp(&DECRYPTED_RTP_PACKET)?;
+    ctx.encrypt_rtcp(&DECRYPTED_RTCP_PACKET)?;
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            KeyLifetimeEvent::Srtp { remaining: 1 },
+            KeyLifetimeEvent::Srtcp { remaining: 0 }
+        ]
+    );
+
+    // Lowering the lifetime past the threshold still warns, once.
+    ctx.rekey(&[], &[0x55; 16], &MASTER_SALT, Duration::ZERO)?;
+    events.lock().unwrap().clear();
+    ctx.set_key_lifetime(1000, 16);
+    for _ in 0..305 {
+        ctx.encrypt_rtp(&DECRYPTED_RTP_PACKET)?;
+    }
+    ctx.set_key_lifetime(320, 16);
+    for _ in 0..2 {
+        ctx.encrypt_rtp(&DECRYPTED_RTP_PACKET)?;
+    }
+    assert_eq!(
+        *events.lock().unwrap(),
+        [KeyLifetimeEvent::Srtp { remaining: 14 }]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_srtcp_encryption_policy() -> Result<()> {
+    for (profile, master_salt) in [
+        (ProtectionProfile::Aes128CmHmacSha1_80, &[0; 14][..]),
+        (ProtectionProfile::AeadAes128Gcm, &MASTER_SALT[..]),
+    ] {
+        let new_context = |policy| -> Result<Context> {
+            let mut ctx = Context::new(&MASTER_KEY, master_salt, profile, None, None)?;
+            ctx.set_srtcp_encryption_policy(policy);
+            Ok(ctx)
+        };
+
+        // Unencrypted packets carry the payload in the clear with the E flag cleared.
+        let mut encrypt_context = new_context(SrtcpEncryptionPolicy::Disabled)?;
+        let unencrypted = encrypt_context.encrypt_rtcp(&DECRYPTED_RTCP_PACKET)?;
+        assert_eq!(
+            &unencrypted[..DECRYPTED_RTCP_PACKET.len()],
+            &DECRYPTED_RTCP_PACKET[..],
+            "{profile:?}"
+        );
+        assert!(
+            !encrypt_context.cipher.is_rtcp_encrypted(&unencrypted),
+            "{profile:?}"
+        );
+
+        for policy in [
+            SrtcpEncryptionPolicy::Disabled,
+            SrtcpEncryptionPolicy::Enabled,
+        ] {
+            let mut decrypt_context = new_context(policy)?;
+            assert_eq!(
+                decrypt_context.decrypt_rtcp(&unencrypted)?,
+                *DECRYPTED_RTCP_PACKET,
+                "{profile:?}"
+            );
+        }
+
+        // Unencrypted packets are still authenticated.
+        let mut tampered = unencrypted.to_vec();
+        tampered[10] ^= 0xff;
+        let mut decrypt_context = new_context(SrtcpEncryptionPolicy::Enabled)?;
+        assert!(
+            decrypt_context.decrypt_rtcp(&tampered).is_err(),
+            "{profile:?}"
+        );
+        // no state is kept for an SSRC until one of its packets is authenticated
+        assert_eq!(decrypt_context.srtcp_stats(0xcafebabe), None);
+        assert_eq!(decrypt_context.srtcp_unknown_ssrc_stats().auth_failures, 1);
+
+        let mut decrypt_context = new_context(SrtcpEncryptionPolicy::Required)?;
+        assert_eq!(
+            decrypt_context.decrypt_rtcp(&unencrypted),
+            Err(Error::ErrSrtcpEncryptionRequired),
+            "{profile:?}"
+        );
+
+        let mut encrypt_context = new_context(SrtcpEncryptionPolicy::Required)?;
+        let encrypted = encrypt_context.encrypt_rtcp(&DECRYPTED_RTCP_PACKET)?;
+        assert_ne!(
+            &encrypted[..DECRYPTED_RTCP_PACKET.len()],
+            &DECRYPTED_RTCP_PACKET[..],
+            "{profile:?}"
+        );
+        assert_eq!(
+            decrypt_context.decrypt_rtcp(&encrypted)?,
+            *DECRYPTED_RTCP_PACKET,
+            "{profile:?}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_ssrc_state_limits() -> Result<()> {
+    let new_context = || {
+        Context::new(
+            &MASTER_KEY,
+            &MASTER_SALT,
+            ProtectionProfile::AeadAes128Gcm,
+            None,
+            None,
+        )
+    };
+    let mut encrypt_context = new_context()?;
+    let mut encrypt = |ssrc: u32| -> Result<Bytes> {
+        let mut packet = DECRYPTED_RTP_PACKET.to_vec();
+        packet[8..12].copy_from_slice(&ssrc.to_be_bytes());
+        encrypt_context.encrypt_rtp(&packet)
+    };
+    let packets = [encrypt(1)?, encrypt(2)?, encrypt(3)?];
+
+    let mut decrypt_context = new_context()?;
+    decrypt_context.set_ssrc_state_limits(SsrcStateLimits {
+        max_ssrcs: Some(2),
+        idle_timeout: None,
+    });
+    decrypt_context.decrypt_rtp(&packets[0])?;
+    decrypt_context.decrypt_rtp(&packets[1])?;
+    decrypt_context.decrypt_rtp(&packets[0])?;
+    assert_eq!(decrypt_context.srtp_ssrc_count(), 2);
+
+    // The least recently used SSRC makes room for the new one.
+    decrypt_context.decrypt_rtp(&packets[2])?;
+    assert_eq!(decrypt_context.srtp_ssrc_count(), 2);
+    assert!(decrypt_context.srtp_stats(1).is_some());
+    assert!(decrypt_context.srtp_stats(2).is_none());
+    assert!(decrypt_context.srtp_stats(3).is_some());
+
+    // An evicted SSRC starts over with a fresh replay detector.
+    decrypt_context.decrypt_rtp(&packets[1])?;
+    assert!(decrypt_context.srtp_stats(1).is_none());
+
+    let mut decrypt_context = new_context()?;
+    decrypt_context.set_ssrc_state_limits(SsrcStateLimits {
+        max_ssrcs: None,
+        idle_timeout: Some(Duration::from_millis(20)),
+    });
+    decrypt_context.decrypt_rtp(&packets[0])?;
+    decrypt_context.decrypt_rtp(&packets[1])?;
+    std::thread::sleep(Duration::from_millis(40));
+    decrypt_context.decrypt_rtp(&packets[1])?;
+    assert_eq!(decrypt_context.srtp_ssrc_count(), 2);
+    decrypt_context.decrypt_rtp(&packets[2])?;
+    assert_eq!(decrypt_context.srtp_ssrc_count(), 2);
+    assert!(decrypt_context.srtp_stats(1).is_none());
+
+    // Forged packets of unknown SSRCs neither evict the genuine SSRCs nor get state.
+    let mut decrypt_context = new_context()?;
+    decrypt_context.set_ssrc_state_limits(SsrcStateLimits {
+        max_ssrcs: Some(2),
+        idle_timeout: None,
+    });
+    decrypt_context.decrypt_rtp(&packets[0])?;
+    decrypt_context.decrypt_rtp(&packets[1])?;
+    for ssrc in 10..20u32 {
+        let mut forged = packets[2].to_vec();
+        forged[8..12].copy_from_slice(&ssrc.to_be_bytes());
+        assert!(decrypt_context.decrypt_rtp(&forged).is_err());
+    }
+    assert_eq!(decrypt_context.srtp_ssrc_count(), 2);
+    assert!(decrypt_context.srtp_stats(1).is_some());
+    assert!(decrypt_context.srtp_stats(2).is_some());
+    assert_eq!(
+        decrypt_context.srtp_unknown_ssrc_stats(),
+        SsrcStats {
+            auth_failures: 10,
+            ..Default::default()
+        }
+    );
+
+    Ok(())
+}
@@ -6,29 +6,58 @@ mod srtcp_test;
 mod srtp_test;
 
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
+use bytes::BytesMut;
+use rtp::sequence::SequenceTracker;
 use util::replay_detector::*;
 
 use crate::cipher::cipher_aead_aes_gcm::*;
 use crate::cipher::cipher_aes_cm_hmac_sha1::*;
 use crate::cipher::*;
+use crate::cryptex::{self, CryptexPolicy};
 use crate::error::{Error, Result};
+use crate::key_derivation::SRTCP_INDEX_SIZE;
 use crate::option::*;
 use crate::protection_profile::*;
 
 pub mod srtcp;
 pub mod srtp;
 
-const MAX_ROC_DISORDER: u16 = 100;
+/// Maximum number of SRTP packets protected with one master key (RFC 3711 section 9.2).
+pub const MAX_SRTP_KEY_LIFETIME: u64 = 1 << 48;
+/// Maximum number of SRTCP packets protected with one master key (RFC 3711 section 9.2).
+pub const MAX_SRTCP_KEY_LIFETIME: u64 = 1 << 31;
+
+// A KeyLifetimeEvent is raised once this fraction of the key lifetime is left.
+const KEY_LIFETIME_WARNING_DIVISOR: u64 = 16;
 
 /// Encrypt/Decrypt state for a single SRTP SSRC
-#[derive(Default)]
 pub(crate) struct SrtpSsrcState {
     ssrc: u32,
-    rollover_counter: u32,
-    rollover_has_processed: bool,
-    last_sequence_number: u16,
+    sequence: SequenceTracker,
     replay_detector: Option<Box<dyn ReplayDetector + Send + 'static>>,
+    replay_window: Option<usize>,
+    stats: SsrcStats,
+    last_used: Option<Instant>,
+}
+
+impl Default for SrtpSsrcState {
+    fn default() -> Self {
+        SrtpSsrcState {
+            ssrc: 0,
+            // The rollover counter is guessed from the closest index, authentication
+            // tells whether the guess was right (RFC 3711 section 3.3.1).
+            sequence: SequenceTracker::with_limits(
+                MAX_SEQUENCE_NUMBER / 2,
+                MAX_SEQUENCE_NUMBER / 2 + 1,
+            ),
+            replay_detector: None,
+            replay_window: None,
+            stats: SsrcStats::default(),
+            last_used: None,
+        }
+    }
 }
 
 /// Encrypt/Decrypt state for a single SRTCP SSRC
@@ -37,78 +66,179 @@ pub(crate) struct SrtcpSsrcState {
     srtcp_index: usize,
     ssrc: u32,
     replay_detector: Option<Box<dyn ReplayDetector + Send + 'static>>,
+    replay_window: Option<usize>,
+    stats: SsrcStats,
+    last_used: Option<Instant>,
 }
 
-impl SrtpSsrcState {
-    pub fn next_rollover_count(&self, sequence_number: u16) -> u32 {
-        let mut roc = self.rollover_counter;
+/// Counters of the packets a decrypting context dropped for a single SSRC
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SsrcStats {
+    /// Packets whose authentication tag did not verify.
+    pub auth_failures: u64,
+    /// Packets rejected by the replay detector.
+    pub replay_drops: u64,
+    /// Packets that could not be decrypted for any other reason.
+    pub decrypt_errors: u64,
+}
 
-        if !self.rollover_has_processed {
-        } else if sequence_number == 0 {
-            // We exactly hit the rollover count
+impl SsrcStats {
+    fn record(&mut self, err: &Error) {
+        match err {
+            Error::SrtpSsrcDuplicated(..) | Error::SrtcpSsrcDuplicated(..) => {
+                self.replay_drops += 1
+            }
+            Error::RtpFailedToVerifyAuthTag
+            | Error::RtcpFailedToVerifyAuthTag
+            | Error::ErrFailedToVerifyAuthTag
+            | Error::AesGcm(_) => self.auth_failures += 1,
+            _ => self.decrypt_errors += 1,
+        }
+    }
+}
 
-            // Only update rolloverCounter if lastSequenceNumber is greater then MAX_ROCDISORDER
-            // otherwise we already incremented for disorder
-            if self.last_sequence_number > MAX_ROC_DISORDER {
-                roc += 1;
+/// SsrcStateLimits bounds the per-SSRC state a decrypting context keeps, so that
+/// seeing many transient SSRCs doesn't grow memory without bound. Evicted SSRCs
+/// start over with a fresh replay detector and rollover counter, dropping any replay
+/// window set for them. Encrypting contexts must not evict, as it would reuse keystream.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SsrcStateLimits {
+    /// Maximum number of SSRCs to keep state for, evicting the least recently used one
+    /// to make room for a new SSRC. Unbounded if None.
+    pub max_ssrcs: Option<usize>,
+    /// Evict the state of SSRCs unused for this long, checked whenever a new SSRC is seen.
+    /// Kept forever if None.
+    pub idle_timeout: Option<Duration>,
+}
+
+impl SsrcStateLimits {
+    fn is_bounded(&self) -> bool {
+        self.max_ssrcs.is_some() || self.idle_timeout.is_some()
+    }
+
+    /// Evicts states to make room for a new SSRC at `now`.
+    fn make_room<S>(
+        &self,
+        states: &mut HashMap<u32, S>,
+        now: Instant,
+        last_used: impl Fn(&S) -> Option<Instant>,
+    ) {
+        if let Some(idle_timeout) = self.idle_timeout {
+            states
+                .retain(|_, s| last_used(s).is_some_and(|t| now.duration_since(t) < idle_timeout));
+        }
+
+        if let Some(max_ssrcs) = self.max_ssrcs {
+            while !states.is_empty() && states.len() >= max_ssrcs {
+                let lru = states
+                    .iter()
+                    .min_by_key(|(_, s)| last_used(s))
+                    .map(|(ssrc, _)| *ssrc);
+                if let Some(ssrc) = lru {
+                    states.remove(&ssrc);
+                }
             }
-        } else if self.last_sequence_number < MAX_ROC_DISORDER
-            && sequence_number > (MAX_SEQUENCE_NUMBER - MAX_ROC_DISORDER)
-        {
-            // Our last sequence number incremented because we crossed 0, but then our current number was within MAX_ROCDISORDER of the max
-            // So we fell behind, drop to account for jitter
-            roc -= 1;
-        } else if sequence_number < MAX_ROC_DISORDER
-            && self.last_sequence_number > (MAX_SEQUENCE_NUMBER - MAX_ROC_DISORDER)
-        {
-            // our current is within a MAX_ROCDISORDER of 0
-            // and our last sequence number was a high sequence number, increment to account for jitter
-            roc += 1;
         }
+    }
+}
+
+/// Packets protected with a master key, counted by an encrypting context.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KeyUsage {
+    pub srtp_packets: u64,
+    pub srtcp_packets: u64,
+}
+
+/// Whether the KeyLifetimeEvents were raised for a master key, so each is raised once.
+#[derive(Default, Debug, Copy, Clone)]
+struct KeyLifetimeWarnings {
+    srtp: bool,
+    srtcp: bool,
+}
 
-        roc
+/// KeyLifetimeEvent is raised by an encrypting context when the master key in use nears
+/// the end of its lifetime, so it can be replaced with `Context::rekey` before packets
+/// get rejected with ErrSrtpKeyLifetimeExhausted or ErrSrtcpKeyLifetimeExhausted.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KeyLifetimeEvent {
+    /// Only `remaining` more SRTP packets can be protected with the key.
+    Srtp { remaining: u64 },
+    /// Only `remaining` more SRTCP packets can be protected with the key.
+    Srtcp { remaining: u64 },
+}
+
+pub type KeyLifetimeHandlerFn = Box<dyn FnMut(KeyLifetimeEvent) + Send>;
+
+/// SrtcpEncryptionPolicy controls whether the payload of SRTCP packets is encrypted.
+/// SRTCP packets are always authenticated, the E flag tells the receiver whether the
+/// payload was encrypted as well (RFC 3711 section 3.4).
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SrtcpEncryptionPolicy {
+    /// Packets are only authenticated when sending; both forms are accepted when receiving.
+    Disabled,
+    /// Packets are encrypted when sending; both forms are accepted when receiving.
+    #[default]
+    Enabled,
+    /// Packets are encrypted when sending; unencrypted packets are rejected when receiving.
+    Required,
+}
+
+impl SrtpSsrcState {
+    pub fn next_rollover_count(&self, sequence_number: u16) -> u32 {
+        (self.sequence.extend(sequence_number) >> 16) as u32
     }
 
-    /// https://tools.ietf.org/html/rfc3550#appendix-A.1
+    /// https://tools.ietf.org/html/rfc3711#section-3.3.1
     pub fn update_rollover_count(&mut self, sequence_number: u16) {
-        if !self.rollover_has_processed {
-            self.rollover_has_processed = true;
-        } else if sequence_number == 0 {
-            // We exactly hit the rollover count
-
-            // Only update rolloverCounter if lastSequenceNumber is greater then MAX_ROCDISORDER
-            // otherwise we already incremented for disorder
-            if self.last_sequence_number > MAX_ROC_DISORDER {
-                self.rollover_counter += 1;
-            }
-        } else if self.last_sequence_number < MAX_ROC_DISORDER
-            && sequence_number > (MAX_SEQUENCE_NUMBER - MAX_ROC_DISORDER)
-        {
-            // Our last sequence number incremented because we crossed 0, but then our current number was within MAX_ROCDISORDER of the max
-            // So we fell behind, drop to account for jitter
-            self.rollover_counter -= 1;
-        } else if sequence_number < MAX_ROC_DISORDER
-            && self.last_sequence_number > (MAX_SEQUENCE_NUMBER - MAX_ROC_DISORDER)
-        {
-            // our current is within a MAX_ROCDISORDER of 0
-            // and our last sequence number was a high sequence number, increment to account for jitter
-            self.rollover_counter += 1;
-        }
-        self.last_sequence_number = sequence_number;
+        self.sequence.update(sequence_number);
+    }
+
+    fn rollover_counter(&self) -> u32 {
+        self.sequence.roll_over_count() as u32
     }
 }
 
+/// Master key replaced by `Context::rekey`, accepted for decryption until `expires_at`.
+struct PreviousKey {
+    mki: Vec<u8>,
+    cipher: Box<dyn Cipher + Send>,
+    expires_at: Instant,
+}
+
 /// Context represents a SRTP cryptographic context
 /// Context can only be used for one-way operations
 /// it must either used ONLY for encryption or ONLY for decryption
 pub struct Context {
+    profile: ProtectionProfile,
     cipher: Box<dyn Cipher + Send>,
 
+    /// MKI of `cipher`, empty if MKIs are not in use.
+    mki: Vec<u8>,
+    /// Ciphers of the other master keys, indexed by MKI.
+    mki_ciphers: HashMap<Vec<u8>, Box<dyn Cipher + Send>>,
+    previous_key: Option<PreviousKey>,
+
     srtp_ssrc_states: HashMap<u32, SrtpSsrcState>,
     srtcp_ssrc_states: HashMap<u32, SrtcpSsrcState>,
+    ssrc_state_limits: SsrcStateLimits,
+    /// Counters of the packets dropped for the SSRCs no state is kept for, such as
+    /// forged ones; state is only created once a packet is authenticated.
+    srtp_unknown_ssrc_stats: SsrcStats,
+    srtcp_unknown_ssrc_stats: SsrcStats,
 
     new_srtp_replay_detector: ContextOption,
     new_srtcp_replay_detector: ContextOption,
+
+    cryptex_policy: CryptexPolicy,
+    srtcp_encryption_policy: SrtcpEncryptionPolicy,
+
+    /// Usage of `cipher`, and of the other master keys which have been used for encryption.
+    key_usage: KeyUsage,
+    key_lifetime_warnings: KeyLifetimeWarnings,
+    mki_key_usages: HashMap<Vec<u8>, (KeyUsage, KeyLifetimeWarnings)>,
+    srtp_key_lifetime: u64,
+    srtcp_key_lifetime: u64,
+    key_lifetime_handler: Option<KeyLifetimeHandlerFn>,
 }
 
 impl Context {
@@ -120,6 +250,70 @@ impl Context {
         srtp_ctx_opt: Option<ContextOption>,
         srtcp_ctx_opt: Option<ContextOption>,
     ) -> Result<Context> {
+        Context::new_with_mki(
+            master_key,
+            master_salt,
+            &[],
+            profile,
+            srtp_ctx_opt,
+            srtcp_ctx_opt,
+        )
+    }
+
+    /// new_with_mki creates a new SRTP Context that identifies its master key with
+    /// the given MKI (RFC 3711 section 3.1). All MKIs of the context must have the
+    /// same length. An empty MKI disables MKIs.
+    pub fn new_with_mki(
+        master_key: &[u8],
+        master_salt: &[u8],
+        mki: &[u8],
+        profile: ProtectionProfile,
+        srtp_ctx_opt: Option<ContextOption>,
+        srtcp_ctx_opt: Option<ContextOption>,
+    ) -> Result<Context> {
+        let cipher = Context::new_cipher(profile, master_key, master_salt)?;
+
+        let srtp_ctx_opt = if let Some(ctx_opt) = srtp_ctx_opt {
+            ctx_opt
+        } else {
+            srtp_no_replay_protection()
+        };
+
+        let srtcp_ctx_opt = if let Some(ctx_opt) = srtcp_ctx_opt {
+            ctx_opt
+        } else {
+            srtcp_no_replay_protection()
+        };
+
+        Ok(Context {
+            profile,
+            cipher,
+            mki: mki.to_vec(),
+            mki_ciphers: HashMap::new(),
+            previous_key: None,
+            srtp_ssrc_states: HashMap::new(),
+            srtcp_ssrc_states: HashMap::new(),
+            ssrc_state_limits: SsrcStateLimits::default(),
+            srtp_unknown_ssrc_stats: SsrcStats::default(),
+            srtcp_unknown_ssrc_stats: SsrcStats::default(),
+            new_srtp_replay_detector: srtp_ctx_opt,
+            new_srtcp_replay_detector: srtcp_ctx_opt,
+            cryptex_policy: CryptexPolicy::Disabled,
+            srtcp_encryption_policy: SrtcpEncryptionPolicy::Enabled,
+            key_usage: KeyUsage::default(),
+            key_lifetime_warnings: KeyLifetimeWarnings::default(),
+            mki_key_usages: HashMap::new(),
+            srtp_key_lifetime: MAX_SRTP_KEY_LIFETIME,
+            srtcp_key_lifetime: MAX_SRTCP_KEY_LIFETIME,
+            key_lifetime_handler: None,
+        })
+    }
+
+    fn new_cipher(
+        profile: ProtectionProfile,
+        master_key: &[u8],
+        master_salt: &[u8],
+    ) -> Result<Box<dyn Cipher + Send>> {
         let key_len = profile.key_len();
         let salt_len = profile.salt_len();
 
@@ -129,7 +323,7 @@ impl Context {
             return Err(Error::SrtpSaltLength(salt_len, master_salt.len()));
         }
 
-        let cipher: Box<dyn Cipher + Send> = match profile {
+        Ok(match profile {
             ProtectionProfile::Aes128CmHmacSha1_32 | ProtectionProfile::Aes128CmHmacSha1_80 => {
                 Box::new(CipherAesCmHmacSha1::new(profile, master_key, master_salt)?)
             }
@@ -137,56 +331,431 @@ impl Context {
             ProtectionProfile::AeadAes128Gcm | ProtectionProfile::AeadAes256Gcm => {
                 Box::new(CipherAeadAesGcm::new(profile, master_key, master_salt)?)
             }
-        };
+        })
+    }
 
-        let srtp_ctx_opt = if let Some(ctx_opt) = srtp_ctx_opt {
-            ctx_opt
+    /// add_cipher_for_mki adds a master key identified by mki. Packets carrying
+    /// the MKI are decrypted with it, and it can be used for encryption by calling
+    /// `set_send_mki`.
+    pub fn add_cipher_for_mki(
+        &mut self,
+        mki: &[u8],
+        master_key: &[u8],
+        master_salt: &[u8],
+    ) -> Result<()> {
+        if self.mki.is_empty() {
+            return Err(Error::ErrMkiNotEnabled);
+        } else if mki.len() != self.mki.len() {
+            return Err(Error::MkiLength(self.mki.len(), mki.len()));
+        } else if self.is_mki_in_use(mki) {
+            return Err(Error::ErrMkiAlreadyInUse);
+        }
+
+        let cipher = Context::new_cipher(self.profile, master_key, master_salt)?;
+        self.mki_ciphers.insert(mki.to_vec(), cipher);
+        Ok(())
+    }
+
+    /// rekey replaces the master key used for encryption and decryption, e.g. after
+    /// a DTLS renegotiation or an EKT update. Packets protected with the previous key
+    /// are still accepted for `overlap`, so in-flight packets survive the switchover.
+    /// If MKIs are in use, the new key must come with a new MKI; otherwise mki must be empty.
+    pub fn rekey(
+        &mut self,
+        mki: &[u8],
+        master_key: &[u8],
+        master_salt: &[u8],
+        overlap: Duration,
+    ) -> Result<()> {
+        let cipher = self.new_rekey_cipher(mki, master_key, master_salt)?;
+        self.switch_key(mki, cipher, overlap);
+        Ok(())
+    }
+
+    /// new_rekey_cipher validates the key `rekey` would switch to and returns its cipher,
+    /// leaving the context untouched.
+    pub(crate) fn new_rekey_cipher(
+        &self,
+        mki: &[u8],
+        master_key: &[u8],
+        master_salt: &[u8],
+    ) -> Result<Box<dyn Cipher + Send>> {
+        if mki.len() != self.mki.len() {
+            return Err(Error::MkiLength(self.mki.len(), mki.len()));
+        } else if !mki.is_empty() && self.is_mki_in_use(mki) {
+            return Err(Error::ErrMkiAlreadyInUse);
+        }
+
+        Context::new_cipher(self.profile, master_key, master_salt)
+    }
+
+    /// switch_key switches to a cipher from new_rekey_cipher, see rekey.
+    pub(crate) fn switch_key(
+        &mut self,
+        mki: &[u8],
+        cipher: Box<dyn Cipher + Send>,
+        overlap: Duration,
+    ) {
+        self.previous_key = Some(PreviousKey {
+            mki: std::mem::replace(&mut self.mki, mki.to_vec()),
+            cipher: std::mem::replace(&mut self.cipher, cipher),
+            expires_at: Instant::now() + overlap,
+        });
+        self.key_usage = KeyUsage::default();
+        self.key_lifetime_warnings = KeyLifetimeWarnings::default();
+    }
+
+    fn is_mki_in_use(&self, mki: &[u8]) -> bool {
+        mki == self.mki
+            || self.mki_ciphers.contains_key(mki)
+            || matches!(&self.previous_key, Some(previous) if previous.mki == mki)
+    }
+
+    /// Runs decrypt with the cipher of mki, falling back to the key
+    /// replaced by `rekey` while its overlap window lasts.
+    fn decrypt_with<T>(
+        &mut self,
+        mki: &[u8],
+        mut decrypt: impl FnMut(&mut Box<dyn Cipher + Send>) -> Result<T>,
+    ) -> Result<T> {
+        if matches!(&self.previous_key, Some(previous) if Instant::now() >= previous.expires_at) {
+            self.previous_key = None;
+        }
+
+        let result = self.cipher_for_mki(mki).and_then(&mut decrypt);
+        match (&mut self.previous_key, result) {
+            (Some(previous), Err(err)) if previous.mki == mki => {
+                decrypt(&mut previous.cipher).map_err(|_| err)
+            }
+            (_, result) => result,
+        }
+    }
+
+    /// set_send_mki selects the master key used for encryption.
+    pub fn set_send_mki(&mut self, mki: &[u8]) -> Result<()> {
+        if self.mki.is_empty() {
+            return Err(Error::ErrMkiNotEnabled);
+        } else if mki == self.mki {
+            return Ok(());
+        }
+
+        let cipher = self.mki_ciphers.remove(mki).ok_or(Error::ErrMkiNotFound)?;
+        let previous_cipher = std::mem::replace(&mut self.cipher, cipher);
+        let previous_mki = std::mem::replace(&mut self.mki, mki.to_vec());
+        let (usage, warnings) = self.mki_key_usages.remove(mki).unwrap_or_default();
+        self.mki_key_usages.insert(
+            previous_mki.clone(),
+            (
+                std::mem::replace(&mut self.key_usage, usage),
+                std::mem::replace(&mut self.key_lifetime_warnings, warnings),
+            ),
+        );
+        self.mki_ciphers.insert(previous_mki, previous_cipher);
+        Ok(())
+    }
+
+    /// remove_mki removes a master key. The key used for encryption can't be removed.
+    pub fn remove_mki(&mut self, mki: &[u8]) -> Result<()> {
+        if self.mki.is_empty() {
+            return Err(Error::ErrMkiNotEnabled);
+        } else if mki == self.mki {
+            return Err(Error::ErrMkiAlreadyInUse);
+        }
+
+        self.mki_key_usages.remove(mki);
+        self.mki_ciphers
+            .remove(mki)
+            .map(|_| ())
+            .ok_or(Error::ErrMkiNotFound)
+    }
+
+    /// send_mki returns the MKI of the master key used for encryption,
+    /// empty if MKIs are not in use.
+    pub fn send_mki(&self) -> &[u8] {
+        &self.mki
+    }
+
+    fn cipher_for_mki(&mut self, mki: &[u8]) -> Result<&mut Box<dyn Cipher + Send>> {
+        if mki == self.mki {
+            Ok(&mut self.cipher)
         } else {
-            srtp_no_replay_protection()
+            self.mki_ciphers.get_mut(mki).ok_or(Error::ErrMkiNotFound)
+        }
+    }
+
+    /// The MKI is placed right in front of the authentication tag, which AEAD
+    /// ciphers don't have (RFC 3711 section 3.1, RFC 7714 section 8).
+    fn insert_mki(&self, packet: &mut BytesMut, auth_tag_len: usize) {
+        if self.mki.is_empty() {
+            return;
+        }
+
+        let mki_offset = packet.len() - auth_tag_len;
+        packet.extend_from_slice(&self.mki);
+        packet[mki_offset..].rotate_right(self.mki.len());
+    }
+
+    /// Removes the MKI from a protected packet and returns it, or None if the packet is too short.
+    fn split_mki(&self, packet: &mut BytesMut, auth_tag_len: usize) -> Option<Vec<u8>> {
+        if self.mki.is_empty() {
+            return Some(vec![]);
+        } else if packet.len() < self.mki.len() + auth_tag_len {
+            return None;
+        }
+
+        let len = packet.len();
+        let mki_offset = len - auth_tag_len - self.mki.len();
+        let mki = packet[mki_offset..mki_offset + self.mki.len()].to_vec();
+        packet[mki_offset..].rotate_left(self.mki.len());
+        packet.truncate(len - self.mki.len());
+        Some(mki)
+    }
+
+    /// rtp_overhead returns the number of bytes encryption adds to a RTP packet at most.
+    pub fn rtp_overhead(&self) -> usize {
+        let cryptex_overhead = if self.cryptex_policy == CryptexPolicy::Disabled {
+            0
+        } else {
+            cryptex::EXTENSION_HEADER_LENGTH
         };
 
-        let srtcp_ctx_opt = if let Some(ctx_opt) = srtcp_ctx_opt {
-            ctx_opt
+        self.cipher.rtp_auth_tag_len()
+            + self.cipher.aead_auth_tag_len()
+            + self.mki.len()
+            + cryptex_overhead
+    }
+
+    /// rtcp_overhead returns the number of bytes encryption adds to a RTCP packet.
+    pub fn rtcp_overhead(&self) -> usize {
+        SRTCP_INDEX_SIZE
+            + self.cipher.rtcp_auth_tag_len()
+            + self.cipher.aead_auth_tag_len()
+            + self.mki.len()
+    }
+
+    /// set_key_lifetime lowers the number of packets protected with a master key before
+    /// encryption fails, e.g. to the lifetime signaled with SDES. It is capped to
+    /// MAX_SRTP_KEY_LIFETIME and MAX_SRTCP_KEY_LIFETIME, which are used by default.
+    pub fn set_key_lifetime(&mut self, srtp_packets: u64, srtcp_packets: u64) {
+        self.srtp_key_lifetime = srtp_packets.min(MAX_SRTP_KEY_LIFETIME);
+        self.srtcp_key_lifetime = srtcp_packets.min(MAX_SRTCP_KEY_LIFETIME);
+    }
+
+    /// on_key_lifetime sets a handler called with a KeyLifetimeEvent once 1/16 of the
+    /// lifetime of the master key used for encryption is left, or a single packet for
+    /// lifetimes shorter than 16 packets.
+    pub fn on_key_lifetime(&mut self, handler: KeyLifetimeHandlerFn) {
+        self.key_lifetime_handler = Some(handler);
+    }
+
+    /// key_usage returns the number of packets protected with the master key used for encryption.
+    pub fn key_usage(&self) -> KeyUsage {
+        self.key_usage
+    }
+
+    fn check_key_lifetime(&self, is_rtp: bool) -> Result<()> {
+        if is_rtp && self.key_usage.srtp_packets >= self.srtp_key_lifetime {
+            Err(Error::ErrSrtpKeyLifetimeExhausted)
+        } else if !is_rtp && self.key_usage.srtcp_packets >= self.srtcp_key_lifetime {
+            Err(Error::ErrSrtcpKeyLifetimeExhausted)
         } else {
-            srtcp_no_replay_protection()
+            Ok(())
+        }
+    }
+
+    fn record_key_usage(&mut self, is_rtp: bool) {
+        let (packets, lifetime, warned) = if is_rtp {
+            self.key_usage.srtp_packets += 1;
+            (
+                self.key_usage.srtp_packets,
+                self.srtp_key_lifetime,
+                &mut self.key_lifetime_warnings.srtp,
+            )
+        } else {
+            self.key_usage.srtcp_packets += 1;
+            (
+                self.key_usage.srtcp_packets,
+                self.srtcp_key_lifetime,
+                &mut self.key_lifetime_warnings.srtcp,
+            )
         };
 
-        Ok(Context {
-            cipher,
-            srtp_ssrc_states: HashMap::new(),
-            srtcp_ssrc_states: HashMap::new(),
-            new_srtp_replay_detector: srtp_ctx_opt,
-            new_srtcp_replay_detector: srtcp_ctx_opt,
-        })
+        // The lifetime may be lowered past the threshold, so it's not only hit exactly.
+        let remaining = lifetime.saturating_sub(packets);
+        if *warned || remaining > (lifetime / KEY_LIFETIME_WARNING_DIVISOR).max(1) {
+            return;
+        }
+        *warned = true;
+        if let Some(handler) = &mut self.key_lifetime_handler {
+            handler(if is_rtp {
+                KeyLifetimeEvent::Srtp { remaining }
+            } else {
+                KeyLifetimeEvent::Srtcp { remaining }
+            });
+        }
+    }
+
+    /// set_cryptex_policy sets whether RTP header extensions and CSRCs are encrypted (RFC 9335).
+    pub fn set_cryptex_policy(&mut self, policy: CryptexPolicy) {
+        self.cryptex_policy = policy;
+    }
+
+    /// cryptex_policy returns the RFC 9335 policy of the context.
+    pub fn cryptex_policy(&self) -> CryptexPolicy {
+        self.cryptex_policy
+    }
+
+    /// set_srtcp_encryption_policy sets whether SRTCP payloads are encrypted or only authenticated.
+    pub fn set_srtcp_encryption_policy(&mut self, policy: SrtcpEncryptionPolicy) {
+        self.srtcp_encryption_policy = policy;
+    }
+
+    /// srtcp_encryption_policy returns the SRTCP encryption policy of the context.
+    pub fn srtcp_encryption_policy(&self) -> SrtcpEncryptionPolicy {
+        self.srtcp_encryption_policy
+    }
+
+    /// set_srtp_replay_protection_window replaces the SRTP replay detector of the given SSRC
+    /// with one of the given window size, overriding the detector of the context options.
+    pub fn set_srtp_replay_protection_window(&mut self, ssrc: u32, window_size: usize) {
+        let state = self.get_srtp_ssrc_state(ssrc);
+        state.replay_detector = Some(Box::new(WrappedSlidingWindowDetector::new(
+            window_size,
+            MAX_SEQUENCE_NUMBER as u64,
+        )));
+        state.replay_window = Some(window_size);
+    }
+
+    /// set_srtcp_replay_protection_window replaces the SRTCP replay detector of the given SSRC
+    /// with one of the given window size, overriding the detector of the context options.
+    pub fn set_srtcp_replay_protection_window(&mut self, ssrc: u32, window_size: usize) {
+        let state = self.get_srtcp_ssrc_state(ssrc);
+        state.replay_detector = Some(Box::new(WrappedSlidingWindowDetector::new(
+            window_size,
+            MAX_SRTCP_INDEX as u64,
+        )));
+        state.replay_window = Some(window_size);
+    }
+
+    /// srtp_replay_protection_window returns the SRTP replay window size set for the given SSRC,
+    /// or None if the SSRC uses the detector of the context options.
+    pub fn srtp_replay_protection_window(&self, ssrc: u32) -> Option<usize> {
+        self.srtp_ssrc_states
+            .get(&ssrc)
+            .and_then(|s| s.replay_window)
+    }
+
+    /// srtcp_replay_protection_window returns the SRTCP replay window size set for the given SSRC,
+    /// or None if the SSRC uses the detector of the context options.
+    pub fn srtcp_replay_protection_window(&self, ssrc: u32) -> Option<usize> {
+        self.srtcp_ssrc_states
+            .get(&ssrc)
+            .and_then(|s| s.replay_window)
+    }
+
+    /// srtp_stats returns the counters of SRTP packets dropped for the given SSRC.
+    pub fn srtp_stats(&self, ssrc: u32) -> Option<SsrcStats> {
+        self.srtp_ssrc_states.get(&ssrc).map(|s| s.stats)
+    }
+
+    /// srtcp_stats returns the counters of SRTCP packets dropped for the given SSRC.
+    pub fn srtcp_stats(&self, ssrc: u32) -> Option<SsrcStats> {
+        self.srtcp_ssrc_states.get(&ssrc).map(|s| s.stats)
+    }
+
+    /// srtp_unknown_ssrc_stats returns the counters of SRTP packets dropped for the SSRCs
+    /// no state is kept for, which is every SSRC until one of its packets is authenticated.
+    pub fn srtp_unknown_ssrc_stats(&self) -> SsrcStats {
+        self.srtp_unknown_ssrc_stats
+    }
+
+    /// srtcp_unknown_ssrc_stats returns the counters of SRTCP packets dropped for the SSRCs
+    /// no state is kept for.
+    pub fn srtcp_unknown_ssrc_stats(&self) -> SsrcStats {
+        self.srtcp_unknown_ssrc_stats
+    }
+
+    /// set_ssrc_state_limits bounds the number of SSRCs state is kept for. Only meant for
+    /// decrypting contexts, see SsrcStateLimits.
+    pub fn set_ssrc_state_limits(&mut self, limits: SsrcStateLimits) {
+        self.ssrc_state_limits = limits;
+    }
+
+    /// ssrc_state_limits returns the bounds on the per-SSRC state of the context.
+    pub fn ssrc_state_limits(&self) -> SsrcStateLimits {
+        self.ssrc_state_limits
+    }
+
+    /// srtp_ssrc_count returns the number of SSRCs SRTP state is currently kept for.
+    pub fn srtp_ssrc_count(&self) -> usize {
+        self.srtp_ssrc_states.len()
+    }
+
+    /// srtcp_ssrc_count returns the number of SSRCs SRTCP state is currently kept for.
+    pub fn srtcp_ssrc_count(&self) -> usize {
+        self.srtcp_ssrc_states.len()
     }
 
     fn get_srtp_ssrc_state(&mut self, ssrc: u32) -> &mut SrtpSsrcState {
-        let s = SrtpSsrcState {
-            ssrc,
-            replay_detector: Some((self.new_srtp_replay_detector)()),
-            ..Default::default()
-        };
+        let limits = self.ssrc_state_limits;
+        let now = limits.is_bounded().then(Instant::now);
+        if let Some(now) = now {
+            if !self.srtp_ssrc_states.contains_key(&ssrc) {
+                limits.make_room(&mut self.srtp_ssrc_states, now, |s| s.last_used);
+            }
+        }
 
-        self.srtp_ssrc_states.entry(ssrc).or_insert(s)
+        let new_replay_detector = &self.new_srtp_replay_detector;
+        let state = self
+            .srtp_ssrc_states
+            .entry(ssrc)
+            .or_insert_with(|| SrtpSsrcState {
+                ssrc,
+                replay_detector: Some(new_replay_detector()),
+                ..Default::default()
+            });
+        if now.is_some() {
+            state.last_used = now;
+        }
+        state
     }
 
     fn get_srtcp_ssrc_state(&mut self, ssrc: u32) -> &mut SrtcpSsrcState {
-        let s = SrtcpSsrcState {
-            ssrc,
-            replay_detector: Some((self.new_srtcp_replay_detector)()),
-            ..Default::default()
-        };
-        self.srtcp_ssrc_states.entry(ssrc).or_insert(s)
+        let limits = self.ssrc_state_limits;
+        let now = limits.is_bounded().then(Instant::now);
+        if let Some(now) = now {
+            if !self.srtcp_ssrc_states.contains_key(&ssrc) {
+                limits.make_room(&mut self.srtcp_ssrc_states, now, |s| s.last_used);
+            }
+        }
+
+        let new_replay_detector = &self.new_srtcp_replay_detector;
+        let state = self
+            .srtcp_ssrc_states
+            .entry(ssrc)
+            .or_insert_with(|| SrtcpSsrcState {
+                ssrc,
+                replay_detector: Some(new_replay_detector()),
+                ..Default::default()
+            });
+        if now.is_some() {
+            state.last_used = now;
+        }
+        state
     }
 
     /// roc returns SRTP rollover counter value of specified SSRC.
     fn get_roc(&self, ssrc: u32) -> Option<u32> {
-        self.srtp_ssrc_states.get(&ssrc).map(|s| s.rollover_counter)
+        self.srtp_ssrc_states
+            .get(&ssrc)
+            .map(|s| s.rollover_counter())
     }
 
     /// set_roc sets SRTP rollover counter value of specified SSRC.
     fn set_roc(&mut self, ssrc: u32, roc: u32) {
-        self.get_srtp_ssrc_state(ssrc).rollover_counter = roc;
+        self.get_srtp_ssrc_state(ssrc)
+            .sequence
+            .set_roll_over_count(roc as u64);
     }
 
     /// index returns SRTCP index value of specified SSRC.
@@ -1,4 +1,5 @@
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
+use rtcp::header::{HEADER_LENGTH, SSRC_LENGTH};
 use util::marshal::*;
 
 use super::*;
@@ -7,34 +8,111 @@ use crate::error::Result;
 impl Context {
     /// DecryptRTCP decrypts a RTCP packet with an encrypted payload
     pub fn decrypt_rtcp(&mut self, encrypted: &[u8]) -> Result<Bytes> {
-        let mut buf = encrypted;
-        rtcp::header::Header::unmarshal(&mut buf)?;
+        let mut packet = BytesMut::from(encrypted);
+        self.decrypt_rtcp_in_place(&mut packet)?;
+        Ok(packet.freeze())
+    }
 
-        let index = self.cipher.get_rtcp_index(encrypted);
-        let ssrc = u32::from_be_bytes([encrypted[4], encrypted[5], encrypted[6], encrypted[7]]);
+    /// decrypt_rtcp_in_place decrypts a RTCP packet in place, truncating the SRTCP
+    /// index, the MKI and the auth tag. The contents of packet are unspecified on error.
+    pub fn decrypt_rtcp_in_place(&mut self, packet: &mut BytesMut) -> Result<()> {
+        rtcp::header::Header::unmarshal(&mut &packet[..])?;
+        if packet.len() < HEADER_LENGTH + SSRC_LENGTH {
+            return Err(Error::SrtcpTooSmall(
+                packet.len(),
+                HEADER_LENGTH + SSRC_LENGTH,
+            ));
+        }
 
-        if let Some(replay_detector) = &mut self.get_srtcp_ssrc_state(ssrc).replay_detector {
-            if !replay_detector.check(index as u64) {
-                return Err(Error::SrtcpSsrcDuplicated(ssrc, index));
+        let ssrc = u32::from_be_bytes([packet[4], packet[5], packet[6], packet[7]]);
+        let result = self.decrypt_rtcp_unrecorded(packet, ssrc);
+        if let Err(err) = &result {
+            match self.srtcp_ssrc_states.get_mut(&ssrc) {
+                Some(state) => state.stats.record(err),
+                None => self.srtcp_unknown_ssrc_stats.record(err),
             }
         }
+        result
+    }
 
-        let dst = self.cipher.decrypt_rtcp(encrypted, index, ssrc)?;
+    fn decrypt_rtcp_unrecorded(&mut self, packet: &mut BytesMut, ssrc: u32) -> Result<()> {
+        let auth_tag_len = self.cipher.rtcp_auth_tag_len();
+        let mki = self
+            .split_mki(packet, auth_tag_len)
+            .ok_or_else(|| Error::SrtcpTooSmall(packet.len(), self.mki.len() + auth_tag_len))?;
+
+        let min_len = HEADER_LENGTH
+            + SSRC_LENGTH
+            + SRTCP_INDEX_SIZE
+            + auth_tag_len
+            + self.cipher.aead_auth_tag_len();
+        if packet.len() < min_len {
+            return Err(Error::SrtcpTooSmall(packet.len(), min_len));
+        }
 
-        if let Some(replay_detector) = &mut self.get_srtcp_ssrc_state(ssrc).replay_detector {
+        if self.srtcp_encryption_policy == SrtcpEncryptionPolicy::Required
+            && !self.cipher.is_rtcp_encrypted(packet)
+        {
+            return Err(Error::ErrSrtcpEncryptionRequired);
+        }
+
+        let index = self.cipher.get_rtcp_index(packet);
+
+        // As with SRTP, the state of an SSRC is only created once a packet is authenticated.
+        let new_replay_detector = match self.srtcp_ssrc_states.get_mut(&ssrc) {
+            Some(state) => {
+                if let Some(replay_detector) = &mut state.replay_detector {
+                    if !replay_detector.check(index as u64) {
+                        return Err(Error::SrtcpSsrcDuplicated(ssrc, index));
+                    }
+                }
+                None
+            }
+            None => {
+                let mut replay_detector = (self.new_srtcp_replay_detector)();
+                if !replay_detector.check(index as u64) {
+                    return Err(Error::SrtcpSsrcDuplicated(ssrc, index));
+                }
+                Some(replay_detector)
+            }
+        };
+
+        self.decrypt_with(&mki, |cipher| cipher.decrypt_rtcp(packet, index, ssrc))?;
+
+        let state = self.get_srtcp_ssrc_state(ssrc);
+        if new_replay_detector.is_some() {
+            state.replay_detector = new_replay_detector;
+        }
+        if let Some(replay_detector) = &mut state.replay_detector {
             replay_detector.accept();
         }
 
-        Ok(dst)
+        Ok(())
     }
 
     /// EncryptRTCP marshals and encrypts an RTCP packet, writing to the dst buffer provided.
     /// If the dst buffer does not have the capacity to hold `len(plaintext) + 14` bytes, a new one will be allocated and returned.
     pub fn encrypt_rtcp(&mut self, decrypted: &[u8]) -> Result<Bytes> {
-        let mut buf = decrypted;
-        rtcp::header::Header::unmarshal(&mut buf)?;
+        let mut packet = BytesMut::with_capacity(decrypted.len() + self.rtcp_overhead());
+        packet.extend_from_slice(decrypted);
+        self.encrypt_rtcp_in_place(&mut packet)?;
+        Ok(packet.freeze())
+    }
+
+    /// encrypt_rtcp_in_place encrypts a RTCP packet in place, appending the SRTCP index,
+    /// the MKI and the auth tag. Packets with `rtcp_overhead` bytes of spare capacity are
+    /// encrypted without allocating. The contents of packet are unspecified on error.
+    pub fn encrypt_rtcp_in_place(&mut self, packet: &mut BytesMut) -> Result<()> {
+        rtcp::header::Header::unmarshal(&mut &packet[..])?;
+        if packet.len() < HEADER_LENGTH + SSRC_LENGTH {
+            return Err(Error::SrtcpTooSmall(
+                packet.len(),
+                HEADER_LENGTH + SSRC_LENGTH,
+            ));
+        }
 
-        let ssrc = u32::from_be_bytes([decrypted[4], decrypted[5], decrypted[6], decrypted[7]]);
+        let ssrc = u32::from_be_bytes([packet[4], packet[5], packet[6], packet[7]]);
+        self.check_key_lifetime(false)?;
 
         let index = {
             let state = self.get_srtcp_ssrc_state(ssrc);
@@ -45,6 +123,11 @@ impl Context {
             state.srtcp_index
         };
 
-        self.cipher.encrypt_rtcp(decrypted, index, ssrc)
+        packet.reserve(self.rtcp_overhead());
+        let encrypt = self.srtcp_encryption_policy != SrtcpEncryptionPolicy::Disabled;
+        self.cipher.encrypt_rtcp(packet, index, ssrc, encrypt)?;
+        self.insert_mki(packet, self.cipher.rtcp_auth_tag_len());
+        self.record_key_usage(false);
+        Ok(())
     }
 }
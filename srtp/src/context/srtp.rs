@@ -1,4 +1,4 @@
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 use util::marshal::*;
 
 use super::*;
@@ -10,30 +10,90 @@ impl Context {
         encrypted: &[u8],
         header: &rtp::header::Header,
     ) -> Result<Bytes> {
-        let roc = {
-            let state = self.get_srtp_ssrc_state(header.ssrc);
-            if let Some(replay_detector) = &mut state.replay_detector {
+        let mut packet = BytesMut::from(encrypted);
+        self.decrypt_rtp_with_header_in_place(&mut packet, header)?;
+        Ok(packet.freeze())
+    }
+
+    /// decrypt_rtp_with_header_in_place decrypts a RTP packet in place, truncating
+    /// the MKI and the auth tag. The contents of packet are unspecified on error.
+    pub fn decrypt_rtp_with_header_in_place(
+        &mut self,
+        packet: &mut BytesMut,
+        header: &rtp::header::Header,
+    ) -> Result<()> {
+        let result = self.decrypt_rtp_with_header_unrecorded(packet, header);
+        if let Err(err) = &result {
+            match self.srtp_ssrc_states.get_mut(&header.ssrc) {
+                Some(state) => state.stats.record(err),
+                None => self.srtp_unknown_ssrc_stats.record(err),
+            }
+        }
+        result
+    }
+
+    fn decrypt_rtp_with_header_unrecorded(
+        &mut self,
+        packet: &mut BytesMut,
+        header: &rtp::header::Header,
+    ) -> Result<()> {
+        let auth_tag_len = self.cipher.rtp_auth_tag_len();
+        let mki = self
+            .split_mki(packet, auth_tag_len)
+            .ok_or_else(|| Error::SrtpTooSmall(packet.len(), self.mki.len() + auth_tag_len))?;
+
+        let is_cryptex = cryptex::is_cryptex(header);
+        match self.cryptex_policy {
+            CryptexPolicy::Disabled if is_cryptex => return Err(Error::ErrCryptexDisabled),
+            CryptexPolicy::Required if !is_cryptex && cryptex::has_protectable_header(header) => {
+                return Err(Error::ErrCryptexRequired)
+            }
+            _ => {}
+        };
+
+        // The state of an SSRC is only created, and the others evicted, once one of its
+        // packets is authenticated, so forged packets can't push the genuine SSRCs out.
+        let (roc, new_replay_detector) = match self.srtp_ssrc_states.get_mut(&header.ssrc) {
+            Some(state) => {
+                if let Some(replay_detector) = &mut state.replay_detector {
+                    if !replay_detector.check(header.sequence_number as u64) {
+                        return Err(Error::SrtpSsrcDuplicated(
+                            header.ssrc,
+                            header.sequence_number,
+                        ));
+                    }
+                }
+                (state.next_rollover_count(header.sequence_number), None)
+            }
+            None => {
+                let mut replay_detector = (self.new_srtp_replay_detector)();
                 if !replay_detector.check(header.sequence_number as u64) {
                     return Err(Error::SrtpSsrcDuplicated(
                         header.ssrc,
                         header.sequence_number,
                     ));
                 }
+                let roc = SrtpSsrcState::default().next_rollover_count(header.sequence_number);
+                (roc, Some(replay_detector))
             }
-
-            state.next_rollover_count(header.sequence_number)
         };
 
-        let dst = self.cipher.decrypt_rtp(encrypted, header, roc)?;
+        self.decrypt_with(&mki, |cipher| cipher.decrypt_rtp(packet, header, roc))?;
+        if is_cryptex {
+            cryptex::unprotect(packet, header);
+        }
         {
             let state = self.get_srtp_ssrc_state(header.ssrc);
+            if new_replay_detector.is_some() {
+                state.replay_detector = new_replay_detector;
+            }
             if let Some(replay_detector) = &mut state.replay_detector {
                 replay_detector.accept();
             }
             state.update_rollover_count(header.sequence_number);
         }
 
-        Ok(dst)
+        Ok(())
     }
 
     /// DecryptRTP decrypts a RTP packet with an encrypted payload
@@ -43,21 +103,67 @@ impl Context {
         self.decrypt_rtp_with_header(encrypted, &header)
     }
 
+    /// decrypt_rtp_in_place decrypts a RTP packet in place. See decrypt_rtp_with_header_in_place.
+    pub fn decrypt_rtp_in_place(&mut self, packet: &mut BytesMut) -> Result<()> {
+        let header = rtp::header::Header::unmarshal(&mut &packet[..])?;
+        self.decrypt_rtp_with_header_in_place(packet, &header)
+    }
+
     pub fn encrypt_rtp_with_header(
         &mut self,
         payload: &[u8],
         header: &rtp::header::Header,
     ) -> Result<Bytes> {
+        let mut packet = BytesMut::with_capacity(payload.len() + self.rtp_overhead());
+        packet.extend_from_slice(payload);
+        self.encrypt_rtp_with_header_in_place(&mut packet, header)?;
+        Ok(packet.freeze())
+    }
+
+    /// encrypt_rtp_with_header_in_place encrypts a RTP packet in place, appending the
+    /// MKI and the auth tag. Packets with `rtp_overhead` bytes of spare capacity are
+    /// encrypted without allocating. The contents of packet are unspecified on error.
+    pub fn encrypt_rtp_with_header_in_place(
+        &mut self,
+        packet: &mut BytesMut,
+        header: &rtp::header::Header,
+    ) -> Result<()> {
+        packet.reserve(self.rtp_overhead());
+
+        if self.cryptex_policy != CryptexPolicy::Disabled {
+            if let Some(header) = cryptex::protect(packet, header) {
+                return self.encrypt_rtp_with_header_unchecked(packet, &header);
+            }
+            if self.cryptex_policy == CryptexPolicy::Required
+                && cryptex::has_protectable_header(header)
+            {
+                return Err(Error::CryptexUnsupportedExtensionProfile(
+                    header.extension_profile,
+                ));
+            }
+        }
+
+        self.encrypt_rtp_with_header_unchecked(packet, header)
+    }
+
+    fn encrypt_rtp_with_header_unchecked(
+        &mut self,
+        packet: &mut BytesMut,
+        header: &rtp::header::Header,
+    ) -> Result<()> {
+        self.check_key_lifetime(true)?;
         let roc = self
             .get_srtp_ssrc_state(header.ssrc)
             .next_rollover_count(header.sequence_number);
 
-        let dst = self.cipher.encrypt_rtp(payload, header, roc)?;
+        self.cipher.encrypt_rtp(packet, header, roc)?;
+        self.insert_mki(packet, self.cipher.rtp_auth_tag_len());
+        self.record_key_usage(true);
 
         self.get_srtp_ssrc_state(header.ssrc)
             .update_rollover_count(header.sequence_number);
 
-        Ok(dst)
+        Ok(())
     }
 
     /// EncryptRTP marshals and encrypts an RTP packet, writing to the dst buffer provided.
@@ -67,4 +173,10 @@ impl Context {
         let header = rtp::header::Header::unmarshal(&mut buf)?;
         self.encrypt_rtp_with_header(plaintext, &header)
     }
+
+    /// encrypt_rtp_in_place encrypts a RTP packet in place. See encrypt_rtp_with_header_in_place.
+    pub fn encrypt_rtp_in_place(&mut self, packet: &mut BytesMut) -> Result<()> {
+        let header = rtp::header::Header::unmarshal(&mut &packet[..])?;
+        self.encrypt_rtp_with_header_in_place(packet, &header)
+    }
 }
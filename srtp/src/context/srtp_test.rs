@@ -101,6 +101,7 @@ fn test_rtp_invalid_auth() -> Result<()> {
                 ..Default::default()
             },
             payload: RTP_TEST_CASE_DECRYPTED.clone(),
+            ..Default::default()
         };
 
         let pkt_raw = pkt.marshal()?;
@@ -130,6 +131,7 @@ fn test_rtp_lifecycle() -> Result<()> {
                 ..Default::default()
             },
             payload: RTP_TEST_CASE_DECRYPTED.clone(),
+            ..Default::default()
         };
 
         let decrypted_raw = decrypted_pkt.marshal()?;
@@ -140,6 +142,7 @@ fn test_rtp_lifecycle() -> Result<()> {
                 ..Default::default()
             },
             payload: test_case.encrypted.clone(),
+            ..Default::default()
         };
 
         let encrypted_raw = encrypted_pkt.marshal()?;
@@ -0,0 +1,178 @@
+use bytes::Bytes;
+
+use super::*;
+use crate::context::Context;
+use crate::error::{Error, Result};
+use crate::protection_profile::ProtectionProfile;
+
+const MASTER_KEY: [u8; 16] = [
+    0xe1, 0xf9, 0x7a, 0x0d, 0x3e, 0x01, 0x8b, 0xe0, 0xd6, 0x4f, 0xa3, 0x2c, 0x06, 0xde, 0x41, 0x39,
+];
+const MASTER_SALT: [u8; 14] = [
+    0x0e, 0xc6, 0x75, 0xad, 0x49, 0x8a, 0xfe, 0xeb, 0xb6, 0x96, 0x0b, 0x3a, 0xab, 0xe6,
+];
+
+// Two CSRCs and a one-byte header extension.
+const DECRYPTED_RTP_PACKET: [u8; 44] = [
+    0x92, 0x0f, 0x12, 0x34, 0xde, 0xca, 0xfb, 0xad, 0xca, 0xfe, 0xba, 0xbe, 0x00, 0x01, 0xe2, 0x40,
+    0x00, 0x00, 0xb2, 0x6e, 0xbe, 0xde, 0x00, 0x01, 0x51, 0x00, 0x02, 0x00, 0xab, 0xab, 0xab, 0xab,
+    0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab,
+];
+const ENCRYPTED_RTP_PACKET_AES_CM: [u8; 54] = [
+    0x92, 0x0f, 0x12, 0x34, 0xde, 0xca, 0xfb, 0xad, 0xca, 0xfe, 0xba, 0xbe, 0xe5, 0xff, 0x95, 0xa7,
+    0x4c, 0x32, 0x61, 0x1d, 0xc0, 0xde, 0x00, 0x01, 0x76, 0x0f, 0x7b, 0xbe, 0x94, 0x9d, 0x24, 0x02,
+    0x34, 0xbb, 0x38, 0x49, 0x1e, 0xe6, 0x0f, 0x20, 0xfa, 0x0c, 0x9c, 0x9f, 0x6a, 0x43, 0x69, 0x96,
+    0x12, 0xb4, 0xdd, 0x06, 0x90, 0x79,
+];
+const ENCRYPTED_RTP_PACKET_AES_GCM: [u8; 60] = [
+    0x92, 0x0f, 0x12, 0x34, 0xde, 0xca, 0xfb, 0xad, 0xca, 0xfe, 0xba, 0xbe, 0xa5, 0x60, 0x45, 0x12,
+    0xf5, 0x42, 0x4c, 0x77, 0xc0, 0xde, 0x00, 0x01, 0x96, 0x78, 0x7b, 0x23, 0xb4, 0x9f, 0x6c, 0xa9,
+    0x13, 0xf1, 0xc6, 0xa9, 0xec, 0x08, 0x01, 0xd3, 0x38, 0x3e, 0x5a, 0xad, 0xc3, 0x10, 0x8d, 0x92,
+    0xc3, 0xaa, 0x09, 0xcd, 0xf5, 0x73, 0x98, 0x21, 0x66, 0x1a, 0xe1, 0xa4,
+];
+
+// Two CSRCs and no header extension.
+const DECRYPTED_RTP_PACKET_CSRC_ONLY: [u8; 36] = [
+    0x82, 0x0f, 0x12, 0x34, 0xde, 0xca, 0xfb, 0xad, 0xca, 0xfe, 0xba, 0xbe, 0x00, 0x01, 0xe2, 0x40,
+    0x00, 0x00, 0xb2, 0x6e, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab,
+    0xab, 0xab, 0xab, 0xab,
+];
+const ENCRYPTED_RTP_PACKET_CSRC_ONLY_AES_CM: [u8; 50] = [
+    0x92, 0x0f, 0x12, 0x34, 0xde, 0xca, 0xfb, 0xad, 0xca, 0xfe, 0xba, 0xbe, 0xe5, 0xff, 0x95, 0xa7,
+    0x4c, 0x32, 0x61, 0x1d, 0xc0, 0xde, 0x00, 0x00, 0x8c, 0xa4, 0xd2, 0x15, 0x94, 0x9d, 0x24, 0x02,
+    0x34, 0xbb, 0x38, 0x49, 0x1e, 0xe6, 0x0f, 0x20, 0x39, 0xdf, 0x2c, 0x9a, 0x2e, 0xbb, 0xdd, 0xea,
+    0x0f, 0x75,
+];
+const ENCRYPTED_RTP_PACKET_CSRC_ONLY_AES_GCM: [u8; 56] = [
+    0x92, 0x0f, 0x12, 0x34, 0xde, 0xca, 0xfb, 0xad, 0xca, 0xfe, 0xba, 0xbe, 0xa5, 0x60, 0x45, 0x12,
+    0xf5, 0x42, 0x4c, 0x77, 0xc0, 0xde, 0x00, 0x00, 0x6c, 0xd3, 0xd2, 0x88, 0xb4, 0x9f, 0x6c, 0xa9,
+    0x13, 0xf1, 0xc6, 0xa9, 0xec, 0x08, 0x01, 0xd3, 0xf6, 0x84, 0x19, 0x13, 0xf2, 0x42, 0xe3, 0x7b,
+    0xea, 0x37, 0xbc, 0x8f, 0x3b, 0xb3, 0x36, 0xee,
+];
+
+fn build_test_context(profile: ProtectionProfile, policy: CryptexPolicy) -> Result<Context> {
+    let mut ctx = Context::new(
+        &MASTER_KEY,
+        &MASTER_SALT[..profile.salt_len()],
+        profile,
+        None,
+        None,
+    )?;
+    ctx.set_cryptex_policy(policy);
+    Ok(ctx)
+}
+
+#[test]
+fn test_cryptex_encrypt_decrypt() -> Result<()> {
+    let tests: [(ProtectionProfile, &[u8], &[u8]); 4] = [
+        (
+            ProtectionProfile::Aes128CmHmacSha1_80,
+            &DECRYPTED_RTP_PACKET,
+            &ENCRYPTED_RTP_PACKET_AES_CM,
+        ),
+        (
+            ProtectionProfile::AeadAes128Gcm,
+            &DECRYPTED_RTP_PACKET,
+            &ENCRYPTED_RTP_PACKET_AES_GCM,
+        ),
+        (
+            ProtectionProfile::Aes128CmHmacSha1_80,
+            &DECRYPTED_RTP_PACKET_CSRC_ONLY,
+            &ENCRYPTED_RTP_PACKET_CSRC_ONLY_AES_CM,
+        ),
+        (
+            ProtectionProfile::AeadAes128Gcm,
+            &DECRYPTED_RTP_PACKET_CSRC_ONLY,
+            &ENCRYPTED_RTP_PACKET_CSRC_ONLY_AES_GCM,
+        ),
+    ];
+
+    for (profile, decrypted, encrypted) in tests {
+        let mut encrypt_context = build_test_context(profile, CryptexPolicy::Enabled)?;
+        let actual_encrypted = encrypt_context.encrypt_rtp(decrypted)?;
+        assert_eq!(
+            actual_encrypted,
+            Bytes::copy_from_slice(encrypted),
+            "{profile:?}"
+        );
+
+        let mut decrypt_context = build_test_context(profile, CryptexPolicy::Required)?;
+        let actual_decrypted = decrypt_context.decrypt_rtp(encrypted)?;
+        assert_eq!(
+            actual_decrypted,
+            Bytes::copy_from_slice(decrypted),
+            "{profile:?}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_cryptex_policy() -> Result<()> {
+    let profile = ProtectionProfile::Aes128CmHmacSha1_80;
+
+    // Packets without CSRCs or header extensions are never transformed.
+    let mut plain = DECRYPTED_RTP_PACKET_CSRC_ONLY[..12].to_vec();
+    plain[0] = 0x80;
+    plain.extend_from_slice(&DECRYPTED_RTP_PACKET_CSRC_ONLY[20..]);
+    let mut encrypt_context = build_test_context(profile, CryptexPolicy::Required)?;
+    let encrypted = encrypt_context.encrypt_rtp(&plain)?;
+    let mut decrypt_context = build_test_context(profile, CryptexPolicy::Disabled)?;
+    assert_eq!(decrypt_context.decrypt_rtp(&encrypted)?, Bytes::from(plain));
+
+    let mut decrypt_context = build_test_context(profile, CryptexPolicy::Disabled)?;
+    assert_eq!(
+        decrypt_context.decrypt_rtp(&ENCRYPTED_RTP_PACKET_AES_CM),
+        Err(Error::ErrCryptexDisabled)
+    );
+
+    let mut encrypt_context = build_test_context(profile, CryptexPolicy::Disabled)?;
+    let encrypted = encrypt_context.encrypt_rtp(&DECRYPTED_RTP_PACKET)?;
+    assert_eq!(&encrypted[12..28], &DECRYPTED_RTP_PACKET[12..28]);
+
+    let mut decrypt_context = build_test_context(profile, CryptexPolicy::Enabled)?;
+    assert_eq!(
+        decrypt_context.decrypt_rtp(&encrypted)?,
+        Bytes::copy_from_slice(&DECRYPTED_RTP_PACKET)
+    );
+
+    let mut decrypt_context = build_test_context(profile, CryptexPolicy::Required)?;
+    assert_eq!(
+        decrypt_context.decrypt_rtp(&encrypted),
+        Err(Error::ErrCryptexRequired)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_cryptex_two_byte_appbits() -> Result<()> {
+    for (profile, appbits, cryptex_profile) in [
+        (ProtectionProfile::Aes128CmHmacSha1_80, 0x0, 0xC2DE),
+        (ProtectionProfile::Aes128CmHmacSha1_80, 0x3, 0xC2DD),
+        (ProtectionProfile::AeadAes128Gcm, 0xF, 0xC2D1),
+    ] {
+        let mut decrypted = DECRYPTED_RTP_PACKET;
+        decrypted[20..22].copy_from_slice(&(EXTENSION_PROFILE_TWO_BYTE | appbits).to_be_bytes());
+
+        // The header extension is encrypted and the appbits are kept in the profile.
+        let mut encrypt_context = build_test_context(profile, CryptexPolicy::Required)?;
+        let encrypted = encrypt_context.encrypt_rtp(&decrypted)?;
+        assert_eq!(
+            &encrypted[20..22],
+            &u16::to_be_bytes(cryptex_profile),
+            "{appbits}"
+        );
+        assert_ne!(&encrypted[24..28], &decrypted[24..28], "{appbits}");
+
+        let mut decrypt_context = build_test_context(profile, CryptexPolicy::Required)?;
+        assert_eq!(
+            decrypt_context.decrypt_rtp(&encrypted)?,
+            Bytes::copy_from_slice(&decrypted),
+            "{appbits}"
+        );
+    }
+
+    Ok(())
+}
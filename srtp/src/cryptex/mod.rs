@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod cryptex_test;
+
+use bytes::BytesMut;
+use rtp::header::*;
+use util::marshal::*;
+
+/// Header extension profile of a cryptex protected RFC 8285 one-byte header extension.
+pub const CRYPTEX_PROFILE_ONE_BYTE: u16 = 0xC0DE;
+/// Header extension profile of a cryptex protected RFC 8285 two-byte header extension.
+pub const CRYPTEX_PROFILE_TWO_BYTE: u16 = 0xC2DE;
+/// The lowest 4 bits of the cryptex two-byte profile carry the appbits of the RFC 8285
+/// profile, flipped so that a profile without appbits maps to CRYPTEX_PROFILE_TWO_BYTE.
+pub const CRYPTEX_PROFILE_TWO_BYTE_MASK: u16 = EXTENSION_PROFILE_TWO_BYTE_MASK;
+
+pub(crate) const EXTENSION_HEADER_LENGTH: usize = 4;
+
+/// CryptexPolicy controls whether the CSRC list and header extensions
+/// of SRTP packets are encrypted as described in RFC 9335.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CryptexPolicy {
+    /// Headers are sent in the clear and cryptex packets are rejected.
+    #[default]
+    Disabled,
+    /// Headers are encrypted when sending; both forms are accepted when receiving.
+    Enabled,
+    /// Headers are encrypted when sending; packets carrying CSRCs or
+    /// header extensions in the clear are rejected when receiving.
+    Required,
+}
+
+/// Returns true if the header extension of the packet is cryptex protected.
+pub(crate) fn is_cryptex(header: &Header) -> bool {
+    header.extension
+        && (header.extension_profile == CRYPTEX_PROFILE_ONE_BYTE
+            || header.extension_profile & CRYPTEX_PROFILE_TWO_BYTE_MASK
+                == CRYPTEX_PROFILE_TWO_BYTE & CRYPTEX_PROFILE_TWO_BYTE_MASK)
+}
+
+/// Returns true if the packet carries anything cryptex would protect.
+pub(crate) fn has_protectable_header(header: &Header) -> bool {
+    header.extension || !header.csrc.is_empty()
+}
+
+/// Returns the offset at which the encrypted portion of an RTP packet starts.
+/// For cryptex packets only the fixed header and the extension header are left
+/// in the clear, once the packet has been passed through `reorder`.
+pub(crate) fn encryption_offset(header: &Header) -> usize {
+    if is_cryptex(header) {
+        CSRC_OFFSET + EXTENSION_HEADER_LENGTH
+    } else {
+        header.marshal_size()
+    }
+}
+
+/// Cryptex encrypts the CSRC list, the header extension body and the payload
+/// as one contiguous range (RFC 9335 section 5.1), so the extension header is
+/// moved in front of the CSRC list before the cipher runs.
+pub(crate) fn reorder(packet: &mut [u8], header: &Header) {
+    if !is_cryptex(header) || header.csrc.is_empty() {
+        return;
+    }
+
+    let csrc_len = header.csrc.len() * CSRC_LENGTH;
+    packet[CSRC_OFFSET..CSRC_OFFSET + csrc_len + EXTENSION_HEADER_LENGTH].rotate_left(csrc_len);
+}
+
+/// Moves the extension header back behind the CSRC list, undoing `reorder`.
+pub(crate) fn restore(packet: &mut [u8], header: &Header) {
+    if !is_cryptex(header) || header.csrc.is_empty() {
+        return;
+    }
+
+    let csrc_len = header.csrc.len() * CSRC_LENGTH;
+    packet[CSRC_OFFSET..CSRC_OFFSET + csrc_len + EXTENSION_HEADER_LENGTH].rotate_right(csrc_len);
+}
+
+/// Converts a plaintext RTP packet to its cryptex form by replacing the RFC 8285
+/// extension profile with its cryptex counterpart. A packet carrying CSRCs but
+/// no header extension gets an empty one. Returns the header of the converted
+/// packet, or None if the packet carries nothing to protect or uses an extension
+/// profile cryptex does not cover, in which case the packet is left unchanged.
+pub(crate) fn protect(packet: &mut BytesMut, header: &Header) -> Option<Header> {
+    let extension_offset = CSRC_OFFSET + header.csrc.len() * CSRC_LENGTH;
+
+    let profile = if header.extension {
+        let profile = match header.extension_profile {
+            EXTENSION_PROFILE_ONE_BYTE => CRYPTEX_PROFILE_ONE_BYTE,
+            profile if profile & EXTENSION_PROFILE_TWO_BYTE_MASK == EXTENSION_PROFILE_TWO_BYTE => {
+                CRYPTEX_PROFILE_TWO_BYTE ^ (profile & !EXTENSION_PROFILE_TWO_BYTE_MASK)
+            }
+            _ => return None,
+        };
+        packet[extension_offset..extension_offset + 2].copy_from_slice(&profile.to_be_bytes());
+        profile
+    } else if !header.csrc.is_empty() {
+        let len = packet.len();
+        packet.resize(len + EXTENSION_HEADER_LENGTH, 0);
+        packet.copy_within(
+            extension_offset..len,
+            extension_offset + EXTENSION_HEADER_LENGTH,
+        );
+        packet[extension_offset..extension_offset + 2]
+            .copy_from_slice(&CRYPTEX_PROFILE_ONE_BYTE.to_be_bytes());
+        packet[extension_offset + 2..extension_offset + EXTENSION_HEADER_LENGTH].fill(0);
+        packet[0] |= 1 << EXTENSION_SHIFT;
+        CRYPTEX_PROFILE_ONE_BYTE
+    } else {
+        return None;
+    };
+
+    let mut header = header.clone();
+    header.extension = true;
+    header.extension_profile = profile;
+    Some(header)
+}
+
+/// Converts a decrypted cryptex packet back to its RFC 8285 form, dropping
+/// the empty header extension a sender adds to packets carrying only CSRCs.
+pub(crate) fn unprotect(packet: &mut BytesMut, header: &Header) {
+    let extension_offset = CSRC_OFFSET + header.csrc.len() * CSRC_LENGTH;
+    let extension_words =
+        u16::from_be_bytes([packet[extension_offset + 2], packet[extension_offset + 3]]);
+
+    if header.extension_profile == CRYPTEX_PROFILE_ONE_BYTE && extension_words == 0 {
+        let len = packet.len();
+        packet.copy_within(
+            extension_offset + EXTENSION_HEADER_LENGTH..len,
+            extension_offset,
+        );
+        packet.truncate(len - EXTENSION_HEADER_LENGTH);
+        packet[0] &= !(1 << EXTENSION_SHIFT);
+    } else {
+        let profile = if header.extension_profile == CRYPTEX_PROFILE_ONE_BYTE {
+            EXTENSION_PROFILE_ONE_BYTE
+        } else {
+            let appbits = (header.extension_profile ^ CRYPTEX_PROFILE_TWO_BYTE)
+                & !CRYPTEX_PROFILE_TWO_BYTE_MASK;
+            EXTENSION_PROFILE_TWO_BYTE | appbits
+        };
+        packet[extension_offset..extension_offset + 2].copy_from_slice(&profile.to_be_bytes());
+    }
+}
@@ -0,0 +1,123 @@
+use super::rust_crypto::RustCryptoProvider;
+use super::*;
+use crate::error::Error;
+
+fn providers() -> Vec<Box<dyn CryptoProvider>> {
+    vec![
+        Box::new(RustCryptoProvider),
+        #[cfg(feature = "ring")]
+        Box::new(super::ring::RingProvider),
+        #[cfg(feature = "openssl")]
+        Box::new(super::openssl::OpensslProvider),
+    ]
+}
+
+#[test]
+fn test_aes_ctr() -> Result<()> {
+    // Keystream Test Vectors from https://tools.ietf.org/html/rfc3711#appendix-B.2
+    let key = [
+        0x2B, 0x7E, 0x15, 0x16, 0x28, 0xAE, 0xD2, 0xA6, 0xAB, 0xF7, 0x15, 0x88, 0x09, 0xCF, 0x4F,
+        0x3C,
+    ];
+    let iv = [
+        0xF0, 0xF1, 0xF2, 0xF3, 0xF4, 0xF5, 0xF6, 0xF7, 0xF8, 0xF9, 0xFA, 0xFB, 0xFC, 0xFD, 0x00,
+        0x00,
+    ];
+    let expected_keystream = [
+        0xE0, 0x3E, 0xAD, 0x09, 0x35, 0xC9, 0x5E, 0x80, 0xE1, 0x66, 0xB1, 0x6D, 0xD9, 0x2B, 0x4E,
+        0xB4, 0xD2, 0x35, 0x13, 0x16, 0x2B, 0x02, 0xD0, 0xF7, 0x2A, 0x43, 0xA2, 0xFE, 0x4A, 0x5F,
+        0x97, 0xAB, 0x41, 0xE9, 0x5B, 0x3B, 0xB0, 0xA2, 0xE8, 0xDD, 0x47, 0x79, 0x01, 0xE4, 0xFC,
+        0xA8, 0x94, 0xC0,
+    ];
+
+    for provider in providers() {
+        let mut cipher = provider.aes_ctr(&key)?;
+
+        // The counter restarts at iv on each call
+        for _ in 0..2 {
+            let mut keystream = [0u8; 48];
+            cipher.apply_keystream(&iv, &mut keystream)?;
+            assert_eq!(keystream, expected_keystream);
+        }
+
+        // Partial blocks are supported
+        let mut keystream = [0u8; 20];
+        cipher.apply_keystream(&iv, &mut keystream)?;
+        assert_eq!(keystream, expected_keystream[..20]);
+
+        assert_eq!(
+            provider.aes_ctr(&key[..15]).err(),
+            Some(Error::UnsupportedMasterKeyLength(15))
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_aes_gcm() -> Result<()> {
+    // Test Case 2 of the GCM specification
+    let key = [0u8; 16];
+    let nonce = [0u8; 12];
+    let expected_ciphertext = [
+        0x03, 0x88, 0xda, 0xce, 0x60, 0xb6, 0xa3, 0x92, 0xf3, 0x28, 0xc2, 0xb9, 0x71, 0xb2, 0xfe,
+        0x78,
+    ];
+    let expected_tag = [
+        0xab, 0x6e, 0x47, 0xd4, 0x2c, 0xec, 0x13, 0xbd, 0xf5, 0x3a, 0x67, 0xb2, 0x12, 0x57, 0xbd,
+        0xdf,
+    ];
+
+    for provider in providers() {
+        let mut cipher = provider.aes_gcm(&key)?;
+
+        let mut msg = [0u8; 16];
+        let tag = cipher.encrypt_in_place(&nonce, &[], &mut msg)?;
+        assert_eq!(msg, expected_ciphertext);
+        assert_eq!(tag, expected_tag);
+
+        // A tampered message is rejected and left as is
+        let mut tampered_tag = tag;
+        tampered_tag[0] ^= 1;
+        assert!(cipher
+            .decrypt_in_place(&nonce, &[], &mut msg, &tampered_tag)
+            .is_err());
+        assert_eq!(msg, expected_ciphertext);
+        assert!(cipher
+            .decrypt_in_place(&nonce, &[0], &mut msg, &tag)
+            .is_err());
+        assert_eq!(msg, expected_ciphertext);
+
+        cipher.decrypt_in_place(&nonce, &[], &mut msg, &tag)?;
+        assert_eq!(msg, [0u8; 16]);
+
+        assert_eq!(
+            provider.aes_gcm(&[0u8; 24]).err(),
+            Some(Error::UnsupportedMasterKeyLength(24))
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_hmac_sha1() -> Result<()> {
+    // Test Case 2 from https://tools.ietf.org/html/rfc2202#section-3
+    let expected_code = [
+        0xef, 0xfc, 0xdf, 0x6a, 0xe5, 0xeb, 0x2f, 0xa2, 0xd2, 0x74, 0x16, 0xd5, 0xf1, 0x84, 0xdf,
+        0x9c, 0x25, 0x9a, 0x7c, 0x79,
+    ];
+
+    for provider in providers() {
+        let mac = provider.hmac_sha1(b"Jefe")?;
+        assert_eq!(mac.sign(&[b"what do ya want for nothing?"])?, expected_code);
+
+        // The parts are signed as their concatenation, and the key is reused
+        assert_eq!(
+            mac.sign(&[b"what do ya ", b"", b"want for nothing?"])?,
+            expected_code
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod crypto_test;
+
+#[cfg(feature = "openssl")]
+pub mod openssl;
+#[cfg(feature = "ring")]
+pub mod ring;
+pub mod rust_crypto;
+
+use std::sync::{Arc, OnceLock};
+
+use crate::error::Result;
+
+pub const AES_BLOCK_SIZE: usize = 16;
+pub const AEAD_TAG_LEN: usize = 16;
+pub const HMAC_SHA1_LEN: usize = 20;
+
+/// CryptoProvider supplies the primitives of the SRTP protection profiles and
+/// of the key derivation, so that they can be backed by a certified module
+/// without forking this crate.
+///
+/// The provider is process wide, see `install_default`.
+pub trait CryptoProvider: Send + Sync {
+    /// aes_ctr returns AES in counter mode keyed with key, a 128 or 256 bits key.
+    fn aes_ctr(&self, key: &[u8]) -> Result<Box<dyn StreamCipher>>;
+
+    /// aes_gcm returns AES-GCM keyed with key, a 128 or 256 bits key.
+    fn aes_gcm(&self, key: &[u8]) -> Result<Box<dyn AeadCipher>>;
+
+    /// hmac_sha1 returns HMAC-SHA1 keyed with key.
+    fn hmac_sha1(&self, key: &[u8]) -> Result<Box<dyn Mac>>;
+}
+
+/// StreamCipher is a block cipher in counter mode.
+pub trait StreamCipher: Send {
+    /// apply_keystream xors data with the key stream starting at the counter block iv.
+    fn apply_keystream(&mut self, iv: &[u8; AES_BLOCK_SIZE], data: &mut [u8]) -> Result<()>;
+}
+
+/// AeadCipher is an AEAD cipher with `AEAD_TAG_LEN` bytes tags.
+pub trait AeadCipher: Send {
+    /// encrypt_in_place encrypts msg in place and returns the tag authenticating it with aad.
+    fn encrypt_in_place(
+        &mut self,
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &mut [u8],
+    ) -> Result<[u8; AEAD_TAG_LEN]>;
+
+    /// decrypt_in_place decrypts msg in place if tag verifies, msg is left unchanged otherwise.
+    fn decrypt_in_place(
+        &mut self,
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &mut [u8],
+        tag: &[u8],
+    ) -> Result<()>;
+}
+
+/// Mac is a keyed HMAC-SHA1.
+pub trait Mac: Send {
+    /// sign returns the code of the concatenation of parts.
+    fn sign(&self, parts: &[&[u8]]) -> Result<[u8; HMAC_SHA1_LEN]>;
+}
+
+static DEFAULT_PROVIDER: OnceLock<Arc<dyn CryptoProvider>> = OnceLock::new();
+
+/// install_default sets the provider of the contexts of this process. It must be
+/// called before the first context is created, the provider is handed back otherwise.
+pub fn install_default(
+    provider: Arc<dyn CryptoProvider>,
+) -> std::result::Result<(), Arc<dyn CryptoProvider>> {
+    DEFAULT_PROVIDER.set(provider)
+}
+
+/// get_default returns the installed provider. Unless one was installed it's
+/// `rust_crypto::RustCryptoProvider`, `ring::RingProvider` with the `ring` feature,
+/// or `openssl::OpensslProvider` with the `openssl` feature.
+pub fn get_default() -> &'static Arc<dyn CryptoProvider> {
+    DEFAULT_PROVIDER.get_or_init(|| {
+        #[cfg(feature = "openssl")]
+        let provider = Arc::new(self::openssl::OpensslProvider);
+        #[cfg(all(feature = "ring", not(feature = "openssl")))]
+        let provider = Arc::new(self::ring::RingProvider);
+        #[cfg(not(any(feature = "ring", feature = "openssl")))]
+        let provider = Arc::new(self::rust_crypto::RustCryptoProvider);
+
+        provider
+    })
+}
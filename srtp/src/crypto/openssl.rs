@@ -0,0 +1,141 @@
+use ::openssl::cipher::{Cipher, CipherRef};
+use ::openssl::cipher_ctx::CipherCtx;
+use ::openssl::hash::MessageDigest;
+use ::openssl::pkey::{PKey, Private};
+use ::openssl::sign::Signer;
+
+use super::*;
+use crate::error::Error;
+
+/// OpensslProvider implements the primitives with OpenSSL, which allows using
+/// a FIPS validated provider when OpenSSL is configured accordingly.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OpensslProvider;
+
+impl CryptoProvider for OpensslProvider {
+    fn aes_ctr(&self, key: &[u8]) -> Result<Box<dyn StreamCipher>> {
+        let cipher = match key.len() {
+            16 => Cipher::aes_128_ctr(),
+            32 => Cipher::aes_256_ctr(),
+            n => return Err(Error::UnsupportedMasterKeyLength(n)),
+        };
+
+        let mut ctx = CipherCtx::new().map_err(other)?;
+        ctx.encrypt_init(Some(cipher), Some(key), None)
+            .map_err(other)?;
+
+        Ok(Box::new(AesCtr(ctx)))
+    }
+
+    fn aes_gcm(&self, key: &[u8]) -> Result<Box<dyn AeadCipher>> {
+        let cipher = match key.len() {
+            16 => Cipher::aes_128_gcm(),
+            32 => Cipher::aes_256_gcm(),
+            n => return Err(Error::UnsupportedMasterKeyLength(n)),
+        };
+
+        Ok(Box::new(AesGcm {
+            cipher,
+            key: key.to_vec(),
+        }))
+    }
+
+    fn hmac_sha1(&self, key: &[u8]) -> Result<Box<dyn Mac>> {
+        Ok(Box::new(HmacSha1(PKey::hmac(key).map_err(other)?)))
+    }
+}
+
+fn other(err: ::openssl::error::ErrorStack) -> Error {
+    Error::Other(err.to_string())
+}
+
+struct AesCtr(CipherCtx);
+
+impl StreamCipher for AesCtr {
+    fn apply_keystream(&mut self, iv: &[u8; AES_BLOCK_SIZE], data: &mut [u8]) -> Result<()> {
+        let len = data.len();
+        self.0.encrypt_init(None, None, Some(iv)).map_err(other)?;
+        self.0.cipher_update_inplace(data, len).map_err(other)?;
+        self.0.cipher_final(&mut []).map_err(other)?;
+        Ok(())
+    }
+}
+
+struct AesGcm {
+    cipher: &'static CipherRef,
+    key: Vec<u8>,
+}
+
+impl AesGcm {
+    fn apply(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &mut [u8],
+        finish: impl FnOnce(&mut CipherCtx) -> std::result::Result<(), ::openssl::error::ErrorStack>,
+    ) -> std::result::Result<(), ::openssl::error::ErrorStack> {
+        let len = msg.len();
+        let mut ctx = CipherCtx::new()?;
+        ctx.encrypt_init(Some(self.cipher), Some(&self.key), Some(nonce))?;
+        ctx.cipher_update(aad, None)?;
+        ctx.cipher_update_inplace(msg, len)?;
+        ctx.cipher_final(&mut [])?;
+        finish(&mut ctx)
+    }
+}
+
+impl AeadCipher for AesGcm {
+    fn encrypt_in_place(
+        &mut self,
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &mut [u8],
+    ) -> Result<[u8; AEAD_TAG_LEN]> {
+        let mut tag = [0u8; AEAD_TAG_LEN];
+        self.apply(nonce, aad, msg, |ctx| ctx.tag(&mut tag))
+            .map_err(other)?;
+
+        Ok(tag)
+    }
+
+    fn decrypt_in_place(
+        &mut self,
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &mut [u8],
+        tag: &[u8],
+    ) -> Result<()> {
+        let len = msg.len();
+        let mut ctx = CipherCtx::new().map_err(other)?;
+        let verified = ctx
+            .decrypt_init(Some(self.cipher), Some(&self.key), Some(nonce))
+            .and_then(|_| ctx.set_tag(tag))
+            .and_then(|_| ctx.cipher_update(aad, None))
+            .and_then(|_| ctx.cipher_update_inplace(msg, len))
+            .and_then(|_| ctx.cipher_final(&mut []));
+
+        if verified.is_err() {
+            // OpenSSL only verifies the tag after decrypting, GCM being a stream
+            // cipher encrypting again restores the ciphertext.
+            self.apply(nonce, aad, msg, |_| Ok(())).map_err(other)?;
+            return Err(Error::ErrFailedToVerifyAuthTag);
+        }
+
+        Ok(())
+    }
+}
+
+struct HmacSha1(PKey<Private>);
+
+impl Mac for HmacSha1 {
+    fn sign(&self, parts: &[&[u8]]) -> Result<[u8; HMAC_SHA1_LEN]> {
+        let mut signer = Signer::new(MessageDigest::sha1(), &self.0).map_err(other)?;
+        for part in parts {
+            signer.update(part).map_err(other)?;
+        }
+
+        let mut code = [0u8; HMAC_SHA1_LEN];
+        signer.sign(&mut code).map_err(other)?;
+        Ok(code)
+    }
+}
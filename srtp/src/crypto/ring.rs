@@ -0,0 +1,106 @@
+use ::ring::aead::{Aad, LessSafeKey, Nonce, Tag, UnboundKey, AES_128_GCM, AES_256_GCM};
+use ::ring::error::Unspecified;
+use ::ring::hmac;
+
+use super::rust_crypto::RustCryptoProvider;
+use super::*;
+use crate::error::Error;
+
+/// RingProvider implements AES-GCM and HMAC-SHA1 with ring. ring doesn't expose
+/// AES in counter mode, it's left to `RustCryptoProvider`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RingProvider;
+
+impl CryptoProvider for RingProvider {
+    fn aes_ctr(&self, key: &[u8]) -> Result<Box<dyn StreamCipher>> {
+        RustCryptoProvider.aes_ctr(key)
+    }
+
+    fn aes_gcm(&self, key: &[u8]) -> Result<Box<dyn AeadCipher>> {
+        let algorithm = match key.len() {
+            16 => &AES_128_GCM,
+            32 => &AES_256_GCM,
+            n => return Err(Error::UnsupportedMasterKeyLength(n)),
+        };
+
+        Ok(Box::new(AesGcm {
+            key: LessSafeKey::new(UnboundKey::new(algorithm, key).map_err(other)?),
+            ciphertext: vec![],
+        }))
+    }
+
+    fn hmac_sha1(&self, key: &[u8]) -> Result<Box<dyn Mac>> {
+        Ok(Box::new(HmacSha1(hmac::Key::new(
+            hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY,
+            key,
+        ))))
+    }
+}
+
+fn other(err: Unspecified) -> Error {
+    Error::Other(err.to_string())
+}
+
+struct AesGcm {
+    key: LessSafeKey,
+    /// Copy of the message being decrypted, ring zeroes it when the tag doesn't verify.
+    ciphertext: Vec<u8>,
+}
+
+impl AeadCipher for AesGcm {
+    fn encrypt_in_place(
+        &mut self,
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &mut [u8],
+    ) -> Result<[u8; AEAD_TAG_LEN]> {
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(other)?;
+        let tag = self
+            .key
+            .seal_in_place_separate_tag(nonce, Aad::from(aad), msg)
+            .map_err(other)?;
+
+        let mut out = [0u8; AEAD_TAG_LEN];
+        out.copy_from_slice(tag.as_ref());
+        Ok(out)
+    }
+
+    fn decrypt_in_place(
+        &mut self,
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &mut [u8],
+        tag: &[u8],
+    ) -> Result<()> {
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(other)?;
+        let tag = Tag::try_from(tag).map_err(|_| Error::ErrFailedToVerifyAuthTag)?;
+
+        self.ciphertext.clear();
+        self.ciphertext.extend_from_slice(msg);
+        if self
+            .key
+            .open_in_place_separate_tag(nonce, Aad::from(aad), tag, msg, 0..)
+            .is_err()
+        {
+            msg.copy_from_slice(&self.ciphertext);
+            return Err(Error::ErrFailedToVerifyAuthTag);
+        }
+
+        Ok(())
+    }
+}
+
+struct HmacSha1(hmac::Key);
+
+impl Mac for HmacSha1 {
+    fn sign(&self, parts: &[&[u8]]) -> Result<[u8; HMAC_SHA1_LEN]> {
+        let mut ctx = hmac::Context::with_key(&self.0);
+        for part in parts {
+            ctx.update(part);
+        }
+
+        let mut code = [0u8; HMAC_SHA1_LEN];
+        code.copy_from_slice(ctx.sign().as_ref());
+        Ok(code)
+    }
+}
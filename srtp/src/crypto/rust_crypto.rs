@@ -0,0 +1,119 @@
+use aes::cipher::generic_array::GenericArray;
+use aes::cipher::{InnerIvInit, KeyInit, StreamCipher as _};
+use aes::{Aes128, Aes256};
+use aes_gcm::aead::AeadInPlace;
+use aes_gcm::{Aes128Gcm, Aes256Gcm, Nonce};
+use ctr::CtrCore;
+use hmac::{Hmac, Mac as _};
+use sha1::Sha1;
+
+use super::*;
+use crate::error::Error;
+
+/// RustCryptoProvider implements the primitives with the RustCrypto crates.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RustCryptoProvider;
+
+impl CryptoProvider for RustCryptoProvider {
+    fn aes_ctr(&self, key: &[u8]) -> Result<Box<dyn StreamCipher>> {
+        match key.len() {
+            16 => Ok(Box::new(AesCtr::Aes128(Box::new(Aes128::new(
+                GenericArray::from_slice(key),
+            ))))),
+            32 => Ok(Box::new(AesCtr::Aes256(Box::new(Aes256::new(
+                GenericArray::from_slice(key),
+            ))))),
+            _ => Err(Error::UnsupportedMasterKeyLength(key.len())),
+        }
+    }
+
+    fn aes_gcm(&self, key: &[u8]) -> Result<Box<dyn AeadCipher>> {
+        match key.len() {
+            16 => Ok(Box::new(AesGcm::Aes128(Box::new(Aes128Gcm::new(
+                GenericArray::from_slice(key),
+            ))))),
+            32 => Ok(Box::new(AesGcm::Aes256(Box::new(Aes256Gcm::new(
+                GenericArray::from_slice(key),
+            ))))),
+            _ => Err(Error::UnsupportedMasterKeyLength(key.len())),
+        }
+    }
+
+    fn hmac_sha1(&self, key: &[u8]) -> Result<Box<dyn Mac>> {
+        Ok(Box::new(HmacSha1(
+            <Hmac<Sha1> as hmac::Mac>::new_from_slice(key)
+                .map_err(|e| Error::Other(e.to_string()))?,
+        )))
+    }
+}
+
+enum AesCtr {
+    Aes128(Box<Aes128>),
+    Aes256(Box<Aes256>),
+}
+
+impl StreamCipher for AesCtr {
+    fn apply_keystream(&mut self, iv: &[u8; AES_BLOCK_SIZE], data: &mut [u8]) -> Result<()> {
+        let iv = GenericArray::from_slice(iv);
+        match self {
+            AesCtr::Aes128(c) => {
+                ctr::Ctr128BE::<Aes128>::from_core(CtrCore::inner_iv_init((**c).clone(), iv))
+                    .apply_keystream(data)
+            }
+            AesCtr::Aes256(c) => {
+                ctr::Ctr128BE::<Aes256>::from_core(CtrCore::inner_iv_init((**c).clone(), iv))
+                    .apply_keystream(data)
+            }
+        }
+        Ok(())
+    }
+}
+
+enum AesGcm {
+    Aes128(Box<Aes128Gcm>),
+    Aes256(Box<Aes256Gcm>),
+}
+
+impl AeadCipher for AesGcm {
+    fn encrypt_in_place(
+        &mut self,
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &mut [u8],
+    ) -> Result<[u8; AEAD_TAG_LEN]> {
+        let nonce = Nonce::from_slice(nonce);
+        let tag = match self {
+            AesGcm::Aes128(c) => c.encrypt_in_place_detached(nonce, aad, msg)?,
+            AesGcm::Aes256(c) => c.encrypt_in_place_detached(nonce, aad, msg)?,
+        };
+        Ok(tag.into())
+    }
+
+    fn decrypt_in_place(
+        &mut self,
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &mut [u8],
+        tag: &[u8],
+    ) -> Result<()> {
+        let nonce = Nonce::from_slice(nonce);
+        let tag = GenericArray::from_slice(tag);
+        match self {
+            AesGcm::Aes128(c) => c.decrypt_in_place_detached(nonce, aad, msg, tag)?,
+            AesGcm::Aes256(c) => c.decrypt_in_place_detached(nonce, aad, msg, tag)?,
+        };
+        Ok(())
+    }
+}
+
+struct HmacSha1(Hmac<Sha1>);
+
+impl Mac for HmacSha1 {
+    fn sign(&self, parts: &[&[u8]]) -> Result<[u8; HMAC_SHA1_LEN]> {
+        let mut signer = self.0.clone();
+        for part in parts {
+            signer.update(part);
+        }
+        Ok(signer.finalize().into_bytes().into())
+    }
+}
@@ -0,0 +1,94 @@
+use super::*;
+
+const EKT_KEY: [u8; 16] = [
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+];
+
+fn test_plaintext() -> EktPlaintext {
+    EktPlaintext {
+        srtp_master_key: vec![
+            0x0d, 0xcd, 0x21, 0x3e, 0x4c, 0xbc, 0xf2, 0x8f, 0x01, 0x7f, 0x69, 0x94, 0x40, 0x1e,
+            0x28, 0x89,
+        ],
+        ssrc: 0xcafebabe,
+        roc: 3,
+    }
+}
+
+#[test]
+fn test_ekt_short_field() -> Result<()> {
+    let ekt = Ekt::new(EktCipher::AesKw128, &EKT_KEY, 0x1234)?;
+    let srtp = vec![0x80, 0x0f, 0x12, 0x34, 0xde, 0xca, 0xfb, 0xad];
+
+    let packet = ekt.append_field(&srtp, None)?;
+    assert_eq!(packet.len(), srtp.len() + 1);
+    assert_eq!(packet[packet.len() - 1], EKT_MSG_TYPE_SHORT);
+
+    let (stripped, field) = ekt.strip_field(&packet)?;
+    assert_eq!(&stripped[..], &srtp[..]);
+    assert_eq!(field, EktField::Short);
+
+    Ok(())
+}
+
+#[test]
+fn test_ekt_full_field() -> Result<()> {
+    for (cipher, key) in [
+        (EktCipher::AesKw128, EKT_KEY.to_vec()),
+        (EktCipher::AesKw256, [EKT_KEY, EKT_KEY].concat()),
+    ] {
+        let ekt = Ekt::new(cipher, &key, 0x1234)?;
+        let srtp = vec![0x80, 0x0f, 0x12, 0x34, 0xde, 0xca, 0xfb, 0xad];
+        let plaintext = test_plaintext();
+
+        let packet = ekt.append_field(&srtp, Some(&plaintext))?;
+        // 25 bytes of plaintext are padded to 32 and wrapped into 40 bytes
+        assert_eq!(packet.len(), srtp.len() + 40 + FULL_EKT_FIELD_TRAILER_LEN);
+        assert_eq!(packet[packet.len() - 1], EKT_MSG_TYPE_FULL);
+
+        let (stripped, field) = ekt.strip_field(&packet)?;
+        assert_eq!(&stripped[..], &srtp[..]);
+        assert_eq!(
+            field,
+            EktField::Full {
+                spi: 0x1234,
+                plaintext,
+            }
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_ekt_full_field_errors() -> Result<()> {
+    let ekt = Ekt::new(EktCipher::AesKw128, &EKT_KEY, 0x1234)?;
+    let srtp = vec![0x80, 0x0f, 0x12, 0x34];
+    let plaintext = test_plaintext();
+    let packet = ekt.append_field(&srtp, Some(&plaintext))?;
+
+    let other_spi = Ekt::new(EktCipher::AesKw128, &EKT_KEY, 0x4321)?;
+    assert_eq!(
+        other_spi.strip_field(&packet),
+        Err(Error::EktSpiMismatch(0x4321, 0x1234))
+    );
+
+    let mut tampered = packet.to_vec();
+    tampered[srtp.len()] ^= 0x01;
+    assert!(matches!(
+        ekt.strip_field(&tampered),
+        Err(Error::EktKeyWrap(_))
+    ));
+
+    assert_eq!(
+        ekt.strip_field(&[0x80, 0x01]),
+        Err(Error::EktInvalidMessageType(0x01))
+    );
+    assert_eq!(ekt.strip_field(&[]), Err(Error::EktTooShort));
+    assert_eq!(
+        Ekt::new(EktCipher::AesKw256, &EKT_KEY, 0).err(),
+        Some(Error::EktKeyLength(32, 16))
+    );
+
+    Ok(())
+}
@@ -0,0 +1,229 @@
+#[cfg(test)]
+mod ekt_test;
+
+use aes::{Aes128, Aes256};
+use aes_kw::Kek;
+use bytes::{BufMut, Bytes, BytesMut};
+
+use crate::error::{Error, Result};
+
+/// EKTMsgType of a ShortEKTField
+pub const EKT_MSG_TYPE_SHORT: u8 = 0x00;
+/// EKTMsgType of a FullEKTField
+pub const EKT_MSG_TYPE_FULL: u8 = 0x02;
+
+// SPI (2 bytes) | EKTLen (2 bytes) | EKTMsgType (1 byte)
+const FULL_EKT_FIELD_TRAILER_LEN: usize = 5;
+// SRTPMasterKeyLength (1 byte) | SSRC (4 bytes) | ROC (4 bytes)
+const EKT_PLAINTEXT_FIXED_LEN: usize = 9;
+
+/// EktCipher is the key wrap algorithm protecting EKTCiphertext.
+/// The values match the EKTCipherType negotiated in DTLS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum EktCipher {
+    AesKw128 = 1,
+    AesKw256 = 2,
+}
+
+impl EktCipher {
+    pub fn key_len(&self) -> usize {
+        match *self {
+            EktCipher::AesKw128 => 16,
+            EktCipher::AesKw256 => 32,
+        }
+    }
+}
+
+/// EktPlaintext is the information transported, encrypted, in a FullEKTField
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EktPlaintext {
+    pub srtp_master_key: Vec<u8>,
+    pub ssrc: u32,
+    pub roc: u32,
+}
+
+/// EktField is the trailer carried by an SRTP packet when EKT is in use
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EktField {
+    Short,
+    Full { spi: u16, plaintext: EktPlaintext },
+}
+
+/// Ekt appends and strips the EKT fields defined in RFC 8870 to/from SRTP packets.
+///
+/// The EKTKey, SPI and cipher are normally obtained from the conference
+/// controller, either through the DTLS EKTKey message or application
+/// signaling. All participants sharing the same EKTKey can recover the SRTP
+/// master key of any sender from its FullEKTFields, which allows an SFU to
+/// switch the SSRC it forwards without a new key exchange.
+///
+/// ## Specifications
+///
+/// * [RFC 8870 §4.1]
+///
+/// [RFC 8870 §4.1]: https://tools.ietf.org/html/rfc8870#section-4.1
+pub struct Ekt {
+    cipher: EktCipher,
+    key: Vec<u8>,
+    spi: u16,
+}
+
+impl Ekt {
+    pub fn new(cipher: EktCipher, key: &[u8], spi: u16) -> Result<Self> {
+        if key.len() != cipher.key_len() {
+            return Err(Error::EktKeyLength(cipher.key_len(), key.len()));
+        }
+
+        Ok(Ekt {
+            cipher,
+            key: key.to_vec(),
+            spi,
+        })
+    }
+
+    pub fn cipher(&self) -> EktCipher {
+        self.cipher
+    }
+
+    pub fn spi(&self) -> u16 {
+        self.spi
+    }
+
+    /// marshal_full_field builds a FullEKTField carrying the given plaintext
+    pub fn marshal_full_field(&self, plaintext: &EktPlaintext) -> Result<Bytes> {
+        if plaintext.srtp_master_key.is_empty() || plaintext.srtp_master_key.len() > 255 {
+            return Err(Error::EktInvalidPlaintext);
+        }
+
+        let mut raw =
+            BytesMut::with_capacity(EKT_PLAINTEXT_FIXED_LEN + plaintext.srtp_master_key.len());
+        raw.put_u8(plaintext.srtp_master_key.len() as u8);
+        raw.extend_from_slice(&plaintext.srtp_master_key);
+        raw.put_u32(plaintext.ssrc);
+        raw.put_u32(plaintext.roc);
+
+        let ciphertext = self.wrap(&raw)?;
+        let ekt_len = ciphertext.len() + FULL_EKT_FIELD_TRAILER_LEN;
+        if ekt_len > u16::MAX as usize {
+            return Err(Error::EktInvalidPlaintext);
+        }
+
+        let mut field = BytesMut::with_capacity(ekt_len);
+        field.extend_from_slice(&ciphertext);
+        field.put_u16(self.spi);
+        field.put_u16(ekt_len as u16);
+        field.put_u8(EKT_MSG_TYPE_FULL);
+
+        Ok(field.freeze())
+    }
+
+    /// append_field appends a FullEKTField if plaintext is given, or a
+    /// ShortEKTField otherwise, to a protected SRTP packet.
+    pub fn append_field(&self, srtp: &[u8], plaintext: Option<&EktPlaintext>) -> Result<Bytes> {
+        let mut packet = BytesMut::from(srtp);
+        if let Some(plaintext) = plaintext {
+            packet.extend_from_slice(&self.marshal_full_field(plaintext)?);
+        } else {
+            packet.put_u8(EKT_MSG_TYPE_SHORT);
+        }
+
+        Ok(packet.freeze())
+    }
+
+    /// strip_field removes the EKT field from the end of a protected SRTP
+    /// packet, returning the SRTP packet and the decoded field.
+    pub fn strip_field(&self, packet: &[u8]) -> Result<(Bytes, EktField)> {
+        let msg_type = match packet.last() {
+            Some(msg_type) => *msg_type,
+            None => return Err(Error::EktTooShort),
+        };
+
+        match msg_type {
+            EKT_MSG_TYPE_SHORT => Ok((
+                Bytes::copy_from_slice(&packet[..packet.len() - 1]),
+                EktField::Short,
+            )),
+            EKT_MSG_TYPE_FULL => {
+                if packet.len() < FULL_EKT_FIELD_TRAILER_LEN {
+                    return Err(Error::EktTooShort);
+                }
+
+                let trailer = &packet[packet.len() - FULL_EKT_FIELD_TRAILER_LEN..];
+                let spi = u16::from_be_bytes([trailer[0], trailer[1]]);
+                let ekt_len = u16::from_be_bytes([trailer[2], trailer[3]]) as usize;
+                if ekt_len < FULL_EKT_FIELD_TRAILER_LEN || ekt_len > packet.len() {
+                    return Err(Error::EktTooShort);
+                }
+                if spi != self.spi {
+                    return Err(Error::EktSpiMismatch(self.spi, spi));
+                }
+
+                let srtp_len = packet.len() - ekt_len;
+                let ciphertext = &packet[srtp_len..packet.len() - FULL_EKT_FIELD_TRAILER_LEN];
+                let raw = self.unwrap(ciphertext)?;
+                let plaintext = unmarshal_plaintext(&raw)?;
+
+                Ok((
+                    Bytes::copy_from_slice(&packet[..srtp_len]),
+                    EktField::Full { spi, plaintext },
+                ))
+            }
+            _ => Err(Error::EktInvalidMessageType(msg_type)),
+        }
+    }
+
+    fn wrap(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let result = match self.cipher {
+            EktCipher::AesKw128 => Kek::<Aes128>::try_from(self.key.as_slice())
+                .and_then(|kek| kek.wrap_with_padding_vec(data)),
+            EktCipher::AesKw256 => Kek::<Aes256>::try_from(self.key.as_slice())
+                .and_then(|kek| kek.wrap_with_padding_vec(data)),
+        };
+
+        result.map_err(|e| Error::EktKeyWrap(e.to_string()))
+    }
+
+    fn unwrap(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let result = match self.cipher {
+            EktCipher::AesKw128 => Kek::<Aes128>::try_from(self.key.as_slice())
+                .and_then(|kek| kek.unwrap_with_padding_vec(data)),
+            EktCipher::AesKw256 => Kek::<Aes256>::try_from(self.key.as_slice())
+                .and_then(|kek| kek.unwrap_with_padding_vec(data)),
+        };
+
+        result.map_err(|e| Error::EktKeyWrap(e.to_string()))
+    }
+}
+
+fn unmarshal_plaintext(raw: &[u8]) -> Result<EktPlaintext> {
+    if raw.is_empty() {
+        return Err(Error::EktInvalidPlaintext);
+    }
+
+    let key_len = raw[0] as usize;
+    if key_len == 0 || raw.len() != EKT_PLAINTEXT_FIXED_LEN + key_len {
+        return Err(Error::EktInvalidPlaintext);
+    }
+
+    let srtp_master_key = raw[1..1 + key_len].to_vec();
+    let offset = 1 + key_len;
+    let ssrc = u32::from_be_bytes([
+        raw[offset],
+        raw[offset + 1],
+        raw[offset + 2],
+        raw[offset + 3],
+    ]);
+    let roc = u32::from_be_bytes([
+        raw[offset + 4],
+        raw[offset + 5],
+        raw[offset + 6],
+        raw[offset + 7],
+    ]);
+
+    Ok(EktPlaintext {
+        srtp_master_key,
+        ssrc,
+        roc,
+    })
+}
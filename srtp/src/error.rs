@@ -40,13 +40,33 @@ pub enum Error {
     ErrStreamAlreadyInited,
     #[error("failed to cast child")]
     ErrFailedTypeAssertion,
+    #[error("received a cryptex protected packet but cryptex is disabled")]
+    ErrCryptexDisabled,
+    #[error("cryptex is required but the packet carries CSRCs or header extensions in the clear")]
+    ErrCryptexRequired,
+    #[error("SRTCP encryption is required but the packet is only authenticated")]
+    ErrSrtcpEncryptionRequired,
+    #[error("MKI is not enabled")]
+    ErrMkiNotEnabled,
+    #[error("MKI is already in use")]
+    ErrMkiAlreadyInUse,
+    #[error("MKI not found")]
+    ErrMkiNotFound,
+    #[error("the master key has protected the maximum number of SRTP packets")]
+    ErrSrtpKeyLifetimeExhausted,
+    #[error("the master key has protected the maximum number of SRTCP packets")]
+    ErrSrtcpKeyLifetimeExhausted,
 
     #[error("index_over_kdr > 0 is not supported yet")]
     UnsupportedIndexOverKdr,
+    #[error("AES master key of len {0} is not supported")]
+    UnsupportedMasterKeyLength(usize),
     #[error("SRTP Master Key must be len {0}, got {1}")]
     SrtpMasterKeyLength(usize, usize),
     #[error("SRTP Salt must be len {0}, got {1}")]
     SrtpSaltLength(usize, usize),
+    #[error("MKI must be len {0}, got {1}")]
+    MkiLength(usize, usize),
     #[error("SyntaxError: {0}")]
     ExtMapParse(String),
     #[error("srtp ssrc={0} index={1}: duplicated")]
@@ -77,6 +97,26 @@ pub enum Error {
     InvalidRtpStream,
     #[error("this stream is not a RTCPStream")]
     InvalidRtcpStream,
+    #[error("EKT key must be len {0}, got {1}")]
+    EktKeyLength(usize, usize),
+    #[error("EKT field is too short")]
+    EktTooShort,
+    #[error("unknown EKT message type {0}")]
+    EktInvalidMessageType(u8),
+    #[error("EKT SPI mismatch: expected {0}, got {1}")]
+    EktSpiMismatch(u16, u16),
+    #[error("invalid EKT plaintext")]
+    EktInvalidPlaintext,
+    #[error("EKT key wrap: {0}")]
+    EktKeyWrap(String),
+    #[error("cryptex cannot protect header extension profile {0:#06x}")]
+    CryptexUnsupportedExtensionProfile(u16),
+    #[error("invalid crypto attribute: {0}")]
+    SdesInvalidCryptoAttribute(String),
+    #[error("unsupported SDES crypto suite {0}")]
+    SdesUnsupportedCryptoSuite(String),
+    #[error("SDES crypto suites differ: local {0}, remote {1}")]
+    SdesCryptoSuiteMismatch(String, String),
 
     #[error("{0}")]
     Io(#[source] IoError),
@@ -1,8 +1,4 @@
-use aes::cipher::generic_array::GenericArray;
-use aes::cipher::BlockEncrypt;
-use aes::Aes128;
-use aes_gcm::KeyInit;
-
+use crate::crypto::{self, AES_BLOCK_SIZE};
 use crate::error::{Error, Result};
 
 pub const LABEL_SRTP_ENCRYPTION: u8 = 0x00;
@@ -31,30 +27,26 @@ pub(crate) fn aes_cm_key_derivation(
     // concatenation of the encryption key label 0x00 with (index DIV kdr),
     // - index is 'rollover count' and DIV is 'divided by'
 
-    let n_master_key = master_key.len();
     let n_master_salt = master_salt.len();
 
-    let mut prf_in = vec![0u8; n_master_key];
+    // The PRF input is a single AES block regardless of the key size
+    if n_master_salt > AES_BLOCK_SIZE - 2 {
+        return Err(Error::SrtpSaltLength(AES_BLOCK_SIZE - 2, n_master_salt));
+    }
+
+    let mut prf_in = [0u8; AES_BLOCK_SIZE];
     prf_in[..n_master_salt].copy_from_slice(master_salt);
 
     prf_in[7] ^= label;
 
-    //The resulting value is then AES encrypted using the master key to get the cipher key.
-    let key = GenericArray::from_slice(master_key);
-    let block = Aes128::new(key);
-
-    let mut out = vec![0u8; ((out_len + n_master_key) / n_master_key) * n_master_key];
-    for (i, n) in (0..out_len).step_by(n_master_key).enumerate() {
-        //BigEndian.PutUint16(prfIn[nMasterKey-2:], i)
-        prf_in[n_master_key - 2] = ((i >> 8) & 0xFF) as u8;
-        prf_in[n_master_key - 1] = (i & 0xFF) as u8;
-
-        out[n..n + n_master_key].copy_from_slice(&prf_in);
-        let out_key = GenericArray::from_mut_slice(&mut out[n..n + n_master_key]);
-        block.encrypt_block(out_key);
-    }
+    // The resulting value is then AES encrypted using the master key to get the cipher key,
+    // i.e. the key stream of AES-CM starting at the PRF input, its last two bytes counting the blocks.
+    let mut out = vec![0u8; out_len];
+    crypto::get_default()
+        .aes_ctr(master_key)?
+        .apply_keystream(&prf_in, &mut out)?;
 
-    Ok(out[..out_len].to_vec())
+    Ok(out)
 }
 
 /// Generate IV https://tools.ietf.org/html/rfc3711#section-4.1.1
@@ -161,6 +153,61 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_valid_session_keys_aes_256() -> Result<()> {
+        // AES-256 Key Derivation Test Vectors from https://tools.ietf.org/html/rfc6188#section-7.2
+        let master_key = vec![
+            0xf0, 0xf0, 0x49, 0x14, 0xb5, 0x13, 0xf2, 0x76, 0x3a, 0x1b, 0x1f, 0xa1, 0x30, 0xf1,
+            0x0e, 0x29, 0x98, 0xf6, 0xf6, 0xe4, 0x3e, 0x43, 0x09, 0xd1, 0xe6, 0x22, 0xa0, 0xe3,
+            0x32, 0xb9, 0xf1, 0xb6,
+        ];
+        let master_salt = vec![
+            0x3b, 0x04, 0x80, 0x3d, 0xe5, 0x1e, 0xe7, 0xc9, 0x64, 0x23, 0xab, 0x5b, 0x78, 0xd2,
+        ];
+
+        let expected_session_key = vec![
+            0x5b, 0xa1, 0x06, 0x4e, 0x30, 0xec, 0x51, 0x61, 0x3c, 0xad, 0x92, 0x6c, 0x5a, 0x28,
+            0xef, 0x73, 0x1e, 0xc7, 0xfb, 0x39, 0x7f, 0x70, 0xa9, 0x60, 0x65, 0x3c, 0xaf, 0x06,
+            0x55, 0x4c, 0xd8, 0xc4,
+        ];
+        let expected_session_salt = vec![
+            0xfa, 0x31, 0x79, 0x16, 0x85, 0xca, 0x44, 0x4a, 0x9e, 0x07, 0xc6, 0xc6, 0x4e, 0x93,
+        ];
+        let expected_session_auth_tag = vec![
+            0xfd, 0x9c, 0x32, 0xd3, 0x9e, 0xd5, 0xfb, 0xb5, 0xa9, 0xdc, 0x96, 0xb3, 0x08, 0x18,
+            0x45, 0x4d, 0x13, 0x13, 0xdc, 0x05,
+        ];
+
+        let session_key = aes_cm_key_derivation(
+            LABEL_SRTP_ENCRYPTION,
+            &master_key,
+            &master_salt,
+            0,
+            master_key.len(),
+        )?;
+        assert_eq!(session_key, expected_session_key);
+
+        let session_salt = aes_cm_key_derivation(
+            LABEL_SRTP_SALT,
+            &master_key,
+            &master_salt,
+            0,
+            master_salt.len(),
+        )?;
+        assert_eq!(session_salt, expected_session_salt);
+
+        let session_auth_tag = aes_cm_key_derivation(
+            LABEL_SRTP_AUTHENTICATION_TAG,
+            &master_key,
+            &master_salt,
+            0,
+            expected_session_auth_tag.len(),
+        )?;
+        assert_eq!(session_auth_tag, expected_session_auth_tag);
+
+        Ok(())
+    }
+
     // This test asserts that calling aesCmKeyDerivation with a non-zero indexOverKdr fails
     // Currently this isn't supported, but the API makes sure we can add this in the future
     #[test]
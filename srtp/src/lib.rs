@@ -4,10 +4,14 @@
 mod cipher;
 pub mod config;
 pub mod context;
+pub mod cryptex;
+pub mod crypto;
+pub mod ekt;
 mod error;
 mod key_derivation;
 pub mod option;
 pub mod protection_profile;
+pub mod sdes;
 pub mod session;
 pub mod stream;
 
@@ -0,0 +1,200 @@
+#[cfg(test)]
+mod sdes_test;
+
+use std::fmt;
+
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
+use base64::Engine;
+use rand::RngCore;
+
+use crate::error::{Error, Result};
+use crate::protection_profile::ProtectionProfile;
+
+const KEY_METHOD_INLINE: &str = "inline:";
+const MAX_MKI_LEN: usize = 8;
+
+/// Session parameter asking for SRTCP to be sent authenticated but unencrypted.
+pub const SESSION_PARAM_UNENCRYPTED_SRTCP: &str = "UNENCRYPTED_SRTCP";
+
+/// CryptoAttribute is the value of an SDP `a=crypto` attribute, carrying the master key
+/// and salt one side uses to protect what it sends (SDES, RFC 4568). These keys travel in
+/// the clear in the SDP, so they are only as safe as the signaling channel itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CryptoAttribute {
+    /// Tag identifying the attribute within a media description, echoed in the answer.
+    pub tag: u32,
+    pub profile: ProtectionProfile,
+    pub master_key: Vec<u8>,
+    pub master_salt: Vec<u8>,
+    /// Maximum number of packets protected with the key, if the peer limits it.
+    pub lifetime: Option<u64>,
+    /// MKI identifying the key on the wire, empty if MKIs are not in use.
+    pub mki: Vec<u8>,
+    /// Session parameters such as `UNENCRYPTED_SRTCP`, kept verbatim.
+    pub session_params: Vec<String>,
+}
+
+impl CryptoAttribute {
+    /// has_session_param returns true if the attribute carries the given session parameter.
+    pub fn has_session_param(&self, param: &str) -> bool {
+        self.session_params.iter().any(|p| p == param)
+    }
+}
+
+impl CryptoAttribute {
+    /// generate creates an attribute with a freshly generated random master key and salt.
+    pub fn generate(tag: u32, profile: ProtectionProfile) -> Self {
+        let mut master_key = vec![0u8; profile.key_len()];
+        let mut master_salt = vec![0u8; profile.salt_len()];
+        rand::thread_rng().fill_bytes(&mut master_key);
+        rand::thread_rng().fill_bytes(&mut master_salt);
+
+        CryptoAttribute {
+            tag,
+            profile,
+            master_key,
+            master_salt,
+            lifetime: None,
+            mki: vec![],
+            session_params: vec![],
+        }
+    }
+
+    /// parse reads the value of an `a=crypto` attribute, i.e. everything after `crypto:`.
+    pub fn parse(value: &str) -> Result<Self> {
+        let invalid = || Error::SdesInvalidCryptoAttribute(value.to_owned());
+
+        let mut fields = value.split_whitespace();
+        let tag = fields
+            .next()
+            .and_then(|tag| tag.parse::<u32>().ok())
+            .ok_or_else(invalid)?;
+        let profile = fields
+            .next()
+            .map(profile_from_crypto_suite)
+            .ok_or_else(invalid)??;
+        let key_params = fields.next().ok_or_else(invalid)?;
+        let session_params = fields.map(str::to_owned).collect();
+
+        // Only a single key is supported, as offered by every endpoint in practice.
+        if key_params.contains(';') {
+            return Err(invalid());
+        }
+        let key_info = key_params
+            .strip_prefix(KEY_METHOD_INLINE)
+            .ok_or_else(invalid)?;
+
+        let mut key_info = key_info.split('|');
+        let key_salt = key_info.next().ok_or_else(invalid)?;
+        let key_salt = STANDARD
+            .decode(key_salt)
+            .or_else(|_| STANDARD_NO_PAD.decode(key_salt))
+            .map_err(|_| invalid())?;
+        let key_len = profile.key_len();
+        if key_salt.len() != key_len + profile.salt_len() {
+            return Err(invalid());
+        }
+
+        let mut lifetime = None;
+        let mut mki = vec![];
+        for param in key_info {
+            if let Some((value, len)) = param.split_once(':') {
+                if !mki.is_empty() {
+                    return Err(invalid());
+                }
+                mki = parse_mki(value, len).ok_or_else(invalid)?;
+            } else {
+                if lifetime.is_some() || !mki.is_empty() {
+                    return Err(invalid());
+                }
+                lifetime = Some(parse_lifetime(param).ok_or_else(invalid)?);
+            }
+        }
+
+        Ok(CryptoAttribute {
+            tag,
+            profile,
+            master_key: key_salt[..key_len].to_vec(),
+            master_salt: key_salt[key_len..].to_vec(),
+            lifetime,
+            mki,
+            session_params,
+        })
+    }
+}
+
+impl fmt::Display for CryptoAttribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key_salt = [&self.master_key[..], &self.master_salt[..]].concat();
+        write!(
+            f,
+            "{} {} {}{}",
+            self.tag,
+            crypto_suite(self.profile),
+            KEY_METHOD_INLINE,
+            STANDARD.encode(key_salt)
+        )?;
+
+        if let Some(lifetime) = self.lifetime {
+            if lifetime.is_power_of_two() {
+                write!(f, "|2^{}", lifetime.trailing_zeros())?;
+            } else {
+                write!(f, "|{lifetime}")?;
+            }
+        }
+        if !self.mki.is_empty() {
+            let value = self
+                .mki
+                .iter()
+                .fold(0u64, |value, b| (value << 8) | *b as u64);
+            write!(f, "|{}:{}", value, self.mki.len())?;
+        }
+        for param in &self.session_params {
+            write!(f, " {param}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// crypto_suite returns the SDES name of a protection profile.
+pub fn crypto_suite(profile: ProtectionProfile) -> &'static str {
+    match profile {
+        ProtectionProfile::Aes128CmHmacSha1_80 => "AES_CM_128_HMAC_SHA1_80",
+        ProtectionProfile::Aes128CmHmacSha1_32 => "AES_CM_128_HMAC_SHA1_32",
+        ProtectionProfile::AeadAes128Gcm => "AEAD_AES_128_GCM",
+        ProtectionProfile::AeadAes256Gcm => "AEAD_AES_256_GCM",
+    }
+}
+
+/// profile_from_crypto_suite returns the protection profile of an SDES crypto suite name.
+pub fn profile_from_crypto_suite(suite: &str) -> Result<ProtectionProfile> {
+    match suite {
+        "AES_CM_128_HMAC_SHA1_80" => Ok(ProtectionProfile::Aes128CmHmacSha1_80),
+        "AES_CM_128_HMAC_SHA1_32" => Ok(ProtectionProfile::Aes128CmHmacSha1_32),
+        "AEAD_AES_128_GCM" => Ok(ProtectionProfile::AeadAes128Gcm),
+        "AEAD_AES_256_GCM" => Ok(ProtectionProfile::AeadAes256Gcm),
+        _ => Err(Error::SdesUnsupportedCryptoSuite(suite.to_owned())),
+    }
+}
+
+// The lifetime is either a decimal number of packets or a power of two written as 2^n.
+fn parse_lifetime(lifetime: &str) -> Option<u64> {
+    if let Some(exponent) = lifetime.strip_prefix("2^") {
+        let exponent = exponent.parse::<u32>().ok()?;
+        1u64.checked_shl(exponent)
+    } else {
+        lifetime.parse::<u64>().ok()
+    }
+}
+
+// The MKI is written as its decimal value and its length in bytes on the wire.
+fn parse_mki(value: &str, len: &str) -> Option<Vec<u8>> {
+    let value = value.parse::<u64>().ok()?;
+    let len = len.parse::<usize>().ok()?;
+    if len == 0 || len > MAX_MKI_LEN || (len < MAX_MKI_LEN && value >> (len * 8) != 0) {
+        return None;
+    }
+
+    Some(value.to_be_bytes()[MAX_MKI_LEN - len..].to_vec())
+}
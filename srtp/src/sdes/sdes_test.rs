@@ -0,0 +1,156 @@
+use super::*;
+use crate::config::Config;
+use crate::context::SrtcpEncryptionPolicy;
+
+// RFC 4568 section 4.
+const RFC_EXAMPLE: &str =
+    "1 AES_CM_128_HMAC_SHA1_80 inline:PS1uQCVeeCFCanVmcjkpPywjNWhcYD0mXXtxaVBR|2^20|1:4";
+
+#[test]
+fn test_crypto_attribute_parse() -> Result<()> {
+    let attr = CryptoAttribute::parse(RFC_EXAMPLE)?;
+    assert_eq!(attr.tag, 1);
+    assert_eq!(attr.profile, ProtectionProfile::Aes128CmHmacSha1_80);
+    assert_eq!(
+        attr.master_key,
+        [
+            0x3d, 0x2d, 0x6e, 0x40, 0x25, 0x5e, 0x78, 0x21, 0x42, 0x6a, 0x75, 0x66, 0x72, 0x39,
+            0x29, 0x3f
+        ]
+    );
+    assert_eq!(attr.master_salt.len(), 14);
+    assert_eq!(attr.lifetime, Some(1 << 20));
+    assert_eq!(attr.mki, [0, 0, 0, 1]);
+    assert!(attr.session_params.is_empty());
+    assert_eq!(attr.to_string(), RFC_EXAMPLE);
+
+    // Unpadded base64 is accepted as well.
+    let attr = CryptoAttribute::parse(
+        "2 AEAD_AES_256_GCM inline:AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA|1000 UNENCRYPTED_SRTCP",
+    )?;
+    assert_eq!(attr.profile, ProtectionProfile::AeadAes256Gcm);
+    assert_eq!(attr.master_key, [0; 32]);
+    assert_eq!(attr.master_salt, [0; 12]);
+    assert_eq!(attr.lifetime, Some(1000));
+    assert_eq!(attr.session_params, ["UNENCRYPTED_SRTCP"]);
+
+    let attr = CryptoAttribute::parse(
+        "3 AES_CM_128_HMAC_SHA1_32 inline:AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA",
+    )?;
+    assert_eq!(attr.profile, ProtectionProfile::Aes128CmHmacSha1_32);
+    assert_eq!(attr.master_key, [0; 16]);
+
+    Ok(())
+}
+
+#[test]
+fn test_crypto_attribute_parse_invalid() {
+    let tests = [
+        "",
+        "1",
+        "x AES_CM_128_HMAC_SHA1_80 inline:PS1uQCVeeCFCanVmcjkpPywjNWhcYD0mXXtxaVBR",
+        "1 AES_CM_128_HMAC_SHA1_80",
+        "1 AES_CM_128_HMAC_SHA1_80 uri:PS1uQCVeeCFCanVmcjkpPywjNWhcYD0mXXtxaVBR",
+        "1 AES_CM_128_HMAC_SHA1_80 inline:PS1uQCVeeCFCanVmcjkp",
+        "1 AES_CM_128_HMAC_SHA1_80 inline:!!!!",
+        "1 AES_CM_128_HMAC_SHA1_80 inline:PS1uQCVeeCFCanVmcjkpPywjNWhcYD0mXXtxaVBR|2^x",
+        "1 AES_CM_128_HMAC_SHA1_80 inline:PS1uQCVeeCFCanVmcjkpPywjNWhcYD0mXXtxaVBR|256:1",
+        "1 AES_CM_128_HMAC_SHA1_80 inline:PS1uQCVeeCFCanVmcjkpPywjNWhcYD0mXXtxaVBR|1:0",
+        "1 AES_CM_128_HMAC_SHA1_80 inline:PS1uQCVeeCFCanVmcjkpPywjNWhcYD0mXXtxaVBR|1:4|2^20",
+        "1 AES_CM_128_HMAC_SHA1_80 inline:PS1uQCVeeCFCanVmcjkpPywjNWhcYD0mXXtxaVBR;inline:PS1uQCVeeCFCanVmcjkpPywjNWhcYD0mXXtxaVBR",
+    ];
+
+    for value in tests {
+        assert_eq!(
+            CryptoAttribute::parse(value),
+            Err(Error::SdesInvalidCryptoAttribute(value.to_owned())),
+            "{value}"
+        );
+    }
+
+    assert_eq!(
+        CryptoAttribute::parse(
+            "1 F8_128_HMAC_SHA1_80 inline:PS1uQCVeeCFCanVmcjkpPywjNWhcYD0mXXtxaVBR"
+        ),
+        Err(Error::SdesUnsupportedCryptoSuite(
+            "F8_128_HMAC_SHA1_80".to_owned()
+        ))
+    );
+}
+
+#[test]
+fn test_crypto_attribute_generate() -> Result<()> {
+    for profile in [
+        ProtectionProfile::Aes128CmHmacSha1_80,
+        ProtectionProfile::Aes128CmHmacSha1_32,
+        ProtectionProfile::AeadAes128Gcm,
+        ProtectionProfile::AeadAes256Gcm,
+    ] {
+        let attr = CryptoAttribute::generate(7, profile);
+        assert_eq!(attr.master_key.len(), profile.key_len());
+        assert_eq!(attr.master_salt.len(), profile.salt_len());
+        assert_ne!(attr, CryptoAttribute::generate(7, profile));
+        assert_eq!(CryptoAttribute::parse(&attr.to_string())?, attr);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_extract_session_keys_from_sdes() -> Result<()> {
+    let local = CryptoAttribute::generate(1, ProtectionProfile::AeadAes128Gcm);
+    let mut remote = CryptoAttribute::parse(RFC_EXAMPLE)?;
+
+    let mut config = Config::default();
+    assert_eq!(
+        config.extract_session_keys_from_sdes(&local, &remote),
+        Err(Error::SdesCryptoSuiteMismatch(
+            "AEAD_AES_128_GCM".to_owned(),
+            "AES_CM_128_HMAC_SHA1_80".to_owned()
+        ))
+    );
+
+    remote.profile = ProtectionProfile::AeadAes128Gcm;
+    remote.master_salt.truncate(12);
+    config.extract_session_keys_from_sdes(&local, &remote)?;
+    assert_eq!(config.profile, ProtectionProfile::AeadAes128Gcm);
+    assert_eq!(config.keys.local_master_key, local.master_key);
+    assert_eq!(config.keys.local_master_salt, local.master_salt);
+    assert!(config.keys.local_mki.is_empty());
+    assert_eq!(config.keys.remote_master_key, remote.master_key);
+    assert_eq!(config.keys.remote_master_salt, remote.master_salt);
+    assert_eq!(config.keys.remote_mki, [0, 0, 0, 1]);
+    assert_eq!(
+        config.srtcp_encryption_policy,
+        SrtcpEncryptionPolicy::Enabled
+    );
+
+    let mut local = local;
+    local
+        .session_params
+        .push(SESSION_PARAM_UNENCRYPTED_SRTCP.to_owned());
+    config.extract_session_keys_from_sdes(&local, &remote)?;
+    assert_eq!(
+        config.srtcp_encryption_policy,
+        SrtcpEncryptionPolicy::Enabled
+    );
+
+    remote
+        .session_params
+        .push(SESSION_PARAM_UNENCRYPTED_SRTCP.to_owned());
+    config.extract_session_keys_from_sdes(&local, &remote)?;
+    assert_eq!(
+        config.srtcp_encryption_policy,
+        SrtcpEncryptionPolicy::Disabled
+    );
+
+    // Without the remote agreeing SRTCP is encrypted.
+    remote.session_params.clear();
+    config.extract_session_keys_from_sdes(&local, &remote)?;
+    assert_eq!(
+        config.srtcp_encryption_policy,
+        SrtcpEncryptionPolicy::Enabled
+    );
+
+    Ok(())
+}
@@ -6,8 +6,9 @@ mod session_rtp_test;
 use std::collections::{HashMap, HashSet};
 use std::marker::{Send, Sync};
 use std::sync::Arc;
+use std::time::Duration;
 
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 use tokio::sync::{mpsc, Mutex};
 use util::conn::Conn;
 use util::marshal::*;
@@ -20,6 +21,7 @@ use crate::stream::*;
 
 const DEFAULT_SESSION_SRTP_REPLAY_PROTECTION_WINDOW: usize = 64;
 const DEFAULT_SESSION_SRTCP_REPLAY_PROTECTION_WINDOW: usize = 64;
+const RECEIVE_MTU: usize = 8192;
 
 /// Session implements io.ReadWriteCloser and provides a bi-directional SRTP session
 /// SRTP itself does not have a design like this, but it is common in most applications
@@ -27,6 +29,7 @@ const DEFAULT_SESSION_SRTCP_REPLAY_PROTECTION_WINDOW: usize = 64;
 /// instead of making everyone re-implement
 pub struct Session {
     local_context: Arc<Mutex<Context>>,
+    remote_context: Arc<Mutex<Context>>,
     streams_map: Arc<Mutex<HashMap<u32, Arc<Stream>>>>,
     new_stream_rx: Arc<Mutex<mpsc::Receiver<Arc<Stream>>>>,
     close_stream_tx: mpsc::Sender<u32>,
@@ -41,17 +44,21 @@ impl Session {
         config: Config,
         is_rtp: bool,
     ) -> Result<Self> {
-        let local_context = Context::new(
+        let mut local_context = Context::new_with_mki(
             &config.keys.local_master_key,
             &config.keys.local_master_salt,
+            &config.keys.local_mki,
             config.profile,
             config.local_rtp_options,
             config.local_rtcp_options,
         )?;
+        local_context.set_cryptex_policy(config.cryptex_policy);
+        local_context.set_srtcp_encryption_policy(config.srtcp_encryption_policy);
 
-        let mut remote_context = Context::new(
+        let mut remote_context = Context::new_with_mki(
             &config.keys.remote_master_key,
             &config.keys.remote_master_salt,
+            &config.keys.remote_mki,
             config.profile,
             if config.remote_rtp_options.is_none() {
                 Some(srtp_replay_protection(
@@ -68,6 +75,11 @@ impl Session {
                 config.remote_rtcp_options
             },
         )?;
+        remote_context.set_cryptex_policy(config.cryptex_policy);
+        remote_context.set_srtcp_encryption_policy(config.srtcp_encryption_policy);
+        remote_context.set_ssrc_state_limits(config.remote_ssrc_state_limits);
+        let remote_context = Arc::new(Mutex::new(remote_context));
+        let cloned_remote_context = Arc::clone(&remote_context);
 
         let streams_map = Arc::new(Mutex::new(HashMap::new()));
         let (mut new_stream_tx, new_stream_rx) = mpsc::channel(8);
@@ -79,7 +91,8 @@ impl Session {
         let cloned_close_stream_tx = close_stream_tx.clone();
 
         tokio::spawn(async move {
-            let mut buf = vec![0u8; 8192];
+            // reused for every packet, which is decrypted in place
+            let mut buf = BytesMut::with_capacity(RECEIVE_MTU);
 
             loop {
                 let incoming_stream = Session::incoming(
@@ -88,7 +101,7 @@ impl Session {
                     &cloned_streams_map,
                     &cloned_close_stream_tx,
                     &mut new_stream_tx,
-                    &mut remote_context,
+                    &cloned_remote_context,
                     is_rtp,
                 );
                 let close_stream = close_stream_rx.recv();
@@ -109,6 +122,7 @@ impl Session {
 
         Ok(Session {
             local_context: Arc::new(Mutex::new(local_context)),
+            remote_context,
             streams_map,
             new_stream_rx: Arc::new(Mutex::new(new_stream_rx)),
             close_stream_tx,
@@ -125,25 +139,31 @@ impl Session {
 
     async fn incoming(
         udp_rx: &Arc<dyn Conn + Send + Sync>,
-        buf: &mut [u8],
+        buf: &mut BytesMut,
         streams_map: &Arc<Mutex<HashMap<u32, Arc<Stream>>>>,
         close_stream_tx: &mpsc::Sender<u32>,
         new_stream_tx: &mut mpsc::Sender<Arc<Stream>>,
-        remote_context: &mut Context,
+        remote_context: &Arc<Mutex<Context>>,
         is_rtp: bool,
     ) -> Result<()> {
+        buf.resize(RECEIVE_MTU, 0);
         let n = udp_rx.recv(buf).await?;
         if n == 0 {
             return Err(Error::SessionEof);
         }
+        buf.truncate(n);
 
-        let decrypted = if is_rtp {
-            remote_context.decrypt_rtp(&buf[0..n])?
-        } else {
-            remote_context.decrypt_rtcp(&buf[0..n])?
-        };
+        {
+            let mut remote_context = remote_context.lock().await;
+            if is_rtp {
+                remote_context.decrypt_rtp_in_place(buf)?;
+            } else {
+                remote_context.decrypt_rtcp_in_place(buf)?;
+            }
+        }
+        let decrypted = &buf[..];
 
-        let mut buf = &decrypted[..];
+        let mut buf = decrypted;
         let ssrcs = if is_rtp {
             vec![rtp::header::Header::unmarshal(&mut buf)?.ssrc]
         } else {
@@ -164,7 +184,7 @@ impl Session {
                 new_stream_tx.send(Arc::clone(&stream)).await?;
             }
 
-            match stream.buffer.write(&decrypted).await {
+            match stream.buffer.write(decrypted).await {
                 Ok(_) => {}
                 Err(err) => {
                     // Silently drop data when the buffer is full.
@@ -220,6 +240,87 @@ impl Session {
         }
     }
 
+    /// rekey switches the session over to new keys without tearing it down, e.g.
+    /// after a DTLS renegotiation or an EKT update. Packets protected with the previous
+    /// remote key are still accepted for `overlap`. See Context::rekey.
+    /// Neither key is switched unless both are valid.
+    pub async fn rekey(&self, keys: &SessionKeys, overlap: Duration) -> Result<()> {
+        let mut remote_context = self.remote_context.lock().await;
+        let mut local_context = self.local_context.lock().await;
+        let remote_cipher = remote_context.new_rekey_cipher(
+            &keys.remote_mki,
+            &keys.remote_master_key,
+            &keys.remote_master_salt,
+        )?;
+        let local_cipher = local_context.new_rekey_cipher(
+            &keys.local_mki,
+            &keys.local_master_key,
+            &keys.local_master_salt,
+        )?;
+
+        remote_context.switch_key(&keys.remote_mki, remote_cipher, overlap);
+        local_context.switch_key(&keys.local_mki, local_cipher, Duration::ZERO);
+        Ok(())
+    }
+
+    /// set_replay_protection_window sets the replay window size of a single incoming stream,
+    /// overriding the session wide window of the `Config`.
+    pub async fn set_replay_protection_window(&self, ssrc: u32, window_size: usize) {
+        let mut remote_context = self.remote_context.lock().await;
+        if self.is_rtp {
+            remote_context.set_srtp_replay_protection_window(ssrc, window_size);
+        } else {
+            remote_context.set_srtcp_replay_protection_window(ssrc, window_size);
+        }
+    }
+
+    /// remote_stats returns the counters of the packets dropped for an incoming stream,
+    /// or None if nothing has been received for the SSRC.
+    pub async fn remote_stats(&self, ssrc: u32) -> Option<SsrcStats> {
+        let remote_context = self.remote_context.lock().await;
+        if self.is_rtp {
+            remote_context.srtp_stats(ssrc)
+        } else {
+            remote_context.srtcp_stats(ssrc)
+        }
+    }
+
+    /// remote_unknown_ssrc_stats returns the counters of the packets dropped for the incoming
+    /// SSRCs no crypto state is kept for, as none of their packets has been authenticated.
+    pub async fn remote_unknown_ssrc_stats(&self) -> SsrcStats {
+        let remote_context = self.remote_context.lock().await;
+        if self.is_rtp {
+            remote_context.srtp_unknown_ssrc_stats()
+        } else {
+            remote_context.srtcp_unknown_ssrc_stats()
+        }
+    }
+
+    /// remote_ssrc_count returns the number of incoming SSRCs crypto state is currently
+    /// kept for, bounded by the `remote_ssrc_state_limits` of the `Config`.
+    pub async fn remote_ssrc_count(&self) -> usize {
+        let remote_context = self.remote_context.lock().await;
+        if self.is_rtp {
+            remote_context.srtp_ssrc_count()
+        } else {
+            remote_context.srtcp_ssrc_count()
+        }
+    }
+
+    /// on_key_lifetime sets a handler called when the local master key nears the end of its
+    /// lifetime, so the session can be re-keyed with `rekey` before writes start failing.
+    /// See Context::on_key_lifetime.
+    pub async fn on_key_lifetime(&self, handler: KeyLifetimeHandlerFn) {
+        let mut local_context = self.local_context.lock().await;
+        local_context.on_key_lifetime(handler);
+    }
+
+    /// local_key_usage returns the number of packets protected with the local master key.
+    pub async fn local_key_usage(&self) -> KeyUsage {
+        let local_context = self.local_context.lock().await;
+        local_context.key_usage()
+    }
+
     pub async fn close(&self) -> Result<()> {
         self.close_session_tx.send(()).await?;
 
@@ -245,8 +346,24 @@ impl Session {
     }
 
     pub async fn write_rtp(&self, pkt: &rtp::packet::Packet) -> Result<usize> {
-        let raw = pkt.marshal()?;
-        self.write(&raw, true).await
+        if !self.is_rtp {
+            return Err(Error::SessionRtpRtcpTypeMismatch);
+        }
+
+        let encrypted = {
+            let mut local_context = self.local_context.lock().await;
+
+            // Marshal straight into a buffer with room for the auth tag, so the
+            // packet is encrypted without further copies.
+            let size = pkt.marshal_size();
+            let mut raw = BytesMut::with_capacity(size + local_context.rtp_overhead());
+            raw.resize(size, 0);
+            pkt.marshal_to(&mut raw)?;
+            local_context.encrypt_rtp_with_header_in_place(&mut raw, &pkt.header)?;
+            raw
+        };
+
+        Ok(self.udp_tx.send(&encrypted).await?)
     }
 
     pub async fn write_rtcp(
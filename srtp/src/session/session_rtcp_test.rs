@@ -6,6 +6,7 @@ use tokio::sync::{mpsc, Mutex};
 use util::conn::conn_pipe::*;
 
 use super::*;
+use crate::cryptex::CryptexPolicy;
 use crate::error::Result;
 use crate::protection_profile::*;
 
@@ -29,6 +30,8 @@ async fn build_session_srtcp_pair() -> Result<(Session, Session)> {
             remote_master_salt: vec![
                 0x0E, 0xC6, 0x75, 0xAD, 0x49, 0x8A, 0xFE, 0xEB, 0xB6, 0x96, 0x0B, 0x3A, 0xAB, 0xE6,
             ],
+            local_mki: vec![],
+            remote_mki: vec![],
         },
 
         local_rtp_options: None,
@@ -36,6 +39,9 @@ async fn build_session_srtcp_pair() -> Result<(Session, Session)> {
 
         local_rtcp_options: None,
         remote_rtcp_options: None,
+        cryptex_policy: CryptexPolicy::Disabled,
+        srtcp_encryption_policy: SrtcpEncryptionPolicy::Enabled,
+        remote_ssrc_state_limits: SsrcStateLimits::default(),
     };
 
     let cb = Config {
@@ -55,6 +61,8 @@ async fn build_session_srtcp_pair() -> Result<(Session, Session)> {
             remote_master_salt: vec![
                 0x0E, 0xC6, 0x75, 0xAD, 0x49, 0x8A, 0xFE, 0xEB, 0xB6, 0x96, 0x0B, 0x3A, 0xAB, 0xE6,
             ],
+            local_mki: vec![],
+            remote_mki: vec![],
         },
 
         local_rtp_options: None,
@@ -62,6 +70,9 @@ async fn build_session_srtcp_pair() -> Result<(Session, Session)> {
 
         local_rtcp_options: None,
         remote_rtcp_options: None,
+        cryptex_policy: CryptexPolicy::Disabled,
+        srtcp_encryption_policy: SrtcpEncryptionPolicy::Enabled,
+        remote_ssrc_state_limits: SsrcStateLimits::default(),
     };
 
     let sa = Session::new(Arc::new(ua), ca, false).await?;
@@ -6,6 +6,7 @@ use tokio::net::UdpSocket;
 use tokio::sync::{mpsc, Mutex};
 
 use super::*;
+use crate::cryptex::CryptexPolicy;
 use crate::error::Result;
 use crate::protection_profile::*;
 
@@ -33,6 +34,8 @@ async fn build_session_srtp_pair() -> Result<(Session, Session)> {
             remote_master_salt: vec![
                 0x0E, 0xC6, 0x75, 0xAD, 0x49, 0x8A, 0xFE, 0xEB, 0xB6, 0x96, 0x0B, 0x3A, 0xAB, 0xE6,
             ],
+            local_mki: vec![],
+            remote_mki: vec![],
         },
 
         local_rtp_options: None,
@@ -40,6 +43,9 @@ async fn build_session_srtp_pair() -> Result<(Session, Session)> {
 
         local_rtcp_options: None,
         remote_rtcp_options: None,
+        cryptex_policy: CryptexPolicy::Disabled,
+        srtcp_encryption_policy: SrtcpEncryptionPolicy::Enabled,
+        remote_ssrc_state_limits: SsrcStateLimits::default(),
     };
 
     let cb = Config {
@@ -59,6 +65,8 @@ async fn build_session_srtp_pair() -> Result<(Session, Session)> {
             remote_master_salt: vec![
                 0x0E, 0xC6, 0x75, 0xAD, 0x49, 0x8A, 0xFE, 0xEB, 0xB6, 0x96, 0x0B, 0x3A, 0xAB, 0xE6,
             ],
+            local_mki: vec![],
+            remote_mki: vec![],
         },
 
         local_rtp_options: None,
@@ -66,6 +74,9 @@ async fn build_session_srtp_pair() -> Result<(Session, Session)> {
 
         local_rtcp_options: None,
         remote_rtcp_options: None,
+        cryptex_policy: CryptexPolicy::Disabled,
+        srtcp_encryption_policy: SrtcpEncryptionPolicy::Enabled,
+        remote_ssrc_state_limits: SsrcStateLimits::default(),
     };
 
     let sa = Session::new(Arc::new(ua), ca, true).await?;
@@ -90,6 +101,7 @@ async fn test_session_srtp_accept() -> Result<()> {
             ..Default::default()
         },
         payload: test_payload.clone(),
+        ..Default::default()
     };
     sa.write_rtp(&packet).await?;
 
@@ -116,6 +128,71 @@ async fn test_session_srtp_accept() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_session_srtp_rekey() -> Result<()> {
+    let test_payload = Bytes::from_static(&[0x00, 0x01, 0x03, 0x04]);
+    let mut read_buffer = BytesMut::with_capacity(RTP_HEADER_SIZE + test_payload.len());
+    read_buffer.resize(RTP_HEADER_SIZE + test_payload.len(), 0u8);
+    let (sa, sb) = build_session_srtp_pair().await?;
+
+    let keys = SessionKeys {
+        local_master_key: vec![0x55; 16],
+        local_master_salt: vec![0x66; 14],
+        remote_master_key: vec![0x55; 16],
+        remote_master_salt: vec![0x66; 14],
+        ..Default::default()
+    };
+
+    let read_stream = sb.open(TEST_SSRC).await;
+
+    // The receiver switches first and keeps accepting the old key during the overlap.
+    sb.rekey(&keys, Duration::from_secs(60)).await?;
+    for sequence_number in 0..2u16 {
+        if sequence_number == 1 {
+            sa.rekey(&keys, Duration::from_secs(60)).await?;
+        }
+
+        let packet = rtp::packet::Packet {
+            header: rtp::header::Header {
+                ssrc: TEST_SSRC,
+                sequence_number,
+                ..Default::default()
+            },
+            payload: test_payload.clone(),
+            ..Default::default()
+        };
+        sa.write_rtp(&packet).await?;
+
+        read_stream.read(&mut read_buffer).await?;
+        assert_eq!(&test_payload[..], &read_buffer[RTP_HEADER_SIZE..]);
+    }
+
+    // An invalid local key leaves the remote key alone too.
+    let invalid_keys = SessionKeys {
+        local_master_key: vec![0x77; 3],
+        remote_master_key: vec![0x77; 16],
+        ..keys.clone()
+    };
+    assert!(sb.rekey(&invalid_keys, Duration::ZERO).await.is_err());
+    let packet = rtp::packet::Packet {
+        header: rtp::header::Header {
+            ssrc: TEST_SSRC,
+            sequence_number: 2,
+            ..Default::default()
+        },
+        payload: test_payload.clone(),
+        ..Default::default()
+    };
+    sa.write_rtp(&packet).await?;
+    read_stream.read(&mut read_buffer).await?;
+    assert_eq!(&test_payload[..], &read_buffer[RTP_HEADER_SIZE..]);
+
+    sa.close().await?;
+    sb.close().await?;
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_session_srtp_listen() -> Result<()> {
     let test_payload = Bytes::from_static(&[0x00, 0x01, 0x03, 0x04]);
@@ -129,6 +206,7 @@ async fn test_session_srtp_listen() -> Result<()> {
             ..Default::default()
         },
         payload: test_payload.clone(),
+        ..Default::default()
     };
 
     let read_stream = sb.open(TEST_SSRC).await;
@@ -172,6 +250,7 @@ async fn test_session_srtp_multi_ssrc() -> Result<()> {
                 ..Default::default()
             },
             payload: test_payload.clone(),
+            ..Default::default()
         };
         sa.write_rtp(&packet).await?;
 
@@ -196,6 +275,59 @@ async fn test_session_srtp_multi_ssrc() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_session_srtp_stats() -> Result<()> {
+    let test_payload = Bytes::from_static(&[0x00, 0x01, 0x03, 0x04]);
+    let (sa, sb) = build_session_srtp_pair().await?;
+
+    let read_stream = sb.open(TEST_SSRC).await;
+    sb.set_replay_protection_window(TEST_SSRC, 16).await;
+
+    let mut packets = vec![];
+    {
+        let mut local_context = sa.local_context.lock().await;
+        for sequence_number in 0..2u16 {
+            let packet = rtp::packet::Packet {
+                header: rtp::header::Header {
+                    ssrc: TEST_SSRC,
+                    sequence_number,
+                    ..Default::default()
+                },
+                payload: test_payload.clone(),
+                ..Default::default()
+            };
+            packets.push(encrypt_srtp(&mut local_context, &packet)?);
+        }
+    }
+
+    let mut tampered = packets[1].to_vec();
+    tampered[RTP_HEADER_SIZE] ^= 0x01;
+
+    // Packets are processed in order, so reading the last one means the others were dropped.
+    sa.udp_tx.send(&packets[0]).await?;
+    sa.udp_tx.send(&packets[0]).await?;
+    sa.udp_tx.send(&tampered).await?;
+    sa.udp_tx.send(&packets[1]).await?;
+    for expected in 0..2u16 {
+        let seq = payload_srtp(&read_stream, RTP_HEADER_SIZE, &test_payload).await?;
+        assert_eq!(seq, expected);
+    }
+
+    assert_eq!(
+        sb.remote_stats(TEST_SSRC).await,
+        Some(SsrcStats {
+            auth_failures: 1,
+            replay_drops: 1,
+            decrypt_errors: 0,
+        })
+    );
+
+    sa.close().await?;
+    sb.close().await?;
+
+    Ok(())
+}
+
 fn encrypt_srtp(context: &mut Context, pkt: &rtp::packet::Packet) -> Result<Bytes> {
     let decrypted = pkt.marshal()?;
     let encrypted = context.encrypt_rtp(&decrypted)?;
@@ -247,6 +379,7 @@ async fn test_session_srtp_replay_protection() -> Result<()> {
                     ..Default::default()
                 },
                 payload: test_payload.clone(),
+                ..Default::default()
             };
 
             let encrypted = encrypt_srtp(&mut local_context, &packet)?;
@@ -306,3 +439,69 @@ async fn test_session_srtp_replay_protection() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_session_srtp_key_usage() -> Result<()> {
+    let (sa, sb) = build_session_srtp_pair().await?;
+    assert_eq!(sa.local_key_usage().await, KeyUsage::default());
+
+    for sequence_number in 0..3u16 {
+        let packet = rtp::packet::Packet {
+            header: rtp::header::Header {
+                ssrc: TEST_SSRC,
+                sequence_number,
+                ..Default::default()
+            },
+            payload: Bytes::from_static(&[0x00, 0x01, 0x03, 0x04]),
+            ..Default::default()
+        };
+        sa.write_rtp(&packet).await?;
+    }
+    assert_eq!(
+        sa.local_key_usage().await,
+        KeyUsage {
+            srtp_packets: 3,
+            srtcp_packets: 0
+        }
+    );
+
+    sa.close().await?;
+    sb.close().await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_session_srtp_ssrc_state_limits() -> Result<()> {
+    let test_payload = Bytes::from_static(&[0x00, 0x01, 0x03, 0x04]);
+    let (sa, sb) = build_session_srtp_pair().await?;
+    {
+        let mut remote_context = sb.remote_context.lock().await;
+        remote_context.set_ssrc_state_limits(SsrcStateLimits {
+            max_ssrcs: Some(2),
+            idle_timeout: None,
+        });
+    }
+
+    for ssrc in TEST_SSRC..TEST_SSRC + 3 {
+        let read_stream = sb.open(ssrc).await;
+        let packet = rtp::packet::Packet {
+            header: rtp::header::Header {
+                ssrc,
+                ..Default::default()
+            },
+            payload: test_payload.clone(),
+            ..Default::default()
+        };
+        sa.write_rtp(&packet).await?;
+        payload_srtp(&read_stream, RTP_HEADER_SIZE, &test_payload).await?;
+    }
+
+    assert_eq!(sb.remote_ssrc_count().await, 2);
+    assert_eq!(sb.remote_stats(TEST_SSRC).await, None);
+
+    sa.close().await?;
+    sb.close().await?;
+
+    Ok(())
+}
@@ -25,3 +25,21 @@ async fn test_pipe() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_pipe_send_vectored() -> Result<()> {
+    let (c1, c2) = pipe();
+    let bufs = [
+        std::io::IoSlice::new(&[1, 2, 3]),
+        std::io::IoSlice::new(&[]),
+        std::io::IoSlice::new(&[4, 5]),
+    ];
+    let n = c1.send_vectored(&bufs).await?;
+    assert_eq!(n, 5);
+
+    let mut b = vec![0; 100];
+    let n = c2.recv(&mut b).await?;
+    assert_eq!(&b[..n], &[1, 2, 3, 4, 5]);
+
+    Ok(())
+}
@@ -16,6 +16,7 @@ mod conn_test;
 #[cfg(test)]
 mod conn_udp_listener_test;
 
+use std::io::IoSlice;
 use std::net::SocketAddr;
 use std::sync::Arc;
 
@@ -30,6 +31,16 @@ pub trait Conn {
     async fn recv(&self, buf: &mut [u8]) -> Result<usize>;
     async fn recv_from(&self, buf: &mut [u8]) -> Result<(usize, SocketAddr)>;
     async fn send(&self, buf: &[u8]) -> Result<usize>;
+    /// send_vectored sends the concatenation of bufs as a single message.
+    /// By default they are gathered into one buffer for send, connections
+    /// able to use them as they are should override it.
+    async fn send_vectored(&self, bufs: &[IoSlice<'_>]) -> Result<usize> {
+        let mut buf = Vec::with_capacity(bufs.iter().map(|b| b.len()).sum());
+        for b in bufs {
+            buf.extend_from_slice(b);
+        }
+        self.send(&buf).await
+    }
     async fn send_to(&self, buf: &[u8], target: SocketAddr) -> Result<usize>;
     fn local_addr(&self) -> Result<SocketAddr>;
     fn remote_addr(&self) -> Option<SocketAddr>;
@@ -35,6 +35,50 @@ impl SlidingWindowDetector {
             mask: FixedBigInt::new(window_size),
         }
     }
+
+    // window returns the sequence numbers accepted so far, to carry the replay
+    // protection over to another detector with from_window.
+    pub fn window(&self) -> ReplayWindow {
+        let mut accepted = vec![0u64; self.window_size.div_ceil(64)];
+        for i in 0..self.window_size {
+            if self.mask.bit(i) != 0 {
+                accepted[i / 64] |= 1 << (i % 64);
+            }
+        }
+
+        ReplayWindow {
+            window_size: self.window_size,
+            latest_seq: self.latest_seq,
+            accepted,
+        }
+    }
+
+    // from_window creates a ReplayDetector rejecting the sequence numbers of window.
+    pub fn from_window(window: &ReplayWindow, max_seq: u64) -> Self {
+        let mut detector = SlidingWindowDetector::new(window.window_size, max_seq);
+        detector.latest_seq = window.latest_seq;
+        for i in 0..window.window_size {
+            if window
+                .accepted
+                .get(i / 64)
+                .is_some_and(|w| w & (1 << (i % 64)) != 0)
+            {
+                detector.mask.set_bit(i);
+            }
+        }
+
+        detector
+    }
+}
+
+// ReplayWindow is the state of a SlidingWindowDetector.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct ReplayWindow {
+    pub window_size: usize,
+    // Highest sequence number accepted.
+    pub latest_seq: u64,
+    // Bit i is set when latest_seq - i was accepted.
+    pub accepted: Vec<u64>,
 }
 
 impl ReplayDetector for SlidingWindowDetector {
@@ -276,3 +276,31 @@ fn test_replay_detector() {
         }
     }
 }
+
+#[test]
+fn test_replay_detector_window() {
+    let mut detector = SlidingWindowDetector::new(80, 0x0000FFFFFFFFFFFF);
+    for seq in [1, 3, 100, 150] {
+        assert!(detector.check(seq));
+        detector.accept();
+    }
+
+    let window = detector.window();
+    assert_eq!(window.window_size, 80);
+    assert_eq!(window.latest_seq, 150);
+    // 1 and 3 are out of the window already
+    assert_eq!(window.accepted, vec![1 | 1 << 50, 0]);
+
+    // The restored detector rejects what was accepted, and accepts the gaps.
+    let mut restored = SlidingWindowDetector::from_window(&window, 0x0000FFFFFFFFFFFF);
+    assert_eq!(restored.window(), window);
+    for (seq, ok) in [
+        (150, false),
+        (100, false),
+        (99, true),
+        (151, true),
+        (3, false),
+    ] {
+        assert_eq!(restored.check(seq), ok, "seq {seq}");
+    }
+}
@@ -276,3 +276,299 @@ func Test_InterceptorRegistry_Build(t *testing.T) {
     closePairNow(t, peerConnectionA, peerConnectionB)
 }
 */
+
+use super::*;
+use crate::rtp_transceiver::rtp_transceiver_direction::RTCRtpTransceiverDirection;
+
+#[test]
+fn test_configure_abs_time_header_extensions() -> Result<()> {
+    let mut media_engine = MediaEngine::default();
+    media_engine.register_default_codecs()?;
+
+    let registry = configure_abs_send_time(Registry::new(), &mut media_engine)?;
+    let (_registry, capture_times) = configure_abs_capture_time(registry, &mut media_engine)?;
+    assert!(capture_times.get(1).is_none());
+
+    for typ in [RTPCodecType::Video, RTPCodecType::Audio] {
+        let params =
+            media_engine.get_rtp_parameters_by_kind(typ, RTCRtpTransceiverDirection::Sendrecv);
+        let uris: Vec<&str> = params
+            .header_extensions
+            .iter()
+            .map(|e| e.uri.as_str())
+            .collect();
+        assert!(uris.contains(&sdp::extmap::ABS_SEND_TIME_URI), "{typ}");
+        assert!(uris.contains(&sdp::extmap::ABS_CAPTURE_TIME_URI), "{typ}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_configure_playout_delay() -> Result<()> {
+    let mut media_engine = MediaEngine::default();
+    media_engine.register_default_codecs()?;
+    configure_playout_delay(Registry::new(), &mut media_engine)?;
+
+    for (typ, registered) in [(RTPCodecType::Video, true), (RTPCodecType::Audio, false)] {
+        let params =
+            media_engine.get_rtp_parameters_by_kind(typ, RTCRtpTransceiverDirection::Sendrecv);
+        let found = params
+            .header_extensions
+            .iter()
+            .any(|e| e.uri == sdp::extmap::PLAYOUT_DELAY_URI);
+        assert_eq!(found, registered, "{typ}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_configure_rtcp_reports_with_source_description() -> Result<()> {
+    use rtcp::source_description::SdesType;
+
+    use crate::api::media_engine::SourceDescriptionConfig;
+
+    let mut media_engine = MediaEngine::default();
+    assert!(media_engine.source_description().is_empty());
+
+    let result = media_engine.set_source_description(SourceDescriptionConfig {
+        note: Some("x".repeat(256)),
+        ..Default::default()
+    });
+    assert!(result.is_err());
+    assert!(media_engine.source_description().is_empty());
+
+    media_engine.set_source_description(SourceDescriptionConfig {
+        cname: Some("cname".to_owned()),
+        tool: Some("webrtc-rs".to_owned()),
+        note: Some("on air".to_owned()),
+        private: vec![("x-recorder".to_owned(), "session=42".to_owned())],
+        ..Default::default()
+    })?;
+    let items = media_engine.source_description().items()?;
+    let types: Vec<SdesType> = items.iter().map(|it| it.sdes_type).collect();
+    assert_eq!(
+        types,
+        vec![
+            SdesType::SdesTool,
+            SdesType::SdesNote,
+            SdesType::SdesPrivate
+        ]
+    );
+    assert_eq!(
+        items[2].private_extension().map(|(prefix, _)| prefix),
+        Some(bytes::Bytes::from_static(b"x-recorder"))
+    );
+
+    let registry = configure_rtcp_reports_with_source_description(Registry::new(), &media_engine)?;
+    registry.build("")?;
+
+    Ok(())
+}
+
+#[test]
+fn test_configure_congestion_control_feedback() -> Result<()> {
+    let mut media_engine = MediaEngine::default();
+    media_engine.register_default_codecs()?;
+    configure_congestion_control_feedback(Registry::new(), &mut media_engine);
+
+    for typ in [RTPCodecType::Video, RTPCodecType::Audio] {
+        let params =
+            media_engine.get_rtp_parameters_by_kind(typ, RTCRtpTransceiverDirection::Sendrecv);
+        assert!(!params.codecs.is_empty(), "{typ}");
+        for codec in &params.codecs {
+            assert!(
+                codec
+                    .capability
+                    .rtcp_feedback
+                    .iter()
+                    .any(|fb| fb.typ == TYPE_RTCP_FB_ACK && fb.parameter == "ccfb"),
+                "{typ}"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_configure_gcc() -> Result<()> {
+    let mut media_engine = MediaEngine::default();
+    media_engine.register_default_codecs()?;
+    let registry = configure_gcc(
+        Registry::new(),
+        &mut media_engine,
+        gcc::sender::Sender::builder().with_initial_bitrate(1_000_000),
+    )?;
+    registry.build("")?;
+
+    for typ in [RTPCodecType::Video, RTPCodecType::Audio] {
+        let params =
+            media_engine.get_rtp_parameters_by_kind(typ, RTCRtpTransceiverDirection::Sendrecv);
+        assert!(
+            params
+                .header_extensions
+                .iter()
+                .any(|e| e.uri == sdp::extmap::TRANSPORT_CC_URI),
+            "{typ}"
+        );
+        for codec in &params.codecs {
+            assert!(
+                codec
+                    .capability
+                    .rtcp_feedback
+                    .iter()
+                    .any(|fb| fb.typ == TYPE_RTCP_FB_TRANSPORT_CC),
+                "{typ}"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_configure_pacer() -> Result<()> {
+    let (registry, rate) = configure_pacer(
+        Registry::new(),
+        pacer::Pacer::builder().with_initial_bitrate(500_000),
+    );
+    registry.build("")?;
+    assert_eq!(rate.target_bitrate(), 500_000);
+
+    Ok(())
+}
+
+#[test]
+fn test_configure_keyframe_aggregation() -> Result<()> {
+    let (registry, requests) =
+        configure_keyframe_aggregation(Registry::new(), aggregator::Aggregator::builder());
+    registry.build("")?;
+    registry.build("")?;
+    requests.keyframe_received(1);
+
+    Ok(())
+}
+
+#[test]
+fn test_configure_jitter_buffer() -> Result<()> {
+    let registry = configure_jitter_buffer(
+        Registry::new(),
+        jitter_buffer::JitterBuffer::builder()
+            .with_target_delay(std::time::Duration::from_millis(100)),
+    );
+    registry.build("")?;
+
+    Ok(())
+}
+
+#[test]
+fn test_configure_flexfec() -> Result<()> {
+    let registry = configure_flexfec(
+        Registry::new(),
+        flexfec::generator::Generator::builder().with_max_overhead(0.3),
+    );
+    registry.build("")?;
+
+    Ok(())
+}
+
+#[test]
+fn test_configure_pcap() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("webrtc-registry-{}.pcapng", std::process::id()));
+    let registry = configure_pcap(Registry::new(), pcap::Pcap::builder().with_path(&path));
+    // the interceptors built share the capture file
+    registry.build("")?;
+    registry.build("")?;
+    std::fs::remove_file(&path).ok();
+
+    Ok(())
+}
+
+#[test]
+fn test_configure_forwarder() -> Result<()> {
+    let (registry, targets) = configure_forwarder(Registry::new(), forwarder::Forwarder::builder());
+    registry.build("")?;
+    targets.set_target_bitrate(1, 500_000);
+    assert_eq!(targets.layer(1), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_configure_policer() -> Result<()> {
+    let (registry, limits) = configure_policer(
+        Registry::new(),
+        policer::Policer::builder().with_default_limit(2_000_000),
+    );
+    registry.build("")?;
+    limits.set_mid_limit("0", 500_000);
+    assert_eq!(limits.stats(1).packets_policed, 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_configure_extension_rewriter() -> Result<()> {
+    let (registry, rewrites) = configure_extension_rewriter(
+        Registry::new(),
+        extension_rewriter::ExtensionRewriter::builder(),
+    );
+    registry.build("")?;
+    rewrites.set_mid(1, "0");
+    rewrites.set_source(1, 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_configure_frame_encryption() -> Result<()> {
+    let registry = configure_frame_encryption(
+        Registry::new(),
+        FrameEncryptorBuilder::default().with_key(Arc::new(|_| Some((0, vec![7; 16])))),
+        FrameDecryptorBuilder::default().with_key(Arc::new(|_, _| Some(vec![7; 16]))),
+    );
+    registry.build("")?;
+
+    Ok(())
+}
+
+#[test]
+fn test_configure_frame_layer() -> Result<()> {
+    struct Recorder;
+    impl interceptor::frame::FrameInterceptor for Recorder {}
+
+    let registry = configure_frame_layer(
+        Registry::new(),
+        FrameLayerBuilder::default().with_frame_interceptor(Arc::new(Recorder)),
+    );
+    registry.build("")?;
+
+    Ok(())
+}
+
+#[test]
+fn test_configure_audio_level() -> Result<()> {
+    let mut media_engine = MediaEngine::default();
+    media_engine.register_default_codecs()?;
+    let sender = audio_level::sender::SenderBuilder::default();
+    let samples = sender.samples();
+    let receiver = audio_level::receiver::ReceiverBuilder::default();
+    let levels = receiver.levels();
+    configure_audio_level(Registry::new(), &mut media_engine, sender, receiver)?.build("")?;
+    samples.push(1, &[0; 160]);
+    assert!(levels.get(1).is_none());
+
+    for (typ, registered) in [(RTPCodecType::Video, false), (RTPCodecType::Audio, true)] {
+        let params =
+            media_engine.get_rtp_parameters_by_kind(typ, RTCRtpTransceiverDirection::Sendrecv);
+        let found = params
+            .header_extensions
+            .iter()
+            .any(|e| e.uri == sdp::extmap::AUDIO_LEVEL_URI);
+        assert_eq!(found, registered, "{typ}");
+    }
+
+    Ok(())
+}
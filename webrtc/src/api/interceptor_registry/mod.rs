@@ -1,9 +1,28 @@
 #[cfg(test)]
 mod interceptor_registry_test;
 
+use std::sync::Arc;
+
+use interceptor::abs_capture_time::{self, CaptureTimes};
+use interceptor::abs_send_time;
+use interceptor::audio_level;
+use interceptor::ccfb;
+use interceptor::extension_rewriter::{self, ExtensionRewrites};
+use interceptor::flexfec;
+use interceptor::forwarder::{self, ForwarderTargets};
+use interceptor::frame::FrameLayerBuilder;
+use interceptor::frame_encryption::decryptor::FrameDecryptorBuilder;
+use interceptor::frame_encryption::encryptor::FrameEncryptorBuilder;
+use interceptor::gcc;
+use interceptor::jitter_buffer;
+use interceptor::keyframe::aggregator::{self, KeyframeRequests};
 use interceptor::nack::generator::Generator;
 use interceptor::nack::responder::Responder;
+use interceptor::pacer::{self, PacerRate};
+use interceptor::pcap;
+use interceptor::policer::{self, PolicerLimits};
 use interceptor::registry::Registry;
+use interceptor::remb;
 use interceptor::report::receiver::ReceiverReport;
 use interceptor::report::sender::SenderReport;
 use interceptor::twcc::receiver::Receiver;
@@ -12,7 +31,9 @@ use interceptor::twcc::sender::Sender;
 use crate::api::media_engine::MediaEngine;
 use crate::error::Result;
 use crate::rtp_transceiver::rtp_codec::{RTCRtpHeaderExtensionCapability, RTPCodecType};
-use crate::rtp_transceiver::{RTCPFeedback, TYPE_RTCP_FB_TRANSPORT_CC};
+use crate::rtp_transceiver::{
+    RTCPFeedback, TYPE_RTCP_FB_ACK, TYPE_RTCP_FB_GOOG_REMB, TYPE_RTCP_FB_TRANSPORT_CC,
+};
 
 /// register_default_interceptors will register some useful interceptors.
 /// If you want to customize which interceptors are loaded, you should copy the
@@ -23,7 +44,7 @@ pub fn register_default_interceptors(
 ) -> Result<Registry> {
     registry = configure_nack(registry, media_engine);
 
-    registry = configure_rtcp_reports(registry);
+    registry = configure_rtcp_reports_with_source_description(registry, media_engine)?;
 
     registry = configure_twcc_receiver_only(registry, media_engine)?;
 
@@ -39,6 +60,32 @@ pub fn configure_rtcp_reports(mut registry: Registry) -> Registry {
     registry
 }
 
+/// configure_rtcp_reports_with_source_description will setup everything necessary for generating
+/// Sender and Receiver Reports, followed by SDES packets with the items the MediaEngine is
+/// configured with, if any.
+pub fn configure_rtcp_reports_with_source_description(
+    mut registry: Registry,
+    media_engine: &MediaEngine,
+) -> Result<Registry> {
+    let config = media_engine.source_description();
+    if config.is_empty() {
+        return Ok(configure_rtcp_reports(registry));
+    }
+
+    let items = config.items()?;
+    let (mut receiver, mut sender) = (ReceiverReport::builder(), SenderReport::builder());
+    if let Some(cname) = &config.cname {
+        receiver = receiver.with_cname(cname.clone());
+        sender = sender.with_cname(cname.clone());
+    }
+    receiver = receiver.with_source_description_items(items.clone());
+    sender = sender.with_source_description_items(items);
+
+    registry.add(Box::new(receiver));
+    registry.add(Box::new(sender));
+    Ok(registry)
+}
+
 /// configure_nack will setup everything necessary for handling generating/responding to nack messages.
 pub fn configure_nack(mut registry: Registry, media_engine: &mut MediaEngine) -> Registry {
     media_engine.register_feedback(
@@ -169,3 +216,325 @@ pub fn configure_twcc_receiver_only(
     registry.add(receiver);
     Ok(registry)
 }
+
+/// configure_congestion_control_feedback will setup everything necessary for generating the
+/// RFC 8888 congestion control feedback, for remote peers which prefer it over transport-cc.
+pub fn configure_congestion_control_feedback(
+    mut registry: Registry,
+    media_engine: &mut MediaEngine,
+) -> Registry {
+    for typ in [RTPCodecType::Video, RTPCodecType::Audio] {
+        media_engine.register_feedback(
+            RTCPFeedback {
+                typ: TYPE_RTCP_FB_ACK.to_owned(),
+                parameter: "ccfb".to_owned(),
+            },
+            typ,
+        );
+    }
+
+    let receiver = Box::new(ccfb::receiver::Receiver::builder());
+    registry.add(receiver);
+    registry
+}
+
+/// configure_gcc will setup everything necessary for estimating the bandwidth available to
+/// the local tracks from the transport-cc feedback of the remote peer, with Google congestion
+/// control. The target bitrate is handed to the handler set with
+/// [`gcc::sender::SenderBuilder::with_on_target_bitrate`], e.g. that of a
+/// [`interceptor::bitrate_allocator::BitrateAllocator`] splitting it between the tracks.
+///
+/// It adds the TWCC sender interceptor too, which mustn't be added again with
+/// [`configure_twcc`] or [`configure_twcc_sender_only`].
+pub fn configure_gcc(
+    mut registry: Registry,
+    media_engine: &mut MediaEngine,
+    builder: gcc::sender::SenderBuilder,
+) -> Result<Registry> {
+    for typ in [RTPCodecType::Video, RTPCodecType::Audio] {
+        media_engine.register_feedback(
+            RTCPFeedback {
+                typ: TYPE_RTCP_FB_TRANSPORT_CC.to_owned(),
+                ..Default::default()
+            },
+            typ,
+        );
+        media_engine.register_header_extension(
+            RTCRtpHeaderExtensionCapability {
+                uri: sdp::extmap::TRANSPORT_CC_URI.to_owned(),
+            },
+            typ,
+            None,
+        )?;
+    }
+
+    // the estimator is called after the TWCC sender, which sets the sequence numbers
+    registry.add(Box::new(builder));
+    registry.add(Box::new(Sender::builder()));
+    Ok(registry)
+}
+
+/// configure_pacer will setup the pacing of the outgoing RTP packets to a multiple of the
+/// target bitrate, which the returned [`PacerRate`] sets, e.g. from the handler of
+/// [`configure_gcc`]. Handed to [`gcc::sender::SenderBuilder::with_pacer`] instead, taken
+/// from [`pacer::PacerBuilder::rate`], it's kept up to date and sends the probes of the link.
+///
+/// The interceptors added before it get the packets once paced, so it should be added right
+/// after [`configure_gcc`], whose estimator records when the packets are actually sent.
+pub fn configure_pacer(
+    mut registry: Registry,
+    builder: pacer::PacerBuilder,
+) -> (Registry, Arc<PacerRate>) {
+    let rate = builder.rate();
+    registry.add(Box::new(builder));
+    (registry, rate)
+}
+
+/// configure_remb will setup everything necessary for estimating the bandwidth of the video
+/// received and sending it in REMB packets, for remote peers which don't support transport-cc.
+/// The estimate is from the abs-send-time header extension of the packets received.
+pub fn configure_remb(mut registry: Registry, media_engine: &mut MediaEngine) -> Result<Registry> {
+    media_engine.register_feedback(
+        RTCPFeedback {
+            typ: TYPE_RTCP_FB_GOOG_REMB.to_owned(),
+            ..Default::default()
+        },
+        RTPCodecType::Video,
+    );
+    media_engine.register_header_extension(
+        RTCRtpHeaderExtensionCapability {
+            uri: sdp::extmap::ABS_SEND_TIME_URI.to_owned(),
+        },
+        RTPCodecType::Video,
+        None,
+    )?;
+
+    registry.add(Box::new(remb::generator::Generator::builder()));
+    Ok(registry)
+}
+
+/// configure_video_orientation will setup everything necessary for sending and receiving the
+/// urn:3gpp:video-orientation header extension, which signals the rotation of video, see
+/// [`media::Sample::video_orientation`].
+pub fn configure_video_orientation(
+    registry: Registry,
+    media_engine: &mut MediaEngine,
+) -> Result<Registry> {
+    media_engine.register_header_extension(
+        RTCRtpHeaderExtensionCapability {
+            uri: sdp::extmap::VIDEO_ORIENTATION_URI.to_owned(),
+        },
+        RTPCodecType::Video,
+        None,
+    )?;
+
+    Ok(registry)
+}
+
+/// configure_playout_delay will setup everything necessary for sending and receiving the
+/// playout-delay header extension, which lets the sender of a video bound the delay the
+/// receiver renders it with, see
+/// [`SampleBuilder::with_playout_delay_extension`](media::io::sample_builder::SampleBuilder::with_playout_delay_extension).
+pub fn configure_playout_delay(
+    registry: Registry,
+    media_engine: &mut MediaEngine,
+) -> Result<Registry> {
+    media_engine.register_header_extension(
+        RTCRtpHeaderExtensionCapability {
+            uri: sdp::extmap::PLAYOUT_DELAY_URI.to_owned(),
+        },
+        RTPCodecType::Video,
+        None,
+    )?;
+
+    Ok(registry)
+}
+
+/// configure_abs_send_time will setup everything necessary for adding an abs-send-time
+/// header extension to outgoing RTP packets, which the remote peer can estimate the
+/// bandwidth with.
+pub fn configure_abs_send_time(
+    mut registry: Registry,
+    media_engine: &mut MediaEngine,
+) -> Result<Registry> {
+    for typ in [RTPCodecType::Video, RTPCodecType::Audio] {
+        media_engine.register_header_extension(
+            RTCRtpHeaderExtensionCapability {
+                uri: sdp::extmap::ABS_SEND_TIME_URI.to_owned(),
+            },
+            typ,
+            None,
+        )?;
+    }
+
+    registry.add(Box::new(abs_send_time::Sender::builder()));
+    Ok(registry)
+}
+
+/// configure_abs_capture_time will setup everything necessary for parsing the
+/// abs-capture-time header extension of incoming RTP packets. The returned capture times
+/// can be used to synchronize the remote tracks of the same capturing system.
+pub fn configure_abs_capture_time(
+    mut registry: Registry,
+    media_engine: &mut MediaEngine,
+) -> Result<(Registry, Arc<CaptureTimes>)> {
+    for typ in [RTPCodecType::Video, RTPCodecType::Audio] {
+        media_engine.register_header_extension(
+            RTCRtpHeaderExtensionCapability {
+                uri: sdp::extmap::ABS_CAPTURE_TIME_URI.to_owned(),
+            },
+            typ,
+            None,
+        )?;
+    }
+
+    let receiver = abs_capture_time::Receiver::builder();
+    let capture_times = receiver.capture_times();
+    registry.add(Box::new(receiver));
+    Ok((registry, capture_times))
+}
+
+/// configure_audio_level will setup everything necessary for adding the ssrc-audio-level
+/// header extension to the packets of the local audio tracks, with the levels of the samples
+/// pushed to the samples of the sender, and for keeping the levels of the remote audio
+/// tracks, and whether they're speaking, in the levels of the receiver.
+pub fn configure_audio_level(
+    mut registry: Registry,
+    media_engine: &mut MediaEngine,
+    sender: audio_level::sender::SenderBuilder,
+    receiver: audio_level::receiver::ReceiverBuilder,
+) -> Result<Registry> {
+    media_engine.register_header_extension(
+        RTCRtpHeaderExtensionCapability {
+            uri: sdp::extmap::AUDIO_LEVEL_URI.to_owned(),
+        },
+        RTPCodecType::Audio,
+        None,
+    )?;
+
+    registry.add(Box::new(sender));
+    registry.add(Box::new(receiver));
+    Ok(registry)
+}
+
+/// configure_keyframe_aggregation will setup the deduplication and rate limiting of the PLI
+/// and FIR packets read, across all the PeerConnections of the API, so that an SFU forwards
+/// at most one keyframe request per interval to the publisher of a track, whatever its number
+/// of subscribers. The returned [`KeyframeRequests`] should be told when the keyframes are
+/// received.
+pub fn configure_keyframe_aggregation(
+    mut registry: Registry,
+    builder: aggregator::AggregatorBuilder,
+) -> (Registry, Arc<KeyframeRequests>) {
+    let requests = builder.requests();
+    registry.add(Box::new(builder));
+    (registry, requests)
+}
+
+/// configure_jitter_buffer will setup the buffering of the incoming RTP packets of the remote
+/// tracks, which are read in sequence order at smoothed times, with the losses given up on in
+/// the [`jitter_buffer::ATTR_PACKETS_LOST`] attribute of the packets following them.
+///
+/// It should be added last, so that the other interceptors get the packets undelayed.
+pub fn configure_jitter_buffer(
+    mut registry: Registry,
+    builder: jitter_buffer::JitterBufferBuilder,
+) -> Registry {
+    registry.add(Box::new(builder));
+    registry
+}
+
+/// configure_flexfec will setup the protection of the outgoing video packets with FlexFEC
+/// packets, sent on the FEC stream of the tracks which negotiated one, at an overhead
+/// following the losses the remote peer reports.
+pub fn configure_flexfec(
+    mut registry: Registry,
+    builder: flexfec::generator::GeneratorBuilder,
+) -> Registry {
+    registry.add(Box::new(builder));
+    registry
+}
+
+/// configure_pcap will setup the capture of the RTP and RTCP packets sent and received,
+/// before SRTP, to a pcapng file rotated at the size configured by the builder.
+///
+/// It should be added first, so that the packets are captured as they are sent and received.
+pub fn configure_pcap(mut registry: Registry, builder: pcap::PcapBuilder) -> Registry {
+    registry.add(Box::new(builder));
+    registry
+}
+
+/// configure_forwarder will setup the forwarding of a single simulcast layer to each local
+/// video track, chosen from the target bitrates set with the returned [`ForwarderTargets`].
+/// The packets of the layers are written to the track with
+/// [`crate::track::track_local::TrackLocalWriter::write_rtp_with_attributes`], with their layer
+/// in the [`forwarder::ATTR_SIMULCAST_LAYER`] attribute.
+pub fn configure_forwarder(
+    mut registry: Registry,
+    builder: forwarder::ForwarderBuilder,
+) -> (Registry, Arc<ForwarderTargets>) {
+    let targets = builder.targets();
+    registry.add(Box::new(builder));
+    (registry, targets)
+}
+
+/// configure_policer will setup the policing of the remote tracks, the packets of which over
+/// the bitrate limits set with the returned [`PolicerLimits`] are dropped or flagged. The
+/// limits of the MIDs need the sdes:mid header extension to be registered with the
+/// MediaEngine, as for simulcast.
+///
+/// It should be added before the interceptors of the remote tracks, e.g. the NACK generator,
+/// so that they don't get the packets dropped.
+pub fn configure_policer(
+    mut registry: Registry,
+    builder: policer::PolicerBuilder,
+) -> (Registry, Arc<PolicerLimits>) {
+    let limits = builder.limits();
+    registry.add(Box::new(builder));
+    (registry, limits)
+}
+
+/// configure_extension_rewriter will setup the rewriting of the header extensions of the
+/// packets forwarded to the local tracks from the remote tracks of other peer connections,
+/// whose extension ids are remapped to those negotiated for the local tracks, and the MIDs and
+/// RIDs rewritten as set with the returned [`ExtensionRewrites`].
+///
+/// The same builder, or its rewrites, should be used for the peer connections forwarded from,
+/// so that the extensions of their remote tracks are known, and the packets written to the
+/// tracks with [`crate::track::track_local::TrackLocalWriter::write_rtp_with_attributes`],
+/// with the SSRC of their remote track in the [`extension_rewriter::ATTR_SOURCE_SSRC`]
+/// attribute.
+pub fn configure_extension_rewriter(
+    mut registry: Registry,
+    builder: extension_rewriter::ExtensionRewriterBuilder,
+) -> (Registry, Arc<ExtensionRewrites>) {
+    let rewrites = builder.rewrites();
+    registry.add(Box::new(builder));
+    (registry, rewrites)
+}
+
+/// configure_frame_encryption will setup the end-to-end encryption of the frames of the local
+/// tracks, and the decryption of the frames of the remote tracks, with the keys returned by
+/// the handlers of the builders, in the format of the insertable streams E2EE of the browsers.
+///
+/// It should be added last, after the jitter buffer if any, so that the other interceptors
+/// get the packets encrypted, and the frames are decrypted from the packets in order.
+pub fn configure_frame_encryption(
+    mut registry: Registry,
+    encryptor: FrameEncryptorBuilder,
+    decryptor: FrameDecryptorBuilder,
+) -> Registry {
+    registry.add(Box::new(encryptor));
+    registry.add(Box::new(decryptor));
+    registry
+}
+
+/// configure_frame_layer will setup the frame interceptors of the builder, which get the
+/// frames of the VP8 and audio tracks reassembled from their packets.
+///
+/// As for the frame encryption, it should be added last, after the jitter buffer if any, so
+/// that the frames are reassembled from the packets in order.
+pub fn configure_frame_layer(mut registry: Registry, builder: FrameLayerBuilder) -> Registry {
+    registry.add(Box::new(builder));
+    registry
+}
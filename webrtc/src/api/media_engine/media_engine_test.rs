@@ -526,6 +526,80 @@ a=fmtp:97 apt=96
     Ok(())
 }
 
+#[tokio::test]
+async fn test_media_engine_multiopus() -> Result<()> {
+    let must_parse = |raw: &str| -> Result<SessionDescription> {
+        let mut reader = Cursor::new(raw.as_bytes());
+        Ok(SessionDescription::unmarshal(&mut reader)?)
+    };
+    let register_surround = |m: &mut MediaEngine| -> Result<()> {
+        m.register_default_codecs()?;
+        m.register_codec(
+            RTCRtpCodecParameters {
+                capability: RTCRtpCodecCapability {
+                    mime_type: MIME_TYPE_MULTIOPUS.to_owned(),
+                    clock_rate: 48000,
+                    channels: 6,
+                    sdp_fmtp_line:
+                        "channel_mapping=0,4,1,2,3,5;coupled_streams=2;num_streams=4;minptime=10"
+                            .to_owned(),
+                    rtcp_feedback: vec![],
+                },
+                payload_type: 112,
+                ..Default::default()
+            },
+            RTPCodecType::Audio,
+        )
+    };
+
+    //"Same channel mapping"
+    {
+        const SURROUND: &str = "v=0
+o=- 4596489990601351948 2 IN IP4 127.0.0.1
+s=-
+t=0 0
+m=audio 9 UDP/TLS/RTP/SAVPF 111 113
+a=rtpmap:111 opus/48000/2
+a=fmtp:111 minptime=10;useinbandfec=1
+a=rtpmap:113 multiopus/48000/6
+a=fmtp:113 channel_mapping=0,4,1,2,3,5;coupled_streams=2;minptime=10;num_streams=4;useinbandfec=1
+";
+        let mut m = MediaEngine::default();
+        register_surround(&mut m)?;
+        m.update_from_remote_description(&must_parse(SURROUND)?)
+            .await?;
+
+        assert!(m.negotiated_audio.load(Ordering::SeqCst));
+        let (codec, _) = m.get_codec_by_payload(113).await?;
+        assert_eq!(codec.capability.mime_type, MIME_TYPE_MULTIOPUS);
+        assert_eq!(codec.capability.channels, 6);
+        assert!(codec.capability.payloader_for_codec().is_ok());
+    }
+
+    //"Other channel mapping"
+    {
+        const SURROUND_7_1: &str = "v=0
+o=- 4596489990601351948 2 IN IP4 127.0.0.1
+s=-
+t=0 0
+m=audio 9 UDP/TLS/RTP/SAVPF 113
+a=rtpmap:113 multiopus/48000/8
+a=fmtp:113 channel_mapping=0,6,1,2,3,4,5,7;coupled_streams=3;minptime=10;num_streams=5
+";
+        let mut m = MediaEngine::default();
+        register_surround(&mut m)?;
+        m.update_from_remote_description(&must_parse(SURROUND_7_1)?)
+            .await?;
+
+        assert!(
+            m.get_codec_by_payload(113).await.is_err(),
+            "a multiopus codec with another channel mapping shouldn't match partially"
+        );
+    }
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_media_engine_header_extension_direction() -> Result<()> {
     let register_codec = |m: &mut MediaEngine| -> Result<()> {
@@ -801,3 +875,27 @@ a=rtpmap:111 opus/48000/2
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_media_engine_two_byte_header_extension_ids() -> Result<()> {
+    let mut m = MediaEngine::default();
+    m.register_default_codecs()?;
+    for i in 0..20 {
+        m.register_header_extension(
+            RTCRtpHeaderExtensionCapability {
+                uri: format!("test-extension-{i}"),
+            },
+            RTPCodecType::Video,
+            None,
+        )?;
+    }
+
+    // Ids above 14 are proposed once the one byte ids are used up, skipping the reserved 15.
+    let params =
+        m.get_rtp_parameters_by_kind(RTPCodecType::Video, RTCRtpTransceiverDirection::Sendrecv);
+    let mut ids: Vec<isize> = params.header_extensions.iter().map(|ext| ext.id).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, (1..15).chain(16..22).collect::<Vec<isize>>());
+
+    Ok(())
+}
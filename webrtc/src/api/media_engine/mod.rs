@@ -6,7 +6,9 @@ use std::ops::Range;
 use std::sync::atomic::Ordering;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use bytes::Bytes;
 use portable_atomic::AtomicBool;
+use rtcp::source_description::{SdesType, SourceDescriptionItem};
 use sdp::description::session::SessionDescription;
 use util::sync::Mutex as SyncMutex;
 
@@ -34,6 +36,11 @@ pub const MIME_TYPE_HEVC: &str = "video/HEVC";
 /// MIME_TYPE_OPUS Opus MIME type
 /// Note: Matching should be case insensitive.
 pub const MIME_TYPE_OPUS: &str = "audio/opus";
+/// MIME_TYPE_MULTIOPUS multichannel Opus MIME type, for more than two channels. Its fmtp
+/// line has to give the channel mapping, e.g. for 5.1 surround
+/// "channel_mapping=0,4,1,2,3,5;coupled_streams=2;num_streams=4".
+/// Note: Matching should be case insensitive.
+pub const MIME_TYPE_MULTIOPUS: &str = "audio/multiopus";
 /// MIME_TYPE_VP8 VP8 MIME type
 /// Note: Matching should be case insensitive.
 pub const MIME_TYPE_VP8: &str = "video/VP8";
@@ -43,6 +50,9 @@ pub const MIME_TYPE_VP9: &str = "video/VP9";
 /// MIME_TYPE_AV1 AV1 MIME type
 /// Note: Matching should be case insensitive.
 pub const MIME_TYPE_AV1: &str = "video/AV1";
+/// MIME_TYPE_JPEG JPEG MIME type, which is used for MJPEG
+/// Note: Matching should be case insensitive.
+pub const MIME_TYPE_JPEG: &str = "video/JPEG";
 /// MIME_TYPE_G722 G722 MIME type
 /// Note: Matching should be case insensitive.
 pub const MIME_TYPE_G722: &str = "audio/G722";
@@ -55,8 +65,18 @@ pub const MIME_TYPE_PCMA: &str = "audio/PCMA";
 /// MIME_TYPE_TELEPHONE_EVENT telephone-event MIME type
 /// Note: Matching should be case insensitive.
 pub const MIME_TYPE_TELEPHONE_EVENT: &str = "audio/telephone-event";
+/// MIME_TYPE_L16 L16 MIME type, 16 bit linear PCM
+/// Note: Matching should be case insensitive.
+pub const MIME_TYPE_L16: &str = "audio/L16";
+/// MIME_TYPE_L24 L24 MIME type, 24 bit linear PCM
+/// Note: Matching should be case insensitive.
+pub const MIME_TYPE_L24: &str = "audio/L24";
 
 const VALID_EXT_IDS: Range<isize> = 1..15;
+/// Ids which only two byte header extensions take, proposed once all of [`VALID_EXT_IDS`] are
+/// used. 15 is left out, as one byte header extensions reserve it. These need the
+/// extmap-allow-mixed attribute, which is always offered.
+const VALID_TWO_BYTE_EXT_IDS: Range<isize> = 16..256;
 
 #[derive(Default, Clone)]
 pub(crate) struct MediaEngineHeaderExtension {
@@ -80,6 +100,59 @@ impl MediaEngineHeaderExtension {
     }
 }
 
+/// SourceDescriptionConfig sets the items of the RTCP SDES packets sent along with the
+/// reports. Monitoring and recording systems may identify the streams by their TOOL or NOTE.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct SourceDescriptionConfig {
+    /// CNAME of the SDES packets. A random one is used when other items are set without it.
+    pub cname: Option<String>,
+    /// NAME item, the name of the user.
+    pub name: Option<String>,
+    /// EMAIL item, the e-mail address of the user.
+    pub email: Option<String>,
+    /// TOOL item, the name and version of the application.
+    pub tool: Option<String>,
+    /// NOTE item, a transient message describing the state of the source.
+    pub note: Option<String>,
+    /// PRIV items, as their prefix and value.
+    pub private: Vec<(String, String)>,
+}
+
+impl SourceDescriptionConfig {
+    /// is_empty returns whether no SDES packet is sent.
+    pub fn is_empty(&self) -> bool {
+        *self == SourceDescriptionConfig::default()
+    }
+
+    /// items returns the SDES items sent after the CNAME.
+    pub fn items(&self) -> Result<Vec<SourceDescriptionItem>> {
+        let mut items = vec![];
+        for (sdes_type, text) in [
+            (SdesType::SdesName, &self.name),
+            (SdesType::SdesEmail, &self.email),
+            (SdesType::SdesTool, &self.tool),
+            (SdesType::SdesNote, &self.note),
+        ] {
+            if let Some(text) = text {
+                if text.len() > u8::MAX as usize {
+                    return Err(rtcp::Error::SdesTextTooLong.into());
+                }
+                items.push(SourceDescriptionItem {
+                    sdes_type,
+                    text: Bytes::from(text.clone()),
+                });
+            }
+        }
+        for (prefix, value) in &self.private {
+            items.push(SourceDescriptionItem::private(
+                prefix.as_bytes(),
+                value.as_bytes(),
+            )?);
+        }
+        Ok(items)
+    }
+}
+
 /// A MediaEngine defines the codecs supported by a PeerConnection, and the
 /// configuration of those codecs. A MediaEngine must not be shared between
 /// PeerConnections.
@@ -97,6 +170,8 @@ pub struct MediaEngine {
     header_extensions: Vec<MediaEngineHeaderExtension>,
     proposed_header_extensions: SyncMutex<HashMap<isize, MediaEngineHeaderExtension>>,
     pub(crate) negotiated_header_extensions: SyncMutex<HashMap<isize, MediaEngineHeaderExtension>>,
+
+    source_description: SourceDescriptionConfig,
 }
 
 impl MediaEngine {
@@ -384,7 +459,9 @@ impl MediaEngine {
                 Some(ext) => ext,
                 None => {
                     // We have registered too many extensions
-                    if self.header_extensions.len() > VALID_EXT_IDS.end as usize {
+                    if self.header_extensions.len()
+                        >= VALID_EXT_IDS.len() + VALID_TWO_BYTE_EXT_IDS.len()
+                    {
                         return Err(Error::ErrRegisterHeaderExtensionNoFreeID);
                     }
                     self.header_extensions.push(MediaEngineHeaderExtension {
@@ -430,6 +507,20 @@ impl MediaEngine {
         }
     }
 
+    /// set_source_description sets the items of the RTCP SDES packets sent along with the
+    /// reports, by the interceptors configured with configure_rtcp_reports_with_source_description.
+    pub fn set_source_description(&mut self, config: SourceDescriptionConfig) -> Result<()> {
+        config.items()?;
+        self.source_description = config;
+        Ok(())
+    }
+
+    /// source_description returns the items of the RTCP SDES packets sent along with the
+    /// reports.
+    pub fn source_description(&self) -> &SourceDescriptionConfig {
+        &self.source_description
+    }
+
     /// get_header_extension_id returns the negotiated ID for a header extension.
     /// If the Header Extension isn't enabled ok will be false
     pub async fn get_header_extension_id(
@@ -457,6 +548,7 @@ impl MediaEngine {
             video_codecs: self.video_codecs.clone(),
             audio_codecs: self.audio_codecs.clone(),
             header_extensions: self.header_extensions.clone(),
+            source_description: self.source_description.clone(),
             ..Default::default()
         }
     }
@@ -772,10 +864,13 @@ impl MediaEngine {
                 }
 
                 // Figure out which (unused id) to propose.
-                let id = VALID_EXT_IDS.clone().find(|id| {
-                    !negotiated_header_extensions.keys().any(|nid| nid == id)
-                        && !proposed_header_extensions.keys().any(|pid| pid == id)
-                });
+                let id = VALID_EXT_IDS
+                    .clone()
+                    .chain(VALID_TWO_BYTE_EXT_IDS)
+                    .find(|id| {
+                        !negotiated_header_extensions.keys().any(|nid| nid == id)
+                            && !proposed_header_extensions.keys().any(|pid| pid == id)
+                    });
 
                 if let Some(id) = id {
                     proposed_header_extensions.insert(
@@ -3,18 +3,25 @@ mod setting_engine_test;
 
 use std::sync::Arc;
 
+use dtls::config::ClientAuthType;
 use dtls::extension::extension_use_srtp::SrtpProtectionProfile;
+use dtls::handshaker::VerifyPeerCertificateFn;
 use ice::agent::agent_config::{InterfaceFilterFn, IpFilterFn};
 use ice::mdns::MulticastDnsMode;
 use ice::network_type::NetworkType;
 use ice::udp_network::UDPNetwork;
+use interceptor::stats::MetricsSink;
+use sctp::congestion::CongestionControlAlgorithm;
+use sctp::stream::StreamScheduler;
+use srtp::context::{SrtcpEncryptionPolicy, SsrcStateLimits};
+use srtp::cryptex::CryptexPolicy;
 use tokio::time::Duration;
 use util::vnet::net::*;
 
 use crate::dtls_transport::dtls_role::DTLSRole;
 use crate::error::{Error, Result};
 use crate::ice_transport::ice_candidate_type::RTCIceCandidateType;
-use crate::RECEIVE_MTU;
+use crate::{RECEIVE_MTU, RTCP_MTU};
 
 #[derive(Default, Clone)]
 pub struct Detach {
@@ -47,6 +54,25 @@ pub struct Candidates {
     pub include_loopback_candidate: bool,
 }
 
+#[derive(Default, Clone)]
+pub struct DtlsClientAuth {
+    pub client_auth: Option<ClientAuthType>,
+    pub client_cas: Option<rustls::RootCertStore>,
+    pub verify_peer_certificate: Option<VerifyPeerCertificateFn>,
+}
+
+impl DtlsClientAuth {
+    /// requires_client_certificate returns true if the configured policy makes the
+    /// DTLS server abort the handshake when the client presents no certificate.
+    pub(crate) fn requires_client_certificate(&self) -> bool {
+        matches!(
+            self.client_auth
+                .unwrap_or(ClientAuthType::RequireAnyClientCert),
+            ClientAuthType::RequireAnyClientCert | ClientAuthType::RequireAndVerifyClientCert
+        )
+    }
+}
+
 #[derive(Default, Clone)]
 pub struct ReplayProtection {
     pub dtls: usize,
@@ -54,6 +80,10 @@ pub struct ReplayProtection {
     pub srtcp: usize,
 }
 
+/// DataChannelFilterFn decides from its label and protocol whether a data channel opened by
+/// the remote side is accepted.
+pub type DataChannelFilterFn = dyn Fn(&str, &str) -> bool + Send + Sync;
+
 /// SettingEngine allows influencing behavior in ways that are not
 /// supported by the WebRTC API. This allows us to support additional
 /// use-cases without deviating from the WebRTC API elsewhere.
@@ -63,6 +93,7 @@ pub struct SettingEngine {
     pub(crate) timeout: Timeout,
     pub(crate) candidates: Candidates,
     pub(crate) replay_protection: ReplayProtection,
+    pub(crate) dtls_client_auth: DtlsClientAuth,
     pub(crate) sdp_media_level_fingerprints: bool,
     pub(crate) answering_dtls_role: DTLSRole,
     pub(crate) disable_certificate_fingerprint_verification: bool,
@@ -76,8 +107,25 @@ pub struct SettingEngine {
     pub(crate) udp_network: UDPNetwork,
     pub(crate) disable_media_engine_copy: bool,
     pub(crate) srtp_protection_profiles: Vec<SrtpProtectionProfile>,
+    pub(crate) srtp_cryptex_policy: CryptexPolicy,
+    pub(crate) srtcp_encryption_policy: SrtcpEncryptionPolicy,
+    pub(crate) srtp_ssrc_state_limits: SsrcStateLimits,
+    pub(crate) srtp_sdes_insecure_signaling: bool,
+    pub(crate) sctp_stream_scheduler: StreamScheduler,
+    pub(crate) sctp_congestion_control: CongestionControlAlgorithm,
+    pub(crate) sctp_sack_delay: Duration,
+    pub(crate) sctp_packets_per_sack: u32,
+    pub(crate) sctp_immediate_sack: bool,
+    pub(crate) sctp_zero_checksum: bool,
+    pub(crate) sctp_heartbeat_interval: Duration,
+    pub(crate) sctp_path_max_retransmits: u32,
+    pub(crate) sctp_association_max_retransmits: u32,
+    pub(crate) sctp_max_send_buffer_size: u32,
     pub(crate) receive_mtu: usize,
+    pub(crate) rtcp_mtu: usize,
     pub(crate) mid_generator: Option<Arc<dyn Fn(isize) -> String + Send + Sync>>,
+    pub(crate) data_channel_filter: Option<Arc<DataChannelFilterFn>>,
+    pub(crate) stats_metrics_sink: Option<Arc<dyn MetricsSink + Send + Sync>>,
 }
 
 impl SettingEngine {
@@ -89,6 +137,16 @@ impl SettingEngine {
             RECEIVE_MTU
         }
     }
+
+    /// get_rtcp_mtu returns the size RTCP datagrams are kept under. If SettingEngine's RTCP MTU
+    /// is configured to 0 it returns the default
+    pub(crate) fn get_rtcp_mtu(&self) -> usize {
+        if self.rtcp_mtu != 0 {
+            self.rtcp_mtu
+        } else {
+            RTCP_MTU
+        }
+    }
     /// detach_data_channels enables detaching data channels. When enabled
     /// data channels have to be detached in the OnOpen callback using the
     /// DataChannel.Detach method.
@@ -96,12 +154,116 @@ impl SettingEngine {
         self.detach.data_channels = true;
     }
 
+    /// set_data_channel_filter sets a callback deciding, given their label and protocol, which
+    /// data channels opened by the remote side are accepted. A rejected data channel is closed
+    /// before it is announced with on_data_channel. By default all data channels are accepted.
+    pub fn set_data_channel_filter(
+        &mut self,
+        f: impl Fn(&str, &str) -> bool + Send + Sync + 'static,
+    ) {
+        self.data_channel_filter = Some(Arc::new(f));
+    }
+
     /// set_srtp_protection_profiles allows the user to override the default srtp Protection Profiles
     /// The default srtp protection profiles are provided by the function `defaultSrtpProtectionProfiles`
     pub fn set_srtp_protection_profiles(&mut self, profiles: Vec<SrtpProtectionProfile>) {
         self.srtp_protection_profiles = profiles
     }
 
+    /// set_srtp_cryptex_policy sets whether CSRCs and RTP header extensions are encrypted
+    /// as described in RFC 9335. Unless disabled, cryptex is signaled with `a=cryptex` and
+    /// used when the remote side signals it too. When required, SRTP fails to start if
+    /// the remote side does not support cryptex.
+    pub fn set_srtp_cryptex_policy(&mut self, policy: CryptexPolicy) {
+        self.srtp_cryptex_policy = policy;
+    }
+
+    /// set_srtcp_encryption_policy sets whether SRTCP payloads are encrypted or only
+    /// authenticated, for interop with endpoints sending unencrypted SRTCP. With SDES,
+    /// disabling encryption is signaled with `UNENCRYPTED_SRTCP` and only applies when
+    /// the remote side signals it too. Encrypted SRTCP is used by default.
+    pub fn set_srtcp_encryption_policy(&mut self, policy: SrtcpEncryptionPolicy) {
+        self.srtcp_encryption_policy = policy;
+    }
+
+    /// set_srtp_ssrc_state_limits bounds the per-SSRC crypto state kept for incoming SRTP
+    /// and SRTCP streams, for long-lived transports that see many transient SSRCs.
+    /// The state of every SSRC seen is kept by default.
+    pub fn set_srtp_ssrc_state_limits(&mut self, limits: SsrcStateLimits) {
+        self.srtp_ssrc_state_limits = limits;
+    }
+
+    /// set_srtp_sdes_insecure_signaling allows SRTP keys to be negotiated with SDES `a=crypto`
+    /// attributes (RFC 4568) for interop with legacy endpoints that don't implement DTLS-SRTP.
+    /// Offers carry `a=crypto` next to the DTLS fingerprint, and SDES is only used when the
+    /// remote description has no fingerprint. The keys travel in plain text in the SDP, so this
+    /// must only be enabled when the signaling channel is trusted and encrypted. Data channels
+    /// are not available over SDES as they need DTLS.
+    pub fn set_srtp_sdes_insecure_signaling(&mut self, enabled: bool) {
+        self.srtp_sdes_insecure_signaling = enabled;
+    }
+
+    /// set_sctp_stream_scheduler sets how messages queued on different data channels share
+    /// the SCTP association. With the weighted fair scheduler each data channel gets a share
+    /// proportional to its priority, so bulk transfers can't starve interactive channels.
+    /// Round robin is used by default.
+    pub fn set_sctp_stream_scheduler(&mut self, scheduler: StreamScheduler) {
+        self.sctp_stream_scheduler = scheduler;
+    }
+
+    /// set_sctp_congestion_control sets the congestion control used by the SCTP association
+    /// carrying data channels. The delay-based Vegas algorithm fills paths with a high
+    /// bandwidth-delay product much faster than the default AIMD, which helps large transfers.
+    pub fn set_sctp_congestion_control(&mut self, algorithm: CongestionControlAlgorithm) {
+        self.sctp_congestion_control = algorithm;
+    }
+
+    /// set_sctp_sack_delay sets how SCTP acknowledges received data.
+    /// * delay is how long received data may go unacknowledged. Default is 200ms, at most 500ms
+    /// * packets_per_sack is the number of packets after which data is acknowledged without waiting for the delay. Default is 2
+    ///
+    /// A shorter delay or fewer packets per SACK make the sender more responsive at the cost of more SACKs.
+    pub fn set_sctp_sack_delay(&mut self, delay: Duration, packets_per_sack: u32) {
+        self.sctp_sack_delay = delay;
+        self.sctp_packets_per_sack = packets_per_sack;
+    }
+
+    /// set_sctp_immediate_sack sets whether SCTP asks the remote side to acknowledge
+    /// the last data sent before the send queue runs empty right away, using the
+    /// I bit of RFC 7053, instead of after its delayed SACK timer.
+    pub fn set_sctp_immediate_sack(&mut self, enabled: bool) {
+        self.sctp_immediate_sack = enabled;
+    }
+
+    /// enable_sctp_zero_checksum sets whether SCTP offers to skip the CRC32c checksum
+    /// of its packets (RFC 9653), which DTLS makes redundant. The checksum is only
+    /// left out once the remote side agreed, saving CPU on busy data channels.
+    pub fn enable_sctp_zero_checksum(&mut self, enabled: bool) {
+        self.sctp_zero_checksum = enabled;
+    }
+
+    /// set_sctp_heartbeat sets how SCTP finds out that the remote side is no longer reachable.
+    /// * interval is how often a HEARTBEAT is sent while no data is in flight. Default is zero, which sends none
+    /// * path_max_retransmits is the number of consecutive timeouts after which the path is reported inactive. Default is 5
+    /// * association_max_retransmits is the number of consecutive timeouts after which the association is closed. Default is zero, which never closes it
+    pub fn set_sctp_heartbeat(
+        &mut self,
+        interval: Duration,
+        path_max_retransmits: u32,
+        association_max_retransmits: u32,
+    ) {
+        self.sctp_heartbeat_interval = interval;
+        self.sctp_path_max_retransmits = path_max_retransmits;
+        self.sctp_association_max_retransmits = association_max_retransmits;
+    }
+
+    /// set_sctp_max_send_buffer_size sets how many bytes all data channels may have buffered
+    /// for sending together. Once several data channels have data buffered, each one may only
+    /// use an equal share, and sends beyond it fail. Default is zero, which sets no limit.
+    pub fn set_sctp_max_send_buffer_size(&mut self, max_send_buffer_size: u32) {
+        self.sctp_max_send_buffer_size = max_send_buffer_size;
+    }
+
     /// set_ice_timeouts sets the behavior around ICE Timeouts
     /// * disconnected_timeout is the duration without network activity before a Agent is considered disconnected. Default is 5 Seconds
     /// * failed_timeout is the duration without network activity before a Agent is considered failed after disconnected. Default is 25 Seconds
@@ -258,6 +420,32 @@ impl SettingEngine {
         self.replay_protection.dtls = n;
     }
 
+    /// set_dtls_client_auth sets the policy the dtls_transport follows for client
+    /// certificate authentication when it acts as DTLS server. By default a client
+    /// certificate is required but not verified, the peer is authenticated through the
+    /// fingerprint exchanged in the SDP instead. Policies that allow the client to omit
+    /// its certificate also need `disable_certificate_fingerprint_verification`.
+    pub fn set_dtls_client_auth(&mut self, client_auth: ClientAuthType) {
+        self.dtls_client_auth.client_auth = Some(client_auth);
+    }
+
+    /// set_dtls_client_cas sets the root certificate authorities used to verify client
+    /// certificates when the client auth policy is `VerifyClientCertIfGiven` or
+    /// `RequireAndVerifyClientCert`.
+    pub fn set_dtls_client_cas(&mut self, client_cas: rustls::RootCertStore) {
+        self.dtls_client_auth.client_cas = Some(client_cas);
+    }
+
+    /// set_dtls_verify_peer_certificate sets a callback which is invoked with the
+    /// certificate chain presented by the remote peer, along with the chains verified
+    /// against the configured CAs, if any. Returning an error aborts the handshake.
+    pub fn set_dtls_verify_peer_certificate(
+        &mut self,
+        verify_peer_certificate: VerifyPeerCertificateFn,
+    ) {
+        self.dtls_client_auth.verify_peer_certificate = Some(verify_peer_certificate);
+    }
+
     /// set_srtp_replay_protection_window sets a replay attack protection window size of srtp session.
     pub fn set_srtp_replay_protection_window(&mut self, n: usize) {
         self.disable_srtp_replay_protection = false;
@@ -321,6 +509,14 @@ impl SettingEngine {
         self.receive_mtu = receive_mtu;
     }
 
+    /// set_rtcp_mtu sets the size the RTCP datagrams sent are kept under, before the SRTCP
+    /// overhead. The batches of RTCP packets which don't fit are split across several
+    /// compounds, moving the reception reports which don't fit to additional ReceiverReports.
+    /// Leave this 0 for the default of 1200 bytes
+    pub fn set_rtcp_mtu(&mut self, rtcp_mtu: usize) {
+        self.rtcp_mtu = rtcp_mtu;
+    }
+
     /// Sets a callback used to generate mid for transceivers created by this side of the RTCPeerconnection.
     /// By having separate "naming schemes" for mids generated by either side of a connection, it's
     /// possible to reduce complexity when handling SDP offers/answers clashing.
@@ -334,4 +530,10 @@ impl SettingEngine {
     pub fn set_mid_generator(&mut self, f: impl Fn(isize) -> String + Send + Sync + 'static) {
         self.mid_generator = Some(Arc::new(f));
     }
+
+    /// set_stats_metrics_sink sets the sink the metrics of the RTP streams of the peer
+    /// connections are handed to as they are measured, besides being reported by get_stats.
+    pub fn set_stats_metrics_sink(&mut self, sink: Arc<dyn MetricsSink + Send + Sync>) {
+        self.stats_metrics_sink = Some(sink);
+    }
 }
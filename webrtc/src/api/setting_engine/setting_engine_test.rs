@@ -1,10 +1,16 @@
 use std::sync::atomic::Ordering;
 
+use bytes::Bytes;
+use tokio::sync::mpsc;
+
 use super::*;
-use crate::api::media_engine::MediaEngine;
+use crate::api::media_engine::{MediaEngine, MIME_TYPE_VP8};
 use crate::api::APIBuilder;
+use crate::dtls_transport::dtls_transport_state::RTCDtlsTransportState;
 use crate::peer_connection::peer_connection_test::*;
-use crate::rtp_transceiver::rtp_codec::RTPCodecType;
+use crate::peer_connection::sdp::session_description::RTCSessionDescription;
+use crate::rtp_transceiver::rtp_codec::{RTCRtpCodecCapability, RTPCodecType};
+use crate::track::track_local::track_local_static_sample::TrackLocalStaticSample;
 
 #[test]
 fn test_set_connection_timeout() -> Result<()> {
@@ -125,6 +131,353 @@ fn test_set_replay_protection() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_set_dtls_client_auth() -> Result<()> {
+    let mut s = SettingEngine::default();
+
+    assert!(s.dtls_client_auth.client_auth.is_none());
+    assert!(s.dtls_client_auth.client_cas.is_none());
+    assert!(s.dtls_client_auth.verify_peer_certificate.is_none());
+    assert!(s.dtls_client_auth.requires_client_certificate());
+
+    s.set_dtls_client_auth(ClientAuthType::RequestClientCert);
+    assert_eq!(
+        s.dtls_client_auth.client_auth,
+        Some(ClientAuthType::RequestClientCert)
+    );
+    assert!(!s.dtls_client_auth.requires_client_certificate());
+
+    s.set_dtls_client_auth(ClientAuthType::RequireAndVerifyClientCert);
+    assert!(s.dtls_client_auth.requires_client_certificate());
+
+    s.set_dtls_client_cas(rustls::RootCertStore::empty());
+    assert!(s.dtls_client_auth.client_cas.is_some());
+
+    s.set_dtls_verify_peer_certificate(Arc::new(|certs, _| {
+        if certs.is_empty() {
+            Err(dtls::Error::ErrClientCertificateRequired)
+        } else {
+            Ok(())
+        }
+    }));
+    if let Some(verify) = &s.dtls_client_auth.verify_peer_certificate {
+        assert!(verify(&[], &[]).is_err());
+        assert!(verify(&[vec![0u8]], &[]).is_ok());
+    } else {
+        panic!("Failed to set DTLS verify_peer_certificate callback");
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_set_srtp_cryptex_policy() -> Result<()> {
+    let mut s = SettingEngine::default();
+    assert_eq!(s.srtp_cryptex_policy, CryptexPolicy::Disabled);
+
+    s.set_srtp_cryptex_policy(CryptexPolicy::Enabled);
+    assert_eq!(s.srtp_cryptex_policy, CryptexPolicy::Enabled);
+
+    let mut m = MediaEngine::default();
+    m.register_default_codecs()?;
+    let api = APIBuilder::new()
+        .with_media_engine(m)
+        .with_setting_engine(s)
+        .build();
+
+    let (mut offerer, mut answerer) = new_pair(&api).await?;
+    offerer
+        .add_transceiver_from_kind(RTPCodecType::Video, None)
+        .await?;
+
+    signal_pair(&mut offerer, &mut answerer).await?;
+
+    for pc in [&offerer, &answerer] {
+        let remote_description = pc.remote_description().await.unwrap();
+        assert!(remote_description.sdp.contains("a=cryptex"));
+        assert!(pc.dtls_transport().remote_cryptex.load(Ordering::SeqCst));
+    }
+
+    close_pair_now(&offerer, &answerer).await;
+
+    // An answerer with cryptex disabled doesn't take it up
+    let mut m = MediaEngine::default();
+    m.register_default_codecs()?;
+    let default_api = APIBuilder::new().with_media_engine(m).build();
+
+    let mut offerer = api.new_peer_connection(Default::default()).await?;
+    let mut answerer = default_api.new_peer_connection(Default::default()).await?;
+    offerer
+        .add_transceiver_from_kind(RTPCodecType::Video, None)
+        .await?;
+
+    signal_pair(&mut offerer, &mut answerer).await?;
+
+    let remote_description = offerer.remote_description().await.unwrap();
+    assert!(!remote_description.sdp.contains("a=cryptex"));
+    assert!(!offerer
+        .dtls_transport()
+        .remote_cryptex
+        .load(Ordering::SeqCst));
+
+    close_pair_now(&offerer, &answerer).await;
+
+    Ok(())
+}
+
+// Removes the DTLS fingerprints, as sent by an endpoint without DTLS-SRTP
+fn strip_fingerprints(sdp: &str) -> String {
+    sdp.split_inclusive("\r\n")
+        .filter(|line| !line.starts_with("a=fingerprint:"))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_set_srtp_sdes_insecure_signaling() -> Result<()> {
+    let mut s = SettingEngine::default();
+    assert!(!s.srtp_sdes_insecure_signaling);
+
+    s.set_srtp_sdes_insecure_signaling(true);
+    assert!(s.srtp_sdes_insecure_signaling);
+
+    let mut m = MediaEngine::default();
+    m.register_default_codecs()?;
+    let api = APIBuilder::new()
+        .with_media_engine(m)
+        .with_setting_engine(s)
+        .build();
+
+    // DTLS is preferred when both sides support it
+    let (mut offerer, mut answerer) = new_pair(&api).await?;
+    offerer
+        .add_transceiver_from_kind(RTPCodecType::Video, None)
+        .await?;
+
+    signal_pair(&mut offerer, &mut answerer).await?;
+
+    assert!(answerer
+        .remote_description()
+        .await
+        .unwrap()
+        .sdp
+        .contains("a=crypto:1 AEAD_AES_128_GCM inline:"));
+    assert!(!offerer
+        .remote_description()
+        .await
+        .unwrap()
+        .sdp
+        .contains("a=crypto"));
+    for pc in [&offerer, &answerer] {
+        assert!(!pc.dtls_transport().sdes_negotiated().await);
+    }
+
+    close_pair_now(&offerer, &answerer).await;
+
+    // SDES is used when the remote side sends no fingerprint
+    let (offerer, answerer) = new_pair(&api).await?;
+    let track = Arc::new(TrackLocalStaticSample::new(
+        RTCRtpCodecCapability {
+            mime_type: MIME_TYPE_VP8.to_owned(),
+            ..Default::default()
+        },
+        "video".to_owned(),
+        "webrtc-rs".to_owned(),
+    ));
+    offerer.add_track(track.clone()).await?;
+
+    let (packet_tx, packet_rx) = mpsc::channel(1);
+    answerer.on_track(Box::new(move |track, _, _| {
+        let packet_tx = packet_tx.clone();
+        tokio::spawn(async move {
+            while let Ok((pkt, _)) = track.read_rtp().await {
+                if pkt.payload.last() == Some(&0xAA) {
+                    let _ = packet_tx.send(()).await;
+                    break;
+                }
+            }
+        });
+        Box::pin(async {})
+    }));
+
+    let offer = offerer.create_offer(None).await?;
+    let mut offer_gathering_complete = offerer.gathering_complete_promise().await;
+    offerer.set_local_description(offer).await?;
+    let _ = offer_gathering_complete.recv().await;
+    let offer = offerer.local_description().await.unwrap();
+    answerer
+        .set_remote_description(RTCSessionDescription::offer(strip_fingerprints(
+            &offer.sdp,
+        ))?)
+        .await?;
+
+    let answer = answerer.create_answer(None).await?;
+    let mut answer_gathering_complete = answerer.gathering_complete_promise().await;
+    answerer.set_local_description(answer).await?;
+    let _ = answer_gathering_complete.recv().await;
+    let answer = answerer.local_description().await.unwrap();
+    assert!(!answer.sdp.contains("a=fingerprint"));
+    assert_eq!(
+        answer.sdp.matches("a=crypto:1 AEAD_AES_128_GCM ").count(),
+        1
+    );
+    offerer.set_remote_description(answer).await?;
+
+    send_video_until_done(
+        packet_rx,
+        vec![track],
+        Bytes::from_static(b"\xDE\xAD\xBE\xEF\xAA"),
+        None,
+    )
+    .await;
+
+    let offerer_cryptos = offerer.dtls_transport().sdes_cryptos.lock().await.clone();
+    let answerer_cryptos = answerer.dtls_transport().sdes_cryptos.lock().await.clone();
+    let (offerer_local, offerer_remote) = offerer_cryptos.unwrap();
+    let (answerer_local, answerer_remote) = answerer_cryptos.unwrap();
+    assert_eq!(offerer_local, answerer_remote);
+    assert_eq!(offerer_remote, answerer_local);
+    for pc in [&offerer, &answerer] {
+        assert_eq!(
+            pc.dtls_transport().state(),
+            RTCDtlsTransportState::Connected
+        );
+    }
+
+    close_pair_now(&offerer, &answerer).await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_set_srtcp_encryption_policy() -> Result<()> {
+    let mut s = SettingEngine::default();
+    assert_eq!(s.srtcp_encryption_policy, SrtcpEncryptionPolicy::Enabled);
+
+    s.set_srtcp_encryption_policy(SrtcpEncryptionPolicy::Disabled);
+    assert_eq!(s.srtcp_encryption_policy, SrtcpEncryptionPolicy::Disabled);
+
+    // Unencrypted SRTCP is asked for in SDES offers
+    s.set_srtp_sdes_insecure_signaling(true);
+    let mut m = MediaEngine::default();
+    m.register_default_codecs()?;
+    let api = APIBuilder::new()
+        .with_media_engine(m)
+        .with_setting_engine(s)
+        .build();
+
+    let pc = api.new_peer_connection(Default::default()).await?;
+    pc.add_transceiver_from_kind(RTPCodecType::Video, None)
+        .await?;
+    let offer = pc.create_offer(None).await?;
+    assert!(offer.sdp.contains("a=crypto:1 AEAD_AES_128_GCM inline:"));
+    assert!(offer
+        .sdp
+        .lines()
+        .filter(|line| line.starts_with("a=crypto:"))
+        .all(|line| line.ends_with(" UNENCRYPTED_SRTCP")));
+
+    pc.close().await?;
+
+    Ok(())
+}
+
+#[test]
+fn test_set_srtp_ssrc_state_limits() {
+    let mut s = SettingEngine::default();
+    assert_eq!(s.srtp_ssrc_state_limits, SsrcStateLimits::default());
+
+    let limits = SsrcStateLimits {
+        max_ssrcs: Some(64),
+        idle_timeout: Some(Duration::from_secs(30)),
+    };
+    s.set_srtp_ssrc_state_limits(limits);
+    assert_eq!(s.srtp_ssrc_state_limits, limits);
+}
+
+#[test]
+fn test_set_sctp_stream_scheduler() {
+    let mut s = SettingEngine::default();
+    assert_eq!(s.sctp_stream_scheduler, StreamScheduler::RoundRobin);
+
+    s.set_sctp_stream_scheduler(StreamScheduler::WeightedFair);
+    assert_eq!(s.sctp_stream_scheduler, StreamScheduler::WeightedFair);
+}
+
+#[test]
+fn test_set_sctp_congestion_control() {
+    let mut s = SettingEngine::default();
+    assert_eq!(s.sctp_congestion_control, CongestionControlAlgorithm::Aimd);
+
+    s.set_sctp_congestion_control(CongestionControlAlgorithm::Vegas);
+    assert_eq!(s.sctp_congestion_control, CongestionControlAlgorithm::Vegas);
+}
+
+#[test]
+fn test_set_sctp_sack_delay() {
+    let mut s = SettingEngine::default();
+    assert_eq!(s.sctp_sack_delay, Duration::ZERO);
+    assert_eq!(s.sctp_packets_per_sack, 0);
+    assert!(!s.sctp_immediate_sack);
+
+    s.set_sctp_sack_delay(Duration::from_millis(20), 1);
+    s.set_sctp_immediate_sack(true);
+    assert_eq!(s.sctp_sack_delay, Duration::from_millis(20));
+    assert_eq!(s.sctp_packets_per_sack, 1);
+    assert!(s.sctp_immediate_sack);
+}
+
+#[test]
+fn test_enable_sctp_zero_checksum() {
+    let mut s = SettingEngine::default();
+    assert!(!s.sctp_zero_checksum);
+
+    s.enable_sctp_zero_checksum(true);
+    assert!(s.sctp_zero_checksum);
+}
+
+#[test]
+fn test_set_sctp_heartbeat() {
+    let mut s = SettingEngine::default();
+    assert_eq!(s.sctp_heartbeat_interval, Duration::ZERO);
+    assert_eq!(s.sctp_path_max_retransmits, 0);
+    assert_eq!(s.sctp_association_max_retransmits, 0);
+
+    s.set_sctp_heartbeat(Duration::from_secs(1), 2, 4);
+    assert_eq!(s.sctp_heartbeat_interval, Duration::from_secs(1));
+    assert_eq!(s.sctp_path_max_retransmits, 2);
+    assert_eq!(s.sctp_association_max_retransmits, 4);
+}
+
+#[test]
+fn test_set_sctp_max_send_buffer_size() {
+    let mut s = SettingEngine::default();
+    assert_eq!(s.sctp_max_send_buffer_size, 0);
+
+    s.set_sctp_max_send_buffer_size(1024 * 1024);
+    assert_eq!(s.sctp_max_send_buffer_size, 1024 * 1024);
+}
+
+#[test]
+fn test_set_rtcp_mtu() {
+    let mut s = SettingEngine::default();
+    assert_eq!(s.get_rtcp_mtu(), RTCP_MTU);
+
+    s.set_rtcp_mtu(500);
+    assert_eq!(s.get_rtcp_mtu(), 500);
+}
+
+#[test]
+fn test_set_data_channel_filter() {
+    let mut s = SettingEngine::default();
+    assert!(s.data_channel_filter.is_none());
+
+    s.set_data_channel_filter(|label, protocol| label == "chat" && protocol.is_empty());
+    let filter = s.data_channel_filter.as_ref().unwrap();
+    assert!(filter("chat", ""));
+    assert!(!filter("chat", "v1"));
+    assert!(!filter("file", ""));
+}
+
 /*TODO:#[test] fn test_setting_engine_set_ice_tcp_mux() ->Result<()> {
 
     listener, err := net.ListenTCP("tcp", &net.TCPAddr{})
@@ -25,6 +25,12 @@ pub struct RTCDataChannelInit {
     /// protocol describes the subprotocol name used for this channel.
     pub protocol: Option<String>,
 
+    /// priority sets how the channel shares the SCTP association with other data
+    /// channels when messages are queued on several of them, see the
+    /// `CHANNEL_PRIORITY_*` constants in `data::message::message_channel_open`.
+    /// The default value of None uses normal priority.
+    pub priority: Option<u16>,
+
     /// negotiated describes if the data channel is created by the local peer or
     /// the remote peer. The default value of None tells the user agent to
     /// announce the channel in-band and instruct the other peer to dispatch a
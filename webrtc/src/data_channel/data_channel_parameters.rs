@@ -8,5 +8,6 @@ pub struct DataChannelParameters {
     pub ordered: bool,
     pub max_packet_life_time: u16,
     pub max_retransmits: u16,
+    pub priority: u16,
     pub negotiated: Option<u16>,
 }
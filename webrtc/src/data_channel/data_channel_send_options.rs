@@ -0,0 +1,23 @@
+/// RTCDataChannelSendOptions overrides the reliability and ordering of a data
+/// channel for a single message, so that one channel can mix messages that must
+/// arrive in order with messages that are worthless once late. Messages given
+/// up on are skipped by the remote peer using the FORWARD TSN chunk of PR-SCTP
+/// (RFC 3758).
+///
+/// At most one of max_packet_life_time and max_retransmits may be set. With
+/// neither set the message is sent reliably, whatever the reliability of the
+/// channel.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RTCDataChannelSendOptions {
+    /// max_packet_life_time limits the time (in milliseconds) during which the
+    /// message will be transmitted or retransmitted if not acknowledged.
+    pub max_packet_life_time: Option<u16>,
+
+    /// max_retransmits limits the number of times the message will be
+    /// retransmitted if not successfully delivered.
+    pub max_retransmits: Option<u16>,
+
+    /// unordered delivers the message as soon as it arrives, even on an
+    /// ordered channel, without waiting for or holding back ordered messages.
+    pub unordered: bool,
+}
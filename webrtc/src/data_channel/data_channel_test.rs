@@ -10,6 +10,7 @@ use super::*;
 use crate::api::media_engine::MediaEngine;
 use crate::api::{APIBuilder, API};
 use crate::data_channel::data_channel_init::RTCDataChannelInit;
+use crate::data_channel::data_channel_send_options::RTCDataChannelSendOptions;
 //use log::LevelFilter;
 //use std::io::Write;
 use crate::dtls_transport::dtls_parameters::DTLSParameters;
@@ -323,6 +324,208 @@ async fn test_data_channel_send_after_connected() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_data_channel_sctp_stats() -> Result<()> {
+    let mut m = MediaEngine::default();
+    m.register_default_codecs()?;
+    let api = APIBuilder::new().with_media_engine(m).build();
+
+    let (mut offer_pc, mut answer_pc) = new_pair(&api).await?;
+
+    let (received_tx, mut received_rx) = mpsc::channel::<()>(1);
+    answer_pc.on_data_channel(Box::new(move |d: Arc<RTCDataChannel>| {
+        if d.label() != EXPECTED_LABEL {
+            return Box::pin(async {});
+        }
+        let received_tx = received_tx.clone();
+        Box::pin(async move {
+            d.on_message(Box::new(move |_: DataChannelMessage| {
+                let received_tx = received_tx.clone();
+                Box::pin(async move {
+                    let _ = received_tx.send(()).await;
+                })
+            }));
+        })
+    }));
+
+    let dc = offer_pc.create_data_channel(EXPECTED_LABEL, None).await?;
+    let dc2 = Arc::clone(&dc);
+    dc.on_open(Box::new(move || {
+        Box::pin(async move {
+            let result = dc2.send_text("Ping".to_owned()).await;
+            assert!(result.is_ok(), "Failed to send string on data channel");
+        })
+    }));
+
+    signal_pair(&mut offer_pc, &mut answer_pc).await?;
+    received_rx.recv().await;
+
+    let stats = offer_pc.get_stats().await;
+    match stats.reports.get("sctp_transport") {
+        Some(StatsReportType::SCTPTransport(sctp_transport_stats)) => {
+            assert!(sctp_transport_stats.congestion_window > 0);
+            assert!(sctp_transport_stats.receiver_window > 0);
+            assert!(sctp_transport_stats.mtu > 0);
+            assert!(sctp_transport_stats.bytes_sent > 0);
+            assert!(sctp_transport_stats.retransmission_timeout > 0.0);
+            assert_eq!(sctp_transport_stats.retransmission_timeouts, 0);
+        }
+        Some(_other) => panic!("found the wrong type"),
+        None => panic!("missed it"),
+    }
+    let data_channel_stats = stats
+        .reports
+        .values()
+        .find_map(|v| match v {
+            StatsReportType::DataChannel(d) if d.label == EXPECTED_LABEL => Some(d),
+            _ => None,
+        })
+        .expect("Should have produced a data channel stat");
+    assert_eq!(data_channel_stats.messages_sent, 1);
+    assert_eq!(data_channel_stats.retransmitted_chunks, 0);
+    assert!(data_channel_stats.opened_at.is_some());
+    assert_eq!(data_channel_stats.closed_at, None);
+
+    let json = serde_json::to_value(data_channel_stats).expect("stats should serialize");
+    assert_eq!(json["type"], "data-channel");
+    assert!(json["openedAt"].is_f64());
+    assert!(json["closedAt"].is_null());
+
+    // Closing the channel locally records when it was closed.
+    dc.close().await?;
+    let data_channel_stats = DataChannelStats::from(&dc).await;
+    assert!(data_channel_stats.closed_at.is_some());
+    assert!(data_channel_stats.closed_at >= data_channel_stats.opened_at);
+
+    close_pair_now(&offer_pc, &answer_pc).await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_data_channel_filter() -> Result<()> {
+    let mut m = MediaEngine::default();
+    m.register_default_codecs()?;
+    let mut s = SettingEngine::default();
+    s.set_data_channel_filter(|label, _protocol| label != "rejected");
+    let api = APIBuilder::new()
+        .with_media_engine(m)
+        .with_setting_engine(s)
+        .build();
+
+    let (mut offer_pc, mut answer_pc) = new_pair(&api).await?;
+
+    let (label_tx, mut label_rx) = mpsc::channel::<String>(3);
+    answer_pc.on_data_channel(Box::new(move |d: Arc<RTCDataChannel>| {
+        let label_tx = label_tx.clone();
+        Box::pin(async move {
+            let _ = label_tx.send(d.label().to_owned()).await;
+        })
+    }));
+
+    let rejected = offer_pc.create_data_channel("rejected", None).await?;
+    let (closed_tx, mut closed_rx) = mpsc::channel::<()>(1);
+    rejected.on_close(Box::new(move || {
+        let closed_tx = closed_tx.clone();
+        Box::pin(async move {
+            let _ = closed_tx.send(()).await;
+        })
+    }));
+    offer_pc.create_data_channel(EXPECTED_LABEL, None).await?;
+
+    signal_pair(&mut offer_pc, &mut answer_pc).await?;
+
+    // signal_pair opens a data channel of its own.
+    loop {
+        let label = label_rx
+            .recv()
+            .await
+            .expect("data channel should be announced");
+        assert_ne!(
+            label, "rejected",
+            "rejected data channel should not be announced"
+        );
+        if label == EXPECTED_LABEL {
+            break;
+        }
+    }
+    tokio::time::timeout(Duration::from_secs(5), closed_rx.recv())
+        .await
+        .expect("rejected data channel should be closed");
+
+    close_pair_now(&offer_pc, &answer_pc).await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_data_channel_send_with_options() -> Result<()> {
+    let mut m = MediaEngine::default();
+    m.register_default_codecs()?;
+    let api = APIBuilder::new().with_media_engine(m).build();
+
+    let (mut offer_pc, mut answer_pc) = new_pair(&api).await?;
+
+    let (received_tx, mut received_rx) = mpsc::channel::<String>(1);
+    answer_pc.on_data_channel(Box::new(move |d: Arc<RTCDataChannel>| {
+        if d.label() != EXPECTED_LABEL {
+            return Box::pin(async {});
+        }
+        let received_tx = received_tx.clone();
+        Box::pin(async move {
+            d.on_message(Box::new(move |msg: DataChannelMessage| {
+                let received_tx = received_tx.clone();
+                Box::pin(async move {
+                    let text = String::from_utf8(msg.data.to_vec()).unwrap();
+                    let _ = received_tx.send(text).await;
+                })
+            }));
+        })
+    }));
+
+    let dc = offer_pc.create_data_channel(EXPECTED_LABEL, None).await?;
+
+    let result = dc
+        .send_text_with_options(
+            "Ping",
+            RTCDataChannelSendOptions {
+                max_packet_life_time: Some(100),
+                max_retransmits: Some(1),
+                ..Default::default()
+            },
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err(),
+        Error::ErrRetransmitsOrPacketLifeTime,
+        "both options should not be allowed"
+    );
+
+    let dc2 = Arc::clone(&dc);
+    dc.on_open(Box::new(move || {
+        Box::pin(async move {
+            let result = dc2
+                .send_text_with_options(
+                    "Ping",
+                    RTCDataChannelSendOptions {
+                        max_retransmits: Some(3),
+                        unordered: true,
+                        ..Default::default()
+                    },
+                )
+                .await;
+            assert!(result.is_ok(), "Failed to send string on data channel");
+        })
+    }));
+
+    signal_pair(&mut offer_pc, &mut answer_pc).await?;
+    assert_eq!(received_rx.recv().await.as_deref(), Some("Ping"));
+
+    close_pair_now(&offer_pc, &answer_pc).await;
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_data_channel_close() -> Result<()> {
     let mut m = MediaEngine::default();
@@ -1103,6 +1306,12 @@ async fn test_eof_detach() -> Result<()> {
 
     wg.wait().await;
 
+    // A detached channel has no read_loop, its times are recorded all the same.
+    attached.close().await?;
+    let data_channel_stats = DataChannelStats::from(&attached).await;
+    assert!(data_channel_stats.opened_at.is_some());
+    assert!(data_channel_stats.closed_at.is_some());
+
     close_pair_now(&pca, &pcb).await;
 
     Ok(())
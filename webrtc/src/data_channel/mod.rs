@@ -4,6 +4,7 @@ mod data_channel_test;
 pub mod data_channel_init;
 pub mod data_channel_message;
 pub mod data_channel_parameters;
+pub mod data_channel_send_options;
 pub mod data_channel_state;
 
 use std::future::Future;
@@ -17,10 +18,12 @@ use bytes::Bytes;
 use data::message::message_channel_open::ChannelType;
 use data_channel_message::*;
 use data_channel_parameters::*;
+use data_channel_send_options::RTCDataChannelSendOptions;
 use data_channel_state::RTCDataChannelState;
 use portable_atomic::{AtomicBool, AtomicU16, AtomicU8, AtomicUsize};
-use sctp::stream::OnBufferedAmountLowFn;
+use sctp::stream::{OnBufferedAmountLowFn, ReliabilityType, WriteOptions};
 use tokio::sync::{Mutex, Notify};
+use tokio::time::Instant;
 use util::sync::Mutex as SyncMutex;
 
 use crate::api::setting_engine::SettingEngine;
@@ -62,12 +65,15 @@ pub struct RTCDataChannel {
     pub(crate) ordered: bool,
     pub(crate) max_packet_lifetime: u16,
     pub(crate) max_retransmits: u16,
+    pub(crate) priority: u16,
     pub(crate) protocol: String,
     pub(crate) negotiated: bool,
     pub(crate) id: AtomicU16,
     pub(crate) ready_state: Arc<AtomicU8>, // DataChannelState
     pub(crate) buffered_amount_low_threshold: AtomicUsize,
     pub(crate) detach_called: Arc<AtomicBool>,
+    pub(crate) opened_at: SyncMutex<Option<Instant>>,
+    pub(crate) closed_at: Arc<SyncMutex<Option<Instant>>>,
 
     // The binaryType represents attribute MUST, on getting, return the value to
     // which it was last set. On setting, if the new value is either the string
@@ -112,6 +118,7 @@ impl RTCDataChannel {
             ordered: params.ordered,
             max_packet_lifetime: params.max_packet_life_time,
             max_retransmits: params.max_retransmits,
+            priority: params.priority,
             ready_state: Arc::new(AtomicU8::new(RTCDataChannelState::Connecting as u8)),
             detach_called: Arc::new(AtomicBool::new(false)),
 
@@ -162,7 +169,7 @@ impl RTCDataChannel {
 
             let cfg = data::data_channel::Config {
                 channel_type,
-                priority: data::message::message_channel_open::CHANNEL_PRIORITY_NORMAL,
+                priority: self.priority,
                 reliability_parameter,
                 label: self.label.clone(),
                 protocol: self.protocol.clone(),
@@ -180,6 +187,21 @@ impl RTCDataChannel {
                 );
             }
 
+            // A data channel sends and receives on the same stream, so streams
+            // beyond the ones negotiated during the handshake are asked for
+            // in both directions.
+            let wanted = self.id().saturating_add(1);
+            let outbound = association.num_outbound_streams().await;
+            let inbound = association.num_inbound_streams().await;
+            if wanted > outbound || wanted > inbound {
+                association
+                    .add_streams(
+                        wanted.saturating_sub(outbound),
+                        wanted.saturating_sub(inbound),
+                    )
+                    .await?;
+            }
+
             let dc = data::data_channel::DataChannel::dial(&association, self.id(), cfg).await?;
 
             // buffered_amount_low_threshold and on_buffered_amount_low might be set earlier
@@ -270,11 +292,13 @@ impl RTCDataChannel {
             *data_channel = Some(Arc::clone(&dc));
         }
         self.set_ready_state(RTCDataChannelState::Open);
+        *self.opened_at.lock() = Some(Instant::now());
 
         self.do_open();
 
         if !self.setting_engine.detach.data_channels {
             let ready_state = Arc::clone(&self.ready_state);
+            let closed_at = Arc::clone(&self.closed_at);
             let on_message_handler = Arc::clone(&self.on_message_handler);
             let on_close_handler = Arc::clone(&self.on_close_handler);
             let on_error_handler = Arc::clone(&self.on_error_handler);
@@ -284,6 +308,7 @@ impl RTCDataChannel {
                     notify_rx,
                     dc,
                     ready_state,
+                    closed_at,
                     on_message_handler,
                     on_close_handler,
                     on_error_handler,
@@ -303,6 +328,7 @@ impl RTCDataChannel {
         notify_rx: Arc<Notify>,
         data_channel: Arc<data::data_channel::DataChannel>,
         ready_state: Arc<AtomicU8>,
+        closed_at: Arc<SyncMutex<Option<Instant>>>,
         on_message_handler: Arc<ArcSwapOption<Mutex<OnMessageHdlrFn>>>,
         on_close_handler: Arc<ArcSwapOption<Mutex<OnCloseHdlrFn>>>,
         on_error_handler: Arc<ArcSwapOption<Mutex<OnErrorHdlrFn>>>,
@@ -318,6 +344,7 @@ impl RTCDataChannel {
                         Ok((0, _)) =>
                         {
                             ready_state.store(RTCDataChannelState::Closed as u8, Ordering::SeqCst);
+                            *closed_at.lock() = Some(Instant::now());
 
                             let on_close_handler2 = Arc::clone(&on_close_handler);
                             tokio::spawn(async move {
@@ -332,6 +359,7 @@ impl RTCDataChannel {
                         Ok((n, is_string)) => (n, is_string),
                         Err(err) => {
                             ready_state.store(RTCDataChannelState::Closed as u8, Ordering::SeqCst);
+                            *closed_at.lock() = Some(Instant::now());
 
                             let on_error_handler2 = Arc::clone(&on_error_handler);
                             tokio::spawn(async move {
@@ -390,6 +418,60 @@ impl RTCDataChannel {
         }
     }
 
+    /// send_with_options sends the binary message to the DataChannel peer,
+    /// retransmitting and ordering it as set by options instead of by the
+    /// channel's parameters
+    pub async fn send_with_options(
+        &self,
+        data: &Bytes,
+        options: RTCDataChannelSendOptions,
+    ) -> Result<usize> {
+        self.send_message(data, false, options).await
+    }
+
+    /// send_text_with_options sends the text message to the DataChannel peer,
+    /// retransmitting and ordering it as set by options instead of by the
+    /// channel's parameters
+    pub async fn send_text_with_options(
+        &self,
+        s: impl Into<String>,
+        options: RTCDataChannelSendOptions,
+    ) -> Result<usize> {
+        self.send_message(&Bytes::from(s.into()), true, options)
+            .await
+    }
+
+    async fn send_message(
+        &self,
+        data: &Bytes,
+        is_string: bool,
+        options: RTCDataChannelSendOptions,
+    ) -> Result<usize> {
+        let reliability = match (options.max_packet_life_time, options.max_retransmits) {
+            (Some(_), Some(_)) => return Err(Error::ErrRetransmitsOrPacketLifeTime),
+            (Some(max_packet_life_time), None) => {
+                (ReliabilityType::Timed, max_packet_life_time as u32)
+            }
+            (None, Some(max_retransmits)) => (ReliabilityType::Rexmit, max_retransmits as u32),
+            (None, None) => (ReliabilityType::Reliable, 0),
+        };
+        let options = WriteOptions {
+            unordered: options.unordered,
+            reliability: Some(reliability),
+        };
+
+        self.ensure_open()?;
+
+        let data_channel = self.data_channel.lock().await;
+        if let Some(dc) = &*data_channel {
+            Ok(dc
+                .write_data_channel_with_options(data, is_string, options)
+                .await?)
+        } else {
+            Err(Error::ErrClosedPipe)
+        }
+    }
+
     fn ensure_open(&self) -> Result<()> {
         if self.ready_state() != RTCDataChannelState::Open {
             Err(Error::ErrClosedPipe)
@@ -432,11 +514,17 @@ impl RTCDataChannel {
         self.notify_tx.notify_waiters();
 
         let data_channel = self.data_channel.lock().await;
-        if let Some(dc) = &*data_channel {
-            Ok(dc.close().await?)
+        let result = if let Some(dc) = &*data_channel {
+            dc.close().await
         } else {
             Ok(())
-        }
+        };
+
+        // The read_loop is stopped above and a detached channel has none, so
+        // the close time is recorded here.
+        self.closed_at.lock().get_or_insert_with(Instant::now);
+
+        Ok(result?)
     }
 
     /// label represents a label that can be used to distinguish this
@@ -464,6 +552,12 @@ impl RTCDataChannel {
         self.max_retransmits
     }
 
+    /// priority represents the priority of this DataChannel when sharing the SCTP
+    /// association with other data channels.
+    pub fn priority(&self) -> u16 {
+        self.priority
+    }
+
     /// protocol represents the name of the sub-protocol used with this
     /// DataChannel.
     pub fn protocol(&self) -> &str {
@@ -14,7 +14,10 @@ use interceptor::stream_info::StreamInfo;
 use interceptor::{Interceptor, RTCPReader, RTPReader};
 use portable_atomic::{AtomicBool, AtomicU8};
 use sha2::{Digest, Sha256};
+use srtp::context::SrtcpEncryptionPolicy;
+use srtp::cryptex::CryptexPolicy;
 use srtp::protection_profile::ProtectionProfile;
+use srtp::sdes::{CryptoAttribute, SESSION_PARAM_UNENCRYPTED_SRTCP};
 use srtp::session::Session;
 use srtp::stream::Stream;
 use tokio::sync::{mpsc, Mutex};
@@ -50,6 +53,20 @@ pub(crate) fn default_srtp_protection_profiles() -> Vec<SrtpProtectionProfile> {
     ]
 }
 
+fn protection_profile(profile: SrtpProtectionProfile) -> Option<ProtectionProfile> {
+    match profile {
+        SrtpProtectionProfile::Srtp_Aead_Aes_128_Gcm => Some(ProtectionProfile::AeadAes128Gcm),
+        SrtpProtectionProfile::Srtp_Aead_Aes_256_Gcm => Some(ProtectionProfile::AeadAes256Gcm),
+        SrtpProtectionProfile::Srtp_Aes128_Cm_Hmac_Sha1_80 => {
+            Some(ProtectionProfile::Aes128CmHmacSha1_80)
+        }
+        SrtpProtectionProfile::Srtp_Aes128_Cm_Hmac_Sha1_32 => {
+            Some(ProtectionProfile::Aes128CmHmacSha1_32)
+        }
+        _ => None,
+    }
+}
+
 pub type OnDTLSTransportStateChangeHdlrFn = Box<
     dyn (FnMut(RTCDtlsTransportState) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>)
         + Send
@@ -70,6 +87,11 @@ pub struct RTCDtlsTransport {
     pub(crate) remote_certificate: Mutex<Bytes>,
     pub(crate) state: AtomicU8, //DTLSTransportState,
     pub(crate) srtp_protection_profile: Mutex<ProtectionProfile>,
+    pub(crate) remote_cryptex: AtomicBool,
+    /// SDES crypto attributes put in the local offer, and the (local, remote) pair once
+    /// SDES has been negotiated instead of DTLS.
+    pub(crate) sdes_offered_cryptos: Mutex<Vec<CryptoAttribute>>,
+    pub(crate) sdes_cryptos: Mutex<Option<(CryptoAttribute, CryptoAttribute)>>,
     pub(crate) on_state_change_handler: ArcSwapOption<Mutex<OnDTLSTransportStateChangeHdlrFn>>,
     pub(crate) conn: Mutex<Option<Arc<DTLSConn>>>,
 
@@ -140,15 +162,19 @@ impl RTCDtlsTransport {
     }
 
     /// write_rtcp sends a user provided RTCP packet to the connected peer. If no peer is connected the
-    /// packet is discarded.
+    /// packet is discarded. Batches larger than the RTCP MTU of the SettingEngine are split
+    /// across several datagrams.
     pub async fn write_rtcp(
         &self,
         pkts: &[Box<dyn rtcp::packet::Packet + Send + Sync>],
     ) -> Result<usize> {
         let srtcp_session = self.srtcp_session.lock().await;
         if let Some(srtcp_session) = &*srtcp_session {
-            let raw = rtcp::packet::marshal(pkts)?;
-            Ok(srtcp_session.write(&raw, false).await?)
+            let mut n = 0;
+            for raw in rtcp::packet::marshal_datagrams(pkts, self.setting_engine.get_rtcp_mtu())? {
+                n += srtcp_session.write(&raw, false).await?;
+            }
+            Ok(n)
         } else {
             Ok(0)
         }
@@ -181,8 +207,18 @@ impl RTCDtlsTransport {
             *srtp_protection_profile
         };
 
+        let cryptex_policy = match self.setting_engine.srtp_cryptex_policy {
+            CryptexPolicy::Required if !self.remote_cryptex.load(Ordering::SeqCst) => {
+                return Err(Error::ErrCryptexNotNegotiated);
+            }
+            policy if self.remote_cryptex.load(Ordering::SeqCst) => policy,
+            _ => CryptexPolicy::Disabled,
+        };
+
         let mut srtp_config = srtp::config::Config {
             profile,
+            cryptex_policy,
+            remote_ssrc_state_limits: self.setting_engine.srtp_ssrc_state_limits,
             ..Default::default()
         };
 
@@ -194,14 +230,7 @@ impl RTCDtlsTransport {
             srtp_config.remote_rtp_options = Some(srtp::option::srtp_no_replay_protection());
         }
 
-        if let Some(conn) = self.conn().await {
-            let conn_state = conn.connection_state().await;
-            srtp_config
-                .extract_session_keys_from_dtls(conn_state, self.role().await == DTLSRole::Client)
-                .await?;
-        } else {
-            return Err(Error::ErrDtlsTransportNotStarted);
-        }
+        self.extract_session_keys(&mut srtp_config).await?;
 
         {
             let mut srtp_session = self.srtp_session.lock().await;
@@ -224,6 +253,8 @@ impl RTCDtlsTransport {
 
         let mut srtcp_config = srtp::config::Config {
             profile,
+            srtcp_encryption_policy: self.setting_engine.srtcp_encryption_policy,
+            remote_ssrc_state_limits: self.setting_engine.srtp_ssrc_state_limits,
             ..Default::default()
         };
         if self.setting_engine.replay_protection.srtcp != 0 {
@@ -234,14 +265,7 @@ impl RTCDtlsTransport {
             srtcp_config.remote_rtcp_options = Some(srtp::option::srtcp_no_replay_protection());
         }
 
-        if let Some(conn) = self.conn().await {
-            let conn_state = conn.connection_state().await;
-            srtcp_config
-                .extract_session_keys_from_dtls(conn_state, self.role().await == DTLSRole::Client)
-                .await?;
-        } else {
-            return Err(Error::ErrDtlsTransportNotStarted);
-        }
+        self.extract_session_keys(&mut srtcp_config).await?;
 
         {
             let mut srtcp_session = self.srtcp_session.lock().await;
@@ -273,6 +297,21 @@ impl RTCDtlsTransport {
         Ok(())
     }
 
+    async fn extract_session_keys(&self, config: &mut srtp::config::Config) -> Result<()> {
+        if let Some((local, remote)) = &*self.sdes_cryptos.lock().await {
+            config.extract_session_keys_from_sdes(local, remote)?;
+        } else if let Some(conn) = self.conn().await {
+            let conn_state = conn.connection_state().await;
+            config
+                .extract_session_keys_from_dtls(conn_state, self.role().await == DTLSRole::Client)
+                .await?;
+        } else {
+            return Err(Error::ErrDtlsTransportNotStarted);
+        }
+
+        Ok(())
+    }
+
     pub(crate) async fn get_srtp_session(&self) -> Option<Arc<Session>> {
         let srtp_session = self.srtp_session.lock().await;
         srtp_session.clone()
@@ -315,10 +354,15 @@ impl RTCDtlsTransport {
         }
     }
 
-    async fn prepare_transport(
-        &self,
-        remote_parameters: DTLSParameters,
-    ) -> Result<(DTLSRole, dtls::config::Config)> {
+    fn srtp_protection_profiles(&self) -> Vec<SrtpProtectionProfile> {
+        if !self.setting_engine.srtp_protection_profiles.is_empty() {
+            self.setting_engine.srtp_protection_profiles.clone()
+        } else {
+            default_srtp_protection_profiles()
+        }
+    }
+
+    async fn prepare_srtp_endpoints(&self) -> Result<()> {
         self.ensure_ice_conn()?;
 
         if self.state() != RTCDtlsTransportState::New {
@@ -333,6 +377,15 @@ impl RTCDtlsTransport {
             let mut srtcp_endpoint = self.srtcp_endpoint.lock().await;
             *srtcp_endpoint = self.ice_transport.new_endpoint(Box::new(match_srtcp)).await;
         }
+
+        Ok(())
+    }
+
+    async fn prepare_transport(
+        &self,
+        remote_parameters: DTLSParameters,
+    ) -> Result<(DTLSRole, dtls::config::Config)> {
+        self.prepare_srtp_endpoints().await?;
         {
             let mut rp = self.remote_parameters.lock().await;
             *rp = remote_parameters;
@@ -345,20 +398,20 @@ impl RTCDtlsTransport {
         };
         self.state_change(RTCDtlsTransportState::Connecting).await;
 
+        let client_auth = &self.setting_engine.dtls_client_auth;
         Ok((
             self.role().await,
             dtls::config::Config {
                 certificates: vec![certificate],
-                srtp_protection_profiles: if !self
-                    .setting_engine
-                    .srtp_protection_profiles
-                    .is_empty()
-                {
-                    self.setting_engine.srtp_protection_profiles.clone()
-                } else {
-                    default_srtp_protection_profiles()
-                },
-                client_auth: ClientAuthType::RequireAnyClientCert,
+                srtp_protection_profiles: self.srtp_protection_profiles(),
+                client_auth: client_auth
+                    .client_auth
+                    .unwrap_or(ClientAuthType::RequireAnyClientCert),
+                client_cas: client_auth
+                    .client_cas
+                    .clone()
+                    .unwrap_or_else(rustls::RootCertStore::empty),
+                verify_peer_certificate: client_auth.verify_peer_certificate.clone(),
                 insecure_skip_verify: true,
                 insecure_verification: self.setting_engine.allow_insecure_verification_algorithm,
                 ..Default::default()
@@ -412,20 +465,9 @@ impl RTCDtlsTransport {
         let srtp_profile = dtls_conn.selected_srtpprotection_profile();
         {
             let mut srtp_protection_profile = self.srtp_protection_profile.lock().await;
-            *srtp_protection_profile = match srtp_profile {
-                dtls::extension::extension_use_srtp::SrtpProtectionProfile::Srtp_Aead_Aes_128_Gcm => {
-                    srtp::protection_profile::ProtectionProfile::AeadAes128Gcm
-                }
-                dtls::extension::extension_use_srtp::SrtpProtectionProfile::Srtp_Aead_Aes_256_Gcm => {
-                    srtp::protection_profile::ProtectionProfile::AeadAes256Gcm
-                }
-                dtls::extension::extension_use_srtp::SrtpProtectionProfile::Srtp_Aes128_Cm_Hmac_Sha1_80 => {
-                    srtp::protection_profile::ProtectionProfile::Aes128CmHmacSha1_80
-                }
-                dtls::extension::extension_use_srtp::SrtpProtectionProfile::Srtp_Aes128_Cm_Hmac_Sha1_32 => {
-                    srtp::protection_profile::ProtectionProfile::Aes128CmHmacSha1_32
-                }
-                _ => {
+            *srtp_protection_profile = match protection_profile(srtp_profile) {
+                Some(profile) => profile,
+                None => {
                     if let Err(err) = dtls_conn.close().await {
                         log::error!("{}", err);
                     }
@@ -439,6 +481,25 @@ impl RTCDtlsTransport {
         // Check the fingerprint if a certificate was exchanged
         let remote_certs = &dtls_conn.connection_state().await.peer_certificates;
         if remote_certs.is_empty() {
+            if self.role().await == DTLSRole::Server
+                && self
+                    .setting_engine
+                    .disable_certificate_fingerprint_verification
+                && !self
+                    .setting_engine
+                    .dtls_client_auth
+                    .requires_client_certificate()
+            {
+                // The client auth policy allows the client to omit its certificate
+                {
+                    let mut conn = self.conn.lock().await;
+                    *conn = Some(Arc::new(dtls_conn));
+                }
+                self.state_change(RTCDtlsTransportState::Connected).await;
+
+                return self.start_srtp().await;
+            }
+
             if let Err(err) = dtls_conn.close().await {
                 log::error!("{}", err);
             }
@@ -475,6 +536,113 @@ impl RTCDtlsTransport {
         self.start_srtp().await
     }
 
+    /// sdes_local_cryptos returns the SDES crypto attributes of a local description, or
+    /// nothing unless SDES is enabled by the SettingEngine. Once SDES is negotiated only
+    /// the attribute in use is returned. Otherwise offers carry one attribute per
+    /// supported protection profile, generated once, and answers none.
+    pub(crate) async fn sdes_local_cryptos(&self, offer: bool) -> Vec<CryptoAttribute> {
+        if !self.setting_engine.srtp_sdes_insecure_signaling {
+            return vec![];
+        }
+
+        if let Some((local, _)) = &*self.sdes_cryptos.lock().await {
+            return vec![local.clone()];
+        }
+        if !offer {
+            return vec![];
+        }
+
+        let mut offered = self.sdes_offered_cryptos.lock().await;
+        if offered.is_empty() {
+            *offered = self
+                .srtp_protection_profiles()
+                .into_iter()
+                .filter_map(protection_profile)
+                .zip(1..)
+                .map(|(profile, tag)| self.sdes_crypto(tag, profile))
+                .collect();
+        }
+        offered.clone()
+    }
+
+    /// sdes_crypto generates an SDES crypto attribute to offer, asking for unencrypted
+    /// SRTCP when disabled by the SettingEngine.
+    fn sdes_crypto(&self, tag: u32, profile: ProtectionProfile) -> CryptoAttribute {
+        let mut crypto = CryptoAttribute::generate(tag, profile);
+        if self.setting_engine.srtcp_encryption_policy == SrtcpEncryptionPolicy::Disabled {
+            crypto
+                .session_params
+                .push(SESSION_PARAM_UNENCRYPTED_SRTCP.to_owned());
+        }
+        crypto
+    }
+
+    /// negotiate_sdes picks the SDES crypto attributes used instead of DTLS, from the
+    /// attributes of a remote description carrying no fingerprint. An answer must match
+    /// one of the attributes offered, and an offer is answered with a fresh key for the
+    /// first attribute using a supported protection profile.
+    pub(crate) async fn negotiate_sdes(
+        &self,
+        remote_cryptos: Vec<CryptoAttribute>,
+        we_offer: bool,
+    ) -> Result<()> {
+        let negotiated = if we_offer {
+            let offered = self.sdes_offered_cryptos.lock().await;
+            remote_cryptos.into_iter().find_map(|remote| {
+                offered
+                    .iter()
+                    .find(|local| local.tag == remote.tag && local.profile == remote.profile)
+                    .map(|local| (local.clone(), remote))
+            })
+        } else {
+            let supported: Vec<ProtectionProfile> = self
+                .srtp_protection_profiles()
+                .into_iter()
+                .filter_map(protection_profile)
+                .collect();
+            remote_cryptos
+                .into_iter()
+                .find(|remote| supported.contains(&remote.profile))
+                .map(|remote| {
+                    let mut local = CryptoAttribute::generate(remote.tag, remote.profile);
+                    if remote.has_session_param(SESSION_PARAM_UNENCRYPTED_SRTCP)
+                        && self.setting_engine.srtcp_encryption_policy
+                            != SrtcpEncryptionPolicy::Required
+                    {
+                        local
+                            .session_params
+                            .push(SESSION_PARAM_UNENCRYPTED_SRTCP.to_owned());
+                    }
+                    (local, remote)
+                })
+        };
+
+        let mut sdes_cryptos = self.sdes_cryptos.lock().await;
+        *sdes_cryptos = Some(negotiated.ok_or(Error::ErrSdesNoMatchingCrypto)?);
+        Ok(())
+    }
+
+    /// sdes_negotiated returns true if SRTP is keyed with SDES instead of DTLS.
+    pub(crate) async fn sdes_negotiated(&self) -> bool {
+        self.sdes_cryptos.lock().await.is_some()
+    }
+
+    /// start_sdes starts SRTP with the keys negotiated with SDES, without a DTLS handshake.
+    pub(crate) async fn start_sdes(&self) -> Result<()> {
+        let profile = match &*self.sdes_cryptos.lock().await {
+            Some((local, _)) => local.profile,
+            None => return Err(Error::ErrSdesNoMatchingCrypto),
+        };
+        self.prepare_srtp_endpoints().await?;
+        {
+            let mut srtp_protection_profile = self.srtp_protection_profile.lock().await;
+            *srtp_protection_profile = profile;
+        }
+        self.state_change(RTCDtlsTransportState::Connected).await;
+
+        self.start_srtp().await
+    }
+
     /// stops and closes the DTLSTransport object.
     pub async fn stop(&self) -> Result<()> {
         // Try closing everything and collect the errors
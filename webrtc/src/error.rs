@@ -239,6 +239,10 @@ pub enum Error {
     ErrDetachBeforeOpened,
     #[error("the DTLS transport has not started yet")]
     ErrDtlsTransportNotStarted,
+    #[error("cryptex is required but was not negotiated")]
+    ErrCryptexNotNegotiated,
+    #[error("no SDES crypto attribute could be negotiated")]
+    ErrSdesNoMatchingCrypto,
     #[error("failed extracting keys from DTLS for SRTP")]
     ErrDtlsKeyExtractionFailed,
     #[error("failed to start SRTP")]
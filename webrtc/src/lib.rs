@@ -39,6 +39,10 @@ pub(crate) const UNSPECIFIED_STR: &str = "Unspecified";
 /// Equal to UDP MTU
 pub(crate) const RECEIVE_MTU: usize = 1460;
 
+/// Size RTCP datagrams are kept under by default, before the SRTCP overhead, which leaves room
+/// for tunnels and path MTUs below the Ethernet one
+pub(crate) const RTCP_MTU: usize = 1200;
+
 pub(crate) const SDP_ATTRIBUTE_RID: &str = "rid";
 pub(crate) const SDP_ATTRIBUTE_SIMULCAST: &str = "simulcast";
 pub(crate) const GENERATED_CERTIFICATE_ORIGIN: &str = "WebRTC";
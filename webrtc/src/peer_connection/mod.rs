@@ -27,11 +27,14 @@ use ::sdp::description::session::*;
 use ::sdp::util::ConnectionRole;
 use arc_swap::ArcSwapOption;
 use async_trait::async_trait;
+use data::message::message_channel_open::CHANNEL_PRIORITY_NORMAL;
+use interceptor::application_defined::ApplicationDefinedInterceptor;
 use interceptor::{stats, Attributes, Interceptor, RTCPWriter};
 use peer_connection_internal::*;
 use portable_atomic::{AtomicBool, AtomicU64, AtomicU8};
 use rand::{thread_rng, Rng};
 use rcgen::KeyPair;
+use rtcp::application_defined::ApplicationDefined;
 use smol_str::SmolStr;
 use srtp::stream::Stream;
 use tokio::sync::{mpsc, Mutex};
@@ -136,6 +139,12 @@ pub type OnPeerConnectionStateChangeHdlrFn = Box<
         + Sync,
 >;
 
+pub type OnApplicationDefinedHdlrFn = Box<
+    dyn (FnMut(ApplicationDefined) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>)
+        + Send
+        + Sync,
+>;
+
 pub type OnDataChannelHdlrFn = Box<
     dyn (FnMut(Arc<RTCDataChannel>) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>)
         + Send
@@ -196,6 +205,7 @@ pub struct RTCPeerConnection {
     interceptor_rtcp_writer: Arc<dyn RTCPWriter + Send + Sync>,
 
     interceptor: Arc<dyn Interceptor + Send + Sync>,
+    application_defined_interceptor: Arc<ApplicationDefinedInterceptor>,
 
     pub(crate) internal: Arc<PeerConnectionInternal>,
 }
@@ -227,10 +237,15 @@ impl RTCPeerConnection {
     pub(crate) async fn new(api: &API, mut configuration: RTCConfiguration) -> Result<Self> {
         RTCPeerConnection::init_configuration(&mut configuration)?;
 
+        let application_defined_interceptor = Arc::new(ApplicationDefinedInterceptor::new());
         let (interceptor, stats_interceptor): (Arc<dyn Interceptor + Send + Sync>, _) = {
             let mut chain = api.interceptor_registry.build_chain("")?;
-            let stats_interceptor = stats::make_stats_interceptor("");
+            let stats_interceptor = match &api.setting_engine.stats_metrics_sink {
+                Some(sink) => stats::make_stats_interceptor_with_metrics_sink("", Arc::clone(sink)),
+                None => stats::make_stats_interceptor(""),
+            };
             chain.add(stats_interceptor.clone());
+            chain.add(application_defined_interceptor.clone());
 
             (Arc::new(chain), stats_interceptor)
         };
@@ -254,6 +269,7 @@ impl RTCPeerConnection {
                     .as_nanos()
             ),
             interceptor,
+            application_defined_interceptor,
             interceptor_rtcp_writer,
             internal,
             configuration: Mutex::new(configuration),
@@ -589,6 +605,17 @@ impl RTCPeerConnection {
             .store(Some(Arc::new(Mutex::new(f))));
     }
 
+    /// on_application_defined sets an event handler which is called with the RTCP APP packets
+    /// named name received, as the RTCP packets of the senders and receivers are read. It
+    /// replaces the handler previously set for name, if any. APP packets are sent with
+    /// write_rtcp.
+    pub fn on_application_defined(&self, name: &str, f: OnApplicationDefinedHdlrFn) -> Result<()> {
+        let app = ApplicationDefined::new(0, name, 0, bytes::Bytes::new())?;
+        self.application_defined_interceptor
+            .on_application_defined(app.name, f);
+        Ok(())
+    }
+
     fn do_track(
         on_track_handler: Arc<ArcSwapOption<Mutex<OnTrackHdlrFn>>>,
         track: Arc<TrackRemote>,
@@ -1553,7 +1580,27 @@ impl RTCPeerConnection {
 
             let remote_is_lite = Self::is_lite_set(parsed);
 
-            let (fingerprint, fingerprint_hash) = extract_fingerprint(parsed)?;
+            // Legacy endpoints without DTLS-SRTP exchange the SRTP keys in the SDP instead
+            let remote_cryptos = if self.internal.setting_engine.srtp_sdes_insecure_signaling
+                && !has_fingerprint(parsed)
+            {
+                extract_cryptos(parsed)
+            } else {
+                vec![]
+            };
+            let (fingerprint, fingerprint_hash) = if remote_cryptos.is_empty() {
+                extract_fingerprint(parsed)?
+            } else {
+                self.internal
+                    .dtls_transport
+                    .negotiate_sdes(remote_cryptos, we_offer)
+                    .await?;
+                (String::new(), String::new())
+            };
+            self.internal
+                .dtls_transport
+                .remote_cryptex
+                .store(has_cryptex(parsed), Ordering::SeqCst);
 
             // If one of the agents is lite and the other one is not, the lite agent must be the controlling agent.
             // If both or neither agents are lite the offering agent is controlling.
@@ -1836,6 +1883,7 @@ impl RTCPeerConnection {
         let mut params = DataChannelParameters {
             label: label.to_owned(),
             ordered: true,
+            priority: CHANNEL_PRIORITY_NORMAL,
             ..Default::default()
         };
 
@@ -1858,6 +1906,10 @@ impl RTCPeerConnection {
                 params.max_retransmits = max_retransmits;
             }
 
+            if let Some(priority) = options.priority {
+                params.priority = priority;
+            }
+
             // https://w3c.github.io/webrtc-pc/#peer-to-peer-data-api (Step #10)
             if let Some(protocol) = options.protocol {
                 params.protocol = protocol;
@@ -4,6 +4,7 @@ use std::sync::Weak;
 use arc_swap::ArcSwapOption;
 use portable_atomic::AtomicIsize;
 use smol_str::SmolStr;
+use srtp::cryptex::CryptexPolicy;
 use tokio::time::Instant;
 use util::Unmarshal;
 
@@ -693,17 +694,21 @@ impl PeerConnectionInternal {
             return;
         }
 
-        // Start the dtls_transport transport
-        let result = self
-            .dtls_transport
-            .start(DTLSParameters {
-                role: dtls_role,
-                fingerprints: vec![RTCDtlsFingerprint {
-                    algorithm: fingerprint_hash,
-                    value: fingerprint,
-                }],
-            })
-            .await;
+        // Start the dtls_transport transport, or SRTP straight away if the keys were
+        // exchanged with SDES
+        let result = if self.dtls_transport.sdes_negotiated().await {
+            self.dtls_transport.start_sdes().await
+        } else {
+            self.dtls_transport
+                .start(DTLSParameters {
+                    role: dtls_role,
+                    fingerprints: vec![RTCDtlsFingerprint {
+                        algorithm: fingerprint_hash,
+                        value: fingerprint,
+                    }],
+                })
+                .await
+        };
         RTCPeerConnection::update_connection_state(
             &self.on_peer_connection_state_change_handler,
             &self.is_closed,
@@ -772,6 +777,8 @@ impl PeerConnectionInternal {
             media_description_fingerprint: self.setting_engine.sdp_media_level_fingerprints,
             is_icelite: self.setting_engine.candidates.ice_lite,
             extmap_allow_mixed: true,
+            cryptex: self.setting_engine.srtp_cryptex_policy != CryptexPolicy::Disabled,
+            cryptos: self.dtls_transport.sdes_local_cryptos(true).await,
             connection_role: DEFAULT_DTLS_ROLE_OFFER.to_connection_role(),
             ice_gathering_state: self.ice_gathering_state(),
             match_bundle_group: None,
@@ -806,10 +813,13 @@ impl PeerConnectionInternal {
         let mut media_sections = vec![];
         let mut already_have_application_media_section = false;
         let mut extmap_allow_mixed = false;
+        let mut cryptex = false;
 
         if let Some(remote_description) = remote_description.as_ref() {
             if let Some(parsed) = &remote_description.parsed {
                 extmap_allow_mixed = parsed.has_attribute(ATTR_KEY_EXTMAP_ALLOW_MIXED);
+                cryptex = self.setting_engine.srtp_cryptex_policy != CryptexPolicy::Disabled
+                    && has_cryptex(parsed);
 
                 for media in &parsed.media_descriptions {
                     if let Some(mid_value) = get_mid_value(media) {
@@ -896,7 +906,12 @@ impl PeerConnectionInternal {
         };
 
         let dtls_fingerprints = if let Some(cert) = self.dtls_transport.certificates.first() {
-            cert.get_fingerprints()
+            // An SDES answer must not signal DTLS to a peer which didn't offer it
+            if !include_unmatched && self.dtls_transport.sdes_negotiated().await {
+                vec![]
+            } else {
+                cert.get_fingerprints()
+            }
         } else {
             return Err(Error::ErrNonCertificate);
         };
@@ -905,6 +920,11 @@ impl PeerConnectionInternal {
             media_description_fingerprint: self.setting_engine.sdp_media_level_fingerprints,
             is_icelite: self.setting_engine.candidates.ice_lite,
             extmap_allow_mixed,
+            cryptex,
+            cryptos: self
+                .dtls_transport
+                .sdes_local_cryptos(include_unmatched)
+                .await,
             connection_role,
             ice_gathering_state: self.ice_gathering_state(),
             match_bundle_group,
@@ -1281,12 +1301,19 @@ impl PeerConnectionInternal {
             .fetch_inbound_stats(track_infos.iter().map(|t| t.ssrc).collect())
             .await;
 
+        let srtp_session = self.dtls_transport.get_srtp_session().await;
+
         for (stats, info) in
             (stream_stats.into_iter().zip(track_infos)).filter_map(|(s, i)| s.map(|s| (s, i)))
         {
             let ssrc = info.ssrc;
             let kind = info.kind;
 
+            let srtp_stats = match &srtp_session {
+                Some(session) => session.remote_stats(ssrc).await.unwrap_or_default(),
+                None => Default::default(),
+            };
+
             let id = format!("RTCInboundRTP{}Stream_{}", capitalize(kind), ssrc);
             let (
                 packets_received,
@@ -1300,6 +1327,7 @@ impl PeerConnectionInternal {
                 remote_round_trip_time,
                 remote_total_round_trip_time,
                 remote_round_trip_time_measurements,
+                remote_smoothed_round_trip_time,
             ) = (
                 stats.packets_received(),
                 stats.header_bytes_received(),
@@ -1312,6 +1340,7 @@ impl PeerConnectionInternal {
                 stats.remote_round_trip_time(),
                 stats.remote_total_round_trip_time(),
                 stats.remote_round_trip_time_measurements(),
+                stats.remote_smoothed_round_trip_time(),
             );
 
             collector.insert(
@@ -1325,6 +1354,7 @@ impl PeerConnectionInternal {
                     packets_received,
                     track_identifier: info.track_id,
                     mid: info.mid,
+                    jitter: stats.jitter(),
                     last_packet_received_timestamp,
                     header_bytes_received,
                     bytes_received,
@@ -1332,6 +1362,14 @@ impl PeerConnectionInternal {
 
                     fir_count: (info.kind == "video").then(|| stats.firs_sent()),
                     pli_count: (info.kind == "video").then(|| stats.plis_sent()),
+
+                    srtp_auth_failures: srtp_stats.auth_failures,
+                    srtp_replay_drops: srtp_stats.replay_drops,
+                    srtp_decrypt_errors: srtp_stats.decrypt_errors,
+
+                    bitrate_received: stats.bitrate_received(),
+                    packet_rate_received: stats.packet_rate_received(),
+                    bursts_lost: stats.burst_loss_histogram().count(),
                 }),
             );
 
@@ -1358,6 +1396,7 @@ impl PeerConnectionInternal {
                     round_trip_time: remote_round_trip_time,
                     total_round_trip_time: remote_total_round_trip_time,
                     round_trip_time_measurements: remote_round_trip_time_measurements,
+                    smoothed_round_trip_time: remote_smoothed_round_trip_time,
                 }),
             );
         }
@@ -1429,6 +1468,7 @@ impl PeerConnectionInternal {
                 remote_rtt_ms,
                 remote_total_rtt_ms,
                 remote_rtt_measurements,
+                remote_smoothed_rtt_ms,
                 remote_fraction_lost,
             ) = (
                 stats.packets_sent(),
@@ -1440,6 +1480,7 @@ impl PeerConnectionInternal {
                 stats.remote_round_trip_time(),
                 stats.remote_total_round_trip_time(),
                 stats.remote_round_trip_time_measurements(),
+                stats.remote_smoothed_round_trip_time(),
                 stats.remote_fraction_lost(),
             );
 
@@ -1469,6 +1510,9 @@ impl PeerConnectionInternal {
 
                     fir_count: (info.kind == "video").then(|| stats.firs_received()),
                     pli_count: (info.kind == "video").then(|| stats.plis_received()),
+
+                    bitrate_sent: stats.bitrate_sent(),
+                    packet_rate_sent: stats.packet_rate_sent(),
                 }),
             );
 
@@ -1497,6 +1541,7 @@ impl PeerConnectionInternal {
                     total_round_trip_time: remote_total_rtt_ms,
                     fraction_lost: remote_fraction_lost.unwrap_or(0.0),
                     round_trip_time_measurements: remote_rtt_measurements,
+                    smoothed_round_trip_time: remote_smoothed_rtt_ms,
                 }),
             );
         }
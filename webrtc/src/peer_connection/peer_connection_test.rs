@@ -377,6 +377,91 @@ async fn test_get_stats() -> Result<()> {
     assert_eq!(inbound_stats.kind, "video");
     assert_eq!(inbound_stats.bytes_received, 8);
     assert_eq!(inbound_stats.header_bytes_received, 12);
+    assert_eq!(inbound_stats.srtp_auth_failures, 0);
+    assert_eq!(inbound_stats.srtp_replay_drops, 0);
+
+    close_pair_now(&pc_offer, &pc_answer).await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_peer_connection_application_defined() -> Result<()> {
+    let mut m = MediaEngine::default();
+    m.register_default_codecs()?;
+    let api = APIBuilder::new().with_media_engine(m).build();
+
+    let (mut pc_offer, mut pc_answer) = new_pair(&api).await?;
+
+    let result = pc_answer.on_application_defined("TOOLONG", Box::new(|_| Box::pin(async {})));
+    assert!(result.is_err());
+
+    let (app_tx, mut app_rx) = mpsc::channel(1);
+    pc_answer.on_application_defined(
+        "TEST",
+        Box::new(move |app: ApplicationDefined| {
+            let app_tx = app_tx.clone();
+            Box::pin(async move {
+                let _ = app_tx.try_send(app);
+            })
+        }),
+    )?;
+
+    let track = Arc::new(TrackLocalStaticSample::new(
+        RTCRtpCodecCapability {
+            mime_type: MIME_TYPE_VP8.to_owned(),
+            ..Default::default()
+        },
+        "video".to_owned(),
+        "webrtc-rs".to_owned(),
+    ));
+    let sender = pc_offer
+        .add_track(track.clone())
+        .await
+        .expect("Failed to add track");
+    let (packet_tx, packet_rx) = mpsc::channel(1);
+
+    pc_answer.on_track(Box::new(move |track, receiver, _| {
+        let packet_tx = packet_tx.clone();
+        tokio::spawn(async move {
+            while let Ok((pkt, _)) = track.read_rtp().await {
+                let last = pkt.payload[pkt.payload.len() - 1];
+                if last == 0xAA {
+                    let _ = packet_tx.send(()).await;
+                    break;
+                }
+            }
+        });
+        // APP packets are handled as the RTCP packets are read
+        tokio::spawn(async move { while receiver.read_rtcp().await.is_ok() {} });
+
+        Box::pin(async move {})
+    }));
+
+    signal_pair(&mut pc_offer, &mut pc_answer).await?;
+
+    send_video_until_done(
+        packet_rx,
+        vec![track],
+        Bytes::from_static(b"\xDE\xAD\xBE\xEF\xAA"),
+        None,
+    )
+    .await;
+
+    let ssrc = sender.get_parameters().await.encodings[0].ssrc;
+    let want = ApplicationDefined::new(ssrc, "TEST", 7, Bytes::from_static(b"ping"))?;
+    let received = tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            pc_offer.write_rtcp(&[Box::new(want.clone())]).await?;
+            tokio::select! {
+                app = app_rx.recv() => return Ok::<_, Error>(app),
+                _ = tokio::time::sleep(Duration::from_millis(100)) => {}
+            }
+        }
+    })
+    .await
+    .expect("APP packet should have been handled")?;
+    assert_eq!(received, Some(want));
 
     close_pair_now(&pc_offer, &pc_answer).await;
 
@@ -620,6 +705,7 @@ async fn test_peer_connection_simulcast_no_data_channel() -> Result<()> {
                 ..Default::default()
             },
             payload: Bytes::from_static(&[0; 2]),
+            ..Default::default()
         };
 
         track_a.write_rtp_with_extensions(&pkt, &[]).await?;
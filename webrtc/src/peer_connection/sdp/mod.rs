@@ -30,6 +30,7 @@ use sdp::description::session::*;
 use sdp::extmap::ExtMap;
 use sdp::util::ConnectionRole;
 use smol_str::SmolStr;
+use srtp::sdes::CryptoAttribute;
 use url::Url;
 
 use crate::peer_connection::MEDIA_SECTION_APPLICATION;
@@ -428,6 +429,7 @@ pub(crate) struct AddTransceiverSdpParams {
     dtls_role: ConnectionRole,
     ice_gathering_state: RTCIceGatheringState,
     offered_direction: Option<RTCRtpTransceiverDirection>,
+    cryptos: Vec<CryptoAttribute>,
 }
 
 pub(crate) async fn add_transceiver_sdp(
@@ -695,6 +697,11 @@ pub(crate) async fn add_transceiver_sdp(
         );
     }
 
+    for crypto in &params.cryptos {
+        // RFC 4568 9.1.
+        media = media.with_value_attribute(ATTR_KEY_CRYPTO.to_owned(), crypto.to_string());
+    }
+
     if should_add_candidates {
         media =
             add_candidates_to_media_descriptions(candidates, media, ice_gathering_state).await?;
@@ -783,6 +790,8 @@ pub(crate) struct PopulateSdpParams {
     pub(crate) media_description_fingerprint: bool,
     pub(crate) is_icelite: bool,
     pub(crate) extmap_allow_mixed: bool,
+    pub(crate) cryptex: bool,
+    pub(crate) cryptos: Vec<CryptoAttribute>,
     pub(crate) connection_role: ConnectionRole,
     pub(crate) ice_gathering_state: RTCIceGatheringState,
     pub(crate) match_bundle_group: Option<String>,
@@ -837,6 +846,7 @@ pub(crate) async fn populate_sdp(
                 dtls_role: params.connection_role,
                 ice_gathering_state: params.ice_gathering_state,
                 offered_direction: m.offered_direction,
+                cryptos: params.cryptos.clone(),
             };
             let (d1, should_add_id) = add_transceiver_sdp(
                 d,
@@ -887,9 +897,56 @@ pub(crate) async fn populate_sdp(
         d = d.with_property_attribute(ATTR_KEY_EXTMAP_ALLOW_MIXED.to_owned());
     }
 
+    if params.cryptex {
+        // RFC 9335 6.
+        d = d.with_property_attribute(ATTR_KEY_CRYPTEX.to_owned());
+    }
+
     Ok(d)
 }
 
+/// has_cryptex returns true if the description signals cryptex at session or media level.
+pub(crate) fn has_cryptex(desc: &SessionDescription) -> bool {
+    desc.has_attribute(ATTR_KEY_CRYPTEX)
+        || desc
+            .media_descriptions
+            .iter()
+            .any(|m| m.attribute(ATTR_KEY_CRYPTEX).is_some())
+}
+
+/// has_fingerprint returns true if the description carries a DTLS fingerprint at session or
+/// media level.
+pub(crate) fn has_fingerprint(desc: &SessionDescription) -> bool {
+    desc.has_attribute("fingerprint")
+        || desc
+            .media_descriptions
+            .iter()
+            .any(|m| m.attribute("fingerprint").is_some())
+}
+
+/// extract_cryptos returns the SDES crypto attributes of the first media description
+/// carrying any, skipping the ones using unsupported crypto suites.
+pub(crate) fn extract_cryptos(desc: &SessionDescription) -> Vec<CryptoAttribute> {
+    desc.media_descriptions
+        .iter()
+        .map(|m| {
+            m.attributes
+                .iter()
+                .filter(|a| a.key == ATTR_KEY_CRYPTO)
+                .filter_map(|a| a.value.as_deref())
+                .filter_map(|value| match CryptoAttribute::parse(value) {
+                    Ok(crypto) => Some(crypto),
+                    Err(err) => {
+                        log::debug!("ignoring crypto attribute: {}", err);
+                        None
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .find(|cryptos| !cryptos.is_empty())
+        .unwrap_or_default()
+}
+
 pub(crate) fn get_mid_value(media: &MediaDescription) -> Option<&String> {
     for attr in &media.attributes {
         if attr.key == "mid" {
@@ -582,6 +582,8 @@ async fn fingerprint_test(
         media_description_fingerprint: sdpmedia_description_fingerprints,
         is_icelite: false,
         extmap_allow_mixed: false,
+        cryptex: false,
+        cryptos: vec![],
         connection_role: ConnectionRole::Active,
         ice_gathering_state: RTCIceGatheringState::New,
         match_bundle_group: None,
@@ -779,6 +781,8 @@ async fn test_populate_sdp() -> Result<()> {
             media_description_fingerprint: se.sdp_media_level_fingerprints,
             is_icelite: se.candidates.ice_lite,
             extmap_allow_mixed: true,
+            cryptex: false,
+            cryptos: vec![],
             connection_role: DEFAULT_DTLS_ROLE_OFFER.to_connection_role(),
             ice_gathering_state: RTCIceGatheringState::Complete,
             match_bundle_group: None,
@@ -885,6 +889,8 @@ async fn test_populate_sdp() -> Result<()> {
             media_description_fingerprint: se.sdp_media_level_fingerprints,
             is_icelite: se.candidates.ice_lite,
             extmap_allow_mixed: true,
+            cryptex: false,
+            cryptos: vec![],
             connection_role: DEFAULT_DTLS_ROLE_OFFER.to_connection_role(),
             ice_gathering_state: RTCIceGatheringState::Complete,
             match_bundle_group: None,
@@ -966,6 +972,8 @@ async fn test_populate_sdp() -> Result<()> {
             media_description_fingerprint: se.sdp_media_level_fingerprints,
             is_icelite: se.candidates.ice_lite,
             extmap_allow_mixed: true,
+            cryptex: false,
+            cryptos: vec![],
             connection_role: DEFAULT_DTLS_ROLE_OFFER.to_connection_role(),
             ice_gathering_state: RTCIceGatheringState::Complete,
             match_bundle_group: None,
@@ -1064,6 +1072,8 @@ async fn test_populate_sdp() -> Result<()> {
             media_description_fingerprint: se.sdp_media_level_fingerprints,
             is_icelite: se.candidates.ice_lite,
             extmap_allow_mixed: true,
+            cryptex: false,
+            cryptos: vec![],
             connection_role: DEFAULT_DTLS_ROLE_OFFER.to_connection_role(),
             ice_gathering_state: RTCIceGatheringState::Complete,
             match_bundle_group: Some("audio".to_owned()),
@@ -1130,6 +1140,8 @@ async fn test_populate_sdp() -> Result<()> {
             media_description_fingerprint: se.sdp_media_level_fingerprints,
             is_icelite: se.candidates.ice_lite,
             extmap_allow_mixed: true,
+            cryptex: false,
+            cryptos: vec![],
             connection_role: DEFAULT_DTLS_ROLE_OFFER.to_connection_role(),
             ice_gathering_state: RTCIceGatheringState::Complete,
             match_bundle_group: Some("".to_owned()),
@@ -1240,6 +1252,8 @@ async fn test_populate_sdp_reject() -> Result<()> {
         media_description_fingerprint: se.sdp_media_level_fingerprints,
         is_icelite: se.candidates.ice_lite,
         extmap_allow_mixed: true,
+        cryptex: false,
+        cryptos: vec![],
         connection_role: DEFAULT_DTLS_ROLE_OFFER.to_connection_role(),
         ice_gathering_state: RTCIceGatheringState::Complete,
         match_bundle_group: None,
@@ -4,7 +4,7 @@ mod generic_test;
 use super::*;
 
 /// fmtp_consist checks that two FMTP parameters are not inconsistent.
-fn fmtp_consist(a: &HashMap<String, String>, b: &HashMap<String, String>) -> bool {
+pub(crate) fn fmtp_consist(a: &HashMap<String, String>, b: &HashMap<String, String>) -> bool {
     //TODO: add unicode case-folding equal support
     for (k, v) in a {
         if let Some(vb) = b.get(k) {
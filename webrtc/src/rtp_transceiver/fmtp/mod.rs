@@ -1,5 +1,6 @@
 pub(crate) mod generic;
 pub(crate) mod h264;
+pub(crate) mod multiopus;
 
 use std::any::Any;
 use std::collections::HashMap;
@@ -7,6 +8,7 @@ use std::fmt;
 
 use crate::rtp_transceiver::fmtp::generic::GenericFmtp;
 use crate::rtp_transceiver::fmtp::h264::H264Fmtp;
+use crate::rtp_transceiver::fmtp::multiopus::MultiOpusFmtp;
 
 /// Fmtp interface for implementing custom
 /// Fmtp parsers based on mime_type
@@ -49,6 +51,11 @@ pub fn parse(mime_type: &str, line: &str) -> Box<dyn Fmtp> {
 
     if mime_type.to_uppercase() == "video/h264".to_uppercase() {
         Box::new(H264Fmtp { parameters })
+    } else if mime_type.to_lowercase() == "audio/multiopus" {
+        Box::new(MultiOpusFmtp {
+            mime_type: mime_type.to_owned(),
+            parameters,
+        })
     } else {
         Box::new(GenericFmtp {
             mime_type: mime_type.to_owned(),
@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod multiopus_test;
+
+use super::generic::fmtp_consist;
+use super::*;
+
+/// MultiOpusChannelMapping is the channel mapping of a multichannel Opus stream, which
+/// holds num_streams Opus streams, the first coupled_streams of them stereo. Each channel
+/// is decoded from the stream channel its entry of channel_mapping points to, see
+/// channel mapping family 1 of RFC 7845 Section 5.1.1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MultiOpusChannelMapping {
+    pub(crate) num_streams: u8,
+    pub(crate) coupled_streams: u8,
+    pub(crate) channel_mapping: Vec<u8>,
+}
+
+impl MultiOpusChannelMapping {
+    /// parse returns the channel mapping of the fmtp parameters, None if it is missing or
+    /// invalid.
+    fn parse(parameters: &HashMap<String, String>) -> Option<Self> {
+        let num_streams: u8 = parameters.get("num_streams")?.trim().parse().ok()?;
+        let coupled_streams: u8 = parameters.get("coupled_streams")?.trim().parse().ok()?;
+        let channel_mapping = parameters
+            .get("channel_mapping")?
+            .split(',')
+            .map(|c| c.trim().parse::<u8>().ok())
+            .collect::<Option<Vec<u8>>>()?;
+
+        // 255 marks a silent channel.
+        let stream_channels = num_streams as u16 + coupled_streams as u16;
+        if num_streams == 0
+            || coupled_streams > num_streams
+            || channel_mapping.is_empty()
+            || channel_mapping
+                .iter()
+                .any(|&c| c != 255 && c as u16 >= stream_channels)
+        {
+            return None;
+        }
+
+        Some(MultiOpusChannelMapping {
+            num_streams,
+            coupled_streams,
+            channel_mapping,
+        })
+    }
+}
+
+/// MultiOpusFmtp is the fmtp of multichannel Opus, as negotiated by browsers with the
+/// audio/multiopus MIME type.
+#[derive(Debug, PartialEq)]
+pub(crate) struct MultiOpusFmtp {
+    pub(crate) mime_type: String,
+    pub(crate) parameters: HashMap<String, String>,
+}
+
+impl MultiOpusFmtp {
+    pub(crate) fn channel_mapping(&self) -> Option<MultiOpusChannelMapping> {
+        MultiOpusChannelMapping::parse(&self.parameters)
+    }
+}
+
+impl Fmtp for MultiOpusFmtp {
+    fn mime_type(&self) -> &str {
+        self.mime_type.as_str()
+    }
+
+    /// Match returns true if both describe the same channel mapping, which can't be
+    /// negotiated as it determines how the streams are decoded, and their remaining
+    /// parameters are consistent.
+    fn match_fmtp(&self, f: &dyn Fmtp) -> bool {
+        if let Some(c) = f.as_any().downcast_ref::<MultiOpusFmtp>() {
+            if self.mime_type.to_lowercase() != c.mime_type().to_lowercase() {
+                return false;
+            }
+
+            match (self.channel_mapping(), c.channel_mapping()) {
+                (Some(a), Some(b)) if a == b => {}
+                _ => return false,
+            }
+
+            fmtp_consist(&self.parameters, &c.parameters)
+        } else {
+            false
+        }
+    }
+
+    fn parameter(&self, key: &str) -> Option<&String> {
+        self.parameters.get(key)
+    }
+
+    fn equal(&self, other: &dyn Fmtp) -> bool {
+        other
+            .as_any()
+            .downcast_ref::<MultiOpusFmtp>()
+            .is_some_and(|a| self == a)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
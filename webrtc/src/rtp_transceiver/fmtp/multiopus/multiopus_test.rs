@@ -0,0 +1,84 @@
+use super::*;
+
+const SURROUND_5_1: &str =
+    "channel_mapping=0,4,1,2,3,5;coupled_streams=2;minptime=10;num_streams=4;useinbandfec=1";
+
+#[test]
+fn test_multiopus_fmtp_parse() {
+    let f = parse("audio/multiopus", SURROUND_5_1);
+    let f = f
+        .as_any()
+        .downcast_ref::<MultiOpusFmtp>()
+        .expect("multiopus should have its own fmtp");
+
+    assert_eq!(
+        f.channel_mapping(),
+        Some(MultiOpusChannelMapping {
+            num_streams: 4,
+            coupled_streams: 2,
+            channel_mapping: vec![0, 4, 1, 2, 3, 5],
+        })
+    );
+    assert_eq!(f.parameter("minptime"), Some(&"10".to_owned()));
+}
+
+#[test]
+fn test_multiopus_fmtp_compare() {
+    let tests = vec![
+        ("Equal", SURROUND_5_1, SURROUND_5_1, true),
+        (
+            "OtherParameterOrder",
+            SURROUND_5_1,
+            "num_streams=4;coupled_streams=2;channel_mapping=0,4,1,2,3,5",
+            true,
+        ),
+        (
+            "InconsistentParameter",
+            SURROUND_5_1,
+            "channel_mapping=0,4,1,2,3,5;coupled_streams=2;num_streams=4;useinbandfec=0",
+            false,
+        ),
+        (
+            "OtherChannelMapping",
+            SURROUND_5_1,
+            "channel_mapping=0,6,1,2,3,4,5,7;coupled_streams=3;num_streams=5",
+            false,
+        ),
+        (
+            "MissingChannelMapping",
+            SURROUND_5_1,
+            "coupled_streams=2;num_streams=4",
+            false,
+        ),
+        (
+            "InvalidChannelMapping",
+            "channel_mapping=0,6;coupled_streams=2;num_streams=4",
+            "channel_mapping=0,6;coupled_streams=2;num_streams=4",
+            false,
+        ),
+        (
+            "TooManyCoupledStreams",
+            "channel_mapping=0,1;coupled_streams=2;num_streams=1",
+            "channel_mapping=0,1;coupled_streams=2;num_streams=1",
+            false,
+        ),
+        (
+            "SilentChannel",
+            "channel_mapping=0,255;coupled_streams=0;num_streams=1",
+            "channel_mapping=0,255;coupled_streams=0;num_streams=1",
+            true,
+        ),
+    ];
+
+    for (name, a, b, consist) in tests {
+        let aa = parse("audio/multiopus", a);
+        let bb = parse("audio/MULTIOPUS", b);
+
+        assert_eq!(aa.match_fmtp(&*bb), consist, "{name}: a to b");
+        assert_eq!(bb.match_fmtp(&*aa), consist, "{name}: b to a");
+    }
+
+    // Opus of up to two channels has no channel mapping to negotiate.
+    let opus = parse("audio/opus", "minptime=10;useinbandfec=1");
+    assert!(!parse("audio/multiopus", SURROUND_5_1).match_fmtp(&*opus));
+}
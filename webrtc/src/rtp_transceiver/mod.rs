@@ -165,6 +165,11 @@ pub(crate) fn create_stream_info(
         channels: codec.channels,
         sdp_fmtp_line: codec.sdp_fmtp_line,
         rtcp_feedback: feedbacks,
+        // packets aren't sent over RTX nor protected by FEC yet
+        ssrc_retransmission: 0,
+        payload_type_retransmission: 0,
+        ssrc_forward_error_correction: 0,
+        payload_type_forward_error_correction: 0,
     }
 }
 
@@ -79,7 +79,9 @@ impl RTCRtpCodecCapability {
             Ok(Box::new(vp8_payloader))
         } else if mime_type == MIME_TYPE_VP9.to_lowercase() {
             Ok(Box::<rtp::codecs::vp9::Vp9Payloader>::default())
-        } else if mime_type == MIME_TYPE_OPUS.to_lowercase() {
+        } else if mime_type == MIME_TYPE_OPUS.to_lowercase()
+            || mime_type == MIME_TYPE_MULTIOPUS.to_lowercase()
+        {
             Ok(Box::<rtp::codecs::opus::OpusPayloader>::default())
         } else if mime_type == MIME_TYPE_G722.to_lowercase()
             || mime_type == MIME_TYPE_PCMU.to_lowercase()
@@ -87,8 +89,18 @@ impl RTCRtpCodecCapability {
             || mime_type == MIME_TYPE_TELEPHONE_EVENT.to_lowercase()
         {
             Ok(Box::<rtp::codecs::g7xx::G7xxPayloader>::default())
+        } else if mime_type == MIME_TYPE_L16.to_lowercase() {
+            Ok(Box::new(rtp::codecs::pcm::LinearPcmPayloader::l16(
+                self.channels,
+            )))
+        } else if mime_type == MIME_TYPE_L24.to_lowercase() {
+            Ok(Box::new(rtp::codecs::pcm::LinearPcmPayloader::l24(
+                self.channels,
+            )))
         } else if mime_type == MIME_TYPE_AV1.to_lowercase() {
             Ok(Box::<rtp::codecs::av1::Av1Payloader>::default())
+        } else if mime_type == MIME_TYPE_JPEG.to_lowercase() {
+            Ok(Box::<rtp::codecs::jpeg::JpegPayloader>::default())
         } else {
             Err(Error::ErrNoPayloaderForCodec)
         }
@@ -159,7 +171,11 @@ pub(crate) fn codec_parameters_fuzzy_search(
         }
     }
 
-    // Fallback to just mime_type
+    // Fallback to just mime_type, but for multichannel Opus, whose streams can't be decoded
+    // with another channel mapping
+    if needle.capability.mime_type.to_lowercase() == MIME_TYPE_MULTIOPUS.to_lowercase() {
+        return (RTCRtpCodecParameters::default(), CodecMatch::None);
+    }
     for c in haystack {
         if c.capability.mime_type.to_uppercase() == needle.capability.mime_type.to_uppercase() {
             return (c.clone(), CodecMatch::Partial);
@@ -8,6 +8,7 @@ use arc_swap::ArcSwapOption;
 use interceptor::stream_info::RTPHeaderExtension;
 use interceptor::{Attributes, Interceptor};
 use log::trace;
+use rtcp::goodbye::Goodbye;
 use smol_str::SmolStr;
 use tokio::sync::{watch, Mutex, RwLock};
 
@@ -171,25 +172,62 @@ impl RTPReceiverInternal {
         // isn't flowing.
         State::wait_for(&mut state_watch_rx, &[State::Started, State::Paused]).await?;
 
-        let tracks = self.tracks.read().await;
-        if let Some(t) = tracks.first() {
-            if let Some(rtcp_interceptor) = &t.stream.rtcp_interceptor {
-                let a = Attributes::new();
-                loop {
-                    tokio::select! {
-                        res = State::error_on_close(&mut state_watch_rx) => {
-                            res?
-                        }
-                        result = rtcp_interceptor.read(b, &a) => {
-                            return Ok(result?)
-                        }
+        let rtcp_interceptor = {
+            let tracks = self.tracks.read().await;
+            if let Some(t) = tracks.first() {
+                t.stream
+                    .rtcp_interceptor
+                    .clone()
+                    .ok_or(Error::ErrInterceptorNotBind)?
+            } else {
+                return Err(Error::ErrExistingTrack);
+            }
+        };
+
+        let a = Attributes::new();
+        loop {
+            tokio::select! {
+                res = State::error_on_close(&mut state_watch_rx) => {
+                    res?
+                }
+                result = rtcp_interceptor.read(b, &a) => {
+                    let (pkts, attributes) = result?;
+                    self.handle_goodbyes(&pkts).await;
+                    return Ok((pkts, attributes));
+                }
+            }
+        }
+    }
+
+    /// handle_goodbyes ends the tracks whose sources left, as told by the RTCP BYEs among pkts.
+    async fn handle_goodbyes(&self, pkts: &[Box<dyn rtcp::packet::Packet + Send + Sync>]) {
+        let goodbyes: Vec<&Goodbye> = pkts
+            .iter()
+            .filter_map(|p| p.as_any().downcast_ref::<Goodbye>())
+            .collect();
+        if goodbyes.is_empty() {
+            return;
+        }
+
+        let mut ended = vec![];
+        {
+            let tracks = self.tracks.read().await;
+            for bye in goodbyes {
+                let reason = if bye.reason.is_empty() {
+                    None
+                } else {
+                    Some(String::from_utf8_lossy(&bye.reason).into_owned())
+                };
+                for t in &*tracks {
+                    if bye.sources.contains(&t.track.ssrc()) {
+                        ended.push((Arc::clone(&t.track), reason.clone()));
                     }
                 }
-            } else {
-                Err(Error::ErrInterceptorNotBind)
             }
-        } else {
-            Err(Error::ErrExistingTrack)
+        }
+
+        for (track, reason) in ended {
+            track.fire_onended(reason).await;
         }
     }
 
@@ -205,28 +243,31 @@ impl RTPReceiverInternal {
         // isn't flowing.
         State::wait_for(&mut state_watch_rx, &[State::Started, State::Paused]).await?;
 
-        let tracks = self.tracks.read().await;
-        for t in &*tracks {
-            if t.track.rid() == rid {
-                if let Some(rtcp_interceptor) = &t.stream.rtcp_interceptor {
-                    let a = Attributes::new();
+        let rtcp_interceptor = {
+            let tracks = self.tracks.read().await;
+            if let Some(t) = tracks.iter().find(|t| t.track.rid() == rid) {
+                t.stream
+                    .rtcp_interceptor
+                    .clone()
+                    .ok_or(Error::ErrInterceptorNotBind)?
+            } else {
+                return Err(Error::ErrRTPReceiverForRIDTrackStreamNotFound);
+            }
+        };
 
-                    loop {
-                        tokio::select! {
-                            res = State::error_on_close(&mut state_watch_rx) => {
-                                res?
-                            }
-                            result = rtcp_interceptor.read(b, &a) => {
-                                return Ok(result?);
-                            }
-                        }
-                    }
-                } else {
-                    return Err(Error::ErrInterceptorNotBind);
+        let a = Attributes::new();
+        loop {
+            tokio::select! {
+                res = State::error_on_close(&mut state_watch_rx) => {
+                    res?
+                }
+                result = rtcp_interceptor.read(b, &a) => {
+                    let (pkts, attributes) = result?;
+                    self.handle_goodbyes(&pkts).await;
+                    return Ok((pkts, attributes));
                 }
             }
         }
-        Err(Error::ErrRTPReceiverForRIDTrackStreamNotFound)
     }
 
     /// read_rtcp is a convenience method that wraps Read and unmarshal for you.
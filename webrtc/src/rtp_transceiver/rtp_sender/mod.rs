@@ -4,10 +4,12 @@ mod rtp_sender_test;
 use std::sync::atomic::Ordering;
 use std::sync::{Arc, Weak};
 
+use bytes::Bytes;
 use ice::rand::generate_crypto_random_string;
 use interceptor::stream_info::StreamInfo;
 use interceptor::{Attributes, Interceptor, RTCPReader, RTPWriter};
 use portable_atomic::AtomicBool;
+use rtcp::goodbye::Goodbye;
 use tokio::sync::{watch, Mutex, Notify};
 use util::sync::Mutex as SyncMutex;
 
@@ -470,6 +472,30 @@ impl RTCRtpSender {
         Ok(())
     }
 
+    /// stop_with_reason irreversibly stops the RTPSender, like stop, after sending an RTCP BYE
+    /// for its streams with reason, which the remote is given along with the end of its tracks.
+    pub async fn stop_with_reason(&self, reason: &str) -> Result<()> {
+        if reason.len() > u8::MAX as usize {
+            return Err(rtcp::Error::ReasonTooLong.into());
+        }
+
+        if !self.stop_called_signal.load(Ordering::SeqCst) && self.has_sent() {
+            let sources: Vec<SSRC> = {
+                let track_encodings = self.track_encodings.lock().await;
+                track_encodings.iter().map(|e| e.ssrc).collect()
+            };
+            let bye = Goodbye {
+                sources,
+                reason: Bytes::from(reason.to_owned()),
+            };
+            if let Err(err) = self.transport.write_rtcp(&[Box::new(bye)]).await {
+                log::warn!("failed to send RTCP BYE: {}", err);
+            }
+        }
+
+        self.stop().await
+    }
+
     /// read reads incoming RTCP for this RTPReceiver
     pub async fn read(
         &self,
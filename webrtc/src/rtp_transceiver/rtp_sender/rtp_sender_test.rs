@@ -623,3 +623,63 @@ async fn test_rtp_sender_add_encoding() -> Result<()> {
     close_pair_now(&sender, &receiver).await;
     Ok(())
 }
+
+#[tokio::test]
+async fn test_rtp_sender_stop_with_reason() -> Result<()> {
+    let mut m = MediaEngine::default();
+    m.register_default_codecs()?;
+    let api = APIBuilder::new().with_media_engine(m).build();
+
+    let (mut sender, mut receiver) = new_pair(&api).await?;
+
+    let track = Arc::new(TrackLocalStaticSample::new(
+        RTCRtpCodecCapability {
+            mime_type: MIME_TYPE_VP8.to_owned(),
+            ..Default::default()
+        },
+        "video".to_owned(),
+        "webrtc-rs".to_owned(),
+    ));
+    let rtp_sender = sender
+        .add_track(Arc::clone(&track) as Arc<dyn TrackLocal + Send + Sync>)
+        .await?;
+
+    let (seen_packet_tx, seen_packet_rx) = mpsc::channel::<()>(1);
+    let (ended_tx, mut ended_rx) = mpsc::channel::<Option<String>>(1);
+    receiver.on_track(Box::new(move |track, rtp_receiver, _| {
+        let seen_packet_tx = seen_packet_tx.clone();
+        let ended_tx = ended_tx.clone();
+        Box::pin(async move {
+            track.onended(move |reason| {
+                let ended_tx = ended_tx.clone();
+                Box::pin(async move {
+                    let _ = ended_tx.send(reason).await;
+                })
+            });
+            // BYEs are received along with the RTCP read
+            tokio::spawn(async move { while rtp_receiver.read_rtcp().await.is_ok() {} });
+            let _ = seen_packet_tx.send(()).await;
+        })
+    }));
+
+    signal_pair(&mut sender, &mut receiver).await?;
+
+    send_video_until_done(
+        seen_packet_rx,
+        vec![track],
+        Bytes::from_static(&[0xAA]),
+        None,
+    )
+    .await;
+
+    assert!(rtp_sender.stop_with_reason(&"x".repeat(256)).await.is_err());
+    rtp_sender.stop_with_reason("camera switched").await?;
+
+    let reason = tokio::time::timeout(Duration::from_secs(5), ended_rx.recv())
+        .await
+        .expect("track should have ended");
+    assert_eq!(reason, Some(Some("camera switched".to_owned())));
+
+    close_pair_now(&sender, &receiver).await;
+    Ok(())
+}
@@ -29,7 +29,7 @@ use crate::error::*;
 use crate::sctp_transport::sctp_transport_capabilities::SCTPTransportCapabilities;
 use crate::stats::stats_collector::StatsCollector;
 use crate::stats::StatsReportType::{PeerConnection, SCTPTransport};
-use crate::stats::{ICETransportStats, PeerConnectionStats};
+use crate::stats::{PeerConnectionStats, SCTPTransportStats};
 
 const SCTP_MAX_CHANNELS: u16 = u16::MAX;
 
@@ -159,6 +159,22 @@ impl RTCSctpTransport {
                         max_receive_buffer_size: 0,
                         max_message_size: 0,
                         name: String::new(),
+                        stream_scheduler: self.setting_engine.sctp_stream_scheduler,
+                        congestion_control: self.setting_engine.sctp_congestion_control,
+                        sack_delay: self.setting_engine.sctp_sack_delay,
+                        packets_per_sack: self.setting_engine.sctp_packets_per_sack,
+                        immediate_sack: self.setting_engine.sctp_immediate_sack,
+                        zero_checksum: self.setting_engine.sctp_zero_checksum,
+                        max_num_outbound_streams: 0,
+                        max_num_inbound_streams: 0,
+                        heartbeat_interval: self.setting_engine.sctp_heartbeat_interval,
+                        path_max_retransmits: self.setting_engine.sctp_path_max_retransmits,
+                        association_max_retransmits: self
+                            .setting_engine
+                            .sctp_association_max_retransmits,
+                        // ECN marks are not reported through DTLS over ICE.
+                        ecn: false,
+                        max_send_buffer_size: self.setting_engine.sctp_max_send_buffer_size,
                     }) => {
                         break Arc::new(association?);
                     }
@@ -220,16 +236,27 @@ impl RTCSctpTransport {
         }
         drop(dcs);
 
+        let filter = param.setting_engine.data_channel_filter.clone();
         loop {
             let dc = tokio::select! {
                 _ = param.notify_rx.notified() => break,
-                result = DataChannel::accept(
+                result = DataChannel::accept_with_filter(
                     &param.sctp_association,
                     data::data_channel::Config::default(),
                     &existing_data_channels,
+                    |config| filter.as_ref().is_none_or(|f| f(&config.label, &config.protocol)),
                 ) => {
                     match result {
                         Ok(dc) => dc,
+                        // The stream of the data channel was reset, others can still be accepted.
+                        Err(err @ data::Error::ErrDataChannelRejected { .. }) => {
+                            log::debug!("{}", err);
+                            continue;
+                        }
+                        Err(err @ data::Error::ErrMalformedDataChannelOpen(_)) => {
+                            log::warn!("Failed to accept data channel: {}", err);
+                            continue;
+                        }
                         Err(err) => {
                             if data::Error::ErrStreamClosed == err {
                                 log::error!("Failed to accept data channel: {}", err);
@@ -287,6 +314,7 @@ impl RTCSctpTransport {
                     ordered,
                     max_packet_life_time: max_packet_lifetime,
                     max_retransmits,
+                    priority: dc.config.priority,
                 },
                 Arc::clone(&param.setting_engine),
             ));
@@ -384,9 +412,11 @@ impl RTCSctpTransport {
             PeerConnectionStats::new(self, peer_connection_id.clone(), data_channels_closed);
         reports.insert(peer_connection_id, PeerConnection(peer_connection_stats));
 
-        // conn
-        if let Some(agent) = dtls_transport.ice_transport.gatherer.get_agent().await {
-            let stats = ICETransportStats::new("sctp_transport".to_owned(), agent);
+        // association
+        let association = self.association().await;
+        if let Some(association) = association {
+            let stats =
+                SCTPTransportStats::new("sctp_transport".to_owned(), association.get_stats().await);
             reports.insert(stats.id.clone(), SCTPTransport(stats));
         }
 
@@ -6,6 +6,7 @@ use ice::agent::agent_stats::{CandidatePairStats, CandidateStats};
 use ice::agent::Agent;
 use ice::candidate::{CandidatePairState, CandidateType};
 use ice::network_type::NetworkType;
+use sctp::association::AssociationStatsReport;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use smol_str::SmolStr;
 use stats_collector::StatsCollector;
@@ -50,6 +51,8 @@ pub enum RTCStatsType {
     RemoteInboundRTP,
     #[serde(rename = "remote-outbound-rtp")]
     RemoteOutboundRTP,
+    #[serde(rename = "sctp-transport")]
+    SCTPTransport,
     #[serde(rename = "sender")]
     Sender,
     #[serde(rename = "transport")]
@@ -70,7 +73,7 @@ pub enum StatsReportType {
     LocalCandidate(ICECandidateStats),
     PeerConnection(PeerConnectionStats),
     RemoteCandidate(ICECandidateStats),
-    SCTPTransport(ICETransportStats),
+    SCTPTransport(SCTPTransportStats),
     Transport(ICETransportStats),
     InboundRTP(InboundRTPStats),
     OutboundRTP(OutboundRTPStats),
@@ -181,6 +184,10 @@ impl<'de> Deserialize<'de> for StatsReportType {
                 let stats = serde_json::from_value(value).map_err(serde::de::Error::custom)?;
                 Ok(StatsReportType::RemoteOutboundRTP(stats))
             }
+            RTCStatsType::SCTPTransport => {
+                let stats = serde_json::from_value(value).map_err(serde::de::Error::custom)?;
+                Ok(StatsReportType::SCTPTransport(stats))
+            }
             RTCStatsType::Sender => {
                 todo!()
             }
@@ -385,6 +392,63 @@ impl ICETransportStats {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SCTPTransportStats {
+    // RTCStats
+    #[serde(with = "serialize::instant_to_epoch_seconds")]
+    pub timestamp: Instant,
+    #[serde(rename = "type")]
+    pub stats_type: RTCStatsType,
+    pub id: String,
+
+    // RTCSctpTransportStats
+    pub smoothed_round_trip_time: Option<f64>,
+    pub congestion_window: u32,
+    pub receiver_window: u32,
+    pub mtu: u32,
+
+    // Non-canon
+    pub bytes_received: usize,
+    pub bytes_sent: usize,
+    pub slow_start_threshold: u32,
+    pub bytes_in_flight: usize,
+    pub local_receiver_window: u32,
+    pub retransmission_timeout: f64,
+    pub retransmitted_chunks: u64,
+    pub fast_retransmits: u64,
+    pub fast_retransmitted_chunks: u64,
+    pub retransmission_timeouts: u64,
+}
+
+impl SCTPTransportStats {
+    pub(crate) fn new(id: String, stats: AssociationStatsReport) -> Self {
+        SCTPTransportStats {
+            id,
+            smoothed_round_trip_time: if stats.srtt.is_zero() {
+                None
+            } else {
+                Some(stats.srtt.as_secs_f64())
+            },
+            congestion_window: stats.cwnd,
+            receiver_window: stats.peer_receiver_window,
+            mtu: stats.mtu,
+            bytes_received: stats.bytes_received,
+            bytes_sent: stats.bytes_sent,
+            slow_start_threshold: stats.ssthresh,
+            bytes_in_flight: stats.bytes_in_flight,
+            local_receiver_window: stats.receiver_window,
+            retransmission_timeout: stats.rto.as_secs_f64(),
+            retransmitted_chunks: stats.retransmitted_chunks,
+            fast_retransmits: stats.fast_retransmits,
+            fast_retransmitted_chunks: stats.fast_retransmitted_chunks,
+            retransmission_timeouts: stats.t3_timeouts,
+            stats_type: RTCStatsType::SCTPTransport,
+            timestamp: Instant::now(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CertificateStats {
@@ -468,6 +532,14 @@ pub struct DataChannelStats {
     pub messages_sent: usize,
     pub protocol: String,
     pub state: RTCDataChannelState,
+
+    // Non-canon
+    pub retransmitted_chunks: u64,
+    pub buffered_amount: usize,
+    #[serde(with = "serialize::option_instant_to_epoch_seconds")]
+    pub opened_at: Option<Instant>,
+    #[serde(with = "serialize::option_instant_to_epoch_seconds")]
+    pub closed_at: Option<Instant>,
 }
 
 impl DataChannelStats {
@@ -478,6 +550,8 @@ impl DataChannelStats {
         let mut bytes_sent = 0;
         let mut messages_received = 0;
         let mut messages_sent = 0;
+        let mut retransmitted_chunks = 0;
+        let mut buffered_amount = 0;
 
         let lock = data_channel.data_channel.lock().await;
 
@@ -486,17 +560,23 @@ impl DataChannelStats {
             bytes_sent = internal.bytes_sent();
             messages_received = internal.messages_received();
             messages_sent = internal.messages_sent();
+            retransmitted_chunks = internal.retransmitted_chunks();
+            buffered_amount = internal.buffered_amount();
         }
 
         Self {
+            buffered_amount,
             bytes_received,
             bytes_sent,
+            closed_at: *data_channel.closed_at.lock(),
             data_channel_identifier: data_channel.id(), // TODO: "The value is initially null"
             id: data_channel.stats_id.clone(),
             label: data_channel.label.clone(),
             messages_received,
             messages_sent,
+            opened_at: *data_channel.opened_at.lock(),
             protocol: data_channel.protocol.clone(),
+            retransmitted_chunks,
             state,
             stats_type: RTCStatsType::DataChannel,
             timestamp: Instant::now(),
@@ -556,7 +636,7 @@ pub struct InboundRTPStats {
     // RTCReceivedRtpStreamStats
     pub packets_received: u64,
     // TODO: packetsLost
-    // TODO: jitter(maybe, might be uattainable for the same reason as `framesDropped`)
+    pub jitter: f64,
     // NB: `framesDropped` can't be produced since we aren't decoding, might be worth introducing a
     // way for consumers to control this in the future.
 
@@ -582,6 +662,16 @@ pub struct InboundRTPStats {
     // `insertedSamplesForDeceleration`, `removedSamplesForAcceleration`, `audioLevel`,
     // `totalAudioEneregy`, `totalSampleDuration`, `framesReceived, and `decoderImplementation` are
     // all decoder specific and can't be produced since we aren't decoding.
+
+    // Non-standard, packets of the stream dropped by SRTP
+    pub srtp_auth_failures: u64,
+    pub srtp_replay_drops: u64,
+    pub srtp_decrypt_errors: u64,
+
+    // Non-standard, the rates of the stream over the last second and its bursts of packets lost
+    pub bitrate_received: f64,
+    pub packet_rate_received: f64,
+    pub bursts_lost: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -625,6 +715,10 @@ pub struct OutboundRTPStats {
     pub pli_count: Option<u64>,
     // NB: `encoderImplementation` is encoder specific and can't be produced since we aren't
     // encoding.
+
+    // Non-standard, the rates of the stream over the last second
+    pub bitrate_sent: f64,
+    pub packet_rate_sent: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -656,6 +750,7 @@ pub struct RemoteInboundRTPStats {
     pub total_round_trip_time: f64,
     pub fraction_lost: f64,
     pub round_trip_time_measurements: u64,
+    pub smoothed_round_trip_time: Option<f64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -685,4 +780,5 @@ pub struct RemoteOutboundRTPStats {
     pub reports_sent: u64,
     pub total_round_trip_time: f64,
     pub round_trip_time_measurements: u64,
+    pub smoothed_round_trip_time: Option<f64>,
 }
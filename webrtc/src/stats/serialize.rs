@@ -43,3 +43,30 @@ pub mod instant_to_epoch_seconds {
         Ok(instant)
     }
 }
+
+/// Serializes an `Option<tokio::time::Instant>` like [`instant_to_epoch_seconds`], with `None`
+/// as `null`.
+pub mod option_instant_to_epoch_seconds {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use tokio::time::Instant;
+
+    pub fn serialize<S>(instant: &Option<Instant>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match instant {
+            Some(instant) => super::instant_to_epoch_seconds::serialize(instant, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Instant>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Wrapper(#[serde(with = "super::instant_to_epoch_seconds")] Instant);
+
+        Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(instant)| instant))
+    }
+}
@@ -26,6 +26,16 @@ pub trait TrackLocalWriter: fmt::Debug {
     /// write_rtp encrypts a RTP packet and writes to the connection
     async fn write_rtp(&self, p: &rtp::packet::Packet) -> Result<usize>;
 
+    /// write_rtp_with_attributes encrypts a RTP packet and writes to the connection, handing
+    /// attributes to the interceptors, e.g. the simulcast layer of the packet to a forwarder.
+    async fn write_rtp_with_attributes(
+        &self,
+        p: &rtp::packet::Packet,
+        _attributes: &Attributes,
+    ) -> Result<usize> {
+        self.write_rtp(p).await
+    }
+
     /// write encrypts and writes a full RTP packet
     async fn write(&self, b: &[u8]) -> Result<usize>;
 }
@@ -150,14 +160,22 @@ impl std::fmt::Debug for InterceptorToTrackLocalWriter {
 #[async_trait]
 impl TrackLocalWriter for InterceptorToTrackLocalWriter {
     async fn write_rtp(&self, pkt: &rtp::packet::Packet) -> Result<usize> {
+        self.write_rtp_with_attributes(pkt, &Attributes::new())
+            .await
+    }
+
+    async fn write_rtp_with_attributes(
+        &self,
+        pkt: &rtp::packet::Packet,
+        attributes: &Attributes,
+    ) -> Result<usize> {
         if self.is_sender_paused() {
             return Ok(0);
         }
 
         let interceptor_rtp_writer = self.interceptor_rtp_writer.lock().await;
         if let Some(writer) = &*interceptor_rtp_writer {
-            let a = Attributes::new();
-            Ok(writer.write(pkt, &a).await?)
+            Ok(writer.write(pkt, attributes).await?)
         } else {
             Ok(0)
         }
@@ -81,6 +81,19 @@ impl TrackLocalStaticRTP {
         &self,
         p: &rtp::packet::Packet,
         extensions: &[rtp::extension::HeaderExtension],
+    ) -> Result<usize> {
+        self.write_rtp_with_extensions_and_attributes(p, extensions, &Attributes::new())
+            .await
+    }
+
+    /// write_rtp_with_extensions_and_attributes writes a RTP Packet to the
+    /// TrackLocalStaticRTP as [`TrackLocalStaticRTP::write_rtp_with_extensions`] does, handing
+    /// attributes to the interceptors of each PeerConnection.
+    pub async fn write_rtp_with_extensions_and_attributes(
+        &self,
+        p: &rtp::packet::Packet,
+        extensions: &[rtp::extension::HeaderExtension],
+        attributes: &Attributes,
     ) -> Result<usize> {
         let mut n = 0;
         let mut write_errs = vec![];
@@ -140,7 +153,10 @@ impl TrackLocalStaticRTP {
             }
 
             if let Some(write_stream) = &b.write_stream {
-                match write_stream.write_rtp(&pkt).await {
+                match write_stream
+                    .write_rtp_with_attributes(&pkt, attributes)
+                    .await
+                {
                     Ok(m) => {
                         n += m;
                     }
@@ -283,6 +299,17 @@ impl TrackLocalWriter for TrackLocalStaticRTP {
         self.write_rtp_with_extensions(p, &[]).await
     }
 
+    /// write_rtp_with_attributes writes a RTP Packet to the TrackLocalStaticRTP, handing
+    /// attributes to the interceptors of each PeerConnection.
+    async fn write_rtp_with_attributes(
+        &self,
+        p: &rtp::packet::Packet,
+        attributes: &Attributes,
+    ) -> Result<usize> {
+        self.write_rtp_with_extensions_and_attributes(p, &[], attributes)
+            .await
+    }
+
     /// write writes a RTP Packet as a buffer to the TrackLocalStaticRTP
     /// If one PeerConnection fails the packets will still be sent to
     /// all PeerConnections. The error message will contain the ID of the failed
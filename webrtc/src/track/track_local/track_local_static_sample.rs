@@ -1,6 +1,7 @@
 use log::warn;
 use media::Sample;
 use tokio::sync::Mutex;
+use util::MarshalSize;
 
 use super::track_local_static_rtp::TrackLocalStaticRTP;
 use super::*;
@@ -12,6 +13,8 @@ struct TrackLocalStaticSampleInternal {
     packetizer: Option<Box<dyn rtp::packetizer::Packetizer + Send + Sync>>,
     sequencer: Option<Box<dyn rtp::sequence::Sequencer + Send + Sync>>,
     clock_rate: f64,
+    mtu: usize,
+    overhead: usize,
     did_warn_about_wonky_pause: bool,
 }
 
@@ -34,6 +37,8 @@ impl TrackLocalStaticSample {
                 packetizer: None,
                 sequencer: None,
                 clock_rate: 0.0f64,
+                mtu: RTP_OUTBOUND_MTU,
+                overhead: 0,
                 did_warn_about_wonky_pause: false,
             }),
         }
@@ -54,6 +59,8 @@ impl TrackLocalStaticSample {
                 packetizer: None,
                 sequencer: None,
                 clock_rate: 0.0f64,
+                mtu: RTP_OUTBOUND_MTU,
+                overhead: 0,
                 did_warn_about_wonky_pause: false,
             }),
         }
@@ -64,16 +71,44 @@ impl TrackLocalStaticSample {
         self.rtp_track.codec()
     }
 
+    /// set_mtu changes the largest size of the RTP packets written for samples, e.g. after the
+    /// path MTU changed, starting with the next sample.
+    pub async fn set_mtu(&self, mtu: usize) {
+        let mut internal = self.internal.lock().await;
+        internal.mtu = mtu;
+        if let Some(packetizer) = &mut internal.packetizer {
+            packetizer.set_mtu(mtu);
+        }
+    }
+
+    /// mtu returns the largest size of the RTP packets written for samples.
+    pub async fn mtu(&self) -> usize {
+        self.internal.lock().await.mtu
+    }
+
+    /// set_packet_overhead reserves overhead bytes of every packet for data added after it is
+    /// written, e.g. the SRTP authentication tag. The header extensions written with a sample
+    /// are accounted for already.
+    pub async fn set_packet_overhead(&self, overhead: usize) {
+        self.internal.lock().await.overhead = overhead;
+    }
+
     /// write_sample writes a Sample to the TrackLocalStaticSample
     /// If one PeerConnection fails the packets will still be sent to
     /// all PeerConnections. The error message will contain the ID of the failed
     /// PeerConnections so you can remove them
+    ///
+    /// The video orientation of the sample, if any, is sent with the
+    /// urn:3gpp:video-orientation header extension when it is negotiated.
     pub async fn write_sample(&self, sample: &Sample) -> Result<()> {
-        self.write_sample_with_extensions(sample, &[]).await
+        self.sample_writer().write_sample(sample).await
     }
 
     /// Write a sample with provided RTP extensions.
     ///
+    /// Unlike [`TrackLocalStaticSample::write_sample`] this ignores the video orientation of
+    /// the sample, which can be set as one of the extensions instead.
+    ///
     /// Alternatively to this method [`TrackLocalStaticSample::sample_writer`] can be used instead.
     ///
     /// See [`TrackLocalStaticSample::write_sample`]  for further details.
@@ -132,8 +167,10 @@ impl TrackLocalStaticSample {
         }
 
         let clock_rate = internal.clock_rate;
+        let overhead = internal.overhead + extensions_overhead(extensions);
 
         let packets = if let Some(packetizer) = &mut internal.packetizer {
+            packetizer.set_overhead(overhead);
             let samples = (sample.duration.as_secs_f64() * clock_rate) as u32;
             if sample.prev_dropped_packets > 0 {
                 packetizer.skip_samples(samples * sample.prev_dropped_packets as u32);
@@ -215,7 +252,7 @@ impl TrackLocal for TrackLocalStaticSample {
         let sequencer: Box<dyn rtp::sequence::Sequencer + Send + Sync> =
             Box::new(rtp::sequence::new_random_sequencer());
         internal.packetizer = Some(Box::new(rtp::packetizer::new_packetizer(
-            RTP_OUTBOUND_MTU,
+            internal.mtu,
             0, // Value is handled when writing
             0, // Value is handled when writing
             payloader,
@@ -261,9 +298,20 @@ impl TrackLocal for TrackLocalStaticSample {
     }
 }
 
+/// extensions_overhead returns the largest number of bytes extensions add to a packet, with the
+/// extension header and the two byte form of each extension, which is never smaller.
+fn extensions_overhead(extensions: &[rtp::extension::HeaderExtension]) -> usize {
+    if extensions.is_empty() {
+        return 0;
+    }
+    let size: usize = extensions.iter().map(|e| 2 + e.marshal_size()).sum();
+    4 + size.div_ceil(4) * 4
+}
+
 mod sample_writer {
     use media::Sample;
     use rtp::extension::audio_level_extension::AudioLevelExtension;
+    use rtp::extension::playout_delay_extension::PlayoutDelayExtension;
     use rtp::extension::video_orientation_extension::VideoOrientationExtension;
     use rtp::extension::HeaderExtension;
 
@@ -300,6 +348,14 @@ mod sample_writer {
             self.with_extension(HeaderExtension::VideoOrientation(ext))
         }
 
+        /// Add a RTP playout delay extension to all packets written for the sample, which asks
+        /// the receiver to render it within the minimum and maximum delay.
+        ///
+        /// This overwrites any previously configured playout delay extension.
+        pub fn with_playout_delay(self, ext: PlayoutDelayExtension) -> Self {
+            self.with_extension(HeaderExtension::PlayoutDelay(ext))
+        }
+
         /// Add any RTP extension to all packets written for the sample.
         pub fn with_extension(mut self, ext: HeaderExtension) -> Self {
             self.extensions.retain(|e| !e.is_same(&ext));
@@ -312,8 +368,18 @@ mod sample_writer {
         /// Write the sample to the track.
         ///
         /// Creates one or more RTP packets with any extensions specified for each packet and sends
-        /// them.
-        pub async fn write_sample(self, sample: &Sample) -> Result<()> {
+        /// them. The video orientation of the sample is added unless one was specified.
+        pub async fn write_sample(mut self, sample: &Sample) -> Result<()> {
+            if let Some(ext) = sample.video_orientation {
+                if !self
+                    .extensions
+                    .iter()
+                    .any(|e| matches!(e, HeaderExtension::VideoOrientation(_)))
+                {
+                    self.extensions.push(HeaderExtension::VideoOrientation(ext));
+                }
+            }
+
             self.track
                 .write_sample_with_extensions(sample, &self.extensions)
                 .await
@@ -432,3 +432,191 @@ async fn test_track_local_static_binding_non_blocking() -> Result<()> {
     }
 }
 */
+
+// The video orientation of samples is sent with the header extension, once negotiated
+#[tokio::test]
+async fn test_track_local_static_sample_video_orientation() -> Result<()> {
+    use interceptor::registry::Registry;
+    use std::time::Duration;
+
+    use media::Sample;
+    use rtp::extension::video_orientation_extension::{
+        CameraDirection, VideoOrientationExtension, VideoRotation,
+    };
+    use util::Unmarshal;
+
+    use crate::api::interceptor_registry::configure_video_orientation;
+
+    let orientation = VideoOrientationExtension {
+        direction: CameraDirection::Front,
+        flip: true,
+        rotation: VideoRotation::Degree270,
+    };
+
+    let new_peer_connection = || async {
+        let mut m = MediaEngine::default();
+        m.register_default_codecs()?;
+        let registry = configure_video_orientation(Registry::new(), &mut m)?;
+        APIBuilder::new()
+            .with_media_engine(m)
+            .with_interceptor_registry(registry)
+            .build()
+            .new_peer_connection(RTCConfiguration::default())
+            .await
+    };
+    let mut offerer = new_peer_connection().await?;
+    let mut answerer = new_peer_connection().await?;
+
+    let track = Arc::new(TrackLocalStaticSample::new(
+        RTCRtpCodecCapability {
+            mime_type: MIME_TYPE_VP8.to_owned(),
+            ..Default::default()
+        },
+        "video".to_owned(),
+        "webrtc-rs".to_owned(),
+    ));
+    offerer
+        .add_transceiver_from_kind(RTPCodecType::Video, None)
+        .await?;
+    answerer
+        .add_track(Arc::clone(&track) as Arc<dyn TrackLocal + Send + Sync>)
+        .await?;
+
+    let (received_tx, mut received_rx) = mpsc::channel::<VideoOrientationExtension>(1);
+    offerer.on_track(Box::new(move |track, _, _| {
+        let received_tx = received_tx.clone();
+        Box::pin(async move {
+            let id = track
+                .params()
+                .header_extensions
+                .iter()
+                .find(|e| e.uri == sdp::extmap::VIDEO_ORIENTATION_URI)
+                .map(|e| e.id as u8)
+                .expect("video orientation should be negotiated");
+            tokio::spawn(async move {
+                while let Ok((pkt, _)) = track.read_rtp().await {
+                    if let Some(mut ext) = pkt.header.get_extension(id) {
+                        let ext = VideoOrientationExtension::unmarshal(&mut ext).unwrap();
+                        let _ = received_tx.send(ext).await;
+                        return;
+                    }
+                }
+            });
+        })
+    }));
+
+    signal_pair(&mut offerer, &mut answerer).await?;
+
+    let received = loop {
+        tokio::select! {
+            ext = received_rx.recv() => break ext,
+            _ = tokio::time::sleep(Duration::from_millis(20)) => {
+                track
+                    .write_sample(&Sample {
+                        data: Bytes::from_static(&[0x00]),
+                        duration: Duration::from_secs(1),
+                        video_orientation: Some(orientation),
+                        ..Default::default()
+                    })
+                    .await?;
+            }
+        }
+    };
+    assert_eq!(received, Some(orientation));
+
+    close_pair_now(&offerer, &answerer).await;
+
+    Ok(())
+}
+
+// Packets written for samples stay within the MTU, together with their header extensions
+#[tokio::test]
+async fn test_track_local_static_sample_mtu() -> Result<()> {
+    use interceptor::registry::Registry;
+    use std::time::Duration;
+
+    use media::Sample;
+    use rtp::extension::video_orientation_extension::VideoOrientationExtension;
+    use util::MarshalSize;
+
+    use crate::api::interceptor_registry::configure_video_orientation;
+
+    const MTU: usize = 300;
+
+    let new_peer_connection = || async {
+        let mut m = MediaEngine::default();
+        m.register_default_codecs()?;
+        let registry = configure_video_orientation(Registry::new(), &mut m)?;
+        APIBuilder::new()
+            .with_media_engine(m)
+            .with_interceptor_registry(registry)
+            .build()
+            .new_peer_connection(RTCConfiguration::default())
+            .await
+    };
+    let mut offerer = new_peer_connection().await?;
+    let mut answerer = new_peer_connection().await?;
+
+    let track = Arc::new(TrackLocalStaticSample::new(
+        RTCRtpCodecCapability {
+            mime_type: MIME_TYPE_VP8.to_owned(),
+            ..Default::default()
+        },
+        "video".to_owned(),
+        "webrtc-rs".to_owned(),
+    ));
+    track.set_mtu(MTU).await;
+    assert_eq!(track.mtu().await, MTU);
+    offerer
+        .add_transceiver_from_kind(RTPCodecType::Video, None)
+        .await?;
+    answerer
+        .add_track(Arc::clone(&track) as Arc<dyn TrackLocal + Send + Sync>)
+        .await?;
+
+    let (received_tx, mut received_rx) = mpsc::channel::<Vec<usize>>(1);
+    offerer.on_track(Box::new(move |track, _, _| {
+        let received_tx = received_tx.clone();
+        Box::pin(async move {
+            tokio::spawn(async move {
+                let mut sizes = vec![];
+                while let Ok((pkt, _)) = track.read_rtp().await {
+                    if !pkt.header.extensions.is_empty() {
+                        sizes.push(pkt.marshal_size());
+                        if pkt.header.marker {
+                            let _ = received_tx.send(sizes).await;
+                            return;
+                        }
+                    }
+                }
+            });
+        })
+    }));
+
+    signal_pair(&mut offerer, &mut answerer).await?;
+
+    let sizes = loop {
+        tokio::select! {
+            sizes = received_rx.recv() => break sizes.unwrap(),
+            _ = tokio::time::sleep(Duration::from_millis(20)) => {
+                track
+                    .write_sample(&Sample {
+                        data: Bytes::from(vec![0u8; 1000]),
+                        duration: Duration::from_secs(1),
+                        video_orientation: Some(VideoOrientationExtension::default()),
+                        ..Default::default()
+                    })
+                    .await?;
+            }
+        }
+    };
+    assert!(sizes.len() >= 4, "sample should be split, got {sizes:?}");
+    assert!(
+        sizes.iter().all(|size| *size <= MTU),
+        "packets should fit the MTU, got {sizes:?}"
+    );
+
+    close_pair_now(&offerer, &answerer).await;
+
+    Ok(())
+}
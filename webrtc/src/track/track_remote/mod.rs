@@ -6,7 +6,7 @@ use std::sync::{Arc, Weak};
 
 use arc_swap::ArcSwapOption;
 use interceptor::{Attributes, Interceptor};
-use portable_atomic::{AtomicU32, AtomicU8, AtomicUsize};
+use portable_atomic::{AtomicBool, AtomicU32, AtomicU8, AtomicUsize};
 use smol_str::SmolStr;
 use tokio::sync::Mutex;
 use util::sync::Mutex as SyncMutex;
@@ -24,10 +24,18 @@ pub type OnMuteHdlrFn = Box<
     dyn (FnMut() -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>) + Send + Sync + 'static,
 >;
 
+pub type OnEndedHdlrFn = Box<
+    dyn (FnMut(Option<String>) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>)
+        + Send
+        + Sync
+        + 'static,
+>;
+
 #[derive(Default)]
 struct Handlers {
     on_mute: ArcSwapOption<Mutex<OnMuteHdlrFn>>,
     on_unmute: ArcSwapOption<Mutex<OnMuteHdlrFn>>,
+    on_ended: ArcSwapOption<Mutex<OnEndedHdlrFn>>,
 }
 
 #[derive(Default)]
@@ -54,6 +62,8 @@ pub struct TrackRemote {
     interceptor: Arc<dyn Interceptor + Send + Sync>,
 
     handlers: Arc<Handlers>,
+    ended: AtomicBool,
+    ended_reason: SyncMutex<Option<String>>,
 
     receiver: Option<Weak<RTPReceiverInternal>>,
     internal: Mutex<TrackRemoteInternal>,
@@ -99,6 +109,8 @@ impl TrackRemote {
             media_engine,
             interceptor,
             handlers: Default::default(),
+            ended: AtomicBool::new(false),
+            ended_reason: Default::default(),
 
             internal: Default::default(),
         }
@@ -210,6 +222,31 @@ impl TrackRemote {
             .store(Some(Arc::new(Mutex::new(Box::new(handler)))));
     }
 
+    /// onended sets a handler called once, when the remote ends the track with an RTCP BYE.
+    /// The handler is given the reason of the BYE, if any, which tells for instance a camera
+    /// switched from an error. The RTCP of the RTPReceiver must be read for BYEs to be received.
+    pub fn onended<F>(&self, handler: F)
+    where
+        F: FnMut(Option<String>) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+            + Send
+            + 'static
+            + Sync,
+    {
+        self.handlers
+            .on_ended
+            .store(Some(Arc::new(Mutex::new(Box::new(handler)))));
+    }
+
+    /// ended returns whether the remote ended the track with an RTCP BYE.
+    pub fn ended(&self) -> bool {
+        self.ended.load(Ordering::SeqCst)
+    }
+
+    /// ended_reason returns the reason of the RTCP BYE which ended the track, if any.
+    pub fn ended_reason(&self) -> Option<String> {
+        self.ended_reason.lock().clone()
+    }
+
     /// Reads data from the track.
     ///
     /// **Cancel Safety:** This method is not cancel safe. Dropping the resulting [`Future`] before
@@ -311,6 +348,21 @@ impl TrackRemote {
         };
     }
 
+    pub(crate) async fn fire_onended(&self, reason: Option<String>) {
+        if self.ended.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        {
+            let mut ended_reason = self.ended_reason.lock();
+            ended_reason.clone_from(&reason);
+        }
+
+        let on_ended = self.handlers.on_ended.load();
+        if let Some(f) = on_ended.as_ref() {
+            (f.lock().await)(reason).await
+        };
+    }
+
     pub(crate) async fn fire_onunmute(&self) {
         let on_unmute = self.handlers.on_unmute.load();
 